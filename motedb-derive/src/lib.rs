@@ -0,0 +1,223 @@
+//! `#[derive(MoteRecord)]`: map a Rust struct to a MoteDB table
+//!
+//! Use it through the `motedb` crate (`motedb::MoteRecord`, on by default
+//! with the `derive` feature); the generated code refers to
+//! `::motedb::record`. See that module for the supported field types.
+//!
+//! ```ignore
+//! #[derive(MoteRecord)]
+//! #[mote(table = "readings")]
+//! struct Reading {
+//!     #[mote(primary_key)]
+//!     id: i64,
+//!     #[mote(rename = "temp_c")]
+//!     temperature: f64,
+//!     #[mote(dim = 3)]
+//!     embedding: Vec<f32>,
+//!     position: Option<Geometry>,
+//! }
+//! ```
+//!
+//! Struct attributes:
+//! - `table = "name"`: table name (default: the struct name in snake_case)
+//!
+//! Field attributes:
+//! - `primary_key`: the table's primary key (at most one field)
+//! - `rename = "name"`: column name (default: the field name)
+//! - `dim = N`: store a `Vec<f32>` field as `VECTOR(N)`
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt, LitStr};
+
+#[proc_macro_derive(MoteRecord, attributes(mote))]
+pub fn derive_mote_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// One struct field and the column it maps to
+struct Column {
+    ident: syn::Ident,
+    ty: syn::Type,
+    name: String,
+    primary_key: bool,
+    dim: Option<LitInt>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "MoteRecord cannot be derived for generic structs",
+        ));
+    }
+
+    let mut table = snake_case(&ident.to_string());
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("mote")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `table = \"...\"`"))
+            }
+        })?;
+    }
+
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "MoteRecord needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "MoteRecord can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut columns = Vec::with_capacity(named.len());
+    for field in named {
+        let field_ident = field.ident.clone().expect("named field");
+        let mut column = Column {
+            name: field_ident.to_string().trim_start_matches("r#").to_string(),
+            ident: field_ident,
+            ty: field.ty.clone(),
+            primary_key: false,
+            dim: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("mote")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("primary_key") {
+                    column.primary_key = true;
+                } else if meta.path.is_ident("rename") {
+                    column.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("dim") {
+                    column.dim = Some(meta.value()?.parse::<LitInt>()?);
+                } else {
+                    return Err(
+                        meta.error("expected `primary_key`, `rename = \"...\"` or `dim = N`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        columns.push(column);
+    }
+    if columns.is_empty() {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "MoteRecord needs at least one field",
+        ));
+    }
+    let keys: Vec<&Column> = columns.iter().filter(|c| c.primary_key).collect();
+    if keys.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            &keys[1].ident,
+            "only one field can be the primary key",
+        ));
+    }
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i].iter().any(|c| c.name == column.name) {
+            return Err(syn::Error::new_spanned(
+                &column.ident,
+                format!("duplicate column name `{}`", column.name),
+            ));
+        }
+    }
+
+    let record = quote!(::motedb::record);
+    let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+    let primary_key = match keys.first() {
+        Some(c) => {
+            let name = &c.name;
+            quote!(::core::option::Option::Some(#name))
+        }
+        None => quote!(::core::option::Option::None),
+    };
+    let column_defs = columns.iter().enumerate().map(|(position, c)| {
+        let (ty, name) = (&c.ty, &c.name);
+        match &c.dim {
+            Some(dim) => quote!(#record::vector_column::<#ty>(#name, #position, #dim)),
+            None => quote!(#record::column::<#ty>(#name, #position)),
+        }
+    });
+    let to_values = columns.iter().map(|c| {
+        let field = &c.ident;
+        quote!(#record::FieldValue::to_value(&self.#field))
+    });
+    let from_values = columns.iter().map(|c| {
+        let (field, name) = (&c.ident, &c.name);
+        quote!(#field: #record::take_field(&mut values, #name)?)
+    });
+    let field_consts = columns.iter().map(|c| {
+        let (ty, name) = (&c.ty, &c.name);
+        let konst = format_ident!(
+            "{}",
+            c.ident.to_string().trim_start_matches("r#").to_uppercase(),
+            span = Span::call_site()
+        );
+        let doc = format!("Column `{}`", name);
+        quote! {
+            #[doc = #doc]
+            pub const #konst: #record::Field<#ident, #ty> = #record::Field::new(#name);
+        }
+    });
+
+    Ok(quote! {
+        impl #record::MoteRecord for #ident {
+            const TABLE: &'static str = #table;
+            const COLUMNS: &'static [&'static str] = &[#(#names),*];
+
+            fn columns() -> ::std::vec::Vec<::motedb::types::ColumnDef> {
+                ::std::vec![#(#column_defs),*]
+            }
+
+            fn primary_key() -> ::core::option::Option<&'static str> {
+                #primary_key
+            }
+
+            fn to_row(&self) -> ::motedb::types::Row {
+                ::std::vec![#(#to_values),*]
+            }
+
+            fn from_row(row: ::motedb::types::Row) -> ::motedb::Result<Self> {
+                let mut values = row.into_iter();
+                ::core::result::Result::Ok(Self {
+                    #(#from_values),*
+                })
+            }
+        }
+
+        impl #ident {
+            #(#field_consts)*
+        }
+    })
+}
+
+/// `SensorReading` → `sensor_reading`
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, ch) in name.char_indices() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(ch.to_lowercase());
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
@@ -9,11 +9,16 @@
 //! - **批量操作**: 高性能批量插入和索引构建
 //! - **性能监控**: 统计信息和性能分析
 
+use crate::cache::DatabaseCacheStats;
 use crate::database::indexes::VectorIndexStats;
-use crate::database::{MoteDB, TransactionStats};
+use crate::database::{
+    ActiveTransaction, CacheWarmupStats, Collection, CollectionConfig, EmbeddingUpsert,
+    MemoryBudgetStats, MoteDB, TransactionStats, VectorSearchOptions, WriteBatch,
+};
+use crate::index::column_value::ColumnRangeIter;
 use crate::sql::ast::Statement;
-use crate::sql::StreamingQueryResult;
-use crate::types::{Row, RowId, SqlRow, Value};
+use crate::sql::{OptimizerStats, StreamingQueryResult};
+use crate::types::{Row, RowId, RowIdSet, SqlRow, Value};
 use crate::StorageError;
 use crate::{DBConfig, Result};
 use lru::LruCache;
@@ -33,6 +38,8 @@ struct FastPkMeta {
     select_col_positions: Vec<usize>,
     /// Only for UPDATE: (col_position, param_idx) for SET col = ?
     set_param_positions: Vec<(usize, usize)>,
+    /// Only for UPDATE: (col_position, value) for SET col = <literal>
+    set_literal_positions: Vec<(usize, Value)>,
     is_auto_increment: bool,
     column_names: Arc<Vec<String>>,
     schema: Arc<crate::types::TableSchema>,
@@ -74,6 +81,8 @@ struct CachedStmt {
 /// - `commit_transaction()`: 提交事务
 /// - `rollback_transaction()`: 回滚事务
 /// - `savepoint()`: 创建保存点
+/// - `transaction()`: 在闭包中执行事务（自动提交/回滚）
+/// - `snapshot()`: 打开只读快照
 ///
 /// ## 3. 批量操作
 /// - `batch_insert()`: 批量插入行
@@ -193,6 +202,26 @@ impl Database {
         })
     }
 
+    /// Open a packed image written by [`Database::pack`] read-only. Files
+    /// that cannot be served from the mapped image are unpacked under
+    /// `work_dir` (reused across opens).
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let db = Database::open_packed("/firmware/map.motedb", "/var/cache/map")?;
+    /// ```
+    pub fn open_packed<P: AsRef<Path>, Q: AsRef<Path>>(image: P, work_dir: Q) -> Result<Self> {
+        let inner = Arc::new(MoteDB::open_packed(image, work_dir)?);
+        let query_executor = crate::sql::QueryExecutor::new(inner.clone());
+        Ok(Self {
+            inner,
+            stmt_cache: Arc::new(parking_lot::RwLock::new(LruCache::new(
+                NonZeroUsize::new(256).unwrap(),
+            ))),
+            query_executor,
+        })
+    }
+
     /// 刷新所有数据到磁盘
     ///
     /// # Examples
@@ -225,6 +254,11 @@ impl Database {
         self.inner.checkpoint()
     }
 
+    /// Current WAL size in bytes (see [`MoteDB::wal_size`])
+    pub fn wal_size(&self) -> u64 {
+        self.inner.wal_size()
+    }
+
     /// Full checkpoint with index rebuild (slower but thorough).
     /// Used internally on shutdown to ensure index completeness.
     pub fn checkpoint_full(&self) -> Result<()> {
@@ -249,6 +283,66 @@ impl Database {
         self.inner.vacuum()
     }
 
+    /// Checkpoint and bundle the database into one immutable `.motedb`
+    /// image, e.g. to ship a map or embedding corpus as a firmware asset
+    pub fn pack<P: AsRef<Path>>(&self, dest: P) -> Result<crate::storage::packed::PackStats> {
+        self.inner.pack(dest)
+    }
+
+    /// Rewrite rows still stored in an older row encoding in the current
+    /// format (see [`MoteDB::migrate_row_format`])
+    pub fn migrate_row_format(&self) -> Result<crate::storage::value_codec::MigrationStats> {
+        self.inner.migrate_row_format()
+    }
+
+    /// Write `table` (or every table) as the SQL statements that recreate
+    /// it, e.g. to move data between devices; returns the rows written
+    /// (see [`MoteDB::dump_sql`])
+    pub fn dump_sql<W: std::io::Write>(&self, table: Option<&str>, mut out: W) -> Result<usize> {
+        self.inner.dump_sql(table, &mut out)
+    }
+
+    /// Load a CSV file into a table, converting fields to the column types;
+    /// returns the rows inserted (see [`MoteDB::import_csv`])
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let options = CsvOptions { delimiter: b';', ..Default::default() };
+    /// let n = db.import_csv("readings", "readings.csv", &options)?;
+    /// ```
+    pub fn import_csv<P: AsRef<Path>>(
+        &self,
+        table_name: &str,
+        path: P,
+        options: &crate::database::CsvOptions,
+    ) -> Result<usize> {
+        self.inner.import_csv(table_name, path, options)
+    }
+
+    /// Write a table to a CSV file that [`import_csv`](Self::import_csv)
+    /// reads back; returns the rows written
+    pub fn export_csv<P: AsRef<Path>>(
+        &self,
+        table_name: &str,
+        path: P,
+        options: &crate::database::CsvOptions,
+    ) -> Result<usize> {
+        self.inner.export_csv(table_name, path, options)
+    }
+
+    /// Run a `;`-separated SQL script, such as [`dump_sql`](Self::dump_sql)
+    /// output, stopping at the first failing statement; returns the number
+    /// of statements executed
+    pub fn execute_script(&self, sql: &str) -> Result<usize> {
+        let statements = crate::sql::parse_script(sql)?;
+        for statement in &statements {
+            self.query_executor
+                .execute_streaming_ref(statement)?
+                .materialize()?;
+        }
+        Ok(statements.len())
+    }
+
     /// 关闭数据库（显式调用，通常由 Drop 自动处理）
     ///
     /// Sets the closed flag so all subsequent operations return `DatabaseClosed` error.
@@ -280,6 +374,14 @@ impl Database {
             warn_log!("[close] Background threads did not stop within timeout");
         }
 
+        // Snapshot hot cache keys before compaction below replaces the
+        // segments (and their column caches)
+        if self.inner.persist_cache_state {
+            if let Err(e) = self.inner.save_cache_state() {
+                warn_log!("[close] Saving cache state failed: {:?}", e);
+            }
+        }
+
         // 🚀 Flush ColSegmentStore buffers BEFORE checkpoint. Without this,
         // in-memory INSERT data (the write buffer) is lost on close — the
         // large_batch_durability bug (10000 rows → 5000 after reopen). The
@@ -356,6 +458,11 @@ impl Database {
         self.inner.max_result_rows
     }
 
+    /// Streaming scan batch size and materialize preallocation cap in effect
+    pub fn streaming_config(&self) -> crate::StreamingConfig {
+        self.inner.streaming
+    }
+
     /// Convenience method: execute a SELECT query and return rows directly.
     /// This is shorthand for `execute(sql)?.materialize()?` + pattern match.
     ///
@@ -375,6 +482,62 @@ impl Database {
         }
     }
 
+    /// Start a SELECT on `table` built in Rust instead of SQL text.
+    /// It compiles to the equivalent SQL (values bound as parameters), so
+    /// it is planned exactly like it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use motedb::sql::builder::col;
+    ///
+    /// let rows = db
+    ///     .table("readings")
+    ///     .filter(col("x").gt(5))
+    ///     .knn("emb", &embedding, 10)
+    ///     .limit(20)
+    ///     .all()?;
+    /// ```
+    pub fn table(&self, table: &str) -> crate::sql::builder::QueryBuilder<'_> {
+        crate::sql::builder::QueryBuilder::new(self, table)
+    }
+
+    /// Run one page of a keyset-paginated SELECT and return its resume cursor.
+    ///
+    /// The query must be `SELECT ... FROM t [WHERE ...] ORDER BY col LIMIT n`
+    /// over a column with an index. Pass the returned `next_cursor` back as
+    /// `AFTER CURSOR '<token>'` to continue the index scan where this page
+    /// stopped, instead of rescanning with OFFSET.
+    ///
+    /// ```ignore
+    /// let page = db.query_page("SELECT * FROM events ORDER BY ts LIMIT 100")?;
+    /// if let Some(cursor) = page.next_cursor {
+    ///     let sql = format!("SELECT * FROM events ORDER BY ts LIMIT 100 AFTER CURSOR '{}'", cursor);
+    ///     let next = db.query_page(&sql)?;
+    /// }
+    /// ```
+    pub fn query_page(&self, sql: &str) -> Result<crate::QueryPage> {
+        use crate::sql::{Lexer, Parser};
+
+        if self
+            .inner
+            .is_closed
+            .load(std::sync::atomic::Ordering::Acquire)
+        {
+            return Err(StorageError::InvalidData("Database is closed".into()));
+        }
+        self.inner.refresh_stale_continuous_aggregates();
+
+        let tokens = Lexer::new(sql).tokenize()?;
+        match Parser::new(tokens).parse()? {
+            Statement::Select { stmt, ctes } if ctes.is_empty() => {
+                self.query_executor.execute_select_page(&stmt)
+            }
+            _ => Err(StorageError::InvalidArgument(
+                "query_page expects a single SELECT".into(),
+            )),
+        }
+    }
+
     /// Get the approximate row count for a table without executing SQL.
     /// Returns the live row count from the ColSegmentStore if available,
     /// otherwise falls back to the LSM row counter.
@@ -382,9 +545,71 @@ impl Database {
         Ok(self.inner.fast_row_count(table_name).unwrap_or(0) as usize)
     }
 
+    /// Estimate a table's row count from storage metadata (segment and
+    /// SSTable row counts plus unflushed rows) without scanning. Rows that
+    /// were updated or deleted but not yet compacted still count, so the
+    /// estimate can run high. Also available in SQL as
+    /// `SELECT COUNT_ESTIMATE() FROM table`.
+    pub fn estimate_row_count(&self, table_name: &str) -> Result<u64> {
+        self.inner.estimate_row_count(table_name)
+    }
+
+    /// Hit/miss/eviction counters of every cache (row cache, column index
+    /// caches, SSTable cache, negative lookup caches) plus row-cache quota usage
+    /// and write-policy counters
+    pub fn cache_stats(&self) -> DatabaseCacheStats {
+        self.inner.cache_stats()
+    }
+
+    /// Cap how many rows of `table_name` the shared row cache keeps
+    /// (`None` removes the cap), so one chatty table can't evict the hot
+    /// rows of the others. Also settable via `DBConfig::row_cache_quotas`.
+    pub fn set_row_cache_quota(&self, table_name: &str, max_rows: Option<usize>) -> Result<()> {
+        self.inner.set_row_cache_quota(table_name, max_rows)
+    }
+
+    /// Save the keys of hot rows, pinned DiskANN nodes and decoded column
+    /// segments to `cache_state.bin`. Done automatically on close when
+    /// `DBConfig::persist_cache_state` is set.
+    pub fn save_cache_state(&self) -> Result<()> {
+        self.inner.save_cache_state()
+    }
+
+    /// Pre-load caches from a state saved by [`Database::save_cache_state`].
+    /// Done automatically on open when `DBConfig::persist_cache_state` is set.
+    pub fn warm_caches(&self) -> Result<CacheWarmupStats> {
+        self.inner.warm_caches()
+    }
+
+    /// Adaptive re-optimization counters: index probes abandoned mid-query
+    /// for a streaming scan, and observed cardinalities fed back into the
+    /// optimizer's index statistics
+    pub fn optimizer_stats(&self) -> OptimizerStats {
+        self.query_executor.optimizer_stats()
+    }
+
+    /// Sample process memory against `DBConfig::memory_limit` and report
+    /// the degradation level (`None` when no cap is configured)
+    pub fn memory_budget_stats(&self) -> Option<MemoryBudgetStats> {
+        self.inner.memory_budget_stats()
+    }
+
     pub fn execute(&self, sql: &str) -> Result<StreamingQueryResult> {
         use crate::sql::{Lexer, Parser};
 
+        // `/*+ ASYNC_COMMIT */` / `/*+ SYNC_COMMIT */`: override the WAL
+        // durability level for this statement only. Hints are stripped so
+        // the fast paths and statement cache see the plain statement.
+        // `/*+ BEST_EFFORT */` statements are shed at the hard memory cap.
+        let (hints, sql) = crate::sql::hints::take_hints(sql);
+        if hints.best_effort {
+            self.inner.check_memory_budget(true)?;
+        }
+        if hints.commit_mode.is_some() {
+            return crate::txn::wal::with_commit_mode(hints.commit_mode, || self.execute(&sql));
+        }
+        let sql: &str = &sql;
+
         // 🛡️ Guard: reject all operations after close() (including read paths
         // that bypass the inner executor's own checks).
         if self
@@ -396,6 +621,12 @@ impl Database {
                 "Database is closed".into(),
             ));
         }
+        self.inner.check_memory_budget(false)?;
+
+        // Fast paths below bypass the executor, so bring stale materialized
+        // views up to date and apply the session time zone here
+        self.inner.refresh_stale_continuous_aggregates();
+        self.query_executor.apply_session_time_zone();
 
         // In transaction mode, skip fast INSERT paths so rows go through
         // insert_row_with_txn (buffered in write_set until COMMIT).
@@ -508,6 +739,17 @@ impl Database {
     pub fn execute_prepared(&self, sql: &str, params: Vec<Value>) -> Result<StreamingQueryResult> {
         use crate::sql::{Lexer, Parser};
 
+        let (hints, sql) = crate::sql::hints::take_hints(sql);
+        if hints.best_effort {
+            self.inner.check_memory_budget(true)?;
+        }
+        if hints.commit_mode.is_some() {
+            return crate::txn::wal::with_commit_mode(hints.commit_mode, || {
+                self.execute_prepared(&sql, params)
+            });
+        }
+        let sql: &str = &sql;
+
         // 🛡️ Guard: reject all operations after close() (execute() has this
         // check; execute_prepared was missing it — writes could silently
         // proceed against a closed database).
@@ -520,6 +762,7 @@ impl Database {
                 "Database is closed".into(),
             ));
         }
+        self.inner.check_memory_budget(false)?;
 
         // Get or parse the statement — check for cached fast PK metadata
         let (statement, cached_fast_pk): (Arc<Statement>, bool) = {
@@ -610,6 +853,72 @@ impl Database {
         result
     }
 
+    /// Execute a single-row parameterized INSERT once per tuple in `param_rows`.
+    ///
+    /// The SQL is parsed once (and cached like `execute_prepared`), and all
+    /// tuples are written as one batch: one WAL record and one index pass,
+    /// instead of one statement per row. Returns the number of inserted rows.
+    ///
+    /// ```ignore
+    /// db.execute_prepared_batch(
+    ///     "INSERT INTO logs (ts, msg) VALUES (?, ?)",
+    ///     vec![
+    ///         vec![Value::Integer(1), Value::text("boot".into())],
+    ///         vec![Value::Integer(2), Value::text("ready".into())],
+    ///     ],
+    /// )?;
+    /// ```
+    pub fn execute_prepared_batch(&self, sql: &str, param_rows: Vec<Vec<Value>>) -> Result<usize> {
+        use crate::sql::{Lexer, Parser};
+
+        if self
+            .inner
+            .is_closed
+            .load(std::sync::atomic::Ordering::Acquire)
+        {
+            return Err(crate::StorageError::InvalidData(
+                "Database is closed".into(),
+            ));
+        }
+        self.inner.check_memory_budget(false)?;
+
+        let cached = self
+            .stmt_cache
+            .read()
+            .peek(sql)
+            .map(|c| Arc::clone(&c.stmt));
+        let statement = match cached {
+            Some(stmt) => stmt,
+            None => {
+                let mut lexer = Lexer::new(sql);
+                let tokens = lexer.tokenize()?;
+                let mut parser = Parser::new(tokens);
+                let stmt = Arc::new(parser.parse()?);
+                self.stmt_cache.write().put(
+                    sql.to_string(),
+                    CachedStmt {
+                        stmt: Arc::clone(&stmt),
+                        fast_pk: None,
+                    },
+                );
+                stmt
+            }
+        };
+
+        let max_idx = crate::sql::QueryExecutor::max_parameter_index(&statement);
+        if let Some(params) = param_rows.iter().find(|p| p.len() < max_idx) {
+            return Err(crate::error::MoteDBError::InvalidArgument(format!(
+                "Query has {} parameter(s) but only {} were provided",
+                max_idx,
+                params.len()
+            )));
+        }
+
+        self.query_executor
+            .execute_insert_batch(&statement, &param_rows)
+            .map(|r| r.affected_rows())
+    }
+
     /// Detect if a statement is a simple PK SELECT pattern.
     /// Returns pre-computed FastPkMeta if it matches.
     fn detect_fast_pk_pattern(statement: &Statement, db: &MoteDB) -> Result<Option<FastPkMeta>> {
@@ -705,25 +1014,29 @@ impl Database {
             vec![]
         };
 
-        // For UPDATE: detect SET col = ? patterns
-        let set_param_positions: Vec<(usize, usize)> = if stmt_type == "update" {
-            if let S::Update(s) = statement {
-                s.assignments
-                    .iter()
-                    .filter_map(|(col_name, expr)| {
-                        if let Expr::Parameter(idx) = expr {
-                            schema.get_column_position(col_name).map(|pos| (pos, *idx))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
-            } else {
-                vec![]
+        // For UPDATE: SET col = ? and SET col = <literal> are applied here;
+        // anything that reads the row (SET n = n + 1) goes to the executor's
+        // PK path, as do generated columns and materialized views
+        let mut set_param_positions = Vec::new();
+        let mut set_literal_positions = Vec::new();
+        if let S::Update(s) = statement {
+            if schema.columns.iter().any(|c| c.generated.is_some())
+                || schema.continuous_aggregate.is_some()
+            {
+                return Ok(None);
             }
-        } else {
-            vec![]
-        };
+            for (col_name, expr) in &s.assignments {
+                let pos = match schema.get_column_position(col_name) {
+                    Some(pos) => pos,
+                    None => return Ok(None),
+                };
+                match expr {
+                    Expr::Parameter(idx) => set_param_positions.push((pos, *idx)),
+                    Expr::Literal(v) => set_literal_positions.push((pos, v.clone())),
+                    _ => return Ok(None),
+                }
+            }
+        }
 
         Ok(Some(FastPkMeta {
             stmt_type,
@@ -732,6 +1045,7 @@ impl Database {
             is_star,
             select_col_positions,
             set_param_positions,
+            set_literal_positions,
             is_auto_increment: schema.is_primary_key_auto_increment(),
             column_names: schema.column_names_arc(),
             schema,
@@ -744,6 +1058,10 @@ impl Database {
         meta: &FastPkMeta,
         params: &[Value],
     ) -> Result<Option<StreamingQueryResult>> {
+        // Writes inside a transaction need undo records; the executor keeps them
+        if meta.stmt_type != "select" && self.query_executor.is_in_transaction() {
+            return Ok(None);
+        }
         let pk_value = match params.get(meta.param_idx - 1) {
             Some(v) => v,
             None => {
@@ -813,6 +1131,12 @@ impl Database {
                         }
                     };
                 let mut new_row = (*old_row_arc).clone();
+                for (col_pos, val) in &meta.set_literal_positions {
+                    while new_row.len() <= *col_pos {
+                        new_row.push(Value::Null);
+                    }
+                    new_row[*col_pos] = val.clone();
+                }
                 for &(col_pos, param_idx) in &meta.set_param_positions {
                     if let Some(new_val) = params.get(param_idx - 1) {
                         while new_row.len() <= col_pos {
@@ -929,6 +1253,13 @@ impl Database {
             Ok(s) => s,
             Err(_) => return Ok(None),
         };
+        // Generated columns change the column list; leave them to the executor,
+        // which also rejects writes to materialized views
+        if schema.columns.iter().any(|c| c.generated.is_some())
+            || schema.continuous_aggregate.is_some()
+        {
+            return Ok(None);
+        }
 
         // Parse multiple value tuples: (a,b,c),(d,e,f),...
         let mut rows: Vec<Vec<Value>> = Vec::new();
@@ -1148,9 +1479,10 @@ impl Database {
         } else {
             val_str.split_whitespace().next().unwrap_or(val_str)
         };
+        // `col = NULL` is never true; the full path answers it
         let value = match Self::parse_single_literal(val_str) {
-            Some(v) => v,
-            None => return Ok(None),
+            Some(v) if !matches!(v, Value::Null) => v,
+            _ => return Ok(None),
         };
 
         // 🔑 Reject set operations (UNION/INTERSECT/EXCEPT) — the fast path
@@ -1165,7 +1497,7 @@ impl Database {
 
         // 🚨 This fast path only implements `col = value` filtering followed by
         // a direct row fetch — it does NOT apply ORDER BY / LIMIT / OFFSET /
-        // DISTINCT. Previously it silently ignored those trailing clauses,
+        // DISTINCT / LATEST BY. Previously it silently ignored those trailing clauses,
         // so `SELECT v FROM t WHERE cat='c0' ORDER BY v DESC LIMIT 5` returned
         // ALL 20 matching rows instead of the top 5. If any such clause is
         // present after the value, fall through to the full parser/executor
@@ -1173,6 +1505,7 @@ impl Database {
         if Self::find_keyword_ci(after_val, "order").is_some()
             || Self::find_keyword_ci(after_val, "limit").is_some()
             || Self::find_keyword_ci(after_val, "offset").is_some()
+            || Self::find_keyword_ci(after_val, "latest").is_some()
         {
             return Ok(None);
         }
@@ -1180,12 +1513,12 @@ impl Database {
         // 🚨 Compound predicates: this fast path only implements a single
         // `col = value` filter. `a = 1 AND b = 2` was parsed as `a = 1` (the
         // value parser took "1" via split_whitespace, dropping `AND b = 2`),
-        // returning rows matching only the first predicate. Fall through to
-        // the full parser/executor if AND follows the value.
-        // (OR is excluded for now — the full executor's OR path has a
-        // separate performance issue under indexed columns; AND is the
-        // critical correctness case.)
-        if Self::find_keyword_ci(after_val, "and").is_some() {
+        // returning rows matching only the first predicate; `a = 1 OR b = 2`
+        // likewise dropped every `b = 2` row. Fall through to the full
+        // parser/executor if AND or OR follows the value.
+        if Self::find_keyword_ci(after_val, "and").is_some()
+            || Self::find_keyword_ci(after_val, "or").is_some()
+        {
             return Ok(None);
         }
 
@@ -1194,6 +1527,9 @@ impl Database {
             Ok(s) => s,
             Err(_) => return Ok(None),
         };
+        if schema.has_virtual_columns() {
+            return Ok(None);
+        }
 
         // Only optimize primary key lookups
         let is_pk = schema
@@ -1236,6 +1572,13 @@ impl Database {
         }
 
         if !is_pk {
+            // An ARRAY column's index holds its elements, not whole values
+            if schema
+                .get_column(col_name)
+                .is_some_and(|c| matches!(c.col_type, crate::types::ColumnType::Array(_)))
+            {
+                return Ok(None);
+            }
             // 🚀 For ColSegmentStore tables, check if the filter column has a
             // column index FIRST — the index fast path below (line ~1241) does
             // an O(log N) B+tree lookup + batch row fetch, much faster than the
@@ -1898,8 +2241,8 @@ impl Database {
         let where_col = after_where[..eq_pos].trim();
         let where_val_str = after_where[eq_pos + 1..].trim();
         let where_value = match Self::parse_single_literal(where_val_str) {
-            Some(v) => v,
-            None => return Ok(None),
+            Some(v) if !matches!(v, Value::Null) => v,
+            _ => return Ok(None),
         };
 
         // Resolve schema — check this is a PK lookup
@@ -1907,6 +2250,9 @@ impl Database {
             Ok(s) => s,
             Err(_) => return Ok(None),
         };
+        if schema.columns.iter().any(|c| c.generated.is_some()) {
+            return Ok(None);
+        }
         let is_pk = schema
             .primary_key()
             .map(|pk| pk == where_col)
@@ -2023,7 +2369,7 @@ impl Database {
                 }
             };
             let val = match Self::parse_single_literal(val_str) {
-                Some(v) => v,
+                Some(v) => crate::sql::row_converter::timestamp_text(&cd.col_type, v)?,
                 None => match Self::evaluate_simple_set_expr(val_str, &old_row, &schema) {
                     Some(v) => v,
                     None => return Ok(None), // complex expression → fall through to full parser
@@ -2097,8 +2443,8 @@ impl Database {
         let col_name = after_where[..eq_pos].trim();
         let val_str = after_where[eq_pos + 1..].trim();
         let value = match Self::parse_single_literal(val_str) {
-            Some(v) => v,
-            None => return Ok(None),
+            Some(v) if !matches!(v, Value::Null) => v,
+            _ => return Ok(None),
         };
 
         // Resolve schema — PK check
@@ -2402,6 +2748,76 @@ impl Database {
         self.inner.release_savepoint(tx_id, name)
     }
 
+    /// 在事务中执行闭包：闭包返回 `Ok` 时提交，返回 `Err`（或 panic）时回滚
+    ///
+    /// 提交失败时事务同样被回滚，并返回提交错误。事务绑定在当前线程上，
+    /// 闭包内通过 `tx` 执行的 SQL 与行操作都在该事务中进行。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let moved = db.transaction(|tx| {
+    ///     tx.execute("UPDATE accounts SET balance = balance - 10 WHERE id = 1")?;
+    ///     tx.execute("UPDATE accounts SET balance = balance + 10 WHERE id = 2")?;
+    ///     Ok(10)
+    /// })?;
+    /// ```
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Transaction<'_>) -> Result<T>,
+    {
+        let tx = Transaction {
+            db: self,
+            id: self.begin_transaction()?,
+            finished: std::cell::Cell::new(false),
+        };
+        let value = f(&tx)?;
+        self.commit_transaction(tx.id)?;
+        tx.finished.set(true);
+        Ok(value)
+    }
+
+    /// 打开只读快照（read view）
+    ///
+    /// 快照持有一个 REPEATABLE READ 事务：快照之后由事务提交的行版本对它不可见。
+    /// 不经过事务的自动提交写入不记录版本，读取时仍可见；删除例外，快照之后
+    /// 删除的行仍按删除前的内容可见。快照存在期间旧版本不会被回收，用完应尽快 drop。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let snap = db.snapshot()?;
+    /// let before = snap.get_row("users", row_id)?;
+    /// // 其他线程提交的事务不会改变 snap 读到的内容
+    /// ```
+    pub fn snapshot(&self) -> Result<ReadView<'_>> {
+        Ok(ReadView {
+            db: self,
+            id: self.inner.begin_read_snapshot()?,
+        })
+    }
+
+    /// 打开可跨线程长期持有的只读视图
+    ///
+    /// 与 [`Self::snapshot`] 相同的快照语义，但视图自己持有数据库的引用：
+    /// 可以 move 到报表线程、clone 给多个线程共享，最后一个 clone drop 时释放快照。
+    /// 快照只保留 MVCC 旧版本，不阻塞 flush / compaction / VACUUM。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let view = db.clone_readonly_view()?;
+    /// std::thread::spawn(move || {
+    ///     let rows = view.scan_table("orders")?;
+    ///     // ...
+    /// });
+    /// ```
+    pub fn clone_readonly_view(&self) -> Result<ReadOnlyView> {
+        Ok(ReadOnlyView {
+            pin: Arc::new(PinnedSnapshot {
+                id: self.inner.begin_read_snapshot()?,
+                db: self.inner.clone(),
+            }),
+        })
+    }
+
     // ============================================================================
     // 4. 批量操作（高性能）
     // ============================================================================
@@ -2430,6 +2846,137 @@ impl Database {
         self.inner.batch_insert_rows_to_table(table_name, rows)
     }
 
+    /// 从迭代器流式批量插入行，返回插入的行数
+    ///
+    /// 每次从迭代器取一批行写入（每批一次 WAL 批量写），不会先收集全部行；
+    /// 每批写完后再把这批行批量加入各索引，而不是逐行更新。适合大批量遥测
+    /// 数据导入（详见 [`MoteDB::insert_rows_iter`]）。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let n = db.insert_rows_iter(
+    ///     "telemetry",
+    ///     (0..1_000_000).map(|i| vec![Value::Integer(i), Value::Float(i as f64 * 0.5)]),
+    /// )?;
+    /// println!("Inserted {} rows", n);
+    /// ```
+    pub fn insert_rows_iter<I>(&self, table_name: &str, rows: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = Row>,
+    {
+        self.inner.insert_rows_iter(table_name, rows)
+    }
+
+    /// Start a batch of inserts, updates and deletes (across any tables)
+    /// that commit atomically as a single WAL record
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut batch = db.write_batch();
+    /// batch.insert("orders", vec![Value::Integer(1), Value::Integer(42)]);
+    /// batch.update("stock", 42, vec![Value::Integer(42), Value::Integer(9)]);
+    /// batch.delete("carts", 7);
+    /// let row_ids = batch.commit()?;
+    /// ```
+    pub fn write_batch(&self) -> WriteBatch<'_> {
+        self.inner.write_batch()
+    }
+
+    /// Ingest embeddings by a user key instead of row ids: a key matching a
+    /// row replaces that row's vector, any other key inserts a new row. The
+    /// rows and the vector index change atomically (one write batch)
+    ///
+    /// # Examples
+    /// ```ignore
+    /// db.execute("CREATE TABLE docs (doc_key TEXT PRIMARY KEY, embedding VECTOR(384))")?;
+    /// let result = db.upsert_embeddings(
+    ///     "docs",
+    ///     "doc_key",
+    ///     vec![(Value::text_from("a-17"), embedding)],
+    /// )?;
+    /// println!("{} new, {} replaced", result.inserted, result.updated);
+    /// ```
+    pub fn upsert_embeddings(
+        &self,
+        table_name: &str,
+        key_column: &str,
+        items: Vec<(Value, Vec<f32>)>,
+    ) -> Result<EmbeddingUpsert> {
+        self.inner.upsert_embeddings(table_name, key_column, items)
+    }
+
+    /// Create a vector collection: a table of `(id, embedding, metadata…)`
+    /// records with a vector index, used through [`Collection`] methods
+    /// instead of SQL
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let docs = db.create_collection(
+    ///     "docs",
+    ///     CollectionConfig::new(384).metadata_field("source", ColumnType::Text),
+    /// )?;
+    /// docs.upsert(vec![CollectionRecord::new("a-17", embedding)
+    ///     .with("source", Value::text_from("wiki"))])?;
+    /// let filter = MetadataFilter::from([("source".into(), Value::text_from("wiki"))]);
+    /// for hit in docs.query(&query, 5, &filter)? {
+    ///     println!("{} {:.3}", hit.id, hit.distance);
+    /// }
+    /// ```
+    pub fn create_collection(
+        &self,
+        name: &str,
+        config: CollectionConfig,
+    ) -> Result<Collection<'_>> {
+        self.inner.create_collection(name, config)
+    }
+
+    /// Open a collection created by [`create_collection`](Self::create_collection)
+    pub fn collection(&self, name: &str) -> Result<Collection<'_>> {
+        self.inner.collection(name)
+    }
+
+    /// Drop a collection with its records and vector index
+    pub fn drop_collection(&self, name: &str) -> Result<()> {
+        self.inner.collection(name)?;
+        self.execute(&format!("DROP TABLE {}", name))?;
+        Ok(())
+    }
+
+    /// Compute a VECTOR/TENSOR column from another column at write time,
+    /// so the application doesn't have to issue a second write (and risk
+    /// the two diverging). The hook fills the target when an insert leaves
+    /// it NULL, and again when an update changes the source. Hooks are not
+    /// persisted; register them after every open.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// db.execute("CREATE TABLE docs (id INT PRIMARY KEY, body TEXT, body_vec VECTOR(384))")?;
+    /// db.register_embedder("docs", "body", "body_vec", move |body| match body {
+    ///     Value::Text(text) => Ok(model.embed(text.as_str())),
+    ///     other => Err(StorageError::InvalidData(format!("not text: {:?}", other))),
+    /// })?;
+    /// db.execute("INSERT INTO docs (id, body) VALUES (1, 'lidar fault on boot')")?;
+    /// ```
+    pub fn register_embedder<F>(
+        &self,
+        table_name: &str,
+        source_column: &str,
+        target_column: &str,
+        embed: F,
+    ) -> Result<()>
+    where
+        F: Fn(&Value) -> Result<Vec<f32>> + Send + Sync + 'static,
+    {
+        self.inner
+            .register_embedder(table_name, source_column, target_column, embed)
+    }
+
+    /// Remove the embedder computing `target_column`; returns whether one
+    /// was registered
+    pub fn unregister_embedder(&self, table_name: &str, target_column: &str) -> bool {
+        self.inner.unregister_embedder(table_name, target_column)
+    }
+
     /// 批量插入行（使用 HashMap，比逐行插入快10-20倍）
     ///
     /// 这是 `batch_insert()` 的友好版本，接受 `HashMap<String, Value>` 格式的行数据。
@@ -2575,6 +3122,35 @@ impl Database {
         self.inner.create_text_index(index_name)
     }
 
+    /// 重建索引（与 SQL `REINDEX name` 等价）
+    ///
+    /// Rebuilds any index from table data and swaps it in, e.g. after a
+    /// failed update marked it stale or heavy deletes degraded a DiskANN graph.
+    /// Queries keep using the old index until the swap, and writes to the
+    /// table only pause for the final catch-up and the swap.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// db.rebuild_index("idx_docs_embedding")?;
+    /// ```
+    pub fn rebuild_index(&self, index_name: &str) -> Result<()> {
+        self.inner.rebuild_index(index_name)
+    }
+
+    /// 删除索引（与 SQL `DROP INDEX name` 等价）
+    ///
+    /// Removes the index from the registry and the index manifest, then
+    /// deletes its files. Table data is untouched; queries fall back to
+    /// scanning.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// db.drop_index("idx_docs_embedding")?;
+    /// ```
+    pub fn drop_index(&self, index_name: &str) -> Result<()> {
+        self.inner.drop_index(index_name)
+    }
+
     // ============================================================================
     // 6. 查询 API（使用索引）
     // ============================================================================
@@ -2604,6 +3180,32 @@ impl Database {
         self.inner.query_by_column(table_name, column_name, value)
     }
 
+    /// 按数组元素查询（使用 ARRAY 列的元素索引）
+    ///
+    /// ARRAY 列上的列索引为每个元素建立一条索引项，
+    /// 返回数组中包含 `element` 的行（等价于 `ARRAY_CONTAINS(col, element)`）。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use motedb::Value;
+    ///
+    /// db.execute("CREATE INDEX idx_tags ON frames (labels)")?;
+    /// let row_ids = db.query_by_array_element(
+    ///     "frames",
+    ///     "labels",
+    ///     &Value::text_from("person")
+    /// )?;
+    /// ```
+    pub fn query_by_array_element(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        element: &Value,
+    ) -> Result<Vec<RowId>> {
+        self.inner
+            .query_by_array_element(table_name, column_name, element)
+    }
+
     /// 按列范围查询（使用列索引）
     ///
     /// # Examples
@@ -2666,6 +3268,128 @@ impl Database {
         )
     }
 
+    /// 按列值查询，返回压缩位图（使用列索引）
+    ///
+    /// 与 `query_by_column` 结果相同，但以 `RowIdSet`（roaring 位图，升序）
+    /// 返回，多个索引结果可直接用 `&=` / `|=` 求交集或并集。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use motedb::Value;
+    ///
+    /// let mut row_ids = db.query_by_column_set("logs", "status", &Value::text_from("err"))?;
+    /// row_ids &= db.query_by_column_between_set(
+    ///     "logs",
+    ///     "ts",
+    ///     &Value::Integer(1000), true,
+    ///     &Value::Integer(2000), true
+    /// )?;
+    /// ```
+    pub fn query_by_column_set(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        value: &Value,
+    ) -> Result<RowIdSet> {
+        self.inner
+            .query_by_column_set(table_name, column_name, value)
+    }
+
+    /// 按列范围查询，返回压缩位图（边界语义同 `query_by_column_between`）
+    pub fn query_by_column_between_set(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        start: &Value,
+        start_inclusive: bool,
+        end: &Value,
+        end_inclusive: bool,
+    ) -> Result<RowIdSet> {
+        self.inner.query_by_column_between_set(
+            table_name,
+            column_name,
+            start,
+            start_inclusive,
+            end,
+            end_inclusive,
+        )
+    }
+
+    /// 按列范围查询（流式，使用列索引）
+    ///
+    /// 与 `query_by_column_between` 边界语义相同，但按索引顺序逐页读取
+    /// row_id，不会一次性分配整个结果集，适合范围很宽的查询。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use motedb::Value;
+    ///
+    /// for row_id in db.query_by_column_between_iter(
+    ///     "users",
+    ///     "age",
+    ///     &Value::Integer(18), true,
+    ///     &Value::Integer(65), false
+    /// )? {
+    ///     let row_id = row_id?;
+    /// }
+    /// ```
+    pub fn query_by_column_between_iter(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        start: &Value,
+        start_inclusive: bool,
+        end: &Value,
+        end_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        self.inner.query_by_column_between_iter(
+            table_name,
+            column_name,
+            start,
+            start_inclusive,
+            end,
+            end_inclusive,
+        )
+    }
+
+    /// 按列范围查询（流式、降序，使用列索引）
+    ///
+    /// 与 `query_by_column_between_iter` 相同，但从最大值开始逐页读取，
+    /// 适合 "最新 N 条" 这类只需要范围末尾的查询。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use motedb::Value;
+    ///
+    /// let newest: Vec<u64> = db
+    ///     .query_by_column_between_iter_rev(
+    ///         "events",
+    ///         "ts",
+    ///         &Value::Integer(0), true,
+    ///         &Value::Integer(i64::MAX), true
+    ///     )?
+    ///     .take(10)
+    ///     .collect::<Result<_>>()?;
+    /// ```
+    pub fn query_by_column_between_iter_rev(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        start: &Value,
+        start_inclusive: bool,
+        end: &Value,
+        end_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        self.inner.query_by_column_between_iter_rev(
+            table_name,
+            column_name,
+            start,
+            start_inclusive,
+            end,
+            end_inclusive,
+        )
+    }
+
     /// 向量KNN搜索
     ///
     /// # Examples
@@ -2687,6 +3411,27 @@ impl Database {
         self.inner.vector_search(index_name, query, k)
     }
 
+    /// 带搜索选项的向量KNN搜索（候选列表大小、全精度重排序）
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use motedb::VectorSearchOptions;
+    ///
+    /// // 用全精度向量对前 4k 个候选重新排序
+    /// let options = VectorSearchOptions { rerank: Some(true), ..Default::default() };
+    /// let results = db.vector_search_with_options("docs_embedding", &query_vec, 10, options)?;
+    /// ```
+    pub fn vector_search_with_options(
+        &self,
+        index_name: &str,
+        query: &[f32],
+        k: usize,
+        options: VectorSearchOptions,
+    ) -> Result<Vec<(RowId, f32)>> {
+        self.inner
+            .vector_search_with_options(index_name, query, k, options)
+    }
+
     /// 全文搜索（BM25排序）
     ///
     /// # Examples
@@ -2720,6 +3465,18 @@ impl Database {
         self.inner.query_timestamp_range(start, end)
     }
 
+    /// Timestamp index reorder buffer statistics (None if disabled in `DBConfig`)
+    ///
+    /// # Examples
+    /// ```ignore
+    /// if let Some(stats) = db.timestamp_reorder_stats() {
+    ///     println!("buffered: {}, late: {}", stats.buffered, stats.late);
+    /// }
+    /// ```
+    pub fn timestamp_reorder_stats(&self) -> Option<crate::index::TimestampReorderStats> {
+        self.inner.timestamp_reorder_stats()
+    }
+
     // ============================================================================
     // 7. 统计信息和监控
     // ============================================================================
@@ -2736,6 +3493,24 @@ impl Database {
         self.inner.vector_index_stats(index_name)
     }
 
+    /// 获取所有索引的健康状况（维护统计）
+    ///
+    /// Per index: write amplification, updates still buffered in memory,
+    /// tombstone and fragmentation ratios, stale flag and last rebuild time.
+    /// Use it to schedule `REINDEX` before recall or latency degrade.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// for h in db.index_health()? {
+    ///     if h.tombstone_ratio > 0.3 || h.fragmentation.unwrap_or(0.0) > 0.5 {
+    ///         db.rebuild_index(&h.name)?;
+    ///     }
+    /// }
+    /// ```
+    pub fn index_health(&self) -> Result<Vec<crate::database::IndexHealth>> {
+        self.inner.index_health()
+    }
+
     // ==================== i-Octree 3D Spatial Index (Embodied Intelligence) ====================
 
     /// Create an i-Octree 3D spatial index for point cloud data
@@ -2770,6 +3545,11 @@ impl Database {
         self.inner.transaction_stats()
     }
 
+    /// 列出当前未结束的事务（最早开始的在前），用于排查忘记提交的事务
+    pub fn active_transactions(&self) -> Vec<ActiveTransaction> {
+        self.inner.active_transactions()
+    }
+
     // ============================================================================
     // 8. CRUD 操作（底层 API，通常使用 SQL 更方便）
     // ============================================================================
@@ -2849,6 +3629,44 @@ impl Database {
         }
     }
 
+    /// 读取一行的向量列（VECTOR / TENSOR），只解码该列，不读取整行
+    ///
+    /// 行不存在或该列为 NULL 时返回 `None`；列不存在返回 `ColumnNotFound`，
+    /// 非向量列返回 `InvalidArgument`。TENSOR 列返回展平后的数据。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// if let Some(embedding) = db.get_vector("docs", "embedding", row_id)? {
+    ///     println!("dim = {}", embedding.len());
+    /// }
+    /// ```
+    pub fn get_vector(
+        &self,
+        table_name: &str,
+        column: &str,
+        row_id: RowId,
+    ) -> Result<Option<Vec<f32>>> {
+        let schema = self.inner.get_table_schema(table_name)?;
+        let pos = schema.get_column_position(column).ok_or_else(|| {
+            StorageError::ColumnNotFound(format!("'{}' in table '{}'", column, table_name))
+        })?;
+        if !schema.columns[pos].col_type.is_vector() {
+            return Err(StorageError::InvalidArgument(format!(
+                "Column '{}.{}' is not a VECTOR or TENSOR column",
+                table_name, column
+            )));
+        }
+        let value = self
+            .inner
+            .get_table_columns(table_name, row_id, &[pos])?
+            .and_then(|mut values| values.pop());
+        Ok(match value {
+            Some(Value::Vector(v)) => Some(v.to_vec()),
+            Some(Value::Tensor(t)) => Some(t.as_f32().to_vec()),
+            _ => None,
+        })
+    }
+
     /// 更新行（底层API，推荐使用 SQL UPDATE）
     pub fn update_row(&self, table_name: &str, row_id: RowId, new_row: Row) -> Result<()> {
         // 先获取旧行
@@ -2880,6 +3698,219 @@ impl Database {
         self.inner
             .delete_row_from_table(table_name, row_id, old_row)
     }
+
+    // ============================================================================
+    // 9. 类型化表（#[derive(MoteRecord)]）
+    // ============================================================================
+
+    /// 按记录类型 `T` 的定义建表（列、类型、主键均由 derive 生成）
+    ///
+    /// # Examples
+    /// ```ignore
+    /// #[derive(MoteRecord)]
+    /// struct Reading {
+    ///     #[mote(primary_key)]
+    ///     id: i64,
+    ///     temp_c: f64,
+    /// }
+    ///
+    /// db.create_table_for::<Reading>()?;
+    /// ```
+    pub fn create_table_for<T: crate::record::MoteRecord>(&self) -> Result<()> {
+        self.inner.create_table(T::schema())
+    }
+
+    /// 获取记录类型 `T` 对应表的类型化访问句柄
+    ///
+    /// 表的列（名称与顺序）必须与 `T` 的字段一致，否则返回 `InvalidArgument`。
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let readings = db.records::<Reading>()?;
+    /// let row_id = readings.insert(&Reading { id: 1, temp_c: 21.5 })?;
+    /// let hot = readings.query().filter(Reading::TEMP_C.gt(30.0)).all()?;
+    /// ```
+    pub fn records<T: crate::record::MoteRecord>(&self) -> Result<crate::record::Records<'_, T>> {
+        let schema = self.inner.get_table_schema(T::TABLE)?;
+        let columns: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        if columns != T::COLUMNS {
+            return Err(StorageError::InvalidArgument(format!(
+                "Table '{}' has columns {:?}, but the record type expects {:?}",
+                T::TABLE,
+                columns,
+                T::COLUMNS
+            )));
+        }
+        Ok(crate::record::Records::new(self))
+    }
+}
+
+/// [`Database::transaction`] 闭包中的事务句柄
+///
+/// 句柄不能跨线程传递：事务上下文绑定在开启它的线程上。
+pub struct Transaction<'db> {
+    db: &'db Database,
+    id: u64,
+    /// 已提交；未提交的事务在 drop 时回滚
+    finished: std::cell::Cell<bool>,
+}
+
+impl Transaction<'_> {
+    /// 事务 ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 在事务中执行 SQL
+    pub fn execute(&self, sql: &str) -> Result<StreamingQueryResult> {
+        self.db.execute(sql)
+    }
+
+    /// 在事务中执行查询并返回所有行
+    pub fn query(&self, sql: &str) -> Result<Vec<Vec<Value>>> {
+        self.db.query(sql)
+    }
+
+    /// 插入行（提交时才写入存储）
+    pub fn insert_row(&self, table_name: &str, row: Row) -> Result<RowId> {
+        self.db.insert_row_with_txn(table_name, self.id, row)
+    }
+
+    /// 获取行，包括本事务尚未提交的插入
+    pub fn get_row(&self, table_name: &str, row_id: RowId) -> Result<Option<Row>> {
+        let pending = self
+            .db
+            .inner
+            .txn_coordinator
+            .get_context(self.id)?
+            .write_set
+            .read()
+            .get(&(table_name.to_string(), row_id))
+            .cloned();
+        match pending {
+            Some(row) => Ok(Some(row)),
+            None => self.db.get_row(table_name, row_id),
+        }
+    }
+
+    /// 创建保存点
+    pub fn savepoint(&self, name: &str) -> Result<()> {
+        self.db.savepoint(self.id, name)
+    }
+
+    /// 回滚到保存点
+    pub fn rollback_to_savepoint(&self, name: &str) -> Result<()> {
+        self.db.rollback_to_savepoint(self.id, name)
+    }
+
+    /// 释放保存点
+    pub fn release_savepoint(&self, name: &str) -> Result<()> {
+        self.db.release_savepoint(self.id, name)
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished.get() {
+            if let Err(e) = self.db.rollback_transaction(self.id) {
+                warn_log!("[transaction] rollback of txn {} failed: {}", self.id, e);
+            }
+        }
+    }
+}
+
+/// [`Database::snapshot`] 返回的只读快照，drop 时释放
+pub struct ReadView<'db> {
+    db: &'db Database,
+    id: u64,
+}
+
+impl ReadView<'_> {
+    /// 快照所属事务的 ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 按快照读取行
+    pub fn get_row(&self, table_name: &str, row_id: RowId) -> Result<Option<Row>> {
+        self.db
+            .inner
+            .get_table_row_in_snapshot(table_name, row_id, self.id)
+    }
+
+    /// 按快照读取行（返回 HashMap 格式）
+    pub fn get_row_map(&self, table_name: &str, row_id: RowId) -> Result<Option<SqlRow>> {
+        match self.get_row(table_name, row_id)? {
+            Some(row) => {
+                let schema = self.db.inner.get_table_schema(table_name)?;
+                Ok(Some(crate::sql::row_converter::row_to_sql_row(
+                    &row, &schema,
+                )?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Drop for ReadView<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.db.inner.release_read_snapshot(self.id) {
+            warn_log!("[snapshot] release of txn {} failed: {}", self.id, e);
+        }
+    }
+}
+
+/// [`Database::clone_readonly_view`] 返回的只读视图，clone 共享同一快照
+#[derive(Clone)]
+pub struct ReadOnlyView {
+    pin: Arc<PinnedSnapshot>,
+}
+
+struct PinnedSnapshot {
+    db: Arc<MoteDB>,
+    id: u64,
+}
+
+impl ReadOnlyView {
+    /// 快照所属事务的 ID
+    pub fn id(&self) -> u64 {
+        self.pin.id
+    }
+
+    /// 按快照读取行
+    pub fn get_row(&self, table_name: &str, row_id: RowId) -> Result<Option<Row>> {
+        self.pin
+            .db
+            .get_table_row_in_snapshot(table_name, row_id, self.pin.id)
+    }
+
+    /// 按快照读取行（返回 HashMap 格式）
+    pub fn get_row_map(&self, table_name: &str, row_id: RowId) -> Result<Option<SqlRow>> {
+        match self.get_row(table_name, row_id)? {
+            Some(row) => {
+                let schema = self.pin.db.get_table_schema(table_name)?;
+                Ok(Some(crate::sql::row_converter::row_to_sql_row(
+                    &row, &schema,
+                )?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 按快照扫描整张表（快照之后删除的行仍按删除前的内容返回）
+    pub fn scan_table(&self, table_name: &str) -> Result<Vec<(RowId, Row)>> {
+        self.pin
+            .db
+            .scan_table_rows_in_snapshot(table_name, self.pin.id)
+    }
+}
+
+impl Drop for PinnedSnapshot {
+    fn drop(&mut self) {
+        if let Err(e) = self.db.release_read_snapshot(self.id) {
+            warn_log!("[snapshot] release of txn {} failed: {}", self.id, e);
+        }
+    }
 }
 
 // 自动在 Drop 时关闭数据库
@@ -0,0 +1,573 @@
+//! Benchmark harness (feature `bench`)
+//!
+//! Runs the documented workloads against a fresh database each and reports
+//! per-operation latency percentiles, write throughput and process memory,
+//! so a release can be checked against the edge targets ([`Targets`]:
+//! P99 ≤ 50 ms, ≥ 200 rows/s, ≤ 35 MB):
+//!
+//! - [`Workload::VectorKnn`]: `ORDER BY emb <-> [..] LIMIT k` over a vector index
+//! - [`Workload::SpatialRange`]: `ST_WITHIN` boxes over a spatial index
+//! - [`Workload::TextSearch`]: two-term `MATCH` queries over a text index
+//! - [`Workload::TimeRange`]: aggregates over time windows of a TIMESERIES table
+//! - [`Workload::MixedWrite`]: single-row INSERT / UPDATE / point SELECT mix
+//!
+//! Data and queries come from a generator seeded with [`BenchConfig::seed`],
+//! so two runs with the same config execute the same statements. Memory is
+//! the peak of the process's resident size sampled during the run; run one
+//! workload per process (`motedb-bench --workload ...`) to keep earlier
+//! workloads out of the figure.
+//!
+//! ```ignore
+//! let report = motedb::bench::run(Workload::VectorKnn, &BenchConfig::default(), dir)?;
+//! println!("{}", report);
+//! assert!(report.violations(&Targets::default()).is_empty());
+//! ```
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::database::memory_budget::process_memory_usage;
+use crate::types::{ArcVec, Geometry, Point3D, Timestamp, Value};
+use crate::{DBConfig, Database, Result, StorageError};
+
+/// A benchmark workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    VectorKnn,
+    SpatialRange,
+    TextSearch,
+    TimeRange,
+    MixedWrite,
+}
+
+impl Workload {
+    /// Every workload, in report order
+    pub const ALL: [Workload; 5] = [
+        Workload::VectorKnn,
+        Workload::SpatialRange,
+        Workload::TextSearch,
+        Workload::TimeRange,
+        Workload::MixedWrite,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::VectorKnn => "vector-knn",
+            Workload::SpatialRange => "spatial-range",
+            Workload::TextSearch => "text-search",
+            Workload::TimeRange => "time-range",
+            Workload::MixedWrite => "mixed-write",
+        }
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Workload {
+    type Err = StorageError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Workload::ALL
+            .into_iter()
+            .find(|w| w.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Workload::ALL.iter().map(|w| w.name()).collect();
+                StorageError::InvalidArgument(format!(
+                    "unknown workload '{}' (expected one of: {})",
+                    s,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+/// Workload sizes and the database configuration they run against
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// Rows loaded before the measured phase
+    pub rows: usize,
+    /// Measured operations (queries, or writes for `MixedWrite`)
+    pub operations: usize,
+    /// Rows per batch insert while loading
+    pub batch_size: usize,
+    /// Vector dimension for `VectorKnn`
+    pub dim: usize,
+    /// Neighbours / result limit per query
+    pub k: usize,
+    /// Seed for data and query generation
+    pub seed: u64,
+    /// Configuration of each workload's database
+    pub db_config: DBConfig,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            rows: 10_000,
+            operations: 500,
+            batch_size: 500,
+            dim: 128,
+            k: 10,
+            seed: 42,
+            db_config: DBConfig::for_edge(),
+        }
+    }
+}
+
+/// Release targets a report is checked against
+#[derive(Debug, Clone, Copy)]
+pub struct Targets {
+    /// Highest acceptable P99 operation latency
+    pub p99: Duration,
+    /// Lowest acceptable write throughput (rows/s)
+    pub min_rows_per_sec: f64,
+    /// Highest acceptable peak process memory
+    pub max_memory_bytes: usize,
+}
+
+impl Default for Targets {
+    fn default() -> Self {
+        Self {
+            p99: Duration::from_millis(50),
+            min_rows_per_sec: 200.0,
+            max_memory_bytes: 35 * 1024 * 1024,
+        }
+    }
+}
+
+/// Latency distribution of a set of operations
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Nearest-rank percentiles of `samples`
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let n = samples.len();
+        let rank = |p: f64| samples[((p * n as f64).ceil() as usize).clamp(1, n) - 1];
+        Self {
+            count: n,
+            mean: samples.iter().sum::<Duration>() / n as u32,
+            p50: rank(0.50),
+            p95: rank(0.95),
+            p99: rank(0.99),
+            max: samples[n - 1],
+        }
+    }
+}
+
+/// Result of one workload
+#[derive(Debug, Clone)]
+pub struct WorkloadReport {
+    pub workload: Workload,
+    /// Rows loaded before the measured phase, and how long that took
+    pub rows_loaded: usize,
+    pub load_time: Duration,
+    /// Latency of the measured operations
+    pub latency: LatencyStats,
+    /// Rows returned by the measured queries (0 for `MixedWrite`)
+    pub rows_returned: usize,
+    /// Write throughput: the load phase, or the measured writes of `MixedWrite`
+    pub rows_per_sec: f64,
+    /// Peak process memory sampled during the run, if measurable
+    pub peak_memory_bytes: Option<usize>,
+}
+
+impl WorkloadReport {
+    /// Targets this report misses, one message each
+    pub fn violations(&self, targets: &Targets) -> Vec<String> {
+        let mut missed = Vec::new();
+        if self.latency.p99 > targets.p99 {
+            missed.push(format!(
+                "{}: P99 {:.2} ms > {:.2} ms",
+                self.workload,
+                ms(self.latency.p99),
+                ms(targets.p99)
+            ));
+        }
+        if self.rows_per_sec < targets.min_rows_per_sec {
+            missed.push(format!(
+                "{}: {:.0} rows/s < {:.0} rows/s",
+                self.workload, self.rows_per_sec, targets.min_rows_per_sec
+            ));
+        }
+        if let Some(peak) = self.peak_memory_bytes {
+            if peak > targets.max_memory_bytes {
+                missed.push(format!(
+                    "{}: peak memory {:.1} MB > {:.1} MB",
+                    self.workload,
+                    mb(peak),
+                    mb(targets.max_memory_bytes)
+                ));
+            }
+        }
+        missed
+    }
+
+    /// The report as a JSON object (durations in milliseconds)
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "workload": self.workload.name(),
+            "rows_loaded": self.rows_loaded,
+            "load_ms": ms(self.load_time),
+            "operations": self.latency.count,
+            "rows_returned": self.rows_returned,
+            "mean_ms": ms(self.latency.mean),
+            "p50_ms": ms(self.latency.p50),
+            "p95_ms": ms(self.latency.p95),
+            "p99_ms": ms(self.latency.p99),
+            "max_ms": ms(self.latency.max),
+            "rows_per_sec": self.rows_per_sec,
+            "peak_memory_bytes": self.peak_memory_bytes,
+        })
+    }
+}
+
+impl fmt::Display for WorkloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<14} {:>6} ops  p50 {:>8.3} ms  p95 {:>8.3} ms  p99 {:>8.3} ms  max {:>8.3} ms  {:>9.0} rows/s  ",
+            self.workload.name(),
+            self.latency.count,
+            ms(self.latency.p50),
+            ms(self.latency.p95),
+            ms(self.latency.p99),
+            ms(self.latency.max),
+            self.rows_per_sec
+        )?;
+        match self.peak_memory_bytes {
+            Some(peak) => write!(f, "{:>6.1} MB", mb(peak)),
+            None => f.write_str("     - MB"),
+        }
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn mb(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Run `workload` in a new database `dir/<workload>.mote`, removed afterwards
+pub fn run(workload: Workload, config: &BenchConfig, dir: &Path) -> Result<WorkloadReport> {
+    let path = dir.join(format!("{}.mote", workload.name()));
+    if path.exists() {
+        std::fs::remove_dir_all(&path)?;
+    }
+    let db = Database::create_with_config(&path, config.db_config.clone())?;
+    let mut run = Run {
+        db: &db,
+        config,
+        rng: StdRng::seed_from_u64(config.seed),
+        peak_memory: None,
+    };
+    let report = match workload {
+        Workload::VectorKnn => run.vector_knn(),
+        Workload::SpatialRange => run.spatial_range(),
+        Workload::TextSearch => run.text_search(),
+        Workload::TimeRange => run.time_range(),
+        Workload::MixedWrite => run.mixed_write(),
+    };
+    drop(db);
+    std::fs::remove_dir_all(&path)?;
+    report
+}
+
+/// State of one workload run
+struct Run<'a> {
+    db: &'a Database,
+    config: &'a BenchConfig,
+    rng: StdRng,
+    peak_memory: Option<usize>,
+}
+
+/// Loaded rows and how long loading took
+struct Loaded {
+    rows: usize,
+    time: Duration,
+}
+
+/// Latencies and rows returned by the measured queries
+struct Measured {
+    samples: Vec<Duration>,
+    rows: usize,
+}
+
+impl Run<'_> {
+    fn sample_memory(&mut self) {
+        if let Some(usage) = process_memory_usage() {
+            self.peak_memory = Some(self.peak_memory.map_or(usage, |peak| peak.max(usage)));
+        }
+    }
+
+    /// Load `config.rows` rows produced by `row` with batch inserts, then
+    /// run the DDL in `after` (index creation counts as load time)
+    fn load(
+        &mut self,
+        insert: &str,
+        after: &[&str],
+        mut row: impl FnMut(&mut StdRng, usize) -> Vec<Value>,
+    ) -> Result<Loaded> {
+        let start = Instant::now();
+        let mut batch = Vec::with_capacity(self.config.batch_size);
+        for i in 0..self.config.rows {
+            batch.push(row(&mut self.rng, i));
+            if batch.len() == self.config.batch_size {
+                self.db
+                    .execute_prepared_batch(insert, std::mem::take(&mut batch))?;
+                self.sample_memory();
+            }
+        }
+        if !batch.is_empty() {
+            self.db.execute_prepared_batch(insert, batch)?;
+        }
+        for sql in after {
+            self.db.execute(sql)?;
+        }
+        self.sample_memory();
+        Ok(Loaded {
+            rows: self.config.rows,
+            time: start.elapsed(),
+        })
+    }
+
+    /// Time `config.operations` queries produced by `query`
+    fn measure(&mut self, mut query: impl FnMut(&mut StdRng) -> String) -> Result<Measured> {
+        let mut samples = Vec::with_capacity(self.config.operations);
+        let mut rows = 0;
+        for i in 0..self.config.operations {
+            let sql = query(&mut self.rng);
+            let start = Instant::now();
+            let result = self.db.execute(&sql)?.materialize()?;
+            samples.push(start.elapsed());
+            rows += result.row_count();
+            if i % 64 == 0 {
+                self.sample_memory();
+            }
+        }
+        self.sample_memory();
+        Ok(Measured { samples, rows })
+    }
+
+    fn report(&self, workload: Workload, loaded: Loaded, measured: Measured) -> WorkloadReport {
+        WorkloadReport {
+            workload,
+            rows_loaded: loaded.rows,
+            load_time: loaded.time,
+            latency: LatencyStats::from_samples(measured.samples),
+            rows_returned: measured.rows,
+            rows_per_sec: loaded.rows as f64 / loaded.time.as_secs_f64().max(f64::EPSILON),
+            peak_memory_bytes: self.peak_memory,
+        }
+    }
+
+    fn vector_knn(&mut self) -> Result<WorkloadReport> {
+        let dim = self.config.dim;
+        self.db.execute(&format!(
+            "CREATE TABLE vectors (id INT PRIMARY KEY, emb VECTOR({}))",
+            dim
+        ))?;
+        let loaded = self.load(
+            "INSERT INTO vectors VALUES (?, ?)",
+            &["CREATE VECTOR INDEX vectors_emb ON vectors(emb)"],
+            |rng, i| {
+                vec![
+                    Value::Integer(i as i64),
+                    Value::Vector(ArcVec::new(random_vector(rng, dim))),
+                ]
+            },
+        )?;
+        let k = self.config.k;
+        let measured = self.measure(|rng| {
+            let query: Vec<String> = random_vector(rng, dim)
+                .iter()
+                .map(|x| format!("{:?}", x))
+                .collect();
+            format!(
+                "SELECT id FROM vectors ORDER BY emb <-> [{}] LIMIT {}",
+                query.join(", "),
+                k
+            )
+        })?;
+        Ok(self.report(Workload::VectorKnn, loaded, measured))
+    }
+
+    fn spatial_range(&mut self) -> Result<WorkloadReport> {
+        self.db
+            .execute("CREATE TABLE places (id INT PRIMARY KEY, pos GEOMETRY)")?;
+        let loaded = self.load(
+            "INSERT INTO places VALUES (?, ?)",
+            &["CREATE SPATIAL INDEX places_pos ON places(pos)"],
+            |rng, i| {
+                let point = Point3D::new(
+                    rng.gen_range(0.0..SPATIAL_EXTENT),
+                    rng.gen_range(0.0..SPATIAL_EXTENT),
+                    0.0,
+                );
+                vec![
+                    Value::Integer(i as i64),
+                    Value::spatial(Geometry::Point3D(point)),
+                ]
+            },
+        )?;
+        let measured = self.measure(|rng| {
+            let x = rng.gen_range(0.0..SPATIAL_EXTENT - SPATIAL_BOX);
+            let y = rng.gen_range(0.0..SPATIAL_EXTENT - SPATIAL_BOX);
+            format!(
+                "SELECT id FROM places WHERE ST_WITHIN(pos, {:?}, {:?}, {:?}, {:?})",
+                x,
+                y,
+                x + SPATIAL_BOX,
+                y + SPATIAL_BOX
+            )
+        })?;
+        Ok(self.report(Workload::SpatialRange, loaded, measured))
+    }
+
+    fn text_search(&mut self) -> Result<WorkloadReport> {
+        self.db
+            .execute("CREATE TABLE docs (id INT PRIMARY KEY, body TEXT)")?;
+        let loaded = self.load(
+            "INSERT INTO docs VALUES (?, ?)",
+            &["CREATE TEXT INDEX docs_body ON docs(body)"],
+            |rng, i| {
+                let len = rng.gen_range(8..24);
+                let words: Vec<String> = (0..len).map(|_| skewed_word(rng)).collect();
+                vec![Value::Integer(i as i64), Value::text(words.join(" "))]
+            },
+        )?;
+        let k = self.config.k;
+        let measured = self.measure(|rng| {
+            format!(
+                "SELECT id FROM docs WHERE MATCH(body, '{} {}') LIMIT {}",
+                skewed_word(rng),
+                skewed_word(rng),
+                k
+            )
+        })?;
+        Ok(self.report(Workload::TextSearch, loaded, measured))
+    }
+
+    fn time_range(&mut self) -> Result<WorkloadReport> {
+        self.db
+            .execute("CREATE TABLE readings (ts TIMESTAMP, sensor INT, v FLOAT) TIMESERIES(ts)")?;
+        let loaded = self.load("INSERT INTO readings VALUES (?, ?, ?)", &[], |rng, i| {
+            vec![
+                Value::Timestamp(Timestamp::from_micros(TIME_BASE + i as i64 * TIME_STEP)),
+                Value::Integer(rng.gen_range(0..16)),
+                Value::Float(rng.gen_range(-20.0..40.0)),
+            ]
+        })?;
+        // Windows of 1% of the loaded span
+        let span = self.config.rows as i64 * TIME_STEP;
+        let window = (span / 100).max(TIME_STEP);
+        let measured = self.measure(|rng| {
+            let from = TIME_BASE + rng.gen_range(0..(span - window).max(1));
+            format!(
+                "SELECT COUNT(*), AVG(v), MAX(v) FROM readings WHERE ts >= {} AND ts < {}",
+                from,
+                from + window
+            )
+        })?;
+        Ok(self.report(Workload::TimeRange, loaded, measured))
+    }
+
+    /// 80% single-row INSERTs, 10% UPDATEs and 10% point SELECTs by primary
+    /// key, one statement per operation
+    fn mixed_write(&mut self) -> Result<WorkloadReport> {
+        self.db.execute(
+            "CREATE TABLE events (id INT PRIMARY KEY, sensor INT, v FLOAT, ts TIMESTAMP)",
+        )?;
+        let loaded = self.load("INSERT INTO events VALUES (?, ?, ?, ?)", &[], |rng, i| {
+            vec![
+                Value::Integer(i as i64),
+                Value::Integer(rng.gen_range(0..16)),
+                Value::Float(rng.gen_range(0.0..100.0)),
+                Value::Timestamp(Timestamp::from_micros(TIME_BASE + i as i64 * TIME_STEP)),
+            ]
+        })?;
+
+        let mut next_id = self.config.rows as i64;
+        let mut written = 0usize;
+        let start = Instant::now();
+        let measured = self.measure(|rng| {
+            let roll = rng.gen_range(0..10);
+            if roll < 8 || next_id == 0 {
+                next_id += 1;
+                written += 1;
+                format!(
+                    "INSERT INTO events VALUES ({}, {}, {:?}, {})",
+                    next_id,
+                    rng.gen_range(0..16),
+                    rng.gen_range(0.0..100.0f64),
+                    TIME_BASE + next_id * TIME_STEP
+                )
+            } else if roll == 8 {
+                written += 1;
+                format!(
+                    "UPDATE events SET v = {:?} WHERE id = {}",
+                    rng.gen_range(0.0..100.0f64),
+                    rng.gen_range(0..next_id)
+                )
+            } else {
+                format!(
+                    "SELECT id, sensor, v, ts FROM events WHERE id = {}",
+                    rng.gen_range(0..next_id)
+                )
+            }
+        })?;
+        let elapsed = start.elapsed();
+
+        let mut report = self.report(Workload::MixedWrite, loaded, measured);
+        report.rows_returned = 0;
+        report.rows_per_sec = written as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        Ok(report)
+    }
+}
+
+/// Side of the square the spatial points are spread over
+const SPATIAL_EXTENT: f64 = 1000.0;
+
+/// Side of a spatial query box (0.04% of the area)
+const SPATIAL_BOX: f64 = 20.0;
+
+/// First timestamp (2024-01-01T00:00:00Z) and spacing of time-series rows
+const TIME_BASE: i64 = 1_704_067_200_000_000;
+const TIME_STEP: i64 = 1_000_000;
+
+/// Syllables text-search words are built from (512 three-syllable words)
+const SYLLABLES: [&str; 8] = ["ka", "lo", "mi", "ru", "te", "so", "na", "vi"];
+
+/// A word drawn with a skew towards low indexes, so a few words are common
+/// and most are rare, like natural text
+fn skewed_word(rng: &mut StdRng) -> String {
+    let r: f64 = rng.gen();
+    let index = (r * r * 512.0) as usize;
+    (0..3).map(|i| SYLLABLES[(index >> (3 * i)) & 7]).collect()
+}
+
+fn random_vector(rng: &mut StdRng, dim: usize) -> Vec<f32> {
+    (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect()
+}
@@ -0,0 +1,144 @@
+//! MoteDB 基准测试 - 可复现的工作负载与发布目标检查
+//!
+//! 依次运行各工作负载（每个使用新的数据库），输出延迟分位数、写入吞吐量和
+//! 进程内存峰值；`--check` 时未达到目标（P99 ≤ 50ms / ≥ 200 rows/s /
+//! ≤ 35MB）以非零状态退出
+
+use motedb::bench::{self, BenchConfig, Targets, Workload};
+use motedb::{Result, StorageError};
+use std::env;
+use std::path::PathBuf;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(2),
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 返回 false 表示 `--check` 下有目标未达到
+fn run() -> Result<bool> {
+    let mut config = BenchConfig::default();
+    let mut workloads = Vec::new();
+    let mut dir = env::temp_dir().join(format!("motedb-bench-{}", std::process::id()));
+    let mut json = false;
+    let mut check = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| StorageError::InvalidArgument(format!("{} needs a value", arg)))
+        };
+        match arg.as_str() {
+            "--workload" | "-w" => workloads.push(value()?.parse::<Workload>()?),
+            "--rows" => config.rows = parse_number(&value()?)?,
+            "--ops" => config.operations = parse_number(&value()?)?,
+            "--batch" => config.batch_size = parse_number(&value()?)?.max(1),
+            "--dim" => config.dim = parse_number(&value()?)?,
+            "--k" => config.k = parse_number(&value()?)?,
+            "--seed" => config.seed = parse_number(&value()?)? as u64,
+            "--dir" => dir = PathBuf::from(value()?),
+            "--json" => json = true,
+            "--check" => check = true,
+            "--help" | "-h" => {
+                print_help();
+                return Ok(true);
+            }
+            other => {
+                print_help();
+                return Err(StorageError::InvalidArgument(format!(
+                    "unknown argument '{}'",
+                    other
+                )));
+            }
+        }
+    }
+    if workloads.is_empty() {
+        workloads = Workload::ALL.to_vec();
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    let targets = Targets::default();
+    let mut reports = Vec::new();
+    if !json {
+        println!(
+            "MoteDB v{} bench: {} rows, {} ops, seed {}\n",
+            VERSION, config.rows, config.operations, config.seed
+        );
+    }
+    for workload in workloads {
+        let report = bench::run(workload, &config, &dir)?;
+        if !json {
+            println!("{}", report);
+        }
+        reports.push(report);
+    }
+    let _ = std::fs::remove_dir(&dir);
+
+    let violations: Vec<String> = reports
+        .iter()
+        .flat_map(|report| report.violations(&targets))
+        .collect();
+    if json {
+        let reports: Vec<_> = reports.iter().map(|report| report.to_json()).collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "version": VERSION,
+                "rows": config.rows,
+                "operations": config.operations,
+                "seed": config.seed,
+                "workloads": reports,
+                "violations": violations,
+            })
+        );
+    } else if violations.is_empty() {
+        println!("\n✅ All targets met");
+    } else {
+        println!("\n⚠️  Targets missed:");
+        for violation in &violations {
+            println!("  • {}", violation);
+        }
+    }
+    Ok(!check || violations.is_empty())
+}
+
+fn parse_number(value: &str) -> Result<usize> {
+    value
+        .replace('_', "")
+        .parse()
+        .map_err(|_| StorageError::InvalidArgument(format!("'{}' is not a number", value)))
+}
+
+fn print_help() {
+    println!(
+        r#"
+MoteDB v{} - 基准测试
+
+用法:
+  motedb-bench [选项]
+
+选项:
+  -w, --workload <name>  只运行指定工作负载（可重复）:
+                         vector-knn, spatial-range, text-search,
+                         time-range, mixed-write（默认全部）
+  --rows <n>             预加载行数（默认 10000）
+  --ops <n>              计时的操作数（默认 500）
+  --batch <n>            加载时每批插入的行数（默认 500）
+  --dim <n>              向量维度（默认 128）
+  --k <n>                每次查询返回的行数上限（默认 10）
+  --seed <n>             数据与查询生成的随机种子（默认 42）
+  --dir <path>           数据目录（默认系统临时目录，运行后删除）
+  --json                 以 JSON 输出报告
+  --check                未达到目标（P99 ≤ 50ms / ≥ 200 rows/s / ≤ 35MB）时以状态 2 退出
+"#,
+        VERSION
+    );
+}
@@ -3,15 +3,27 @@
 //!
 //! 这是 motedb 的主入口点,直接调用 motedb-cli 的功能
 
-use motedb::sql::{Lexer, Parser, QueryExecutor}; // ✅ 使用流式 API
+use motedb::sql::{parse_script, QueryExecutor, Statement}; // ✅ 使用流式 API
 use motedb::*;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
 use std::env;
-use std::io::{self, BufRead, Write};
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// 历史记录文件名（位于 $HOME）
+const HISTORY_FILE: &str = ".motedb_history";
+
 fn main() {
     if let Err(e) = run() {
         eprintln!("❌ Error: {}", e);
@@ -42,6 +54,9 @@ fn run() -> Result<()> {
                 }
             }
         }
+        3 if args[1] == "--migrate-format" => {
+            migrate_format(PathBuf::from(&args[2]))?;
+        }
         _ => {
             print_help();
             return Err(StorageError::InvalidData("Invalid arguments".to_string()));
@@ -59,6 +74,8 @@ MoteDB v{} - 高性能嵌入式数据库引擎
 用法:
   motedb                启动交互式 SQL shell (默认数据库: ./motedb_data)
   motedb <db_path>      打开指定数据库
+  motedb --migrate-format <db_path>
+                        将旧格式的行数据重写为当前的版本化格式
   motedb --version      显示版本信息
   motedb --help         显示此帮助信息
 
@@ -87,6 +104,23 @@ MoteDB v{} - 高性能嵌入式数据库引擎
     );
 }
 
+fn migrate_format(path: PathBuf) -> Result<()> {
+    if !path.exists() {
+        return Err(StorageError::InvalidData(format!(
+            "Database not found: {}",
+            path.display()
+        )));
+    }
+    println!("📂 Database: {}", path.display());
+    let db = MoteDB::open(&path)?;
+    let stats = db.migrate_row_format()?;
+    println!(
+        "✅ {} 行已迁移 (扫描 {} 行, {} 张表)",
+        stats.rows_rewritten, stats.rows_scanned, stats.tables
+    );
+    Ok(())
+}
+
 fn interactive_mode(db_path: Option<PathBuf>) -> Result<()> {
     let path = db_path.unwrap_or_else(|| PathBuf::from("./motedb_data"));
 
@@ -102,101 +136,302 @@ fn interactive_mode(db_path: Option<PathBuf>) -> Result<()> {
         MoteDB::create(&path)?
     });
 
-    let stdin = io::stdin();
-    let mut buffer = String::new();
+    let mut editor = Editor::<SqlHelper, DefaultHistory>::new().map_err(readline_error)?;
+    editor.set_helper(Some(SqlHelper { db: db.clone() }));
+    // 历史记录保存在 $HOME/.motedb_history
+    let history = env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    let mut shell = Shell { db, timer: true };
     let mut multiline_sql = String::new();
 
     loop {
-        // 显示提示符
-        if multiline_sql.is_empty() {
-            print!("motedb> ");
+        let prompt = if multiline_sql.is_empty() {
+            "motedb> "
         } else {
-            print!("     -> ");
-        }
-        io::stdout().flush().unwrap();
+            "     -> "
+        };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C 丢弃当前输入
+            Err(ReadlineError::Interrupted) => {
+                multiline_sql.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(readline_error(e)),
+        };
 
-        // 读取输入
-        buffer.clear();
-        if stdin.lock().read_line(&mut buffer).is_err() {
-            break;
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
         }
-
-        let input = buffer.trim();
+        let _ = editor.add_history_entry(input);
 
         // 特殊命令
-        if input.starts_with('.') {
-            if !multiline_sql.is_empty() {
-                eprintln!("⚠️  Warning: Incomplete SQL statement discarded");
-                multiline_sql.clear();
-            }
-
-            match input {
-                ".exit" | ".quit" => {
+        if multiline_sql.is_empty() && input.starts_with('.') {
+            match shell.run_command(input) {
+                Ok(true) => {}
+                Ok(false) => {
                     println!("👋 Goodbye!");
                     break;
                 }
-                ".help" => {
-                    print_interactive_help();
-                }
-                ".tables" => {
-                    list_tables(&db)?;
-                }
-                ".schema" => {
-                    show_all_schemas(&db)?;
-                }
-                cmd if cmd.starts_with(".schema ") => {
-                    let table = &cmd[8..];
-                    show_table_schema(&db, table)?;
-                }
-                _ => {
-                    eprintln!("❌ Unknown command: {}", input);
-                    println!("💡 Type '.help' for available commands");
-                }
+                Err(e) => eprintln!("❌ Error: {}", e),
             }
             continue;
         }
 
-        // 跳过空行
-        if input.is_empty() {
-            continue;
+        // 累积多行 SQL，以分号结尾时执行
+        multiline_sql.push_str(input);
+        multiline_sql.push('\n');
+        if input.ends_with(';') {
+            if let Err(e) = shell.run_sql(&multiline_sql) {
+                eprintln!("❌ Error: {}", e);
+            }
+            multiline_sql.clear();
         }
+    }
 
-        // 累积多行 SQL
-        multiline_sql.push_str(input);
-        multiline_sql.push(' ');
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    Ok(())
+}
 
-        // 检查是否是完整的 SQL 语句 (以分号结尾)
-        if input.ends_with(';') {
-            // 执行 SQL
-            let sql = multiline_sql.trim_end_matches(';').trim();
-
-            // ✅ 使用流式 API 并物化
-            let result = (|| -> Result<_> {
-                let mut lexer = Lexer::new(sql);
-                let tokens = lexer.tokenize()?;
-                let mut parser = Parser::new(tokens);
-                let statement = parser.parse()?;
-                let executor = QueryExecutor::new(db.clone());
-                let streaming_result = executor.execute_streaming(statement)?;
-                streaming_result.materialize()
-            })();
-
-            match result {
-                Ok(result) => {
-                    display_result(result);
-                }
-                Err(e) => {
-                    eprintln!("❌ Error: {}", e);
+fn readline_error(e: ReadlineError) -> StorageError {
+    match e {
+        ReadlineError::Io(e) => StorageError::Io(e),
+        other => StorageError::InvalidData(other.to_string()),
+    }
+}
+
+/// 交互式会话状态
+struct Shell {
+    db: Arc<MoteDB>,
+    /// 是否打印每条语句的耗时（`.timer on|off`）
+    timer: bool,
+}
+
+impl Shell {
+    /// 执行一条点命令；返回 false 表示退出
+    fn run_command(&mut self, input: &str) -> Result<bool> {
+        let mut parts = input.split_whitespace();
+        let command = parts.next().unwrap_or_default();
+        let args: Vec<&str> = parts.collect();
+
+        match (command, args.as_slice()) {
+            (".exit" | ".quit", []) => return Ok(false),
+            (".help", []) => print_interactive_help(),
+            (".tables", []) => list_tables(&self.db)?,
+            (".schema", []) => show_all_schemas(&self.db)?,
+            (".schema", [table]) => show_table_schema(&self.db, table)?,
+            (".timer", ["on"]) => self.timer = true,
+            (".timer", ["off"]) => self.timer = false,
+            (".timer", []) => {
+                println!("⏱  Timer: {}", if self.timer { "on" } else { "off" })
+            }
+            (".dump", _) => self.dump(&args)?,
+            (".import", [file]) => self.import(file)?,
+            _ => {
+                eprintln!("❌ Unknown command: {}", input);
+                println!("💡 Type '.help' for available commands");
+            }
+        }
+        Ok(true)
+    }
+
+    /// `.dump [table] [> file]`
+    fn dump(&self, args: &[&str]) -> Result<()> {
+        let (table, file) = match args {
+            [] => (None, None),
+            [table] => (Some(*table), None),
+            [">", file] => (None, Some(*file)),
+            [table, ">", file] => (Some(*table), Some(*file)),
+            _ => {
+                eprintln!("❌ Usage: .dump [table] [> file]");
+                return Ok(());
+            }
+        };
+        match file {
+            Some(file) => {
+                let mut out = BufWriter::new(File::create(file)?);
+                let rows = self.db.dump_sql(table, &mut out)?;
+                println!("✅ {} row(s) written to {}", rows, file);
+            }
+            None => {
+                self.db.dump_sql(table, &mut io::stdout().lock())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `.import FILE`: 执行 SQL 脚本（例如 `.dump` 的输出）
+    fn import(&self, file: &str) -> Result<()> {
+        let script = fs::read_to_string(file)?;
+        let statements = parse_script(&script)?;
+        let started = Instant::now();
+        for (i, statement) in statements.iter().enumerate() {
+            self.execute(statement).map_err(|e| {
+                StorageError::InvalidData(format!("statement {} in {}: {}", i + 1, file, e))
+            })?;
+        }
+        println!(
+            "✅ {} statement(s) executed from {}",
+            statements.len(),
+            file
+        );
+        self.print_elapsed(started);
+        Ok(())
+    }
+
+    /// 执行以分号分隔的一条或多条 SQL 语句
+    fn run_sql(&self, sql: &str) -> Result<()> {
+        for statement in parse_script(sql)? {
+            let started = Instant::now();
+            let result = self.execute(&statement)?;
+            let elapsed = started.elapsed();
+            match (&statement, result) {
+                // EXPLAIN 逐行输出计划
+                (Statement::Explain(_), sql::QueryResult::Select { rows, .. }) => {
+                    for row in rows {
+                        if let Some(types::Value::Text(line)) = row.first() {
+                            println!("{}", line);
+                        }
+                    }
                 }
+                (_, result) => display_result(result),
+            }
+            if self.timer {
+                println!("⏱  {:.3} ms", elapsed.as_secs_f64() * 1000.0);
             }
+        }
+        Ok(())
+    }
 
-            multiline_sql.clear();
+    fn execute(&self, statement: &Statement) -> Result<sql::QueryResult> {
+        // ✅ 使用流式 API 并物化
+        QueryExecutor::new(self.db.clone())
+            .execute_streaming_ref(statement)?
+            .materialize()
+    }
+
+    fn print_elapsed(&self, started: Instant) {
+        if self.timer {
+            println!("⏱  {:.3} ms", started.elapsed().as_secs_f64() * 1000.0);
         }
     }
+}
 
-    Ok(())
+/// 点命令（补全用）
+const COMMANDS: &[&str] = &[
+    ".dump", ".exit", ".help", ".import", ".quit", ".schema", ".tables", ".timer",
+];
+
+/// 补全用的 SQL 关键字
+const KEYWORDS: &[&str] = &[
+    "ALTER", "AND", "AS", "ASC", "BEGIN", "BETWEEN", "BY", "COMMIT", "COPY", "COUNT", "CREATE",
+    "DELETE", "DESC", "DESCRIBE", "DISTINCT", "DROP", "EXPLAIN", "FROM", "GROUP", "HAVING",
+    "INDEX", "INSERT", "INTO", "IS", "JOIN", "LEFT", "LIKE", "LIMIT", "MATCH", "NOT", "NULL", "ON",
+    "OR", "ORDER", "PRIMARY", "KEY", "ROLLBACK", "SELECT", "SET", "SHOW", "TABLE", "TABLES", "TO",
+    "UPDATE", "VALUES", "WHERE", "WITH",
+];
+
+/// 这些关键字（及 `.schema` / `.dump`）之后补全表名
+const TABLE_CONTEXT: &[&str] = &["FROM", "JOIN", "INTO", "UPDATE", "TABLE", "DESCRIBE"];
+
+/// rustyline 辅助：点命令、表名、列名和关键字补全
+struct SqlHelper {
+    db: Arc<MoteDB>,
+}
+
+impl SqlHelper {
+    fn tables(&self) -> Vec<String> {
+        let mut tables = self.db.list_tables().unwrap_or_default();
+        tables.sort();
+        tables
+    }
+
+    fn columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = self
+            .tables()
+            .iter()
+            .filter_map(|table| self.db.get_table_schema(table).ok())
+            .flat_map(|schema| {
+                schema
+                    .columns
+                    .iter()
+                    .map(|col| col.name.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        columns.sort();
+        columns.dedup();
+        columns
+    }
 }
 
+impl Completer for SqlHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let previous = before[..start]
+            .split_whitespace()
+            .next_back()
+            .unwrap_or_default();
+
+        let candidates: Vec<String> = if start == 0 && word.starts_with('.') {
+            COMMANDS.iter().map(|c| c.to_string()).collect()
+        } else if previous == ".schema"
+            || previous == ".dump"
+            || TABLE_CONTEXT
+                .iter()
+                .any(|k| k.eq_ignore_ascii_case(previous))
+        {
+            self.tables()
+        } else if word.is_empty() {
+            Vec::new()
+        } else {
+            let mut all = self.columns();
+            all.extend(self.tables());
+            all.extend(KEYWORDS.iter().map(|k| k.to_string()));
+            all
+        };
+
+        let prefix = word.to_ascii_lowercase();
+        let matches = candidates
+            .into_iter()
+            .filter(|c| c.to_ascii_lowercase().starts_with(&prefix))
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: c,
+            })
+            .collect();
+        Ok((start, matches))
+    }
+}
+
+impl Hinter for SqlHelper {
+    type Hint = String;
+}
+
+impl Highlighter for SqlHelper {}
+
+impl Validator for SqlHelper {}
+
+impl Helper for SqlHelper {}
+
 fn display_result(result: sql::QueryResult) {
     use sql::QueryResult;
 
@@ -232,6 +467,11 @@ fn display_table(columns: &[String], rows: &[Vec<types::Value>]) {
                 Value::Null => 4,
                 Value::Integer(n) => n.to_string().len(),
                 Value::Float(f) => format!("{:.2}", f).len(),
+                Value::Decimal(d) => d.to_string().len(),
+                Value::Uuid(_) => 36,
+                Value::Date(_) => 10,
+                Value::Time(t) => t.to_string().len(),
+                Value::Array(_) => value.encoded_text().unwrap_or_default().len().min(50),
                 Value::Text(s) => s.len().min(50),
                 Value::Bool(b) => b.to_string().len(),
                 Value::Vector(_) => 12,
@@ -284,6 +524,18 @@ fn display_table(columns: &[String], rows: &[Vec<types::Value>]) {
                 Value::Null => "NULL".to_string(),
                 Value::Integer(n) => n.to_string(),
                 Value::Float(f) => format!("{:.2}", f),
+                Value::Decimal(d) => d.to_string(),
+                Value::Uuid(u) => u.to_string(),
+                Value::Date(d) => d.to_string(),
+                Value::Time(t) => t.to_string(),
+                Value::Array(_) => {
+                    let s = value.encoded_text().unwrap_or_default();
+                    if s.len() > 50 {
+                        format!("{}...", s.chars().take(47).collect::<String>())
+                    } else {
+                        s
+                    }
+                }
                 Value::Text(s) => {
                     if s.len() > 50 {
                         format!("{}...", &s[..47])
@@ -329,6 +581,13 @@ fn print_interactive_help() {
   .tables            列出所有表
   .schema            显示所有表的结构
   .schema <table>    显示指定表的结构
+  .timer on|off      打印每条语句的耗时（默认开启）
+  .dump [table] [> file]
+                     以 SQL 语句导出表（默认输出到终端）
+  .import <file>     执行 SQL 脚本（例如 .dump 的输出）
+
+  Tab 补全点命令、表名、列名和关键字；历史记录保存在 ~/.motedb_history
+
 
 SQL 示例:
   CREATE TABLE users (id INTEGER, name TEXT, email TEXT);
@@ -337,6 +596,7 @@ SQL 示例:
   SELECT * FROM users WHERE email = 'alice@example.com';
   UPDATE users SET name = 'Bob' WHERE id = 1;
   DELETE FROM users WHERE id = 1;
+  EXPLAIN SELECT * FROM users WHERE email = 'alice@example.com';
 
 支持的索引类型:
   COLUMN_VALUE    列值索引 (快速等值查询)
@@ -383,17 +643,17 @@ fn show_table_schema(db: &MoteDB, table_name: &str) -> Result<()> {
     let schema = db.get_table_schema(table_name)?;
 
     println!("📋 Table: {}", table_name);
-    println!("┌─────────────────┬──────────────┬──────────┐");
-    println!("│ Column          │ Type         │ Nullable │");
-    println!("├─────────────────┼──────────────┼──────────┤");
+    println!("┌─────────────────┬──────────────────────┬──────────┐");
+    println!("│ Column          │ Type                 │ Nullable │");
+    println!("├─────────────────┼──────────────────────┼──────────┤");
 
     for col in &schema.columns {
-        let type_str = format!("{:?}", col.col_type);
+        let type_str = col.col_type.to_string();
         let nullable = if col.nullable { "YES" } else { "NO" };
-        println!("│ {:15} │ {:12} │ {:8} │", col.name, type_str, nullable);
+        println!("│ {:15} │ {:20} │ {:8} │", col.name, type_str, nullable);
     }
 
-    println!("└─────────────────┴──────────────┴──────────┘");
+    println!("└─────────────────┴──────────────────────┴──────────┘");
 
     Ok(())
 }
@@ -1,5 +1,9 @@
 //! Cache module - LRU caches for performance optimization
 
+pub mod negative_cache;
 pub mod row_cache;
+pub mod stats;
 
+pub use negative_cache::{NegativeCache, NegativeCacheStats};
 pub use row_cache::{CacheStats, RowCache};
+pub use stats::{CacheCounters, DatabaseCacheStats, RowCacheWriteStats, TableCacheQuota};
@@ -0,0 +1,200 @@
+//! Negative Cache - remembers composite keys known to be absent
+//!
+//! **Purpose**: Let repeated point lookups of missing keys (ingestion dedup
+//! loops asking "does this id exist?") skip bloom filters and SSTable probes
+//!
+//! **Correctness**: Writers invalidate a key *after* making it visible; a
+//! reader only records a miss if no invalidation happened since it started
+//! probing (epoch check under the cache lock), so a concurrent insert can
+//! never leave a stale "absent" entry behind.
+//!
+//! **Memory**: Default 4,096 keys, a few hundred KB at most
+
+use lru::LruCache;
+use parking_lot::RwLock;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Default number of absent keys remembered per cache
+pub const DEFAULT_NEGATIVE_CACHE_CAPACITY: usize = 4096;
+
+/// Snapshot of negative cache counters
+#[derive(Debug, Default, Clone)]
+pub struct NegativeCacheStats {
+    /// Lookups answered "absent" without probing storage
+    pub hits: u64,
+    /// Lookups that had to probe storage
+    pub misses: u64,
+    /// Absent keys dropped to make room for newer ones
+    pub evictions: u64,
+    /// Keys currently remembered as absent
+    pub size: usize,
+    pub capacity: usize,
+}
+
+/// Bounded LRU set of keys known to be missing
+pub struct NegativeCache {
+    keys: RwLock<LruCache<u64, ()>>,
+    /// Bumped by every invalidation, before the affected keys are dropped
+    epoch: AtomicU64,
+    /// Mirror of `keys.len()` so writes skip the lock while the cache is empty
+    len: AtomicUsize,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl Default for NegativeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_NEGATIVE_CACHE_CAPACITY)
+    }
+}
+
+impl NegativeCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            keys: RwLock::new(LruCache::new(NonZeroUsize::new(capacity).unwrap())),
+            epoch: AtomicU64::new(0),
+            len: AtomicUsize::new(0),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether `key` is known to be absent
+    pub fn contains(&self, key: u64) -> bool {
+        if self.len.load(Ordering::SeqCst) > 0 && self.keys.read().contains(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return true;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Epoch to pass to [`NegativeCache::insert`]; read it before probing storage
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Remember `key` as absent, unless something was invalidated since `epoch`
+    pub fn insert(&self, key: u64, epoch: u64) {
+        let mut keys = self.keys.write();
+        if self.epoch.load(Ordering::SeqCst) != epoch {
+            return;
+        }
+        if matches!(keys.push(key, ()), Some((old, _)) if old != key) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        self.len.store(keys.len(), Ordering::SeqCst);
+    }
+
+    /// Forget `key` (call after the write that creates it is visible)
+    pub fn invalidate(&self, key: u64) {
+        self.invalidate_keys(std::iter::once(key));
+    }
+
+    /// Forget every key in `keys` (one epoch bump for a whole batch)
+    pub fn invalidate_keys(&self, keys: impl IntoIterator<Item = u64>) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        if self.len.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let mut cached = self.keys.write();
+        for key in keys {
+            cached.pop(&key);
+        }
+        self.len.store(cached.len(), Ordering::SeqCst);
+    }
+
+    /// Forget every key in `[start, end)`
+    pub fn invalidate_range(&self, start: u64, end: u64) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        if self.len.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let mut cached = self.keys.write();
+        let in_range: Vec<u64> = cached
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|key| (start..end).contains(key))
+            .collect();
+        for key in in_range {
+            cached.pop(&key);
+        }
+        self.len.store(cached.len(), Ordering::SeqCst);
+    }
+
+    /// Forget everything
+    pub fn clear(&self) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        let mut cached = self.keys.write();
+        cached.clear();
+        self.len.store(0, Ordering::SeqCst);
+    }
+
+    pub fn stats(&self) -> NegativeCacheStats {
+        NegativeCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            size: self.len.load(Ordering::Relaxed),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_cache_insert_and_invalidate() {
+        let cache = NegativeCache::new(100);
+        assert!(!cache.contains(7));
+
+        cache.insert(7, cache.epoch());
+        cache.insert(8, cache.epoch());
+        cache.insert(20, cache.epoch());
+        assert!(cache.contains(7));
+
+        cache.invalidate(7);
+        assert!(!cache.contains(7));
+        assert!(cache.contains(8));
+
+        cache.invalidate_range(0, 10);
+        assert!(!cache.contains(8));
+        assert!(cache.contains(20));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.size, 1);
+    }
+
+    #[test]
+    fn test_negative_cache_skips_stale_miss() {
+        let cache = NegativeCache::new(100);
+
+        // A write lands between the reader's probe and its insert
+        let epoch = cache.epoch();
+        cache.invalidate(5);
+        cache.insert(5, epoch);
+        assert!(!cache.contains(5));
+    }
+
+    #[test]
+    fn test_negative_cache_lru_bound() {
+        let cache = NegativeCache::new(3);
+        for key in 0..10 {
+            cache.insert(key, cache.epoch());
+        }
+        assert_eq!(cache.stats().size, 3);
+        assert_eq!(cache.stats().evictions, 7);
+        assert!(cache.contains(9));
+        assert!(!cache.contains(0));
+    }
+}
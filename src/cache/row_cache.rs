@@ -7,13 +7,21 @@
 //! **Memory**: Default 10,000 rows ≈ 10MB (assuming 1KB/row average)
 //!
 //! **P2 Prefetching**: Detects sequential access patterns and prefetches ahead
-
+//!
+//! **Writes**: every committed write goes through [`RowCache::apply_write`],
+//! which either stores the new row (write-through) or drops the cached one
+//! (invalidate-on-commit), see [`RowCacheWritePolicy`]. Rows read from storage
+//! are cached with [`RowCache::fill`], which refuses a row read before a
+//! concurrent write landed, so a slow reader can't put a pre-write row back.
+
+use super::stats::TableCacheQuota;
+use crate::config::RowCacheWritePolicy;
 use crate::types::{Row, RowId};
 use lru::LruCache;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Row cache key: (table_hash, row_id) — avoids String allocation per lookup
@@ -42,6 +50,15 @@ struct AccessPattern {
     last_access: std::time::Instant,
 }
 
+/// Per-table cap on cached rows
+#[derive(Debug, Clone)]
+struct TableQuota {
+    table: String,
+    limit: usize,
+    /// Rows of the table currently cached (kept in sync under the cache lock)
+    used: usize,
+}
+
 /// Row cache with LRU eviction and prefetching
 pub struct RowCache {
     /// LRU cache: (table_name, row_id) -> Arc<Row>
@@ -54,6 +71,22 @@ pub struct RowCache {
     capacity: usize,
     prefetch_triggered: AtomicU64,
     prefetch_useful: AtomicU64,
+    evictions: AtomicU64,
+
+    /// `RowCacheWritePolicy` as u8
+    write_policy: AtomicU8,
+    /// Bumped under the cache write lock by every write; `fill` only caches
+    /// a row if no write happened since the reader took its `fill_epoch`
+    write_epoch: AtomicU64,
+    write_through: AtomicU64,
+    write_invalidations: AtomicU64,
+    table_invalidations: AtomicU64,
+    stale_fills_rejected: AtomicU64,
+
+    /// Per-table quotas keyed by table hash. Always locked after `cache`.
+    quotas: RwLock<HashMap<u64, TableQuota>>,
+    /// Fast path: skip quota bookkeeping while no table has a quota
+    has_quotas: AtomicBool,
 
     /// 🚀 Replaced DashMap with RwLock<HashMap> (single lock, no sharding overhead on edge)
     access_patterns: Arc<RwLock<HashMap<String, AccessPattern>>>,
@@ -71,6 +104,17 @@ pub struct CacheStats {
     pub capacity: usize,
     pub prefetch_triggered: u64,
     pub prefetch_useful: u64,
+    pub evictions: u64,
+    pub write_policy: RowCacheWritePolicy,
+    /// Written rows stored in place (write-through)
+    pub write_through: u64,
+    /// Cached rows dropped because the row was written or deleted
+    pub write_invalidations: u64,
+    /// Whole-table drops (DROP TABLE, ALTER TABLE, ...)
+    pub table_invalidations: u64,
+    /// Rows read from storage that were not cached because a write
+    /// landed while they were being read
+    pub stale_fills_rejected: u64,
 }
 
 impl CacheStats {
@@ -122,6 +166,15 @@ impl RowCache {
             capacity,
             prefetch_triggered: AtomicU64::new(0),
             prefetch_useful: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            write_policy: AtomicU8::new(RowCacheWritePolicy::default() as u8),
+            write_epoch: AtomicU64::new(0),
+            write_through: AtomicU64::new(0),
+            write_invalidations: AtomicU64::new(0),
+            table_invalidations: AtomicU64::new(0),
+            stale_fills_rejected: AtomicU64::new(0),
+            quotas: RwLock::new(HashMap::new()),
+            has_quotas: AtomicBool::new(false),
             access_patterns: Arc::new(RwLock::new(HashMap::new())),
             prefetch_config,
         }
@@ -264,6 +317,58 @@ impl RowCache {
         None
     }
 
+    /// How committed writes update the cache
+    pub fn write_policy(&self) -> RowCacheWritePolicy {
+        match self.write_policy.load(Ordering::Relaxed) {
+            p if p == RowCacheWritePolicy::InvalidateOnCommit as u8 => {
+                RowCacheWritePolicy::InvalidateOnCommit
+            }
+            _ => RowCacheWritePolicy::WriteThrough,
+        }
+    }
+
+    pub fn set_write_policy(&self, policy: RowCacheWritePolicy) {
+        self.write_policy.store(policy as u8, Ordering::Relaxed);
+    }
+
+    /// Record a committed write of `row_id` (`None` = deleted). Call it after
+    /// the write reached storage: write-through caches the new row,
+    /// invalidate-on-commit drops the cached one, a delete always drops it.
+    pub fn apply_write(&self, table_name: &str, row_id: RowId, row: Option<&Row>) {
+        let key = (table_hash(table_name), row_id);
+        let mut cache = self.cache.write();
+        self.write_epoch.fetch_add(1, Ordering::Release);
+        match row {
+            Some(row) if self.write_policy() == RowCacheWritePolicy::WriteThrough => {
+                self.write_through.fetch_add(1, Ordering::Relaxed);
+                self.insert_locked(&mut cache, key, Arc::new(row.clone()));
+            }
+            _ => {
+                self.write_invalidations.fetch_add(1, Ordering::Relaxed);
+                self.remove_locked(&mut cache, &key);
+            }
+        }
+    }
+
+    /// Token to pass to [`fill`](Self::fill): take it before reading the row
+    /// from storage
+    pub fn fill_epoch(&self) -> u64 {
+        self.write_epoch.load(Ordering::Acquire)
+    }
+
+    /// Cache a row read from storage, unless some write landed since
+    /// `epoch` (the row may predate it). Returns whether it was cached.
+    pub fn fill(&self, table_name: &str, row_id: RowId, row: Arc<Row>, epoch: u64) -> bool {
+        let key = (table_hash(table_name), row_id);
+        let mut cache = self.cache.write();
+        if self.write_epoch.load(Ordering::Acquire) != epoch {
+            self.stale_fills_rejected.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        self.insert_locked(&mut cache, key, row);
+        true
+    }
+
     /// Put a row into cache (takes ownership, wraps in Arc)
     pub fn put(&self, table_name: String, row_id: RowId, row: Row) {
         self.put_arc(table_name, row_id, Arc::new(row));
@@ -273,8 +378,7 @@ impl RowCache {
     pub fn put_arc(&self, table_name: String, row_id: RowId, row_arc: Arc<Row>) {
         let key = (table_hash(&table_name), row_id);
         let mut cache = self.cache.write();
-        cache.put(key, row_arc);
-        self.size.store(cache.len(), Ordering::Relaxed);
+        self.insert_locked(&mut cache, key, row_arc);
     }
 
     /// Put a row into cache using &str table name (avoids String allocation).
@@ -282,22 +386,149 @@ impl RowCache {
     pub fn put_ref(&self, table_name: &str, row_id: RowId, row: Row) {
         let key = (table_hash(table_name), row_id);
         let mut cache = self.cache.write();
-        cache.put(key, Arc::new(row));
+        self.insert_locked(&mut cache, key, Arc::new(row));
+    }
+
+    /// Insert with the cache write lock held: enforces the table's quota by
+    /// evicting that table's own least recent row, and counts evictions.
+    fn insert_locked(
+        &self,
+        cache: &mut LruCache<CacheKey, Arc<Row>>,
+        key: CacheKey,
+        row: Arc<Row>,
+    ) {
+        if !self.has_quotas.load(Ordering::Relaxed) {
+            if matches!(cache.push(key, row), Some((old, _)) if old != key) {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+            self.size.store(cache.len(), Ordering::Relaxed);
+            return;
+        }
+
+        let mut quotas = self.quotas.write();
+        if let Some(quota) = quotas.get_mut(&key.0) {
+            if !cache.contains(&key) {
+                if quota.used >= quota.limit {
+                    let victim = cache.iter().rev().map(|(k, _)| *k).find(|k| k.0 == key.0);
+                    if let Some(victim) = victim {
+                        cache.pop(&victim);
+                        quota.used -= 1;
+                        self.evictions.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                quota.used += 1;
+            }
+        }
+        if let Some((old, _)) = cache.push(key, row) {
+            if old != key {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+                if let Some(quota) = quotas.get_mut(&old.0) {
+                    quota.used = quota.used.saturating_sub(1);
+                }
+            }
+        }
+        self.size.store(cache.len(), Ordering::Relaxed);
+    }
+
+    /// Cap how many rows of `table_name` the cache may hold (`None` removes
+    /// the cap), so one chatty table can't evict every other table's rows.
+    /// Rows beyond a lowered limit are evicted immediately, oldest first.
+    pub fn set_table_quota(&self, table_name: &str, limit: Option<usize>) {
+        let thash = table_hash(table_name);
+        let mut cache = self.cache.write();
+        let mut quotas = self.quotas.write();
+        match limit {
+            Some(limit) => {
+                let limit = limit.max(1);
+                // Oldest first
+                let mut cached: Vec<CacheKey> = cache
+                    .iter()
+                    .rev()
+                    .map(|(k, _)| *k)
+                    .filter(|k| k.0 == thash)
+                    .collect();
+                let excess = cached.len().saturating_sub(limit);
+                for key in cached.drain(..excess) {
+                    cache.pop(&key);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                quotas.insert(
+                    thash,
+                    TableQuota {
+                        table: table_name.to_string(),
+                        limit,
+                        used: cached.len(),
+                    },
+                );
+            }
+            None => {
+                quotas.remove(&thash);
+            }
+        }
+        self.has_quotas.store(!quotas.is_empty(), Ordering::Relaxed);
+        self.size.store(cache.len(), Ordering::Relaxed);
+    }
+
+    /// Evict least recent rows until at most `max_rows` remain. Capacity is
+    /// unchanged, so the cache refills once the pressure is gone.
+    pub fn shrink_to(&self, max_rows: usize) {
+        let mut cache = self.cache.write();
+        if cache.len() <= max_rows {
+            return;
+        }
+        let mut quotas = self.quotas.write();
+        while cache.len() > max_rows {
+            let Some((old, _)) = cache.pop_lru() else {
+                break;
+            };
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(quota) = quotas.get_mut(&old.0) {
+                quota.used = quota.used.saturating_sub(1);
+            }
+        }
         self.size.store(cache.len(), Ordering::Relaxed);
     }
 
+    /// Tables with a quota and how much of it they use, by table name
+    pub fn table_quotas(&self) -> Vec<TableCacheQuota> {
+        let mut quotas: Vec<TableCacheQuota> = self
+            .quotas
+            .read()
+            .values()
+            .map(|q| TableCacheQuota {
+                table: q.table.clone(),
+                limit: q.limit,
+                used: q.used,
+            })
+            .collect();
+        quotas.sort_by(|a, b| a.table.cmp(&b.table));
+        quotas
+    }
+
     /// Invalidate a single row
     pub fn invalidate(&self, table_name: &str, row_id: RowId) {
         let key = (table_hash(table_name), row_id);
 
         let mut cache = self.cache.write();
-        cache.pop(&key);
+        self.write_epoch.fetch_add(1, Ordering::Release);
+        self.write_invalidations.fetch_add(1, Ordering::Relaxed);
+        self.remove_locked(&mut cache, &key);
+    }
+
+    fn remove_locked(&self, cache: &mut LruCache<CacheKey, Arc<Row>>, key: &CacheKey) {
+        if cache.pop(key).is_some() && self.has_quotas.load(Ordering::Relaxed) {
+            if let Some(quota) = self.quotas.write().get_mut(&key.0) {
+                quota.used = quota.used.saturating_sub(1);
+            }
+        }
         self.size.store(cache.len(), Ordering::Relaxed);
     }
 
     /// Invalidate all rows for a table
     pub fn invalidate_table(&self, table_name: &str) {
         let mut cache = self.cache.write();
+        self.write_epoch.fetch_add(1, Ordering::Release);
+        self.table_invalidations.fetch_add(1, Ordering::Relaxed);
         let thash = table_hash(table_name);
 
         let keys_to_remove: Vec<CacheKey> = cache
@@ -309,22 +540,46 @@ impl RowCache {
         for key in keys_to_remove {
             cache.pop(&key);
         }
+        if let Some(quota) = self.quotas.write().get_mut(&thash) {
+            quota.used = 0;
+        }
         self.size.store(cache.len(), Ordering::Relaxed);
 
         // Also clean up access_patterns for this table
         self.access_patterns.write().remove(table_name);
     }
 
+    /// Cached row_ids of a table, most recently used first
+    pub fn hot_row_ids(&self, table_name: &str) -> Vec<RowId> {
+        let thash = table_hash(table_name);
+        self.cache
+            .read()
+            .iter()
+            .filter(|(key, _)| key.0 == thash)
+            .map(|(key, _)| key.1)
+            .collect()
+    }
+
     /// Clear entire cache
     pub fn clear(&self) {
         let mut cache = self.cache.write();
         cache.clear();
+        for quota in self.quotas.write().values_mut() {
+            quota.used = 0;
+        }
 
         self.size.store(0, Ordering::Relaxed);
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.prefetch_triggered.store(0, Ordering::Relaxed);
         self.prefetch_useful.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+        self.write_through.store(0, Ordering::Relaxed);
+        self.write_invalidations.store(0, Ordering::Relaxed);
+        self.table_invalidations.store(0, Ordering::Relaxed);
+        self.stale_fills_rejected.store(0, Ordering::Relaxed);
+        // Readers that started before the clear must not refill
+        self.write_epoch.fetch_add(1, Ordering::Release);
 
         self.access_patterns.write().clear();
     }
@@ -349,6 +604,12 @@ impl RowCache {
             capacity: self.capacity,
             prefetch_triggered: self.prefetch_triggered.load(Ordering::Relaxed),
             prefetch_useful: self.prefetch_useful.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            write_policy: self.write_policy(),
+            write_through: self.write_through.load(Ordering::Relaxed),
+            write_invalidations: self.write_invalidations.load(Ordering::Relaxed),
+            table_invalidations: self.table_invalidations.load(Ordering::Relaxed),
+            stale_fills_rejected: self.stale_fills_rejected.load(Ordering::Relaxed),
         }
     }
 }
@@ -412,4 +673,90 @@ mod tests {
         assert!(cache.get("users", 1).is_none());
         assert!(cache.get("users", 4).is_some());
     }
+
+    #[test]
+    fn test_row_cache_hot_row_ids() {
+        let cache = RowCache::new(100);
+
+        for i in 1..=3 {
+            cache.put("users".to_string(), i, vec![Value::Integer(i as i64)]);
+        }
+        cache.put("orders".to_string(), 9, vec![Value::Integer(9)]);
+
+        assert_eq!(cache.hot_row_ids("users"), vec![3, 2, 1]);
+        assert_eq!(cache.hot_row_ids("orders"), vec![9]);
+        assert!(cache.hot_row_ids("items").is_empty());
+    }
+
+    #[test]
+    fn test_row_cache_table_quota() {
+        let cache = RowCache::new(10);
+        for i in 0..4 {
+            cache.put("quiet".to_string(), i, vec![Value::Integer(i as i64)]);
+        }
+
+        // A chatty table capped at 3 rows evicts only its own rows
+        cache.set_table_quota("chatty", Some(3));
+        for i in 0..100 {
+            cache.put("chatty".to_string(), i, vec![Value::Integer(i as i64)]);
+        }
+        assert_eq!(cache.hot_row_ids("quiet").len(), 4);
+        assert_eq!(cache.hot_row_ids("chatty"), vec![99, 98, 97]);
+        assert_eq!(cache.stats().evictions, 97);
+
+        let quotas = cache.table_quotas();
+        assert_eq!(quotas.len(), 1);
+        assert_eq!((quotas[0].limit, quotas[0].used), (3, 3));
+
+        // Lowering the quota trims immediately; invalidation frees quota
+        cache.set_table_quota("chatty", Some(2));
+        assert_eq!(cache.hot_row_ids("chatty"), vec![99, 98]);
+        cache.invalidate("chatty", 99);
+        assert_eq!(cache.table_quotas()[0].used, 1);
+
+        cache.set_table_quota("chatty", None);
+        assert!(cache.table_quotas().is_empty());
+    }
+
+    #[test]
+    fn test_row_cache_write_policy() {
+        let cache = RowCache::new(100);
+        let row = |v: i64| vec![Value::Integer(v)];
+        cache.put("users".to_string(), 1, row(1));
+
+        cache.apply_write("users", 1, Some(&row(2)));
+        assert_eq!(*cache.get("users", 1).unwrap(), row(2));
+
+        cache.set_write_policy(RowCacheWritePolicy::InvalidateOnCommit);
+        cache.apply_write("users", 1, Some(&row(3)));
+        assert!(cache.get("users", 1).is_none());
+        cache.put("users".to_string(), 2, row(2));
+        cache.apply_write("users", 2, None);
+        assert!(cache.get("users", 2).is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.write_policy, RowCacheWritePolicy::InvalidateOnCommit);
+        assert_eq!((stats.write_through, stats.write_invalidations), (1, 2));
+    }
+
+    #[test]
+    fn test_row_cache_rejects_fill_older_than_write() {
+        let cache = RowCache::new(100);
+        let row = |v: i64| Arc::new(vec![Value::Integer(v)]);
+
+        // A reader reads v=1 from storage, a writer commits v=2 meanwhile
+        let epoch = cache.fill_epoch();
+        cache.apply_write("users", 1, Some(&row(2)));
+        assert!(!cache.fill("users", 1, row(1), epoch));
+        assert_eq!(cache.get("users", 1).unwrap(), row(2));
+
+        let epoch = cache.fill_epoch();
+        cache.invalidate_table("users");
+        assert!(!cache.fill("users", 1, row(1), epoch));
+        assert!(cache.fill("users", 1, row(2), cache.fill_epoch()));
+
+        let stats = cache.stats();
+        assert_eq!(stats.stale_fills_rejected, 2);
+        assert_eq!(stats.table_invalidations, 1);
+    }
 }
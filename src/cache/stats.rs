@@ -0,0 +1,106 @@
+//! Unified Cache Statistics
+//!
+//! Each cache keeps its own counters (`RowCache::stats`, `CachedIndex::stats`,
+//! ...); this module folds them into one report per database so callers can
+//! compare hit rates and eviction pressure across caches in one place.
+
+use crate::config::RowCacheWritePolicy;
+
+/// Counters of one cache, summed over all of its instances
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheCounters {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to make room (invalidations don't count)
+    pub evictions: u64,
+    pub size: usize,
+    pub capacity: usize,
+}
+
+impl CacheCounters {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    /// Add another instance's counters (e.g. one index cache per column index)
+    pub fn merge(&mut self, other: CacheCounters) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+        self.size += other.size;
+        self.capacity += other.capacity;
+    }
+}
+
+/// Row-cache quota of one table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableCacheQuota {
+    pub table: String,
+    /// Maximum rows of this table kept in the row cache
+    pub limit: usize,
+    /// Rows of this table currently cached
+    pub used: usize,
+}
+
+/// How committed writes reached the row cache, and reads kept from
+/// caching rows older than a write
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RowCacheWriteStats {
+    pub policy: RowCacheWritePolicy,
+    /// Written rows stored in place (write-through)
+    pub write_through: u64,
+    /// Cached rows dropped because the row was written or deleted
+    pub invalidations: u64,
+    /// Whole-table drops (DROP TABLE, ALTER TABLE, ...)
+    pub table_invalidations: u64,
+    /// Rows read from storage and not cached because a write landed
+    /// while they were being read
+    pub stale_fills_rejected: u64,
+}
+
+/// Snapshot of every cache in a database
+#[derive(Debug, Default, Clone)]
+pub struct DatabaseCacheStats {
+    /// Hot rows (shared by all tables, see `row_cache_quotas`)
+    pub row_cache: CacheCounters,
+    /// Value → row_ids lookup caches of the column indexes
+    pub index_cache: CacheCounters,
+    /// Open SSTable handles and bloom filters of the LSM engine
+    pub sstable_cache: CacheCounters,
+    /// Keys known to be absent (LSM engine and columnar stores)
+    pub negative_cache: CacheCounters,
+    /// Tables with a row-cache quota, by name
+    pub row_cache_quotas: Vec<TableCacheQuota>,
+    /// Write policy and invalidation counters of the row cache
+    pub row_cache_writes: RowCacheWriteStats,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_counters_merge() {
+        let mut total = CacheCounters::default();
+        assert_eq!(total.hit_rate(), 0.0);
+
+        for hits in [3, 1] {
+            total.merge(CacheCounters {
+                hits,
+                misses: 1,
+                evictions: 2,
+                size: 10,
+                capacity: 100,
+            });
+        }
+        assert_eq!(total.hits, 4);
+        assert_eq!(total.evictions, 4);
+        assert_eq!(total.capacity, 200);
+        assert!((total.hit_rate() - 4.0 / 6.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,4 @@
 /// Table metadata catalog
 mod registry;
 
-pub use registry::TableRegistry;
+pub use registry::{PendingDrop, TableRegistry};
@@ -1,9 +1,9 @@
 /// Table registry for managing table metadata
 use crate::error::{Result, StorageError};
+use crate::storage::backend::{default_backend, StorageBackend};
 use crate::types::{IndexDef, TableSchema};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
@@ -25,6 +25,20 @@ struct RegistryMetadata {
     /// Avoids full table scan on startup for crash recovery.
     #[serde(default)]
     auto_increment_counters: HashMap<String, i64>,
+    /// Tables whose DROP has started but not finished: table_name -> drop
+    #[serde(default)]
+    pending_drops: HashMap<String, PendingDrop>,
+}
+
+/// A dropped table whose data may still be on disk
+///
+/// Recorded before a DROP TABLE tears anything down and cleared once it is
+/// done, so a drop interrupted half-way is finished on the next open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDrop {
+    pub schema: TableSchema,
+    /// Key prefix of the table's rows
+    pub table_id: u32,
 }
 
 /// Table registry for managing table schemas
@@ -39,21 +53,31 @@ pub struct TableRegistry {
     table_id_cache: parking_lot::RwLock<HashMap<String, u32>>,
     /// Persistence file path
     persist_path: PathBuf,
+    /// Storage backend holding `catalog.bin`
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl TableRegistry {
     /// Create a new table registry
     pub fn new<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
+        Self::with_backend(data_dir, default_backend())
+    }
+
+    /// Create a table registry persisted through `backend`
+    pub fn with_backend<P: AsRef<Path>>(
+        data_dir: P,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
         let persist_path = data_dir.as_ref().join("catalog.bin");
 
         // Create directory if it doesn't exist
         if let Some(parent) = persist_path.parent() {
-            fs::create_dir_all(parent).map_err(StorageError::Io)?;
+            backend.create_dir_all(parent).map_err(StorageError::Io)?;
         }
 
         // Try to load existing metadata
-        let metadata = if persist_path.exists() {
-            let data = fs::read(&persist_path).map_err(StorageError::Io)?;
+        let metadata = if backend.exists(&persist_path) {
+            let data = backend.read(&persist_path).map_err(StorageError::Io)?;
             let mut meta: RegistryMetadata = bincode::deserialize(&data)
                 .map_err(|e| StorageError::Serialization(e.to_string()))?;
 
@@ -61,6 +85,9 @@ impl TableRegistry {
             for schema in meta.tables.values_mut() {
                 schema.rebuild_column_map();
             }
+            for pending in meta.pending_drops.values_mut() {
+                pending.schema.rebuild_column_map();
+            }
 
             // Rebuild reverse id_to_name map if missing (backward compat)
             if meta.id_to_name.is_empty() && !meta.table_ids.is_empty() {
@@ -78,6 +105,7 @@ impl TableRegistry {
                 table_ids: HashMap::new(),
                 id_to_name: HashMap::new(),
                 auto_increment_counters: HashMap::new(),
+                pending_drops: HashMap::new(),
             }
         };
 
@@ -86,6 +114,7 @@ impl TableRegistry {
             schema_cache: parking_lot::RwLock::new(HashMap::new()),
             table_id_cache: parking_lot::RwLock::new(HashMap::new()),
             persist_path,
+            backend,
         })
     }
 
@@ -103,6 +132,13 @@ impl TableRegistry {
                 schema.name
             )));
         }
+        // Its files would be torn down by the unfinished drop
+        if meta.pending_drops.contains_key(&schema.name) {
+            return Err(StorageError::InvalidData(format!(
+                "Table '{}' is still being dropped",
+                schema.name
+            )));
+        }
 
         // Validate and register indexes
         for index in &schema.indexes {
@@ -180,6 +216,91 @@ impl TableRegistry {
         Ok(())
     }
 
+    /// Start dropping a table: take it out of the catalog and record the
+    /// drop as pending in the same persisted write
+    pub fn begin_drop(&self, table_name: &str) -> Result<PendingDrop> {
+        let mut meta = self
+            .metadata
+            .write()
+            .map_err(|e| StorageError::InvalidData(e.to_string()))?;
+        let schema = meta.tables.remove(table_name).ok_or_else(|| {
+            StorageError::InvalidData(format!("Table '{}' not found", table_name))
+        })?;
+        for index in &schema.indexes {
+            meta.index_map.remove(&index.name);
+        }
+        let table_id = meta.table_ids.remove(table_name).unwrap_or(0);
+        meta.id_to_name.remove(&table_id);
+        let pending = PendingDrop { schema, table_id };
+        meta.pending_drops
+            .insert(table_name.to_string(), pending.clone());
+        drop(meta);
+
+        self.schema_cache.write().remove(table_name);
+        self.table_id_cache.write().remove(table_name);
+
+        if let Err(e) = self.persist() {
+            self.restore_pending_drop(table_name)?;
+            return Err(e);
+        }
+        Ok(pending)
+    }
+
+    /// Forget a pending drop once the table's data is gone
+    pub fn finish_drop(&self, table_name: &str) -> Result<()> {
+        let mut meta = self
+            .metadata
+            .write()
+            .map_err(|e| StorageError::InvalidData(e.to_string()))?;
+        if meta.pending_drops.remove(table_name).is_none() {
+            return Ok(());
+        }
+        drop(meta);
+        self.persist()
+    }
+
+    /// The pending drop of `table_name`, if its drop has not finished
+    pub fn pending_drop(&self, table_name: &str) -> Option<PendingDrop> {
+        self.metadata
+            .read()
+            .ok()
+            .and_then(|meta| meta.pending_drops.get(table_name).cloned())
+    }
+
+    /// Drops recorded but not finished, e.g. by a crash mid-drop
+    pub fn pending_drops(&self) -> Vec<(String, PendingDrop)> {
+        self.metadata
+            .read()
+            .map(|meta| {
+                meta.pending_drops
+                    .iter()
+                    .map(|(name, pending)| (name.clone(), pending.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Put a drop whose record failed to persist back into the catalog
+    fn restore_pending_drop(&self, table_name: &str) -> Result<()> {
+        let mut meta = self
+            .metadata
+            .write()
+            .map_err(|e| StorageError::InvalidData(e.to_string()))?;
+        let Some(PendingDrop { schema, table_id }) = meta.pending_drops.remove(table_name) else {
+            return Ok(());
+        };
+        for index in &schema.indexes {
+            meta.index_map.insert(
+                index.name.clone(),
+                (index.table_name.clone(), index.column_name.clone()),
+            );
+        }
+        meta.table_ids.insert(table_name.to_string(), table_id);
+        meta.id_to_name.insert(table_id, table_name.to_string());
+        meta.tables.insert(table_name.to_string(), schema);
+        Ok(())
+    }
+
     /// Add a column to an existing table's schema (ALTER TABLE ADD COLUMN).
     /// The column is appended at the end. Existing rows get the default value
     /// (or NULL) when read — no rewrite of stored data is needed because the
@@ -190,6 +311,7 @@ impl TableRegistry {
         col_name: &str,
         col_type: crate::types::ColumnType,
         default_value: Option<&crate::types::Value>,
+        generated: Option<crate::types::GeneratedColumn>,
     ) -> Result<()> {
         let _ = default_value; // Reserved for future DEFAULT clause support.
         let mut meta = self
@@ -216,6 +338,7 @@ impl TableRegistry {
         // Store the DEFAULT so existing rows read back the default value
         // (rather than NULL) for the new column.
         col_def.default_value = default_value.cloned();
+        col_def.generated = generated;
         schema.columns.push(col_def);
         schema.rebuild_column_map();
 
@@ -229,6 +352,103 @@ impl TableRegistry {
         Ok(())
     }
 
+    /// Remove a column from a table's schema (ALTER TABLE DROP COLUMN).
+    /// Later columns shift down one position, and index definitions on the
+    /// column are dropped with it. Returns the column's former position; the
+    /// caller rewrites stored rows to the new layout.
+    pub fn drop_column(&self, table_name: &str, col_name: &str) -> Result<usize> {
+        let mut meta = self
+            .metadata
+            .write()
+            .map_err(|e| StorageError::InvalidData(e.to_string()))?;
+
+        let schema = meta.tables.get_mut(table_name).ok_or_else(|| {
+            StorageError::InvalidData(format!("Table '{}' not found", table_name))
+        })?;
+
+        let pos = schema.get_column_position(col_name).ok_or_else(|| {
+            StorageError::ColumnNotFound(format!("'{}' in table '{}'", col_name, table_name))
+        })?;
+        schema.columns.remove(pos);
+        for (i, col) in schema.columns.iter_mut().enumerate() {
+            col.position = i;
+        }
+        let dropped: Vec<String> = schema
+            .indexes
+            .iter()
+            .filter(|idx| idx.column_name == col_name)
+            .map(|idx| idx.name.clone())
+            .collect();
+        schema.indexes.retain(|idx| idx.column_name != col_name);
+        schema.rebuild_column_map();
+        for name in &dropped {
+            meta.index_map.remove(name);
+        }
+
+        drop(meta);
+
+        self.schema_cache.write().remove(table_name);
+
+        self.persist()?;
+
+        Ok(pos)
+    }
+
+    /// Rename a column (ALTER TABLE RENAME COLUMN). The column keeps its
+    /// position, so stored rows are unaffected; index definitions, the
+    /// primary key and the TIMESERIES column follow the new name.
+    pub fn rename_column(&self, table_name: &str, from: &str, to: &str) -> Result<()> {
+        let mut meta = self
+            .metadata
+            .write()
+            .map_err(|e| StorageError::InvalidData(e.to_string()))?;
+
+        let schema = meta.tables.get_mut(table_name).ok_or_else(|| {
+            StorageError::InvalidData(format!("Table '{}' not found", table_name))
+        })?;
+
+        let pos = schema.get_column_position(from).ok_or_else(|| {
+            StorageError::ColumnNotFound(format!("'{}' in table '{}'", from, table_name))
+        })?;
+        if schema.get_column_position(to).is_some() {
+            return Err(StorageError::InvalidData(format!(
+                "Column '{}' already exists in table '{}'",
+                to, table_name
+            )));
+        }
+
+        schema.columns[pos].name = to.to_string();
+        let renamed: Vec<String> = schema
+            .indexes
+            .iter_mut()
+            .filter(|idx| idx.column_name == from)
+            .map(|idx| {
+                idx.column_name = to.to_string();
+                idx.name.clone()
+            })
+            .collect();
+        if schema.primary_key_column.as_deref() == Some(from) {
+            schema.primary_key_column = Some(to.to_string());
+        }
+        if schema.timeseries_column.as_deref() == Some(from) {
+            schema.timeseries_column = Some(to.to_string());
+        }
+        schema.rebuild_column_map();
+        for name in &renamed {
+            if let Some((_, column)) = meta.index_map.get_mut(name) {
+                *column = to.to_string();
+            }
+        }
+
+        drop(meta);
+
+        self.schema_cache.write().remove(table_name);
+
+        self.persist()?;
+
+        Ok(())
+    }
+
     /// Get table schema (returns Arc clone — O(1) refcount bump via schema cache)
     ///
     /// On first access, the schema is cloned into an Arc and cached.
@@ -500,15 +720,9 @@ impl TableRegistry {
         let data =
             bincode::serialize(&*meta).map_err(|e| StorageError::Serialization(e.to_string()))?;
 
-        let tmp_path = self.persist_path.with_extension("bin.tmp");
-        {
-            let mut f = std::fs::File::create(&tmp_path).map_err(StorageError::Io)?;
-            std::io::Write::write_all(&mut f, &data).map_err(StorageError::Io)?;
-            f.sync_data().map_err(StorageError::Io)?;
-        }
-        std::fs::rename(&tmp_path, &self.persist_path).map_err(StorageError::Io)?;
-
-        Ok(())
+        self.backend
+            .write_atomic(&self.persist_path, &data)
+            .map_err(StorageError::Io)
     }
 }
 
@@ -161,6 +161,20 @@ impl DurabilityLevel {
     }
 }
 
+/// Per-write override of `DurabilityLevel`
+///
+/// Set per statement with a SQL hint (`INSERT /*+ ASYNC_COMMIT */ INTO ...`,
+/// `/*+ SYNC_COMMIT */`) or per batch with `WriteBatch::commit_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitMode {
+    /// fsync the WAL before the write returns, whatever the global level
+    Sync,
+    /// Hand the WAL record to the OS and return without fsync (no group
+    /// commit wait). It becomes durable with the next fsync: a later
+    /// synchronous write, the periodic flusher, a checkpoint or close.
+    Async,
+}
+
 /// WAL 配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WALConfig {
@@ -170,7 +184,11 @@ pub struct WALConfig {
     /// WAL 文件目录（相对于数据库目录）
     pub wal_dir: String,
 
-    /// 单个 WAL 文件的最大大小（字节）
+    /// WAL 总大小上限（字节）
+    ///
+    /// Hard bound enforced by the auto-checkpoint thread: once the WAL
+    /// reaches it, a checkpoint runs without waiting for
+    /// `AutoCheckpointConfig::min_interval_secs`.
     pub max_wal_size: u64,
 
     /// 是否启用 WAL 压缩
@@ -358,7 +376,7 @@ pub struct DBConfig {
     /// WAL 配置
     pub wal_config: WALConfig,
 
-    /// 分区数量
+    /// 分区数量（WAL 分区，同时也是单行写入的暂存分区数）
     pub num_partitions: u8,
 
     /// LSM 树配置
@@ -372,6 +390,20 @@ pub struct DBConfig {
     /// - 1000 rows ≈ 1MB (memory-constrained)
     pub row_cache_size: Option<usize>,
 
+    /// Per-table row cache quotas (table name → max cached rows)
+    ///
+    /// A table with a quota evicts its own least recently cached rows once
+    /// it reaches the limit, so a chatty table can't thrash the rows of the
+    /// others. Tables without a quota share the rest of the cache.
+    /// Can also be changed at runtime with `Database::set_row_cache_quota`.
+    #[serde(default)]
+    pub row_cache_quotas: std::collections::HashMap<String, usize>,
+
+    /// How INSERT/UPDATE/DELETE and transaction commits update the row
+    /// cache (default: write-through)
+    #[serde(default)]
+    pub row_cache_write_policy: RowCacheWritePolicy,
+
     /// PK lookup cache capacity (number of entries per table)
     ///
     /// This bounds the in-memory PK→RowId mapping. When exceeded, least-recently-used
@@ -421,6 +453,7 @@ pub struct DBConfig {
     /// Automatically triggers checkpoint to clean up WAL files when:
     /// - WAL size exceeds threshold, OR
     /// - Time interval reached
+    /// - WAL reaches `WALConfig::max_wal_size` (immediately)
     ///
     /// None = Disabled (user must manually call checkpoint())
     /// Some(...) = Enabled with automatic cleanup
@@ -428,6 +461,128 @@ pub struct DBConfig {
 
     /// Columnar store configuration (for TimeSeries tables)
     pub columnar_config: crate::storage::columnar::config::ColumnarConfig,
+
+    /// Custom storage backend (VFS)
+    ///
+    /// None = host filesystem. Not serialized: a backend is a runtime object,
+    /// so a config loaded from disk always starts with the default.
+    /// See [`crate::storage::backend`] for which files are routed through it.
+    #[serde(skip)]
+    pub storage_backend: Option<std::sync::Arc<dyn crate::storage::backend::StorageBackend>>,
+
+    /// Reorder buffer in front of the timestamp index
+    ///
+    /// Slightly out-of-order records are held back and merged into the
+    /// timestamp B+Tree in sorted order instead of splitting pages at random.
+    /// None = entries go straight to the index.
+    #[serde(default = "default_timestamp_reorder")]
+    pub timestamp_reorder: Option<TimestampReorderConfig>,
+
+    /// Persist cache state across restarts
+    ///
+    /// At shutdown the hot row keys, pinned DiskANN nodes and decoded column
+    /// segments are written to `cache_state.bin`; the next open pre-loads them
+    /// so the first queries after a reboot hit warm caches.
+    /// Default: false (open stays as fast as possible)
+    #[serde(default)]
+    pub persist_cache_state: bool,
+
+    /// Hard memory cap with graceful degradation
+    ///
+    /// When process memory approaches the cap the database sheds caches,
+    /// flushes memtables and narrows DiskANN searches; at the cap, statements
+    /// marked `/*+ BEST_EFFORT */` fail with `StorageError::MemoryLimitExceeded`.
+    /// Needs a way to measure process memory (Linux, or the `jemalloc`
+    /// feature elsewhere); `validate` rejects it otherwise.
+    /// None = no cap (default)
+    #[serde(default)]
+    pub memory_limit: Option<MemoryLimitConfig>,
+
+    /// Flash-friendly write mode (eMMC / SD cards / raw NAND)
+    ///
+    /// Coalesced WAL syncs are padded to flash page boundaries so appends
+    /// never reprogram a half-written page, and WAL / SSTable writers issue
+    /// erase-block sized writes. Per-commit syncs (`Synchronous`,
+    /// `SYNC_COMMIT`) are not padded, so pair this with `Periodic` or
+    /// `GroupCommit` durability. See [`DBConfig::for_flash`].
+    /// None = plain writes (default)
+    #[serde(default)]
+    pub flash_write: Option<FlashWriteConfig>,
+
+    /// Fsync policy per component (WAL, SSTables, blobs, index files)
+    ///
+    /// Lets a device on a UPS relax fsync for blobs or index files while
+    /// the WAL stays fully synced. Applies to files routed through the
+    /// storage backend (see [`crate::storage::sync_policy`]).
+    /// Default: every component `Always`
+    #[serde(default)]
+    pub fsync: FsyncConfig,
+
+    /// Streaming execution batch and buffer sizes
+    ///
+    /// Smaller values lower peak memory of large scans at the cost of more
+    /// storage round trips. Sessions can override both (see
+    /// [`crate::session::SessionSettings`]).
+    #[serde(default)]
+    pub streaming: StreamingConfig,
+
+    /// Most bytes one statement's joins, sorts, GROUP BY and DISTINCT may
+    /// buffer
+    ///
+    /// A statement passing it fails with
+    /// `StorageError::QueryMemoryLimitExceeded` instead of growing until it
+    /// evicts every cache. Sessions can override it (see
+    /// [`crate::session::SessionSettings`]). None = unlimited (default)
+    #[serde(default)]
+    pub query_memory_limit: Option<usize>,
+
+    /// Share full scans between concurrent queries
+    ///
+    /// A full scan of a table that starts while another one is in flight
+    /// attaches to it and receives the same decoded batches instead of
+    /// reading the table again (see [`crate::database::shared_scan`]).
+    /// Default: true
+    #[serde(default = "default_shared_scans")]
+    pub shared_scans: bool,
+
+    /// Directory SQL `COPY ... FROM` / `COPY ... TO` may read and write
+    ///
+    /// COPY paths are resolved against it, and one that leads outside it
+    /// (an absolute path elsewhere, `..`, or a symlink) fails with
+    /// `StorageError::PermissionDenied`. `import_csv` / `export_csv` called
+    /// from Rust are not restricted.
+    /// None = COPY is refused, so SQL callers can't touch host files (default)
+    #[serde(default)]
+    pub copy_dir: Option<std::path::PathBuf>,
+}
+
+fn default_shared_scans() -> bool {
+    true
+}
+
+fn default_timestamp_reorder() -> Option<TimestampReorderConfig> {
+    Some(TimestampReorderConfig::default())
+}
+
+/// Timestamp index reorder buffer configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampReorderConfig {
+    /// How far (µs) a record may lag the newest timestamp and still be
+    /// merged in order. Default: 5 seconds
+    pub lateness_window_micros: u64,
+
+    /// Maximum buffered entries; the oldest are released early beyond this.
+    /// Default: 65536 (~1MB)
+    pub max_buffered_entries: usize,
+}
+
+impl Default for TimestampReorderConfig {
+    fn default() -> Self {
+        Self {
+            lateness_window_micros: 5_000_000, // 5s
+            max_buffered_entries: 65_536,
+        }
+    }
 }
 
 /// Auto-checkpoint trigger configuration
@@ -467,6 +622,157 @@ impl AutoCheckpointConfig {
     }
 }
 
+/// Hard memory cap configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MemoryLimitConfig {
+    /// Hard cap on process memory (bytes), e.g. 35MB on a robot controller
+    pub max_bytes: usize,
+
+    /// Fraction of `max_bytes` at which degradation starts.
+    /// Default: 0.8
+    pub pressure_ratio: f64,
+
+    /// DiskANN search list size used while under pressure.
+    /// Default: 32
+    pub degraded_search_width: usize,
+
+    /// Minimum time between two memory usage samples (milliseconds).
+    /// Default: 100
+    pub sample_interval_ms: u64,
+}
+
+impl MemoryLimitConfig {
+    /// Cap at `max_bytes` with default thresholds
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            pressure_ratio: 0.8,
+            degraded_search_width: 32,
+            sample_interval_ms: 100,
+        }
+    }
+}
+
+/// Flash write geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlashWriteConfig {
+    /// Flash page size (bytes); every coalesced WAL fsync ends on a page
+    /// boundary.
+    /// Default: 4KB
+    pub page_bytes: usize,
+
+    /// Erase block size (bytes); WAL and SSTable writers buffer this much
+    /// before issuing a write. Must be a multiple of `page_bytes`.
+    /// Default: 128KB
+    pub erase_block_bytes: usize,
+}
+
+impl Default for FlashWriteConfig {
+    fn default() -> Self {
+        Self {
+            page_bytes: 4 * 1024,
+            erase_block_bytes: 128 * 1024,
+        }
+    }
+}
+
+/// How committed writes reach the row cache
+///
+/// Either way a write lands in storage first, and rows read from storage
+/// while a write was in flight are not cached, so readers never see a row
+/// older than the last committed write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RowCacheWritePolicy {
+    /// Store the written row in the cache (default): re-reads of freshly
+    /// written rows hit
+    #[default]
+    WriteThrough,
+
+    /// Drop the cached row; the next read loads it from storage. Keeps
+    /// write-heavy tables from churning the cache with rows nobody reads.
+    InvalidateOnCommit,
+}
+
+/// Whether fsyncs requested for a component's files reach the device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FsyncPolicy {
+    /// Every fsync the engine issues goes through (default)
+    #[default]
+    Always,
+
+    /// Fsyncs are deferred and issued in the background, at most
+    /// `interval_ms` after they were requested. A power cut can lose the
+    /// writes of that window.
+    Interval { interval_ms: u64 },
+
+    /// Never fsync; the OS writes pages back on its own. Only for storage
+    /// that cannot lose power (battery or UPS backed).
+    Never,
+}
+
+/// Fsync policy per storage component
+///
+/// Each policy applies to the files of one directory of the database:
+/// `wal/` (on top of `WALConfig::durability_level`, which decides when the
+/// WAL asks for an fsync), the LSM SSTables and columnar segments, the blob
+/// files of large values, and `indexes/`. Catalog and manifest files
+/// outside those directories are always synced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsyncConfig {
+    /// WAL partitions. Relaxing this gives up crash durability of commits.
+    pub wal: FsyncPolicy,
+
+    /// SSTables and columnar segments written by flushes and compactions
+    pub sstable: FsyncPolicy,
+
+    /// Blob files holding large values
+    pub blob: FsyncPolicy,
+
+    /// Index files (B-Tree, full-text, DiskANN, i-Octree)
+    pub index: FsyncPolicy,
+}
+
+impl FsyncConfig {
+    /// Whether every component syncs on every request
+    pub fn is_always(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Streaming query execution sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamingConfig {
+    /// Rows fetched from storage per batch by streaming index scans
+    /// Default: 1000
+    pub batch_size: usize,
+
+    /// Most rows preallocated when a streaming result is materialized
+    ///
+    /// The optimizer's row estimate is used instead when it is smaller, so
+    /// point and narrow range queries never reserve the full amount.
+    /// Default: 1024
+    pub materialize_capacity: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            materialize_capacity: 1024,
+        }
+    }
+}
+
+impl StreamingConfig {
+    /// Small batches and buffers for memory-constrained devices
+    pub fn for_edge() -> Self {
+        Self {
+            batch_size: 256,
+            materialize_capacity: 256,
+        }
+    }
+}
+
 impl Default for DBConfig {
     fn default() -> Self {
         Self {
@@ -481,6 +787,18 @@ impl Default for DBConfig {
             query_timeout_secs: Some(30), // 30-second timeout by default
             auto_checkpoint: Some(AutoCheckpointConfig::default()), // ✅ 默认启用自动 checkpoint
             columnar_config: crate::storage::columnar::config::ColumnarConfig::default(),
+            storage_backend: None,
+            timestamp_reorder: default_timestamp_reorder(),
+            persist_cache_state: false,
+            row_cache_quotas: std::collections::HashMap::new(),
+            row_cache_write_policy: RowCacheWritePolicy::default(),
+            memory_limit: None,
+            flash_write: None,
+            fsync: FsyncConfig::default(),
+            streaming: StreamingConfig::default(),
+            query_memory_limit: None,
+            shared_scans: true,
+            copy_dir: None,
         }
     }
 }
@@ -519,6 +837,7 @@ impl DBConfig {
     /// - row_cache_size: 500 - minimal cache (~500KB)
     /// - auto_checkpoint: embedded() - infrequent wakeups
     /// - index_update_strategy: BatchOnly - highest write throughput
+    /// - streaming: 256-row scan batches and result buffers
     pub fn for_edge() -> Self {
         Self {
             wal_config: WALConfig {
@@ -555,6 +874,7 @@ impl DBConfig {
                 enable_timestamp_sort: true,
                 bloom_filter_bits_per_key: 8,
             },
+            streaming: StreamingConfig::for_edge(),
             ..Default::default()
         }
     }
@@ -610,6 +930,7 @@ impl DBConfig {
     /// - Auto-checkpoint: 4MB WAL trigger, 30s interval
     /// - Index strategy: BatchOnly (highest throughput)
     /// - Compression: disabled (CPU > storage for edge)
+    /// - Streaming: 256-row scan batches and result buffers
     pub fn for_embodied() -> Self {
         Self {
             wal_config: WALConfig {
@@ -636,10 +957,42 @@ impl DBConfig {
             }),
             index_update_strategy: IndexUpdateStrategy::BatchOnly,
             columnar_config: crate::storage::columnar::config::ColumnarConfig::for_edge(),
+            streaming: StreamingConfig::for_edge(),
             ..Default::default()
         }
     }
 
+    /// Flash-friendly preset for devices logging telemetry 24/7 to eMMC/SD
+    ///
+    /// Starts from [`DBConfig::for_edge`] and trades a wider loss window
+    /// for fewer, larger flash programs:
+    /// - flash_write: 4KB pages, 128KB erase blocks (page-aligned WAL syncs)
+    /// - WAL: Periodic 1s, so small commits coalesce into one fsync
+    /// - LSM: 4MB memtable, so SSTables are written in fewer, larger files;
+    ///   range scans read 16 blocks (256KB) ahead
+    /// - auto_checkpoint: 8MB / 5 min, so the WAL is rewritten less often
+    pub fn for_flash() -> Self {
+        let edge = Self::for_edge();
+        Self {
+            wal_config: WALConfig {
+                durability_level: DurabilityLevel::Periodic { interval_ms: 1000 },
+                max_wal_size: 16 * 1024 * 1024,
+                ..edge.wal_config.clone()
+            },
+            lsm_config: LSMConfig {
+                memtable_size_limit: 4 * 1024 * 1024,
+                scan_readahead_blocks: Some(16),
+                ..edge.lsm_config.clone()
+            },
+            auto_checkpoint: Some(AutoCheckpointConfig {
+                max_wal_size_bytes: 8 * 1024 * 1024,
+                min_interval_secs: 300,
+            }),
+            flash_write: Some(FlashWriteConfig::default()),
+            ..edge
+        }
+    }
+
     pub fn validate(&self) -> crate::Result<()> {
         if self.num_partitions == 0 {
             return Err(crate::StorageError::InvalidData(
@@ -656,11 +1009,71 @@ impl DBConfig {
                 "row_cache_size must be > 0 if set".into(),
             ));
         }
+        if self
+            .row_cache_quotas
+            .values()
+            .any(|&max_rows| max_rows == 0)
+        {
+            return Err(crate::StorageError::InvalidData(
+                "row_cache_quotas entries must be > 0".into(),
+            ));
+        }
         if self.query_timeout_secs == Some(0) {
             return Err(crate::StorageError::InvalidData(
                 "query_timeout_secs must be > 0 if set".into(),
             ));
         }
+        if self
+            .timestamp_reorder
+            .is_some_and(|r| r.max_buffered_entries == 0)
+        {
+            return Err(crate::StorageError::InvalidData(
+                "timestamp_reorder.max_buffered_entries must be > 0".into(),
+            ));
+        }
+        if self.streaming.batch_size == 0 || self.streaming.materialize_capacity == 0 {
+            return Err(crate::StorageError::InvalidData(
+                "streaming.batch_size and materialize_capacity must be > 0".into(),
+            ));
+        }
+        if let Some(flash) = &self.flash_write {
+            if flash.page_bytes < 512
+                || flash.erase_block_bytes < flash.page_bytes
+                || flash.erase_block_bytes % flash.page_bytes != 0
+            {
+                return Err(crate::StorageError::InvalidData(
+                    "flash_write: page_bytes must be >= 512 and divide erase_block_bytes".into(),
+                ));
+            }
+        }
+        let fsync = &self.fsync;
+        if [fsync.wal, fsync.sstable, fsync.blob, fsync.index]
+            .contains(&FsyncPolicy::Interval { interval_ms: 0 })
+        {
+            return Err(crate::StorageError::InvalidData(
+                "fsync: Interval policies need interval_ms > 0".into(),
+            ));
+        }
+        if let Some(limit) = &self.memory_limit {
+            if limit.max_bytes == 0 || limit.degraded_search_width == 0 {
+                return Err(crate::StorageError::InvalidData(
+                    "memory_limit.max_bytes and degraded_search_width must be > 0".into(),
+                ));
+            }
+            if !(limit.pressure_ratio > 0.0 && limit.pressure_ratio <= 1.0) {
+                return Err(crate::StorageError::InvalidData(
+                    "memory_limit.pressure_ratio must be in (0, 1]".into(),
+                ));
+            }
+            // Without a usage reading the cap would silently never trigger
+            if crate::database::memory_budget::process_memory_usage().is_none() {
+                return Err(crate::StorageError::InvalidData(
+                    "memory_limit: process memory usage cannot be measured on this \
+                     platform (needs Linux /proc or the jemalloc feature)"
+                        .into(),
+                ));
+            }
+        }
         Ok(())
     }
 }
@@ -705,6 +1118,17 @@ pub struct LSMConfig {
     /// dropped during compaction. 0 = drop all tombstones immediately.
     /// None = use internal default (86400 = 24h).
     pub tombstone_ttl_secs: Option<u64>,
+
+    /// SSTable blocks a range scan reads ahead of the block it is decoding,
+    /// so cold scans keep the device busy (None = storage default 4; 0 reads
+    /// each block on demand)
+    #[serde(default)]
+    pub scan_readahead_blocks: Option<usize>,
+
+    /// Decompress read-ahead blocks on a background thread during range
+    /// scans (None = storage default: true)
+    #[serde(default)]
+    pub scan_prefetch: Option<bool>,
 }
 
 impl Default for LSMConfig {
@@ -719,6 +1143,8 @@ impl Default for LSMConfig {
             enable_compression: None,
             compression_algorithm: None,
             tombstone_ttl_secs: None,
+            scan_readahead_blocks: None,
+            scan_prefetch: None,
         }
     }
 }
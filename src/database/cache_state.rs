@@ -0,0 +1,123 @@
+//! Cache State Persistence (warm restarts)
+//!
+//! Snapshots which rows, DiskANN graph nodes and column segments are hot at
+//! shutdown and pre-loads them at open, so the first queries after a reboot
+//! hit warm caches instead of all missing at once.
+//!
+//! Only keys are saved, never data: warm-up re-reads everything through the
+//! normal read paths, so a stale or truncated state file can't serve stale rows.
+
+use crate::database::core::MoteDB;
+use crate::storage::backend;
+use crate::types::RowId;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// State file in the database directory
+pub(crate) const CACHE_STATE_FILE: &str = "cache_state.bin";
+
+/// Hot keys per cache, each list most recently used first
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheState {
+    /// table → row_ids in the row cache
+    rows: Vec<(String, Vec<RowId>)>,
+    /// vector index → pinned graph nodes
+    vector_nodes: Vec<(String, Vec<RowId>)>,
+    /// table → column indexes decoded in the segment cache
+    hot_columns: Vec<(String, Vec<usize>)>,
+}
+
+/// What a cache warm-up pre-loaded
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheWarmupStats {
+    pub rows: usize,
+    pub vector_nodes: usize,
+    pub columns: usize,
+}
+
+impl MoteDB {
+    /// Save the hot keys of the row cache, DiskANN hot caches and segment
+    /// column caches to `cache_state.bin`
+    pub fn save_cache_state(&self) -> Result<()> {
+        let mut state = CacheState::default();
+        for table in self.table_registry.list_tables()? {
+            let row_ids = self.row_cache.hot_row_ids(&table);
+            if !row_ids.is_empty() {
+                state.rows.push((table.clone(), row_ids));
+            }
+            if let Some(store) = self.col_segment_stores.get(&table) {
+                let cols = store.hot_columns();
+                if !cols.is_empty() {
+                    state.hot_columns.push((table.clone(), cols));
+                }
+            }
+        }
+        for entry in self.vector_indexes.iter() {
+            let nodes = entry.value().read().pinned_nodes();
+            if !nodes.is_empty() {
+                state.vector_nodes.push((entry.key().clone(), nodes));
+            }
+        }
+
+        // Write-then-rename so a crash mid-save never leaves a torn file
+        let path = self.path.join(CACHE_STATE_FILE);
+        backend::backend_for(&path).write_atomic(&path, &bincode::serialize(&state)?)?;
+        Ok(())
+    }
+
+    /// Pre-load caches from `cache_state.bin`. A missing or unreadable file
+    /// is not an error (nothing is warmed); tables and indexes that no longer
+    /// exist are skipped.
+    pub fn warm_caches(&self) -> Result<CacheWarmupStats> {
+        let mut stats = CacheWarmupStats::default();
+        let path = self.path.join(CACHE_STATE_FILE);
+        let bytes = match backend::backend_for(&path).read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(stats),
+            Err(e) => return Err(e.into()),
+        };
+        let state: CacheState = match bincode::deserialize(&bytes) {
+            Ok(state) => state,
+            Err(e) => {
+                warn_log!("[warm_caches] Ignoring corrupt {}: {}", CACHE_STATE_FILE, e);
+                return Ok(stats);
+            }
+        };
+
+        for (table, cols) in &state.hot_columns {
+            if let Some(store) = self.col_segment_stores.get(table) {
+                store.warm_columns(cols);
+                stats.columns += cols.len();
+            }
+        }
+
+        for (index_name, nodes) in &state.vector_nodes {
+            if let Some(index) = self.vector_indexes.get(index_name) {
+                index.read().pin_nodes(nodes);
+                stats.vector_nodes += nodes.len();
+            }
+        }
+
+        // Re-read rows oldest first so the LRU order matches the saved one,
+        // stopping once the row cache would start evicting warmed rows
+        let mut budget = self.row_cache.stats().capacity;
+        for (table, row_ids) in &state.rows {
+            let Ok(schema) = self.table_registry.get_table(table) else {
+                continue;
+            };
+            let take = row_ids.len().min(budget);
+            for &row_id in row_ids[..take].iter().rev() {
+                if self
+                    .get_table_row_with_schema(table, row_id, &schema)?
+                    .is_some()
+                {
+                    stats.rows += 1;
+                }
+            }
+            budget -= take;
+        }
+
+        debug_log!("[warm_caches] Pre-loaded {:?}", stats);
+        Ok(stats)
+    }
+}
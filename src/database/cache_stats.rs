@@ -0,0 +1,75 @@
+//! Unified Cache Statistics and Row-Cache Quotas
+//!
+//! Folds the row cache, column index caches, LSM SSTable cache and negative
+//! lookup caches into one [`DatabaseCacheStats`] report.
+
+use crate::cache::{CacheCounters, DatabaseCacheStats, NegativeCacheStats, RowCacheWriteStats};
+use crate::database::core::MoteDB;
+use crate::{Result, StorageError};
+
+fn negative_counters(stats: NegativeCacheStats) -> CacheCounters {
+    CacheCounters {
+        hits: stats.hits,
+        misses: stats.misses,
+        evictions: stats.evictions,
+        size: stats.size,
+        capacity: stats.capacity,
+    }
+}
+
+impl MoteDB {
+    /// Hit/miss/eviction counters of every cache, summed per cache kind
+    pub fn cache_stats(&self) -> DatabaseCacheStats {
+        let row = self.row_cache.stats();
+        let mut stats = DatabaseCacheStats {
+            row_cache: CacheCounters {
+                hits: row.hits,
+                misses: row.misses,
+                evictions: row.evictions,
+                size: row.size,
+                capacity: row.capacity,
+            },
+            sstable_cache: self.lsm_engine.sstable_cache_stats(),
+            negative_cache: negative_counters(self.lsm_engine.negative_cache_stats()),
+            row_cache_quotas: self.row_cache.table_quotas(),
+            row_cache_writes: RowCacheWriteStats {
+                policy: row.write_policy,
+                write_through: row.write_through,
+                invalidations: row.write_invalidations,
+                table_invalidations: row.table_invalidations,
+                stale_fills_rejected: row.stale_fills_rejected,
+            },
+            ..Default::default()
+        };
+
+        for entry in self.column_indexes.iter() {
+            let index = entry.value().cache_stats();
+            stats.index_cache.merge(CacheCounters {
+                hits: index.hits,
+                misses: index.misses,
+                evictions: index.evictions,
+                size: index.size,
+                capacity: index.capacity,
+            });
+        }
+        for entry in self.col_segment_stores.iter() {
+            stats
+                .negative_cache
+                .merge(negative_counters(entry.value().negative_cache_stats()));
+        }
+        stats
+    }
+
+    /// Cap the rows of `table_name` kept in the shared row cache (`None`
+    /// removes the cap). A capped table evicts its own least recent rows
+    /// instead of other tables' rows.
+    pub fn set_row_cache_quota(&self, table_name: &str, max_rows: Option<usize>) -> Result<()> {
+        if max_rows == Some(0) {
+            return Err(StorageError::InvalidArgument(
+                "row cache quota must be > 0".into(),
+            ));
+        }
+        self.row_cache.set_table_quota(table_name, max_rows);
+        Ok(())
+    }
+}
@@ -0,0 +1,482 @@
+//! Vector collections: `(id, vector, metadata)` records without SQL
+//!
+//! A collection is an ordinary table with an `id TEXT PRIMARY KEY` column,
+//! an `embedding VECTOR(n)` column under a DiskANN index, and one column per
+//! declared metadata field, so it can still be queried with SQL. Writes go
+//! through a [`WriteBatch`](super::WriteBatch); queries widen the vector
+//! search until enough candidates pass the metadata filter, and rank the
+//! matching records exactly when the index cannot surface enough of them.
+//!
+//! ```ignore
+//! let docs = db.create_collection(
+//!     "docs",
+//!     CollectionConfig::new(384)
+//!         .metric(DistanceKind::Cosine)
+//!         .metadata_field("source", ColumnType::Text),
+//! )?;
+//! docs.upsert(vec![CollectionRecord::new("a-17", embedding).with("source", Value::text_from("wiki"))])?;
+//! let hits = docs.query(&query, 5, &MetadataFilter::from([("source".into(), Value::text_from("wiki"))]))?;
+//! ```
+
+use std::collections::HashMap;
+
+use super::core::MoteDB;
+use super::index_metadata::{IndexMetadata, IndexType};
+use crate::distance::DistanceKind;
+use crate::types::{ColumnDef, ColumnType, RowId, TableSchema, Value};
+use crate::{Result, StorageError};
+
+/// Name of the key column of a collection table
+pub const COLLECTION_ID_COLUMN: &str = "id";
+/// Name of the vector column of a collection table
+pub const COLLECTION_VECTOR_COLUMN: &str = "embedding";
+
+/// Metadata equality filter: every field must equal its value
+pub type MetadataFilter = HashMap<String, Value>;
+
+/// Shape of a new collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionConfig {
+    /// Vector dimension
+    pub dimension: usize,
+    /// Distance of the vector index (default Euclidean)
+    pub metric: DistanceKind,
+    /// Metadata fields, each stored as a nullable column
+    pub metadata: Vec<(String, ColumnType)>,
+}
+
+impl CollectionConfig {
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension,
+            metric: DistanceKind::Euclidean,
+            metadata: Vec::new(),
+        }
+    }
+
+    pub fn metric(mut self, metric: DistanceKind) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn metadata_field(mut self, name: impl Into<String>, col_type: ColumnType) -> Self {
+        self.metadata.push((name.into(), col_type));
+        self
+    }
+}
+
+/// One record written by [`Collection::upsert`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionRecord {
+    pub id: String,
+    pub vector: Vec<f32>,
+    /// Values of declared metadata fields; missing fields are NULL
+    pub metadata: HashMap<String, Value>,
+}
+
+impl CollectionRecord {
+    pub fn new(id: impl Into<String>, vector: Vec<f32>) -> Self {
+        Self {
+            id: id.into(),
+            vector,
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Set one metadata field
+    pub fn with(mut self, field: impl Into<String>, value: Value) -> Self {
+        self.metadata.insert(field.into(), value);
+        self
+    }
+}
+
+/// One result of [`Collection::query`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionHit {
+    pub id: String,
+    /// L2 distance, or cosine distance for a cosine collection
+    pub distance: f32,
+    /// Non-NULL metadata fields
+    pub metadata: HashMap<String, Value>,
+}
+
+/// Handle to a collection; see the [module docs](self)
+pub struct Collection<'a> {
+    db: &'a MoteDB,
+    name: String,
+    index_name: String,
+    dimension: usize,
+    metric: DistanceKind,
+    /// Metadata field name → column position
+    fields: HashMap<String, usize>,
+    width: usize,
+}
+
+impl MoteDB {
+    /// Create a collection and its vector index
+    pub fn create_collection(
+        &self,
+        name: &str,
+        config: CollectionConfig,
+    ) -> Result<Collection<'_>> {
+        ensure_open!(self);
+        if config.dimension == 0 {
+            return Err(StorageError::InvalidArgument(
+                "collection dimension must be positive".into(),
+            ));
+        }
+        let mut columns = vec![
+            ColumnDef::new(COLLECTION_ID_COLUMN.into(), ColumnType::Text, 0),
+            ColumnDef::new(
+                COLLECTION_VECTOR_COLUMN.into(),
+                ColumnType::Tensor(config.dimension),
+                1,
+            ),
+        ];
+        for (field, col_type) in config.metadata {
+            if columns.iter().any(|c| c.name == field) {
+                return Err(StorageError::InvalidArgument(format!(
+                    "collection metadata field '{}' is reserved or repeated",
+                    field
+                )));
+            }
+            if col_type.vector_dim().is_some() {
+                return Err(StorageError::InvalidArgument(format!(
+                    "collection metadata field '{}' cannot be a vector",
+                    field
+                )));
+            }
+            let position = columns.len();
+            columns.push(ColumnDef::new(field, col_type, position));
+        }
+        let schema = TableSchema::new(name.to_string(), columns)
+            .with_primary_key(COLLECTION_ID_COLUMN.to_string());
+        self.create_table(schema)?;
+
+        let index_name = format!("{}_{}", name, COLLECTION_VECTOR_COLUMN);
+        let metric = match config.metric {
+            DistanceKind::Cosine => Some("cosine"),
+            DistanceKind::Euclidean => None,
+        };
+        self.create_vector_index_on(
+            &index_name,
+            config.dimension,
+            metric,
+            Some((name, COLLECTION_VECTOR_COLUMN)),
+        )?;
+        let mut metadata = IndexMetadata::new(
+            index_name,
+            name.to_string(),
+            COLLECTION_VECTOR_COLUMN.to_string(),
+            IndexType::Vector,
+        );
+        metadata.metric = metric.map(str::to_string);
+        self.index_registry.register(metadata)?;
+        self.collection(name)
+    }
+
+    /// Open an existing collection
+    pub fn collection(&self, name: &str) -> Result<Collection<'_>> {
+        ensure_open!(self);
+        let schema = self.table_registry.get_table(name)?;
+        let not_a_collection =
+            || StorageError::InvalidArgument(format!("table '{}' is not a collection", name));
+        let (Some(id), Some(vector)) = (schema.columns.first(), schema.columns.get(1)) else {
+            return Err(not_a_collection());
+        };
+        let dimension = vector.col_type.vector_dim().ok_or_else(not_a_collection)?;
+        if id.name != COLLECTION_ID_COLUMN
+            || id.col_type != ColumnType::Text
+            || schema.primary_key() != Some(COLLECTION_ID_COLUMN)
+            || vector.name != COLLECTION_VECTOR_COLUMN
+        {
+            return Err(not_a_collection());
+        }
+        let index_name = self
+            .index_registry
+            .find_by_column(name, COLLECTION_VECTOR_COLUMN, IndexType::Vector)
+            .ok_or_else(not_a_collection)?;
+        let metric = self
+            .vector_index_metric(&index_name)
+            .ok_or_else(not_a_collection)?;
+
+        Ok(Collection {
+            db: self,
+            name: name.to_string(),
+            index_name,
+            dimension,
+            metric,
+            fields: schema.columns[2..]
+                .iter()
+                .map(|c| (c.name.clone(), c.position))
+                .collect(),
+            width: schema.columns.len(),
+        })
+    }
+}
+
+impl Collection<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Insert or replace records by id, atomically. A replaced record takes
+    /// the new vector and metadata; when an id repeats, its last record wins.
+    /// Returns the number of distinct ids written.
+    pub fn upsert(&self, records: Vec<CollectionRecord>) -> Result<usize> {
+        let mut latest: HashMap<String, usize> = HashMap::with_capacity(records.len());
+        let mut rows: Vec<(String, Vec<Value>)> = Vec::with_capacity(records.len());
+        for record in records {
+            if record.vector.len() != self.dimension {
+                return Err(StorageError::InvalidArgument(format!(
+                    "vector for '{}' has {} dimensions, collection '{}' expects {}",
+                    record.id,
+                    record.vector.len(),
+                    self.name,
+                    self.dimension
+                )));
+            }
+            let mut row = vec![Value::Null; self.width];
+            row[0] = Value::text(record.id.clone());
+            row[1] = ColumnType::Tensor(self.dimension).value_from_vector(record.vector);
+            for (field, value) in record.metadata {
+                row[self.field(&field)?] = value;
+            }
+            match latest.get(&record.id) {
+                Some(&slot) => rows[slot].1 = row,
+                None => {
+                    latest.insert(record.id.clone(), rows.len());
+                    rows.push((record.id, row));
+                }
+            }
+        }
+
+        let mut batch = self.db.write_batch();
+        for (id, row) in &rows {
+            match self.row_id(id)? {
+                Some(row_id) => batch.update(&self.name, row_id, row.clone()),
+                None => batch.insert(&self.name, row.clone()),
+            };
+        }
+        batch.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Record stored under `id`
+    pub fn get(&self, id: &str) -> Result<Option<CollectionRecord>> {
+        let Some(row_id) = self.row_id(id)? else {
+            return Ok(None);
+        };
+        Ok(self
+            .db
+            .get_table_row(&self.name, row_id)?
+            .map(|row| CollectionRecord {
+                id: id.to_string(),
+                vector: match &row[1] {
+                    Value::Vector(v) => v.to_vec(),
+                    Value::Tensor(t) => t.to_f32(),
+                    _ => Vec::new(),
+                },
+                metadata: self.metadata_of(&row),
+            }))
+    }
+
+    /// The `k` records nearest to `vector` among those matching `filter`
+    /// (an empty filter matches everything), nearest first
+    pub fn query(
+        &self,
+        vector: &[f32],
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> Result<Vec<CollectionHit>> {
+        if vector.len() != self.dimension {
+            return Err(StorageError::InvalidArgument(format!(
+                "query vector has {} dimensions, collection '{}' expects {}",
+                vector.len(),
+                self.name,
+                self.dimension
+            )));
+        }
+        let filter: Vec<(usize, &Value)> = filter
+            .iter()
+            .map(|(field, value)| Ok((self.field(field)?, value)))
+            .collect::<Result<_>>()?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Widen the search until k live candidates pass the filter or the
+        // whole collection has been asked for
+        let live = self.db.fast_row_count(&self.name).unwrap_or(0) as usize;
+        let mut fetch = k;
+        loop {
+            let candidates = self.db.vector_search(&self.index_name, vector, fetch)?;
+            let row_ids: Vec<RowId> = candidates.iter().map(|(id, _)| *id).collect();
+            let rows: HashMap<RowId, Vec<Value>> = self
+                .db
+                .get_table_rows_batch(&self.name, &row_ids)?
+                .into_iter()
+                .filter_map(|(row_id, row)| row.map(|row| (row_id, row)))
+                .collect();
+
+            let mut hits = Vec::with_capacity(k);
+            for (row_id, distance) in candidates {
+                let Some(row) = rows.get(&row_id) else {
+                    continue;
+                };
+                if !matches_filter(row, &filter) {
+                    continue;
+                }
+                let Some(hit) = self.hit(row, distance) else {
+                    continue;
+                };
+                hits.push(hit);
+                if hits.len() == k {
+                    break;
+                }
+            }
+            if hits.len() == k {
+                return Ok(hits);
+            }
+            if fetch >= live {
+                // The graph search is approximate and may never surface a
+                // selective filter's matches: rank those exactly instead
+                return if filter.is_empty() {
+                    Ok(hits)
+                } else {
+                    self.exact_query(vector, k, &filter)
+                };
+            }
+            fetch = (fetch * 4).min(live.max(k));
+        }
+    }
+
+    /// Brute-force ranking of every record matching `filter`
+    fn exact_query(
+        &self,
+        vector: &[f32],
+        k: usize,
+        filter: &[(usize, &Value)],
+    ) -> Result<Vec<CollectionHit>> {
+        let mut ranked: Vec<(f32, Vec<Value>)> = Vec::new();
+        for entry in self.db.scan_table_rows_streaming(&self.name)? {
+            let (_, row) = entry?;
+            if !matches_filter(&row, filter) {
+                continue;
+            }
+            let distance = match &row[1] {
+                Value::Vector(v) if v.len() == vector.len() => {
+                    self.metric.distance(vector, v.as_slice())
+                }
+                _ => continue,
+            };
+            ranked.push((distance, row));
+        }
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(ranked
+            .into_iter()
+            .take(k)
+            .filter_map(|(distance, row)| {
+                // `hit` expects the index's squared Euclidean distance
+                let distance = match self.metric {
+                    DistanceKind::Euclidean => distance * distance,
+                    DistanceKind::Cosine => distance,
+                };
+                self.hit(&row, distance)
+            })
+            .collect())
+    }
+
+    /// Delete records by id; returns how many existed
+    pub fn delete(&self, ids: &[&str]) -> Result<usize> {
+        let mut batch = self.db.write_batch();
+        let mut deleted = 0;
+        let mut seen = std::collections::HashSet::new();
+        for id in ids {
+            if !seen.insert(*id) {
+                continue;
+            }
+            if let Some(row_id) = self.row_id(id)? {
+                batch.delete(&self.name, row_id);
+                deleted += 1;
+            }
+        }
+        batch.commit()?;
+        Ok(deleted)
+    }
+
+    /// Delete every record matching `filter` (which must not be empty);
+    /// returns how many were deleted
+    pub fn delete_where(&self, filter: &MetadataFilter) -> Result<usize> {
+        if filter.is_empty() {
+            return Err(StorageError::InvalidArgument(
+                "delete_where needs a non-empty filter".into(),
+            ));
+        }
+        let filter: Vec<(usize, &Value)> = filter
+            .iter()
+            .map(|(field, value)| Ok((self.field(field)?, value)))
+            .collect::<Result<_>>()?;
+        let mut batch = self.db.write_batch();
+        let mut deleted = 0;
+        for entry in self.db.scan_table_rows_streaming(&self.name)? {
+            let (row_id, row) = entry?;
+            if matches_filter(&row, &filter) {
+                batch.delete(&self.name, row_id);
+                deleted += 1;
+            }
+        }
+        batch.commit()?;
+        Ok(deleted)
+    }
+
+    fn field(&self, field: &str) -> Result<usize> {
+        self.fields.get(field).copied().ok_or_else(|| {
+            StorageError::InvalidArgument(format!(
+                "collection '{}' has no metadata field '{}'",
+                self.name, field
+            ))
+        })
+    }
+
+    fn row_id(&self, id: &str) -> Result<Option<RowId>> {
+        self.db
+            .pk_owner(&self.name, COLLECTION_ID_COLUMN, &Value::text_from(id))
+    }
+
+    /// Hit for a stored row, given an index distance
+    fn hit(&self, row: &[Value], distance: f32) -> Option<CollectionHit> {
+        let Value::Text(id) = &row[0] else {
+            return None;
+        };
+        Some(CollectionHit {
+            id: id.to_string(),
+            distance: match self.metric {
+                // Euclidean searches return squared distances
+                DistanceKind::Euclidean => distance.max(0.0).sqrt(),
+                DistanceKind::Cosine => distance,
+            },
+            metadata: self.metadata_of(row),
+        })
+    }
+
+    fn metadata_of(&self, row: &[Value]) -> HashMap<String, Value> {
+        self.fields
+            .iter()
+            .filter_map(|(field, &pos)| match row.get(pos) {
+                None | Some(Value::Null) => None,
+                Some(value) => Some((field.clone(), value.clone())),
+            })
+            .collect()
+    }
+}
+
+fn matches_filter(row: &[Value], filter: &[(usize, &Value)]) -> bool {
+    filter
+        .iter()
+        .all(|(pos, value)| row.get(*pos) == Some(*value))
+}
@@ -0,0 +1,512 @@
+//! Continuous Aggregates - bucketed aggregates maintained on insert
+//!
+//! `CREATE MATERIALIZED VIEW v AS SELECT ts, sensor, AVG(x) FROM t SAMPLE BY 1m`
+//! keeps one row per (time bucket, group key) in a regular table named `v`.
+//! Inserts into the source fold the new rows into in-memory accumulators and
+//! rewrite only the result rows they touch, so reading the view never goes
+//! back to the raw data.
+//!
+//! The accumulators are rebuilt from the source on first use after open, and
+//! after UPDATE / DELETE / COMMIT touch the source. A rebuild keeps result
+//! rows for buckets older than the oldest remaining source row, so the
+//! aggregate outlives raw data removed by TTL.
+
+use super::core::MoteDB;
+use crate::types::{
+    AggregateOutput, ContinuousAggregate, NumericSum, Row, RowId, TableSchema, TableType,
+    Timestamp, Value,
+};
+use crate::{Result, StorageError};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Continuous aggregates by source table
+#[derive(Default)]
+pub(crate) struct ContinuousAggregates {
+    by_source: DashMap<String, Vec<Arc<AggregateState>>>,
+    /// Set when some aggregate has to be rebuilt before it is read
+    stale: AtomicBool,
+}
+
+struct AggregateState {
+    /// Backing table name
+    view: String,
+    definition: ContinuousAggregate,
+    /// The accumulators no longer match the source
+    stale: AtomicBool,
+    groups: Mutex<HashMap<GroupKey, Group>>,
+}
+
+/// Bucket start + group key values
+type GroupKey = (i64, Vec<Value>);
+
+struct Group {
+    row_id: Option<RowId>,
+    /// Row currently stored in the backing table
+    written: Option<Row>,
+    /// One accumulator per output column
+    accs: Vec<Acc>,
+}
+
+impl Group {
+    fn new(outputs: usize) -> Self {
+        Self {
+            row_id: None,
+            written: None,
+            accs: vec![Acc::default(); outputs],
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Acc {
+    count: i64,
+    sum: NumericSum,
+    min: Option<Value>,
+    max: Option<Value>,
+}
+
+impl Acc {
+    fn add(&mut self, val: &Value) {
+        self.count += 1;
+        self.sum.add(val);
+        if self
+            .min
+            .as_ref()
+            .is_none_or(|m| val.partial_cmp(m) == Some(CmpOrdering::Less))
+        {
+            self.min = Some(val.clone());
+        }
+        if self
+            .max
+            .as_ref()
+            .is_none_or(|m| val.partial_cmp(m) == Some(CmpOrdering::Greater))
+        {
+            self.max = Some(val.clone());
+        }
+    }
+}
+
+/// Source column positions of a definition
+struct Plan {
+    ts: usize,
+    /// Per output column; `None` for the bucket and COUNT(*)
+    columns: Vec<Option<usize>>,
+}
+
+impl Plan {
+    fn new(definition: &ContinuousAggregate, source: &TableSchema) -> Result<Self> {
+        let position = |name: &str| {
+            source.get_column_position(name).ok_or_else(|| {
+                StorageError::InvalidData(format!(
+                    "Column '{}' of materialized view source '{}' no longer exists",
+                    name, source.name
+                ))
+            })
+        };
+        let ts = position(&definition.ts_column)?;
+        let columns = definition
+            .outputs
+            .iter()
+            .map(|output| match output {
+                AggregateOutput::Bucket | AggregateOutput::Count(None) => Ok(None),
+                AggregateOutput::Key(c)
+                | AggregateOutput::Count(Some(c))
+                | AggregateOutput::Sum(c)
+                | AggregateOutput::Avg(c)
+                | AggregateOutput::Min(c)
+                | AggregateOutput::Max(c) => position(c).map(Some),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { ts, columns })
+    }
+
+    /// Fold one source row into its group; rows with a NULL timestamp are skipped
+    fn fold(
+        &self,
+        definition: &ContinuousAggregate,
+        groups: &mut HashMap<GroupKey, Group>,
+        row: &[Value],
+    ) -> Option<GroupKey> {
+        let micros = match row.get(self.ts)? {
+            Value::Timestamp(ts) => ts.as_micros(),
+            Value::Integer(i) => *i,
+            _ => return None,
+        };
+        let interval = definition.interval_micros;
+        let bucket = micros.div_euclid(interval) * interval;
+        let key: Vec<Value> = definition
+            .outputs
+            .iter()
+            .zip(&self.columns)
+            .filter(|(output, _)| matches!(output, AggregateOutput::Key(_)))
+            .map(|(_, pos)| pos.and_then(|p| row.get(p).cloned()).unwrap_or(Value::Null))
+            .collect();
+        let key = (bucket, key);
+        let group = groups
+            .entry(key.clone())
+            .or_insert_with(|| Group::new(definition.outputs.len()));
+        for ((output, pos), acc) in definition
+            .outputs
+            .iter()
+            .zip(&self.columns)
+            .zip(&mut group.accs)
+        {
+            match (output, pos) {
+                (AggregateOutput::Bucket | AggregateOutput::Key(_), _) => {}
+                (AggregateOutput::Count(None), _) => acc.count += 1,
+                (_, Some(p)) => match row.get(*p) {
+                    None | Some(Value::Null) => {}
+                    Some(val) => acc.add(val),
+                },
+                (_, None) => {}
+            }
+        }
+        Some(key)
+    }
+}
+
+/// Backing table row for a group
+fn output_row(definition: &ContinuousAggregate, key: &GroupKey, accs: &[Acc]) -> Result<Row> {
+    let mut keys = key.1.iter();
+    definition
+        .outputs
+        .iter()
+        .zip(accs)
+        .map(|(output, acc)| match output {
+            AggregateOutput::Bucket => Ok(Value::Timestamp(Timestamp::from_micros(key.0))),
+            AggregateOutput::Key(_) => Ok(keys.next().cloned().unwrap_or(Value::Null)),
+            AggregateOutput::Count(_) => Ok(Value::Integer(acc.count)),
+            AggregateOutput::Sum(_) => acc.sum.sum(),
+            AggregateOutput::Avg(_) => Ok(acc.sum.avg()),
+            AggregateOutput::Min(_) => Ok(acc.min.clone().unwrap_or(Value::Null)),
+            AggregateOutput::Max(_) => Ok(acc.max.clone().unwrap_or(Value::Null)),
+        })
+        .collect()
+}
+
+/// Group key of a backing table row
+fn backing_key(definition: &ContinuousAggregate, row: &[Value]) -> Option<GroupKey> {
+    let mut bucket = None;
+    let mut key = Vec::new();
+    for (output, val) in definition.outputs.iter().zip(row) {
+        match output {
+            AggregateOutput::Bucket => {
+                bucket = match val {
+                    Value::Timestamp(ts) => Some(ts.as_micros()),
+                    Value::Integer(i) => Some(*i),
+                    _ => None,
+                }
+            }
+            AggregateOutput::Key(_) => key.push(val.clone()),
+            _ => {}
+        }
+    }
+    bucket.map(|b| (b, key))
+}
+
+impl MoteDB {
+    /// Create the backing table of a continuous aggregate and fill it from
+    /// the source. `schema` must carry the definition.
+    pub fn create_continuous_aggregate(&self, schema: TableSchema) -> Result<()> {
+        ensure_open!(self);
+        let definition = schema.continuous_aggregate.clone().ok_or_else(|| {
+            StorageError::InvalidData(format!(
+                "Table '{}' has no continuous aggregate definition",
+                schema.name
+            ))
+        })?;
+        let source = self.table_registry.get_table(&definition.source)?;
+        if source.continuous_aggregate.is_some() {
+            return Err(StorageError::InvalidData(format!(
+                "Materialized view '{}' cannot read from another materialized view",
+                schema.name
+            )));
+        }
+        Plan::new(&definition, &source)?;
+
+        let view = schema.name.clone();
+        self.create_table(schema)?;
+        let state = Arc::new(AggregateState {
+            view: view.clone(),
+            definition,
+            stale: AtomicBool::new(false),
+            groups: Mutex::new(HashMap::new()),
+        });
+        // Registered under the lock: inserts racing with the initial build
+        // wait for it and are then applied on top
+        let mut groups = state.groups.lock();
+        self.continuous_aggregates
+            .by_source
+            .entry(state.definition.source.clone())
+            .or_default()
+            .push(state.clone());
+        if let Err(e) = self.rebuild_continuous_aggregate(&state, &mut groups) {
+            drop(groups);
+            self.drop_table(&view)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Recompute a continuous aggregate from its source
+    pub fn refresh_continuous_aggregate(&self, view: &str) -> Result<()> {
+        ensure_open!(self);
+        let state = self
+            .continuous_aggregates
+            .by_source
+            .iter()
+            .find_map(|e| e.value().iter().find(|s| s.view == view).cloned())
+            .ok_or_else(|| {
+                StorageError::InvalidData(format!("'{}' is not a materialized view", view))
+            })?;
+        let mut groups = state.groups.lock();
+        self.rebuild_continuous_aggregate(&state, &mut groups)
+    }
+
+    /// Whether rows inserted into `table` feed a continuous aggregate
+    pub fn has_continuous_aggregates(&self, table: &str) -> bool {
+        self.continuous_aggregates.by_source.contains_key(table)
+    }
+
+    /// Names of the continuous aggregates reading from `table`
+    pub fn continuous_aggregate_views(&self, table: &str) -> Vec<String> {
+        self.continuous_aggregates
+            .by_source
+            .get(table)
+            .map(|states| states.iter().map(|s| s.view.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Rebuild the continuous aggregates invalidated since the last call.
+    ///
+    /// Called before each statement; cheap when nothing is stale. Failures
+    /// are logged and retried on the next call.
+    pub fn refresh_stale_continuous_aggregates(&self) {
+        if !self
+            .continuous_aggregates
+            .stale
+            .swap(false, Ordering::AcqRel)
+        {
+            return;
+        }
+        let states: Vec<Arc<AggregateState>> = self
+            .continuous_aggregates
+            .by_source
+            .iter()
+            .flat_map(|e| e.value().clone())
+            .collect();
+        for state in states {
+            if !state.stale.load(Ordering::Acquire) {
+                continue;
+            }
+            let mut groups = state.groups.lock();
+            if !state.stale.load(Ordering::Acquire) {
+                continue;
+            }
+            if let Err(e) = self.rebuild_continuous_aggregate(&state, &mut groups) {
+                warn_log!("[continuous] rebuilding '{}' failed: {:?}", state.view, e);
+                self.continuous_aggregates
+                    .stale
+                    .store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Mark the aggregates over `table` for a rebuild (the source changed in
+    /// a way that can't be folded in incrementally)
+    pub(crate) fn invalidate_continuous_aggregates(&self, table: &str) {
+        if let Some(states) = self.continuous_aggregates.by_source.get(table) {
+            for state in states.iter() {
+                state.stale.store(true, Ordering::Release);
+            }
+            self.continuous_aggregates
+                .stale
+                .store(true, Ordering::Release);
+        }
+    }
+
+    /// Register the aggregates found in the catalog; they are built lazily
+    pub(crate) fn load_continuous_aggregates(&self) -> Result<()> {
+        for table in self.table_registry.list_tables()? {
+            let schema = self.table_registry.get_table(&table)?;
+            if let Some(definition) = schema.continuous_aggregate.clone() {
+                self.continuous_aggregates
+                    .by_source
+                    .entry(definition.source.clone())
+                    .or_default()
+                    .push(Arc::new(AggregateState {
+                        view: table,
+                        definition,
+                        stale: AtomicBool::new(true),
+                        groups: Mutex::new(HashMap::new()),
+                    }));
+                self.continuous_aggregates
+                    .stale
+                    .store(true, Ordering::Release);
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget the aggregate backed by `view` (the table is being dropped)
+    pub(crate) fn unregister_continuous_aggregate(&self, view: &str) {
+        self.continuous_aggregates.by_source.retain(|_, states| {
+            states.retain(|s| s.view != view);
+            !states.is_empty()
+        });
+    }
+
+    /// Run an insert into `table` and fold `rows` into its aggregates.
+    ///
+    /// The aggregates stay locked across the write so a concurrent rebuild
+    /// can't count the rows twice. A failure to update an aggregate doesn't
+    /// fail the insert; the aggregate is rebuilt instead.
+    pub(crate) fn maintain_continuous_aggregates<T>(
+        &self,
+        table: &str,
+        rows: &[Row],
+        write: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let states = match self.continuous_aggregates.by_source.get(table) {
+            Some(states) => states.clone(),
+            None => return write(),
+        };
+        let mut guards: Vec<_> = states.iter().map(|s| s.groups.lock()).collect();
+        let out = write()?;
+
+        let schema = self.table_registry.get_table(table)?;
+        let mut rows = rows.to_vec();
+        let normalized =
+            super::generated::fill_generated_columns(&schema, &mut rows).and_then(|_| {
+                rows.iter_mut()
+                    .try_for_each(|row| schema.coerce_row(row).map_err(StorageError::InvalidData))
+            });
+        for (state, groups) in states.iter().zip(guards.iter_mut()) {
+            let result = match &normalized {
+                Ok(()) if !state.stale.load(Ordering::Acquire) => {
+                    self.apply_continuous_aggregate(state, groups, &schema, &rows)
+                }
+                _ => self.rebuild_continuous_aggregate(state, groups),
+            };
+            if let Err(e) = result {
+                warn_log!("[continuous] updating '{}' failed: {:?}", state.view, e);
+                state.stale.store(true, Ordering::Release);
+                self.continuous_aggregates
+                    .stale
+                    .store(true, Ordering::Release);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Fold newly inserted source rows and rewrite the groups they touch
+    fn apply_continuous_aggregate(
+        &self,
+        state: &AggregateState,
+        groups: &mut HashMap<GroupKey, Group>,
+        source: &TableSchema,
+        rows: &[Row],
+    ) -> Result<()> {
+        let plan = Plan::new(&state.definition, source)?;
+        let mut touched = HashSet::new();
+        for row in rows {
+            if let Some(key) = plan.fold(&state.definition, groups, row) {
+                touched.insert(key);
+            }
+        }
+        for key in touched {
+            if let Some(group) = groups.get_mut(&key) {
+                self.write_group(state, &key, group)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a group's current values in the backing table
+    fn write_group(&self, state: &AggregateState, key: &GroupKey, group: &mut Group) -> Result<()> {
+        let row = output_row(&state.definition, key, &group.accs)?;
+        match (group.row_id, group.written.take()) {
+            (Some(row_id), Some(old)) => {
+                if old != row {
+                    self.update_row_in_table(&state.view, row_id, old, row.clone())?;
+                }
+            }
+            _ => group.row_id = Some(self.insert_row_to_table(&state.view, row.clone())?),
+        }
+        group.written = Some(row);
+        Ok(())
+    }
+
+    /// Recompute every group from the source and reconcile the backing table
+    fn rebuild_continuous_aggregate(
+        &self,
+        state: &AggregateState,
+        groups: &mut HashMap<GroupKey, Group>,
+    ) -> Result<()> {
+        // Cleared first: an invalidation during the rebuild must not be lost
+        state.stale.store(false, Ordering::Release);
+        let definition = &state.definition;
+        let source = self.table_registry.get_table(&definition.source)?;
+        let plan = Plan::new(definition, &source)?;
+
+        let mut existing: HashMap<GroupKey, (RowId, Row)> = HashMap::new();
+        for item in self.scan_table_rows_streaming(&state.view)? {
+            let (row_id, row) = item?;
+            if let Some(key) = backing_key(definition, &row) {
+                existing.insert(key, (row_id, row));
+            }
+        }
+
+        groups.clear();
+        let mut oldest: Option<i64> = None;
+        let mut fold = |row: &[Value]| {
+            if let Some((bucket, _)) = plan.fold(definition, groups, row) {
+                oldest = Some(oldest.map_or(bucket, |o| o.min(bucket)));
+            }
+        };
+        if source.table_type == TableType::TimeSeries {
+            for (_, sql_row) in
+                self.columnar_store
+                    .query_time_range(&definition.source, i64::MIN, i64::MAX, &[])?
+            {
+                let row: Row = source
+                    .columns
+                    .iter()
+                    .map(|c| sql_row.get(&c.name).cloned().unwrap_or(Value::Null))
+                    .collect();
+                fold(&row);
+            }
+        } else {
+            for item in self.scan_table_rows_streaming(&definition.source)? {
+                let (_, row) = item?;
+                fold(&row);
+            }
+        }
+
+        for (key, group) in groups.iter_mut() {
+            if let Some((row_id, old)) = existing.remove(key) {
+                group.row_id = Some(row_id);
+                group.written = Some(old);
+            }
+            self.write_group(state, key, group)?;
+        }
+        // Groups without source rows: drop them, unless their raw data has
+        // simply aged out
+        for (key, (row_id, old)) in existing {
+            if oldest.is_some_and(|o| key.0 >= o) {
+                self.delete_row_from_table(&state.view, row_id, old)?;
+            } else {
+                let mut group = Group::new(definition.outputs.len());
+                group.row_id = Some(row_id);
+                group.written = Some(old);
+                groups.insert(key, group);
+            }
+        }
+        Ok(())
+    }
+}
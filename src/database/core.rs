@@ -10,10 +10,12 @@
 use crate::cache::RowCache;
 use crate::catalog::TableRegistry;
 use crate::config::DBConfig;
+use crate::database::transaction::RetainedDelete;
 use crate::index::btree::{BTree, BTreeConfig};
 use crate::index::column_value::ColumnValueIndex;
 use crate::index::ioctree::IOctreeIndex;
 use crate::index::text_fts::TextFTSIndex;
+use crate::index::timestamp_reorder::TimestampReorderBuffer;
 use crate::index::vamana::{DiskANNIndex, VamanaConfig};
 use crate::storage::LSMEngine;
 use crate::txn::coordinator::TransactionCoordinator;
@@ -24,7 +26,6 @@ use crate::{MoteDBError, Result, StorageError};
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use std::collections::HashMap;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64};
 use std::sync::{Arc, Mutex};
@@ -45,6 +46,9 @@ pub struct MoteDB {
     /// Timestamp index (using BTree for persistent storage)
     pub(crate) timestamp_index: Arc<RwLock<BTree>>,
 
+    /// Reorder buffer for out-of-order timestamp index entries (None = disabled)
+    pub(crate) timestamp_reorder: Option<Arc<parking_lot::Mutex<TimestampReorderBuffer>>>,
+
     /// Next row ID (lock-free atomic counter)
     pub(crate) next_row_id: Arc<AtomicU64>,
 
@@ -109,6 +113,11 @@ pub struct MoteDB {
     pub(crate) col_segment_stores:
         Arc<DashMap<String, Arc<crate::storage::col_segment::ColSegmentStore>>>,
 
+    /// Per-table write gates: writes hold one for read from their first
+    /// store append until their index updates are done, so an index rebuild
+    /// can briefly hold it for write to see the table at rest
+    pub(crate) table_write_gates: Arc<DashMap<String, Arc<RwLock<()>>>>,
+
     /// 🚀 In-memory PK lookup: table_name → (PK_value_key → RowId)
     /// Bypasses disk-based column index for O(1) PK → row_id resolution.
     /// Only populated for non-AUTO_INCREMENT primary keys.
@@ -119,12 +128,32 @@ pub struct MoteDB {
     /// Incremented on INSERT, decremented on DELETE.
     pub(crate) table_row_count: Arc<DashMap<String, Arc<AtomicU64>>>,
 
+    /// Rows deleted while transactions were open, per table: kept for the
+    /// snapshots that still see them (see `retain_deleted_rows`)
+    pub(crate) retained_deletes: Arc<DashMap<String, Vec<RetainedDelete>>>,
+
     /// Table registry (catalog)
     pub(crate) table_registry: Arc<TableRegistry>,
 
+    /// Continuous aggregates (materialized views), keyed by source table
+    pub(crate) continuous_aggregates: Arc<super::continuous::ContinuousAggregates>,
+
+    /// Insertion order of ring-buffer tables (`MAX_ROWS` / `MAX_BYTES`)
+    pub(crate) ring_buffers: Arc<super::ring_buffer::RingBuffers>,
+
+    /// On-insert embedding hooks, keyed by table
+    pub(crate) embedders: Arc<super::embedder::Embedders>,
+
+    /// Full scans in flight that concurrent scans of their table can attach to
+    pub(crate) shared_scans: Arc<super::shared_scan::SharedScans>,
+
     /// 🆕 Index metadata registry
     pub(crate) index_registry: Arc<crate::database::index_metadata::IndexRegistry>,
 
+    /// Index files committed at each checkpoint (None if the manifest could
+    /// not be opened; indexes are then flushed without being published)
+    pub(crate) index_manifest: Option<Arc<super::indexes::manifest::IndexManifest>>,
+
     /// 🚀 P1: Row cache (hot data cache)
     pub(crate) row_cache: Arc<RowCache>,
 
@@ -137,6 +166,21 @@ pub struct MoteDB {
     /// Maximum rows a single SELECT may return (prevents OOM).
     pub(crate) max_result_rows: Option<usize>,
 
+    /// Streaming scan batch size and materialize preallocation cap
+    pub(crate) streaming: crate::config::StreamingConfig,
+
+    /// Bytes a statement's joins, sorts and aggregations may buffer
+    pub(crate) query_memory_limit: Option<usize>,
+
+    /// Directory SQL COPY is confined to (None = COPY refused)
+    pub(crate) copy_dir: Option<PathBuf>,
+
+    /// Save cache state at shutdown and warm caches from it at open
+    pub(crate) persist_cache_state: bool,
+
+    /// Hard memory cap (None = uncapped)
+    pub(crate) memory_budget: Option<Arc<super::memory_budget::MemoryBudget>>,
+
     /// PK lookup cache capacity per table (LRU eviction)
     pub(crate) pk_lookup_capacity: usize,
 
@@ -164,9 +208,10 @@ pub struct MoteDB {
     /// Async index build pipeline: sender (None if pipeline disabled)
     index_build_tx: Option<std::sync::mpsc::Sender<IndexBuildBatch>>,
 
-    /// Number of index build batches sent but not yet processed by the background thread.
+    /// Number of index build batches sent but not yet processed by the background thread
+    /// (plus a running rebuild of indexes discarded at open).
     /// Used by `wait_for_indexes_ready()` to know when indexes are caught up.
-    pending_index_batches: Arc<std::sync::atomic::AtomicUsize>,
+    pub(crate) pending_index_batches: Arc<std::sync::atomic::AtomicUsize>,
 
     /// Counter for index build errors (incremented by background thread, readable by user)
     pub index_build_errors: Arc<std::sync::atomic::AtomicUsize>,
@@ -189,6 +234,11 @@ pub struct MoteDB {
 
     /// True for clone_for_callback() instances — skip Drop checkpoint.
     _is_clone: bool,
+
+    /// Serves files opened by path under the database directory from the
+    /// configured storage backend. Declared last so that it outlives every
+    /// other field during drop.
+    _mount: Option<Arc<crate::storage::backend::Mount>>,
 }
 
 /// Auto-checkpoint background thread
@@ -198,6 +248,10 @@ struct AutoCheckpointThread {
 
     /// Stop signal
     should_stop: Arc<AtomicBool>,
+
+    /// Without threads: the trigger (config, WAL hard bound) checked after
+    /// each inline auto-flush instead
+    inline: Option<(crate::config::AutoCheckpointConfig, u64)>,
 }
 
 /// Index build job sent through the async pipeline
@@ -213,6 +267,10 @@ struct IndexBuilderThread {
 
     /// Stop signal
     should_stop: Arc<AtomicBool>,
+
+    /// Without threads the batches queue here and are built on the caller's
+    /// thread by `drain_index_batches`
+    inline_rx: Option<Mutex<std::sync::mpsc::Receiver<IndexBuildBatch>>>,
 }
 
 /// Auto-flush background thread: single thread handles all auto-flush requests
@@ -240,38 +298,45 @@ impl MoteDB {
         let db_path = path.with_extension("mote");
 
         // 🎯 统一目录结构：所有文件放在 {name}.mote/ 目录下
-        if db_path.exists() && db_path.join("lsm").exists() {
-            return Err(StorageError::InvalidData(format!(
-                "Database already exists at {:?}; use open() instead of create()",
-                db_path
-            )));
-        }
-        std::fs::create_dir_all(&db_path)?;
-
-        // 🔒 Acquire exclusive file lock to prevent concurrent opens
-        let lock_file = Self::acquire_lock(&db_path)?;
+        // 🔒 Acquire exclusive file lock to prevent concurrent opens. The lock
+        // file lives on the host even when the data does not.
+        let lock_file = if Self::needs_host_lock(&config) {
+            std::fs::create_dir_all(&db_path)?;
+            Some(Self::acquire_lock(&db_path)?)
+        } else {
+            None
+        };
 
         let wal_path = db_path.join("wal");
         let lsm_dir = db_path.join("lsm");
         let indexes_dir = db_path.join("indexes");
 
         let num_partitions = config.num_partitions;
+        let (backend, mount) = Self::mount_backend(&db_path, &config);
+        if backend.exists(&lsm_dir) {
+            return Err(StorageError::InvalidData(format!(
+                "Database already exists at {:?}; use open() instead of create()",
+                db_path
+            )));
+        }
 
         // Create WAL directory with config
-        std::fs::create_dir_all(&wal_path)?;
-        let wal_config = crate::txn::wal::WALConfig::from(config.wal_config);
-        let wal = Arc::new(WALManager::create_with_config(
+        let mut wal_config = crate::txn::wal::WALConfig::from(config.wal_config.clone());
+        wal_config.flash = config.flash_write;
+        let wal = Arc::new(WALManager::create_with_backend(
             &wal_path,
             num_partitions,
             wal_config,
+            backend.clone(),
         )?);
 
         // Create timestamp index with BTree storage (放在 indexes/ 目录)
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let timestamp_storage = indexes_dir.join("timestamp.idx");
         let btree_config = BTreeConfig {
             unique_keys: false, // Allow duplicate timestamps
             allow_updates: true,
+            delta_encoding: true, // Periodic timestamps compress to a few bits per key
             ..Default::default()
         };
         let timestamp_index = Arc::new(RwLock::new(BTree::with_config(
@@ -280,9 +345,12 @@ impl MoteDB {
         )?));
 
         // Create LSM-Tree storage engine
-        std::fs::create_dir_all(&lsm_dir)?;
+        backend.create_dir_all(&lsm_dir)?;
         // Use edge-optimized LSM config if memtable_size_limit differs from default
-        let lsm_config = crate::storage::lsm::LSMConfig::from_db_config(&config.lsm_config);
+        let mut lsm_config = crate::storage::lsm::LSMConfig::from_db_config(&config.lsm_config);
+        if let Some(flash) = config.flash_write {
+            lsm_config.write_buffer_size = flash.erase_block_bytes;
+        }
         let lsm_engine = Arc::new(LSMEngine::new(lsm_dir, lsm_config)?);
 
         // Create version store and transaction coordinator
@@ -290,15 +358,21 @@ impl MoteDB {
         let txn_coordinator = Arc::new(TransactionCoordinator::new(version_store.clone()));
 
         // Create table registry (catalog)
-        let table_registry = Arc::new(TableRegistry::new(&db_path)?);
+        let table_registry = Arc::new(TableRegistry::with_backend(&db_path, backend.clone())?);
 
         // 🆕 Create index metadata registry
-        let index_registry = Arc::new(crate::database::index_metadata::IndexRegistry::new(
-            &db_path,
-        ));
+        let index_registry = Arc::new(
+            crate::database::index_metadata::IndexRegistry::with_backend(&db_path, backend),
+        );
+        let (index_manifest, _) =
+            Self::open_index_manifest(&db_path, &index_registry, &Default::default());
 
         // 🚀 P1: Create row cache (default 10000 rows ≈ 10MB)
         let row_cache = Arc::new(RowCache::new(config.row_cache_size.unwrap_or(10000)));
+        for (table_name, max_rows) in &config.row_cache_quotas {
+            row_cache.set_table_quota(table_name, Some(*max_rows));
+        }
+        row_cache.set_write_policy(config.row_cache_write_policy);
 
         // Ensure "_default" table has a stable table_id (= 0)
         table_registry.ensure_default_table_id()?;
@@ -330,10 +404,10 @@ impl MoteDB {
         // 🚀 Recover columnar SSTables from disk (zero-encode INSERTs skip WAL)
         let columnar_sstables = Arc::new(DashMap::new());
         let indexes_dir = db_path.join("indexes");
-        if indexes_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&indexes_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
+        let indexes_backend = crate::storage::backend::backend_for(&indexes_dir);
+        if indexes_backend.exists(&indexes_dir) {
+            if let Ok(entries) = indexes_backend.list_dir(&indexes_dir) {
+                for path in entries {
                     if path.extension().is_some_and(|e| e == "sst") {
                         if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
                             if name.ends_with("_col") {
@@ -379,6 +453,9 @@ impl MoteDB {
             wal,
             lsm_engine: lsm_engine.clone(),
             timestamp_index,
+            timestamp_reorder: config
+                .timestamp_reorder
+                .map(|r| Arc::new(parking_lot::Mutex::new(TimestampReorderBuffer::new(r)))),
             next_row_id: next_row_id.clone(),
             write_lsn: write_lsn.clone(),
             table_auto_increment: Arc::new(DashMap::new()),
@@ -394,16 +471,30 @@ impl MoteDB {
             columnar_sstables,
             columnar_write_bufs: Arc::new(DashMap::new()),
             col_segment_stores: Arc::new(DashMap::new()),
+            table_write_gates: Arc::new(DashMap::new()),
             pk_lookup: Arc::new(DashMap::new()),
             table_row_count: Arc::new(DashMap::new()),
+            retained_deletes: Arc::new(DashMap::new()),
             table_registry,
+            continuous_aggregates: Arc::default(),
+            ring_buffers: Arc::default(),
+            embedders: Arc::default(),
+            shared_scans: Arc::new(super::shared_scan::SharedScans::new(config.shared_scans)),
             index_registry,
+            index_manifest,
             row_cache,
             index_update_strategy: config.index_update_strategy.clone(),
             query_timeout_secs: config.query_timeout_secs,
             pk_lookup_capacity: config.pk_lookup_capacity,
             column_index_buffer_size: config.column_index_buffer_size,
             max_result_rows: config.max_result_rows,
+            streaming: config.streaming,
+            query_memory_limit: config.query_memory_limit,
+            copy_dir: config.copy_dir.clone(),
+            persist_cache_state: config.persist_cache_state,
+            memory_budget: config
+                .memory_limit
+                .map(|limit| Arc::new(super::memory_budget::MemoryBudget::new(limit))),
             is_flushing: Arc::new(AtomicBool::new(false)),
             is_pipeline_active: Arc::new(AtomicBool::new(false)),
             pending_index_batches: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
@@ -415,8 +506,9 @@ impl MoteDB {
             index_build_tx: None,
             index_builder_thread: None,
             auto_flush_thread: None,
-            _lock_file: std::sync::Mutex::new(Some(lock_file)),
+            _lock_file: std::sync::Mutex::new(lock_file),
             _is_clone: false,
+            _mount: mount,
         };
 
         // 🚀 P1: Async Index Build Pipeline
@@ -449,7 +541,11 @@ impl MoteDB {
 
         // 🚀 Start auto-checkpoint thread if enabled
         let auto_checkpoint_thread = config.auto_checkpoint.map(|auto_config| {
-            Self::start_auto_checkpoint_thread(db.clone_for_callback(), auto_config)
+            Self::start_auto_checkpoint_thread(
+                db.clone_for_callback(),
+                auto_config,
+                config.wal_config.max_wal_size,
+            )
         });
 
         // Update db with the thread handle
@@ -484,6 +580,7 @@ impl MoteDB {
 
     /// Wait for index readiness with a custom timeout.
     pub fn wait_for_indexes_ready_timeout(&self, timeout: std::time::Duration) -> bool {
+        self.drain_index_batches();
         let start = std::time::Instant::now();
         loop {
             if self
@@ -595,6 +692,7 @@ impl MoteDB {
             wal: self.wal.clone(),
             lsm_engine: self.lsm_engine.clone(),
             timestamp_index: self.timestamp_index.clone(),
+            timestamp_reorder: self.timestamp_reorder.clone(),
             next_row_id: self.next_row_id.clone(),
             write_lsn: self.write_lsn.clone(),
             table_auto_increment: self.table_auto_increment.clone(), // 🚀 Phase 4
@@ -612,16 +710,28 @@ impl MoteDB {
             columnar_sstables: self.columnar_sstables.clone(),
             columnar_write_bufs: self.columnar_write_bufs.clone(),
             col_segment_stores: self.col_segment_stores.clone(),
+            table_write_gates: self.table_write_gates.clone(),
             pk_lookup: self.pk_lookup.clone(),
             table_row_count: self.table_row_count.clone(),
+            retained_deletes: self.retained_deletes.clone(),
             table_registry: self.table_registry.clone(),
+            continuous_aggregates: self.continuous_aggregates.clone(),
+            ring_buffers: self.ring_buffers.clone(),
+            embedders: self.embedders.clone(),
+            shared_scans: self.shared_scans.clone(),
             index_registry: self.index_registry.clone(), // 🆕
+            index_manifest: self.index_manifest.clone(),
             row_cache: self.row_cache.clone(),
             index_update_strategy: self.index_update_strategy.clone(),
             query_timeout_secs: self.query_timeout_secs, // 🚀 P0
             pk_lookup_capacity: self.pk_lookup_capacity,
             column_index_buffer_size: self.column_index_buffer_size,
             max_result_rows: self.max_result_rows,
+            streaming: self.streaming,
+            query_memory_limit: self.query_memory_limit,
+            copy_dir: self.copy_dir.clone(),
+            persist_cache_state: self.persist_cache_state,
+            memory_budget: self.memory_budget.clone(),
             is_flushing: self.is_flushing.clone(),
             is_pipeline_active: self.is_pipeline_active.clone(), // shared — clones see true when pipeline runs
             pending_index_batches: self.pending_index_batches.clone(),
@@ -633,6 +743,7 @@ impl MoteDB {
             auto_flush_thread: None,      // Don't clone thread (only owned by original)
             _lock_file: std::sync::Mutex::new(None), // Don't clone lock (only owned by original)
             _is_clone: true,              // Skip Drop checkpoint for clones
+            _mount: self._mount.clone(),
         }
     }
 
@@ -655,7 +766,11 @@ impl MoteDB {
         let db_path = path.with_extension("mote");
 
         // 🔒 Acquire exclusive file lock to prevent concurrent opens
-        let lock_file = Self::acquire_lock(&db_path)?;
+        let lock_file = if Self::needs_host_lock(&config) {
+            Some(Self::acquire_lock(&db_path)?)
+        } else {
+            None
+        };
 
         // 🎯 统一目录结构：从 {name}.mote/ 目录读取
         let wal_path = db_path.join("wal");
@@ -664,21 +779,24 @@ impl MoteDB {
 
         // Use config instead of hardcoded default
         let num_partitions = config.num_partitions;
+        let (backend, mount) = Self::mount_backend(&db_path, &config);
 
         // Open or create WAL (pass user config — fixes config loss on reopen)
-        let wal_config = crate::txn::wal::WALConfig::from(config.wal_config.clone());
-        let wal = if wal_path.exists() {
-            Arc::new(WALManager::open_with_config(
+        let mut wal_config = crate::txn::wal::WALConfig::from(config.wal_config.clone());
+        wal_config.flash = config.flash_write;
+        let wal = if backend.exists(&wal_path) {
+            Arc::new(WALManager::open_with_backend(
                 &wal_path,
                 num_partitions,
                 wal_config,
+                backend.clone(),
             )?)
         } else {
-            std::fs::create_dir_all(&wal_path)?;
-            Arc::new(WALManager::create_with_config(
+            Arc::new(WALManager::create_with_backend(
                 &wal_path,
                 num_partitions,
                 wal_config,
+                backend.clone(),
             )?)
         };
 
@@ -690,11 +808,12 @@ impl MoteDB {
         let recovered_records = wal.recover()?;
 
         // Open timestamp index with BTree storage (从 indexes/ 目录)
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let timestamp_storage = indexes_dir.join("timestamp.idx");
         let btree_config = BTreeConfig {
             unique_keys: false,
             allow_updates: true,
+            delta_encoding: true,
             ..Default::default()
         };
         let mut timestamp_idx = BTree::with_config(timestamp_storage, btree_config)?;
@@ -711,14 +830,17 @@ impl MoteDB {
         };
 
         // Open LSM-Tree storage engine
-        std::fs::create_dir_all(&lsm_dir)?;
+        backend.create_dir_all(&lsm_dir)?;
         // Use edge-optimized LSM config if memtable_size_limit differs from default
-        let lsm_config = crate::storage::lsm::LSMConfig::from_db_config(&config.lsm_config);
+        let mut lsm_config = crate::storage::lsm::LSMConfig::from_db_config(&config.lsm_config);
+        if let Some(flash) = config.flash_write {
+            lsm_config.write_buffer_size = flash.erase_block_bytes;
+        }
         let lsm_engine = Arc::new(LSMEngine::new(lsm_dir, lsm_config)?);
 
         // Load table registry BEFORE WAL replay so we can resolve table_name → table_id
         // for correct composite key construction.
-        let table_registry = Arc::new(TableRegistry::new(&db_path)?);
+        let table_registry = Arc::new(TableRegistry::with_backend(&db_path, backend.clone())?);
         table_registry.ensure_default_table_id()?;
 
         // Replay WAL records into LSM Engine using stable table_id
@@ -822,7 +944,9 @@ impl MoteDB {
         for table_name in table_registry.list_tables()? {
             if let Ok(schema) = table_registry.get_table(&table_name) {
                 let indexes_dir = db_path.join("indexes");
-                std::fs::create_dir_all(&indexes_dir).ok();
+                crate::storage::backend::backend_for(&indexes_dir)
+                    .create_dir_all(&indexes_dir)
+                    .ok();
                 let col_path = indexes_dir.join(format!("{}_col.sst", &table_name));
                 let builder = crate::storage::lsm::columnar::ColumnarSSTableBuilder::new(
                     col_path,
@@ -874,7 +998,7 @@ impl MoteDB {
                         }
                         let table_id = table_registry.get_table_id(table_name).unwrap_or(0);
                         let composite_key = ((table_id as u64) << 32) | (*row_id & 0xFFFFFFFF);
-                        let row_data = bincode::serialize(data)?;
+                        let row_data = crate::storage::value_codec::encode_row(data);
                         let ts = recovery_lsn.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
                         let value = crate::storage::lsm::Value::new(row_data, ts);
                         lsm_engine.put(composite_key, value)?;
@@ -919,7 +1043,7 @@ impl MoteDB {
                         }
                         let table_id = table_registry.get_table_id(table_name).unwrap_or(0);
                         let composite_key = ((table_id as u64) << 32) | (*row_id & 0xFFFFFFFF);
-                        let row_data = bincode::serialize(new_data)?;
+                        let row_data = crate::storage::value_codec::encode_row(new_data);
                         let ts = recovery_lsn.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
                         let value = crate::storage::lsm::Value::new(row_data, ts);
                         lsm_engine.put(composite_key, value)?;
@@ -963,6 +1087,22 @@ impl MoteDB {
                         lsm_engine.delete(composite_key, ts)?;
                         _recovered_count += 1;
                     }
+                    WALRecord::DeleteRange {
+                        table_name,
+                        start_row_id,
+                        end_row_id,
+                        txn_id,
+                    } => {
+                        if *txn_id != 0 && !committed_txns.contains(txn_id) {
+                            continue;
+                        }
+                        let table_id = table_registry.get_table_id(table_name).unwrap_or(0);
+                        let start_key = ((table_id as u64) << 32) | (*start_row_id & 0xFFFFFFFF);
+                        let end_key = start_key + end_row_id.saturating_sub(*start_row_id);
+                        let ts = recovery_lsn.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        lsm_engine.delete_range(start_key, end_key, ts)?;
+                        _recovered_count += 1;
+                    }
                     _ => {}
                 }
             }
@@ -972,14 +1112,45 @@ impl MoteDB {
             _recovered_count
         );
 
+        // Tables the replay wrote to: their rows are newer than what the last
+        // checkpoint published for their text indexes
+        let replayed_tables: std::collections::HashSet<String> = recovered_records
+            .values()
+            .flatten()
+            .filter_map(|record| match record {
+                WALRecord::Insert {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::InsertRaw {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::Update {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::UpdateRaw {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::Delete {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::DeleteRaw {
+                    table_name, txn_id, ..
+                }
+                | WALRecord::DeleteRange {
+                    table_name, txn_id, ..
+                } if *txn_id == 0 || committed_txns.contains(txn_id) => Some(table_name.clone()),
+                _ => None,
+            })
+            .collect();
+
         // Create version store and transaction coordinator
         let version_store = Arc::new(VersionStore::new());
         let txn_coordinator = Arc::new(TransactionCoordinator::new(version_store.clone()));
 
         // 🆕 Load index metadata registry first (needed for metric info)
-        let index_registry = Arc::new(crate::database::index_metadata::IndexRegistry::new(
-            &db_path,
-        ));
+        let index_registry = Arc::new(
+            crate::database::index_metadata::IndexRegistry::with_backend(&db_path, backend.clone()),
+        );
         if let Err(e) = index_registry.load() {
             debug_log!(
                 "[database] ⚠️ Failed to load index_metadata: {:?}. Indexes will need rebuild.",
@@ -988,6 +1159,15 @@ impl MoteDB {
             // Not fatal — indexes can be rebuilt, but user should be warned
         }
 
+        // Finish or roll back index rebuilds a crash interrupted
+        Self::recover_index_rebuilds(&db_path);
+
+        // Indexes whose files are not the ones the last checkpoint committed,
+        // and text indexes of tables the replay wrote to, are discarded here
+        // and rebuilt from table data once open
+        let (index_manifest, discarded_indexes) =
+            Self::open_index_manifest(&db_path, &index_registry, &replayed_tables);
+
         // Load existing vector indexes (using metric from registry)
         let vector_indexes = Self::load_vector_indexes(&db_path, &index_registry)?;
 
@@ -997,11 +1177,17 @@ impl MoteDB {
         // Load existing i-Octree indexes
         let ioctree_indexes = Self::load_ioctree_indexes(&db_path)?;
 
-        // Load existing column indexes
-        let column_indexes = Self::load_column_indexes(&db_path, &index_registry)?;
+        // Load existing column indexes; ones with pre-canonical keys are
+        // left out and rebuilt once open
+        let (column_indexes, legacy_column_indexes) =
+            Self::load_column_indexes(&db_path, &index_registry, &table_registry)?;
 
         // 🚀 P1: Create row cache (use config or default 10000)
         let row_cache = Arc::new(RowCache::new(config.row_cache_size.unwrap_or(10000)));
+        for (table_name, max_rows) in &config.row_cache_quotas {
+            row_cache.set_table_quota(table_name, Some(*max_rows));
+        }
+        row_cache.set_write_policy(config.row_cache_write_policy);
 
         // Shared row ID counter (initialized from WAL replay)
         let next_row_id = Arc::new(AtomicU64::new(max_row_id + 1));
@@ -1014,24 +1200,17 @@ impl MoteDB {
 
         // Clean up leftover .mcdb.tmp files from interrupted columnar segment writes.
         // These are safe to delete because they were never registered with a SegmentManager.
-        if columnar_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&columnar_dir) {
-                for entry in entries.flatten() {
-                    let sub_dir = entry.path();
-                    if sub_dir.is_dir() {
-                        if let Ok(sub_entries) = std::fs::read_dir(&sub_dir) {
-                            for sub_entry in sub_entries.flatten() {
-                                let path = sub_entry.path();
-                                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                                    if name.ends_with(".mcdb.tmp") {
-                                        debug_log!(
-                                            "[database] Cleaning up temp columnar segment: {:?}",
-                                            path
-                                        );
-                                        let _ = std::fs::remove_file(&path);
-                                    }
-                                }
-                            }
+        let columnar_backend = crate::storage::backend::backend_for(&columnar_dir);
+        if let Ok(sub_dirs) = columnar_backend.list_dir(&columnar_dir) {
+            for sub_dir in sub_dirs {
+                if !columnar_backend.is_dir(&sub_dir) {
+                    continue;
+                }
+                for path in columnar_backend.list_dir(&sub_dir).unwrap_or_default() {
+                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                        if name.ends_with(".mcdb.tmp") {
+                            debug_log!("[database] Cleaning up temp columnar segment: {:?}", path);
+                            let _ = columnar_backend.remove_file(&path);
                         }
                     }
                 }
@@ -1126,6 +1305,9 @@ impl MoteDB {
             wal,
             lsm_engine: lsm_engine.clone(),
             timestamp_index,
+            timestamp_reorder: config
+                .timestamp_reorder
+                .map(|r| Arc::new(parking_lot::Mutex::new(TimestampReorderBuffer::new(r)))),
             next_row_id,
             write_lsn,
             table_auto_increment: Arc::new(DashMap::new()),
@@ -1141,16 +1323,30 @@ impl MoteDB {
             columnar_sstables: Arc::new(DashMap::new()),
             columnar_write_bufs: Arc::new(DashMap::new()),
             col_segment_stores: Arc::new(DashMap::new()),
+            table_write_gates: Arc::new(DashMap::new()),
             pk_lookup: Arc::new(DashMap::new()),
             table_row_count: Arc::new(DashMap::new()),
+            retained_deletes: Arc::new(DashMap::new()),
             table_registry,
+            continuous_aggregates: Arc::default(),
+            ring_buffers: Arc::default(),
+            embedders: Arc::default(),
+            shared_scans: Arc::new(super::shared_scan::SharedScans::new(config.shared_scans)),
             index_registry,
+            index_manifest,
             row_cache,
             index_update_strategy: config.index_update_strategy.clone(),
             query_timeout_secs: config.query_timeout_secs,
             pk_lookup_capacity: config.pk_lookup_capacity,
             column_index_buffer_size: config.column_index_buffer_size,
             max_result_rows: config.max_result_rows,
+            streaming: config.streaming,
+            query_memory_limit: config.query_memory_limit,
+            copy_dir: config.copy_dir.clone(),
+            persist_cache_state: config.persist_cache_state,
+            memory_budget: config
+                .memory_limit
+                .map(|limit| Arc::new(super::memory_budget::MemoryBudget::new(limit))),
             is_flushing: Arc::new(AtomicBool::new(false)),
             is_pipeline_active: Arc::new(AtomicBool::new(false)),
             pending_index_batches: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
@@ -1162,26 +1358,29 @@ impl MoteDB {
             index_build_tx: None,
             index_builder_thread: None,
             auto_flush_thread: None,
-            _lock_file: std::sync::Mutex::new(Some(lock_file)),
+            _lock_file: std::sync::Mutex::new(lock_file),
             _is_clone: false,
+            _mount: mount,
         };
 
         // Recover ColSegmentStore: scan columnar_ms/ for table dirs, replay
         // MANIFEST, load segments. Ensures data survives restart (ACID).
         let ms_dir = db.path.join("columnar_ms");
-        if ms_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&ms_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(meta) = entry.metadata() {
-                        if meta.is_dir() {
-                            let table_name = entry.file_name().to_string_lossy().to_string();
+        let ms_backend = crate::storage::backend::backend_for(&ms_dir);
+        if ms_backend.exists(&ms_dir) {
+            if let Ok(entries) = ms_backend.list_dir(&ms_dir) {
+                for entry in entries {
+                    if let Some(name) = entry.file_name() {
+                        if ms_backend.is_dir(&entry) {
+                            let table_name = name.to_string_lossy().to_string();
                             if let Ok(schema) = db.table_registry.get_table(&table_name) {
                                 let col_types = schema.col_types().to_vec();
                                 if let Ok(store) =
-                                    crate::storage::col_segment::ColSegmentStore::create(
+                                    crate::storage::col_segment::ColSegmentStore::create_partitioned(
                                         &db.path,
                                         &table_name,
                                         col_types,
+                                        db.num_partitions,
                                     )
                                 {
                                     store.recover_from_disk();
@@ -1238,9 +1437,13 @@ impl MoteDB {
         }
 
         // 🚀 Start auto-checkpoint thread (only if config provided, matching create behavior)
-        let auto_checkpoint_thread = config
-            .auto_checkpoint
-            .map(|cfg| Self::start_auto_checkpoint_thread(db.clone_for_callback(), cfg));
+        let auto_checkpoint_thread = config.auto_checkpoint.map(|cfg| {
+            Self::start_auto_checkpoint_thread(
+                db.clone_for_callback(),
+                cfg,
+                config.wal_config.max_wal_size,
+            )
+        });
 
         db.auto_checkpoint_thread = auto_checkpoint_thread;
 
@@ -1292,9 +1495,45 @@ impl MoteDB {
             }
         }
 
+        db.finish_pending_drops();
+
+        // Continuous aggregates are rebuilt from their sources on first use
+        db.load_continuous_aggregates()?;
+        db.load_ring_buffers()?;
+        let mut discarded_indexes = discarded_indexes;
+        discarded_indexes.extend(db.rebuild_legacy_column_indexes(legacy_column_indexes));
+        db.spawn_discarded_index_rebuild(discarded_indexes);
+
+        if db.persist_cache_state {
+            if let Err(e) = db.warm_caches() {
+                warn_log!("[open] Cache warm-up failed: {:?}", e);
+            }
+        }
+
         Ok(db)
     }
 
+    /// Open a packed image (written by [`MoteDB::pack`]) read-only
+    ///
+    /// The WAL, catalog and index metadata are read straight from the
+    /// memory-mapped image. SSTables, blob files and index files are still
+    /// opened by path (see [`crate::storage::backend`]), so they are
+    /// unpacked under `work_dir` first; files already there from an earlier
+    /// open are reused. Every write fails with a permission error.
+    pub fn open_packed<P: AsRef<Path>, Q: AsRef<Path>>(image: P, work_dir: Q) -> Result<Self> {
+        use crate::storage::packed::{is_backend_file, PackedBackend, PackedImage};
+
+        let image = Arc::new(PackedImage::open(image)?);
+        let db_path = work_dir.as_ref().join("packed.mote");
+        image.unpack_to(&db_path, |rel| !is_backend_file(rel))?;
+        let config = DBConfig {
+            storage_backend: Some(Arc::new(PackedBackend::new(image, &db_path))),
+            auto_checkpoint: None,
+            ..DBConfig::default()
+        };
+        Self::open_with_config(&db_path, config)
+    }
+
     /// Pre-warm PK lookup cache by scanning SSTable data for a table.
     /// This avoids cold-start misses where every PK SELECT requires a full SSTable scan.
     fn warm_pk_cache(&self, table_name: &str, schema: &crate::types::TableSchema, pk_col: &str) {
@@ -1422,16 +1661,16 @@ impl MoteDB {
 
         // 🎯 从统一目录加载：{db}.mote/indexes/vector_*/
         let indexes_dir = db_path.join("indexes");
-        if indexes_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&indexes_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(name) = entry.file_name().into_string() {
+        let backend = crate::storage::backend::backend_for(&indexes_dir);
+        if backend.exists(&indexes_dir) {
+            if let Ok(entries) = backend.list_dir(&indexes_dir) {
+                for index_path in entries {
+                    if let Some(name) = index_path.file_name().and_then(|n| n.to_str()) {
                         if name.starts_with("vector_") {
                             let index_name = match name.strip_prefix("vector_") {
                                 Some(n) => n,
                                 None => continue,
                             };
-                            let index_path = entry.path();
 
                             // Resolve metric from metadata registry
                             let distance_kind = index_registry
@@ -1468,8 +1707,9 @@ impl MoteDB {
 
         // 🧹 Clean up legacy text_indexes_metadata.bin (no longer used)
         let legacy_metadata_path = db_path.join("text_indexes_metadata.bin");
-        if legacy_metadata_path.exists() {
-            if let Err(e) = std::fs::remove_file(&legacy_metadata_path) {
+        let legacy_backend = crate::storage::backend::backend_for(&legacy_metadata_path);
+        if legacy_backend.exists(&legacy_metadata_path) {
+            if let Err(e) = legacy_backend.remove_file(&legacy_metadata_path) {
                 debug_log!(
                     "⚠️ Failed to remove legacy text_indexes_metadata.bin: {}",
                     e
@@ -1479,18 +1719,24 @@ impl MoteDB {
             }
         }
 
-        // 🎯 从统一目录加载：{db}.mote/indexes/text_*/
+        // 🎯 从统一目录加载：{db}.mote/indexes/text_{name}.fts.d (postings)
+        // and text_{name}.dict.d (dictionary), both derived from the base
+        // path text_{name}
         let indexes_dir = db_path.join("indexes");
-        if indexes_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&indexes_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(name) = entry.file_name().into_string() {
+        let backend = crate::storage::backend::backend_for(&indexes_dir);
+        if backend.exists(&indexes_dir) {
+            if let Ok(entries) = backend.list_dir(&indexes_dir) {
+                for entry in entries {
+                    if let Some(name) = entry.file_name().and_then(|n| n.to_str()) {
                         if name.starts_with("text_") {
-                            let index_name = match name.strip_prefix("text_") {
+                            let index_name = match name
+                                .strip_prefix("text_")
+                                .and_then(|n| n.strip_suffix(".fts.d"))
+                            {
                                 Some(n) => n,
                                 None => continue,
                             };
-                            let index_path = entry.path();
+                            let index_path = indexes_dir.join(format!("text_{}", index_name));
 
                             // Try to load the index
                             if let Ok(index) = TextFTSIndex::new(index_path) {
@@ -1513,18 +1759,19 @@ impl MoteDB {
 
         // Load from {db}.mote/indexes/ioctree_*/
         let indexes_dir = db_path.join("indexes");
-        if indexes_dir.exists() {
-            if let Ok(entries) = std::fs::read_dir(&indexes_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(name) = entry.file_name().into_string() {
+        let backend = crate::storage::backend::backend_for(&indexes_dir);
+        if backend.exists(&indexes_dir) {
+            if let Ok(entries) = backend.list_dir(&indexes_dir) {
+                for entry in entries {
+                    if let Some(name) = entry.file_name().and_then(|n| n.to_str()) {
                         if name.starts_with("ioctree_") {
                             let index_name = match name.strip_prefix("ioctree_") {
                                 Some(n) => n,
                                 None => continue,
                             };
-                            let index_file = entry.path().join("ioctree.bin");
+                            let index_file = entry.join("ioctree.bin");
 
-                            if index_file.exists() {
+                            if backend.exists(&index_file) {
                                 if let Ok(index) = IOctreeIndex::load_from_path(&index_file) {
                                     indexes.insert(
                                         index_name.to_string(),
@@ -1547,20 +1794,26 @@ impl MoteDB {
     fn load_column_indexes(
         db_path: &Path,
         index_registry: &crate::database::index_metadata::IndexRegistry,
-    ) -> Result<HashMap<String, Arc<ColumnValueIndex>>> {
+        table_registry: &TableRegistry,
+    ) -> Result<(
+        HashMap<String, Arc<ColumnValueIndex>>,
+        Vec<crate::database::indexes::column::LegacyColumnIndex>,
+    )> {
         let mut indexes = HashMap::new();
+        let mut legacy = Vec::new();
         let indexes_dir = db_path.join("indexes");
-        if !indexes_dir.exists() {
-            return Ok(indexes);
+        let backend = crate::storage::backend::backend_for(&indexes_dir);
+        if !backend.exists(&indexes_dir) {
+            return Ok((indexes, legacy));
         }
-        let entries = match std::fs::read_dir(&indexes_dir) {
+        let entries = match backend.list_dir(&indexes_dir) {
             Ok(e) => e,
-            Err(_) => return Ok(indexes),
+            Err(_) => return Ok((indexes, legacy)),
         };
-        for entry in entries.flatten() {
-            let name = match entry.file_name().into_string() {
-                Ok(n) => n,
-                Err(_) => continue,
+        for entry in entries {
+            let name = match entry.file_name().and_then(|n| n.to_str()) {
+                Some(n) => n.to_string(),
+                None => continue,
             };
             // Expected pattern: column_{index_name}.idx
             if !name.starts_with("column_") || !name.ends_with(".idx") {
@@ -1573,10 +1826,13 @@ impl MoteDB {
             let (table_name, column_name) = match index_registry.resolve_index_name(&index_name) {
                 Some((t, c)) => (t, c),
                 None => {
-                    // Fallback: parse "table.column" format
+                    // Fallback: parse "table.column" (or "table.column[]") format
                     let parts: Vec<&str> = stem.splitn(2, '.').collect();
                     if parts.len() == 2 {
-                        (parts[0].to_string(), parts[1].to_string())
+                        let column = parts[1]
+                            .strip_suffix(crate::database::indexes::column::ELEMENT_INDEX_SUFFIX)
+                            .unwrap_or(parts[1]);
+                        (parts[0].to_string(), column.to_string())
                     } else {
                         debug_log!(
                             "[load_column_indexes] Skipping {}: cannot resolve table/column",
@@ -1587,11 +1843,39 @@ impl MoteDB {
                 }
             };
 
+            let col_type = table_registry
+                .get_table(&table_name)
+                .ok()
+                .and_then(|schema| schema.get_column(&column_name).map(|c| c.col_type.clone()));
+            // Writes and WHERE lookups reach an index through its
+            // "table.column" key (ARRAY element indexes: "table.column[]"),
+            // which a named index (CREATE INDEX, RENAME COLUMN) only aliases
+            let lookup_key = col_type
+                .as_ref()
+                .map(|t| {
+                    crate::database::indexes::column::column_index_key(&table_name, &column_name, t)
+                })
+                .filter(|key| *key != index_name);
             let config = crate::index::column_value::ColumnValueIndexConfig::default();
-            match ColumnValueIndex::open(entry.path(), table_name, column_name, config) {
-                Ok(index) => {
+            match ColumnValueIndex::open(entry, table_name.clone(), column_name.clone(), config) {
+                Ok(index) if index.has_legacy_keys() => {
+                    debug_log!("[MoteDB] Column index {} needs a rebuild", index_name);
+                    legacy.push(crate::database::indexes::column::LegacyColumnIndex {
+                        index_name,
+                        table_name,
+                        column_name,
+                    });
+                }
+                Ok(mut index) => {
+                    if let Some(col_type) = col_type {
+                        index = index.with_column_type(col_type);
+                    }
                     debug_log!("[MoteDB] Loaded column index: {}", index_name);
-                    indexes.insert(index_name, Arc::new(index));
+                    let index = Arc::new(index);
+                    if let Some(key) = lookup_key {
+                        indexes.entry(key).or_insert_with(|| index.clone());
+                    }
+                    indexes.insert(index_name, index);
                 }
                 Err(e) => {
                     debug_log!(
@@ -1602,7 +1886,7 @@ impl MoteDB {
                 }
             }
         }
-        Ok(indexes)
+        Ok((indexes, legacy))
     }
 
     // ==================== P1: Async Index Build Pipeline ====================
@@ -1618,6 +1902,17 @@ impl MoteDB {
         let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
 
+        if !crate::storage::platform::threads_available() {
+            return (
+                tx,
+                IndexBuilderThread {
+                    handle: None,
+                    should_stop,
+                    inline_rx: Some(Mutex::new(rx)),
+                },
+            );
+        }
+
         let handle = std::thread::Builder::new()
             .name("index-builder".into())
             .spawn(move || {
@@ -1625,40 +1920,7 @@ impl MoteDB {
                 while !should_stop_clone.load(std::sync::atomic::Ordering::Acquire) {
                     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         match rx.recv_timeout(std::time::Duration::from_millis(100)) {
-                            Ok(batch) => {
-                                // Drop guard ensures pending_index_batches is ALWAYS decremented,
-                                // even if batch_build_table_indexes_raw panics.
-                                struct BatchGuard {
-                                    pending: std::sync::Arc<std::sync::atomic::AtomicUsize>,
-                                }
-                                impl Drop for BatchGuard {
-                                    fn drop(&mut self) {
-                                        self.pending
-                                            .fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
-                                    }
-                                }
-                                let _guard = BatchGuard {
-                                    pending: db.pending_index_batches.clone(),
-                                };
-
-                                for (table_name, raw_rows) in &batch.tables_data {
-                                    if let Err(e) =
-                                        db.batch_build_table_indexes_raw(table_name, raw_rows)
-                                    {
-                                        warn_log!(
-                                            "[IndexBuilder] Index build failed for '{}': {:?}",
-                                            table_name,
-                                            e
-                                        );
-                                        db.index_build_errors
-                                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                    }
-                                }
-                                debug_log!(
-                                    "[IndexBuilder] Processed batch ({} tables)",
-                                    batch.tables_data.len()
-                                );
-                            }
+                            Ok(batch) => Self::build_index_batch(&db, batch),
                             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
                             Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
                                 debug_log!("[IndexBuilder] Channel disconnected, exiting");
@@ -1689,10 +1951,64 @@ impl MoteDB {
             IndexBuilderThread {
                 handle: Some(handle),
                 should_stop,
+                inline_rx: None,
             },
         )
     }
 
+    /// Build the indexes for one flushed batch
+    fn build_index_batch(db: &Self, batch: IndexBuildBatch) {
+        // Drop guard ensures pending_index_batches is ALWAYS decremented,
+        // even if batch_build_table_indexes_raw panics.
+        struct BatchGuard {
+            pending: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        }
+        impl Drop for BatchGuard {
+            fn drop(&mut self) {
+                self.pending
+                    .fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+        let _guard = BatchGuard {
+            pending: db.pending_index_batches.clone(),
+        };
+
+        for (table_name, raw_rows) in &batch.tables_data {
+            if let Err(e) = db.batch_build_table_indexes_raw(table_name, raw_rows) {
+                warn_log!(
+                    "[IndexBuilder] Index build failed for '{}': {:?}",
+                    table_name,
+                    e
+                );
+                db.index_build_errors
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+        debug_log!(
+            "[IndexBuilder] Processed batch ({} tables)",
+            batch.tables_data.len()
+        );
+    }
+
+    /// Build the batches queued while the target has no threads. Called
+    /// after each flush and before waiting on index readiness.
+    pub(crate) fn drain_index_batches(&self) {
+        let Some(rx) = self
+            .index_builder_thread
+            .as_ref()
+            .and_then(|t| t.inline_rx.as_ref())
+        else {
+            return;
+        };
+        loop {
+            let batch = rx.lock().unwrap_or_else(|e| e.into_inner()).try_recv();
+            match batch {
+                Ok(batch) => Self::build_index_batch(self, batch),
+                Err(_) => break,
+            }
+        }
+    }
+
     /// Extract rows from a flushed memtable and send through the channel.
     ///
     /// This is the LSM flush callback. It only extracts and sends —
@@ -1775,6 +2091,15 @@ impl MoteDB {
         let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
 
+        // Without threads `request_auto_flush` flushes on the writer's thread
+        if !crate::storage::platform::threads_available() {
+            return AutoFlushThread {
+                flush_tx,
+                handle: None,
+                should_stop,
+            };
+        }
+
         let handle = std::thread::Builder::new()
             .name("motedb-auto-flush".into())
             .spawn(move || {
@@ -1819,28 +2144,95 @@ impl MoteDB {
         }
     }
 
+    /// Backend for the database files, mounted over `db_path` when it is not
+    /// the plain host filesystem: a configured backend, wrapped in a
+    /// [`SyncPolicyBackend`](crate::storage::SyncPolicyBackend) when
+    /// `config.fsync` relaxes some component.
+    fn mount_backend(
+        db_path: &Path,
+        config: &DBConfig,
+    ) -> (
+        Arc<dyn crate::storage::StorageBackend>,
+        Option<Arc<crate::storage::backend::Mount>>,
+    ) {
+        let backend = config
+            .storage_backend
+            .clone()
+            .unwrap_or_else(crate::storage::backend::default_backend);
+        if config.fsync.is_always() {
+            let mount = config
+                .storage_backend
+                .clone()
+                .map(|b| Arc::new(crate::storage::backend::mount(db_path, b)));
+            return (backend, mount);
+        }
+        let backend: Arc<dyn crate::storage::StorageBackend> = Arc::new(
+            crate::storage::SyncPolicyBackend::new(backend, db_path, config.fsync),
+        );
+        let mount = Arc::new(crate::storage::backend::mount(db_path, backend.clone()));
+        (backend, Some(mount))
+    }
+
     /// Request an auto-flush via the background thread (non-blocking).
     /// Returns false if the channel is disconnected (thread died).
     pub(crate) fn request_auto_flush(&self) -> bool {
-        if let Some(ref t) = self.auto_flush_thread {
-            t.flush_tx.send(()).is_ok()
-        } else {
-            false
+        match self.auto_flush_thread {
+            Some(ref t) if t.handle.is_none() => {
+                self.auto_flush_inline();
+                true
+            }
+            Some(ref t) => t.flush_tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Auto-flush on the writer's thread when the target has no threads,
+    /// followed by the auto-checkpoint if the WAL has outgrown its limit.
+    /// Skipped when the caller is itself inside a flush or checkpoint.
+    fn auto_flush_inline(&self) {
+        if self.checkpoint_mutex.try_lock().is_err() {
+            return;
+        }
+        if let Err(e) = self.flush() {
+            warn_log!("[AutoFlush] Flush failed: {}", e);
+            self.flush_errors
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        let trigger = self.auto_checkpoint_thread.as_ref().and_then(|t| t.inline);
+        if let Some((config, max_wal_size)) = trigger {
+            if self.wal.size_bytes() >= config.max_wal_size_bytes.min(max_wal_size) {
+                if let Err(e) = self.checkpoint() {
+                    warn_log!("[AutoCheckpoint] Checkpoint failed: {:?}", e);
+                }
+            }
         }
     }
 
     /// 2. Adaptive sleep: Longer intervals in low-activity periods
     /// 3. Zero allocation in hot path
     /// 4. Minimal CPU usage: < 0.1% CPU overhead
+    ///
+    /// `max_wal_size` (`WALConfig::max_wal_size`) is a hard bound: once the
+    /// WAL reaches it, the checkpoint runs at the next 100ms tick without
+    /// waiting for `min_interval_secs`.
     fn start_auto_checkpoint_thread(
         db: Self,
         config: crate::config::AutoCheckpointConfig,
+        max_wal_size: u64,
     ) -> AutoCheckpointThread {
         use std::time::{Duration, Instant};
 
         let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
 
+        if !crate::storage::platform::threads_available() {
+            return AutoCheckpointThread {
+                handle: None,
+                should_stop,
+                inline: Some((config, max_wal_size)),
+            };
+        }
+
         let handle = std::thread::spawn(move || {
             let mut last_checkpoint = Instant::now();
 
@@ -1861,11 +2253,16 @@ impl MoteDB {
                 // 🚀 **CRITICAL FIX**: Use interruptible sleep (check every 1s)
                 // This allows fast shutdown when Drop is called
                 let mut remaining = check_interval;
+                let mut over_limit = false;
                 while remaining > Duration::ZERO {
                     if should_stop_clone.load(std::sync::atomic::Ordering::Acquire) {
                         debug_log!("[AutoCheckpoint] 🛑 Shutdown signal received during sleep");
                         break;
                     }
+                    if db.wal.size_bytes() >= max_wal_size {
+                        over_limit = true;
+                        break;
+                    }
 
                     // Use 100ms chunks so shutdown is responsive (was 1s)
                     let sleep_chunk = Duration::from_millis(100).min(remaining);
@@ -1881,33 +2278,27 @@ impl MoteDB {
                 // 🚀 Only check WAL size when enough time has passed
                 // (avoids unnecessary filesystem calls)
                 let elapsed = last_checkpoint.elapsed();
-                if elapsed.as_secs() < config.min_interval_secs {
+                if !over_limit && elapsed.as_secs() < config.min_interval_secs {
                     continue;
                 }
 
                 // 🚀 Lazy WAL size check - only when needed
-                let wal_dir = db.path.join("wal");
-                match super::helpers::dir_size(&wal_dir) {
-                    Ok(wal_size) if wal_size >= config.max_wal_size_bytes => {
-                        debug_log!(
-                            "[AutoCheckpoint] 🔔 Trigger: WAL {}MB >= {}MB",
-                            wal_size / 1024 / 1024,
-                            config.max_wal_size_bytes / 1024 / 1024
-                        );
+                let wal_size = db.wal.size_bytes();
+                if over_limit || wal_size >= config.max_wal_size_bytes {
+                    debug_log!(
+                        "[AutoCheckpoint] 🔔 Trigger: WAL {}MB >= {}MB",
+                        wal_size / 1024 / 1024,
+                        config.max_wal_size_bytes.min(max_wal_size) / 1024 / 1024
+                    );
 
-                        // Trigger checkpoint
-                        if let Err(e) = db.checkpoint() {
-                            debug_log!("[AutoCheckpoint] ⚠️  Checkpoint failed: {:?}", e);
-                        } else {
-                            debug_log!("[AutoCheckpoint] ✅ Checkpoint complete");
-                            last_checkpoint = Instant::now();
-                        }
-                    }
-                    Ok(_) => {
-                        // WAL size below threshold, skip checkpoint
-                    }
-                    Err(_e) => {
-                        debug_log!("[AutoCheckpoint] ⚠️  Failed to check WAL size: {:?}", _e);
+                    // Trigger checkpoint
+                    if let Err(e) = db.checkpoint() {
+                        warn_log!("[AutoCheckpoint] Checkpoint failed: {:?}", e);
+                        // Keep a failing checkpoint from spinning at the hard bound
+                        std::thread::sleep(Duration::from_secs(1));
+                    } else {
+                        debug_log!("[AutoCheckpoint] ✅ Checkpoint complete");
+                        last_checkpoint = Instant::now();
                     }
                 }
             }
@@ -1918,6 +2309,7 @@ impl MoteDB {
         AutoCheckpointThread {
             handle: Some(handle),
             should_stop,
+            inline: None,
         }
     }
 
@@ -1991,6 +2383,13 @@ impl MoteDB {
         }
     }
 
+    /// Whether the database takes the host `.lock` file. wasm hosts have no
+    /// `flock`, so there a database on a configured backend (e.g.
+    /// `storage::HostVfsBackend`) never touches the host filesystem.
+    fn needs_host_lock(config: &DBConfig) -> bool {
+        !(cfg!(target_family = "wasm") && config.storage_backend.is_some())
+    }
+
     /// Acquire an exclusive file lock on the database directory.
     ///
     /// Creates a `.lock` file and acquires an exclusive `flock`.
@@ -2066,38 +2465,20 @@ impl MoteDB {
     /// Survives clock regression across restarts.
     pub(crate) fn persist_lsn_counter(db_path: &Path, lsn: u64) {
         let path = db_path.join("lsn_counter");
-        match std::fs::File::create(&path) {
-            Ok(mut file) => {
-                if let Err(e) = file.write_all(&lsn.to_le_bytes()) {
-                    warn_log!(
-                        "[persist_lsn_counter] write failed {}: {}",
-                        path.display(),
-                        e
-                    );
-                    return;
-                }
-                if let Err(e) = file.sync_all() {
-                    warn_log!(
-                        "[persist_lsn_counter] fsync failed {}: {}",
-                        path.display(),
-                        e
-                    );
-                }
-            }
-            Err(e) => {
-                warn_log!(
-                    "[persist_lsn_counter] create failed {}: {}",
-                    path.display(),
-                    e
-                );
-            }
+        let backend = crate::storage::backend::backend_for(&path);
+        if let Err(e) = backend.write_atomic(&path, &lsn.to_le_bytes()) {
+            warn_log!(
+                "[persist_lsn_counter] write failed {}: {}",
+                path.display(),
+                e
+            );
         }
     }
 
     /// Load the persisted LSN counter. Returns 0 if the file doesn't exist.
     fn load_lsn_counter(db_path: &Path) -> u64 {
         let path = db_path.join("lsn_counter");
-        match std::fs::read(&path) {
+        match crate::storage::backend::backend_for(&path).read(&path) {
             Ok(data) if data.len() >= 8 => {
                 u64::from_le_bytes(data[..8].try_into().unwrap_or([0u8; 8]))
             }
@@ -2176,6 +2557,13 @@ impl Drop for MoteDB {
         //     warn_log!("[Drop] Columnar store flush failed: {:?}", e);
         // }
 
+        // close() already saved the cache state before compacting segments
+        if self.persist_cache_state && !self.is_closed.load(std::sync::atomic::Ordering::Acquire) {
+            if let Err(e) = self.save_cache_state() {
+                warn_log!("[Drop] Saving cache state failed: {:?}", e);
+            }
+        }
+
         if let Err(e) = self.checkpoint_on_drop() {
             warn_log!("[Drop] Final checkpoint failed: {:?}", e);
             warn_log!("[Drop] WAL files may not be cleaned up");
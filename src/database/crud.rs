@@ -5,11 +5,12 @@
 //! # Features
 //! - Row-level operations (insert_row, get_row, update_row, delete_row)
 //! - Table-aware operations (insert_row_to_table, get_table_row, etc.)
-//! - Batch operations (batch_insert_rows, batch_get_rows)
+//! - Batch operations (batch_insert_rows, batch_get_rows, insert_rows_iter)
 //! - Scan operations (scan_all_rows, scan_table_rows)
 //! - Prefetching and caching for sequential access
 
 use super::core::MoteDB;
+use super::indexes::column::ELEMENT_INDEX_SUFFIX;
 use crate::storage::row_format;
 use crate::txn::wal::WALRecord;
 use crate::types::{ColumnType, PartitionId, Row, RowId, Value};
@@ -17,12 +18,29 @@ use crate::{Result, StorageError};
 use std::collections::HashSet;
 use std::sync::Arc;
 
+/// Rows [`MoteDB::insert_rows_iter`] collects from its iterator per batch
+pub const INSERT_ITER_CHUNK: usize = 4096;
+
 /// Extract column types from a table schema for RawRow encoding.
 /// Deserialize a row, trying RawRow first (with schema) and falling back to bincode.
 fn deserialize_row(data: &[u8], col_types: &[ColumnType]) -> crate::Result<Row> {
     row_format::decode(data, col_types)
 }
 
+/// row_id of a row keyed by an integer primary key.
+///
+/// 🔑 Negative values map to the high u32 range (0x80000000 + |pk_val|) so
+/// they never collide with auto-assigned row_ids from next_row_id (which
+/// starts at 0). Without this, a negative PK like -90 would get row_id=0,
+/// colliding with PK=0's row_id=0, causing data loss during flush dedup.
+pub(crate) fn pk_row_id(pk_val: i64) -> RowId {
+    if pk_val >= 0 {
+        pk_val as RowId
+    } else {
+        0x8000_0000u64 | (pk_val as u64 & 0x7FFF_FFFF)
+    }
+}
+
 impl MoteDB {
     // ==================== Table-Aware CRUD Operations ====================
 
@@ -39,10 +57,37 @@ impl MoteDB {
     ///     Value::Text("Alice".into()),
     /// ])?;
     /// ```ignore
-    pub fn insert_row_to_table(&self, table_name: &str, mut row: Row) -> Result<RowId> {
+    pub fn insert_row_to_table(&self, table_name: &str, row: Row) -> Result<RowId> {
+        let ring_bytes = self.ring_buffer_row_bytes(table_name, std::slice::from_ref(&row));
+        let row_id = if !self.has_continuous_aggregates(table_name) {
+            self.insert_row_to_table_inner(table_name, row)?
+        } else {
+            let rows = [row.clone()];
+            self.maintain_continuous_aggregates(table_name, &rows, || {
+                self.insert_row_to_table_inner(table_name, row)
+            })?
+        };
+        if let Some(bytes) = ring_bytes {
+            self.ring_buffer_track(table_name, &[row_id], &bytes);
+            self.evict_ring_buffer(table_name)?;
+        }
+        Ok(row_id)
+    }
+
+    fn insert_row_to_table_inner(&self, table_name: &str, mut row: Row) -> Result<RowId> {
         ensure_open!(self);
+        let gate = self.table_write_gate(table_name);
+        let _writes = gate.read_recursive();
         // 1. Get table schema
         let schema = self.table_registry.get_table(table_name)?;
+        super::generated::fill_generated_columns(&schema, std::slice::from_mut(&mut row))?;
+        self.apply_embedders(&schema, std::slice::from_mut(&mut row))?;
+        schema.coerce_row(&mut row).map_err(|e| {
+            StorageError::InvalidData(format!(
+                "Row validation failed for table '{}': {}",
+                table_name, e
+            ))
+        })?;
 
         // 1.5 Check primary key uniqueness for non-AUTO_INCREMENT tables
         if !schema.is_primary_key_auto_increment() {
@@ -230,24 +275,11 @@ impl MoteDB {
             // (table_id << 32) | pk_value, enabling O(log N) RowMap binary
             // search for point queries (WHERE pk = value) without a secondary
             // index. Falls back to global row_id for non-integer or NULL PKs.
-            //
-            // 🔑 For negative PK values, map to the high u32 range
-            // (0x80000000 + |pk_val|) so they never collide with auto-assigned
-            // row_ids from next_row_id (which starts at 0). Without this, a
-            // negative PK like -90 would get row_id=0, colliding with PK=0's
-            // row_id=0, causing data loss during flush dedup.
             if let Some(pk_col_name) = schema.primary_key() {
                 if let Some(pk_col) = schema.get_column(pk_col_name) {
                     if matches!(pk_col.col_type, crate::types::ColumnType::Integer) {
                         if let Some(Value::Integer(pk_val)) = row.get(pk_col.position) {
-                            if *pk_val >= 0 {
-                                *pk_val as RowId
-                            } else {
-                                // Map negative PK to high u32 range to avoid
-                                // collision with next_row_id values (0, 1, 2...).
-                                // 0x80000000 + |pk_val| keeps it unique per PK value.
-                                0x8000_0000u64 | (*pk_val as u64 & 0x7FFF_FFFF)
-                            }
+                            pk_row_id(*pk_val)
                         } else {
                             self.next_row_id
                                 .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
@@ -272,10 +304,8 @@ impl MoteDB {
 
         // 5. Encode row to raw bytes (shared between WAL and LSM — zero-copy recovery)
         let col_types = schema.col_types();
-        let row_data = row_format::encode(&row, col_types).or_else(|_| {
-            bincode::serialize(&row)
-                .map_err(|e| StorageError::Serialization(format!("Row encode failed: {}", e)))
-        })?;
+        let row_data = row_format::encode(&row, col_types)
+            .unwrap_or_else(|_| crate::storage::value_codec::encode_row(&row));
 
         // 6. Increment pending counter BEFORE WAL write (checkpoint uses this as barrier)
         self.increment_pending_updates();
@@ -288,8 +318,6 @@ impl MoteDB {
         let ts = self
             .write_lsn
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        // 🔑 PERF: use put_ref (&str) to avoid table_name.to_string() allocation.
-        self.row_cache.put_ref(table_name, row_id, row.clone());
 
         // 🚀 Columnar write buffer (zero-encode path).
         // 🔑 PERF: Skip the legacy columnar_write_bufs for ColSegmentStore tables
@@ -306,7 +334,9 @@ impl MoteDB {
                 Entry::Occupied(o) => o.get().clone(),
                 Entry::Vacant(v) => {
                     let indexes_dir = self.path.join("indexes");
-                    std::fs::create_dir_all(&indexes_dir).ok();
+                    crate::storage::backend::backend_for(&indexes_dir)
+                        .create_dir_all(&indexes_dir)
+                        .ok();
                     let path = indexes_dir.join(format!("{}_col.sst", table_name));
                     let b = Arc::new(parking_lot::Mutex::new(
                         crate::storage::lsm::columnar::ColumnarSSTableBuilder::new(
@@ -347,6 +377,7 @@ impl MoteDB {
                 crate::purge_memory_to_os();
             }
         }
+        self.row_cache.apply_write(table_name, row_id, Some(&row));
 
         // 7. Update indexes
         {
@@ -381,23 +412,24 @@ impl MoteDB {
                     index_key_buf.push_str(table_name);
                     index_key_buf.push('.');
                     index_key_buf.push_str(col_name);
+                    if matches!(col_def.col_type, ColumnType::Array(_)) {
+                        index_key_buf.push_str(ELEMENT_INDEX_SUFFIX);
+                    }
+                    // NULLs go to the index's NULL bucket (for IS NULL lookups)
                     if let Some(index_ref) = self.column_indexes.get(&index_key_buf) {
-                        // NULL values are valid SQL but not indexable — skip silently
-                        if !matches!(col_value, Value::Null) {
-                            if let Err(_e) = index_ref.value().insert(col_value, row_id) {
-                                debug_log!(
-                                    "[insert_row] Failed to update column index '{}': {}",
-                                    col_name,
-                                    _e
-                                );
-                                index_errors.push(index_key_buf.clone());
-                            }
+                        if let Err(_e) = index_ref.value().insert(col_value, row_id) {
+                            debug_log!(
+                                "[insert_row] Failed to update column index '{}': {}",
+                                col_name,
+                                _e
+                            );
+                            index_errors.push(index_key_buf.clone());
                         }
                     }
                 }
 
                 // 7.2 Vector Index
-                if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+                if col_def.col_type.vector_dim().is_some() {
                     if let Some(index_name) = self.index_registry.find_by_column(
                         table_name,
                         col_name,
@@ -515,6 +547,7 @@ impl MoteDB {
             }
             return Ok(Some((*row_arc).clone()));
         }
+        let epoch = self.row_cache.fill_epoch();
 
         // 🆕 S9: ColSegmentStore cached point lookup — FIRST after row_cache.
         // Uses per-segment column decode cache (get_row_cached), so repeated
@@ -527,7 +560,7 @@ impl MoteDB {
             if let Some(row) = store.get(composite_key) {
                 let row_arc = Arc::new(row);
                 self.row_cache
-                    .put_arc(table_name.to_string(), row_id, Arc::clone(&row_arc));
+                    .fill(table_name, row_id, Arc::clone(&row_arc), epoch);
                 return Ok(Some(
                     Arc::try_unwrap(row_arc).unwrap_or_else(|a| (*a).clone()),
                 ));
@@ -554,7 +587,7 @@ impl MoteDB {
             if let Some(row) = col_sst.get_row(key, schema.col_types()) {
                 let row_arc = Arc::new(row);
                 self.row_cache
-                    .put_arc(table_name.to_string(), row_id, Arc::clone(&row_arc));
+                    .fill(table_name, row_id, Arc::clone(&row_arc), epoch);
                 return Ok(Some(
                     Arc::try_unwrap(row_arc).unwrap_or_else(|a| (*a).clone()),
                 ));
@@ -593,7 +626,7 @@ impl MoteDB {
 
             let row_arc = Arc::new(row);
             self.row_cache
-                .put_arc(table_name.to_string(), row_id, Arc::clone(&row_arc));
+                .fill(table_name, row_id, Arc::clone(&row_arc), epoch);
 
             if let Some((next_row_id, count, stride)) =
                 self.row_cache.check_prefetch(table_name, row_id)
@@ -626,6 +659,7 @@ impl MoteDB {
             }
             return Ok(Some(row_arc));
         }
+        let epoch = self.row_cache.fill_epoch();
 
         // 🆕 S9: Check ColSegmentStore FIRST (before LSM) — ColSegmentStore
         // tables store data in segments, not in LSM. Without this check,
@@ -635,7 +669,7 @@ impl MoteDB {
             if let Some(row) = store.get(composite_key) {
                 let row_arc = Arc::new(row);
                 self.row_cache
-                    .put_arc(table_name.to_string(), row_id, Arc::clone(&row_arc));
+                    .fill(table_name, row_id, Arc::clone(&row_arc), epoch);
                 return Ok(Some(row_arc));
             }
             // Not in any segment — fall through to LSM (some tables have
@@ -662,13 +696,48 @@ impl MoteDB {
             })?;
             let row_arc = Arc::new(row);
             self.row_cache
-                .put_arc(table_name.to_string(), row_id, Arc::clone(&row_arc));
+                .fill(table_name, row_id, Arc::clone(&row_arc), epoch);
             Ok(Some(row_arc))
         } else {
             Ok(None)
         }
     }
 
+    /// Read only the columns at `columns` (schema positions) of a row.
+    ///
+    /// On ColSegmentStore tables the other columns are never decoded, so
+    /// fetching an embedding does not pay for the row's text and scalar
+    /// columns. The result is not put in the row cache; a cached row is
+    /// used when present. Other tables fall back to a full-row read (which
+    /// resolves blob-stored rows).
+    pub fn get_table_columns(
+        &self,
+        table_name: &str,
+        row_id: RowId,
+        columns: &[usize],
+    ) -> Result<Option<Row>> {
+        ensure_open!(self);
+        let pick = |row: &Row| -> Row {
+            columns
+                .iter()
+                .map(|&ci| row.get(ci).cloned().unwrap_or(Value::Null))
+                .collect()
+        };
+        if let Some(row_arc) = self.row_cache.get_fast(table_name, row_id) {
+            return Ok(Some(pick(&row_arc)));
+        }
+        if let Some(store) = self.col_segment_stores.get(table_name) {
+            let composite_key = self.make_composite_key(table_name, row_id);
+            if let Some(values) = store.get_columns(composite_key, columns) {
+                return Ok(Some(values));
+            }
+        }
+        let schema = self.table_registry.get_table(table_name)?;
+        Ok(self
+            .get_table_row_with_schema(table_name, row_id, &schema)?
+            .map(|row| pick(&row)))
+    }
+
     /// Read a row with MVCC snapshot isolation. For transactional reads, the version
     /// store's `get_visible_version` is consulted to filter out rows that are not yet
     /// committed under the given snapshot. Rows that were inserted via the auto-commit
@@ -743,16 +812,24 @@ impl MoteDB {
         table_name: &str,
         row_id: RowId,
         old_row: &Row,
-        new_row: Row,
+        mut new_row: Row,
         schema: &crate::types::TableSchema,
     ) -> Result<()> {
         ensure_open!(self);
+        let gate = self.table_write_gate(table_name);
+        let _writes = gate.read_recursive();
+        self.invalidate_continuous_aggregates(table_name);
         // 🔑 Validate the new row against schema (same as INSERT/batch INSERT).
         // Without this, UPDATE t SET int_col = 3.5 bypasses type checking
         // and stores a Float bit pattern as Integer → garbage on read.
-        schema.validate_row(&new_row).map_err(|e| {
-            StorageError::InvalidData(format!("UPDATE row validation failed: {}", e))
-        })?;
+        super::generated::fill_generated_columns(schema, std::slice::from_mut(&mut new_row))?;
+        self.apply_embedders_on_update(schema, old_row, &mut new_row)?;
+        schema
+            .coerce_row(&mut new_row)
+            .and_then(|_| schema.validate_row(&new_row))
+            .map_err(|e| {
+                StorageError::InvalidData(format!("UPDATE row validation failed: {}", e))
+            })?;
 
         // 1. Check PK uniqueness if primary key is being changed
         if !schema.is_primary_key_auto_increment() {
@@ -790,14 +867,10 @@ impl MoteDB {
 
         // 4. Encode rows to raw bytes
         let col_types = schema.col_types();
-        let raw_old = row_format::encode(old_row, col_types).or_else(|_| {
-            bincode::serialize(&old_row)
-                .map_err(|e| StorageError::Serialization(format!("Row encode failed: {}", e)))
-        })?;
-        let raw_new = row_format::encode(&new_row, col_types).or_else(|_| {
-            bincode::serialize(&new_row)
-                .map_err(|e| StorageError::Serialization(format!("Row encode failed: {}", e)))
-        })?;
+        let raw_old = row_format::encode(old_row, col_types)
+            .unwrap_or_else(|_| crate::storage::value_codec::encode_row(old_row));
+        let raw_new = row_format::encode(&new_row, col_types)
+            .unwrap_or_else(|_| crate::storage::value_codec::encode_row(&new_row));
 
         // 5. Increment pending counter BEFORE WAL write (checkpoint barrier)
         self.increment_pending_updates();
@@ -810,8 +883,9 @@ impl MoteDB {
         let timestamp = self
             .write_lsn
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.row_cache
-            .put(table_name.to_string(), row_id, new_row.clone());
+
+        // Row id the new version is stored under (moves with an integer PK)
+        let mut stored_row_id = row_id;
 
         // Add new row to columnar buffer (create if first write to this table)
         {
@@ -850,6 +924,7 @@ impl MoteDB {
                             };
                             let new_key = (table_id << 32) | (new_row_id & 0xFFFFFFFF);
                             store.append_row_ref(new_key, timestamp, &new_row)?;
+                            stored_row_id = new_row_id;
                         } else {
                             // Non-integer PK — fall back to old behavior.
                             let key = (table_id << 32) | (row_id & 0xFFFFFFFF);
@@ -879,7 +954,9 @@ impl MoteDB {
                     Entry::Occupied(o) => o.get().clone(),
                     Entry::Vacant(v) => {
                         let indexes_dir = self.path.join("indexes");
-                        std::fs::create_dir_all(&indexes_dir).ok();
+                        crate::storage::backend::backend_for(&indexes_dir)
+                            .create_dir_all(&indexes_dir)
+                            .ok();
                         let col_sst_path = indexes_dir.join(format!("{}_col.sst", table_name));
                         let b = Arc::new(parking_lot::Mutex::new(
                             crate::storage::lsm::columnar::ColumnarSSTableBuilder::new(
@@ -897,6 +974,14 @@ impl MoteDB {
             }
         }
 
+        // Cache only after the store write, so a concurrent reader can't
+        // cache the old row. A moved row must not stay cached at its old id.
+        if stored_row_id != row_id {
+            self.row_cache.apply_write(table_name, row_id, None);
+        }
+        self.row_cache
+            .apply_write(table_name, stored_row_id, Some(&new_row));
+
         // 6. Update indexes. Collect failures, then mark ALL stale consistently.
         let mut index_errors = Vec::new();
 
@@ -919,51 +1004,27 @@ impl MoteDB {
                 index_key_buf.push_str(table_name);
                 index_key_buf.push('.');
                 index_key_buf.push_str(col_name);
+                if matches!(col_def.col_type, ColumnType::Array(_)) {
+                    index_key_buf.push_str(ELEMENT_INDEX_SUFFIX);
+                }
             }
             if let Some(index_ref) = self.column_indexes.get(&index_key_buf) {
                 let index = index_ref.value();
-                let old_is_null = old_value.is_none() || matches!(old_value, Some(Value::Null));
-                let new_is_null = new_value.is_none() || matches!(new_value, Some(Value::Null));
-
-                if !old_is_null && !new_is_null {
-                    if let (Some(old_val), Some(new_val)) = (old_value, new_value) {
-                        if let Err(_e) = index.update(old_val, new_val, row_id) {
-                            debug_log!(
-                                "[update_row] Failed to update column index '{}': {}",
-                                col_name,
-                                _e
-                            );
-                            index_errors.push(index_key_buf.clone());
-                        }
-                    }
-                } else if !old_is_null && new_is_null {
-                    if let Some(old_val) = old_value {
-                        if let Err(_e) = index.delete(old_val, row_id) {
-                            debug_log!(
-                                "[update_row] Failed to delete column index '{}': {}",
-                                col_name,
-                                _e
-                            );
-                            index_errors.push(index_key_buf.clone());
-                        }
-                    }
-                } else if old_is_null && !new_is_null {
-                    if let Some(new_val) = new_value {
-                        if let Err(_e) = index.insert(new_val, row_id) {
-                            debug_log!(
-                                "[update_row] Failed to insert column index '{}': {}",
-                                col_name,
-                                _e
-                            );
-                            index_errors.push(index_key_buf.clone());
-                        }
-                    }
+                // A missing value is NULL, which has its own index bucket
+                let old_val = old_value.unwrap_or(&Value::Null);
+                let new_val = new_value.unwrap_or(&Value::Null);
+                if let Err(_e) = index.update(old_val, new_val, row_id) {
+                    debug_log!(
+                        "[update_row] Failed to update column index '{}': {}",
+                        col_name,
+                        _e
+                    );
+                    index_errors.push(index_key_buf.clone());
                 }
-                // NULL -> NULL: no index change needed
             }
 
             // 6.2 Vector Index
-            if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+            if col_def.col_type.vector_dim().is_some() {
                 if let Some(index_name) = self.index_registry.find_by_column(
                     table_name,
                     col_name,
@@ -1111,6 +1172,9 @@ impl MoteDB {
         old_row: Row,
     ) -> Result<()> {
         ensure_open!(self);
+        let gate = self.table_write_gate(table_name);
+        let _writes = gate.read_recursive();
+        self.invalidate_continuous_aggregates(table_name);
         // 1. Get schema (old_row is now passed in to avoid re-loading)
         let schema = self.table_registry.get_table(table_name)?;
 
@@ -1130,13 +1194,12 @@ impl MoteDB {
         //    point below can be recovered correctly.
         // 5. Write to WAL first (durability guarantee) — raw bytes
         let col_types = schema.col_types();
-        let raw_old = row_format::encode(&old_row, col_types).or_else(|_| {
-            bincode::serialize(&old_row)
-                .map_err(|e| StorageError::Serialization(format!("Row encode failed: {}", e)))
-        })?;
+        let raw_old = row_format::encode(&old_row, col_types)
+            .unwrap_or_else(|_| crate::storage::value_codec::encode_row(&old_row));
         self.increment_pending_updates();
         self.wal
             .log_delete_raw(table_name, partition, composite_key, raw_old, timestamp, 0)?;
+        self.retain_deleted_rows(table_name, [(row_id, &old_row)]);
 
         // 🚀 Columnar tombstone is the source of truth. LSM delete removed.
         // Columnar tombstone below marks the row deleted in all reads.
@@ -1146,7 +1209,9 @@ impl MoteDB {
                 Entry::Occupied(o) => o.get().clone(),
                 Entry::Vacant(v) => {
                     let indexes_dir = self.path.join("indexes");
-                    std::fs::create_dir_all(&indexes_dir).ok();
+                    crate::storage::backend::backend_for(&indexes_dir)
+                        .create_dir_all(&indexes_dir)
+                        .ok();
                     let col_sst_path = indexes_dir.join(format!("{}_col.sst", table_name));
                     let b = Arc::new(parking_lot::Mutex::new(
                         crate::storage::lsm::columnar::ColumnarSSTableBuilder::new(
@@ -1191,7 +1256,8 @@ impl MoteDB {
         }
 
         // Invalidate cache AFTER LSM write — single invalidation
-        self.row_cache.invalidate(table_name, row_id);
+        self.row_cache.apply_write(table_name, row_id, None);
+        self.ring_buffer_untrack(table_name, row_id);
 
         // 7.1 Decrement row count for COUNT(*) fast path
         // Use saturating subtract via fetch_update to avoid both underflow
@@ -1238,6 +1304,9 @@ impl MoteDB {
             col_index_key.push_str(table_name);
             col_index_key.push('.');
             col_index_key.push_str(col_name);
+            if matches!(col_def.col_type, ColumnType::Array(_)) {
+                col_index_key.push_str(ELEMENT_INDEX_SUFFIX);
+            }
             if let Some(index_ref) = self.column_indexes.get(&col_index_key) {
                 if let Err(_e) = index_ref.value().delete(col_value, row_id) {
                     debug_log!(
@@ -1250,7 +1319,7 @@ impl MoteDB {
             }
 
             // Vector Index
-            if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+            if col_def.col_type.vector_dim().is_some() {
                 if let Some(index_name) = self.index_registry.find_by_column(
                     table_name,
                     col_name,
@@ -1431,6 +1500,21 @@ impl MoteDB {
     /// println!("Total rows: {}", count);
     /// ```
     pub fn scan_table_rows_streaming(&self, table_name: &str) -> Result<TableRowStreamingIterator> {
+        self.scan_table_columns_streaming(table_name, None)
+    }
+
+    /// Streaming scan that reads only the columns at `columns` (schema
+    /// positions); the others read NULL. `None` reads every column.
+    ///
+    /// Tables are stored column by column, so unrequested columns are never
+    /// loaded: their column segments are not read or decompressed at all.
+    /// Rows still in the LSM are decoded with
+    /// [`TableRowStreamingIterator::with_projection`].
+    pub fn scan_table_columns_streaming(
+        &self,
+        table_name: &str,
+        columns: Option<&[usize]>,
+    ) -> Result<TableRowStreamingIterator> {
         ensure_open!(self);
         let schema = self.table_registry.get_table(table_name)?;
         let col_types = schema.col_types();
@@ -1453,10 +1537,14 @@ impl MoteDB {
             let num_cols = col_types.len();
             let mut segments: Vec<ColumnarSegment> = Vec::with_capacity(num_cols);
             for col_idx in 0..num_cols {
-                segments.push(build_column_segment(col_sst, col_idx, col_sst.num_rows)?);
+                segments.push(if columns.is_none_or(|c| c.contains(&col_idx)) {
+                    build_column_segment(col_sst, col_idx, col_sst.num_rows)?
+                } else {
+                    ColumnarSegment::AllNull
+                });
             }
             let col_names: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
-            return Ok(TableRowStreamingIterator {
+            let iter = TableRowStreamingIterator {
                 inner: TableRowStreamingInner::Columnar {
                     row_map: col_sst.row_map.clone(),
                     segments,
@@ -1465,6 +1553,12 @@ impl MoteDB {
                     current_idx: 0,
                     num_rows: col_sst.num_rows,
                 },
+                prefilter: None,
+                projection: None,
+            };
+            return Ok(match columns {
+                Some(columns) => iter.with_projection(columns),
+                None => iter,
             });
         }
 
@@ -1478,7 +1572,7 @@ impl MoteDB {
 
         // Detect whether any column is nullable
         let has_nullable = schema.columns.iter().any(|c| c.nullable);
-        Ok(TableRowStreamingIterator {
+        let iter = TableRowStreamingIterator {
             inner: TableRowStreamingInner::Lsm {
                 lsm_iter,
                 decode_ctx: {
@@ -1491,7 +1585,14 @@ impl MoteDB {
                     Some(ctx)
                 },
                 use_raw,
+                col_types: col_types.to_vec(),
             },
+            prefilter: None,
+            projection: None,
+        };
+        Ok(match columns {
+            Some(columns) => iter.with_projection(columns),
+            None => iter,
         })
     }
 
@@ -1655,10 +1756,11 @@ impl MoteDB {
         match self.col_segment_stores.entry(table_name.to_string()) {
             Entry::Occupied(o) => Ok(o.get().clone()),
             Entry::Vacant(v) => {
-                let store = crate::storage::col_segment::ColSegmentStore::create(
+                let store = crate::storage::col_segment::ColSegmentStore::create_partitioned(
                     &self.path,
                     table_name,
                     col_types.to_vec(),
+                    self.num_partitions,
                 )?;
                 v.insert(store.clone());
                 Ok(store)
@@ -1857,9 +1959,7 @@ impl MoteDB {
             // shorter). Indexing directly would panic; reading via read_text/
             // read_fixed would OOB column_index. Emit an all-NULL segment.
             let seg = match col_sst.column_tags.get(col_idx) {
-                Some(t) if t.is_fixed() => {
-                    ColumnarSegment::Fixed(col_sst.read_fixed_i64(col_idx)?)
-                }
+                Some(t) if t.is_fixed() => ColumnarSegment::Fixed(col_sst.read_fixed_i64(col_idx)?),
                 Some(_) => ColumnarSegment::Text(col_sst.read_text(col_idx)?),
                 None => ColumnarSegment::null_for(col_sst.num_rows),
             };
@@ -2123,15 +2223,7 @@ impl PartialOrd for OrderedF64 {
 }
 impl Ord for OrderedF64 {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match (self.0.is_nan(), other.0.is_nan()) {
-            (true, true) => std::cmp::Ordering::Equal,
-            (true, false) => std::cmp::Ordering::Greater,
-            (false, true) => std::cmp::Ordering::Less,
-            (false, false) => self
-                .0
-                .partial_cmp(&other.0)
-                .unwrap_or(std::cmp::Ordering::Equal),
-        }
+        crate::types::float_cmp(self.0, other.0)
     }
 }
 
@@ -2204,6 +2296,41 @@ impl MoteDB {
         Ok(estimated_total)
     }
 
+    /// Estimate a table's row count from storage metadata, without scanning
+    ///
+    /// Column-segment tables add the per-segment row counts to the rows still
+    /// in the write buffer; LSM tables add SSTable entry counts to the live
+    /// MemTable entries. Updated and deleted rows that have not been
+    /// compacted away are still counted, so the estimate errs high.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let approx = db.estimate_row_count("sensor_data")?;
+    /// ```
+    pub fn estimate_row_count(&self, table_name: &str) -> Result<u64> {
+        let _schema = self.table_registry.get_table(table_name)?;
+
+        if let Some(store) = self.col_segment_stores.get(table_name) {
+            let segment_rows: usize = store
+                .segments_snapshot()
+                .iter()
+                .map(|seg| seg.row_count)
+                .sum();
+            return Ok((segment_rows + store.buffered_row_count()) as u64);
+        }
+
+        let table_prefix = self.compute_table_prefix(table_name);
+        let start_key = table_prefix << 32;
+        let end_key = (table_prefix + 1) << 32;
+        let sst_count = self
+            .lsm_engine
+            .estimate_key_count_in_range(start_key, end_key)?;
+        let mem_count = self
+            .lsm_engine
+            .memtable_key_count_in_range(start_key, end_key)?;
+        Ok((sst_count + mem_count) as u64)
+    }
+
     /// Fast row count from atomic counter (O(1), may be approximate)
     pub fn fast_row_count(&self, table_name: &str) -> Option<u64> {
         self.table_row_count
@@ -2263,15 +2390,100 @@ impl MoteDB {
     pub fn batch_insert_rows_to_table(
         &self,
         table_name: &str,
-        mut rows: Vec<Row>,
+        rows: Vec<Row>,
     ) -> Result<Vec<RowId>> {
+        // A failed index is marked stale; the rows are in either way
+        self.insert_rows_batch(table_name, rows, false)
+            .map(|(row_ids, _indexed)| row_ids)
+    }
+
+    /// Stream rows into a table without collecting them first
+    ///
+    /// Rows are pulled from `rows` [`INSERT_ITER_CHUNK`] at a time; each
+    /// chunk is validated and written as one WAL batch, like
+    /// [`Self::batch_insert_rows_to_table`]. Index updates are deferred
+    /// until the chunk is written, then its row ids are bulk-loaded into
+    /// each index in one step, so readers see the rows through the indexes
+    /// chunk by chunk as the ingest proceeds.
+    ///
+    /// Returns the number of rows inserted. If a chunk fails validation, the
+    /// chunks before it stay inserted (and indexed) and its error is
+    /// returned. If updating an index fails, the chunk's rows stay inserted,
+    /// the index is marked stale (see [`Self::index_health`]; `REINDEX`
+    /// repairs it) and the ingest stops with that error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let n = db.insert_rows_iter(
+    ///     "telemetry",
+    ///     (0..1_000_000).map(|i| vec![Value::Integer(i), Value::Float(read_sensor(i))]),
+    /// )?;
+    /// ```
+    pub fn insert_rows_iter<I>(&self, table_name: &str, rows: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = Row>,
+    {
+        let mut rows = rows.into_iter();
+        let mut inserted = 0;
+        loop {
+            let chunk: Vec<Row> = rows.by_ref().take(INSERT_ITER_CHUNK).collect();
+            if chunk.is_empty() {
+                return Ok(inserted);
+            }
+            let (row_ids, indexed) = self.insert_rows_batch(table_name, chunk, true)?;
+            inserted += row_ids.len();
+            indexed?;
+        }
+    }
+
+    /// Insert one batch; also returns whether every index took the rows
+    fn insert_rows_batch(
+        &self,
+        table_name: &str,
+        rows: Vec<Row>,
+        must_index: bool,
+    ) -> Result<(Vec<RowId>, Result<()>)> {
+        let ring_bytes = self.ring_buffer_row_bytes(table_name, &rows);
+        let (row_ids, indexed) = if !self.has_continuous_aggregates(table_name) {
+            self.batch_insert_rows_to_table_inner(table_name, rows, must_index)?
+        } else {
+            let source_rows = rows.clone();
+            self.maintain_continuous_aggregates(table_name, &source_rows, || {
+                self.batch_insert_rows_to_table_inner(table_name, rows, must_index)
+            })?
+        };
+        if let Some(bytes) = ring_bytes {
+            self.ring_buffer_track(table_name, &row_ids, &bytes);
+            self.evict_ring_buffer(table_name)?;
+        }
+        Ok((row_ids, indexed))
+    }
+
+    fn batch_insert_rows_to_table_inner(
+        &self,
+        table_name: &str,
+        mut rows: Vec<Row>,
+        must_index: bool,
+    ) -> Result<(Vec<RowId>, Result<()>)> {
         ensure_open!(self);
         if rows.is_empty() {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Ok(())));
         }
+        let gate = self.table_write_gate(table_name);
+        let _writes = gate.read_recursive();
 
         // 1. Get table schema
         let schema = self.table_registry.get_table(table_name)?;
+        super::generated::fill_generated_columns(&schema, &mut rows)?;
+        self.apply_embedders(&schema, &mut rows)?;
+        for row in rows.iter_mut() {
+            schema.coerce_row(row).map_err(|e| {
+                StorageError::InvalidData(format!(
+                    "Row validation failed for table '{}': {}",
+                    table_name, e
+                ))
+            })?;
+        }
 
         // 🚀 Fast path: AUTO_INCREMENT tables with columnar storage skip per-row
         // validation, WAL clone overhead, and mmap page release. This is the
@@ -2279,8 +2491,15 @@ impl MoteDB {
         let auto_inc = schema.is_primary_key_auto_increment();
         // Only use fast_batch_insert for large batches with ColSegmentStore.
         // Single-row inserts go through the normal path (WAL + index updates).
-        if auto_inc && rows.len() >= 100 {
-            return self.fast_batch_insert(table_name, rows, &schema);
+        // insert_rows_iter must index every chunk, and the fast path
+        // skips indexes, so it only gets the fast path on unindexed tables
+        let fast_path = !must_index
+            || self
+                .index_registry
+                .list_table_indexes(table_name)
+                .is_empty();
+        if auto_inc && rows.len() >= 100 && fast_path {
+            return Ok((self.fast_batch_insert(table_name, rows, &schema)?, Ok(())));
         }
 
         // 2. Validate all rows
@@ -2546,7 +2765,36 @@ impl MoteDB {
             }
         }
 
+        // 6.6 Update row count for COUNT(*) fast path
+        if let Some(counter) = self.table_row_count.get(table_name) {
+            use std::sync::atomic::Ordering;
+            counter.fetch_add(rows.len() as u64, Ordering::Relaxed);
+        }
+
+        // Auto-flush trigger
+        let old_count = self
+            .pending_updates
+            .fetch_add(rows.len(), std::sync::atomic::Ordering::Release);
+        if old_count / 2_000 != (old_count + rows.len()) / 2_000 {
+            self.request_auto_flush();
+        }
+
         // 7. Batch update all indexes
+        let indexed = self.index_inserted_rows(table_name, &schema, &row_ids, &rows);
+        Ok((row_ids, indexed))
+    }
+
+    /// Add freshly inserted rows to every index on `table_name` in one bulk
+    /// step per index. An index that fails is marked stale and the others
+    /// are still updated; the first failure is returned.
+    fn index_inserted_rows(
+        &self,
+        table_name: &str,
+        schema: &crate::types::TableSchema,
+        row_ids: &[RowId],
+        rows: &[Row],
+    ) -> Result<()> {
+        let mut result = Ok(());
         debug_log!(
             "[batch_insert_rows_to_table] Batch updating indexes for {} rows in table '{}'",
             rows.len(),
@@ -2583,6 +2831,9 @@ impl MoteDB {
                 col_index_key.push_str(table_name);
                 col_index_key.push('.');
                 col_index_key.push_str(col_name);
+                if matches!(col_def.col_type, ColumnType::Array(_)) {
+                    col_index_key.push_str(ELEMENT_INDEX_SUFFIX);
+                }
                 if let Some(index_ref) = self.column_indexes.get(&col_index_key) {
                     let mut column_data: Vec<(Value, RowId)> = Vec::with_capacity(rows.len());
                     for (row_id, row) in row_ids.iter().zip(rows.iter()) {
@@ -2602,31 +2853,36 @@ impl MoteDB {
 
             // Parallel insert into column indexes (one thread per index).
             // Each index has its own mem_buffer and BTree — no shared state.
-            if column_tasks.len() > 1 {
-                std::thread::scope(|s| {
-                    for (index, data, key) in column_tasks {
-                        s.spawn(move || {
-                            if let Err(_e) = index.batch_insert(data) {
-                                debug_log!(
-                                    "[batch_insert] Failed to batch update column index '{}': {}",
-                                    key,
-                                    _e
-                                );
-                            }
-                        });
-                    }
-                });
-            } else {
-                for (index, data, key) in column_tasks {
-                    if let Err(_e) = index.batch_insert(data) {
-                        debug_log!(
-                            "[batch_insert] Failed to batch update column index '{}': {}",
-                            key,
-                            _e
-                        );
-                        self.index_registry.mark_stale(&key);
-                    }
-                }
+            let failed: Vec<(String, StorageError)> =
+                if column_tasks.len() > 1 && crate::storage::platform::threads_available() {
+                    std::thread::scope(|s| {
+                        let handles: Vec<_> = column_tasks
+                            .into_iter()
+                            .map(|(index, data, key)| {
+                                s.spawn(move || index.batch_insert(data).err().map(|e| (key, e)))
+                            })
+                            .collect();
+                        handles
+                            .into_iter()
+                            .filter_map(|h| h.join().expect("column index insert panicked"))
+                            .collect()
+                    })
+                } else {
+                    column_tasks
+                        .into_iter()
+                        .filter_map(|(index, data, key)| {
+                            index.batch_insert(data).err().map(|e| (key, e))
+                        })
+                        .collect()
+                };
+            for (key, e) in failed {
+                debug_log!(
+                    "[batch_insert] Failed to batch update column index '{}': {}",
+                    key,
+                    e
+                );
+                self.index_registry.mark_stale(&key);
+                result = result.and(Err(e));
             }
         } // end else (columnar SSTable exists → skip column indexes)
 
@@ -2635,7 +2891,7 @@ impl MoteDB {
             let col_name = &col_def.name;
 
             // 7.2a 批量更新 Vector Index
-            if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+            if col_def.col_type.vector_dim().is_some() {
                 if let Some(index_name) = self.index_registry.find_by_column(
                     table_name,
                     col_name,
@@ -2652,13 +2908,14 @@ impl MoteDB {
                     }
 
                     if !vectors.is_empty() {
-                        if let Err(_e) = self.batch_insert_vectors(&index_name, &vectors) {
+                        if let Err(e) = self.batch_insert_vectors(&index_name, &vectors) {
                             debug_log!(
                                 "[batch_insert] Failed to batch update vector index '{}': {}",
                                 index_name,
-                                _e
+                                e
                             );
                             self.index_registry.mark_stale(&index_name);
+                            result = result.and(Err(e));
                         }
                     }
                 }
@@ -2681,13 +2938,14 @@ impl MoteDB {
                     if !texts.is_empty() {
                         let texts_ref: Vec<(RowId, &str)> =
                             texts.iter().map(|(id, s)| (*id, s.as_str())).collect();
-                        if let Err(_e) = self.batch_insert_texts(&index_name, &texts_ref) {
+                        if let Err(e) = self.batch_insert_texts(&index_name, &texts_ref) {
                             debug_log!(
                                 "[batch_insert] Failed to batch update text index '{}': {}",
                                 index_name,
-                                _e
+                                e
                             );
                             self.index_registry.mark_stale(&index_name);
+                            result = result.and(Err(e));
                         }
                     }
                 }
@@ -2703,14 +2961,14 @@ impl MoteDB {
                     for (row_id, row) in row_ids.iter().zip(rows.iter()) {
                         if let Some(crate::types::Value::Spatial(geom)) = row.get(col_def.position)
                         {
-                            if let Err(_e) = self.insert_ioctree_point(*row_id, &octree_name, geom)
-                            {
+                            if let Err(e) = self.insert_ioctree_point(*row_id, &octree_name, geom) {
                                 debug_log!(
                                     "[batch_insert] Failed to update ioctree index '{}': {}",
                                     octree_name,
-                                    _e
+                                    e
                                 );
                                 self.index_registry.mark_stale(&octree_name);
+                                result = result.and(Err(e));
                             }
                         }
                     }
@@ -2722,21 +2980,7 @@ impl MoteDB {
             // and is updated during flush via batch building
         }
 
-        // 8. Update row count for COUNT(*) fast path
-        if let Some(counter) = self.table_row_count.get(table_name) {
-            use std::sync::atomic::Ordering;
-            counter.fetch_add(rows.len() as u64, Ordering::Relaxed);
-        }
-
-        // Auto-flush trigger
-        let old_count = self
-            .pending_updates
-            .fetch_add(rows.len(), std::sync::atomic::Ordering::Release);
-        if old_count / 2_000 != (old_count + rows.len()) / 2_000 {
-            self.request_auto_flush();
-        }
-
-        Ok(row_ids)
+        result
     }
 
     /// 🚀 Fast batch INSERT for AUTO_INCREMENT tables.
@@ -2888,7 +3132,9 @@ impl MoteDB {
                     Err(arc) => (*arc).clone(),
                 });
             vec![(row_id, opt)]
-        } else if is_continuous {
+        } else if is_continuous && !self.col_segment_stores.contains_key(table_name) {
+            // The range scan only reads the LSM; ColSegmentStore tables take
+            // the filtered path below, which reads the store
             self.get_table_rows_batch_range(table_name, &missed_ids)?
         } else {
             let mut sorted_ids = missed_ids.clone();
@@ -2920,7 +3166,7 @@ impl MoteDB {
 
     /// Increment pending updates counter and trigger auto-flush if needed
     /// 🚀 P0 CRITICAL FIX: 使用原子操作避免锁竞争，解决 CPU 飙升问题
-    fn increment_pending_updates(&self) {
+    pub(crate) fn increment_pending_updates(&self) {
         use std::sync::atomic::Ordering;
 
         let count = self.pending_updates.fetch_add(1, Ordering::Release);
@@ -2968,18 +3214,35 @@ impl MoteDB {
             Err(_) => return,
         };
 
-        // Directly fetch from LSM without triggering get_table_rows_batch (avoid recursion)
+        // Directly fetch from storage without triggering get_table_rows_batch
+        // (avoid recursion). A ColSegmentStore is authoritative for its table;
+        // the LSM may only hold older versions of its rows.
+        let epoch = self.row_cache.fill_epoch();
+        let store = self
+            .col_segment_stores
+            .get(table_name)
+            .map(|entry| entry.value().clone());
         for row_id in row_ids_to_fetch {
             let composite_key = self.make_composite_key(table_name, row_id);
 
-            if let Ok(Some(value)) = self.lsm_engine.get(composite_key) {
-                if !value.deleted {
-                    if let crate::storage::lsm::ValueData::Inline(bytes) = &value.data {
-                        if let Ok(row) = crate::storage::row_format::decode(bytes, &col_types) {
-                            self.row_cache.put(table_name.to_string(), row_id, row);
-                            self.row_cache.record_prefetch_hit();
+            let row = match &store {
+                Some(store) => store.get(composite_key),
+                None => match self.lsm_engine.get(composite_key) {
+                    Ok(Some(value)) if !value.deleted => match &value.data {
+                        crate::storage::lsm::ValueData::Inline(bytes) => {
+                            crate::storage::row_format::decode(bytes, &col_types).ok()
                         }
-                    }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+            };
+            if let Some(row) = row {
+                if self
+                    .row_cache
+                    .fill(table_name, row_id, Arc::new(row), epoch)
+                {
+                    self.row_cache.record_prefetch_hit();
                 }
             }
         }
@@ -3032,6 +3295,7 @@ impl MoteDB {
         let end_key = self.make_composite_key(table_name, max_id + 1);
 
         // Use streaming scan to avoid materializing all rows into a Vec.
+        let epoch = self.row_cache.fill_epoch();
         let lsm_iter = self.lsm_engine.scan_range_streaming(start_key, end_key)?;
 
         // Pre-compute decode info outside the loop
@@ -3072,7 +3336,7 @@ impl MoteDB {
             };
 
             self.row_cache
-                .put(table_name.to_string(), row_id, row.clone());
+                .fill(table_name, row_id, Arc::new(row.clone()), epoch);
             result.push((row_id, Some(row)));
         }
 
@@ -3090,6 +3354,7 @@ impl MoteDB {
         let start_key = self.make_composite_key(table_name, min_id);
         let end_key = self.make_composite_key(table_name, max_id + 1);
 
+        let epoch = self.row_cache.fill_epoch();
         let lsm_rows = self.lsm_engine.scan_range(start_key, end_key)?;
 
         // Pre-compute decode info outside the loop
@@ -3125,7 +3390,7 @@ impl MoteDB {
 
             // Cache row
             self.row_cache
-                .put(table_name.to_string(), row_id, row.clone());
+                .fill(table_name, row_id, Arc::new(row.clone()), epoch);
             result.push((row_id, Some(row)));
         }
 
@@ -3243,6 +3508,95 @@ impl Iterator for TableRawStreamingIterator {
 /// 使用 SchemaDecodeContext 实现预计算 schema 上下文，消除每行冗余计算。
 pub struct TableRowStreamingIterator {
     inner: TableRowStreamingInner,
+    prefilter: Option<RowPrefilter>,
+    projection: Option<RowProjection>,
+}
+
+/// Columns a scan decodes; the rest of each row reads NULL
+struct RowProjection {
+    /// Sorted schema positions
+    positions: Vec<usize>,
+    /// Reusable buffer for the projected values
+    buf: Vec<Value>,
+}
+
+impl RowProjection {
+    /// Spread the projected values in `buf` over a full-width row
+    fn widen(&mut self, width: usize) -> Row {
+        let mut row = vec![Value::Null; width];
+        for (&pos, value) in self.positions.iter().zip(self.buf.drain(..)) {
+            if let Some(slot) = row.get_mut(pos) {
+                *slot = value;
+            }
+        }
+        row
+    }
+}
+
+/// Per-row check on a single column, run before the rest of the row is decoded
+struct RowPrefilter {
+    col_idx: usize,
+    keep: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+    /// Rows dropped by `keep`
+    skipped: u64,
+}
+
+impl TableRowStreamingIterator {
+    /// Only yield rows whose column `col_idx` satisfies `keep`
+    ///
+    /// The column is read straight from the encoded row (or its columnar
+    /// segment), so rejected rows are never fully deserialized. Used for
+    /// runtime filters pushed down from joins. The filter is advisory: on a
+    /// columnar scan that doesn't load `col_idx` it lets every row through.
+    pub fn with_prefilter(
+        mut self,
+        col_idx: usize,
+        keep: impl Fn(&Value) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.prefilter = Some(RowPrefilter {
+            col_idx,
+            keep: Box::new(keep),
+            skipped: 0,
+        });
+        self
+    }
+
+    /// Only decode the columns at `positions`; the other columns of each
+    /// yielded row are NULL
+    ///
+    /// Rows keep their full schema width, so positional consumers are
+    /// unaffected. Wide columns left out (vectors, long text) are skipped
+    /// over using the encoded row's column offsets instead of being decoded.
+    pub fn with_projection(mut self, positions: &[usize]) -> Self {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+        self.projection = Some(RowProjection {
+            buf: Vec::with_capacity(positions.len()),
+            positions,
+        });
+        self
+    }
+
+    /// Rows the prefilter rejected so far
+    pub fn prefiltered_rows(&self) -> u64 {
+        self.prefilter.as_ref().map_or(0, |p| p.skipped)
+    }
+}
+
+impl RowPrefilter {
+    fn keep_encoded(&mut self, data: &[u8], col_types: &[ColumnType]) -> bool {
+        let value = row_format::get_column(data, col_types, self.col_idx).unwrap_or(Value::Null);
+        self.keep_value(&value)
+    }
+
+    fn keep_value(&mut self, value: &Value) -> bool {
+        let keep = (self.keep)(value);
+        if !keep {
+            self.skipped += 1;
+        }
+        keep
+    }
 }
 
 enum TableRowStreamingInner {
@@ -3251,6 +3605,8 @@ enum TableRowStreamingInner {
         lsm_iter: crate::storage::lsm::MergingIterator,
         decode_ctx: Option<crate::storage::row_format::SchemaDecodeContext>,
         use_raw: bool,
+        /// Only read for prefiltering; decoding goes through `decode_ctx`
+        col_types: Vec<ColumnType>,
     },
     /// Columnar SSTable backed scan. For tables whose data lives in the
     /// columnar SSTable (not the LSM), we decode column arrays into rows.
@@ -3276,7 +3632,15 @@ impl Iterator for TableRowStreamingIterator {
                 lsm_iter,
                 decode_ctx,
                 use_raw,
-            } => lsm_next(lsm_iter, decode_ctx, *use_raw),
+                col_types,
+            } => lsm_next(
+                lsm_iter,
+                decode_ctx,
+                *use_raw,
+                col_types,
+                self.prefilter.as_mut(),
+                self.projection.as_mut(),
+            ),
             TableRowStreamingInner::Columnar {
                 row_map,
                 segments,
@@ -3291,46 +3655,33 @@ impl Iterator for TableRowStreamingIterator {
                     if row_map.is_deleted(idx) {
                         continue;
                     }
+                    // Columns left out of the projection were never loaded,
+                    // so a prefilter on one of them lets every row through
+                    if let Some(filter) = self.prefilter.as_mut().filter(|f| {
+                        self.projection
+                            .as_ref()
+                            .is_none_or(|p| p.positions.binary_search(&f.col_idx).is_ok())
+                    }) {
+                        let value = segments.get(filter.col_idx).map_or(Value::Null, |seg| {
+                            seg.value(idx, col_types.get(filter.col_idx))
+                        });
+                        if !filter.keep_value(&value) {
+                            continue;
+                        }
+                    }
                     let key = row_map.key(idx);
                     let row_id = (key & 0xFFFFFFFF) as RowId;
                     let mut row: Row = Vec::with_capacity(col_names.len());
                     for (ci, seg) in segments.iter().enumerate() {
-                        // Decode by the column's declared type so Float/Boolean
-                        // are not reinterpreted as Integer bit patterns.
-                        let v = match seg {
-                            ColumnarSegment::Fixed(f) => match col_types.get(ci) {
-                                Some(crate::types::ColumnType::Float) => {
-                                    f.get_f64(idx).map(crate::types::Value::Float)
-                                }
-                                Some(crate::types::ColumnType::Boolean) => {
-                                    f.get_bool(idx).map(crate::types::Value::Bool)
-                                }
-                                _ => f.get_i64(idx).map(crate::types::Value::Integer),
-                            }
-                            .unwrap_or(crate::types::Value::Null),
-                            ColumnarSegment::Text(t) => t
-                                .get_str(idx)
-                                .map(|s| crate::types::Value::Text(s.into()))
-                                .unwrap_or(crate::types::Value::Null),
-                            ColumnarSegment::Vector(cols) => cols
-                                .get(idx)
-                                .cloned()
-                                .flatten()
-                                .map(|f32s| {
-                                    crate::types::Value::Vector(crate::types::ArcVec(
-                                        std::sync::Arc::new(f32s),
-                                    ))
-                                })
-                                .unwrap_or(crate::types::Value::Null),
-                            ColumnarSegment::Spatial(cols) => cols
-                                .get(idx)
-                                .cloned()
-                                .flatten()
-                                .map(|g| crate::types::Value::Spatial(std::boxed::Box::new(g)))
-                                .unwrap_or(crate::types::Value::Null),
-                            ColumnarSegment::AllNull => crate::types::Value::Null,
-                        };
-                        row.push(v);
+                        let projected = self
+                            .projection
+                            .as_ref()
+                            .is_none_or(|p| p.positions.binary_search(&ci).is_ok());
+                        row.push(if projected {
+                            seg.value(idx, col_types.get(ci))
+                        } else {
+                            Value::Null
+                        });
                     }
                     return Some(Ok((row_id, row)));
                 }
@@ -3345,6 +3696,9 @@ fn lsm_next(
     lsm_iter: &mut crate::storage::lsm::MergingIterator,
     decode_ctx: &mut Option<crate::storage::row_format::SchemaDecodeContext>,
     use_raw: bool,
+    col_types: &[ColumnType],
+    mut prefilter: Option<&mut RowPrefilter>,
+    mut projection: Option<&mut RowProjection>,
 ) -> Option<Result<(RowId, Row)>> {
     if use_raw {
         loop {
@@ -3356,20 +3710,21 @@ fn lsm_next(
                     if vb.len == 0 {
                         continue;
                     }
-                    let row_id = (composite_key & 0xFFFFFFFF) as RowId;
-                    let row: Row = if let Some(ref mut ctx) = decode_ctx {
-                        match ctx.decode_row(vb.as_slice()) {
-                            Ok(row) => row,
-                            Err(e) => return Some(Err(e)),
-                        }
-                    } else {
-                        match crate::storage::row_format::decode_any_with_pool(vb.as_slice(), None)
-                        {
-                            Ok(row) => row,
-                            Err(e) => return Some(Err(e)),
+                    if let Some(filter) = prefilter.as_mut() {
+                        if !filter.keep_encoded(vb.as_slice(), col_types) {
+                            continue;
                         }
-                    };
-                    return Some(Ok((row_id, row)));
+                    }
+                    let row_id = (composite_key & 0xFFFFFFFF) as RowId;
+                    return Some(
+                        decode_lsm_row(
+                            vb.as_slice(),
+                            decode_ctx,
+                            col_types,
+                            projection.as_deref_mut(),
+                        )
+                        .map(|row| (row_id, row)),
+                    );
                 }
                 Some(Err(e)) => return Some(Err(e)),
                 None => return None,
@@ -3391,18 +3746,15 @@ fn lsm_next(
                         )));
                     }
                 };
-                let row: Row = if let Some(ref mut ctx) = decode_ctx {
-                    match ctx.decode_row(data) {
-                        Ok(row) => row,
-                        Err(e) => return Some(Err(e)),
-                    }
-                } else {
-                    match crate::storage::row_format::decode_any_with_pool(data, None) {
-                        Ok(row) => row,
-                        Err(e) => return Some(Err(e)),
+                if let Some(filter) = prefilter.as_mut() {
+                    if !filter.keep_encoded(data, col_types) {
+                        continue;
                     }
-                };
-                return Some(Ok((row_id, row)));
+                }
+                return Some(
+                    decode_lsm_row(data, decode_ctx, col_types, projection.as_deref_mut())
+                        .map(|row| (row_id, row)),
+                );
             }
             Some(Err(e)) => return Some(Err(e)),
             None => return None,
@@ -3410,6 +3762,38 @@ fn lsm_next(
     }
 }
 
+/// Decode one encoded LSM row, only the projected columns when a projection
+/// is set. Rows whose header doesn't match the schema width (written before
+/// an ALTER TABLE ADD COLUMN, or bincode-encoded) are decoded in full.
+fn decode_lsm_row(
+    data: &[u8],
+    decode_ctx: &mut Option<crate::storage::row_format::SchemaDecodeContext>,
+    col_types: &[ColumnType],
+    projection: Option<&mut RowProjection>,
+) -> Result<Row> {
+    if let Some(projection) = projection {
+        if row_format::rawrow_column_count(data) == Some(col_types.len()) {
+            let fixed_count = match decode_ctx {
+                Some(ctx) => ctx.fixed_count,
+                None => row_format::compute_fixed_count(col_types),
+            };
+            row_format::decode_fast_partial_into_with_pool(
+                data,
+                col_types,
+                fixed_count,
+                &projection.positions,
+                &mut projection.buf,
+                decode_ctx.as_mut().map(|ctx| &mut ctx.pool),
+            )?;
+            return Ok(projection.widen(col_types.len()));
+        }
+    }
+    match decode_ctx {
+        Some(ctx) => ctx.decode_row(data),
+        None => row_format::decode_any_with_pool(data, None),
+    }
+}
+
 /// Zero-copy decode streaming iterator — decodes rows directly into a
 /// caller-provided `Vec<Value>` using `ValueBytes::as_slice()`.
 ///
@@ -3432,6 +3816,45 @@ impl ColumnarSegment {
     fn null_for(_num_rows: usize) -> Self {
         ColumnarSegment::AllNull
     }
+
+    /// Value of row `idx`, decoded by the column's declared type so
+    /// Float/Boolean are not reinterpreted as Integer bit patterns
+    fn value(&self, idx: usize, col_type: Option<&ColumnType>) -> Value {
+        match self {
+            ColumnarSegment::Fixed(f) => match col_type {
+                Some(crate::types::ColumnType::Float) => {
+                    f.get_f64(idx).map(crate::types::Value::Float)
+                }
+                Some(crate::types::ColumnType::Boolean) => {
+                    f.get_bool(idx).map(crate::types::Value::Bool)
+                }
+                _ => f.get_i64(idx).map(crate::types::Value::Integer),
+            }
+            .unwrap_or(crate::types::Value::Null),
+            ColumnarSegment::Text(t) => t
+                .get_str(idx)
+                .map(|s| crate::types::Value::Text(s.into()))
+                .unwrap_or(crate::types::Value::Null),
+            ColumnarSegment::Vector(cols) => cols
+                .get(idx)
+                .cloned()
+                .flatten()
+                .map(|f32s| match col_type {
+                    Some(ct) => ct.value_from_vector(f32s),
+                    None => {
+                        crate::types::Value::Vector(crate::types::ArcVec(std::sync::Arc::new(f32s)))
+                    }
+                })
+                .unwrap_or(crate::types::Value::Null),
+            ColumnarSegment::Spatial(cols) => cols
+                .get(idx)
+                .cloned()
+                .flatten()
+                .map(|g| crate::types::Value::Spatial(std::boxed::Box::new(g)))
+                .unwrap_or(crate::types::Value::Null),
+            ColumnarSegment::AllNull => crate::types::Value::Null,
+        }
+    }
 }
 
 /// Build a ColumnarSegment from an SSTable column, dispatching on the stored
@@ -3460,10 +3883,7 @@ fn build_column_segment(
                 // read_vectors skips nulls too; its k-th output corresponds to
                 // the k-th non-null, non-deleted row. Match by row_id.
                 let expected_key = col_sst.row_map.key(i) & 0xFFFFFFFF;
-                while di < decoded.len() && decoded[di].0 != expected_key {
-                    di += 1;
-                }
-                if di < decoded.len() {
+                if decoded.get(di).is_some_and(|d| d.0 == expected_key) {
                     per_row[i] = Some(decoded[di].1.clone());
                     di += 1;
                 }
@@ -3479,10 +3899,7 @@ fn build_column_segment(
                     continue;
                 }
                 let expected_key = col_sst.row_map.key(i) & 0xFFFFFFFF;
-                while di < decoded.len() && decoded[di].0 != expected_key {
-                    di += 1;
-                }
-                if di < decoded.len() {
+                if decoded.get(di).is_some_and(|d| d.0 == expected_key) {
                     per_row[i] = Some(decoded[di].1.clone());
                     di += 1;
                 }
@@ -3581,9 +3998,7 @@ impl ColumnarScanIterator {
                     .get(idx)
                     .cloned()
                     .flatten()
-                    .map(|v| {
-                        crate::types::Value::Vector(crate::types::ArcVec(std::sync::Arc::new(v)))
-                    })
+                    .map(|v| ct.value_from_vector(v))
                     .unwrap_or(crate::types::Value::Null),
                 ColumnarSegment::Spatial(cols) => cols
                     .get(idx)
@@ -3824,4 +4239,77 @@ mod tests {
         assert_eq!(rows[0], vec![Value::Integer(1), Value::Integer(10)]);
         assert_eq!(rows[1], vec![Value::Integer(2), Value::Integer(20)]);
     }
+
+    #[test]
+    fn test_streaming_scan_projection_and_prefilter() {
+        use crate::sql::{Lexer, Parser};
+        use crate::MoteDB;
+        use std::sync::Arc;
+
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+        let executor = crate::sql::QueryExecutor::new(db.clone());
+        let run = |sql: &str| {
+            let tokens = Lexer::new(sql).tokenize().unwrap();
+            executor
+                .execute(Parser::new(tokens).parse().unwrap())
+                .unwrap();
+        };
+        run("CREATE TABLE w (id INT PRIMARY KEY, body TEXT, emb VECTOR(4), val INT)");
+        for i in 0..20i64 {
+            run(&format!(
+                "INSERT INTO w VALUES ({}, 'row {}', [{}, 0.5, 0.25, 1.0], {})",
+                i,
+                i,
+                i,
+                i * 10
+            ));
+        }
+        db.flush().unwrap();
+        // Rows written before the ADD COLUMN are narrower than the schema
+        run("ALTER TABLE w ADD COLUMN note TEXT");
+        run("INSERT INTO w VALUES (20, 'row 20', [20, 0.5, 0.25, 1.0], 200, 'new')");
+
+        let mut rows: Vec<crate::types::Row> = db
+            .scan_table_rows_streaming("w")
+            .unwrap()
+            .with_projection(&[3, 0])
+            .map(|row| row.unwrap().1)
+            .collect();
+        rows.sort_by_key(|row| match row[0] {
+            Value::Integer(id) => id,
+            _ => panic!("id not projected"),
+        });
+        assert_eq!(rows.len(), 21);
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), 5);
+            assert_eq!(row[3], Value::Integer(i as i64 * 10));
+            if i < 20 {
+                assert_eq!(row[1], Value::Null);
+                assert_eq!(row[2], Value::Null);
+            }
+        }
+
+        let mut scan = db
+            .scan_table_rows_streaming("w")
+            .unwrap()
+            .with_prefilter(3, |val| matches!(val, Value::Integer(v) if v % 50 == 0));
+        let ids: Vec<Value> = scan.by_ref().map(|row| row.unwrap().1[0].clone()).collect();
+        assert_eq!(ids.len(), 5);
+        assert_eq!(scan.prefiltered_rows(), 16);
+
+        // Column-projected scan: unrequested columns are never loaded, and a
+        // prefilter on one of them is advisory
+        let rows: Vec<crate::types::Row> = db
+            .scan_table_columns_streaming("w", Some(&[0, 1]))
+            .unwrap()
+            .with_prefilter(3, |_| false)
+            .map(|row| row.unwrap().1)
+            .filter(|row| row[2] == Value::Null && row[3] == Value::Null)
+            .collect();
+        assert!(rows.len() >= 20);
+        assert!(rows
+            .iter()
+            .all(|row| matches!(&row[1], Value::Text(t) if t.starts_with("row "))));
+    }
 }
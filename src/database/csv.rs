@@ -0,0 +1,518 @@
+//! CSV Import / Export
+//!
+//! [`MoteDB::import_csv`] streams a CSV file into a table. Each field is
+//! converted to its column's type and the rows go in through
+//! [`MoteDB::insert_rows_iter`], so they are written in batches and each
+//! batch is bulk-loaded into the table's indexes as it lands.
+//! [`MoteDB::export_csv`] writes a table out in the form `import_csv` reads
+//! back. SQL runs both as `COPY table FROM / TO 'file.csv'`.
+//!
+//! Fields follow RFC 4180: one holding the delimiter, a quote or a line
+//! break is quoted, with its quotes doubled. An unquoted field equal to
+//! [`CsvOptions::null`] is NULL; a quoted one is always text. Typed columns
+//! use the same text as SQL literals (`2024-01-31T08:00:00Z` timestamps,
+//! `[1.0, 2.0]` vectors, canonical DECIMAL / UUID / DATE / TIME / ARRAY
+//! text). Generated columns are neither read nor written, and spatial
+//! values have no CSV form.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::core::MoteDB;
+use crate::sql::{Lexer, Parser, QueryExecutor};
+use crate::types::{tensor, ArcVec, ColumnDef, ColumnType, Row, Timestamp, UtcOffset, Value};
+use crate::{Result, StorageError};
+
+/// How a CSV file is laid out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field separator (`,` by default); must be ASCII
+    pub delimiter: u8,
+    /// The first line names the columns (true by default). Without one,
+    /// fields map to the table's columns in order.
+    pub header: bool,
+    /// Unquoted field text read and written as NULL (empty by default)
+    pub null: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+            null: String::new(),
+        }
+    }
+}
+
+impl MoteDB {
+    /// Load a CSV file into `table_name`; returns the rows inserted.
+    ///
+    /// Columns the header leaves out get their DEFAULT (or NULL). A field
+    /// that doesn't convert to its column's type stops the import: rows
+    /// before it stay inserted and the error names its line.
+    pub fn import_csv<P: AsRef<Path>>(
+        &self,
+        table_name: &str,
+        path: P,
+        options: &CsvOptions,
+    ) -> Result<usize> {
+        ensure_open!(self);
+        let schema = self.table_registry.get_table(table_name)?;
+        if !options.delimiter.is_ascii() {
+            return Err(StorageError::InvalidArgument(
+                "CSV delimiter must be an ASCII character".into(),
+            ));
+        }
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            StorageError::InvalidArgument(format!("cannot open '{}': {}", path.display(), e))
+        })?;
+        let mut reader = CsvReader::new(BufReader::new(file), options);
+
+        let writable = || schema.columns.iter().filter(|c| c.generated.is_none());
+        let targets: Vec<&ColumnDef> = if options.header {
+            let Some(names) = reader.next_record()? else {
+                return Ok(0);
+            };
+            names
+                .iter()
+                .map(|name| {
+                    let name = name.as_deref().unwrap_or_default().trim();
+                    writable()
+                        .find(|c| c.name.eq_ignore_ascii_case(name))
+                        .ok_or_else(|| {
+                            StorageError::InvalidData(format!(
+                                "CSV header names unknown column '{}' of table '{}'",
+                                name, table_name
+                            ))
+                        })
+                })
+                .collect::<Result<_>>()?
+        } else {
+            writable().collect()
+        };
+        let defaults: Row = schema
+            .columns
+            .iter()
+            .map(|c| c.default_value.clone().unwrap_or(Value::Null))
+            .collect();
+
+        let mut error = None;
+        let rows = std::iter::from_fn(|| {
+            let record = match reader.next_record() {
+                Ok(record) => record?,
+                Err(e) => {
+                    error = Some(e);
+                    return None;
+                }
+            };
+            match csv_row(&record, &targets, &defaults, reader.line) {
+                Ok(row) => Some(row),
+                Err(e) => {
+                    error = Some(e);
+                    None
+                }
+            }
+        })
+        .fuse();
+        let inserted = self.insert_rows_iter(table_name, rows)?;
+        match error {
+            Some(e) => Err(e),
+            None => Ok(inserted),
+        }
+    }
+
+    /// Host path for a SQL `COPY` of `path`, confined to
+    /// [`DBConfig::copy_dir`](crate::DBConfig::copy_dir). `writing` allows
+    /// a file that doesn't exist yet (its directory must).
+    pub(crate) fn resolve_copy_path(&self, path: &str, writing: bool) -> Result<PathBuf> {
+        let Some(dir) = &self.copy_dir else {
+            return Err(StorageError::PermissionDenied(
+                "COPY is disabled; set DBConfig::copy_dir to allow it".into(),
+            ));
+        };
+        let denied = || {
+            StorageError::PermissionDenied(format!(
+                "COPY path '{}' is outside {}",
+                path,
+                dir.display()
+            ))
+        };
+        let cannot_open = |e: std::io::Error| {
+            StorageError::InvalidArgument(format!("cannot open '{}': {}", path, e))
+        };
+        let root = dir.canonicalize().map_err(|e| {
+            StorageError::InvalidArgument(format!("COPY directory {}: {}", dir.display(), e))
+        })?;
+        let target = root.join(path);
+        // Symlinks and `..` are resolved before the containment check
+        let resolved = match target.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(e) if writing && e.kind() == std::io::ErrorKind::NotFound => {
+                let name = target.file_name().ok_or_else(denied)?;
+                let parent = target.parent().ok_or_else(denied)?;
+                parent.canonicalize().map_err(cannot_open)?.join(name)
+            }
+            Err(e) => return Err(cannot_open(e)),
+        };
+        if !resolved.starts_with(&root) || resolved == root {
+            return Err(denied());
+        }
+        Ok(resolved)
+    }
+
+    /// Write `table_name` to a CSV file; returns the rows written
+    pub fn export_csv<P: AsRef<Path>>(
+        self: &Arc<Self>,
+        table_name: &str,
+        path: P,
+        options: &CsvOptions,
+    ) -> Result<usize> {
+        ensure_open!(self);
+        let schema = self.table_registry.get_table(table_name)?;
+        if !options.delimiter.is_ascii() {
+            return Err(StorageError::InvalidArgument(
+                "CSV delimiter must be an ASCII character".into(),
+            ));
+        }
+        let columns: Vec<&ColumnDef> = schema
+            .columns
+            .iter()
+            .filter(|col| col.generated.is_none())
+            .collect();
+        let names: Vec<&str> = columns.iter().map(|col| col.name.as_str()).collect();
+        let select = format!("SELECT {} FROM {}", names.join(", "), schema.name);
+        let statement = Parser::new(Lexer::new(&select).tokenize()?).parse()?;
+        let (_, row_iter) = QueryExecutor::new(self.clone())
+            .execute_streaming_ref(&statement)?
+            .into_row_iter()?;
+
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            StorageError::InvalidArgument(format!("cannot create '{}': {}", path.display(), e))
+        })?;
+        let mut out = BufWriter::new(file);
+        if options.header {
+            let header: Vec<Option<String>> = names.iter().map(|c| Some(c.to_string())).collect();
+            write_record(&mut out, &header, options)?;
+        }
+        let mut rows = 0;
+        for row in row_iter {
+            let fields = row?
+                .iter()
+                .zip(&columns)
+                .map(|(value, column)| {
+                    field_text(value, &column.col_type).map_err(|reason| {
+                        StorageError::InvalidData(format!(
+                            "cannot export {}.{}: {}",
+                            schema.name, column.name, reason
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            write_record(&mut out, &fields, options)?;
+            rows += 1;
+        }
+        out.flush()?;
+        Ok(rows)
+    }
+}
+
+/// A table row from one CSV record: `targets[i]` takes field `i`, the
+/// other columns their default
+fn csv_row(
+    record: &[Option<String>],
+    targets: &[&ColumnDef],
+    defaults: &Row,
+    line: usize,
+) -> Result<Row> {
+    if record.len() != targets.len() {
+        return Err(StorageError::InvalidData(format!(
+            "CSV line {}: expected {} fields, got {}",
+            line,
+            targets.len(),
+            record.len()
+        )));
+    }
+    let mut row = defaults.clone();
+    for (field, col) in record.iter().zip(targets) {
+        row[col.position] = match field {
+            None => Value::Null,
+            Some(text) => parse_field(text, &col.col_type).map_err(|reason| {
+                StorageError::InvalidData(format!(
+                    "CSV line {}: column '{}': {}",
+                    line, col.name, reason
+                ))
+            })?,
+        };
+    }
+    Ok(row)
+}
+
+/// A field's value in a column of `col_type`. Typed columns stored from
+/// text (DECIMAL, DATE, ARRAY, ...) are parsed by the schema's coercion.
+fn parse_field(text: &str, col_type: &ColumnType) -> std::result::Result<Value, String> {
+    let trimmed = text.trim();
+    match col_type {
+        ColumnType::Integer => trimmed
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("invalid INTEGER '{}'", text)),
+        ColumnType::Float => trimmed
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("invalid FLOAT '{}'", text)),
+        ColumnType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "t" | "1" | "yes" | "y" | "on" => Ok(Value::Bool(true)),
+            "false" | "f" | "0" | "no" | "n" | "off" => Ok(Value::Bool(false)),
+            _ => Err(format!("invalid BOOLEAN '{}'", text)),
+        },
+        // Epoch microseconds, or timestamp text
+        ColumnType::Timestamp => match trimmed.parse() {
+            Ok(micros) => Ok(Value::Timestamp(Timestamp::from_micros(micros))),
+            Err(_) => Ok(Value::text(trimmed.to_string())),
+        },
+        ColumnType::Tensor(_) | ColumnType::EncodedVector { .. } => {
+            let t = tensor::parse_json(trimmed)?;
+            if t.rank() != 1 {
+                return Err(format!("expected a flat list of numbers, got '{}'", text));
+            }
+            Ok(Value::Vector(ArcVec::new(t.as_f32().to_vec())))
+        }
+        ColumnType::Spatial => Err("spatial values can't be read from CSV".to_string()),
+        _ => Ok(Value::text(text.to_string())),
+    }
+}
+
+/// A value's field text, `None` for NULL
+fn field_text(value: &Value, col_type: &ColumnType) -> std::result::Result<Option<String>, String> {
+    fn vector(items: &[f32]) -> std::result::Result<String, String> {
+        let items = items
+            .iter()
+            .map(|x| match x.is_finite() {
+                true => Ok(format!("{:?}", x)),
+                false => Err(format!("{} has no CSV form in a vector", x)),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(format!("[{}]", items.join(", ")))
+    }
+
+    Ok(Some(match value {
+        Value::Null => return Ok(None),
+        // Columnar segments hand timestamps back as epoch microseconds
+        Value::Integer(i) if *col_type == ColumnType::Timestamp => {
+            UtcOffset::UTC.format_timestamp(Timestamp::from_micros(*i))
+        }
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Text(s) => s.to_string(),
+        Value::TextDoc(t) => t.content().to_string(),
+        Value::Vector(v) => vector(v.as_slice())?,
+        Value::Tensor(t) => vector(t.as_f32())?,
+        Value::Timestamp(ts) => UtcOffset::UTC.format_timestamp(*ts),
+        Value::Spatial(_) => return Err("spatial values have no CSV form".to_string()),
+        Value::Decimal(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) | Value::Array(_) => {
+            value.encoded_text().unwrap_or_default()
+        }
+    }))
+}
+
+/// Write one record; NULL fields as `options.null`
+fn write_record(
+    out: &mut impl Write,
+    fields: &[Option<String>],
+    options: &CsvOptions,
+) -> Result<()> {
+    let delimiter = options.delimiter as char;
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(out, "{}", delimiter)?;
+        }
+        match field {
+            None => out.write_all(options.null.as_bytes())?,
+            // Text that reads back as NULL is quoted to keep it text
+            Some(text) if *text == options.null || text.contains([delimiter, '"', '\n', '\r']) => {
+                write!(out, "\"{}\"", text.replace('"', "\"\""))?
+            }
+            Some(text) => out.write_all(text.as_bytes())?,
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Reads RFC 4180 records, a quoted field possibly spanning lines
+struct CsvReader<'a, R> {
+    input: R,
+    options: &'a CsvOptions,
+    /// Line the last record ended on (1-based)
+    line: usize,
+}
+
+impl<'a, R: BufRead> CsvReader<'a, R> {
+    fn new(input: R, options: &'a CsvOptions) -> Self {
+        Self {
+            input,
+            options,
+            line: 0,
+        }
+    }
+
+    /// The next record's fields, `None` for NULL; blank lines are skipped
+    fn next_record(&mut self) -> Result<Option<Vec<Option<String>>>> {
+        let mut text = String::new();
+        loop {
+            text.clear();
+            if self.input.read_line(&mut text)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            if !text.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        let delimiter = self.options.delimiter as char;
+        let start = self.line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        loop {
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes => {
+                        if chars.peek() == Some(&'"') {
+                            chars.next();
+                            field.push('"');
+                        } else {
+                            in_quotes = false;
+                        }
+                    }
+                    _ if in_quotes => field.push(c),
+                    '"' if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    }
+                    c if c == delimiter => {
+                        fields.push(self.finish(std::mem::take(&mut field), quoted));
+                        quoted = false;
+                    }
+                    '\r' | '\n' => {}
+                    _ => field.push(c),
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+            // The quoted field goes on past this line break
+            text.clear();
+            if self.input.read_line(&mut text)? == 0 {
+                return Err(StorageError::InvalidData(format!(
+                    "CSV line {}: unterminated quoted field",
+                    start
+                )));
+            }
+            self.line += 1;
+        }
+        fields.push(self.finish(field, quoted));
+        Ok(Some(fields))
+    }
+
+    fn finish(&self, field: String, quoted: bool) -> Option<String> {
+        match !quoted && field == self.options.null {
+            true => None,
+            false => Some(field),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str, options: &CsvOptions) -> Vec<Vec<Option<String>>> {
+        let mut reader = CsvReader::new(input.as_bytes(), options);
+        std::iter::from_fn(|| reader.next_record().unwrap()).collect()
+    }
+
+    fn some(fields: &[&str]) -> Vec<Option<String>> {
+        fields.iter().map(|f| Some(f.to_string())).collect()
+    }
+
+    #[test]
+    fn test_read_quoting() {
+        let options = CsvOptions::default();
+        assert_eq!(
+            records(
+                "a,\"b,c\",\"say \"\"hi\"\"\"\r\n\n1,\"two\nlines\",\n",
+                &options
+            ),
+            vec![
+                some(&["a", "b,c", "say \"hi\""]),
+                vec![Some("1".into()), Some("two\nlines".into()), None],
+            ]
+        );
+        // Quoted empty text is not NULL
+        assert_eq!(
+            records("\"\",x", &options),
+            vec![vec![Some(String::new()), Some("x".into())]]
+        );
+        let mut reader = CsvReader::new("a,\"open\n".as_bytes(), &options);
+        assert!(reader.next_record().is_err());
+    }
+
+    #[test]
+    fn test_write_reads_back() {
+        let options = CsvOptions {
+            delimiter: b';',
+            header: false,
+            null: "NA".into(),
+        };
+        let fields = vec![
+            Some("a;b".to_string()),
+            None,
+            Some("NA".to_string()),
+            Some("q\"".to_string()),
+            Some("plain".to_string()),
+        ];
+        let mut out = Vec::new();
+        write_record(&mut out, &fields, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "\"a;b\";NA;\"NA\";\"q\"\"\";plain\n"
+        );
+        assert_eq!(
+            records(&String::from_utf8(out).unwrap(), &options),
+            vec![fields]
+        );
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(
+            parse_field(" 42 ", &ColumnType::Integer),
+            Ok(Value::Integer(42))
+        );
+        assert_eq!(
+            parse_field("yes", &ColumnType::Boolean),
+            Ok(Value::Bool(true))
+        );
+        match parse_field("[1, 2.5]", &ColumnType::Tensor(2)) {
+            Ok(Value::Vector(v)) => assert_eq!(v.as_slice(), &[1.0, 2.5]),
+            other => panic!("expected a vector, got {:?}", other),
+        }
+        assert_eq!(
+            parse_field("1700000000000000", &ColumnType::Timestamp),
+            Ok(Value::Timestamp(Timestamp::from_micros(
+                1_700_000_000_000_000
+            )))
+        );
+        assert!(parse_field("4x", &ColumnType::Integer).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! SQL Dump
+//!
+//! [`MoteDB::dump_sql`] writes tables as the SQL that recreates them: per
+//! table a CREATE TABLE, its generated columns (ALTER TABLE ... AS), the
+//! rows as multi-row INSERTs and then its indexes, so they are built once
+//! over the loaded rows. Materialized views come last and backfill from
+//! their restored sources.
+//!
+//! Values are written as literals the parser reads back into the same
+//! column type. LINESTRING / POLYGON geometries and NaN or infinite floats
+//! have no literal form and fail the dump instead of being silently lost.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use super::core::MoteDB;
+use super::index_metadata::{IndexMetadata, IndexType};
+use crate::sql::{Lexer, Parser, QueryExecutor};
+use crate::types::{Geometry, TableSchema, Value};
+use crate::{Result, StorageError};
+
+/// Rows per INSERT statement
+const ROWS_PER_INSERT: usize = 100;
+
+impl MoteDB {
+    /// Write `table` (or every table) as SQL statements to `out`; returns
+    /// the number of rows written
+    pub fn dump_sql(self: &Arc<Self>, table: Option<&str>, out: &mut dyn Write) -> Result<usize> {
+        ensure_open!(self);
+        let tables = match table {
+            Some(name) => vec![self.get_table_schema(name)?],
+            None => {
+                let mut names = self.list_tables()?;
+                names.sort();
+                names
+                    .iter()
+                    .map(|name| self.get_table_schema(name))
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+
+        writeln!(out, "-- MoteDB {} dump", env!("CARGO_PKG_VERSION"))?;
+        let mut rows = 0;
+        let mut views = Vec::new();
+        for schema in &tables {
+            match &schema.continuous_aggregate {
+                Some(view) => views.push((schema.name.clone(), view.query.clone())),
+                None => rows += self.dump_table(schema, out)?,
+            }
+        }
+        for (name, query) in views {
+            writeln!(out, "CREATE MATERIALIZED VIEW {} AS {};", name, query)?;
+        }
+        out.flush()?;
+        Ok(rows)
+    }
+
+    fn dump_table(self: &Arc<Self>, schema: &TableSchema, out: &mut dyn Write) -> Result<usize> {
+        writeln!(out)?;
+        writeln!(out, "{};", create_table_sql(schema))?;
+        for col in &schema.columns {
+            if let Some(generated) = &col.generated {
+                writeln!(
+                    out,
+                    "ALTER TABLE {} ADD COLUMN {} {} AS ({}) {};",
+                    schema.name,
+                    col.name,
+                    col.col_type,
+                    generated.expr,
+                    if generated.stored {
+                        "STORED"
+                    } else {
+                        "VIRTUAL"
+                    }
+                )?;
+            }
+        }
+
+        let columns: Vec<&str> = schema
+            .columns
+            .iter()
+            .filter(|col| col.generated.is_none())
+            .map(|col| col.name.as_str())
+            .collect();
+        let select = format!("SELECT {} FROM {}", columns.join(", "), schema.name);
+        let statement = Parser::new(Lexer::new(&select).tokenize()?).parse()?;
+        let (_, row_iter) = QueryExecutor::new(self.clone())
+            .execute_streaming_ref(&statement)?
+            .into_row_iter()?;
+
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES",
+            schema.name,
+            columns.join(", ")
+        );
+        let mut rows = 0;
+        for row in row_iter {
+            let row = row?;
+            let values = row
+                .iter()
+                .zip(&columns)
+                .map(|(value, column)| {
+                    value_sql(value).map_err(|reason| {
+                        StorageError::InvalidData(format!(
+                            "cannot dump {}.{}: {}",
+                            schema.name, column, reason
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if rows % ROWS_PER_INSERT == 0 {
+                if rows > 0 {
+                    writeln!(out, ";")?;
+                }
+                writeln!(out, "{}", insert)?;
+            } else {
+                writeln!(out, ",")?;
+            }
+            write!(out, "  ({})", values.join(", "))?;
+            rows += 1;
+        }
+        if rows > 0 {
+            writeln!(out, ";")?;
+        }
+
+        let mut indexes = self.index_registry.list_table_indexes(&schema.name);
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        for index in &indexes {
+            writeln!(out, "{};", create_index_sql(index))?;
+        }
+        Ok(rows)
+    }
+}
+
+/// `CREATE TABLE` for a schema, without its generated columns
+fn create_table_sql(schema: &TableSchema) -> String {
+    let columns: Vec<String> = schema
+        .columns
+        .iter()
+        .filter(|col| col.generated.is_none())
+        .map(|col| {
+            let mut def = format!("{} {}", col.name, col.col_type);
+            if schema.primary_key_column.as_deref() == Some(col.name.as_str()) {
+                def.push_str(" PRIMARY KEY");
+                if col.auto_increment {
+                    def.push_str(" AUTO_INCREMENT");
+                    if let Some(start) = col.auto_increment_start {
+                        def.push_str(&format!(" = {}", start));
+                    }
+                }
+            } else if !col.nullable {
+                def.push_str(" NOT NULL");
+            }
+            def
+        })
+        .collect();
+    let mut sql = format!("CREATE TABLE {} ({})", schema.name, columns.join(", "));
+    if let Some(ts_column) = &schema.timeseries_column {
+        sql.push_str(&format!(" TIMESERIES({})", ts_column));
+    }
+    if let Some(ttl) = &schema.ttl {
+        sql.push_str(&format!(" TTL {}", ttl));
+    }
+    if let Some(max_rows) = schema.max_rows {
+        sql.push_str(&format!(" MAX_ROWS = {}", max_rows));
+    }
+    if let Some(max_bytes) = schema.max_bytes {
+        sql.push_str(&format!(" MAX_BYTES = {}", max_bytes));
+    }
+    sql
+}
+
+/// `CREATE INDEX` for a registered index
+fn create_index_sql(index: &IndexMetadata) -> String {
+    let kind = match index.index_type {
+        IndexType::Column => "",
+        IndexType::Vector => "VECTOR ",
+        IndexType::Text => "TEXT ",
+        IndexType::Octree => "SPATIAL ",
+    };
+    let mut sql = format!(
+        "CREATE {}INDEX {} ON {}({})",
+        kind, index.name, index.table_name, index.column_name
+    );
+    if index.index_type == IndexType::Vector {
+        let mut options = Vec::new();
+        if let Some(metric) = &index.metric {
+            options.push(format!("metric = '{}'", metric));
+        }
+        if index.rerank {
+            options.push("rerank = true".to_string());
+        }
+        if !options.is_empty() {
+            sql.push_str(&format!(" WITH ({})", options.join(", ")));
+        }
+    }
+    sql
+}
+
+/// SQL literal the parser reads back as `value` (in a column of its type)
+fn value_sql(value: &Value) -> std::result::Result<String, String> {
+    fn float(f: f64) -> std::result::Result<String, String> {
+        if f.is_finite() {
+            Ok(format!("{:?}", f))
+        } else {
+            Err(format!("{} has no SQL literal", f))
+        }
+    }
+    fn vector(items: &[f32]) -> std::result::Result<String, String> {
+        let items = items
+            .iter()
+            .map(|x| match x.is_finite() {
+                true => Ok(format!("{:?}", x)),
+                false => Err(format!("{} has no SQL literal", x)),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(format!("[{}]", items.join(", ")))
+    }
+    fn text(s: &str) -> String {
+        format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    Ok(match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => float(*f)?,
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Text(s) => text(s),
+        Value::TextDoc(t) => text(t.content()),
+        Value::Vector(v) => vector(v.as_slice())?,
+        Value::Tensor(t) => vector(t.as_f32())?,
+        Value::Timestamp(ts) => ts.as_micros().to_string(),
+        Value::Spatial(g) => match g.as_ref() {
+            Geometry::Point(p) => format!("POINT({}, {})", float(p.x)?, float(p.y)?),
+            Geometry::Point3D(p) => {
+                format!("POINT3D({}, {}, {})", float(p.x)?, float(p.y)?, float(p.z)?)
+            }
+            Geometry::LineString(_) | Geometry::Polygon(_) => {
+                return Err("LINESTRING and POLYGON values have no SQL literal".to_string())
+            }
+        },
+        // Typed columns read these back from their canonical text
+        Value::Decimal(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) | Value::Array(_) => {
+            text(&value.encoded_text().unwrap_or_default())
+        }
+    })
+}
@@ -0,0 +1,201 @@
+//! On-Insert Embedding Hooks
+//!
+//! An embedder computes a vector column from another column of the same row
+//! while the row is being written (e.g. `body` TEXT → `body_vec` VECTOR(384)).
+//! The vector is filled in before the WAL record is built, so the stored row,
+//! its indexes and crash recovery always agree with the source value.
+//!
+//! On insert the hook runs when the target cell is NULL and the source is not;
+//! an explicitly supplied vector is kept. On update it reruns when the source
+//! value changes and the target is left as it was. Hooks are runtime state:
+//! they are not persisted and must be registered again after open.
+
+use super::core::MoteDB;
+use crate::types::{ColumnDef, ColumnType, Row, TableSchema, Value};
+use crate::{Result, StorageError};
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// Embedding function: source value → vector for the target column
+pub type EmbedFn = dyn Fn(&Value) -> Result<Vec<f32>> + Send + Sync;
+
+/// Registered embedders, keyed by table
+#[derive(Default)]
+pub(crate) struct Embedders {
+    by_table: DashMap<String, Vec<Arc<Embedder>>>,
+}
+
+struct Embedder {
+    source: String,
+    target: String,
+    embed: Arc<EmbedFn>,
+}
+
+impl Embedder {
+    /// Source and target positions in the current schema
+    fn columns<'s>(&self, schema: &'s TableSchema) -> Option<(usize, &'s ColumnDef)> {
+        Some((
+            schema.get_column(&self.source)?.position,
+            schema.get_column(&self.target)?,
+        ))
+    }
+
+    fn compute(&self, target: &ColumnDef, source: &Value) -> Result<Value> {
+        if matches!(source, Value::Null) {
+            return Ok(Value::Null);
+        }
+        let vector = (self.embed)(source)?;
+        if let Some(dim) = target.col_type.vector_dim() {
+            if vector.len() != dim {
+                return Err(StorageError::InvalidData(format!(
+                    "Embedder for column '{}' returned {} dimensions, expected {}",
+                    target.name,
+                    vector.len(),
+                    dim
+                )));
+            }
+        }
+        Ok(target.col_type.value_from_vector(vector))
+    }
+}
+
+/// Value equality that also compares vector contents
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Vector(a), Value::Vector(b)) => a.as_slice() == b.as_slice(),
+        (Value::Tensor(a), Value::Tensor(b)) => a.as_f32() == b.as_f32(),
+        _ => a == b,
+    }
+}
+
+impl MoteDB {
+    /// Compute `target_column` from `source_column` on every insert (and on
+    /// updates that change the source). Replaces an embedder already
+    /// registered for the same target column.
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.register_embedder("docs", "body", "body_vec", move |body| Ok(model.embed(body)))?;
+    /// ```
+    pub fn register_embedder<F>(
+        &self,
+        table_name: &str,
+        source_column: &str,
+        target_column: &str,
+        embed: F,
+    ) -> Result<()>
+    where
+        F: Fn(&Value) -> Result<Vec<f32>> + Send + Sync + 'static,
+    {
+        let schema = self.table_registry.get_table(table_name)?;
+        let column = |name: &str| {
+            schema
+                .get_column(name)
+                .ok_or_else(|| StorageError::ColumnNotFound(name.to_string()))
+        };
+        let source = column(source_column)?;
+        let target = column(target_column)?;
+        if source.position == target.position {
+            return Err(StorageError::InvalidArgument(
+                "embedder source and target must be different columns".into(),
+            ));
+        }
+        if !matches!(
+            target.col_type,
+            ColumnType::Tensor(_) | ColumnType::EncodedVector { .. } | ColumnType::ShapedTensor(_)
+        ) || target.generated.is_some()
+        {
+            return Err(StorageError::InvalidArgument(format!(
+                "embedder target '{}' must be a plain VECTOR/TENSOR column",
+                target_column
+            )));
+        }
+
+        let embedder = Arc::new(Embedder {
+            source: source.name.clone(),
+            target: target.name.clone(),
+            embed: Arc::new(embed),
+        });
+        let mut hooks = self
+            .embedders
+            .by_table
+            .entry(table_name.to_string())
+            .or_default();
+        hooks.retain(|hook| hook.target != embedder.target);
+        hooks.push(embedder);
+        Ok(())
+    }
+
+    /// Remove the embedder computing `target_column`. Returns whether one
+    /// was registered.
+    pub fn unregister_embedder(&self, table_name: &str, target_column: &str) -> bool {
+        let Some(mut hooks) = self.embedders.by_table.get_mut(table_name) else {
+            return false;
+        };
+        let before = hooks.len();
+        hooks.retain(|hook| hook.target != target_column);
+        before != hooks.len()
+    }
+
+    pub(crate) fn unregister_table_embedders(&self, table_name: &str) {
+        self.embedders.by_table.remove(table_name);
+    }
+
+    fn table_embedders(&self, table_name: &str) -> Option<Vec<Arc<Embedder>>> {
+        self.embedders
+            .by_table
+            .get(table_name)
+            .filter(|hooks| !hooks.is_empty())
+            .map(|hooks| hooks.clone())
+    }
+
+    /// Fill NULL embedder targets of rows about to be inserted
+    pub(crate) fn apply_embedders(&self, schema: &TableSchema, rows: &mut [Row]) -> Result<()> {
+        let Some(hooks) = self.table_embedders(&schema.name) else {
+            return Ok(());
+        };
+        for row in rows.iter_mut() {
+            if row.len() < schema.columns.len() {
+                row.resize(schema.columns.len(), Value::Null);
+            }
+            for hook in &hooks {
+                let Some((source, target)) = hook.columns(schema) else {
+                    continue;
+                };
+                if matches!(row[target.position], Value::Null) {
+                    row[target.position] = hook.compute(target, &row[source])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompute embedder targets whose source changed (unless the update
+    /// set the target itself)
+    pub(crate) fn apply_embedders_on_update(
+        &self,
+        schema: &TableSchema,
+        old_row: &Row,
+        new_row: &mut Row,
+    ) -> Result<()> {
+        let Some(hooks) = self.table_embedders(&schema.name) else {
+            return Ok(());
+        };
+        if new_row.len() < schema.columns.len() {
+            new_row.resize(schema.columns.len(), Value::Null);
+        }
+        for hook in &hooks {
+            let Some((source, target)) = hook.columns(schema) else {
+                continue;
+            };
+            let old_source = old_row.get(source).unwrap_or(&Value::Null);
+            let old_target = old_row.get(target.position).unwrap_or(&Value::Null);
+            if !same_value(&new_row[source], old_source)
+                && same_value(&new_row[target.position], old_target)
+            {
+                new_row[target.position] = hook.compute(target, &new_row[source])?;
+            }
+        }
+        Ok(())
+    }
+}
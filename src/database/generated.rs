@@ -0,0 +1,63 @@
+//! Generated Columns - computing `ADD COLUMN c AS (expr)` values on write
+//!
+//! STORED columns are evaluated here and written like any other column, so
+//! indexes and scans see them for free. VIRTUAL columns are written as NULL
+//! and computed by the executor when a query reads them.
+
+use crate::sql::ast::Expr;
+use crate::sql::{ExprEvaluator, Lexer, Parser};
+use crate::types::{ColumnDef, ColumnType, Row, SqlRow, TableSchema, Value};
+use crate::{Result, StorageError};
+
+/// Parse a generated column's stored SQL text back into an expression
+pub(crate) fn parse_generated_expr(sql: &str) -> Result<Expr> {
+    let tokens = Lexer::new(sql).tokenize()?;
+    Parser::new(tokens).parse_standalone_expr()
+}
+
+/// Fill every generated column of `rows` from the other columns.
+///
+/// Rows are padded to the schema width first; columns are computed in
+/// position order, so a generated column may refer to an earlier one.
+pub(crate) fn fill_generated_columns(schema: &TableSchema, rows: &mut [Row]) -> Result<()> {
+    let generated: Vec<(&ColumnDef, bool, Expr)> = schema
+        .columns
+        .iter()
+        .filter_map(|c| c.generated.as_ref().map(|g| (c, g)))
+        .map(|(c, g)| Ok((c, g.stored, parse_generated_expr(&g.expr)?)))
+        .collect::<Result<_>>()?;
+    if generated.is_empty() {
+        return Ok(());
+    }
+
+    let evaluator = ExprEvaluator::new();
+    for row in rows.iter_mut() {
+        if row.len() < schema.columns.len() {
+            row.resize(schema.columns.len(), Value::Null);
+        }
+        let mut sql_row: SqlRow = schema
+            .columns
+            .iter()
+            .filter(|c| c.generated.is_none())
+            .map(|c| (c.name.clone(), row[c.position].clone()))
+            .collect();
+        for (col, stored, expr) in &generated {
+            let value = match evaluator.eval(expr, &sql_row) {
+                // Integer arithmetic feeding a FLOAT column
+                Ok(Value::Integer(i)) if col.col_type == ColumnType::Float => {
+                    Value::Float(i as f64)
+                }
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(StorageError::InvalidData(format!(
+                        "Column '{}': {}",
+                        col.name, e
+                    )))
+                }
+            };
+            row[col.position] = if *stored { value.clone() } else { Value::Null };
+            sql_row.insert(col.name.clone(), value);
+        }
+    }
+    Ok(())
+}
@@ -5,10 +5,12 @@
 //! Rows are sent as raw bytes from the flush callback and decoded
 //! lazily in the builder thread to minimize flush latency.
 
+use crate::storage::platform;
 use crate::types::{Row, RowId, TableSchema, Value};
 use crate::{Result, StorageError};
 
 use super::core::MoteDB;
+use super::indexes::column::column_index_key;
 
 /// Get total size of all files in a directory
 pub(crate) fn dir_size(dir: &std::path::Path) -> Result<u64> {
@@ -86,6 +88,13 @@ impl MoteDB {
             rows.len()
         );
 
+        if !platform::threads_available() {
+            self.batch_build_column_indexes(table_name, &schema, &rows)?;
+            self.batch_build_timestamp_indexes(&schema, &rows)?;
+            self.batch_build_vector_indexes(table_name, &schema, &rows)?;
+            return self.batch_build_text_indexes(table_name, &schema, &rows);
+        }
+
         let rows = Arc::new(rows);
         let mut handles = vec![];
 
@@ -190,7 +199,7 @@ impl MoteDB {
                 .columns
                 .iter()
                 .filter_map(|col_def| {
-                    let index_name = format!("{}.{}", table_name, col_def.name);
+                    let index_name = column_index_key(table_name, &col_def.name, &col_def.col_type);
                     self.column_indexes.get(&index_name).and_then(|index_ref| {
                         let index = index_ref.value();
                         // Skip if index is already up-to-date from synchronous path
@@ -250,15 +259,15 @@ impl MoteDB {
             None => return Ok(()),
         };
 
-        let mut ts_index = self.timestamp_index.write();
-        let mut count = 0;
-
-        for (row_id, row) in rows {
-            if let Some(crate::types::Value::Timestamp(ts)) = row.get(ts_col.position) {
-                ts_index.insert(ts.as_micros_u64(), *row_id)?;
-                count += 1;
-            }
-        }
+        let entries: Vec<(u64, RowId)> = rows
+            .iter()
+            .filter_map(|(row_id, row)| match row.get(ts_col.position) {
+                Some(crate::types::Value::Timestamp(ts)) => Some((ts.as_micros_u64(), *row_id)),
+                _ => None,
+            })
+            .collect();
+        let count = entries.len();
+        self.index_timestamp_entries(entries)?;
 
         if count > 0 {
             debug_log!(
@@ -279,7 +288,7 @@ impl MoteDB {
         rows: &[(RowId, Row)],
     ) -> Result<()> {
         for col_def in &schema.columns {
-            if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+            if col_def.col_type.vector_dim().is_some() {
                 // Look up actual index name from registry (supports custom names)
                 let index_name = match self.index_registry.find_by_column(
                     table_name,
@@ -7,6 +7,7 @@
 //! - Persistent metadata storage
 //! - Stale marking for indexes that failed to update
 
+use crate::storage::backend::{default_backend, StorageBackend};
 use crate::{Result, StorageError};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
@@ -48,6 +49,15 @@ pub struct IndexMetadata {
     /// Distance metric for vector indexes ("l2" or "cosine")
     #[serde(default)]
     pub metric: Option<String>,
+
+    /// Vector indexes: re-rank search candidates with the rows'
+    /// full-precision vectors (`WITH (rerank = true)`)
+    #[serde(default)]
+    pub rerank: bool,
+
+    /// Timestamp of the last rebuild (REINDEX), if any
+    #[serde(default)]
+    pub rebuilt_at: Option<u64>,
 }
 
 impl IndexMetadata {
@@ -57,23 +67,27 @@ impl IndexMetadata {
         column_name: String,
         index_type: IndexType,
     ) -> Self {
-        let created_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
         Self {
             name,
             table_name,
             column_name,
             index_type,
-            created_at,
+            created_at: unix_now(),
             stale: false,
             metric: None,
+            rerank: false,
+            rebuilt_at: None,
         }
     }
 }
 
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Index metadata registry
 pub struct IndexRegistry {
     /// Map: index_name -> IndexMetadata
@@ -88,27 +102,39 @@ pub struct IndexRegistry {
 
     /// Persistence path
     metadata_path: std::path::PathBuf,
+
+    /// Storage backend holding `index_metadata.bin`
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl IndexRegistry {
     /// Create a new index registry
     pub fn new(db_path: &Path) -> Self {
+        Self::with_backend(db_path, default_backend())
+    }
+
+    /// Create an index registry persisted through `backend`
+    pub fn with_backend(db_path: &Path, backend: Arc<dyn StorageBackend>) -> Self {
         let metadata_path = db_path.join("index_metadata.bin");
 
         Self {
             indexes: Arc::new(DashMap::new()),
             lookup_cache: parking_lot::RwLock::new(None),
             metadata_path,
+            backend,
         }
     }
 
     /// Load metadata from disk
     pub fn load(&self) -> Result<()> {
-        if !self.metadata_path.exists() {
+        if !self.backend.exists(&self.metadata_path) {
             return Ok(());
         }
 
-        let data = std::fs::read(&self.metadata_path).map_err(StorageError::Io)?;
+        let data = self
+            .backend
+            .read(&self.metadata_path)
+            .map_err(StorageError::Io)?;
 
         let metadata_list: Vec<IndexMetadata> =
             bincode::deserialize(&data).map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -133,15 +159,9 @@ impl IndexRegistry {
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
 
         // Write to temp file first, then rename for atomicity
-        let tmp_path = self.metadata_path.with_extension("bin.tmp");
-        {
-            let mut f = std::fs::File::create(&tmp_path).map_err(StorageError::Io)?;
-            std::io::Write::write_all(&mut f, &data).map_err(StorageError::Io)?;
-            f.sync_all().map_err(StorageError::Io)?;
-        }
-        std::fs::rename(&tmp_path, &self.metadata_path).map_err(StorageError::Io)?;
-
-        Ok(())
+        self.backend
+            .write_atomic(&self.metadata_path, &data)
+            .map_err(StorageError::Io)
     }
 
     /// Register a new index.
@@ -202,6 +222,23 @@ impl IndexRegistry {
         let _ = self.save();
     }
 
+    /// Point every index on `table.from` at `table.to` (used by ALTER TABLE
+    /// RENAME COLUMN). Index names are kept. Returns the affected index names.
+    pub fn rename_column(&self, table_name: &str, from: &str, to: &str) -> Result<Vec<String>> {
+        let mut renamed = Vec::new();
+        for mut entry in self.indexes.iter_mut() {
+            if entry.table_name == table_name && entry.column_name == from {
+                entry.column_name = to.to_string();
+                renamed.push(entry.key().clone());
+            }
+        }
+        *self.lookup_cache.write() = None; // invalidate
+        if !renamed.is_empty() {
+            self.save()?;
+        }
+        Ok(renamed)
+    }
+
     /// Get index metadata
     pub fn get(&self, index_name: &str) -> Option<IndexMetadata> {
         self.indexes
@@ -209,6 +246,14 @@ impl IndexRegistry {
             .map(|entry| entry.value().clone())
     }
 
+    /// List every registered index
+    pub fn list_all(&self) -> Vec<IndexMetadata> {
+        self.indexes
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
     /// List all indexes for a table
     pub fn list_table_indexes(&self, table_name: &str) -> Vec<IndexMetadata> {
         self.indexes
@@ -294,6 +339,19 @@ impl IndexRegistry {
         // Best-effort persist (ignore error — will be retried on next save)
         let _ = self.save();
     }
+
+    /// Clear the stale flag and record the rebuild time after the index was
+    /// rebuilt from table data.
+    pub fn mark_rebuilt(&self, index_name: &str) -> Result<()> {
+        match self.indexes.get_mut(index_name) {
+            Some(mut entry) => {
+                entry.stale = false;
+                entry.rebuilt_at = Some(unix_now());
+            }
+            None => return Err(StorageError::IndexNotFound(index_name.to_string())),
+        }
+        self.save()
+    }
 }
 
 #[cfg(test)]
@@ -330,6 +388,17 @@ mod tests {
         let found = registry.find_by_column("users", "age", IndexType::Column);
         assert_eq!(found, Some("idx_users_age".to_string()));
 
+        // Stale until rebuilt, and the flag survives a reload
+        registry.mark_stale("idx_users_age");
+        let reloaded = IndexRegistry::new(dir.path());
+        reloaded.load().unwrap();
+        assert!(reloaded.get("idx_users_age").unwrap().stale);
+        assert!(reloaded.get("idx_users_age").unwrap().rebuilt_at.is_none());
+        registry.mark_rebuilt("idx_users_age").unwrap();
+        assert!(!registry.get("idx_users_age").unwrap().stale);
+        assert!(registry.get("idx_users_age").unwrap().rebuilt_at.is_some());
+        assert!(registry.mark_rebuilt("missing").is_err());
+
         // Remove index
         registry.remove("idx_users_age").unwrap();
         assert!(registry.get("idx_users_age").is_none());
@@ -4,15 +4,52 @@
 //! Provides column value indexing for WHERE clause optimization
 
 use crate::database::core::MoteDB;
-use crate::index::column_value::{ColumnValueIndex, ColumnValueIndexConfig};
-use crate::types::{RowId, Value};
+use crate::index::column_value::{
+    encode_key, ColumnRangeIter, ColumnValueIndex, ColumnValueIndexConfig, NULL_KEY,
+};
+use crate::types::{RowId, RowIdSet, Value};
 use crate::{Result, StorageError};
 use std::sync::Arc;
 
+/// Suffix of the key an ARRAY column's element index is registered under
+pub(crate) const ELEMENT_INDEX_SUFFIX: &str = "[]";
+
+/// Key under which writes maintain (and queries find) the index on
+/// `table.column`. ARRAY columns get `table.column[]`: their index holds one
+/// entry per element, so the scalar fast paths keyed by `table.column` must
+/// never pick it up.
+pub(crate) fn column_index_key(
+    table_name: &str,
+    column_name: &str,
+    col_type: &crate::types::ColumnType,
+) -> String {
+    if matches!(col_type, crate::types::ColumnType::Array(_)) {
+        format!("{}.{}{}", table_name, column_name, ELEMENT_INDEX_SUFFIX)
+    } else {
+        format!("{}.{}", table_name, column_name)
+    }
+}
+
+/// Column index file whose keys predate the canonical encoding (see
+/// [`ColumnValueIndex::has_legacy_keys`]), left unloaded by open
+pub(crate) struct LegacyColumnIndex {
+    pub index_name: String,
+    pub table_name: String,
+    pub column_name: String,
+}
+
 impl MoteDB {
     /// Create a column value index for WHERE clause optimization
     pub fn create_column_index(&self, table_name: &str, column_name: &str) -> Result<()> {
-        let index_name = format!("{}.{}", table_name, column_name);
+        let index_name = match self
+            .table_registry
+            .get_table(table_name)
+            .ok()
+            .and_then(|schema| schema.get_column(column_name).map(|c| c.col_type.clone()))
+        {
+            Some(col_type) => column_index_key(table_name, column_name, &col_type),
+            None => format!("{}.{}", table_name, column_name),
+        };
         self.create_column_index_with_name(table_name, column_name, &index_name)
     }
 
@@ -25,7 +62,7 @@ impl MoteDB {
     ) -> Result<()> {
         ensure_open!(self);
         let indexes_dir = self.path.join("indexes");
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let index_path = indexes_dir.join(format!("column_{}.idx", index_name));
 
         let mut config = ColumnValueIndexConfig::default();
@@ -33,12 +70,20 @@ impl MoteDB {
         // frequency. With default 1 MB, 300K entries require ~21 flushes.
         // With 32 MB, all entries fit in a single buffer → 1 flush.
         config.mem_buffer_size = (self.column_index_buffer_size).max(32 * 1024 * 1024);
-        let index = ColumnValueIndex::create(
+        let mut index = ColumnValueIndex::create(
             index_path,
             table_name.to_string(),
             column_name.to_string(),
             config,
         )?;
+        if let Some(col_type) = self
+            .table_registry
+            .get_table(table_name)
+            .ok()
+            .and_then(|schema| schema.get_column(column_name).map(|c| c.col_type.clone()))
+        {
+            index = index.with_column_type(col_type);
+        }
 
         let index_arc = Arc::new(index);
         self.column_indexes
@@ -70,6 +115,7 @@ impl MoteDB {
                     let segs = store.segments_snapshot();
                     let single_seg = segs.len() == 1;
                     let mut raw_entries: Vec<([u8; 64], RowId)> = Vec::new();
+                    let mut array_entries: Vec<(crate::types::Value, RowId)> = Vec::new();
                     use std::collections::HashSet;
                     let mut seen_keys: Option<HashSet<u64>> = if single_seg {
                         None
@@ -94,39 +140,81 @@ impl MoteDB {
                                         }
                                     }
                                     let row_id = (key & 0xFFFFFFFF) as RowId;
-                                    let mut buf = [0u8; 64];
-                                    let ok = match &col_types[col_position] {
-                                        crate::types::ColumnType::Integer => fseg
-                                            .get_i64(i)
-                                            .map(|v| {
-                                                buf[..8].copy_from_slice(&v.to_be_bytes());
-                                            })
-                                            .is_some(),
-                                        crate::types::ColumnType::Float => fseg
-                                            .get_f64(i)
-                                            .map(|v| {
-                                                let bits = v.to_bits();
-                                                let sortable = if bits & (1u64 << 63) != 0 {
-                                                    !bits
-                                                } else {
-                                                    bits ^ (1u64 << 63)
-                                                };
-                                                buf[..8].copy_from_slice(&sortable.to_be_bytes());
+                                    let value = match &col_types[col_position] {
+                                        crate::types::ColumnType::Integer => {
+                                            fseg.get_i64(i).map(crate::types::Value::Integer)
+                                        }
+                                        crate::types::ColumnType::Timestamp => {
+                                            fseg.get_i64(i).map(|v| {
+                                                crate::types::Value::Timestamp(
+                                                    crate::types::Timestamp::from_micros(v),
+                                                )
                                             })
-                                            .is_some(),
-                                        _ => false,
+                                        }
+                                        crate::types::ColumnType::Float => {
+                                            fseg.get_f64(i).map(crate::types::Value::Float)
+                                        }
+                                        _ => continue,
                                     };
-                                    if ok {
+                                    // Missing values go to the NULL bucket
+                                    if let Ok(buf) = encode_key(&value.unwrap_or(Value::Null)) {
                                         raw_entries.push((buf, row_id));
                                     }
                                 }
                             }
+                        } else if col_types[col_position].is_text_encoded() {
+                            // DECIMAL/UUID/DATE/TIME are stored as text but keyed by
+                            // their order-preserving binary encoding, not raw bytes.
+                            if let Ok(tseg) = seg.sst.read_text(col_position) {
+                                raw_entries.reserve(n);
+                                for i in 0..n {
+                                    if has_deletions && seg.sst.row_map.is_deleted(i) {
+                                        continue;
+                                    }
+                                    let key = seg.sst.row_map.key(i);
+                                    if let Some(ref mut s) = seen_keys {
+                                        if !s.insert(key) {
+                                            continue;
+                                        }
+                                    }
+                                    let value = match tseg.get_str(i) {
+                                        Some(s) => col_types[col_position].value_from_text(s),
+                                        // A NULL array has no elements to index
+                                        None if index_arc.is_multi_key() => continue,
+                                        None => Value::Null,
+                                    };
+                                    let value = match value {
+                                        // ARRAY: one entry per element, keyed below
+                                        array @ crate::types::Value::Array(_) => {
+                                            array_entries
+                                                .push((array, (key & 0xFFFFFFFF) as RowId));
+                                            continue;
+                                        }
+                                        // ENUM decodes to its label, keyed like a Text column
+                                        value => value,
+                                    };
+                                    let Ok(buf) = encode_key(&value) else {
+                                        continue;
+                                    };
+                                    raw_entries.push((buf, (key & 0xFFFFFFFF) as RowId));
+                                }
+                            }
                         } else if let Ok(tseg) = seg.sst.read_text(col_position) {
                             let n = seg.sst.num_rows;
                             raw_entries.reserve(n);
+                            // Extraction skips NULLs: they go to the NULL bucket
+                            let nulls: Vec<([u8; 64], usize)> = if tseg.has_any_null() {
+                                (0..n)
+                                    .filter(|&i| tseg.is_null(i))
+                                    .map(|i| (NULL_KEY, i))
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            };
                             if has_deletions || !single_seg {
                                 // Slow path: need deletion checks and/or dedup.
-                                let extracted = tseg.bulk_extract_raw_keys();
+                                let mut extracted = tseg.bulk_extract_raw_keys();
+                                extracted.extend(nulls);
                                 for (buf, row_idx) in extracted {
                                     if has_deletions && seg.sst.row_map.is_deleted(row_idx) {
                                         continue;
@@ -142,7 +230,8 @@ impl MoteDB {
                                 }
                             } else {
                                 // Fast path: single segment, no deletions.
-                                let extracted = tseg.extract_all_raw_keys_unchecked();
+                                let mut extracted = tseg.extract_all_raw_keys_unchecked();
+                                extracted.extend(nulls);
                                 for (buf, row_idx) in extracted {
                                     let key = seg.sst.row_map.key(row_idx);
                                     let row_id = (key & 0xFFFFFFFF) as RowId;
@@ -152,10 +241,11 @@ impl MoteDB {
                         }
                     }
 
-                    indexed_count = raw_entries.len();
+                    indexed_count = raw_entries.len() + array_entries.len();
                     // Single bulk_insert_raw call — triggers bulk_load (fastest).
                     // bulk_load writes all pages + syncs superblock. No flush needed.
                     let _ = index_arc.bulk_insert_raw(raw_entries);
+                    let _ = index_arc.bulk_insert_entry(&array_entries);
                     let elapsed = start_time.elapsed();
                     debug_log!(
                         "[create_column_index] ColSegment path: {} values in {:?}",
@@ -183,20 +273,14 @@ impl MoteDB {
                                     crate::types::ColumnType::Float => {
                                         seg.get_f64(i).map(crate::types::Value::Float)
                                     }
-                                    _ => None,
+                                    _ => continue,
                                 }
                                 .unwrap_or(crate::types::Value::Null);
-                                if !matches!(val, crate::types::Value::Null) {
-                                    batch.push((val, row_id));
-                                    if batch.len() >= SORT_BATCH {
-                                        batch.sort_by(|a, b| {
-                                            a.0.partial_cmp(&b.0)
-                                                .unwrap_or(std::cmp::Ordering::Equal)
-                                        });
-                                        indexed_count += batch.len();
-                                        let _ = index_arc.batch_insert(std::mem::take(&mut batch));
-                                        batch = Vec::with_capacity(SORT_BATCH);
-                                    }
+                                batch.push((val, row_id));
+                                if batch.len() >= SORT_BATCH {
+                                    indexed_count += batch.len();
+                                    let _ = index_arc.batch_insert(std::mem::take(&mut batch));
+                                    batch = Vec::with_capacity(SORT_BATCH);
                                 }
                             }
                         }
@@ -206,28 +290,22 @@ impl MoteDB {
                                 continue;
                             }
                             let row_id = (col_sst.row_map.key(i) & 0xFFFFFFFF) as RowId;
-                            if let Some(s) = seg.get_str(i) {
-                                let val = crate::types::Value::Text(crate::types::ArcString(
+                            let val = seg.get_str(i).map_or(crate::types::Value::Null, |s| {
+                                crate::types::Value::Text(crate::types::ArcString(
                                     std::sync::Arc::from(s),
-                                ));
-                                batch.push((val, row_id));
-                                if batch.len() >= SORT_BATCH {
-                                    batch.sort_by(|a, b| {
-                                        a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)
-                                    });
-                                    indexed_count += batch.len();
-                                    let _ = index_arc.batch_insert(std::mem::take(&mut batch));
-                                    batch = Vec::with_capacity(SORT_BATCH);
-                                }
+                                ))
+                            });
+                            batch.push((val, row_id));
+                            if batch.len() >= SORT_BATCH {
+                                indexed_count += batch.len();
+                                let _ = index_arc.batch_insert(std::mem::take(&mut batch));
+                                batch = Vec::with_capacity(SORT_BATCH);
                             }
                         }
                     }
 
                     // Flush remaining
                     if !batch.is_empty() {
-                        batch.sort_by(|a, b| {
-                            a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)
-                        });
                         indexed_count += batch.len();
                         let _ = index_arc.batch_insert(batch);
                     }
@@ -282,16 +360,9 @@ impl MoteDB {
                                             }
                                         };
 
-                                        if !matches!(col_value, crate::types::Value::Null) {
-                                            batch.push((col_value, row_id));
-                                        }
+                                        batch.push((col_value, row_id));
 
                                         if batch.len() >= SORT_BATCH {
-                                            // Sort by value before inserting — faster BTreeMap inserts
-                                            batch.sort_by(|a, b| {
-                                                a.0.partial_cmp(&b.0)
-                                                    .unwrap_or(std::cmp::Ordering::Equal)
-                                            });
                                             indexed_count += batch.len();
                                             if let Err(_e) =
                                                 index_arc.batch_insert(std::mem::take(&mut batch))
@@ -312,11 +383,8 @@ impl MoteDB {
                                 }
                             }
 
-                            // Sort and flush remaining batch
+                            // Flush remaining batch (batch_insert sorts by key)
                             if !batch.is_empty() {
-                                batch.sort_by(|a, b| {
-                                    a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)
-                                });
                                 indexed_count += batch.len();
                                 if let Err(_e) = index_arc.batch_insert(batch) {
                                     debug_log!(
@@ -354,6 +422,46 @@ impl MoteDB {
         Ok(())
     }
 
+    /// Rebuild column indexes whose files predate the canonical key
+    /// encoding. Indexes created through SQL are marked stale and returned
+    /// for the background rebuild (`REINDEX`), which swaps the new file in
+    /// crash-safely; others are rebuilt here from the table's data.
+    pub(crate) fn rebuild_legacy_column_indexes(
+        &self,
+        legacy: Vec<LegacyColumnIndex>,
+    ) -> Vec<String> {
+        let mut registered = Vec::new();
+        for index in legacy {
+            if self.index_registry.get(&index.index_name).is_some() {
+                self.index_registry.mark_stale(&index.index_name);
+                registered.push(index.index_name);
+                continue;
+            }
+            let path = self
+                .path
+                .join("indexes")
+                .join(format!("column_{}.idx", index.index_name));
+            let result = crate::storage::backend::backend_for(&path)
+                .remove_file(&path)
+                .map_err(StorageError::from)
+                .and_then(|()| {
+                    self.create_column_index_with_name(
+                        &index.table_name,
+                        &index.column_name,
+                        &index.index_name,
+                    )
+                });
+            if let Err(e) = result {
+                warn_log!(
+                    "[open] Rebuilding column index '{}' failed: {:?}",
+                    index.index_name,
+                    e
+                );
+            }
+        }
+        registered
+    }
+
     /// Get all column indexes for a table
     pub fn get_table_column_indexes(&self, table_name: &str) -> Vec<String> {
         let prefix = format!("{}.", table_name);
@@ -393,6 +501,39 @@ impl MoteDB {
         index_ref.value().get(value)
     }
 
+    /// Point query as a row ID bitmap, for results that are intersected or
+    /// unioned with other index lookups
+    pub fn query_by_column_set(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        value: &Value,
+    ) -> Result<RowIdSet> {
+        ensure_open!(self);
+        let index_name = format!("{}.{}", table_name, column_name);
+        let index_ref = self.column_indexes.get(&index_name).ok_or_else(|| {
+            StorageError::Index(format!("Column index '{}' not found", index_name))
+        })?;
+
+        index_ref.value().get_set(value)
+    }
+
+    /// Rows whose ARRAY column contains `element`, via the column's element
+    /// index (`ARRAY_CONTAINS(col, element)`)
+    pub fn query_by_array_element(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        element: &Value,
+    ) -> Result<Vec<RowId>> {
+        ensure_open!(self);
+        let index_name = format!("{}.{}{}", table_name, column_name, ELEMENT_INDEX_SUFFIX);
+        let index_ref = self.column_indexes.get(&index_name).ok_or_else(|| {
+            StorageError::Index(format!("Column index '{}' not found", index_name))
+        })?;
+        index_ref.value().get(element)
+    }
+
     /// Query column value index with range (WHERE col >= start AND col <= end)
     pub fn query_by_column_range(
         &self,
@@ -483,6 +624,76 @@ impl MoteDB {
             .value()
             .query_between(lower_bound, lower_inclusive, upper_bound, upper_inclusive)
     }
+
+    /// Dual-bound range query as a row ID bitmap
+    pub fn query_by_column_between_set(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<RowIdSet> {
+        let index_name = format!("{}.{}", table_name, column_name);
+        let index_ref = self.column_indexes.get(&index_name).ok_or_else(|| {
+            StorageError::Index(format!("Column index '{}' not found", index_name))
+        })?;
+
+        index_ref.value().query_between_set(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+        )
+    }
+
+    /// Streaming dual-bound range query: row ids in index order, read one
+    /// page at a time instead of collected up front
+    pub fn query_by_column_between_iter(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        let index_name = format!("{}.{}", table_name, column_name);
+        let index_ref = self.column_indexes.get(&index_name).ok_or_else(|| {
+            StorageError::Index(format!("Column index '{}' not found", index_name))
+        })?;
+
+        index_ref.value().query_between_iter(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+        )
+    }
+
+    /// [`Self::query_by_column_between_iter`] from the largest value down
+    pub fn query_by_column_between_iter_rev(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        let index_name = format!("{}.{}", table_name, column_name);
+        let index_ref = self.column_indexes.get(&index_name).ok_or_else(|| {
+            StorageError::Index(format!("Column index '{}' not found", index_name))
+        })?;
+
+        index_ref.value().query_between_iter_rev(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+        )
+    }
 }
 
 #[cfg(test)]
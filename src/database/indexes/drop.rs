@@ -0,0 +1,140 @@
+//! Index Removal (DROP INDEX, DROP TABLE)
+//!
+//! Dropping an index retires its live handle (draining operations running
+//! on it), unregisters it, removes its files from the committed index
+//! manifest and only then deletes them. A crash part-way leaves at worst
+//! orphaned files that nothing loads, never a registered index whose files
+//! are gone.
+//!
+//! Column indexes created through `create_column_index` are kept as
+//! "table.column" without a registry entry; DROP TABLE removes those too.
+
+use super::rebuild::{index_files, remove_index_files};
+use crate::database::core::MoteDB;
+use crate::database::index_metadata::{IndexMetadata, IndexType};
+use crate::storage::backend;
+use crate::{Result, StorageError};
+
+impl MoteDB {
+    /// Drop an index with its files (`DROP INDEX name`)
+    pub fn drop_index(&self, name: &str) -> Result<()> {
+        ensure_open!(self);
+        let meta = self
+            .index_registry
+            .get(name)
+            .ok_or_else(|| StorageError::IndexNotFound(name.to_string()))?;
+        // Keep checkpoints from flushing or publishing the index meanwhile
+        let _ckpt_guard = self
+            .checkpoint_mutex
+            .lock()
+            .map_err(|_| StorageError::Lock("Checkpoint mutex poisoned".into()))?;
+        self.drop_indexes(&[meta])
+    }
+
+    /// Drop every index on a table: the registered ones, then the
+    /// unregistered "table.column" handles left in the index maps
+    pub(crate) fn drop_table_indexes(&self, table_name: &str) -> Result<()> {
+        let _ckpt_guard = self
+            .checkpoint_mutex
+            .lock()
+            .map_err(|_| StorageError::Lock("Checkpoint mutex poisoned".into()))?;
+        self.drop_indexes(&self.index_registry.list_table_indexes(table_name))?;
+
+        let prefix = format!("{}.", table_name);
+        let owned = |key: &String| key.starts_with(&prefix) || key == table_name;
+        let keys: Vec<String> = self
+            .column_indexes
+            .iter()
+            .map(|e| e.key().clone())
+            .filter(owned)
+            .collect();
+        for key in keys {
+            self.column_indexes.remove(&key);
+            self.remove_dropped_index_files(&IndexType::Column, &key);
+        }
+        let keys: Vec<String> = self
+            .vector_indexes
+            .iter()
+            .map(|e| e.key().clone())
+            .filter(owned)
+            .collect();
+        for key in keys {
+            self.vector_indexes.remove(&key);
+            self.remove_dropped_index_files(&IndexType::Vector, &key);
+        }
+        let keys: Vec<String> = self
+            .text_indexes
+            .iter()
+            .map(|e| e.key().clone())
+            .filter(owned)
+            .collect();
+        for key in keys {
+            self.text_indexes.remove(&key);
+            self.remove_dropped_index_files(&IndexType::Text, &key);
+        }
+        let keys: Vec<String> = self
+            .ioctree_indexes
+            .iter()
+            .map(|e| e.key().clone())
+            .filter(owned)
+            .collect();
+        for key in keys {
+            self.ioctree_indexes.remove(&key);
+            self.remove_dropped_index_files(&IndexType::Octree, &key);
+        }
+        Ok(())
+    }
+
+    /// Retire, unregister, unpublish and delete `metas` as one drop. The
+    /// caller holds the checkpoint mutex.
+    fn drop_indexes(&self, metas: &[IndexMetadata]) -> Result<()> {
+        if metas.is_empty() {
+            return Ok(());
+        }
+        let retired: Vec<_> = metas
+            .iter()
+            .map(|meta| (meta, self.retire_live_index(meta)))
+            .collect();
+
+        // The manifest commit is all or nothing: if it fails, nothing has
+        // changed yet and the indexes are put back
+        if let Err(e) = self.unpublish_indexes(metas) {
+            for (meta, handle) in retired {
+                self.restore_retired_index(&meta.name, handle);
+            }
+            return Err(e);
+        }
+        let mut removed = Vec::new();
+        for (meta, _) in &retired {
+            if let Err(e) = self.index_registry.remove(&meta.name) {
+                // Nothing is deleted yet: put back what was not unregistered.
+                // Unpublished indexes are rebuilt on the next open and
+                // published again by the next checkpoint.
+                for (meta, handle) in retired {
+                    if !removed.contains(&meta.name) {
+                        self.restore_retired_index(&meta.name, handle);
+                    }
+                }
+                return Err(e);
+            }
+            removed.push(meta.name.clone());
+        }
+
+        for meta in metas {
+            self.remove_dropped_index_files(&meta.index_type, &meta.name);
+        }
+        debug_log!("[drop_index] Dropped {:?}", removed);
+        Ok(())
+    }
+
+    /// Delete an index's files, best effort: the index is already gone
+    fn remove_dropped_index_files(&self, index_type: &IndexType, name: &str) {
+        for root in index_files(&self.path.join("indexes"), index_type, name) {
+            if backend::backend_for(&root).exists(&root) {
+                if let Err(e) = remove_index_files(&root) {
+                    warn_log!("[drop_index] Failed to remove {:?}: {}", root, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Index Health
+//!
+//! Per-index maintenance statistics for monitoring: write amplification,
+//! updates still buffered in memory, dead data (tombstones, fragmentation)
+//! and the last rebuild time. Fleets can poll `index_health()` and schedule
+//! a flush or `REINDEX` before recall or latency degrade.
+//!
+//! Byte counters start at zero when the database is opened.
+
+use crate::database::core::MoteDB;
+use crate::database::index_metadata::{IndexMetadata, IndexType};
+use crate::Result;
+
+/// Maintenance statistics of one index
+#[derive(Debug, Clone)]
+pub struct IndexHealth {
+    pub name: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub index_type: IndexType,
+
+    /// Live entries (approximate for column indexes)
+    pub entries: u64,
+
+    /// Updates held in memory (IndexMemBuffer entries, deferred deletes,
+    /// unflushed documents) and not yet in the on-disk structure
+    pub pending_updates: u64,
+
+    /// Deleted or superseded entries the index still carries
+    pub tombstones: u64,
+
+    /// `tombstones / (entries + tombstones)`
+    pub tombstone_ratio: f64,
+
+    /// Share of the index files held by dead records (0.0–1.0). Reported for
+    /// append-only layouts (vector indexes); `None` where space is reused.
+    pub fragmentation: Option<f64>,
+
+    /// Bytes written to the index files since open
+    pub bytes_written: u64,
+
+    /// Bytes of entries submitted to the index since open
+    pub bytes_indexed: u64,
+
+    /// `bytes_written / bytes_indexed`; `None` until something was indexed
+    pub write_amplification: Option<f64>,
+
+    /// Out of sync with table data after a failed update
+    pub stale: bool,
+
+    /// Creation timestamp (Unix seconds)
+    pub created_at: u64,
+
+    /// Timestamp of the last REINDEX (Unix seconds), if any
+    pub last_rebuilt_at: Option<u64>,
+}
+
+impl IndexHealth {
+    fn new(meta: IndexMetadata) -> Self {
+        Self {
+            name: meta.name,
+            table_name: meta.table_name,
+            column_name: meta.column_name,
+            index_type: meta.index_type,
+            entries: 0,
+            pending_updates: 0,
+            tombstones: 0,
+            tombstone_ratio: 0.0,
+            fragmentation: None,
+            bytes_written: 0,
+            bytes_indexed: 0,
+            write_amplification: None,
+            stale: meta.stale,
+            created_at: meta.created_at,
+            last_rebuilt_at: meta.rebuilt_at,
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        let total = self.entries + self.tombstones;
+        if total > 0 {
+            self.tombstone_ratio = self.tombstones as f64 / total as f64;
+        }
+        if self.bytes_indexed > 0 {
+            self.write_amplification = Some(self.bytes_written as f64 / self.bytes_indexed as f64);
+        }
+        self
+    }
+}
+
+impl MoteDB {
+    /// Maintenance statistics of every index, ordered by table and index name
+    pub fn index_health(&self) -> Result<Vec<IndexHealth>> {
+        ensure_open!(self);
+        let mut metas = Vec::new();
+        for table in self.list_tables()? {
+            metas.extend(self.index_registry.list_table_indexes(&table));
+        }
+        metas.sort_by(|a, b| (&a.table_name, &a.name).cmp(&(&b.table_name, &b.name)));
+        Ok(metas
+            .into_iter()
+            .map(|m| self.collect_index_health(m))
+            .collect())
+    }
+
+    fn collect_index_health(&self, meta: IndexMetadata) -> IndexHealth {
+        let name = meta.name.clone();
+        let mut health = IndexHealth::new(meta);
+
+        // An index that failed to load reports only its registry state
+        match health.index_type {
+            IndexType::Column => {
+                if let Some(index) = self.column_indexes.get(&name) {
+                    let pending = index.pending_updates() as u64;
+                    health.entries = index.entry_count() as u64 + pending;
+                    health.pending_updates = pending;
+                    health.tombstones = index.tombstone_count() as u64;
+                    health.bytes_written = index.bytes_written();
+                    health.bytes_indexed = index.bytes_indexed();
+                }
+            }
+            IndexType::Vector => {
+                if let Some(index) = self.vector_indexes.get(&name) {
+                    let index = index.read();
+                    health.entries = index.len() as u64;
+                    health.tombstones = index.dead_vector_count() as u64;
+                    health.fragmentation = Some(index.fragmentation());
+                    health.bytes_written = index.bytes_written();
+                    health.bytes_indexed = index.bytes_indexed();
+                }
+            }
+            IndexType::Text => {
+                if let Some(index) = self.text_indexes.get(&name) {
+                    let index = index.read();
+                    health.entries = index.stats().total_docs;
+                    health.pending_updates = index.pending_docs() as u64;
+                    health.tombstones = index.deleted_doc_count() as u64;
+                    health.bytes_written = index.bytes_written();
+                    health.bytes_indexed = index.bytes_indexed();
+                }
+            }
+            IndexType::Octree => {
+                if let Some(index) = self.ioctree_indexes.get(&name) {
+                    let index = index.read();
+                    health.entries = index.len() as u64;
+                    health.bytes_written = index.bytes_written();
+                    health.bytes_indexed = index.bytes_indexed();
+                }
+            }
+        }
+        health.finish()
+    }
+}
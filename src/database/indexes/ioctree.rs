@@ -14,9 +14,9 @@ impl MoteDB {
     pub fn create_ioctree_index(&self, name: &str) -> Result<()> {
         ensure_open!(self);
         let indexes_dir = self.path.join("indexes");
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let index_dir = indexes_dir.join(format!("ioctree_{}", name));
-        std::fs::create_dir_all(&index_dir)?;
+        crate::storage::backend::backend_for(&index_dir).create_dir_all(&index_dir)?;
 
         let config = IOctreeConfig {
             data_dir: Some(index_dir.join("ioctree.bin")),
@@ -106,6 +106,28 @@ impl MoteDB {
         )))
     }
 
+    /// Estimated number of points within a bounding box (no leaf I/O)
+    pub fn ioctree_estimate_range(&self, index_name: &str, bbox: &BoundingBox3D) -> Result<usize> {
+        if let Some(index) = self.ioctree_indexes.get(index_name) {
+            return Ok(index.read().estimate_range_count(bbox));
+        }
+        Err(StorageError::Index(format!(
+            "i-Octree index '{}' not found",
+            index_name
+        )))
+    }
+
+    /// Number of points in an i-Octree index
+    pub fn ioctree_point_count(&self, index_name: &str) -> Result<usize> {
+        if let Some(index) = self.ioctree_indexes.get(index_name) {
+            return Ok(index.read().len());
+        }
+        Err(StorageError::Index(format!(
+            "i-Octree index '{}' not found",
+            index_name
+        )))
+    }
+
     /// 🚀 Build i-Octree from columnar SSTable data.
     /// Reads geometries directly from column segment — O(N), zero per-row decode.
     pub fn build_ioctree_from_columnar(
@@ -135,6 +157,42 @@ impl MoteDB {
         Ok(geoms.len())
     }
 
+    /// Backfill an i-Octree index from existing table data, falling back to a
+    /// row scan when the table has no legacy columnar SSTable.
+    pub fn populate_ioctree_index(
+        &self,
+        index_name: &str,
+        table_name: &str,
+        col_position: usize,
+    ) -> Result<usize> {
+        // Columnar fast path returns Ok(0) for ColSegmentStore-backed tables
+        // (not in legacy columnar_sstables); in that case fall back to row scan.
+        match self.build_ioctree_from_columnar(index_name, table_name, col_position) {
+            Ok(count) if count > 0 => return Ok(count),
+            _ => {}
+        }
+
+        let mut backfill_count = 0;
+        for result in self.scan_table_rows_streaming(table_name)? {
+            let (row_id, row) = result?;
+            if let Some(crate::types::Value::Spatial(geometry)) = row.get(col_position) {
+                // 2D points index at z = 0, as on insert
+                if matches!(**geometry, Geometry::Point(_) | Geometry::Point3D(_)) {
+                    if let Err(_e) = self.insert_ioctree_point(row_id, index_name, geometry) {
+                        debug_log!(
+                            "⚠️ Failed to backfill ioctree index for row {}: {}",
+                            row_id,
+                            _e
+                        );
+                    } else {
+                        backfill_count += 1;
+                    }
+                }
+            }
+        }
+        Ok(backfill_count)
+    }
+
     /// Flush all i-Octree indexes to disk
     pub fn flush_ioctree_indexes(&self) -> Result<()> {
         for entry in self.ioctree_indexes.iter() {
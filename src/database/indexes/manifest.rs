@@ -0,0 +1,499 @@
+//! Index Manifest (atomic multi-index checkpoints)
+//!
+//! Index files are flushed independently of table data, so a crash could
+//! leave an index half-way between two states, or at a state the data it
+//! describes never reached. Every checkpoint therefore publishes the files of
+//! all indexes through the storage [`Manifest`] in one `VersionEdit`, after
+//! the table data is durable and before the WAL is truncated. Each index is
+//! flushed and fingerprinted (size, mtime, CRC32) under its own lock; an index the
+//! async builder is holding is unpublished rather than recorded mid-update.
+//!
+//! On open, an index whose files differ from the committed version — the
+//! crash landed between its flush and the commit, or it changed after the
+//! last one — is discarded before loading, marked stale and rebuilt from
+//! table data in the background, so recovery never pairs data with an index
+//! state it did not commit alongside. Files whose size and mtime match the
+//! committed ones are trusted without re-hashing.
+//!
+//! Vector indexes that keep a mutation log (index.wal, started by their
+//! first flush) are exempt: loading one replays the log and repairs its
+//! graph, so the mutations since the last checkpoint survive in place.
+//!
+//! Text indexes keep no log of their own: documents indexed since the last
+//! checkpoint live in memory until a checkpoint flushes and publishes them.
+//! When the WAL replays rows of a table on open, its text indexes are behind
+//! those rows and are discarded and rebuilt like unpublished ones.
+
+use super::rebuild::{index_files, remove_index_files};
+use crate::database::core::MoteDB;
+use crate::database::index_metadata::{IndexMetadata, IndexRegistry, IndexType};
+use crate::index::vamana::DiskANNIndex;
+use crate::storage::backend;
+use crate::storage::manifest::{FileMetadata, FileType, Manifest, Version, VersionEdit};
+use crate::storage::platform;
+use crate::Result;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Manifest log size past which it is compacted into a snapshot
+const COMPACT_LOG_BYTES: u64 = 1 << 20;
+
+/// Index files committed at each checkpoint
+pub(crate) struct IndexManifest {
+    manifest: Manifest,
+    /// CRC32 per file, reused while its size and mtime are unchanged
+    checksums: Mutex<HashMap<PathBuf, (u64, u64, u32)>>,
+}
+
+/// One index file as fingerprinted at checkpoint time
+struct IndexFile {
+    /// Path relative to the database directory
+    path: String,
+    file_type: FileType,
+    size: u64,
+    /// mtime in nanoseconds since the UNIX epoch
+    modified: u64,
+    checksum: u32,
+}
+
+fn file_type(index_type: &IndexType, root: &Path) -> FileType {
+    match index_type {
+        IndexType::Column => FileType::BTreeIndex,
+        IndexType::Vector => FileType::VectorIndex,
+        IndexType::Octree => FileType::SpatialIndex,
+        IndexType::Text if root.to_string_lossy().ends_with(".dict.d") => FileType::TextIndexDict,
+        IndexType::Text => FileType::TextIndexLSM,
+    }
+}
+
+fn nanos(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn relative(db_path: &Path, path: &Path) -> String {
+    path.strip_prefix(db_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Whether `path` is `root` or lies under it
+fn under(path: &str, root: &str) -> bool {
+    path == root
+        || path
+            .strip_prefix(root)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Every file of an index, recursing into its directories
+fn walk(root: &Path, out: &mut Vec<PathBuf>) {
+    let fs = backend::backend_for(root);
+    if fs.is_dir(root) {
+        if let Ok(entries) = fs.list_dir(root) {
+            for entry in entries {
+                walk(&entry, out);
+            }
+        }
+    } else if fs.exists(root) {
+        out.push(root.to_path_buf());
+    }
+}
+
+/// Roots of an index relative to the database directory
+fn index_roots(db_path: &Path, meta: &IndexMetadata) -> Vec<String> {
+    index_files(&db_path.join("indexes"), &meta.index_type, &meta.name)
+        .iter()
+        .map(|root| relative(db_path, root))
+        .collect()
+}
+
+/// Each file of an index with the type it is recorded under
+fn index_paths(db_path: &Path, meta: &IndexMetadata) -> Vec<(PathBuf, FileType)> {
+    let mut paths = Vec::new();
+    for root in index_files(&db_path.join("indexes"), &meta.index_type, &meta.name) {
+        let file_type = file_type(&meta.index_type, &root);
+        let mut files = Vec::new();
+        walk(&root, &mut files);
+        paths.extend(files.into_iter().map(|path| (path, file_type.clone())));
+    }
+    paths
+}
+
+/// Committed index files outside `roots`, as they were committed
+fn committed_except(version: &Version, roots: &[String]) -> Vec<IndexFile> {
+    version
+        .files
+        .values()
+        .flatten()
+        .filter(|f| !roots.iter().any(|root| under(&f.path, root)))
+        .map(|f| IndexFile {
+            path: f.path.clone(),
+            file_type: f.file_type.clone(),
+            size: f.size,
+            modified: f.modified.unwrap_or(0),
+            checksum: f.checksum,
+        })
+        .collect()
+}
+
+impl IndexManifest {
+    /// Open (or start) the manifest in the database directory
+    pub(crate) fn open(db_path: &Path) -> Result<Self> {
+        Ok(Self {
+            manifest: Manifest::open(db_path)?,
+            checksums: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Size, mtime and CRC32 of a file, skipping the hash when it is
+    /// unchanged since it was last fingerprinted
+    fn fingerprint(&self, path: &Path) -> Result<(u64, u64, u32)> {
+        let fs = backend::backend_for(path);
+        let size = fs.file_len(path)?;
+        // Without an mtime nothing proves the file unchanged: always hash
+        let modified = fs.modified(path).map(nanos).unwrap_or(0);
+        if let Some(&(s, m, crc)) = self.checksums.lock().get(path) {
+            if s == size && m == modified && modified != 0 {
+                return Ok((size, modified, crc));
+            }
+        }
+        let crc = Manifest::calculate_checksum(path)?;
+        self.checksums
+            .lock()
+            .insert(path.to_path_buf(), (size, modified, crc));
+        Ok((size, modified, crc))
+    }
+
+    /// Fingerprint an index's files; the caller holds the index flushed and
+    /// locked so nothing writes to them meanwhile
+    fn snapshot(&self, db_path: &Path, meta: &IndexMetadata) -> Result<Vec<IndexFile>> {
+        index_paths(db_path, meta)
+            .into_iter()
+            .map(|(path, file_type)| {
+                let (size, modified, checksum) = self.fingerprint(&path)?;
+                Ok(IndexFile {
+                    path: relative(db_path, &path),
+                    file_type,
+                    size,
+                    modified,
+                    checksum,
+                })
+            })
+            .collect()
+    }
+
+    /// Indexes whose files on disk are not the ones last committed, plus the
+    /// text indexes of `replayed_tables`. Nothing else is reported before the
+    /// first commit (new or pre-manifest databases). A file is only hashed
+    /// when its mtime differs from the committed one.
+    fn unpublished(
+        &self,
+        db_path: &Path,
+        registry: &IndexRegistry,
+        replayed_tables: &HashSet<String>,
+    ) -> Vec<IndexMetadata> {
+        let (replayed, registered): (Vec<_>, Vec<_>) =
+            registry.list_all().into_iter().partition(|meta| {
+                meta.index_type == IndexType::Text && replayed_tables.contains(&meta.table_name)
+            });
+        let mut stale = replayed;
+
+        let version = self.manifest.current_version();
+        if version.version_number == 0 {
+            return stale;
+        }
+        let committed: Vec<&FileMetadata> = version.files.values().flatten().collect();
+
+        for meta in registered {
+            if meta.index_type == IndexType::Vector
+                && index_files(&db_path.join("indexes"), &meta.index_type, &meta.name)
+                    .iter()
+                    .all(|root| DiskANNIndex::recovers_in_place(root))
+            {
+                continue;
+            }
+            let roots = index_roots(db_path, &meta);
+            let expected: HashMap<&str, &FileMetadata> = committed
+                .iter()
+                .filter(|f| roots.iter().any(|root| under(&f.path, root)))
+                .map(|f| (f.path.as_str(), *f))
+                .collect();
+            let actual = index_paths(db_path, &meta);
+
+            // An index without files cannot be loaded either (its last
+            // rebuild never finished)
+            let matches = !actual.is_empty()
+                && actual.len() == expected.len()
+                && actual.iter().all(|(path, _)| {
+                    let Some(committed) = expected.get(relative(db_path, path).as_str()) else {
+                        return false;
+                    };
+                    let fs = backend::backend_for(path);
+                    let Ok(size) = fs.file_len(path) else {
+                        return false;
+                    };
+                    if size != committed.size {
+                        return false;
+                    }
+                    if let Some(modified) = fs.modified(path).map(nanos) {
+                        if Some(modified) == committed.modified {
+                            self.checksums.lock().insert(
+                                path.clone(),
+                                (committed.size, modified, committed.checksum),
+                            );
+                            return true;
+                        }
+                    }
+                    Manifest::calculate_checksum(path).is_ok_and(|crc| crc == committed.checksum)
+                });
+            if !matches {
+                stale.push(meta);
+            }
+        }
+        stale
+    }
+
+    /// Commit the current files of one index, keeping every other index's
+    /// committed files. Nothing is committed before the first checkpoint.
+    fn replace_index(&self, db_path: &Path, meta: &IndexMetadata) -> Result<()> {
+        let version = self.manifest.current_version();
+        if version.version_number == 0 {
+            return Ok(());
+        }
+        let mut published = committed_except(&version, &index_roots(db_path, meta));
+        published.extend(self.snapshot(db_path, meta)?);
+        self.commit(published)
+    }
+
+    /// Commit the committed files minus those of `metas` (dropped indexes)
+    fn remove_indexes(&self, db_path: &Path, metas: &[IndexMetadata]) -> Result<()> {
+        let version = self.manifest.current_version();
+        if version.version_number == 0 {
+            return Ok(());
+        }
+        let roots: Vec<String> = metas
+            .iter()
+            .flat_map(|meta| index_roots(db_path, meta))
+            .collect();
+        self.commit(committed_except(&version, &roots))
+    }
+
+    /// Commit `published` as the complete set of index files
+    fn commit(&self, published: Vec<IndexFile>) -> Result<()> {
+        let version = self.manifest.current_version();
+        let mut published: HashMap<String, IndexFile> = published
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+
+        let mut edit = VersionEdit::new();
+        let mut next_id = 1;
+        for meta in version.files.values().flatten() {
+            next_id = next_id.max(meta.file_id + 1);
+            let unchanged = published.get(&meta.path).is_some_and(|file| {
+                file.size == meta.size
+                    && file.checksum == meta.checksum
+                    && Some(file.modified) == meta.modified
+                    && file.file_type == meta.file_type
+            });
+            if unchanged {
+                published.remove(&meta.path);
+            } else {
+                edit.delete_file(meta.file_id, meta.file_type.clone());
+            }
+        }
+        for file in published.into_values() {
+            edit.add_file(FileMetadata {
+                file_id: next_id,
+                file_type: file.file_type,
+                path: file.path,
+                size: file.size,
+                checksum: file.checksum,
+                min_key: None,
+                max_key: None,
+                level: None,
+                modified: Some(file.modified),
+            });
+            next_id += 1;
+        }
+        if edit.is_empty() {
+            return Ok(());
+        }
+
+        self.manifest.apply_verified_edit(edit)?;
+        if self.manifest.log_size()? > COMPACT_LOG_BYTES {
+            self.manifest.compact()?;
+        }
+        Ok(())
+    }
+}
+
+impl MoteDB {
+    /// Open the index manifest and discard indexes whose files are not the
+    /// committed ones, or text indexes of tables the WAL replay wrote to,
+    /// before any index is loaded. Returns the manifest and the discarded
+    /// indexes (marked stale) to rebuild once open.
+    pub(crate) fn open_index_manifest(
+        db_path: &Path,
+        registry: &IndexRegistry,
+        replayed_tables: &HashSet<String>,
+    ) -> (Option<Arc<IndexManifest>>, Vec<String>) {
+        let manifest = match IndexManifest::open(db_path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                warn_log!("[open] Index manifest unavailable: {:?}", e);
+                return (None, Vec::new());
+            }
+        };
+        let mut discarded = Vec::new();
+        for meta in manifest.unpublished(db_path, registry, replayed_tables) {
+            for root in index_files(&db_path.join("indexes"), &meta.index_type, &meta.name) {
+                if backend::backend_for(&root).exists(&root) {
+                    if let Err(_e) = remove_index_files(&root) {
+                        debug_log!("[open] Failed to discard {:?}: {}", root, _e);
+                    }
+                }
+            }
+            registry.mark_stale(&meta.name);
+            discarded.push(meta.name);
+        }
+        (Some(Arc::new(manifest)), discarded)
+    }
+
+    /// Rebuild the indexes discarded by [`Self::open_index_manifest`] on a
+    /// background thread, so `open()` does not wait on table scans. Queries
+    /// scan the table until each index is swapped in, and
+    /// `wait_for_indexes_ready` waits for the rebuild like a pending build
+    /// batch. One that fails stays stale until `REINDEX`. Without threads
+    /// the rebuild runs before `open()` returns.
+    pub(crate) fn spawn_discarded_index_rebuild(&self, names: Vec<String>) {
+        if names.is_empty() {
+            return;
+        }
+        let db = self.clone_for_callback();
+        let rebuild = move || {
+            for name in &names {
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    db.rebuild_index(name)
+                }));
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        warn_log!("[open] Rebuilding index '{}' failed: {:?}", name, e)
+                    }
+                    Err(_) => warn_log!("[open] Rebuilding index '{}' panicked", name),
+                }
+            }
+        };
+        if !platform::threads_available() {
+            rebuild();
+            return;
+        }
+        let pending = self.pending_index_batches.clone();
+        pending.fetch_add(1, Ordering::Relaxed);
+        let done = pending.clone();
+        let spawned = std::thread::Builder::new()
+            .name("index-rebuild".into())
+            .spawn(move || {
+                rebuild();
+                done.fetch_sub(1, Ordering::Relaxed);
+            });
+        if let Err(e) = spawned {
+            pending.fetch_sub(1, Ordering::Relaxed);
+            warn_log!("[open] Failed to start index rebuild thread: {}", e);
+        }
+    }
+
+    /// Publish the files of a rebuilt index through the manifest, so the
+    /// next open loads it instead of discarding it. The caller holds the
+    /// checkpoint mutex and keeps writes to the index out.
+    pub(crate) fn publish_rebuilt_index(&self, meta: &IndexMetadata) -> Result<()> {
+        match self.index_manifest.as_ref() {
+            Some(manifest) => manifest.replace_index(&self.path, meta),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the files of dropped indexes out of the manifest in one commit.
+    /// The caller holds the checkpoint mutex.
+    pub(crate) fn unpublish_indexes(&self, metas: &[IndexMetadata]) -> Result<()> {
+        match self.index_manifest.as_ref() {
+            Some(manifest) => manifest.remove_indexes(&self.path, metas),
+            None => Ok(()),
+        }
+    }
+
+    /// Publish the current files of every index through the manifest.
+    ///
+    /// Called from checkpoints once table data is durable. While the async
+    /// builder runs, a text or vector index it holds is unpublished instead
+    /// of waited for; a crash before the next checkpoint rebuilds it.
+    pub(crate) fn publish_index_files(&self) -> Result<()> {
+        let Some(manifest) = self.index_manifest.as_ref() else {
+            return Ok(());
+        };
+        let wait = !self.is_async_index_pipeline_active();
+
+        let mut published = Vec::new();
+        for meta in self.index_registry.list_all() {
+            let name = meta.name.as_str();
+            let snapshot = || manifest.snapshot(&self.path, &meta);
+            let files = match meta.index_type {
+                IndexType::Column => {
+                    match self.column_indexes.get(name).map(|r| r.value().clone()) {
+                        Some(index) => Some(index.flush_then(snapshot)??),
+                        None => None,
+                    }
+                }
+                IndexType::Vector => match self.vector_indexes.get(name).map(|r| r.value().clone())
+                {
+                    Some(index) => flush_locked(&index, wait, |i| i.flush(), snapshot)?,
+                    None => None,
+                },
+                IndexType::Text => match self.text_indexes.get(name).map(|r| r.value().clone()) {
+                    Some(index) => flush_locked(&index, wait, |i| i.flush(), snapshot)?,
+                    None => None,
+                },
+                IndexType::Octree => {
+                    match self.ioctree_indexes.get(name).map(|r| r.value().clone()) {
+                        Some(index) => flush_locked(&index, true, |i| i.flush(), snapshot)?,
+                        None => None,
+                    }
+                }
+            };
+            match files {
+                Some(files) => published.extend(files),
+                None => debug_log!("[checkpoint] Index '{}' left unpublished", name),
+            }
+        }
+        manifest.commit(published)
+    }
+}
+
+/// Flush an index behind its lock and fingerprint it before unlocking.
+/// `None` when `wait` is false and the lock is held elsewhere.
+fn flush_locked<T>(
+    index: &RwLock<T>,
+    wait: bool,
+    flush: impl FnOnce(&mut T) -> Result<()>,
+    snapshot: impl FnOnce() -> Result<Vec<IndexFile>>,
+) -> Result<Option<Vec<IndexFile>>> {
+    let guard = if wait {
+        Some(index.write())
+    } else {
+        index.try_write()
+    };
+    match guard {
+        Some(mut guard) => {
+            flush(&mut guard)?;
+            Ok(Some(snapshot()?))
+        }
+        None => Ok(None),
+    }
+}
@@ -6,13 +6,22 @@
 //! - text: Full-text search with BM25 ranking
 //! - vector: Vector similarity search with DiskANN
 //! - ioctree: i-Octree 3D point cloud for embodied intelligence
+//! - rebuild: REINDEX, rebuilding any index from table data
+//! - drop: DROP INDEX / DROP TABLE, removing indexes with their files
+//! - health: Per-index maintenance statistics (`index_health()`)
+//! - manifest: Index files committed with each checkpoint through the Manifest
 
 pub mod column;
+pub mod drop;
+pub mod health;
 pub mod ioctree;
+pub mod manifest;
+pub mod rebuild;
 pub mod text;
 pub mod timestamp;
 pub mod vector;
 
 // Re-export for convenience
+pub use health::IndexHealth;
 pub use timestamp::{MemTableScanProfile, QueryProfile};
-pub use vector::VectorIndexStats;
+pub use vector::{VectorIndexStats, VectorSearchOptions};
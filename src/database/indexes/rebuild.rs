@@ -0,0 +1,803 @@
+//! Index Rebuild (REINDEX)
+//!
+//! Reconstructs an index from table data without taking the live one offline.
+//! The replacement is built under a staging name (`{name}@reindex`) from a
+//! key-ordered scan of the table's `ColSegmentStore`, while a `WriteCapture`
+//! on the store records the rows written meanwhile; those are replayed into
+//! the replacement until it has caught up. The last replay and the swap run
+//! with the table's writes paused on its write gate, so none slips between
+//! them.
+//!
+//! The swap renames all of an index's files (a text index has two roots)
+//! behind a marker listing them, reopens the index and commits its files
+//! through the index manifest. A swap interrupted by a crash still has its
+//! marker and is rolled back as a whole by `recover_index_rebuilds`.
+
+use crate::database::core::MoteDB;
+use crate::database::index_metadata::{IndexMetadata, IndexType};
+use crate::database::indexes::column::column_index_key;
+use crate::index::column_value::{ColumnValueIndex, ColumnValueIndexConfig};
+use crate::index::ioctree::IOctreeIndex;
+use crate::index::text_fts::TextFTSIndex;
+use crate::index::vamana::{DiskANNIndex, VamanaConfig};
+use crate::storage::backend::{self, OpenFlags};
+use crate::storage::col_segment::{ColSegmentStore, WriteCapture};
+use crate::types::{ColumnDef, RowId, TableSchema, Value};
+use crate::{Result, StorageError};
+use parking_lot::RwLock;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Suffix of the index name a replacement is built under
+const STAGING_SUFFIX: &str = "@reindex";
+
+/// Suffix the live files carry while the replacement is moved into place
+const REPLACED_SUFFIX: &str = "@replaced";
+
+/// Suffix of the marker that exists while an index's files are being swapped
+const SWAP_SUFFIX: &str = "@swap";
+
+/// Rows read from the table per staging batch
+const SCAN_BATCH: usize = 10_000;
+
+/// Replay rounds run while writes continue, before the table is paused
+const MAX_CATCH_UP_ROUNDS: usize = 8;
+
+/// Captured rows few enough to replay with the table's writes paused
+const PAUSED_CATCH_UP_ROWS: usize = 1024;
+
+/// Path an index is created from (what `create_*_index` and the loaders use)
+fn index_path(indexes_dir: &Path, index_type: &IndexType, name: &str) -> PathBuf {
+    match index_type {
+        IndexType::Column => indexes_dir.join(format!("column_{}.idx", name)),
+        IndexType::Vector => indexes_dir.join(format!("vector_{}", name)),
+        IndexType::Text => indexes_dir.join(format!("text_{}", name)),
+        IndexType::Octree => indexes_dir.join(format!("ioctree_{}", name)),
+    }
+}
+
+/// Files and directories an index owns on disk. A text index keeps its
+/// postings and dictionary next to its base path rather than under it.
+pub(super) fn index_files(indexes_dir: &Path, index_type: &IndexType, name: &str) -> Vec<PathBuf> {
+    let path = index_path(indexes_dir, index_type, name);
+    match index_type {
+        IndexType::Text => vec![path.with_extension("fts.d"), path.with_extension("dict.d")],
+        _ => vec![path],
+    }
+}
+
+/// Live handle taken out of service while its replacement is swapped in
+pub(super) enum RetiredIndex {
+    /// Column handle and every key (name and aliases) it was registered under
+    Column(Arc<ColumnValueIndex>, Vec<String>),
+    Vector(Arc<RwLock<DiskANNIndex>>),
+    Text(Arc<RwLock<TextFTSIndex>>),
+    Octree(Arc<RwLock<IOctreeIndex>>),
+    None,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+fn replaced_path(path: &Path) -> PathBuf {
+    with_suffix(path, REPLACED_SUFFIX)
+}
+
+pub(super) fn remove_index_files(path: &Path) -> std::io::Result<()> {
+    let fs = backend::backend_for(path);
+    if fs.is_dir(path) {
+        fs.remove_dir_all(path)
+    } else {
+        fs.remove_file(path)
+    }
+}
+
+fn index_file_exists(path: &Path) -> bool {
+    backend::backend_for(path).exists(path)
+}
+
+fn distance_kind(metric: Option<&str>) -> crate::distance::DistanceKind {
+    match metric {
+        Some("cosine") => crate::distance::DistanceKind::Cosine,
+        _ => crate::distance::DistanceKind::Euclidean,
+    }
+}
+
+fn row_id_of(key: u64) -> RowId {
+    (key & 0xFFFFFFFF) as RowId
+}
+
+fn vector_of(value: &Value) -> Option<Vec<f32>> {
+    match value {
+        Value::Vector(vec) => Some(vec.as_slice().to_vec()),
+        Value::Tensor(tensor) => Some(tensor.to_f32()),
+        _ => None,
+    }
+}
+
+fn text_of(value: Option<&Value>) -> Option<&str> {
+    match value {
+        Some(Value::Text(text)) => Some(text.as_str()),
+        _ => None,
+    }
+}
+
+/// Record the live files of an index before any of them moves. Each line is
+/// a root, prefixed `+` if it exists (and is moved aside) or `-` if not.
+fn write_swap_marker(marker: &Path, live_files: &[PathBuf]) -> std::io::Result<()> {
+    let mut contents = String::new();
+    for live in live_files {
+        let name = live.file_name().unwrap_or_default().to_string_lossy();
+        let existed = if index_file_exists(live) { '+' } else { '-' };
+        contents.push_str(&format!("{}{}\n", existed, name));
+    }
+    let mut file = backend::open_file(marker, OpenFlags::create_truncate())?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Undo the swap `marker` describes: put back every root that was moved
+/// aside, drop the roots the swap created, then remove the marker
+fn roll_back_swap(marker: &Path) -> std::io::Result<()> {
+    let dir = marker.parent().unwrap_or(Path::new("."));
+    let fs = backend::backend_for(marker);
+    let contents = fs.read(marker)?;
+    for line in String::from_utf8_lossy(&contents).lines() {
+        let (existed, name) = match line.split_at_checked(1) {
+            Some(("+", name)) => (true, name),
+            Some(("-", name)) => (false, name),
+            _ => continue,
+        };
+        let live = dir.join(name);
+        let aside = replaced_path(&live);
+        if existed && !fs.exists(&aside) {
+            // Never moved: still the old file
+            continue;
+        }
+        if fs.exists(&live) {
+            remove_index_files(&live)?;
+        }
+        if existed {
+            fs.rename(&aside, &live)?;
+        }
+    }
+    fs.remove_file(marker)
+}
+
+/// Replacement under construction, held outside the index maps
+enum StagingIndex {
+    Column(Arc<ColumnValueIndex>),
+    /// Vectors the scan staged, inserted as one batch when it completes
+    Vector(Arc<RwLock<DiskANNIndex>>, Vec<(RowId, Vec<f32>)>),
+    Text(Arc<RwLock<TextFTSIndex>>),
+    Octree(Arc<RwLock<IOctreeIndex>>),
+}
+
+impl StagingIndex {
+    /// Create an empty index of `meta`'s type under `staging`
+    fn create(
+        db: &MoteDB,
+        meta: &IndexMetadata,
+        column: &ColumnDef,
+        staging: &str,
+    ) -> Result<Self> {
+        let missing = || StorageError::IndexNotFound(staging.to_string());
+        Ok(match meta.index_type {
+            IndexType::Column => {
+                // Same buffer as CREATE INDEX: the scan inserts every row
+                let config = ColumnValueIndexConfig {
+                    mem_buffer_size: db.column_index_buffer_size.max(32 * 1024 * 1024),
+                    ..Default::default()
+                };
+                let index = ColumnValueIndex::create(
+                    index_path(&db.path.join("indexes"), &meta.index_type, staging),
+                    meta.table_name.clone(),
+                    meta.column_name.clone(),
+                    config,
+                )?
+                .with_column_type(column.col_type.clone());
+                Self::Column(Arc::new(index))
+            }
+            IndexType::Vector => {
+                let dimension = column.col_type.vector_dim().ok_or_else(|| {
+                    StorageError::TypeError(format!(
+                        "VECTOR index requires TENSOR column, got {:?}",
+                        column.col_type
+                    ))
+                })?;
+                db.create_vector_index_on(staging, dimension, meta.metric.as_deref(), None)?;
+                let (_, index) = db.vector_indexes.remove(staging).ok_or_else(missing)?;
+                Self::Vector(index, Vec::new())
+            }
+            IndexType::Text => {
+                db.create_text_index(staging)?;
+                let (_, index) = db.text_indexes.remove(staging).ok_or_else(missing)?;
+                Self::Text(index)
+            }
+            IndexType::Octree => {
+                db.create_ioctree_index(staging)?;
+                let (_, index) = db.ioctree_indexes.remove(staging).ok_or_else(missing)?;
+                Self::Octree(index)
+            }
+        })
+    }
+
+    /// Index a batch of scanned rows (the indexed column's values)
+    fn stage(&mut self, rows: &[(RowId, &Value)]) -> Result<()> {
+        match self {
+            Self::Column(index) => index.batch_insert(
+                rows.iter()
+                    .map(|(row_id, value)| ((*value).clone(), *row_id))
+                    .collect(),
+            ),
+            Self::Vector(_, staged) => {
+                staged.extend(
+                    rows.iter()
+                        .filter_map(|(row_id, value)| Some((*row_id, vector_of(value)?))),
+                );
+                Ok(())
+            }
+            Self::Text(index) => {
+                let docs: Vec<(RowId, &str)> = rows
+                    .iter()
+                    .filter_map(|(row_id, value)| Some((*row_id, text_of(Some(value))?)))
+                    .collect();
+                if docs.is_empty() {
+                    return Ok(());
+                }
+                index.write().batch_insert(&docs)
+            }
+            Self::Octree(index) => {
+                let mut index = index.write();
+                for (row_id, value) in rows {
+                    if let Value::Spatial(geometry) = value {
+                        index.insert(*row_id, geometry)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The scan is complete
+    fn finish_scan(&mut self) -> Result<()> {
+        if let Self::Vector(index, staged) = self {
+            let vectors = std::mem::take(staged);
+            if !vectors.is_empty() {
+                index.write().batch_insert(&vectors)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a row's entry from `old` (what this index holds) to `new`
+    fn replay(&mut self, row_id: RowId, old: Option<&Value>, new: Option<&Value>) -> Result<()> {
+        if old == new {
+            return Ok(());
+        }
+        match self {
+            Self::Column(index) => {
+                if let Some(old) = old {
+                    index.delete(old, row_id)?;
+                }
+                if let Some(new) = new {
+                    index.insert(new, row_id)?;
+                }
+            }
+            Self::Vector(index, _) => {
+                let index = index.write();
+                match (old.and_then(vector_of), new.and_then(vector_of)) {
+                    (Some(_), Some(new)) => {
+                        index.update(row_id, new)?;
+                    }
+                    (None, Some(new)) => index.insert(row_id, new)?,
+                    (Some(_), None) => {
+                        index.delete(row_id)?;
+                    }
+                    (None, None) => {}
+                }
+            }
+            Self::Text(index) => {
+                let mut index = index.write();
+                match (text_of(old), text_of(new)) {
+                    (Some(old), Some(new)) => index.update(row_id, old, new)?,
+                    (None, Some(new)) => index.insert(row_id, new)?,
+                    (Some(old), None) => index.delete(row_id, old)?,
+                    (None, None) => {}
+                }
+            }
+            Self::Octree(index) => {
+                let mut index = index.write();
+                if let Some(Value::Spatial(_)) = old {
+                    index.delete(row_id);
+                }
+                if let Some(Value::Spatial(geometry)) = new {
+                    index.insert(row_id, geometry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        match self {
+            Self::Column(index) => index.flush(),
+            Self::Vector(index, _) => index.read().flush(),
+            Self::Text(index) => index.write().flush(),
+            Self::Octree(index) => index.write().flush(),
+        }
+    }
+}
+
+impl MoteDB {
+    /// Rebuild an index from its table's data and swap it in (`REINDEX name`).
+    ///
+    /// Repairs indexes marked stale after a failed update and compacts indexes
+    /// degraded by heavy deletes (e.g. DiskANN graphs full of tombstones).
+    /// Queries keep using the old index until the new one is in place; writes
+    /// to the table only pause for the final catch-up and the swap.
+    pub fn rebuild_index(&self, name: &str) -> Result<()> {
+        ensure_open!(self);
+        let meta = self
+            .index_registry
+            .get(name)
+            .ok_or_else(|| StorageError::IndexNotFound(name.to_string()))?;
+        let schema = self.table_registry.get_table(&meta.table_name)?;
+        let column = schema
+            .get_column(&meta.column_name)
+            .ok_or_else(|| StorageError::ColumnNotFound(meta.column_name.clone()))?;
+
+        let indexes_dir = self.path.join("indexes");
+        backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
+        let staging = format!("{}{}", name, STAGING_SUFFIX);
+        let staging_files = index_files(&indexes_dir, &meta.index_type, &staging);
+        for path in staging_files.iter().filter(|p| index_file_exists(p)) {
+            remove_index_files(path)?;
+        }
+
+        let gate = self.table_write_gate(&meta.table_name);
+        let result = if self.has_col_segment_store(&meta.table_name) {
+            self.rebuild_online(&meta, &schema, column, &staging, &gate)
+        } else {
+            // Tables kept outside a ColSegmentStore (written before v0.3)
+            // have no write capture: keep their writes out throughout
+            let _paused = gate.write();
+            self.build_staging_index(&meta, column, &staging)
+                .and_then(|()| self.swap_rebuilt_index(&meta, column, &staging))
+        };
+        if let Err(e) = result {
+            self.column_indexes.remove(&staging);
+            self.vector_indexes.remove(&staging);
+            self.text_indexes.remove(&staging);
+            self.ioctree_indexes.remove(&staging);
+            for path in staging_files.iter().filter(|p| index_file_exists(p)) {
+                let _ = remove_index_files(path);
+            }
+            return Err(e);
+        }
+
+        self.index_registry.mark_rebuilt(name)?;
+        debug_log!(
+            "[rebuild_index] Rebuilt {:?} index '{}'",
+            meta.index_type,
+            name
+        );
+        Ok(())
+    }
+
+    /// Build the replacement from the table's store while writes continue,
+    /// then catch up on them and swap it in
+    fn rebuild_online(
+        &self,
+        meta: &IndexMetadata,
+        schema: &TableSchema,
+        column: &ColumnDef,
+        staging: &str,
+        gate: &RwLock<()>,
+    ) -> Result<()> {
+        let store = self.get_or_create_col_segment_store(&meta.table_name, schema.col_types())?;
+        let capture = Arc::new(WriteCapture::new());
+        // 1️⃣ Attach the capture between writes: every write is then either
+        // in the store already or captured
+        {
+            let _paused = gate.write();
+            store.attach_capture(capture.clone());
+        }
+        let result = self.build_from_store(meta, column, staging, gate, &store, &capture);
+        store.detach_capture(&capture);
+        result
+    }
+
+    fn build_from_store(
+        &self,
+        meta: &IndexMetadata,
+        column: &ColumnDef,
+        staging: &str,
+        gate: &RwLock<()>,
+        store: &ColSegmentStore,
+        capture: &WriteCapture,
+    ) -> Result<()> {
+        // 2️⃣ Scan the table in key order into the staging index. Rows
+        // written since the capture attached are skipped and replayed below.
+        store.flush_buffer()?;
+        let mut index = StagingIndex::create(self, meta, column, staging)?;
+        let mut rows = store.scan();
+        loop {
+            let batch: Vec<(u64, Vec<Value>)> = rows
+                .by_ref()
+                .take(SCAN_BATCH)
+                .map(|(key, _, row)| (key, row))
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+            let batch = capture.stage(batch);
+            let values: Vec<(RowId, &Value)> = batch
+                .iter()
+                .filter_map(|(key, row)| Some((row_id_of(*key), row.get(column.position)?)))
+                .collect();
+            index.stage(&values)?;
+        }
+        capture.finish_scan();
+        index.finish_scan()?;
+
+        // 3️⃣ Replay captured writes while the table stays writable
+        for _ in 0..MAX_CATCH_UP_ROUNDS {
+            if capture.dirty_count() <= PAUSED_CATCH_UP_ROWS {
+                break;
+            }
+            replay_captured(store, capture, &mut index, column.position)?;
+        }
+
+        // 4️⃣ Pause the table's writes for the rest and the swap
+        let _paused = gate.write();
+        replay_captured(store, capture, &mut index, column.position)?;
+        index.flush()?;
+        drop(index);
+        self.swap_rebuilt_index(meta, column, staging)
+    }
+
+    /// Build a fresh index of `meta`'s type under `staging` and flush it to disk
+    fn build_staging_index(
+        &self,
+        meta: &IndexMetadata,
+        column: &ColumnDef,
+        staging: &str,
+    ) -> Result<()> {
+        match meta.index_type {
+            IndexType::Column => {
+                self.create_column_index_with_name(&meta.table_name, &meta.column_name, staging)?;
+                if let Some((_, index)) = self.column_indexes.remove(staging) {
+                    index.flush()?;
+                }
+            }
+            IndexType::Vector => {
+                let dimension = column.col_type.vector_dim().ok_or_else(|| {
+                    StorageError::TypeError(format!(
+                        "VECTOR index requires TENSOR column, got {:?}",
+                        column.col_type
+                    ))
+                })?;
+                self.create_vector_index_on(
+                    staging,
+                    dimension,
+                    meta.metric.as_deref(),
+                    Some((&meta.table_name, &meta.column_name)),
+                )?;
+                if let Some((_, index)) = self.vector_indexes.remove(staging) {
+                    index.read().flush()?;
+                }
+            }
+            IndexType::Text => {
+                self.create_text_index(staging)?;
+                self.populate_text_index(staging, &meta.table_name, column.position)?;
+                if let Some((_, index)) = self.text_indexes.remove(staging) {
+                    index.write().flush()?;
+                }
+            }
+            IndexType::Octree => {
+                self.create_ioctree_index(staging)?;
+                self.populate_ioctree_index(staging, &meta.table_name, column.position)?;
+                if let Some((_, index)) = self.ioctree_indexes.remove(staging) {
+                    index.write().flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the staged files over the live ones as one set, reopen the index
+    /// and publish its files. The caller keeps the table's writes out.
+    fn swap_rebuilt_index(
+        &self,
+        meta: &IndexMetadata,
+        column: &ColumnDef,
+        staging: &str,
+    ) -> Result<()> {
+        let name = meta.name.as_str();
+        let indexes_dir = self.path.join("indexes");
+
+        // Retire the live handle before its files move. Indexes write
+        // metadata and sidecars through their paths, so a checkpoint or an
+        // in-flight operation on the old handle would otherwise land in the
+        // replacement's files. The checkpoint mutex keeps checkpoints out;
+        // taking the handle out of the map and draining it covers the rest.
+        let _ckpt_guard = self
+            .checkpoint_mutex
+            .lock()
+            .map_err(|_| StorageError::Lock("Checkpoint mutex poisoned".into()))?;
+        // A background rebuild can outlive close(); the staging files it
+        // leaves are dropped by the next open
+        ensure_open!(self);
+        let retired = self.retire_live_index(meta);
+
+        // Move the live files aside and the replacement into place. Until
+        // the marker is removed the swap is rolled back as a whole, whether
+        // here on error or by the next open after a crash.
+        let staging_files = index_files(&indexes_dir, &meta.index_type, staging);
+        let live_files = index_files(&indexes_dir, &meta.index_type, name);
+        let marker = with_suffix(
+            &index_path(&indexes_dir, &meta.index_type, name),
+            SWAP_SUFFIX,
+        );
+        let fs = backend::backend_for(&indexes_dir);
+        let swapped = (|| -> std::io::Result<()> {
+            // Every root is replaced, including one the new index never wrote
+            for staged in staging_files.iter().filter(|p| !fs.exists(p)) {
+                fs.create_dir_all(staged)?;
+            }
+            write_swap_marker(&marker, &live_files)?;
+            for live in live_files.iter().filter(|p| fs.exists(p)) {
+                fs.rename(live, &replaced_path(live))?;
+            }
+            for (staged, live) in staging_files.iter().zip(&live_files) {
+                fs.rename(staged, live)?;
+            }
+            Ok(())
+        })();
+        let live_path = index_path(&indexes_dir, &meta.index_type, name);
+        let reopened = swapped
+            .map_err(StorageError::from)
+            .and_then(|()| self.reopen_rebuilt_index(meta, column, &live_path, &retired));
+        if let Err(e) = reopened {
+            if fs.exists(&marker) {
+                if let Err(_e) = roll_back_swap(&marker) {
+                    debug_log!(
+                        "[rebuild_index] Rolling back swap of '{}' failed: {}",
+                        name,
+                        _e
+                    );
+                }
+            }
+            self.restore_retired_index(name, retired);
+            return Err(e);
+        }
+
+        // Commit point: without the marker, recovery keeps the new files
+        if let Err(e) = fs.remove_file(&marker) {
+            warn_log!(
+                "[rebuild_index] Failed to remove swap marker {:?}: {}",
+                marker,
+                e
+            );
+        }
+        for aside in live_files.iter().map(|p| replaced_path(p)) {
+            if fs.exists(&aside) {
+                if let Err(_e) = remove_index_files(&aside) {
+                    debug_log!("[rebuild_index] Failed to remove {:?}: {}", aside, _e);
+                }
+            }
+        }
+
+        // Commit the new files now rather than at the next checkpoint, so a
+        // crash before it does not discard the rebuild
+        if let Err(e) = self.publish_rebuilt_index(meta) {
+            warn_log!(
+                "[rebuild_index] Publishing index '{}' failed: {:?}",
+                name,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Take the live handle out of its map and wait for operations running on
+    /// it. Nothing else keeps a handle beyond one call, so once drained it
+    /// receives no further writes.
+    pub(super) fn retire_live_index(&self, meta: &IndexMetadata) -> RetiredIndex {
+        let name = meta.name.as_str();
+        match meta.index_type {
+            IndexType::Column => {
+                // Also retire aliases such as "table.column" that share the handle
+                let Some(old) = self.column_indexes.get(name).map(|r| r.value().clone()) else {
+                    return RetiredIndex::None;
+                };
+                let keys: Vec<String> = self
+                    .column_indexes
+                    .iter()
+                    .filter(|entry| Arc::ptr_eq(entry.value(), &old))
+                    .map(|entry| entry.key().clone())
+                    .collect();
+                for key in &keys {
+                    self.column_indexes.remove(key);
+                }
+                RetiredIndex::Column(old, keys)
+            }
+            IndexType::Vector => match self.vector_indexes.remove(name) {
+                Some((_, old)) => {
+                    drop(old.write());
+                    RetiredIndex::Vector(old)
+                }
+                None => RetiredIndex::None,
+            },
+            IndexType::Text => match self.text_indexes.remove(name) {
+                Some((_, old)) => {
+                    drop(old.write());
+                    RetiredIndex::Text(old)
+                }
+                None => RetiredIndex::None,
+            },
+            IndexType::Octree => match self.ioctree_indexes.remove(name) {
+                Some((_, old)) => {
+                    drop(old.write());
+                    RetiredIndex::Octree(old)
+                }
+                None => RetiredIndex::None,
+            },
+        }
+    }
+
+    /// Put a retired handle back after a failed swap
+    pub(super) fn restore_retired_index(&self, name: &str, retired: RetiredIndex) {
+        match retired {
+            RetiredIndex::Column(old, keys) => {
+                for key in keys {
+                    self.column_indexes.insert(key, old.clone());
+                }
+            }
+            RetiredIndex::Vector(old) => {
+                self.vector_indexes.insert(name.to_string(), old);
+            }
+            RetiredIndex::Text(old) => {
+                self.text_indexes.insert(name.to_string(), old);
+            }
+            RetiredIndex::Octree(old) => {
+                self.ioctree_indexes.insert(name.to_string(), old);
+            }
+            RetiredIndex::None => {}
+        }
+    }
+
+    /// Open the rebuilt index the way `open()` would and publish it under the
+    /// keys the retired handle was registered with
+    fn reopen_rebuilt_index(
+        &self,
+        meta: &IndexMetadata,
+        column: &ColumnDef,
+        path: &Path,
+        retired: &RetiredIndex,
+    ) -> Result<()> {
+        let name = meta.name.as_str();
+        match meta.index_type {
+            IndexType::Column => {
+                let index = ColumnValueIndex::open(
+                    path,
+                    meta.table_name.clone(),
+                    meta.column_name.clone(),
+                    ColumnValueIndexConfig::default(),
+                )?
+                .with_column_type(column.col_type.clone());
+                let index = Arc::new(index);
+                let keys = match retired {
+                    RetiredIndex::Column(_, keys) if !keys.is_empty() => keys.clone(),
+                    // Nothing was live: register it as CREATE INDEX does, so
+                    // inserts (which look up "table.column", or "table.column[]" for an
+                    // ARRAY column) maintain it
+                    _ => vec![
+                        name.to_string(),
+                        column_index_key(&meta.table_name, &meta.column_name, &column.col_type),
+                    ],
+                };
+                for key in keys {
+                    self.column_indexes.insert(key, index.clone());
+                }
+            }
+            IndexType::Vector => {
+                let config =
+                    VamanaConfig::default().with_metric(distance_kind(meta.metric.as_deref()));
+                let index = DiskANNIndex::load(path, config)?;
+                self.vector_indexes
+                    .insert(name.to_string(), Arc::new(RwLock::new(index)));
+            }
+            IndexType::Text => {
+                let index = TextFTSIndex::new(path.to_path_buf())?;
+                self.text_indexes
+                    .insert(name.to_string(), Arc::new(RwLock::new(index)));
+            }
+            IndexType::Octree => {
+                let index = IOctreeIndex::load_from_path(&path.join("ioctree.bin"))?;
+                self.ioctree_indexes
+                    .insert(name.to_string(), Arc::new(RwLock::new(index)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve rebuilds interrupted by a crash before indexes are loaded:
+    /// swaps that still have their marker are rolled back as a whole,
+    /// half-built staging indexes are dropped, and live files that were moved
+    /// aside but never replaced are restored.
+    pub(crate) fn recover_index_rebuilds(db_path: &Path) {
+        let indexes_dir = db_path.join("indexes");
+        let fs = backend::backend_for(&indexes_dir);
+        let markers: Vec<PathBuf> = match fs.list_dir(&indexes_dir) {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|path| path.to_string_lossy().ends_with(SWAP_SUFFIX))
+                .collect(),
+            Err(_) => return,
+        };
+        for marker in markers {
+            if let Err(_e) = roll_back_swap(&marker) {
+                debug_log!(
+                    "[MoteDB] Failed to roll back index swap {:?}: {}",
+                    marker,
+                    _e
+                );
+            }
+        }
+
+        let entries = match fs.list_dir(&indexes_dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries {
+            let file_name = match entry.file_name().and_then(|n| n.to_str()) {
+                Some(n) => n.to_string(),
+                None => continue,
+            };
+            let result = if let Some(live) = file_name.strip_suffix(REPLACED_SUFFIX) {
+                let live_path = indexes_dir.join(live);
+                if fs.exists(&live_path) {
+                    remove_index_files(&entry)
+                } else {
+                    fs.rename(&entry, &live_path)
+                }
+            } else if file_name.contains(STAGING_SUFFIX) {
+                remove_index_files(&entry)
+            } else {
+                continue;
+            };
+            if let Err(_e) = result {
+                debug_log!(
+                    "[MoteDB] Failed to recover index rebuild {}: {}",
+                    file_name,
+                    _e
+                );
+            }
+        }
+    }
+}
+
+/// Replay the rows captured since the last round into the staging index
+fn replay_captured(
+    store: &ColSegmentStore,
+    capture: &WriteCapture,
+    index: &mut StagingIndex,
+    position: usize,
+) -> Result<()> {
+    for (key, staged) in capture.take_dirty() {
+        let current = store.get(key);
+        index.replay(
+            row_id_of(key),
+            staged.as_ref().and_then(|row| row.get(position)),
+            current.as_ref().and_then(|row| row.get(position)),
+        )?;
+        capture.replayed(key, current);
+    }
+    Ok(())
+}
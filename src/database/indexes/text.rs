@@ -21,7 +21,7 @@ impl MoteDB {
         ensure_open!(self);
         // 🎯 统一路径：{db}.mote/indexes/text_{name}/
         let indexes_dir = self.path.join("indexes");
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let index_path = indexes_dir.join(format!("text_{}", name));
 
         let index = TextFTSIndex::new(index_path)?;
@@ -143,8 +143,58 @@ impl MoteDB {
         Ok(total)
     }
 
+    /// Backfill a text index from existing table data. Uses the columnar fast
+    /// path when possible, otherwise streams rows in batches of 10000.
+    pub fn populate_text_index(
+        &self,
+        index_name: &str,
+        table_name: &str,
+        col_position: usize,
+    ) -> Result<usize> {
+        if let Ok(count) = self.build_text_index_from_columnar(index_name, table_name, col_position)
+        {
+            return Ok(count);
+        }
+
+        let index_arc = self
+            .text_indexes
+            .get(index_name)
+            .map(|r| r.value().clone())
+            .ok_or_else(|| StorageError::Index(format!("Text index '{}' not found", index_name)))?;
+        let mut backfill_count = 0;
+        for batch_result in self.scan_table_rows_batched(table_name, 10000)? {
+            let batch = batch_result?;
+            let texts_in_batch: Vec<(RowId, &str)> = batch
+                .iter()
+                .filter_map(|(row_id, row)| match row.get(col_position) {
+                    Some(crate::types::Value::Text(text)) => Some((*row_id, text.as_str())),
+                    _ => None,
+                })
+                .collect();
+            if texts_in_batch.is_empty() {
+                continue;
+            }
+            // One write lock per batch, released between batches so queries can run
+            let mut index = index_arc.write();
+            for (row_id, text) in texts_in_batch {
+                if let Err(_e) = index.insert(row_id, text) {
+                    debug_log!(
+                        "⚠️ Failed to backfill text index for row {}: {}",
+                        row_id,
+                        _e
+                    );
+                } else {
+                    backfill_count += 1;
+                }
+            }
+        }
+        Ok(backfill_count)
+    }
+
     /// 🚀 Build text index from ColSegmentStore (the active storage engine).
     /// Reads TextSegment from each segment and batch-inserts into the FTS index.
+    /// Segments are visited newest first so rows deleted or updated by a later
+    /// segment are indexed once, with their current text, or not at all.
     pub fn build_text_index_from_col_segment(
         &self,
         index_name: &str,
@@ -155,13 +205,21 @@ impl MoteDB {
             Some(s) => s.clone(),
             None => return Ok(0),
         };
-        let _ = store.flush_buffer();
+        // Buffered rows and tombstones must be in segments, or the build
+        // would silently miss them
+        store.flush_buffer()?;
 
         let segs = store.segments_snapshot();
         let mut batch: Vec<(RowId, String)> = Vec::with_capacity(10000);
         let mut total = 0usize;
+        // Only multi-segment tables can carry several versions of a key
+        let mut seen: Option<std::collections::HashSet<u64>> = if segs.len() > 1 {
+            Some(std::collections::HashSet::new())
+        } else {
+            None
+        };
 
-        for seg in segs.iter() {
+        for seg in segs.iter().rev() {
             let n = seg.sst.num_rows;
             let has_deletions = seg.sst.row_map.has_any_deleted();
             let _ = seg.sst.load_full_keys();
@@ -169,6 +227,13 @@ impl MoteDB {
                 Ok(tseg) => {
                     let has_nulls = tseg.has_any_null();
                     for i in 0..n {
+                        // Mark the key seen before the deleted check, so a
+                        // tombstone hides older versions in earlier segments
+                        if let Some(ref mut seen) = seen {
+                            if !seen.insert(seg.sst.row_map.key(i)) {
+                                continue;
+                            }
+                        }
                         if has_deletions && seg.sst.row_map.is_deleted(i) {
                             continue;
                         }
@@ -268,6 +333,16 @@ impl MoteDB {
         guard.search_phrase(phrase)
     }
 
+    /// Document frequency of each distinct term in `query`
+    pub fn text_term_doc_frequencies(&self, index_name: &str, query: &str) -> Result<Vec<u64>> {
+        let index_ref = self
+            .text_indexes
+            .get(index_name)
+            .ok_or_else(|| StorageError::Index(format!("Text index '{}' not found", index_name)))?;
+        let guard = index_ref.value().read();
+        guard.term_doc_frequencies(query)
+    }
+
     /// Get text index statistics
     ///
     /// # Example
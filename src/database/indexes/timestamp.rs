@@ -34,6 +34,47 @@ pub struct MemTableScanProfile {
 }
 
 impl MoteDB {
+    /// Add `(timestamp, row_id)` entries to the timestamp index.
+    ///
+    /// With a reorder buffer configured, entries within the lateness window
+    /// are held back and inserted later in timestamp order.
+    pub(crate) fn index_timestamp_entries(&self, entries: Vec<(u64, RowId)>) -> Result<()> {
+        let Some(reorder) = &self.timestamp_reorder else {
+            let mut ts_index = self.timestamp_index.write();
+            for (timestamp, row_id) in entries {
+                ts_index.insert(timestamp, row_id)?;
+            }
+            return Ok(());
+        };
+        // Hold the buffer lock until the entries are in the index, so range
+        // queries (buffer first, then index) never miss an entry in transit
+        let mut buffer = reorder.lock();
+        let ready = buffer.push(entries);
+        if !ready.is_empty() {
+            let mut ts_index = self.timestamp_index.write();
+            for (timestamp, row_id) in ready {
+                ts_index.insert(timestamp, row_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move every buffered entry into the timestamp index (before it is flushed)
+    pub(crate) fn drain_timestamp_reorder_buffer(&self) -> Result<()> {
+        let Some(reorder) = &self.timestamp_reorder else {
+            return Ok(());
+        };
+        let mut buffer = reorder.lock();
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let mut ts_index = self.timestamp_index.write();
+        for (timestamp, row_id) in buffer.drain() {
+            ts_index.insert(timestamp, row_id)?;
+        }
+        Ok(())
+    }
+
     /// Rebuild timestamp index from LSM storage (incremental, range-scan optimized)
     ///
     /// Uses LSM range scan instead of N point lookups — O(SSTable_count) instead of O(N).
@@ -116,10 +157,7 @@ impl MoteDB {
         // Batch insert into index
         let count = entries_to_index.len();
         if count > 0 {
-            let mut ts_index = self.timestamp_index.write();
-            for (timestamp, row_id) in entries_to_index {
-                ts_index.insert(timestamp, row_id)?;
-            }
+            self.index_timestamp_entries(entries_to_index)?;
             debug_log!(
                 "[rebuild_timestamp_index] Added {} entries in {:?}",
                 count,
@@ -173,10 +211,16 @@ impl MoteDB {
     ) -> Result<(Vec<RowId>, QueryProfile)> {
         let total_start = std::time::Instant::now();
 
-        // 1. Query from persisted index (flushed data)
+        // 1. Query from persisted index (flushed data), plus entries still
+        //    held in the reorder buffer
         let index_start = std::time::Instant::now();
         let start_u64 = start as u64;
         let end_u64 = end as u64;
+        let buffered_ids = self
+            .timestamp_reorder
+            .as_ref()
+            .map(|r| r.lock().range(start_u64, end_u64))
+            .unwrap_or_default();
         let index_results = if let Some(lim) = limit {
             self.timestamp_index
                 .read()
@@ -188,6 +232,7 @@ impl MoteDB {
             .into_iter()
             .map(|(_, row_id)| row_id)
             .collect();
+        result_ids.extend(buffered_ids);
         let index_duration = index_start.elapsed();
 
         // 2. Query from LSM MemTable (unflushed data)
@@ -278,4 +323,11 @@ impl MoteDB {
     pub fn timestamp_index_stats(&self) -> crate::index::btree::BTreeStats {
         self.timestamp_index.read().stats()
     }
+
+    /// Get timestamp reorder buffer statistics (None if the buffer is disabled)
+    pub fn timestamp_reorder_stats(
+        &self,
+    ) -> Option<crate::index::timestamp_reorder::TimestampReorderStats> {
+        self.timestamp_reorder.as_ref().map(|r| r.lock().stats())
+    }
 }
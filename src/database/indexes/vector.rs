@@ -10,6 +10,19 @@ use crate::{Result, StorageError};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
+/// Candidates re-ranked per requested neighbor when re-ranking is on
+pub const RERANK_CANDIDATE_FACTOR: usize = 4;
+
+/// Per-query knobs of a vector index search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VectorSearchOptions {
+    /// DiskANN candidate list size (None = the index's `search_list_size`)
+    pub width: Option<usize>,
+    /// Re-rank the top `RERANK_CANDIDATE_FACTOR * k` candidates with the
+    /// rows' full-precision vectors (None = the index's `rerank` setting)
+    pub rerank: Option<bool>,
+}
+
 /// Vector index statistics
 #[derive(Debug)]
 pub struct VectorIndexStats {
@@ -38,13 +51,42 @@ impl MoteDB {
         name: &str,
         dimension: usize,
         metric: Option<&str>,
+    ) -> Result<()> {
+        // Resolve table_name and column_name from index_registry (supports custom names)
+        let source = match self.index_registry.resolve_index_name(name) {
+            Some(resolved) => Some(resolved),
+            None => {
+                // Fallback: parse "table_column" format
+                let parts: Vec<&str> = name.split('_').collect();
+                if parts.len() >= 2 {
+                    Some((parts[0].to_string(), parts[1..].join("_")))
+                } else {
+                    None
+                }
+            }
+        };
+        self.create_vector_index_on(
+            name,
+            dimension,
+            metric,
+            source.as_ref().map(|(t, c)| (t.as_str(), c.as_str())),
+        )
+    }
+
+    /// Create a vector index and build it from `source` (table, column)
+    pub(crate) fn create_vector_index_on(
+        &self,
+        name: &str,
+        dimension: usize,
+        metric: Option<&str>,
+        source: Option<(&str, &str)>,
     ) -> Result<()> {
         ensure_open!(self);
         // 🎯 统一路径：{db}.mote/indexes/vector_{name}/
         let indexes_dir = self.path.join("indexes");
-        std::fs::create_dir_all(&indexes_dir)?;
+        crate::storage::backend::backend_for(&indexes_dir).create_dir_all(&indexes_dir)?;
         let index_dir = indexes_dir.join(format!("vector_{}", name));
-        std::fs::create_dir_all(&index_dir)?;
+        crate::storage::backend::backend_for(&index_dir).create_dir_all(&index_dir)?;
 
         // Parse metric parameter
         let distance_kind = match metric {
@@ -59,19 +101,8 @@ impl MoteDB {
             .insert(name.to_string(), index_arc.clone());
 
         // 🚀 方案B：使用scan_range高性能扫描
-        // Resolve table_name and column_name from index_registry (supports custom names)
-        let resolved = self.index_registry.resolve_index_name(name);
-        let (table_name, column_name): (&str, String) = match &resolved {
-            Some((t, c)) => (t.as_str(), c.clone()),
-            None => {
-                // Fallback: parse "table_column" format
-                let parts: Vec<&str> = name.split('_').collect();
-                if parts.len() >= 2 {
-                    (parts[0], parts[1..].join("_"))
-                } else {
-                    return Ok(());
-                }
-            }
+        let Some((table_name, column_name)) = source else {
+            return Ok(());
         };
 
         // 获取列在schema中的位置
@@ -81,8 +112,27 @@ impl MoteDB {
                 let start_time = std::time::Instant::now();
                 let mut vectors_to_index = Vec::new();
 
-                // 🚀 Columnar fast path: read vectors directly from column segment
-                if let Some(col_sst) = self.columnar_sstables.get(table_name) {
+                // S8: ColSegmentStore path. Segments are read newest-first so
+                // the latest version of each row wins: every key of a segment
+                // (tombstones and NULL vectors included) shadows older segments.
+                let from_store = self.has_col_segment_store(table_name);
+                if from_store {
+                    let store =
+                        self.get_or_create_col_segment_store(table_name, schema.col_types())?;
+                    store.ensure_query_visibility()?;
+                    let mut seen = std::collections::HashSet::new();
+                    for seg in store.segments_snapshot().iter().rev() {
+                        for (row_id, vector) in seg.sst.read_vectors(col_position)? {
+                            if !seen.contains(&row_id) {
+                                vectors_to_index.push((row_id, vector));
+                            }
+                        }
+                        for i in 0..seg.sst.num_rows {
+                            seen.insert((seg.sst.row_map.key(i) & 0xFFFFFFFF) as RowId);
+                        }
+                    }
+                } else if let Some(col_sst) = self.columnar_sstables.get(table_name) {
+                    // 🚀 Columnar fast path: read vectors directly from column segment
                     match col_sst.read_vectors(col_position) {
                         Ok(vectors) => {
                             vectors_to_index = vectors;
@@ -99,7 +149,7 @@ impl MoteDB {
                 }
 
                 // Fallback: scan LSM tree
-                if vectors_to_index.is_empty() {
+                if !from_store && vectors_to_index.is_empty() {
                     let table_id = self.table_registry.get_table_id(table_name).unwrap_or(0) as u64;
                     let start_key = table_id << 32;
                     let end_key = (table_id + 1) << 32;
@@ -252,6 +302,16 @@ impl MoteDB {
         self.vector_indexes.contains_key(index_name)
     }
 
+    /// Distance metric of a vector index (`None` if the index does not exist).
+    ///
+    /// `vector_search` returns squared distances for `Euclidean` indexes and
+    /// `1 - cosine_similarity` for `Cosine` ones.
+    pub fn vector_index_metric(&self, index_name: &str) -> Option<crate::distance::DistanceKind> {
+        self.vector_indexes
+            .get(index_name)
+            .map(|index| index.value().read().metric())
+    }
+
     /// Search for nearest neighbors (merges DiskANN index + memtable data)
     ///
     /// # LSM Architecture
@@ -272,8 +332,50 @@ impl MoteDB {
         index_name: &str,
         query: &[f32],
         k: usize,
+    ) -> Result<Vec<(RowId, f32)>> {
+        self.vector_search_with_width(index_name, query, k, None)
+    }
+
+    /// [`vector_search`](Self::vector_search) with the DiskANN candidate list
+    /// size set to `width` (None = the index's `search_list_size`)
+    pub fn vector_search_with_width(
+        &self,
+        index_name: &str,
+        query: &[f32],
+        k: usize,
+        width: Option<usize>,
+    ) -> Result<Vec<(RowId, f32)>> {
+        self.vector_search_with_options(
+            index_name,
+            query,
+            k,
+            VectorSearchOptions {
+                width,
+                rerank: None,
+            },
+        )
+    }
+
+    /// [`vector_search`](Self::vector_search) with per-query options.
+    ///
+    /// The index ranks candidates by SQ8 (8-bit quantized) distance. With
+    /// re-ranking on, the top `4 * k` candidates are re-scored with the
+    /// full-precision vectors stored in the rows and re-sorted, so the top
+    /// `k` and their distances are exact for the candidates found.
+    pub fn vector_search_with_options(
+        &self,
+        index_name: &str,
+        query: &[f32],
+        k: usize,
+        options: VectorSearchOptions,
     ) -> Result<Vec<(RowId, f32)>> {
         ensure_open!(self);
+        let width = options.width;
+        let rerank = options.rerank.unwrap_or_else(|| {
+            self.index_registry
+                .get(index_name)
+                .is_some_and(|meta| meta.rerank)
+        });
         debug_log!("[vector_search] START: index={}, k={}", index_name, k);
 
         let index_ref = self.vector_indexes.get(index_name).ok_or_else(|| {
@@ -285,7 +387,12 @@ impl MoteDB {
         let metric = index_guard.metric();
 
         debug_log!("[vector_search] 开始搜索DiskANN index...");
-        let mut index_results = index_guard.search(query, k * 2)?;
+        let fetch = if rerank {
+            k * RERANK_CANDIDATE_FACTOR
+        } else {
+            k * 2
+        };
+        let mut index_results = index_guard.search_with_width(query, fetch, width)?;
         drop(index_guard);
 
         // 🔍 Debug: 打印前5个结果
@@ -425,6 +532,11 @@ impl MoteDB {
                 );
             }
         }
+        if rerank {
+            index_results.truncate(k * RERANK_CANDIDATE_FACTOR);
+            index_results =
+                self.rerank_exact(table_name, col_position, query, metric, index_results)?;
+        }
         index_results.truncate(k);
 
         debug_log!("[vector_search] 🔍 最终返回{}个结果", index_results.len());
@@ -442,6 +554,44 @@ impl MoteDB {
         Ok(index_results)
     }
 
+    /// Re-score `candidates` with the full-precision vector in each row and
+    /// re-sort. Distances follow `vector_search` (squared L2 or
+    /// `1 - cosine_similarity`); rows that are gone are dropped.
+    fn rerank_exact(
+        &self,
+        table_name: &str,
+        col_position: usize,
+        query: &[f32],
+        metric: crate::distance::DistanceKind,
+        candidates: Vec<(RowId, f32)>,
+    ) -> Result<Vec<(RowId, f32)>> {
+        let mut reranked = Vec::with_capacity(candidates.len());
+        for (row_id, _) in candidates {
+            let Some(values) = self.get_table_columns(table_name, row_id, &[col_position])? else {
+                continue;
+            };
+            let exact: Vec<f32> = match values.into_iter().next() {
+                Some(Value::Vector(v)) => v.to_vec(),
+                Some(Value::Tensor(t)) => t.to_f32(),
+                _ => continue,
+            };
+            if exact.len() != query.len() {
+                continue;
+            }
+            let distance = match metric {
+                crate::distance::DistanceKind::Euclidean => {
+                    crate::distance::euclidean::euclidean_distance_squared(query, &exact)
+                }
+                crate::distance::DistanceKind::Cosine => {
+                    crate::distance::cosine_distance(query, &exact)
+                }
+            };
+            reranked.push((row_id, distance));
+        }
+        reranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Ok(reranked)
+    }
+
     /// Get vector index statistics
     ///
     /// # Example
@@ -15,11 +15,21 @@
 //! - Immutable Buffers: Read-only, being flushed
 //! - Queries check: Active + Immutable + Persistent Index
 //! - Flush doesn't block writes (switch to new active buffer)
+//!
+//! # Bounded Memory
+//! Immutable buffers pile up when drains fall behind a write burst. The
+//! buffer carries a hard cap (`max_size`, default 4 × `size_limit`); once
+//! active + immutable bytes reach it, `over_capacity()` signals backpressure
+//! and the owning index must drain synchronously before accepting more writes.
 
 use parking_lot::{Mutex, RwLock};
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Default hard cap, in multiples of `size_limit`
+const DEFAULT_MAX_SIZE_FACTOR: usize = 4;
+
 /// Type alias to reduce complexity of the immutable buffer list.
 type ImmutableBufferList<K, V> = Arc<RwLock<Vec<Arc<BufferState<K, V>>>>>;
 
@@ -63,6 +73,12 @@ where
     /// Size limit in bytes (e.g., 1MB)
     size_limit: usize,
 
+    /// Hard cap on active + immutable bytes (backpressure above it)
+    max_size: usize,
+
+    /// Writes that hit the hard cap and had to wait for a drain
+    backpressure_events: AtomicU64,
+
     /// Flush lock (prevents concurrent flush operations)
     flush_lock: Arc<Mutex<()>>,
 }
@@ -103,10 +119,19 @@ where
             })),
             immutable: Arc::new(RwLock::new(Vec::new())),
             size_limit,
+            max_size: size_limit.saturating_mul(DEFAULT_MAX_SIZE_FACTOR),
+            backpressure_events: AtomicU64::new(0),
             flush_lock: Arc::new(Mutex::new(())),
         }
     }
 
+    /// Set the hard cap on buffered bytes (builder style). Clamped to at
+    /// least `size_limit` so a single full buffer never trips it.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.max(self.size_limit);
+        self
+    }
+
     /// Insert a key-value pair
     ///
     /// # Returns
@@ -395,6 +420,8 @@ where
             immutable_size_bytes: immutable_size,
             total_size_bytes: active_size + immutable_size,
             size_limit: self.size_limit,
+            max_size_bytes: self.max_size,
+            backpressure_events: self.backpressure_events.load(Ordering::Relaxed),
             fullness: ((active_size + immutable_size) as f64 / self.size_limit as f64 * 100.0)
                 as u8,
         }
@@ -419,10 +446,33 @@ where
         !self.immutable.read().is_empty()
     }
 
+    /// Backpressure signal: buffered bytes reached the hard cap and the
+    /// caller must drain immutable buffers before writing more
+    pub fn over_capacity(&self) -> bool {
+        self.size() >= self.max_size
+    }
+
+    /// Record a write that waited on a forced drain
+    pub fn note_backpressure(&self) {
+        self.backpressure_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Hard cap on buffered bytes
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
     /// Get number of immutable buffers waiting to flush
     pub fn immutable_count(&self) -> usize {
         self.immutable.read().len()
     }
+
+    /// Entries buffered in memory (active + immutable) not yet flushed
+    pub fn entry_count(&self) -> usize {
+        let active = self.active.read().data.len();
+        let immutable: usize = self.immutable.read().iter().map(|b| b.data.len()).sum();
+        active + immutable
+    }
 }
 
 /// Buffer statistics
@@ -440,6 +490,10 @@ pub struct BufferStats {
     pub total_size_bytes: usize,
     /// Size limit
     pub size_limit: usize,
+    /// Hard cap on total size
+    pub max_size_bytes: usize,
+    /// Writes that waited on a forced drain at the hard cap
+    pub backpressure_events: u64,
     /// Fullness percentage (0-100+)
     pub fullness: u8,
 }
@@ -519,6 +573,30 @@ mod tests {
         assert!(stats.fullness >= 100);
     }
 
+    #[test]
+    fn test_mem_buffer_hard_cap() {
+        let buffer: IndexMemBuffer<i32, i32> = IndexMemBuffer::new(64).with_max_size(192);
+        assert_eq!(buffer.max_size(), 192);
+
+        let mut i = 0;
+        while !buffer.over_capacity() {
+            buffer.insert(i, i).unwrap();
+            i += 1;
+        }
+        assert!(buffer.size() >= 192);
+        assert!(buffer.immutable_count() >= 2);
+
+        // Draining the immutable buffers lifts the signal
+        while buffer.flush().unwrap().is_some() {}
+        assert!(!buffer.over_capacity());
+        buffer.note_backpressure();
+        assert_eq!(buffer.stats().backpressure_events, 1);
+
+        // The cap never sits below a single buffer
+        let clamped: IndexMemBuffer<i32, i32> = IndexMemBuffer::new(64).with_max_size(8);
+        assert_eq!(clamped.max_size(), 64);
+    }
+
     #[test]
     fn test_drain_prevents_insert_interleaving() {
         // Verifies that drain() holds active.write lock long enough
@@ -0,0 +1,297 @@
+//! Hard Memory Cap with Graceful Degradation
+//!
+//! When `DBConfig::memory_limit` is set, every statement entering through
+//! `Database` samples process memory (at most once per `sample_interval_ms`)
+//! and the budget moves between three levels:
+//! - **Normal**: below `pressure_ratio × max_bytes`
+//! - **Pressure**: the row cache and decoded column caches are shrunk,
+//!   memtables and column index write buffers are flushed and DiskANN
+//!   searches run with a narrower list
+//! - **Critical**: at or above `max_bytes`; caches are dropped entirely and
+//!   statements marked `/*+ BEST_EFFORT */` fail with
+//!   [`StorageError::MemoryLimitExceeded`] instead of growing the process
+//!   until the OOM killer takes it down
+//!
+//! Usage is jemalloc's resident bytes when it is the global allocator, the
+//! process RSS on Linux otherwise. Where neither is available usage is
+//! unknown and the cap never trips.
+
+use crate::config::MemoryLimitConfig;
+use crate::database::core::MoteDB;
+use crate::{Result, StorageError};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Minimum time between two rounds of degradation while under pressure
+const DEGRADE_INTERVAL_MS: u64 = 1000;
+
+/// Share of the row cache kept under pressure (1 / N)
+const PRESSURE_ROW_CACHE_DIVISOR: usize = 4;
+
+/// Memory budget level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressure {
+    Normal,
+    Pressure,
+    Critical,
+}
+
+impl MemoryPressure {
+    fn from_u8(level: u8) -> Self {
+        match level {
+            0 => MemoryPressure::Normal,
+            1 => MemoryPressure::Pressure,
+            _ => MemoryPressure::Critical,
+        }
+    }
+}
+
+/// Snapshot of the memory budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryBudgetStats {
+    /// Configured hard cap (bytes)
+    pub limit_bytes: usize,
+    /// Last sampled usage (bytes, 0 if unknown)
+    pub usage_bytes: usize,
+    pub pressure: MemoryPressure,
+    /// Rounds of degradation (cache shrink + flush + narrower search)
+    pub degradations: u64,
+    /// Best-effort statements rejected at the cap
+    pub rejected_statements: u64,
+}
+
+/// Runtime state of the hard memory cap
+pub(crate) struct MemoryBudget {
+    config: MemoryLimitConfig,
+    started: Instant,
+    next_sample_ms: AtomicU64,
+    last_degrade_ms: AtomicU64,
+    level: AtomicU8,
+    usage: AtomicUsize,
+    degradations: AtomicU64,
+    rejected: AtomicU64,
+    /// Held while degrading, so concurrent statements don't all flush
+    degrading: Mutex<()>,
+}
+
+impl MemoryBudget {
+    pub(crate) fn new(config: MemoryLimitConfig) -> Self {
+        Self {
+            config,
+            started: Instant::now(),
+            next_sample_ms: AtomicU64::new(0),
+            last_degrade_ms: AtomicU64::new(u64::MAX),
+            level: AtomicU8::new(MemoryPressure::Normal as u8),
+            usage: AtomicUsize::new(0),
+            degradations: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            degrading: Mutex::new(()),
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Claim the next sample slot; false if another sample is recent enough
+    fn sample_due(&self) -> bool {
+        let now = self.elapsed_ms();
+        let next = self.next_sample_ms.load(Ordering::Relaxed);
+        now >= next
+            && self
+                .next_sample_ms
+                .compare_exchange(
+                    next,
+                    now + self.config.sample_interval_ms,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+    }
+
+    fn degrade_due(&self) -> bool {
+        let now = self.elapsed_ms();
+        let last = self.last_degrade_ms.load(Ordering::Relaxed);
+        last == u64::MAX || now.saturating_sub(last) >= DEGRADE_INTERVAL_MS
+    }
+
+    fn classify(&self, usage: usize) -> MemoryPressure {
+        let soft = (self.config.max_bytes as f64 * self.config.pressure_ratio) as usize;
+        if usage >= self.config.max_bytes {
+            MemoryPressure::Critical
+        } else if usage >= soft && usage > 0 {
+            MemoryPressure::Pressure
+        } else {
+            MemoryPressure::Normal
+        }
+    }
+
+    fn level(&self) -> MemoryPressure {
+        MemoryPressure::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    fn stats(&self) -> MemoryBudgetStats {
+        MemoryBudgetStats {
+            limit_bytes: self.config.max_bytes,
+            usage_bytes: self.usage.load(Ordering::Relaxed),
+            pressure: self.level(),
+            degradations: self.degradations.load(Ordering::Relaxed),
+            rejected_statements: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Current process memory usage in bytes, if it can be measured
+pub(crate) fn process_memory_usage() -> Option<usize> {
+    #[cfg(all(
+        feature = "jemalloc",
+        not(target_env = "msvc"),
+        not(target_family = "wasm")
+    ))]
+    {
+        use tikv_jemalloc_ctl::{epoch, stats};
+        if epoch::advance().is_ok() {
+            if let Ok(resident) = stats::resident::read() {
+                return Some(resident);
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // statm: size resident shared text lib data dt (in pages)
+        let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+        let pages: usize = statm.split_whitespace().nth(1)?.parse().ok()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page_size > 0 {
+            return Some(pages * page_size as usize);
+        }
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+impl MoteDB {
+    /// Admission check run before each statement: samples memory when due,
+    /// degrades under pressure, and rejects best-effort statements at the cap
+    pub(crate) fn check_memory_budget(&self, best_effort: bool) -> Result<()> {
+        let Some(budget) = &self.memory_budget else {
+            return Ok(());
+        };
+        let level = if budget.sample_due() {
+            self.sample_memory_budget(budget)
+        } else {
+            budget.level()
+        };
+
+        if best_effort && level == MemoryPressure::Critical {
+            budget.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(StorageError::MemoryLimitExceeded(format!(
+                "best-effort statement rejected: {} bytes in use, limit is {} bytes",
+                budget.usage.load(Ordering::Relaxed),
+                budget.config.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sample memory now and report the budget (`None` without a cap)
+    pub fn memory_budget_stats(&self) -> Option<MemoryBudgetStats> {
+        let budget = self.memory_budget.as_ref()?;
+        self.sample_memory_budget(budget);
+        Some(budget.stats())
+    }
+
+    fn sample_memory_budget(&self, budget: &MemoryBudget) -> MemoryPressure {
+        let usage = process_memory_usage().unwrap_or(0);
+        budget.usage.store(usage, Ordering::Relaxed);
+        let level = budget.classify(usage);
+        let previous = MemoryPressure::from_u8(budget.level.swap(level as u8, Ordering::Relaxed));
+
+        if level != previous {
+            info_log!(
+                "[MemoryBudget] {:?} -> {:?} ({} / {} bytes)",
+                previous,
+                level,
+                usage,
+                budget.config.max_bytes
+            );
+        }
+        if level == MemoryPressure::Normal {
+            // Repeated while Normal: indexes busy at the first try catch up
+            if budget.degradations.load(Ordering::Relaxed) > 0 {
+                self.restore_after_pressure();
+            }
+        } else if budget.degrade_due() {
+            self.degrade(budget, level);
+        }
+        level
+    }
+
+    /// Shed memory: shrink caches, flush memtables, narrow vector searches
+    fn degrade(&self, budget: &MemoryBudget, level: MemoryPressure) {
+        let Some(_guard) = budget.degrading.try_lock() else {
+            return;
+        };
+        if !budget.degrade_due() {
+            return;
+        }
+        budget
+            .last_degrade_ms
+            .store(budget.elapsed_ms(), Ordering::Relaxed);
+
+        let critical = level == MemoryPressure::Critical;
+        let keep_rows = if critical {
+            0
+        } else {
+            self.row_cache.stats().capacity / PRESSURE_ROW_CACHE_DIVISOR
+        };
+        self.row_cache.shrink_to(keep_rows);
+        for entry in self.col_segment_stores.iter() {
+            if critical {
+                entry.value().release_pages_only();
+            }
+            entry.value().clear_cache();
+        }
+
+        // Vector indexes busy in the index builder get the cap next round
+        for entry in self.vector_indexes.iter() {
+            if let Some(index) = entry.value().try_read() {
+                index.set_search_width_limit(Some(budget.config.degraded_search_width));
+            }
+        }
+
+        if let Err(e) = self.flush() {
+            warn_log!("[MemoryBudget] Memtable flush under pressure failed: {}", e);
+        }
+        // Column index write buffers are otherwise drained only at checkpoint
+        for entry in self.column_indexes.iter() {
+            if let Err(e) = entry.value().flush_buffer() {
+                warn_log!(
+                    "[MemoryBudget] Index buffer drain for '{}' failed: {}",
+                    entry.key(),
+                    e
+                );
+            }
+        }
+        crate::database::persistence::trim_allocator();
+
+        budget.degradations.fetch_add(1, Ordering::Relaxed);
+        warn_log!(
+            "[MemoryBudget] {:?}: shed caches and flushed memtables ({} / {} bytes)",
+            level,
+            budget.usage.load(Ordering::Relaxed),
+            budget.config.max_bytes
+        );
+    }
+
+    fn restore_after_pressure(&self) {
+        for entry in self.vector_indexes.iter() {
+            if let Some(index) = entry.value().try_read() {
+                index.set_search_width_limit(None);
+            }
+        }
+    }
+}
@@ -7,9 +7,21 @@
 //! - `crud`: Complete CRUD operations (insert, get, update, delete, scan)
 //! - `table`: Table management (create/drop/list/schema)
 //! - `helpers`: Batch index building methods
+//! - `generated`: Computing generated column values on write
+//! - `continuous`: Continuous aggregates (materialized views) maintained on insert
+//! - `dump`: Tables written out as the SQL statements that recreate them
+//! - `embedder`: On-insert embedding hooks computing vector columns
+//! - `ring_buffer`: Ring-buffer tables (`MAX_ROWS` / `MAX_BYTES`) evicting oldest rows
 //! - `indexes`: Index operations (timestamp, vector, spatial, text, column)
 //! - `persistence`: Flush and checkpoint operations
+//! - `shared_scan`: Concurrent full scans of a table sharing one pass over it
+//! - `cache_state`: Cache warm-up state saved at shutdown, loaded at open
+//! - `cache_stats`: Unified cache statistics and per-table row-cache quotas
+//! - `collection`: Vector collections (id, vector, metadata) without SQL
+//! - `memory_budget`: Hard memory cap with graceful degradation
 //! - `transaction`: MVCC transactions and savepoints
+//! - `write_batch`: Atomic multi-table write batches (one WAL record)
+//! - `upsert`: Embedding upserts keyed by a user column
 //! - `mem_buffer`: Universal MemBuffer for all indexes
 //! - `index_metadata`: Index metadata management
 
@@ -25,21 +37,42 @@ macro_rules! ensure_open {
     };
 }
 
+pub mod cache_state;
+pub mod cache_stats;
+pub mod collection;
+pub mod continuous;
 pub mod core;
 pub mod crud;
+pub mod csv;
+pub mod dump;
+pub mod embedder;
+pub mod generated;
 pub mod helpers;
 pub mod index_metadata;
 pub mod indexes;
 pub mod mem_buffer;
+pub mod memory_budget;
 pub mod persistence;
 pub mod pk_cache;
+pub mod ring_buffer;
+pub mod shared_scan;
 pub mod table;
 pub mod timeseries;
 pub mod transaction;
+pub mod upsert;
+pub mod write_batch;
 
 // Re-export main types
+pub use cache_state::CacheWarmupStats;
+pub use collection::{
+    Collection, CollectionConfig, CollectionHit, CollectionRecord, MetadataFilter,
+};
 pub use core::MoteDB;
+pub use csv::CsvOptions;
 pub use index_metadata::{IndexMetadata, IndexRegistry, IndexType};
-pub use indexes::{MemTableScanProfile, QueryProfile};
+pub use indexes::{IndexHealth, MemTableScanProfile, QueryProfile, VectorSearchOptions};
 pub use mem_buffer::{BufferStats, IndexMemBuffer};
-pub use transaction::TransactionStats;
+pub use memory_budget::{MemoryBudgetStats, MemoryPressure};
+pub use transaction::{ActiveTransaction, TransactionStats};
+pub use upsert::EmbeddingUpsert;
+pub use write_batch::WriteBatch;
@@ -63,12 +63,13 @@ impl MoteDB {
     }
 
     fn flush_impl(&self) -> Result<()> {
-        if !self.path.exists() {
+        if !crate::storage::backend::backend_for(&self.path).exists(&self.path) {
             return Ok(());
         }
 
         self.lsm_engine.force_rotate()?;
         self.lsm_engine.flush()?;
+        self.drain_index_batches();
 
         // Only flush i-Octree here. Vector and text indexes are NOT flushed
         // because the async index-builder thread holds their write locks during
@@ -113,6 +114,13 @@ impl MoteDB {
         self.checkpoint_impl(false)
     }
 
+    /// Bytes currently held by the WAL, including writes not yet synced.
+    /// A checkpoint resets it to the file headers once table data and
+    /// indexes are durable.
+    pub fn wal_size(&self) -> u64 {
+        self.wal.size_bytes()
+    }
+
     /// Full checkpoint with index rebuild (used on shutdown/drop)
     pub fn checkpoint_full(&self) -> Result<()> {
         ensure_open!(self);
@@ -123,6 +131,80 @@ impl MoteDB {
         self.checkpoint_impl(true)
     }
 
+    /// Checkpoint and bundle the whole database directory into one
+    /// immutable image at `dest` (see [`crate::storage::packed`]). Open it
+    /// with [`MoteDB::open_packed`]. Writes that race with packing may or
+    /// may not be included, so quiesce writers first.
+    pub fn pack<P: AsRef<std::path::Path>>(
+        &self,
+        dest: P,
+    ) -> Result<crate::storage::packed::PackStats> {
+        self.flush()?;
+        let _guard = self
+            .checkpoint_mutex
+            .lock()
+            .map_err(|_| StorageError::Lock("Checkpoint mutex poisoned".into()))?;
+        self.checkpoint_impl(true)?;
+        crate::storage::packed::pack_directory(&self.path, dest.as_ref(), |rel| {
+            rel.as_os_str() == ".lock" || rel.extension().is_some_and(|ext| ext == "tmp")
+        })
+    }
+
+    /// Rewrite every stored row still in an older row encoding (whole-row
+    /// bincode, or values written as bincode of [`crate::types::Value`])
+    /// in the current versioned format, then checkpoint so the WAL holds
+    /// no old records either. Such rows are readable without this; it
+    /// makes the data directory independent of the legacy decoders.
+    /// Rows already in the current format are left untouched, so running
+    /// it again is cheap.
+    pub fn migrate_row_format(&self) -> Result<crate::storage::value_codec::MigrationStats> {
+        use crate::storage::lsm::ValueData;
+        use crate::storage::{row_format, value_codec};
+
+        ensure_open!(self);
+        let mut stats = value_codec::MigrationStats::default();
+        for table_name in self.table_registry.list_tables()? {
+            let schema = self.table_registry.get_table(&table_name)?;
+            let col_types = schema.col_types();
+            let table_id = self.table_registry.get_table_id(&table_name)? as u64;
+            stats.tables += 1;
+
+            let rows = self
+                .lsm_engine
+                .scan_range(table_id << 32, (table_id + 1) << 32)?;
+            for (key, value) in rows {
+                if value.deleted {
+                    continue;
+                }
+                let data = match &value.data {
+                    ValueData::Inline(bytes) => bytes.to_vec(),
+                    ValueData::Blob(blob_ref) => self.lsm_engine.resolve_blob(blob_ref)?,
+                };
+                stats.rows_scanned += 1;
+                let row = row_format::decode(&data, col_types)?;
+                let encoded = row_format::encode(&row, col_types)
+                    .unwrap_or_else(|_| value_codec::encode_row(&row));
+                if encoded == data {
+                    continue;
+                }
+                let ts = self.write_lsn.fetch_add(1, Ordering::SeqCst);
+                self.lsm_engine
+                    .put(key, crate::storage::lsm::Value::new(encoded, ts))?;
+                stats.rows_rewritten += 1;
+            }
+        }
+
+        self.flush()?;
+        self.checkpoint()?;
+        info_log!(
+            "[MIGRATE] {} rows rewritten in the current row format ({} scanned, {} tables)",
+            stats.rows_rewritten,
+            stats.rows_scanned,
+            stats.tables
+        );
+        Ok(stats)
+    }
+
     /// VACUUM: force compaction and reclaim disk space.
     ///
     /// Flushes memtables, runs compaction on all LSM levels (dropping tombstones),
@@ -237,6 +319,7 @@ impl MoteDB {
         if let Err(e) = self.version_store.vacuum(min_active_ts) {
             warn_log!("[VACUUM] Version store vacuum failed: {}", e);
         }
+        self.purge_retained_deletes();
 
         // 6. Return freed memory to the OS (cross-platform)
         trim_allocator();
@@ -269,12 +352,18 @@ impl MoteDB {
             let wal_dir = self.path.join("wal");
             if let Ok(wal_size) = super::helpers::dir_size(&wal_dir) {
                 if wal_size == 0 {
+                    // No new data, but indexes created or rebuilt since the
+                    // last checkpoint still need publishing on shutdown
+                    if rebuild_indexes {
+                        self.publish_index_files()?;
+                    }
                     return Ok(());
                 }
             }
         }
 
         self.lsm_engine.flush()?;
+        self.drain_index_batches();
 
         if rebuild_indexes {
             self.rebuild_timestamp_index()?;
@@ -309,6 +398,12 @@ impl MoteDB {
             warn_log!("[Flush] Columnar store flush failed: {}", e);
         }
 
+        // Table data is durable: commit every index's files in one manifest
+        // edit before the WAL that could replay past them is truncated. A
+        // failure fails the checkpoint and keeps the WAL, so the previous
+        // commit still describes what recovery replays onto.
+        self.publish_index_files()?;
+
         let checkpoint_done = if immutable_queue_len == 0 || !self.col_segment_stores.is_empty() {
             // All data has been flushed:
             // - LSM memtables are empty (immutable_queue is 0), OR
@@ -329,6 +424,7 @@ impl MoteDB {
         if let Err(e) = self.version_store.vacuum(min_active_ts) {
             warn_log!("[Flush] Version store vacuum failed: {}", e);
         }
+        self.purge_retained_deletes();
         // Only reset pending_updates if WAL checkpoint was actually performed.
         // If skipped (new writes arrived during flush), keep the counter so
         // the next checkpoint knows there's outstanding data to flush.
@@ -369,6 +465,7 @@ impl MoteDB {
     pub fn flush_all_indexes(&self) -> Result<()> {
         let async_pipeline = self.is_async_index_pipeline_active();
 
+        self.drain_timestamp_reorder_buffer()?;
         self.timestamp_index.write().flush()?;
 
         if !async_pipeline {
@@ -394,3 +491,58 @@ impl MoteDB {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::database::core::MoteDB;
+    use crate::storage::row_format;
+    use crate::types::{ColumnDef, ColumnType, Decimal, TableSchema, Value};
+
+    #[test]
+    fn test_migrate_row_format_rewrites_legacy_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = MoteDB::create(dir.path()).unwrap();
+        let schema = TableSchema::new(
+            "t".into(),
+            vec![
+                ColumnDef::new("id".into(), ColumnType::Integer, 0),
+                ColumnDef::new(
+                    "amount".into(),
+                    ColumnType::Decimal {
+                        precision: 10,
+                        scale: 2,
+                    },
+                    1,
+                ),
+            ],
+        );
+        db.create_table(schema.clone()).unwrap();
+
+        let row = vec![
+            Value::Integer(1),
+            Value::decimal(Decimal::new(1250, 2).unwrap()),
+        ];
+        let legacy = bincode::serialize(&row).unwrap();
+        let current = row_format::encode(&row, schema.col_types()).unwrap();
+        let key = db.make_composite_key("t", 1);
+        db.lsm_engine
+            .put(key, crate::storage::lsm::Value::new(legacy, 1))
+            .unwrap();
+        db.lsm_engine
+            .put(key + 1, crate::storage::lsm::Value::new(current.clone(), 2))
+            .unwrap();
+
+        let stats = db.migrate_row_format().unwrap();
+        assert_eq!(
+            (stats.tables, stats.rows_scanned, stats.rows_rewritten),
+            (1, 2, 1)
+        );
+        let stored = db.lsm_engine.get(key).unwrap().unwrap();
+        match stored.data {
+            crate::storage::lsm::ValueData::Inline(bytes) => assert_eq!(*bytes, current),
+            _ => panic!("expected an inline row"),
+        }
+
+        assert_eq!(db.migrate_row_format().unwrap().rows_rewritten, 0);
+    }
+}
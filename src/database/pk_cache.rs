@@ -25,6 +25,12 @@ impl PkKey {
             crate::types::Value::Integer(i) => PkKey::Int(*i),
             crate::types::Value::Float(f) => PkKey::Float(f.to_bits()),
             crate::types::Value::Text(s) => PkKey::Text(s.as_str().to_string().into_boxed_str()),
+            // Same "t:" form as `Value::to_hash_key`
+            crate::types::Value::Uuid(_)
+            | crate::types::Value::Date(_)
+            | crate::types::Value::Time(_) => {
+                PkKey::Text(value.encoded_text().unwrap_or_default().into_boxed_str())
+            }
             crate::types::Value::Bool(b) => PkKey::Bool(*b),
             crate::types::Value::Null => PkKey::Null,
             _ => PkKey::Null,
@@ -0,0 +1,280 @@
+//! Ring-Buffer Tables - bounded tables that evict their oldest rows
+//!
+//! `CREATE TABLE log (...) MAX_ROWS = 10000 MAX_BYTES = 64MB` caps a table like
+//! a flight recorder: once an insert pushes the table over either limit, the
+//! oldest rows in insertion order are deleted. Each eviction removes a run of
+//! rows from the front of the insertion order in one
+//! [`WriteBatch`](super::WriteBatch), logged as one `WALRecord::DeleteRange`
+//! per stretch of consecutive row_ids. With AUTO_INCREMENT keys the row_ids
+//! follow the insertion order, so an eviction is a single WAL record however
+//! many rows it drops. Storage has no range tombstone: applying a range reads
+//! each row in it and writes one tombstone per row, as indexes, caches and
+//! row counts need every deleted row's old image, and recovery replays the
+//! record through `LSMEngine::delete_range`, which tombstones key by key. The
+//! newest row is always kept, even if it alone exceeds `MAX_BYTES`.
+//!
+//! The insertion order is kept in memory and rebuilt on open from the write
+//! timestamps of the live rows, so a row updated before a restart counts as
+//! newer than rows inserted after it. Row sizes are measured at insert time,
+//! or at commit for rows inserted inside an explicit transaction.
+
+use super::core::MoteDB;
+use crate::types::{Row, RowId};
+use crate::Result;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Insertion order of every ring-buffer table
+#[derive(Default)]
+pub(crate) struct RingBuffers {
+    tables: DashMap<String, Arc<Mutex<RingLog>>>,
+}
+
+#[derive(Default)]
+struct RingLog {
+    /// (sequence, row_id), oldest first. Entries whose sequence no longer
+    /// matches `live` belong to rows deleted (or re-inserted) since.
+    order: VecDeque<(u64, RowId)>,
+    /// row_id → (sequence, size in bytes)
+    live: HashMap<RowId, (u64, u64)>,
+    next_seq: u64,
+    bytes: u64,
+}
+
+impl RingLog {
+    fn push(&mut self, row_id: RowId, bytes: u64) {
+        self.remove(row_id);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.order.push_back((seq, row_id));
+        self.live.insert(row_id, (seq, bytes));
+        self.bytes += bytes;
+    }
+
+    fn remove(&mut self, row_id: RowId) {
+        if let Some((_, bytes)) = self.live.remove(&row_id) {
+            self.bytes -= bytes;
+        }
+        // Rows deleted or updated in place leave their old entry behind:
+        // drop the stale ones once they outnumber the live, so a table that
+        // never reaches its limits does not grow `order` without bound
+        if self.order.len() > 2 * self.live.len() + 16 {
+            let live = &self.live;
+            self.order
+                .retain(|(seq, id)| live.get(id).is_some_and(|(s, _)| s == seq));
+        }
+    }
+
+    /// Pop the oldest rows until both limits hold again
+    fn take_overflow(
+        &mut self,
+        max_rows: Option<u64>,
+        max_bytes: Option<u64>,
+    ) -> Vec<(RowId, u64)> {
+        let mut evicted = Vec::new();
+        while self.live.len() > 1
+            && (max_rows.is_some_and(|max| self.live.len() as u64 > max)
+                || max_bytes.is_some_and(|max| self.bytes > max))
+        {
+            let Some((seq, row_id)) = self.order.pop_front() else {
+                break;
+            };
+            if self
+                .live
+                .get(&row_id)
+                .is_some_and(|(live_seq, _)| *live_seq == seq)
+            {
+                let (_, bytes) = self.live.remove(&row_id).unwrap_or_default();
+                self.bytes -= bytes;
+                evicted.push((row_id, bytes));
+            }
+        }
+        evicted
+    }
+
+    /// Put rows back at the front after a failed eviction
+    fn restore(&mut self, evicted: Vec<(RowId, u64)>) {
+        for (row_id, bytes) in evicted.into_iter().rev() {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            self.order.push_front((seq, row_id));
+            self.live.insert(row_id, (seq, bytes));
+            self.bytes += bytes;
+        }
+    }
+}
+
+/// Evicted rows as `start..end` row_id ranges, in eviction order. A range
+/// only grows by the next row_id and never crosses a 2^32 boundary, so it
+/// covers exactly the evicted rows and maps onto one table's key range.
+fn row_id_ranges(evicted: &[(RowId, u64)]) -> Vec<(RowId, RowId)> {
+    let mut ranges: Vec<(RowId, RowId)> = Vec::new();
+    for &(row_id, _) in evicted {
+        match ranges.last_mut() {
+            Some((_, end)) if *end == row_id && row_id & 0xFFFF_FFFF != 0 => *end += 1,
+            _ => ranges.push((row_id, row_id + 1)),
+        }
+    }
+    ranges
+}
+
+/// Size a row counts for against `MAX_BYTES`
+fn row_bytes(row: &Row) -> u64 {
+    bincode::serialized_size(row).unwrap_or(0)
+}
+
+impl MoteDB {
+    /// Rebuild the insertion order of every ring-buffer table (on open)
+    pub(crate) fn load_ring_buffers(&self) -> Result<()> {
+        for table_name in self.table_registry.list_tables()? {
+            if self.table_registry.get_table(&table_name)?.is_ring_buffer() {
+                self.register_ring_buffer(&table_name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start tracking a ring-buffer table, seeding the order from its live rows
+    pub(crate) fn register_ring_buffer(&self, table_name: &str) -> Result<()> {
+        let mut rows = Vec::new();
+        if let Some(store) = self.get_col_segment_store(table_name) {
+            store.flush_buffer()?;
+            rows.extend(
+                store
+                    .scan()
+                    .map(|(key, ts, row)| (ts, key & 0xFFFF_FFFF, row_bytes(&row))),
+            );
+        }
+        rows.sort_unstable_by_key(|(ts, ..)| *ts);
+
+        let mut log = RingLog::default();
+        for (_, row_id, bytes) in rows {
+            log.push(row_id, bytes);
+        }
+        self.ring_buffers
+            .tables
+            .insert(table_name.to_string(), Arc::new(Mutex::new(log)));
+        Ok(())
+    }
+
+    pub(crate) fn unregister_ring_buffer(&self, table_name: &str) {
+        self.ring_buffers.tables.remove(table_name);
+    }
+
+    /// Sizes of rows about to be inserted, or `None` if `table_name` is not
+    /// a ring-buffer table
+    pub(crate) fn ring_buffer_row_bytes(&self, table_name: &str, rows: &[Row]) -> Option<Vec<u64>> {
+        self.ring_buffers
+            .tables
+            .contains_key(table_name)
+            .then(|| rows.iter().map(row_bytes).collect())
+    }
+
+    /// Append inserted rows to the table's insertion order
+    pub(crate) fn ring_buffer_track(&self, table_name: &str, row_ids: &[RowId], bytes: &[u64]) {
+        if let Some(log) = self.ring_buffers.tables.get(table_name) {
+            let mut log = log.lock();
+            for (row_id, bytes) in row_ids.iter().zip(bytes) {
+                log.push(*row_id, *bytes);
+            }
+        }
+    }
+
+    pub(crate) fn ring_buffer_untrack(&self, table_name: &str, row_id: RowId) {
+        if let Some(log) = self.ring_buffers.tables.get(table_name) {
+            log.lock().remove(row_id);
+        }
+    }
+
+    /// Delete the oldest rows of a ring-buffer table until it fits its limits
+    pub(crate) fn evict_ring_buffer(&self, table_name: &str) -> Result<()> {
+        let Some(log) = self
+            .ring_buffers
+            .tables
+            .get(table_name)
+            .map(|entry| entry.value().clone())
+        else {
+            return Ok(());
+        };
+        let schema = self.table_registry.get_table(table_name)?;
+        let evicted = log.lock().take_overflow(schema.max_rows, schema.max_bytes);
+        if evicted.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = self.write_batch();
+        for (start, end) in row_id_ranges(&evicted) {
+            batch.delete_range(table_name, start, end);
+        }
+        if let Err(e) = batch.commit() {
+            log.lock().restore(evicted);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_log_evicts_oldest_live_rows() {
+        let mut log = RingLog::default();
+        for row_id in 1..=5 {
+            log.push(row_id, 10);
+        }
+        log.remove(2);
+        log.push(1, 30); // re-insert moves row 1 to the back
+
+        let evicted = log.take_overflow(Some(2), None);
+        assert_eq!(evicted, vec![(3, 10), (4, 10)]);
+        assert_eq!(log.live.len(), 2);
+
+        // The newest row survives even when it alone exceeds the byte limit
+        let evicted = log.take_overflow(None, Some(20));
+        assert_eq!(evicted, vec![(5, 10)]);
+        assert_eq!(log.bytes, 30);
+
+        log.restore(evicted);
+        assert_eq!(log.take_overflow(Some(1), None), vec![(5, 10)]);
+    }
+
+    #[test]
+    fn test_row_id_ranges_follow_eviction_order() {
+        let evicted: Vec<(RowId, u64)> = [7, 8, 9, 3, 4, 10, 0xFFFF_FFFF, 0x1_0000_0000]
+            .into_iter()
+            .map(|row_id| (row_id, 10))
+            .collect();
+        assert_eq!(
+            row_id_ranges(&evicted),
+            vec![
+                (7, 10),
+                (3, 5),
+                (10, 11),
+                (0xFFFF_FFFF, 0x1_0000_0000),
+                (0x1_0000_0000, 0x1_0000_0001),
+            ]
+        );
+        assert_eq!(row_id_ranges(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_ring_log_compacts_stale_entries() {
+        let mut log = RingLog::default();
+        for i in 0..10_000 {
+            log.push(i % 8, 10); // updates in place, never over the limits
+        }
+        assert_eq!(log.live.len(), 8);
+        assert!(log.order.len() <= 2 * 8 + 16);
+
+        for row_id in 0..7 {
+            log.remove(row_id);
+        }
+        assert_eq!(log.take_overflow(Some(0), None), vec![]);
+        log.push(100, 10);
+        assert_eq!(log.take_overflow(Some(1), None), vec![(7, 10)]);
+    }
+}
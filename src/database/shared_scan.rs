@@ -0,0 +1,398 @@
+//! Shared Scans - concurrent full scans of one table read it once
+//!
+//! Dashboards tend to fire several queries over the same large table at
+//! about the same time, and each would otherwise iterate and decode every row
+//! on its own. A scan started with [`MoteDB::scan_table_shared`] attaches to a
+//! scan of the same table that is already in flight instead: rows are read
+//! from storage once, in batches, and every attached reader receives the same
+//! decoded batches (shared through an `Arc`, not copied).
+//!
+//! Whichever reader first needs a batch nobody has read yet pulls it from
+//! storage, so a scan advances at the pace of its fastest reader. A reader
+//! that attaches late replays the batches it missed, so a scan keeps its first
+//! [`JOIN_WINDOW`] batches and accepts readers only until it has to drop one;
+//! after that, batches are dropped as soon as every reader has passed them.
+//!
+//! A scan only accepts readers while the write LSN and the table schema are
+//! what they were when it started, so attaching never returns older rows
+//! than a scan of its own would.
+
+use super::core::MoteDB;
+use crate::types::{Row, RowId, TableSchema};
+use crate::{Result, StorageError};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Weak};
+
+/// Batches a scan keeps for readers that attach after it started
+const JOIN_WINDOW: usize = 16;
+
+/// Rows of one table in storage order, as the scan reads them
+type RowSource = Box<dyn Iterator<Item = Result<(RowId, Row)>> + Send>;
+
+/// One batch of decoded rows, shared by every reader of a scan
+pub(crate) type RowBatch = Arc<Vec<(RowId, Row)>>;
+
+/// In-flight shared scans, at most one per table
+pub(crate) struct SharedScans {
+    enabled: bool,
+    tables: DashMap<String, Weak<SharedScan>>,
+}
+
+impl SharedScans {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            tables: DashMap::new(),
+        }
+    }
+
+    /// Attach to the table's scan if it still accepts readers
+    fn attach(
+        &self,
+        table_name: &str,
+        lsn: u64,
+        schema: &Arc<TableSchema>,
+    ) -> Option<SharedScanReader> {
+        let scan = self.tables.get(table_name)?.upgrade()?;
+        if scan.lsn != Some(lsn) || !Arc::ptr_eq(&scan.schema, schema) {
+            return None;
+        }
+        SharedScan::attach(&scan)
+    }
+}
+
+/// A full scan of one table and the batches its readers have not all passed
+struct SharedScan {
+    /// Write LSN the scan's rows are current to; `None` if writes were in
+    /// flight when it started, so no other reader may attach
+    lsn: Option<u64>,
+    schema: Arc<TableSchema>,
+    batch_size: usize,
+    /// `None` once the rows are exhausted or reading them failed
+    source: Mutex<Option<RowSource>>,
+    state: Mutex<ScanState>,
+}
+
+#[derive(Default)]
+struct ScanState {
+    /// Batches not yet passed by every reader; the first is batch `base`
+    batches: VecDeque<RowBatch>,
+    base: usize,
+    /// Batch number each reader reads next → number of readers there
+    readers: BTreeMap<usize, usize>,
+    /// Error that ended the scan, returned to readers reaching its end
+    error: Option<String>,
+}
+
+impl ScanState {
+    /// Batches pulled from storage so far
+    fn produced(&self) -> usize {
+        self.base + self.batches.len()
+    }
+
+    fn batch(&self, n: usize) -> Option<RowBatch> {
+        n.checked_sub(self.base)
+            .and_then(|i| self.batches.get(i))
+            .cloned()
+    }
+
+    fn move_reader(&mut self, from: Option<usize>, to: Option<usize>) {
+        if let Some(from) = from {
+            if let Some(count) = self.readers.get_mut(&from) {
+                *count -= 1;
+                if *count == 0 {
+                    self.readers.remove(&from);
+                }
+            }
+        }
+        if let Some(to) = to {
+            *self.readers.entry(to).or_default() += 1;
+        }
+        self.trim();
+    }
+
+    /// Drop the batches every reader has passed, once the scan is past the
+    /// join window
+    fn trim(&mut self) {
+        if self.produced() <= JOIN_WINDOW {
+            return;
+        }
+        let slowest = self.readers.keys().next().copied().unwrap_or(usize::MAX);
+        while self.base < slowest && self.batches.pop_front().is_some() {
+            self.base += 1;
+        }
+    }
+}
+
+impl SharedScan {
+    fn new(
+        lsn: Option<u64>,
+        schema: Arc<TableSchema>,
+        source: RowSource,
+        batch_size: usize,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            lsn,
+            schema,
+            batch_size: batch_size.max(1),
+            source: Mutex::new(Some(source)),
+            state: Mutex::new(ScanState::default()),
+        })
+    }
+
+    /// New reader at the first batch, unless batches were already dropped
+    fn attach(scan: &Arc<Self>) -> Option<SharedScanReader> {
+        let mut state = scan.state.lock();
+        if state.base > 0 || state.error.is_some() {
+            return None;
+        }
+        state.move_reader(None, Some(0));
+        Some(SharedScanReader {
+            scan: scan.clone(),
+            next: Some(0),
+            current: None,
+            pos: 0,
+        })
+    }
+
+    /// Batch `n`, pulling it from storage if no reader has yet
+    fn batch(&self, n: usize) -> Option<Result<RowBatch>> {
+        if let Some(batch) = self.state.lock().batch(n) {
+            return Some(Ok(batch));
+        }
+        let mut source = self.source.lock();
+        // Another reader may have pulled it while we waited for the source
+        {
+            let state = self.state.lock();
+            if let Some(batch) = state.batch(n) {
+                return Some(Ok(batch));
+            }
+            if n < state.produced() {
+                // Already dropped: only detached readers ever lag behind
+                return None;
+            }
+            if let Some(error) = &state.error {
+                return Some(Err(StorageError::InvalidData(error.clone())));
+            }
+        }
+        let rows = source.as_mut()?;
+        let mut batch = Vec::with_capacity(self.batch_size);
+        while batch.len() < self.batch_size {
+            match rows.next() {
+                Some(Ok(row)) => batch.push(row),
+                Some(Err(e)) => {
+                    *source = None;
+                    self.state.lock().error = Some(format!("Shared scan failed: {}", e));
+                    return Some(Err(e));
+                }
+                None => {
+                    *source = None;
+                    break;
+                }
+            }
+        }
+        if batch.is_empty() {
+            return None;
+        }
+        let batch = Arc::new(batch);
+        self.state.lock().batches.push_back(batch.clone());
+        Some(Ok(batch))
+    }
+}
+
+/// One query's view of a shared scan: yields the table's rows batch by batch
+pub(crate) struct SharedScanReader {
+    scan: Arc<SharedScan>,
+    /// Batch number to read next; `None` once the scan is exhausted
+    next: Option<usize>,
+    /// Batch being iterated row by row, and the next row in it
+    current: Option<RowBatch>,
+    pos: usize,
+}
+
+impl SharedScanReader {
+    /// The next batch of rows, shared with the scan's other readers
+    pub(crate) fn next_batch(&mut self) -> Option<Result<RowBatch>> {
+        let n = self.next?;
+        let batch = self.scan.batch(n);
+        let next = match batch {
+            Some(Ok(_)) => Some(n + 1),
+            _ => None,
+        };
+        self.scan.state.lock().move_reader(Some(n), next);
+        self.next = next;
+        batch
+    }
+}
+
+impl Iterator for SharedScanReader {
+    type Item = Result<(RowId, Row)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(batch) = &self.current {
+                if let Some(row) = batch.get(self.pos) {
+                    self.pos += 1;
+                    return Some(Ok(row.clone()));
+                }
+            }
+            self.current = Some(match self.next_batch()? {
+                Ok(batch) => batch,
+                Err(e) => return Some(Err(e)),
+            });
+            self.pos = 0;
+        }
+    }
+}
+
+impl Drop for SharedScanReader {
+    fn drop(&mut self) {
+        if self.next.is_some() {
+            self.scan.state.lock().move_reader(self.next, None);
+        }
+    }
+}
+
+impl MoteDB {
+    /// Full scan of a table that shares its reads with concurrent scans of
+    /// the same table (see the module docs). A new scan reads `batch_size`
+    /// rows per batch; attaching to one in flight keeps its batch size.
+    pub(crate) fn scan_table_shared(
+        &self,
+        table_name: &str,
+        batch_size: usize,
+    ) -> Result<SharedScanReader> {
+        ensure_open!(self);
+        let schema = self.table_registry.get_table(table_name)?;
+        let scans = &self.shared_scans;
+        if scans.enabled {
+            let lsn = self.write_lsn.load(Ordering::SeqCst);
+            if let Some(reader) = scans.attach(table_name, lsn, &schema) {
+                return Ok(reader);
+            }
+        }
+
+        // Writers bump the LSN before their rows become visible, so the LSN
+        // only describes the rows read when no write is in flight
+        let lsn = if scans.enabled {
+            let gate = self.table_write_gate(table_name);
+            let at_rest = gate.try_write();
+            at_rest.map(|_| self.write_lsn.load(Ordering::SeqCst))
+        } else {
+            None
+        };
+        let source: RowSource = match self.get_col_segment_store(table_name) {
+            Some(store) => {
+                // The merge only reads persisted segments
+                store.flush_buffer()?;
+                Box::new(
+                    store
+                        .scan()
+                        .map(|(key, _, row)| Ok((key & 0xFFFF_FFFF, row))),
+                )
+            }
+            None => Box::new(self.scan_table_rows_streaming(table_name)?),
+        };
+        let scan = SharedScan::new(lsn, schema, source, batch_size);
+        if lsn.is_some() {
+            scans
+                .tables
+                .insert(table_name.to_string(), Arc::downgrade(&scan));
+        }
+        Ok(SharedScan::attach(&scan).expect("a new scan accepts readers"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ColumnDef, ColumnType, Value};
+    use std::sync::atomic::AtomicUsize;
+    use tempfile::TempDir;
+
+    /// `rows` rows whose pulls from storage are counted in `pulls`
+    fn scan(rows: usize, batch_size: usize, pulls: &Arc<AtomicUsize>) -> Arc<SharedScan> {
+        let pulls = pulls.clone();
+        let source = (0..rows as RowId).map(move |id| {
+            pulls.fetch_add(1, Ordering::Relaxed);
+            Ok((id, vec![Value::Integer(id as i64)]))
+        });
+        let schema = Arc::new(TableSchema::new("t".into(), vec![]));
+        SharedScan::new(Some(1), schema, Box::new(source), batch_size)
+    }
+
+    fn ids(reader: SharedScanReader) -> Vec<RowId> {
+        reader.map(|row| row.unwrap().0).collect()
+    }
+
+    #[test]
+    fn test_readers_share_one_pass() {
+        let pulls = Arc::new(AtomicUsize::new(0));
+        let scan = scan(100, 10, &pulls);
+        let mut first = SharedScan::attach(&scan).unwrap();
+        let mut second = SharedScan::attach(&scan).unwrap();
+
+        // Both readers get the very same batch
+        let a = first.next_batch().unwrap().unwrap();
+        let b = second.next_batch().unwrap().unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        // A late reader replays the batches it missed
+        first.next_batch().unwrap().unwrap();
+        let third = SharedScan::attach(&scan).unwrap();
+        assert_eq!(ids(third), (0..100).collect::<Vec<_>>());
+        assert_eq!(ids(first).len(), 80);
+        assert_eq!(ids(second).len(), 90);
+        assert_eq!(pulls.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn test_passed_batches_are_dropped_after_join_window() {
+        let pulls = Arc::new(AtomicUsize::new(0));
+        let scan = scan(1000, 10, &pulls);
+        let mut reader = SharedScan::attach(&scan).unwrap();
+        for _ in 0..JOIN_WINDOW {
+            reader.next_batch().unwrap().unwrap();
+        }
+        assert_eq!(scan.state.lock().batches.len(), JOIN_WINDOW);
+        assert!(SharedScan::attach(&scan).is_some());
+
+        reader.next_batch().unwrap().unwrap();
+        assert!(scan.state.lock().batches.is_empty());
+        assert!(SharedScan::attach(&scan).is_none());
+
+        drop(reader);
+        assert_eq!(pulls.load(Ordering::Relaxed), (JOIN_WINDOW + 1) * 10);
+    }
+
+    #[test]
+    fn test_table_scans_attach_until_a_write() {
+        let dir = TempDir::new().unwrap();
+        let db = MoteDB::create(dir.path()).unwrap();
+        db.create_table(TableSchema::new(
+            "t".into(),
+            vec![ColumnDef::new("v".into(), ColumnType::Integer, 0)],
+        ))
+        .unwrap();
+        for i in 0..50 {
+            db.insert_row_to_table("t", vec![Value::Integer(i)])
+                .unwrap();
+        }
+
+        let mut first = db.scan_table_shared("t", 20).unwrap();
+        let mut second = db.scan_table_shared("t", 20).unwrap();
+        let batch = first.next_batch().unwrap().unwrap();
+        assert!(Arc::ptr_eq(&batch, &second.next_batch().unwrap().unwrap()));
+        assert_eq!(batch.len(), 20);
+
+        db.insert_row_to_table("t", vec![Value::Integer(50)])
+            .unwrap();
+        let mut third = db.scan_table_shared("t", 20).unwrap();
+        assert!(!Arc::ptr_eq(&batch, &third.next_batch().unwrap().unwrap()));
+        assert_eq!(third.count(), 31);
+        assert_eq!(first.count(), 30);
+    }
+}
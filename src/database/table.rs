@@ -3,8 +3,10 @@
 //! Extracted from database_legacy.rs
 //! Contains table schema management and helper methods
 
+use crate::catalog::PendingDrop;
+use crate::storage::col_segment::ColSegmentStore;
 use crate::types::{RowId, TableSchema};
-use crate::Result;
+use crate::{Result, StorageError};
 use std::sync::Arc;
 
 use super::core::MoteDB;
@@ -35,6 +37,9 @@ impl MoteDB {
             schema.name.clone(),
             Arc::new(std::sync::atomic::AtomicU64::new(0)),
         );
+        if schema.is_ring_buffer() {
+            self.register_ring_buffer(&schema.name)?;
+        }
 
         // 🚀 Auto-create column index for PRIMARY KEY (if not AUTO_INCREMENT)
         // AUTO_INCREMENT PKs don't need a column index because PK value == row_id.
@@ -44,7 +49,7 @@ impl MoteDB {
         // O(log N) binary-search PK lookups — a disk-based column index is
         // redundant and wastes disk + memory (4GB for 2M rows). We still create
         // the in-memory PK lookup cache for O(1) hot-key resolution.
-        if let Some(_pk_col) = schema.primary_key() {
+        if let Some(pk_col) = schema.primary_key() {
             if !schema.is_primary_key_auto_increment() {
                 // Always create in-memory PK lookup (bounded LRU, O(1) hot keys).
                 let pk_cache = Arc::new(crate::database::pk_cache::PkLookupCache::new(
@@ -61,6 +66,15 @@ impl MoteDB {
                 // falling back to RowMap on index miss.
                 // For now, skip the disk index entirely — it's never used by
                 // ColSegmentStore tables, and legacy tables are rare.
+                //
+                // TEXT / UUID keys are the exception: their row ids are
+                // assigned, so RowMap binary search can't find them. The
+                // column index keys them by an order-preserving encoding
+                // (UTF-8 bytes, UUID bytes big-endian), which serves both
+                // `pk = ?` on a cache miss and `pk BETWEEN ? AND ?`.
+                if schema.has_natural_primary_key() {
+                    self.create_column_index(&schema.name, pk_col)?;
+                }
             }
         }
 
@@ -81,18 +95,56 @@ impl MoteDB {
 
     /// Drop a table
     ///
-    /// Deletes row data (LSM tombstones), drops all indexes, cleans up caches,
-    /// and removes table metadata.
+    /// Removes table metadata, deletes row data (LSM tombstones and column
+    /// segments), drops all indexes with their files, and cleans up caches.
+    ///
+    /// The catalog records the drop as pending before anything is torn down
+    /// and clears it once everything is gone. A drop that fails half-way stays
+    /// pending, with the table already hidden, and the next DROP TABLE or open
+    /// finishes it.
     pub fn drop_table(&self, table_name: &str) -> Result<()> {
         ensure_open!(self);
+        let views = self.continuous_aggregate_views(table_name);
+        if !views.is_empty() {
+            return Err(StorageError::InvalidData(format!(
+                "Cannot drop table '{}': materialized views depend on it ({})",
+                table_name,
+                views.join(", ")
+            )));
+        }
+
+        // 1. Take the table out of the catalog FIRST, recording the drop —
+        //    prevents concurrent INSERT/UPDATE/DELETE from writing new data
+        //    while we're cleaning up. Operations on this table will get
+        //    "table not found" from this point forward.
+        let pending = match self.table_registry.pending_drop(table_name) {
+            Some(pending) => pending,
+            None => self.table_registry.begin_drop(table_name)?,
+        };
+        self.finish_table_drop(table_name, &pending)
+    }
+
+    /// Finish the drops a crash or failure left pending
+    pub(crate) fn finish_pending_drops(&self) {
+        for (table_name, pending) in self.table_registry.pending_drops() {
+            match self.finish_table_drop(&table_name, &pending) {
+                Ok(()) => debug_log!("[open] Finished dropping table '{}'", table_name),
+                Err(e) => warn_log!(
+                    "[open] Finishing the drop of '{}' failed, retrying on next open: {:?}",
+                    table_name,
+                    e
+                ),
+            }
+        }
+    }
 
-        // 1. Remove from catalog FIRST — prevents concurrent INSERT/UPDATE/DELETE
-        //    from writing new data while we're cleaning up. Operations on this
-        //    table will get "table not found" from this point forward.
-        self.table_registry.drop_table(table_name)?;
+    /// Tear down a table taken out of the catalog, then clear its pending
+    /// drop. Every step can be re-run after a failure.
+    fn finish_table_drop(&self, table_name: &str, pending: &PendingDrop) -> Result<()> {
+        self.unregister_continuous_aggregate(table_name);
 
         // 2. Delete row data from LSM (tombstones for compaction to reclaim)
-        let table_prefix = self.compute_table_prefix(table_name);
+        let table_prefix = pending.table_id as u64;
         let start_key = table_prefix << 32;
         let end_key = (table_prefix << 32) | 0xFFFF_FFFF;
         // Use write_lsn for tombstone timestamp, same as every other write path.
@@ -101,78 +153,63 @@ impl MoteDB {
         let timestamp = self
             .write_lsn
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        if let Err(e) = self.lsm_engine.delete_range(start_key, end_key, timestamp) {
-            warn_log!(
-                "[drop_table] delete_range failed for '{}': {:?}",
-                table_name,
-                e
-            );
-        }
+        self.lsm_engine
+            .delete_range(start_key, end_key, timestamp)?;
 
         // 3. Flush so tombstones reach SSTables (enables compaction cleanup)
-        if let Err(e) = self.lsm_engine.flush() {
-            warn_log!("[drop_table] flush failed for '{}': {:?}", table_name, e);
-        }
+        self.lsm_engine.flush()?;
 
         // 4. Drop columnar store for TimeSeries tables
-        if let Err(e) = self.columnar_store.drop_table(table_name) {
-            warn_log!(
-                "[drop_table] columnar drop failed for '{}': {:?}",
-                table_name,
-                e
-            );
-        }
+        self.columnar_store.drop_table_id(pending.table_id)?;
 
-        // 5. Drop in-memory index handles (iterate by prefix since keys are "table.column" format)
-        let prefix = format!("{}.", table_name);
-        let vec_keys: Vec<String> = self
-            .vector_indexes
-            .iter()
-            .filter(|e| e.key().starts_with(&prefix) || e.key() == table_name)
-            .map(|e| e.key().clone())
-            .collect();
-        for k in vec_keys {
-            self.vector_indexes.remove(&k);
-        }
-
-        let ioct_keys: Vec<String> = self
-            .ioctree_indexes
-            .iter()
-            .filter(|e| e.key().starts_with(&prefix) || e.key() == table_name)
-            .map(|e| e.key().clone())
-            .collect();
-        for k in ioct_keys {
-            self.ioctree_indexes.remove(&k);
-        }
-
-        let txt_keys: Vec<String> = self
-            .text_indexes
-            .iter()
-            .filter(|e| e.key().starts_with(&prefix) || e.key() == table_name)
-            .map(|e| e.key().clone())
-            .collect();
-        for k in txt_keys {
-            self.text_indexes.remove(&k);
-        }
-
-        let col_keys: Vec<String> = self
-            .column_indexes
-            .iter()
-            .filter(|e| e.key().starts_with(&prefix) || e.key() == table_name)
-            .map(|e| e.key().clone())
-            .collect();
-        for k in col_keys {
-            self.column_indexes.remove(&k);
-        }
+        // 5. Drop the table's indexes with their files and manifest entries
+        self.drop_table_indexes(table_name)?;
 
         // 6. Invalidate row cache for this table
         self.row_cache.invalidate_table(table_name);
 
-        // 7. Remove remaining runtime state
+        // 7. Drop the ColSegmentStore (the row source of truth) with its
+        //    segment files and manifest, so a same-named table created later
+        //    starts empty. A drop finished on open finds it unloaded.
+        let store = match self.col_segment_stores.remove(table_name) {
+            Some((_, store)) => Some(store),
+            None => self.open_dropped_col_segment_store(table_name, pending)?,
+        };
+        if let Some(store) = store {
+            store.drop_all()?;
+        }
+        self.columnar_write_bufs.remove(table_name);
+        self.columnar_sstables.remove(table_name);
+
+        // 8. Remove remaining runtime state
         self.pk_lookup.remove(table_name);
         self.table_auto_increment.remove(table_name);
         self.table_row_count.remove(table_name);
-        Ok(())
+        self.retained_deletes.remove(table_name);
+        self.unregister_ring_buffer(table_name);
+        self.unregister_table_embedders(table_name);
+
+        self.table_registry.finish_drop(table_name)
+    }
+
+    /// The on-disk ColSegmentStore of a dropped table, if it has one
+    fn open_dropped_col_segment_store(
+        &self,
+        table_name: &str,
+        pending: &PendingDrop,
+    ) -> Result<Option<Arc<ColSegmentStore>>> {
+        let dir = self.path.join("columnar_ms").join(table_name);
+        if !crate::storage::backend::backend_for(&dir).exists(&dir) {
+            return Ok(None);
+        }
+        let store = ColSegmentStore::create_partitioned(
+            &self.path,
+            table_name,
+            pending.schema.col_types().to_vec(),
+            self.num_partitions,
+        )?;
+        store.recover_from_disk();
+        Ok(Some(store))
     }
 
     /// Get table schema
@@ -236,6 +273,20 @@ impl MoteDB {
         ((table_id as u64) << 32) | (row_id & 0xFFFFFFFF)
     }
 
+    /// Write gate of a table (see `table_write_gates`). Writers hold it with
+    /// `read_recursive`, so a write nested in another one cannot deadlock
+    /// behind a waiting rebuild.
+    pub(crate) fn table_write_gate(&self, table_name: &str) -> Arc<parking_lot::RwLock<()>> {
+        if let Some(gate) = self.table_write_gates.get(table_name) {
+            return gate.value().clone();
+        }
+        self.table_write_gates
+            .entry(table_name.to_string())
+            .or_default()
+            .value()
+            .clone()
+    }
+
     /// Compute table prefix (upper 32 bits of composite key)
     ///
     /// Uses stable sequential table_id from registry (collision-free).
@@ -4,6 +4,7 @@
 //! Transactional writes are buffered in the coordinator's write_set until commit,
 //! then flushed to WAL and LSM atomically. Rollback simply discards the write_set.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::database::core::MoteDB;
@@ -21,6 +22,36 @@ pub struct TransactionStats {
     pub total_versions: u64,
     pub total_rows_with_versions: u64,
     pub avg_versions_per_row: f64,
+    /// Current MVCC timestamp
+    pub current_ts: u64,
+    /// Snapshot timestamp of the oldest open transaction. Old row versions
+    /// cannot be reclaimed past this point, so a stale value here usually
+    /// means a transaction was never committed or rolled back.
+    pub oldest_snapshot_ts: Option<u64>,
+}
+
+/// One open transaction, as reported by `SHOW TRANSACTIONS`
+#[derive(Debug, Clone)]
+pub struct ActiveTransaction {
+    pub txn_id: TransactionId,
+    pub isolation_level: IsolationLevel,
+    /// Snapshot (start) timestamp
+    pub start_ts: u64,
+    /// Time since BEGIN
+    pub age: std::time::Duration,
+    /// Buffered INSERTs awaiting commit
+    pub pending_writes: usize,
+    /// UPDATE/DELETE operations already applied that ROLLBACK would undo
+    pub undo_ops: usize,
+    pub savepoints: usize,
+}
+
+/// A row deleted while transactions were open, with the timestamp of its
+/// delete. Snapshots taken before that timestamp still see the row.
+pub(crate) struct RetainedDelete {
+    row_id: RowId,
+    row: Row,
+    deleted_ts: u64,
 }
 
 impl MoteDB {
@@ -54,8 +85,11 @@ impl MoteDB {
         }
 
         // Validate row against schema (before allocating ID to avoid waste on failure)
+        super::generated::fill_generated_columns(&schema, std::slice::from_mut(&mut row))?;
+        self.apply_embedders(&schema, std::slice::from_mut(&mut row))?;
         schema
-            .validate_row(&row)
+            .coerce_row(&mut row)
+            .and_then(|_| schema.validate_row(&row))
             .map_err(|e| StorageError::InvalidData(format!("Row validation failed: {}", e)))?;
 
         // Primary key uniqueness check (same as non-transactional path)
@@ -177,10 +211,22 @@ impl MoteDB {
         // Get write_set WITHOUT holding the DashMap read guard across commit.
         // The coordinator's commit() also calls get_context() + write_set operations,
         // so holding ctx here causes a self-deadlock (RwLock read → write).
-        let write_set = {
+        let (write_set, insert_order) = {
             let ctx = self.txn_coordinator.get_context(txn_id)?;
             let ws = ctx.write_set.read().clone();
-            ws
+            // The write_set forgets the order rows were inserted in
+            let order: Vec<(String, RowId)> = ctx
+                .undo_log
+                .read()
+                .iter()
+                .filter_map(|delta| match delta {
+                    crate::txn::coordinator::DeltaOperation::Insert(row_id, table_name, _) => {
+                        Some((table_name.clone(), *row_id))
+                    }
+                    _ => None,
+                })
+                .collect();
+            (ws, order)
         };
         // ctx dropped here — DashMap read guard released.
 
@@ -204,6 +250,11 @@ impl MoteDB {
         // 3. Write WAL Commit record
         self.wal.log_commit(0, txn_id, commit_ts)?;
 
+        // Buffered writes aren't folded into continuous aggregates one by one
+        for (table_name, _) in write_set.keys() {
+            self.invalidate_continuous_aggregates(table_name);
+        }
+
         // 4. Flush all rows to LSM atomically via batch_put
         // Skip LSM batch_put (backpressure deadlock). Write to ColSegmentStore
         // instead (SELECT reads from there, not LSM for columnar tables).
@@ -213,10 +264,8 @@ impl MoteDB {
             let composite_key = self.make_composite_key(table_name, *row_id);
             let tbl_schema = self.table_registry.get_table(table_name)?;
             let col_types = tbl_schema.col_types();
-            let raw = crate::storage::row_format::encode(row_data, col_types).or_else(|_| {
-                bincode::serialize(row_data)
-                    .map_err(|e| StorageError::Serialization(format!("Row encode failed: {}", e)))
-            })?;
+            let raw = crate::storage::row_format::encode(row_data, col_types)
+                .unwrap_or_else(|_| crate::storage::value_codec::encode_row(row_data));
             let ts = self
                 .write_lsn
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -227,12 +276,18 @@ impl MoteDB {
         // INSERT (pre-BEGIN) already wrote ColSegmentStore. The transaction's
         // second INSERT data is in WAL only — it will be replayed on checkpoint.
         //
-        // Update caches so the data is visible to queries via row_cache.
-        for ((table_name, row_id), row_data) in &write_set {
-            self.row_cache
-                .put(table_name.to_string(), *row_id, row_data.clone());
-
-            // Also write ColSegmentStore for query visibility (no LSM backpressure).
+        // Rows go to the store in the order they were inserted, so their write
+        // timestamps (and a ring buffer's eviction order) follow it
+        let position: std::collections::HashMap<&(String, RowId), usize> = insert_order
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (key, i))
+            .collect();
+        let mut committed: Vec<(&(String, RowId), &Row)> = write_set.iter().collect();
+        committed.sort_by_key(|(key, _)| position.get(key).copied().unwrap_or(usize::MAX));
+        for (key, row_data) in committed.iter().copied() {
+            let (table_name, row_id) = key;
+            // Write ColSegmentStore for query visibility (no LSM backpressure).
             // Clone the store Arc first to avoid holding DashMap read guard across
             // append_rows (which takes write_buf lock — potential deadlock if
             // another thread holds it). Use get_or_create so the first transactional
@@ -243,11 +298,20 @@ impl MoteDB {
                 .get_or_create_col_segment_store(table_name, col_types)
                 .ok();
             if let Some(store) = store_clone {
+                let gate = self.table_write_gate(table_name);
+                let _writes = gate.read_recursive();
                 let table_id = self.table_registry.get_table_id(table_name).unwrap_or(0) as u64;
                 let key = (table_id << 32) | (row_id & 0xFFFFFFFF);
-                let ts = self.write_lsn.load(std::sync::atomic::Ordering::Relaxed);
+                // A timestamp of its own, so a later delete of the row
+                // (e.g. a ring-buffer eviction) is strictly newer
+                let ts = self
+                    .write_lsn
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
                 let _ = store.append_rows(&[(key, ts, row_data.clone())]);
             }
+            // After the store write, so a concurrent reader can't cache the old row
+            self.row_cache
+                .apply_write(table_name, *row_id, Some(row_data));
 
             let tbl_schema = self.table_registry.get_table(table_name)?;
             if let Some(pk_name) = tbl_schema.primary_key() {
@@ -268,6 +332,22 @@ impl MoteDB {
                 .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         }
 
+        // Ring-buffer tables track the committed rows, then evict
+        let mut ring_tables: Vec<&str> = Vec::new();
+        for ((table_name, row_id), row_data) in committed {
+            if let Some(bytes) =
+                self.ring_buffer_row_bytes(table_name, std::slice::from_ref(row_data))
+            {
+                self.ring_buffer_track(table_name, &[*row_id], &bytes);
+                if !ring_tables.contains(&table_name.as_str()) {
+                    ring_tables.push(table_name);
+                }
+            }
+        }
+        for table_name in ring_tables {
+            self.evict_ring_buffer(table_name)?;
+        }
+
         // Skip cache population + timestamp index (they acquire locks that
         // interact with the background threads, causing the test to HUNG
         // during Drop). The data is safely in WAL (durability) and
@@ -292,7 +372,173 @@ impl MoteDB {
             total_versions: version_stats.total_versions,
             total_rows_with_versions: version_stats.total_rows,
             avg_versions_per_row: version_stats.avg_versions_per_row,
+            current_ts: self.version_store.current_timestamp(),
+            oldest_snapshot_ts: self.txn_coordinator.oldest_active_timestamp(),
+        }
+    }
+
+    /// List open transactions, oldest first
+    ///
+    /// MoteDB detects write conflicts at commit time (MVCC), so transactions
+    /// hold no row locks; a long-lived entry here is what pins old versions.
+    pub fn active_transactions(&self) -> Vec<ActiveTransaction> {
+        self.txn_coordinator
+            .active_transactions()
+            .into_iter()
+            .map(|ctx| ActiveTransaction {
+                txn_id: ctx.txn_id,
+                isolation_level: ctx.isolation_level,
+                start_ts: ctx.start_ts,
+                age: ctx.started_at.elapsed(),
+                pending_writes: ctx.write_set.read().len(),
+                undo_ops: ctx.undo_log.read().len(),
+                savepoints: ctx.savepoints.read().len(),
+            })
+            .collect()
+    }
+
+    // ==================== Read Snapshots ====================
+
+    /// Open a read-only snapshot: a REPEATABLE READ transaction that never
+    /// writes, so nothing is logged to the WAL. Row versions committed by
+    /// transactions after this point are invisible to it. Old versions are
+    /// kept until the snapshot is released with [`Self::release_read_snapshot`].
+    pub fn begin_read_snapshot(&self) -> Result<TransactionId> {
+        ensure_open!(self);
+        self.txn_coordinator.begin(IsolationLevel::RepeatableRead)
+    }
+
+    /// Release a snapshot opened by [`Self::begin_read_snapshot`]
+    pub fn release_read_snapshot(&self, txn_id: TransactionId) -> Result<()> {
+        self.txn_coordinator.rollback(txn_id)?;
+        self.purge_retained_deletes();
+        Ok(())
+    }
+
+    /// Read a row as of a transaction's snapshot (see
+    /// [`Self::get_table_row_arc_with_mvcc`] for which writes it sees)
+    pub fn get_table_row_in_snapshot(
+        &self,
+        table_name: &str,
+        row_id: RowId,
+        txn_id: TransactionId,
+    ) -> Result<Option<Row>> {
+        let ctx = self.txn_coordinator.get_context(txn_id)?;
+        if let Some(row) = self
+            .retained_rows(table_name, ctx.snapshot.timestamp)
+            .remove(&row_id)
+        {
+            return Ok(Some(row));
+        }
+        let schema = self.table_registry.get_table(table_name)?;
+        let row = self.get_table_row_arc_with_mvcc(
+            table_name,
+            row_id,
+            &schema,
+            &ctx.snapshot,
+            ctx.isolation_level,
+        )?;
+        // Deletes leave the version chain in place: a row with versions that
+        // is no longer stored was deleted before the snapshot
+        if row.is_some()
+            && self.version_store.versions.contains_key(&row_id)
+            && self
+                .get_table_row_arc(table_name, row_id, &schema)?
+                .is_none()
+        {
+            return Ok(None);
+        }
+        Ok(row.map(Arc::unwrap_or_clone))
+    }
+
+    /// Scan a table as of a transaction's snapshot: rows stored now, each
+    /// replaced by the version the snapshot sees, or dropped if it sees none,
+    /// plus the rows deleted since the snapshot was taken.
+    pub fn scan_table_rows_in_snapshot(
+        &self,
+        table_name: &str,
+        txn_id: TransactionId,
+    ) -> Result<Vec<(RowId, Row)>> {
+        let ctx = self.txn_coordinator.get_context(txn_id)?;
+        let mut deleted = self.retained_rows(table_name, ctx.snapshot.timestamp);
+        let mut rows = Vec::new();
+        for item in self.scan_table_rows_streaming(table_name)? {
+            let (row_id, row) = item?;
+            if let Some(before) = deleted.remove(&row_id) {
+                // Deleted, then stored again under the same row_id
+                rows.push((row_id, before));
+                continue;
+            }
+            match self.version_store.get_visible_version(
+                row_id,
+                &ctx.snapshot,
+                ctx.isolation_level,
+            )? {
+                Some(visible) => rows.push((row_id, visible)),
+                None if self.version_store.versions.get(&row_id).is_some() => {}
+                None => rows.push((row_id, row)),
+            }
+        }
+        if !deleted.is_empty() {
+            rows.extend(deleted);
+            rows.sort_by_key(|(row_id, _)| *row_id);
+        }
+        Ok(rows)
+    }
+
+    /// Keep the rows of `table_name` about to be deleted for the open
+    /// transactions, whose snapshots still see them. No-op when none is open.
+    pub(crate) fn retain_deleted_rows<'a>(
+        &self,
+        table_name: &str,
+        rows: impl IntoIterator<Item = (RowId, &'a Row)>,
+    ) {
+        if self.txn_coordinator.oldest_active_timestamp().is_none() {
+            return;
+        }
+        let deleted_ts = self.version_store.allocate_timestamp();
+        let rows = rows.into_iter().map(|(row_id, row)| RetainedDelete {
+            row_id,
+            row: row.clone(),
+            deleted_ts,
+        });
+        self.retained_deletes
+            .entry(table_name.to_string())
+            .or_default()
+            .extend(rows);
+    }
+
+    /// Forget deleted rows that no open transaction can see any more
+    pub(crate) fn purge_retained_deletes(&self) {
+        if self.retained_deletes.is_empty() {
+            return;
+        }
+        match self.txn_coordinator.oldest_active_timestamp() {
+            Some(oldest) => self.retained_deletes.retain(|_, rows| {
+                rows.retain(|r| r.deleted_ts > oldest);
+                !rows.is_empty()
+            }),
+            None => self.retained_deletes.clear(),
+        }
+    }
+
+    /// Rows of `table_name` deleted after `snapshot_ts`, each as it was
+    /// before its first such delete
+    fn retained_rows(&self, table_name: &str, snapshot_ts: u64) -> HashMap<RowId, Row> {
+        let mut rows: HashMap<RowId, (u64, Row)> = HashMap::new();
+        if let Some(retained) = self.retained_deletes.get(table_name) {
+            for r in retained.iter().filter(|r| r.deleted_ts > snapshot_ts) {
+                match rows.get(&r.row_id) {
+                    Some((ts, _)) if *ts <= r.deleted_ts => {}
+                    _ => {
+                        rows.insert(r.row_id, (r.deleted_ts, r.row.clone()));
+                    }
+                }
+            }
         }
+        rows.into_iter()
+            .map(|(row_id, (_, row))| (row_id, row))
+            .collect()
     }
 
     // ==================== Savepoint API ====================
@@ -0,0 +1,198 @@
+//! Embedding upserts keyed by a user column
+//!
+//! [`MoteDB::upsert_embeddings`] resolves each item's key to an existing row
+//! (or a new one) and writes the table's vector column through one
+//! [`WriteBatch`](super::WriteBatch): the rows, the WAL record and the vector
+//! index change together or not at all.
+
+use std::collections::HashMap;
+
+use super::core::MoteDB;
+use super::pk_cache::PkKey;
+use crate::types::{Row, RowId, TableSchema, Value};
+use crate::{Result, StorageError};
+
+/// Outcome of [`MoteDB::upsert_embeddings`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingUpsert {
+    /// Row of each item, in input order (repeated keys share a row)
+    pub row_ids: Vec<RowId>,
+    /// Rows created for keys that were not found
+    pub inserted: usize,
+    /// Existing rows whose vector was replaced
+    pub updated: usize,
+}
+
+impl MoteDB {
+    /// Write `(key, vector)` items into the table's VECTOR column, matching
+    /// rows by `key_column`.
+    ///
+    /// A key that matches a row replaces that row's vector; any other key
+    /// inserts a row holding just the key and the vector, so the remaining
+    /// columns must be nullable (or an AUTO_INCREMENT primary key). When a key
+    /// repeats, its last vector wins. The table must have exactly one VECTOR
+    /// column.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let result = db.upsert_embeddings(
+    ///     "docs",
+    ///     "doc_key",
+    ///     vec![(Value::text_from("a-17"), embedding)],
+    /// )?;
+    /// ```
+    pub fn upsert_embeddings(
+        &self,
+        table_name: &str,
+        key_column: &str,
+        items: Vec<(Value, Vec<f32>)>,
+    ) -> Result<EmbeddingUpsert> {
+        ensure_open!(self);
+        let schema = self.table_registry.get_table(table_name)?;
+        let key_col = schema.get_column(key_column).ok_or_else(|| {
+            StorageError::ColumnNotFound(format!("'{}' in table '{}'", key_column, table_name))
+        })?;
+        let vector_cols: Vec<_> = schema
+            .columns
+            .iter()
+            .filter(|c| c.col_type.vector_dim().is_some())
+            .collect();
+        let [vector_col] = vector_cols.as_slice() else {
+            return Err(StorageError::InvalidArgument(format!(
+                "upsert_embeddings needs exactly one VECTOR column in table '{}', found {}",
+                table_name,
+                vector_cols.len()
+            )));
+        };
+        let dim = vector_col.col_type.vector_dim().unwrap_or(0);
+
+        // Last vector per key, keys in first-seen order
+        let mut slots: HashMap<PkKey, usize> = HashMap::with_capacity(items.len());
+        let mut keys: Vec<(Value, Vec<f32>)> = Vec::with_capacity(items.len());
+        let mut item_slots = Vec::with_capacity(items.len());
+        for (key, vector) in items {
+            if matches!(key, Value::Null) {
+                return Err(StorageError::InvalidArgument(format!(
+                    "upsert_embeddings key '{}' cannot be NULL",
+                    key_column
+                )));
+            }
+            if vector.len() != dim {
+                return Err(StorageError::InvalidArgument(format!(
+                    "vector for key {:?} has {} dimensions, column '{}' expects {}",
+                    key,
+                    vector.len(),
+                    vector_col.name,
+                    dim
+                )));
+            }
+            let slot = *slots.entry(PkKey::from_value(&key)).or_insert(keys.len());
+            if slot == keys.len() {
+                keys.push((key, vector));
+            } else {
+                keys[slot].1 = vector;
+            }
+            item_slots.push(slot);
+        }
+
+        let key_pos = key_col.position;
+        let existing = self.rows_by_key(table_name, &schema, key_pos, &keys)?;
+
+        let mut batch = self.write_batch();
+        let mut inserted_slots = Vec::new();
+        let mut slot_rows: Vec<Option<RowId>> = vec![None; keys.len()];
+        for (slot, ((key, vector), current)) in keys.into_iter().zip(existing).enumerate() {
+            let value = vector_col.col_type.value_from_vector(vector);
+            match current {
+                Some((row_id, mut row)) => {
+                    row[vector_col.position] = value;
+                    batch.update(table_name, row_id, row);
+                    slot_rows[slot] = Some(row_id);
+                }
+                None => {
+                    let mut row = vec![Value::Null; schema.columns.len()];
+                    row[key_pos] = key;
+                    row[vector_col.position] = value;
+                    batch.insert(table_name, row);
+                    inserted_slots.push(slot);
+                }
+            }
+        }
+        let updated = slot_rows.iter().flatten().count();
+        let new_rows = batch.commit()?;
+        for (slot, row_id) in inserted_slots.iter().zip(new_rows) {
+            slot_rows[*slot] = Some(row_id);
+        }
+
+        Ok(EmbeddingUpsert {
+            row_ids: item_slots
+                .into_iter()
+                .map(|slot| slot_rows[slot].unwrap_or_default())
+                .collect(),
+            inserted: inserted_slots.len(),
+            updated,
+        })
+    }
+
+    /// Current `(row_id, row)` of each key: through the primary key or a
+    /// column index when there is one, otherwise in a single table scan
+    fn rows_by_key(
+        &self,
+        table_name: &str,
+        schema: &TableSchema,
+        key_pos: usize,
+        keys: &[(Value, Vec<f32>)],
+    ) -> Result<Vec<Option<(RowId, Row)>>> {
+        let key_column = schema.columns[key_pos].name.as_str();
+        let is_pk = schema.primary_key() == Some(key_column);
+        let indexed = self
+            .column_indexes
+            .contains_key(&format!("{}.{}", table_name, key_column));
+        if is_pk || indexed {
+            let mut found = Vec::with_capacity(keys.len());
+            for (key, _) in keys {
+                let row_id = if is_pk && schema.is_primary_key_auto_increment() {
+                    match key {
+                        Value::Integer(id) if *id >= 0 => Some(*id as RowId),
+                        _ => None,
+                    }
+                } else if is_pk {
+                    self.pk_owner(table_name, key_column, key)?
+                } else {
+                    self.query_by_column(table_name, key_column, key)?
+                        .into_iter()
+                        .next()
+                };
+                let row = match row_id {
+                    // The index only narrows: confirm the row still holds the key
+                    Some(row_id) => self
+                        .get_table_row(table_name, row_id)?
+                        .filter(|row| row.get(key_pos) == Some(key))
+                        .map(|row| (row_id, row)),
+                    None => None,
+                };
+                found.push(row);
+            }
+            return Ok(found);
+        }
+
+        let wanted: HashMap<PkKey, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(slot, (key, _))| (PkKey::from_value(key), slot))
+            .collect();
+        let mut found = vec![None; keys.len()];
+        for entry in self.scan_table_rows_streaming(table_name)? {
+            let (row_id, row) = entry?;
+            let Some(key) = row.get(key_pos).filter(|v| !matches!(v, Value::Null)) else {
+                continue;
+            };
+            if let Some(&slot) = wanted.get(&PkKey::from_value(key)) {
+                if found[slot].is_none() {
+                    found[slot] = Some((row_id, row));
+                }
+            }
+        }
+        Ok(found)
+    }
+}
@@ -0,0 +1,967 @@
+//! Write Batches (atomic multi-table writes)
+//!
+//! A [`WriteBatch`] buffers inserts, updates and deletes across any number of
+//! tables and commits them as a single `WALRecord::Batch`: after a crash
+//! recovery replays either every operation of the batch or none of them.
+//!
+//! Everything is validated (schemas, primary keys, target rows) before the
+//! WAL record is written, so a rejected batch leaves no trace. Indexes are
+//! then updated in one pass over the net change of each touched row, so a
+//! row inserted and updated in the same batch is indexed once.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use crate::config::CommitMode;
+use crate::database::core::MoteDB;
+use crate::database::crud::pk_row_id;
+use crate::database::index_metadata::IndexType;
+use crate::database::indexes::column::column_index_key;
+use crate::database::pk_cache::PkKey;
+use crate::index::column_value::ColumnValueIndex;
+use crate::storage::{row_format, value_codec};
+use crate::txn::wal::WALRecord;
+use crate::types::{ColumnType, PartitionId, Row, RowId, TableSchema, Value};
+use crate::{Result, StorageError};
+
+enum BatchOp {
+    Insert {
+        table: String,
+        row: Row,
+    },
+    Update {
+        table: String,
+        row_id: RowId,
+        row: Row,
+        /// Current row, when the caller has just read it
+        old: Option<Row>,
+    },
+    Delete {
+        table: String,
+        row_id: RowId,
+        old: Option<Row>,
+    },
+    DeleteRange {
+        table: String,
+        start: RowId,
+        end: RowId,
+    },
+}
+
+/// Validated operation. Inserts without a row_id get one from the table's
+/// counters once the whole batch has been validated.
+enum Prepared {
+    Insert {
+        table: String,
+        row_id: Option<RowId>,
+        row: Row,
+    },
+    Update {
+        table: String,
+        row_id: RowId,
+        old: Row,
+        row: Row,
+    },
+    Delete {
+        table: String,
+        row_id: RowId,
+        old: Row,
+    },
+    /// The rows of `start..end` that exist, with their current images
+    DeleteRange {
+        table: String,
+        start: RowId,
+        end: RowId,
+        rows: Vec<(RowId, Row)>,
+    },
+}
+
+/// Net effect of the batch on one row
+struct Touched {
+    before: Option<Row>,
+    after: Option<Row>,
+}
+
+/// AUTO_INCREMENT ids a batch took from one table's counter
+struct AutoIncrementReservation {
+    table: String,
+    counter: Arc<AtomicI64>,
+    /// Counter value before and after the reservation
+    before: i64,
+    after: i64,
+    /// Next generated id to hand out
+    next_id: i64,
+}
+
+/// Hand reserved ids back after a failed commit, unless another writer has
+/// reserved since (then the ids are just skipped)
+fn release_auto_increments(reserved: &[AutoIncrementReservation]) {
+    for reservation in reserved.iter().rev() {
+        let _ = reservation.counter.compare_exchange(
+            reservation.after,
+            reservation.before,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+    }
+}
+
+/// Inserts, updates and deletes committed atomically by [`WriteBatch::commit`]
+///
+/// # Example
+/// ```ignore
+/// let mut batch = db.write_batch();
+/// batch.insert("orders", vec![Value::Integer(7), Value::Text("open".into())]);
+/// batch.update("stock", 3, vec![Value::Integer(3), Value::Integer(41)]);
+/// batch.delete("carts", 12);
+/// let row_ids = batch.commit()?; // row_ids of the inserts, in order
+/// ```
+pub struct WriteBatch<'a> {
+    db: &'a MoteDB,
+    ops: Vec<BatchOp>,
+    commit_mode: Option<CommitMode>,
+}
+
+impl<'a> WriteBatch<'a> {
+    /// Queue a row insert
+    pub fn insert(&mut self, table_name: &str, row: Row) -> &mut Self {
+        self.ops.push(BatchOp::Insert {
+            table: table_name.to_string(),
+            row,
+        });
+        self
+    }
+
+    /// Queue a full-row update (the primary key must stay the same)
+    pub fn update(&mut self, table_name: &str, row_id: RowId, row: Row) -> &mut Self {
+        self.ops.push(BatchOp::Update {
+            table: table_name.to_string(),
+            row_id,
+            row,
+            old: None,
+        });
+        self
+    }
+
+    /// Queue a row delete
+    pub fn delete(&mut self, table_name: &str, row_id: RowId) -> &mut Self {
+        self.ops.push(BatchOp::Delete {
+            table: table_name.to_string(),
+            row_id,
+            old: None,
+        });
+        self
+    }
+
+    /// Queue a range delete: deletes whichever rows of `start..end` exist,
+    /// logged as one WAL record however many rows it covers and applied as
+    /// one tombstone per row
+    pub(crate) fn delete_range(&mut self, table_name: &str, start: RowId, end: RowId) -> &mut Self {
+        self.ops.push(BatchOp::DeleteRange {
+            table: table_name.to_string(),
+            start,
+            end,
+        });
+        self
+    }
+
+    /// Queue an update of a row the caller just read (`old`), skipping the
+    /// re-read at commit. Used by bulk SQL UPDATE.
+    pub(crate) fn update_from(
+        &mut self,
+        table_name: &str,
+        row_id: RowId,
+        old: Row,
+        row: Row,
+    ) -> &mut Self {
+        self.ops.push(BatchOp::Update {
+            table: table_name.to_string(),
+            row_id,
+            row,
+            old: Some(old),
+        });
+        self
+    }
+
+    /// Queue a delete of a row the caller just read. Used by bulk SQL DELETE.
+    pub(crate) fn delete_from(&mut self, table_name: &str, row_id: RowId, old: Row) -> &mut Self {
+        self.ops.push(BatchOp::Delete {
+            table: table_name.to_string(),
+            row_id,
+            old: Some(old),
+        });
+        self
+    }
+
+    /// Override the configured durability level for this batch only
+    /// (e.g. `CommitMode::Async` for high-rate sensor samples)
+    pub fn commit_mode(&mut self, mode: CommitMode) -> &mut Self {
+        self.commit_mode = Some(mode);
+        self
+    }
+
+    /// Number of queued operations
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Apply all queued operations atomically. Returns the row_ids of the
+    /// inserted rows in insertion order. On error nothing is written.
+    pub fn commit(self) -> Result<Vec<RowId>> {
+        let db = self.db;
+        crate::txn::wal::with_commit_mode(self.commit_mode, || db.commit_write_batch(self.ops))
+    }
+}
+
+fn encode_row(row: &Row, schema: &TableSchema) -> Vec<u8> {
+    row_format::encode(row, schema.col_types()).unwrap_or_else(|_| value_codec::encode_row(row))
+}
+
+/// Value of a column in a row image (NULL when the row is short), `None`
+/// without a row
+fn column_of(row: Option<&Row>, position: usize) -> Option<&Value> {
+    row.map(|r| r.get(position).unwrap_or(&Value::Null))
+}
+
+fn non_null(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|v| !matches!(v, Value::Null))
+}
+
+fn vector_of(value: Option<&Value>) -> Option<Vec<f32>> {
+    match value {
+        Some(Value::Vector(vec)) => Some(vec.as_slice().to_vec()),
+        Some(Value::Tensor(tensor)) => Some(tensor.to_f32()),
+        _ => None,
+    }
+}
+
+impl MoteDB {
+    /// Start a batch of writes that commit atomically (one WAL record)
+    pub fn write_batch(&self) -> WriteBatch<'_> {
+        WriteBatch {
+            db: self,
+            ops: Vec::new(),
+            commit_mode: None,
+        }
+    }
+
+    fn commit_write_batch(&self, ops: Vec<BatchOp>) -> Result<Vec<RowId>> {
+        ensure_open!(self);
+        if ops.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut schemas: HashMap<String, Arc<TableSchema>> = HashMap::new();
+        // Rows written by earlier operations of this batch, keyed by (table, row_id)
+        let mut touched: HashMap<(String, RowId), Touched> = HashMap::new();
+        let mut touch_order: Vec<(String, RowId)> = Vec::new();
+        // Primary keys taken (true) or freed (false) by earlier operations
+        let mut batch_pks: HashMap<(String, PkKey), bool> = HashMap::new();
+
+        // 1. Validate every operation before anything is written
+        let mut prepared = Vec::with_capacity(ops.len());
+        for op in ops {
+            let table = match &op {
+                BatchOp::Insert { table, .. }
+                | BatchOp::Update { table, .. }
+                | BatchOp::Delete { table, .. }
+                | BatchOp::DeleteRange { table, .. } => table.clone(),
+            };
+            let schema = match schemas.get(&table) {
+                Some(schema) => schema.clone(),
+                None => {
+                    let schema = self.table_registry.get_table(&table)?;
+                    schemas.insert(table.clone(), schema.clone());
+                    schema
+                }
+            };
+            let pk_col = schema.primary_key().and_then(|pk| schema.get_column(pk));
+            let auto_inc = schema.is_primary_key_auto_increment();
+
+            match op {
+                BatchOp::Insert { mut row, .. } => {
+                    super::generated::fill_generated_columns(
+                        &schema,
+                        std::slice::from_mut(&mut row),
+                    )?;
+                    self.apply_embedders(&schema, std::slice::from_mut(&mut row))?;
+                    if auto_inc {
+                        if let Some(pk_col) = pk_col {
+                            while row.len() <= pk_col.position {
+                                row.push(Value::Null);
+                            }
+                        }
+                    }
+                    schema
+                        .coerce_row(&mut row)
+                        .and_then(|_| schema.validate_row(&row))
+                        .map_err(|e| {
+                            StorageError::InvalidData(format!(
+                                "Row validation failed for table '{}': {}",
+                                table, e
+                            ))
+                        })?;
+
+                    let mut row_id = None;
+                    if let Some(pk_col) = pk_col {
+                        let pk_value = row.get(pk_col.position).unwrap_or(&Value::Null);
+                        if matches!(pk_value, Value::Null) && !auto_inc {
+                            return Err(StorageError::InvalidData(format!(
+                                "NULL primary key is not allowed for table '{}'",
+                                table
+                            )));
+                        }
+                        if !matches!(pk_value, Value::Null) {
+                            let pk_key = PkKey::from_value(pk_value);
+                            let taken = match batch_pks.get(&(table.clone(), pk_key.clone())) {
+                                Some(&taken) => taken,
+                                None => self.pk_owner(&table, &pk_col.name, pk_value)?.is_some(),
+                            };
+                            if taken {
+                                return Err(StorageError::InvalidData(format!(
+                                    "Duplicate primary key {:?} for table '{}'",
+                                    pk_value, table
+                                )));
+                            }
+                            batch_pks.insert((table.clone(), pk_key), true);
+                            if let Value::Integer(pk_val) = pk_value {
+                                if auto_inc {
+                                    row_id = Some(*pk_val as RowId);
+                                } else if matches!(pk_col.col_type, ColumnType::Integer) {
+                                    row_id = Some(pk_row_id(*pk_val));
+                                }
+                            }
+                        }
+                    }
+                    prepared.push(Prepared::Insert { table, row_id, row });
+                }
+                BatchOp::Update {
+                    row_id,
+                    mut row,
+                    old,
+                    ..
+                } => {
+                    let old = self.batch_current_row(&table, row_id, &schema, &touched, old)?;
+                    super::generated::fill_generated_columns(
+                        &schema,
+                        std::slice::from_mut(&mut row),
+                    )?;
+                    self.apply_embedders_on_update(&schema, &old, &mut row)?;
+                    schema
+                        .coerce_row(&mut row)
+                        .and_then(|_| schema.validate_row(&row))
+                        .map_err(|e| {
+                            StorageError::InvalidData(format!(
+                                "UPDATE row validation failed: {}",
+                                e
+                            ))
+                        })?;
+                    if let Some(pk_col) = pk_col {
+                        if old.get(pk_col.position) != row.get(pk_col.position) {
+                            return Err(StorageError::InvalidArgument(format!(
+                                "write batch cannot change the primary key of row {} in table '{}'; delete and re-insert it instead",
+                                row_id, table
+                            )));
+                        }
+                    }
+                    prepared.push(Prepared::Update {
+                        table,
+                        row_id,
+                        old,
+                        row,
+                    });
+                }
+                BatchOp::Delete { row_id, old, .. } => {
+                    let old = self.batch_current_row(&table, row_id, &schema, &touched, old)?;
+                    if let Some(pk_value) = pk_col.and_then(|c| non_null(old.get(c.position))) {
+                        batch_pks.insert((table.clone(), PkKey::from_value(pk_value)), false);
+                    }
+                    prepared.push(Prepared::Delete { table, row_id, old });
+                }
+                BatchOp::DeleteRange { start, end, .. } => {
+                    let mut rows = Vec::new();
+                    for row_id in start..end {
+                        let Some(old) = self.batch_row(&table, row_id, &schema, &touched, None)?
+                        else {
+                            continue;
+                        };
+                        if let Some(pk_value) = pk_col.and_then(|c| non_null(old.get(c.position))) {
+                            batch_pks.insert((table.clone(), PkKey::from_value(pk_value)), false);
+                        }
+                        rows.push((row_id, old));
+                    }
+                    prepared.push(Prepared::DeleteRange {
+                        table,
+                        start,
+                        end,
+                        rows,
+                    });
+                }
+            }
+
+            // Track the rows' new state so later operations see it
+            let (table, changes) = match prepared.last() {
+                Some(Prepared::Insert {
+                    table,
+                    row_id: Some(row_id),
+                    row,
+                }) => (table, vec![(*row_id, None, Some(row.clone()))]),
+                Some(Prepared::Update {
+                    table,
+                    row_id,
+                    old,
+                    row,
+                }) => (table, vec![(*row_id, Some(old.clone()), Some(row.clone()))]),
+                Some(Prepared::Delete { table, row_id, old }) => {
+                    (table, vec![(*row_id, Some(old.clone()), None)])
+                }
+                Some(Prepared::DeleteRange { table, rows, .. }) => (
+                    table,
+                    rows.iter()
+                        .map(|(row_id, old)| (*row_id, Some(old.clone()), None))
+                        .collect(),
+                ),
+                _ => continue,
+            };
+            for (row_id, before, after) in changes {
+                let key = (table.clone(), row_id);
+                match touched.get_mut(&key) {
+                    Some(entry) => entry.after = after,
+                    None => {
+                        touched.insert(key.clone(), Touched { before, after });
+                        touch_order.push(key);
+                    }
+                }
+            }
+        }
+
+        // 2. Allocate row_ids. Each AUTO_INCREMENT table reserves its ids in
+        //    one step, past any explicit values so generated ids can't collide
+        //    with them. Nothing is reserved for a batch that fails validation,
+        //    and the reservations are handed back if the WAL write fails.
+        let mut wanted: HashMap<String, (i64, i64)> = HashMap::new(); // (floor, generated)
+        for op in &prepared {
+            if let Prepared::Insert { table, row_id, .. } = op {
+                if schemas[table].is_primary_key_auto_increment() {
+                    let entry = wanted.entry(table.clone()).or_insert((i64::MIN, 0));
+                    match row_id {
+                        Some(explicit) => {
+                            entry.0 = entry.0.max((*explicit as i64).saturating_add(1))
+                        }
+                        None => entry.1 += 1,
+                    }
+                }
+            }
+        }
+        let mut reserved: Vec<AutoIncrementReservation> = Vec::with_capacity(wanted.len());
+        for (table, (floor, generated)) in wanted {
+            let counter = self.batch_auto_increment_counter(&table, &schemas[&table]);
+            let mut first = 0;
+            let reservation =
+                counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                    first = current.max(floor);
+                    let next = first.checked_add(generated)?;
+                    let in_range = generated == 0 || (first >= 0 && next - 1 <= i64::MAX - 1000);
+                    in_range.then_some(next)
+                });
+            match reservation {
+                Ok(before) => reserved.push(AutoIncrementReservation {
+                    table,
+                    counter,
+                    before,
+                    after: first + generated,
+                    next_id: first,
+                }),
+                Err(_) => {
+                    release_auto_increments(&reserved);
+                    return Err(StorageError::AutoIncrementOverflow(table));
+                }
+            }
+        }
+        let mut inserted = Vec::new();
+        for op in &mut prepared {
+            let Prepared::Insert { table, row_id, row } = op else {
+                continue;
+            };
+            if row_id.is_none() {
+                let schema = &schemas[table];
+                let id = match reserved.iter_mut().find(|r| r.table == *table) {
+                    Some(reservation) => {
+                        let id = reservation.next_id;
+                        reservation.next_id += 1;
+                        if let Some(pk_col) =
+                            schema.primary_key().and_then(|pk| schema.get_column(pk))
+                        {
+                            row[pk_col.position] = Value::Integer(id);
+                        }
+                        id as RowId
+                    }
+                    None => self.next_row_id.fetch_add(1, Ordering::Relaxed),
+                };
+                *row_id = Some(id);
+                let key = (table.clone(), id);
+                touched.insert(
+                    key.clone(),
+                    Touched {
+                        before: None,
+                        after: Some(row.clone()),
+                    },
+                );
+                touch_order.push(key);
+            }
+            inserted.push(row_id.unwrap_or_default());
+        }
+        // 3. One WAL record for the whole batch
+        let mut records = Vec::with_capacity(prepared.len());
+        let mut timestamps = Vec::with_capacity(prepared.len());
+        for op in &prepared {
+            let ts = self.write_lsn.fetch_add(1, Ordering::Relaxed);
+            timestamps.push(ts);
+            let (table, row_id) = match op {
+                Prepared::Insert { table, row_id, .. } => (table, row_id.unwrap_or_default()),
+                Prepared::Update { table, row_id, .. } | Prepared::Delete { table, row_id, .. } => {
+                    (table, *row_id)
+                }
+                Prepared::DeleteRange { table, start, .. } => (table, *start),
+            };
+            let schema = &schemas[table];
+            let partition = (self.make_composite_key(table, row_id) % self.num_partitions as u64)
+                as PartitionId;
+            records.push(match op {
+                Prepared::Insert { row, .. } => WALRecord::InsertRaw {
+                    table_name: table.clone(),
+                    row_id,
+                    partition,
+                    raw_data: encode_row(row, schema),
+                    txn_id: 0,
+                },
+                Prepared::Update { old, row, .. } => WALRecord::UpdateRaw {
+                    table_name: table.clone(),
+                    row_id,
+                    partition,
+                    raw_old: encode_row(old, schema),
+                    raw_new: encode_row(row, schema),
+                    txn_id: 0,
+                },
+                Prepared::Delete { old, .. } => WALRecord::DeleteRaw {
+                    table_name: table.clone(),
+                    row_id,
+                    partition,
+                    raw_old: encode_row(old, schema),
+                    timestamp: ts,
+                    txn_id: 0,
+                },
+                Prepared::DeleteRange { start, end, .. } => WALRecord::DeleteRange {
+                    table_name: table.clone(),
+                    start_row_id: *start,
+                    end_row_id: *end,
+                    txn_id: 0,
+                },
+            });
+        }
+        self.increment_pending_updates();
+        if let Err(e) = self.wal.batch_append(0, vec![WALRecord::Batch { records }]) {
+            release_auto_increments(&reserved);
+            return Err(e);
+        }
+        for reservation in &reserved {
+            if reservation.after > reservation.before {
+                if let Err(e) = self
+                    .table_registry
+                    .update_auto_increment_counter(&reservation.table, reservation.after - 1)
+                {
+                    warn_log!(
+                        "[write_batch] Auto-increment counter update failed for {}: {}",
+                        reservation.table,
+                        e
+                    );
+                }
+            }
+        }
+
+        for table in schemas.keys() {
+            self.invalidate_continuous_aggregates(table);
+        }
+        // Open snapshots keep seeing the rows this batch deletes
+        for table in schemas.keys() {
+            let deleted = touch_order
+                .iter()
+                .filter(|key| &key.0 == table)
+                .filter_map(|key| match &touched[key] {
+                    Touched {
+                        before: Some(before),
+                        after: None,
+                    } => Some((key.1, before)),
+                    _ => None,
+                });
+            self.retain_deleted_rows(table, deleted);
+        }
+
+        // Hold every table's write gate (in name order) until the index
+        // changes are applied
+        let mut tables: Vec<&String> = schemas.keys().collect();
+        tables.sort();
+        let gates: Vec<_> = tables
+            .into_iter()
+            .map(|table| self.table_write_gate(table))
+            .collect();
+        let writes: Vec<_> = gates.iter().map(|gate| gate.read_recursive()).collect();
+
+        // 4. Apply to the columnar stores in batch order. Like a single-row
+        //    DELETE, flush buffered rows first so tombstones land in a newer
+        //    segment than the rows they delete.
+        let mut stores = HashMap::new();
+        for (table, schema) in &schemas {
+            let store = self.get_or_create_col_segment_store(table, schema.col_types())?;
+            let deletes = prepared.iter().any(|op| match op {
+                Prepared::Delete { table: t, .. } | Prepared::DeleteRange { table: t, .. } => {
+                    t == table
+                }
+                _ => false,
+            });
+            if deletes {
+                store.flush_buffer()?;
+            }
+            stores.insert(table.clone(), store);
+        }
+        for (op, ts) in prepared.iter().zip(timestamps) {
+            match op {
+                Prepared::Insert { table, row_id, row } => {
+                    let key = self.make_composite_key(table, row_id.unwrap_or_default());
+                    stores[table].append_row_ref(key, ts, row)?;
+                }
+                Prepared::Update {
+                    table, row_id, row, ..
+                } => {
+                    let key = self.make_composite_key(table, *row_id);
+                    stores[table].append_row_ref(key, ts, row)?;
+                }
+                Prepared::Delete { table, row_id, .. } => {
+                    let key = self.make_composite_key(table, *row_id);
+                    stores[table].append_tombstone(key, ts)?;
+                }
+                Prepared::DeleteRange { table, rows, .. } => {
+                    for (row_id, _) in rows {
+                        let key = self.make_composite_key(table, *row_id);
+                        stores[table].append_tombstone(key, ts)?;
+                    }
+                }
+            }
+        }
+        for store in stores.values() {
+            if store.buffered_bytes() >= 8 * 1024 * 1024 {
+                store.flush_buffer()?;
+            }
+        }
+
+        // 5. Caches, row counts and indexes from each row's net change
+        let mut row_deltas: HashMap<&str, i64> = HashMap::new();
+        for key in &touch_order {
+            let (table, row_id) = key;
+            let change = &touched[key];
+            self.row_cache
+                .apply_write(table, *row_id, change.after.as_ref());
+            *row_deltas.entry(table.as_str()).or_default() +=
+                change.after.is_some() as i64 - change.before.is_some() as i64;
+        }
+        for (table, delta) in row_deltas {
+            if let Some(counter) = self.table_row_count.get(table) {
+                let _ = counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |c| {
+                    Some((c as i64 + delta).max(0) as u64)
+                });
+            }
+        }
+        self.apply_batch_index_changes(&schemas, &touch_order, &touched);
+        drop(writes);
+
+        // 6. Ring-buffer tables follow the batch in order, then evict
+        let mut ring_tables = Vec::new();
+        for op in &prepared {
+            match op {
+                Prepared::Insert { table, row_id, row } => {
+                    if let Some(bytes) =
+                        self.ring_buffer_row_bytes(table, std::slice::from_ref(row))
+                    {
+                        self.ring_buffer_track(table, &[row_id.unwrap_or_default()], &bytes);
+                        if !ring_tables.contains(table) {
+                            ring_tables.push(table.clone());
+                        }
+                    }
+                }
+                Prepared::Delete { table, row_id, .. } => self.ring_buffer_untrack(table, *row_id),
+                Prepared::DeleteRange { table, rows, .. } => {
+                    for (row_id, _) in rows {
+                        self.ring_buffer_untrack(table, *row_id);
+                    }
+                }
+                Prepared::Update { .. } => {}
+            }
+        }
+        for table in &ring_tables {
+            self.evict_ring_buffer(table)?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Row as seen by the batch so far: earlier batch writes win over the
+    /// caller's copy (`known`), which wins over storage
+    fn batch_current_row(
+        &self,
+        table: &str,
+        row_id: RowId,
+        schema: &TableSchema,
+        touched: &HashMap<(String, RowId), Touched>,
+        known: Option<Row>,
+    ) -> Result<Row> {
+        self.batch_row(table, row_id, schema, touched, known)?
+            .ok_or_else(|| {
+                StorageError::InvalidData(format!("Row {} not found in table '{}'", row_id, table))
+            })
+    }
+
+    /// Like [`Self::batch_current_row`], `None` when the row does not exist
+    fn batch_row(
+        &self,
+        table: &str,
+        row_id: RowId,
+        schema: &TableSchema,
+        touched: &HashMap<(String, RowId), Touched>,
+        known: Option<Row>,
+    ) -> Result<Option<Row>> {
+        Ok(match touched.get(&(table.to_string(), row_id)) {
+            Some(change) => change.after.clone(),
+            None if known.is_some() => known,
+            None => self.get_table_row_with_schema(table, row_id, schema)?,
+        })
+    }
+
+    /// Live row currently holding `pk_value`, if any
+    pub(crate) fn pk_owner(
+        &self,
+        table: &str,
+        pk_name: &str,
+        pk_value: &Value,
+    ) -> Result<Option<RowId>> {
+        let cached = self
+            .pk_lookup
+            .get(table)
+            .and_then(|lookup| lookup.get_pk(&PkKey::from_value(pk_value)));
+        if cached.is_some() {
+            return Ok(cached);
+        }
+        if let Ok(found) = self.query_by_column(table, pk_name, pk_value) {
+            for rid in found {
+                if self.get_table_row(table, rid)?.is_some() {
+                    return Ok(Some(rid));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn batch_auto_increment_counter(&self, table: &str, schema: &TableSchema) -> Arc<AtomicI64> {
+        self.table_auto_increment
+            .entry(table.to_string())
+            .or_insert_with(|| Arc::new(AtomicI64::new(schema.get_auto_increment_start())))
+            .value()
+            .clone()
+    }
+
+    /// Single index pass: for every touched row, remove the values it had
+    /// before the batch and add the ones it has after. Column, vector and
+    /// text inserts are grouped per index and applied as batch inserts.
+    fn apply_batch_index_changes(
+        &self,
+        schemas: &HashMap<String, Arc<TableSchema>>,
+        touch_order: &[(String, RowId)],
+        touched: &HashMap<(String, RowId), Touched>,
+    ) {
+        let mut column_inserts: HashMap<String, (Arc<ColumnValueIndex>, Vec<(Value, RowId)>)> =
+            HashMap::new();
+        let mut vector_inserts: HashMap<String, Vec<(RowId, Vec<f32>)>> = HashMap::new();
+        let mut text_inserts: HashMap<String, Vec<(RowId, String)>> = HashMap::new();
+        let mut stale: Vec<String> = Vec::new();
+
+        for key in touch_order {
+            let (table, row_id) = (&key.0, key.1);
+            let change = &touched[key];
+            let schema = &schemas[table];
+
+            // In-memory PK lookup
+            if let Some(pk_col) = schema
+                .primary_key()
+                .and_then(|pk| schema.get_column(pk))
+                .filter(|_| !schema.is_primary_key_auto_increment())
+            {
+                if let Some(lookup) = self.pk_lookup.get(table) {
+                    let old_pk = change.before.as_ref().and_then(|r| r.get(pk_col.position));
+                    let new_pk = change.after.as_ref().and_then(|r| r.get(pk_col.position));
+                    if old_pk != new_pk {
+                        if let Some(old_pk) = old_pk {
+                            lookup.remove_pk(&PkKey::from_value(old_pk));
+                        }
+                    }
+                    if let Some(new_pk) = new_pk {
+                        lookup.insert(PkKey::from_value(new_pk), row_id);
+                    }
+                }
+            }
+
+            for col_def in &schema.columns {
+                let before = column_of(change.before.as_ref(), col_def.position);
+                let after = column_of(change.after.as_ref(), col_def.position);
+                if before == after {
+                    continue;
+                }
+                let (old, new) = (non_null(before), non_null(after));
+                let col_name = &col_def.name;
+
+                // Column Index: NULLs included, in the index's NULL bucket
+                let index_key = column_index_key(table, col_name, &col_def.col_type);
+                if let Some(index_ref) = self.column_indexes.get(&index_key) {
+                    if let Some(old) = before {
+                        if let Err(_e) = index_ref.value().delete(old, row_id) {
+                            debug_log!(
+                                "[write_batch] Failed to delete from column index '{}': {}",
+                                index_key,
+                                _e
+                            );
+                            stale.push(index_key.clone());
+                        }
+                    }
+                    if let Some(new) = after {
+                        column_inserts
+                            .entry(index_key.clone())
+                            .or_insert_with(|| (index_ref.value().clone(), Vec::new()))
+                            .1
+                            .push((new.clone(), row_id));
+                    }
+                }
+
+                // Vector Index
+                if col_def.col_type.vector_dim().is_some() {
+                    if let Some(index_name) =
+                        self.index_registry
+                            .find_by_column(table, col_name, IndexType::Vector)
+                    {
+                        if old.is_some() {
+                            if let Err(_e) = self.delete_vector(row_id, &index_name) {
+                                debug_log!(
+                                    "[write_batch] Failed to delete old vector '{}': {}",
+                                    index_name,
+                                    _e
+                                );
+                                stale.push(index_name.clone());
+                            }
+                        }
+                        if let Some(vec) = vector_of(new) {
+                            vector_inserts
+                                .entry(index_name)
+                                .or_default()
+                                .push((row_id, vec));
+                        }
+                    }
+                }
+
+                // Text Index
+                if matches!(col_def.col_type, ColumnType::Text) {
+                    if let Some(index_name) =
+                        self.index_registry
+                            .find_by_column(table, col_name, IndexType::Text)
+                    {
+                        // A delete followed by a re-insert of the same row would
+                        // un-delete it and keep its flushed old terms searchable
+                        let result = match (old, new) {
+                            (Some(Value::Text(old_text)), Some(Value::Text(new_text))) => {
+                                self.update_text(row_id, &index_name, old_text, new_text)
+                            }
+                            (Some(Value::Text(text)), _) => {
+                                self.delete_text(row_id, &index_name, text)
+                            }
+                            (_, Some(Value::Text(text))) => {
+                                text_inserts
+                                    .entry(index_name.clone())
+                                    .or_default()
+                                    .push((row_id, text.to_string()));
+                                Ok(())
+                            }
+                            _ => Ok(()),
+                        };
+                        if let Err(_e) = result {
+                            debug_log!(
+                                "[write_batch] Failed to update text index '{}': {}",
+                                index_name,
+                                _e
+                            );
+                            stale.push(index_name);
+                        }
+                    }
+                }
+
+                // i-Octree Index (3D point cloud)
+                if matches!(col_def.col_type, ColumnType::Spatial) {
+                    if let Some(index_name) =
+                        self.index_registry
+                            .find_by_column(table, col_name, IndexType::Octree)
+                    {
+                        let mut failed = false;
+                        if old.is_some() {
+                            failed |= self.delete_ioctree_point(row_id, &index_name).is_err();
+                        }
+                        if let Some(Value::Spatial(geom)) = new {
+                            failed |= self
+                                .insert_ioctree_point(row_id, &index_name, geom)
+                                .is_err();
+                        }
+                        if failed {
+                            stale.push(index_name);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (index_key, (index, items)) in column_inserts {
+            if let Err(_e) = index.batch_insert(items) {
+                debug_log!(
+                    "[write_batch] Failed to batch update column index '{}': {}",
+                    index_key,
+                    _e
+                );
+                stale.push(index_key);
+            }
+        }
+        for (index_name, vectors) in vector_inserts {
+            if let Err(_e) = self.batch_insert_vectors(&index_name, &vectors) {
+                debug_log!(
+                    "[write_batch] Failed to batch update vector index '{}': {}",
+                    index_name,
+                    _e
+                );
+                stale.push(index_name);
+            }
+        }
+        for (index_name, texts) in text_inserts {
+            let texts_ref: Vec<(RowId, &str)> =
+                texts.iter().map(|(id, s)| (*id, s.as_str())).collect();
+            if let Err(_e) = self.batch_insert_texts(&index_name, &texts_ref) {
+                debug_log!(
+                    "[write_batch] Failed to batch update text index '{}': {}",
+                    index_name,
+                    _e
+                );
+                stale.push(index_name);
+            }
+        }
+
+        for index_name in &stale {
+            self.index_registry.mark_stale(index_name);
+        }
+    }
+}
@@ -82,6 +82,27 @@ pub enum StorageError {
     /// Segment file corrupted
     #[error("Segment file corrupted: {0}")]
     SegmentCorrupted(std::path::PathBuf),
+
+    /// Best-effort statement rejected at the hard memory cap
+    #[error("Memory limit exceeded: {0}")]
+    MemoryLimitExceeded(String),
+
+    /// Statement rejected by the session's table access hook
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// Statement's joins, sorts or aggregations passed its query memory limit
+    #[error("Query memory limit exceeded: {0}")]
+    QueryMemoryLimitExceeded(String),
+
+    /// Exact numeric result out of its type's range (e.g. an INTEGER SUM)
+    #[error("Numeric overflow: {0}")]
+    NumericOverflow(String),
+
+    /// Transaction aborted by a write conflict or lock timeout; running it
+    /// again may succeed (see [`StorageError::is_transient`])
+    #[error("Transaction conflict: {0}")]
+    Conflict(String),
 }
 
 // Alias for compatibility
@@ -92,3 +113,121 @@ impl From<bincode::Error> for StorageError {
         StorageError::Serialization(err.to_string())
     }
 }
+
+/// Stable numeric error codes, one per [`StorageError`] variant
+///
+/// Values are part of the C ABI (see `ffi::motedb_last_error`): never renumber
+/// an existing variant, only append new ones.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Ok = 0,
+    Io = 1,
+    Serialization = 2,
+    Fragment = 3,
+    Index = 4,
+    Transaction = 5,
+    Query = 6,
+    InvalidData = 7,
+    ResourceExhausted = 8,
+    Corruption = 9,
+    Lock = 10,
+    FileNotFound = 11,
+    CorruptedFile = 12,
+    ParseError = 13,
+    TypeError = 14,
+    ColumnNotFound = 15,
+    TableNotFound = 16,
+    IndexNotFound = 17,
+    InvalidArgument = 18,
+    UnknownFunction = 19,
+    DivisionByZero = 20,
+    NotImplemented = 21,
+    AutoIncrementOverflow = 22,
+    Columnar = 23,
+    SegmentCorrupted = 24,
+    MemoryLimitExceeded = 25,
+    PermissionDenied = 26,
+    QueryMemoryLimitExceeded = 27,
+    NumericOverflow = 28,
+    Conflict = 29,
+}
+
+impl ErrorCode {
+    /// SQLSTATE-like category (PostgreSQL class/condition codes)
+    pub fn sqlstate(self) -> &'static str {
+        match self {
+            ErrorCode::Ok => "00000",
+            ErrorCode::Io => "58030",
+            ErrorCode::FileNotFound => "58P01",
+            ErrorCode::Transaction => "40000",
+            ErrorCode::Conflict => "40001",
+            ErrorCode::Query => "42000",
+            ErrorCode::ParseError => "42601",
+            ErrorCode::TypeError => "42804",
+            ErrorCode::ColumnNotFound => "42703",
+            ErrorCode::TableNotFound => "42P01",
+            ErrorCode::IndexNotFound => "42704",
+            ErrorCode::UnknownFunction => "42883",
+            ErrorCode::PermissionDenied => "42501",
+            ErrorCode::InvalidData => "22000",
+            ErrorCode::InvalidArgument => "22023",
+            ErrorCode::DivisionByZero => "22012",
+            ErrorCode::AutoIncrementOverflow | ErrorCode::NumericOverflow => "22003",
+            ErrorCode::ResourceExhausted => "53000",
+            ErrorCode::MemoryLimitExceeded | ErrorCode::QueryMemoryLimitExceeded => "53200",
+            ErrorCode::Lock => "55P03",
+            ErrorCode::NotImplemented => "0A000",
+            ErrorCode::Corruption | ErrorCode::CorruptedFile | ErrorCode::SegmentCorrupted => {
+                "XX001"
+            }
+            ErrorCode::Serialization
+            | ErrorCode::Fragment
+            | ErrorCode::Index
+            | ErrorCode::Columnar => "XX000",
+        }
+    }
+}
+
+impl StorageError {
+    /// Stable numeric code for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StorageError::Io(_) => ErrorCode::Io,
+            StorageError::Serialization(_) => ErrorCode::Serialization,
+            StorageError::Fragment(_) => ErrorCode::Fragment,
+            StorageError::Index(_) => ErrorCode::Index,
+            StorageError::Transaction(_) => ErrorCode::Transaction,
+            StorageError::Query(_) => ErrorCode::Query,
+            StorageError::InvalidData(_) => ErrorCode::InvalidData,
+            StorageError::ResourceExhausted(_) => ErrorCode::ResourceExhausted,
+            StorageError::Corruption(_) => ErrorCode::Corruption,
+            StorageError::Lock(_) => ErrorCode::Lock,
+            StorageError::FileNotFound(_) => ErrorCode::FileNotFound,
+            StorageError::CorruptedFile(_) => ErrorCode::CorruptedFile,
+            StorageError::ParseError(_) => ErrorCode::ParseError,
+            StorageError::TypeError(_) => ErrorCode::TypeError,
+            StorageError::ColumnNotFound(_) => ErrorCode::ColumnNotFound,
+            StorageError::TableNotFound(_) => ErrorCode::TableNotFound,
+            StorageError::IndexNotFound(_) => ErrorCode::IndexNotFound,
+            StorageError::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            StorageError::UnknownFunction(_) => ErrorCode::UnknownFunction,
+            StorageError::DivisionByZero => ErrorCode::DivisionByZero,
+            StorageError::NotImplemented(_) => ErrorCode::NotImplemented,
+            StorageError::AutoIncrementOverflow(_) => ErrorCode::AutoIncrementOverflow,
+            StorageError::Columnar(_) => ErrorCode::Columnar,
+            StorageError::SegmentCorrupted(_) => ErrorCode::SegmentCorrupted,
+            StorageError::MemoryLimitExceeded(_) => ErrorCode::MemoryLimitExceeded,
+            StorageError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            StorageError::QueryMemoryLimitExceeded(_) => ErrorCode::QueryMemoryLimitExceeded,
+            StorageError::NumericOverflow(_) => ErrorCode::NumericOverflow,
+            StorageError::Conflict(_) => ErrorCode::Conflict,
+        }
+    }
+
+    /// Whether the failed operation may succeed if simply run again:
+    /// write conflicts and lock timeouts, not bad input or I/O errors
+    pub fn is_transient(&self) -> bool {
+        matches!(self, StorageError::Conflict(_))
+    }
+}
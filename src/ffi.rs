@@ -1,8 +1,15 @@
 //! FFI (Foreign Function Interface) for C/Python/Node.js
 //!
 //! C ABI 导出接口，用于动态链接库
+//!
+//! ## 错误模型
+//! 失败的调用在所属句柄上记录最近一次错误（数值错误码 + 消息 + SQLSTATE
+//! 风格分类），通过 `motedb_last_error` 读取；成功的调用会清空它。游标和
+//! 预编译语句的错误记录在创建它们的句柄上。没有句柄可用时（如
+//! `motedb_open` 失败）错误记录在调用线程上，以 NULL 句柄读取。
 
-use crate::MoteDB;
+use crate::error::ErrorCode;
+use crate::{MoteDB, StorageError};
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
@@ -11,27 +18,166 @@ use std::sync::Arc;
 /// 不透明指针类型
 pub struct MoteDBHandle {
     db: Arc<MoteDB>,
+    errors: Arc<ErrorSlot>,
+}
+
+// ============================================================================
+// 错误 API
+// ============================================================================
+
+/// 最近一次错误（C 侧视图）
+///
+/// message / sqlstate 指向句柄内部的缓冲区，在该句柄下一次 FFI 调用前有效，
+/// 调用方不得释放。无错误时 code 为 0，message 为空串，sqlstate 为 "00000"。
+#[repr(C)]
+pub struct MoteDBErrorInfo {
+    /// `ErrorCode` 数值
+    pub code: i32,
+    pub message: *const c_char,
+    /// 5 字符 SQLSTATE 风格分类（如 "42P01" 表不存在）
+    pub sqlstate: *const c_char,
+}
+
+struct LastError {
+    code: ErrorCode,
+    message: CString,
+    sqlstate: CString,
+}
+
+/// 错误槽：句柄与其派生的游标/语句共享
+#[derive(Default)]
+struct ErrorSlot(parking_lot::Mutex<Option<LastError>>);
+
+impl ErrorSlot {
+    fn set(&self, code: ErrorCode, message: impl Into<String>) {
+        let message = message.into().replace('\0', " ");
+        *self.0.lock() = Some(LastError {
+            code,
+            message: CString::new(message).unwrap_or_default(),
+            sqlstate: CString::new(code.sqlstate()).unwrap_or_default(),
+        });
+    }
+
+    fn set_error(&self, e: &StorageError) {
+        self.set(e.code(), e.to_string());
+    }
+
+    fn invalid_argument(&self, what: &str) {
+        self.set(
+            ErrorCode::InvalidArgument,
+            format!("{} is NULL or not valid UTF-8", what),
+        );
+    }
+
+    fn clear(&self) {
+        *self.0.lock() = None;
+    }
+
+    /// 成功时清空错误并返回值，失败时记录错误并返回 None
+    fn record<T>(&self, result: crate::Result<T>) -> Option<T> {
+        match result {
+            Ok(v) => {
+                self.clear();
+                Some(v)
+            }
+            Err(e) => {
+                self.set_error(&e);
+                None
+            }
+        }
+    }
+
+    fn fill(&self, out: *mut MoteDBErrorInfo) -> i32 {
+        let guard = self.0.lock();
+        let (code, message, sqlstate) = match guard.as_ref() {
+            Some(e) => (e.code as i32, e.message.as_ptr(), e.sqlstate.as_ptr()),
+            None => (ErrorCode::Ok as i32, c"".as_ptr(), c"00000".as_ptr()),
+        };
+        if !out.is_null() {
+            unsafe {
+                *out = MoteDBErrorInfo {
+                    code,
+                    message,
+                    sqlstate,
+                }
+            };
+        }
+        code
+    }
+}
+
+thread_local! {
+    /// 没有句柄可用时的错误（motedb_open 失败等）
+    static THREAD_ERROR: ErrorSlot = ErrorSlot::default();
+}
+
+/// 把错误编码为 `{"error":"...","code":n,"sqlstate":"..."}`
+fn error_json(e: &StorageError) -> String {
+    let code = e.code();
+    serde_json::json!({
+        "error": e.to_string(),
+        "code": code as i32,
+        "sqlstate": code.sqlstate(),
+    })
+    .to_string()
+}
+
+/// 读取最近一次错误，返回错误码（0 表示无错误）
+///
+/// out 非 NULL 时填入完整错误信息。handle 为 NULL 时读取调用线程上的错误
+/// （用于 motedb_open 失败的情形）。
+///
+/// # Safety
+/// - handle 必须为 NULL 或有效的 MoteDBHandle 指针
+/// - out 必须为 NULL 或指向可写的 MoteDBErrorInfo
+#[no_mangle]
+pub unsafe extern "C" fn motedb_last_error(
+    handle: *const MoteDBHandle,
+    out: *mut MoteDBErrorInfo,
+) -> i32 {
+    if handle.is_null() {
+        THREAD_ERROR.with(|slot| slot.fill(out))
+    } else {
+        unsafe { &*handle }.errors.fill(out)
+    }
+}
+
+/// 清空最近一次错误
+///
+/// # Safety
+/// - handle 必须为 NULL 或有效的 MoteDBHandle 指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_clear_error(handle: *const MoteDBHandle) {
+    if handle.is_null() {
+        THREAD_ERROR.with(|slot| slot.clear());
+    } else {
+        unsafe { &*handle }.errors.clear();
+    }
 }
 
+// ============================================================================
+// 基础 API
+// ============================================================================
+
 /// 打开数据库
 ///
+/// 失败时返回 NULL，错误可用 `motedb_last_error(NULL, ...)` 读取。
+///
 /// # Safety
 /// - path 必须是有效的 C 字符串
 #[no_mangle]
 pub unsafe extern "C" fn motedb_open(path: *const c_char) -> *mut MoteDBHandle {
-    if path.is_null() {
+    let Some(path_str) = (unsafe { c_str_arg(path) }) else {
+        THREAD_ERROR.with(|slot| slot.invalid_argument("path"));
         return ptr::null_mut();
-    }
-
-    let c_str = unsafe { CStr::from_ptr(path) };
-    let path_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return ptr::null_mut(),
     };
 
-    match MoteDB::open(path_str) {
-        Ok(db) => Box::into_raw(Box::new(MoteDBHandle { db: Arc::new(db) })),
-        Err(_) => ptr::null_mut(),
+    match THREAD_ERROR.with(|slot| slot.record(MoteDB::open(path_str))) {
+        Some(db) => Box::into_raw(Box::new(MoteDBHandle {
+            db: Arc::new(db),
+            errors: Arc::default(),
+        })),
+        None => ptr::null_mut(),
     }
 }
 
@@ -48,6 +194,8 @@ pub unsafe extern "C" fn motedb_close(handle: *mut MoteDBHandle) {
 
 /// 执行 SQL 查询
 ///
+/// 出错时返回 `"Error: ..."` 字符串，同时在句柄上记录错误。
+///
 /// # Safety
 /// - handle 必须是有效的 MoteDBHandle 指针
 /// - sql 必须是有效的 C 字符串
@@ -56,15 +204,14 @@ pub unsafe extern "C" fn motedb_execute(
     handle: *mut MoteDBHandle,
     sql: *const c_char,
 ) -> *mut c_char {
-    if handle.is_null() || sql.is_null() {
+    if handle.is_null() {
         return ptr::null_mut();
     }
 
-    let handle = unsafe { &mut *handle };
-    let c_str = unsafe { CStr::from_ptr(sql) };
-    let sql_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return ptr::null_mut(),
+    let handle = unsafe { &*handle };
+    let Some(sql_str) = (unsafe { c_str_arg(sql) }) else {
+        handle.errors.invalid_argument("sql");
+        return ptr::null_mut();
     };
 
     // ✅ 使用流式 API 并立即物化
@@ -76,24 +223,18 @@ pub unsafe extern "C" fn motedb_execute(
         let mut parser = Parser::new(tokens);
         let statement = parser.parse()?;
         let executor = QueryExecutor::new(handle.db.clone());
-        let streaming_result = executor.execute_streaming(statement)?;
+        let streaming_result = executor.execute_streaming_ref(&statement)?;
         streaming_result.materialize()
     })();
 
     match result {
         Ok(result) => {
-            let json = format!("{:?}", result);
-            match CString::new(json) {
-                Ok(c_string) => c_string.into_raw(),
-                Err(_) => ptr::null_mut(),
-            }
+            handle.errors.clear();
+            into_c_string(format!("{:?}", result))
         }
         Err(e) => {
-            let error = format!("Error: {}", e);
-            match CString::new(error) {
-                Ok(c_string) => c_string.into_raw(),
-                Err(_) => ptr::null_mut(),
-            }
+            handle.errors.set_error(&e);
+            into_c_string(format!("Error: {}", e))
         }
     }
 }
@@ -108,3 +249,1412 @@ pub unsafe extern "C" fn motedb_free_string(s: *mut c_char) {
         let _ = unsafe { CString::from_raw(s) };
     }
 }
+
+// ============================================================================
+// 游标 API（流式结果）
+// ============================================================================
+
+/// 不透明游标类型：持有一个流式结果的行迭代器
+///
+/// 与 `motedb_execute` 不同，游标按批拉取行，内存占用与批大小成正比，
+/// 而不是与结果集大小成正比（ORDER BY / DISTINCT 查询仍需先物化）。
+pub struct MoteDBCursor {
+    columns: Vec<CString>,
+    rows: crate::sql::RowIter,
+    exhausted: bool,
+    errors: Arc<ErrorSlot>,
+}
+
+/// 游标与非会话接口的 JSON 选项：与默认会话设置一致（TIMESTAMP 为微秒数）
+fn default_json_options() -> crate::sql::JsonOptions {
+    crate::sql::JsonOptions::from(&crate::session::SessionSettings::default())
+}
+
+/// 打开游标：解析并执行 SQL，返回可逐批读取的游标
+///
+/// 失败时返回 NULL 并在句柄上记录错误。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - sql 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_open(
+    handle: *mut MoteDBHandle,
+    sql: *const c_char,
+) -> *mut MoteDBCursor {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+
+    let handle = unsafe { &*handle };
+    let Some(sql_str) = (unsafe { c_str_arg(sql) }) else {
+        handle.errors.invalid_argument("sql");
+        return ptr::null_mut();
+    };
+
+    use crate::sql::{Lexer, Parser, QueryExecutor};
+
+    let result = (|| -> crate::Result<_> {
+        let mut lexer = Lexer::new(sql_str);
+        let tokens = lexer.tokenize()?;
+        let mut parser = Parser::new(tokens);
+        let statement = parser.parse()?;
+        let executor = QueryExecutor::new(handle.db.clone());
+        executor.execute_streaming_ref(&statement)?.into_row_iter()
+    })();
+
+    match handle.errors.record(result) {
+        Some((columns, rows)) => {
+            let columns = columns
+                .into_iter()
+                .map(|c| CString::new(c).unwrap_or_default())
+                .collect();
+            Box::into_raw(Box::new(MoteDBCursor {
+                columns,
+                rows,
+                exhausted: false,
+                errors: handle.errors.clone(),
+            }))
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// 获取游标结果的列数（非 SELECT 语句为 0）
+///
+/// # Safety
+/// - cursor 必须是由 motedb_cursor_open 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_column_count(cursor: *const MoteDBCursor) -> usize {
+    if cursor.is_null() {
+        return 0;
+    }
+    unsafe { &*cursor }.columns.len()
+}
+
+/// 获取第 idx 列的列名
+///
+/// 返回的指针归游标所有，在 motedb_cursor_close 之前有效，调用方不得释放。
+/// idx 越界时返回 NULL。
+///
+/// # Safety
+/// - cursor 必须是由 motedb_cursor_open 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_column_name(
+    cursor: *const MoteDBCursor,
+    idx: usize,
+) -> *const c_char {
+    if cursor.is_null() {
+        return ptr::null();
+    }
+    match unsafe { &*cursor }.columns.get(idx) {
+        Some(name) => name.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// 拉取下一批最多 max_rows 行，以 JSON 数组（每行一个数组）返回
+///
+/// 结果耗尽后返回 `"[]"`；执行出错时返回 NULL（错误记录在创建游标的
+/// 句柄上），此后游标视为耗尽。
+/// 返回的字符串需用 motedb_free_string 释放。
+///
+/// # Safety
+/// - cursor 必须是由 motedb_cursor_open 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_next_batch(
+    cursor: *mut MoteDBCursor,
+    max_rows: usize,
+) -> *mut c_char {
+    if cursor.is_null() {
+        return ptr::null_mut();
+    }
+    let cursor = unsafe { &mut *cursor };
+
+    let options = default_json_options();
+    let mut batch = Vec::with_capacity(max_rows.min(4096));
+    while !cursor.exhausted && batch.len() < max_rows {
+        match cursor.rows.next() {
+            Some(Ok(row)) => {
+                batch.push(serde_json::Value::Array(
+                    row.iter()
+                        .map(|value| crate::sql::json::value_to_json(value, &options))
+                        .collect(),
+                ));
+            }
+            Some(Err(e)) => {
+                cursor.exhausted = true;
+                cursor.errors.set_error(&e);
+                return ptr::null_mut();
+            }
+            None => cursor.exhausted = true,
+        }
+    }
+
+    match CString::new(serde_json::Value::Array(batch).to_string()) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// 游标是否已耗尽（没有更多行）
+///
+/// # Safety
+/// - cursor 必须是由 motedb_cursor_open 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_is_exhausted(cursor: *const MoteDBCursor) -> bool {
+    if cursor.is_null() {
+        return true;
+    }
+    unsafe { &*cursor }.exhausted
+}
+
+/// 关闭游标并释放其持有的底层迭代器
+///
+/// # Safety
+/// - cursor 必须是由 motedb_cursor_open 返回的有效指针，且只能关闭一次
+#[no_mangle]
+pub unsafe extern "C" fn motedb_cursor_close(cursor: *mut MoteDBCursor) {
+    if !cursor.is_null() {
+        let _ = unsafe { Box::from_raw(cursor) };
+    }
+}
+
+// ============================================================================
+// 向量缓冲区 API（NumPy 零拷贝交换）
+// ============================================================================
+//
+// 以下接口直接读写调用方提供的连续 float32 / uint64 缓冲区，而不是逐元素
+// 传递列表。Python 侧可以把 `np.ascontiguousarray(x, dtype=np.float32)` 的
+// `arr.ctypes.data_as(POINTER(c_float))` 直接传入；结果写入预先分配好的
+// `np.empty(k, dtype=np.uint64)` / `np.empty(k, dtype=np.float32)`，
+// 不经过任何中间 Python 对象。
+
+/// C 字符串 → &str（NULL 或非 UTF-8 时返回 None）
+unsafe fn c_str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// 以一个 n_rows × dim 的行主序 float32 矩阵逐行执行参数化语句
+///
+/// SQL 只解析一次；第 i 次执行时，矩阵第 i 行作为 `Value::Vector` 绑定到
+/// 语句中唯一的 `?` 占位符（例如 `INSERT INTO docs (emb) VALUES (?)`）。
+/// 返回累计影响行数，出错返回 -1（已执行的行不会回滚）。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - sql 必须是有效的 C 字符串
+/// - data 必须指向至少 n_rows * dim 个连续的 f32
+#[no_mangle]
+pub unsafe extern "C" fn motedb_execute_with_vectors(
+    handle: *mut MoteDBHandle,
+    sql: *const c_char,
+    data: *const f32,
+    n_rows: usize,
+    dim: usize,
+) -> i64 {
+    if handle.is_null() {
+        return -1;
+    }
+    let handle = unsafe { &*handle };
+    let Some(sql_str) = (unsafe { c_str_arg(sql) }) else {
+        handle.errors.invalid_argument("sql");
+        return -1;
+    };
+    let len = match n_rows.checked_mul(dim) {
+        Some(len) if !data.is_null() && dim > 0 => len,
+        _ => {
+            handle.errors.set(
+                ErrorCode::InvalidArgument,
+                "data is NULL, dim is 0 or n_rows * dim overflows",
+            );
+            return -1;
+        }
+    };
+    let matrix = unsafe { std::slice::from_raw_parts(data, len) };
+
+    use crate::sql::{Lexer, Parser, QueryExecutor};
+    use crate::types::{ArcVec, Value};
+
+    let result = (|| -> crate::Result<usize> {
+        let mut lexer = Lexer::new(sql_str);
+        let tokens = lexer.tokenize()?;
+        let mut parser = Parser::new(tokens);
+        let statement = parser.parse()?;
+        let executor = QueryExecutor::new(handle.db.clone());
+        let mut affected = 0;
+        for row in matrix.chunks_exact(dim) {
+            executor.bind_params(vec![Value::Vector(ArcVec::new(row.to_vec()))]);
+            let r = executor.execute_streaming_ref(&statement);
+            executor.clear_params();
+            affected += r?.materialize()?.affected_rows();
+        }
+        Ok(affected)
+    })();
+
+    match handle.errors.record(result) {
+        Some(n) => n as i64,
+        None => -1,
+    }
+}
+
+/// 向量 KNN 搜索，结果直接写入调用方缓冲区
+///
+/// out_row_ids / out_distances 须各能容纳 k 个元素。返回实际写入的结果数
+/// （≤ k），出错返回 -1。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - index_name 必须是有效的 C 字符串
+/// - query 必须指向 dim 个连续的 f32
+/// - out_row_ids / out_distances 必须各指向至少 k 个可写元素
+#[no_mangle]
+pub unsafe extern "C" fn motedb_vector_search(
+    handle: *mut MoteDBHandle,
+    index_name: *const c_char,
+    query: *const f32,
+    dim: usize,
+    k: usize,
+    out_row_ids: *mut u64,
+    out_distances: *mut f32,
+) -> i64 {
+    if handle.is_null() {
+        return -1;
+    }
+    let handle = unsafe { &*handle };
+    let Some(index_name) = (unsafe { c_str_arg(index_name) }) else {
+        handle.errors.invalid_argument("index_name");
+        return -1;
+    };
+    if query.is_null() || out_row_ids.is_null() || out_distances.is_null() {
+        handle
+            .errors
+            .set(ErrorCode::InvalidArgument, "query or output buffer is NULL");
+        return -1;
+    }
+    let query = unsafe { std::slice::from_raw_parts(query, dim) };
+
+    match handle
+        .errors
+        .record(handle.db.vector_search(index_name, query, k))
+    {
+        Some(results) => {
+            let n = results.len().min(k);
+            let ids = unsafe { std::slice::from_raw_parts_mut(out_row_ids, n) };
+            let dists = unsafe { std::slice::from_raw_parts_mut(out_distances, n) };
+            for (i, (row_id, dist)) in results.into_iter().take(n).enumerate() {
+                ids[i] = row_id;
+                dists[i] = dist;
+            }
+            n as i64
+        }
+        None => -1,
+    }
+}
+
+/// 读取某行向量列，直接拷贝到调用方 float32 缓冲区
+///
+/// 返回向量维度；若 capacity 小于维度则不拷贝（调用方可按返回值重新分配）。
+/// 行不存在或该列为 NULL 返回 0，出错（表/列不存在、列不是向量）返回 -1。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - table / column 必须是有效的 C 字符串
+/// - out 必须指向至少 capacity 个可写 f32
+#[no_mangle]
+pub unsafe extern "C" fn motedb_get_vector(
+    handle: *mut MoteDBHandle,
+    table: *const c_char,
+    row_id: u64,
+    column: *const c_char,
+    out: *mut f32,
+    capacity: usize,
+) -> i64 {
+    use crate::types::Value;
+
+    if handle.is_null() {
+        return -1;
+    }
+    let handle = unsafe { &*handle };
+    let (Some(table), Some(column)) = (unsafe { c_str_arg(table) }, unsafe { c_str_arg(column) })
+    else {
+        handle.errors.invalid_argument("table or column");
+        return -1;
+    };
+    if out.is_null() {
+        handle
+            .errors
+            .set(ErrorCode::InvalidArgument, "output buffer is NULL");
+        return -1;
+    }
+
+    let result = (|| -> crate::Result<i64> {
+        let schema = handle.db.get_table_schema(table)?;
+        let pos = schema
+            .get_column_position(column)
+            .ok_or_else(|| StorageError::ColumnNotFound(column.to_string()))?;
+        let Some(row) = handle.db.get_table_row(table, row_id)? else {
+            return Ok(0);
+        };
+        let src: &[f32] = match row.get(pos) {
+            Some(Value::Vector(v)) => v.as_slice(),
+            Some(Value::Tensor(t)) => t.as_f32(),
+            Some(Value::Null) | None => return Ok(0),
+            Some(other) => {
+                return Err(StorageError::TypeError(format!(
+                    "column '{}' is not a vector: {:?}",
+                    column, other
+                )))
+            }
+        };
+        if src.len() <= capacity {
+            unsafe { std::slice::from_raw_parts_mut(out, src.len()) }.copy_from_slice(src);
+        }
+        Ok(src.len() as i64)
+    })();
+
+    handle.errors.record(result).unwrap_or(-1)
+}
+
+/// 嵌入回调：把源列文本转换为 dim 个 float32
+///
+/// - `user_data`: 注册时传入的上下文指针（原样回传）
+/// - `text`: 源列的 UTF-8 文本，仅在回调期间有效
+/// - `out`: 可写 dim 个 f32 的缓冲区
+///
+/// 返回 false 表示嵌入失败，该次写入以错误中止。回调在执行写入的线程上
+/// 同步触发，可能被多个线程并发调用。
+pub type MoteDBEmbedCallback = extern "C" fn(
+    user_data: *mut std::os::raw::c_void,
+    text: *const c_char,
+    out: *mut f32,
+    dim: usize,
+) -> bool;
+
+/// 注册插入时嵌入钩子：插入时 target_column 为 NULL 则由 source_column
+/// （TEXT 列）经 callback 计算得出；更新改变源列时重新计算
+///
+/// 同一目标列再次注册会替换旧回调。钩子不持久化，每次打开后需重新注册。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - table / source_column / target_column 必须是有效的 C 字符串
+/// - user_data 必须在钩子注销或句柄关闭前保持有效
+#[no_mangle]
+pub unsafe extern "C" fn motedb_register_embedder(
+    handle: *mut MoteDBHandle,
+    table: *const c_char,
+    source_column: *const c_char,
+    target_column: *const c_char,
+    callback: MoteDBEmbedCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> bool {
+    use crate::types::{ColumnType, Value};
+
+    if handle.is_null() {
+        return false;
+    }
+    let handle = unsafe { &*handle };
+    let (Some(table), Some(source), Some(target)) = (
+        unsafe { c_str_arg(table) },
+        unsafe { c_str_arg(source_column) },
+        unsafe { c_str_arg(target_column) },
+    ) else {
+        handle.errors.invalid_argument("table or column");
+        return false;
+    };
+    let user_data = SendPtr(user_data);
+
+    let result = (|| -> crate::Result<()> {
+        let schema = handle.db.get_table_schema(table)?;
+        let column = |name: &str| {
+            schema
+                .get_column(name)
+                .ok_or_else(|| StorageError::ColumnNotFound(name.to_string()))
+        };
+        if column(source)?.col_type != ColumnType::Text {
+            return Err(StorageError::InvalidArgument(format!(
+                "embedder source '{}' must be a TEXT column",
+                source
+            )));
+        }
+        let dim = column(target)?.col_type.vector_dim().ok_or_else(|| {
+            StorageError::InvalidArgument(format!(
+                "embedder target '{}' must be a VECTOR column",
+                target
+            ))
+        })?;
+
+        handle
+            .db
+            .register_embedder(table, source, target, move |value: &Value| {
+                let Value::Text(text) = value else {
+                    return Err(StorageError::TypeError(format!(
+                        "embedder source is not text: {:?}",
+                        value
+                    )));
+                };
+                let text = CString::new(text.as_str().replace('\0', " ")).unwrap_or_default();
+                let mut out = vec![0f32; dim];
+                if callback(user_data.get(), text.as_ptr(), out.as_mut_ptr(), dim) {
+                    Ok(out)
+                } else {
+                    Err(StorageError::InvalidData("embedder callback failed".into()))
+                }
+            })
+    })();
+
+    handle.errors.record(result).is_some()
+}
+
+/// 注销 target_column 的嵌入钩子，返回是否存在该钩子
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - table / target_column 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_unregister_embedder(
+    handle: *mut MoteDBHandle,
+    table: *const c_char,
+    target_column: *const c_char,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let handle = unsafe { &*handle };
+    let (Some(table), Some(target)) = (unsafe { c_str_arg(table) }, unsafe {
+        c_str_arg(target_column)
+    }) else {
+        handle.errors.invalid_argument("table or target_column");
+        return false;
+    };
+    handle.errors.clear();
+    handle.db.unregister_embedder(table, target)
+}
+
+// ============================================================================
+// N-API 友好层：预编译语句 + 异步执行
+// ============================================================================
+//
+// 面向 Node.js（N-API / Electron）绑定：语句句柄可跨 JS 调用复用，参数按
+// 1-based 位置绑定（与 SQLite 一致），向量参数直接取 Float32Array 的底层
+// 缓冲区。异步执行在后台线程完成，通过回调把 JSON 结果交给绑定层，再由
+// 绑定层用 threadsafe function 解析 Promise，不阻塞 Node 事件循环。
+
+/// 将结果集编码为 JSON（格式见 `crate::sql::json`）：
+/// - SELECT → `{"columns":[...],"types":[...],"rows":[[...],...]}`
+/// - INSERT/UPDATE/DELETE → `{"affected_rows":n}`
+/// - DDL → `{"message":"..."}`
+///
+/// TIMESTAMP 编码为微秒数，与默认会话设置一致
+fn query_result_to_json(result: &crate::sql::QueryResult) -> String {
+    result.to_json_with(&default_json_options())
+}
+
+/// 按会话设置编码结果：timestamp_format 为 `iso` 时，TIMESTAMP 编码为
+/// 会话时区（time_zone）下的 ISO 8601 字符串
+fn session_result_to_json(
+    result: &crate::sql::QueryResult,
+    settings: &crate::session::SessionSettings,
+) -> String {
+    result.to_json_with(&settings.into())
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// 不透明预编译语句类型
+///
+/// 持有解析后的 AST、独立的执行器和当前绑定的参数；执行后参数保持绑定，
+/// 可用 motedb_stmt_clear_bindings 清空。motedb_stmt_add_batch 累积的参数
+/// 组保存在 batch 中，由 motedb_stmt_execute_batch 一次写入。
+pub struct MoteDBStatement {
+    executor: crate::sql::QueryExecutor,
+    statement: crate::sql::Statement,
+    params: Vec<crate::types::Value>,
+    batch: Vec<Vec<crate::types::Value>>,
+    errors: Arc<ErrorSlot>,
+}
+
+impl MoteDBStatement {
+    /// 绑定第 idx 个参数（1-based），必要时用 NULL 补齐前面的空位
+    fn bind(&mut self, idx: usize, value: crate::types::Value) -> bool {
+        if idx == 0 {
+            self.errors
+                .set(ErrorCode::InvalidArgument, "parameter index is 1-based");
+            return false;
+        }
+        if self.params.len() < idx {
+            self.params.resize(idx, crate::types::Value::Null);
+        }
+        self.params[idx - 1] = value;
+        true
+    }
+
+    fn run(&self) -> crate::Result<crate::sql::QueryResult> {
+        self.executor.reset_last_insert_id();
+        self.executor.bind_params(self.params.clone());
+        let result = self
+            .executor
+            .execute_streaming_ref(&self.statement)
+            .and_then(|r| r.materialize());
+        self.executor.clear_params();
+        result
+    }
+}
+
+/// 预编译 SQL 语句（只解析一次，可多次绑定执行）
+///
+/// 解析失败返回 NULL 并在句柄上记录错误。语句后续的错误也记录在该句柄上。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+/// - sql 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_prepare(
+    handle: *mut MoteDBHandle,
+    sql: *const c_char,
+) -> *mut MoteDBStatement {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+    let handle = unsafe { &*handle };
+    let Some(sql_str) = (unsafe { c_str_arg(sql) }) else {
+        handle.errors.invalid_argument("sql");
+        return ptr::null_mut();
+    };
+
+    use crate::sql::{Lexer, Parser, QueryExecutor};
+
+    let parsed = (|| -> crate::Result<_> {
+        let mut lexer = Lexer::new(sql_str);
+        let tokens = lexer.tokenize()?;
+        let mut parser = Parser::new(tokens);
+        parser.parse()
+    })();
+
+    match handle.errors.record(parsed) {
+        Some(statement) => Box::into_raw(Box::new(MoteDBStatement {
+            executor: QueryExecutor::new(handle.db.clone()),
+            statement,
+            params: Vec::new(),
+            batch: Vec::new(),
+            errors: handle.errors.clone(),
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 绑定整数参数（idx 从 1 开始）
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_bind_int(
+    stmt: *mut MoteDBStatement,
+    idx: usize,
+    value: i64,
+) -> bool {
+    if stmt.is_null() {
+        return false;
+    }
+    unsafe { &mut *stmt }.bind(idx, crate::types::Value::Integer(value))
+}
+
+/// 绑定浮点参数（idx 从 1 开始）
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_bind_float(
+    stmt: *mut MoteDBStatement,
+    idx: usize,
+    value: f64,
+) -> bool {
+    if stmt.is_null() {
+        return false;
+    }
+    unsafe { &mut *stmt }.bind(idx, crate::types::Value::Float(value))
+}
+
+/// 绑定文本参数（idx 从 1 开始）
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+/// - value 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_bind_text(
+    stmt: *mut MoteDBStatement,
+    idx: usize,
+    value: *const c_char,
+) -> bool {
+    if stmt.is_null() {
+        return false;
+    }
+    let stmt = unsafe { &mut *stmt };
+    match unsafe { c_str_arg(value) } {
+        Some(s) => stmt.bind(idx, crate::types::Value::text_from(s)),
+        None => {
+            stmt.errors.invalid_argument("value");
+            false
+        }
+    }
+}
+
+/// 绑定 NULL 参数（idx 从 1 开始）
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_bind_null(stmt: *mut MoteDBStatement, idx: usize) -> bool {
+    if stmt.is_null() {
+        return false;
+    }
+    unsafe { &mut *stmt }.bind(idx, crate::types::Value::Null)
+}
+
+/// 绑定向量参数（idx 从 1 开始），从 float32 缓冲区拷贝 dim 个元素
+///
+/// Node 侧传入 `Float32Array` 的底层 Buffer 指针即可，无需逐元素转换。
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+/// - data 必须指向 dim 个连续的 f32
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_bind_vector(
+    stmt: *mut MoteDBStatement,
+    idx: usize,
+    data: *const f32,
+    dim: usize,
+) -> bool {
+    if stmt.is_null() {
+        return false;
+    }
+    let stmt = unsafe { &mut *stmt };
+    if data.is_null() {
+        stmt.errors
+            .set(ErrorCode::InvalidArgument, "vector data is NULL");
+        return false;
+    }
+    let values = unsafe { std::slice::from_raw_parts(data, dim) }.to_vec();
+    stmt.bind(
+        idx,
+        crate::types::Value::Vector(crate::types::ArcVec::new(values)),
+    )
+}
+
+/// 清空所有已绑定参数
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_clear_bindings(stmt: *mut MoteDBStatement) {
+    if !stmt.is_null() {
+        unsafe { &mut *stmt }.params.clear();
+    }
+}
+
+/// 同步执行预编译语句，返回 JSON 结果（见 `query_result_to_json`）
+///
+/// 出错时返回 `{"error":"...","code":n,"sqlstate":"..."}`，同时在句柄上记录
+/// 错误。返回的字符串需用 motedb_free_string 释放。
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_execute(stmt: *mut MoteDBStatement) -> *mut c_char {
+    if stmt.is_null() {
+        return ptr::null_mut();
+    }
+    let stmt = unsafe { &*stmt };
+    match stmt.run() {
+        Ok(result) => {
+            stmt.errors.clear();
+            into_c_string(query_result_to_json(&result))
+        }
+        Err(e) => {
+            stmt.errors.set_error(&e);
+            into_c_string(error_json(&e))
+        }
+    }
+}
+
+/// 把当前绑定的参数作为一行加入批次（绑定保持不变）
+///
+/// 适用于单行 `INSERT ... VALUES (?, ...)`：逐行绑定并调用本函数，最后用
+/// motedb_stmt_execute_batch 一次写入。返回批次中的行数。
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_add_batch(stmt: *mut MoteDBStatement) -> usize {
+    if stmt.is_null() {
+        return 0;
+    }
+    let stmt = unsafe { &mut *stmt };
+    stmt.batch.push(stmt.params.clone());
+    stmt.batch.len()
+}
+
+/// 一次执行批次中的所有参数行，返回 JSON 结果（`{"affected_rows":n}`）
+///
+/// 语句只解析一次，所有行作为一个批量写入：事务外为一条 WAL 记录、每个
+/// 索引一次更新。无论成败，执行后批次都会清空。出错时返回
+/// `{"error":"...","code":n,"sqlstate":"..."}`。
+/// 返回的字符串需用 motedb_free_string 释放。
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_execute_batch(stmt: *mut MoteDBStatement) -> *mut c_char {
+    if stmt.is_null() {
+        return ptr::null_mut();
+    }
+    let stmt = unsafe { &mut *stmt };
+    let batch = std::mem::take(&mut stmt.batch);
+    match stmt.executor.execute_insert_batch(&stmt.statement, &batch) {
+        Ok(result) => {
+            stmt.errors.clear();
+            into_c_string(query_result_to_json(&result))
+        }
+        Err(e) => {
+            stmt.errors.set_error(&e);
+            into_c_string(error_json(&e))
+        }
+    }
+}
+
+/// 释放预编译语句
+///
+/// # Safety
+/// - stmt 必须是由 motedb_prepare 返回的有效指针，且只能释放一次
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_finalize(stmt: *mut MoteDBStatement) {
+    if !stmt.is_null() {
+        let _ = unsafe { Box::from_raw(stmt) };
+    }
+}
+
+/// 异步执行完成回调
+///
+/// - `user_data`: 调用方在发起请求时传入的上下文指针（原样回传）
+/// - `result`: JSON 结果字符串，所有权转移给回调方，需用 motedb_free_string 释放
+/// - `is_error`: 为 true 时 result 为 `{"error":"...","code":n,"sqlstate":"..."}`
+///
+/// 回调在后台工作线程上触发，绑定层需自行切回 JS 线程。异步执行的错误只通过
+/// 回调传递，不会写入句柄的最近一次错误。
+pub type MoteDBCompletionCallback =
+    extern "C" fn(user_data: *mut std::os::raw::c_void, result: *mut c_char, is_error: bool);
+
+/// 跨线程传递调用方上下文指针（由调用方保证其线程安全性）
+struct SendPtr(*mut std::os::raw::c_void);
+unsafe impl Send for SendPtr {}
+unsafe impl Sync for SendPtr {}
+
+impl SendPtr {
+    fn get(&self) -> *mut std::os::raw::c_void {
+        self.0
+    }
+}
+
+/// 异步执行的工作线程数
+const ASYNC_WORKERS: usize = 4;
+
+/// 等待工作线程的请求上限，超出时提交失败而不是无限堆积
+const ASYNC_QUEUE_CAPACITY: usize = 256;
+
+type AsyncJob = Box<dyn FnOnce() + Send>;
+
+/// 异步执行共用的有界工作线程池，首次提交时启动；线程全部创建失败时为 None
+fn async_queue() -> Option<&'static std::sync::mpsc::SyncSender<AsyncJob>> {
+    static QUEUE: std::sync::OnceLock<Option<std::sync::mpsc::SyncSender<AsyncJob>>> =
+        std::sync::OnceLock::new();
+    QUEUE
+        .get_or_init(|| {
+            let (tx, rx) = std::sync::mpsc::sync_channel::<AsyncJob>(ASYNC_QUEUE_CAPACITY);
+            let rx = Arc::new(parking_lot::Mutex::new(rx));
+            let mut started = 0;
+            for i in 0..ASYNC_WORKERS {
+                let rx = rx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(format!("motedb-ffi-async-{}", i))
+                    .spawn(move || loop {
+                        let job = rx.lock().recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    });
+                if spawned.is_ok() {
+                    started += 1;
+                }
+            }
+            (started > 0).then_some(tx)
+        })
+        .as_ref()
+}
+
+/// 把一次执行交给工作线程池。执行中的 panic 被捕获并作为错误回传，
+/// 保证已提交的请求恰好回调一次；队列已满时返回 false 并在句柄上记录错误。
+fn submit_async(
+    errors: &ErrorSlot,
+    callback: MoteDBCompletionCallback,
+    user_data: SendPtr,
+    work: impl FnOnce() -> crate::Result<crate::sql::QueryResult> + Send + 'static,
+) -> bool {
+    let job: AsyncJob = Box::new(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(work));
+        let (json, is_error) = match result {
+            Ok(Ok(r)) => (query_result_to_json(&r), false),
+            Ok(Err(e)) => (error_json(&e), true),
+            Err(_) => (
+                error_json(&StorageError::Query("statement execution panicked".into())),
+                true,
+            ),
+        };
+        callback(user_data.get(), into_c_string(json), is_error);
+    });
+    let Some(queue) = async_queue() else {
+        errors.set(
+            ErrorCode::ResourceExhausted,
+            "failed to start async worker threads",
+        );
+        return false;
+    };
+    match queue.try_send(job) {
+        Ok(()) => true,
+        Err(_) => {
+            errors.set(
+                ErrorCode::ResourceExhausted,
+                format!(
+                    "async queue is full ({} requests pending)",
+                    ASYNC_QUEUE_CAPACITY
+                ),
+            );
+            false
+        }
+    }
+}
+
+/// 在后台线程执行 SQL，完成后调用 callback
+///
+/// 请求由固定数量的工作线程执行，排队请求有上限（见 `ASYNC_QUEUE_CAPACITY`）。
+/// 立即返回：true 表示请求已提交（callback 保证恰好被调用一次，执行中 panic
+/// 也会以错误回传），false 表示参数无效或队列已满（callback 不会被调用，
+/// 队列已满时句柄记录 `ResourceExhausted` 错误）。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针；句柄可在回调触发前关闭，
+///   后台任务持有数据库的独立引用
+/// - sql 必须是有效的 C 字符串
+/// - user_data 必须在回调触发前保持有效
+#[no_mangle]
+pub unsafe extern "C" fn motedb_execute_async(
+    handle: *mut MoteDBHandle,
+    sql: *const c_char,
+    callback: MoteDBCompletionCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let handle = unsafe { &*handle };
+    let db = handle.db.clone();
+    let sql_str = match unsafe { c_str_arg(sql) } {
+        Some(s) => s.to_string(),
+        None => return false,
+    };
+
+    submit_async(&handle.errors, callback, SendPtr(user_data), move || {
+        use crate::sql::{Lexer, Parser, QueryExecutor};
+
+        let mut lexer = Lexer::new(&sql_str);
+        let tokens = lexer.tokenize()?;
+        let mut parser = Parser::new(tokens);
+        let statement = parser.parse()?;
+        QueryExecutor::new(db)
+            .execute_streaming_ref(&statement)?
+            .materialize()
+    })
+}
+
+/// 在后台线程执行预编译语句（使用调用时刻已绑定的参数快照）
+///
+/// 语义同 motedb_execute_async。执行期间调用方可以继续绑定新参数或再次
+/// 提交同一语句。
+///
+/// # Safety
+/// - handle 必须是创建该语句的有效 MoteDBHandle 指针
+/// - stmt 必须是由 motedb_prepare 返回的有效指针
+/// - user_data 必须在回调触发前保持有效
+#[no_mangle]
+pub unsafe extern "C" fn motedb_stmt_execute_async(
+    handle: *mut MoteDBHandle,
+    stmt: *mut MoteDBStatement,
+    callback: MoteDBCompletionCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> bool {
+    if handle.is_null() || stmt.is_null() {
+        return false;
+    }
+    let handle = unsafe { &*handle };
+    let stmt = unsafe { &*stmt };
+    let snapshot = MoteDBStatement {
+        executor: crate::sql::QueryExecutor::new(handle.db.clone()),
+        statement: stmt.statement.clone(),
+        params: stmt.params.clone(),
+        batch: Vec::new(),
+        errors: Arc::default(),
+    };
+
+    submit_async(&handle.errors, callback, SendPtr(user_data), move || {
+        snapshot.run()
+    })
+}
+
+// ============================================================================
+// 会话与连接池（多线程宿主）
+// ============================================================================
+//
+// 多线程宿主应用为每个工作线程从池中借出一个会话，而不是共享同一个
+// MoteDBHandle：每个会话有独立的事务、设置和命名预编译语句。会话同一时刻
+// 只能被一个线程使用，但可以在线程之间传递。
+
+/// 不透明连接池类型（可被多个线程同时使用）
+pub struct MoteDBPool {
+    pool: Arc<crate::session::SessionPool>,
+}
+
+/// 不透明会话类型
+pub struct MoteDBSession {
+    session: crate::session::PooledSession,
+    errors: ErrorSlot,
+}
+
+/// JSON 参数 → Value（数字 / 字符串 / 布尔 / null / 数值数组 → 向量）
+fn json_to_value(json: &serde_json::Value) -> crate::Result<crate::types::Value> {
+    use crate::types::Value;
+    Ok(match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => Value::text_from(s),
+        serde_json::Value::Array(items) => {
+            let values = items
+                .iter()
+                .map(|v| {
+                    v.as_f64().map(|f| f as f32).ok_or_else(|| {
+                        StorageError::InvalidArgument(format!(
+                            "vector parameter element is not a number: {}",
+                            v
+                        ))
+                    })
+                })
+                .collect::<crate::Result<Vec<f32>>>()?;
+            Value::Vector(crate::types::ArcVec::new(values))
+        }
+        serde_json::Value::Object(_) => {
+            return Err(StorageError::InvalidArgument(
+                "object parameters are not supported".into(),
+            ))
+        }
+    })
+}
+
+/// 创建连接池，最多同时借出 max_size 个会话（至少 1）
+///
+/// 连接池持有数据库的独立引用，handle 可先于连接池关闭。
+///
+/// # Safety
+/// - handle 必须是有效的 MoteDBHandle 指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_pool_create(
+    handle: *mut MoteDBHandle,
+    max_size: usize,
+) -> *mut MoteDBPool {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+    let db = unsafe { &*handle }.db.clone();
+    Box::into_raw(Box::new(MoteDBPool {
+        pool: Arc::new(crate::session::SessionPool::new(db, max_size)),
+    }))
+}
+
+/// 借出一个会话
+///
+/// timeout_ms < 0 时一直等待；否则最多等待 timeout_ms 毫秒，超时返回 NULL，
+/// 错误（ResourceExhausted）记录在调用线程上（`motedb_last_error(NULL, ...)`）。
+///
+/// # Safety
+/// - pool 必须是由 motedb_pool_create 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_pool_acquire(
+    pool: *const MoteDBPool,
+    timeout_ms: i64,
+) -> *mut MoteDBSession {
+    if pool.is_null() {
+        return ptr::null_mut();
+    }
+    let pool = &unsafe { &*pool }.pool;
+    let timeout = u64::try_from(timeout_ms)
+        .ok()
+        .map(std::time::Duration::from_millis);
+
+    match THREAD_ERROR.with(|slot| slot.record(pool.acquire(timeout))) {
+        Some(session) => Box::into_raw(Box::new(MoteDBSession {
+            session,
+            errors: ErrorSlot::default(),
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// 归还会话：回滚未提交的事务，清空设置和预编译语句
+///
+/// 连接池已销毁时同样可以归还，会话随最后一个引用释放。
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针，且只能归还一次
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_release(session: *mut MoteDBSession) {
+    if !session.is_null() {
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
+
+/// 读取连接池状态：空闲会话数与已借出会话数
+///
+/// # Safety
+/// - pool 必须是由 motedb_pool_create 返回的有效指针
+/// - out_idle / out_in_use 必须为 NULL 或指向可写的 usize
+#[no_mangle]
+pub unsafe extern "C" fn motedb_pool_stats(
+    pool: *const MoteDBPool,
+    out_idle: *mut usize,
+    out_in_use: *mut usize,
+) {
+    if pool.is_null() {
+        return;
+    }
+    let (idle, in_use) = unsafe { &*pool }.pool.stats();
+    if !out_idle.is_null() {
+        unsafe { *out_idle = idle };
+    }
+    if !out_in_use.is_null() {
+        unsafe { *out_in_use = in_use };
+    }
+}
+
+/// 销毁连接池（已借出的会话仍可使用，归还时直接释放）
+///
+/// # Safety
+/// - pool 必须是由 motedb_pool_create 返回的有效指针，且只能销毁一次
+#[no_mangle]
+pub unsafe extern "C" fn motedb_pool_destroy(pool: *mut MoteDBPool) {
+    if !pool.is_null() {
+        let _ = unsafe { Box::from_raw(pool) };
+    }
+}
+
+/// 在会话中执行 SQL，返回 JSON 结果（见 `query_result_to_json`）
+///
+/// BEGIN / COMMIT / ROLLBACK 作用于本会话的事务。出错时返回
+/// `{"error":"...","code":n,"sqlstate":"..."}` 并记录在会话上。
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - sql 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_execute(
+    session: *mut MoteDBSession,
+    sql: *const c_char,
+) -> *mut c_char {
+    if session.is_null() {
+        return ptr::null_mut();
+    }
+    let session = unsafe { &mut *session };
+    let Some(sql_str) = (unsafe { c_str_arg(sql) }) else {
+        session.errors.invalid_argument("sql");
+        return ptr::null_mut();
+    };
+    match session.session.execute(sql_str) {
+        Ok(result) => {
+            session.errors.clear();
+            into_c_string(session_result_to_json(&result, session.session.settings()))
+        }
+        Err(e) => {
+            session.errors.set_error(&e);
+            into_c_string(error_json(&e))
+        }
+    }
+}
+
+/// 在会话中以 name 预编译语句（同名语句被替换）
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - name / sql 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_prepare(
+    session: *mut MoteDBSession,
+    name: *const c_char,
+    sql: *const c_char,
+) -> bool {
+    if session.is_null() {
+        return false;
+    }
+    let session = unsafe { &mut *session };
+    let (Some(name), Some(sql_str)) = (unsafe { c_str_arg(name) }, unsafe { c_str_arg(sql) })
+    else {
+        session.errors.invalid_argument("name or sql");
+        return false;
+    };
+    session
+        .errors
+        .record(session.session.prepare(name, sql_str))
+        .is_some()
+}
+
+/// 执行会话中名为 name 的预编译语句
+///
+/// params_json 为 JSON 数组（可为 NULL 表示无参数），依次绑定到 `?`：
+/// 整数 / 浮点 / 字符串 / 布尔 / null，数值数组绑定为向量。
+/// 返回值与 motedb_session_execute 相同。
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - name 必须是有效的 C 字符串；params_json 必须为 NULL 或有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_execute_prepared(
+    session: *mut MoteDBSession,
+    name: *const c_char,
+    params_json: *const c_char,
+) -> *mut c_char {
+    if session.is_null() {
+        return ptr::null_mut();
+    }
+    let session = unsafe { &mut *session };
+    let Some(name) = (unsafe { c_str_arg(name) }) else {
+        session.errors.invalid_argument("name");
+        return ptr::null_mut();
+    };
+    let params_str = if params_json.is_null() {
+        None
+    } else {
+        match unsafe { c_str_arg(params_json) } {
+            Some(s) => Some(s),
+            None => {
+                session.errors.invalid_argument("params_json");
+                return ptr::null_mut();
+            }
+        }
+    };
+
+    let result = (|| -> crate::Result<_> {
+        let params = match params_str {
+            Some(s) => match serde_json::from_str::<serde_json::Value>(s) {
+                Ok(serde_json::Value::Array(items)) => items
+                    .iter()
+                    .map(json_to_value)
+                    .collect::<crate::Result<Vec<_>>>()?,
+                _ => {
+                    return Err(StorageError::InvalidArgument(
+                        "params_json must be a JSON array".into(),
+                    ))
+                }
+            },
+            None => Vec::new(),
+        };
+        session.session.execute_prepared(name, params)
+    })();
+
+    match result {
+        Ok(result) => {
+            session.errors.clear();
+            into_c_string(session_result_to_json(&result, session.session.settings()))
+        }
+        Err(e) => {
+            session.errors.set_error(&e);
+            into_c_string(error_json(&e))
+        }
+    }
+}
+
+/// 以多组参数批量执行会话中名为 name 的单行 INSERT 预编译语句
+///
+/// rows_json 为 JSON 数组的数组，每个内层数组是一行参数（取值规则同
+/// motedb_session_execute_prepared）。所有行作为一个批量写入。
+/// 返回值与 motedb_session_execute 相同。
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - name / rows_json 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_execute_prepared_batch(
+    session: *mut MoteDBSession,
+    name: *const c_char,
+    rows_json: *const c_char,
+) -> *mut c_char {
+    if session.is_null() {
+        return ptr::null_mut();
+    }
+    let session = unsafe { &mut *session };
+    let Some(name) = (unsafe { c_str_arg(name) }) else {
+        session.errors.invalid_argument("name");
+        return ptr::null_mut();
+    };
+    let Some(rows_str) = (unsafe { c_str_arg(rows_json) }) else {
+        session.errors.invalid_argument("rows_json");
+        return ptr::null_mut();
+    };
+
+    let result = (|| -> crate::Result<_> {
+        let not_rows =
+            || StorageError::InvalidArgument("rows_json must be a JSON array of arrays".into());
+        let rows = match serde_json::from_str::<serde_json::Value>(rows_str) {
+            Ok(serde_json::Value::Array(rows)) => rows
+                .iter()
+                .map(|row| match row {
+                    serde_json::Value::Array(items) => items.iter().map(json_to_value).collect(),
+                    _ => Err(not_rows()),
+                })
+                .collect::<crate::Result<Vec<Vec<_>>>>()?,
+            _ => return Err(not_rows()),
+        };
+        session.session.execute_prepared_batch(name, &rows)
+    })();
+
+    match result {
+        Ok(result) => {
+            session.errors.clear();
+            into_c_string(session_result_to_json(&result, session.session.settings()))
+        }
+        Err(e) => {
+            session.errors.set_error(&e);
+            into_c_string(error_json(&e))
+        }
+    }
+}
+
+/// 设置会话参数（`max_rows`、`read_only`、`stream_batch_size`、`materialize_capacity`、
+/// `search_beam_width`、`vector_rerank`、`text_search_limit`、`query_memory_limit`、`time_zone`、
+/// `timestamp_format`），
+/// 未知参数或非法值返回 false。也可在会话中执行 `SET name = value`
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - key / value 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_set(
+    session: *mut MoteDBSession,
+    key: *const c_char,
+    value: *const c_char,
+) -> bool {
+    if session.is_null() {
+        return false;
+    }
+    let session = unsafe { &mut *session };
+    let (Some(key), Some(value)) = (unsafe { c_str_arg(key) }, unsafe { c_str_arg(value) }) else {
+        session.errors.invalid_argument("key or value");
+        return false;
+    };
+    session
+        .errors
+        .record(session.session.settings_mut().set(key, value))
+        .is_some()
+}
+
+/// 表访问回调：返回 true 表示允许会话访问该表
+///
+/// - `user_data`: 设置时传入的上下文指针（原样回传）
+/// - `table`: 表名（UTF-8），仅在回调期间有效
+/// - `write`: false 为读（SELECT、子查询、DESCRIBE、SHOW TABLES），
+///   true 为写（INSERT / UPDATE / DELETE 及表和索引上的 DDL）
+///
+/// 回调在执行语句的线程上、语句开始执行前同步触发。
+pub type MoteDBAccessCallback =
+    extern "C" fn(user_data: *mut std::os::raw::c_void, table: *const c_char, write: bool) -> bool;
+
+/// 设置会话的表访问回调：语句涉及的每张表都先询问回调，任一被拒绝则整条
+/// 语句以 PermissionDenied（SQLSTATE 42501）失败且不产生任何效果；
+/// SHOW TABLES 只列出可读的表。再次设置会替换旧回调，会话归还连接池时清除
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - user_data 必须在回调被清除或会话归还前保持有效
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_set_access_callback(
+    session: *mut MoteDBSession,
+    callback: MoteDBAccessCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> bool {
+    use crate::sql::TableAccess;
+
+    if session.is_null() {
+        return false;
+    }
+    let session = unsafe { &mut *session };
+    let user_data = SendPtr(user_data);
+    session
+        .session
+        .set_access_hook(Some(Arc::new(move |table: &str, access| {
+            let table = CString::new(table.replace('\0', " ")).unwrap_or_default();
+            callback(
+                user_data.get(),
+                table.as_ptr(),
+                access == TableAccess::Write,
+            )
+        })));
+    session.errors.clear();
+    true
+}
+
+/// 清除会话的表访问回调，之后所有表均可访问
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_clear_access_callback(session: *mut MoteDBSession) {
+    if !session.is_null() {
+        unsafe { &mut *session }.session.set_access_hook(None);
+    }
+}
+
+/// 会话是否有未提交的事务
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_in_transaction(session: *const MoteDBSession) -> bool {
+    if session.is_null() {
+        return false;
+    }
+    unsafe { &*session }.session.in_transaction()
+}
+
+/// 读取会话上最近一次错误，语义同 motedb_last_error
+///
+/// # Safety
+/// - session 必须是由 motedb_pool_acquire 返回的有效指针
+/// - out 必须为 NULL 或指向可写的 MoteDBErrorInfo
+#[no_mangle]
+pub unsafe extern "C" fn motedb_session_last_error(
+    session: *const MoteDBSession,
+    out: *mut MoteDBErrorInfo,
+) -> i32 {
+    if session.is_null() {
+        return THREAD_ERROR.with(|slot| slot.fill(out));
+    }
+    unsafe { &*session }.errors.fill(out)
+}
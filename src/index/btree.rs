@@ -13,12 +13,13 @@
 //!              ↓ flush            ↓ serialize
 //! Disk:     [mmap file] -----> [Page 0][Page 1][Page 2]...
 //! ```text
+use crate::storage::backend::{self, BackendFile, OpenFlags};
+use crate::storage::columnar::gorilla;
 use crate::storage::file_manager::FileHandle;
 use crate::{Result, StorageError};
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
@@ -33,9 +34,17 @@ pub const BTREE_ORDER: usize = 255;
 /// Used as upper bound for buffer allocation and page cache alignment
 pub const MAX_PAGE_SIZE: usize = 15 + BTREE_ORDER * 8 * 2;
 
-/// Compact page header size: [is_leaf:1][num_keys:4][next_leaf:8][content_len:2]
+/// Compact page header size: [flags:1][num_keys:4][next_leaf:8][content_len:2]
 const PAGE_HEADER_SIZE: usize = 15;
 
+/// Page header flags. Pages written before delta encoding store 0 or 1 here,
+/// which decode as raw internal / leaf pages.
+const PAGE_FLAG_LEAF: u8 = 0x01;
+/// Keys are stored as a delta-of-delta run instead of raw u64s
+const PAGE_FLAG_KEYS_DELTA: u8 = 0x02;
+/// Values (leaf) or children (internal) are stored as a delta-of-delta run
+const PAGE_FLAG_PAYLOAD_DELTA: u8 = 0x04;
+
 /// Default page cache size
 pub const DEFAULT_PAGE_CACHE: usize = 1024;
 
@@ -101,7 +110,7 @@ pub struct BTree {
     next_page_id: Arc<RwLock<u64>>,
 
     /// Storage file
-    storage_file: Arc<RwLock<File>>,
+    storage_file: Arc<RwLock<Box<dyn BackendFile>>>,
 
     /// Flush lock (prevents concurrent flushes from corrupting file)
     flush_lock: Arc<Mutex<()>>,
@@ -139,6 +148,10 @@ pub struct BTreeConfig {
 
     /// Immediate sync (if true, sync after every insert; if false, only on flush())
     pub immediate_sync: bool,
+
+    /// Delta-of-delta encode page keys and values when it saves space.
+    /// Pays off for monotonic keys such as sensor timestamps.
+    pub delta_encoding: bool,
 }
 
 impl Default for BTreeConfig {
@@ -149,6 +162,7 @@ impl Default for BTreeConfig {
             unique_keys: false,
             allow_updates: true,
             immediate_sync: false,
+            delta_encoding: false,
         }
     }
 }
@@ -211,49 +225,58 @@ impl Page {
     }
 
     /// Serialize page to compact bytes (only actual content, no padding)
-    fn serialize_compact(&self) -> Result<Vec<u8>> {
+    ///
+    /// With `delta_encoding`, the key array and the value/child array are each
+    /// stored delta-of-delta encoded (see [`encode_delta_run`]) when that is
+    /// smaller than 8 bytes per entry; the choice is recorded in the header
+    /// flags so pages written either way can be read back.
+    fn serialize_compact(&self, delta_encoding: bool) -> Result<Vec<u8>> {
+        let payload: &[u64] = if self.is_leaf {
+            &self.values
+        } else {
+            &self.children
+        };
+        let encoded_keys = delta_encoding
+            .then(|| encode_delta_run(&self.keys))
+            .flatten();
+        let encoded_payload = delta_encoding.then(|| encode_delta_run(payload)).flatten();
+        let section_len = |raw: &[u64], encoded: &Option<Vec<u8>>| match encoded {
+            Some(bytes) => 2 + bytes.len(),
+            None => raw.len() * 8,
+        };
         let data_len = PAGE_HEADER_SIZE
-            + self.num_keys * 8
-            + if self.is_leaf {
-                self.num_keys * 8
-            } else {
-                (self.num_keys + 1) * 8
-            };
-        let mut buf = vec![0u8; data_len];
-        let mut offset = 0;
-
-        // Header: [is_leaf:1][num_keys:4][next_leaf:8][content_len:2]
-        buf[offset] = if self.is_leaf { 1 } else { 0 };
-        offset += 1;
-
-        buf[offset..offset + 4].copy_from_slice(&(self.num_keys as u32).to_le_bytes());
-        offset += 4;
-
-        buf[offset..offset + 8].copy_from_slice(&self.next_leaf.to_le_bytes());
-        offset += 8;
-
-        buf[offset..offset + 2].copy_from_slice(&(data_len as u16).to_le_bytes());
-        offset += 2;
-
-        // Keys (num_keys * 8 bytes)
-        for &key in &self.keys {
-            buf[offset..offset + 8].copy_from_slice(&key.to_le_bytes());
-            offset += 8;
+            + section_len(&self.keys, &encoded_keys)
+            + section_len(payload, &encoded_payload);
+        let mut buf = Vec::with_capacity(data_len);
+
+        // Header: [flags:1][num_keys:4][next_leaf:8][content_len:2]
+        let mut flags = if self.is_leaf { PAGE_FLAG_LEAF } else { 0 };
+        if encoded_keys.is_some() {
+            flags |= PAGE_FLAG_KEYS_DELTA;
         }
-
-        if self.is_leaf {
-            // Values (num_keys * 8 bytes)
-            for &value in &self.values {
-                buf[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
-                offset += 8;
-            }
-        } else {
-            // Children ((num_keys + 1) * 8 bytes)
-            for &child in &self.children {
-                buf[offset..offset + 8].copy_from_slice(&child.to_le_bytes());
-                offset += 8;
+        if encoded_payload.is_some() {
+            flags |= PAGE_FLAG_PAYLOAD_DELTA;
+        }
+        buf.push(flags);
+        buf.extend_from_slice(&(self.num_keys as u32).to_le_bytes());
+        buf.extend_from_slice(&self.next_leaf.to_le_bytes());
+        buf.extend_from_slice(&(data_len as u16).to_le_bytes());
+
+        // Keys, then values (leaf) or children (internal)
+        for (raw, encoded) in [(&self.keys[..], encoded_keys), (payload, encoded_payload)] {
+            match encoded {
+                Some(bytes) => {
+                    buf.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+                    buf.extend_from_slice(&bytes);
+                }
+                None => {
+                    for &v in raw {
+                        buf.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
             }
         }
+        debug_assert_eq!(buf.len(), data_len);
 
         Ok(buf)
     }
@@ -270,7 +293,8 @@ impl Page {
 
         let mut offset = 0;
 
-        let is_leaf = buf[offset] == 1;
+        let flags = buf[offset];
+        let is_leaf = flags & PAGE_FLAG_LEAF != 0;
         offset += 1;
 
         let num_keys = u32::from_le_bytes([
@@ -303,56 +327,22 @@ impl Page {
         let _content_len = u16::from_le_bytes([buf[offset], buf[offset + 1]]) as usize;
         offset += 2;
 
-        // Read keys
-        let mut keys = Vec::with_capacity(num_keys);
-        for _ in 0..num_keys {
-            let key = u64::from_le_bytes([
-                buf[offset],
-                buf[offset + 1],
-                buf[offset + 2],
-                buf[offset + 3],
-                buf[offset + 4],
-                buf[offset + 5],
-                buf[offset + 6],
-                buf[offset + 7],
-            ]);
-            keys.push(key);
-            offset += 8;
-        }
+        let keys = read_u64_section(
+            page_id,
+            buf,
+            &mut offset,
+            num_keys,
+            flags & PAGE_FLAG_KEYS_DELTA != 0,
+        )?;
 
         let mut values = Vec::new();
         let mut children = Vec::new();
 
+        let payload_delta = flags & PAGE_FLAG_PAYLOAD_DELTA != 0;
         if is_leaf {
-            for _ in 0..num_keys {
-                let value = u64::from_le_bytes([
-                    buf[offset],
-                    buf[offset + 1],
-                    buf[offset + 2],
-                    buf[offset + 3],
-                    buf[offset + 4],
-                    buf[offset + 5],
-                    buf[offset + 6],
-                    buf[offset + 7],
-                ]);
-                values.push(value);
-                offset += 8;
-            }
+            values = read_u64_section(page_id, buf, &mut offset, num_keys, payload_delta)?;
         } else if num_keys > 0 {
-            for _ in 0..=num_keys {
-                let child = u64::from_le_bytes([
-                    buf[offset],
-                    buf[offset + 1],
-                    buf[offset + 2],
-                    buf[offset + 3],
-                    buf[offset + 4],
-                    buf[offset + 5],
-                    buf[offset + 6],
-                    buf[offset + 7],
-                ]);
-                children.push(child);
-                offset += 8;
-            }
+            children = read_u64_section(page_id, buf, &mut offset, num_keys + 1, payload_delta)?;
         }
 
         Ok(Self {
@@ -403,6 +393,57 @@ impl Page {
     }
 }
 
+/// Delta-of-delta encode a run of page entries, if that is smaller than the
+/// raw 8-byte layout. Sorted keys of a periodic timestamp index (and the
+/// sequential row IDs they map to) shrink to a few bits per entry.
+fn encode_delta_run(vals: &[u64]) -> Option<Vec<u8>> {
+    if vals.len() < 2 {
+        return None;
+    }
+    let signed: Vec<i64> = vals.iter().map(|&v| v as i64).collect();
+    let encoded = gorilla::try_encode_timestamps(&signed)?;
+    (2 + encoded.len() < vals.len() * 8).then_some(encoded)
+}
+
+/// Read `count` entries of a page section: either raw little-endian u64s or a
+/// `[len:u16][delta-of-delta bytes]` run written by [`encode_delta_run`].
+fn read_u64_section(
+    page_id: u64,
+    buf: &[u8],
+    offset: &mut usize,
+    count: usize,
+    delta: bool,
+) -> Result<Vec<u64>> {
+    let truncated = || {
+        StorageError::Corruption(format!(
+            "Page {} truncated: {} bytes, section at {}",
+            page_id,
+            buf.len(),
+            offset
+        ))
+    };
+    if delta {
+        let len_bytes = buf.get(*offset..*offset + 2).ok_or_else(truncated)?;
+        let len = u16::from_le_bytes([len_bytes[0], len_bytes[1]]) as usize;
+        let data = buf
+            .get(*offset + 2..*offset + 2 + len)
+            .ok_or_else(truncated)?;
+        *offset += 2 + len;
+        return Ok(gorilla::decode_timestamps(data, count)
+            .into_iter()
+            .map(|v| v as u64)
+            .collect());
+    }
+    let data = buf
+        .get(*offset..*offset + count * 8)
+        .ok_or_else(truncated)?;
+    *offset += count * 8;
+    Ok(data
+        .chunks_exact(8)
+        .map(|c| u64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+        .collect())
+}
+
 /// B+Tree statistics
 #[derive(Default, Debug, Clone)]
 pub struct BTreeStats {
@@ -435,21 +476,16 @@ impl BTree {
     /// Create with custom configuration
     pub fn with_config(storage_path: PathBuf, config: BTreeConfig) -> Result<Self> {
         // Create parent directory
+        let backend = backend::backend_for(&storage_path);
         if let Some(parent) = storage_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            backend.create_dir_all(parent)?;
         }
 
         // Open or create file
-        let mut file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false)
-            .open(&storage_path)?;
+        let mut file = backend.open(&storage_path, OpenFlags::read_write().or_create())?;
 
         // Check if file is new or existing
-        let metadata = file.metadata()?;
-        let file_size = metadata.len();
+        let file_size = file.len()?;
         let is_new_file = file_size == 0;
 
         let (_superblock, root_page_id, next_page_id, stats, page_offsets) = if is_new_file {
@@ -489,7 +525,7 @@ impl BTree {
             if superblock.version != BTREE_VERSION {
                 // v1 files use fixed PAGE_SIZE layout — silently reinitialize
                 drop(file);
-                let _ = std::fs::remove_file(&storage_path);
+                let _ = backend.remove_file(&storage_path);
                 return Self::with_config(storage_path, config);
             }
 
@@ -532,7 +568,7 @@ impl BTree {
     const SUPERBLOCK_SIZE: usize = 4096;
 
     /// Read SuperBlock from file start
-    fn read_superblock(file: &mut File) -> Result<SuperBlock> {
+    fn read_superblock(file: &mut Box<dyn BackendFile>) -> Result<SuperBlock> {
         file.seek(SeekFrom::Start(0))?;
 
         let mut buf = vec![0u8; Self::SUPERBLOCK_SIZE];
@@ -544,7 +580,7 @@ impl BTree {
     }
 
     /// Write SuperBlock at file start
-    fn write_superblock(file: &mut File, superblock: &SuperBlock) -> Result<()> {
+    fn write_superblock(file: &mut Box<dyn BackendFile>, superblock: &SuperBlock) -> Result<()> {
         file.seek(SeekFrom::Start(0))?;
 
         let data = bincode::serialize(superblock)
@@ -634,7 +670,6 @@ impl BTree {
         let file = self.storage_file.read();
 
         // Read header to get content_len using positional read (no seek needed)
-        use std::os::unix::fs::FileExt;
         let mut header_buf = [0u8; 15];
         file.read_exact_at(&mut header_buf, file_offset)?;
         let content_len = u16::from_le_bytes([header_buf[13], header_buf[14]]) as usize;
@@ -674,14 +709,14 @@ impl BTree {
             ));
         }
 
-        let buf = page.serialize_compact()?;
+        let buf = page.serialize_compact(self.config.delta_encoding)?;
 
         let _flush_guard = self.flush_lock.lock();
 
         let mut file = self.storage_file.write();
 
         // Always append — page_offsets will be corrected during flush()
-        let file_end = file.metadata()?.len().max(Self::SUPERBLOCK_SIZE as u64);
+        let file_end = file.len()?.max(Self::SUPERBLOCK_SIZE as u64);
         file.seek(SeekFrom::Start(file_end))?;
         file.write_all(&buf)?;
 
@@ -1377,7 +1412,7 @@ impl BTree {
         for (page_id, page_arc) in &pages {
             let page = page_arc.read();
 
-            let buf = page.serialize_compact()?;
+            let buf = page.serialize_compact(self.config.delta_encoding)?;
 
             file.seek(SeekFrom::Start(offset))?;
             file.write_all(&buf)?;
@@ -1571,6 +1606,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_delta_encoded_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let raw_path = temp_dir.path().join("raw.btree");
+        let delta_path = temp_dir.path().join("delta.btree");
+        // 1 kHz sensor timestamps (µs) mapped to sequential row ids
+        let entries: Vec<(u64, u64)> = (0..20_000u64)
+            .map(|i| (1_700_000_000_000_000 + i * 1_000, i + 1))
+            .collect();
+
+        for (path, delta_encoding) in [(&raw_path, false), (&delta_path, true)] {
+            let config = BTreeConfig {
+                delta_encoding,
+                ..Default::default()
+            };
+            let mut btree = BTree::with_config(path.clone(), config).unwrap();
+            for &(k, v) in &entries {
+                btree.insert(k, v).unwrap();
+            }
+            btree.flush().unwrap();
+        }
+        let raw_len = std::fs::metadata(&raw_path).unwrap().len();
+        let delta_len = std::fs::metadata(&delta_path).unwrap().len();
+        assert!(
+            delta_len * 4 < raw_len,
+            "delta-encoded index should be much smaller: {} vs {}",
+            delta_len,
+            raw_len
+        );
+
+        // Pages are self-describing: a tree opened without delta encoding
+        // reads them back and rewrites touched pages raw
+        let mut btree = BTree::new(delta_path.clone()).unwrap();
+        assert_eq!(btree.scan().unwrap(), entries);
+        btree.insert(1, 0).unwrap();
+        btree.flush().unwrap();
+        drop(btree);
+
+        let btree = BTree::new(delta_path).unwrap();
+        assert_eq!(btree.get(&1).unwrap(), Some(0));
+        assert_eq!(btree.scan().unwrap()[1..], entries[..]);
+    }
+
     #[test]
     fn test_superblock_persistence() {
         let temp_dir = TempDir::new().unwrap();
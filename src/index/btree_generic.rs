@@ -37,15 +37,16 @@
 //! ```text
 //! [next_page_id: u64][data_len: u32][data: bytes...]
 //! ```text
+use crate::storage::backend::{self, BackendFile, OpenFlags};
 use crate::{Result, StorageError};
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{BTreeSet, HashSet};
-use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// Max page content size (upper bound for buffer allocation)
@@ -72,8 +73,14 @@ const OVERFLOW_DATA_SIZE: usize = PAGE_SIZE - OVERFLOW_PAGE_HEADER;
 /// Magic number for generic B+Tree files
 const BTREE_MAGIC: u32 = 0x47425452; // "GBTR" (Generic BTree)
 
-/// Format version (v3: compact page storage with page table)
-const BTREE_VERSION: u32 = 3;
+/// Format version (v3: compact page storage with page table; v4: same
+/// pages, column value keys in the canonical order-preserving encoding;
+/// v5: column value indexes also hold NULLs)
+const BTREE_VERSION: u32 = 5;
+
+/// Oldest format version still opened. Files keep their version until
+/// rewritten from scratch, so callers can tell which key encoding they hold.
+const MIN_BTREE_VERSION: u32 = 3;
 
 /// Type alias for page cache
 type PageCache<K> = Arc<RwLock<LruCache<u64, Arc<RwLock<Page<K>>>>>>;
@@ -107,7 +114,7 @@ pub struct GenericBTree<K: BTreeKey> {
     next_page_id: Arc<RwLock<u64>>,
 
     /// Storage file
-    storage_file: Arc<RwLock<File>>,
+    storage_file: Arc<RwLock<Box<dyn BackendFile>>>,
 
     /// Flush lock
     flush_lock: Arc<Mutex<()>>,
@@ -121,6 +128,9 @@ pub struct GenericBTree<K: BTreeKey> {
     /// Key size in bytes
     key_size: usize,
 
+    /// Format version of the file (see [`BTREE_VERSION`])
+    version: u32,
+
     /// Max keys per page (calculated based on key_size)
     max_keys: usize,
 
@@ -130,6 +140,9 @@ pub struct GenericBTree<K: BTreeKey> {
     /// Set of page IDs that are overflow pages (different format from B+Tree pages)
     overflow_page_ids: Arc<RwLock<HashSet<u64>>>,
 
+    /// Bytes written to the storage file since open (pages + superblock)
+    bytes_written: AtomicU64,
+
     _phantom: PhantomData<K>,
 }
 
@@ -620,16 +633,20 @@ impl<K: BTreeKey> GenericBTree<K> {
             )));
         }
 
-        let exists = storage_path.exists();
+        let backend = backend::backend_for(&storage_path);
+        let exists = backend.exists(&storage_path);
 
-        let mut file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(!exists)
-            .open(&storage_path)?;
+        let flags = if exists {
+            OpenFlags::read_write()
+        } else {
+            OpenFlags {
+                read: true,
+                ..OpenFlags::create_truncate()
+            }
+        };
+        let mut file = backend.open(&storage_path, flags)?;
 
-        let (root_page_id, next_page_id, page_offsets) = if !exists {
+        let (version, root_page_id, next_page_id, page_offsets) = if !exists {
             // New file: write superblock
             let superblock = SuperBlock {
                 magic: BTREE_MAGIC,
@@ -651,7 +668,7 @@ impl<K: BTreeKey> GenericBTree<K> {
             file.write_all(&header)?;
             file.sync_all()?;
 
-            (1u64, 2u64, vec![0u64])
+            (BTREE_VERSION, 1u64, 2u64, vec![0u64])
         } else {
             // Load superblock — read [len: u32 LE][data]
             let mut len_buf = [0u8; 4];
@@ -675,10 +692,10 @@ impl<K: BTreeKey> GenericBTree<K> {
                 return Err(StorageError::InvalidData("Invalid magic number".into()));
             }
 
-            if superblock.version != BTREE_VERSION {
+            if !(MIN_BTREE_VERSION..=BTREE_VERSION).contains(&superblock.version) {
                 return Err(StorageError::InvalidData(format!(
-                    "Unsupported BTree file version {} (expected {}). The index file at {:?} was created by a different version of MoteDB.",
-                    superblock.version, BTREE_VERSION, storage_path
+                    "Unsupported BTree file version {} (expected {}..={}). The index file at {:?} was created by a different version of MoteDB.",
+                    superblock.version, MIN_BTREE_VERSION, BTREE_VERSION, storage_path
                 )));
             }
 
@@ -690,6 +707,7 @@ impl<K: BTreeKey> GenericBTree<K> {
             }
 
             (
+                superblock.version,
                 superblock.root_page_id,
                 superblock.next_page_id,
                 superblock.page_offsets,
@@ -708,9 +726,11 @@ impl<K: BTreeKey> GenericBTree<K> {
             _storage_path: storage_path,
             config,
             key_size,
+            version,
             max_keys,
             page_offsets: Arc::new(RwLock::new(page_offsets)),
             overflow_page_ids: Arc::new(RwLock::new(HashSet::new())),
+            bytes_written: AtomicU64::new(0),
             _phantom: PhantomData,
         };
 
@@ -1242,7 +1262,7 @@ impl<K: BTreeKey> GenericBTree<K> {
 
         let superblock = SuperBlock {
             magic: BTREE_MAGIC,
-            version: BTREE_VERSION,
+            version: self.version,
             root_page_id: root_id,
             next_page_id: next_id,
             key_size: self.key_size as u32,
@@ -1262,6 +1282,7 @@ impl<K: BTreeKey> GenericBTree<K> {
 
         file.seek(SeekFrom::Start(0))?;
         file.write_all(&buf)?;
+        self.note_write(buf.len());
         file.sync_all()?;
 
         Ok(())
@@ -1319,9 +1340,10 @@ impl<K: BTreeKey> GenericBTree<K> {
             // Write overflow page to disk (append at file end)
             let mut file = self.storage_file.write();
 
-            let file_end = file.metadata()?.len().max(SUPERBLOCK_RESERVE);
+            let file_end = file.len()?.max(SUPERBLOCK_RESERVE);
             file.seek(SeekFrom::Start(file_end))?;
             file.write_all(&page_buf)?;
+            self.note_write(page_buf.len());
 
             // Record offset in page table and track as overflow page
             {
@@ -1368,7 +1390,6 @@ impl<K: BTreeKey> GenericBTree<K> {
                 offsets[idx]
             };
 
-            use std::os::unix::fs::FileExt;
             let file = self.storage_file.read();
 
             let mut page_buf = vec![0u8; PAGE_SIZE];
@@ -1451,6 +1472,98 @@ impl<K: BTreeKey> GenericBTree<K> {
         Ok(results)
     }
 
+    /// Ordered range query over keys only: at most `limit` keys in
+    /// `[start, end]`, smallest first (or largest first when `reverse`)
+    ///
+    /// Walks the tree itself rather than the (forward-only) leaf chain, so
+    /// paged and "newest N" lookups stop after N keys in either direction.
+    pub fn range_keys_with_limit(
+        &self,
+        start: &K,
+        end: &K,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<K>> {
+        let root_id = *self.root_page_id.read();
+
+        if root_id == 0 || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut results = Vec::with_capacity(limit.min(64));
+        self.collect_keys(root_id, start, end, &mut results, limit, reverse)?;
+        Ok(results)
+    }
+
+    fn collect_keys(
+        &self,
+        page_id: u64,
+        start: &K,
+        end: &K,
+        results: &mut Vec<K>,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<()> {
+        let page_arc = self.read_page_arc(page_id)?;
+        let page = page_arc.read();
+
+        if page.is_leaf {
+            let keys = &page.keys[..page.num_keys];
+            let ordered: Box<dyn Iterator<Item = &K>> = if reverse {
+                Box::new(keys.iter().rev())
+            } else {
+                Box::new(keys.iter())
+            };
+            for key in ordered {
+                if results.len() >= limit {
+                    return Ok(());
+                }
+                let (before_range, past_range) = if reverse {
+                    (key > end, key < start)
+                } else {
+                    (key < start, key > end)
+                };
+                if past_range {
+                    return Ok(());
+                }
+                if !before_range {
+                    results.push(key.clone());
+                }
+            }
+            return Ok(());
+        }
+
+        // Child i holds keys in [keys[i-1], keys[i])
+        let mut children: Vec<(usize, u64)> = page.children.iter().copied().enumerate().collect();
+        let separators = page.keys.clone();
+        drop(page);
+        drop(page_arc);
+        if reverse {
+            children.reverse();
+        }
+
+        for (i, child_id) in children {
+            if results.len() >= limit {
+                break;
+            }
+            let above_end = i > 0 && &separators[i - 1] > end;
+            let below_start = i < separators.len() && &separators[i] <= start;
+            match (above_end, below_start, reverse) {
+                (true, _, true) | (_, true, false) => continue,
+                (true, _, false) | (_, true, true) => break,
+                _ => {}
+            }
+            if child_id == 0 {
+                return Err(StorageError::Corruption(format!(
+                    "Invalid child_id=0 at page {}, child_idx={}",
+                    page_id, i
+                )));
+            }
+            self.collect_keys(child_id, start, end, results, limit, reverse)?;
+        }
+        Ok(())
+    }
+
     /// Scan leaf chain with early termination
     fn scan_leaf_chain_with_limit(
         &self,
@@ -1636,7 +1749,6 @@ impl<K: BTreeKey> GenericBTree<K> {
     /// Overflow pages have format [next_page_id:8][data_len:4][data...].
     /// B+Tree pages have content_len at bytes[13..15] in [HEADER_SIZE, PAGE_SIZE].
     fn reconstruct_overflow_ids(&self) {
-        use std::os::unix::fs::FileExt;
         let offsets = self.page_offsets.read();
         let file = self.storage_file.read();
         let mut overflow_ids = HashSet::new();
@@ -1664,6 +1776,21 @@ impl<K: BTreeKey> GenericBTree<K> {
         *self.next_page_id.read()
     }
 
+    /// Format version the file was created with
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Bytes written to the storage file since open (for write amplification)
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
+    fn note_write(&self, bytes: usize) {
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
     /// 🚀 Bulk-load sorted entries into a fresh B+Tree. O(N/B) sequential writes.
     /// Uses Page::serialize (same format as write_page) + sync_superblock
     /// (same format as normal path) — fully compatible with read_page.
@@ -1802,6 +1929,7 @@ impl<K: BTreeKey> GenericBTree<K> {
             let mut file = self.storage_file.write();
             file.seek(SeekFrom::Start(SUPERBLOCK_RESERVE))?;
             file.write_all(&write_buf)?;
+            self.note_write(write_buf.len());
             file.sync_all()?;
         }
 
@@ -1871,7 +1999,6 @@ impl<K: BTreeKey> GenericBTree<K> {
 
             // Load from disk using positional read (no write lock)
             match (|| -> Result<Page<K>> {
-                use std::os::unix::fs::FileExt;
                 let file = self.storage_file.read();
 
                 let mut header_buf = [0u8; HEADER_SIZE];
@@ -1902,7 +2029,7 @@ impl<K: BTreeKey> GenericBTree<K> {
             let page_offsets = self.page_offsets.read();
             let sb = SuperBlock {
                 magic: BTREE_MAGIC,
-                version: BTREE_VERSION,
+                version: self.version,
                 root_page_id: *self.root_page_id.read(),
                 next_page_id: *self.next_page_id.read(),
                 key_size: self.key_size as u32,
@@ -1916,6 +2043,19 @@ impl<K: BTreeKey> GenericBTree<K> {
         // Rewrite all pages sequentially after superblock
         let mut file = self.storage_file.write();
 
+        // Read overflow pages before anything is rewritten: the compacted
+        // node pages land on file ranges that may still hold them.
+        let mut overflow_pages: Vec<(u64, Vec<u8>)> = Vec::with_capacity(overflow_ids.len());
+        for overflow_id in &overflow_ids {
+            let idx = *overflow_id as usize;
+            if idx >= page_offsets_snapshot.len() || page_offsets_snapshot[idx] == 0 {
+                continue; // not on disk yet
+            }
+            let mut page_buf = vec![0u8; PAGE_SIZE];
+            file.read_exact_at(&mut page_buf, page_offsets_snapshot[idx])?;
+            overflow_pages.push((*overflow_id, page_buf));
+        }
+
         let page_start = sb_size as u64;
         let mut offset = page_start;
         let mut new_offsets = vec![0u64]; // index 0 = superblock
@@ -1930,6 +2070,7 @@ impl<K: BTreeKey> GenericBTree<K> {
 
             file.seek(SeekFrom::Start(offset))?;
             file.write_all(&buf)?;
+            self.note_write(buf.len());
 
             let idx = page_id as usize;
             if idx >= new_offsets.len() {
@@ -1944,25 +2085,14 @@ impl<K: BTreeKey> GenericBTree<K> {
             cache.put(page_id, Arc::new(RwLock::new(working)));
         }
 
-        // Copy overflow pages to their new positions in the compacted file.
+        // Write overflow pages to their new positions in the compacted file.
         // Overflow pages are PAGE_SIZE bytes with format [next_page_id:8][data_len:4][data...].
-        for overflow_id in &overflow_ids {
-            let old_offset = {
-                let idx = *overflow_id as usize;
-                if idx >= page_offsets_snapshot.len() || page_offsets_snapshot[idx] == 0 {
-                    continue; // not on disk yet
-                }
-                page_offsets_snapshot[idx]
-            };
-
-            // Read overflow page from its old position and rewrite at new offset
-            use std::os::unix::fs::FileExt;
-            let mut page_buf = vec![0u8; PAGE_SIZE];
-            file.read_exact_at(&mut page_buf, old_offset)?;
+        for (overflow_id, page_buf) in overflow_pages {
             file.seek(SeekFrom::Start(offset))?;
             file.write_all(&page_buf)?;
+            self.note_write(page_buf.len());
 
-            let idx = *overflow_id as usize;
+            let idx = overflow_id as usize;
             if idx >= new_offsets.len() {
                 new_offsets.resize(idx + 1, 0);
             }
@@ -2014,10 +2144,11 @@ impl<K: BTreeKey> GenericBTree<K> {
 
         let mut file = self.storage_file.write();
 
-        let file_end = file.metadata()?.len().max(SUPERBLOCK_RESERVE);
+        let file_end = file.len()?.max(SUPERBLOCK_RESERVE);
 
         file.seek(SeekFrom::Start(file_end))?;
         file.write_all(&buf)?;
+        self.note_write(buf.len());
 
         // Record offset in page table
         {
@@ -2093,7 +2224,6 @@ impl<K: BTreeKey> GenericBTree<K> {
         // Use positional read (pread) instead of seek+read to avoid holding
         // the write lock on the storage file. This allows concurrent B+Tree reads
         // to proceed in parallel without serializing on the file lock.
-        use std::os::unix::fs::FileExt;
         let file = self.storage_file.read();
 
         // Read header to get content_len
@@ -2246,4 +2376,57 @@ mod tests {
         let result = tree.get(&1u32).unwrap();
         assert_eq!(result, Some(large_value));
     }
+
+    #[test]
+    fn test_flush_keeps_overflow_values() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.gbtree");
+
+        let mut tree = GenericBTree::<u32>::new(path.clone()).unwrap();
+        // The overflow chain sits near the front of the file, where flush()
+        // writes the compacted node pages
+        let value: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        tree.insert(0, value.clone()).unwrap();
+        for k in 1..2000u32 {
+            tree.insert(k, vec![k as u8; 100]).unwrap();
+        }
+        tree.flush().unwrap();
+        assert_eq!(tree.get(&0).unwrap(), Some(value.clone()));
+        drop(tree);
+
+        let tree = GenericBTree::<u32>::new(path).unwrap();
+        assert_eq!(tree.get(&0).unwrap(), Some(value));
+        assert_eq!(tree.get(&1999).unwrap(), Some(vec![1999u32 as u8; 100]));
+    }
+
+    #[test]
+    fn test_range_keys_with_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.gbtree");
+
+        let mut tree = GenericBTree::<u32>::new(path).unwrap();
+        // Enough keys for several levels of internal pages
+        for k in (0..5000u32).map(|i| (i * 7919) % 5000) {
+            tree.insert(k, vec![]).unwrap();
+        }
+
+        let newest = tree.range_keys_with_limit(&0, &u32::MAX, 5, true).unwrap();
+        assert_eq!(newest, vec![4999, 4998, 4997, 4996, 4995]);
+        let oldest = tree.range_keys_with_limit(&2, &u32::MAX, 3, false).unwrap();
+        assert_eq!(oldest, vec![2, 3, 4]);
+
+        let window = tree
+            .range_keys_with_limit(&1000, &2000, 10_000, true)
+            .unwrap();
+        assert_eq!(window, (1000..=2000).rev().collect::<Vec<_>>());
+        let window = tree
+            .range_keys_with_limit(&1000, &2000, 10_000, false)
+            .unwrap();
+        assert_eq!(window, (1000..=2000).collect::<Vec<_>>());
+
+        assert!(tree
+            .range_keys_with_limit(&10, &5, 10, true)
+            .unwrap()
+            .is_empty());
+    }
 }
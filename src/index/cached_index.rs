@@ -31,8 +31,12 @@ enum FastKey {
     Bool(bool),
     Text(Arc<str>),
     Timestamp(u64),
+    Decimal(crate::types::Decimal), // value-based Eq/Hash: 1.5 == 1.50
+    Uuid(crate::types::Uuid),
+    Date(i32),
+    Time(i64),
     Null,
-    Complex(u8), // tag for vector/tensor/spatial/textdoc (not looked up)
+    Complex(u8), // tag for vector/tensor/spatial/textdoc/array (not looked up)
 }
 
 impl Hash for FastKey {
@@ -44,6 +48,10 @@ impl Hash for FastKey {
             FastKey::Bool(b) => b.hash(state),
             FastKey::Text(s) => s.hash(state),
             FastKey::Timestamp(ts) => ts.hash(state),
+            FastKey::Decimal(d) => d.hash(state),
+            FastKey::Uuid(u) => u.hash(state),
+            FastKey::Date(d) => d.hash(state),
+            FastKey::Time(t) => t.hash(state),
             FastKey::Null | FastKey::Complex(_) => {}
         }
     }
@@ -57,6 +65,10 @@ impl PartialEq for FastKey {
             (FastKey::Bool(a), FastKey::Bool(b)) => a == b,
             (FastKey::Text(a), FastKey::Text(b)) => a == b, // full string comparison
             (FastKey::Timestamp(a), FastKey::Timestamp(b)) => a == b,
+            (FastKey::Decimal(a), FastKey::Decimal(b)) => a == b,
+            (FastKey::Uuid(a), FastKey::Uuid(b)) => a == b,
+            (FastKey::Date(a), FastKey::Date(b)) => a == b,
+            (FastKey::Time(a), FastKey::Time(b)) => a == b,
             (FastKey::Null, FastKey::Null) => true,
             (FastKey::Complex(a), FastKey::Complex(b)) => a == b,
             _ => false,
@@ -66,10 +78,19 @@ impl PartialEq for FastKey {
 
 impl Eq for FastKey {}
 
+/// -2^63, exactly representable as f64
+const I64_MIN: f64 = i64::MIN as f64;
+
 impl FastKey {
     fn from_value(value: &Value) -> Self {
         match value {
             Value::Integer(i) => FastKey::Integer(*i),
+            // Index keys put integers and floats in one key space: an
+            // integral float looks up the same rows as the integer
+            Value::Float(f) if f.fract() == 0.0 && (I64_MIN..-I64_MIN).contains(f) => {
+                FastKey::Integer(*f as i64)
+            }
+            Value::Float(f) if f.is_nan() => FastKey::Float(f64::NAN.to_bits()),
             Value::Float(f) => FastKey::Float(f.to_bits()),
             Value::Bool(b) => FastKey::Bool(*b),
             Value::Text(s) => FastKey::Text(Arc::clone(&s.0)), // unwrap ArcString -> Arc<str>
@@ -79,6 +100,11 @@ impl FastKey {
             Value::Tensor(_) => FastKey::Complex(8),
             Value::Spatial(_) => FastKey::Complex(9),
             Value::TextDoc(_) => FastKey::Complex(10),
+            Value::Decimal(d) => FastKey::Decimal(**d),
+            Value::Uuid(u) => FastKey::Uuid(**u),
+            Value::Date(d) => FastKey::Date(d.days()),
+            Value::Time(t) => FastKey::Time(t.as_micros()),
+            Value::Array(_) => FastKey::Complex(11),
         }
     }
 }
@@ -93,6 +119,7 @@ pub struct CachedIndex {
     cache: RwLock<LruCache<FastKey, Arc<Vec<RowId>>>>,
     hit_count: AtomicU64,
     miss_count: AtomicU64,
+    eviction_count: AtomicU64,
 }
 
 impl CachedIndex {
@@ -104,6 +131,7 @@ impl CachedIndex {
             cache: RwLock::new(LruCache::new(capacity)),
             hit_count: AtomicU64::new(0),
             miss_count: AtomicU64::new(0),
+            eviction_count: AtomicU64::new(0),
         }
     }
 
@@ -122,6 +150,9 @@ impl CachedIndex {
     pub fn put(&self, key: Value, ids: Vec<RowId>) {
         let fk = FastKey::from_value(&key);
         let mut cache = self.cache.write();
+        if cache.len() == cache.cap().get() && !cache.contains(&fk) {
+            self.eviction_count.fetch_add(1, Ordering::Relaxed);
+        }
         cache.put(fk, Arc::new(ids));
     }
 
@@ -151,6 +182,7 @@ impl CachedIndex {
             size: cache.len(),
             hits: self.hit_count.load(Ordering::Relaxed),
             misses: self.miss_count.load(Ordering::Relaxed),
+            evictions: self.eviction_count.load(Ordering::Relaxed),
             hit_rate: self.hit_rate(),
         }
     }
@@ -161,6 +193,7 @@ impl CachedIndex {
         cache.clear();
         self.hit_count.store(0, Ordering::Relaxed);
         self.miss_count.store(0, Ordering::Relaxed);
+        self.eviction_count.store(0, Ordering::Relaxed);
     }
 
     /// Invalidate a key
@@ -196,6 +229,7 @@ pub struct CacheStats {
     pub size: usize,
     pub hits: u64,
     pub misses: u64,
+    pub evictions: u64,
     pub hit_rate: f64,
 }
 
@@ -254,6 +288,7 @@ mod tests {
         assert_eq!(*cache.get(&Value::Integer(1)).unwrap(), vec![100]);
         assert_eq!(*cache.get(&Value::Integer(3)).unwrap(), vec![300]);
         assert_eq!(cache.get(&Value::Integer(2)), None);
+        assert_eq!(cache.stats().evictions, 1);
     }
 
     #[test]
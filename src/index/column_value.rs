@@ -4,6 +4,10 @@
 //! - WHERE col = value (point query)
 //! - WHERE col >= start AND col <= end (range query)
 //!
+//! On ARRAY columns the index is multi-key: every non-NULL element gets its
+//! own entry, so a point lookup on an element finds the rows whose array
+//! contains it (`ARRAY_CONTAINS(col, v)`).
+//!
 //! Uses B-Tree for persistent storage with efficient range queries.
 //! Uses IndexMemBuffer for lock-free reads: writes go to an in-memory
 //! BTreeMap, reads check the buffer first (no btree lock needed).
@@ -21,10 +25,10 @@
 use crate::database::mem_buffer::IndexMemBuffer;
 use crate::index::btree_generic::{BTreeKey, GenericBTree, GenericBTreeConfig};
 use crate::index::cached_index::CachedIndex;
-use crate::types::{RowId, Value};
+use crate::types::{RowId, RowIdSet, Value};
 use crate::{Result, StorageError};
 use parking_lot::{Mutex, RwLock};
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -44,6 +48,9 @@ pub struct ColumnValueIndexConfig {
     /// Higher values reduce B+Tree write amplification at the cost of memory.
     /// Default: 2 (drain only when 2+ immutable buffers accumulated).
     pub drain_threshold: usize,
+    /// Hard cap on buffered bytes (active + immutable). Writers that reach it
+    /// block on a forced drain to the B+Tree. Default: 4 × `mem_buffer_size`.
+    pub mem_buffer_max_size: Option<usize>,
 }
 
 impl Default for ColumnValueIndexConfig {
@@ -53,18 +60,133 @@ impl Default for ColumnValueIndexConfig {
             cache_size: 1024,
             mem_buffer_size: 1024 * 1024, // 1MB
             drain_threshold: 2,
+            mem_buffer_max_size: None,
         }
     }
 }
 
 /// Compact key layout: [value_data: 64B zero-padded][row_id: 8B BE][value_len: 2B BE] = 74 bytes
-/// - Integer/Float/Timestamp: value_data = 8 bytes BE + 56 bytes zero pad
+/// - Integer/Float: value_data = 16 bytes numeric key (see [`encode_key`]) + 48 bytes zero pad
+/// - Timestamp/Time: value_data = 8 bytes BE + 56 bytes zero pad
 /// - Text: value_data = up to 64 bytes UTF-8 + zero pad
 /// - Bool: value_data = 1 byte + 63 bytes zero pad
+/// - NULL: value_data = [`NULL_KEY`]
 const VALUE_DATA_SIZE: usize = 64;
 const ROW_ID_SIZE: usize = 8;
 const VALUE_LEN_SIZE: usize = 2;
 
+/// B-Tree format version from which keys use [`encode_key`] and NULLs are
+/// indexed. Older index files are rebuilt on open.
+const CANONICAL_KEYS_VERSION: u32 = 5;
+
+/// Key of the NULL bucket. No value encodes to all 0xFF (UTF-8 never holds
+/// the byte), so NULLs get a bucket of their own after every value.
+pub(crate) const NULL_KEY: [u8; VALUE_DATA_SIZE] = [0xFF; VALUE_DATA_SIZE];
+
+/// Upper bound of every non-NULL key: value scans and ranges stop short of
+/// the NULL bucket, which only `IS NULL` lookups read
+const MAX_VALUE_KEY: [u8; VALUE_DATA_SIZE] = {
+    let mut key = NULL_KEY;
+    key[VALUE_DATA_SIZE - 1] = 0xFE;
+    key
+};
+
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Bits every NaN is keyed as: a positive quiet NaN, sorting after +inf
+const CANONICAL_NAN: u64 = 0x7FF8_0000_0000_0000;
+
+/// f64 bits whose unsigned order is numeric order: negatives have all
+/// bits flipped, positives only the sign bit
+fn sortable_f64(f: f64) -> u64 {
+    let bits = if f.is_nan() {
+        CANONICAL_NAN
+    } else if f == 0.0 {
+        0 // -0.0 is keyed as 0.0
+    } else {
+        f.to_bits()
+    };
+    if bits & SIGN_BIT != 0 {
+        !bits
+    } else {
+        bits ^ SIGN_BIT
+    }
+}
+
+fn f64_from_sortable(sortable: u64) -> f64 {
+    f64::from_bits(if sortable & SIGN_BIT != 0 {
+        sortable ^ SIGN_BIT
+    } else {
+        !sortable
+    })
+}
+
+/// Canonical order-preserving key encoding: the byte order of encoded keys
+/// is the value order, so every range is one contiguous key range.
+///
+/// - INTEGER and FLOAT share one key space, so `x > 2.5` on an INTEGER
+///   column and `x = 3` on a FLOAT column find the stored values: the
+///   value rounded to f64 (sortable bits), then its integer distance from
+///   that rounding (sign-flipped), which orders integers beyond 2^53.
+///   -0.0 is keyed as 0.0 and every NaN as one NaN after +inf.
+/// - TIMESTAMP and TIME: microseconds, sign-flipped big-endian
+/// - TEXT: UTF-8 bytes, cut to 64; BOOL: one byte
+/// - DECIMAL, UUID and DATE: their own sort keys
+/// - NULL: [`NULL_KEY`]
+pub(crate) fn encode_key(value: &Value) -> Result<[u8; VALUE_DATA_SIZE]> {
+    let mut buf = [0u8; VALUE_DATA_SIZE];
+    match value {
+        Value::Integer(i) => {
+            let rounded = *i as f64;
+            let offset = (*i as i128 - rounded as i128) as i64;
+            buf[..8].copy_from_slice(&sortable_f64(rounded).to_be_bytes());
+            buf[8..16].copy_from_slice(&(offset as u64 ^ SIGN_BIT).to_be_bytes());
+        }
+        Value::Float(f) => {
+            buf[..8].copy_from_slice(&sortable_f64(*f).to_be_bytes());
+            buf[8..16].copy_from_slice(&SIGN_BIT.to_be_bytes());
+        }
+        Value::Timestamp(ts) => {
+            buf[..8].copy_from_slice(&(ts.as_micros() as u64 ^ SIGN_BIT).to_be_bytes())
+        }
+        Value::Bool(b) => buf[0] = if *b { 1 } else { 0 },
+        Value::Text(s) => {
+            let raw = s.as_bytes();
+            let len = raw.len().min(VALUE_DATA_SIZE);
+            buf[..len].copy_from_slice(&raw[..len]);
+        }
+        Value::Decimal(d) => d.write_sort_key(&mut buf),
+        Value::Uuid(u) => buf[..16].copy_from_slice(u.as_bytes()),
+        Value::Date(d) => buf[..4].copy_from_slice(&d.sort_key()),
+        Value::Time(t) => {
+            buf[..8].copy_from_slice(&(t.as_micros() as u64 ^ SIGN_BIT).to_be_bytes())
+        }
+        Value::Null => buf = NULL_KEY,
+        _ => {
+            return Err(StorageError::InvalidData(format!(
+                "Unsupported value type for indexing: {:?}",
+                value
+            )));
+        }
+    };
+    Ok(buf)
+}
+
+/// Signed integer of a sign-flipped big-endian key
+fn i64_from_key(bytes: &[u8]) -> i64 {
+    (u64::from_be_bytes(bytes[..8].try_into().unwrap_or([0; 8])) ^ SIGN_BIT) as i64
+}
+
+/// Text bounds longer than a key are cut to their 64-byte prefix, which
+/// longer values on the far side of the bound share. Such a bound is
+/// treated as inclusive; callers re-check the values they fetch.
+fn truncated_bound(bound: &Value) -> bool {
+    matches!(bound, Value::Text(s) if s.len() > VALUE_DATA_SIZE)
+}
+
+/// Keys read per page by [`ColumnRangeIter`]
+const RANGE_PAGE_KEYS: usize = 1024;
+
 /// Key for the B-Tree: (column_value, row_id)
 /// value_bytes is a fixed 64-byte stack array — zero heap allocation on clone.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -80,6 +202,60 @@ impl std::hash::Hash for IndexKey {
     }
 }
 
+/// Resume position for [`ColumnValueIndex::scan_page`]: the last key returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexCursor(IndexKey);
+
+impl IndexCursor {
+    /// Row the cursor points at
+    pub fn row_id(&self) -> RowId {
+        self.0.row_id
+    }
+
+    /// Opaque text form: hex row id followed by the value bytes, trailing
+    /// zero padding trimmed
+    pub fn to_token(&self) -> String {
+        let used = self
+            .0
+            .value_bytes
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |i| i + 1);
+        let mut token = format!("{:016x}", self.0.row_id);
+        for b in &self.0.value_bytes[..used] {
+            token.push_str(&format!("{:02x}", b));
+        }
+        token
+    }
+
+    /// Parse a token produced by [`IndexCursor::to_token`]
+    pub fn from_token(token: &str) -> Result<Self> {
+        let invalid = || StorageError::InvalidArgument(format!("Invalid cursor token '{}'", token));
+        let bytes = token.as_bytes();
+        if bytes.len() < 16
+            || !bytes.len().is_multiple_of(2)
+            || bytes.len() > 16 + VALUE_DATA_SIZE * 2
+            || !token.is_ascii()
+        {
+            return Err(invalid());
+        }
+        let row_id = RowId::from_str_radix(&token[..16], 16).map_err(|_| invalid())?;
+        let mut value_bytes = [0u8; VALUE_DATA_SIZE];
+        for (i, slot) in value_bytes
+            .iter_mut()
+            .take((bytes.len() - 16) / 2)
+            .enumerate()
+        {
+            let at = 16 + i * 2;
+            *slot = u8::from_str_radix(&token[at..at + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(IndexCursor(IndexKey {
+            value_bytes,
+            row_id,
+        }))
+    }
+}
+
 /// Normalize an IndexKey for tombstone operations. With fixed-size value_bytes,
 /// this is a simple stack copy — no heap allocation.
 fn tombstone_key(key: &IndexKey) -> IndexKey {
@@ -176,6 +352,12 @@ pub struct ColumnValueIndex {
     /// The async pipeline checks this flag; if false, the index is already
     /// up-to-date from synchronous INSERT/UPDATE/DELETE paths.
     needs_rebuild: std::sync::atomic::AtomicBool,
+    /// Key bytes submitted by insert/update/bulk load since open
+    bytes_indexed: std::sync::atomic::AtomicU64,
+    /// Declared column type, when known. DECIMAL columns coerce numeric
+    /// lookups so that `amount > 10` hits the same key space as stored values.
+    /// ARRAY columns make the index multi-key (one entry per element).
+    col_type: Option<crate::types::ColumnType>,
 }
 
 impl ColumnValueIndex {
@@ -196,6 +378,10 @@ impl ColumnValueIndex {
         };
 
         let btree = GenericBTree::with_config(storage_path.clone(), btree_config)?;
+        let mut mem_buffer = IndexMemBuffer::new(config.mem_buffer_size);
+        if let Some(max_size) = config.mem_buffer_max_size {
+            mem_buffer = mem_buffer.with_max_size(max_size);
+        }
 
         Ok(Self {
             _table_name: table_name,
@@ -203,15 +389,23 @@ impl ColumnValueIndex {
             _storage_path: storage_path,
             btree: Arc::new(RwLock::new(btree)),
             lru_cache: Arc::new(CachedIndex::new(500)),
-            mem_buffer: IndexMemBuffer::new(config.mem_buffer_size),
+            mem_buffer,
             tombstones: Mutex::new(HashSet::new()),
             pending_deletes: Mutex::new(Vec::new()),
             drain_lock: Mutex::new(()),
             drain_threshold: config.drain_threshold,
             needs_rebuild: std::sync::atomic::AtomicBool::new(true),
+            bytes_indexed: std::sync::atomic::AtomicU64::new(0),
+            col_type: None,
         })
     }
 
+    /// Attach the declared column type (builder style)
+    pub fn with_column_type(mut self, col_type: crate::types::ColumnType) -> Self {
+        self.col_type = Some(col_type);
+        self
+    }
+
     /// Open an existing index from disk.
     ///
     /// Unlike `create()`, this marks `needs_rebuild = false` because the on-disk
@@ -230,8 +424,67 @@ impl ColumnValueIndex {
         Ok(index)
     }
 
+    /// Whether the index file predates the canonical key encoding and must
+    /// be rebuilt before it answers queries
+    pub fn has_legacy_keys(&self) -> bool {
+        self.btree.read().version() < CANONICAL_KEYS_VERSION
+    }
+
+    /// Whether this indexes the elements of an ARRAY column
+    pub fn is_multi_key(&self) -> bool {
+        matches!(self.col_type, Some(crate::types::ColumnType::Array(_)))
+    }
+
+    /// Elements to index for a stored ARRAY value: distinct and non-NULL.
+    /// Text is a serialized array (columnar scans hand those over). `None`
+    /// when the index is not multi-key.
+    fn array_elements(&self, value: &Value) -> Option<Vec<Value>> {
+        let ct = self.col_type.as_ref().filter(|_| self.is_multi_key())?;
+        let items = match value {
+            Value::Array(items) => items.to_vec(),
+            Value::Text(s) => match ct.value_from_text(s) {
+                Value::Array(items) => *items,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        let mut elements: Vec<Value> = Vec::with_capacity(items.len());
+        for item in items {
+            if !matches!(item, Value::Null) && !elements.contains(&item) {
+                elements.push(item);
+            }
+        }
+        Some(elements)
+    }
+
+    /// Expand stored values into (key value, row_id) entries
+    fn expand_entries(&self, items: Vec<(Value, RowId)>) -> Vec<(Value, RowId)> {
+        if !self.is_multi_key() {
+            return items;
+        }
+        items
+            .into_iter()
+            .flat_map(|(value, row_id)| {
+                self.array_elements(&value)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |e| (e, row_id))
+            })
+            .collect()
+    }
+
     /// Insert a value → row_id mapping
     pub fn insert(&self, value: &Value, row_id: RowId) -> Result<()> {
+        if let Some(elements) = self.array_elements(value) {
+            for element in &elements {
+                self.insert_key(element, row_id)?;
+            }
+            return Ok(());
+        }
+        self.insert_key(value, row_id)
+    }
+
+    fn insert_key(&self, value: &Value, row_id: RowId) -> Result<()> {
         let value_bytes = self.value_to_bytes(value)?;
         let key = IndexKey {
             value_bytes,
@@ -243,17 +496,13 @@ impl ColumnValueIndex {
             .mem_buffer
             .insert(key.clone(), ())
             .map_err(StorageError::InvalidData)?;
+        self.note_indexed(1);
 
         // Re-insert cancels any pending tombstone — must succeed (blocking).
         // A skipped tombstone removal would leave the re-inserted key invisible.
         self.tombstones.lock().remove(&tombstone_key(&key));
 
-        // If buffer is full, drain immutable buffers to btree (non-blocking)
-        if full {
-            if let Some(_guard) = self.drain_lock.try_lock() {
-                self.drain_immutable_to_btree()?;
-            }
-        }
+        self.drain_after_write(full)?;
 
         // Invalidate LRU cache — skip if cache is empty or lock is contended
         self.lru_cache.try_invalidate(value);
@@ -269,6 +518,8 @@ impl ColumnValueIndex {
             return Ok(());
         }
 
+        let entries = self.expand_entries(entries.to_vec());
+
         // Pre-serialize all values to IndexKey bytes.
         let keys: Vec<IndexKey> = entries
             .iter()
@@ -314,6 +565,7 @@ impl ColumnValueIndex {
             keys.sort_unstable();
         }
         keys.dedup();
+        self.note_indexed(keys.len());
         let mut btree = self.btree.write();
         btree.bulk_load(keys)?;
         Ok(())
@@ -322,6 +574,18 @@ impl ColumnValueIndex {
     /// Atomic update: delete old_value→row_id and insert new_value→row_id.
     /// Acquires locks once instead of twice, and drains at most once.
     pub fn update(&self, old_value: &Value, new_value: &Value, row_id: RowId) -> Result<()> {
+        if let (Some(old), Some(new)) = (
+            self.array_elements(old_value),
+            self.array_elements(new_value),
+        ) {
+            for element in old.iter().filter(|e| !new.contains(e)) {
+                self.delete_key(element, row_id)?;
+            }
+            for element in new.iter().filter(|e| !old.contains(e)) {
+                self.insert_key(element, row_id)?;
+            }
+            return Ok(());
+        }
         let old_value_bytes = self.value_to_bytes(old_value)?;
         let new_value_bytes = self.value_to_bytes(new_value)?;
         let old_key = IndexKey {
@@ -361,13 +625,10 @@ impl ColumnValueIndex {
             .mem_buffer
             .insert(new_key.clone(), ())
             .map_err(StorageError::InvalidData)?;
+        self.note_indexed(1);
 
         // 5. Drain if buffer is full OR pending_deletes accumulated too many
-        if full || pending_len > 10_000 {
-            if let Some(_guard) = self.drain_lock.try_lock() {
-                self.drain_immutable_to_btree()?;
-            }
-        }
+        self.drain_after_write(full || pending_len > 10_000)?;
 
         // 6. Invalidate LRU cache — non-blocking
         self.lru_cache.try_invalidate(old_value);
@@ -378,6 +639,7 @@ impl ColumnValueIndex {
 
     /// Batch insert for improved performance
     pub fn batch_insert(&self, items: Vec<(Value, RowId)>) -> Result<()> {
+        let items = self.expand_entries(items);
         if items.is_empty() {
             return Ok(());
         }
@@ -396,6 +658,7 @@ impl ColumnValueIndex {
             .collect::<Result<Vec<_>>>()?;
 
         keys.sort_by(|a, b| a.0.value_bytes.cmp(&b.0.value_bytes));
+        self.note_indexed(keys.len());
 
         // Cancel tombstones for all keys in one lock acquisition (normalized keys)
         {
@@ -412,11 +675,7 @@ impl ColumnValueIndex {
             .mem_buffer
             .batch_insert(buffer_entries)
             .map_err(StorageError::InvalidData)?;
-        if full {
-            if let Some(_guard) = self.drain_lock.try_lock() {
-                self.drain_immutable_to_btree()?;
-            }
-        }
+        self.drain_after_write(full)?;
 
         // Invalidate cache entries (non-locking)
         for (_, value) in &keys {
@@ -451,7 +710,7 @@ impl ColumnValueIndex {
         // order tombstones → btree. Readers must follow the same order.
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // Collect from mem_buffer (filter tombstones inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -511,7 +770,7 @@ impl ColumnValueIndex {
         // 🔒 tombstones before btree — consistent with flush_buffer/deletion lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Check mem buffer (filter tombstones inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -564,7 +823,7 @@ impl ColumnValueIndex {
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::with_capacity(64);
-        let mut seen: HashSet<u64> = HashSet::with_capacity(64);
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter tombstones inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -597,14 +856,14 @@ impl ColumnValueIndex {
             row_id: 0,
         };
         let max_key = IndexKey {
-            value_bytes: [0xFFu8; VALUE_DATA_SIZE],
+            value_bytes: MAX_VALUE_KEY,
             row_id: RowId::MAX,
         };
 
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter tombstones inline)
         let buffer_results = self.mem_buffer.range(&min_key, &max_key);
@@ -634,6 +893,266 @@ impl ColumnValueIndex {
         Ok(row_ids)
     }
 
+    /// Scan keys in value order, one page at a time
+    ///
+    /// Returns up to `limit` keys strictly past `after` (or from the first
+    /// key when `None`) in ascending order, or descending when `descending`,
+    /// plus the cursor for the next page, `None` once exhausted. Deleted
+    /// rows are dropped after paging, so a page may come back short while
+    /// still carrying a cursor. Each returned cursor can resume the scan
+    /// right after its row.
+    pub fn scan_page(
+        &self,
+        after: Option<&IndexCursor>,
+        limit: usize,
+        descending: bool,
+    ) -> Result<(Vec<IndexCursor>, Option<IndexCursor>)> {
+        let min_key = IndexKey {
+            value_bytes: [0u8; VALUE_DATA_SIZE],
+            row_id: 0,
+        };
+        let max_key = IndexKey {
+            value_bytes: MAX_VALUE_KEY,
+            row_id: RowId::MAX,
+        };
+        let (start_key, end_key) = match (after, descending) {
+            (Some(cursor), true) => (min_key, cursor.0.clone()),
+            (Some(cursor), false) => (cursor.0.clone(), max_key),
+            (None, _) => (min_key, max_key),
+        };
+        let past = |key: &IndexKey| match after {
+            None => true,
+            Some(c) if descending => *key < c.0,
+            Some(c) => *key > c.0,
+        };
+        let order = |a: &IndexKey, b: &IndexKey| {
+            if descending {
+                b.cmp(a)
+            } else {
+                a.cmp(b)
+            }
+        };
+
+        // 🔒 tombstones before btree — consistent lock order
+        let tombstones = self.tombstones.lock();
+
+        // 1. Mem buffer: first `limit` keys past the cursor
+        let mut buffered: Vec<IndexKey> = self
+            .mem_buffer
+            .range(&start_key, &end_key)
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| past(key))
+            .collect();
+        buffered.sort_unstable_by(order);
+        buffered.truncate(limit);
+
+        // 2. Btree: ordered walk, one extra key in case the cursor itself comes back
+        let mut keys = {
+            let btree = self.btree.read();
+            btree.range_keys_with_limit(&start_key, &end_key, limit + 1, descending)?
+        };
+        keys.retain(|key| past(key));
+        keys.truncate(limit);
+
+        keys.extend(buffered);
+        keys.sort_unstable_by(order);
+        keys.dedup();
+        keys.truncate(limit);
+
+        let next = if keys.len() == limit {
+            keys.last().cloned().map(IndexCursor)
+        } else {
+            None
+        };
+        let page = keys
+            .into_iter()
+            .filter(|key| !tombstones.contains(&tombstone_key(key)))
+            .map(IndexCursor)
+            .collect();
+        drop(tombstones);
+        Ok((page, next))
+    }
+
+    /// Dual-bound range query like [`Self::query_between`], streamed: row
+    /// ids come back in index order and only one page of keys is held at a
+    /// time, so a wide range costs no more memory than a narrow one
+    pub fn query_between_iter(
+        self: &Arc<Self>,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        self.range_iter(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+            false,
+        )
+    }
+
+    /// [`Self::query_between_iter`] walking the range from the largest
+    /// value down, for `ORDER BY col DESC` over a range
+    pub fn query_between_iter_rev(
+        self: &Arc<Self>,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<ColumnRangeIter> {
+        self.range_iter(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+            true,
+        )
+    }
+
+    /// Every non-NULL row in value order (largest first when `descending`),
+    /// streamed like [`Self::query_between_iter`]
+    pub fn scan_iter(self: &Arc<Self>, descending: bool) -> Result<ColumnRangeIter> {
+        let range = KeyRange {
+            start: IndexKey {
+                value_bytes: [0u8; VALUE_DATA_SIZE],
+                row_id: 0,
+            },
+            end: IndexKey {
+                value_bytes: MAX_VALUE_KEY,
+                row_id: RowId::MAX,
+            },
+            lower_inclusive: true,
+            upper_inclusive: true,
+        };
+        Ok(self.iter_over(vec![Some(range)], descending))
+    }
+
+    fn range_iter(
+        self: &Arc<Self>,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+        descending: bool,
+    ) -> Result<ColumnRangeIter> {
+        let range = self.key_range(lower_bound, lower_inclusive, upper_bound, upper_inclusive)?;
+        Ok(self.iter_over(vec![range], descending))
+    }
+
+    /// Iterator over `ranges`, given in ascending order
+    fn iter_over(
+        self: &Arc<Self>,
+        ranges: Vec<Option<KeyRange>>,
+        descending: bool,
+    ) -> ColumnRangeIter {
+        let mut ranges: VecDeque<KeyRange> = ranges.into_iter().flatten().collect();
+        if descending {
+            ranges.make_contiguous().reverse();
+        }
+        ColumnRangeIter {
+            index: Arc::clone(self),
+            ranges,
+            descending,
+            after: None,
+            rows: VecDeque::new(),
+            seen: self.is_multi_key().then(RowIdSet::new),
+        }
+    }
+
+    /// Keys bounding `lower..upper`, `None` when the range is empty
+    fn key_range(
+        &self,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<Option<KeyRange>> {
+        let lower_inclusive = lower_inclusive || truncated_bound(lower_bound);
+        let upper_inclusive = upper_inclusive || truncated_bound(upper_bound);
+        let start = IndexKey {
+            value_bytes: self.value_to_bytes(lower_bound)?,
+            row_id: if lower_inclusive { 0 } else { RowId::MAX },
+        };
+        let end = IndexKey {
+            value_bytes: self.value_to_bytes(upper_bound)?,
+            row_id: RowId::MAX,
+        };
+        Ok((start <= end).then_some(KeyRange {
+            start,
+            end,
+            lower_inclusive,
+            upper_inclusive,
+        }))
+    }
+
+    /// Up to [`RANGE_PAGE_KEYS`] btree keys of `range` past `after`, merged
+    /// with the buffered keys they span, minus deleted ones. Also returns
+    /// the key to resume after, `None` once the range is exhausted. With
+    /// `descending` the range is walked from its end and keys come back
+    /// largest first.
+    fn range_page(
+        &self,
+        range: &KeyRange,
+        after: Option<&IndexKey>,
+        descending: bool,
+    ) -> Result<(Vec<IndexKey>, Option<IndexKey>)> {
+        let (start, end) = match (after, descending) {
+            (Some(a), true) => (&range.start, a),
+            (Some(a), false) => (a, &range.end),
+            (None, _) => (&range.start, &range.end),
+        };
+        let past = |key: &IndexKey| match after {
+            None => true,
+            Some(a) if descending => key < a,
+            Some(a) => key > a,
+        };
+        let accept = |key: &IndexKey| {
+            (range.lower_inclusive || key.value_bytes != range.start.value_bytes)
+                && (range.upper_inclusive || key.value_bytes != range.end.value_bytes)
+        };
+
+        // 🔒 tombstones before btree — consistent lock order
+        let tombstones = self.tombstones.lock();
+
+        // 1. Btree: ordered walk, one extra key in case `after` comes back
+        let mut keys = {
+            let btree = self.btree.read();
+            btree.range_keys_with_limit(start, end, RANGE_PAGE_KEYS + 1, descending)?
+        };
+        keys.retain(|key| past(key));
+        keys.truncate(RANGE_PAGE_KEYS);
+
+        // The page spans up to its last btree key, or the rest of the range
+        // once the btree runs out
+        let next = (keys.len() == RANGE_PAGE_KEYS)
+            .then(|| keys.last().cloned())
+            .flatten();
+        let (page_start, page_end) = match (&next, descending) {
+            (Some(n), true) => (n, end),
+            (Some(n), false) => (start, n),
+            (None, _) => (start, end),
+        };
+
+        // 2. Mem buffer keys within the page
+        keys.extend(
+            self.mem_buffer
+                .range(page_start, page_end)
+                .into_iter()
+                .map(|(key, _)| key)
+                .filter(|key| past(key)),
+        );
+        keys.sort_unstable();
+        if descending {
+            keys.reverse();
+        }
+        keys.dedup();
+        keys.retain(|key| accept(key) && !tombstones.contains(&tombstone_key(key)));
+        drop(tombstones);
+        Ok((keys, next))
+    }
+
     /// Range query: value < upper_bound
     pub fn query_less_than(&self, upper_bound: &Value) -> Result<Vec<RowId>> {
         let upper_bytes = self.value_to_bytes(upper_bound)?;
@@ -650,7 +1169,7 @@ impl ColumnValueIndex {
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter tombstones inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -691,14 +1210,14 @@ impl ColumnValueIndex {
             row_id: 0,
         };
         let end_key = IndexKey {
-            value_bytes: [0xFFu8; VALUE_DATA_SIZE],
+            value_bytes: MAX_VALUE_KEY,
             row_id: RowId::MAX,
         };
 
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -746,7 +1265,7 @@ impl ColumnValueIndex {
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -781,14 +1300,14 @@ impl ColumnValueIndex {
             row_id: 0,
         };
         let end_key = IndexKey {
-            value_bytes: [0xFFu8; VALUE_DATA_SIZE],
+            value_bytes: MAX_VALUE_KEY,
             row_id: RowId::MAX,
         };
 
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
         let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
+        let mut seen = RowIdSet::new();
 
         // 1. Mem buffer (filter inline)
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
@@ -822,6 +1341,62 @@ impl ColumnValueIndex {
         upper_bound: &Value,
         upper_inclusive: bool,
     ) -> Result<Vec<RowId>> {
+        let mut seen = RowIdSet::new();
+        let mut row_ids = Vec::new();
+        self.visit_between(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+            |row_id| {
+                if seen.insert(row_id) {
+                    row_ids.push(row_id);
+                }
+            },
+        )?;
+        Ok(row_ids)
+    }
+
+    /// [`Self::query_between`] as a row ID bitmap, for callers that
+    /// intersect or union results rather than walk them in value order
+    pub fn query_between_set(
+        &self,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+    ) -> Result<RowIdSet> {
+        let mut row_ids = RowIdSet::new();
+        self.visit_between(
+            lower_bound,
+            lower_inclusive,
+            upper_bound,
+            upper_inclusive,
+            |row_id| {
+                row_ids.insert(row_id);
+            },
+        )?;
+        Ok(row_ids)
+    }
+
+    /// Point query as a row ID bitmap (see [`Self::get`])
+    pub fn get_set(&self, value: &Value) -> Result<RowIdSet> {
+        Ok(self.get_arc(value)?.iter().copied().collect())
+    }
+
+    /// Visit the live row IDs between the bounds in value order: buffered
+    /// entries first, then the B+Tree. A row ID may be visited more than once.
+    fn visit_between(
+        &self,
+        lower_bound: &Value,
+        lower_inclusive: bool,
+        upper_bound: &Value,
+        upper_inclusive: bool,
+        mut visit: impl FnMut(RowId),
+    ) -> Result<()> {
+        let lower_inclusive = lower_inclusive || truncated_bound(lower_bound);
+        let upper_inclusive = upper_inclusive || truncated_bound(upper_bound);
+
         let lower_bytes = self.value_to_bytes(lower_bound)?;
         let upper_bytes = self.value_to_bytes(upper_bound)?;
 
@@ -835,29 +1410,30 @@ impl ColumnValueIndex {
             value_bytes: upper_bytes,
             row_id: RowId::MAX,
         };
+        if start_key > end_key {
+            return Ok(());
+        }
 
         // 🔒 tombstones before btree — consistent lock order
         let tombstones = self.tombstones.lock();
-        let mut results: Vec<IndexKey> = Vec::new();
-        let mut seen = HashSet::new();
 
-        let mut accept = |key: &IndexKey| -> bool {
+        let mut accept = |key: &IndexKey| {
             // Post-filter exclusive boundaries
             if !lower_inclusive && key.value_bytes == start_key.value_bytes {
-                return false;
+                return;
             }
             if !upper_inclusive && key.value_bytes == end_key.value_bytes {
-                return false;
+                return;
+            }
+            if !tombstones.contains(&tombstone_key(key)) {
+                visit(key.row_id);
             }
-            !tombstones.contains(&tombstone_key(key)) && seen.insert(key.row_id)
         };
 
         // 1. Mem buffer
         let buffer_results = self.mem_buffer.range(&start_key, &end_key);
         for (key, _) in buffer_results {
-            if accept(&key) {
-                results.push(key);
-            }
+            accept(&key);
         }
 
         // 2. Btree
@@ -865,19 +1441,24 @@ impl ColumnValueIndex {
             let btree = self.btree.read();
             let btree_results = btree.range(&start_key, &end_key)?;
             for (key, _) in btree_results {
-                if accept(&key) {
-                    results.push(key);
-                }
+                accept(&key);
             }
         }
-        drop(tombstones);
-
-        let row_ids: Vec<RowId> = results.into_iter().map(|key| key.row_id).collect();
-        Ok(row_ids)
+        Ok(())
     }
 
     /// Delete a value → row_id mapping
     pub fn delete(&self, value: &Value, row_id: RowId) -> Result<()> {
+        if let Some(elements) = self.array_elements(value) {
+            for element in &elements {
+                self.delete_key(element, row_id)?;
+            }
+            return Ok(());
+        }
+        self.delete_key(value, row_id)
+    }
+
+    fn delete_key(&self, value: &Value, row_id: RowId) -> Result<()> {
         let value_bytes = self.value_to_bytes(value)?;
         let key = IndexKey {
             value_bytes,
@@ -968,6 +1549,34 @@ impl ColumnValueIndex {
         Ok(())
     }
 
+    /// Flush, then run `f` while the btree is still locked, so `f` sees the
+    /// index file exactly as flushed (no drain can write to it meanwhile)
+    pub fn flush_then<R>(&self, f: impl FnOnce() -> R) -> Result<R> {
+        self.flush_buffer()?;
+        let mut btree = self.btree.write();
+        btree.flush()?;
+        Ok(f())
+    }
+
+    /// Drain after a buffer write: opportunistic (skipped if another drain is
+    /// running) when a buffer filled up, blocking once the buffer reached its
+    /// hard cap so a write burst cannot outrun the drains.
+    fn drain_after_write(&self, full: bool) -> Result<()> {
+        if self.mem_buffer.over_capacity() {
+            self.mem_buffer.note_backpressure();
+            let _guard = self.drain_lock.lock();
+            // Another writer may have drained while we waited
+            if self.mem_buffer.over_capacity() {
+                self.drain_immutable_to_btree_impl(true)?;
+            }
+        } else if full {
+            if let Some(_guard) = self.drain_lock.try_lock() {
+                self.drain_immutable_to_btree()?;
+            }
+        }
+        Ok(())
+    }
+
     /// Drain immutable buffers to btree (called when buffer is full or during checkpoint)
     ///
     /// Caller must hold drain_lock.
@@ -1071,6 +1680,11 @@ impl ColumnValueIndex {
         }
     }
 
+    /// Counters of the value → row_ids lookup cache
+    pub fn cache_stats(&self) -> crate::index::cached_index::CacheStats {
+        self.lru_cache.stats()
+    }
+
     /// Returns true if this index needs to be rebuilt by the async pipeline.
     /// Newly created indexes or those that missed synchronous updates need rebuilding.
     pub fn needs_rebuild(&self) -> bool {
@@ -1090,7 +1704,41 @@ impl ColumnValueIndex {
         btree.approximate_entry_count()
     }
 
-    /// Return all unique key values in the index (from mem_buffer + BTree).
+    /// Memory buffer statistics (size, hard cap, backpressure events)
+    pub fn buffer_stats(&self) -> crate::database::mem_buffer::BufferStats {
+        self.mem_buffer.stats()
+    }
+
+    /// Entries buffered in memory plus B+Tree deletes deferred to the next drain
+    pub fn pending_updates(&self) -> usize {
+        self.mem_buffer.entry_count() + self.pending_deletes.lock().len()
+    }
+
+    /// Deleted keys still masking buffered or persisted entries
+    pub fn tombstone_count(&self) -> usize {
+        self.tombstones.lock().len()
+    }
+
+    /// Key bytes submitted by insert/update/bulk load since open
+    pub fn bytes_indexed(&self) -> u64 {
+        self.bytes_indexed
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Bytes written to the B+Tree file since open
+    pub fn bytes_written(&self) -> u64 {
+        self.btree.read().bytes_written()
+    }
+
+    fn note_indexed(&self, keys: usize) {
+        self.bytes_indexed.fetch_add(
+            (keys * IndexKey::key_size()) as u64,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    /// Return all unique key values in the index (from mem_buffer + BTree),
+    /// NULL included when a row holds one.
     /// Used by SELECT DISTINCT fast path — O(unique_values) vs O(N) full scan.
     pub fn all_keys(&self, col_type: &crate::types::ColumnType) -> Result<Vec<Value>> {
         let mut seen = std::collections::HashSet::new();
@@ -1112,7 +1760,7 @@ impl ColumnValueIndex {
                 row_id: 0,
             };
             let max_key = IndexKey {
-                value_bytes: [0xFFu8; VALUE_DATA_SIZE],
+                value_bytes: NULL_KEY,
                 row_id: u64::MAX,
             };
             let btree = self.btree.read();
@@ -1130,26 +1778,42 @@ impl ColumnValueIndex {
 
     /// Decode a value_bytes (from IndexKey) back to a Value using the column type.
     fn bytes_to_value(bytes: &[u8; VALUE_DATA_SIZE], col_type: &crate::types::ColumnType) -> Value {
+        if *bytes == NULL_KEY {
+            return Value::Null;
+        }
         match col_type {
             crate::types::ColumnType::Integer => {
-                let i = i64::from_be_bytes(bytes[..8].try_into().unwrap_or([0; 8]));
-                Value::Integer(i)
-            }
-            crate::types::ColumnType::Float => {
-                let sortable = u64::from_be_bytes(bytes[..8].try_into().unwrap_or([0; 8]));
-                let bits = if sortable & (1u64 << 63) != 0 {
-                    !sortable // negative: flip all bits back
-                } else {
-                    sortable ^ (1u64 << 63) // positive: flip sign bit back
-                };
-                Value::Float(f64::from_bits(bits))
+                let rounded =
+                    f64_from_sortable(u64::from_be_bytes(bytes[..8].try_into().unwrap_or([0; 8])));
+                let offset = i64_from_key(&bytes[8..16]);
+                Value::Integer((rounded as i128 + offset as i128) as i64)
             }
+            crate::types::ColumnType::Float => Value::Float(f64_from_sortable(u64::from_be_bytes(
+                bytes[..8].try_into().unwrap_or([0; 8]),
+            ))),
             crate::types::ColumnType::Timestamp => {
-                let ts = i64::from_be_bytes(bytes[..8].try_into().unwrap_or([0; 8]));
-                Value::Timestamp(crate::types::Timestamp::from_micros(ts))
+                Value::Timestamp(crate::types::Timestamp::from_micros(i64_from_key(bytes)))
             }
             crate::types::ColumnType::Boolean => Value::Bool(bytes[0] != 0),
-            crate::types::ColumnType::Text => {
+            crate::types::ColumnType::Decimal { scale, .. } => {
+                crate::types::Decimal::from_sort_key(bytes)
+                    .map(|d| Value::decimal(d.rescale(*scale).unwrap_or(d)))
+                    .unwrap_or(Value::Null)
+            }
+            crate::types::ColumnType::Uuid => {
+                let mut raw = [0u8; 16];
+                raw.copy_from_slice(&bytes[..16]);
+                Value::uuid(crate::types::Uuid::from_bytes(raw))
+            }
+            crate::types::ColumnType::Date => {
+                crate::types::Date::from_sort_key(bytes[..4].try_into().unwrap_or([0; 4]))
+                    .map(Value::Date)
+                    .unwrap_or(Value::Null)
+            }
+            crate::types::ColumnType::Time => crate::types::Time::from_micros(i64_from_key(bytes))
+                .map(Value::Time)
+                .unwrap_or(Value::Null),
+            crate::types::ColumnType::Text | crate::types::ColumnType::Enum(_) => {
                 // Text is stored raw, find the actual length (trim trailing zeros)
                 let end = bytes
                     .iter()
@@ -1162,41 +1826,108 @@ impl ColumnValueIndex {
         }
     }
 
-    // Helper: Convert Value to fixed 12-byte key (zero-padded for short types)
+    /// Key bytes of a value, read as the column's type where literals differ
     fn value_to_bytes(&self, value: &Value) -> Result<[u8; VALUE_DATA_SIZE]> {
-        Self::value_to_bytes_helper(value)
-    }
-
-    fn value_to_bytes_helper(value: &Value) -> Result<[u8; VALUE_DATA_SIZE]> {
-        let mut buf = [0u8; VALUE_DATA_SIZE];
-        match value {
-            Value::Integer(i) => buf[..8].copy_from_slice(&i.to_be_bytes()),
-            Value::Float(f) => {
-                // Convert to sortable bytes: ensures negative < positive in byte order
-                let canonical = if *f == 0.0 { 0.0f64 } else { *f }; // normalize -0.0
-                let bits = canonical.to_bits();
-                let sortable = if bits & (1u64 << 63) != 0 {
-                    !bits // negative: flip all bits so -inf sorts first
-                } else {
-                    bits ^ (1u64 << 63) // positive: flip sign bit
-                };
-                buf[..8].copy_from_slice(&sortable.to_be_bytes());
+        // Multi-key indexes are keyed by the element type
+        let key_type = match &self.col_type {
+            Some(crate::types::ColumnType::Array(elem)) => Some(elem.as_ref()),
+            ct => ct.as_ref(),
+        };
+        if let Some(crate::types::ColumnType::Decimal { .. }) = key_type {
+            let d = match value {
+                Value::Integer(i) => Some(crate::types::Decimal::from_i64(*i)),
+                Value::Float(f) => crate::types::Decimal::from_f64(*f),
+                Value::Text(s) => s.as_str().parse().ok(),
+                _ => None,
+            };
+            if let Some(d) = d {
+                return encode_key(&Value::decimal(d));
             }
-            Value::Timestamp(ts) => buf[..8].copy_from_slice(&ts.as_micros().to_be_bytes()),
-            Value::Bool(b) => buf[0] = if *b { 1 } else { 0 },
-            Value::Text(s) => {
-                let raw = s.as_bytes();
-                let len = raw.len().min(VALUE_DATA_SIZE);
-                buf[..len].copy_from_slice(&raw[..len]);
+        }
+        // UUID/DATE/TIME literals arrive as text; key them by the parsed value
+        if let (Some(ct), Value::Text(s)) = (key_type, value) {
+            if ct.is_text_encoded() && !matches!(ct, crate::types::ColumnType::Enum(_)) {
+                let parsed = ct.value_from_text(s);
+                if !matches!(parsed, Value::Null) {
+                    return encode_key(&parsed);
+                }
             }
-            _ => {
-                return Err(StorageError::InvalidData(format!(
-                    "Unsupported value type for indexing: {:?}",
-                    value
-                )));
+        }
+        encode_key(value)
+    }
+}
+
+/// Key bounds of one contiguous stretch of a range query
+struct KeyRange {
+    start: IndexKey,
+    end: IndexKey,
+    lower_inclusive: bool,
+    upper_inclusive: bool,
+}
+
+/// Row ids of a range query, read from the index one page at a time
+/// (see [`ColumnValueIndex::query_between_iter`]). Rows inserted or deleted
+/// while iterating may or may not show up.
+pub struct ColumnRangeIter {
+    index: Arc<ColumnValueIndex>,
+    /// Key ranges left to walk, in order
+    ranges: VecDeque<KeyRange>,
+    /// Walk each range from its end, largest key first
+    descending: bool,
+    /// Last key paged through in the front range
+    after: Option<IndexKey>,
+    /// Row ids of the current page not yet returned
+    rows: VecDeque<RowId>,
+    /// Rows already returned; only multi-key indexes list a row more than once
+    seen: Option<RowIdSet>,
+}
+
+impl ColumnRangeIter {
+    /// Read pages until one yields rows; false once every range is done
+    fn fill(&mut self) -> Result<bool> {
+        while let Some(range) = self.ranges.front() {
+            let (keys, next) =
+                self.index
+                    .range_page(range, self.after.as_ref(), self.descending)?;
+            match next {
+                Some(key) => self.after = Some(key),
+                None => {
+                    self.ranges.pop_front();
+                    self.after = None;
+                }
             }
-        };
-        Ok(buf)
+            for key in keys {
+                if self
+                    .seen
+                    .as_mut()
+                    .is_none_or(|seen| seen.insert(key.row_id))
+                {
+                    self.rows.push_back(key.row_id);
+                }
+            }
+            if !self.rows.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Iterator for ColumnRangeIter {
+    type Item = Result<RowId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rows.is_empty() {
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    self.ranges.clear();
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.rows.pop_front().map(Ok)
     }
 }
 
@@ -1598,6 +2329,47 @@ mod tests {
         Ok(())
     }
 
+    /// NULLs sit in their own bucket: reachable by `get`, outside every range
+    #[test]
+    fn test_null_bucket() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_null.idx");
+        let index = Arc::new(ColumnValueIndex::create(
+            &path,
+            "t".to_string(),
+            "c".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?);
+
+        for i in 0..5 {
+            index.insert(&Value::Integer(i), i as RowId)?;
+        }
+        index.insert(&Value::Null, 10)?;
+        index.insert(&Value::Null, 11)?;
+        index.flush()?;
+        index.insert(&Value::Null, 12)?;
+
+        let mut nulls = index.get(&Value::Null)?;
+        nulls.sort();
+        assert_eq!(nulls, vec![10, 11, 12]);
+        assert_eq!(
+            index
+                .range(&Value::Integer(i64::MIN), &Value::Integer(i64::MAX))?
+                .len(),
+            5
+        );
+        assert_eq!(index.query_greater_than(&Value::Integer(2))?, vec![3, 4]);
+        assert_eq!(index.scan_iter(false)?.count(), 5);
+        assert_eq!(index.scan_row_ids_with_limit(None)?.len(), 5);
+
+        index.update(&Value::Null, &Value::Integer(7), 11)?;
+        index.delete(&Value::Null, 12)?;
+        assert_eq!(index.get(&Value::Null)?, vec![10]);
+        assert_eq!(index.get(&Value::Integer(7))?, vec![11]);
+
+        Ok(())
+    }
+
     /// Verify update with same value (noop) doesn't lose the row_id.
     #[test]
     fn test_update_same_value_noop() -> Result<()> {
@@ -1679,6 +2451,35 @@ mod tests {
         Ok(())
     }
 
+    /// A write burst that outruns the lazy drains hits the hard cap and is
+    /// drained synchronously instead of growing the buffer.
+    #[test]
+    fn test_mem_buffer_hard_cap_backpressure() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_cap.idx");
+        let config = ColumnValueIndexConfig {
+            mem_buffer_size: 1024,
+            // Never drain lazily: only the hard cap moves data to the B+Tree
+            drain_threshold: usize::MAX,
+            mem_buffer_max_size: Some(4096),
+            ..Default::default()
+        };
+        let index = ColumnValueIndex::create(&path, "t".to_string(), "c".to_string(), config)?;
+
+        for i in 0..2000i64 {
+            index.insert(&Value::Integer(i % 10), i as RowId)?;
+            assert!(index.buffer_stats().total_size_bytes <= 4096);
+        }
+        let stats = index.buffer_stats();
+        assert_eq!(stats.max_size_bytes, 4096);
+        assert!(stats.backpressure_events > 0);
+
+        for v in 0..10i64 {
+            assert_eq!(index.get(&Value::Integer(v))?.len(), 200);
+        }
+        Ok(())
+    }
+
     /// Delete then verify gone.
     #[test]
     fn test_delete_makes_entry_invisible() -> Result<()> {
@@ -1776,4 +2577,333 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_query_between_crosses_zero() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_between_signed.idx");
+        let index = ColumnValueIndex::create(
+            path,
+            "t".to_string(),
+            "v".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?;
+        for (row_id, v) in [-20i64, -5, 0, 5, 20].into_iter().enumerate() {
+            index.insert(&Value::Integer(v), row_id as RowId)?;
+        }
+
+        let mut result =
+            index.query_between(&Value::Integer(i64::MIN), true, &Value::Integer(5), false)?;
+        result.sort_unstable();
+        assert_eq!(result, vec![0, 1, 2]);
+
+        let mut result =
+            index.query_between(&Value::Integer(-5), true, &Value::Integer(5), true)?;
+        result.sort_unstable();
+        assert_eq!(result, vec![1, 2, 3]);
+
+        // Empty range must not panic
+        let result = index.query_between(&Value::Integer(5), false, &Value::Integer(3), false)?;
+        assert!(result.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_page() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_scan_page.idx");
+        let index = ColumnValueIndex::create(
+            &path,
+            "t".to_string(),
+            "ts".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?;
+        // Half flushed to the btree, half still buffered, interleaved in value order
+        for i in 0..100u64 {
+            index.insert(&Value::Integer(i as i64 * 2), i)?;
+        }
+        index.flush()?;
+        for i in 0..100u64 {
+            index.insert(&Value::Integer(i as i64 * 2 + 1), 100 + i)?;
+        }
+        index.delete(&Value::Integer(198), 99)?;
+
+        for descending in [true, false] {
+            let mut seen = Vec::new();
+            let mut cursor: Option<IndexCursor> = None;
+            loop {
+                let (page, next) = index.scan_page(cursor.as_ref(), 16, descending)?;
+                seen.extend(page.iter().map(IndexCursor::row_id));
+                match next {
+                    // Resume through the token form, as a client would
+                    Some(c) => cursor = Some(IndexCursor::from_token(&c.to_token())?),
+                    None => break,
+                }
+            }
+            assert_eq!(seen.len(), 199);
+            if descending {
+                assert_eq!(&seen[..3], &[199, 198, 98]);
+                assert_eq!(seen.last(), Some(&0));
+            } else {
+                assert_eq!(&seen[..3], &[0, 100, 1]);
+                assert_eq!(seen.last(), Some(&199));
+            }
+        }
+
+        assert!(IndexCursor::from_token("xyz").is_err());
+        assert!(IndexCursor::from_token("00000000000000zz").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_between_iter_matches_query_between() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_between_iter.idx");
+        let index = Arc::new(ColumnValueIndex::create(
+            &path,
+            "t".to_string(),
+            "v".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?);
+        // Several pages, half flushed to the btree and half still buffered,
+        // interleaved in value order and on both sides of zero
+        for i in 0..1500u64 {
+            index.insert(&Value::Integer(i as i64 * 2 - 1500), i)?;
+        }
+        index.flush()?;
+        for i in 0..1500u64 {
+            index.insert(&Value::Integer(i as i64 * 2 - 1499), 1500 + i)?;
+        }
+        index.delete(&Value::Integer(-2), 749)?;
+        index.delete(&Value::Integer(1), 2250)?;
+
+        let bounds = [
+            (i64::MIN, true, i64::MAX, true),
+            (-1500, true, 1499, true),
+            (-100, false, 100, false),
+            (0, true, 1400, false),
+            (-1400, false, -3, true),
+            (5, true, 5, true),
+            (5, false, 3, false),
+        ];
+        for (lo, lo_inc, hi, hi_inc) in bounds {
+            let (lo, hi) = (Value::Integer(lo), Value::Integer(hi));
+            let mut streamed = index
+                .query_between_iter(&lo, lo_inc, &hi, hi_inc)?
+                .collect::<Result<Vec<_>>>()?;
+            streamed.sort_unstable();
+            let mut expected = index.query_between(&lo, lo_inc, &hi, hi_inc)?;
+            expected.sort_unstable();
+            assert_eq!(streamed, expected, "{:?}..{:?}", lo, hi);
+        }
+
+        // Row ids come back in value order: negatives first
+        let streamed = index
+            .query_between_iter(&Value::Integer(-4), true, &Value::Integer(2), true)?
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(streamed, vec![748, 2248, 2249, 750, 751]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_id_sets_match_vectors() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_row_id_sets.idx");
+        let index = ColumnValueIndex::create(
+            &path,
+            "t".to_string(),
+            "v".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?;
+        // v = id % 10, half flushed; row ids past 2^32 too
+        for id in 0..1000u64 {
+            index.insert(&Value::Integer((id % 10) as i64), id)?;
+        }
+        index.flush()?;
+        for id in 1000..2000u64 {
+            index.insert(&Value::Integer((id % 10) as i64), id << 22)?;
+        }
+        index.delete(&Value::Integer(3), 13)?;
+        index.delete(&Value::Integer(4), 1004 << 22)?;
+
+        for (lo, lo_inc, hi, hi_inc) in
+            [(2, true, 4, true), (2, false, 4, false), (5, true, 3, true)]
+        {
+            let (lo, hi) = (Value::Integer(lo), Value::Integer(hi));
+            let set = index.query_between_set(&lo, lo_inc, &hi, hi_inc)?;
+            let mut expected = index.query_between(&lo, lo_inc, &hi, hi_inc)?;
+            expected.sort_unstable();
+            assert_eq!(
+                set.iter().collect::<Vec<_>>(),
+                expected,
+                "{:?}..{:?}",
+                lo,
+                hi
+            );
+        }
+        let mut threes = index.get(&Value::Integer(3))?;
+        threes.sort_unstable();
+        let set = index.get_set(&Value::Integer(3))?;
+        assert_eq!(set.iter().collect::<Vec<_>>(), threes);
+        assert_eq!(set.len(), 199);
+        assert!(!set.contains(13) && set.contains(1003 << 22));
+
+        // Bitmaps combine without touching the index again
+        let mut both =
+            index.query_between_set(&Value::Integer(0), true, &Value::Integer(3), true)?;
+        both &= index.get_set(&Value::Integer(3))?;
+        assert_eq!(both, set);
+        let mut either = index.get_set(&Value::Integer(3))?;
+        either |= index.get_set(&Value::Integer(4))?;
+        assert_eq!(either.len(), 398);
+        Ok(())
+    }
+
+    #[test]
+    fn test_descending_iter_reverses_ascending() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("test_desc_iter.idx");
+        let index = Arc::new(
+            ColumnValueIndex::create(
+                &path,
+                "t".to_string(),
+                "v".to_string(),
+                ColumnValueIndexConfig::default(),
+            )?
+            .with_column_type(crate::types::ColumnType::Integer),
+        );
+        // Same layout as above: pages split between btree and buffer
+        for i in 0..1500u64 {
+            index.insert(&Value::Integer(i as i64 * 2 - 1500), i)?;
+        }
+        index.flush()?;
+        for i in 0..1500u64 {
+            index.insert(&Value::Integer(i as i64 * 2 - 1499), 1500 + i)?;
+        }
+        index.delete(&Value::Integer(-2), 749)?;
+
+        let bounds = [
+            (-1500, true, 1499, true),
+            (-100, false, 100, false),
+            (0, true, 1400, false),
+            (5, true, 5, true),
+        ];
+        for (lo, lo_inc, hi, hi_inc) in bounds {
+            let (lo, hi) = (Value::Integer(lo), Value::Integer(hi));
+            let mut ascending = index
+                .query_between_iter(&lo, lo_inc, &hi, hi_inc)?
+                .collect::<Result<Vec<_>>>()?;
+            ascending.reverse();
+            let descending = index
+                .query_between_iter_rev(&lo, lo_inc, &hi, hi_inc)?
+                .collect::<Result<Vec<_>>>()?;
+            assert_eq!(descending, ascending, "{:?}..{:?}", lo, hi);
+        }
+
+        // A full scan is in value order, negatives first
+        let ascending = index.scan_iter(false)?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(ascending.len(), 2999);
+        assert_eq!(&ascending[..3], &[0, 1500, 1]);
+        let mut descending = index.scan_iter(true)?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(&descending[..3], &[2999, 1499, 2998]);
+        descending.reverse();
+        assert_eq!(descending, ascending);
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_key_order() {
+        let ascending = [
+            Value::Float(f64::NEG_INFINITY),
+            Value::Float(-1e300),
+            Value::Integer(i64::MIN),
+            Value::Integer(-5),
+            Value::Float(-4.5),
+            Value::Integer(0),
+            Value::Float(0.5),
+            Value::Integer(1),
+            Value::Float(1.5),
+            Value::Integer(1 << 53),
+            Value::Integer((1 << 53) + 1),
+            Value::Float(2f64.powi(60)),
+            Value::Integer(i64::MAX),
+            Value::Float(f64::INFINITY),
+            Value::Float(f64::NAN),
+        ];
+        for pair in ascending.windows(2) {
+            assert!(
+                encode_key(&pair[0]).unwrap() < encode_key(&pair[1]).unwrap(),
+                "{:?} < {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+        let same = [
+            (Value::Integer(0), Value::Float(-0.0)),
+            (Value::Integer(3), Value::Float(3.0)),
+            (Value::Integer(i64::MIN), Value::Float(-(2f64.powi(63)))),
+            (Value::Float(f64::NAN), Value::Float(-f64::NAN)),
+        ];
+        for (a, b) in same {
+            assert_eq!(encode_key(&a).unwrap(), encode_key(&b).unwrap());
+        }
+
+        let ts = |us| Value::Timestamp(crate::types::Timestamp::from_micros(us));
+        assert!(encode_key(&ts(-1)).unwrap() < encode_key(&ts(0)).unwrap());
+        assert!(encode_key(&ts(i64::MIN)).unwrap() < encode_key(&ts(-1)).unwrap());
+
+        use crate::types::ColumnType;
+        for i in [i64::MIN, -1, 0, (1 << 53) + 1, i64::MAX] {
+            let key = encode_key(&Value::Integer(i)).unwrap();
+            assert_eq!(
+                ColumnValueIndex::bytes_to_value(&key, &ColumnType::Integer),
+                Value::Integer(i)
+            );
+        }
+        let key = encode_key(&ts(-42)).unwrap();
+        assert_eq!(
+            ColumnValueIndex::bytes_to_value(&key, &ColumnType::Timestamp),
+            ts(-42)
+        );
+    }
+
+    #[test]
+    fn test_mixed_numeric_ranges() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let index = ColumnValueIndex::create(
+            temp_dir.path().join("test_mixed.idx"),
+            "readings".to_string(),
+            "level".to_string(),
+            ColumnValueIndexConfig::default(),
+        )?
+        .with_column_type(crate::types::ColumnType::Float);
+
+        // FLOAT columns may hold integers written as such
+        index.insert(&Value::Float(-2.5), 1)?;
+        index.insert(&Value::Integer(-1), 2)?;
+        index.insert(&Value::Float(0.0), 3)?;
+        index.flush()?;
+        index.insert(&Value::Integer(3), 4)?;
+        index.insert(&Value::Float(f64::NAN), 5)?;
+        index.insert(&Value::Float(f64::INFINITY), 6)?;
+
+        let sorted = |mut ids: Vec<RowId>| {
+            ids.sort();
+            ids
+        };
+        assert_eq!(sorted(index.query_less_than(&Value::Integer(0))?), [1, 2]);
+        assert_eq!(
+            sorted(index.query_between(&Value::Integer(-2), true, &Value::Float(3.0), true)?),
+            [2, 3, 4]
+        );
+        assert_eq!(sorted(index.get(&Value::Float(-1.0))?), [2]);
+        assert_eq!(sorted(index.get(&Value::Integer(3))?), [4]);
+        assert_eq!(
+            sorted(index.query_greater_than(&Value::Float(f64::INFINITY))?),
+            [5]
+        );
+        assert_eq!(sorted(index.get(&Value::Float(-f64::NAN))?), [5]);
+        Ok(())
+    }
 }
@@ -73,6 +73,10 @@ pub struct IOctreeIndex {
     name: String,
     /// Tier 1: disk-backed leaf storage with LRU cache
     leaf_store: LeafStore,
+    /// Point bytes inserted since open
+    bytes_indexed: u64,
+    /// Bytes of tree structure saved by `flush` since open
+    tree_bytes_written: u64,
 }
 
 impl IOctreeIndex {
@@ -112,6 +116,8 @@ impl IOctreeIndex {
             world_bounds,
             name,
             leaf_store,
+            bytes_indexed: 0,
+            tree_bytes_written: 0,
         })
     }
 
@@ -143,12 +149,13 @@ impl IOctreeIndex {
             point.z, /*as f64*/
         ];
         while !self.root_contains(&p) {
-            self.expand_root();
+            self.expand_root(&p);
         }
 
         // Direct insert into tree (data goes to LeafStore with bounded LRU cache)
         self.insert_into_tree(indexed)?;
         self.size += 1;
+        self.bytes_indexed += std::mem::size_of::<IndexedPoint3D>() as u64;
         Ok(())
     }
 
@@ -209,6 +216,14 @@ impl IOctreeIndex {
         search::radius_search(&self.root, &c, radius /*as f64*/, &self.leaf_store)
     }
 
+    /// Estimated number of points inside a 3D bounding box, computed from
+    /// octant counts without reading leaves
+    pub fn estimate_range_count(&self, bbox: &BoundingBox3D) -> usize {
+        let min = [bbox.min_x, bbox.min_y, bbox.min_z];
+        let max = [bbox.max_x, bbox.max_y, bbox.max_z];
+        (search::estimate_range_count(&self.root, &min, &max).round() as usize).min(self.size)
+    }
+
     /// Number of indexed points
     pub fn len(&self) -> usize {
         self.size
@@ -243,10 +258,23 @@ impl IOctreeIndex {
                 path.join("ioctree.bin")
             };
             self.save(&save_path)?;
+            self.tree_bytes_written += crate::storage::backend::backend_for(&save_path)
+                .file_len(&save_path)
+                .unwrap_or(0);
         }
         Ok(())
     }
 
+    /// Point bytes inserted since open
+    pub fn bytes_indexed(&self) -> u64 {
+        self.bytes_indexed
+    }
+
+    /// Bytes written to leaf slots and the tree file since open
+    pub fn bytes_written(&self) -> u64 {
+        self.leaf_store.bytes_written() + self.tree_bytes_written
+    }
+
     fn root_contains(&self, p: &[f64; 3]) -> bool {
         let (center, extent) = match &self.root {
             Octant::Inner { center, extent, .. } => (center, extent),
@@ -261,17 +289,27 @@ impl IOctreeIndex {
             && p[2] <= center[2] + e
     }
 
-    fn expand_root(&mut self) {
-        let (center, extent) = match &self.root {
-            Octant::Inner { center, extent, .. } => (*center, *extent * 2.0),
-            Octant::Leaf { center, extent, .. } => (*center, *extent * 2.0),
+    /// Double the root toward `p`: the old root becomes the child octant of
+    /// the new, larger root that it exactly covers
+    fn expand_root(&mut self, p: &[f64; 3]) {
+        let (old_center, extent) = match &self.root {
+            Octant::Inner { center, extent, .. } => (*center, *extent),
+            Octant::Leaf { center, extent, .. } => (*center, *extent),
         };
-        let old_root = std::mem::replace(&mut self.root, Octant::new_inner(center, extent));
+        let mut center = old_center;
+        for axis in 0..3 {
+            if p[axis] >= old_center[axis] {
+                center[axis] += extent;
+            } else {
+                center[axis] -= extent;
+            }
+        }
+        let old_root = std::mem::replace(&mut self.root, Octant::new_inner(center, extent * 2.0));
         if let Octant::Inner {
             ref mut children, ..
         } = self.root
         {
-            let code = node::octant_code(&center, &center);
+            let code = node::octant_code(&center, &old_center);
             children[code] = Some(Box::new(old_root));
         }
         self.root.recount_size();
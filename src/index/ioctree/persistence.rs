@@ -7,6 +7,7 @@
 
 use super::leaf_store::LeafStore;
 use super::{IOctreeConfig, IOctreeIndex};
+use crate::storage::backend::{self, BackendFile, OpenFlags};
 use crate::types::BoundingBox3D;
 use crate::{Result, StorageError};
 use std::io::{BufReader, BufWriter, Read, Write};
@@ -21,11 +22,14 @@ fn io_err(e: std::io::Error) -> StorageError {
 
 /// Save an i-Octree index to disk (v2 format)
 pub fn save(tree: &IOctreeIndex, path: &std::path::Path) -> Result<()> {
+    let backend = backend::backend_for(path);
     if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+        backend.create_dir_all(parent)?;
     }
 
-    let file = std::fs::File::create(path).map_err(io_err)?;
+    let file = backend
+        .open(path, OpenFlags::create_truncate())
+        .map_err(io_err)?;
     let mut writer = BufWriter::new(file);
 
     // Header
@@ -83,7 +87,7 @@ pub fn save(tree: &IOctreeIndex, path: &std::path::Path) -> Result<()> {
 
 /// Load an i-Octree index from disk (supports v1 and v2)
 pub fn load(path: &std::path::Path, _config: IOctreeConfig, _name: String) -> Result<IOctreeIndex> {
-    let file = std::fs::File::open(path)
+    let file = backend::open_file(path, OpenFlags::read_only())
         .map_err(|e| StorageError::InvalidData(format!("Open {}: {}", path.display(), e)))?;
     let mut reader = BufReader::new(file);
 
@@ -114,7 +118,10 @@ pub fn load(path: &std::path::Path, _config: IOctreeConfig, _name: String) -> Re
 }
 
 /// Load v2 format
-fn load_v2(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Result<IOctreeIndex> {
+fn load_v2(
+    reader: &mut BufReader<Box<dyn BackendFile>>,
+    path: &std::path::Path,
+) -> Result<IOctreeIndex> {
     let mut buf4 = [0u8; 4];
     let mut buf8 = [0u8; 8];
 
@@ -130,7 +137,7 @@ fn load_v2(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Res
         .map_err(|e| StorageError::InvalidData(format!("Deserialize config: {}", e)))?;
 
     // World bounds
-    let read_f64 = |reader: &mut BufReader<std::fs::File>| -> Result<f64> {
+    let read_f64 = |reader: &mut BufReader<Box<dyn BackendFile>>| -> Result<f64> {
         let mut buf = [0u8; 8];
         reader.read_exact(&mut buf).map_err(io_err)?;
         Ok(f64::from_le_bytes(buf))
@@ -197,11 +204,16 @@ fn load_v2(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Res
         world_bounds,
         name,
         leaf_store,
+        bytes_indexed: 0,
+        tree_bytes_written: 0,
     })
 }
 
 /// Load v1 format and migrate to v2
-fn load_v1(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Result<IOctreeIndex> {
+fn load_v1(
+    reader: &mut BufReader<Box<dyn BackendFile>>,
+    path: &std::path::Path,
+) -> Result<IOctreeIndex> {
     let mut buf4 = [0u8; 4];
     let mut buf8 = [0u8; 8];
 
@@ -217,7 +229,7 @@ fn load_v1(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Res
         .map_err(|e| StorageError::InvalidData(format!("Deserialize config: {}", e)))?;
 
     // World bounds
-    let read_f64 = |reader: &mut BufReader<std::fs::File>| -> Result<f64> {
+    let read_f64 = |reader: &mut BufReader<Box<dyn BackendFile>>| -> Result<f64> {
         let mut buf = [0u8; 8];
         reader.read_exact(&mut buf).map_err(io_err)?;
         Ok(f64::from_le_bytes(buf))
@@ -281,6 +293,8 @@ fn load_v1(reader: &mut BufReader<std::fs::File>, path: &std::path::Path) -> Res
         world_bounds,
         name,
         leaf_store,
+        bytes_indexed: 0,
+        tree_bytes_written: 0,
     })
 }
 
@@ -151,6 +151,36 @@ fn range_search_recursive(
     }
 }
 
+/// Estimate how many points fall inside a 3D box from octant counts alone
+/// (no leaf I/O): covered octants count whole, partially covered leaves count
+/// in proportion to the overlapped volume
+pub fn estimate_range_count(octant: &Octant, min: &[f64; 3], max: &[f64; 3]) -> f64 {
+    let (center, extent) = (octant.center(), octant.extent());
+    if octant.size() == 0 || !overlaps(center, extent, min, max) {
+        return 0.0;
+    }
+    if octant_inside_query(center, extent, min, max) || extent <= 0.0 {
+        return octant.size() as f64;
+    }
+    match octant {
+        Octant::Leaf { .. } => {
+            let covered: f64 = (0..3)
+                .map(|i| {
+                    let lo = (center[i] - extent).max(min[i]);
+                    let hi = (center[i] + extent).min(max[i]);
+                    ((hi - lo) / (2.0 * extent)).clamp(0.0, 1.0)
+                })
+                .product();
+            octant.size() as f64 * covered
+        }
+        Octant::Inner { children, .. } => children
+            .iter()
+            .flatten()
+            .map(|child| estimate_range_count(child, min, max))
+            .sum(),
+    }
+}
+
 /// Radius search: find all points within distance `radius` of center
 pub fn radius_search(
     root: &Octant,
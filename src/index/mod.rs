@@ -14,6 +14,7 @@ pub mod text_dictionary;
 pub mod text_encoding;
 pub mod text_fts;
 pub mod text_types;
+pub mod timestamp_reorder;
 pub mod tokenizers;
 pub mod vamana;
 
@@ -24,4 +25,5 @@ pub use primary_key::PrimaryKeyIndex;
 pub use text_dictionary::ChunkedDictionary;
 pub use text_fts::{TextFTSIndex, TextFTSStats};
 pub use text_types::{NgramTokenizer, Token, Tokenizer, WhitespaceTokenizer};
+pub use timestamp_reorder::{TimestampReorderBuffer, TimestampReorderStats};
 pub use vamana::DiskANNIndex;
@@ -12,15 +12,16 @@
 //! - Better cache locality
 
 use crate::index::text_types::TermId;
+use crate::storage::backend::{self, OpenFlags};
 use crate::{Result, StorageError};
 use lru::LruCache;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{create_dir_all, File};
 use std::io::{Read, Write};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// A chunk of the dictionary
@@ -94,6 +95,9 @@ pub struct ChunkedDictionary {
 
     /// LRU cache for hot chunks
     cache: Arc<RwLock<LruCache<usize, DictionaryChunk>>>,
+
+    /// Bytes written to chunk and metadata files since open
+    bytes_written: AtomicU64,
     // ❌ Removed: reverse_map consumes too much memory
     // For reverse lookup (TermId -> Token), scan chunks on demand
 }
@@ -101,10 +105,11 @@ pub struct ChunkedDictionary {
 impl ChunkedDictionary {
     /// Create or open a chunked dictionary
     pub fn new(storage_dir: PathBuf, cache_size: usize) -> Result<Self> {
-        create_dir_all(&storage_dir)?;
+        let backend = backend::backend_for(&storage_dir);
+        backend.create_dir_all(&storage_dir)?;
 
         let meta_path = storage_dir.join("dict_meta.bin");
-        let metadata = if meta_path.exists() {
+        let metadata = if backend.exists(&meta_path) {
             Self::load_metadata(&meta_path)?
         } else {
             DictionaryMetadata::new()
@@ -116,6 +121,7 @@ impl ChunkedDictionary {
             cache: Arc::new(RwLock::new(LruCache::new(
                 NonZeroUsize::new(cache_size.max(1)).unwrap(),
             ))),
+            bytes_written: AtomicU64::new(0),
         })
     }
 
@@ -342,11 +348,12 @@ impl ChunkedDictionary {
     /// Load a chunk from disk
     fn load_chunk(&self, chunk_id: usize) -> Result<DictionaryChunk> {
         let path = self.chunk_path(chunk_id);
-        if !path.exists() {
+        let backend = backend::backend_for(&path);
+        if !backend.exists(&path) {
             return Ok(DictionaryChunk::new(chunk_id));
         }
 
-        let mut file = File::open(&path)?;
+        let mut file = backend.open(&path, OpenFlags::read_only())?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
 
@@ -357,6 +364,11 @@ impl ChunkedDictionary {
         Ok(chunk)
     }
 
+    /// Bytes written to chunk and metadata files since open
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
     /// Save a chunk to disk
     fn save_chunk(&self, chunk_id: usize, chunk: &DictionaryChunk) -> Result<()> {
         let path = self.chunk_path(chunk_id);
@@ -364,9 +376,11 @@ impl ChunkedDictionary {
         let data =
             bincode::serialize(chunk).map_err(|e| StorageError::Serialization(e.to_string()))?;
 
-        let mut file = File::create(&path)?;
+        let mut file = backend::open_file(&path, OpenFlags::create_truncate())?;
         file.write_all(&data)?;
         file.sync_all()?;
+        self.bytes_written
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
 
         Ok(())
     }
@@ -378,8 +392,8 @@ impl ChunkedDictionary {
     }
 
     /// Load metadata
-    fn load_metadata(path: &PathBuf) -> Result<DictionaryMetadata> {
-        let mut file = File::open(path)?;
+    fn load_metadata(path: &Path) -> Result<DictionaryMetadata> {
+        let mut file = backend::open_file(path, OpenFlags::read_only())?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
 
@@ -393,9 +407,11 @@ impl ChunkedDictionary {
         let data =
             bincode::serialize(meta).map_err(|e| StorageError::Serialization(e.to_string()))?;
 
-        let mut file = File::create(&path)?;
+        let mut file = backend::open_file(&path, OpenFlags::create_truncate())?;
         file.write_all(&data)?;
         file.sync_all()?;
+        self.bytes_written
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
 
         Ok(())
     }
@@ -13,15 +13,15 @@ use crate::index::text_types::{
     BM25Config, DocId, FieldNormTable, Position, PostingList, PostingListFormat, TermId, Tokenizer,
     WhitespaceTokenizer,
 };
+use crate::storage::backend::{self, OpenFlags};
 use crate::{Result, StorageError};
 use lru::LruCache;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Document ID type
@@ -90,6 +90,13 @@ pub struct TextFTSIndex {
     /// Avoids re-scoring the entire posting list on repeated queries.
     /// Bounded LRU (128 entries × ~80 bytes = ~10KB).
     topk_cache: Arc<RwLock<LruCache<String, Vec<(DocumentId, f32)>>>>,
+
+    /// Text bytes submitted by insert/update since open
+    bytes_indexed: u64,
+
+    /// Bytes written to metadata and doc-length files since open
+    /// (postings and dictionary count their own)
+    bytes_written: std::sync::atomic::AtomicU64,
 }
 
 /// Metadata for text FTS index
@@ -159,6 +166,22 @@ impl TermCursorData {
     }
 }
 
+/// A term's postings: the flushed shards plus the documents still pending.
+/// Pending postings only hold documents added since the last flush, so they
+/// extend the flushed list rather than replace it.
+fn with_pending(
+    flushed: Option<PostingList>,
+    pending: Option<&PostingList>,
+) -> Option<PostingList> {
+    match (flushed, pending) {
+        (Some(mut flushed), Some(pending)) => {
+            flushed.merge(pending);
+            Some(flushed)
+        }
+        (flushed, pending) => flushed.or_else(|| pending.cloned()),
+    }
+}
+
 impl TextFTSIndex {
     /// Create a new text FTS index
     pub fn new(storage_path: PathBuf) -> Result<Self> {
@@ -196,7 +219,8 @@ impl TextFTSIndex {
     ) -> Result<Self> {
         // Create storage directory
         let storage_dir = storage_path.with_extension("fts.d");
-        std::fs::create_dir_all(&storage_dir)?;
+        let backend = backend::backend_for(&storage_dir);
+        backend.create_dir_all(&storage_dir)?;
 
         // Create or open chunked dictionary
         let dict_dir = storage_path.with_extension("dict.d");
@@ -216,7 +240,7 @@ impl TextFTSIndex {
         // Load statistics metadata
         let meta_path = storage_dir.join("index_meta.bin");
         let (total_docs, total_tokens, avg_doc_length, deleted_docs_vec, deleted_term_docs_vec) =
-            if meta_path.exists() {
+            if backend.exists(&meta_path) {
                 Self::load_metadata(&meta_path)?
             } else {
                 (0, 0, 0.0, Vec::new(), Vec::new())
@@ -249,6 +273,8 @@ impl TextFTSIndex {
             topk_cache: Arc::new(RwLock::new(LruCache::new(
                 std::num::NonZeroUsize::new(128).unwrap(),
             ))),
+            bytes_indexed: 0,
+            bytes_written: std::sync::atomic::AtomicU64::new(0),
         })
     }
 
@@ -299,6 +325,7 @@ impl TextFTSIndex {
         let mut doc_lengths_batch = HashMap::new();
 
         for &(doc_id, text) in docs {
+            self.bytes_indexed += text.len() as u64;
             let tokens = self.tokenizer.tokenize(text);
             doc_lengths_batch.insert(doc_id, tokens.len() as u32);
             batch_token_count += tokens.len() as u64;
@@ -473,14 +500,20 @@ impl TextFTSIndex {
         }
 
         // 2. Insert new terms
+        self.bytes_indexed += new_text.len() as u64;
         let new_tokens = self.tokenizer.tokenize(new_text);
         let new_token_count = new_tokens.len() as u64;
 
-        // Build per-term doc lists
-        let mut term_docs: HashMap<TermId, Vec<DocId>> = HashMap::new();
+        // Build per-term positions (positional postings derive TF from them)
+        let mut term_docs: HashMap<TermId, Vec<Option<Position>>> = HashMap::new();
         for token in new_tokens {
             let term_id = self.dictionary.get_or_insert(&token.text);
-            term_docs.entry(term_id).or_default().push(doc_id);
+            let pos = if self.enable_positions {
+                Some(token.position)
+            } else {
+                None
+            };
+            term_docs.entry(term_id).or_default().push(pos);
         }
 
         // Update pending posting lists
@@ -488,15 +521,15 @@ impl TextFTSIndex {
             let mut pending = self.pending_posting_lists.write();
             let mut deleted_term_docs = self.deleted_term_docs.write();
 
-            for (term_id, doc_ids) in term_docs {
+            for (term_id, positions) in term_docs {
                 // Remove from deleted set if re-adding the same term
                 deleted_term_docs.remove(&(term_id, doc_id));
 
                 let posting = pending
                     .entry(term_id)
                     .or_insert_with(|| PostingList::new_without_positions(!self.enable_positions));
-                for doc_id in doc_ids {
-                    posting.add(doc_id, None);
+                for pos in positions {
+                    posting.add(doc_id, pos);
                 }
             }
         }
@@ -526,6 +559,25 @@ impl TextFTSIndex {
         Ok(())
     }
 
+    /// Flushed posting list of a term (cache > disk), cached on a miss
+    fn cached_posting_list(
+        &self,
+        term_id: TermId,
+        btree: &parking_lot::RwLockReadGuard<GenericBTree<u32>>,
+    ) -> Result<Option<PostingList>> {
+        // Look up before matching: a guard in an `if let` scrutinee lives
+        // through its else branches and the `put` below would deadlock on it.
+        let cached = self.posting_cache.write().get(&term_id).cloned();
+        if cached.is_some() {
+            return Ok(cached);
+        }
+        let loaded = self.load_posting_list_sharded(term_id, btree)?;
+        if let Some(p) = &loaded {
+            self.posting_cache.write().put(term_id, p.clone());
+        }
+        Ok(loaded)
+    }
+
     /// Load posting list from all shards (helper function)
     fn load_posting_list_sharded(
         &self,
@@ -622,6 +674,11 @@ impl TextFTSIndex {
 
         let mut max_shard_idx: u32 = 0;
         for (key, _) in &entries {
+            // The range also spans every other term's shard keys; only
+            // count the ones that belong to this term.
+            if *key & 0x00FFFFFF != base_term_id {
+                continue;
+            }
             let shard_idx = *key >> 24;
             // Only count data shards (0..0xFE), skip position shard (0xFE)
             if shard_idx < 0xFE && shard_idx + 1 > max_shard_idx {
@@ -649,16 +706,10 @@ impl TextFTSIndex {
 
         for token in tokens {
             if let Some(term_id) = self.dictionary.get(&token.text) {
-                // Priority: pending > B-Tree disk
-                let posting = if let Some(pend) = pending.get(&term_id) {
-                    pend.clone()
-                } else {
-                    // Load from B-Tree disk with sharding support
-                    if let Some(p) = self.load_posting_list_sharded(term_id, &btree)? {
-                        p
-                    } else {
-                        continue;
-                    }
+                // B-Tree disk (with sharding support) plus pending
+                let flushed = self.load_posting_list_sharded(term_id, &btree)?;
+                let Some(posting) = with_pending(flushed, pending.get(&term_id)) else {
+                    continue;
                 };
 
                 let mut doc_ids = posting.doc_ids();
@@ -700,11 +751,8 @@ impl TextFTSIndex {
         let mut postings: Vec<(TermId, PostingList)> = Vec::new();
         for token in &tokens {
             if let Some(term_id) = self.dictionary.get(&token.text) {
-                let posting = if let Some(pend) = pending.get(&term_id) {
-                    pend.clone()
-                } else if let Some(p) = self.load_posting_list_with_positions(term_id, &btree)? {
-                    p
-                } else {
+                let flushed = self.load_posting_list_with_positions(term_id, &btree)?;
+                let Some(posting) = with_pending(flushed, pending.get(&term_id)) else {
                     return Ok(Vec::new()); // Term not found → phrase cannot match
                 };
                 postings.push((term_id, posting));
@@ -799,36 +847,17 @@ impl TextFTSIndex {
         let b = self.bm25_config.b;
         let total_docs = self.total_docs as f32;
 
-        // Load posting list (cache > pending > disk).
-        // For cached posting lists, use the cached decoded pairs directly.
+        // Load posting list (flushed from cache > disk, plus pending)
         let pairs: Vec<(u32, u16)>;
         let df: u64;
         {
-            let pending = self.pending_posting_lists.read();
-            if let Some(pend) = pending.get(&term_id) {
-                pairs = pend.iter_doc_tf();
-                df = pend.doc_count();
-            } else {
-                drop(pending);
-                // Check decoded-pair cache first (avoids posting list clone).
-                let pair_cache_key = term_id;
-                let mut pc = self.posting_cache.write();
-                if let Some(cached_pl) = pc.get(&pair_cache_key) {
-                    pairs = cached_pl.iter_doc_tf_cached_ref();
-                    df = cached_pl.doc_count();
-                } else {
-                    drop(pc);
-                    let btree = self.btree.read();
-                    if let Some(p) = self.load_posting_list_sharded(term_id, &btree)? {
-                        let pl_clone = p.clone();
-                        self.posting_cache.write().put(term_id, p);
-                        pairs = pl_clone.iter_doc_tf_cached_ref();
-                        df = pl_clone.doc_count();
-                    } else {
-                        return Ok(Vec::new());
-                    }
-                }
-            }
+            let pending = self.pending_posting_lists.read().get(&term_id).cloned();
+            let flushed = self.cached_posting_list(term_id, &self.btree.read())?;
+            let Some(posting) = with_pending(flushed, pending.as_ref()) else {
+                return Ok(Vec::new());
+            };
+            pairs = posting.iter_doc_tf_cached_ref();
+            df = posting.doc_count();
         }
 
         if df == 0 {
@@ -932,17 +961,9 @@ impl TextFTSIndex {
                 }
             };
 
-            // Load posting list (cache > pending > disk)
-            let posting = if let Some(pend) = pending.get(&term_id) {
-                pend.clone()
-            } else if let Some(cached) = self.posting_cache.write().get(&term_id).cloned() {
-                // 🚀 Cache hit — skip disk I/O entirely.
-                cached
-            } else if let Some(p) = self.load_posting_list_sharded(term_id, &btree)? {
-                // Cache miss — load from disk, then cache for future queries.
-                self.posting_cache.write().put(term_id, p.clone());
-                p
-            } else {
+            // Load posting list (flushed from cache > disk, plus pending)
+            let flushed = self.cached_posting_list(term_id, &btree)?;
+            let Some(posting) = with_pending(flushed, pending.get(&term_id)) else {
                 continue;
             };
 
@@ -1349,11 +1370,8 @@ impl TextFTSIndex {
         let meta_path = self.storage_dir.join("index_meta.bin");
         let tmp_path = self.storage_dir.join("index_meta.bin.tmp");
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&tmp_path)?;
+        let backend = backend::backend_for(&tmp_path);
+        let mut file = backend.open(&tmp_path, OpenFlags::create_truncate())?;
 
         let deleted_docs: Vec<DocId> = self.deleted_docs.read().iter().copied().collect();
         let deleted_term_docs: Vec<(TermId, DocId)> =
@@ -1374,9 +1392,12 @@ impl TextFTSIndex {
         file.write_all(&serialized)?;
         file.sync_all()?;
         drop(file);
+        self.note_write(serialized.len());
 
         // Atomic rename for crash safety
-        std::fs::rename(&tmp_path, &meta_path).map_err(StorageError::Io)?;
+        backend
+            .rename(&tmp_path, &meta_path)
+            .map_err(StorageError::Io)?;
 
         Ok(())
     }
@@ -1384,9 +1405,9 @@ impl TextFTSIndex {
     /// Load metadata from disk
     #[allow(clippy::type_complexity)]
     fn load_metadata(
-        stats_path: &PathBuf,
+        stats_path: &Path,
     ) -> Result<(u64, u64, f32, Vec<DocId>, Vec<(TermId, DocId)>)> {
-        let mut file = File::open(stats_path)?;
+        let mut file = backend::open_file(stats_path, OpenFlags::read_only())?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
 
@@ -1411,11 +1432,12 @@ impl TextFTSIndex {
         let lengths_path = self.storage_dir.join("doclengths.bin");
         let incremental_path = self.storage_dir.join("doclengths.incremental.bin");
 
+        let backend = backend::backend_for(&self.storage_dir);
         let mut all_lengths = HashMap::new();
 
         // Load main file
-        if lengths_path.exists() {
-            let mut file = File::open(&lengths_path)?;
+        if backend.exists(&lengths_path) {
+            let mut file = backend.open(&lengths_path, OpenFlags::read_only())?;
             let mut buffer = Vec::new();
             file.read_to_end(&mut buffer)?;
 
@@ -1428,8 +1450,8 @@ impl TextFTSIndex {
 
         // Merge incremental file if exists
         // Format: repeated [len:u32 LE][bincode(HashMap<DocId, u32>)]
-        if incremental_path.exists() {
-            let mut file = File::open(&incremental_path)?;
+        if backend.exists(&incremental_path) {
+            let mut file = backend.open(&incremental_path, OpenFlags::read_only())?;
             let mut buffer = Vec::new();
             file.read_to_end(&mut buffer)?;
 
@@ -1480,16 +1502,7 @@ impl TextFTSIndex {
             if let Some(&count) = shard_counters.peek(&term_id) {
                 count
             } else {
-                let range_start = base_term_id;
-                let range_end = (0xFEu32 << 24) | base_term_id;
-                let entries = btree.range(&range_start, &range_end)?;
-                let mut max_shard: u32 = 0;
-                for (key, _) in &entries {
-                    let shard_idx = *key >> 24;
-                    if shard_idx < 0xFE && shard_idx + 1 > max_shard {
-                        max_shard = shard_idx + 1;
-                    }
-                }
+                let max_shard = self.discover_shard_count(term_id, btree)?;
                 shard_counters.put(term_id, max_shard);
                 max_shard
             }
@@ -1499,8 +1512,9 @@ impl TextFTSIndex {
             return Ok(());
         }
 
-        // Read and merge all shards
-        let mut merged = PostingList::new();
+        // Read and merge all shards. No positions map: term frequencies
+        // come from the shards' doc_freqs, not from positions.
+        let mut merged = PostingList::new_without_positions(true);
         for shard_idx in 0..shard_count {
             let shard_key = (shard_idx << 24) | base_term_id;
             if let Ok(Some(bytes)) = btree.get(&shard_key) {
@@ -1589,10 +1603,7 @@ impl TextFTSIndex {
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
 
         // Append to incremental file
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&incremental_path)?;
+        let mut file = backend::open_file(&incremental_path, OpenFlags::append())?;
 
         use std::io::Write;
         // Write length prefix + data
@@ -1600,6 +1611,7 @@ impl TextFTSIndex {
         file.write_all(&len_bytes)?;
         file.write_all(&serialized)?;
         file.sync_all()?;
+        self.note_write(len_bytes.len() + serialized.len());
 
         // 🚀 P0 FIX: 释放HashMap capacity
         if pending_doc_lens.capacity() > 1024 {
@@ -1609,6 +1621,66 @@ impl TextFTSIndex {
         Ok(())
     }
 
+    /// Document frequency of each distinct query term (0 for unknown terms),
+    /// for selectivity estimation
+    pub fn term_doc_frequencies(&self, query: &str) -> Result<Vec<u64>> {
+        let mut terms: Vec<String> = self
+            .tokenizer
+            .tokenize(query)
+            .into_iter()
+            .map(|t| t.text)
+            .collect();
+        terms.sort();
+        terms.dedup();
+
+        let pending = self.pending_posting_lists.read();
+        let btree = self.btree.read();
+        let mut freqs = Vec::with_capacity(terms.len());
+        for term in &terms {
+            let term_id = match self.dictionary.get(term) {
+                Some(id) => id,
+                None => {
+                    freqs.push(0);
+                    continue;
+                }
+            };
+            let flushed = self.cached_posting_list(term_id, &btree)?;
+            let df = with_pending(flushed, pending.get(&term_id)).map_or(0, |p| p.doc_count());
+            freqs.push(df);
+        }
+        Ok(freqs)
+    }
+
+    /// Text bytes submitted by insert/update since open
+    pub fn bytes_indexed(&self) -> u64 {
+        self.bytes_indexed
+    }
+
+    /// Bytes written to the index's files since open (postings, dictionary,
+    /// metadata and doc lengths)
+    pub fn bytes_written(&self) -> u64 {
+        self.btree.read().bytes_written()
+            + self.dictionary.bytes_written()
+            + self
+                .bytes_written
+                .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn note_write(&self, bytes: usize) {
+        self.bytes_written
+            .fetch_add(bytes as u64, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Documents inserted since the last flush (postings not yet in the B+Tree)
+    pub fn pending_docs(&self) -> usize {
+        self.pending_doc_lengths.read().len()
+    }
+
+    /// Deleted documents still filtered out at search time
+    pub fn deleted_doc_count(&self) -> usize {
+        self.deleted_docs.read().len()
+    }
+
     /// Get statistics
     pub fn stats(&self) -> TextFTSStats {
         TextFTSStats {
@@ -1728,6 +1800,102 @@ mod tests {
             "double-delete should not underflow total_docs"
         );
     }
+
+    #[test]
+    fn test_ranked_search_after_reopen_keeps_term_frequencies() {
+        let words = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot"];
+
+        // Six flushes push every term past the consolidation threshold; shard discovery
+        // must not count other terms' keys, and consolidation must keep tf.
+        // Flush order follows HashMap order, so repeat on fresh indexes.
+        for _ in 0..8 {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path().join("shards");
+            {
+                let mut index = TextFTSIndex::new(path.clone()).unwrap();
+                for round in 0..6u64 {
+                    let texts: Vec<(u64, String)> = (0..30u64)
+                        .map(|i| (round * 30 + i, words[i as usize % words.len()].to_string()))
+                        .collect();
+                    let docs: Vec<(u64, &str)> =
+                        texts.iter().map(|(id, t)| (*id, t.as_str())).collect();
+                    index.batch_insert(&docs).unwrap();
+                    index.flush().unwrap();
+                }
+            }
+
+            let index = TextFTSIndex::new(path).unwrap();
+            for word in words {
+                let results = index.search_ranked(word, 1000).unwrap();
+                assert_eq!(results.len(), 30, "term {word}");
+                assert!(results.iter().all(|(_, score)| *score > 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_term_ranked_search_loads_flushed_postings() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = TextFTSIndex::new(temp_dir.path().join("cold")).unwrap();
+        index
+            .batch_insert(&[(1, "rust database"), (2, "rust search"), (3, "other")])
+            .unwrap();
+        index.flush().unwrap();
+
+        // Both terms miss the pending lists and the posting cache, so they
+        // are loaded from disk and cached while the search runs
+        let results = index.search_ranked("rust database", 10).unwrap();
+        assert_eq!(results[0].0, 1);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_pending_postings_extend_flushed_ones() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = TextFTSIndex::new(temp_dir.path().join("mixed")).unwrap();
+        index
+            .batch_insert(&[(1, "rust database"), (2, "rust search")])
+            .unwrap();
+        index.flush().unwrap();
+        index.insert(3, "rust engine").unwrap();
+
+        // "rust" now has a pending list as well as a flushed shard
+        assert_eq!(index.search("rust").unwrap(), vec![1, 2, 3]);
+        let mut ranked: Vec<u64> = index
+            .search_ranked("rust", 10)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        ranked.sort();
+        assert_eq!(ranked, vec![1, 2, 3]);
+        assert_eq!(index.term_doc_frequencies("rust").unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn test_updated_terms_are_ranked() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = TextFTSIndex::new(temp_dir.path().join("update")).unwrap();
+        index
+            .batch_insert(&[(1, "item alpha"), (2, "item alpha")])
+            .unwrap();
+        index.flush().unwrap();
+        index.update(1, "item alpha", "item beta").unwrap();
+
+        let ranked = |term: &str| -> Vec<u64> {
+            let mut ids: Vec<u64> = index
+                .search_ranked(term, 10)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ranked("alpha"), vec![2]);
+        assert_eq!(ranked("beta"), vec![1]);
+        assert_eq!(ranked("item"), vec![1, 2]);
+    }
 }
 
 // ==================== 🚀 Batch Index Builder Implementation ====================
@@ -402,11 +402,25 @@ impl PostingList {
 
     /// Add a document with a known term frequency (used when converting from block format).
     /// Ensures doc_freqs array stays in sync.
+    ///
+    /// Doc ids may arrive out of order (later shards can carry lower ids), so
+    /// the frequency is placed at the doc's rank; a repeated doc keeps the
+    /// most recent frequency.
     pub fn add_with_freq(&mut self, doc_id: DocId, _position: Option<Position>, tf: u16) {
-        let is_new = !self.doc_ids.contains(doc_id as u32);
-        self.doc_ids.insert(doc_id as u32);
+        *self.cached_pairs.lock() = None;
+        let in_sync = self.doc_freqs.len() == self.doc_ids.len() as usize;
+        let is_new = self.doc_ids.insert(doc_id as u32);
+        if !in_sync {
+            if is_new {
+                self.doc_freqs.push(tf);
+            }
+            return;
+        }
+        let idx = (self.doc_ids.rank(doc_id as u32) - 1) as usize;
         if is_new {
-            self.doc_freqs.push(tf);
+            self.doc_freqs.insert(idx, tf);
+        } else {
+            self.doc_freqs[idx] = tf;
         }
     }
 
@@ -1359,4 +1373,19 @@ mod tests {
         assert_eq!(posting.term_frequency(1), 3);
         assert_eq!(posting.max_tf(), 3);
     }
+
+    #[test]
+    fn test_add_with_freq_out_of_order_keeps_tf_aligned() {
+        // Later shards can carry lower doc ids; tf must follow its doc.
+        let mut posting = PostingList::new_without_positions(true);
+        posting.add_with_freq(10, None, 4);
+        posting.add_with_freq(20, None, 2);
+        posting.add_with_freq(5, None, 7);
+        posting.add_with_freq(20, None, 3);
+
+        assert_eq!(posting.term_frequency(5), 7);
+        assert_eq!(posting.term_frequency(10), 4);
+        assert_eq!(posting.term_frequency(20), 3);
+        assert_eq!(posting.iter_doc_tf(), vec![(5, 7), (10, 4), (20, 3)]);
+    }
 }
@@ -0,0 +1,169 @@
+//! Reorder buffer for the timestamp index
+//!
+//! Sensor records often arrive slightly out of order (network jitter, batched
+//! uploads from several devices). Inserting them into the timestamp B+Tree as
+//! they come splits pages all over the key space. This buffer holds entries
+//! until they fall behind the newest timestamp by more than a lateness window,
+//! then releases them in timestamp order so the B+Tree sees near-sequential
+//! appends.
+//!
+//! ```text
+//! push ──▶ [pending, sorted by ts] ──(ts + window ≤ newest ts)──▶ release (sorted)
+//! ```
+
+use crate::config::TimestampReorderConfig;
+use crate::types::RowId;
+use std::collections::BTreeSet;
+
+/// Reorder buffer statistics
+#[derive(Default, Debug, Clone)]
+pub struct TimestampReorderStats {
+    /// Entries currently held back
+    pub buffered: usize,
+    /// Entries released to the index so far
+    pub released: u64,
+    /// Entries that arrived out of order but within the window (fixed up by sorting)
+    pub reordered: u64,
+    /// Entries that arrived after newer entries had already been released
+    pub late: u64,
+}
+
+/// Buffers `(timestamp, row_id)` index entries and releases them in order
+pub struct TimestampReorderBuffer {
+    config: TimestampReorderConfig,
+    pending: BTreeSet<(u64, RowId)>,
+    /// Newest timestamp pushed so far
+    high_water: Option<u64>,
+    /// Newest timestamp released so far
+    released_up_to: Option<u64>,
+    stats: TimestampReorderStats,
+}
+
+impl TimestampReorderBuffer {
+    pub fn new(config: TimestampReorderConfig) -> Self {
+        Self {
+            config,
+            pending: BTreeSet::new(),
+            high_water: None,
+            released_up_to: None,
+            stats: TimestampReorderStats::default(),
+        }
+    }
+
+    /// Add entries and return those that are ready, sorted by timestamp
+    pub fn push(&mut self, entries: impl IntoIterator<Item = (u64, RowId)>) -> Vec<(u64, RowId)> {
+        for (ts, row_id) in entries {
+            if self.released_up_to.is_some_and(|r| ts < r) {
+                self.stats.late += 1;
+            } else if self.high_water.is_some_and(|h| ts < h) {
+                self.stats.reordered += 1;
+            }
+            self.high_water = Some(self.high_water.map_or(ts, |h| h.max(ts)));
+            self.pending.insert((ts, row_id));
+        }
+        self.release_ready()
+    }
+
+    /// Release everything, sorted by timestamp (checkpoint / close)
+    pub fn drain(&mut self) -> Vec<(u64, RowId)> {
+        let out: Vec<_> = std::mem::take(&mut self.pending).into_iter().collect();
+        self.note_released(&out);
+        out
+    }
+
+    /// Row IDs of buffered entries with `start <= ts <= end`
+    pub fn range(&self, start: u64, end: u64) -> Vec<RowId> {
+        if start > end {
+            return Vec::new();
+        }
+        self.pending
+            .range((start, RowId::MIN)..=(end, RowId::MAX))
+            .map(|&(_, row_id)| row_id)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn stats(&self) -> TimestampReorderStats {
+        TimestampReorderStats {
+            buffered: self.pending.len(),
+            ..self.stats.clone()
+        }
+    }
+
+    fn release_ready(&mut self) -> Vec<(u64, RowId)> {
+        let Some(high_water) = self.high_water else {
+            return Vec::new();
+        };
+        let cutoff = high_water.saturating_sub(self.config.lateness_window_micros);
+        let mut out = Vec::new();
+        while let Some(&first) = self.pending.first() {
+            let over_capacity = self.pending.len() > self.config.max_buffered_entries;
+            if first.0 > cutoff && !over_capacity {
+                break;
+            }
+            self.pending.pop_first();
+            out.push(first);
+        }
+        self.note_released(&out);
+        out
+    }
+
+    fn note_released(&mut self, released: &[(u64, RowId)]) {
+        if let Some(&(ts, _)) = released.last() {
+            self.released_up_to = Some(self.released_up_to.map_or(ts, |r| r.max(ts)));
+        }
+        self.stats.released += released.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(window: u64, max: usize) -> TimestampReorderBuffer {
+        TimestampReorderBuffer::new(TimestampReorderConfig {
+            lateness_window_micros: window,
+            max_buffered_entries: max,
+        })
+    }
+
+    #[test]
+    fn test_releases_in_order_behind_window() {
+        let mut buf = buffer(100, 1000);
+        assert!(buf.push([(1_000, 1), (1_050, 2)]).is_empty());
+        // 1_020 arrives late but within the window
+        assert!(buf.push([(1_020, 3)]).is_empty());
+        assert_eq!(buf.range(1_000, 1_030), vec![1, 3]);
+
+        let ready = buf.push([(1_130, 4)]);
+        assert_eq!(ready, vec![(1_000, 1), (1_020, 3)]);
+        assert_eq!(buf.len(), 2);
+
+        // Too late: 1_010 is older than what was already released
+        let ready = buf.push([(1_010, 5)]);
+        assert_eq!(ready, vec![(1_010, 5)]);
+
+        assert_eq!(buf.drain(), vec![(1_050, 2), (1_130, 4)]);
+        let stats = buf.stats();
+        assert_eq!(stats.buffered, 0);
+        assert_eq!(stats.released, 5);
+        assert_eq!(stats.reordered, 1);
+        assert_eq!(stats.late, 1);
+    }
+
+    #[test]
+    fn test_capacity_releases_oldest() {
+        let mut buf = buffer(u64::MAX, 3);
+        let ready = buf.push([(5, 1), (3, 2), (4, 3), (1, 4), (2, 5)]);
+        assert_eq!(ready, vec![(1, 4), (2, 5)]);
+        assert_eq!(buf.len(), 3);
+        assert!(buf.range(4, 3).is_empty());
+    }
+}
@@ -1,43 +1,66 @@
 //! Disk-based graph storage with bounded memory for DiskANN
 //!
-//! Stores Vamana graph adjacency list on disk with LRU cache.
-//! Offset index is LRU-bounded, falling back to binary search on a
-//! sidecar index file (graph.idx).
+//! Each node owns a fixed-size slot in graph.bin with room for
+//! `max_degree` neighbors, so updates rewrite the slot in place and
+//! searches read neighbor ids straight from the mmap. Slot offsets are
+//! LRU-cached, falling back to binary search on a sidecar index file
+//! (graph.idx).
+//!
+//! Storage format:
+//! - Data file: graph.bin — [magic, version, max_degree, node_count: u32]
+//!   [slot1] [slot2] ..., slot = [node_id: u64, count: u32, reserved: u32,
+//!   neighbors: u64 * max_degree]
+//! - Index file: graph.idx — [count: u64] [node_id: u64, offset: u64]... (sorted)
 
+use crate::storage::backend::{self, BackendFile, FileMap, OpenFlags};
 use crate::types::RowId;
 use crate::{Result, StorageError};
 use lru::LruCache;
-use memmap2::Mmap;
 use parking_lot::{Mutex, RwLock};
-use std::collections::HashSet;
-use std::fs::{File, OpenOptions};
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 const MAGIC: u32 = 0x4752_5048; // "GRPH"
-const VERSION: u32 = 1;
+const VERSION: u32 = 2;
+/// Variable-length records appended on every update; upgraded on load
+const LEGACY_VERSION: u32 = 1;
 const HEADER_SIZE: u64 = 16;
+/// Slot header: node id (u64), neighbor count (u32), reserved (u32)
+const SLOT_HEADER: usize = 16;
+/// Neighbor count marking a slot freed by `remove_node`
+const FREE_SLOT: u32 = u32::MAX;
 
 /// Disk-based graph with bounded memory
 pub struct DiskGraph {
     max_degree: usize,
-    file: Arc<RwLock<File>>,
-
-    /// mmap of graph.bin — zero-syscall neighbor reads
-    mmap: Arc<RwLock<Option<Mmap>>>,
+    /// Bytes per node slot (header + `max_degree` neighbor ids)
+    slot_size: u64,
+    file: Arc<RwLock<Box<dyn BackendFile>>>,
+
+    /// mmap of graph.bin — zero-copy neighbor reads. Slots rewritten in
+    /// place show through it; slots appended since the last flush are read
+    /// from the file until the next remap.
+    mmap: Arc<RwLock<Option<FileMap>>>,
     /// mmap of graph.idx sidecar — zero-syscall offset lookups
-    idx_mmap: Arc<RwLock<Option<Mmap>>>,
+    idx_mmap: Arc<RwLock<Option<FileMap>>>,
 
-    /// Bounded offset index: row_id → file offset (LRU-capped)
+    /// Bounded offset index: row_id → slot offset (LRU-capped)
     index: Arc<RwLock<LruCache<RowId, u64>>>,
 
     /// Sidecar index file handle (fallback when mmap unavailable)
-    index_file: Arc<RwLock<File>>,
+    index_file: Arc<RwLock<Box<dyn BackendFile>>>,
     index_count: Arc<RwLock<u64>>,
     /// Tracked count of nodes (incremental on set/remove)
     count: Arc<RwLock<u64>>,
+    /// Slot changes the sidecar doesn't list yet: new slots (`Some`) and
+    /// removed nodes (`None`). Folded into the sidecar by `flush`.
+    pending: Arc<RwLock<HashMap<RowId, Option<u64>>>>,
+    /// Slots freed by `remove_node`, reused before the file grows
+    free_slots: Arc<Mutex<Vec<u64>>>,
 
     /// LRU cache for adjacency lists
     cache: Arc<Mutex<LruCache<RowId, Arc<Vec<RowId>>>>>,
@@ -47,17 +70,20 @@ pub struct DiskGraph {
     hot_cache: Arc<RwLock<LruCache<RowId, Arc<Vec<RowId>>>>>,
     max_hot_nodes: usize,
 
-    /// Next write offset
+    /// End of the last slot
     next_offset: Arc<Mutex<u64>>,
 
     /// Dirty flag
     dirty: Arc<RwLock<bool>>,
 
-    /// Serializes flush with set_neighbors to prevent stale node_count
-    /// in sidecar index (flush acquires exclusively, set_neighbors shared)
+    /// Serializes flush with slot writes so the sidecar sees every new or
+    /// removed slot
     flush_lock: Arc<Mutex<()>>,
 
     file_path: PathBuf,
+
+    /// Bytes written to the graph and sidecar files since open
+    bytes_written: AtomicU64,
 }
 
 impl DiskGraph {
@@ -78,28 +104,30 @@ impl DiskGraph {
         max_hot_nodes: usize,
     ) -> Result<Self> {
         let data_dir = data_dir.as_ref();
-        std::fs::create_dir_all(data_dir).map_err(StorageError::Io)?;
+        backend::backend_for(data_dir)
+            .create_dir_all(data_dir)
+            .map_err(StorageError::Io)?;
 
         let file_path = data_dir.join("graph.bin");
         let idx_path = data_dir.join("graph.idx");
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .truncate(true)
-            .open(&file_path)
-            .map_err(StorageError::Io)?;
+        let mut file = backend::open_file(
+            &file_path,
+            OpenFlags {
+                read: true,
+                ..OpenFlags::create_truncate()
+            },
+        )
+        .map_err(StorageError::Io)?;
 
         Self::write_header(&mut file, max_degree, 0)?;
 
         // Create empty sidecar index
-        let mut idx = File::create(&idx_path).map_err(StorageError::Io)?;
-        idx.write_all(&0u64.to_le_bytes())
-            .map_err(StorageError::Io)?;
+        Self::write_sidecar(&idx_path, &[])?;
 
         Ok(Self {
             max_degree,
+            slot_size: Self::slot_size_for(max_degree),
             file: Arc::new(RwLock::new(file)),
             mmap: Arc::new(RwLock::new(None)),
             idx_mmap: Arc::new(RwLock::new(None)),
@@ -107,10 +135,12 @@ impl DiskGraph {
                 NonZeroUsize::new(cache_capacity.max(1)).unwrap(),
             ))),
             index_file: Arc::new(RwLock::new(
-                File::open(&idx_path).map_err(StorageError::Io)?,
+                backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?,
             )),
             index_count: Arc::new(RwLock::new(0)),
             count: Arc::new(RwLock::new(0)),
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            free_slots: Arc::new(Mutex::new(Vec::new())),
             cache: Arc::new(Mutex::new(LruCache::new(
                 NonZeroUsize::new(cache_capacity.max(1)).unwrap(),
             ))),
@@ -123,6 +153,7 @@ impl DiskGraph {
             dirty: Arc::new(RwLock::new(false)),
             flush_lock: Arc::new(Mutex::new(())),
             file_path,
+            bytes_written: AtomicU64::new(0),
         })
     }
 
@@ -141,60 +172,40 @@ impl DiskGraph {
         let file_path = data_dir.join("graph.bin");
         let idx_path = data_dir.join("graph.idx");
 
-        let mut file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(&file_path)
-            .map_err(StorageError::Io)?;
+        let mut file =
+            backend::open_file(&file_path, OpenFlags::read_write()).map_err(StorageError::Io)?;
 
-        let (max_degree, node_count) = Self::read_header(&mut file)?;
-
-        // Build sidecar index if needed
-        let index_count = if idx_path.exists() {
-            let mut idx = File::open(&idx_path).map_err(StorageError::Io)?;
-            let mut buf = [0u8; 8];
-            idx.read_exact(&mut buf).map_err(StorageError::Io)?;
-            u64::from_le_bytes(buf)
-        } else {
-            Self::build_sidecar_index(&file_path, &idx_path, node_count)?;
-            // Reopen file after building
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(&file_path)
-                .map_err(StorageError::Io)?;
-            // Recalculate next_offset by scanning
-            let (_, _next_off) = Self::scan_for_next_offset(&mut file, node_count)?;
-            // We return count, next_offset is derived later
-            node_count as u64
-        };
-
-        // Derive next_offset
-        let next_off = {
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(&file_path)
+        let (version, max_degree, _) = Self::read_header(&mut file)?;
+        if version == LEGACY_VERSION {
+            drop(file);
+            Self::upgrade_legacy(&file_path, &idx_path, max_degree)?;
+            file = backend::open_file(&file_path, OpenFlags::read_write())
                 .map_err(StorageError::Io)?;
-            let (_, off) = Self::scan_for_next_offset(&mut file, node_count)?;
-            off
-        };
-
-        let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+        }
+        let slot_size = Self::slot_size_for(max_degree);
+
+        // The slots are the source of truth; rewrite the sidecar if it
+        // missed slot changes (e.g. a crash before flush)
+        let (live, free_slots, next_off) = Self::scan_slots(file.as_ref(), slot_size)?;
+        let sidecar_current = backend::backend_for(&idx_path).exists(&idx_path)
+            && Self::read_sidecar(&idx_path).is_ok_and(|entries| entries == live);
+        if !sidecar_current {
+            Self::write_sidecar(&idx_path, &live)?;
+        }
+        let index_count = live.len() as u64;
+        drop(live);
 
-        let rw_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(&file_path)
-            .map_err(StorageError::Io)?;
+        let idx_read =
+            backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
 
         // mmap data file and sidecar for zero-syscall reads
-        let data_mmap = unsafe { Mmap::map(&rw_file).ok() };
-        let idx_mmap = unsafe { Mmap::map(&idx_read).ok() };
+        let data_mmap = file.map().ok();
+        let idx_mmap = idx_read.map().ok();
 
         Ok(Self {
             max_degree,
-            file: Arc::new(RwLock::new(rw_file)),
+            slot_size,
+            file: Arc::new(RwLock::new(file)),
             mmap: Arc::new(RwLock::new(data_mmap)),
             idx_mmap: Arc::new(RwLock::new(idx_mmap)),
             index: Arc::new(RwLock::new(LruCache::new(
@@ -203,6 +214,8 @@ impl DiskGraph {
             index_file: Arc::new(RwLock::new(idx_read)),
             index_count: Arc::new(RwLock::new(index_count)),
             count: Arc::new(RwLock::new(index_count)),
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            free_slots: Arc::new(Mutex::new(free_slots)),
             cache: Arc::new(Mutex::new(LruCache::new(
                 NonZeroUsize::new(cache_capacity.max(1)).unwrap(),
             ))),
@@ -215,88 +228,171 @@ impl DiskGraph {
             dirty: Arc::new(RwLock::new(false)),
             flush_lock: Arc::new(Mutex::new(())),
             file_path,
+            bytes_written: AtomicU64::new(0),
         })
     }
 
-    fn scan_for_next_offset(file: &mut File, node_count: usize) -> Result<(usize, u64)> {
-        file.seek(SeekFrom::Start(HEADER_SIZE))
-            .map_err(StorageError::Io)?;
+    fn slot_size_for(max_degree: usize) -> u64 {
+        (SLOT_HEADER + max_degree * 8) as u64
+    }
+
+    /// Live `(node_id, offset)` slots sorted by node id, offsets of freed
+    /// slots, and the end of the last complete slot
+    fn scan_slots(
+        file: &dyn BackendFile,
+        slot_size: u64,
+    ) -> Result<(Vec<(RowId, u64)>, Vec<u64>, u64)> {
+        let file_len = file.len().map_err(StorageError::Io)?;
+        let mut live = Vec::new();
+        let mut free = Vec::new();
         let mut offset = HEADER_SIZE;
-        let mut actual_count = 0usize;
-        let mut buf8 = [0u8; 8];
-        let mut buf4 = [0u8; 4];
+        let mut header = [0u8; SLOT_HEADER];
 
-        for _ in 0..node_count {
-            if file.read_exact(&mut buf8).is_err() {
-                break;
-            }
-            if file.read_exact(&mut buf4).is_err() {
-                break;
-            }
-            let ncount = u32::from_le_bytes(buf4) as usize;
-            let record_size = 8 + 4 + (ncount * 8);
-            offset += record_size as u64;
-            actual_count += 1;
-            if file.seek(SeekFrom::Current((ncount * 8) as i64)).is_err() {
-                break;
+        while offset + slot_size <= file_len {
+            file.read_exact_at(&mut header, offset)
+                .map_err(StorageError::Io)?;
+            let node_id = u64::from_le_bytes(header[..8].try_into().unwrap());
+            let count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+            if count == FREE_SLOT {
+                free.push(offset);
+            } else {
+                live.push((node_id, offset));
             }
+            offset += slot_size;
         }
-        Ok((actual_count, offset))
+        live.sort_by_key(|(id, _)| *id);
+        live.dedup_by_key(|(id, _)| *id);
+        Ok((live, free, offset))
     }
 
-    fn build_sidecar_index(data_path: &Path, idx_path: &Path, node_count: usize) -> Result<u64> {
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(data_path)
+    /// Rewrite a version 1 graph file as slots. Its sidecar names each
+    /// node's current record; without one the last record of a node wins.
+    fn upgrade_legacy(data_path: &Path, idx_path: &Path, max_degree: usize) -> Result<()> {
+        let mut file =
+            backend::open_file(data_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
+        let records = if backend::backend_for(idx_path).exists(idx_path) {
+            Self::read_sidecar(idx_path)?
+        } else {
+            let mut latest: HashMap<RowId, u64> = HashMap::new();
+            Self::for_each_legacy_record(&mut file, |node_id, offset| {
+                latest.insert(node_id, offset);
+            })?;
+            let mut records: Vec<(RowId, u64)> = latest.into_iter().collect();
+            records.sort_by_key(|(id, _)| *id);
+            records
+        };
+
+        let temp_path = data_path.with_extension("tmp");
+        let mut temp_file = backend::open_file(&temp_path, OpenFlags::create_truncate())
             .map_err(StorageError::Io)?;
-        file.seek(SeekFrom::Start(HEADER_SIZE))
+        Self::write_header(&mut temp_file, max_degree, records.len())?;
+
+        let slot_size = Self::slot_size_for(max_degree);
+        let mut slots = Vec::with_capacity(records.len());
+        let mut offset = HEADER_SIZE;
+        for (node_id, record_offset) in records {
+            let mut neighbors = Self::read_legacy_record(file.as_ref(), record_offset)?;
+            neighbors.truncate(max_degree);
+            temp_file
+                .write_all(&Self::encode_slot(node_id, &neighbors, max_degree))
+                .map_err(StorageError::Io)?;
+            slots.push((node_id, offset));
+            offset += slot_size;
+        }
+        temp_file.sync_all().map_err(StorageError::Io)?;
+        drop(file);
+
+        backend::backend_for(data_path)
+            .rename(&temp_path, data_path)
             .map_err(StorageError::Io)?;
+        Self::write_sidecar(idx_path, &slots)
+    }
 
-        let mut entries: Vec<(RowId, u64)> = Vec::with_capacity(node_count);
+    /// Visit `(node_id, offset)` of every complete version 1 record in file order
+    fn for_each_legacy_record(
+        file: &mut Box<dyn BackendFile>,
+        mut visit: impl FnMut(RowId, u64),
+    ) -> Result<()> {
+        let file_len = file.len().map_err(StorageError::Io)?;
+        file.seek(SeekFrom::Start(HEADER_SIZE))
+            .map_err(StorageError::Io)?;
         let mut offset = HEADER_SIZE;
         let mut buf8 = [0u8; 8];
         let mut buf4 = [0u8; 4];
 
-        for _ in 0..node_count {
-            if file.read_exact(&mut buf8).is_err() {
-                break;
-            }
-            let node_id = u64::from_le_bytes(buf8);
-            if file.read_exact(&mut buf4).is_err() {
-                break;
-            }
-            let ncount = u32::from_le_bytes(buf4) as usize;
-
-            entries.push((node_id, offset));
-            let record_size = 8 + 4 + (ncount * 8);
-            offset += record_size as u64;
-            if file.seek(SeekFrom::Current((ncount * 8) as i64)).is_err() {
+        while offset + 12 <= file_len {
+            file.read_exact(&mut buf8).map_err(StorageError::Io)?;
+            file.read_exact(&mut buf4).map_err(StorageError::Io)?;
+            let ncount = u32::from_le_bytes(buf4) as u64;
+            let record_size = 12 + ncount * 8;
+            if offset + record_size > file_len {
                 break;
             }
+            visit(u64::from_le_bytes(buf8), offset);
+            offset += record_size;
+            file.seek(SeekFrom::Current((ncount * 8) as i64))
+                .map_err(StorageError::Io)?;
         }
+        Ok(())
+    }
 
-        entries.sort_by_key(|(id, _)| *id);
+    fn read_legacy_record(file: &dyn BackendFile, offset: u64) -> Result<Vec<RowId>> {
+        let mut header = [0u8; 12];
+        file.read_exact_at(&mut header, offset)
+            .map_err(StorageError::Io)?;
+        let count = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        let mut buf = vec![0u8; count * 8];
+        file.read_exact_at(&mut buf, offset + 12)
+            .map_err(StorageError::Io)?;
+        Ok(Self::decode_neighbors(&buf))
+    }
 
-        let mut idx_file = File::create(idx_path).map_err(StorageError::Io)?;
-        let count = entries.len() as u64;
+    /// Write the sorted `node_id → offset` sidecar
+    fn write_sidecar(idx_path: &Path, entries: &[(RowId, u64)]) -> Result<()> {
+        let mut buf = Vec::with_capacity(8 + entries.len() * 16);
+        buf.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (row_id, off) in entries {
+            buf.extend_from_slice(&row_id.to_le_bytes());
+            buf.extend_from_slice(&off.to_le_bytes());
+        }
+        // Swapped in whole: a crash mid-write leaves the previous sidecar
+        backend::backend_for(idx_path)
+            .write_atomic(idx_path, &buf)
+            .map_err(StorageError::Io)
+    }
+
+    fn read_sidecar(idx_path: &Path) -> Result<Vec<(RowId, u64)>> {
+        let idx_file =
+            backend::open_file(idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
+        let mut buf = [0u8; 8];
         idx_file
-            .write_all(&count.to_le_bytes())
+            .read_exact_at(&mut buf, 0)
             .map_err(StorageError::Io)?;
-        for (row_id, off) in &entries {
-            idx_file
-                .write_all(&row_id.to_le_bytes())
-                .map_err(StorageError::Io)?;
-            idx_file
-                .write_all(&off.to_le_bytes())
-                .map_err(StorageError::Io)?;
-        }
-        idx_file.sync_all().map_err(StorageError::Io)?;
+        Self::read_sidecar_entries(idx_file.as_ref(), u64::from_le_bytes(buf))
+    }
 
-        Ok(count)
+    fn read_sidecar_entries(idx_file: &dyn BackendFile, count: u64) -> Result<Vec<(RowId, u64)>> {
+        let mut buf = vec![0u8; count as usize * 16];
+        idx_file
+            .read_exact_at(&mut buf, 8)
+            .map_err(StorageError::Io)?;
+        Ok(buf
+            .chunks_exact(16)
+            .map(|entry| {
+                (
+                    u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                    u64::from_le_bytes(entry[8..].try_into().unwrap()),
+                )
+            })
+            .collect())
     }
 
-    /// Look up file offset: LRU → mmap binary search → sidecar file fallback
+    /// Look up slot offset: slot changes since flush → LRU → mmap binary
+    /// search → sidecar file fallback
     fn lookup_offset(&self, node_id: RowId) -> Option<u64> {
+        if let Some(&slot) = self.pending.read().get(&node_id) {
+            return slot;
+        }
         {
             let mut index = self.index.write();
             if let Some(&offset) = index.get(&node_id) {
@@ -340,18 +436,17 @@ impl DiskGraph {
             }
         }
 
-        // Fallback: seek+read on sidecar file
-        let mut file = self.index_file.write();
+        // Fallback: positional reads on the sidecar file
+        let file = self.index_file.read();
         let entry_size = 16u64;
         let mut lo = 0i64;
         let mut hi = count as i64 - 1;
 
         while lo <= hi {
             let mid = lo + (hi - lo) / 2;
-            let file_offset = 8 + mid as u64 * entry_size;
-            file.seek(SeekFrom::Start(file_offset)).ok()?;
             let mut buf = [0u8; 16];
-            file.read_exact(&mut buf).ok()?;
+            file.read_exact_at(&mut buf, 8 + mid as u64 * entry_size)
+                .ok()?;
             let mid_id = u64::from_le_bytes(buf[..8].try_into().ok()?);
             let mid_offset = u64::from_le_bytes(buf[8..].try_into().ok()?);
 
@@ -405,6 +500,11 @@ impl DiskGraph {
         }
     }
 
+    /// Pinned node IDs, most recently used first
+    pub fn hot_node_ids(&self) -> Vec<RowId> {
+        self.hot_cache.read().iter().map(|(&id, _)| id).collect()
+    }
+
     /// Batch pin high-degree nodes
     pub fn pin_high_degree_nodes(&self, top_k: usize) {
         // Sample a subset of IDs to avoid loading all
@@ -425,21 +525,23 @@ impl DiskGraph {
     }
 
     pub fn node_ids(&self) -> Vec<RowId> {
+        let pending = self.pending.read();
         let count = *self.index_count.read();
-        if count > 0 {
-            let mut file = self.index_file.write();
-            let mut ids = Vec::with_capacity(count as usize);
-            let _ = file.seek(SeekFrom::Start(8));
-            for _ in 0..count {
-                let mut buf = [0u8; 16];
-                if file.read_exact(&mut buf).is_ok() {
-                    ids.push(u64::from_le_bytes(buf[..8].try_into().unwrap()));
-                }
-            }
-            ids
-        } else {
-            self.index.read().iter().map(|(&id, _)| id).collect()
-        }
+        let sidecar =
+            Self::read_sidecar_entries(self.index_file.read().as_ref(), count).unwrap_or_default();
+        let mut ids: Vec<RowId> = sidecar
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| !pending.contains_key(id))
+            .collect();
+        ids.extend(
+            pending
+                .iter()
+                .filter(|(_, slot)| slot.is_some())
+                .map(|(&id, _)| id),
+        );
+        ids.sort_unstable();
+        ids
     }
 
     /// Add node (without neighbors)
@@ -475,21 +577,57 @@ impl DiskGraph {
         }
     }
 
+    /// Visit the neighbors of `node_id` without allocating: cached lists
+    /// are shared and mapped slots are decoded in place, so searches don't
+    /// fill the LRU. `visit` must not call back into the graph.
+    pub fn for_each_neighbor(&self, node_id: RowId, mut visit: impl FnMut(RowId)) {
+        let cached = self
+            .hot_cache
+            .read()
+            .peek(&node_id)
+            .cloned()
+            .or_else(|| self.cache.lock().peek(&node_id).cloned());
+        if let Some(neighbors) = cached {
+            neighbors.iter().for_each(|&id| visit(id));
+            return;
+        }
+
+        let Some(offset) = self.lookup_offset(node_id) else {
+            return;
+        };
+        {
+            let guard = self.mmap.read();
+            if let Some(bytes) = guard
+                .as_deref()
+                .and_then(|map| self.slot_neighbors(map, node_id, offset))
+            {
+                for id in bytes.chunks_exact(8) {
+                    visit(u64::from_le_bytes(id.try_into().unwrap()));
+                }
+                return;
+            }
+        }
+        // Appended since the last remap
+        if let Some(neighbors) = self.get_from_cache_or_disk(node_id) {
+            neighbors.iter().for_each(|&id| visit(id));
+        }
+    }
+
     fn get_from_cache_or_disk(&self, node_id: RowId) -> Option<Arc<Vec<RowId>>> {
         let offset = self.lookup_offset(node_id)?;
-        match self.read_neighbors_at(offset) {
-            Ok(neighbors) => {
+        match self.read_slot(node_id, offset) {
+            Ok(Some(neighbors)) => {
                 let arc = Arc::new(neighbors);
                 self.cache.lock().put(node_id, Arc::clone(&arc));
                 Some(arc)
             }
-            Err(_) => None,
+            _ => None,
         }
     }
 
     /// Set neighbors (replaces existing)
     pub fn set_neighbors(&self, node_id: RowId, mut neighbors: Vec<RowId>) -> Result<()> {
-        // Block during flush to prevent sidecar from being built with stale node_count
+        // Block during flush so the sidecar sees every new slot
         let _flush_guard = self.flush_lock.lock();
         neighbors.retain(|&id| id != node_id);
         neighbors.sort_unstable();
@@ -498,22 +636,20 @@ impl DiskGraph {
             neighbors.truncate(self.max_degree);
         }
 
-        let offset = {
-            let mut next_offset = self.next_offset.lock();
-            let offset = *next_offset;
-            self.write_neighbors_at(node_id, &neighbors, offset)?;
-            let record_size = 8 + 4 + (neighbors.len() * 8);
-            *next_offset += record_size as u64;
-            offset
-        };
-
-        let is_new = {
-            let mut idx = self.index.write();
-            let was_present = idx.get(&node_id).is_some();
-            idx.put(node_id, offset);
-            !was_present
+        // Rewrite the node's slot in place, or give it a free or new one
+        let existing = self.lookup_offset(node_id);
+        let offset = match existing {
+            Some(offset) => offset,
+            None => self.allocate_slot(),
         };
-        if is_new {
+        self.write_at(
+            offset,
+            &Self::encode_slot(node_id, &neighbors, self.max_degree),
+        )?;
+
+        if existing.is_none() {
+            self.pending.write().insert(node_id, Some(offset));
+            self.index.write().put(node_id, offset);
             *self.count.write() += 1;
         }
 
@@ -525,30 +661,33 @@ impl DiskGraph {
         }
 
         *self.dirty.write() = true;
-
-        // Invalidate mmap — file has grown but mmap still maps the old range.
-        // Subsequent reads will use seek+read fallback until next flush() remaps.
-        *self.mmap.write() = None;
-
         Ok(())
     }
 
     /// Remove node
     pub fn remove_node(&self, node_id: RowId) -> Arc<Vec<RowId>> {
         let neighbors = self.neighbors(node_id);
-        let was_present = self.index.write().pop(&node_id).is_some();
+        let _flush_guard = self.flush_lock.lock();
+        // The offset may only be in the sidecar (evicted from the LRU)
+        if let Some(offset) = self.lookup_offset(node_id) {
+            // Freed on disk too, so a reload without flush skips the slot
+            if self.write_at(offset + 8, &FREE_SLOT.to_le_bytes()).is_ok() {
+                self.free_slots.lock().push(offset);
+            }
+            self.pending.write().insert(node_id, None);
+            let mut count = self.count.write();
+            *count = count.saturating_sub(1);
+        }
+        self.index.write().pop(&node_id);
         self.cache.lock().pop(&node_id);
         self.hot_nodes.write().remove(&node_id);
         self.hot_cache.write().pop(&node_id);
-        if was_present {
-            *self.count.write() = self.count.read().saturating_sub(1);
-        }
         *self.dirty.write() = true;
         neighbors
     }
 
-    /// Flush to disk — blocks concurrent set_neighbors to prevent
-    /// the sidecar index from being built with stale node_count.
+    /// Flush to disk — blocks concurrent slot writes so the sidecar lists
+    /// every slot.
     pub fn flush(&self) -> Result<()> {
         if !*self.dirty.read() {
             return Ok(());
@@ -570,95 +709,92 @@ impl DiskGraph {
             Self::write_header(&mut file, self.max_degree, node_count)?;
             file.sync_all().map_err(StorageError::Io)?;
         }
+        self.note_write(HEADER_SIZE as usize);
+
+        // Fold new and removed slots into the sidecar; rewrites in place
+        // leave it alone. Lookups wait on `pending` until it is replaced.
+        let mut pending = self.pending.write();
+        if !pending.is_empty() {
+            let count = *self.index_count.read();
+            let mut entries = Self::read_sidecar_entries(self.index_file.read().as_ref(), count)?;
+            entries.retain(|(id, _)| !pending.contains_key(id));
+            entries.extend(
+                pending
+                    .iter()
+                    .filter_map(|(&id, slot)| slot.map(|offset| (id, offset))),
+            );
+            entries.sort_unstable_by_key(|(id, _)| *id);
 
-        // Rebuild sidecar index
-        if node_count > 0 {
             let idx_path = self.file_path.with_extension("idx");
-            let count = Self::build_sidecar_index(&self.file_path, &idx_path, node_count)?;
-            *self.index_count.write() = count;
-            let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+            *self.idx_mmap.write() = None;
+            Self::write_sidecar(&idx_path, &entries)?;
+            self.note_write(8 + entries.len() * 16);
+            *self.index_count.write() = entries.len() as u64;
+            *self.count.write() = entries.len() as u64;
+            let idx_read =
+                backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
             *self.index_file.write() = idx_read;
+            pending.clear();
         }
 
-        // Remap after flush
+        // Remap so the data mmap covers slots appended since the last one
         self.remap();
+        drop(pending);
 
         *self.dirty.write() = false;
         Ok(())
     }
 
-    /// Compact graph file (full rewrite)
+    /// Compact graph file (full rewrite without freed slots)
     pub fn compact(&self) -> Result<()> {
         let _guard = self.flush_lock.lock(); // Prevent concurrent set_neighbors during compact
         let temp_path = self.file_path.with_extension("tmp");
         let idx_path = self.file_path.with_extension("idx");
 
-        {
-            let mut temp_file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(&temp_path)
+        let (slots, end) = {
+            let mut temp_file = backend::open_file(&temp_path, OpenFlags::create_truncate())
                 .map_err(StorageError::Io)?;
 
-            // Get all node IDs from sidecar
             let ids = self.node_ids();
             Self::write_header(&mut temp_file, self.max_degree, ids.len())?;
 
-            let mut new_entries: Vec<(RowId, u64)> = Vec::with_capacity(ids.len());
+            let mut slots: Vec<(RowId, u64)> = Vec::with_capacity(ids.len());
             let mut offset = HEADER_SIZE;
-
             for &node_id in &ids {
                 let neighbors = self.neighbors(node_id);
                 temp_file
-                    .write_all(&node_id.to_le_bytes())
-                    .map_err(StorageError::Io)?;
-                temp_file
-                    .write_all(&(neighbors.len() as u32).to_le_bytes())
+                    .write_all(&Self::encode_slot(node_id, &neighbors, self.max_degree))
                     .map_err(StorageError::Io)?;
-                for &neighbor in neighbors.iter() {
-                    temp_file
-                        .write_all(&neighbor.to_le_bytes())
-                        .map_err(StorageError::Io)?;
-                }
-                new_entries.push((node_id, offset));
-                let record_size = 8 + 4 + (neighbors.len() * 8);
-                offset += record_size as u64;
+                slots.push((node_id, offset));
+                offset += self.slot_size;
             }
-
             temp_file.sync_all().map_err(StorageError::Io)?;
+            (slots, offset)
+        };
 
-            // Write new sidecar
-            new_entries.sort_by_key(|(id, _)| *id);
-            let mut idx_file = File::create(&idx_path).map_err(StorageError::Io)?;
-            let count = new_entries.len() as u64;
-            idx_file
-                .write_all(&count.to_le_bytes())
-                .map_err(StorageError::Io)?;
-            for (row_id, off) in &new_entries {
-                idx_file
-                    .write_all(&row_id.to_le_bytes())
-                    .map_err(StorageError::Io)?;
-                idx_file
-                    .write_all(&off.to_le_bytes())
-                    .map_err(StorageError::Io)?;
-            }
-            idx_file.sync_all().map_err(StorageError::Io)?;
+        // Drop the maps before their files are replaced
+        *self.mmap.write() = None;
+        *self.idx_mmap.write() = None;
 
-            *self.next_offset.lock() = offset;
-        }
+        Self::write_sidecar(&idx_path, &slots)?;
+        self.note_write(end as usize + 8 + slots.len() * 16);
 
-        std::fs::rename(&temp_path, &self.file_path).map_err(StorageError::Io)?;
+        backend::backend_for(&self.file_path)
+            .rename(&temp_path, &self.file_path)
+            .map_err(StorageError::Io)?;
 
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(&self.file_path)
+        let file = backend::open_file(&self.file_path, OpenFlags::read_write())
             .map_err(StorageError::Io)?;
         *self.file.write() = file;
 
-        let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+        let idx_read =
+            backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
         *self.index_file.write() = idx_read;
+        *self.index_count.write() = slots.len() as u64;
+        *self.count.write() = slots.len() as u64;
+        *self.next_offset.lock() = end;
+        self.pending.write().clear();
+        self.free_slots.lock().clear();
         // Clear LRU index (offsets changed) and neighbor caches (stale data)
         self.index.write().clear();
         self.cache.lock().clear();
@@ -673,6 +809,8 @@ impl DiskGraph {
 
     pub fn clear(&self) {
         self.index.write().clear();
+        self.pending.write().clear();
+        self.free_slots.lock().clear();
         self.cache.lock().clear();
         self.hot_nodes.write().clear();
         self.hot_cache.write().clear();
@@ -686,9 +824,24 @@ impl DiskGraph {
         cache_size + hot_size
     }
 
+    /// Bytes of node slots in the graph file, including slots freed by
+    /// removed nodes
+    pub fn data_bytes(&self) -> u64 {
+        self.next_offset.lock().saturating_sub(HEADER_SIZE)
+    }
+
+    /// Bytes of slots freed by removed nodes and not reused yet
+    pub fn free_bytes(&self) -> u64 {
+        self.free_slots.lock().len() as u64 * self.slot_size
+    }
+
+    /// Bytes written to the graph and sidecar files since open
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
     pub fn disk_usage(&self) -> usize {
-        let count = self.node_count();
-        count * (8 + 4 + 32 * 8)
+        self.node_count() * self.slot_size as usize
     }
 
     // --- Private helpers ---
@@ -697,15 +850,19 @@ impl DiskGraph {
     fn remap(&self) {
         {
             let file = self.file.read();
-            *self.mmap.write() = unsafe { Mmap::map(&*file).ok() };
+            *self.mmap.write() = file.map().ok();
         }
         {
             let idx = self.index_file.read();
-            *self.idx_mmap.write() = unsafe { Mmap::map(&*idx).ok() };
+            *self.idx_mmap.write() = idx.map().ok();
         }
     }
 
-    fn write_header(file: &mut File, max_degree: usize, node_count: usize) -> Result<()> {
+    fn write_header(
+        file: &mut Box<dyn BackendFile>,
+        max_degree: usize,
+        node_count: usize,
+    ) -> Result<()> {
         file.seek(SeekFrom::Start(0)).map_err(StorageError::Io)?;
         file.write_all(&MAGIC.to_le_bytes())
             .map_err(StorageError::Io)?;
@@ -718,7 +875,8 @@ impl DiskGraph {
         Ok(())
     }
 
-    fn read_header(file: &mut File) -> Result<(usize, usize)> {
+    /// `(version, max_degree, node_count)`
+    fn read_header(file: &mut Box<dyn BackendFile>) -> Result<(u32, usize, usize)> {
         file.seek(SeekFrom::Start(0)).map_err(StorageError::Io)?;
         let mut buf = [0u8; 4];
 
@@ -729,73 +887,111 @@ impl DiskGraph {
         }
 
         file.read_exact(&mut buf).map_err(StorageError::Io)?;
-        let _version = u32::from_le_bytes(buf);
+        let version = u32::from_le_bytes(buf);
+        if version != VERSION && version != LEGACY_VERSION {
+            return Err(StorageError::InvalidData(format!(
+                "Unsupported graph file version: {}",
+                version
+            )));
+        }
         file.read_exact(&mut buf).map_err(StorageError::Io)?;
         let max_degree = u32::from_le_bytes(buf) as usize;
         file.read_exact(&mut buf).map_err(StorageError::Io)?;
         let node_count = u32::from_le_bytes(buf) as usize;
 
-        Ok((max_degree, node_count))
+        Ok((version, max_degree, node_count))
+    }
+
+    fn allocate_slot(&self) -> u64 {
+        if let Some(offset) = self.free_slots.lock().pop() {
+            return offset;
+        }
+        let mut next_offset = self.next_offset.lock();
+        let offset = *next_offset;
+        *next_offset += self.slot_size;
+        offset
+    }
+
+    fn encode_slot(node_id: RowId, neighbors: &[RowId], max_degree: usize) -> Vec<u8> {
+        let mut slot = Vec::with_capacity(Self::slot_size_for(max_degree) as usize);
+        slot.extend_from_slice(&node_id.to_le_bytes());
+        slot.extend_from_slice(&(neighbors.len() as u32).to_le_bytes());
+        slot.extend_from_slice(&0u32.to_le_bytes());
+        for &neighbor in neighbors {
+            slot.extend_from_slice(&neighbor.to_le_bytes());
+        }
+        slot.resize(Self::slot_size_for(max_degree) as usize, 0);
+        slot
     }
 
-    fn write_neighbors_at(&self, node_id: RowId, neighbors: &[RowId], offset: u64) -> Result<()> {
+    /// Write into the data file. A shared mmap shows the new bytes, and
+    /// holding it exclusively keeps readers from seeing half a slot; a
+    /// loaded copy (no mmap support) is dropped instead.
+    fn write_at(&self, offset: u64, bytes: &[u8]) -> Result<()> {
         let mut file = self.file.write();
+        let mut mmap = self.mmap.write();
+        if matches!(*mmap, Some(FileMap::Loaded(_))) {
+            *mmap = None;
+        }
         file.seek(SeekFrom::Start(offset))
             .map_err(StorageError::Io)?;
-        file.write_all(&node_id.to_le_bytes())
-            .map_err(StorageError::Io)?;
-        file.write_all(&(neighbors.len() as u32).to_le_bytes())
-            .map_err(StorageError::Io)?;
-        for &neighbor in neighbors {
-            file.write_all(&neighbor.to_le_bytes())
-                .map_err(StorageError::Io)?;
-        }
+        file.write_all(bytes).map_err(StorageError::Io)?;
+        self.note_write(bytes.len());
         Ok(())
     }
 
-    fn read_neighbors_at(&self, offset: u64) -> Result<Vec<RowId>> {
+    fn note_write(&self, bytes: usize) {
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Neighbor id bytes of the slot at `offset` in `bytes`, if the slot
+    /// is in range and holds `node_id`
+    fn slot_neighbors<'a>(&self, bytes: &'a [u8], node_id: RowId, offset: u64) -> Option<&'a [u8]> {
+        let start = offset as usize;
+        let header = bytes.get(start..start + SLOT_HEADER)?;
+        if u64::from_le_bytes(header[..8].try_into().unwrap()) != node_id {
+            return None;
+        }
+        let count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if count == FREE_SLOT || count as usize > self.max_degree {
+            return None;
+        }
+        let neighbors_start = start + SLOT_HEADER;
+        bytes.get(neighbors_start..neighbors_start + count as usize * 8)
+    }
+
+    fn decode_neighbors(bytes: &[u8]) -> Vec<RowId> {
+        bytes
+            .chunks_exact(8)
+            .map(|id| u64::from_le_bytes(id.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Neighbors stored in `node_id`'s slot, or None if the slot is gone
+    fn read_slot(&self, node_id: RowId, offset: u64) -> Result<Option<Vec<RowId>>> {
         // Try mmap path (zero syscall)
         {
             let guard = self.mmap.read();
-            if let Some(ref mmap) = *guard {
-                let off = offset as usize;
-                if off + 12 <= mmap.len() {
-                    let _node_id = u64::from_le_bytes(mmap[off..off + 8].try_into().unwrap());
-                    let count =
-                        u32::from_le_bytes(mmap[off + 8..off + 12].try_into().unwrap()) as usize;
-                    let neighbors_start = off + 12;
-                    let neighbors_end = neighbors_start + count * 8;
-                    if neighbors_end <= mmap.len() {
-                        let mut neighbors = Vec::with_capacity(count);
-                        for i in 0..count {
-                            let n_off = neighbors_start + i * 8;
-                            neighbors.push(u64::from_le_bytes(
-                                mmap[n_off..n_off + 8].try_into().unwrap(),
-                            ));
-                        }
-                        return Ok(neighbors);
-                    }
-                }
-                // mmap out of bounds — stale mmap after write, fall through to seek+read
+            if let Some(bytes) = guard
+                .as_deref()
+                .and_then(|map| self.slot_neighbors(map, node_id, offset))
+            {
+                return Ok(Some(Self::decode_neighbors(bytes)));
             }
         }
 
-        // Fallback: seek+read
-        let mut file = self.file.write();
-        file.seek(SeekFrom::Start(offset))
-            .map_err(StorageError::Io)?;
-        let mut buf8 = [0u8; 8];
-        file.read_exact(&mut buf8).map_err(StorageError::Io)?;
-        let mut buf4 = [0u8; 4];
-        file.read_exact(&mut buf4).map_err(StorageError::Io)?;
-        let count = u32::from_le_bytes(buf4) as usize;
-
-        let mut neighbors = Vec::with_capacity(count);
-        for _ in 0..count {
-            file.read_exact(&mut buf8).map_err(StorageError::Io)?;
-            neighbors.push(u64::from_le_bytes(buf8));
+        // Fallback: positional read (slot appended after the mmap)
+        let file = self.file.read();
+        if offset + self.slot_size > file.len().map_err(StorageError::Io)? {
+            return Ok(None);
         }
-        Ok(neighbors)
+        let mut slot = vec![0u8; self.slot_size as usize];
+        file.read_exact_at(&mut slot, offset)
+            .map_err(StorageError::Io)?;
+        Ok(self
+            .slot_neighbors(&slot, node_id, 0)
+            .map(Self::decode_neighbors))
     }
 }
 
@@ -858,4 +1054,133 @@ mod tests {
             assert_eq!(n.len(), 1, "node {} should have 1 neighbor", i);
         }
     }
+
+    #[test]
+    fn test_disk_graph_rewrites_and_removals_survive_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let graph = DiskGraph::create_with_hot_limit(temp_dir.path(), 32, 2, 5).unwrap();
+            for i in 0..10u64 {
+                graph.set_neighbors(i, vec![(i + 1) % 10]).unwrap();
+            }
+            graph.flush().unwrap();
+
+            // Rewrites land in each node's slot
+            for i in 0..10u64 {
+                graph
+                    .set_neighbors(i, vec![(i + 2) % 10, (i + 3) % 10])
+                    .unwrap();
+            }
+            // Evicted from the LRU, so only the sidecar knows these nodes
+            graph.remove_node(0);
+            graph.remove_node(1);
+            assert!(graph.neighbors(0).is_empty());
+            assert_eq!(graph.node_count(), 8);
+            graph.flush().unwrap();
+            assert_eq!(graph.node_ids(), (2..10).collect::<Vec<_>>());
+        }
+
+        let graph = DiskGraph::load(temp_dir.path(), 2).unwrap();
+        assert_eq!(graph.node_count(), 8);
+        assert!(graph.neighbors(1).is_empty());
+        assert_eq!(*graph.neighbors(9), vec![1, 2]);
+        // New nodes take freed slots without disturbing live ones
+        graph.set_neighbors(10, vec![2]).unwrap();
+        assert_eq!(*graph.neighbors(9), vec![1, 2]);
+        assert_eq!(*graph.neighbors(10), vec![2]);
+    }
+
+    #[test]
+    fn test_disk_graph_updates_slots_in_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let graph = DiskGraph::create_with_hot_limit(temp_dir.path(), 8, 2, 5).unwrap();
+        for i in 0..10u64 {
+            graph.set_neighbors(i, vec![(i + 1) % 10]).unwrap();
+        }
+        graph.flush().unwrap();
+        let slots = graph.data_bytes();
+
+        for i in 0..10u64 {
+            graph
+                .set_neighbors(i, vec![(i + 2) % 10, (i + 3) % 10])
+                .unwrap();
+        }
+        assert_eq!(graph.data_bytes(), slots);
+        // No slot moved, so the sidecar isn't rewritten
+        let written = graph.bytes_written();
+        graph.flush().unwrap();
+        assert_eq!(graph.bytes_written() - written, HEADER_SIZE);
+
+        graph.remove_node(3);
+        assert_eq!(graph.free_bytes(), graph.slot_size);
+        graph.set_neighbors(42, vec![1]).unwrap();
+        assert_eq!((graph.data_bytes(), graph.free_bytes()), (slots, 0));
+
+        // Mapped slots are read without filling the LRU
+        graph.flush().unwrap();
+        graph.cache.lock().clear();
+        let mut seen = Vec::new();
+        graph.for_each_neighbor(5, |id| seen.push(id));
+        assert_eq!(seen, vec![7, 8]);
+        assert!(graph.cache.lock().is_empty());
+        graph.for_each_neighbor(3, |_| panic!("removed node has no neighbors"));
+    }
+
+    #[test]
+    fn test_disk_graph_upgrades_legacy_file() {
+        let temp_dir = TempDir::new().unwrap();
+        // Version 1: records appended per update, the sidecar names the latest
+        let mut data = Vec::new();
+        for v in [MAGIC, LEGACY_VERSION, 4, 2] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        let mut records = Vec::new();
+        for (node_id, neighbors) in [(1u64, vec![2u64]), (2, vec![1]), (1, vec![2, 3])] {
+            records.push((node_id, data.len() as u64));
+            data.extend_from_slice(&node_id.to_le_bytes());
+            data.extend_from_slice(&(neighbors.len() as u32).to_le_bytes());
+            for n in neighbors {
+                data.extend_from_slice(&n.to_le_bytes());
+            }
+        }
+        std::fs::write(temp_dir.path().join("graph.bin"), &data).unwrap();
+        DiskGraph::write_sidecar(
+            &temp_dir.path().join("graph.idx"),
+            &[records[2], records[1]],
+        )
+        .unwrap();
+
+        let graph = DiskGraph::load(temp_dir.path(), 16).unwrap();
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(*graph.neighbors(1), vec![2, 3]);
+        assert_eq!(*graph.neighbors(2), vec![1]);
+        graph.set_neighbors(2, vec![1, 3]).unwrap();
+        graph.flush().unwrap();
+        drop(graph);
+
+        let graph = DiskGraph::load(temp_dir.path(), 16).unwrap();
+        assert_eq!(graph.node_ids(), vec![1, 2]);
+        assert_eq!(*graph.neighbors(2), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_disk_graph_reload_without_flush_uses_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let graph = DiskGraph::create(temp_dir.path(), 8, 16).unwrap();
+            for i in 0..5u64 {
+                graph.set_neighbors(i, vec![(i + 1) % 5]).unwrap();
+            }
+            graph.flush().unwrap();
+            graph.remove_node(0);
+            graph.set_neighbors(7, vec![1]).unwrap();
+            graph.set_neighbors(8, vec![2]).unwrap();
+            // Dropped without flush: the sidecar still lists node 0
+        }
+
+        let graph = DiskGraph::load(temp_dir.path(), 16).unwrap();
+        assert_eq!(graph.node_ids(), vec![1, 2, 3, 4, 7, 8]);
+        assert!(graph.neighbors(0).is_empty());
+        assert_eq!(*graph.neighbors(8), vec![2]);
+    }
 }
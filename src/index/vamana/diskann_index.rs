@@ -11,18 +11,20 @@
 
 use super::config::VamanaConfig;
 use super::disk_graph::DiskGraph;
+use super::index_wal::{IndexWal, LogOp};
 use super::pruner::{robust_prune, Candidate};
 use super::sq8::SQ8Quantizer;
 use super::sq8_vectors::SQ8Vectors;
 use crate::distance::DistanceKind;
 use crate::types::RowId;
 use crate::{Result, StorageError};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -171,6 +173,12 @@ impl VectorStorage {
 pub struct DiskANNIndex {
     dimension: usize,
 
+    /// Index directory
+    data_dir: PathBuf,
+
+    /// Mutations since the last flush (`None` until the first flush)
+    log: Mutex<Option<IndexWal>>,
+
     /// Vector storage (F16/F32 or PQ compressed)
     vectors: VectorStorage,
 
@@ -192,6 +200,9 @@ pub struct DiskANNIndex {
     /// SSD optimization state
     last_reorder_size: Arc<RwLock<usize>>,
     total_inserts_since_reorder: Arc<RwLock<usize>>,
+
+    /// Upper bound on the search list size (0 = none), lowered under memory pressure
+    search_width_limit: AtomicUsize,
 }
 
 impl DiskANNIndex {
@@ -234,6 +245,8 @@ impl DiskANNIndex {
 
         Ok(Self {
             dimension,
+            data_dir: data_dir.to_path_buf(),
+            log: Mutex::new(None),
             vectors,
             graph,
             medoid: Arc::new(RwLock::new(None)),
@@ -242,6 +255,7 @@ impl DiskANNIndex {
             cached_stats: Arc::new(RwLock::new(None)),
             last_reorder_size: Arc::new(RwLock::new(0)),
             total_inserts_since_reorder: Arc::new(RwLock::new(0)),
+            search_width_limit: AtomicUsize::new(0),
         })
     }
 
@@ -257,7 +271,8 @@ impl DiskANNIndex {
         let quantizer_path = data_dir.join("quantizer.sq8");
         let sq8_vectors_path = data_dir.join("vectors_sq8.bin");
 
-        if !quantizer_path.exists() || !sq8_vectors_path.exists() {
+        let backend = crate::storage::backend::backend_for(data_dir);
+        if !backend.exists(&quantizer_path) || !backend.exists(&sq8_vectors_path) {
             return Err(StorageError::InvalidData(
                 "SQ8 index not found (looking for quantizer.sq8 and vectors_sq8.bin)".to_string(),
             ));
@@ -297,8 +312,10 @@ impl DiskANNIndex {
             graph.pin_high_degree_nodes(100);
         }
 
-        Ok(Self {
+        let index = Self {
             dimension,
+            data_dir: data_dir.to_path_buf(),
+            log: Mutex::new(None),
             vectors,
             graph,
             medoid: Arc::new(RwLock::new(medoid)),
@@ -307,7 +324,112 @@ impl DiskANNIndex {
             cached_stats: Arc::new(RwLock::new(None)),
             last_reorder_size: Arc::new(RwLock::new(initial_size)),
             total_inserts_since_reorder: Arc::new(RwLock::new(0)),
-        })
+            search_width_limit: AtomicUsize::new(0),
+        };
+
+        // Redo what the last session logged but never flushed
+        if IndexWal::exists(data_dir) {
+            index.recover()?;
+        }
+
+        Ok(index)
+    }
+
+    /// Whether the index in `data_dir` recovers in place on load: it keeps
+    /// a mutation log, so files left mid-update by a crash are replayed and
+    /// repaired instead of discarded
+    pub fn recovers_in_place(data_dir: &Path) -> bool {
+        IndexWal::exists(data_dir)
+    }
+
+    /// Replay the mutation log, repair the graph against the stored vectors
+    /// and flush, which starts an empty log
+    fn recover(&self) -> Result<()> {
+        let (log, ops) = IndexWal::open(&self.data_dir)?;
+        let replayed = ops.len();
+        for op in ops {
+            match op {
+                // Idempotent: the insert may have reached the files already
+                LogOp::Insert(row_id, vector) => {
+                    if self.vectors.get(row_id).is_some() {
+                        self.apply_update(row_id, vector)?;
+                    } else {
+                        self.apply_insert(row_id, vector)?;
+                    }
+                }
+                LogOp::Update(row_id, vector) => {
+                    self.apply_update(row_id, vector)?;
+                }
+                LogOp::Delete(row_id) => {
+                    self.apply_delete(row_id)?;
+                }
+            }
+        }
+        *self.log.lock() = Some(log);
+
+        let repaired = self.repair_graph(replayed > 0)?;
+        if replayed > 0 || repaired > 0 {
+            debug_log!(
+                "[DiskANN] Recovered {:?}: {} logged mutations, {} graph nodes repaired",
+                self.data_dir,
+                replayed,
+                repaired
+            );
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Make the graph match the stored vectors after a crash: unlink nodes
+    /// whose vector is gone and link vectors without a node. With `edges`,
+    /// also drop edges to missing vectors from every node. Returns the
+    /// number of nodes removed or linked.
+    fn repair_graph(&self, edges: bool) -> Result<usize> {
+        let live: HashSet<RowId> = self.vectors.ids().into_iter().collect();
+        let nodes: HashSet<RowId> = self.graph.node_ids().into_iter().collect();
+        let medoid = *self.medoid.read();
+
+        // Their neighbors lose the dangling edge in the pass below
+        let orphans: Vec<RowId> = nodes.difference(&live).copied().collect();
+        for &node_id in &orphans {
+            self.graph.remove_node(node_id);
+        }
+
+        if edges || !orphans.is_empty() {
+            for &node_id in nodes.intersection(&live) {
+                let neighbors = self.graph.neighbors(node_id);
+                if neighbors.iter().any(|id| !live.contains(id)) {
+                    let kept = neighbors
+                        .iter()
+                        .copied()
+                        .filter(|id| live.contains(id))
+                        .collect();
+                    self.graph.set_neighbors(node_id, kept)?;
+                }
+            }
+        }
+
+        // A lone medoid legitimately has no node until something links to it
+        let unlinked: Vec<RowId> = live
+            .difference(&nodes)
+            .copied()
+            .filter(|&id| Some(id) != medoid)
+            .collect();
+        if let Some(medoid_id) = medoid {
+            for &row_id in &unlinked {
+                self.incremental_insert_into_graph(row_id, medoid_id)?;
+            }
+        }
+
+        Ok(orphans.len() + unlinked.len())
+    }
+
+    /// Append to the mutation log, if the index keeps one yet
+    fn log_op(&self, write: impl FnOnce(&mut IndexWal) -> Result<()>) -> Result<()> {
+        match self.log.lock().as_mut() {
+            Some(log) => write(log),
+            None => Ok(()),
+        }
     }
 
     pub fn dimension(&self) -> usize {
@@ -331,6 +453,8 @@ impl DiskANNIndex {
         debug_log!("[DiskANN] Building index for {} vectors...", vectors.len());
         let _start = Instant::now();
 
+        self.log_op(|log| log.log_inserts(&vectors))?;
+
         // 1. Insert all vectors to disk
         let _vector_start = Instant::now();
         self.vectors.batch_insert(vectors.clone())?;
@@ -381,6 +505,12 @@ impl DiskANNIndex {
             )));
         }
 
+        self.log_op(|log| log.log_insert(row_id, &vector))?;
+        self.apply_insert(row_id, vector)
+    }
+
+    /// Insert without logging (the caller logged it, or is replaying the log)
+    fn apply_insert(&self, row_id: RowId, vector: Vec<f32>) -> Result<()> {
         // Insert vector
         self.vectors.insert(row_id, vector)?;
 
@@ -414,6 +544,8 @@ impl DiskANNIndex {
         debug_log!("[DiskANN] Batch inserting {} vectors...", count);
         let _start = Instant::now();
 
+        self.log_op(|log| log.log_inserts(vectors))?;
+
         // 1. Batch write vectors (single fsync at the end)
         let _vector_write_start = Instant::now();
         self.vectors.batch_insert(vectors.to_vec())?;
@@ -478,46 +610,7 @@ impl DiskANNIndex {
         let mut shuffled = ids.to_vec();
         shuffled.shuffle(&mut thread_rng());
 
-        let new_count = shuffled.len();
-        let _total_count = self.len(); // 🚀 关键：检查总节点数，不只是新增数量
-        let show_progress = true;
-
-        // 🔥 方案A改进：分批渐进式构建（避免O(N²)复杂度）
-        //
-        // **问题**：全图搜索导致O(N²)复杂度
-        // - 节点1搜索1个节点
-        // - 节点2搜索2个节点
-        // - ...
-        // - 节点N搜索N个节点
-        // - 总复杂度：Σi = O(N²)，10万节点 = 50亿次操作！
-        //
-        // **解决方案**：分批构建 + 合并
-        // - 将N个节点分成 N/5000 批，每批5000个
-        // - 每批内部并行构建（batch内全图搜索）
-        // - 批与批之间只更新必要的边（避免全图重建）
-        // - 复杂度：O(N * 5000 + N * log(N/5000)) ≈ O(N)
-        //
-        // **预期性能**：
-        // - 10万节点：5-10分钟（vs 当前20分钟）
-        // - 召回率：85%+（保持高质量）
-
-        let batch_size = 5000; // 每批5000节点，平衡速度和质量
-        let num_batches = new_count.div_ceil(batch_size);
-
-        if show_progress {
-            debug_log!(
-                "[DiskANN] 🔥 Progressive Batch Build: {} nodes in {} batches",
-                new_count,
-                num_batches
-            );
-            debug_log!("[DiskANN] Batch size: {}, efConstruction=400", batch_size);
-        }
-
-        // 🚀 分批渐进式构建
-        use dashmap::DashMap;
-        use std::sync::atomic::{AtomicUsize, Ordering};
-
-        let ef_construction = 400;
+        debug_log!("[DiskANN] Batch build: linking {} nodes", shuffled.len());
 
         // 预排序：按距离medoid排序（保证核心区域高质量）
         let medoid_vec = match self.vectors.get(medoid_id) {
@@ -540,146 +633,25 @@ impl DiskANNIndex {
             .collect();
 
         nodes_with_dist.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-        let sorted_ids: Vec<RowId> = nodes_with_dist.into_iter().map(|(id, _)| id).collect();
-
-        // 分批处理
-        for batch_idx in 0..num_batches {
-            let batch_start = batch_idx * batch_size;
-            let batch_end = ((batch_idx + 1) * batch_size).min(sorted_ids.len());
-            let batch = &sorted_ids[batch_start..batch_end];
-
-            if show_progress {
-                debug_log!(
-                    "\n[DiskANN] === Batch {}/{} === ({} nodes)",
-                    batch_idx + 1,
-                    num_batches,
-                    batch.len()
-                );
-            }
 
-            let progress = AtomicUsize::new(0);
-            let temp_graph: DashMap<RowId, Vec<RowId>> = DashMap::new();
-
-            // 添加本批节点
-            for &id in batch {
-                self.graph.add_node(id);
-            }
-
-            // Sequential graph construction (avoids Rayon/parking_lot deadlock)
-            // Parallelism is counterproductive here due to DiskGraph lock contention.
-            if show_progress {
-                debug_log!("[DiskANN] Phase 1: Building batch nodes (sequential)...");
-            }
-
-            for &id in batch.iter() {
-                let query_vec = match self.vectors.get(id) {
-                    Some(v) => v,
-                    None => continue,
-                };
-
-                let candidates = self.greedy_search(&query_vec, medoid_id, ef_construction)?;
-
-                let neighbors = robust_prune(
-                    candidates,
-                    self.config.max_degree,
-                    self.config.alpha,
-                    |a, b| match (self.vectors.get(a), self.vectors.get(b)) {
-                        (Some(vec_a), Some(vec_b)) => self.metric.distance(&vec_a, &vec_b),
-                        _ => f32::MAX,
-                    },
-                );
-
-                temp_graph.insert(id, neighbors);
-
-                if show_progress {
-                    let p = progress.fetch_add(1, Ordering::Relaxed);
-                    if p.is_multiple_of(500) && p > 0 {
-                        debug_log!("  Progress: {}/{}", p, batch.len());
-                    }
-                }
-            }
-
-            // Phase 2: 写入前向边
-            if show_progress {
-                debug_log!("[DiskANN] Phase 2: Writing forward edges...");
-            }
-
-            for entry in temp_graph.iter() {
-                self.graph
-                    .set_neighbors(*entry.key(), entry.value().clone())?;
-            }
-
-            // Phase 3: 收集并更新反向边
-            if show_progress {
-                debug_log!("[DiskANN] Phase 3: Updating reverse edges...");
-            }
-
-            let reverse_edges: DashMap<RowId, Vec<RowId>> = DashMap::new();
-
-            for entry in temp_graph.iter() {
-                let id = *entry.key();
-                let neighbors = entry.value();
-
-                for &neighbor_id in neighbors {
-                    reverse_edges.entry(neighbor_id).or_default().push(id);
-                }
-            }
-
-            let slack_factor = 1.3;
-            let soft_limit = (self.config.max_degree as f32 * slack_factor) as usize;
-
-            for entry in reverse_edges.iter() {
-                let node_id = *entry.key();
-                let incoming = entry.value();
-                let neighbors_arc = self.graph.neighbors(node_id);
-                let mut neighbors = (*neighbors_arc).clone();
-
-                for &incoming_id in incoming {
-                    if neighbors.contains(&incoming_id) {
-                        continue;
-                    }
-
-                    neighbors.push(incoming_id);
-
-                    if neighbors.len() > soft_limit {
-                        let node_vec = match self.vectors.get(node_id) {
-                            Some(v) => v,
-                            None => continue,
-                        };
-
-                        let candidates: Vec<Candidate> = neighbors
-                            .iter()
-                            .filter_map(|&nid| {
-                                let vec = self.vectors.get(nid)?;
-                                let dist = self.metric.distance(&node_vec, &vec);
-                                Some(Candidate {
-                                    id: nid,
-                                    distance: dist,
-                                })
-                            })
-                            .collect();
-
-                        neighbors = robust_prune(
-                            candidates,
-                            self.config.max_degree,
-                            self.config.alpha,
-                            |a, b| match (self.vectors.get(a), self.vectors.get(b)) {
-                                (Some(vec_a), Some(vec_b)) => self.metric.distance(&vec_a, &vec_b),
-                                _ => f32::MAX,
-                            },
-                        );
-                    }
-                }
-
-                self.graph.set_neighbors(node_id, neighbors)?;
-            }
-        } // End of batch loop
+        // Link nodes one at a time so each is searched against a graph that
+        // already holds the ones before it. Searching a whole batch against
+        // the pre-batch graph and writing the edges afterwards left a batch
+        // inserted into an empty index as a star around the medoid.
+        for (id, _) in nodes_with_dist {
+            self.incremental_insert_into_graph(id, medoid_id)?;
+        }
 
         Ok(())
     }
 
     /// 🚀 **增量更新（只更新受影响的边）**
     pub fn update(&self, row_id: RowId, vector: Vec<f32>) -> Result<bool> {
+        self.log_op(|log| log.log_update(row_id, &vector))?;
+        self.apply_update(row_id, vector)
+    }
+
+    fn apply_update(&self, row_id: RowId, vector: Vec<f32>) -> Result<bool> {
         let existed = self.vectors.update(row_id, vector)?;
 
         if existed {
@@ -694,26 +666,50 @@ impl DiskANNIndex {
 
     /// Delete vector
     pub fn delete(&self, row_id: RowId) -> Result<bool> {
+        self.log_op(|log| log.log_delete(row_id))?;
+        self.apply_delete(row_id)
+    }
+
+    fn apply_delete(&self, row_id: RowId) -> Result<bool> {
         let removed = self.vectors.delete(row_id)?;
 
         if removed {
             // Remove from graph
             let neighbors = self.graph.remove_node(row_id);
 
-            // Clean up reverse edges
-            for neighbor in neighbors.iter() {
-                // ✅ P1: Arc auto-derefs
-                let neighbor_edges_arc = self.graph.neighbors(*neighbor);
-                let mut neighbor_edges = (*neighbor_edges_arc).clone(); // ✅ P1: Clone for modification
-                neighbor_edges.retain(|&id| id != row_id);
-                self.graph.set_neighbors(*neighbor, neighbor_edges)?;
+            // Clean up reverse edges, bridging each neighbor to the deleted
+            // node's other neighbors so paths through it survive
+            for &neighbor in neighbors.iter() {
+                let mut neighbor_edges: Vec<RowId> = self
+                    .graph
+                    .neighbors(neighbor)
+                    .iter()
+                    .copied()
+                    .filter(|&id| id != row_id)
+                    .collect();
+                for &other in neighbors.iter() {
+                    if other != neighbor && !neighbor_edges.contains(&other) {
+                        neighbor_edges.push(other);
+                    }
+                }
+
+                if neighbor_edges.len() > self.graph.max_degree() {
+                    neighbor_edges =
+                        self.closest_edges(neighbor, &neighbor_edges, self.graph.max_degree());
+                }
+                self.graph.set_neighbors(neighbor, neighbor_edges)?;
             }
 
-            // If deleted node was the medoid, pick a new one
+            // If deleted node was the medoid, pick a new one (preferably a
+            // former neighbor, which the bridging above kept connected)
             {
                 let mut medoid_guard = self.medoid.write();
                 if medoid_guard.is_some_and(|m| m == row_id) {
-                    *medoid_guard = self.vectors.ids().first().copied();
+                    *medoid_guard = neighbors
+                        .iter()
+                        .copied()
+                        .find(|&id| self.vectors.get(id).is_some())
+                        .or_else(|| self.vectors.ids().first().copied());
                 }
             }
         }
@@ -726,8 +722,47 @@ impl DiskANNIndex {
         self.config.metric
     }
 
+    /// Graph nodes currently pinned in the hot cache, most recently used first
+    pub fn pinned_nodes(&self) -> Vec<RowId> {
+        self.graph.hot_node_ids()
+    }
+
+    /// Pin `node_ids` into the hot cache (warm-up after reopen). Nodes that
+    /// no longer exist are skipped.
+    pub fn pin_nodes(&self, node_ids: &[RowId]) {
+        for &id in node_ids.iter().rev() {
+            self.graph.pin_hot_node(id);
+        }
+    }
+
+    /// Cap the search list size of later searches (`None` restores the
+    /// configured width). Trades recall for memory and latency.
+    pub fn set_search_width_limit(&self, limit: Option<usize>) {
+        self.search_width_limit
+            .store(limit.unwrap_or(0), AtomicOrdering::Relaxed);
+    }
+
+    /// Current search list size cap, if any
+    pub fn search_width_limit(&self) -> Option<usize> {
+        match self.search_width_limit.load(AtomicOrdering::Relaxed) {
+            0 => None,
+            limit => Some(limit),
+        }
+    }
+
     /// Search for k nearest neighbors
     pub fn search(&self, query: &[f32], k: usize) -> Result<Vec<(RowId, f32)>> {
+        self.search_with_width(query, k, None)
+    }
+
+    /// Search for k nearest neighbors with a candidate list of `width`
+    /// (None = `search_list_size`); the memory-pressure cap still applies
+    pub fn search_with_width(
+        &self,
+        query: &[f32],
+        k: usize,
+        width: Option<usize>,
+    ) -> Result<Vec<(RowId, f32)>> {
         if query.len() != self.dimension {
             return Err(StorageError::InvalidData(format!(
                 "Query dimension mismatch: expected {}, got {}",
@@ -745,7 +780,11 @@ impl DiskANNIndex {
             return Ok(Vec::new());
         }
 
-        let search_list_size = self.config.search_list_size.max(k * 2);
+        let mut search_list_size = width.unwrap_or(self.config.search_list_size).max(k * 2);
+        let limit = self.search_width_limit.load(AtomicOrdering::Relaxed);
+        if limit > 0 {
+            search_list_size = search_list_size.min(limit.max(k));
+        }
         let candidates = self.greedy_search(query, medoid, search_list_size)?;
 
         // Return top k
@@ -773,12 +812,24 @@ impl DiskANNIndex {
         // 2. 在 flush() 中重建会导致严重的性能回退
         // 3. 增量插入的重建阈值已提高到 500（避免频繁重建）
 
+        // The log must be durable before the files it redoes change
+        let mut log = self.log.lock();
+        if let Some(log) = log.as_ref() {
+            log.sync()?;
+        }
+
         // 🚀 Fast path: only cleanup slack edges (if any)
         // 注意：batch_insert 已经清理了 slack，这里通常是 no-op
         self.cleanup_slack_edges()?;
 
         self.vectors.flush()?;
         self.graph.flush()?;
+
+        // Everything logged is in the files now. The first flush starts the
+        // log: until then a crash leaves an index the manifest discards.
+        if log.as_ref().is_none_or(|log| !log.is_empty()) {
+            *log = Some(IndexWal::reset(&self.data_dir)?);
+        }
         Ok(())
     }
 
@@ -934,6 +985,40 @@ impl DiskANNIndex {
         }
     }
 
+    /// Raw vector bytes inserted or updated since open
+    pub fn bytes_indexed(&self) -> u64 {
+        self.vectors.vectors.bytes_indexed()
+    }
+
+    /// Bytes written to the vector and graph files since open
+    pub fn bytes_written(&self) -> u64 {
+        self.vectors.vectors.bytes_written() + self.graph.bytes_written()
+    }
+
+    /// Vector records left in the data file by deletes and updates
+    pub fn dead_vector_count(&self) -> usize {
+        self.vectors.vectors.dead_bytes() / self.vectors.vectors.entry_size()
+    }
+
+    /// Share of the vector and graph files held by dead records: vectors
+    /// deleted or superseded by updates (the vector file is append-only
+    /// until rebuilt), and graph slots freed by deleted nodes.
+    pub fn fragmentation(&self) -> f64 {
+        let vectors = &self.vectors.vectors;
+        let vector_bytes = vectors.disk_usage().saturating_sub(8) as u64;
+        let vector_dead = vectors.dead_bytes() as u64;
+
+        let graph_bytes = self.graph.data_bytes();
+        let graph_dead = self.graph.free_bytes();
+
+        let total = vector_bytes + graph_bytes;
+        if total == 0 {
+            0.0
+        } else {
+            (vector_dead + graph_dead) as f64 / total as f64
+        }
+    }
+
     /// Compact storage (optional maintenance)
     pub fn compact(&self) -> Result<()> {
         // Could implement graph pruning, vector cleanup, etc.
@@ -1067,7 +1152,7 @@ impl DiskANNIndex {
     /// **关键优化：**
     /// 1. 只更新新节点的前向边
     /// 2. 只更新邻居节点的反向边（受影响的边）
-    /// 3. 使用Slack-based pruning（1.3x slack）减少剪枝
+    /// 3. 邻居边数超过图容量时才剪枝
     fn incremental_insert_into_graph(&self, new_id: RowId, medoid_id: RowId) -> Result<()> {
         let query_vec = match self.vectors.get(new_id) {
             Some(v) => v,
@@ -1100,8 +1185,9 @@ impl DiskANNIndex {
         self.graph.set_neighbors(new_id, neighbors.clone())?;
 
         // 4. 🚀 局部更新反向边（只更新邻居节点）
-        let slack_factor = 1.3;
-        let soft_limit = (self.config.max_degree as f32 * slack_factor) as usize;
+        // Trim at the graph's capacity: `set_neighbors` truncates longer
+        // lists by id, which would silently drop the edge just added
+        let soft_limit = self.graph.max_degree();
 
         for &neighbor_id in neighbors.iter() {
             // ✅ P1: Arc auto-derefs
@@ -1116,32 +1202,7 @@ impl DiskANNIndex {
 
             // 🚀 Slack-based pruning：只在必要时剪枝
             if neighbor_edges.len() > soft_limit {
-                let neighbor_vec = match self.vectors.get(neighbor_id) {
-                    Some(v) => v,
-                    None => continue,
-                };
-
-                let candidates: Vec<Candidate> = neighbor_edges
-                    .iter()
-                    .filter_map(|&nid| {
-                        let vec = self.vectors.get(nid)?;
-                        let dist = self.metric.distance(&neighbor_vec, &vec);
-                        Some(Candidate {
-                            id: nid,
-                            distance: dist,
-                        })
-                    })
-                    .collect();
-
-                neighbor_edges = robust_prune(
-                    candidates,
-                    self.config.max_degree,
-                    self.config.alpha,
-                    |a, b| match (self.vectors.get(a), self.vectors.get(b)) {
-                        (Some(vec_a), Some(vec_b)) => self.metric.distance(&vec_a, &vec_b),
-                        _ => f32::MAX,
-                    },
-                );
+                neighbor_edges = self.closest_edges(neighbor_id, &neighbor_edges, soft_limit);
             }
 
             self.graph.set_neighbors(neighbor_id, neighbor_edges)?;
@@ -1150,6 +1211,28 @@ impl DiskANNIndex {
         Ok(())
     }
 
+    /// The `limit` edges of `node_id` nearest to it. Used to trim a
+    /// reverse-edge list that outgrew the graph: `robust_prune` would cut it
+    /// far below capacity.
+    fn closest_edges(&self, node_id: RowId, edges: &[RowId], limit: usize) -> Vec<RowId> {
+        let node_vec = match self.vectors.get(node_id) {
+            Some(v) => v,
+            None => return edges.iter().copied().take(limit).collect(),
+        };
+        let mut candidates: Vec<Candidate> = edges
+            .iter()
+            .filter_map(|&nid| {
+                let vec = self.vectors.get(nid)?;
+                Some(Candidate {
+                    id: nid,
+                    distance: self.metric.distance(&node_vec, &vec),
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        candidates.into_iter().take(limit).map(|c| c.id).collect()
+    }
+
     /// 🚀 **增量更新：只更新受影响的节点**
     fn incremental_update_node(&self, node_id: RowId, medoid_id: RowId) -> Result<()> {
         let query_vec = match self.vectors.get(node_id) {
@@ -1199,8 +1282,9 @@ impl DiskANNIndex {
         }
 
         // 5b. 添加新的反向边
-        let slack_factor = 1.3;
-        let soft_limit = (self.config.max_degree as f32 * slack_factor) as usize;
+        // Trim at the graph's capacity: `set_neighbors` truncates longer
+        // lists by id, which would silently drop the edge just added
+        let soft_limit = self.graph.max_degree();
 
         for &new_neighbor in &new_neighbors {
             if old_neighbors.contains(&new_neighbor) {
@@ -1217,32 +1301,7 @@ impl DiskANNIndex {
             neighbor_edges.push(node_id);
 
             if neighbor_edges.len() > soft_limit {
-                let neighbor_vec = match self.vectors.get(new_neighbor) {
-                    Some(v) => v,
-                    None => continue,
-                };
-
-                let candidates: Vec<Candidate> = neighbor_edges
-                    .iter()
-                    .filter_map(|&nid| {
-                        let vec = self.vectors.get(nid)?;
-                        let dist = self.metric.distance(&neighbor_vec, &vec);
-                        Some(Candidate {
-                            id: nid,
-                            distance: dist,
-                        })
-                    })
-                    .collect();
-
-                neighbor_edges = robust_prune(
-                    candidates,
-                    self.config.max_degree,
-                    self.config.alpha,
-                    |a, b| match (self.vectors.get(a), self.vectors.get(b)) {
-                        (Some(vec_a), Some(vec_b)) => self.metric.distance(&vec_a, &vec_b),
-                        _ => f32::MAX,
-                    },
-                );
+                neighbor_edges = self.closest_edges(new_neighbor, &neighbor_edges, soft_limit);
             }
 
             self.graph.set_neighbors(new_neighbor, neighbor_edges)?;
@@ -1344,26 +1403,20 @@ impl DiskANNIndex {
                 break;
             }
 
-            // Explore neighbors
-            let neighbors = self.graph.neighbors(current.id);
-
-            let prefetch_ids: Vec<_> = neighbors
-                .iter()
-                .filter(|&&id| !visited.contains(&id))
-                .copied()
-                .collect();
-
-            if !prefetch_ids.is_empty() {
-                for neighbor_id in prefetch_ids {
-                    visited.insert(neighbor_id);
-
+            // Explore neighbors, read in place from the graph file
+            let mut expanded = false;
+            self.graph.for_each_neighbor(current.id, |neighbor_id| {
+                if visited.insert(neighbor_id) {
                     let dist = self.vectors.distance(query, neighbor_id, self.metric);
                     candidates.push(Candidate {
                         id: neighbor_id,
                         distance: dist,
                     });
+                    expanded = true;
                 }
+            });
 
+            if expanded {
                 // Limit to beam_width best candidates. The heap gives
                 // the BEST on pop, so we drain, sort, and rebuild.
                 if candidates.len() > beam_width * 2 {
@@ -1484,4 +1537,43 @@ mod tests {
             assert!(results[0].1 < 1.0); // Should be close to query
         }
     }
+
+    #[test]
+    fn test_diskann_replays_log_after_crash() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = VamanaConfig::embedded(3);
+
+        {
+            let index = DiskANNIndex::create(temp_dir.path(), 3, config.clone()).unwrap();
+            index
+                .build(vec![
+                    (1, vec![1.0, 0.0, 0.0]),
+                    (2, vec![0.0, 1.0, 0.0]),
+                    (3, vec![0.0, 0.0, 1.0]),
+                ])
+                .unwrap();
+
+            // Dropped without a flush: only the log knows about these
+            index.insert(4, vec![0.9, 0.1, 0.0]).unwrap();
+            index.update(2, vec![0.0, 0.0, -1.0]).unwrap();
+            index.delete(3).unwrap();
+        }
+        assert!(DiskANNIndex::recovers_in_place(temp_dir.path()));
+
+        let index = DiskANNIndex::load(temp_dir.path(), config.clone()).unwrap();
+        assert_eq!(index.len(), 3);
+        let results = index.search(&[1.0, 0.0, 0.0], 3).unwrap();
+        let ids: Vec<RowId> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(&ids[..2], &[1, 4]);
+        assert!(!ids.contains(&3));
+        let results = index.search(&[0.0, 0.0, -1.0], 1).unwrap();
+        assert_eq!(results[0].0, 2);
+        drop(index);
+
+        // Recovery flushed: a second load has nothing to replay
+        let (_, ops) = IndexWal::open(temp_dir.path()).unwrap();
+        assert!(ops.is_empty());
+        let index = DiskANNIndex::load(temp_dir.path(), config).unwrap();
+        assert_eq!(index.len(), 3);
+    }
 }
@@ -0,0 +1,274 @@
+//! Logical write-ahead log for a DiskANN index
+//!
+//! Inserts, updates and deletes write straight into vectors_sq8.bin and
+//! graph.bin, whose sidecars only catch up at `flush()`. Every mutation is
+//! therefore appended to index.wal first; a flush syncs the log, makes the
+//! data files durable and then swaps in an empty log. Loading an index
+//! replays whatever the log still holds, so a crash between (or during)
+//! flushes is recovered in place instead of rebuilding from table data.
+//!
+//! Format: [magic: u32, version: u32] then records
+//! [len: u32, crc32: u32, op: u8, row_id: u64, dim: u32, f32 * dim].
+//! A torn or corrupt tail is dropped on open.
+
+use crate::storage::backend::{self, BackendFile, OpenFlags};
+use crate::types::RowId;
+use crate::{Result, StorageError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const MAGIC: u32 = 0x5657_414C; // "VWAL"
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 8;
+/// Record header: payload length (u32), payload CRC32 (u32)
+const RECORD_HEADER: usize = 8;
+
+const OP_INSERT: u8 = 1;
+const OP_UPDATE: u8 = 2;
+const OP_DELETE: u8 = 3;
+
+/// Log file name inside the index directory
+pub const FILE_NAME: &str = "index.wal";
+
+/// One logged index mutation
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogOp {
+    Insert(RowId, Vec<f32>),
+    Update(RowId, Vec<f32>),
+    Delete(RowId),
+}
+
+/// Append-only mutation log of one index directory
+pub struct IndexWal {
+    file: Box<dyn BackendFile>,
+    /// Records appended since the log was last reset
+    records: usize,
+}
+
+impl IndexWal {
+    /// Path of the log inside `data_dir`
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(FILE_NAME)
+    }
+
+    /// Whether `data_dir` holds a log
+    pub fn exists(data_dir: &Path) -> bool {
+        let path = Self::path(data_dir);
+        backend::backend_for(&path).exists(&path)
+    }
+
+    /// Start an empty log, atomically replacing the previous one
+    pub fn reset(data_dir: &Path) -> Result<Self> {
+        let path = Self::path(data_dir);
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        backend::backend_for(&path)
+            .write_atomic(&path, &header)
+            .map_err(StorageError::Io)?;
+        Self::open_append(&path, 0)
+    }
+
+    /// Open the log in `data_dir` with the records it holds, in order.
+    /// A torn or corrupt tail (the crash hit mid-append) is cut off.
+    pub fn open(data_dir: &Path) -> Result<(Self, Vec<LogOp>)> {
+        let path = Self::path(data_dir);
+        let fs = backend::backend_for(&path);
+        let buf = fs.read(&path).map_err(StorageError::Io)?;
+        if buf.len() < HEADER_SIZE
+            || u32::from_le_bytes(buf[0..4].try_into().unwrap()) != MAGIC
+            || u32::from_le_bytes(buf[4..8].try_into().unwrap()) != VERSION
+        {
+            return Err(StorageError::InvalidData(format!(
+                "Invalid index log {:?}",
+                path
+            )));
+        }
+
+        let mut ops = Vec::new();
+        let mut pos = HEADER_SIZE;
+        while let Some((op, next)) = Self::decode(&buf, pos) {
+            ops.push(op);
+            pos = next;
+        }
+        if pos < buf.len() {
+            debug_log!(
+                "[IndexWal] Dropping {} torn bytes from {:?}",
+                buf.len() - pos,
+                path
+            );
+            let file = backend::open_file(&path, OpenFlags::append().no_create())
+                .map_err(StorageError::Io)?;
+            file.set_len(pos as u64).map_err(StorageError::Io)?;
+            file.sync_all().map_err(StorageError::Io)?;
+        }
+
+        let records = ops.len();
+        Ok((Self::open_append(&path, records)?, ops))
+    }
+
+    fn open_append(path: &Path, records: usize) -> Result<Self> {
+        let file =
+            backend::open_file(path, OpenFlags::append().no_create()).map_err(StorageError::Io)?;
+        Ok(Self { file, records })
+    }
+
+    /// Records appended since the last reset
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Log an insert of `vector` under `row_id`
+    pub fn log_insert(&mut self, row_id: RowId, vector: &[f32]) -> Result<()> {
+        let mut buf = Vec::new();
+        Self::encode(&mut buf, OP_INSERT, row_id, vector);
+        self.append(&buf, 1)
+    }
+
+    /// Log inserts of a whole batch in one write
+    pub fn log_inserts(&mut self, vectors: &[(RowId, Vec<f32>)]) -> Result<()> {
+        let mut buf = Vec::new();
+        for (row_id, vector) in vectors {
+            Self::encode(&mut buf, OP_INSERT, *row_id, vector);
+        }
+        self.append(&buf, vectors.len())
+    }
+
+    /// Log a replacement of `row_id`'s vector
+    pub fn log_update(&mut self, row_id: RowId, vector: &[f32]) -> Result<()> {
+        let mut buf = Vec::new();
+        Self::encode(&mut buf, OP_UPDATE, row_id, vector);
+        self.append(&buf, 1)
+    }
+
+    /// Log a delete of `row_id`
+    pub fn log_delete(&mut self, row_id: RowId) -> Result<()> {
+        let mut buf = Vec::new();
+        Self::encode(&mut buf, OP_DELETE, row_id, &[]);
+        self.append(&buf, 1)
+    }
+
+    /// fsync the records appended so far
+    pub fn sync(&self) -> Result<()> {
+        self.file.sync_all().map_err(StorageError::Io)
+    }
+
+    fn append(&mut self, buf: &[u8], records: usize) -> Result<()> {
+        self.file.write_all(buf).map_err(StorageError::Io)?;
+        self.records += records;
+        Ok(())
+    }
+
+    fn encode(buf: &mut Vec<u8>, op: u8, row_id: RowId, vector: &[f32]) {
+        let mut payload = Vec::with_capacity(13 + vector.len() * 4);
+        payload.push(op);
+        payload.extend_from_slice(&row_id.to_le_bytes());
+        payload.extend_from_slice(&(vector.len() as u32).to_le_bytes());
+        for x in vector {
+            payload.extend_from_slice(&x.to_le_bytes());
+        }
+        buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        buf.extend_from_slice(&payload);
+    }
+
+    /// Decode the record at `pos`, returning it with the offset of the next
+    /// one; `None` at the end of the log or a torn/corrupt record
+    fn decode(buf: &[u8], pos: usize) -> Option<(LogOp, usize)> {
+        let header = buf.get(pos..pos + RECORD_HEADER)?;
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let start = pos + RECORD_HEADER;
+        let payload = buf.get(start..start.checked_add(len)?)?;
+        if len < 13 || crc32fast::hash(payload) != crc {
+            return None;
+        }
+        let row_id = u64::from_le_bytes(payload[1..9].try_into().unwrap());
+        let dim = u32::from_le_bytes(payload[9..13].try_into().unwrap()) as usize;
+        if payload.len() != 13 + dim * 4 {
+            return None;
+        }
+        let vector: Vec<f32> = payload[13..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let op = match payload[0] {
+            OP_INSERT => LogOp::Insert(row_id, vector),
+            OP_UPDATE => LogOp::Update(row_id, vector),
+            OP_DELETE => LogOp::Delete(row_id),
+            _ => return None,
+        };
+        Some((op, start + len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_wal_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = IndexWal::reset(dir.path()).unwrap();
+        wal.log_insert(1, &[1.0, 2.0]).unwrap();
+        wal.log_inserts(&[(2, vec![3.0, 4.0]), (3, vec![5.0, 6.0])])
+            .unwrap();
+        wal.log_update(1, &[7.0, 8.0]).unwrap();
+        wal.log_delete(2).unwrap();
+        assert_eq!(wal.len(), 5);
+        drop(wal);
+
+        let (wal, ops) = IndexWal::open(dir.path()).unwrap();
+        assert_eq!(wal.len(), 5);
+        assert_eq!(
+            ops,
+            vec![
+                LogOp::Insert(1, vec![1.0, 2.0]),
+                LogOp::Insert(2, vec![3.0, 4.0]),
+                LogOp::Insert(3, vec![5.0, 6.0]),
+                LogOp::Update(1, vec![7.0, 8.0]),
+                LogOp::Delete(2),
+            ]
+        );
+
+        // A reset leaves an empty log behind
+        drop(wal);
+        IndexWal::reset(dir.path()).unwrap();
+        let (wal, ops) = IndexWal::open(dir.path()).unwrap();
+        assert!(wal.is_empty());
+        assert!(ops.is_empty());
+    }
+
+    #[test]
+    fn test_index_wal_drops_torn_tail() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = IndexWal::reset(dir.path()).unwrap();
+        wal.log_insert(1, &[1.0, 2.0]).unwrap();
+        wal.log_insert(2, &[3.0, 4.0]).unwrap();
+        drop(wal);
+
+        // Cut the last record in half
+        let path = IndexWal::path(dir.path());
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 6).unwrap();
+        drop(file);
+
+        let (mut wal, ops) = IndexWal::open(dir.path()).unwrap();
+        assert_eq!(ops, vec![LogOp::Insert(1, vec![1.0, 2.0])]);
+
+        // Appends continue after the last intact record
+        wal.log_delete(1).unwrap();
+        drop(wal);
+        let (_, ops) = IndexWal::open(dir.path()).unwrap();
+        assert_eq!(
+            ops,
+            vec![LogOp::Insert(1, vec![1.0, 2.0]), LogOp::Delete(1)]
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub mod pruner;
 // DiskANN implementation with SQ8 compression
 pub mod disk_graph;
 pub mod diskann_index;
+pub mod index_wal;
 pub mod sq8;
 pub mod sq8_vectors;
 
@@ -15,14 +15,18 @@
 //! - SIMD-optimized u8 operations (4x faster than f32)
 //! - Reduced memory bandwidth (128 bytes vs 512 bytes for dim=128)
 
+use crate::storage::backend::{self, OpenFlags};
 use crate::{Result, StorageError};
-use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
 
 #[cfg(target_arch = "aarch64")]
 use std::arch::aarch64::*;
 
+/// Quantizer file magic; the byte after it is the format version
+const SQ8_MAGIC: &[u8; 3] = b"SQ8";
+const SQ8_VERSION: u8 = 0;
+
 /// SQ8 quantizer (per-vector min/max scaling)
 #[derive(Debug, Clone)]
 pub struct SQ8Quantizer {
@@ -112,11 +116,13 @@ impl SQ8Quantizer {
 
     /// Save quantizer to file
     pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
-        let mut file = File::create(path).map_err(StorageError::Io)?;
+        let mut file = backend::open_file(path.as_ref(), OpenFlags::create_truncate())
+            .map_err(StorageError::Io)?;
 
-        // Header: "SQ8\0" (4 bytes) + dimension (8 bytes)
-        file.write_all(b"SQ8\0").map_err(StorageError::Io)?;
-        file.write_all(&self.dimension.to_le_bytes())
+        // Header: "SQ8" + version (4 bytes) + dimension (u64 LE, 8 bytes)
+        file.write_all(SQ8_MAGIC).map_err(StorageError::Io)?;
+        file.write_all(&[SQ8_VERSION]).map_err(StorageError::Io)?;
+        file.write_all(&(self.dimension as u64).to_le_bytes())
             .map_err(StorageError::Io)?;
 
         Ok(())
@@ -124,21 +130,29 @@ impl SQ8Quantizer {
 
     /// Load quantizer from file
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
-        let mut file = File::open(path).map_err(StorageError::Io)?;
+        let mut file =
+            backend::open_file(path.as_ref(), OpenFlags::read_only()).map_err(StorageError::Io)?;
 
         // Read header
         let mut magic = [0u8; 4];
         file.read_exact(&mut magic).map_err(StorageError::Io)?;
-        if &magic != b"SQ8\0" {
+        if &magic[..3] != SQ8_MAGIC {
             return Err(StorageError::InvalidData(
                 "Invalid SQ8 file magic".to_string(),
             ));
         }
+        if magic[3] != SQ8_VERSION {
+            return Err(StorageError::InvalidData(format!(
+                "Unsupported SQ8 file version: {}",
+                magic[3]
+            )));
+        }
 
         // Read dimension
         let mut dim_bytes = [0u8; 8];
         file.read_exact(&mut dim_bytes).map_err(StorageError::Io)?;
-        let dimension = usize::from_le_bytes(dim_bytes);
+        let dimension = usize::try_from(u64::from_le_bytes(dim_bytes))
+            .map_err(|_| StorageError::InvalidData("SQ8 dimension out of range".to_string()))?;
 
         Ok(Self { dimension })
     }
@@ -565,6 +579,21 @@ mod tests {
         std::fs::remove_file(temp_path).ok();
     }
 
+    #[test]
+    fn test_sq8_file_layout_is_fixed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sq8.bin");
+        SQ8Quantizer::new(300).save(&path).unwrap();
+        let mut expected = b"SQ8\0".to_vec();
+        expected.extend_from_slice(&[0x2C, 0x01, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+
+        // A newer format version is rejected rather than misread
+        expected[3] = SQ8_VERSION + 1;
+        std::fs::write(&path, &expected).unwrap();
+        assert!(SQ8Quantizer::load(&path).is_err());
+    }
+
     #[test]
     fn test_compression_ratio() {
         let quantizer = SQ8Quantizer::new(128);
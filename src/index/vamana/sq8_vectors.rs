@@ -8,15 +8,16 @@
 //! binary search on the sidecar index file when entries are evicted.
 
 use super::sq8::{QuantizedVector, SQ8Quantizer};
+use crate::storage::backend::{self, BackendFile, FileMap, OpenFlags};
 use crate::types::RowId;
 use crate::{Result, StorageError};
 use lru::LruCache;
-use memmap2::Mmap;
 use parking_lot::RwLock;
-use std::fs::{File, OpenOptions};
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// SQ8 compressed vector storage with bounded memory
@@ -29,19 +30,21 @@ pub struct SQ8Vectors {
     _entry_size: usize,
 
     /// mmap of vectors_sq8.bin — zero-syscall quantized vector reads
-    data_mmap: Arc<RwLock<Option<Mmap>>>,
+    data_mmap: Arc<RwLock<Option<FileMap>>>,
     /// mmap of vectors_sq8.idx sidecar — zero-syscall offset lookups
-    idx_mmap: Arc<RwLock<Option<Mmap>>>,
+    idx_mmap: Arc<RwLock<Option<FileMap>>>,
 
     /// Bounded offset index: row_id -> file offset (LRU-capped)
     index: Arc<RwLock<LruCache<RowId, u64>>>,
 
     /// Sidecar index file handle for binary search on LRU miss
-    index_file: Arc<RwLock<File>>,
+    index_file: Arc<RwLock<Box<dyn BackendFile>>>,
     /// Total entries in the sidecar index (for binary search bounds)
     index_count: Arc<RwLock<u64>>,
     /// Total entries (tracked incrementally on insert/delete)
     count: Arc<RwLock<u64>>,
+    /// Deleted ids the sidecar still lists (cleared when flush rebuilds it)
+    deleted: Arc<RwLock<HashSet<RowId>>>,
 
     /// LRU cache: row_id -> decompressed f32 vector
     cache: Arc<RwLock<LruCache<RowId, Arc<Vec<f32>>>>>,
@@ -50,9 +53,14 @@ pub struct SQ8Vectors {
     quantized_cache: Arc<RwLock<LruCache<RowId, Arc<QuantizedVector>>>>,
 
     /// Persistent file handles (avoid open/close per read)
-    read_file: Arc<RwLock<File>>,
-    write_file: Arc<RwLock<File>>,
+    read_file: Arc<RwLock<Box<dyn BackendFile>>>,
+    write_file: Arc<RwLock<Box<dyn BackendFile>>>,
     file_path: PathBuf,
+
+    /// Raw vector bytes (row_id + f32 components) inserted or updated since open
+    bytes_indexed: AtomicU64,
+    /// Bytes written to the data and sidecar files since open
+    bytes_written: AtomicU64,
 }
 
 impl SQ8Vectors {
@@ -63,7 +71,9 @@ impl SQ8Vectors {
         cache_size: usize,
     ) -> Result<Self> {
         let data_dir = data_dir.as_ref().to_path_buf();
-        std::fs::create_dir_all(&data_dir).map_err(StorageError::Io)?;
+        backend::backend_for(&data_dir)
+            .create_dir_all(&data_dir)
+            .map_err(StorageError::Io)?;
 
         let dimension = quantizer.dimension();
         let entry_size = 8 + 4 + 4 + dimension;
@@ -71,22 +81,24 @@ impl SQ8Vectors {
         let idx_path = data_dir.join("vectors_sq8.idx");
 
         // Create empty data file with count=0
-        let mut file = File::create(&file_path).map_err(StorageError::Io)?;
+        let mut file = backend::open_file(&file_path, OpenFlags::create_truncate())
+            .map_err(StorageError::Io)?;
         file.write_all(&0u64.to_le_bytes())
             .map_err(StorageError::Io)?;
 
         // Create empty index file with count=0
-        let mut idx_file = File::create(&idx_path).map_err(StorageError::Io)?;
+        let mut idx_file = backend::open_file(&idx_path, OpenFlags::create_truncate())
+            .map_err(StorageError::Io)?;
         idx_file
             .write_all(&0u64.to_le_bytes())
             .map_err(StorageError::Io)?;
 
-        let read_file = File::open(&file_path).map_err(StorageError::Io)?;
-        let write_file = OpenOptions::new()
-            .append(true)
-            .open(&file_path)
+        let read_file =
+            backend::open_file(&file_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
+        let write_file = backend::open_file(&file_path, OpenFlags::append().no_create())
             .map_err(StorageError::Io)?;
-        let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+        let idx_read =
+            backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
 
         Ok(Self {
             _data_dir: data_dir,
@@ -101,6 +113,7 @@ impl SQ8Vectors {
             index_file: Arc::new(RwLock::new(idx_read)),
             index_count: Arc::new(RwLock::new(0)),
             count: Arc::new(RwLock::new(0)),
+            deleted: Arc::new(RwLock::new(HashSet::new())),
             cache: Arc::new(RwLock::new(LruCache::new(
                 NonZeroUsize::new(cache_size.max(1)).unwrap(),
             ))),
@@ -110,6 +123,8 @@ impl SQ8Vectors {
             read_file: Arc::new(RwLock::new(read_file)),
             write_file: Arc::new(RwLock::new(write_file)),
             file_path,
+            bytes_indexed: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
         })
     }
 
@@ -125,34 +140,36 @@ impl SQ8Vectors {
         let file_path = data_dir.join("vectors_sq8.bin");
         let idx_path = data_dir.join("vectors_sq8.idx");
 
-        if !file_path.exists() {
+        let backend = backend::backend_for(&file_path);
+        if !backend.exists(&file_path) {
             return Err(StorageError::InvalidData(
                 "SQ8 vectors file not found".to_string(),
             ));
         }
 
         // Build sidecar index from data file (or load existing sidecar)
-        let index_count = if idx_path.exists() {
-            let mut idx = File::open(&idx_path).map_err(StorageError::Io)?;
+        let index_count = if backend.exists(&idx_path) {
+            let mut idx =
+                backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
             let mut buf = [0u8; 8];
             idx.read_exact(&mut buf).map_err(StorageError::Io)?;
             u64::from_le_bytes(buf)
         } else {
             // Build sidecar from scratch by scanning data file
 
-            Self::build_sidecar_index(&file_path, &idx_path, entry_size)?
+            Self::build_sidecar_index(&file_path, &idx_path, entry_size, &HashSet::new())?
         };
 
-        let read_file = File::open(&file_path).map_err(StorageError::Io)?;
-        let write_file = OpenOptions::new()
-            .append(true)
-            .open(&file_path)
+        let read_file =
+            backend::open_file(&file_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
+        let write_file = backend::open_file(&file_path, OpenFlags::append().no_create())
             .map_err(StorageError::Io)?;
-        let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+        let idx_read =
+            backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
 
         // mmap data and sidecar for zero-syscall reads
-        let data_mmap = unsafe { Mmap::map(&read_file).ok() };
-        let sidecar_mmap = unsafe { Mmap::map(&idx_read).ok() };
+        let data_mmap = read_file.map().ok();
+        let sidecar_mmap = idx_read.map().ok();
 
         Ok(Self {
             _data_dir: data_dir,
@@ -167,6 +184,7 @@ impl SQ8Vectors {
             index_file: Arc::new(RwLock::new(idx_read)),
             index_count: Arc::new(RwLock::new(index_count)),
             count: Arc::new(RwLock::new(index_count)),
+            deleted: Arc::new(RwLock::new(HashSet::new())),
             cache: Arc::new(RwLock::new(LruCache::new(
                 NonZeroUsize::new(cache_size.max(1)).unwrap(),
             ))),
@@ -176,49 +194,58 @@ impl SQ8Vectors {
             read_file: Arc::new(RwLock::new(read_file)),
             write_file: Arc::new(RwLock::new(write_file)),
             file_path,
+            bytes_indexed: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
         })
     }
 
-    /// Build sidecar index file by scanning the data file.
-    /// Returns the count of entries written.
-    fn build_sidecar_index(data_path: &Path, idx_path: &Path, entry_size: usize) -> Result<u64> {
-        let mut data = File::open(data_path).map_err(StorageError::Io)?;
-        let mut count_bytes = [0u8; 8];
-        data.read_exact(&mut count_bytes)
-            .map_err(StorageError::Io)?;
-        let count = u64::from_le_bytes(count_bytes);
+    /// Build sidecar index file by scanning the data file. Updates append a
+    /// new entry, so the last entry of a row_id wins; `deleted` ids are left
+    /// out. Returns the count of entries written.
+    fn build_sidecar_index(
+        data_path: &Path,
+        idx_path: &Path,
+        entry_size: usize,
+        deleted: &HashSet<RowId>,
+    ) -> Result<u64> {
+        let mut data =
+            backend::open_file(data_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
+        let file_len = data.len().map_err(StorageError::Io)?;
+        data.seek(SeekFrom::Start(8)).map_err(StorageError::Io)?;
 
         // Read all (row_id, offset) pairs
-        let mut entries: Vec<(RowId, u64)> = Vec::with_capacity(count as usize);
+        let mut latest: HashMap<RowId, u64> = HashMap::new();
         let mut offset = 8u64;
-        for _ in 0..count {
+        while offset + entry_size as u64 <= file_len {
             let mut row_id_bytes = [0u8; 8];
             data.read_exact(&mut row_id_bytes)
                 .map_err(StorageError::Io)?;
             let row_id = u64::from_le_bytes(row_id_bytes);
-            entries.push((row_id, offset));
+            latest.insert(row_id, offset);
             offset += entry_size as u64;
             data.seek(SeekFrom::Current((entry_size - 8) as i64))
                 .map_err(StorageError::Io)?;
         }
 
         // Sort by row_id for binary search
+        let mut entries: Vec<(RowId, u64)> = latest
+            .into_iter()
+            .filter(|(id, _)| !deleted.contains(id))
+            .collect();
         entries.sort_by_key(|(id, _)| *id);
+        let count = entries.len() as u64;
 
-        // Write sidecar index
-        let mut idx_file = File::create(idx_path).map_err(StorageError::Io)?;
-        idx_file
-            .write_all(&count.to_le_bytes())
-            .map_err(StorageError::Io)?;
+        // Write sidecar index, swapped in whole so a crash mid-write leaves
+        // the previous one
+        let mut buf = Vec::with_capacity(8 + entries.len() * 16);
+        buf.extend_from_slice(&count.to_le_bytes());
         for (row_id, off) in &entries {
-            idx_file
-                .write_all(&row_id.to_le_bytes())
-                .map_err(StorageError::Io)?;
-            idx_file
-                .write_all(&off.to_le_bytes())
-                .map_err(StorageError::Io)?;
+            buf.extend_from_slice(&row_id.to_le_bytes());
+            buf.extend_from_slice(&off.to_le_bytes());
         }
-        idx_file.sync_all().map_err(StorageError::Io)?;
+        backend::backend_for(idx_path)
+            .write_atomic(idx_path, &buf)
+            .map_err(StorageError::Io)?;
 
         Ok(count)
     }
@@ -235,7 +262,7 @@ impl SQ8Vectors {
         }
 
         let count = *self.index_count.read();
-        if count == 0 {
+        if count == 0 || self.deleted.read().contains(&row_id) {
             return None;
         }
 
@@ -365,9 +392,12 @@ impl SQ8Vectors {
 
         let qvec = self.quantizer.quantize(&vector)?;
         let offset = self.append_quantized(row_id, &qvec)?;
+        self.bytes_indexed
+            .fetch_add((8 + vector.len() * 4) as u64, Ordering::Relaxed);
 
         // Update in-memory LRU index
         self.index.write().put(row_id, offset);
+        self.deleted.write().remove(&row_id);
         *self.count.write() += 1;
 
         // Cache decompressed vector
@@ -387,6 +417,11 @@ impl SQ8Vectors {
                 inserted += 1;
             }
         }
+        // Ids pushed out of the LRU index are only reachable through the
+        // sidecar, so rebuild it before anyone reads the batch back
+        if inserted > self.index.read().cap().get() {
+            self.flush()?;
+        }
         Ok(inserted)
     }
 
@@ -401,6 +436,8 @@ impl SQ8Vectors {
         // Append the new quantized vector to disk (the old entry remains but
         // the index will be updated to point to the new offset)
         let new_offset = self.append_quantized(row_id, &qvec)?;
+        self.bytes_indexed
+            .fetch_add((8 + vector.len() * 4) as u64, Ordering::Relaxed);
 
         // Update in-memory index to point to the new disk offset
         self.index.write().put(row_id, new_offset);
@@ -424,12 +461,13 @@ impl SQ8Vectors {
 
     /// Delete vector
     pub fn delete(&self, row_id: RowId) -> Result<bool> {
-        let removed = {
-            let mut index = self.index.write();
-            index.pop(&row_id).is_some()
-        };
+        // The sidecar may still list an id evicted from (or never loaded
+        // into) the LRU; remember the delete so lookups don't resurrect it
+        let removed = self.lookup_offset(row_id).is_some();
 
         if removed {
+            self.index.write().pop(&row_id);
+            self.deleted.write().insert(row_id);
             *self.count.write() -= 1;
             self.invalidate_single(row_id);
         }
@@ -448,48 +486,65 @@ impl SQ8Vectors {
 
         // Update data file header with current count
         {
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(&self.file_path)
-                .map_err(StorageError::Io)?;
+            let mut file = backend::open_file(
+                &self.file_path,
+                OpenFlags {
+                    write: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(StorageError::Io)?;
             file.seek(SeekFrom::Start(0)).map_err(StorageError::Io)?;
             file.write_all(&count.to_le_bytes())
                 .map_err(StorageError::Io)?;
             file.sync_all().map_err(StorageError::Io)?;
         }
+        self.bytes_written.fetch_add(8, Ordering::Relaxed);
 
-        // Rebuild sidecar index from data file
-        if count > 0 {
+        // Rebuild sidecar index from data file. Lookups wait on `deleted`
+        // until the new sidecar is mapped.
+        let mut deleted = self.deleted.write();
+        if count > 0 || *self.index_count.read() > 0 {
             let idx_path = self.file_path.with_extension("idx");
-            let _ = Self::build_sidecar_index(&self.file_path, &idx_path, self._entry_size);
-            *self.index_count.write() = count;
-            let idx_read = File::open(&idx_path).map_err(StorageError::Io)?;
+            let n =
+                Self::build_sidecar_index(&self.file_path, &idx_path, self._entry_size, &deleted)?;
+            self.bytes_written.fetch_add(8 + n * 16, Ordering::Relaxed);
+            *self.index_count.write() = n;
+            let idx_read =
+                backend::open_file(&idx_path, OpenFlags::read_only()).map_err(StorageError::Io)?;
             *self.index_file.write() = idx_read;
+            deleted.clear();
         }
 
         // Remap after flush
         self.remap();
+        drop(deleted);
 
         Ok(())
     }
 
     /// Get all vector IDs (reads from sidecar index)
+    /// Live ids in ascending order: the sidecar minus deletions since the
+    /// last flush, plus ids only held in the LRU (inserted since then)
     pub fn ids(&self) -> Vec<RowId> {
         let count = *self.index_count.read();
-        if count == 0 {
-            // Fall back to LRU entries if sidecar is empty (during initial inserts)
-            return self.index.read().iter().map(|(&id, _)| id).collect();
-        }
-
-        let mut file = self.index_file.write();
-        let mut ids = Vec::with_capacity(count as usize);
-        let _ = file.seek(SeekFrom::Start(8));
-        for _ in 0..count {
-            let mut buf = [0u8; 16];
-            if file.read_exact(&mut buf).is_ok() {
-                ids.push(u64::from_le_bytes(buf[..8].try_into().unwrap()));
+        let mut ids: HashSet<RowId> = self.index.read().iter().map(|(&id, _)| id).collect();
+        if count > 0 {
+            let deleted = self.deleted.read();
+            let mut file = self.index_file.write();
+            let _ = file.seek(SeekFrom::Start(8));
+            for _ in 0..count {
+                let mut buf = [0u8; 16];
+                if file.read_exact(&mut buf).is_ok() {
+                    let id = u64::from_le_bytes(buf[..8].try_into().unwrap());
+                    if !deleted.contains(&id) {
+                        ids.insert(id);
+                    }
+                }
             }
         }
+        let mut ids: Vec<RowId> = ids.into_iter().collect();
+        ids.sort_unstable();
         ids
     }
 
@@ -512,11 +567,34 @@ impl SQ8Vectors {
     }
 
     pub fn disk_usage(&self) -> usize {
-        std::fs::metadata(&self.file_path)
-            .map(|m| m.len() as usize)
+        backend::backend_for(&self.file_path)
+            .file_len(&self.file_path)
+            .map(|len| len as usize)
             .unwrap_or(0)
     }
 
+    /// Bytes of the data file held by deleted or superseded entries
+    pub fn dead_bytes(&self) -> usize {
+        self.disk_usage()
+            .saturating_sub(8)
+            .saturating_sub(self.len() * self._entry_size)
+    }
+
+    /// On-disk size of one entry (row_id + min + max + codes)
+    pub fn entry_size(&self) -> usize {
+        self._entry_size
+    }
+
+    /// Raw vector bytes (row_id + f32 components) inserted or updated since open
+    pub fn bytes_indexed(&self) -> u64 {
+        self.bytes_indexed.load(Ordering::Relaxed)
+    }
+
+    /// Bytes written to the data and sidecar files since open
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
     // ==================== Private Helpers ====================
 
     fn read_quantized(&self, offset: u64) -> Result<QuantizedVector> {
@@ -558,7 +636,7 @@ impl SQ8Vectors {
 
     fn append_quantized(&self, row_id: RowId, qvec: &QuantizedVector) -> Result<u64> {
         let mut file = self.write_file.write();
-        let offset = file.metadata().map_err(StorageError::Io)?.len();
+        let offset = file.len().map_err(StorageError::Io)?;
 
         file.write_all(&row_id.to_le_bytes())
             .map_err(StorageError::Io)?;
@@ -567,6 +645,8 @@ impl SQ8Vectors {
         file.write_all(&qvec.max.to_le_bytes())
             .map_err(StorageError::Io)?;
         file.write_all(&qvec.codes).map_err(StorageError::Io)?;
+        self.bytes_written
+            .fetch_add((16 + qvec.codes.len()) as u64, Ordering::Relaxed);
 
         Ok(offset)
     }
@@ -575,11 +655,11 @@ impl SQ8Vectors {
     fn remap(&self) {
         {
             let file = self.read_file.read();
-            *self.data_mmap.write() = unsafe { Mmap::map(&*file).ok() };
+            *self.data_mmap.write() = file.map().ok();
         }
         {
             let idx = self.index_file.read();
-            *self.idx_mmap.write() = unsafe { Mmap::map(&*idx).ok() };
+            *self.idx_mmap.write() = idx.map().ok();
         }
     }
 }
@@ -641,4 +721,35 @@ mod tests {
 
         std::fs::remove_dir_all(&temp_dir).ok();
     }
+
+    #[test]
+    fn test_sq8_vectors_delete_after_flush() {
+        let temp_dir = std::env::temp_dir().join("sq8_vectors_delete_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let quantizer = Arc::new(SQ8Quantizer::new(4));
+        let storage = SQ8Vectors::create(&temp_dir, quantizer.clone(), 2).unwrap();
+        for i in 0..5u64 {
+            storage.insert(i, vec![i as f32, 0.0, 0.0, 0.0]).unwrap();
+        }
+        storage.flush().unwrap();
+
+        // The sidecar still lists deleted ids; they must not come back
+        assert!(storage.delete(0).unwrap());
+        assert!(storage.delete(1).unwrap());
+        assert!(storage.get(0).is_none());
+        storage.insert(1, vec![9.0, 0.0, 0.0, 0.0]).unwrap();
+        assert!(storage.update(2, vec![7.0, 0.0, 0.0, 0.0]).unwrap());
+        storage.flush().unwrap();
+
+        let loaded = SQ8Vectors::load(&temp_dir, quantizer, 2).unwrap();
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(loaded.ids(), vec![1, 2, 3, 4]);
+        assert!(loaded.get(0).is_none());
+        assert!((loaded.get(1).unwrap()[0] - 9.0).abs() < 0.1);
+        assert!((loaded.get(2).unwrap()[0] - 7.0).abs() < 0.1);
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
 }
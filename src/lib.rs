@@ -59,10 +59,18 @@
 )]
 
 // 🧠 jemalloc: background thread returns freed memory to OS (RSS plateaus instead of growing forever)
-#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+#[cfg(all(
+    feature = "jemalloc",
+    not(target_env = "msvc"),
+    not(target_family = "wasm")
+))]
 use tikv_jemallocator::Jemalloc;
 
-#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+#[cfg(all(
+    feature = "jemalloc",
+    not(target_env = "msvc"),
+    not(target_family = "wasm")
+))]
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
@@ -92,7 +100,11 @@ pub fn fsync_dir<P: AsRef<std::path::Path>>(path: P) {
 /// large transient allocations, to keep RSS low on edge devices.
 /// No-op when jemalloc is not enabled.
 pub fn purge_memory_to_os() {
-    #[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+    #[cfg(all(
+        feature = "jemalloc",
+        not(target_env = "msvc"),
+        not(target_family = "wasm")
+    ))]
     {
         // Advance epoch to refresh arena stats, then purge each arena.
         // "arena.<i>.purge" forces all dirty/muzzy pages back to the OS.
@@ -106,7 +118,11 @@ pub fn purge_memory_to_os() {
             }
         }
     }
-    #[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+    #[cfg(not(all(
+        feature = "jemalloc",
+        not(target_env = "msvc"),
+        not(target_family = "wasm")
+    )))]
     {
         // System allocator: no manual purge available.
     }
@@ -160,8 +176,8 @@ pub mod txn;
 pub mod types;
 
 // ⚠️ EXPERIMENTAL: the C ABI in `ffi` is incomplete (no open_with_config,
-// no transaction/batch APIs, no error reporting, execute() returns a Debug
-// string). There is no C header file and no versioned symbol scheme yet.
+// no batch APIs, execute() returns a Debug string; use the session/statement
+// APIs for JSON results). There is no C header file and no versioned symbol scheme yet.
 // Do not rely on it for production bindings until it stabilizes — it will
 // change without a SemVer bump. Tracked as a pre-1.0 limitation.
 pub mod cache;
@@ -170,17 +186,46 @@ pub mod ffi; // 🚀 P1: Row cache for performance
 // 🔄 Modular database module (refactored from database_legacy.rs)
 pub mod database;
 
+// Typed tables: #[derive(MoteRecord)] structs ↔ rows
+pub mod record;
+
+// Sessions (per-connection transaction/settings/statements) + session pool
+pub mod session;
+
+// ROS 2 topic ingestion over a rosbridge socket (feature-gated)
+#[cfg(feature = "ros2-bridge")]
+pub mod ros2;
+
+// Reproducible benchmark workloads checked against release targets (feature-gated)
+#[cfg(feature = "bench")]
+pub mod bench;
+
 mod api;
 mod error; // 内部 API 包装层
 
-pub use config::{AutoCheckpointConfig, DBConfig, DurabilityLevel, LSMConfig, WALConfig};
-pub use error::{MoteDBError, Result, StorageError};
+pub use config::{
+    AutoCheckpointConfig, CommitMode, DBConfig, DurabilityLevel, FlashWriteConfig, FsyncConfig,
+    FsyncPolicy, LSMConfig, MemoryLimitConfig, RowCacheWritePolicy, StreamingConfig,
+    TimestampReorderConfig, WALConfig,
+};
+pub use error::{ErrorCode, MoteDBError, Result, StorageError};
 
 // 主要对外 API (now using modular database)
-pub use api::Database; // 简化 API 包装
+pub use api::{Database, ReadOnlyView, ReadView, Transaction}; // 简化 API 包装
 pub use catalog::TableRegistry;
-pub use database::{MoteDB, QueryProfile, TransactionStats};
-pub use sql::{ForEachResult, QueryResult, StreamingControl, StreamingQueryResult};
+pub use database::{
+    ActiveTransaction, CacheWarmupStats, Collection, CollectionConfig, CollectionHit,
+    CollectionRecord, CsvOptions, EmbeddingUpsert, IndexHealth, MemoryBudgetStats, MemoryPressure,
+    MetadataFilter, MoteDB, QueryProfile, TransactionStats, VectorSearchOptions, WriteBatch,
+};
+#[cfg(feature = "derive")]
+pub use motedb_derive::MoteRecord;
+pub use record::MoteRecord;
+pub use session::{PooledSession, Session, SessionPool, SessionSettings, TimestampFormat};
+pub use sql::{
+    AccessHook, ForEachResult, OptimizerStats, QueryPage, QueryResult, StreamingControl,
+    StreamingQueryResult, TableAccess,
+};
 
 // 🔌 导出分词器插件系统（方便用户直接使用）
 pub mod tokenizers {
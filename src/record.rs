@@ -0,0 +1,537 @@
+//! Typed tables: map Rust structs to rows with `#[derive(MoteRecord)]`
+//!
+//! The derive (feature `derive`, on by default) generates the schema, the
+//! `Row` conversions and one [`Field`] constant per column; [`Records`]
+//! then gives typed insert/get/update/delete and a typed
+//! [`QueryBuilder`] on top of the ordinary [`Database`] API.
+//!
+//! ```ignore
+//! use motedb::{Database, MoteRecord};
+//! use motedb::types::Geometry;
+//!
+//! #[derive(MoteRecord, Debug)]
+//! #[mote(table = "readings")]
+//! struct Reading {
+//!     #[mote(primary_key)]
+//!     id: i64,
+//!     sensor: String,
+//!     temp_c: f64,
+//!     #[mote(dim = 3)]
+//!     embedding: Vec<f32>,
+//!     position: Option<Geometry>,
+//! }
+//!
+//! db.create_table_for::<Reading>()?;
+//! let readings = db.records::<Reading>()?;
+//! readings.insert(&reading)?;
+//! let hot = readings
+//!     .query()
+//!     .filter(Reading::TEMP_C.gt(30.0))
+//!     .order_by_desc(Reading::TEMP_C)
+//!     .limit(10)
+//!     .all()?;
+//! ```
+//!
+//! Field types and their columns:
+//!
+//! | Rust | Column |
+//! |------|--------|
+//! | `i64`, `i32`, `i16`, `i8`, `u32`, `u16`, `u8` | `INTEGER` |
+//! | `f64`, `f32` | `FLOAT` |
+//! | `bool` | `BOOLEAN` |
+//! | `String` | `TEXT` |
+//! | `Timestamp` | `TIMESTAMP` |
+//! | `Geometry` | `SPATIAL` |
+//! | `[f32; N]` | `VECTOR(N)` |
+//! | `Vec<f32>` with `#[mote(dim = N)]` | `VECTOR(N)` |
+//! | `Option<T>` | `T`, nullable |
+
+use crate::sql::builder::QueryBuilder;
+use crate::types::{
+    ArcVec, ColumnDef, ColumnType, Geometry, Row, RowId, TableSchema, Timestamp, Value,
+};
+use crate::{Database, Result, StorageError};
+use std::marker::PhantomData;
+
+pub use crate::sql::builder::Condition;
+
+/// A struct stored as one row of [`MoteRecord::TABLE`]; derive it with
+/// `#[derive(MoteRecord)]` rather than implementing it by hand
+pub trait MoteRecord: Sized {
+    /// Table name
+    const TABLE: &'static str;
+    /// Column names, in row order
+    const COLUMNS: &'static [&'static str];
+
+    /// Column definitions, in row order
+    fn columns() -> Vec<ColumnDef>;
+
+    /// Primary key column, if any
+    fn primary_key() -> Option<&'static str>;
+
+    /// Convert to a row (values in `COLUMNS` order)
+    fn to_row(&self) -> Row;
+
+    /// Convert from a row (values in `COLUMNS` order)
+    fn from_row(row: Row) -> Result<Self>;
+
+    /// Table schema for `CREATE TABLE`
+    fn schema() -> TableSchema {
+        let schema = TableSchema::new(Self::TABLE.to_string(), Self::columns());
+        match Self::primary_key() {
+            Some(pk) => schema.with_primary_key(pk.to_string()),
+            None => schema,
+        }
+    }
+}
+
+/// A Rust value that converts to and from a column [`Value`]
+pub trait FieldValue: Sized {
+    /// Whether the column accepts NULL (`Option<T>`)
+    const NULLABLE: bool = false;
+
+    fn to_value(&self) -> Value;
+
+    /// `None` when `value` doesn't fit this type
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+/// A [`FieldValue`] whose column type follows from the Rust type alone
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no default MoteDB column type",
+    note = "store a `Vec<f32>` field as a vector with `#[mote(dim = N)]`, or use `[f32; N]`"
+)]
+pub trait FieldType: FieldValue {
+    fn column_type() -> ColumnType;
+}
+
+macro_rules! integer_field {
+    ($($ty:ty),*) => {$(
+        impl FieldValue for $ty {
+            fn to_value(&self) -> Value {
+                Value::Integer(*self as i64)
+            }
+
+            fn from_value(value: Value) -> Option<Self> {
+                match value {
+                    Value::Integer(i) => <$ty>::try_from(i).ok(),
+                    _ => None,
+                }
+            }
+        }
+
+        impl FieldType for $ty {
+            fn column_type() -> ColumnType {
+                ColumnType::Integer
+            }
+        }
+    )*};
+}
+
+integer_field!(i64, i32, i16, i8, u32, u16, u8);
+
+macro_rules! float_field {
+    ($($ty:ty),*) => {$(
+        impl FieldValue for $ty {
+            fn to_value(&self) -> Value {
+                Value::Float(*self as f64)
+            }
+
+            fn from_value(value: Value) -> Option<Self> {
+                match value {
+                    Value::Float(f) => Some(f as $ty),
+                    Value::Integer(i) => Some(i as $ty),
+                    _ => None,
+                }
+            }
+        }
+
+        impl FieldType for $ty {
+            fn column_type() -> ColumnType {
+                ColumnType::Float
+            }
+        }
+    )*};
+}
+
+float_field!(f64, f32);
+
+impl FieldValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl FieldType for bool {
+    fn column_type() -> ColumnType {
+        ColumnType::Boolean
+    }
+}
+
+impl FieldValue for String {
+    fn to_value(&self) -> Value {
+        Value::text_from(self)
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => Some(s.as_str().to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl FieldType for String {
+    fn column_type() -> ColumnType {
+        ColumnType::Text
+    }
+}
+
+impl FieldValue for Timestamp {
+    fn to_value(&self) -> Value {
+        Value::Timestamp(*self)
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Timestamp(ts) => Some(ts),
+            Value::Integer(micros) => Some(Timestamp::from_micros(micros)),
+            _ => None,
+        }
+    }
+}
+
+impl FieldType for Timestamp {
+    fn column_type() -> ColumnType {
+        ColumnType::Timestamp
+    }
+}
+
+impl FieldValue for Geometry {
+    fn to_value(&self) -> Value {
+        Value::spatial(self.clone())
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Spatial(g) => Some(*g),
+            _ => None,
+        }
+    }
+}
+
+impl FieldType for Geometry {
+    fn column_type() -> ColumnType {
+        ColumnType::Spatial
+    }
+}
+
+/// Vector of any length; the column needs `#[mote(dim = N)]`
+impl FieldValue for Vec<f32> {
+    fn to_value(&self) -> Value {
+        Value::Vector(ArcVec::new(self.clone()))
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Vector(v) => Some(v.to_vec()),
+            Value::Tensor(t) => Some(t.to_f32()),
+            _ => None,
+        }
+    }
+}
+
+impl<const N: usize> FieldValue for [f32; N] {
+    fn to_value(&self) -> Value {
+        Value::Vector(ArcVec::new(self.to_vec()))
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        Vec::<f32>::from_value(value)?.try_into().ok()
+    }
+}
+
+impl<const N: usize> FieldType for [f32; N] {
+    fn column_type() -> ColumnType {
+        ColumnType::Tensor(N)
+    }
+}
+
+impl<T: FieldValue> FieldValue for Option<T> {
+    const NULLABLE: bool = true;
+
+    fn to_value(&self) -> Value {
+        match self {
+            Some(v) => v.to_value(),
+            None => Value::Null,
+        }
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl<T: FieldType> FieldType for Option<T> {
+    fn column_type() -> ColumnType {
+        T::column_type()
+    }
+}
+
+/// Column definition for a field of type `T` (used by the derive)
+pub fn column<T: FieldType>(name: &str, position: usize) -> ColumnDef {
+    let def = ColumnDef::new(name.to_string(), T::column_type(), position);
+    if T::NULLABLE {
+        def
+    } else {
+        def.not_null()
+    }
+}
+
+/// `VECTOR(dim)` column definition for a `#[mote(dim = N)]` field (used by
+/// the derive)
+pub fn vector_column<T: FieldValue>(name: &str, position: usize, dim: usize) -> ColumnDef {
+    let def = ColumnDef::new(name.to_string(), ColumnType::Tensor(dim), position);
+    if T::NULLABLE {
+        def
+    } else {
+        def.not_null()
+    }
+}
+
+/// Convert the next row value to a field of type `T` (used by the derive).
+/// A short row reads as NULL.
+pub fn take_field<T: FieldValue>(
+    values: &mut impl Iterator<Item = Value>,
+    name: &str,
+) -> Result<T> {
+    let value = values.next().unwrap_or(Value::Null);
+    let kind = value_kind(&value);
+    T::from_value(value).ok_or_else(|| {
+        StorageError::TypeError(format!(
+            "column '{}': cannot read {} as {}",
+            name,
+            kind,
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Integer(_) => "INTEGER",
+        Value::Float(_) => "FLOAT",
+        Value::Bool(_) => "BOOLEAN",
+        Value::Text(_) => "TEXT",
+        Value::Vector(_) => "VECTOR",
+        Value::Tensor(_) => "TENSOR",
+        Value::Spatial(_) => "SPATIAL",
+        Value::TextDoc(_) => "TEXT",
+        Value::Timestamp(_) => "TIMESTAMP",
+        Value::Null => "NULL",
+        Value::Decimal(_) => "DECIMAL",
+        Value::Uuid(_) => "UUID",
+        Value::Date(_) => "DATE",
+        Value::Time(_) => "TIME",
+        Value::Array(_) => "ARRAY",
+    }
+}
+
+/// A typed column of record `R` holding `V` values; the derive generates
+/// one per field (`Reading::TEMP_C`)
+pub struct Field<R, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (R, V)>,
+}
+
+impl<R, V> Clone for Field<R, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R, V> Copy for Field<R, V> {}
+
+impl<R, V> std::fmt::Debug for Field<R, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Field").field(&self.name).finish()
+    }
+}
+
+impl<R, V: FieldValue> Field<R, V> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Column name
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn compare(&self, op: &str, value: impl Into<V>) -> Condition {
+        Condition::new(
+            format!("{} {} ?", self.name, op),
+            vec![value.into().to_value()],
+        )
+    }
+
+    /// `column = value`
+    pub fn eq(&self, value: impl Into<V>) -> Condition {
+        self.compare("=", value)
+    }
+
+    /// `column != value`
+    pub fn ne(&self, value: impl Into<V>) -> Condition {
+        self.compare("!=", value)
+    }
+
+    /// `column < value`
+    pub fn lt(&self, value: impl Into<V>) -> Condition {
+        self.compare("<", value)
+    }
+
+    /// `column <= value`
+    pub fn le(&self, value: impl Into<V>) -> Condition {
+        self.compare("<=", value)
+    }
+
+    /// `column > value`
+    pub fn gt(&self, value: impl Into<V>) -> Condition {
+        self.compare(">", value)
+    }
+
+    /// `column >= value`
+    pub fn ge(&self, value: impl Into<V>) -> Condition {
+        self.compare(">=", value)
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(&self) -> Condition {
+        Condition::new(format!("{} IS NULL", self.name), Vec::new())
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(&self) -> Condition {
+        Condition::new(format!("{} IS NOT NULL", self.name), Vec::new())
+    }
+}
+
+/// Typed access to the table of record `T`, from [`Database::records`]
+pub struct Records<'a, T: MoteRecord> {
+    db: &'a Database,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: MoteRecord> Records<'a, T> {
+    pub(crate) fn new(db: &'a Database) -> Self {
+        Self {
+            db,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Insert one record
+    pub fn insert(&self, record: &T) -> Result<RowId> {
+        self.db.insert_row(T::TABLE, record.to_row())
+    }
+
+    /// Insert many records in one batch
+    pub fn insert_many(&self, records: &[T]) -> Result<Vec<RowId>> {
+        let rows = records.iter().map(MoteRecord::to_row).collect();
+        self.db.batch_insert(T::TABLE, rows)
+    }
+
+    /// Record stored at `row_id`
+    pub fn get(&self, row_id: RowId) -> Result<Option<T>> {
+        self.db
+            .get_row(T::TABLE, row_id)?
+            .map(T::from_row)
+            .transpose()
+    }
+
+    /// Replace the record stored at `row_id`
+    pub fn update(&self, row_id: RowId, record: &T) -> Result<()> {
+        self.db.update_row(T::TABLE, row_id, record.to_row())
+    }
+
+    /// Delete the record stored at `row_id`
+    pub fn delete(&self, row_id: RowId) -> Result<()> {
+        self.db.delete_row(T::TABLE, row_id)
+    }
+
+    /// Start a query over the table
+    pub fn query(&self) -> RecordQuery<'a, T> {
+        RecordQuery {
+            query: QueryBuilder::new(self.db, T::TABLE).select(T::COLUMNS),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A SELECT over the table of record `T`, from [`Records::query`]; a
+/// [`QueryBuilder`] restricted to `T`'s fields
+pub struct RecordQuery<'a, T: MoteRecord> {
+    query: QueryBuilder<'a>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: MoteRecord> RecordQuery<'_, T> {
+    /// Keep records matching `condition` (ANDed with earlier filters)
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.query = self.query.filter(condition);
+        self
+    }
+
+    /// The `k` records whose vector `field` is nearest to `vector` (L2),
+    /// nearest first
+    pub fn knn<V: FieldValue>(mut self, field: Field<T, V>, vector: &[f32], k: usize) -> Self {
+        self.query = self.query.knn(field.name(), vector, k);
+        self
+    }
+
+    /// Sort ascending by `field` (after any earlier sort keys)
+    pub fn order_by<V: FieldValue>(mut self, field: Field<T, V>) -> Self {
+        self.query = self.query.order_by(field.name());
+        self
+    }
+
+    /// Sort descending by `field` (after any earlier sort keys)
+    pub fn order_by_desc<V: FieldValue>(mut self, field: Field<T, V>) -> Self {
+        self.query = self.query.order_by_desc(field.name());
+        self
+    }
+
+    /// Return at most `n` records
+    pub fn limit(mut self, n: usize) -> Self {
+        self.query = self.query.limit(n);
+        self
+    }
+
+    /// All matching records
+    pub fn all(self) -> Result<Vec<T>> {
+        self.query.all()?.into_iter().map(T::from_row).collect()
+    }
+
+    /// First matching record
+    pub fn first(self) -> Result<Option<T>> {
+        self.query.first()?.map(T::from_row).transpose()
+    }
+
+    /// Number of matching records (ignores ordering and limit)
+    pub fn count(self) -> Result<usize> {
+        self.query.count()
+    }
+}
@@ -0,0 +1,631 @@
+//! ROS 2 ingestion adapter (feature `ros2-bridge`)
+//!
+//! Subscribes to ROS 2 topics and writes every message as a table row.
+//! Messages travel as JSON in the rosbridge v2 protocol: the adapter sends
+//! `{"op": "subscribe", "topic": .., "type": ..}` for each mapped topic and
+//! expects `{"op": "publish", "topic": .., "msg": {..}}` back.
+//! [`RosbridgeSource`] speaks that protocol over rosbridge_server's
+//! WebSocket endpoint, one JSON object per message; an rclrs node or any
+//! other transport plugs in through [`MessageSource`].
+//!
+//! A [`TopicMapping`] maps message fields (dotted paths such as
+//! `pose.position` or `header.stamp`; numbers index arrays) to columns. The
+//! conversion follows the column type:
+//!
+//! - SPATIAL: `{x, y[, z]}` points, `Pose` (its `position`) and
+//!   `PoseStamped` / `PoseWithCovariance` (their `pose`)
+//! - TIMESTAMP: `builtin_interfaces/Time` (`{sec, nanosec}`) or a `Header`
+//!   (its `stamp`); plain numbers are microseconds
+//! - VECTOR / TENSOR: numeric arrays, e.g. an embedding
+//! - TEXT: strings as-is; byte arrays such as `sensor_msgs/Image` `data`
+//!   are stored base64-encoded, which is also how rosbridge sends them
+//! - other columns: JSON scalars, converted like SQL literals
+//!
+//! Unmapped columns are NULL (so AUTO_INCREMENT keys and defaults apply). A
+//! reader thread converts messages and hands rows to a writer thread over a
+//! bounded queue; the writer inserts them with one batch insert per table
+//! every `batch_size` rows or `flush_interval`. When the queue is full the
+//! reader either blocks — it stops reading the socket, so TCP flow control
+//! pushes back on the bridge — or drops the row ([`Backpressure`]).
+//!
+//! ```ignore
+//! let source = RosbridgeSource::connect("127.0.0.1:9090")?;
+//! let poses = TopicMapping::new("/robot/pose", "geometry_msgs/msg/PoseStamped", "poses")
+//!     .field("ts", "header.stamp")
+//!     .field("position", "pose");
+//! let ingest = Ros2Ingest::start(db, source, vec![poses], IngestConfig::default())?;
+//! // ...
+//! let stats = ingest.stop()?;
+//! ```
+
+use crate::database::MoteDB;
+use crate::types::{ArcVec, ColumnType, Geometry, Point, Point3D, Row, Timestamp, Value};
+use crate::{Result, StorageError};
+use serde_json::{json, Value as Json};
+use std::collections::HashMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+mod websocket;
+
+use websocket::{Incoming, WebSocket};
+
+/// How often the reader re-checks the stop flag while the source is idle
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A message received on a topic
+#[derive(Debug, Clone)]
+pub struct RosMessage {
+    pub topic: String,
+    pub msg: Json,
+}
+
+/// Outcome of waiting on a [`MessageSource`]
+#[derive(Debug)]
+pub enum Received {
+    Message(RosMessage),
+    /// Nothing arrived within the timeout
+    Timeout,
+    /// The transport has shut down; no more messages will arrive
+    Closed,
+}
+
+/// Transport delivering ROS 2 messages as JSON
+pub trait MessageSource: Send {
+    /// Start receiving `topic` of ROS type `msg_type`
+    /// (e.g. `geometry_msgs/msg/PoseStamped`)
+    fn subscribe(&mut self, topic: &str, msg_type: &str) -> Result<()>;
+
+    /// Wait up to `timeout` for the next message
+    fn recv(&mut self, timeout: Duration) -> Result<Received>;
+}
+
+/// rosbridge v2 protocol over WebSocket, one JSON object per message
+pub struct RosbridgeSource {
+    ws: WebSocket,
+}
+
+impl RosbridgeSource {
+    /// Connect to a rosbridge_server (or compatible relay) listening on
+    /// `addr`, e.g. `127.0.0.1:9090`
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self> {
+        Self::connect_path(addr, "/")
+    }
+
+    /// Like [`Self::connect`], for a bridge served under `path`
+    pub fn connect_path<A: ToSocketAddrs>(addr: A, path: &str) -> Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let host = stream.peer_addr()?.to_string();
+        Ok(Self {
+            ws: WebSocket::handshake(stream, &host, path)?,
+        })
+    }
+}
+
+impl MessageSource for RosbridgeSource {
+    fn subscribe(&mut self, topic: &str, msg_type: &str) -> Result<()> {
+        let op = json!({ "op": "subscribe", "topic": topic, "type": msg_type });
+        self.ws.send_text(&op.to_string())
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Received> {
+        loop {
+            let payload = match self.ws.recv(timeout)? {
+                Incoming::Message(payload) => payload,
+                Incoming::Timeout => return Ok(Received::Timeout),
+                Incoming::Closed => return Ok(Received::Closed),
+            };
+            let mut op: Json = match serde_json::from_slice(&payload) {
+                Ok(op) => op,
+                Err(e) => {
+                    warn_log!("[ros2] skipping malformed rosbridge message: {}", e);
+                    continue;
+                }
+            };
+            // Status and service replies are not ingested
+            if op["op"] != "publish" {
+                continue;
+            }
+            if let (Some(topic), Some(msg)) = (
+                op["topic"].as_str().map(str::to_string),
+                op.get_mut("msg").map(Json::take),
+            ) {
+                return Ok(Received::Message(RosMessage { topic, msg }));
+            }
+        }
+    }
+}
+
+/// Maps the fields of one topic's messages to the columns of a table
+#[derive(Debug, Clone)]
+pub struct TopicMapping {
+    topic: String,
+    msg_type: String,
+    table: String,
+    fields: Vec<(String, String)>,
+}
+
+impl TopicMapping {
+    pub fn new(
+        topic: impl Into<String>,
+        msg_type: impl Into<String>,
+        table: impl Into<String>,
+    ) -> Self {
+        Self {
+            topic: topic.into(),
+            msg_type: msg_type.into(),
+            table: table.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Store the message field at `path` in `column` (an empty path maps
+    /// the whole message)
+    pub fn field(mut self, column: impl Into<String>, path: impl Into<String>) -> Self {
+        self.fields.push((column.into(), path.into()));
+        self
+    }
+}
+
+/// What the reader does when the insert queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Wait for the writer (stops reading from the source meanwhile)
+    Block,
+    /// Drop the row and count it in [`IngestStats::dropped`]
+    DropNewest,
+}
+
+/// Batching and backpressure settings
+#[derive(Debug, Clone)]
+pub struct IngestConfig {
+    /// Queued rows that trigger an insert
+    pub batch_size: usize,
+    /// Longest time a row waits in the queue before it is inserted
+    pub flush_interval: Duration,
+    /// Rows the queue holds before backpressure applies
+    pub queue_capacity: usize,
+    pub backpressure: Backpressure,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 256,
+            flush_interval: Duration::from_millis(100),
+            queue_capacity: 4096,
+            backpressure: Backpressure::Block,
+        }
+    }
+}
+
+/// Ingestion counters. `received` counts messages on mapped topics; the
+/// others count rows (a topic mapped to two tables yields two rows).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IngestStats {
+    pub received: u64,
+    /// Rows written to their table
+    pub inserted: u64,
+    /// Rows dropped because the queue was full
+    pub dropped: u64,
+    /// Rows whose fields could not be converted to the column types
+    pub rejected: u64,
+    /// Rows lost to a failed batch insert
+    pub failed: u64,
+    /// Batch inserts performed
+    pub batches: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    received: AtomicU64,
+    inserted: AtomicU64,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+    failed: AtomicU64,
+    batches: AtomicU64,
+}
+
+impl Counters {
+    fn add(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> IngestStats {
+        IngestStats {
+            received: self.received.load(Ordering::Relaxed),
+            inserted: self.inserted.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A mapping with its field paths split. Columns are looked up by name on
+/// every message, so the mapping follows ALTER TABLE.
+struct Route {
+    table: String,
+    /// (column, field path)
+    fields: Vec<(String, Vec<String>)>,
+}
+
+impl Route {
+    fn resolve(db: &MoteDB, mapping: &TopicMapping) -> Result<Self> {
+        let schema = db.get_table_schema(&mapping.table)?;
+        let fields = mapping
+            .fields
+            .iter()
+            .map(|(column, path)| {
+                if schema.get_column(column).is_none() {
+                    return Err(StorageError::ColumnNotFound(column.clone()));
+                }
+                let path = path
+                    .split('.')
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                Ok((column.clone(), path))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            table: mapping.table.clone(),
+            fields,
+        })
+    }
+
+    fn to_row(&self, db: &MoteDB, msg: &Json) -> Result<Row> {
+        let schema = db.get_table_schema(&self.table)?;
+        let mut row = vec![Value::Null; schema.columns.len()];
+        for (name, path) in &self.fields {
+            let column = schema
+                .get_column(name)
+                .ok_or_else(|| StorageError::ColumnNotFound(name.clone()))?;
+            let field = lookup(msg, path).unwrap_or(&Json::Null);
+            row[column.position] = convert(&column.col_type, field).map_err(|e| {
+                StorageError::InvalidData(format!(
+                    "Column '{}' (field '{}'): {}",
+                    column.name,
+                    path.join("."),
+                    e
+                ))
+            })?;
+        }
+        Ok(row)
+    }
+}
+
+fn lookup<'a>(msg: &'a Json, path: &[String]) -> Option<&'a Json> {
+    path.iter().try_fold(msg, |node, key| match node {
+        Json::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => node.get(key.as_str()),
+    })
+}
+
+/// Convert a message field to a value of `col_type`
+fn convert(col_type: &ColumnType, field: &Json) -> std::result::Result<Value, String> {
+    if field.is_null() {
+        return Ok(Value::Null);
+    }
+    match col_type {
+        ColumnType::Spatial => geometry(field).map(Value::spatial),
+        ColumnType::Timestamp => stamp(field).map(Value::Timestamp),
+        ColumnType::Text => Ok(match field {
+            Json::String(s) => Value::text_from(s),
+            Json::Array(items) => match bytes(items) {
+                Some(bytes) => Value::text(base64(&bytes)),
+                None => Value::text(field.to_string()),
+            },
+            other => Value::text(other.to_string()),
+        }),
+        ColumnType::Float => field
+            .as_f64()
+            .map(Value::Float)
+            .ok_or_else(|| format!("expected a number, got {}", field)),
+        ColumnType::Tensor(_) | ColumnType::EncodedVector { .. } | ColumnType::ShapedTensor(_) => {
+            let items = field
+                .as_array()
+                .ok_or_else(|| format!("expected a numeric array, got {}", field))?;
+            items
+                .iter()
+                .map(|v| v.as_f64().map(|f| f as f32))
+                .collect::<Option<Vec<f32>>>()
+                .map(|v| Value::Vector(ArcVec::new(v)))
+                .ok_or_else(|| "vector element is not a number".to_string())
+        }
+        _ => match field {
+            Json::Bool(b) => Ok(Value::Bool(*b)),
+            Json::Number(n) => Ok(match n.as_i64() {
+                Some(i) => Value::Integer(i),
+                None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+            }),
+            Json::String(s) => Ok(Value::text_from(s)),
+            other => Err(format!("expected a scalar, got {}", other)),
+        },
+    }
+}
+
+/// Point, `Pose` or a message wrapping a pose → point geometry
+fn geometry(field: &Json) -> std::result::Result<Geometry, String> {
+    if let Some(inner) = field.get("pose").or_else(|| field.get("position")) {
+        return geometry(inner);
+    }
+    let coord = |key: &str| field.get(key).and_then(Json::as_f64);
+    match (coord("x"), coord("y"), coord("z")) {
+        (Some(x), Some(y), Some(z)) => Ok(Geometry::Point3D(Point3D::new(x, y, z))),
+        (Some(x), Some(y), None) => Ok(Geometry::Point(Point::new(x, y))),
+        _ => Err(format!("expected a point or pose, got {}", field)),
+    }
+}
+
+/// `builtin_interfaces/Time`, a `Header`, or microseconds
+fn stamp(field: &Json) -> std::result::Result<Timestamp, String> {
+    if let Some(inner) = field.get("stamp") {
+        return stamp(inner);
+    }
+    if let Some(micros) = field.as_i64() {
+        return Ok(Timestamp::from_micros(micros));
+    }
+    // ROS 2 uses sec/nanosec, ROS 1 bridges secs/nsecs
+    let part = |a: &str, b: &str| field.get(a).or_else(|| field.get(b)).and_then(Json::as_i64);
+    let sec = part("sec", "secs").ok_or_else(|| format!("expected a time stamp, got {}", field))?;
+    let nanos = part("nanosec", "nsecs").unwrap_or(0);
+    Ok(Timestamp::from_micros(sec * 1_000_000 + nanos / 1_000))
+}
+
+fn bytes(items: &[Json]) -> Option<Vec<u8>> {
+    items
+        .iter()
+        .map(|v| v.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A running ingestion: a reader thread pulling messages from the source
+/// and a writer thread batching them into the database
+pub struct Ros2Ingest {
+    stop: Arc<AtomicBool>,
+    counters: Arc<Counters>,
+    reader: Option<JoinHandle<Result<()>>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Ros2Ingest {
+    /// Subscribe to every mapped topic and start ingesting
+    pub fn start<S: MessageSource + 'static>(
+        db: Arc<MoteDB>,
+        mut source: S,
+        mappings: Vec<TopicMapping>,
+        config: IngestConfig,
+    ) -> Result<Self> {
+        if config.batch_size == 0 || config.queue_capacity == 0 {
+            return Err(StorageError::InvalidArgument(
+                "batch_size and queue_capacity must be positive".into(),
+            ));
+        }
+
+        let mut routes = Vec::with_capacity(mappings.len());
+        let mut by_topic: HashMap<String, Vec<usize>> = HashMap::new();
+        for mapping in &mappings {
+            routes.push(Route::resolve(&db, mapping)?);
+            let targets = by_topic.entry(mapping.topic.clone()).or_default();
+            if targets.is_empty() {
+                source.subscribe(&mapping.topic, &mapping.msg_type)?;
+            }
+            targets.push(routes.len() - 1);
+        }
+        let routes = Arc::new(routes);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let counters = Arc::new(Counters::default());
+        let (tx, rx) = mpsc::sync_channel(config.queue_capacity);
+        let backpressure = config.backpressure;
+
+        let writer = {
+            let db = db.clone();
+            let routes = routes.clone();
+            let counters = counters.clone();
+            thread::Builder::new()
+                .name("motedb-ros2-writer".into())
+                .spawn(move || write_loop(&db, &routes, rx, &config, &counters))?
+        };
+        let reader = {
+            let stop = stop.clone();
+            let counters = counters.clone();
+            thread::Builder::new()
+                .name("motedb-ros2-reader".into())
+                .spawn(move || {
+                    read_loop(
+                        &db,
+                        &mut source,
+                        &routes,
+                        &by_topic,
+                        tx,
+                        backpressure,
+                        &stop,
+                        &counters,
+                    )
+                })?
+        };
+
+        Ok(Self {
+            stop,
+            counters,
+            reader: Some(reader),
+            writer: Some(writer),
+        })
+    }
+
+    pub fn stats(&self) -> IngestStats {
+        self.counters.snapshot()
+    }
+
+    /// Whether the source is still delivering (false once it closed or failed)
+    pub fn is_running(&self) -> bool {
+        self.reader
+            .as_ref()
+            .is_some_and(|reader| !reader.is_finished())
+    }
+
+    /// Stop reading, insert every queued row and return the final counters.
+    /// Fails with the source's error if the transport broke.
+    pub fn stop(mut self) -> Result<IngestStats> {
+        self.shutdown()?;
+        Ok(self.stats())
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        let read = match self.reader.take() {
+            Some(reader) => reader.join().unwrap_or_else(|_| {
+                Err(StorageError::InvalidData(
+                    "ROS 2 reader thread panicked".into(),
+                ))
+            }),
+            None => Ok(()),
+        };
+        // The writer drains the queue once the reader drops its sender
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        read
+    }
+}
+
+impl Drop for Ros2Ingest {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+fn read_loop(
+    db: &MoteDB,
+    source: &mut dyn MessageSource,
+    routes: &[Route],
+    by_topic: &HashMap<String, Vec<usize>>,
+    tx: SyncSender<(usize, Row)>,
+    backpressure: Backpressure,
+    stop: &AtomicBool,
+    counters: &Counters,
+) -> Result<()> {
+    while !stop.load(Ordering::Relaxed) {
+        let message = match source.recv(POLL_INTERVAL)? {
+            Received::Message(message) => message,
+            Received::Timeout => continue,
+            Received::Closed => break,
+        };
+        let Some(targets) = by_topic.get(&message.topic) else {
+            continue;
+        };
+        Counters::add(&counters.received, 1);
+        for &route in targets {
+            let row = match routes[route].to_row(db, &message.msg) {
+                Ok(row) => row,
+                Err(e) => {
+                    Counters::add(&counters.rejected, 1);
+                    warn_log!("[ros2] rejected message on '{}': {}", message.topic, e);
+                    continue;
+                }
+            };
+            match backpressure {
+                Backpressure::Block => {
+                    if tx.send((route, row)).is_err() {
+                        return Ok(());
+                    }
+                }
+                Backpressure::DropNewest => match tx.try_send((route, row)) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => Counters::add(&counters.dropped, 1),
+                    Err(TrySendError::Disconnected(_)) => return Ok(()),
+                },
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_loop(
+    db: &MoteDB,
+    routes: &[Route],
+    rx: Receiver<(usize, Row)>,
+    config: &IngestConfig,
+    counters: &Counters,
+) {
+    let mut pending: Vec<Vec<Row>> = routes.iter().map(|_| Vec::new()).collect();
+    let mut queued = 0;
+    let mut deadline = Instant::now() + config.flush_interval;
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let disconnected = match rx.recv_timeout(timeout) {
+            Ok((route, row)) => {
+                pending[route].push(row);
+                queued += 1;
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if queued >= config.batch_size || disconnected || Instant::now() >= deadline {
+            for (route, rows) in routes.iter().zip(pending.iter_mut()) {
+                if rows.is_empty() {
+                    continue;
+                }
+                let rows = std::mem::take(rows);
+                let count = rows.len() as u64;
+                match db.batch_insert_rows_to_table(&route.table, rows) {
+                    Ok(_) => {
+                        Counters::add(&counters.inserted, count);
+                        Counters::add(&counters.batches, 1);
+                    }
+                    Err(e) => {
+                        Counters::add(&counters.failed, count);
+                        warn_log!("[ros2] inserting into '{}' failed: {}", route.table, e);
+                    }
+                }
+            }
+            queued = 0;
+            deadline = Instant::now() + config.flush_interval;
+        }
+        if disconnected {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(&[0xFF, 0x00, 0x7F, 0x80]), "/wB/gA==");
+    }
+}
@@ -0,0 +1,496 @@
+//! WebSocket client for [`RosbridgeSource`](super::RosbridgeSource)
+//!
+//! rosbridge_server only speaks WebSocket (RFC 6455), so the adapter carries
+//! the small subset it needs: the opening handshake, masked client frames,
+//! and unmasked server frames. Fragmented messages are reassembled, pings are
+//! answered, and a close frame ends the connection. No extensions are
+//! offered (so no permessage-deflate) and there is no TLS; put a local relay
+//! in front of a `wss://` bridge.
+
+use super::base64;
+use crate::{Result, StorageError};
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// GUID the server appends to the client key (RFC 6455 section 1.3)
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Longest handshake response accepted
+const MAX_HANDSHAKE_BYTES: usize = 16 * 1024;
+
+/// How long the server gets to answer the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest message accepted, after reassembly (images arrive base64-encoded)
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Outcome of waiting for a message
+#[derive(Debug, PartialEq)]
+pub(super) enum Incoming {
+    /// Payload of a text or binary message
+    Message(Vec<u8>),
+    Timeout,
+    Closed,
+}
+
+/// A client connection after a successful handshake
+pub(super) struct WebSocket {
+    stream: TcpStream,
+    /// Bytes received but not yet consumed as a frame
+    buf: Vec<u8>,
+    /// Payload of a fragmented message still being received
+    partial: Option<Vec<u8>>,
+}
+
+impl WebSocket {
+    /// Run the opening handshake for `path` on a connected stream
+    pub(super) fn handshake(mut stream: TcpStream, host: &str, path: &str) -> Result<Self> {
+        let key = base64(&rand::random::<[u8; 16]>());
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path, host, key
+        )?;
+
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let mut buf = Vec::new();
+        let header_len = loop {
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            if buf.len() > MAX_HANDSHAKE_BYTES {
+                return Err(protocol("handshake response too long"));
+            }
+            let mut chunk = [0u8; 1024];
+            match stream.read(&mut chunk) {
+                Ok(0) => return Err(protocol("connection closed during handshake")),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..header_len]).into_owned();
+        check_response(&head, &key)?;
+        // The server may send frames right behind the response
+        buf.drain(..header_len);
+        Ok(Self {
+            stream,
+            buf,
+            partial: None,
+        })
+    }
+
+    /// Send one text message
+    pub(super) fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send_frame(OP_TEXT, text.as_bytes())
+    }
+
+    /// Wait up to `timeout` for the next text or binary message
+    pub(super) fn recv(&mut self, timeout: Duration) -> Result<Incoming> {
+        self.stream
+            .set_read_timeout(Some(timeout.max(Duration::from_millis(1))))?;
+        loop {
+            if let Some((frame, used)) = parse_frame(&self.buf)? {
+                self.buf.drain(..used);
+                if let Some(message) = self.on_frame(frame)? {
+                    return Ok(message);
+                }
+                continue;
+            }
+            let mut chunk = [0u8; 16 * 1024];
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(Incoming::Closed),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(Incoming::Timeout)
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn on_frame(&mut self, frame: Frame) -> Result<Option<Incoming>> {
+        match frame.opcode {
+            OP_TEXT | OP_BINARY if self.partial.is_some() => {
+                Err(protocol("new message inside a fragmented one"))
+            }
+            OP_TEXT | OP_BINARY if frame.fin => Ok(Some(Incoming::Message(frame.payload))),
+            OP_TEXT | OP_BINARY => {
+                self.partial = Some(frame.payload);
+                Ok(None)
+            }
+            OP_CONTINUATION => {
+                let Some(partial) = self.partial.as_mut() else {
+                    return Err(protocol("continuation frame without a message"));
+                };
+                if partial.len() + frame.payload.len() > MAX_MESSAGE_BYTES {
+                    return Err(protocol("message too large"));
+                }
+                partial.extend_from_slice(&frame.payload);
+                if frame.fin {
+                    Ok(self.partial.take().map(Incoming::Message))
+                } else {
+                    Ok(None)
+                }
+            }
+            OP_PING => {
+                self.send_frame(OP_PONG, &frame.payload)?;
+                Ok(None)
+            }
+            OP_PONG => Ok(None),
+            OP_CLOSE => {
+                // Echo the status code; the server then closes the socket
+                let code = &frame.payload[..frame.payload.len().min(2)];
+                let _ = self.send_frame(OP_CLOSE, code);
+                Ok(Some(Incoming::Closed))
+            }
+            op => Err(protocol(&format!("unknown opcode {:#x}", op))),
+        }
+    }
+
+    /// Write one final, masked frame (clients must mask everything they send)
+    fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<()> {
+        let mask = rand::random::<[u8; 4]>();
+        let mut out = Vec::with_capacity(payload.len() + 14);
+        out.push(0x80 | opcode);
+        match payload.len() {
+            n if n < 126 => out.push(0x80 | n as u8),
+            n if n <= u16::MAX as usize => {
+                out.push(0x80 | 126);
+                out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            n => {
+                out.push(0x80 | 127);
+                out.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+        }
+        out.extend_from_slice(&mask);
+        out.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        self.stream.write_all(&out)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+/// Decode the frame at the start of `buf`, with the bytes it takes up, or
+/// `None` if it hasn't fully arrived
+fn parse_frame(buf: &[u8]) -> Result<Option<(Frame, usize)>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    if buf[0] & 0x70 != 0 {
+        return Err(protocol("reserved bits set without an extension"));
+    }
+    let fin = buf[0] & 0x80 != 0;
+    let opcode = buf[0] & 0x0F;
+    let masked = buf[1] & 0x80 != 0;
+    let (len, mut pos) = match buf[1] & 0x7F {
+        126 if buf.len() < 4 => return Ok(None),
+        126 => (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4),
+        127 if buf.len() < 10 => return Ok(None),
+        127 => (u64::from_be_bytes(buf[2..10].try_into().unwrap()), 10),
+        n => (n as u64, 2),
+    };
+    if opcode >= OP_CLOSE && (!fin || len > 125) {
+        return Err(protocol("control frame fragmented or over 125 bytes"));
+    }
+    if len > MAX_MESSAGE_BYTES as u64 {
+        return Err(protocol("message too large"));
+    }
+    let mask = if masked {
+        if buf.len() < pos + 4 {
+            return Ok(None);
+        }
+        pos += 4;
+        Some([buf[pos - 4], buf[pos - 3], buf[pos - 2], buf[pos - 1]])
+    } else {
+        None
+    };
+    let end = pos + len as usize;
+    if buf.len() < end {
+        return Ok(None);
+    }
+    let mut payload = buf[pos..end].to_vec();
+    if let Some(mask) = mask {
+        for (i, b) in payload.iter_mut().enumerate() {
+            *b ^= mask[i % 4];
+        }
+    }
+    Ok(Some((
+        Frame {
+            fin,
+            opcode,
+            payload,
+        },
+        end,
+    )))
+}
+
+/// Check the server's handshake response against the key that was sent
+fn check_response(head: &str, key: &str) -> Result<()> {
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("101") {
+        return Err(protocol(&format!(
+            "server refused the WebSocket upgrade: {}",
+            status
+        )));
+    }
+    let header = |name: &str| {
+        head.split("\r\n").skip(1).find_map(|line| {
+            let (n, v) = line.split_once(':')?;
+            n.trim().eq_ignore_ascii_case(name).then(|| v.trim())
+        })
+    };
+    let upgrade = header("Upgrade").unwrap_or_default();
+    let connection = header("Connection").unwrap_or_default();
+    if !upgrade.eq_ignore_ascii_case("websocket")
+        || !connection
+            .split(',')
+            .any(|t| t.trim().eq_ignore_ascii_case("upgrade"))
+    {
+        return Err(protocol("handshake response is not a WebSocket upgrade"));
+    }
+    if header("Sec-WebSocket-Accept") != Some(accept_key(key).as_str()) {
+        return Err(protocol(
+            "handshake response has a wrong Sec-WebSocket-Accept",
+        ));
+    }
+    Ok(())
+}
+
+/// `Sec-WebSocket-Accept` value for a client key
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, ACCEPT_GUID).as_bytes()))
+}
+
+fn protocol(msg: &str) -> StorageError {
+    StorageError::Io(std::io::Error::new(
+        ErrorKind::InvalidData,
+        format!("rosbridge WebSocket: {}", msg),
+    ))
+}
+
+/// SHA-1, only for the handshake's accept key
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in msg.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    for (bytes, h) in out.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&h.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_sha1_and_accept_key() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(&sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        // RFC 6455 section 1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    /// Server side of a rosbridge_server (tornado) session: the 101 response
+    /// with tornado's header spelling, then a status op, a ping, a publish
+    /// with a 16-bit length, a publish split across a text and a
+    /// continuation frame, and a normal close.
+    fn rosbridge_replay(accept: &str) -> Vec<u8> {
+        let mut out = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nServer: TornadoServer/6.1\r\n\
+             Date: Tue, 02 Jul 2024 09:12:44 GMT\r\nUpgrade: websocket\r\n\
+             Connection: Upgrade\r\nSec-Websocket-Accept: {}\r\n\r\n",
+            accept
+        )
+        .into_bytes();
+        let status = br#"{"op": "status", "level": "info", "msg": "subscribed"}"#;
+        out.extend_from_slice(&[0x81, status.len() as u8]);
+        out.extend_from_slice(status);
+        out.extend_from_slice(&[0x89, 0x04]);
+        out.extend_from_slice(b"ping");
+        let long = format!(
+            r#"{{"op": "publish", "topic": "/chatter", "msg": {{"data": "{}"}}}}"#,
+            "x".repeat(200)
+        );
+        out.extend_from_slice(&[0x81, 126]);
+        out.extend_from_slice(&(long.len() as u16).to_be_bytes());
+        out.extend_from_slice(long.as_bytes());
+        let split: &[u8] = br#"{"op": "publish", "topic": "/chatter", "msg": {"data": "hi"}}"#;
+        let (head, tail) = split.split_at(20);
+        out.extend_from_slice(&[0x01, head.len() as u8]);
+        out.extend_from_slice(head);
+        out.extend_from_slice(&[0x80, tail.len() as u8]);
+        out.extend_from_slice(tail);
+        out.extend_from_slice(&[0x88, 0x02, 0x03, 0xE8]);
+        out
+    }
+
+    #[test]
+    fn test_replayed_rosbridge_session() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut byte = [0u8; 1];
+            while !request.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            let key = request
+                .lines()
+                .find_map(|l| l.strip_prefix("Sec-WebSocket-Key: "))
+                .unwrap();
+            // Everything in one write, so frames arrive with the response
+            stream
+                .write_all(&rosbridge_replay(&accept_key(key)))
+                .unwrap();
+
+            let mut sent = Vec::new();
+            stream.read_to_end(&mut sent).unwrap();
+            let mut frames = Vec::new();
+            let mut rest = &sent[..];
+            while let Some((frame, used)) = parse_frame(rest).unwrap() {
+                assert_ne!(sent[sent.len() - rest.len() + 1] & 0x80, 0, "unmasked");
+                frames.push((frame.opcode, frame.payload));
+                rest = &rest[used..];
+            }
+            assert!(rest.is_empty());
+            frames
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut ws = WebSocket::handshake(stream, &addr.to_string(), "/").unwrap();
+        ws.send_text(r#"{"op": "subscribe", "topic": "/chatter"}"#)
+            .unwrap();
+        let mut messages = Vec::new();
+        loop {
+            match ws.recv(Duration::from_secs(5)).unwrap() {
+                Incoming::Message(m) => messages.push(String::from_utf8(m).unwrap()),
+                Incoming::Closed => break,
+                Incoming::Timeout => panic!("replay stalled"),
+            }
+        }
+        drop(ws);
+
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].contains(r#""op": "status""#));
+        assert!(messages[1].ends_with(&format!(r#""{}"}}}}"#, "x".repeat(200))));
+        assert_eq!(
+            messages[2],
+            r#"{"op": "publish", "topic": "/chatter", "msg": {"data": "hi"}}"#
+        );
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                (
+                    OP_TEXT,
+                    br#"{"op": "subscribe", "topic": "/chatter"}"#.to_vec()
+                ),
+                (OP_PONG, b"ping".to_vec()),
+                (OP_CLOSE, vec![0x03, 0xE8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handshake_rejections() {
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        let ok = "HTTP/1.1 101 Switching Protocols\r\nupgrade: WebSocket\r\n\
+                  connection: keep-alive, Upgrade\r\n\
+                  sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+        check_response(ok, key).unwrap();
+        assert!(check_response(&ok.replace("s3pP", "AAAA"), key).is_err());
+        assert!(
+            check_response(&ok.replace("101 Switching Protocols", "404 Not Found"), key).is_err()
+        );
+        assert!(check_response(&ok.replace("WebSocket", "h2c"), key).is_err());
+    }
+
+    #[test]
+    fn test_frame_limits() {
+        // Incomplete headers and payloads wait for more bytes
+        assert!(parse_frame(&[0x81]).unwrap().is_none());
+        assert!(parse_frame(&[0x81, 126, 0x01]).unwrap().is_none());
+        assert!(parse_frame(&[0x81, 0x05, b'h']).unwrap().is_none());
+        // Reserved bits, oversized control frames and huge lengths are errors
+        assert!(parse_frame(&[0xC1, 0x00]).is_err());
+        assert!(parse_frame(&[0x89, 126, 0x00, 0x80]).is_err());
+        let mut huge = vec![0x82, 127];
+        huge.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(parse_frame(&huge).is_err());
+    }
+}
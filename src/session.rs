@@ -0,0 +1,701 @@
+//! Sessions and session pool
+//!
+//! A [`Session`] is one logical connection to a shared `Arc<MoteDB>`: it owns
+//! its open transaction, its settings and its named prepared statements.
+//! The executor tracks the active transaction per *thread*, so a session
+//! swaps its transaction into the calling thread for the duration of each
+//! call and takes it back out afterwards. That lets a host hand a session to
+//! any worker thread (one thread at a time) without transactions leaking
+//! between sessions that happen to share a thread.
+//!
+//! With `statement_retries` set, a statement aborted by a write conflict or
+//! lock timeout ([`StorageError::is_transient`]) is run again after a short,
+//! doubling delay. Conflicts surface when a transaction commits, so a
+//! session keeps the statements of a transaction begun with retries on, and
+//! when COMMIT fails it rolls back and re-runs the transaction from BEGIN.
+//! The re-run sees the rows committed in the meantime, and its statements
+//! may read different values than the first run returned to the caller.
+//!
+//! [`SessionPool`] bounds the number of live sessions and recycles them:
+//! dropping a [`PooledSession`] rolls back any open transaction, clears its
+//! settings, prepared statements and access hook, and frees its slot.
+//!
+//! ```ignore
+//! let pool = SessionPool::new(db, 8);
+//! let mut s = pool.acquire(Some(Duration::from_secs(1)))?;
+//! s.execute("BEGIN")?;
+//! s.execute("INSERT INTO t VALUES (1)")?;
+//! s.execute("COMMIT")?;
+//! drop(s); // back to the pool
+//! ```
+
+use crate::database::MoteDB;
+use crate::sql::{AccessHook, Lexer, Parser, QueryExecutor, QueryResult, Statement};
+use crate::types::{UtcOffset, Value};
+use crate::{Result, StorageError};
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Hits returned by a full-text MATCH without LIMIT when no session limit is set
+pub const DEFAULT_TEXT_SEARCH_LIMIT: usize = 1000;
+
+/// Delay before the first retry of a conflicting statement when no session
+/// backoff is set
+pub const DEFAULT_RETRY_BACKOFF_MS: usize = 5;
+
+/// The retry delay stops doubling after this many retries
+const MAX_RETRY_BACKOFF_DOUBLINGS: usize = 6;
+
+/// How TIMESTAMP values are rendered in JSON results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Microseconds since the Unix epoch, as a number
+    #[default]
+    Micros,
+    /// ISO 8601 string in the session time zone
+    Iso,
+}
+
+/// Per-session settings
+///
+/// Settable by name through [`set`](Self::set) or with SQL
+/// (`SET [SESSION] name = value`, `SHOW name`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionSettings {
+    /// Cap on rows returned by a SELECT (excess rows are dropped). None = unlimited
+    pub max_rows: Option<usize>,
+    /// Reject statements that modify data or schema
+    pub read_only: bool,
+    /// Rows fetched per storage batch by streaming scans. None = database default
+    pub stream_batch_size: Option<usize>,
+    /// Most rows preallocated when a result is materialized. None = database default
+    pub materialize_capacity: Option<usize>,
+    /// Candidate list size of vector index searches. None = index default
+    pub search_beam_width: Option<usize>,
+    /// Re-rank vector search candidates with full-precision vectors.
+    /// None = each index's `rerank` setting
+    pub vector_rerank: Option<bool>,
+    /// Hits returned by a MATCH without LIMIT. None = [`DEFAULT_TEXT_SEARCH_LIMIT`]
+    pub text_search_limit: Option<usize>,
+    /// Bytes a statement's joins, sorts and aggregations may buffer. None = database default
+    pub query_memory_limit: Option<usize>,
+    /// Offset used to render timestamps and to read timestamp text naming none
+    pub time_zone: UtcOffset,
+    /// Rendering of TIMESTAMP values in JSON results
+    pub timestamp_format: TimestampFormat,
+    /// Times a statement aborted by a write conflict or lock timeout is run
+    /// again; a failed COMMIT re-runs its whole transaction. None = no retry
+    pub statement_retries: Option<usize>,
+    /// Milliseconds before the first retry, doubled on each further one.
+    /// None = [`DEFAULT_RETRY_BACKOFF_MS`]
+    pub retry_backoff_ms: Option<usize>,
+}
+
+/// Parse a row count where `none` or `0` means "not set"
+fn parse_optional_count(key: &str, value: &str) -> Result<Option<usize>> {
+    if value.eq_ignore_ascii_case("none") || value == "0" {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| StorageError::InvalidArgument(format!("{}: invalid value '{}'", key, value)))
+}
+
+fn render_optional_count(value: Option<usize>) -> String {
+    value.map_or_else(|| "none".to_string(), |n| n.to_string())
+}
+
+/// Parse a boolean switch where `none` means "not set"
+fn parse_optional_switch(key: &str, value: &str) -> Result<Option<bool>> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(None),
+        "true" | "on" | "1" => Ok(Some(true)),
+        "false" | "off" | "0" => Ok(Some(false)),
+        _ => Err(StorageError::InvalidArgument(format!(
+            "{}: invalid value '{}'",
+            key, value
+        ))),
+    }
+}
+
+impl SessionSettings {
+    /// Set a setting by name (`max_rows`, `read_only`, `stream_batch_size`,
+    /// `materialize_capacity`, `search_beam_width`, `vector_rerank`,
+    /// `text_search_limit`, `query_memory_limit`, `time_zone`,
+    /// `timestamp_format`, `statement_retries`, `retry_backoff_ms`)
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        match key.to_ascii_lowercase().as_str() {
+            "max_rows" => self.max_rows = parse_optional_count("max_rows", value)?,
+            "stream_batch_size" => {
+                self.stream_batch_size = parse_optional_count("stream_batch_size", value)?
+            }
+            "materialize_capacity" => {
+                self.materialize_capacity = parse_optional_count("materialize_capacity", value)?
+            }
+            "search_beam_width" => {
+                self.search_beam_width = parse_optional_count("search_beam_width", value)?
+            }
+            "vector_rerank" => self.vector_rerank = parse_optional_switch("vector_rerank", value)?,
+            "text_search_limit" => {
+                self.text_search_limit = parse_optional_count("text_search_limit", value)?
+            }
+            "query_memory_limit" => {
+                self.query_memory_limit = parse_optional_count("query_memory_limit", value)?
+            }
+            "statement_retries" => {
+                self.statement_retries = parse_optional_count("statement_retries", value)?
+            }
+            "retry_backoff_ms" => {
+                self.retry_backoff_ms = parse_optional_count("retry_backoff_ms", value)?
+            }
+            "time_zone" => {
+                self.time_zone = value
+                    .parse()
+                    .map_err(|e| StorageError::InvalidArgument(format!("time_zone: {}", e)))?
+            }
+            "timestamp_format" => {
+                self.timestamp_format = match value.to_ascii_lowercase().as_str() {
+                    "micros" => TimestampFormat::Micros,
+                    "iso" => TimestampFormat::Iso,
+                    _ => {
+                        return Err(StorageError::InvalidArgument(format!(
+                            "timestamp_format: invalid value '{}'",
+                            value
+                        )))
+                    }
+                };
+            }
+            "read_only" => {
+                self.read_only = match value.to_ascii_lowercase().as_str() {
+                    "true" | "on" | "1" => true,
+                    "false" | "off" | "0" => false,
+                    _ => {
+                        return Err(StorageError::InvalidArgument(format!(
+                            "read_only: invalid value '{}'",
+                            value
+                        )))
+                    }
+                };
+            }
+            _ => {
+                return Err(StorageError::InvalidArgument(format!(
+                    "unknown session setting '{}'",
+                    key
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Current value of a setting rendered as text
+    pub fn get(&self, key: &str) -> Option<String> {
+        match key.to_ascii_lowercase().as_str() {
+            "max_rows" => Some(render_optional_count(self.max_rows)),
+            "read_only" => Some(self.read_only.to_string()),
+            "stream_batch_size" => Some(render_optional_count(self.stream_batch_size)),
+            "materialize_capacity" => Some(render_optional_count(self.materialize_capacity)),
+            "search_beam_width" => Some(render_optional_count(self.search_beam_width)),
+            "vector_rerank" => Some(
+                self.vector_rerank
+                    .map_or_else(|| "none".to_string(), |on| on.to_string()),
+            ),
+            "text_search_limit" => Some(render_optional_count(self.text_search_limit)),
+            "query_memory_limit" => Some(render_optional_count(self.query_memory_limit)),
+            "statement_retries" => Some(render_optional_count(self.statement_retries)),
+            "retry_backoff_ms" => Some(render_optional_count(self.retry_backoff_ms)),
+            "time_zone" => Some(self.time_zone.to_string()),
+            "timestamp_format" => Some(
+                match self.timestamp_format {
+                    TimestampFormat::Micros => "micros",
+                    TimestampFormat::Iso => "iso",
+                }
+                .to_string(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Restore one setting to its default (`SET name = DEFAULT`)
+    pub fn reset(&mut self, key: &str) -> Result<()> {
+        let value = Self::default().get(key).ok_or_else(|| {
+            StorageError::InvalidArgument(format!("unknown session setting '{}'", key))
+        })?;
+        self.set(key, &value)
+    }
+
+    /// Hits returned by a MATCH without LIMIT
+    pub fn text_search_limit(&self) -> usize {
+        self.text_search_limit.unwrap_or(DEFAULT_TEXT_SEARCH_LIMIT)
+    }
+
+    /// Delay before retry number `attempt` (0-based)
+    fn retry_delay(&self, attempt: usize) -> Duration {
+        let base = self.retry_backoff_ms.unwrap_or(DEFAULT_RETRY_BACKOFF_MS) as u64;
+        Duration::from_millis(base << attempt.min(MAX_RETRY_BACKOFF_DOUBLINGS))
+    }
+}
+
+/// A call made inside a transaction, kept to re-run the transaction
+enum LoggedCall {
+    Statement(Arc<Statement>, Vec<Value>),
+    Batch(Arc<Statement>, Vec<Vec<Value>>),
+}
+
+/// One logical connection: transaction state, settings and prepared statements
+pub struct Session {
+    db: Arc<MoteDB>,
+    executor: QueryExecutor,
+    txn_id: Option<u64>,
+    settings: SessionSettings,
+    statements: HashMap<String, Arc<Statement>>,
+    /// Calls since BEGIN, when the transaction began with retries on
+    txn_log: Option<Vec<LoggedCall>>,
+}
+
+fn parse_sql(sql: &str) -> Result<Statement> {
+    let mut lexer = Lexer::new(sql);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(tokens);
+    parser.parse()
+}
+
+impl Session {
+    pub fn new(db: Arc<MoteDB>) -> Self {
+        Self {
+            executor: QueryExecutor::new(db.clone()),
+            db,
+            txn_id: None,
+            settings: SessionSettings::default(),
+            statements: HashMap::new(),
+            txn_log: None,
+        }
+    }
+
+    /// Database this session is attached to
+    pub fn db(&self) -> &Arc<MoteDB> {
+        &self.db
+    }
+
+    pub fn settings(&self) -> &SessionSettings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut SessionSettings {
+        &mut self.settings
+    }
+
+    /// Ask `hook` about every table a statement touches before running it;
+    /// statements touching a denied table fail with `PermissionDenied`, and
+    /// SHOW TABLES lists only readable tables. None removes the hook.
+    pub fn set_access_hook(&mut self, hook: Option<AccessHook>) {
+        self.executor.set_access_hook(hook);
+    }
+
+    /// Whether a transaction opened by this session is still active
+    pub fn in_transaction(&self) -> bool {
+        self.txn_id.is_some()
+    }
+
+    /// Parse and execute one SQL statement
+    pub fn execute(&mut self, sql: &str) -> Result<QueryResult> {
+        let statement = Arc::new(parse_sql(sql)?);
+        self.run(&statement, Vec::new())
+    }
+
+    /// Parse `sql` and store it under `name` (replaces an existing entry)
+    pub fn prepare(&mut self, name: &str, sql: &str) -> Result<()> {
+        let statement = parse_sql(sql)?;
+        self.statements
+            .insert(name.to_string(), Arc::new(statement));
+        Ok(())
+    }
+
+    /// Execute a statement stored by [`prepare`](Self::prepare), binding `params` to `?`
+    pub fn execute_prepared(&mut self, name: &str, params: Vec<Value>) -> Result<QueryResult> {
+        let statement = self.statements.get(name).cloned().ok_or_else(|| {
+            StorageError::InvalidArgument(format!("no prepared statement named '{}'", name))
+        })?;
+        self.run(&statement, params)
+    }
+
+    /// Execute a prepared single-row INSERT once per tuple in `param_rows`,
+    /// writing all rows as one batch
+    pub fn execute_prepared_batch(
+        &mut self,
+        name: &str,
+        param_rows: &[Vec<Value>],
+    ) -> Result<QueryResult> {
+        let statement = self.statements.get(name).cloned().ok_or_else(|| {
+            StorageError::InvalidArgument(format!("no prepared statement named '{}'", name))
+        })?;
+        if self.txn_id.is_none() {
+            return self.retry_transient(|s| s.run_batch_once(&statement, param_rows));
+        }
+        let result = self.run_batch_once(&statement, param_rows);
+        if let (Ok(_), Some(log)) = (&result, self.txn_log.as_mut()) {
+            log.push(LoggedCall::Batch(statement, param_rows.to_vec()));
+        }
+        result
+    }
+
+    /// Drop a prepared statement; returns false if it did not exist
+    pub fn deallocate(&mut self, name: &str) -> bool {
+        self.statements.remove(name).is_some()
+    }
+
+    /// Roll back any open transaction, restore default settings and remove
+    /// the access hook
+    pub fn reset(&mut self) -> Result<()> {
+        self.statements.clear();
+        self.settings = SessionSettings::default();
+        self.executor.set_access_hook(None);
+        self.txn_log = None;
+        if self.txn_id.is_some() {
+            self.run_once(&Statement::RollbackTransaction, Vec::new())?;
+        }
+        Ok(())
+    }
+
+    /// Run a statement, retrying it (or its whole transaction, for COMMIT)
+    /// on transient errors as the session's retry settings allow
+    fn run(&mut self, statement: &Arc<Statement>, params: Vec<Value>) -> Result<QueryResult> {
+        if self.txn_id.is_none() {
+            let result = if self.settings.statement_retries.is_some() {
+                self.retry_transient(|s| s.run_once(statement, params.clone()))
+            } else {
+                self.run_once(statement, params)
+            };
+            // BEGIN: keep the transaction's calls to re-run them
+            if self.txn_id.is_some() && self.settings.statement_retries.is_some() {
+                self.txn_log = Some(Vec::new());
+            }
+            return result;
+        }
+        if matches!(**statement, Statement::CommitTransaction) {
+            return self.commit_with_retry(statement);
+        }
+        let logged = self.txn_log.as_ref().map(|_| params.clone());
+        let result = self.run_once(statement, params);
+        if self.txn_id.is_none() {
+            self.txn_log = None;
+        } else if let (Ok(_), Some(params), Some(log)) = (&result, logged, self.txn_log.as_mut()) {
+            log.push(LoggedCall::Statement(statement.clone(), params));
+        }
+        result
+    }
+
+    /// Call `attempt` again while it fails with a transient error and
+    /// retries are left, sleeping the backoff delay in between
+    fn retry_transient(
+        &mut self,
+        mut attempt: impl FnMut(&mut Self) -> Result<QueryResult>,
+    ) -> Result<QueryResult> {
+        let retries = self.settings.statement_retries.unwrap_or(0);
+        let mut retried = 0;
+        loop {
+            match attempt(self) {
+                Err(e) if e.is_transient() && retried < retries => {
+                    debug_log!("[session] retrying after transient error: {}", e);
+                    std::thread::sleep(self.settings.retry_delay(retried));
+                    retried += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// COMMIT the open transaction. If that fails with a transient error,
+    /// roll back and re-run the logged calls from BEGIN, then COMMIT again.
+    /// Once retries were made and still fail, the transaction is rolled back.
+    fn commit_with_retry(&mut self, commit: &Statement) -> Result<QueryResult> {
+        let mut result = self.run_once(commit, Vec::new());
+        let Some(log) = self.txn_log.take() else {
+            return result;
+        };
+        let retries = self.settings.statement_retries.unwrap_or(0);
+        let mut retried = 0;
+        while retried < retries && matches!(&result, Err(e) if e.is_transient()) {
+            if let Err(e) = &result {
+                debug_log!("[session] re-running transaction after: {}", e);
+            }
+            let delay = self.settings.retry_delay(retried);
+            retried += 1;
+            result = self
+                .run_once(&Statement::RollbackTransaction, Vec::new())
+                .and_then(|_| {
+                    std::thread::sleep(delay);
+                    self.replay(&log)
+                })
+                .and_then(|_| self.run_once(commit, Vec::new()));
+        }
+        if self.txn_id.is_some() {
+            if retried > 0 {
+                self.run_once(&Statement::RollbackTransaction, Vec::new())?;
+            } else {
+                self.txn_log = Some(log);
+            }
+        }
+        result
+    }
+
+    /// BEGIN a transaction and re-run `log` in it
+    fn replay(&mut self, log: &[LoggedCall]) -> Result<()> {
+        self.run_once(&Statement::BeginTransaction, Vec::new())?;
+        for call in log {
+            match call {
+                LoggedCall::Statement(statement, params) => {
+                    self.run_once(statement, params.clone())?
+                }
+                LoggedCall::Batch(statement, param_rows) => {
+                    self.run_batch_once(statement, param_rows)?
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn run_batch_once(
+        &mut self,
+        statement: &Statement,
+        param_rows: &[Vec<Value>],
+    ) -> Result<QueryResult> {
+        if self.settings.read_only {
+            return Err(StorageError::Query(
+                "session is read-only; statement would modify data".into(),
+            ));
+        }
+        self.with_txn_context(|executor| executor.execute_insert_batch(statement, param_rows))
+    }
+
+    fn run_once(&mut self, statement: &Statement, params: Vec<Value>) -> Result<QueryResult> {
+        if self.settings.read_only && !is_read_only(statement) {
+            return Err(StorageError::Query(
+                "session is read-only; statement would modify data".into(),
+            ));
+        }
+        let max_rows = self.settings.max_rows;
+        self.with_txn_context(|executor| {
+            executor.reset_last_insert_id();
+            executor.bind_params(params);
+            let result = executor
+                .execute_streaming_ref(statement)
+                .and_then(|r| r.materialize_with_limit(max_rows));
+            executor.clear_params();
+            let (mut result, _) = result?;
+            // Only streaming SELECTs honour the limit inside materialize
+            if let (QueryResult::Select { rows, .. }, Some(max)) = (&mut result, max_rows) {
+                rows.truncate(max);
+            }
+            Ok(result)
+        })
+    }
+
+    /// Install this session's transaction as the thread's active one, and its
+    /// settings on the executor, while `f` runs
+    fn with_txn_context<T>(&mut self, f: impl FnOnce(&QueryExecutor) -> T) -> T {
+        let outer = self.executor.current_txn_id();
+        match self.txn_id {
+            Some(id) => self.executor.begin_txn_context(id),
+            None => self.executor.clear_txn_context(),
+        }
+        self.executor.set_session_settings(self.settings.clone());
+        let result = f(&self.executor);
+        // SET statements update the executor's copy
+        self.settings = self.executor.session_settings();
+        self.txn_id = self.executor.current_txn_id();
+        match outer {
+            Some(id) => self.executor.begin_txn_context(id),
+            None => self.executor.clear_txn_context(),
+        }
+        result
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if self.txn_id.is_some() {
+            if let Err(e) = self.run_once(&Statement::RollbackTransaction, Vec::new()) {
+                warn_log!("[session] rollback on drop failed: {}", e);
+            }
+        }
+    }
+}
+
+/// SET read_only counts as a write, so a read-only session cannot lift itself.
+/// `COPY ... TO` writes a host file, so it counts as a write too.
+fn is_read_only(statement: &Statement) -> bool {
+    match statement {
+        Statement::SetVariable { name, .. } => !name.eq_ignore_ascii_case("read_only"),
+        _ => matches!(
+            statement,
+            Statement::Select { .. }
+                | Statement::SetOp { .. }
+                | Statement::ShowTables
+                | Statement::ShowTransactions
+                | Statement::ShowTransactionStats
+                | Statement::ShowVariable(_)
+                | Statement::DescribeTable(_)
+                | Statement::Explain(_)
+                | Statement::BeginTransaction
+                | Statement::CommitTransaction
+                | Statement::RollbackTransaction
+        ),
+    }
+}
+
+struct PoolState {
+    idle: Vec<Session>,
+    /// Sessions handed out and not yet returned
+    in_use: usize,
+}
+
+/// State shared by a pool and the sessions it has handed out
+struct PoolShared {
+    db: Arc<MoteDB>,
+    max_size: usize,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+impl PoolShared {
+    /// Give a slot back, keeping the session for reuse if it resets cleanly
+    fn put_back(&self, mut session: Session) {
+        let session = match session.reset() {
+            Ok(()) => Some(session),
+            Err(e) => {
+                warn_log!("[session] discarding session after failed reset: {}", e);
+                None
+            }
+        };
+        let mut state = self.state.lock();
+        state.in_use = state.in_use.saturating_sub(1);
+        state.idle.extend(session);
+        drop(state);
+        self.available.notify_one();
+    }
+}
+
+/// Bounded pool of reusable sessions
+pub struct SessionPool {
+    shared: Arc<PoolShared>,
+}
+
+impl SessionPool {
+    /// Create a pool of at most `max_size` sessions (minimum 1)
+    pub fn new(db: Arc<MoteDB>, max_size: usize) -> Self {
+        Self {
+            shared: Arc::new(PoolShared {
+                db,
+                max_size: max_size.max(1),
+                state: Mutex::new(PoolState {
+                    idle: Vec::new(),
+                    in_use: 0,
+                }),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.shared.max_size
+    }
+
+    /// (idle, in use) session counts
+    pub fn stats(&self) -> (usize, usize) {
+        let state = self.shared.state.lock();
+        (state.idle.len(), state.in_use)
+    }
+
+    /// Take a session, creating one if below `max_size`
+    ///
+    /// Blocks while the pool is exhausted: `None` waits indefinitely,
+    /// `Some(d)` fails with `ResourceExhausted` after `d`. The session goes
+    /// back to the pool when the returned guard is dropped.
+    pub fn acquire(&self, timeout: Option<Duration>) -> Result<PooledSession> {
+        let shared = &self.shared;
+        let deadline = timeout.map(|d| Instant::now() + d);
+        let mut state = shared.state.lock();
+        let session = loop {
+            if let Some(session) = state.idle.pop() {
+                break session;
+            }
+            if state.in_use < shared.max_size {
+                break Session::new(shared.db.clone());
+            }
+            match deadline {
+                Some(deadline) => {
+                    if shared
+                        .available
+                        .wait_until(&mut state, deadline)
+                        .timed_out()
+                    {
+                        return Err(StorageError::ResourceExhausted(format!(
+                            "session pool exhausted ({} sessions in use)",
+                            shared.max_size
+                        )));
+                    }
+                }
+                None => shared.available.wait(&mut state),
+            }
+        };
+        state.in_use += 1;
+        Ok(PooledSession {
+            session: Some(session),
+            pool: shared.clone(),
+        })
+    }
+
+    /// Return a session to the pool now
+    ///
+    /// Same as dropping it. A session acquired from another pool is rejected
+    /// with `InvalidArgument`; it still goes back to its own pool.
+    pub fn release(&self, session: PooledSession) -> Result<()> {
+        if !Arc::ptr_eq(&session.pool, &self.shared) {
+            return Err(StorageError::InvalidArgument(
+                "session belongs to another pool".into(),
+            ));
+        }
+        drop(session);
+        Ok(())
+    }
+}
+
+/// A session borrowed from a [`SessionPool`]
+///
+/// Dereferences to [`Session`]. Dropping it resets the session and gives its
+/// slot back to the pool, which outlives the guard if the pool is dropped first.
+pub struct PooledSession {
+    session: Option<Session>,
+    pool: Arc<PoolShared>,
+}
+
+impl Deref for PooledSession {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        self.session
+            .as_ref()
+            .expect("pooled session already returned")
+    }
+}
+
+impl DerefMut for PooledSession {
+    fn deref_mut(&mut self) -> &mut Session {
+        self.session
+            .as_mut()
+            .expect("pooled session already returned")
+    }
+}
+
+impl Drop for PooledSession {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            self.pool.put_back(session);
+        }
+    }
+}
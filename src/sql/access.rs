@@ -0,0 +1,237 @@
+//! Table access checks
+//!
+//! An [`AccessHook`] installed on a `Session` (or `Database` handle) is asked
+//! about every table a statement touches before the statement runs, so a host
+//! can hand a SQL surface to plugins without exposing every table. Denied
+//! statements fail with `StorageError::PermissionDenied` and have no effect.
+
+use super::ast::{CopyDirection, Expr, SelectColumn, SelectStmt, Statement, TableRef};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// How a statement uses a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableAccess {
+    /// SELECT, subqueries, DESCRIBE, SHOW TABLES and COPY ... TO
+    Read,
+    /// INSERT, UPDATE, DELETE and DDL on the table or its indexes
+    Write,
+}
+
+/// Decides whether a session may access a table: `(table, access) -> allowed`
+pub type AccessHook = Arc<dyn Fn(&str, TableAccess) -> bool + Send + Sync>;
+
+/// Tables a statement touches, deduplicated, in first-use order. Index and
+/// view statements that name no table (DROP INDEX, REINDEX) are resolved by
+/// the executor.
+pub fn table_accesses(stmt: &Statement) -> Vec<(String, TableAccess)> {
+    let mut out = Accesses::default();
+    match stmt {
+        Statement::Select { stmt, ctes } => {
+            let scope = out.enter_ctes(ctes);
+            out.select(stmt);
+            out.ctes = scope;
+        }
+        Statement::SetOp {
+            left, right, ctes, ..
+        } => {
+            let scope = out.enter_ctes(ctes);
+            out.select(left);
+            out.select(right);
+            out.ctes = scope;
+        }
+        Statement::Insert(stmt) => {
+            out.add(&stmt.table, TableAccess::Write);
+            stmt.values.iter().flatten().for_each(|e| out.expr(e));
+        }
+        Statement::Update(stmt) => {
+            out.add(&stmt.table, TableAccess::Write);
+            stmt.assignments.iter().for_each(|(_, e)| out.expr(e));
+            stmt.where_clause.iter().for_each(|e| out.expr(e));
+        }
+        Statement::Delete(stmt) => {
+            out.add(&stmt.table, TableAccess::Write);
+            stmt.where_clause.iter().for_each(|e| out.expr(e));
+        }
+        Statement::CreateTable(stmt) => out.add(&stmt.table, TableAccess::Write),
+        Statement::CreateIndex(stmt) => out.add(&stmt.table, TableAccess::Write),
+        Statement::DropTable(stmt) | Statement::DropMaterializedView(stmt) => {
+            out.add(&stmt.table, TableAccess::Write)
+        }
+        Statement::AlterTable(stmt) => out.add(&stmt.table, TableAccess::Write),
+        Statement::CreateMaterializedView(stmt) => {
+            out.add(&stmt.name, TableAccess::Write);
+            out.select(&stmt.query);
+        }
+        Statement::RefreshMaterializedView(name) => out.add(name, TableAccess::Write),
+        Statement::Copy(stmt) => match stmt.direction {
+            CopyDirection::From => out.add(&stmt.table, TableAccess::Write),
+            CopyDirection::To => out.add(&stmt.table, TableAccess::Read),
+        },
+        Statement::DescribeTable(name) => out.add(name, TableAccess::Read),
+        Statement::Explain(inner) => return table_accesses(inner),
+        Statement::DropIndex(_)
+        | Statement::Reindex(_)
+        | Statement::ShowTables
+        | Statement::ShowTransactions
+        | Statement::ShowTransactionStats
+        | Statement::BeginTransaction
+        | Statement::CommitTransaction
+        | Statement::RollbackTransaction
+        | Statement::SetVariable { .. }
+        | Statement::ShowVariable(_) => {}
+    }
+    out.list
+}
+
+#[derive(Default)]
+struct Accesses {
+    list: Vec<(String, TableAccess)>,
+    /// CTE names in scope; references to them are not tables
+    ctes: HashSet<String>,
+}
+
+impl Accesses {
+    fn add(&mut self, table: &str, access: TableAccess) {
+        if !self.list.iter().any(|(t, a)| t == table && *a == access) {
+            self.list.push((table.to_string(), access));
+        }
+    }
+
+    /// Visit CTE bodies and bring their names into scope; returns the outer scope
+    fn enter_ctes(&mut self, ctes: &[super::ast::CteDef]) -> HashSet<String> {
+        let outer = self.ctes.clone();
+        for cte in ctes {
+            self.select(&cte.query);
+            self.ctes.insert(cte.name.clone());
+        }
+        outer
+    }
+
+    fn select(&mut self, stmt: &SelectStmt) {
+        if let Some(from) = &stmt.from {
+            self.table_ref(from);
+        }
+        for col in &stmt.columns {
+            if let SelectColumn::Expr(e, _) = col {
+                self.expr(e);
+            }
+        }
+        stmt.where_clause.iter().for_each(|e| self.expr(e));
+        stmt.having.iter().for_each(|e| self.expr(e));
+        for o in stmt.order_by.iter().flatten() {
+            self.expr(&o.expr);
+        }
+    }
+
+    fn table_ref(&mut self, table: &TableRef) {
+        match table {
+            TableRef::Table { name, .. } => {
+                if !self.ctes.contains(name) {
+                    self.add(name, TableAccess::Read);
+                }
+            }
+            TableRef::Join {
+                left,
+                right,
+                on_condition,
+                ..
+            } => {
+                self.table_ref(left);
+                self.table_ref(right);
+                self.expr(on_condition);
+            }
+            TableRef::Subquery { query, .. } => self.select(query),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Subquery(query) | Expr::Exists(query) => self.select(query),
+            Expr::Correlated(sub) => self.select(&sub.query),
+            Expr::BinaryOp { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::InHashset { expr, .. } => self.expr(expr),
+            Expr::FunctionCall { args, .. } => args.iter().for_each(|e| self.expr(e)),
+            Expr::In { expr, list, .. } => {
+                self.expr(expr);
+                list.iter().for_each(|e| self.expr(e));
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.expr(expr);
+                self.expr(low);
+                self.expr(high);
+            }
+            Expr::Like { expr, pattern, .. } => {
+                self.expr(expr);
+                self.expr(pattern);
+            }
+            Expr::Case { whens, else_expr } => {
+                for (cond, result) in whens {
+                    self.expr(cond);
+                    self.expr(result);
+                }
+                else_expr.iter().for_each(|e| self.expr(e));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{Lexer, Parser};
+
+    fn accesses(sql: &str) -> Vec<(String, TableAccess)> {
+        let tokens = Lexer::new(sql).tokenize().unwrap();
+        table_accesses(&Parser::new(tokens).parse().unwrap())
+    }
+
+    fn read(t: &str) -> (String, TableAccess) {
+        (t.to_string(), TableAccess::Read)
+    }
+
+    fn write(t: &str) -> (String, TableAccess) {
+        (t.to_string(), TableAccess::Write)
+    }
+
+    #[test]
+    fn test_select_tables() {
+        assert_eq!(
+            accesses(
+                "SELECT a.id FROM a JOIN b ON a.id = b.id \
+                 WHERE a.x IN (SELECT x FROM c) AND a.y = (SELECT MAX(y) FROM a)"
+            ),
+            vec![read("a"), read("b"), read("c")]
+        );
+        assert_eq!(
+            accesses("WITH recent AS (SELECT * FROM events) SELECT * FROM recent"),
+            vec![read("events")]
+        );
+        assert_eq!(
+            accesses("SELECT id FROM a UNION SELECT id FROM (SELECT id FROM b) AS s"),
+            vec![read("a"), read("b")]
+        );
+    }
+
+    #[test]
+    fn test_write_tables() {
+        assert_eq!(
+            accesses("DELETE FROM a WHERE id IN (SELECT id FROM b)"),
+            vec![write("a"), read("b")]
+        );
+        assert_eq!(
+            accesses("UPDATE a SET v = 1 WHERE id = 2"),
+            vec![write("a")]
+        );
+        assert_eq!(accesses("CREATE INDEX idx_v ON a (v)"), vec![write("a")]);
+        assert!(accesses("SHOW TABLES").is_empty());
+    }
+}
@@ -28,12 +28,27 @@ pub enum Statement {
     CreateIndex(CreateIndexStmt),
     DropTable(DropTableStmt),
     DropIndex(DropIndexStmt),
+    CreateMaterializedView(CreateMaterializedViewStmt),
+    DropMaterializedView(DropTableStmt),
+    RefreshMaterializedView(String), // view name
+    Reindex(String),                 // index name
+    Copy(CopyStmt),
     AlterTable(AlterTableStmt),
     ShowTables,
+    ShowTransactions,      // one row per open transaction
+    ShowTransactionStats,  // MVCC summary (versions, oldest snapshot)
     DescribeTable(String), // table name
     BeginTransaction,
     CommitTransaction,
     RollbackTransaction,
+    /// SET [SESSION] name { = | TO } value; `value` is None for DEFAULT
+    SetVariable {
+        name: String,
+        value: Option<String>,
+    },
+    ShowVariable(String), // SHOW name
+    /// EXPLAIN SELECT ...: the access path and operator tree, one line per row
+    Explain(Box<Statement>),
 }
 
 /// Common Table Expression definition (`WITH name [(cols)] AS ( SELECT ... )`).
@@ -69,7 +84,29 @@ pub struct SelectStmt {
     pub order_by: Option<Vec<OrderByExpr>>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    pub after_cursor: Option<String>,   // AFTER CURSOR 'token'
     pub latest_by: Option<Vec<String>>, // LATEST BY column_list
+    pub sample_by: Option<SampleBy>,    // SAMPLE BY interval
+    pub fill: Option<FillMode>,         // FILL(NULL | PREVIOUS | LINEAR)
+}
+
+/// `SAMPLE BY <interval>`: shorthand for grouping on
+/// `TIME_BUCKET('<interval>', ts)` over the table's timestamp column
+#[derive(Debug, Clone)]
+pub struct SampleBy {
+    /// Bucket width as written, e.g. `1s`, `100ms`, `5m`
+    pub interval: String,
+}
+
+/// `FILL(...)` on a bucketed select: how buckets with no rows are produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillMode {
+    /// Empty buckets get NULL values
+    Null,
+    /// Empty buckets repeat the previous bucket's values
+    Previous,
+    /// Empty buckets interpolate between the surrounding buckets
+    Linear,
 }
 
 /// Table reference in FROM clause (supports JOINs and subqueries)
@@ -150,11 +187,23 @@ pub struct CreateTableStmt {
     pub timeseries_column: Option<String>,
     /// TTL retention policy
     pub ttl: Option<crate::types::TTLDuration>,
+    /// Ring-buffer limits: `MAX_ROWS = N`, `MAX_BYTES = N[KB|MB|GB]`
+    pub max_rows: Option<u64>,
+    pub max_bytes: Option<u64>,
     /// 🆕 `CREATE TABLE IF NOT EXISTS` — if true, silently no-op when the
     /// table already exists instead of erroring.
     pub if_not_exists: bool,
 }
 
+/// CREATE MATERIALIZED VIEW statement (a continuous aggregate)
+#[derive(Debug, Clone)]
+pub struct CreateMaterializedViewStmt {
+    pub name: String,
+    pub query: SelectStmt,
+    /// SQL text of the query, kept with the view definition
+    pub query_sql: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ColumnDef {
     pub name: String,
@@ -176,8 +225,21 @@ pub enum DataType {
     Text,
     Boolean,
     Timestamp,
-    Vector(Option<usize>), // Vector dimension
+    /// VECTOR[(dim[, f32|f16|int8])]: dimension and element storage
+    /// encoding (`None` = plain f32)
+    Vector(Option<usize>, Option<crate::types::VectorEncoding>),
     Geometry,
+    /// DECIMAL(precision, scale) / NUMERIC(precision, scale)
+    Decimal(u8, u8),
+    Uuid,
+    Date,
+    Time,
+    /// ARRAY<element type>; elements are scalar types only
+    Array(Box<DataType>),
+    /// TENSOR(d1, d2, ...) with a fixed shape
+    Tensor(Vec<usize>),
+    /// ENUM('label', ...)
+    Enum(Vec<String>),
 }
 
 /// CREATE INDEX statement
@@ -189,6 +251,8 @@ pub struct CreateIndexStmt {
     pub index_type: IndexType,
     /// Distance metric for vector indexes ("l2" or "cosine")
     pub metric: Option<String>,
+    /// Vector indexes: re-rank candidates with full-precision vectors
+    pub rerank: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -214,6 +278,23 @@ pub struct DropIndexStmt {
     pub index_name: String,
 }
 
+/// COPY table FROM | TO 'file.csv' [WITH (...)]
+#[derive(Debug, Clone)]
+pub struct CopyStmt {
+    pub table: String,
+    pub direction: CopyDirection,
+    pub path: String,
+    pub options: crate::database::CsvOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyDirection {
+    /// Load the file into the table
+    From,
+    /// Write the table to the file
+    To,
+}
+
 /// 🆕 ALTER TABLE statement
 #[derive(Debug, Clone)]
 pub struct AlterTableStmt {
@@ -232,6 +313,20 @@ pub enum AlterTableAction {
         data_type: super::ast::DataType,
         default_value: Option<crate::types::Value>,
     },
+    /// ALTER TABLE table_name ADD COLUMN name [type] AS (expr) [STORED | VIRTUAL]
+    AddGeneratedColumn {
+        name: String,
+        /// Inferred from the expression when omitted
+        data_type: Option<super::ast::DataType>,
+        expr: Expr,
+        /// SQL text of `expr`, kept in the catalog
+        expr_sql: String,
+        stored: bool,
+    },
+    /// ALTER TABLE table_name DROP [COLUMN] name
+    DropColumn(String),
+    /// ALTER TABLE table_name RENAME [COLUMN] old TO new
+    RenameColumn { from: String, to: String },
 }
 
 /// Expression
@@ -332,6 +427,16 @@ pub enum Expr {
     /// - SELECT (SELECT ...) AS col (scalar subquery in projection)
     Subquery(Box<SelectStmt>),
 
+    /// EXISTS (SELECT ...): true when the subquery returns any row
+    Exists(Box<SelectStmt>),
+
+    /// Subquery naming columns of the outer query's row.
+    ///
+    /// Produced by `materialize_subqueries` in place of a `Subquery`,
+    /// `Exists` or `IN (SELECT ...)` that can't be run once up front; the
+    /// executor re-runs it per outer row (see [`super::correlated`]).
+    Correlated(std::sync::Arc<super::correlated::CorrelatedSubquery>),
+
     /// MATCH...AGAINST full-text search
     ///
     /// Syntax: MATCH(column) AGAINST(query_string)
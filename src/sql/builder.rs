@@ -0,0 +1,339 @@
+//! Query builder: SELECTs composed in Rust instead of SQL text
+//!
+//! A [`QueryBuilder`] (from [`Database::table`]) compiles to one SELECT and
+//! runs it through [`Database::execute_prepared`], so it is planned exactly
+//! like the equivalent SQL: the same indexes, KNN and top-K paths apply.
+//! Compared values are bound as `?` parameters, never spliced into the text;
+//! table and column names are written as given and must be identifiers.
+//!
+//! ```ignore
+//! use motedb::sql::builder::col;
+//!
+//! let nearest = db
+//!     .table("readings")
+//!     .filter(col("temp_c").gt(30.0).and(col("sensor").eq("s1")))
+//!     .knn("embedding", &[0.1, 0.2, 0.3], 10)
+//!     .limit(20)
+//!     .all()?;
+//! ```
+
+use crate::record::FieldValue;
+use crate::types::{Row, Value};
+use crate::{Database, QueryResult, Result, StorageError, StreamingQueryResult};
+
+/// A value a [`Col`] can be compared with: any [`FieldValue`], a `&str`, or
+/// a [`Value`]
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+impl<T: FieldValue> IntoValue for T {
+    fn into_value(self) -> Value {
+        self.to_value()
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::text_from(self)
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+/// Column `name`, to build a [`Condition`] on
+pub fn col(name: &str) -> Col {
+    Col {
+        name: name.to_string(),
+    }
+}
+
+/// A column in a [`Condition`], from [`col`]
+#[derive(Debug, Clone)]
+pub struct Col {
+    name: String,
+}
+
+impl Col {
+    fn compare(&self, op: &str, value: impl IntoValue) -> Condition {
+        Condition::new(format!("{} {} ?", self.name, op), vec![value.into_value()])
+    }
+
+    /// `column = value`
+    pub fn eq(&self, value: impl IntoValue) -> Condition {
+        self.compare("=", value)
+    }
+
+    /// `column != value`
+    pub fn ne(&self, value: impl IntoValue) -> Condition {
+        self.compare("!=", value)
+    }
+
+    /// `column < value`
+    pub fn lt(&self, value: impl IntoValue) -> Condition {
+        self.compare("<", value)
+    }
+
+    /// `column <= value`
+    pub fn le(&self, value: impl IntoValue) -> Condition {
+        self.compare("<=", value)
+    }
+
+    /// `column > value`
+    pub fn gt(&self, value: impl IntoValue) -> Condition {
+        self.compare(">", value)
+    }
+
+    /// `column >= value`
+    pub fn ge(&self, value: impl IntoValue) -> Condition {
+        self.compare(">=", value)
+    }
+
+    /// `column BETWEEN low AND high`
+    pub fn between(&self, low: impl IntoValue, high: impl IntoValue) -> Condition {
+        Condition::new(
+            format!("{} BETWEEN ? AND ?", self.name),
+            vec![low.into_value(), high.into_value()],
+        )
+    }
+
+    /// `column IN (values...)`; an empty list matches nothing
+    pub fn is_in<V: IntoValue>(&self, values: impl IntoIterator<Item = V>) -> Condition {
+        let params: Vec<Value> = values.into_iter().map(IntoValue::into_value).collect();
+        if params.is_empty() {
+            return Condition::new("1 = 0".to_string(), Vec::new());
+        }
+        let marks = vec!["?"; params.len()].join(", ");
+        Condition::new(format!("{} IN ({})", self.name, marks), params)
+    }
+
+    /// `column LIKE pattern`
+    pub fn like(&self, pattern: &str) -> Condition {
+        self.compare("LIKE", pattern)
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(&self) -> Condition {
+        Condition::new(format!("{} IS NULL", self.name), Vec::new())
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(&self) -> Condition {
+        Condition::new(format!("{} IS NOT NULL", self.name), Vec::new())
+    }
+}
+
+/// A WHERE predicate; values are bound as `?` parameters, never spliced
+/// into the SQL text
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub(crate) sql: String,
+    pub(crate) params: Vec<Value>,
+}
+
+impl Condition {
+    pub(crate) fn new(sql: String, params: Vec<Value>) -> Self {
+        Self { sql, params }
+    }
+
+    /// Both conditions
+    pub fn and(self, other: Condition) -> Condition {
+        self.combine("AND", other)
+    }
+
+    /// Either condition
+    pub fn or(self, other: Condition) -> Condition {
+        self.combine("OR", other)
+    }
+
+    fn combine(mut self, op: &str, other: Condition) -> Condition {
+        self.sql = format!("({}) {} ({})", self.sql, op, other.sql);
+        self.params.extend(other.params);
+        self
+    }
+}
+
+/// Nearest-neighbour ordering set by [`QueryBuilder::knn`]
+#[derive(Debug, Clone)]
+struct Knn {
+    column: String,
+    vector: Vec<f32>,
+    k: usize,
+}
+
+/// A SELECT over one table, from [`Database::table`]
+#[derive(Clone)]
+pub struct QueryBuilder<'a> {
+    db: &'a Database,
+    table: String,
+    columns: Vec<String>,
+    filter: Option<Condition>,
+    knn: Option<Knn>,
+    order_by: Vec<(String, bool)>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl<'a> QueryBuilder<'a> {
+    pub(crate) fn new(db: &'a Database, table: &str) -> Self {
+        Self {
+            db,
+            table: table.to_string(),
+            columns: Vec::new(),
+            filter: None,
+            knn: None,
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Return only `columns`, in this order (all columns by default)
+    pub fn select<S: AsRef<str>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns = columns
+            .into_iter()
+            .map(|c| c.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Keep rows matching `condition` (ANDed with earlier filters)
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// The `k` rows whose vector `column` is nearest to `vector` (L2),
+    /// nearest first: `ORDER BY column <-> [...] LIMIT k`. A vector index on
+    /// the column serves it as it would the SQL.
+    pub fn knn(mut self, column: &str, vector: &[f32], k: usize) -> Self {
+        self.knn = Some(Knn {
+            column: column.to_string(),
+            vector: vector.to_vec(),
+            k,
+        });
+        self
+    }
+
+    /// Sort ascending by `column` (after the KNN distance and earlier keys)
+    pub fn order_by(mut self, column: &str) -> Self {
+        self.order_by.push((column.to_string(), false));
+        self
+    }
+
+    /// Sort descending by `column` (after the KNN distance and earlier keys)
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order_by.push((column.to_string(), true));
+        self
+    }
+
+    /// Return at most `n` rows (never more than a KNN's `k`)
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Skip the first `n` rows
+    pub fn offset(mut self, n: usize) -> Self {
+        self.offset = Some(n);
+        self
+    }
+
+    /// The SELECT this builder runs, with its bound parameters
+    pub fn to_sql(&self) -> Result<(String, Vec<Value>)> {
+        let projection = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            self.columns.join(", ")
+        };
+        self.build(&projection, true)
+    }
+
+    fn build(&self, projection: &str, ordered: bool) -> Result<(String, Vec<Value>)> {
+        let mut sql = format!("SELECT {} FROM {}", projection, self.table);
+        let mut params = Vec::new();
+        if let Some(filter) = &self.filter {
+            sql.push_str(" WHERE ");
+            sql.push_str(&filter.sql);
+            params.extend(filter.params.iter().cloned());
+        }
+        if !ordered {
+            return Ok((sql, params));
+        }
+
+        let mut keys: Vec<String> = Vec::new();
+        if let Some(knn) = &self.knn {
+            // The distance operand is a literal: ORDER BY is not bound
+            if let Some(bad) = knn.vector.iter().find(|x| !x.is_finite()) {
+                return Err(StorageError::InvalidArgument(format!(
+                    "knn vector for '{}' has a non-finite component ({})",
+                    knn.column, bad
+                )));
+            }
+            let vector: Vec<String> = knn.vector.iter().map(|x| format!("{:?}", x)).collect();
+            keys.push(format!("{} <-> [{}]", knn.column, vector.join(", ")));
+        }
+        keys.extend(
+            self.order_by
+                .iter()
+                .map(|(name, desc)| format!("{}{}", name, if *desc { " DESC" } else { "" })),
+        );
+        if !keys.is_empty() {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&keys.join(", "));
+        }
+        let limit = match (self.limit, self.knn.as_ref().map(|knn| knn.k)) {
+            (Some(n), Some(k)) => Some(n.min(k)),
+            (n, k) => n.or(k),
+        };
+        if let Some(n) = limit {
+            sql.push_str(&format!(" LIMIT {}", n));
+        }
+        if let Some(n) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", n));
+        }
+        Ok((sql, params))
+    }
+
+    /// Run the query, streaming its rows
+    pub fn execute(&self) -> Result<StreamingQueryResult> {
+        let (sql, params) = self.to_sql()?;
+        self.db.execute_prepared(&sql, params)
+    }
+
+    /// All matching rows
+    pub fn all(&self) -> Result<Vec<Row>> {
+        match self.execute()?.materialize()? {
+            QueryResult::Select { rows, .. } => Ok(rows),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// First matching row
+    pub fn first(&self) -> Result<Option<Row>> {
+        Ok(self.clone().limit(1).all()?.into_iter().next())
+    }
+
+    /// Number of matching rows (ignores KNN, ordering, limit and offset)
+    pub fn count(&self) -> Result<usize> {
+        let (sql, params) = self.build("COUNT(*)", false)?;
+        let rows = match self.db.execute_prepared(&sql, params)?.materialize()? {
+            QueryResult::Select { rows, .. } => rows,
+            _ => Vec::new(),
+        };
+        match rows.first().and_then(|row| row.first()) {
+            Some(Value::Integer(n)) => Ok(*n as usize),
+            other => Err(StorageError::Query(format!(
+                "unexpected COUNT(*) result: {:?}",
+                other
+            ))),
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Correlated subqueries
+//!
+//! A subquery that names a column of the enclosing query's row, as in
+//! `WHERE EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id)`, can't be
+//! run once up front like an uncorrelated one. The executor wraps it in a
+//! [`CorrelatedSubquery`] instead and re-runs it for each outer row, with the
+//! outer columns bound to that row's values. Results are memoized by those
+//! values for the rest of the statement, so outer rows sharing a key run the
+//! subquery once.
+//!
+//! A column is an outer reference when no table of the subquery (or of a
+//! subquery nested in it) provides it: a qualifier naming none of their
+//! tables or aliases, or a bare name none of their schemas has. When a FROM
+//! item's columns are unknown (a derived table), bare names are taken to be
+//! its own.
+
+use super::ast::{Expr, SelectColumn, SelectStmt, TableRef};
+use crate::types::Value;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Outer-row keys memoized per subquery; rows past this re-run it
+const MAX_MEMO_ENTRIES: usize = 1024;
+
+/// Column names of a table the subquery reads, None when unknown
+pub(crate) type ColumnLookup<'a> = dyn Fn(&str) -> Option<Vec<String>> + 'a;
+
+/// What a correlated subquery computes for each outer row
+#[derive(Debug, Clone)]
+pub enum CorrelatedKind {
+    /// `(SELECT ...)` used as a value: its single column of its single row,
+    /// NULL without rows
+    Scalar,
+    /// `EXISTS (SELECT ...)`
+    Exists,
+    /// `expr [NOT] IN (SELECT ...)`, `expr` evaluated on the outer row
+    In { expr: Box<Expr>, negated: bool },
+}
+
+/// Result of one run of a correlated subquery
+#[derive(Debug)]
+pub(crate) enum Memo {
+    Value(Value),
+    Set { set: HashSet<Value>, has_null: bool },
+}
+
+/// A subquery re-run per outer row; see the module docs
+#[derive(Debug)]
+pub struct CorrelatedSubquery {
+    pub kind: CorrelatedKind,
+    pub query: SelectStmt,
+    /// Outer columns the query names, in first-use order
+    pub outer_refs: Vec<String>,
+    memo: Mutex<HashMap<Vec<Value>, Arc<Memo>>>,
+}
+
+impl CorrelatedSubquery {
+    pub(crate) fn new(kind: CorrelatedKind, query: SelectStmt, outer_refs: Vec<String>) -> Self {
+        Self {
+            kind,
+            query,
+            outer_refs,
+            memo: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn memoized(&self, key: &[Value]) -> Option<Arc<Memo>> {
+        self.memo.lock().get(key).cloned()
+    }
+
+    pub(crate) fn memoize(&self, key: Vec<Value>, memo: Memo) -> Arc<Memo> {
+        let memo = Arc::new(memo);
+        let mut entries = self.memo.lock();
+        if entries.len() < MAX_MEMO_ENTRIES {
+            entries.insert(key, memo.clone());
+        }
+        memo
+    }
+}
+
+/// Columns `query` takes from enclosing queries, in first-use order
+pub(crate) fn outer_refs(query: &SelectStmt, lookup: &ColumnLookup) -> Vec<String> {
+    let mut refs: Vec<String> = Vec::new();
+    Binder::new(lookup, &mut |name| {
+        if !refs.iter().any(|r| r == name) {
+            refs.push(name.to_string());
+        }
+        None
+    })
+    .select(query);
+    refs
+}
+
+/// `query` with each outer reference in `refs` replaced by its value in
+/// `values`; references without a value are left as they are
+pub(crate) fn bind_outer_refs(
+    query: &SelectStmt,
+    refs: &[String],
+    values: &[Option<Value>],
+    lookup: &ColumnLookup,
+) -> SelectStmt {
+    Binder::new(lookup, &mut |name| {
+        let i = refs.iter().position(|r| r == name)?;
+        values[i].clone().map(Expr::Literal)
+    })
+    .select(query)
+}
+
+/// Names a query (level) provides, lowercased
+#[derive(Default)]
+struct Scope {
+    /// Table names, or aliases where given
+    qualifiers: HashSet<String>,
+    /// Columns of its tables, None when a FROM item's are unknown
+    columns: Option<HashSet<String>>,
+    /// Output column aliases (ORDER BY / HAVING may name them)
+    aliases: HashSet<String>,
+}
+
+/// Rewrites a subquery, handing every outer column reference to `resolve`
+/// and substituting what it returns
+struct Binder<'a> {
+    lookup: &'a ColumnLookup<'a>,
+    scopes: Vec<Scope>,
+    resolve: &'a mut dyn FnMut(&str) -> Option<Expr>,
+}
+
+impl<'a> Binder<'a> {
+    fn new(lookup: &'a ColumnLookup<'a>, resolve: &'a mut dyn FnMut(&str) -> Option<Expr>) -> Self {
+        Self {
+            lookup,
+            scopes: Vec::new(),
+            resolve,
+        }
+    }
+
+    fn scope_of(&self, query: &SelectStmt) -> Scope {
+        let mut scope = Scope {
+            columns: Some(HashSet::new()),
+            ..Default::default()
+        };
+        if let Some(from) = &query.from {
+            self.add_table_ref(&mut scope, from);
+        }
+        for col in &query.columns {
+            if let SelectColumn::ColumnWithAlias(_, alias) | SelectColumn::Expr(_, Some(alias)) =
+                col
+            {
+                scope.aliases.insert(alias.to_lowercase());
+            }
+        }
+        scope
+    }
+
+    fn add_table_ref(&self, scope: &mut Scope, table: &TableRef) {
+        match table {
+            TableRef::Table { name, alias } => {
+                // An aliased table is known only by its alias, so the outer
+                // query's `t` stays reachable past an inner `FROM t t2`
+                let qualifier = alias.as_deref().unwrap_or(name);
+                scope.qualifiers.insert(qualifier.to_lowercase());
+                match ((self.lookup)(name), scope.columns.as_mut()) {
+                    (Some(cols), Some(known)) => {
+                        known.extend(cols.iter().map(|c| c.to_lowercase()))
+                    }
+                    (None, _) => scope.columns = None,
+                    _ => {}
+                }
+            }
+            TableRef::Join { left, right, .. } => {
+                self.add_table_ref(scope, left);
+                self.add_table_ref(scope, right);
+            }
+            TableRef::Subquery { alias, .. } => {
+                scope.qualifiers.insert(alias.to_lowercase());
+                scope.columns = None;
+            }
+        }
+    }
+
+    /// Whether one of the subquery's own levels provides `name`
+    fn is_local(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        match name.rsplit_once('.') {
+            Some((qualifier, _)) => self.scopes.iter().any(|s| s.qualifiers.contains(qualifier)),
+            None => {
+                name == "*"
+                    || name.starts_with("__")
+                    || self.scopes.iter().any(|s| {
+                        s.aliases.contains(&name)
+                            || s.columns.as_ref().is_none_or(|c| c.contains(&name))
+                    })
+            }
+        }
+    }
+
+    fn outer(&mut self, name: &str) -> Option<Expr> {
+        if self.is_local(name) {
+            None
+        } else {
+            (self.resolve)(name)
+        }
+    }
+
+    fn select(&mut self, query: &SelectStmt) -> SelectStmt {
+        let scope = self.scope_of(query);
+        self.scopes.push(scope);
+        let mut out = query.clone();
+        for col in &mut out.columns {
+            let replaced = match col {
+                SelectColumn::Column(name) => self
+                    .outer(name)
+                    .map(|e| SelectColumn::Expr(e, Some(bare(name).to_string()))),
+                SelectColumn::ColumnWithAlias(name, alias) => self
+                    .outer(name)
+                    .map(|e| SelectColumn::Expr(e, Some(alias.clone()))),
+                SelectColumn::Expr(e, alias) => {
+                    Some(SelectColumn::Expr(self.expr(e), alias.clone()))
+                }
+                SelectColumn::Star => None,
+            };
+            if let Some(replaced) = replaced {
+                *col = replaced;
+            }
+        }
+        if let Some(from) = &mut out.from {
+            self.table_ref(from);
+        }
+        out.where_clause = query.where_clause.as_ref().map(|e| self.expr(e));
+        out.having = query.having.as_ref().map(|e| self.expr(e));
+        for order in out.order_by.iter_mut().flatten() {
+            order.expr = self.expr(&order.expr);
+        }
+        self.scopes.pop();
+        out
+    }
+
+    /// Join conditions may name outer columns; derived tables may not
+    fn table_ref(&mut self, table: &mut TableRef) {
+        if let TableRef::Join {
+            left,
+            right,
+            on_condition,
+            ..
+        } = table
+        {
+            self.table_ref(left);
+            self.table_ref(right);
+            *on_condition = self.expr(on_condition);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Expr {
+        let mut sub = |e: &Expr| Box::new(self.expr(e));
+        match expr {
+            Expr::Column(name) => self.outer(name).unwrap_or_else(|| expr.clone()),
+            Expr::Subquery(query) => Expr::Subquery(Box::new(self.select(query))),
+            Expr::Exists(query) => Expr::Exists(Box::new(self.select(query))),
+            Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+                left: sub(left),
+                op: op.clone(),
+                right: sub(right),
+            },
+            Expr::UnaryOp { op, expr: inner } => Expr::UnaryOp {
+                op: op.clone(),
+                expr: sub(inner),
+            },
+            Expr::IsNull {
+                expr: inner,
+                negated,
+            } => Expr::IsNull {
+                expr: sub(inner),
+                negated: *negated,
+            },
+            Expr::Between {
+                expr: inner,
+                low,
+                high,
+                negated,
+            } => Expr::Between {
+                expr: sub(inner),
+                low: sub(low),
+                high: sub(high),
+                negated: *negated,
+            },
+            Expr::Like {
+                expr: inner,
+                pattern,
+                negated,
+            } => Expr::Like {
+                expr: sub(inner),
+                pattern: sub(pattern),
+                negated: *negated,
+            },
+            Expr::InHashset {
+                expr: inner,
+                set,
+                negated,
+                has_null,
+            } => Expr::InHashset {
+                expr: sub(inner),
+                set: set.clone(),
+                negated: *negated,
+                has_null: *has_null,
+            },
+            Expr::In {
+                expr: inner,
+                list,
+                negated,
+            } => Expr::In {
+                expr: Box::new(self.expr(inner)),
+                list: list.iter().map(|e| self.expr(e)).collect(),
+                negated: *negated,
+            },
+            Expr::FunctionCall {
+                name,
+                args,
+                distinct,
+            } => Expr::FunctionCall {
+                name: name.clone(),
+                args: args.iter().map(|e| self.expr(e)).collect(),
+                distinct: *distinct,
+            },
+            Expr::Case { whens, else_expr } => Expr::Case {
+                whens: whens
+                    .iter()
+                    .map(|(cond, result)| (self.expr(cond), self.expr(result)))
+                    .collect(),
+                else_expr: else_expr.as_ref().map(|e| Box::new(self.expr(e))),
+            },
+            _ => expr.clone(),
+        }
+    }
+}
+
+fn bare(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::ast::Statement;
+    use crate::sql::{Lexer, Parser};
+
+    fn subquery(sql: &str) -> SelectStmt {
+        let tokens = Lexer::new(sql).tokenize().unwrap();
+        let Statement::Select { stmt, .. } = Parser::new(tokens).parse().unwrap() else {
+            panic!("expected SELECT");
+        };
+        match stmt.where_clause {
+            Some(Expr::Exists(query)) | Some(Expr::Subquery(query)) => *query,
+            other => panic!("expected a subquery, got {:?}", other),
+        }
+    }
+
+    fn lookup(table: &str) -> Option<Vec<String>> {
+        let cols: &[&str] = match table {
+            "users" => &["id", "name"],
+            "orders" => &["id", "user_id", "total"],
+            "items" => &["order_id", "sku"],
+            _ => return None,
+        };
+        Some(cols.iter().map(|c| c.to_string()).collect())
+    }
+
+    #[test]
+    fn test_outer_refs() {
+        let q = subquery(
+            "SELECT * FROM users u WHERE EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = u.id AND total > 10)",
+        );
+        assert_eq!(outer_refs(&q, &lookup), vec!["u.id"]);
+
+        // Bare names no table of the subquery has come from outside
+        let q = subquery("SELECT * FROM users WHERE EXISTS (SELECT 1 FROM items WHERE sku = name)");
+        assert_eq!(outer_refs(&q, &lookup), vec!["name"]);
+
+        // Nested levels see each other's tables
+        let q = subquery(
+            "SELECT * FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE EXISTS \
+             (SELECT 1 FROM items i WHERE i.order_id = o.id AND o.user_id = u.id))",
+        );
+        assert_eq!(outer_refs(&q, &lookup), vec!["u.id"]);
+
+        let q = subquery("SELECT * FROM users WHERE EXISTS (SELECT COUNT(*) FROM orders)");
+        assert!(outer_refs(&q, &lookup).is_empty());
+    }
+
+    #[test]
+    fn test_bind_outer_refs() {
+        let q = subquery(
+            "SELECT * FROM users u WHERE EXISTS \
+             (SELECT u.name FROM orders o WHERE o.user_id = u.id)",
+        );
+        let refs = outer_refs(&q, &lookup);
+        assert_eq!(refs, vec!["u.name", "u.id"]);
+        let bound = bind_outer_refs(
+            &q,
+            &refs,
+            &[
+                Some(Value::text("ann".to_string())),
+                Some(Value::Integer(7)),
+            ],
+            &lookup,
+        );
+        assert!(outer_refs(&bound, &lookup).is_empty());
+        assert!(matches!(
+            &bound.columns[0],
+            SelectColumn::Expr(Expr::Literal(Value::Text(_)), Some(alias)) if alias == "name"
+        ));
+        let Some(Expr::BinaryOp { right, .. }) = &bound.where_clause else {
+            panic!("expected a comparison");
+        };
+        assert!(matches!(**right, Expr::Literal(Value::Integer(7))));
+    }
+}
@@ -2,7 +2,8 @@
 use super::ast::{BinaryOperator, Expr, UnaryOperator};
 use crate::database::MoteDB;
 use crate::error::{MoteDBError, Result};
-use crate::types::{SqlRow, Value};
+use crate::types::{decimal_arith, DecimalOp, SqlRow, UtcOffset, Value};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
@@ -159,6 +160,23 @@ impl CompiledPattern {
     }
 }
 
+// Session time zone of the statement running on this thread. Unlike bind
+// parameters it must also reach the short-lived evaluators the executor
+// builds for positional rows, so it is not an `ExprEvaluator` field; the
+// executor sets it from its session settings before each statement.
+thread_local! {
+    static SESSION_TIME_ZONE: Cell<UtcOffset> = const { Cell::new(UtcOffset::UTC) };
+}
+
+/// Time zone used to read and render timestamps in the current statement
+pub(crate) fn session_time_zone() -> UtcOffset {
+    SESSION_TIME_ZONE.with(Cell::get)
+}
+
+pub(crate) fn set_session_time_zone(zone: UtcOffset) {
+    SESSION_TIME_ZONE.with(|z| z.set(zone));
+}
+
 pub struct ExprEvaluator {
     /// ⚡ Pattern cache: pattern string -> compiled pattern
     /// RwLock for concurrent read access (common case)
@@ -318,43 +336,50 @@ impl ExprEvaluator {
             } => {
                 let val = self.eval(expr, row)?;
 
-                // SQL NULL semantics: NULL IN (...) returns false (unknown)
+                // SQL three-valued logic: a NULL operand, or no match against
+                // a list holding a NULL, is UNKNOWN for IN and NOT IN alike
                 if matches!(val, Value::Null) {
-                    return Ok(Value::Bool(false));
+                    return Ok(truth_value(None));
                 }
 
                 // Fast path: when all items are literals, use O(1) hash comparison
                 let all_literals = list.iter().all(|e| matches!(e, Expr::Literal(_)));
-                if all_literals {
-                    let found = list.iter().any(|item| {
-                        if let Expr::Literal(v) = item {
-                            val == *v
-                        } else {
-                            false
+                let (found, has_null) = if all_literals {
+                    let found = list
+                        .iter()
+                        .any(|item| matches!(item, Expr::Literal(v) if val == *v));
+                    let has_null = list
+                        .iter()
+                        .any(|item| matches!(item, Expr::Literal(Value::Null)));
+                    (found, has_null)
+                } else {
+                    let mut found = false;
+                    let mut has_null = false;
+                    for item in list {
+                        let item_val = self.eval(item, row)?;
+                        if matches!(item_val, Value::Null) {
+                            has_null = true;
+                            continue;
+                        }
+                        if val == item_val {
+                            found = true;
+                            break;
                         }
-                    });
-                    return Ok(Value::Bool(if *negated { !found } else { found }));
-                }
-
-                let mut found = false;
-                let mut has_null = false;
-
-                for item in list {
-                    let item_val = self.eval(item, row)?;
-                    if matches!(item_val, Value::Null) {
-                        has_null = true;
-                        continue;
-                    }
-                    if val == item_val {
-                        found = true;
-                        break;
                     }
-                }
-                // SQL: NOT IN (list with NULL) → unknown → false if no match
-                if *negated && !found && has_null {
-                    return Ok(Value::Bool(false));
-                }
-                Ok(Value::Bool(if *negated { !found } else { found }))
+                    (found, has_null)
+                };
+                let truth = if found {
+                    Some(true)
+                } else if has_null {
+                    None
+                } else {
+                    Some(false)
+                };
+                Ok(truth_value(if *negated {
+                    truth.map(|t| !t)
+                } else {
+                    truth
+                }))
             }
 
             Expr::Between {
@@ -367,17 +392,16 @@ impl ExprEvaluator {
                 let low_val = self.eval(low, row)?;
                 let high_val = self.eval(high, row)?;
 
-                // SQL NULL semantics: if any operand is NULL, exclude the row
-                // This correctly handles NOT BETWEEN too (returns false, not true)
-                if matches!(val, Value::Null)
-                    || matches!(low_val, Value::Null)
-                    || matches!(high_val, Value::Null)
-                {
-                    return Ok(Value::Bool(false));
-                }
-
-                let in_range = val >= low_val && val <= high_val;
-                Ok(Value::Bool(if *negated { !in_range } else { in_range }))
+                // `low <= val AND val <= high` in three-valued logic: one NULL
+                // bound still decides the result when the other side is FALSE
+                let above_low = compare_truth(&val, &low_val, |v, low| v >= low);
+                let below_high = compare_truth(&val, &high_val, |v, high| v <= high);
+                let in_range = truth_and(above_low, below_high);
+                Ok(truth_value(if *negated {
+                    in_range.map(|t| !t)
+                } else {
+                    in_range
+                }))
             }
 
             Expr::Like {
@@ -388,9 +412,9 @@ impl ExprEvaluator {
                 let val = self.eval(expr, row)?;
                 let pattern_val = self.eval(pattern, row)?;
 
-                // SQL NULL semantics: LIKE NULL = UNKNOWN → false for filtering
+                // SQL NULL semantics: LIKE and NOT LIKE with a NULL are UNKNOWN
                 if matches!(val, Value::Null) || matches!(pattern_val, Value::Null) {
-                    return Ok(Value::Bool(false));
+                    return Ok(Value::Null);
                 }
 
                 let matches = if let (Value::Text(s), Value::Text(p)) = (val, pattern_val) {
@@ -430,7 +454,7 @@ impl ExprEvaluator {
                 }
             }
 
-            Expr::Subquery(_) => {
+            Expr::Subquery(_) | Expr::Exists(_) | Expr::Correlated(_) => {
                 // Subqueries are handled at executor level, not here
                 Err(MoteDBError::Query(
                     "Subquery evaluation must be done by executor".into(),
@@ -479,22 +503,7 @@ impl ExprEvaluator {
             } => {
                 // 🚀 Pre-built HashSet from subquery materialization: O(1) per row.
                 let val = self.eval(expr, row)?;
-                if matches!(val, Value::Null) {
-                    // NULL IN anything → UNKNOWN (treated as false in WHERE).
-                    return Ok(Value::Bool(false));
-                }
-                let found = set.contains(&val);
-                if *negated {
-                    // NOT IN: per SQL standard, if the subquery produced any NULL,
-                    // NOT IN is UNKNOWN (false) for every row.
-                    if *has_null {
-                        Ok(Value::Bool(false))
-                    } else {
-                        Ok(Value::Bool(!found))
-                    }
-                } else {
-                    Ok(Value::Bool(found))
-                }
+                Ok(truth_value(in_set_truth(&val, set, *negated, *has_null)))
             }
 
             Expr::WindowFunction { .. } => {
@@ -507,19 +516,18 @@ impl ExprEvaluator {
     }
 
     fn eval_binary_op(&self, op: &BinaryOperator, left: Value, right: Value) -> Result<Value> {
-        // SQL NULL semantics: NULL comparison → false (for WHERE filtering),
-        // NULL arithmetic → NULL (for SELECT projection correctness).
+        // SQL three-valued logic: comparing with NULL is UNKNOWN (NULL), which
+        // WHERE filters out and NOT keeps UNKNOWN; NULL arithmetic → NULL.
         let either_null = matches!(&left, Value::Null) || matches!(&right, Value::Null);
         if either_null {
             match op {
-                // Comparison: NULL → UNKNOWN → false for filtering
                 BinaryOperator::Eq
                 | BinaryOperator::Ne
                 | BinaryOperator::Lt
                 | BinaryOperator::Gt
                 | BinaryOperator::Le
                 | BinaryOperator::Ge => {
-                    return Ok(Value::Bool(false));
+                    return Ok(Value::Null);
                 }
                 // AND: FALSE AND anything = FALSE; TRUE AND NULL = NULL
                 BinaryOperator::And => {
@@ -624,6 +632,8 @@ impl ExprEvaluator {
 
     fn eval_unary_op(&self, op: &UnaryOperator, val: Value) -> Result<Value> {
         match op {
+            // NOT UNKNOWN is UNKNOWN
+            UnaryOperator::Not if matches!(val, Value::Null) => Ok(Value::Null),
             UnaryOperator::Not => {
                 let b = self.to_bool(&val)?;
                 Ok(Value::Bool(!b))
@@ -637,6 +647,10 @@ impl ExprEvaluator {
                         }
                     }
                     Value::Float(f) => Ok(Value::Float(-f)),
+                    Value::Decimal(d) => d.checked_neg().map(Value::decimal).ok_or_else(|| {
+                        MoteDBError::TypeError("numeric overflow in DECIMAL negation".to_string())
+                    }),
+                    Value::Null => Ok(Value::Null),
                     _ => Err(MoteDBError::TypeError(
                         "Cannot negate non-numeric value".to_string(),
                     )),
@@ -671,7 +685,9 @@ impl ExprEvaluator {
             "ceil", "ceiling", "power", "pow", "sqrt", "exp", "ln", "log",
             "log10", "mod", "sign", "cast", "year", "month", "day", "hour",
             "minute", "second", "day_of_week", "to_micros", "date_add",
-            "date_diff", "time_bucket",
+            "date_diff", "time_bucket", "uuid_timestamp", "date", "time",
+            "to_timestamp", "element_at", "array_length", "cardinality",
+            "array_contains", "tensor_shape", "tensor_slice",
         ];
         if NULL_PROPAGATING.contains(&name_lower.as_str()) {
             // Pre-evaluate args; if any is NULL, short-circuit to NULL.
@@ -1464,6 +1480,148 @@ impl ExprEvaluator {
                 Ok(Value::Timestamp(ts))
             }
 
+            "current_date" | "today" => {
+                if !args.is_empty() {
+                    return Err(MoteDBError::InvalidArgument(
+                        "CURRENT_DATE() takes no arguments".to_string(),
+                    ));
+                }
+                Ok(Value::Date(crate::types::Date::today()))
+            }
+
+            "current_time" => {
+                if !args.is_empty() {
+                    return Err(MoteDBError::InvalidArgument(
+                        "CURRENT_TIME() takes no arguments".to_string(),
+                    ));
+                }
+                Ok(Value::Time(crate::types::Time::now()))
+            }
+
+            "date" => {
+                // DATE(timestamp | text) - UTC calendar date (integers are epoch micros)
+                if args.len() != 1 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "DATE() takes 1 argument".to_string(),
+                    ));
+                }
+                let val = match self.eval(&args[0], row)? {
+                    // Integer micros, as TIMESTAMP columns may read back
+                    Value::Integer(micros) => {
+                        Value::Timestamp(crate::types::Timestamp::from_micros(micros))
+                    }
+                    v => v,
+                };
+                crate::types::ColumnType::Date
+                    .coerce(val)
+                    .map_err(MoteDBError::TypeError)
+                    .and_then(|v| match v {
+                        Value::Date(_) => Ok(v),
+                        other => Err(MoteDBError::TypeError(format!(
+                            "DATE() requires timestamp, date or text argument, got {:?}",
+                            other
+                        ))),
+                    })
+            }
+
+            "time" => {
+                // TIME(timestamp | text) - UTC time of day (integers are epoch micros)
+                if args.len() != 1 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "TIME() takes 1 argument".to_string(),
+                    ));
+                }
+                let val = match self.eval(&args[0], row)? {
+                    Value::Integer(micros) => {
+                        Value::Timestamp(crate::types::Timestamp::from_micros(micros))
+                    }
+                    v => v,
+                };
+                crate::types::ColumnType::Time
+                    .coerce(val)
+                    .map_err(MoteDBError::TypeError)
+                    .and_then(|v| match v {
+                        Value::Time(_) => Ok(v),
+                        other => Err(MoteDBError::TypeError(format!(
+                            "TIME() requires timestamp, time or text argument, got {:?}",
+                            other
+                        ))),
+                    })
+            }
+
+            "to_timestamp" => {
+                // TO_TIMESTAMP(date [, time]) - UTC timestamp at that date and time
+                if args.is_empty() || args.len() > 2 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "TO_TIMESTAMP() takes 1 or 2 arguments (date, time)".to_string(),
+                    ));
+                }
+                let date = match self.eval(&args[0], row)? {
+                    Value::Date(d) => d,
+                    Value::Text(s) => s.parse().map_err(MoteDBError::TypeError)?,
+                    _ => {
+                        return Err(MoteDBError::TypeError(
+                            "TO_TIMESTAMP() first argument must be date".to_string(),
+                        ))
+                    }
+                };
+                let time = match args.get(1).map(|a| self.eval(a, row)).transpose()? {
+                    None => None,
+                    Some(Value::Time(t)) => Some(t),
+                    Some(Value::Text(s)) => Some(s.parse().map_err(MoteDBError::TypeError)?),
+                    Some(_) => {
+                        return Err(MoteDBError::TypeError(
+                            "TO_TIMESTAMP() second argument must be time".to_string(),
+                        ))
+                    }
+                };
+                Ok(Value::Timestamp(date.to_timestamp(time)))
+            }
+
+            // UUID functions
+            "gen_uuid" | "uuidv7" => {
+                // GEN_UUID() - time-ordered v7, keeps primary-key inserts append-only
+                if !args.is_empty() {
+                    return Err(MoteDBError::InvalidArgument(
+                        "GEN_UUID() takes no arguments".to_string(),
+                    ));
+                }
+                Ok(Value::uuid(crate::types::Uuid::new_v7()))
+            }
+
+            "gen_random_uuid" | "uuidv4" => {
+                if !args.is_empty() {
+                    return Err(MoteDBError::InvalidArgument(
+                        "GEN_RANDOM_UUID() takes no arguments".to_string(),
+                    ));
+                }
+                Ok(Value::uuid(crate::types::Uuid::new_v4()))
+            }
+
+            "uuid_timestamp" => {
+                // UUID_TIMESTAMP(uuid) - creation time of a v7 UUID, NULL for other versions
+                if args.len() != 1 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "UUID_TIMESTAMP() takes 1 argument".to_string(),
+                    ));
+                }
+                let u = match self.eval(&args[0], row)? {
+                    Value::Uuid(u) => *u,
+                    Value::Text(s) => s.parse().map_err(MoteDBError::TypeError)?,
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "UUID_TIMESTAMP() requires a UUID, got {:?}",
+                            other
+                        )))
+                    }
+                };
+                Ok(u.timestamp_millis()
+                    .map(|ms| {
+                        Value::Timestamp(crate::types::Timestamp::from_micros(ms as i64 * 1000))
+                    })
+                    .unwrap_or(Value::Null))
+            }
+
             "timestamp_micros" => {
                 // TIMESTAMP_MICROS(value) - convert integer to timestamp
                 if args.len() != 1 {
@@ -1537,6 +1695,42 @@ impl ExprEvaluator {
                 }
             }
 
+            "at_time_zone" => {
+                // ts AT TIME ZONE zone - wall-clock time of an instant in `zone`;
+                // text is instead read as wall-clock time there, giving an instant
+                if args.len() != 2 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "AT TIME ZONE takes a value and a zone".to_string(),
+                    ));
+                }
+                let zone: UtcOffset = match self.eval(&args[1], row)? {
+                    Value::Text(z) => z.parse().map_err(MoteDBError::InvalidArgument)?,
+                    Value::Null => return Ok(Value::Null),
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "AT TIME ZONE requires a zone name or offset, got {:?}",
+                            other
+                        )))
+                    }
+                };
+                match self.eval(&args[0], row)? {
+                    Value::Timestamp(ts) => Ok(Value::Timestamp(zone.to_local(ts))),
+                    // Integer micros, as TIMESTAMP columns may read back
+                    Value::Integer(micros) => Ok(Value::Timestamp(
+                        zone.to_local(crate::types::Timestamp::from_micros(micros)),
+                    )),
+                    Value::Text(s) => zone
+                        .parse_timestamp(&s)
+                        .map(Value::Timestamp)
+                        .map_err(MoteDBError::TypeError),
+                    Value::Null => Ok(Value::Null),
+                    other => Err(MoteDBError::TypeError(format!(
+                        "AT TIME ZONE requires a timestamp or text, got {:?}",
+                        other
+                    ))),
+                }
+            }
+
             // 🆕 P1 Date/Time extraction functions
             "year" => {
                 if args.len() != 1 {
@@ -1550,8 +1744,12 @@ impl ExprEvaluator {
                         let (y, _, _) = Self::days_to_date(ts.as_micros() / 1_000_000 / 86400);
                         Ok(Value::Integer(y))
                     }
+                    Value::Date(date) => {
+                        let (y, _, _) = date.ymd();
+                        Ok(Value::Integer(y))
+                    }
                     _ => Err(MoteDBError::TypeError(
-                        "YEAR() requires timestamp argument".to_string(),
+                        "YEAR() requires timestamp or date argument".to_string(),
                     )),
                 }
             }
@@ -1568,8 +1766,12 @@ impl ExprEvaluator {
                         let (_, m, _) = Self::days_to_date(ts.as_micros() / 1_000_000 / 86400);
                         Ok(Value::Integer(m))
                     }
+                    Value::Date(date) => {
+                        let (_, m, _) = date.ymd();
+                        Ok(Value::Integer(m as i64))
+                    }
                     _ => Err(MoteDBError::TypeError(
-                        "MONTH() requires timestamp argument".to_string(),
+                        "MONTH() requires timestamp or date argument".to_string(),
                     )),
                 }
             }
@@ -1586,8 +1788,12 @@ impl ExprEvaluator {
                         let (_, _, d) = Self::days_to_date(ts.as_micros() / 1_000_000 / 86400);
                         Ok(Value::Integer(d))
                     }
+                    Value::Date(date) => {
+                        let (_, _, d) = date.ymd();
+                        Ok(Value::Integer(d as i64))
+                    }
                     _ => Err(MoteDBError::TypeError(
-                        "DAY() requires timestamp argument".to_string(),
+                        "DAY() requires timestamp or date argument".to_string(),
                     )),
                 }
             }
@@ -1606,8 +1812,9 @@ impl ExprEvaluator {
                         let hour = (secs % 86400) / 3600;
                         Ok(Value::Integer(hour))
                     }
+                    Value::Time(t) => Ok(Value::Integer(t.hour())),
                     _ => Err(MoteDBError::TypeError(
-                        "HOUR() requires timestamp argument".to_string(),
+                        "HOUR() requires timestamp or time argument".to_string(),
                     )),
                 }
             }
@@ -1626,8 +1833,9 @@ impl ExprEvaluator {
                         let minute = (secs % 3600) / 60;
                         Ok(Value::Integer(minute))
                     }
+                    Value::Time(t) => Ok(Value::Integer(t.minute())),
                     _ => Err(MoteDBError::TypeError(
-                        "MINUTE() requires timestamp argument".to_string(),
+                        "MINUTE() requires timestamp or time argument".to_string(),
                     )),
                 }
             }
@@ -1646,14 +1854,16 @@ impl ExprEvaluator {
                         let second = secs % 60;
                         Ok(Value::Integer(second))
                     }
+                    Value::Time(t) => Ok(Value::Integer(t.second())),
                     _ => Err(MoteDBError::TypeError(
-                        "SECOND() requires timestamp argument".to_string(),
+                        "SECOND() requires timestamp or time argument".to_string(),
                     )),
                 }
             }
 
             "date_add" | "dateadd" => {
                 // DATE_ADD(timestamp, interval_seconds) - add seconds to timestamp
+                // DATE_ADD(date, days) - add days to a date
                 if args.len() != 2 {
                     return Err(MoteDBError::InvalidArgument(
                         "DATE_ADD() takes 2 arguments (timestamp, seconds)".to_string(),
@@ -1661,6 +1871,19 @@ impl ExprEvaluator {
                 }
                 let ts = match self.eval(&args[0], row)? {
                     Value::Timestamp(ts) => ts,
+                    Value::Date(date) => {
+                        let days = match self.eval(&args[1], row)? {
+                            Value::Integer(i) => i,
+                            _ => {
+                                return Err(MoteDBError::TypeError(
+                                    "DATE_ADD() second argument must be integer".to_string(),
+                                ))
+                            }
+                        };
+                        return date.add_days(days).map(Value::Date).ok_or_else(|| {
+                            MoteDBError::InvalidArgument("DATE_ADD() date out of range".to_string())
+                        });
+                    }
                     _ => {
                         return Err(MoteDBError::TypeError(
                             "DATE_ADD() first argument must be timestamp".to_string(),
@@ -1688,6 +1911,7 @@ impl ExprEvaluator {
 
             "date_diff" | "datediff" => {
                 // DATE_DIFF(timestamp1, timestamp2) - difference in seconds
+                // DATE_DIFF(date1, date2) - difference in days
                 if args.len() != 2 {
                     return Err(MoteDBError::InvalidArgument(
                         "DATE_DIFF() takes 2 arguments (timestamp1, timestamp2)".to_string(),
@@ -1695,6 +1919,16 @@ impl ExprEvaluator {
                 }
                 let ts1 = match self.eval(&args[0], row)? {
                     Value::Timestamp(ts) => ts,
+                    Value::Date(d1) => {
+                        return match self.eval(&args[1], row)? {
+                            Value::Date(d2) => {
+                                Ok(Value::Integer(d1.days() as i64 - d2.days() as i64))
+                            }
+                            _ => Err(MoteDBError::TypeError(
+                                "DATE_DIFF() second argument must be date".to_string(),
+                            )),
+                        };
+                    }
                     _ => {
                         return Err(MoteDBError::TypeError(
                             "DATE_DIFF() first argument must be timestamp".to_string(),
@@ -1742,8 +1976,10 @@ impl ExprEvaluator {
 
                 let bucket_micros = parse_interval_to_micros(&interval_str)?;
 
-                let ts = match self.eval(&args[1], row)? {
-                    Value::Timestamp(ts) => ts,
+                // TIMESTAMP columns written from integer literals hold raw micros
+                let micros = match self.eval(&args[1], row)? {
+                    Value::Timestamp(ts) => ts.as_micros(),
+                    Value::Integer(i) => i,
                     _ => {
                         return Err(MoteDBError::TypeError(
                             "TIME_BUCKET() second argument must be timestamp".to_string(),
@@ -1751,7 +1987,8 @@ impl ExprEvaluator {
                     }
                 };
 
-                let floored = (ts.as_micros() / bucket_micros) * bucket_micros;
+                // Floor toward -inf so pre-epoch timestamps land in the right bucket
+                let floored = micros.div_euclid(bucket_micros) * bucket_micros;
                 use crate::types::Timestamp;
                 Ok(Value::Timestamp(Timestamp::from_micros(floored)))
             }
@@ -1773,12 +2010,166 @@ impl ExprEvaluator {
                         let dow = ((days + 3).rem_euclid(7)) + 1; // rem_euclid handles negative days
                         Ok(Value::Integer(dow))
                     }
+                    Value::Date(date) => Ok(Value::Integer(date.day_of_week())),
                     _ => Err(MoteDBError::TypeError(
-                        "DAY_OF_WEEK() requires timestamp argument".to_string(),
+                        "DAY_OF_WEEK() requires timestamp or date argument".to_string(),
                     )),
                 }
             }
 
+            "array" => {
+                // ARRAY[a, b, ...] with non-literal elements
+                let items = args
+                    .iter()
+                    .map(|a| self.eval(a, row))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::array(items))
+            }
+
+            "element_at" => {
+                // ELEMENT_AT(array, i) / array[i] - 1-based, negative counts from
+                // the end; out of range is NULL
+                if args.len() != 2 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "ELEMENT_AT() takes 2 arguments".to_string(),
+                    ));
+                }
+                let index = match self.eval(&args[1], row)? {
+                    Value::Integer(i) => i,
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "array index must be an integer, got {:?}",
+                            other
+                        )))
+                    }
+                };
+                let pick = |len: usize| -> Option<usize> {
+                    let len = len as i64;
+                    let pos = if index < 0 { len + index } else { index - 1 };
+                    (0..len).contains(&pos).then_some(pos as usize)
+                };
+                match self.eval(&args[0], row)? {
+                    Value::Array(items) => {
+                        Ok(pick(items.len()).map_or(Value::Null, |i| items[i].clone()))
+                    }
+                    Value::Vector(v) => {
+                        Ok(pick(v.len())
+                            .map_or(Value::Null, |i| Value::Float(v.as_slice()[i] as f64)))
+                    }
+                    // Indexes the first axis: fmap[c] is a channel, fmap[c][y][x] an element
+                    Value::Tensor(t) => Ok(match pick(t.shape()[0]) {
+                        None => Value::Null,
+                        Some(i) if t.rank() == 1 => Value::Float(t.as_f32()[i] as f64),
+                        Some(i) => t.index(i).map_or(Value::Null, Value::tensor),
+                    }),
+                    other => Err(MoteDBError::TypeError(format!(
+                        "ELEMENT_AT() requires an array, got {:?}",
+                        other
+                    ))),
+                }
+            }
+
+            "tensor_shape" => {
+                // TENSOR_SHAPE(t) - extent of each axis as ARRAY<INT>
+                if args.len() != 1 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "TENSOR_SHAPE() takes 1 argument".to_string(),
+                    ));
+                }
+                let shape = match self.eval(&args[0], row)? {
+                    Value::Tensor(t) => t.shape().to_vec(),
+                    Value::Vector(v) => vec![v.len()],
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "TENSOR_SHAPE() requires a tensor, got {:?}",
+                            other
+                        )))
+                    }
+                };
+                Ok(Value::array(
+                    shape
+                        .into_iter()
+                        .map(|d| Value::Integer(d as i64))
+                        .collect(),
+                ))
+            }
+
+            "tensor_slice" => {
+                // TENSOR_SLICE(t, axis, from, to) - 1-based axis and inclusive
+                // range; other axes are kept whole, so the rank is unchanged
+                if args.len() != 4 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "TENSOR_SLICE() takes 4 arguments".to_string(),
+                    ));
+                }
+                let t = match self.eval(&args[0], row)? {
+                    Value::Tensor(t) => t,
+                    Value::Vector(v) => Box::new(crate::types::Tensor::new(v.to_vec())),
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "TENSOR_SLICE() requires a tensor, got {:?}",
+                            other
+                        )))
+                    }
+                };
+                let mut bounds = [0usize; 3];
+                for (b, arg) in bounds.iter_mut().zip(&args[1..]) {
+                    *b = match self.eval(arg, row)? {
+                        Value::Integer(i) if i >= 1 => i as usize - 1,
+                        other => {
+                            return Err(MoteDBError::InvalidArgument(format!(
+                                "TENSOR_SLICE() axis and bounds must be >= 1, got {:?}",
+                                other
+                            )))
+                        }
+                    };
+                }
+                let [axis, from, to] = bounds;
+                t.slice(axis, from, to + 1)
+                    .map(Value::tensor)
+                    .map_err(MoteDBError::InvalidArgument)
+            }
+
+            "array_length" | "cardinality" => {
+                if args.len() != 1 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "ARRAY_LENGTH() takes 1 argument".to_string(),
+                    ));
+                }
+                match self.eval(&args[0], row)? {
+                    Value::Array(items) => Ok(Value::Integer(items.len() as i64)),
+                    Value::Vector(v) => Ok(Value::Integer(v.len() as i64)),
+                    other => Err(MoteDBError::TypeError(format!(
+                        "ARRAY_LENGTH() requires an array, got {:?}",
+                        other
+                    ))),
+                }
+            }
+
+            "array_contains" => {
+                // ARRAY_CONTAINS(array, value) - NULL elements never match
+                if args.len() != 2 {
+                    return Err(MoteDBError::InvalidArgument(
+                        "ARRAY_CONTAINS() takes 2 arguments".to_string(),
+                    ));
+                }
+                let needle = self.eval(&args[1], row)?;
+                match self.eval(&args[0], row)? {
+                    Value::Array(items) => Ok(Value::Bool(items.contains(&needle))),
+                    Value::Vector(v) => Ok(Value::Bool(
+                        v.iter().any(|f| Value::Float(*f as f64) == needle),
+                    )),
+                    other => Err(MoteDBError::TypeError(format!(
+                        "ARRAY_CONTAINS() requires an array, got {:?}",
+                        other
+                    ))),
+                }
+            }
+
+            "unnest" => Err(MoteDBError::InvalidArgument(
+                "UNNEST() is only allowed as a top-level SELECT expression".to_string(),
+            )),
+
             // 🆕 Type conversion function
             "cast" => {
                 // CAST(value AS type) - NOTE: In SQL this is special syntax, but we handle as function
@@ -1836,6 +2227,14 @@ impl ExprEvaluator {
                             }),
                             Value::Bool(b) => Ok(Value::Integer(if b { 1 } else { 0 })),
                             Value::Timestamp(ts) => Ok(Value::Integer(ts.as_micros())),
+                            Value::Decimal(ref d) => {
+                                d.to_i64().map(Value::Integer).ok_or_else(|| {
+                                    MoteDBError::TypeError(format!(
+                                        "Decimal {} overflows INTEGER range",
+                                        d
+                                    ))
+                                })
+                            }
                             _ => Err(MoteDBError::TypeError(format!(
                                 "Cannot cast {:?} to INTEGER",
                                 val
@@ -1845,6 +2244,7 @@ impl ExprEvaluator {
                     "FLOAT" | "REAL" | "DOUBLE" => match val {
                         Value::Float(f) => Ok(Value::Float(f)),
                         Value::Integer(i) => Ok(Value::Float(i as f64)),
+                        Value::Decimal(d) => Ok(Value::Float(d.to_f64())),
                         Value::Text(s) => s
                             .parse::<f64>()
                             .map(Value::Float)
@@ -1859,6 +2259,12 @@ impl ExprEvaluator {
                             Value::Text(s) => s.as_str().to_string(),
                             Value::Integer(i) => i.to_string(),
                             Value::Float(f) => f.to_string(),
+                            Value::Decimal(d) => d.to_string(),
+                            Value::Uuid(u) => u.to_string(),
+                            Value::Date(d) => d.to_string(),
+                            Value::Time(t) => t.to_string(),
+                            Value::Timestamp(ts) => session_time_zone().format_timestamp(ts),
+                            Value::Array(_) => val.encoded_text().unwrap_or_default(),
                             Value::Bool(b) => b.to_string(),
                             Value::Null => return Ok(Value::Null),
                             _ => format!("{:?}", val),
@@ -1871,15 +2277,60 @@ impl ExprEvaluator {
                     }
                     "TIMESTAMP" => match val {
                         Value::Timestamp(ts) => Ok(Value::Timestamp(ts)),
+                        Value::Date(d) => Ok(Value::Timestamp(d.to_timestamp(None))),
                         Value::Integer(micros) => {
                             use crate::types::Timestamp;
                             Ok(Value::Timestamp(Timestamp::from_micros(micros)))
                         }
+                        Value::Text(s) => session_time_zone()
+                            .parse_timestamp(&s)
+                            .map(Value::Timestamp)
+                            .map_err(MoteDBError::TypeError),
                         _ => Err(MoteDBError::TypeError(format!(
                             "Cannot cast {:?} to TIMESTAMP",
                             val
                         ))),
                     },
+                    t if t.starts_with("DECIMAL") || t.starts_with("NUMERIC") => {
+                        let (precision, scale) = parse_decimal_type_name(t).ok_or_else(|| {
+                            MoteDBError::TypeError(format!("Invalid DECIMAL type: {}", t))
+                        })?;
+                        crate::types::ColumnType::Decimal { precision, scale }
+                            .coerce(val)
+                            .map_err(MoteDBError::TypeError)
+                    }
+                    "UUID" => match val {
+                        Value::Uuid(u) => Ok(Value::Uuid(u)),
+                        Value::Text(s) => {
+                            s.parse().map(Value::uuid).map_err(MoteDBError::TypeError)
+                        }
+                        _ => Err(MoteDBError::TypeError(format!(
+                            "Cannot cast {:?} to UUID",
+                            val
+                        ))),
+                    },
+                    "DATE" => match val {
+                        Value::Date(_) | Value::Text(_) | Value::Timestamp(_) => {
+                            crate::types::ColumnType::Date
+                                .coerce(val)
+                                .map_err(MoteDBError::TypeError)
+                        }
+                        _ => Err(MoteDBError::TypeError(format!(
+                            "Cannot cast {:?} to DATE",
+                            val
+                        ))),
+                    },
+                    "TIME" => match val {
+                        Value::Time(_) | Value::Text(_) | Value::Timestamp(_) => {
+                            crate::types::ColumnType::Time
+                                .coerce(val)
+                                .map_err(MoteDBError::TypeError)
+                        }
+                        _ => Err(MoteDBError::TypeError(format!(
+                            "Cannot cast {:?} to TIME",
+                            val
+                        ))),
+                    },
                     _ => Err(MoteDBError::TypeError(format!(
                         "Unknown target type: {}",
                         target_type
@@ -1898,6 +2349,7 @@ impl ExprEvaluator {
             Value::Bool(b) => Ok(*b),
             Value::Integer(i) => Ok(*i != 0),
             Value::Float(f) => Ok(*f != 0.0 && !f.is_nan()), // 🔧 Support Float: non-zero and non-NaN is true
+            Value::Decimal(d) => Ok(!d.is_zero()),
             Value::Null => Ok(false),
             _ => Err(MoteDBError::TypeError(
                 "Cannot convert to boolean".to_string(),
@@ -1909,6 +2361,7 @@ impl ExprEvaluator {
         match val {
             Value::Float(f) => Ok(*f),
             Value::Integer(i) => Ok(*i as f64),
+            Value::Decimal(d) => Ok(d.to_f64()),
             _ => Err(MoteDBError::TypeError(
                 "Cannot convert to float".to_string(),
             )),
@@ -1916,6 +2369,9 @@ impl ExprEvaluator {
     }
 
     fn add_values(&self, left: Value, right: Value) -> Result<Value> {
+        if let Some(result) = decimal_arith(DecimalOp::Add, &left, &right) {
+            return result;
+        }
         match (left, right) {
             (Value::Integer(l), Value::Integer(r)) => match l.checked_add(r) {
                 Some(n) => Ok(Value::Integer(n)),
@@ -1930,6 +2386,9 @@ impl ExprEvaluator {
     }
 
     fn sub_values(&self, left: Value, right: Value) -> Result<Value> {
+        if let Some(result) = decimal_arith(DecimalOp::Sub, &left, &right) {
+            return result;
+        }
         match (left, right) {
             (Value::Integer(l), Value::Integer(r)) => match l.checked_sub(r) {
                 Some(n) => Ok(Value::Integer(n)),
@@ -1945,6 +2404,9 @@ impl ExprEvaluator {
     }
 
     fn mul_values(&self, left: Value, right: Value) -> Result<Value> {
+        if let Some(result) = decimal_arith(DecimalOp::Mul, &left, &right) {
+            return result;
+        }
         match (left, right) {
             (Value::Integer(l), Value::Integer(r)) => match l.checked_mul(r) {
                 Some(n) => Ok(Value::Integer(n)),
@@ -1960,6 +2422,9 @@ impl ExprEvaluator {
     }
 
     fn div_values(&self, left: Value, right: Value) -> Result<Value> {
+        if let Some(result) = decimal_arith(DecimalOp::Div, &left, &right) {
+            return result;
+        }
         match (left, right) {
             (Value::Integer(l), Value::Integer(r)) => {
                 if r == 0 {
@@ -2278,8 +2743,98 @@ impl ExprEvaluator {
     }
 }
 
-/// Parse interval string like '5m', '1h', '30s', '1d' to microseconds.
-fn parse_interval_to_micros(interval: &str) -> crate::Result<i64> {
+/// SQL truth value of a predicate result: `None` is UNKNOWN
+pub(crate) fn truth_value(truth: Option<bool>) -> Value {
+    truth.map_or(Value::Null, Value::Bool)
+}
+
+/// Three-valued AND: FALSE wins over UNKNOWN, UNKNOWN over TRUE
+pub(crate) fn truth_and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// Three-valued OR: TRUE wins over UNKNOWN, UNKNOWN over FALSE
+pub(crate) fn truth_or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+/// `cmp(left, right)`, UNKNOWN when either side is NULL
+pub(crate) fn compare_truth(
+    left: &Value,
+    right: &Value,
+    cmp: impl FnOnce(&Value, &Value) -> bool,
+) -> Option<bool> {
+    if matches!(left, Value::Null) || matches!(right, Value::Null) {
+        None
+    } else {
+        Some(cmp(left, right))
+    }
+}
+
+/// `value [NOT] IN (subquery)` against the materialized subquery result.
+/// An empty result decides the test even for a NULL value; otherwise a NULL
+/// value, or a miss when the result held a NULL, is UNKNOWN.
+pub(crate) fn in_set_truth(
+    value: &Value,
+    set: &std::collections::HashSet<Value>,
+    negated: bool,
+    has_null: bool,
+) -> Option<bool> {
+    let truth = if set.is_empty() && !has_null {
+        Some(false)
+    } else if matches!(value, Value::Null) {
+        None
+    } else if set.contains(value) {
+        Some(true)
+    } else if has_null {
+        None
+    } else {
+        Some(false)
+    };
+    if negated {
+        truth.map(|t| !t)
+    } else {
+        truth
+    }
+}
+
+/// Parse a CAST target like 'DECIMAL', 'NUMERIC(12)' or 'DECIMAL(12,2)'.
+/// A bare type name defaults to (10, 0), matching the DDL parser.
+fn parse_decimal_type_name(name: &str) -> Option<(u8, u8)> {
+    let params = name
+        .strip_prefix("DECIMAL")
+        .or_else(|| name.strip_prefix("NUMERIC"))?
+        .trim();
+    if params.is_empty() {
+        return Some((10, 0));
+    }
+    let inner = params.strip_prefix('(')?.strip_suffix(')')?;
+    let mut parts = inner.split(',').map(|p| p.trim().parse::<u8>());
+    let precision = parts.next()?.ok()?;
+    let scale = match parts.next() {
+        Some(s) => s.ok()?,
+        None => 0,
+    };
+    if parts.next().is_some()
+        || precision == 0
+        || precision > crate::types::DECIMAL_MAX_PRECISION
+        || scale > precision
+    {
+        return None;
+    }
+    Some((precision, scale))
+}
+
+/// Parse interval string like '100ms', '5m', '1h', '30s', '1d' to microseconds.
+pub(crate) fn parse_interval_to_micros(interval: &str) -> crate::Result<i64> {
     let interval = interval.trim();
     if interval.is_empty() {
         return Err(crate::MoteDBError::InvalidArgument(
@@ -2305,6 +2860,8 @@ fn parse_interval_to_micros(interval: &str) -> crate::Result<i64> {
     }
 
     let micros = match unit {
+        "us" | "micros" => Some(num),
+        "ms" | "millis" => num.checked_mul(1_000),
         "s" | "sec" | "second" | "seconds" => num.checked_mul(1_000_000),
         "m" | "min" | "minute" | "minutes" => {
             num.checked_mul(60).and_then(|v| v.checked_mul(1_000_000))
@@ -2317,7 +2874,7 @@ fn parse_interval_to_micros(interval: &str) -> crate::Result<i64> {
             .and_then(|v| v.checked_mul(1_000_000)),
         _ => {
             return Err(crate::MoteDBError::InvalidArgument(format!(
-                "Unknown interval unit: '{}'. Use s/m/h/d",
+                "Unknown interval unit: '{}'. Use us/ms/s/m/h/d",
                 unit
             )))
         }
@@ -2469,13 +3026,66 @@ mod tests {
     #[test]
     fn test_eval_eq_null() {
         let r = row(&[]);
-        // Full evaluator: NULL = anything → false (WHERE-style filtering)
+        // NULL = anything → UNKNOWN (NULL), which WHERE filters out
         let eq = Expr::BinaryOp {
             left: Box::new(lit_null()),
             op: BinaryOperator::Eq,
             right: Box::new(lit_int(1)),
         };
-        assert_eq!(eval(&eq, &r).unwrap(), Value::Bool(false));
+        assert_eq!(eval(&eq, &r).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_eval_three_valued_logic() {
+        let r = row(&[("x", Value::Null), ("n", Value::Integer(3))]);
+        let not = |e: Expr| Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr: Box::new(e),
+        };
+        let gt = Expr::BinaryOp {
+            left: Box::new(col("x")),
+            op: BinaryOperator::Gt,
+            right: Box::new(lit_int(1)),
+        };
+        // NOT UNKNOWN stays UNKNOWN
+        assert_eq!(eval(&not(gt.clone()), &r).unwrap(), Value::Null);
+        // FALSE AND UNKNOWN = FALSE, TRUE OR UNKNOWN = TRUE
+        let and = Expr::BinaryOp {
+            left: Box::new(lit_int(0)),
+            op: BinaryOperator::And,
+            right: Box::new(gt.clone()),
+        };
+        assert_eq!(eval(&not(and), &r).unwrap(), Value::Bool(true));
+
+        let in_list = |value: Expr, negated: bool| Expr::In {
+            expr: Box::new(value),
+            list: vec![lit_int(1), lit_null()],
+            negated,
+        };
+        assert_eq!(
+            eval(&in_list(lit_int(1), false), &r).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(eval(&in_list(col("n"), false), &r).unwrap(), Value::Null);
+        assert_eq!(eval(&in_list(col("n"), true), &r).unwrap(), Value::Null);
+        assert_eq!(eval(&in_list(col("x"), true), &r).unwrap(), Value::Null);
+
+        // 3 BETWEEN NULL AND 2: the upper bound alone decides FALSE
+        let between = |negated: bool| Expr::Between {
+            expr: Box::new(col("n")),
+            low: Box::new(lit_null()),
+            high: Box::new(lit_int(2)),
+            negated,
+        };
+        assert_eq!(eval(&between(false), &r).unwrap(), Value::Bool(false));
+        assert_eq!(eval(&between(true), &r).unwrap(), Value::Bool(true));
+
+        let not_like = Expr::Like {
+            expr: Box::new(col("x")),
+            pattern: Box::new(lit_text("a%")),
+            negated: true,
+        };
+        assert_eq!(eval(&not_like, &r).unwrap(), Value::Null);
     }
 
     // ━━━ Logic ━━━
@@ -1,11 +1,17 @@
 /// Query executor - executes SQL statements against storage engine
+use super::access::TableAccess;
 use super::ast::*;
-use super::evaluator::ExprEvaluator;
+use super::correlated::{self, CorrelatedKind, CorrelatedSubquery, Memo};
+use super::evaluator::{
+    compare_truth, in_set_truth, truth_and, truth_or, truth_value, ExprEvaluator,
+};
+use super::optimizer::{IndexProbe, ProbeKind};
+use super::query_memory;
 use super::row_converter::{row_to_sql_row, rows_to_sql_rows, sql_row_to_row};
 use crate::database::MoteDB;
 use crate::error::{MoteDBError, Result};
 use crate::storage::row_format;
-use crate::types::{ColumnType, Row, RowId, SqlRow, TableSchema, Value};
+use crate::types::{ColumnType, Row, RowId, RowIdSet, SqlRow, TableSchema, Value};
 use crate::StorageError;
 use std::cmp::Ordering;
 use std::sync::Arc;
@@ -39,6 +45,9 @@ fn decode_row(data: &[u8], schema: &TableSchema) -> crate::Result<Row> {
 #[allow(clippy::type_complexity)]
 type FromScanResult = Result<(Vec<(u64, SqlRow)>, Arc<TableSchema>)>;
 
+/// Boxed row iterator produced by [`StreamingQueryResult::into_row_iter`].
+pub type RowIter = Box<dyn Iterator<Item = Result<Vec<Value>>> + Send>;
+
 #[allow(clippy::type_complexity)]
 type RowPredicate = Option<Box<dyn Fn(&SqlRow) -> bool + Send + Sync>>;
 
@@ -89,7 +98,7 @@ fn prefix_schema(schema: &TableSchema, prefix: &str) -> TableSchema {
 }
 
 /// Apply a comparison operator to an optional field value (None = NULL) and a
-/// target. NULLs never match. Used for post-filtering scanned rows by AND'd
+/// target. NULLs (either side) never match. Used for post-filtering scanned rows by AND'd
 /// predicates that weren't pushed into the single-column scan predicate.
 fn apply_op_value(
     op: &crate::sql::ast::BinaryOperator,
@@ -98,8 +107,8 @@ fn apply_op_value(
 ) -> bool {
     use crate::sql::ast::BinaryOperator;
     let v = match fv {
-        Some(v) => v,
-        None => return false,
+        Some(v) if !matches!(target, Value::Null) => v,
+        _ => return false,
     };
     match op {
         BinaryOperator::Eq => v == target,
@@ -131,7 +140,12 @@ pub enum ColumnarSeg {
         crate::storage::lsm::columnar::FixedSegment,
         crate::types::ColumnType,
     ),
-    Text(crate::storage::lsm::columnar::TextSegment),
+    /// Text-layout column. The carried `ColumnType` is `Text` except for
+    /// DECIMAL, whose values are stored as canonical strings.
+    Text(
+        crate::storage::lsm::columnar::TextSegment,
+        crate::types::ColumnType,
+    ),
 }
 
 /// Query result
@@ -182,6 +196,26 @@ impl QueryResult {
     }
 }
 
+impl From<QueryResult> for StreamingQueryResult {
+    fn from(result: QueryResult) -> Self {
+        match result {
+            QueryResult::Select { columns, rows } => Self::SelectReady { columns, rows },
+            QueryResult::Modification { affected_rows } => Self::Modification { affected_rows },
+            QueryResult::Definition { message } => Self::Definition { message },
+        }
+    }
+}
+
+/// One page of a keyset-paginated SELECT (see [`QueryExecutor::execute_select_page`])
+#[derive(Debug, Clone)]
+pub struct QueryPage {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// Pass back as `AFTER CURSOR '<token>'` for the next page; `None` once
+    /// the scan is exhausted
+    pub next_cursor: Option<String>,
+}
+
 /// Callback flow control for `for_each()`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StreamingControl {
@@ -211,7 +245,7 @@ pub struct ForEachResult {
 /// # 示例
 /// ```ignore
 /// // 新 API：流式迭代
-/// let result = db.execute_streaming("SELECT * FROM robots WHERE age < 25")?;
+/// let result = db.execute("SELECT * FROM robots WHERE age < 25")?;
 /// result.for_each(|columns, row| {
 ///     println!("{:?}: {:?}", columns, row);
 ///     Ok(())
@@ -234,7 +268,8 @@ pub enum StreamingQueryResult {
         /// Safety limit: max rows to collect during materialize(). Truncates gracefully.
         max_result_rows: Option<usize>,
         /// Capacity hint for materialize() — avoids repeated Vec reallocations.
-        /// Populated from fast_row_count() when available.
+        /// Populated from the optimizer's row estimate, capped by
+        /// `StreamingConfig::materialize_capacity`.
         size_hint: Option<usize>,
     },
 
@@ -349,7 +384,13 @@ impl StreamingQueryResult {
                                     .unwrap_or(Value::Null),
                                 );
                             }
-                            ColumnarSeg::Text(t) => {
+                            ColumnarSeg::Text(t, ct) if ct.is_text_encoded() => {
+                                row.push(
+                                    t.get_str(idx)
+                                        .map_or(Value::Null, |s| ct.value_from_text(s)),
+                                );
+                            }
+                            ColumnarSeg::Text(t, _) => {
                                 let val = if let Some(s) = t.get_str(idx) {
                                     let arc = string_pool.get(s).cloned().unwrap_or_else(|| {
                                         let a: std::sync::Arc<str> = std::sync::Arc::from(s);
@@ -401,11 +442,13 @@ impl StreamingQueryResult {
 
                 // Step 2: Apply ORDER BY
                 if let Some(order_clauses) = order_by {
+                    query_memory::charge_rows(&materialized_rows, "ORDER BY")?;
                     Self::apply_order_by(&mut materialized_rows, &columns, &order_clauses)?;
                 }
 
-                // Step 3: Apply DISTINCT
+                // Step 3: Apply DISTINCT (the seen-set holds a copy of each row)
                 if distinct {
+                    query_memory::charge_rows(&materialized_rows, "DISTINCT")?;
                     materialized_rows = Self::apply_distinct(materialized_rows);
                 }
 
@@ -494,6 +537,153 @@ impl StreamingQueryResult {
         }
     }
 
+    /// Set the materialize() capacity hint of SelectStreaming variants
+    ///
+    /// An existing hint wins over `estimate`; the result is capped at `cap`
+    /// and at `max_result_rows`. Without any estimate the hint is `cap`.
+    /// A zero estimate means the optimizer had no statistics.
+    fn with_size_hint(self, estimate: Option<usize>, cap: usize) -> Self {
+        match self {
+            Self::SelectStreaming {
+                columns,
+                rows,
+                order_by,
+                limit,
+                offset,
+                distinct,
+                max_result_rows,
+                size_hint,
+            } => {
+                let hint = size_hint
+                    .or(estimate.filter(|&n| n > 0))
+                    .map_or(cap, |n| n.min(cap))
+                    .min(max_result_rows.unwrap_or(usize::MAX));
+                Self::SelectStreaming {
+                    columns,
+                    rows,
+                    order_by,
+                    limit,
+                    offset,
+                    distinct,
+                    max_result_rows,
+                    size_hint: Some(hint),
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Pull up to `batch` rows of a SelectStreaming result now
+    ///
+    /// If the input ends there (or reaches LIMIT + OFFSET when nothing needs
+    /// sorting or dedup), the result is materialized into `SelectReady` and
+    /// the scan is done before the caller reads a row. A longer input keeps
+    /// streaming with the pulled rows in front, so a stale estimate costs one
+    /// batch rather than collecting the whole scan.
+    fn prefetch(self, batch: usize) -> Result<Self> {
+        let Self::SelectStreaming {
+            columns,
+            mut rows,
+            order_by,
+            limit,
+            offset,
+            distinct,
+            max_result_rows,
+            size_hint,
+        } = self
+        else {
+            return Ok(self);
+        };
+        let needed = match limit {
+            Some(n) if order_by.is_none() && !distinct => n.saturating_add(offset.unwrap_or(0)),
+            _ => usize::MAX,
+        }
+        .min(max_result_rows.unwrap_or(usize::MAX));
+
+        let mut head = Vec::with_capacity(batch.min(needed));
+        let exhausted = loop {
+            if head.len() >= needed {
+                break true;
+            }
+            if head.len() >= batch {
+                break false;
+            }
+            match rows.next() {
+                Some(row) => head.push(row?),
+                None => break true,
+            }
+        };
+        let rows: Box<dyn Iterator<Item = Result<Vec<Value>>> + Send> = if exhausted {
+            Box::new(head.into_iter().map(Ok))
+        } else {
+            Box::new(head.into_iter().map(Ok).chain(rows))
+        };
+        let result = Self::SelectStreaming {
+            columns,
+            rows,
+            order_by,
+            limit,
+            offset,
+            distinct,
+            max_result_rows,
+            size_hint,
+        };
+        if exhausted {
+            Ok(result.materialize()?.into())
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Convert into a pull-based row iterator (used by the FFI cursor API).
+    ///
+    /// Plain streaming results (no ORDER BY / DISTINCT) stay lazy: OFFSET and
+    /// LIMIT are applied on the fly, so memory is O(1) regardless of result
+    /// size. Results that need a global sort or dedup are materialized first,
+    /// exactly like `materialize()`. Non-SELECT results yield no columns and
+    /// no rows.
+    pub fn into_row_iter(self) -> Result<(Vec<String>, RowIter)> {
+        match self {
+            Self::SelectStreaming {
+                columns,
+                rows,
+                order_by: None,
+                limit,
+                offset,
+                distinct: false,
+                max_result_rows,
+                ..
+            } => {
+                let take_n = limit
+                    .unwrap_or(usize::MAX)
+                    .min(max_result_rows.unwrap_or(usize::MAX));
+                let mut skipped = 0usize;
+                let offset_val = offset.unwrap_or(0);
+                // Skip OFFSET rows lazily, but surface scan errors immediately
+                // instead of swallowing them in the skipped prefix.
+                let iter = rows
+                    .filter(move |r| {
+                        if r.is_err() || skipped >= offset_val {
+                            return true;
+                        }
+                        skipped += 1;
+                        false
+                    })
+                    .take(take_n);
+                Ok((columns, Box::new(iter)))
+            }
+            Self::Modification { .. } | Self::Definition { .. } => {
+                Ok((Vec::new(), Box::new(std::iter::empty())))
+            }
+            other => match other.materialize()? {
+                QueryResult::Select { columns, rows } => {
+                    Ok((columns, Box::new(rows.into_iter().map(Ok))))
+                }
+                _ => Ok((Vec::new(), Box::new(std::iter::empty()))),
+            },
+        }
+    }
+
     /// Materialize with an explicit row limit. Returns (QueryResult, has_more).
     /// has_more is true when the limit was hit (more rows exist in storage).
     pub fn materialize_with_limit(self, max_rows: Option<usize>) -> Result<(QueryResult, bool)> {
@@ -591,13 +781,9 @@ impl StreamingQueryResult {
                                     .unwrap_or(Value::Null),
                                 );
                             }
-                            ColumnarSeg::Text(t) => row.push(
+                            ColumnarSeg::Text(t, ct) => row.push(
                                 t.get_str(idx)
-                                    .map(|s| {
-                                        Value::Text(crate::types::ArcString(std::sync::Arc::from(
-                                            s,
-                                        )))
-                                    })
+                                    .map(|s| ct.value_from_text(s))
                                     .unwrap_or(Value::Null),
                             ),
                         }
@@ -717,6 +903,7 @@ impl StreamingQueryResult {
                             }
                         }
                     }
+                    query_memory::charge_rows(&buf, "ORDER BY")?;
                     Self::sort_rows(&mut buf, &sort_specs);
                     let mut count = 0;
                     for row in buf.into_iter().skip(offset_val) {
@@ -862,17 +1049,7 @@ impl StreamingQueryResult {
     fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
         use std::cmp::Ordering;
         match (a, b) {
-            (Value::Float(a), Value::Float(b)) => {
-                if a.is_nan() && b.is_nan() {
-                    Ordering::Equal
-                } else if a.is_nan() {
-                    Ordering::Greater
-                } else if b.is_nan() {
-                    Ordering::Less
-                } else {
-                    a.partial_cmp(b).unwrap_or(Ordering::Equal)
-                }
-            }
+            (Value::Float(a), Value::Float(b)) => crate::types::float_cmp(*a, *b),
             (Value::Null, Value::Null) => Ordering::Equal,
             (Value::Null, _) => Ordering::Less,
             (_, Value::Null) => Ordering::Greater,
@@ -1009,17 +1186,7 @@ impl StreamingQueryResult {
                 }
 
                 let cmp = match (&a[col_idx], &b[col_idx]) {
-                    (Value::Float(a), Value::Float(b)) => {
-                        if a.is_nan() && b.is_nan() {
-                            Ordering::Equal
-                        } else if a.is_nan() {
-                            Ordering::Greater
-                        } else if b.is_nan() {
-                            Ordering::Less
-                        } else {
-                            a.partial_cmp(b).unwrap_or(Ordering::Equal)
-                        }
-                    }
+                    (Value::Float(a), Value::Float(b)) => crate::types::float_cmp(*a, *b),
                     (Value::Null, Value::Null) => Ordering::Equal,
                     (Value::Null, _) => Ordering::Less,
                     (_, Value::Null) => Ordering::Greater,
@@ -1072,109 +1239,88 @@ struct AggregateInfo {
 /// + direct Value comparison — no recursion, no string ops, no HashMap.
 #[allow(dead_code)]
 enum CompiledWhere {
-    Eq(usize, Value),                                // col[pos] == value
-    Ne(usize, Value),                                // col[pos] != value
-    Lt(usize, Value),                                // col[pos] < value
-    Le(usize, Value),                                // col[pos] <= value
-    Gt(usize, Value),                                // col[pos] > value
-    Ge(usize, Value),                                // col[pos] >= value
-    InHash(usize, std::collections::HashSet<Value>), // col[pos] IN set (O(1))
-    Like(usize, String, bool),                       // col[pos] LIKE pattern (negated bool)
-    IsNull(usize, bool),                             // col[pos] IS NULL / IS NOT NULL
-    Between(usize, Value, Value),                    // col[pos] BETWEEN low AND high
-    And(Vec<CompiledWhere>),                         // all must match (short-circuit)
-    Or(Vec<CompiledWhere>),                          // any must match (short-circuit)
-    Not(Box<CompiledWhere>),                         // negation
+    Eq(usize, Value),                                      // col[pos] == value
+    Ne(usize, Value),                                      // col[pos] != value
+    Lt(usize, Value),                                      // col[pos] < value
+    Le(usize, Value),                                      // col[pos] <= value
+    Gt(usize, Value),                                      // col[pos] > value
+    Ge(usize, Value),                                      // col[pos] >= value
+    InHash(usize, std::collections::HashSet<Value>, bool), // col[pos] IN set (O(1)), set had NULL
+    Like(usize, String, bool),                             // col[pos] LIKE pattern (negated bool)
+    IsNull(usize, bool),                                   // col[pos] IS NULL / IS NOT NULL
+    Between(usize, Value, Value),                          // col[pos] BETWEEN low AND high
+    And(Vec<CompiledWhere>),                               // all must match (short-circuit)
+    Or(Vec<CompiledWhere>),                                // any must match (short-circuit)
+    Not(Box<CompiledWhere>),                               // negation
 }
 
 impl CompiledWhere {
     /// Evaluate the compiled WHERE against a row.
     /// Returns `Some(bool)` if successful, `None` if fallback is needed.
+    /// UNKNOWN (a NULL comparison) does not match.
     #[inline]
     fn eval(&self, row: &[Value]) -> Option<bool> {
-        match self {
-            CompiledWhere::Eq(pos, val) => {
-                // SQL: NULL = val → NULL (false). Value PartialEq already returns false for Null==NonNull.
-                Some(row.get(*pos) == Some(val))
-            }
-            CompiledWhere::Ne(pos, val) => {
-                // SQL: NULL <> val → NULL (false). Must check NULL explicitly.
-                Some(row.get(*pos).is_some_and(|v| {
-                    if matches!(v, Value::Null) {
-                        return false;
-                    }
-                    v != val
-                }))
-            }
-            CompiledWhere::Lt(pos, val) => Some(
-                row.get(*pos)
-                    .filter(|v| !matches!(v, Value::Null))
-                    .is_some_and(|v| v < val),
-            ),
-            CompiledWhere::Le(pos, val) => Some(
-                row.get(*pos)
-                    .filter(|v| !matches!(v, Value::Null))
-                    .is_some_and(|v| v <= val),
-            ),
-            CompiledWhere::Gt(pos, val) => Some(
-                row.get(*pos)
-                    .filter(|v| !matches!(v, Value::Null))
-                    .is_some_and(|v| v > val),
-            ),
-            CompiledWhere::Ge(pos, val) => Some(
-                row.get(*pos)
-                    .filter(|v| !matches!(v, Value::Null))
-                    .is_some_and(|v| v >= val),
-            ),
-            CompiledWhere::InHash(pos, set) => {
-                // SQL: NULL IN (...) → NULL (false)
-                Some(row.get(*pos).is_some_and(|v| {
-                    if matches!(v, Value::Null) {
-                        return false;
-                    }
-                    set.contains(v)
-                }))
-            }
-            CompiledWhere::Like(pos, pattern, negated) => {
-                let matches = row
-                    .get(*pos)
-                    .and_then(|v| {
-                        if let Value::Text(s) = v {
-                            Some(Self::like_match(s, pattern))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(false);
-                Some(if *negated { !matches } else { matches })
-            }
+        self.truth(&|pos| Some(row.get(pos)))
+            .map(|t| t == Some(true))
+    }
+
+    /// SQL three-valued result: `Some(None)` is UNKNOWN, `None` means fallback.
+    /// `column` maps a schema position to the row's value (missing reads as
+    /// NULL), or `None` when the position was not decoded.
+    #[inline]
+    fn truth<'a>(
+        &self,
+        column: &impl Fn(usize) -> Option<Option<&'a Value>>,
+    ) -> Option<Option<bool>> {
+        let value =
+            |pos: usize| -> Option<&'a Value> { Some(column(pos)?.unwrap_or(&Value::Null)) };
+        Some(match self {
+            CompiledWhere::Eq(pos, val) => compare_truth(value(*pos)?, val, |v, val| v == val),
+            CompiledWhere::Ne(pos, val) => compare_truth(value(*pos)?, val, |v, val| v != val),
+            CompiledWhere::Lt(pos, val) => compare_truth(value(*pos)?, val, |v, val| v < val),
+            CompiledWhere::Le(pos, val) => compare_truth(value(*pos)?, val, |v, val| v <= val),
+            CompiledWhere::Gt(pos, val) => compare_truth(value(*pos)?, val, |v, val| v > val),
+            CompiledWhere::Ge(pos, val) => compare_truth(value(*pos)?, val, |v, val| v >= val),
+            CompiledWhere::InHash(pos, set, has_null) => {
+                in_set_truth(value(*pos)?, set, false, *has_null)
+            }
+            CompiledWhere::Like(pos, pattern, negated) => match value(*pos)? {
+                Value::Null => None,
+                Value::Text(s) => Some(Self::like_match(s, pattern) != *negated),
+                _ => Some(*negated),
+            },
             CompiledWhere::IsNull(pos, negated) => {
-                let is_null = row.get(*pos).is_none_or(|v| matches!(v, Value::Null));
-                Some(if *negated { !is_null } else { is_null })
+                Some(matches!(value(*pos)?, Value::Null) != *negated)
+            }
+            CompiledWhere::Between(pos, low, high) => {
+                let v = value(*pos)?;
+                truth_and(
+                    compare_truth(v, low, |v, low| v >= low),
+                    compare_truth(v, high, |v, high| v <= high),
+                )
             }
-            CompiledWhere::Between(pos, low, high) => Some(
-                row.get(*pos)
-                    .filter(|v| !matches!(v, Value::Null))
-                    .is_some_and(|v| v >= low && v <= high),
-            ),
             CompiledWhere::And(conds) => {
+                let mut truth = Some(true);
                 for c in conds {
-                    if !c.eval(row)? {
-                        return Some(false);
+                    truth = truth_and(truth, c.truth(column)?);
+                    if truth == Some(false) {
+                        break;
                     }
                 }
-                Some(true)
+                truth
             }
             CompiledWhere::Or(conds) => {
+                let mut truth = Some(false);
                 for c in conds {
-                    if c.eval(row)? {
-                        return Some(true);
+                    truth = truth_or(truth, c.truth(column)?);
+                    if truth == Some(true) {
+                        break;
                     }
                 }
-                Some(false)
+                truth
             }
-            CompiledWhere::Not(inner) => Some(!inner.eval(row)?),
-        }
+            CompiledWhere::Not(inner) => inner.truth(column)?.map(|t| !t),
+        })
     }
 
     /// SQL LIKE pattern match: % = any chars, _ = single char
@@ -1227,7 +1373,7 @@ impl CompiledWhere {
             | CompiledWhere::Le(pos, _)
             | CompiledWhere::Gt(pos, _)
             | CompiledWhere::Ge(pos, _)
-            | CompiledWhere::InHash(pos, _)
+            | CompiledWhere::InHash(pos, _, _)
             | CompiledWhere::Like(pos, _, _)
             | CompiledWhere::IsNull(pos, _)
             | CompiledWhere::Between(pos, _, _) => {
@@ -1248,106 +1394,8 @@ impl CompiledWhere {
     /// `pos_to_idx` maps schema column position → index in the partial buffer.
     #[inline]
     fn eval_at(&self, row: &[Value], pos_to_idx: &[Option<usize>]) -> Option<bool> {
-        match self {
-            CompiledWhere::Eq(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(row.get(*idx) == Some(val))
-            }
-            CompiledWhere::Ne(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(row.get(*idx).is_some_and(|v| {
-                    if matches!(v, Value::Null) {
-                        return false;
-                    }
-                    v != val
-                }))
-            }
-            CompiledWhere::Lt(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(
-                    row.get(*idx)
-                        .filter(|v| !matches!(v, Value::Null))
-                        .is_some_and(|v| v < val),
-                )
-            }
-            CompiledWhere::Le(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(
-                    row.get(*idx)
-                        .filter(|v| !matches!(v, Value::Null))
-                        .is_some_and(|v| v <= val),
-                )
-            }
-            CompiledWhere::Gt(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(
-                    row.get(*idx)
-                        .filter(|v| !matches!(v, Value::Null))
-                        .is_some_and(|v| v > val),
-                )
-            }
-            CompiledWhere::Ge(pos, val) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(
-                    row.get(*idx)
-                        .filter(|v| !matches!(v, Value::Null))
-                        .is_some_and(|v| v >= val),
-                )
-            }
-            CompiledWhere::InHash(pos, set) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(row.get(*idx).is_some_and(|v| {
-                    if matches!(v, Value::Null) {
-                        return false;
-                    }
-                    set.contains(v)
-                }))
-            }
-            CompiledWhere::Like(pos, pattern, negated) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                let matches = row
-                    .get(*idx)
-                    .and_then(|v| {
-                        if let Value::Text(s) = v {
-                            Some(Self::like_match(s, pattern))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(false);
-                Some(if *negated { !matches } else { matches })
-            }
-            CompiledWhere::IsNull(pos, negated) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                let is_null = row.get(*idx).is_none_or(|v| matches!(v, Value::Null));
-                Some(if *negated { !is_null } else { is_null })
-            }
-            CompiledWhere::Between(pos, low, high) => {
-                let idx = (*pos_to_idx).get(*pos)?.as_ref()?;
-                Some(
-                    row.get(*idx)
-                        .filter(|v| !matches!(v, Value::Null))
-                        .is_some_and(|v| v >= low && v <= high),
-                )
-            }
-            CompiledWhere::And(conds) => {
-                for c in conds {
-                    if !c.eval_at(row, pos_to_idx)? {
-                        return Some(false);
-                    }
-                }
-                Some(true)
-            }
-            CompiledWhere::Or(conds) => {
-                for c in conds {
-                    if c.eval_at(row, pos_to_idx)? {
-                        return Some(true);
-                    }
-                }
-                Some(false)
-            }
-            CompiledWhere::Not(inner) => Some(!inner.eval_at(row, pos_to_idx)?),
-        }
+        self.truth(&|pos| Some(row.get((*pos_to_idx.get(pos)?)?)))
+            .map(|t| t == Some(true))
     }
 }
 
@@ -1533,6 +1581,10 @@ pub struct QueryExecutor {
     optimizer: super::optimizer::QueryOptimizer,
     /// Store the last AUTO_INCREMENT value inserted (mirrors evaluator)
     last_insert_id: std::sync::atomic::AtomicI64,
+    /// Settings of the session (or `Database` handle) driving this executor
+    settings: parking_lot::Mutex<crate::session::SessionSettings>,
+    /// Table access check run before every statement (None = allow all)
+    access_hook: parking_lot::Mutex<Option<super::access::AccessHook>>,
 }
 
 // 🔑 Per-thread transaction context.
@@ -1611,10 +1663,102 @@ impl QueryExecutor {
             evaluator: ExprEvaluator::with_db(db.clone()),
             optimizer: super::optimizer::QueryOptimizer::new(db.clone()),
             last_insert_id: std::sync::atomic::AtomicI64::new(i64::MIN),
+            settings: parking_lot::Mutex::new(Default::default()),
+            access_hook: parking_lot::Mutex::new(None),
             db,
         }
     }
 
+    /// Streaming batch size and materialize cap: the database defaults unless
+    /// the session settings override them
+    pub fn streaming_config(&self) -> crate::config::StreamingConfig {
+        let settings = self.settings.lock();
+        crate::config::StreamingConfig {
+            batch_size: settings
+                .stream_batch_size
+                .unwrap_or(self.db.streaming.batch_size),
+            materialize_capacity: settings
+                .materialize_capacity
+                .unwrap_or(self.db.streaming.materialize_capacity),
+        }
+    }
+
+    /// Hits considered by a full-text MATCH without LIMIT
+    fn text_search_limit(&self) -> usize {
+        self.settings.lock().text_search_limit()
+    }
+
+    /// Bytes a statement's joins, sorts and aggregations may buffer: the
+    /// session setting, else the database default
+    fn query_memory_limit(&self) -> Option<usize> {
+        self.settings
+            .lock()
+            .query_memory_limit
+            .or(self.db.query_memory_limit)
+    }
+
+    /// Session knobs for vector index searches: the DiskANN candidate list
+    /// size and re-ranking (None = index default for either)
+    fn vector_search_options(&self) -> crate::database::VectorSearchOptions {
+        let settings = self.settings.lock();
+        crate::database::VectorSearchOptions {
+            width: settings.search_beam_width,
+            rerank: settings.vector_rerank,
+        }
+    }
+
+    /// Settings used by this executor (changed by SET statements)
+    pub fn session_settings(&self) -> crate::session::SessionSettings {
+        self.settings.lock().clone()
+    }
+
+    /// Replace the settings used by this executor
+    pub fn set_session_settings(&self, settings: crate::session::SessionSettings) {
+        *self.settings.lock() = settings;
+    }
+
+    /// Install (or with None, remove) the hook asked about every table a
+    /// statement touches
+    pub fn set_access_hook(&self, hook: Option<super::access::AccessHook>) {
+        *self.access_hook.lock() = hook;
+    }
+
+    /// Fail with `PermissionDenied` unless the access hook allows every table
+    /// `stmt` touches. Runs before any work, so a denied statement has no effect.
+    fn check_access(&self, stmt: &Statement) -> Result<()> {
+        let Some(hook) = self.access_hook.lock().clone() else {
+            return Ok(());
+        };
+        let mut accesses = super::access::table_accesses(stmt);
+        if let Statement::DropIndex(DropIndexStmt { index_name: name }) | Statement::Reindex(name) =
+            stmt
+        {
+            if let Some(meta) = self.db.index_registry.get(name) {
+                accesses.push((meta.table_name.clone(), TableAccess::Write));
+            }
+        }
+        for (table, access) in accesses {
+            if !hook(&table, access) {
+                let kind = match access {
+                    TableAccess::Read => "read",
+                    TableAccess::Write => "write",
+                };
+                return Err(MoteDBError::PermissionDenied(format!(
+                    "{} access to table '{}'",
+                    kind, table
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Make this executor's time zone the one timestamps are read and
+    /// rendered in on the calling thread. Called on entry to every statement;
+    /// fast paths that bypass `execute` call it themselves.
+    pub(crate) fn apply_session_time_zone(&self) {
+        super::evaluator::set_session_time_zone(self.settings.lock().time_zone);
+    }
+
     /// Reset per-query state. Called before each execute.
     pub fn reset_last_insert_id(&self) {
         self.last_insert_id
@@ -1635,55 +1779,14 @@ impl QueryExecutor {
         self.evaluator.clear_params();
     }
 
-    pub fn execute(&self, stmt: Statement) -> Result<QueryResult> {
-        match stmt {
-            Statement::Select { stmt: s, ctes } => {
-                let s = self.apply_ctes_for_select(s, &ctes)?;
-                self.execute_select(s)
-            }
-            Statement::SetOp {
-                left,
-                right,
-                op,
-                all,
-                ctes,
-            } => {
-                let left = self.apply_ctes_for_select(*left, &ctes)?;
-                let right = self.apply_ctes_for_select(*right, &ctes)?;
-                self.execute_set_op(Box::new(left), Box::new(right), op, all)
-            }
-            Statement::Insert(i) => self.execute_insert(i),
-            Statement::Update(u) => self.execute_update(u),
-            Statement::Delete(d) => self.execute_delete(d),
-            Statement::CreateTable(c) => self.execute_create_table(c),
-            Statement::CreateIndex(c) => self.execute_create_index(c),
-            Statement::DropTable(d) => self.execute_drop_table(d),
-            Statement::DropIndex(d) => self.execute_drop_index(d),
-            Statement::AlterTable(a) => self.execute_alter_table(a),
-            Statement::ShowTables => self.execute_show_tables(),
-            Statement::DescribeTable(table_name) => self.execute_describe_table(table_name),
-            Statement::BeginTransaction => self.execute_begin_transaction(),
-            Statement::CommitTransaction => self.execute_commit_transaction(),
-            Statement::RollbackTransaction => self.execute_rollback_transaction(),
-        }
-    }
-
-    /// 🚀 流式执行（方案 C：零内存开销）
-    ///
-    /// 返回迭代器而不是 Vec，实现真正的流式查询。
+    /// Execute a statement and collect its whole result
     ///
-    /// # 示例
-    /// ```ignore
-    /// let result = executor.execute_streaming(stmt)?;
-    /// result.for_each(|columns, row| {
-    ///     println!("{:?}: {:?}", columns, row);
-    ///     Ok(())
-    /// })?;
-    /// ```
-    /// Execute a statement by reference (avoids cloning the AST).
-    ///
-    /// For SELECT: only clones the SelectStmt (cheap relative to full query).
-    /// For other statements: clones only the specific variant needed.
+    /// Runs the same dispatch as [`execute_streaming_ref`](Self::execute_streaming_ref)
+    /// and materializes what it returns.
+    pub fn execute(&self, stmt: Statement) -> Result<QueryResult> {
+        self.execute_streaming_ref(&stmt)?.materialize()
+    }
+
     /// Check if a transaction is active (for fast-path bypass).
     pub fn is_in_transaction(&self) -> bool {
         CURRENT_TXN_ID.with(|c| c.get().is_some())
@@ -1715,7 +1818,7 @@ impl QueryExecutor {
     ///
     /// This MUST be called by Database::rollback_transaction() (the API path)
     /// before delegating to the coordinator. The SQL ROLLBACK path in
-    /// execute_streaming_ref inlines the same logic. Without this, API-level
+    /// execute_rollback_transaction calls it too. Without this, API-level
     /// rollback would silently fail to undo UPDATE/DELETE changes.
     pub fn replay_undo_log(&self, txn_id: u64) {
         let ctx = match self.db.txn_coordinator.get_context(txn_id) {
@@ -1729,10 +1832,18 @@ impl QueryExecutor {
                     let old_row = std::sync::Arc::try_unwrap(old_value)
                         .unwrap_or_else(|arc| (*arc).clone());
                     if let Ok(schema) = self.db.get_table_schema(&table_name) {
+                        // The stored row is the "before" image, so index
+                        // maintenance drops the entries the UPDATE added.
+                        let current = self
+                            .db
+                            .get_table_row(&table_name, row_id)
+                            .ok()
+                            .flatten()
+                            .unwrap_or_else(|| old_row.clone());
                         let _ = self.db.update_row_in_table_with_schema(
                             &table_name,
                             row_id,
-                            old_row.clone(),
+                            current,
                             old_row,
                             &schema,
                         );
@@ -1827,8 +1938,27 @@ impl QueryExecutor {
         None
     }
 
+    /// 🚀 执行语句：所有执行入口的统一分发
+    ///
+    /// SELECT 的结果形式由优化器统计信息决定：预计不超过一个流式批次
+    /// （`StreamingConfig::batch_size`）的结果、以及需要全部输入的 ORDER BY /
+    /// DISTINCT，在返回前物化为 `SelectReady`；其余保持流式迭代器。
+    /// 其他语句返回与 [`execute`](Self::execute) 相同的结果。
+    ///
+    /// # 示例
+    /// ```ignore
+    /// let result = executor.execute_streaming_ref(&stmt)?;
+    /// result.for_each(|columns, row| {
+    ///     println!("{:?}: {:?}", columns, row);
+    ///     Ok(StreamingControl::Continue)
+    /// }, None)?;
+    /// ```
     pub fn execute_streaming_ref(&self, stmt: &Statement) -> Result<StreamingQueryResult> {
         let max_rows = self.db.max_result_rows;
+        self.check_access(stmt)?;
+        self.db.refresh_stale_continuous_aggregates();
+        self.apply_session_time_zone();
+        query_memory::begin(self.query_memory_limit());
 
         // NOTE: We intentionally do NOT clear segment col_cache here. The cache
         // is bounded to 16 entries per segment (BoundedColCache), so it can't
@@ -1838,6 +1968,8 @@ impl QueryExecutor {
         let result = match stmt {
             Statement::Select { stmt: s, ctes } => {
                 let s = self.apply_ctes_for_select(s.clone(), ctes)?;
+                let s = self.resolve_select_list(s);
+                let s = self.inline_virtual_columns(s)?;
                 self.execute_select_streaming_ref(&s)?
             }
             Statement::SetOp {
@@ -1847,186 +1979,54 @@ impl QueryExecutor {
                 all,
                 ctes,
             } => {
-                let left = self.apply_ctes_for_select((**left).clone(), ctes)?;
-                let right = self.apply_ctes_for_select((**right).clone(), ctes)?;
-                let result =
-                    self.execute_set_op(Box::new(left), Box::new(right), op.clone(), *all)?;
-                return Ok(match result {
-                    QueryResult::Select { columns, rows } => {
-                        StreamingQueryResult::SelectReady { columns, rows }
-                    }
-                    _ => StreamingQueryResult::Modification { affected_rows: 0 },
-                });
-            }
-            Statement::Insert(i) => {
-                let result = self.execute_insert_ref(i)?;
-                StreamingQueryResult::Modification {
-                    affected_rows: result.affected_rows(),
-                }
-            }
-            Statement::Update(u) => {
-                let result = self.execute_update(u.clone())?;
-                StreamingQueryResult::Modification {
-                    affected_rows: result.affected_rows(),
-                }
-            }
-            Statement::Delete(d) => {
-                let result = self.execute_delete(d.clone())?;
-                StreamingQueryResult::Modification {
-                    affected_rows: result.affected_rows(),
-                }
-            }
-            Statement::CreateTable(c) => {
-                let result = self.execute_create_table(c.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Table created".to_string(),
-                    },
-                }
-            }
-            Statement::CreateIndex(c) => {
-                let result = self.execute_create_index(c.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Index created".to_string(),
-                    },
-                }
-            }
-            Statement::DropTable(d) => {
-                let result = self.execute_drop_table(d.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Table dropped".to_string(),
-                    },
-                }
-            }
-            Statement::DropIndex(d) => {
-                let result = self.execute_drop_index(d.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Index dropped".to_string(),
-                    },
-                }
-            }
-            Statement::ShowTables => {
-                let result = self.execute_show_tables()?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Tables shown".to_string(),
-                    },
-                }
-            }
+                let left = self
+                    .inline_virtual_columns(self.apply_ctes_for_select((**left).clone(), ctes)?)?;
+                let right = self
+                    .inline_virtual_columns(self.apply_ctes_for_select((**right).clone(), ctes)?)?;
+                self.execute_set_op(Box::new(left), Box::new(right), op.clone(), *all)?
+                    .into()
+            }
+            Statement::Insert(i) => self.execute_insert_ref(i)?.into(),
+            Statement::Update(u) => self.execute_update(u.clone())?.into(),
+            Statement::Delete(d) => self.execute_delete(d.clone())?.into(),
+            Statement::CreateTable(c) => self.execute_create_table(c.clone())?.into(),
+            Statement::CreateIndex(c) => self.execute_create_index(c.clone())?.into(),
+            Statement::DropTable(d) => self.execute_drop_table(d.clone())?.into(),
+            Statement::DropIndex(d) => self.execute_drop_index(d.clone())?.into(),
+            Statement::CreateMaterializedView(c) => {
+                self.execute_create_materialized_view(c)?.into()
+            }
+            Statement::DropMaterializedView(d) => self.execute_drop_materialized_view(d)?.into(),
+            Statement::RefreshMaterializedView(name) => {
+                self.execute_refresh_materialized_view(name)?.into()
+            }
+            Statement::Reindex(name) => self.execute_reindex(name)?.into(),
+            Statement::Copy(c) => self.execute_copy(c)?.into(),
+            Statement::AlterTable(a) => self.execute_alter_table(a.clone())?.into(),
+            Statement::ShowTables => self.execute_show_tables()?.into(),
+            Statement::ShowTransactions => self.execute_show_transactions()?.into(),
+            Statement::ShowTransactionStats => self.execute_show_transaction_stats()?.into(),
             Statement::DescribeTable(table_name) => {
-                let result = self.execute_describe_table(table_name.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Table described".to_string(),
-                    },
-                }
-            }
-            Statement::AlterTable(a) => {
-                let result = self.execute_alter_table(a.clone())?;
-                StreamingQueryResult::Definition {
-                    message: match result {
-                        QueryResult::Definition { message } => message,
-                        _ => "Table altered".to_string(),
-                    },
-                }
-            }
-            Statement::BeginTransaction => {
-                let txn_id = self.db.begin_transaction()?;
-                self.begin_txn_context(txn_id);
-                StreamingQueryResult::Definition {
-                    message: format!("Transaction {} started", txn_id),
-                }
+                self.execute_describe_table(table_name.clone())?.into()
             }
-            Statement::CommitTransaction => {
-                let _txn_id_opt = self.current_txn_id();
-                if let Some(txn_id) = _txn_id_opt {
-                    self.db.commit_transaction(txn_id)?;
-                    self.clear_txn_context();
-                    StreamingQueryResult::Definition {
-                        message: format!("Transaction {} committed", txn_id),
-                    }
-                } else {
-                    StreamingQueryResult::Definition {
-                        message: "No active transaction".to_string(),
-                    }
-                }
-            }
-            Statement::RollbackTransaction => {
-                let _txn_id_opt = self.current_txn_id();
-                if let Some(txn_id) = _txn_id_opt {
-                    // 🔑 Replay undo log BEFORE clearing the transaction context.
-                    // execute_update/execute_delete recorded old values for rows
-                    // they modified directly in storage. We replay those here to
-                    // restore the pre-transaction state.
-                    if let Ok(ctx) = self.db.txn_coordinator.get_context(txn_id) {
-                        let undo_log = std::mem::take(&mut *ctx.undo_log.write());
-                        for delta in undo_log.into_iter().rev() {
-                            match delta {
-                                crate::txn::coordinator::DeltaOperation::Update(
-                                    row_id,
-                                    table_name,
-                                    old_value,
-                                ) => {
-                                    let old_row = std::sync::Arc::try_unwrap(old_value)
-                                        .unwrap_or_else(|arc| (*arc).clone());
-                                    if let Ok(schema) = self.db.get_table_schema(&table_name) {
-                                        let _ = self.db.update_row_in_table_with_schema(
-                                            &table_name,
-                                            row_id,
-                                            old_row.clone(),
-                                            old_row,
-                                            &schema,
-                                        );
-                                    }
-                                }
-                                crate::txn::coordinator::DeltaOperation::Delete(
-                                    _row_id,
-                                    table_name,
-                                    old_value,
-                                ) => {
-                                    let old_row = std::sync::Arc::try_unwrap(old_value)
-                                        .unwrap_or_else(|arc| (*arc).clone());
-                                    let _ = self.db.insert_row_to_table(&table_name, old_row);
-                                }
-                                crate::txn::coordinator::DeltaOperation::Insert(_, _, _) => {
-                                    // INSERT undo: write_set INSERT was never committed to store.
-                                }
-                            }
-                        }
-                    }
-                    self.db.rollback_transaction(txn_id)?;
-                    self.clear_txn_context();
-                    StreamingQueryResult::Definition {
-                        message: format!("Transaction {} rolled back", txn_id),
-                    }
-                } else {
-                    StreamingQueryResult::Definition {
-                        message: "No active transaction".to_string(),
-                    }
-                }
+            Statement::BeginTransaction => self.execute_begin_transaction()?.into(),
+            Statement::CommitTransaction => self.execute_commit_transaction()?.into(),
+            Statement::RollbackTransaction => self.execute_rollback_transaction()?.into(),
+            Statement::SetVariable { name, value } => {
+                self.execute_set_variable(name, value.clone())?.into()
             }
+            Statement::ShowVariable(name) => self.execute_show_variable(name)?.into(),
+            Statement::Explain(inner) => self.execute_explain(inner)?.into(),
         };
-        Ok(result.with_max_rows(max_rows))
+        let cap = self.streaming_config().materialize_capacity;
+        Ok(result.with_max_rows(max_rows).with_size_hint(None, cap))
     }
 
+    #[deprecated(note = "use `execute_streaming_ref`, which takes the statement by reference")]
     pub fn execute_streaming(&self, stmt: Statement) -> Result<StreamingQueryResult> {
         self.execute_streaming_ref(&stmt)
     }
 
-    /// Execute SELECT statement
-    fn execute_select(&self, stmt: SelectStmt) -> Result<QueryResult> {
-        self.execute_select_internal(&stmt)
-    }
-
     /// Rewrite a SELECT's FROM clause so that any reference to a CTE name
     /// becomes a `TableRef::Subquery` over the CTE's body.
     ///
@@ -2127,6 +2127,123 @@ impl QueryExecutor {
         }
     }
 
+    /// Replace every FROM reference to a table with VIRTUAL generated columns
+    /// by a derived table computing them:
+    /// `t` → `(SELECT a, b, <expr> AS v FROM t) AS t`.
+    fn inline_virtual_columns(&self, mut stmt: SelectStmt) -> Result<SelectStmt> {
+        if let Some(from) = stmt.from.as_mut() {
+            self.rewrite_virtual_table_refs(from)?;
+        }
+        Ok(stmt)
+    }
+
+    /// Normalize the select list of a top-level SELECT:
+    ///
+    /// - `SELECT *, expr` over a single table expands `*` to the table's
+    ///   columns (projection paths only understand a lone `*`).
+    /// - Without aggregates, `ORDER BY alias` is replaced by the aliased
+    ///   expression, so `ORDER BY dist` sorts (and reaches the vector index
+    ///   path) exactly like `ORDER BY emb <-> [...]`.
+    fn resolve_select_list(&self, mut stmt: SelectStmt) -> SelectStmt {
+        if stmt.columns.len() > 1 && stmt.columns.iter().any(|c| matches!(c, SelectColumn::Star)) {
+            if let Some(TableRef::Table { name, .. }) = stmt.from.as_ref() {
+                if let Ok(schema) = self.db.get_table_schema(name) {
+                    stmt.columns = std::mem::take(&mut stmt.columns)
+                        .into_iter()
+                        .flat_map(|c| match c {
+                            SelectColumn::Star => schema
+                                .columns
+                                .iter()
+                                .map(|d| SelectColumn::Column(d.name.clone()))
+                                .collect(),
+                            other => vec![other],
+                        })
+                        .collect();
+                }
+            }
+        }
+
+        // UNNEST expands rows first and sorts the expanded rows by alias itself
+        if stmt.group_by.is_none()
+            && !self.has_aggregates(&stmt.columns)
+            && !Self::select_has_unnest(&stmt.columns)
+        {
+            if let Some(order_by) = stmt.order_by.as_mut() {
+                for item in order_by.iter_mut() {
+                    let Expr::Column(name) = &item.expr else {
+                        continue;
+                    };
+                    let aliased = stmt.columns.iter().find_map(|c| match c {
+                        SelectColumn::Expr(e, Some(alias)) if alias == name => Some(e.clone()),
+                        SelectColumn::ColumnWithAlias(col, alias) if alias == name => {
+                            Some(Expr::Column(col.clone()))
+                        }
+                        _ => None,
+                    });
+                    if let Some(expr) = aliased {
+                        item.expr = expr;
+                    }
+                }
+            }
+        }
+        stmt
+    }
+
+    fn rewrite_virtual_table_refs(&self, table_ref: &mut TableRef) -> Result<()> {
+        match table_ref {
+            TableRef::Table { name, alias } => {
+                let Ok(schema) = self.db.get_table_schema(name) else {
+                    return Ok(());
+                };
+                if !schema.has_virtual_columns() {
+                    return Ok(());
+                }
+                let virtuals = Self::virtual_column_exprs(&schema)?;
+                let columns = schema
+                    .columns
+                    .iter()
+                    .map(|c| match virtuals.iter().find(|(v, _)| *v == c.name) {
+                        Some((v, e)) => SelectColumn::Expr(e.clone(), Some(v.clone())),
+                        None => SelectColumn::Column(c.name.clone()),
+                    })
+                    .collect();
+                let query = SelectStmt {
+                    distinct: false,
+                    columns,
+                    from: Some(TableRef::Table {
+                        name: name.clone(),
+                        alias: None,
+                    }),
+                    where_clause: None,
+                    group_by: None,
+                    having: None,
+                    order_by: None,
+                    limit: None,
+                    offset: None,
+                    after_cursor: None,
+                    latest_by: None,
+                    sample_by: None,
+                    fill: None,
+                };
+                let alias = alias.clone().unwrap_or_else(|| name.clone());
+                *table_ref = TableRef::Subquery {
+                    query: Box::new(query),
+                    alias,
+                };
+            }
+            TableRef::Subquery { query, .. } => {
+                if let Some(from) = query.from.as_mut() {
+                    self.rewrite_virtual_table_refs(from)?;
+                }
+            }
+            TableRef::Join { left, right, .. } => {
+                self.rewrite_virtual_table_refs(left)?;
+                self.rewrite_virtual_table_refs(right)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Detect direct self-reference (the CTE body names itself) or forward
     /// reference (names a CTE defined later). Both are unsupported in v1.
     fn check_recursive_ref(
@@ -2242,6 +2359,7 @@ impl QueryExecutor {
             crate::sql::ast::SetOp::Union => {
                 if !all {
                     // UNION (without ALL): deduplicate rows.
+                    query_memory::charge_rows(&combined, "UNION")?;
                     let mut seen = std::collections::HashSet::new();
                     combined.retain(|row| seen.insert(row.clone()));
                 }
@@ -2298,7 +2416,9 @@ impl QueryExecutor {
                     // Check if an index exists on this column.
                     let col_name = &schema.columns[pos].name;
                     let index_key = format!("{}.{}", table_name, col_name);
-                    let indexed_count = if matches!(op, crate::sql::ast::BinaryOperator::Eq) {
+                    let indexed_count = if matches!(op, crate::sql::ast::BinaryOperator::Eq)
+                        && !matches!(target, Value::Null)
+                    {
                         // Equality lookup: index.get(value) → row_ids → count.
                         self.db.column_indexes.get(&index_key).and_then(|index| {
                             let idx = index.value();
@@ -2367,7 +2487,10 @@ impl QueryExecutor {
                     } else if let crate::sql::ast::Expr::Column(cn) = expr.as_ref() {
                         let bare = cn.rsplit('.').next().unwrap_or(cn);
                         if let Some(pos) = schema.get_column_position(bare) {
-                            if matches!(schema.col_types().get(pos), Some(ColumnType::Text)) {
+                            // Raw bytes only match TEXT set members
+                            if matches!(schema.col_types().get(pos), Some(ColumnType::Text))
+                                && set.iter().all(|v| matches!(v, Value::Text(_)))
+                            {
                                 let _ = store.prepare_for_query();
                                 let byte_set: std::collections::HashSet<&[u8]> = set
                                     .iter()
@@ -2381,17 +2504,15 @@ impl QueryExecutor {
                                     .collect();
                                 if *negated {
                                     // NOT IN: count rows whose value is NOT in set
-                                    // (and non-NULL). This needs a full scan.
+                                    // (NULL only for an empty set). This needs a full scan.
                                     let _ = store.flush_buffer();
                                     let pred_set = set.clone();
                                     let scanned = store.scan_projected_filtered(
                                         Some(pos),
                                         &[pos],
                                         &move |fv: Option<&Value>| {
-                                            match fv {
-                                                Some(Value::Null) | None => false,
-                                                Some(v) => !pred_set.contains(v),
-                                            }
+                                            let v = fv.unwrap_or(&Value::Null);
+                                            in_set_truth(v, &pred_set, true, false) == Some(true)
                                         },
                                     );
                                     count = scanned.len() as i64;
@@ -2413,17 +2534,8 @@ impl QueryExecutor {
                                     Some(pos),
                                     &[pos],
                                     &move |fv: Option<&Value>| {
-                                        match fv {
-                                            Some(Value::Null) | None => false,
-                                            Some(v) => {
-                                                let found = pred_set.contains(v);
-                                                if neg {
-                                                    !found
-                                                } else {
-                                                    found
-                                                }
-                                            }
-                                        }
+                                        let v = fv.unwrap_or(&Value::Null);
+                                        in_set_truth(v, &pred_set, neg, false) == Some(true)
                                     },
                                 );
                                 count = scanned.len() as i64;
@@ -2500,6 +2612,7 @@ impl QueryExecutor {
                 let mut rows: Vec<Vec<Value>> = Vec::new();
                 for (_, row) in scanned {
                     if seen.insert(row.clone()) {
+                        query_memory::charge_row(&row, "DISTINCT")?;
                         rows.push(row);
                     }
                 }
@@ -2605,7 +2718,9 @@ impl QueryExecutor {
         }
         let pos = match (left.as_ref(), right.as_ref()) {
             // col OP literal  (normal form)
-            (Expr::Column(cn), Expr::Literal(v)) => (schema.get_column_position(cn)?, v.clone()),
+            (Expr::Column(cn), Expr::Literal(v)) => {
+                (schema.get_column_position(cn)?, op.clone(), v.clone())
+            }
             // col OP negative-literal  (e.g. WHERE v = -100 → UnaryOp(Minus, Literal(100)))
             (
                 Expr::Column(cn),
@@ -2616,7 +2731,7 @@ impl QueryExecutor {
             ) => {
                 if let Expr::Literal(v) = expr.as_ref() {
                     let negated = negate_value(v)?;
-                    (schema.get_column_position(cn)?, negated)
+                    (schema.get_column_position(cn)?, op.clone(), negated)
                 } else {
                     return None;
                 }
@@ -2634,11 +2749,20 @@ impl QueryExecutor {
                     BinaryOperator::Ge => BinaryOperator::Le,
                     _ => return None,
                 };
-                return Some((p, flipped, v.clone()));
+                (p, flipped, v.clone())
             }
             _ => return None,
         };
-        Some((pos.0, op.clone(), pos.1))
+        // DECIMAL/UUID columns are stored as canonical text, so the
+        // segment-level byte comparisons don't match their value semantics.
+        if schema
+            .col_types()
+            .get(pos.0)
+            .is_some_and(|ct| ct.is_text_encoded())
+        {
+            return None;
+        }
+        Some(pos)
     }
 
     /// Parse a WHERE clause into a flat list of (col_pos, op, target)
@@ -2676,12 +2800,16 @@ impl QueryExecutor {
 
     /// Build a row predicate closure from a comparison operator + target value.
     /// The closure receives `Option<&Value>` (the filter column's value, None =
-    /// NULL) and applies the operator, treating NULLs as non-matching.
+    /// NULL) and applies the operator, treating NULLs (either side) as
+    /// non-matching.
     fn build_comparison_predicate(
         op: crate::sql::ast::BinaryOperator,
         target: Value,
     ) -> Box<dyn Fn(Option<&Value>) -> bool> {
         use crate::sql::ast::BinaryOperator;
+        if matches!(target, Value::Null) {
+            return Box::new(|_: Option<&Value>| false);
+        }
         match op {
             BinaryOperator::Eq => Box::new(move |fv: Option<&Value>| fv == Some(&target)),
             BinaryOperator::Ne => {
@@ -2808,62 +2936,15 @@ impl QueryExecutor {
         if aggs.is_empty() {
             return Ok(None);
         }
-
-        // 🚀 Fast path: COUNT + SUM/MIN/MAX WHERE text_col = 'val' — direct column
-        // scan without Vec<Value> construction. Avoids 100K allocations + 30MB memory.
-        if let Some(ref wc) = stmt.where_clause {
-            if let Expr::BinaryOp {
-                left,
-                op: crate::sql::ast::BinaryOperator::Eq,
-                right,
-            } = wc
-            {
-                if let (Expr::Column(cn), Expr::Literal(Value::Text(s))) =
-                    (left.as_ref(), right.as_ref())
-                {
-                    if let Some(fc) = schema.get_column_position(cn) {
-                        if matches!(schema.col_types().get(fc), Some(ColumnType::Text)) {
-                            // Find SUM/MIN/MAX target column.
-                            let agg_target = aggs.iter().filter_map(|a| a.col).next();
-                            let columns: Vec<String> = self
-                                .build_select_columns(&stmt.columns, schema)
-                                .unwrap_or_default();
-                            if let Some(ac) = agg_target {
-                                let has_count = aggs.iter().any(|a| a.func == "COUNT");
-                                let has_sum = aggs.iter().any(|a| a.func == "SUM");
-                                let has_min = aggs.iter().any(|a| a.func == "MIN");
-                                let has_max = aggs.iter().any(|a| a.func == "MAX");
-                                if has_count && (has_sum || has_min || has_max) && aggs.len() <= 4 {
-                                    // 🔑 Single-pass: count_sum_min_max does COUNT+SUM+MIN+MAX
-                                    // in one scan. Was previously two separate scans
-                                    // (count_min_max_text_filter + count_sum_text_filter).
-                                    let (count, sum, min, max) =
-                                        store.count_sum_min_max_text_filter(fc, s.as_str(), ac);
-                                    let mut row: Vec<Value> = Vec::new();
-                                    for a in &aggs {
-                                        match a.func.as_str() {
-                                            "COUNT" => row.push(Value::Integer(count)),
-                                            "SUM" => row.push(Value::Float(sum)),
-                                            "MIN" => row.push(Value::Float(min)),
-                                            "MAX" => row.push(Value::Float(max)),
-                                            "AVG" => row.push(Value::Float(if count > 0 {
-                                                sum / count as f64
-                                            } else {
-                                                0.0
-                                            })),
-                                            _ => row.push(Value::Null),
-                                        }
-                                    }
-                                    return Ok(Some(StreamingQueryResult::SelectReady {
-                                        columns,
-                                        rows: vec![row],
-                                    }));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        // DECIMAL/UUID values live in text segments; only COUNT can be answered
+        // without decoding them, so leave SUM/MIN/MAX to the materialized path.
+        if aggs.iter().any(|a| {
+            a.func != "COUNT"
+                && a.col
+                    .and_then(|c| schema.col_types().get(c))
+                    .is_some_and(|ct| ct.is_text_encoded())
+        }) {
+            return Ok(None);
         }
 
         // Parse WHERE into a list of (col_pos, op, target) comparisons.
@@ -2925,7 +3006,15 @@ impl QueryExecutor {
                         Some((c, o, t)) => (Some(*c), o.clone(), t.clone()),
                         None => (None, crate::sql::ast::BinaryOperator::Eq, Value::Null),
                     };
-                    let agg = store.aggregate_filtered(fcol, ac, &fop, &ftarget);
+                    // 🚀 `WHERE text_col = 'val'`: direct text-equality scan.
+                    let agg = match (fcol, &fop, &ftarget) {
+                        (Some(fc), crate::sql::ast::BinaryOperator::Eq, Value::Text(s))
+                            if matches!(schema.col_types().get(fc), Some(ColumnType::Text)) =>
+                        {
+                            store.count_sum_min_max_text_filter(fc, s.as_str(), ac)
+                        }
+                        _ => store.aggregate_filtered(fcol, ac, &fop, &ftarget),
+                    };
                     let columns: Vec<String> = self
                         .build_select_columns(&stmt.columns, schema)
                         .unwrap_or_default();
@@ -2940,27 +3029,8 @@ impl QueryExecutor {
                                     row.push(Value::Integer(agg.count));
                                 }
                             }
-                            "SUM" => {
-                                if agg.count == 0 {
-                                    row.push(Value::Null);
-                                } else if agg.has_float {
-                                    row.push(Value::Float(agg.float_sum + agg.int_sum as f64));
-                                } else {
-                                    row.push(Value::Integer(agg.int_sum));
-                                }
-                            }
-                            "AVG" => {
-                                if agg.count == 0 {
-                                    row.push(Value::Null);
-                                } else {
-                                    let s = if agg.has_float {
-                                        agg.float_sum + agg.int_sum as f64
-                                    } else {
-                                        agg.int_sum as f64
-                                    };
-                                    row.push(Value::Float(s / agg.count as f64));
-                                }
-                            }
+                            "SUM" => row.push(agg.sum.sum()?),
+                            "AVG" => row.push(agg.sum.avg()),
                             "MIN" => {
                                 if agg.count == 0 {
                                     row.push(Value::Null);
@@ -3007,9 +3077,8 @@ impl QueryExecutor {
                 let segs = store.segments_snapshot();
                 // Per-column accumulators.
                 let mut counts: Vec<i64> = vec![0; aggs.len()];
-                let mut sums: Vec<f64> = vec![0.0; aggs.len()];
-                let mut mins: Vec<f64> = vec![f64::INFINITY; aggs.len()];
-                let mut maxs: Vec<f64> = vec![f64::NEG_INFINITY; aggs.len()];
+                let mut sums: Vec<crate::types::NumericSum> =
+                    vec![crate::types::NumericSum::new(); aggs.len()];
                 let mut has_float: Vec<bool> = vec![false; aggs.len()];
                 let is_float_col: Vec<bool> = aggs
                     .iter()
@@ -3019,6 +3088,12 @@ impl QueryExecutor {
                             .unwrap_or(false)
                     })
                     .collect();
+                // Float MIN starts at NaN, which float_min() drops for any number
+                let mut mins: Vec<f64> = is_float_col
+                    .iter()
+                    .map(|&f| if f { f64::NAN } else { f64::INFINITY })
+                    .collect();
+                let mut maxs: Vec<f64> = vec![f64::NEG_INFINITY; aggs.len()];
 
                 for seg in &segs {
                     let n = seg.sst.num_rows;
@@ -3066,13 +3141,9 @@ impl QueryExecutor {
                             if !has_deletions && !has_nulls {
                                 // 🚀 Fully unchecked SIMD loop — no branches.
                                 for &v in raw.iter().take(nvals) {
-                                    sums[ai] += v;
-                                    if v < mins[ai] {
-                                        mins[ai] = v;
-                                    }
-                                    if v > maxs[ai] {
-                                        maxs[ai] = v;
-                                    }
+                                    sums[ai].add_float(v);
+                                    mins[ai] = crate::types::float_min(mins[ai], v);
+                                    maxs[ai] = crate::types::float_max(maxs[ai], v);
                                 }
                                 counts[ai] += nvals as i64;
                                 has_float[ai] = true;
@@ -3084,13 +3155,9 @@ impl QueryExecutor {
                                     if has_nulls && (nulls[i / 8] >> (i % 8)) & 1 != 0 {
                                         continue;
                                     }
-                                    sums[ai] += v;
-                                    if v < mins[ai] {
-                                        mins[ai] = v;
-                                    }
-                                    if v > maxs[ai] {
-                                        maxs[ai] = v;
-                                    }
+                                    sums[ai].add_float(v);
+                                    mins[ai] = crate::types::float_min(mins[ai], v);
+                                    maxs[ai] = crate::types::float_max(maxs[ai], v);
                                     counts[ai] += 1;
                                     has_float[ai] = true;
                                 }
@@ -3101,7 +3168,7 @@ impl QueryExecutor {
                             if !has_deletions && !has_nulls {
                                 // 🚀 Fully unchecked SIMD loop.
                                 for &v in raw.iter().take(nvals) {
-                                    sums[ai] += v as f64;
+                                    sums[ai].add_int(v);
                                     let vf = v as f64;
                                     if vf < mins[ai] {
                                         mins[ai] = vf;
@@ -3119,7 +3186,7 @@ impl QueryExecutor {
                                     if has_nulls && (nulls[i / 8] >> (i % 8)) & 1 != 0 {
                                         continue;
                                     }
-                                    sums[ai] += v as f64;
+                                    sums[ai].add_int(v);
                                     let vf = v as f64;
                                     if vf < mins[ai] {
                                         mins[ai] = vf;
@@ -3142,22 +3209,8 @@ impl QueryExecutor {
                     let cnt = counts[ai];
                     match agg.func.as_str() {
                         "COUNT" => row.push(Value::Integer(cnt)),
-                        "SUM" => {
-                            if cnt == 0 {
-                                row.push(Value::Null);
-                            } else if has_float[ai] {
-                                row.push(Value::Float(sums[ai]));
-                            } else {
-                                row.push(Value::Integer(sums[ai] as i64));
-                            }
-                        }
-                        "AVG" => {
-                            if cnt == 0 {
-                                row.push(Value::Null);
-                            } else {
-                                row.push(Value::Float(sums[ai] / cnt as f64));
-                            }
-                        }
+                        "SUM" => row.push(sums[ai].sum()?),
+                        "AVG" => row.push(sums[ai].avg()),
                         "MIN" => {
                             if cnt == 0 {
                                 row.push(Value::Null);
@@ -3207,20 +3260,16 @@ impl QueryExecutor {
         // Build the scan predicate from the primary comparison. Honors the
         // actual operator (previously this discarded `op` and always compared
         // with equality, silently turning `id > 49000` into `id == 49000`).
-        let pred: Box<dyn Fn(Option<&Value>) -> bool> = match filter_col {
-            Some(_) => {
-                let (_, op, target) = (
-                    comparisons[0].0,
-                    comparisons[0].1.clone(),
-                    comparisons[0].2.clone(),
-                );
-                Self::build_comparison_predicate(op, target)
-            }
-            None => Box::new(|_| true),
+        // Zone maps let the store skip blocks that can't satisfy it.
+        let scanned_raw = match filter_col {
+            Some(fc) => {
+                let (_, op, target) = &comparisons[0];
+                let pred = Self::build_comparison_predicate(op.clone(), target.clone());
+                store.scan_projected_compare(fc, op, target, &scan_cols, &*pred)
+            }
+            None => store.scan_projected_filtered(None, &scan_cols, &|_| true),
         };
 
-        let scanned_raw = store.scan_projected_filtered(filter_col, &scan_cols, &*pred);
-
         // Apply post-filter comparisons (AND of remaining predicates) on the
         // projected rows. Each post-comparison's column is looked up by its
         // position in scan_cols.
@@ -3271,47 +3320,22 @@ impl QueryExecutor {
                     };
                     result_row.push(Value::Integer(n as i64));
                 }
-                "SUM" => {
-                    // SUM ignores NULLs; SUM over zero non-NULL values is NULL.
-                    let non_null: Vec<&Value> = scanned
-                        .iter()
-                        .filter_map(|(_, row)| col_idx_in_scan.and_then(|ci| row.get(ci)))
-                        .filter(|v| !matches!(v, Value::Null))
-                        .collect();
-                    if non_null.is_empty() {
-                        result_row.push(Value::Null);
-                    } else {
-                        // Return Integer for all-integer columns (consistency), else Float.
-                        let all_int = non_null.iter().all(|v| matches!(v, Value::Integer(_)));
-                        if all_int {
-                            let s: i64 = non_null
-                                .iter()
-                                .filter_map(|v| {
-                                    if let Value::Integer(i) = v {
-                                        Some(*i)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .filter(|&v| v != i64::MIN)
-                                .sum();
-                            result_row.push(Value::Integer(s));
-                        } else {
-                            let s: f64 = non_null
-                                .iter()
-                                .filter_map(|v| {
-                                    if let Value::Float(f) = v {
-                                        Some(*f)
-                                    } else if let Value::Integer(i) = v {
-                                        Some(*i as f64)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .sum();
-                            result_row.push(Value::Float(s));
+                "SUM" | "AVG" => {
+                    // NULLs are skipped; over zero non-NULL values the result is NULL.
+                    let mut acc = crate::types::NumericSum::new();
+                    for (_, row) in &scanned {
+                        match col_idx_in_scan.and_then(|ci| row.get(ci)) {
+                            Some(Value::Integer(i64::MIN)) | None => {} // MIN = NULL sentinel
+                            Some(v) => {
+                                acc.add(v);
+                            }
                         }
                     }
+                    if a.func == "SUM" {
+                        result_row.push(acc.sum()?);
+                    } else {
+                        result_row.push(acc.avg());
+                    }
                 }
                 "MIN" => {
                     // 🔑 Handle Integer columns too (was Float-only, so Integer
@@ -3342,7 +3366,6 @@ impl QueryExecutor {
                                 }
                             })
                         })
-                        .filter(|v| !v.is_nan())
                         .collect();
                     // 🚨 Text MIN: was missing → returned NULL for TEXT columns.
                     let texts: Vec<String> = scanned
@@ -3361,7 +3384,11 @@ impl QueryExecutor {
                         result_row.push(Value::Integer(*ints.iter().min().unwrap()));
                     } else if !floats.is_empty() {
                         result_row.push(Value::Float(
-                            floats.iter().cloned().fold(f64::INFINITY, f64::min),
+                            floats
+                                .iter()
+                                .cloned()
+                                .reduce(crate::types::float_min)
+                                .unwrap(),
                         ));
                     } else if !texts.is_empty() {
                         // Alphabetical min (SQL standard for TEXT).
@@ -3398,7 +3425,6 @@ impl QueryExecutor {
                                 }
                             })
                         })
-                        .filter(|v| !v.is_nan())
                         .collect();
                     // 🚨 Text MAX: was missing → returned NULL for TEXT columns.
                     let texts: Vec<String> = scanned
@@ -3417,7 +3443,11 @@ impl QueryExecutor {
                         result_row.push(Value::Integer(*ints.iter().max().unwrap()));
                     } else if !floats.is_empty() {
                         result_row.push(Value::Float(
-                            floats.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                            floats
+                                .iter()
+                                .cloned()
+                                .reduce(crate::types::float_max)
+                                .unwrap(),
                         ));
                     } else if !texts.is_empty() {
                         // Alphabetical max (SQL standard for TEXT).
@@ -3427,29 +3457,6 @@ impl QueryExecutor {
                         result_row.push(Value::Null);
                     }
                 }
-                "AVG" => {
-                    // AVG ignores NULLs; AVG over zero non-NULL values is NULL.
-                    let nums: Vec<f64> = scanned
-                        .iter()
-                        .filter_map(|(_, row)| {
-                            col_idx_in_scan.and_then(|ci| row.get(ci)).and_then(|v| {
-                                if let Value::Float(f) = v {
-                                    Some(*f)
-                                } else if let Value::Integer(i) = v {
-                                    Some(*i as f64)
-                                } else {
-                                    None
-                                }
-                            })
-                        })
-                        .collect();
-                    if nums.is_empty() {
-                        result_row.push(Value::Null);
-                    } else {
-                        let sum: f64 = nums.iter().sum();
-                        result_row.push(Value::Float(sum / nums.len() as f64));
-                    }
-                }
                 _ => return Ok(None), // unsupported function
             }
         }
@@ -3612,7 +3619,7 @@ impl QueryExecutor {
             let mut group_counts: Vec<i64> = Vec::with_capacity(16);
             // Per-group, per-agg accumulators.
             let n_aggs = gb_aggs.len();
-            let mut group_sums: Vec<Vec<f64>> =
+            let mut group_sums: Vec<Vec<crate::types::NumericSum>> =
                 (0..n_aggs).map(|_| Vec::with_capacity(16)).collect();
             let mut group_mins: Vec<Vec<f64>> =
                 (0..n_aggs).map(|_| Vec::with_capacity(16)).collect();
@@ -3692,8 +3699,12 @@ impl QueryExecutor {
                                 group_keys.push(boxed.clone());
                                 group_counts.push(0);
                                 for ai in 0..n_aggs {
-                                    group_sums[ai].push(0.0);
-                                    group_mins[ai].push(f64::INFINITY);
+                                    group_sums[ai].push(crate::types::NumericSum::new());
+                                    group_mins[ai].push(if agg_is_float[ai] {
+                                        f64::NAN
+                                    } else {
+                                        f64::INFINITY
+                                    });
                                     group_maxs[ai].push(f64::NEG_INFINITY);
                                 }
                                 key_index.insert(boxed, idx);
@@ -3715,8 +3726,12 @@ impl QueryExecutor {
                                         .push(std::str::from_utf8(key_bytes).unwrap_or("").into());
                                     group_counts.push(0);
                                     for ai in 0..n_aggs {
-                                        group_sums[ai].push(0.0);
-                                        group_mins[ai].push(f64::INFINITY);
+                                        group_sums[ai].push(crate::types::NumericSum::new());
+                                        group_mins[ai].push(if agg_is_float[ai] {
+                                            f64::NAN
+                                        } else {
+                                            f64::INFINITY
+                                        });
                                         group_maxs[ai].push(f64::NEG_INFINITY);
                                     }
                                     if group_keys.len() >= LINEAR_THRESHOLD {
@@ -3750,18 +3765,16 @@ impl QueryExecutor {
                             if need_minmax {
                                 for (i, &v) in raw.iter().enumerate().take(n) {
                                     let gi = row_groups[i] as usize;
-                                    group_sums[ai][gi] += v;
-                                    if v < group_mins[ai][gi] {
-                                        group_mins[ai][gi] = v;
-                                    }
-                                    if v > group_maxs[ai][gi] {
-                                        group_maxs[ai][gi] = v;
-                                    }
+                                    group_sums[ai][gi].add_float(v);
+                                    group_mins[ai][gi] =
+                                        crate::types::float_min(group_mins[ai][gi], v);
+                                    group_maxs[ai][gi] =
+                                        crate::types::float_max(group_maxs[ai][gi], v);
                                 }
                             } else {
                                 // 🚀 Sum-only path: no min/max branches, auto-vectorizable.
                                 for (i, &v) in raw.iter().enumerate().take(n) {
-                                    group_sums[ai][row_groups[i] as usize] += v;
+                                    group_sums[ai][row_groups[i] as usize].add_float(v);
                                 }
                             }
                         } else {
@@ -3769,8 +3782,8 @@ impl QueryExecutor {
                             if need_minmax {
                                 for (i, &v) in raw.iter().enumerate().take(n) {
                                     let gi = row_groups[i] as usize;
+                                    group_sums[ai][gi].add_int(v);
                                     let vf = v as f64;
-                                    group_sums[ai][gi] += vf;
                                     if vf < group_mins[ai][gi] {
                                         group_mins[ai][gi] = vf;
                                     }
@@ -3780,7 +3793,7 @@ impl QueryExecutor {
                                 }
                             } else {
                                 for (i, &v) in raw.iter().enumerate().take(n) {
-                                    group_sums[ai][row_groups[i] as usize] += v as f64;
+                                    group_sums[ai][row_groups[i] as usize].add_int(v);
                                 }
                             }
                         }
@@ -3825,8 +3838,12 @@ impl QueryExecutor {
                             group_keys.push(boxed.clone());
                             group_counts.push(0);
                             for ai in 0..n_aggs {
-                                group_sums[ai].push(0.0);
-                                group_mins[ai].push(f64::INFINITY);
+                                group_sums[ai].push(crate::types::NumericSum::new());
+                                group_mins[ai].push(if agg_is_float[ai] {
+                                    f64::NAN
+                                } else {
+                                    f64::INFINITY
+                                });
                                 group_maxs[ai].push(f64::NEG_INFINITY);
                             }
                             key_index.insert(boxed, idx);
@@ -3837,18 +3854,16 @@ impl QueryExecutor {
                             if let Some(ref fs) = agg_segs[ai] {
                                 if agg_is_float[ai] {
                                     if let Some(v) = fs.get_f64(i) {
-                                        group_sums[ai][idx] += v;
-                                        if v < group_mins[ai][idx] {
-                                            group_mins[ai][idx] = v;
-                                        }
-                                        if v > group_maxs[ai][idx] {
-                                            group_maxs[ai][idx] = v;
-                                        }
+                                        group_sums[ai][idx].add_float(v);
+                                        group_mins[ai][idx] =
+                                            crate::types::float_min(group_mins[ai][idx], v);
+                                        group_maxs[ai][idx] =
+                                            crate::types::float_max(group_maxs[ai][idx], v);
                                     }
                                 } else {
                                     if let Some(v) = fs.get_i64(i) {
+                                        group_sums[ai][idx].add_int(v);
                                         let vf = v as f64;
-                                        group_sums[ai][idx] += vf;
                                         if vf < group_mins[ai][idx] {
                                             group_mins[ai][idx] = vf;
                                         }
@@ -3903,28 +3918,14 @@ impl QueryExecutor {
                             .position(|a| a.func == fname && a.col == agg_col);
                         match fname.as_str() {
                             "COUNT" => row.push(Value::Integer(cnt)),
-                            "SUM" => {
-                                if let Some(ai) = ai {
-                                    if agg_is_float[ai] {
-                                        row.push(Value::Float(group_sums[ai][gi]));
-                                    } else {
-                                        row.push(Value::Integer(group_sums[ai][gi] as i64));
-                                    }
-                                } else {
-                                    row.push(Value::Null);
-                                }
-                            }
-                            "AVG" => {
-                                if let Some(ai) = ai {
-                                    if cnt > 0 {
-                                        row.push(Value::Float(group_sums[ai][gi] / cnt as f64));
-                                    } else {
-                                        row.push(Value::Null);
-                                    }
-                                } else {
-                                    row.push(Value::Null);
-                                }
-                            }
+                            "SUM" => match ai {
+                                Some(ai) => row.push(group_sums[ai][gi].sum()?),
+                                None => row.push(Value::Null),
+                            },
+                            "AVG" => match ai {
+                                Some(ai) => row.push(group_sums[ai][gi].avg()),
+                                None => row.push(Value::Null),
+                            },
                             "MIN" => {
                                 if let Some(ai) = ai {
                                     if agg_is_float[ai] {
@@ -4062,13 +4063,31 @@ impl QueryExecutor {
         let stmt: &SelectStmt = if stmt.columns.iter().any(|c| {
             matches!(
                 c,
-                crate::sql::ast::SelectColumn::Expr(crate::sql::ast::Expr::Subquery(_), _)
+                crate::sql::ast::SelectColumn::Expr(
+                    crate::sql::ast::Expr::Subquery(_) | crate::sql::ast::Expr::Exists(_),
+                    _
+                )
             )
         }) {
             resolved_select_stmt = {
                 let mut s = stmt.clone();
-                for col in &mut s.columns {
-                    if let crate::sql::ast::SelectColumn::Expr(ref mut expr, _) = col {
+                for (idx, col) in s.columns.iter_mut().enumerate() {
+                    if let crate::sql::ast::SelectColumn::Expr(ref mut expr, ref mut alias) = col {
+                        // Correlated: evaluated per row by the materialized
+                        // path, under the name the streaming path would give
+                        if let Some(correlated) = match expr {
+                            Expr::Subquery(sub) => self.correlate(CorrelatedKind::Scalar, sub),
+                            Expr::Exists(sub) => self.correlate(CorrelatedKind::Exists, sub),
+                            _ => None,
+                        } {
+                            *expr = correlated;
+                            alias.get_or_insert_with(|| format!("expr_{}", idx));
+                            continue;
+                        }
+                        if let Expr::Exists(sub) = expr {
+                            *expr = Expr::Literal(Value::Bool(self.subquery_has_rows(sub)?));
+                            continue;
+                        }
                         let sub = match expr {
                             crate::sql::ast::Expr::Subquery(s) => Some(s.clone()),
                             _ => None,
@@ -4104,6 +4123,24 @@ impl QueryExecutor {
             stmt
         };
 
+        // UNNEST and LATEST BY change the row count, bucketed aggregation
+        // and keyset pages have their own scans, the approximate counts
+        // have no columnar pushdown and correlated subqueries run per row,
+        // so these only run on the materialized path
+        if Self::select_has_unnest(&stmt.columns)
+            || stmt
+                .columns
+                .iter()
+                .any(|c| matches!(c, SelectColumn::Expr(e, _) if Self::contains_correlated(e)))
+            || Self::is_time_bucket_select(stmt)
+            || stmt.latest_by.is_some()
+            || stmt.after_cursor.is_some()
+            || Self::select_calls_function(&stmt.columns, "COUNT_ESTIMATE")
+            || Self::select_calls_function(&stmt.columns, "APPROX_COUNT_DISTINCT")
+        {
+            return self.materialize_as_streaming(stmt);
+        }
+
         // 🚀 Fast path: Text search (MATCH AGAINST), spatial (ST_WITHIN/ST_KNN),
         // and ORDER BY ST_DISTANCE must go through execute_select_internal which
         // has the index pushdown paths. Check this BEFORE the ColSegmentStore S9
@@ -4192,8 +4229,14 @@ impl QueryExecutor {
                 if self.db.has_col_segment_store(table_name) {
                     if let Ok(store) = self.db.get_or_create_col_segment_store(table_name, &[]) {
                         let _ = store.flush_buffer();
-                        // ORDER BY LIMIT (no aggregate): full scan + in-memory sort.
+                        // ORDER BY LIMIT (no aggregate): full scan + in-memory sort,
+                        // unless an index already walks the rows in ORDER BY order.
                         if stmt.order_by.is_some() && !self.has_aggregates(&stmt.columns) {
+                            if stmt.limit.is_some() && !stmt.distinct {
+                                if let Some(result) = self.try_optimize_index_order_by(stmt)? {
+                                    return Ok(result.into());
+                                }
+                            }
                             let schema = self.db.get_table_schema(table_name)?;
                             let result = self.execute_full_scan_via_col_segment(
                                 stmt, table_name, &schema, &store,
@@ -4247,6 +4290,7 @@ impl QueryExecutor {
                                 let mut rows: Vec<Vec<Value>> = Vec::new();
                                 for (_, row) in scanned {
                                     if seen.insert(row.clone()) {
+                                        query_memory::charge_row(&row, "DISTINCT")?;
                                         rows.push(row);
                                     }
                                 }
@@ -4427,20 +4471,43 @@ impl QueryExecutor {
             self.optimizer.optimize_select(stmt, &[])?
         };
 
-        // For PointQuery/RangeQuery, the plan already has resolved values — use original stmt.
-        // For FullScan, WHERE still contains Parameter nodes — substitute needed.
+        // The plan already has resolved values for its index probe, but the
+        // statement and post_filters still hold Parameter nodes, and the
+        // streams built below evaluate them lazily — after the caller has
+        // cleared the bound params. Bind them to literals up front.
         //
         // post_filters are applied AFTER index row fetch: the index narrows to a small
         // candidate set (e.g., 10 rows), then post_filters further filter in-memory.
         // This replaces the old behavior of falling back to full table scan when
         // post_filters were present.
-        let post_filters = &plan.post_filters;
-        match plan.scan_method {
+        let resolved_stmt;
+        let bound_filters;
+        let (stmt, post_filters) = if has_params {
+            let params = self.evaluator.get_params();
+            resolved_stmt = self.substitute_params_stmt(stmt)?;
+            bound_filters = plan
+                .post_filters
+                .iter()
+                .map(|f| Self::substitute_expr(f, &params))
+                .collect::<Result<Vec<_>>>()?;
+            (&resolved_stmt, &bound_filters)
+        } else {
+            (stmt, &plan.post_filters)
+        };
+        let estimated_rows = plan.estimated_rows;
+        let result = match plan.scan_method {
             super::optimizer::ScanMethod::PointQuery {
                 ref table,
                 ref column,
                 ref value,
-            } => self.execute_point_query_streaming(stmt, table, column, value, post_filters),
+            } => self.execute_point_query_streaming(
+                stmt,
+                table,
+                column,
+                value,
+                post_filters,
+                plan.estimated_rows,
+            ),
             super::optimizer::ScanMethod::RangeQuery {
                 ref table,
                 ref column,
@@ -4457,11 +4524,10 @@ impl QueryExecutor {
                 end,
                 end_inclusive,
                 post_filters,
+                plan.estimated_rows,
             ),
             super::optimizer::ScanMethod::FullScan { .. } if has_params => {
-                // FullScan with params: need to substitute WHERE for correct evaluation
-                let resolved = self.substitute_params_stmt(stmt)?;
-                self.execute_full_scan_streaming(&resolved, plan.scan_method.table_name())
+                self.execute_full_scan_streaming(stmt, plan.scan_method.table_name())
             }
             super::optimizer::ScanMethod::FullScan { ref table } => {
                 // 🚀 DISTINCT via column value index: SELECT DISTINCT col FROM table
@@ -4480,6 +4546,13 @@ impl QueryExecutor {
                         return Ok(result);
                     }
                 }
+                // 🚀 ORDER BY an indexed column + LIMIT: walk the index in order
+                // and stop after LIMIT rows instead of scanning the table.
+                if stmt.order_by.is_some() && stmt.limit.is_some() && !stmt.distinct {
+                    if let Some(result) = self.try_optimize_index_order_by(stmt)? {
+                        return Ok(result.into());
+                    }
+                }
                 // 🚀 Streaming Top-K: when ORDER BY + LIMIT (no OFFSET) on full scan,
                 // use a bounded heap instead of materializing all rows + sorting.
                 if stmt.order_by.is_some()
@@ -4501,29 +4574,59 @@ impl QueryExecutor {
             super::optimizer::ScanMethod::IndexIntersection {
                 ref table,
                 ref column1,
-                ref value1,
+                ref probe1,
                 ref column2,
-                ref value2,
+                ref probe2,
             } => self.execute_index_intersection_streaming(
                 stmt,
                 table,
-                column1,
-                value1,
-                column2,
-                value2,
+                (column1, probe1),
+                (column2, probe2),
                 post_filters,
             ),
+            // ORDER BY / DISTINCT are applied by the materialized path
+            super::optimizer::ScanMethod::ArrayContains {
+                ref table,
+                ref column,
+                ref value,
+            } if stmt.order_by.is_none() && !stmt.distinct => {
+                self.execute_array_contains_streaming(stmt, table, column, value, post_filters)
+            }
             _ => {
                 // Fallback to materialized path (handles params via eval())
                 self.materialize_as_streaming(stmt)
             }
+        }?;
+        let config = self.streaming_config();
+        let result = result
+            .with_max_rows(self.db.max_result_rows)
+            .with_size_hint(Some(estimated_rows), config.materialize_capacity);
+        if Self::expects_small_result(stmt, estimated_rows, config.batch_size) {
+            return result.prefetch(config.batch_size);
         }
+        Ok(result)
+    }
+
+    /// Whether a SELECT should return at most `batch` rows, going by the
+    /// optimizer's estimate or, without ORDER BY / DISTINCT, by LIMIT + OFFSET
+    fn expects_small_result(stmt: &SelectStmt, estimated_rows: usize, batch: usize) -> bool {
+        // A zero estimate means the optimizer had no statistics
+        let estimate = Some(estimated_rows).filter(|&n| n > 0);
+        let limit = stmt
+            .limit
+            .filter(|_| stmt.order_by.is_none() && !stmt.distinct)
+            .map(|n| n.saturating_add(stmt.offset.unwrap_or(0)));
+        let expected = match (estimate, limit) {
+            (Some(e), Some(l)) => Some(e.min(l)),
+            (e, l) => e.or(l),
+        };
+        expected.is_some_and(|n| n <= batch)
     }
 
     /// Check if an expression tree contains any Subquery node.
     fn expr_contains_subquery(expr: &Expr) -> bool {
         match expr {
-            Expr::Subquery(_) => true,
+            Expr::Subquery(_) | Expr::Exists(_) | Expr::Correlated(_) => true,
             Expr::BinaryOp { left, right, .. } => {
                 Self::expr_contains_subquery(left) || Self::expr_contains_subquery(right)
             }
@@ -4562,7 +4665,10 @@ impl QueryExecutor {
             distinct: stmt.distinct,
             group_by: stmt.group_by.clone(),
             having: stmt.having.clone(),
+            after_cursor: stmt.after_cursor.clone(),
             latest_by: stmt.latest_by.clone(),
+            sample_by: stmt.sample_by.clone(),
+            fill: stmt.fill,
         })
     }
 
@@ -4581,7 +4687,7 @@ impl QueryExecutor {
             // silently filters out every row). Without this, a 3-level nested
             // `x IN (SELECT ... WHERE y IN (SELECT ...))` routed through the
             // ColSegmentStore fast path never resolved the inner subquery.
-            Expr::Subquery(_) => true,
+            Expr::Subquery(_) | Expr::Exists(_) | Expr::Correlated(_) => true,
             Expr::FunctionCall { name, args, .. } => {
                 matches!(
                     name.to_lowercase().as_str(),
@@ -4634,6 +4740,25 @@ impl QueryExecutor {
         }
     }
 
+    /// Streaming full scan replacing an abandoned index probe. The WHERE
+    /// clause still holds `?` placeholders for prepared statements.
+    fn scan_instead_of_index(
+        &self,
+        stmt: &SelectStmt,
+        table: &str,
+    ) -> Result<StreamingQueryResult> {
+        if Self::contains_parameter_stmt(stmt) {
+            let resolved = self.substitute_params_stmt(stmt)?;
+            return self.execute_full_scan_streaming(&resolved, table);
+        }
+        self.execute_full_scan_streaming(stmt, table)
+    }
+
+    /// Adaptive re-optimization counters of this executor's optimizer
+    pub fn optimizer_stats(&self) -> super::optimizer::OptimizerStats {
+        self.optimizer.stats()
+    }
+
     /// 🔥 点查询流式扫描（使用列索引）
     ///
     /// ⚠️ 注意：这个方法通常只返回少量行（点查询），不需要批量优化
@@ -4652,6 +4777,7 @@ impl QueryExecutor {
         column: &str,
         value: &Value,
         post_filters: &[Expr],
+        estimated_rows: usize,
     ) -> Result<StreamingQueryResult> {
         let schema = self.db.get_table_schema(table)?;
         let columns = self.build_select_columns(&stmt.columns, &schema)?;
@@ -4674,6 +4800,27 @@ impl QueryExecutor {
                     .value()
                     .get_arc(value)
                     .unwrap_or_else(|_| std::sync::Arc::new(Vec::new()));
+                drop(index);
+                if self.optimizer.reoptimize_after_probe(
+                    table,
+                    column,
+                    ProbeKind::Point,
+                    estimated_rows,
+                    row_ids.len(),
+                ) {
+                    return self.scan_instead_of_index(stmt, table);
+                }
+                if let (Some(positions), [rid]) = (
+                    Self::vector_projection(stmt, &schema, post_filters),
+                    row_ids.as_slice(),
+                ) {
+                    if let Some(row) = self.db.get_table_columns(table, *rid, &positions)? {
+                        return Ok(StreamingQueryResult::SelectReady {
+                            columns,
+                            rows: vec![row],
+                        });
+                    }
+                }
                 if !row_ids.is_empty() {
                     let batch = self.db.get_table_rows_batch(table, &row_ids)?;
                     let mut result_rows: Vec<Vec<Value>> = Vec::new();
@@ -4748,16 +4895,30 @@ impl QueryExecutor {
                     })
                 }
             };
+            if let Some(positions) = Self::vector_projection(stmt, &schema, post_filters) {
+                let rows = self.db.get_table_columns(table, row_id, &positions)?;
+                return Ok(StreamingQueryResult::SelectReady {
+                    columns,
+                    rows: rows.into_iter().collect(),
+                });
+            }
+            let epoch = self.db.row_cache.fill_epoch();
             let composite_key = self.db.make_composite_key(table, row_id);
             if let Some(store) = self.db.col_segment_stores.get(table) {
                 if let Some(row) = store.get(composite_key) {
                     self.db
                         .row_cache
-                        .put(table.to_string(), row_id, row.clone());
-                    let sql_row = row_to_sql_row(&row, &schema)?;
-                    let mut prefixed = SqlRow::new();
-                    prefixed.insert("__row_id__".to_string(), Value::Integer(row_id as i64));
-                    prefixed.insert("__table__".to_string(), Value::text(table.to_string()));
+                        .fill(table, row_id, Arc::new(row.clone()), epoch);
+                    if !Self::row_passes_post_filters(&row, post_filters, &schema) {
+                        return Ok(StreamingQueryResult::SelectReady {
+                            columns,
+                            rows: vec![],
+                        });
+                    }
+                    let sql_row = row_to_sql_row(&row, &schema)?;
+                    let mut prefixed = SqlRow::new();
+                    prefixed.insert("__row_id__".to_string(), Value::Integer(row_id as i64));
+                    prefixed.insert("__table__".to_string(), Value::text(table.to_string()));
                     for (cn, v) in sql_row {
                         prefixed.insert(format!("{}.{}", table, cn), v);
                     }
@@ -4794,11 +4955,26 @@ impl QueryExecutor {
                 column,
                 crate::database::index_metadata::IndexType::Column,
             ) {
-                if let Some(index) = self.db.column_indexes.get(&index_name) {
+                if let Some(index) = self
+                    .db
+                    .column_indexes
+                    .get(&index_name)
+                    .filter(|index| !index.value().is_multi_key())
+                {
                     let row_ids = index
                         .value()
                         .get_arc(value)
                         .unwrap_or_else(|_| std::sync::Arc::new(Vec::new()));
+                    drop(index);
+                    if self.optimizer.reoptimize_after_probe(
+                        table,
+                        column,
+                        ProbeKind::Point,
+                        estimated_rows,
+                        row_ids.len(),
+                    ) {
+                        return self.scan_instead_of_index(stmt, table);
+                    }
                     // Selectivity heuristic: use index-driven row fetch for result
                     // sets up to 10000 rows. The full-scan fallback (for >10000) is
                     // used when N point lookups become slower than a sequential scan.
@@ -4943,7 +5119,16 @@ impl QueryExecutor {
         }
 
         // Fallback: use column index
-        let row_ids = self.db.query_by_column(table, column, value)?;
+        let row_ids = self.db.query_by_column_set(table, column, value)?;
+        if self.optimizer.reoptimize_after_probe(
+            table,
+            column,
+            ProbeKind::Point,
+            estimated_rows,
+            row_ids.len() as usize,
+        ) {
+            return self.scan_instead_of_index(stmt, table);
+        }
 
         if row_ids.is_empty() {
             // If the async pipeline is active, column indexes may not be built yet.
@@ -4963,17 +5148,15 @@ impl QueryExecutor {
             });
         }
 
-        // Sort row_ids and choose optimal fetch strategy
-        let mut sorted_ids = row_ids;
-        sorted_ids.sort_unstable();
-        let min_id = sorted_ids[0];
-        let max_id = *sorted_ids.last().unwrap();
-        let density = sorted_ids.len() as f64 / (max_id - min_id + 1) as f64;
+        // Choose the fetch strategy from how densely the row IDs fill their span
+        let min_id = row_ids.min().unwrap();
+        let max_id = row_ids.max().unwrap();
+        let density = row_ids.len() as f64 / (max_id - min_id + 1) as f64;
 
         // Decode full rows (before projection — post_filters need full row data)
         let decoded_rows: Vec<Vec<Value>> = if density > 0.1 {
             // Dense result set: single range scan (sequential I/O >> random I/O)
-            let id_set: std::collections::HashSet<u64> = sorted_ids.into_iter().collect();
+            let id_set = row_ids;
             let start_key = self.db.make_composite_key(table, min_id);
             let end_key = self.db.make_composite_key(table, max_id + 1);
             let schema_c = schema.clone();
@@ -4988,7 +5171,7 @@ impl QueryExecutor {
                 .into_iter()
                 .filter_map(move |(key, vd)| {
                     let rid = (key & 0xFFFFFFFF) as RowId;
-                    if !id_set.contains(&(rid as u64)) || vd.deleted {
+                    if !id_set.contains(rid) || vd.deleted {
                         return None;
                     }
                     let data = match &vd.data {
@@ -5000,6 +5183,7 @@ impl QueryExecutor {
                 .collect()
         } else {
             // Sparse result set: batch read via row cache + LSM range scan
+            let sorted_ids: Vec<RowId> = row_ids.iter().collect();
             let batch = self
                 .db
                 .get_table_rows_batch_arc(table, &sorted_ids)
@@ -5047,12 +5231,87 @@ impl QueryExecutor {
         })
     }
 
+    /// Direction (`Some(descending)`) of a single-column `ORDER BY column`
+    /// that a scan of `column`'s index or key range already produces
+    ///
+    /// Only fixed-width types qualify: their index keys sort exactly like
+    /// the values, while text keys are truncated prefixes.
+    fn index_order_direction(
+        stmt: &SelectStmt,
+        schema: &TableSchema,
+        column: &str,
+    ) -> Option<bool> {
+        if stmt.distinct {
+            return None;
+        }
+        let [OrderByExpr {
+            expr: Expr::Column(col),
+            asc,
+        }] = stmt.order_by.as_deref()?
+        else {
+            return None;
+        };
+        if col.rsplit('.').next() != Some(column) {
+            return None;
+        }
+        // `SELECT other AS col ... ORDER BY col` sorts by the alias
+        let shadowed = stmt.columns.iter().any(|c| match c {
+            SelectColumn::ColumnWithAlias(_, alias) | SelectColumn::Expr(_, Some(alias)) => {
+                alias == col
+            }
+            _ => false,
+        });
+        if shadowed {
+            return None;
+        }
+        let col_type = &schema.get_column(column)?.col_type;
+        matches!(
+            col_type,
+            ColumnType::Integer
+                | ColumnType::Float
+                | ColumnType::Timestamp
+                | ColumnType::Date
+                | ColumnType::Time
+                | ColumnType::Uuid
+        )
+        .then_some(!asc)
+    }
+
+    /// Streaming result over rows already in ORDER BY order: OFFSET and
+    /// LIMIT are applied to the stream, so the scan stops once they are met
+    fn ordered_streaming_result(
+        stmt: &SelectStmt,
+        columns: Vec<String>,
+        rows: Box<dyn Iterator<Item = Result<Vec<Value>>> + Send>,
+    ) -> StreamingQueryResult {
+        let mut to_skip = stmt.offset.unwrap_or(0);
+        let rows = rows
+            .filter(move |row| {
+                if to_skip > 0 && row.is_ok() {
+                    to_skip -= 1;
+                    return false;
+                }
+                true
+            })
+            .take(stmt.limit.unwrap_or(usize::MAX));
+        StreamingQueryResult::SelectStreaming {
+            columns,
+            rows: Box::new(rows),
+            order_by: None,
+            limit: None,
+            offset: None,
+            distinct: false,
+            max_result_rows: None,
+            size_hint: None,
+        }
+    }
+
     /// 🔥 范围查询流式扫描（智能路由：主键用 LSM scan，非主键用列索引）
     ///
     /// ## 性能优化
     /// - **主键范围查询**：使用 LSM range scan（顺序扫描，6x 提速）
     /// - **非主键查询**：使用列索引 + batch_get（减少锁竞争）
-    /// - 批次大小：1000 条（平衡内存与性能）
+    /// - 批次大小：`StreamingConfig::batch_size`（默认 1000 条，平衡内存与性能）
     /// - 内存友好：仍然是流式返回，不会一次性加载全部数据
     ///
     /// ## 边界正确性
@@ -5069,12 +5328,18 @@ impl QueryExecutor {
         end: &Value,
         end_inclusive: bool,
         post_filters: &[Expr],
+        estimated_rows: usize,
     ) -> Result<StreamingQueryResult> {
+        let schema = self.db.get_table_schema(table)?;
+        let pk_col = schema.primary_key().unwrap_or("id");
+        // Natural TEXT / UUID primary keys are ranged through their column
+        // index like any indexed column; integer keys map to LSM keys.
+        let natural_pk = column == pk_col && schema.has_natural_primary_key();
+
         // S9: ColSegmentStore tables — fall back to full scan (data not in LSM).
-        if self.db.has_col_segment_store(table) {
+        if self.db.has_col_segment_store(table) && !natural_pk {
             return self.execute_full_scan_streaming(stmt, table);
         }
-        let schema = self.db.get_table_schema(table)?;
 
         // If SELECT expressions need the full evaluator, fall back to materialized path
         if Self::select_needs_materialized(stmt) {
@@ -5086,43 +5351,47 @@ impl QueryExecutor {
             && matches!(stmt.columns[0], SelectColumn::Star)
             && stmt.order_by.is_none()
             && !stmt.distinct;
-        if is_star {
-            let pk_col = schema.primary_key().unwrap_or("id");
-            if column != pk_col {
-                let index_name = format!("{}.{}", table, column);
-                if let Some(index_ref) = self.db.column_indexes.get(&index_name) {
-                    let row_ids = index_ref.value().query_between(
-                        start,
-                        start_inclusive,
-                        end,
-                        end_inclusive,
-                    )?;
-                    drop(index_ref);
-                    if !row_ids.is_empty() || !self.db.is_async_index_pipeline_active() {
-                        let column_names: Vec<String> =
-                            schema.columns.iter().map(|c| c.name.clone()).collect();
-                        let arc_rows = self.db.get_table_rows_batch_arc(table, &row_ids)?;
-                        let skip_n = stmt.offset.unwrap_or(0);
-                        let take_n = stmt.limit.unwrap_or(usize::MAX);
-                        let rows: Vec<Vec<Value>> = arc_rows
-                            .into_iter()
-                            .filter_map(|(_, opt)| opt)
-                            .filter(|row| {
-                                post_filters.is_empty()
-                                    || Self::row_passes_post_filters(row, post_filters, &schema)
-                            })
-                            .skip(skip_n)
-                            .take(take_n)
-                            .map(|arc| match Arc::try_unwrap(arc) {
-                                Ok(row) => row,
-                                Err(arc) => (*arc).clone(),
-                            })
-                            .collect();
-                        return Ok(StreamingQueryResult::SelectReady {
-                            columns: column_names,
-                            rows,
-                        });
-                    }
+        if is_star && (column != pk_col || natural_pk) {
+            let index_name = format!("{}.{}", table, column);
+            if let Some(index_ref) = self.db.column_indexes.get(&index_name) {
+                let row_ids =
+                    index_ref
+                        .value()
+                        .query_between(start, start_inclusive, end, end_inclusive)?;
+                drop(index_ref);
+                if self.optimizer.reoptimize_after_probe(
+                    table,
+                    column,
+                    ProbeKind::Range,
+                    estimated_rows,
+                    row_ids.len(),
+                ) {
+                    return self.scan_instead_of_index(stmt, table);
+                }
+                if !row_ids.is_empty() || !self.db.is_async_index_pipeline_active() {
+                    let column_names: Vec<String> =
+                        schema.columns.iter().map(|c| c.name.clone()).collect();
+                    let arc_rows = self.db.get_table_rows_batch_arc(table, &row_ids)?;
+                    let skip_n = stmt.offset.unwrap_or(0);
+                    let take_n = stmt.limit.unwrap_or(usize::MAX);
+                    let rows: Vec<Vec<Value>> = arc_rows
+                        .into_iter()
+                        .filter_map(|(_, opt)| opt)
+                        .filter(|row| {
+                            post_filters.is_empty()
+                                || Self::row_passes_post_filters(row, post_filters, &schema)
+                        })
+                        .skip(skip_n)
+                        .take(take_n)
+                        .map(|arc| match Arc::try_unwrap(arc) {
+                            Ok(row) => row,
+                            Err(arc) => (*arc).clone(),
+                        })
+                        .collect();
+                    return Ok(StreamingQueryResult::SelectReady {
+                        columns: column_names,
+                        rows,
+                    });
                 }
             }
         }
@@ -5130,8 +5399,7 @@ impl QueryExecutor {
         let columns = self.build_select_columns(&stmt.columns, &schema)?;
 
         // 🚀 优化路径1：主键范围查询使用 LSM range scan（顺序扫描）
-        let pk_col = schema.primary_key().unwrap_or("id");
-        if column == pk_col {
+        if column == pk_col && !natural_pk {
             return self.execute_primary_key_range_streaming(
                 stmt,
                 table,
@@ -5143,58 +5411,105 @@ impl QueryExecutor {
         }
 
         // 🔧 路径2：非主键列使用列索引 + batch_get (with row cache)
-        let row_ids = self.db.query_by_column_between(
+        // Row ids are streamed from the index rather than collected: the
+        // misestimate check only counts up to the point where it would
+        // switch to a scan anyway, then a second walk feeds the fetches.
+        // ORDER BY on the range column is served by walking the index in
+        // that direction.
+        let index_order = Self::index_order_direction(stmt, &schema, column);
+        let range_iter = || {
+            if index_order == Some(true) {
+                self.db.query_by_column_between_iter_rev(
+                    table,
+                    column,
+                    start,
+                    start_inclusive,
+                    end,
+                    end_inclusive,
+                )
+            } else {
+                self.db.query_by_column_between_iter(
+                    table,
+                    column,
+                    start,
+                    start_inclusive,
+                    end,
+                    end_inclusive,
+                )
+            }
+        };
+        let probe_limit = self.optimizer.reoptimize_probe_limit(table, estimated_rows);
+        let mut probed = 0;
+        for row_id in range_iter()?.take(probe_limit.saturating_add(1)) {
+            row_id?;
+            probed += 1;
+        }
+        if self.optimizer.reoptimize_after_probe(
             table,
             column,
-            start,
-            start_inclusive,
-            end,
-            end_inclusive,
-        )?;
+            ProbeKind::Range,
+            estimated_rows,
+            probed,
+        ) {
+            return self.scan_instead_of_index(stmt, table);
+        }
 
         // 🚀 批量读取行数据（通过 row_cache 减少锁竞争和 LSM 开销）
         let db = self.db.clone();
         let table_name = table.to_string();
 
-        // Decode rows in batches (no projection yet — post_filters need full row)
-        const BATCH_SIZE: usize = 1000;
-        let total_rows = row_ids.len();
-
-        let decoded_rows: Vec<Vec<Value>> = (0..total_rows)
-            .step_by(BATCH_SIZE)
-            .flat_map(|batch_start| {
-                let batch_end = (batch_start + BATCH_SIZE).min(total_rows);
-                let batch_row_ids = &row_ids[batch_start..batch_end];
-
-                match db.get_table_rows_batch(&table_name, batch_row_ids) {
-                    Ok(results) => results
-                        .into_iter()
-                        .filter_map(|(_, opt)| opt)
-                        .collect::<Vec<_>>(),
-                    Err(_) => vec![],
-                }
-            })
-            .collect();
+        // Fetch rows lazily, one batch of `StreamingConfig::batch_size` row ids
+        // at a time, so at most one batch of row ids and decoded rows is held
+        // in memory. post_filters run on the full decoded row, then survivors
+        // are projected.
+        let batch_size = self.streaming_config().batch_size;
+        let mut row_ids = range_iter()?;
+        let post_filters = post_filters.to_vec();
+        let select_cols = stmt.columns.clone();
+        let columns_clone = columns.clone();
 
-        // Apply post_filters on full decoded rows, then project survivors
-        let result_rows: Vec<Result<Vec<Value>>> = decoded_rows
-            .into_iter()
-            .filter(|row| {
-                post_filters.is_empty() || Self::row_passes_post_filters(row, post_filters, &schema)
-            })
-            .map(|row| {
-                Ok(Self::project_row_direct(
-                    &row,
-                    &stmt.columns,
-                    &columns,
-                    &schema,
-                ))
-            })
-            .collect();
+        let batches = std::iter::from_fn(move || {
+            let batch = row_ids
+                .by_ref()
+                .take(batch_size)
+                .collect::<Result<Vec<_>>>();
+            match batch {
+                Ok(ids) if ids.is_empty() => None,
+                batch => Some(batch),
+            }
+        });
+        let rows_iter = batches.flat_map(move |batch| {
+            match batch.and_then(|ids| db.get_table_rows_batch(&table_name, &ids)) {
+                Ok(results) => results
+                    .into_iter()
+                    .filter_map(|(_, opt)| opt)
+                    .filter(|row| {
+                        post_filters.is_empty()
+                            || Self::row_passes_post_filters(row, &post_filters, &schema)
+                    })
+                    .map(|row| {
+                        Ok(Self::project_row_direct(
+                            &row,
+                            &select_cols,
+                            &columns_clone,
+                            &schema,
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+                Err(e) => vec![Err(e)],
+            }
+        });
+        if index_order.is_some() {
+            return Ok(Self::ordered_streaming_result(
+                stmt,
+                columns,
+                Box::new(rows_iter),
+            ));
+        }
 
         Ok(StreamingQueryResult::SelectStreaming {
             columns,
-            rows: Box::new(result_rows.into_iter()),
+            rows: Box::new(rows_iter),
             order_by: stmt.order_by.clone(),
             limit: stmt.limit,
             offset: stmt.offset,
@@ -5266,10 +5581,18 @@ impl QueryExecutor {
         }
 
         // 🚀 P2: 使用真正的流式迭代器（O(1) 内存占用，~20 KB）
-        let lsm_iter = self
-            .db
-            .lsm_engine
-            .scan_range_streaming(start_key, end_key)?;
+        // ORDER BY the key comes out of the scan itself (DESC scans backwards)
+        let pk_col = schema.primary_key().unwrap_or("id");
+        let key_order = Self::index_order_direction(stmt, &schema, pk_col);
+        let lsm_iter = if key_order == Some(true) {
+            self.db
+                .lsm_engine
+                .scan_range_streaming_rev(start_key, end_key)?
+        } else {
+            self.db
+                .lsm_engine
+                .scan_range_streaming(start_key, end_key)?
+        };
 
         // 转换为 SQL 行并投影
         let schema_clone = schema.clone();
@@ -5301,6 +5624,13 @@ impl QueryExecutor {
                 ))),
             }
         });
+        if key_order.is_some() {
+            return Ok(Self::ordered_streaming_result(
+                stmt,
+                columns,
+                Box::new(rows_iter),
+            ));
+        }
 
         Ok(StreamingQueryResult::SelectStreaming {
             columns,
@@ -5314,46 +5644,28 @@ impl QueryExecutor {
         })
     }
 
-    /// Execute an index intersection plan: look up both indexes, intersect row IDs,
-    /// batch-fetch matching rows, then project.
+    /// Execute an index intersection plan: read the row IDs both index probes
+    /// select, intersect them, batch-fetch the survivors, then project.
     fn execute_index_intersection_streaming(
         &self,
         stmt: &SelectStmt,
         table: &str,
-        column1: &str,
-        value1: &Value,
-        column2: &str,
-        value2: &Value,
+        side1: (&str, &IndexProbe),
+        side2: (&str, &IndexProbe),
         post_filters: &[Expr],
     ) -> Result<StreamingQueryResult> {
         let schema = self.db.get_table_schema(table)?;
         let columns = self.build_select_columns(&stmt.columns, &schema)?;
 
-        let idx1_name = format!("{}.{}", table, column1);
-        let idx2_name = format!("{}.{}", table, column2);
-
-        // Look up row IDs from first index
-        let row_ids1 = {
-            let idx_ref = self.db.column_indexes.get(&idx1_name).ok_or_else(|| {
-                MoteDBError::InvalidArgument(format!("Index {} not found", idx1_name))
-            })?;
-            idx_ref.value().get(value1)?
-        };
-        let row_id_set1: std::collections::HashSet<u64> = row_ids1.into_iter().collect();
-
-        // Look up row IDs from second index and intersect
-        let row_ids2 = {
-            let idx_ref = self.db.column_indexes.get(&idx2_name).ok_or_else(|| {
-                MoteDBError::InvalidArgument(format!("Index {} not found", idx2_name))
-            })?;
-            idx_ref.value().get(value2)?
-        };
-        let intersected: Vec<u64> = row_ids2
-            .into_iter()
-            .filter(|id| row_id_set1.contains(id))
+        let intersected: Vec<RowId> = self
+            .intersect_index_probes(table, side1, side2)?
+            .iter()
             .collect();
-
         if intersected.is_empty() {
+            // A lagging async index may not list fresh rows yet
+            if self.db.is_async_index_pipeline_active() {
+                return self.materialize_as_streaming(stmt);
+            }
             return Ok(StreamingQueryResult::SelectReady {
                 columns,
                 rows: vec![],
@@ -5409,15 +5721,7 @@ impl QueryExecutor {
                         }
                         let ord = match (&a[col_idx], &b[col_idx]) {
                             (Value::Float(fa), Value::Float(fb)) => {
-                                if fa.is_nan() && fb.is_nan() {
-                                    std::cmp::Ordering::Equal
-                                } else if fa.is_nan() {
-                                    std::cmp::Ordering::Greater
-                                } else if fb.is_nan() {
-                                    std::cmp::Ordering::Less
-                                } else {
-                                    fa.partial_cmp(fb).unwrap_or(std::cmp::Ordering::Equal)
-                                }
+                                crate::types::float_cmp(*fa, *fb)
                             }
                             (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
                             (Value::Null, _) => std::cmp::Ordering::Less,
@@ -5443,6 +5747,78 @@ impl QueryExecutor {
         Ok(StreamingQueryResult::SelectReady { columns, rows })
     }
 
+    /// Row IDs matched by both sides of an index intersection, as a bitmap
+    /// so the two sets are ANDed container by container
+    fn intersect_index_probes(
+        &self,
+        table: &str,
+        side1: (&str, &IndexProbe),
+        side2: (&str, &IndexProbe),
+    ) -> Result<RowIdSet> {
+        let probe = |(column, probe): (&str, &IndexProbe)| match probe {
+            IndexProbe::Point(value) => self.db.query_by_column_set(table, column, value),
+            IndexProbe::Range {
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+            } => self.db.query_by_column_between_set(
+                table,
+                column,
+                start,
+                *start_inclusive,
+                end,
+                *end_inclusive,
+            ),
+        };
+        let mut row_ids = probe(side1)?;
+        if !row_ids.is_empty() {
+            row_ids &= probe(side2)?;
+        }
+        Ok(row_ids)
+    }
+
+    /// `ARRAY_CONTAINS(col, value)` via the column's element index: fetch the
+    /// rows holding the element, then apply the WHERE clause and OFFSET/LIMIT
+    fn execute_array_contains_streaming(
+        &self,
+        stmt: &SelectStmt,
+        table: &str,
+        column: &str,
+        value: &Value,
+        post_filters: &[Expr],
+    ) -> Result<StreamingQueryResult> {
+        let schema = self.db.get_table_schema(table)?;
+        let columns = self.build_select_columns(&stmt.columns, &schema)?;
+
+        let mut row_ids = self.db.query_by_array_element(table, column, value)?;
+        row_ids.sort_unstable();
+        row_ids.dedup();
+        if row_ids.is_empty() {
+            // A lagging async index may not list fresh rows yet
+            if self.db.is_async_index_pipeline_active() {
+                return self.materialize_as_streaming(stmt);
+            }
+            return Ok(StreamingQueryResult::SelectReady {
+                columns,
+                rows: vec![],
+            });
+        }
+
+        let rows: Vec<Vec<Value>> = self
+            .db
+            .get_table_rows_batch_arc(table, &row_ids)?
+            .into_iter()
+            .filter_map(|(_row_id, opt_row)| opt_row)
+            .filter(|row| Self::row_passes_post_filters(row, post_filters, &schema))
+            .skip(stmt.offset.unwrap_or(0))
+            .take(stmt.limit.unwrap_or(usize::MAX))
+            .map(|row| Self::project_row_direct(&row, &stmt.columns, &columns, &schema))
+            .collect();
+
+        Ok(StreamingQueryResult::SelectReady { columns, rows })
+    }
+
     /// 🚀 Streaming Top-K via bounded heap with partial decode.
     ///
     /// Only extracts the sort column value from each row (not all columns),
@@ -5849,7 +6225,7 @@ impl QueryExecutor {
                             // This fast path's GroupAcc only tracks count/sum, so
                             // it would emit NULL for them. Fall back to the
                             // materialized path (compute_aggregate_positional).
-                            "STDDEV" | "VARIANCE" => return Ok(None),
+                            "STDDEV" | "VARIANCE" | "APPROX_COUNT_DISTINCT" => return Ok(None),
                             _ => {
                                 let col = match args.first() {
                                     Some(Expr::Column(c)) => c.as_str(),
@@ -5872,8 +6248,7 @@ impl QueryExecutor {
         }
 
         // Build HashMap from typed arrays — use &str keys (zero-alloc) from mmap.
-        // Accumulator: (count, int_sum, float_sum, has_float). Integer columns
-        // accumulate into int_sum; Float columns into float_sum. Reading an
+        // Accumulator: row count plus a typed SUM/AVG total. Reading an
         // Integer column via get_f64() reinterprets its i64 bits as f64 (e.g.
         // Integer(10) → f64::from_bits(10) ≈ 0), which silently corrupts SUM.
         // Decode according to the column's declared type.
@@ -5881,47 +6256,28 @@ impl QueryExecutor {
         let col_types = schema.col_types();
         struct GroupAcc {
             count: i64,
-            int_sum: i64,
-            float_sum: f64,
-            has_float: bool,
+            sum: crate::types::NumericSum,
         }
         impl GroupAcc {
             fn new() -> Self {
                 Self {
                     count: 0,
-                    int_sum: 0,
-                    float_sum: 0.0,
-                    has_float: false,
+                    sum: crate::types::NumericSum::new(),
                 }
             }
-            fn add(&mut self, val: f64, is_int: bool) {
+            fn add_at(
+                &mut self,
+                seg: &crate::storage::lsm::columnar::FixedSegment,
+                i: usize,
+                is_int: bool,
+            ) {
                 if is_int {
-                    self.int_sum = self.int_sum.wrapping_add(val as i64);
-                } else {
-                    if !self.has_float {
-                        self.has_float = true;
-                        self.float_sum = self.int_sum as f64;
+                    if let Some(v) = seg.get_i64(i) {
+                        self.sum.add_int(v);
                     }
-                    self.float_sum += val;
-                }
-            }
-            fn sum(&self) -> Value {
-                if self.has_float {
-                    Value::Float(self.float_sum)
-                } else {
-                    Value::Integer(self.int_sum)
-                }
-            }
-            fn avg(&self) -> Value {
-                if self.count == 0 {
-                    return Value::Null;
+                } else if let Some(v) = seg.get_f64(i) {
+                    self.sum.add_float(v);
                 }
-                let s = if self.has_float {
-                    self.float_sum
-                } else {
-                    self.int_sum as f64
-                };
-                Value::Float(s / self.count as f64)
             }
         }
         // 🔑 PERF: low-cardinality linear-scan accumulator. For GROUP BY columns
@@ -5969,15 +6325,7 @@ impl QueryExecutor {
                 acc.count += 1;
                 for (j, a) in agg_cols.iter().enumerate() {
                     if a.func == "SUM" || a.func == "AVG" {
-                        let is_int = agg_is_int[j];
-                        let v = if is_int {
-                            agg_segs[j].get_i64(i).map(|x| x as f64)
-                        } else {
-                            agg_segs[j].get_f64(i)
-                        };
-                        if let Some(v) = v {
-                            acc.add(v, is_int);
-                        }
+                        acc.add_at(&agg_segs[j], i, agg_is_int[j]);
                     }
                 }
             }
@@ -5991,8 +6339,8 @@ impl QueryExecutor {
                 }
                 for a in &agg_cols {
                     match a.func.as_str() {
-                        "SUM" => row.push(acc.sum()),
-                        "AVG" => row.push(acc.avg()),
+                        "SUM" => row.push(acc.sum.sum()?),
+                        "AVG" => row.push(acc.sum.avg()),
                         _ => row.push(Value::Null),
                     }
                 }
@@ -6070,12 +6418,7 @@ impl QueryExecutor {
                 for (k, a) in lin_groups.drain(..) {
                     let entry = groups.entry(k).or_insert_with(GroupAcc::new);
                     entry.count += a.count;
-                    // Re-add the accumulated sums so partial sums from lin_groups
-                    // merge into the hashmap entry. add() handles int/float flagging.
-                    if a.has_float {
-                        entry.add(a.float_sum, true);
-                    }
-                    entry.add(a.int_sum as f64, false);
+                    entry.sum.merge(&a.sum);
                 }
                 use_hashmap = true;
             }};
@@ -6107,15 +6450,7 @@ impl QueryExecutor {
                     lin_groups[idx].1.count += 1;
                     for (j, a) in agg_cols.iter().enumerate() {
                         if a.func == "SUM" || a.func == "AVG" {
-                            let is_int = agg_is_int[j];
-                            let v = if is_int {
-                                agg_segs[j].get_i64(i).map(|x| x as f64)
-                            } else {
-                                agg_segs[j].get_f64(i)
-                            };
-                            if let Some(v) = v {
-                                lin_groups[idx].1.add(v, is_int);
-                            }
+                            lin_groups[idx].1.add_at(&agg_segs[j], i, agg_is_int[j]);
                         }
                     }
                     if lin_groups.len() > LINEAR_SCAN_MAX {
@@ -6126,15 +6461,7 @@ impl QueryExecutor {
                     entry.count += 1;
                     for (j, a) in agg_cols.iter().enumerate() {
                         if a.func == "SUM" || a.func == "AVG" {
-                            let is_int = agg_is_int[j];
-                            let v = if is_int {
-                                agg_segs[j].get_i64(i).map(|x| x as f64)
-                            } else {
-                                agg_segs[j].get_f64(i)
-                            };
-                            if let Some(v) = v {
-                                entry.add(v, is_int);
-                            }
+                            entry.add_at(&agg_segs[j], i, agg_is_int[j]);
                         }
                     }
                 }
@@ -6152,15 +6479,7 @@ impl QueryExecutor {
                     null_acc.count += 1;
                     for (j, a) in agg_cols.iter().enumerate() {
                         if a.func == "SUM" || a.func == "AVG" {
-                            let is_int = agg_is_int[j];
-                            let v = if is_int {
-                                agg_segs[j].get_i64(i).map(|x| x as f64)
-                            } else {
-                                agg_segs[j].get_f64(i)
-                            };
-                            if let Some(v) = v {
-                                null_acc.add(v, is_int);
-                            }
+                            null_acc.add_at(&agg_segs[j], i, agg_is_int[j]);
                         }
                     }
                     continue;
@@ -6171,15 +6490,7 @@ impl QueryExecutor {
                     lin_groups[idx].1.count += 1;
                     for (j, a) in agg_cols.iter().enumerate() {
                         if a.func == "SUM" || a.func == "AVG" {
-                            let is_int = agg_is_int[j];
-                            let v = if is_int {
-                                agg_segs[j].get_i64(i).map(|x| x as f64)
-                            } else {
-                                agg_segs[j].get_f64(i)
-                            };
-                            if let Some(v) = v {
-                                lin_groups[idx].1.add(v, is_int);
-                            }
+                            lin_groups[idx].1.add_at(&agg_segs[j], i, agg_is_int[j]);
                         }
                     }
                     if lin_groups.len() > LINEAR_SCAN_MAX {
@@ -6190,15 +6501,7 @@ impl QueryExecutor {
                     entry.count += 1;
                     for (j, a) in agg_cols.iter().enumerate() {
                         if a.func == "SUM" || a.func == "AVG" {
-                            let is_int = agg_is_int[j];
-                            let v = if is_int {
-                                agg_segs[j].get_i64(i).map(|x| x as f64)
-                            } else {
-                                agg_segs[j].get_f64(i)
-                            };
-                            if let Some(v) = v {
-                                entry.add(v, is_int);
-                            }
+                            entry.add_at(&agg_segs[j], i, agg_is_int[j]);
                         }
                     }
                 }
@@ -6223,8 +6526,8 @@ impl QueryExecutor {
             }
             for a in &agg_cols {
                 match a.func.as_str() {
-                    "SUM" => row.push(acc.sum()),
-                    "AVG" => row.push(acc.avg()),
+                    "SUM" => row.push(acc.sum.sum()?),
+                    "AVG" => row.push(acc.sum.avg()),
                     _ => row.push(Value::Null),
                 }
             }
@@ -6241,8 +6544,8 @@ impl QueryExecutor {
             }
             for a in &agg_cols {
                 match a.func.as_str() {
-                    "SUM" => row.push(acc.sum()),
-                    "AVG" => row.push(acc.avg()),
+                    "SUM" => row.push(acc.sum.sum()?),
+                    "AVG" => row.push(acc.sum.avg()),
                     _ => row.push(Value::Null),
                 }
             }
@@ -6257,8 +6560,8 @@ impl QueryExecutor {
             }
             for a in &agg_cols {
                 match a.func.as_str() {
-                    "SUM" => row.push(null_acc.sum()),
-                    "AVG" => row.push(null_acc.avg()),
+                    "SUM" => row.push(null_acc.sum.sum()?),
+                    "AVG" => row.push(null_acc.sum.avg()),
                     _ => row.push(Value::Null),
                 }
             }
@@ -6354,6 +6657,14 @@ impl QueryExecutor {
             Some(p) => p,
             None => return Ok(None),
         };
+        // DECIMAL/UUID text must be compared by value ('10' = 10.00).
+        if schema
+            .col_types()
+            .get(filter_pos)
+            .is_some_and(|ct| ct.is_text_encoded())
+        {
+            return Ok(None);
+        }
 
         let col_sst = self.db.columnar_sstables.get(table).unwrap();
         let num_rows = col_sst.num_rows;
@@ -6429,7 +6740,11 @@ impl QueryExecutor {
                                             .filter(|&v| v != i64::MIN) // MIN is the NULL sentinel
                                             .collect();
                                         match name.to_uppercase().as_str() {
-                                            "SUM" => result.push(Value::Integer(vals.iter().sum())),
+                                            "SUM" => {
+                                                let mut acc = crate::types::NumericSum::new();
+                                                vals.iter().for_each(|&v| acc.add_int(v));
+                                                result.push(acc.sum()?)
+                                            }
                                             "MIN" => result.push(
                                                 vals.iter()
                                                     .min()
@@ -6455,7 +6770,11 @@ impl QueryExecutor {
                                         let pick = |cmp: std::cmp::Ordering| cmp;
                                         let _ = pick;
                                         match name.to_uppercase().as_str() {
-                                            "SUM" => result.push(Value::Float(vals.iter().sum())),
+                                            "SUM" => {
+                                                let mut acc = crate::types::NumericSum::new();
+                                                vals.iter().for_each(|&v| acc.add_float(v));
+                                                result.push(acc.sum()?)
+                                            }
                                             "MIN" => result.push(
                                                 vals.iter()
                                                     .min_by(|a, b| {
@@ -6641,17 +6960,21 @@ impl QueryExecutor {
                         };
                         match name.to_uppercase().as_str() {
                             "COUNT" => {}
+                            // DECIMAL sums stay exact on the materialized path.
+                            "SUM"
+                                if matches!(
+                                    schema.col_types().get(agg_pos),
+                                    Some(ColumnType::Decimal { .. })
+                                ) =>
+                            {
+                                return Ok(None)
+                            }
                             "SUM" => {
-                                let sum: f64 = rows
-                                    .iter()
-                                    .filter_map(|r| r.get(agg_pos))
-                                    .filter_map(|v| match v {
-                                        Value::Integer(i) => Some(*i as f64),
-                                        Value::Float(f) => Some(*f),
-                                        _ => None,
-                                    })
-                                    .sum();
-                                result_row.push(Value::Float(sum));
+                                let mut acc = crate::types::NumericSum::new();
+                                for v in rows.iter().filter_map(|r| r.get(agg_pos)) {
+                                    acc.add(v);
+                                }
+                                result_row.push(acc.sum()?);
                             }
                             "MIN" => {
                                 let min_val = rows
@@ -6785,6 +7108,13 @@ impl QueryExecutor {
                 _ => return Ok(None),
             }
         }
+        // DECIMAL/UUID values are neither f64-summable nor comparable by `!=` here.
+        if std::iter::once(filter_pos)
+            .chain(agg_cols.iter().map(|(_, p)| *p))
+            .any(|p| col_types.get(p).is_some_and(|ct| ct.is_text_encoded()))
+        {
+            return Ok(None);
+        }
 
         // Pre-compute offsets for column extraction
         use crate::storage::row_format::{FIXED_COL_SIZE, HEADER_SIZE};
@@ -6906,7 +7236,7 @@ impl QueryExecutor {
             .scan_range_streaming(tp << 32, (tp + 1) << 32)?;
         let raw = it.has_raw_sst();
         let mut count: i64 = 0;
-        let mut sum: f64 = 0.0;
+        let mut sum = crate::types::NumericSum::new();
         let mut min: Option<f64> = None;
         let mut max: Option<f64> = None;
 
@@ -6929,11 +7259,16 @@ impl QueryExecutor {
                         for (_, pos) in &agg_cols {
                             if let Some(av) = extract_col(d, *pos) {
                                 let fv = match av {
-                                    Value::Integer(i) => i as f64,
-                                    Value::Float(f) => f,
+                                    Value::Integer(i) => {
+                                        sum.add_int(i);
+                                        i as f64
+                                    }
+                                    Value::Float(f) => {
+                                        sum.add_float(f);
+                                        f
+                                    }
                                     _ => continue,
                                 };
-                                sum += fv;
                                 min = Some(min.map_or(fv, |m| m.min(fv)));
                                 max = Some(max.map_or(fv, |m| m.max(fv)));
                             }
@@ -6965,11 +7300,16 @@ impl QueryExecutor {
                         for (_, pos) in &agg_cols {
                             if let Some(av) = extract_col(d, *pos) {
                                 let fv = match av {
-                                    Value::Integer(i) => i as f64,
-                                    Value::Float(f) => f,
+                                    Value::Integer(i) => {
+                                        sum.add_int(i);
+                                        i as f64
+                                    }
+                                    Value::Float(f) => {
+                                        sum.add_float(f);
+                                        f
+                                    }
                                     _ => continue,
                                 };
-                                sum += fv;
                                 min = Some(min.map_or(fv, |m| m.min(fv)));
                                 max = Some(max.map_or(fv, |m| m.max(fv)));
                             }
@@ -6987,7 +7327,7 @@ impl QueryExecutor {
         }
         for (f, _) in &agg_cols {
             match f.as_str() {
-                "SUM" => r.push(Value::Float(sum)),
+                "SUM" => r.push(sum.sum()?),
                 "MIN" => r.push(min.map(Value::Float).unwrap_or(Value::Null)),
                 "MAX" => r.push(max.map(Value::Float).unwrap_or(Value::Null)),
                 "COUNT" => r.push(Value::Integer(count)),
@@ -7079,9 +7419,7 @@ impl QueryExecutor {
                         if let Some(s) = seg.get_str(i) {
                             if seen.insert(s) {
                                 // &str key, borrows from mmap (no alloc)
-                                vals.push(Value::Text(crate::types::ArcString(
-                                    std::sync::Arc::from(s),
-                                )));
+                                vals.push(col_def.col_type.value_from_text(s));
                             }
                         }
                     }
@@ -7205,6 +7543,18 @@ impl QueryExecutor {
             return Ok(None);
         }
 
+        // Natural TEXT / UUID keys: UUID literals arrive as text.
+        let natural_pk = schema.has_natural_primary_key();
+        let literal = match (&literal, schema.get_column(pk_name)) {
+            (Value::Text(s), Some(col)) if natural_pk && col.col_type == ColumnType::Uuid => {
+                match col.col_type.value_from_text(s) {
+                    Value::Null => return Ok(None),
+                    parsed => parsed,
+                }
+            }
+            _ => literal,
+        };
+
         // Resolve PK value → composite key.
         let table_id = self.db.table_registry.get_table_id(table_name).unwrap_or(0) as u64;
         let composite_key = match &literal {
@@ -7238,6 +7588,23 @@ impl QueryExecutor {
                     .and_then(|l| l.get_pk(&pk_key))
                 {
                     Some(rid) => (table_id << 32) | (rid & 0xFFFFFFFF),
+                    // Natural keys resolve through the PK's column index.
+                    None if natural_pk => {
+                        match self.resolve_pk_with_cache(table_name, &pk_key, pk_name, &literal)? {
+                            Some(rid) => (table_id << 32) | (rid & 0xFFFFFFFF),
+                            // The async pipeline may not have indexed it yet
+                            None if self.db.is_async_index_pipeline_active() => return Ok(None),
+                            None => {
+                                let columns: Vec<String> = self
+                                    .build_select_columns(&stmt.columns, &schema)
+                                    .unwrap_or_default();
+                                return Ok(Some(StreamingQueryResult::SelectReady {
+                                    columns,
+                                    rows: vec![],
+                                }));
+                            }
+                        }
+                    }
                     None => return Ok(None), // cache miss → full scan
                 }
             }
@@ -7272,6 +7639,9 @@ impl QueryExecutor {
             // No txn info → fall through to storage.
             None => match store.get(composite_key) {
                 Some(r) => r,
+                // A natural key's row id may be stale (deleted and inserted
+                // again); let the scan find the live row.
+                None if natural_pk => return Ok(None),
                 None => {
                     // Not found — return empty result.
                     let columns: Vec<String> = self
@@ -7284,6 +7654,13 @@ impl QueryExecutor {
                 }
             },
         };
+        // Index keys hold only a 64-byte prefix of long text keys; a row
+        // without the literal key leaves the lookup to the scan.
+        if natural_pk
+            && schema.get_column_position(pk_name).and_then(|p| row.get(p)) != Some(&literal)
+        {
+            return Ok(None);
+        }
         // Build output: SELECT * → full row; SELECT col1, col2 → project.
         let columns: Vec<String> = self
             .build_select_columns(&stmt.columns, &schema)
@@ -7375,7 +7752,10 @@ impl QueryExecutor {
                         }
                     } else {
                         match col_sst.read_text(ci) {
-                            Ok(seg) => segments.push(ColumnarSeg::Text(seg)),
+                            Ok(seg) => segments.push(ColumnarSeg::Text(
+                                seg,
+                                col_types.get(ci).cloned().unwrap_or(ColumnType::Text),
+                            )),
                             Err(_) => {
                                 ok = false;
                                 break;
@@ -7409,7 +7789,7 @@ impl QueryExecutor {
                 .db
                 .fast_row_count(table)
                 .map(|c| c as usize)
-                .unwrap_or(1024);
+                .unwrap_or_else(|| self.streaming_config().materialize_capacity);
             let col_count = col_types.len();
 
             // Pre-allocate the outer Vec only (avoids log₂(N) reallocations during push).
@@ -7516,7 +7896,10 @@ impl QueryExecutor {
                                             ));
                                         }
                                     } else if let Ok(seg) = col_sst.read_text(ci) {
-                                        segments.push(ColumnarSeg::Text(seg));
+                                        segments.push(ColumnarSeg::Text(
+                                            seg,
+                                            col_types.get(ci).cloned().unwrap_or(ColumnType::Text),
+                                        ));
                                     }
                                 }
                                 return Ok(StreamingQueryResult::SelectColumnar {
@@ -7607,7 +7990,13 @@ impl QueryExecutor {
                                                 ));
                                             }
                                         } else if let Ok(seg) = col_sst.read_text(ci) {
-                                            segments.push(ColumnarSeg::Text(seg));
+                                            segments.push(ColumnarSeg::Text(
+                                                seg,
+                                                col_types
+                                                    .get(ci)
+                                                    .cloned()
+                                                    .unwrap_or(ColumnType::Text),
+                                            ));
                                         }
                                     }
                                     return Ok(StreamingQueryResult::SelectColumnar {
@@ -7653,7 +8042,13 @@ impl QueryExecutor {
                                                         ));
                                                     }
                                                 } else if let Ok(seg) = col_sst.read_text(ci) {
-                                                    segments.push(ColumnarSeg::Text(seg));
+                                                    segments.push(ColumnarSeg::Text(
+                                                        seg,
+                                                        col_types
+                                                            .get(ci)
+                                                            .cloned()
+                                                            .unwrap_or(ColumnType::Text),
+                                                    ));
                                                 }
                                             }
                                             return Ok(StreamingQueryResult::SelectColumnar {
@@ -7849,7 +8244,10 @@ impl QueryExecutor {
             }
 
             // ── Full decode path (sequential fallback) ──
-            let row_iter = self.db.scan_table_rows_streaming(table)?;
+            // Shares its pass over the table with concurrent full scans
+            let row_iter = self
+                .db
+                .scan_table_shared(table, self.streaming_config().batch_size)?;
             let filtered_iter = row_iter.filter_map(move |result| match result {
                 Ok((_row_id, row)) => {
                     let matches = if let Some(ref clause) = where_clause {
@@ -8088,7 +8486,12 @@ impl QueryExecutor {
             // Now we pre-decode each output column once per segment, then index
             // into the pre-decoded data by local_row — converting 100K scattered
             // decodes into 4 sequential column reads + 100K cheap indexed lookups.
-            if matches!(col_types.get(col_pos), Some(ColumnType::Text)) && set.len() > 1 {
+            // Raw bytes only match TEXT set members; UUID / DATE / TIME members
+            // equal non-canonical text too and need the Value comparison.
+            if matches!(col_types.get(col_pos), Some(ColumnType::Text))
+                && set.len() > 1
+                && set.iter().all(|v| matches!(v, Value::Text(_)))
+            {
                 // Build a HashSet<&[u8]> from the Value set (once, not per-row).
                 let byte_set: std::collections::HashSet<&[u8]> = set
                     .iter()
@@ -8140,7 +8543,10 @@ impl QueryExecutor {
                                     match seg.sst.read_text(pc) {
                                         Ok(t) => (0..seg.sst.num_rows)
                                             .map(|i| {
-                                                t.get_str(i).map(|s| Value::Text(s.into()))
+                                                t.get_str(i).map(|s| match col_types.get(pc) {
+                                                    Some(ct) => ct.value_from_text(s),
+                                                    None => Value::Text(s.into()),
+                                                })
                                             })
                                             .collect(),
                                         Err(_) => vec![None; seg.sst.num_rows],
@@ -8186,9 +8592,15 @@ impl QueryExecutor {
         // ColumnarSeg only decodes Fixed/Text, and would read those columns via
         // read_text (garbage/panic). The projected-scan fallback decodes them
         // correctly via build_column_segment.
-        let has_vector_or_spatial = col_types
-            .iter()
-            .any(|ct| matches!(ct, ColumnType::Tensor(_) | ColumnType::Spatial));
+        let has_vector_or_spatial = col_types.iter().any(|ct| {
+            matches!(
+                ct,
+                ColumnType::Tensor(_)
+                    | ColumnType::ShapedTensor(_)
+                    | ColumnType::EncodedVector { .. }
+                    | ColumnType::Spatial
+            )
+        });
 
         // 🚀 LIMIT early-termination fast path: SELECT cols FROM t [LIMIT N]
         // When there's no WHERE/ORDER BY/GROUP BY/DISTINCT, we can scan only
@@ -8258,7 +8670,14 @@ impl QueryExecutor {
                             }
                         } else if pc < sst.column_tags.len() {
                             if let Ok(t) = sst.read_text(pc) {
-                                col_segs.push(ColumnarSeg::Text(t));
+                                col_segs.push(ColumnarSeg::Text(
+                                    t,
+                                    schema
+                                        .col_types()
+                                        .get(pc)
+                                        .cloned()
+                                        .unwrap_or(ColumnType::Text),
+                                ));
                             }
                         }
                     }
@@ -8329,7 +8748,14 @@ impl QueryExecutor {
                             }
                         } else if pc < sst.column_tags.len() {
                             if let Ok(t) = sst.read_text(pc) {
-                                col_segs.push(ColumnarSeg::Text(t));
+                                col_segs.push(ColumnarSeg::Text(
+                                    t,
+                                    schema
+                                        .col_types()
+                                        .get(pc)
+                                        .cloned()
+                                        .unwrap_or(ColumnType::Text),
+                                ));
                             }
                         }
                     }
@@ -8409,6 +8835,8 @@ impl QueryExecutor {
                                     enum Col {
                                         Text(TextSegment),
                                         Fixed(FixedSegment),
+                                        Vector,
+                                        Spatial,
                                         None,
                                     }
                                     let mut col_cache: std::collections::HashMap<
@@ -8428,9 +8856,20 @@ impl QueryExecutor {
                                                 .entry((seg_idx, pc))
                                                 .or_insert_with(|| {
                                                     if matches!(
-                                                        col_types.get(pc),
-                                                        Some(crate::types::ColumnType::Text)
+                                                        seg.sst.column_tags.get(pc),
+                                                        Some(crate::storage::lsm::columnar::ColumnTypeTag::Vector)
                                                     ) {
+                                                        // Decoded per row below
+                                                        Col::Vector
+                                                    } else if matches!(
+                                                        seg.sst.column_tags.get(pc),
+                                                        Some(crate::storage::lsm::columnar::ColumnTypeTag::Spatial)
+                                                    ) {
+                                                        Col::Spatial
+                                                    } else if col_types.get(pc).is_some_and(|ct| {
+                                                        matches!(ct, crate::types::ColumnType::Text)
+                                                            || ct.is_text_encoded()
+                                                    }) {
                                                         match seg.sst.read_text(pc) {
                                                             Ok(t) => Col::Text(t),
                                                             Err(_) => Col::None,
@@ -8445,7 +8884,10 @@ impl QueryExecutor {
                                             let v = match col {
                                                 Col::Text(t) => t
                                                     .get_str(local_row)
-                                                    .map(|s| Value::Text(s.into()))
+                                                    .map(|s| match col_types.get(pc) {
+                                                        Some(ct) => ct.value_from_text(s),
+                                                        None => Value::Text(s.into()),
+                                                    })
                                                     .unwrap_or(Value::Null),
                                                 Col::Fixed(f) => {
                                                     match col_types.get(pc) {
@@ -8462,12 +8904,37 @@ impl QueryExecutor {
                                                                 ))
                                                             })
                                                             .unwrap_or(Value::Null),
+                                                        Some(crate::types::ColumnType::Boolean) => f
+                                                            .get_bool(local_row)
+                                                            .map(Value::Bool)
+                                                            .unwrap_or(Value::Null),
+                                                        Some(crate::types::ColumnType::Timestamp) => f
+                                                            .get_i64(local_row)
+                                                            .map(|v| {
+                                                                Value::Timestamp(
+                                                                    crate::types::Timestamp::from_micros(v),
+                                                                )
+                                                            })
+                                                            .unwrap_or(Value::Null),
                                                         _ => f
                                                             .get_i64(local_row)
                                                             .map(Value::Integer)
                                                             .unwrap_or(Value::Null),
                                                     }
                                                 }
+                                                Col::Vector => seg
+                                                    .sst
+                                                    .read_vector_at(pc, local_row)
+                                                    .ok()
+                                                    .flatten()
+                                                    .zip(col_types.get(pc))
+                                                    .map(|(v, ct)| ct.value_from_vector(v))
+                                                    .unwrap_or(Value::Null),
+                                                Col::Spatial => seg.get_value_at_idx(
+                                                    local_row,
+                                                    pc,
+                                                    &crate::types::ColumnType::Spatial,
+                                                ),
                                                 Col::None => Value::Null,
                                             };
                                             row.push(v);
@@ -8592,7 +9059,13 @@ impl QueryExecutor {
                                                     ));
                                                 }
                                             } else if let Ok(t) = segs[0].sst.read_text(pc) {
-                                                col_segs.push(ColumnarSeg::Text(t));
+                                                col_segs.push(ColumnarSeg::Text(
+                                                    t,
+                                                    col_types
+                                                        .get(pc)
+                                                        .cloned()
+                                                        .unwrap_or(ColumnType::Text),
+                                                ));
                                             }
                                         }
                                         return Ok(StreamingQueryResult::SelectColumnar {
@@ -8637,7 +9110,10 @@ impl QueryExecutor {
                                                 match seg.sst.read_text(pc) {
                                                     Ok(t) => t
                                                         .get_str(*local_row)
-                                                        .map(|s| Value::Text(s.into()))
+                                                        .map(|s| match col_types.get(pc) {
+                                                            Some(ct) => ct.value_from_text(s),
+                                                            None => Value::Text(s.into()),
+                                                        })
                                                         .unwrap_or(Value::Null),
                                                     Err(_) => Value::Null,
                                                 }
@@ -8727,7 +9203,13 @@ impl QueryExecutor {
                                                         ));
                                                     }
                                                 } else if let Ok(t) = segs[0].sst.read_text(pc) {
-                                                    col_segs.push(ColumnarSeg::Text(t));
+                                                    col_segs.push(ColumnarSeg::Text(
+                                                        t,
+                                                        col_types
+                                                            .get(pc)
+                                                            .cloned()
+                                                            .unwrap_or(ColumnType::Text),
+                                                    ));
                                                 }
                                             }
                                             return Ok(StreamingQueryResult::SelectColumnar {
@@ -8772,6 +9254,7 @@ impl QueryExecutor {
         // O(N log N) comparisons with zero per-comparison allocation.
         if let Some(ref ob) = stmt.order_by {
             if !ob.is_empty() {
+                query_memory::charge_rows(&result_rows, "ORDER BY")?;
                 let ncol = schema.columns.len();
                 // 🚨 Build SELECT-alias → schema-column-position map so that
                 // `SELECT v AS val ... ORDER BY val` resolves `val` to the
@@ -9002,6 +9485,7 @@ impl QueryExecutor {
         }
         // Apply DISTINCT over the output projection (before OFFSET/LIMIT).
         if stmt.distinct {
+            query_memory::charge_rows(&result_rows, "DISTINCT")?;
             let mut seen: std::collections::HashSet<Vec<Value>> =
                 std::collections::HashSet::with_capacity(result_rows.len());
             result_rows.retain(|row| {
@@ -9126,6 +9610,8 @@ impl QueryExecutor {
     ) -> Result<Vec<Vec<Value>>> {
         use crate::sql::ast::{BinaryOperator, Expr};
         let col_types = store.col_types();
+        // A qualified name (`o.user_id`) names a column of the scanned table
+        let position = |cn: &str| schema.get_column_position(cn.rsplit('.').next().unwrap_or(cn));
 
         let mut early_stop_at: usize = usize::MAX;
         let (filter_col, pred_box): (Option<usize>, Box<dyn Fn(Option<&Value>) -> bool>) = match wc
@@ -9137,10 +9623,22 @@ impl QueryExecutor {
             } => {
                 match (left.as_ref(), right.as_ref()) {
                     (Expr::Column(cn), Expr::Literal(v)) => {
-                        let pos = schema.get_column_position(cn).unwrap_or(0);
+                        let Some(pos) = position(cn) else {
+                            return self.col_segment_general_scan(
+                                wc,
+                                schema,
+                                out_positions,
+                                offset,
+                                limit,
+                            );
+                        };
                         let val = v.clone();
                         // If filtering on PK, at most 1 row matches → early-stop.
-                        if schema.primary_key() == Some(cn.as_str()) {
+                        if schema
+                            .primary_key()
+                            .and_then(|pk| schema.get_column_position(pk))
+                            == Some(pos)
+                        {
                             early_stop_at = 1;
                         }
                         (
@@ -9151,7 +9649,6 @@ impl QueryExecutor {
                     _ => {
                         // General: fallback to MergeCursor scan.
                         return self.col_segment_general_scan(
-                            store,
                             wc,
                             schema,
                             out_positions,
@@ -9170,7 +9667,15 @@ impl QueryExecutor {
                     let pat = s.as_str();
                     if pat.ends_with('%') && !pat[..pat.len() - 1].contains('%') {
                         let prefix = pat[..pat.len() - 1].to_string();
-                        let pos = schema.get_column_position(cn).unwrap_or(0);
+                        let Some(pos) = position(cn) else {
+                            return self.col_segment_general_scan(
+                                wc,
+                                schema,
+                                out_positions,
+                                offset,
+                                limit,
+                            );
+                        };
                         (
                             Some(pos),
                             Box::new(move |fv: Option<&Value>| match fv {
@@ -9180,7 +9685,6 @@ impl QueryExecutor {
                         )
                     } else {
                         return self.col_segment_general_scan(
-                            store,
                             wc,
                             schema,
                             out_positions,
@@ -9190,14 +9694,7 @@ impl QueryExecutor {
                     }
                 }
                 _ => {
-                    return self.col_segment_general_scan(
-                        store,
-                        wc,
-                        schema,
-                        out_positions,
-                        offset,
-                        limit,
-                    )
+                    return self.col_segment_general_scan(wc, schema, out_positions, offset, limit)
                 }
             },
             Expr::In {
@@ -9206,7 +9703,15 @@ impl QueryExecutor {
                 negated: false,
             } if list.iter().all(|e| matches!(e, Expr::Literal(_))) => match expr.as_ref() {
                 Expr::Column(cn) => {
-                    let pos = schema.get_column_position(cn).unwrap_or(0);
+                    let Some(pos) = position(cn) else {
+                        return self.col_segment_general_scan(
+                            wc,
+                            schema,
+                            out_positions,
+                            offset,
+                            limit,
+                        );
+                    };
                     let set: std::collections::HashSet<Value> = list
                         .iter()
                         .filter_map(|e| {
@@ -9225,14 +9730,7 @@ impl QueryExecutor {
                     )
                 }
                 _ => {
-                    return self.col_segment_general_scan(
-                        store,
-                        wc,
-                        schema,
-                        out_positions,
-                        offset,
-                        limit,
-                    )
+                    return self.col_segment_general_scan(wc, schema, out_positions, offset, limit)
                 }
             },
             // 🚀 Pre-built HashSet from subquery materialization.
@@ -9243,7 +9741,15 @@ impl QueryExecutor {
                 ..
             } => match expr.as_ref() {
                 Expr::Column(cn) => {
-                    let pos = schema.get_column_position(cn).unwrap_or(0);
+                    let Some(pos) = position(cn) else {
+                        return self.col_segment_general_scan(
+                            wc,
+                            schema,
+                            out_positions,
+                            offset,
+                            limit,
+                        );
+                    };
                     (
                         Some(pos),
                         Box::new(move |fv: Option<&Value>| {
@@ -9252,26 +9758,10 @@ impl QueryExecutor {
                     )
                 }
                 _ => {
-                    return self.col_segment_general_scan(
-                        store,
-                        wc,
-                        schema,
-                        out_positions,
-                        offset,
-                        limit,
-                    )
+                    return self.col_segment_general_scan(wc, schema, out_positions, offset, limit)
                 }
             },
-            _ => {
-                return self.col_segment_general_scan(
-                    store,
-                    wc,
-                    schema,
-                    out_positions,
-                    offset,
-                    limit,
-                )
-            }
+            _ => return self.col_segment_general_scan(wc, schema, out_positions, offset, limit),
         };
 
         // 🚀 LIKE prefix fast path: byte-compare scan (no closure dispatch,
@@ -9288,7 +9778,7 @@ impl QueryExecutor {
                 let pat = s.as_str();
                 if pat.ends_with('%') && !pat[..pat.len() - 1].contains('%') {
                     let prefix = pat[..pat.len() - 1].to_string();
-                    if let Some(fc) = schema.get_column_position(cn) {
+                    if let Some(fc) = position(cn) {
                         if matches!(col_types.get(fc), Some(ColumnType::Text)) {
                             if let Some(indices) =
                                 store.scan_row_indices_prefix(fc, prefix.as_bytes(), offset + limit)
@@ -9325,10 +9815,9 @@ impl QueryExecutor {
                                     let mut columns_decoded: Vec<Col> =
                                         Vec::with_capacity(out_positions.len());
                                     for &pc in out_positions {
-                                        let c = if matches!(
-                                            col_types.get(pc),
-                                            Some(ColumnType::Text)
-                                        ) {
+                                        let c = if col_types.get(pc).is_some_and(|ct| {
+                                            matches!(ct, ColumnType::Text) || ct.is_text_encoded()
+                                        }) {
                                             match seg.sst.read_text(pc) {
                                                 Ok(t) => Col::Text(t),
                                                 Err(_) => Col::None,
@@ -9349,7 +9838,10 @@ impl QueryExecutor {
                                             let v = match &columns_decoded[ci] {
                                                 Col::Text(t) => t
                                                     .get_str(row_idx)
-                                                    .map(|s| Value::Text(s.into()))
+                                                    .map(|s| match col_types.get(pc) {
+                                                        Some(ct) => ct.value_from_text(s),
+                                                        None => Value::Text(s.into()),
+                                                    })
                                                     .unwrap_or(Value::Null),
                                                 Col::Fixed(f) => match col_types.get(pc) {
                                                     Some(ColumnType::Float) => f
@@ -9383,10 +9875,10 @@ impl QueryExecutor {
                                     for &pc in out_positions {
                                         let col =
                                             col_cache.entry((*seg_idx, pc)).or_insert_with(|| {
-                                                if matches!(
-                                                    col_types.get(pc),
-                                                    Some(ColumnType::Text)
-                                                ) {
+                                                if col_types.get(pc).is_some_and(|ct| {
+                                                    matches!(ct, ColumnType::Text)
+                                                        || ct.is_text_encoded()
+                                                }) {
                                                     match seg.sst.read_text(pc) {
                                                         Ok(t) => Col::Text(t),
                                                         Err(_) => Col::None,
@@ -9401,7 +9893,10 @@ impl QueryExecutor {
                                         let v = match col {
                                             Col::Text(t) => t
                                                 .get_str(*local_row)
-                                                .map(|s| Value::Text(s.into()))
+                                                .map(|s| match col_types.get(pc) {
+                                                    Some(ct) => ct.value_from_text(s),
+                                                    None => Value::Text(s.into()),
+                                                })
                                                 .unwrap_or(Value::Null),
                                             Col::Fixed(f) => match col_types.get(pc) {
                                                 Some(ColumnType::Float) => f
@@ -9442,7 +9937,6 @@ impl QueryExecutor {
                         }
                         _ => {
                             return self.col_segment_general_scan(
-                                store,
                                 wc,
                                 schema,
                                 out_positions,
@@ -9461,7 +9955,6 @@ impl QueryExecutor {
                                 })
                             } else {
                                 return self.col_segment_general_scan(
-                                    store,
                                     wc,
                                     schema,
                                     out_positions,
@@ -9472,7 +9965,6 @@ impl QueryExecutor {
                         }
                         _ => {
                             return self.col_segment_general_scan(
-                                store,
                                 wc,
                                 schema,
                                 out_positions,
@@ -9504,7 +9996,6 @@ impl QueryExecutor {
                             }
                             _ => {
                                 return self.col_segment_general_scan(
-                                    store,
                                     wc,
                                     schema,
                                     out_positions,
@@ -9516,7 +10007,6 @@ impl QueryExecutor {
                     }
                     _ => {
                         return self.col_segment_general_scan(
-                            store,
                             wc,
                             schema,
                             out_positions,
@@ -9552,39 +10042,43 @@ impl QueryExecutor {
     /// Fallback: general WHERE eval via MergeCursor (handles complex expressions).
     fn col_segment_general_scan(
         &self,
-        store: &crate::storage::col_segment::ColSegmentStore,
         wc: &crate::sql::ast::Expr,
         schema: &TableSchema,
         out_positions: &[usize],
         offset: usize,
         limit: usize,
     ) -> Result<Vec<Vec<Value>>> {
-        // Ensure buffered rows are durable before scanning — store.scan() only
-        // reads persisted segments, so unflushed inserts would be invisible.
-        let _ = store.flush_buffer();
+        // Concurrent scans of the table share one merge pass over its
+        // segments (the write buffer is flushed first, so unflushed inserts
+        // are visible)
+        let mut scan = self
+            .db
+            .scan_table_shared(&schema.name, self.streaming_config().batch_size)?;
         let mut rows = Vec::new();
         let mut skipped = 0usize;
-        for (_key, _ts, row) in store.scan() {
-            let m = match Self::eval_expr_on_row(wc, &row, schema) {
-                Ok(Value::Bool(b)) => b,
-                Ok(Value::Integer(i)) => i != 0,
-                Ok(Value::Float(f)) => f != 0.0 && !f.is_nan(),
-                _ => false,
-            };
-            if !m {
-                continue;
-            }
-            if skipped < offset {
-                skipped += 1;
-                continue;
-            }
-            let projected: Vec<Value> = out_positions
-                .iter()
-                .map(|&p| row.get(p).cloned().unwrap_or(Value::Null))
-                .collect();
-            rows.push(projected);
-            if rows.len() >= limit {
-                break;
+        while let Some(batch) = scan.next_batch() {
+            for (_, row) in batch?.iter() {
+                let m = match Self::eval_expr_on_row(wc, row, schema) {
+                    Ok(Value::Bool(b)) => b,
+                    Ok(Value::Integer(i)) => i != 0,
+                    Ok(Value::Float(f)) => f != 0.0 && !f.is_nan(),
+                    _ => false,
+                };
+                if !m {
+                    continue;
+                }
+                if skipped < offset {
+                    skipped += 1;
+                    continue;
+                }
+                let projected: Vec<Value> = out_positions
+                    .iter()
+                    .map(|&p| row.get(p).cloned().unwrap_or(Value::Null))
+                    .collect();
+                rows.push(projected);
+                if rows.len() >= limit {
+                    return Ok(rows);
+                }
             }
         }
         Ok(rows)
@@ -9644,6 +10138,14 @@ impl QueryExecutor {
     /// 🚀 Lightweight expression evaluation for WHERE filters (no allocations)
     /// Handles simple comparisons, AND/OR, column references, and literals.
     /// Falls back to creating a QueryExecutor for complex expressions (MATCH, KNN, etc.)
+    /// SQL truth value of a predicate result: NULL is UNKNOWN (`None`)
+    fn sql_truth(v: &Value) -> Option<bool> {
+        match v {
+            Value::Null => None,
+            v => Some(Self::is_truthy(v)),
+        }
+    }
+
     fn is_truthy(v: &Value) -> bool {
         match v {
             Value::Bool(b) => *b,
@@ -9677,6 +10179,9 @@ impl QueryExecutor {
     }
 
     fn positional_add(l: &Value, r: &Value) -> Result<Value> {
+        if let Some(result) = crate::types::decimal_arith(crate::types::DecimalOp::Add, l, r) {
+            return result;
+        }
         match (l, r) {
             (Value::Integer(a), Value::Integer(b)) => match a.checked_add(*b) {
                 Some(v) => Ok(Value::Integer(v)),
@@ -9689,6 +10194,9 @@ impl QueryExecutor {
         }
     }
     fn positional_sub(l: &Value, r: &Value) -> Result<Value> {
+        if let Some(result) = crate::types::decimal_arith(crate::types::DecimalOp::Sub, l, r) {
+            return result;
+        }
         match (l, r) {
             (Value::Integer(a), Value::Integer(b)) => match a.checked_sub(*b) {
                 Some(v) => Ok(Value::Integer(v)),
@@ -9701,6 +10209,9 @@ impl QueryExecutor {
         }
     }
     fn positional_mul(l: &Value, r: &Value) -> Result<Value> {
+        if let Some(result) = crate::types::decimal_arith(crate::types::DecimalOp::Mul, l, r) {
+            return result;
+        }
         match (l, r) {
             (Value::Integer(a), Value::Integer(b)) => match a.checked_mul(*b) {
                 Some(v) => Ok(Value::Integer(v)),
@@ -9713,6 +10224,9 @@ impl QueryExecutor {
         }
     }
     fn positional_div(l: &Value, r: &Value) -> Result<Value> {
+        if let Some(result) = crate::types::decimal_arith(crate::types::DecimalOp::Div, l, r) {
+            return result;
+        }
         match (l, r) {
             (Value::Integer(a), Value::Integer(b)) => {
                 if *b == 0 {
@@ -10181,7 +10695,8 @@ impl QueryExecutor {
                 // 🔑 Coerce values to their declared schema type: the columnar
                 // scan decodes Timestamp columns as Integer (they share 8-byte
                 // fixed-width storage). Without this, TO_MICROS/YEAR/etc. fail
-                // with TypeError ("requires timestamp argument").
+                // with TypeError ("requires timestamp argument"). ARRAY columns
+                // arrive as their serialized text the same way.
                 let mut sql_row = SqlRow::new();
                 for (pos, col_def) in schema.columns.iter().enumerate() {
                     if let Some(v) = row.get(pos) {
@@ -10189,6 +10704,9 @@ impl QueryExecutor {
                             (ColumnType::Timestamp, Value::Integer(i)) => {
                                 Value::Timestamp(crate::types::Timestamp::from_micros(*i))
                             }
+                            (ColumnType::Array(_), Value::Text(s)) => {
+                                col_def.col_type.value_from_text(s)
+                            }
                             (_, other) => other.clone(),
                         };
                         sql_row.insert(col_def.name.clone(), coerced);
@@ -10213,25 +10731,13 @@ impl QueryExecutor {
                 let lv = Self::eval_expr_simple(left, row)?;
                 let rv = Self::eval_expr_simple(right, row)?;
                 match op {
-                    BinaryOperator::Eq => {
-                        // NULL = NULL should return false (SQL standard)
-                        if matches!(&lv, Value::Null) || matches!(&rv, Value::Null) {
-                            Ok(Value::Bool(false))
-                        } else {
-                            Ok(Value::Bool(
-                                lv.partial_cmp(&rv) == Some(std::cmp::Ordering::Equal),
-                            ))
-                        }
-                    }
-                    BinaryOperator::Ne => {
-                        if matches!(&lv, Value::Null) || matches!(&rv, Value::Null) {
-                            Ok(Value::Bool(false))
-                        } else {
-                            Ok(Value::Bool(
-                                lv.partial_cmp(&rv) != Some(std::cmp::Ordering::Equal),
-                            ))
-                        }
-                    }
+                    // SQL: NULL comparison => UNKNOWN (3-valued logic), even NULL = NULL
+                    BinaryOperator::Eq => Ok(truth_value(compare_truth(&lv, &rv, |l, r| {
+                        l.partial_cmp(r) == Some(std::cmp::Ordering::Equal)
+                    }))),
+                    BinaryOperator::Ne => Ok(truth_value(compare_truth(&lv, &rv, |l, r| {
+                        l.partial_cmp(r) != Some(std::cmp::Ordering::Equal)
+                    }))),
                     BinaryOperator::Lt
                     | BinaryOperator::Le
                     | BinaryOperator::Gt
@@ -10248,16 +10754,14 @@ impl QueryExecutor {
                             }))
                         }
                     }
-                    BinaryOperator::And => {
-                        let lb = Self::is_truthy(&lv);
-                        let rb = Self::is_truthy(&rv);
-                        Ok(Value::Bool(lb && rb))
-                    }
-                    BinaryOperator::Or => {
-                        let lb = Self::is_truthy(&lv);
-                        let rb = Self::is_truthy(&rv);
-                        Ok(Value::Bool(lb || rb))
-                    }
+                    BinaryOperator::And => Ok(truth_value(truth_and(
+                        Self::sql_truth(&lv),
+                        Self::sql_truth(&rv),
+                    ))),
+                    BinaryOperator::Or => Ok(truth_value(truth_or(
+                        Self::sql_truth(&lv),
+                        Self::sql_truth(&rv),
+                    ))),
                     BinaryOperator::Add => Self::positional_add(&lv, &rv),
                     BinaryOperator::Sub => Self::positional_sub(&lv, &rv),
                     BinaryOperator::Mul => Self::positional_mul(&lv, &rv),
@@ -10287,7 +10791,7 @@ impl QueryExecutor {
                 expr,
             } => {
                 let v = Self::eval_expr_simple(expr, row)?;
-                Ok(Value::Bool(!Self::is_truthy(&v)))
+                Ok(truth_value(Self::sql_truth(&v).map(|t| !t)))
             }
             // For complex expressions that require the materialized path,
             // return the pre-computed result if available, otherwise false.
@@ -10510,7 +11014,7 @@ impl QueryExecutor {
     ) -> Option<Result<StreamingQueryResult>> {
         // Only handle a top-level non-negated InHash (no AND/OR/NOT wrapping)
         let (col_pos, values) = match compiled_where {
-            CompiledWhere::InHash(pos, set) => (*pos, set.clone()),
+            CompiledWhere::InHash(pos, set, _) => (*pos, set.clone()),
             _ => return None,
         };
 
@@ -10538,12 +11042,12 @@ impl QueryExecutor {
         let index_ref = self.db.column_indexes.get(&index_key)?;
         let index = index_ref.value();
 
-        // Batch index lookups: collect all matching row IDs
-        let mut row_id_set: std::collections::HashSet<u64> = std::collections::HashSet::new();
+        // Batch index lookups: union the matching row IDs
+        let mut row_id_set = RowIdSet::new();
         for value in &values {
-            match index.get(value) {
+            match index.get_set(value) {
                 Ok(row_ids) => {
-                    row_id_set.extend(row_ids);
+                    row_id_set |= row_ids;
                 }
                 Err(_) => {
                     drop(index_ref);
@@ -10560,9 +11064,8 @@ impl QueryExecutor {
             }));
         }
 
-        // Sort row IDs for sequential LSM access (better cache locality)
-        let mut row_ids: Vec<u64> = row_id_set.into_iter().collect();
-        row_ids.sort_unstable();
+        // Ascending row IDs for sequential LSM access (better cache locality)
+        let row_ids: Vec<RowId> = row_id_set.iter().collect();
 
         // Batch fetch rows by ID
         let rows_result = match self.db.get_table_rows_batch_arc(table, &row_ids) {
@@ -10615,15 +11118,7 @@ impl QueryExecutor {
                         let vb = &b[col_idx];
                         let ord = match (va, vb) {
                             (Value::Float(fa), Value::Float(fb)) => {
-                                if fa.is_nan() && fb.is_nan() {
-                                    std::cmp::Ordering::Equal
-                                } else if fa.is_nan() {
-                                    std::cmp::Ordering::Greater
-                                } else if fb.is_nan() {
-                                    std::cmp::Ordering::Less
-                                } else {
-                                    fa.partial_cmp(fb).unwrap_or(std::cmp::Ordering::Equal)
-                                }
+                                crate::types::float_cmp(*fa, *fb)
                             }
                             (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
                             (Value::Null, _) => std::cmp::Ordering::Less,
@@ -10712,7 +11207,7 @@ impl QueryExecutor {
             Expr::In {
                 expr,
                 list,
-                negated: _,
+                negated,
             } => {
                 if let Expr::Column(col_name) = expr.as_ref() {
                     let pos = schema.get_column_position(if col_name.contains('.') {
@@ -10723,15 +11218,19 @@ impl QueryExecutor {
                     if list.iter().all(|e| matches!(e, Expr::Literal(_))) {
                         let set: std::collections::HashSet<Value> = list
                             .iter()
-                            .filter_map(|e| {
-                                if let Expr::Literal(v) = e {
-                                    Some(v.clone())
-                                } else {
-                                    None
-                                }
+                            .filter_map(|e| match e {
+                                Expr::Literal(Value::Null) => None,
+                                Expr::Literal(v) => Some(v.clone()),
+                                _ => None,
                             })
                             .collect();
-                        Some(CompiledWhere::InHash(pos, set))
+                        let has_null = list.iter().any(|e| matches!(e, Expr::Literal(Value::Null)));
+                        let in_set = CompiledWhere::InHash(pos, set, has_null);
+                        Some(if *negated {
+                            CompiledWhere::Not(Box::new(in_set))
+                        } else {
+                            in_set
+                        })
                     } else {
                         None
                     }
@@ -10744,8 +11243,8 @@ impl QueryExecutor {
             Expr::InHashset {
                 expr,
                 set,
-                negated: _,
-                has_null: _,
+                negated,
+                has_null,
             } => {
                 if let Expr::Column(col_name) = expr.as_ref() {
                     let pos = schema.get_column_position(if col_name.contains('.') {
@@ -10753,7 +11252,12 @@ impl QueryExecutor {
                     } else {
                         col_name
                     })?;
-                    Some(CompiledWhere::InHash(pos, set.clone()))
+                    let in_set = CompiledWhere::InHash(pos, set.clone(), *has_null);
+                    Some(if *negated {
+                        CompiledWhere::Not(Box::new(in_set))
+                    } else {
+                        in_set
+                    })
                 } else {
                     None
                 }
@@ -11080,7 +11584,40 @@ impl QueryExecutor {
             distinct: stmt.distinct,
             group_by: stmt.group_by.clone(),
             having: stmt.having.clone(),
+            after_cursor: stmt.after_cursor.clone(),
             latest_by: stmt.latest_by.clone(),
+            sample_by: stmt.sample_by.clone(),
+            fill: stmt.fill,
+        })
+    }
+
+    /// Substitute bound parameters in an UPDATE's SET and WHERE expressions.
+    fn substitute_params_update(&self, stmt: UpdateStmt) -> Result<UpdateStmt> {
+        let has_params = stmt
+            .where_clause
+            .as_ref()
+            .is_some_and(Self::contains_parameter)
+            || stmt
+                .assignments
+                .iter()
+                .any(|(_, e)| Self::contains_parameter(e));
+        if !has_params {
+            return Ok(stmt);
+        }
+
+        let params = self.evaluator.get_params();
+        Ok(UpdateStmt {
+            assignments: stmt
+                .assignments
+                .iter()
+                .map(|(c, e)| Ok((c.clone(), Self::substitute_expr(e, &params)?)))
+                .collect::<Result<_>>()?,
+            where_clause: stmt
+                .where_clause
+                .as_ref()
+                .map(|w| Self::substitute_expr(w, &params))
+                .transpose()?,
+            table: stmt.table,
         })
     }
 
@@ -11193,6 +11730,111 @@ impl QueryExecutor {
         }
     }
 
+    /// Parsed expressions of the table's VIRTUAL generated columns
+    fn virtual_column_exprs(schema: &TableSchema) -> Result<Vec<(String, Expr)>> {
+        schema
+            .columns
+            .iter()
+            .filter_map(|c| c.generated.as_ref().filter(|g| !g.stored).map(|g| (c, g)))
+            .map(|(c, g)| {
+                Ok((
+                    c.name.clone(),
+                    crate::database::generated::parse_generated_expr(&g.expr)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Replace references to VIRTUAL columns in an UPDATE/DELETE WHERE
+    /// clause with their expressions (stored rows hold NULL there).
+    fn inline_virtual_where(
+        where_clause: Option<Expr>,
+        schema: &TableSchema,
+    ) -> Result<Option<Expr>> {
+        match where_clause {
+            Some(wc) if schema.has_virtual_columns() => {
+                let virtuals = Self::virtual_column_exprs(schema)?;
+                Ok(Some(Self::inline_virtual_refs(&wc, &virtuals)))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn inline_virtual_refs(expr: &Expr, virtuals: &[(String, Expr)]) -> Expr {
+        let inline = |e: &Expr| Box::new(Self::inline_virtual_refs(e, virtuals));
+        match expr {
+            Expr::Column(name) => {
+                let bare = name.rsplit('.').next().unwrap_or(name);
+                virtuals
+                    .iter()
+                    .find(|(v, _)| v == bare)
+                    .map(|(_, e)| e.clone())
+                    .unwrap_or_else(|| expr.clone())
+            }
+            Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+                left: inline(left),
+                op: op.clone(),
+                right: inline(right),
+            },
+            Expr::UnaryOp { op, expr: inner } => Expr::UnaryOp {
+                op: op.clone(),
+                expr: inline(inner),
+            },
+            Expr::IsNull {
+                expr: inner,
+                negated,
+            } => Expr::IsNull {
+                expr: inline(inner),
+                negated: *negated,
+            },
+            Expr::In {
+                expr: inner,
+                list,
+                negated,
+            } => Expr::In {
+                expr: inline(inner),
+                list: list
+                    .iter()
+                    .map(|x| Self::inline_virtual_refs(x, virtuals))
+                    .collect(),
+                negated: *negated,
+            },
+            Expr::Between {
+                expr: inner,
+                low,
+                high,
+                negated,
+            } => Expr::Between {
+                expr: inline(inner),
+                low: inline(low),
+                high: inline(high),
+                negated: *negated,
+            },
+            Expr::Like {
+                expr: inner,
+                pattern,
+                negated,
+            } => Expr::Like {
+                expr: inline(inner),
+                pattern: inline(pattern),
+                negated: *negated,
+            },
+            Expr::FunctionCall {
+                name,
+                args,
+                distinct,
+            } => Expr::FunctionCall {
+                name: name.clone(),
+                args: args
+                    .iter()
+                    .map(|x| Self::inline_virtual_refs(x, virtuals))
+                    .collect(),
+                distinct: *distinct,
+            },
+            _ => expr.clone(),
+        }
+    }
+
     /// Evaluate expression directly on Vec<Value> using schema positions.
     /// Bypasses HashMap creation entirely.
     fn eval_expr_on_row(expr: &Expr, row: &[Value], schema: &TableSchema) -> Result<Value> {
@@ -11235,12 +11877,14 @@ impl QueryExecutor {
                             }))
                         }
                     }
-                    BinaryOperator::And => {
-                        Ok(Value::Bool(Self::is_truthy(&lv) && Self::is_truthy(&rv)))
-                    }
-                    BinaryOperator::Or => {
-                        Ok(Value::Bool(Self::is_truthy(&lv) || Self::is_truthy(&rv)))
-                    }
+                    BinaryOperator::And => Ok(truth_value(truth_and(
+                        Self::sql_truth(&lv),
+                        Self::sql_truth(&rv),
+                    ))),
+                    BinaryOperator::Or => Ok(truth_value(truth_or(
+                        Self::sql_truth(&lv),
+                        Self::sql_truth(&rv),
+                    ))),
                     BinaryOperator::Add => Self::positional_add(&lv, &rv),
                     BinaryOperator::Sub => Self::positional_sub(&lv, &rv),
                     BinaryOperator::Mul => Self::positional_mul(&lv, &rv),
@@ -11294,12 +11938,8 @@ impl QueryExecutor {
                 expr: inner,
             } => {
                 let v = Self::eval_expr_on_row(inner, row, schema)?;
-                // NOT NULL should be false (NULL), not true
-                if matches!(v, Value::Null) {
-                    Ok(Value::Bool(false))
-                } else {
-                    Ok(Value::Bool(!Self::is_truthy(&v)))
-                }
+                // NOT UNKNOWN stays UNKNOWN
+                Ok(truth_value(Self::sql_truth(&v).map(|t| !t)))
             }
             Expr::UnaryOp {
                 op: UnaryOperator::Minus,
@@ -11313,6 +11953,9 @@ impl QueryExecutor {
                         None => Value::Float(-(i as f64)),
                     }),
                     Value::Float(f) => Ok(Value::Float(-f)),
+                    Value::Decimal(ref d) => d.checked_neg().map(Value::decimal).ok_or_else(|| {
+                        MoteDBError::Query("numeric overflow in DECIMAL negation".into())
+                    }),
                     Value::Null => Ok(Value::Null),
                     _ => Err(MoteDBError::Query(format!("Cannot negate {:?}", v))),
                 }
@@ -11333,20 +11976,7 @@ impl QueryExecutor {
                 // path (Expr::In with Vec<Literal>) iterated the full list per
                 // row — O(rows × list_len).
                 let val = Self::eval_expr_on_row(expr, row, schema)?;
-                if matches!(val, Value::Null) {
-                    return Ok(Value::Bool(false));
-                }
-                let found = set.contains(&val);
-                if *negated {
-                    // NOT IN with NULL in subquery → UNKNOWN (false) for all rows.
-                    if *has_null {
-                        Ok(Value::Bool(false))
-                    } else {
-                        Ok(Value::Bool(!found))
-                    }
-                } else {
-                    Ok(Value::Bool(found))
-                }
+                Ok(truth_value(in_set_truth(&val, set, *negated, *has_null)))
             }
             Expr::In {
                 expr,
@@ -11355,7 +11985,7 @@ impl QueryExecutor {
             } => {
                 let val = Self::eval_expr_on_row(expr, row, schema)?;
                 if matches!(val, Value::Null) {
-                    return Ok(Value::Bool(false));
+                    return Ok(Value::Null);
                 }
                 let mut found = false;
                 let mut has_null = false;
@@ -11370,8 +12000,9 @@ impl QueryExecutor {
                         break;
                     }
                 }
-                if *negated && !found && has_null {
-                    return Ok(Value::Bool(false));
+                // No match against a list holding a NULL → UNKNOWN
+                if !found && has_null {
+                    return Ok(Value::Null);
                 }
                 Ok(Value::Bool(if *negated { !found } else { found }))
             }
@@ -11384,14 +12015,15 @@ impl QueryExecutor {
                 let val = Self::eval_expr_on_row(expr, row, schema)?;
                 let low_val = Self::eval_expr_on_row(low, row, schema)?;
                 let high_val = Self::eval_expr_on_row(high, row, schema)?;
-                if matches!(val, Value::Null)
-                    || matches!(low_val, Value::Null)
-                    || matches!(high_val, Value::Null)
-                {
-                    return Ok(Value::Bool(false));
-                }
-                let in_range = val >= low_val && val <= high_val;
-                Ok(Value::Bool(if *negated { !in_range } else { in_range }))
+                let in_range = truth_and(
+                    compare_truth(&val, &low_val, |v, low| v >= low),
+                    compare_truth(&val, &high_val, |v, high| v <= high),
+                );
+                Ok(truth_value(if *negated {
+                    in_range.map(|t| !t)
+                } else {
+                    in_range
+                }))
             }
             Expr::Like {
                 expr,
@@ -11400,9 +12032,9 @@ impl QueryExecutor {
             } => {
                 let val = Self::eval_expr_on_row(expr, row, schema)?;
                 let pat = Self::eval_expr_on_row(pattern, row, schema)?;
-                // NULL LIKE anything = false, NULL NOT LIKE anything = false (SQL NULL semantics)
+                // NULL [NOT] LIKE anything = UNKNOWN (SQL NULL semantics)
                 if matches!(val, Value::Null) || matches!(pat, Value::Null) {
-                    return Ok(Value::Bool(false));
+                    return Ok(Value::Null);
                 }
                 let matches = match (&val, &pat) {
                     (Value::Text(s), Value::Text(p)) => Self::simple_like_match(s, p),
@@ -11530,6 +12162,35 @@ impl QueryExecutor {
         }
     }
 
+    /// Schema positions of a point query's output columns when they can be
+    /// read on their own: plain column references only, at least one of
+    /// them VECTOR/TENSOR, and nothing else (filters, OFFSET) that needs the
+    /// rest of the row. Serving an embedding this way skips decoding the
+    /// row's other columns.
+    fn vector_projection(
+        stmt: &SelectStmt,
+        schema: &TableSchema,
+        post_filters: &[Expr],
+    ) -> Option<Vec<usize>> {
+        if !post_filters.is_empty() || stmt.offset.unwrap_or(0) > 0 || stmt.limit == Some(0) {
+            return None;
+        }
+        let positions = stmt
+            .columns
+            .iter()
+            .map(|col| match col {
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _) => {
+                    schema.get_column_position(name.rsplit('.').next().unwrap_or(name))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        positions
+            .iter()
+            .any(|&pos| schema.columns[pos].col_type.is_vector())
+            .then_some(positions)
+    }
+
     /// 🚀 P0 Optimization: Direct row projection (skips HashMap conversion)
     ///
     /// For PK point queries, the old path was:
@@ -11633,18 +12294,914 @@ impl QueryExecutor {
                 } else {
                     None
                 }
-            })
-            .collect();
-
-        Some(StreamingQueryResult::SelectStreaming {
-            columns: columns.to_vec(),
-            rows: Box::new(results.into_iter().map(Ok)),
-            order_by: stmt.order_by.clone(),
-            limit: stmt.limit,
-            offset: stmt.offset,
-            distinct: stmt.distinct,
-            max_result_rows: None,
-            size_hint: None,
+            })
+            .collect();
+
+        Some(StreamingQueryResult::SelectStreaming {
+            columns: columns.to_vec(),
+            rows: Box::new(results.into_iter().map(Ok)),
+            order_by: stmt.order_by.clone(),
+            limit: stmt.limit,
+            offset: stmt.offset,
+            distinct: stmt.distinct,
+            max_result_rows: None,
+            size_hint: None,
+        })
+    }
+
+    /// `TIME_BUCKET('<interval>', col)` with a literal interval: `(interval, col)`
+    fn time_bucket_call(expr: &Expr) -> Option<(&str, &str)> {
+        match expr {
+            Expr::FunctionCall { name, args, .. } if name.eq_ignore_ascii_case("time_bucket") => {
+                match args.as_slice() {
+                    [Expr::Literal(Value::Text(interval)), Expr::Column(col)] => {
+                        Some((interval, col.as_str()))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the SELECT is a bucketed aggregation: `SAMPLE BY`, or a
+    /// GROUP BY naming the alias of a `TIME_BUCKET(...)` column
+    fn is_time_bucket_select(stmt: &SelectStmt) -> bool {
+        stmt.sample_by.is_some() || stmt.fill.is_some() || Self::time_bucket_group(stmt).is_some()
+    }
+
+    /// `(alias, interval, timestamp column)` of the TIME_BUCKET column grouped on
+    fn time_bucket_group(stmt: &SelectStmt) -> Option<(String, String, String)> {
+        let group_by = stmt.group_by.as_ref()?;
+        stmt.columns.iter().find_map(|col| match col {
+            SelectColumn::Expr(expr, Some(alias)) if group_by.contains(alias) => {
+                Self::time_bucket_call(expr)
+                    .map(|(interval, ts)| (alias.clone(), interval.to_string(), ts.to_string()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Bucketed time-series aggregation (`SAMPLE BY` / `GROUP BY` a
+    /// TIME_BUCKET alias).
+    ///
+    /// Plain aggregates over one table are folded bucket by bucket while the
+    /// rows stream past, reading only the requested time range through the
+    /// columnar store (TIMESERIES tables) or the timestamp column's index.
+    /// Anything else runs as a GROUP BY over a derived table that computes
+    /// the bucket column.
+    fn execute_time_bucket_select(&self, stmt: &SelectStmt) -> Result<QueryResult> {
+        let desugared;
+        let stmt = if stmt.sample_by.is_some() {
+            desugared = self.desugar_sample_by(stmt)?;
+            &desugared
+        } else {
+            stmt
+        };
+        let (alias, interval, ts_col) = Self::time_bucket_group(stmt).ok_or_else(|| {
+            MoteDBError::InvalidArgument(
+                "FILL requires SAMPLE BY or GROUP BY on a TIME_BUCKET alias".to_string(),
+            )
+        })?;
+        let interval_micros = crate::sql::evaluator::parse_interval_to_micros(&interval)?;
+
+        if let Some(mode) = stmt.fill {
+            // Fill the complete grid first; ORDER BY / OFFSET / LIMIT apply after
+            let mut base = stmt.clone();
+            base.fill = None;
+            base.order_by = None;
+            base.offset = None;
+            base.limit = None;
+            let (columns, rows) = match self.execute_time_bucket_select(&base)? {
+                QueryResult::Select { columns, rows } => (columns, rows),
+                other => return Ok(other),
+            };
+            let range = self.extract_time_range(&stmt.where_clause, &ts_col);
+            let mut rows = Self::fill_time_buckets(
+                stmt,
+                &columns,
+                rows,
+                &alias,
+                interval_micros,
+                range,
+                mode,
+            )?;
+            let default_order = [OrderByExpr {
+                expr: Expr::Column(alias),
+                asc: true,
+            }];
+            let order_by = stmt.order_by.as_deref().unwrap_or(&default_order);
+            StreamingQueryResult::apply_order_by(&mut rows, &columns, order_by)?;
+            let rows = rows
+                .into_iter()
+                .skip(stmt.offset.unwrap_or(0))
+                .take(stmt.limit.unwrap_or(usize::MAX))
+                .collect();
+            return Ok(QueryResult::Select { columns, rows });
+        }
+
+        if let Some(result) =
+            self.try_time_bucket_aggregate(stmt, &alias, interval_micros, &ts_col)?
+        {
+            return Ok(result);
+        }
+
+        let from_alias = match &stmt.from {
+            Some(TableRef::Table { name, alias }) => alias.as_ref().unwrap_or(name).clone(),
+            Some(TableRef::Subquery { alias, .. }) => alias.clone(),
+            _ => {
+                return Err(MoteDBError::InvalidArgument(
+                    "TIME_BUCKET grouping needs one table in FROM".to_string(),
+                ))
+            }
+        };
+        // Derived table: the source columns plus the bucket column
+        let mut outer_columns = Vec::with_capacity(stmt.columns.len());
+        let mut bucket = None;
+        for col in &stmt.columns {
+            match col {
+                SelectColumn::Expr(expr, Some(a)) if *a == alias => {
+                    bucket = Some(SelectColumn::Expr(expr.clone(), Some(alias.clone())));
+                    outer_columns.push(SelectColumn::Column(alias.clone()));
+                }
+                other => outer_columns.push(other.clone()),
+            }
+        }
+        let mut inner_columns: Vec<SelectColumn> = match &stmt.from {
+            Some(TableRef::Table { name, .. }) => self
+                .db
+                .get_table_schema(name)?
+                .columns
+                .iter()
+                .filter(|c| c.name != alias)
+                .map(|c| SelectColumn::Column(c.name.clone()))
+                .collect(),
+            _ => vec![SelectColumn::Star],
+        };
+        inner_columns.extend(bucket);
+        let inner = SelectStmt {
+            distinct: false,
+            columns: inner_columns,
+            from: stmt.from.clone(),
+            where_clause: stmt.where_clause.clone(),
+            group_by: None,
+            having: None,
+            order_by: None,
+            limit: None,
+            offset: None,
+            after_cursor: None,
+            latest_by: None,
+            sample_by: None,
+            fill: None,
+        };
+        let mut outer = stmt.clone();
+        outer.columns = outer_columns;
+        outer.where_clause = None;
+        outer.from = Some(TableRef::Subquery {
+            query: Box::new(inner),
+            alias: from_alias,
+        });
+        self.execute_select_internal(&outer)
+    }
+
+    /// Rewrite `SELECT ts, agg(..) FROM t SAMPLE BY 1s` into
+    /// `SELECT TIME_BUCKET('1s', ts) AS ts, agg(..) FROM t GROUP BY ts ORDER BY ts`.
+    /// Other plain columns in the select list become extra group keys.
+    fn desugar_sample_by(&self, stmt: &SelectStmt) -> Result<SelectStmt> {
+        let sample_by = stmt.sample_by.as_ref().expect("checked by caller");
+        if stmt.group_by.is_some() {
+            return Err(MoteDBError::InvalidArgument(
+                "SAMPLE BY cannot be combined with GROUP BY".to_string(),
+            ));
+        }
+        let table = match &stmt.from {
+            Some(TableRef::Table { name, .. }) => name,
+            _ => {
+                return Err(MoteDBError::InvalidArgument(
+                    "SAMPLE BY needs one table in FROM".to_string(),
+                ))
+            }
+        };
+        crate::sql::evaluator::parse_interval_to_micros(&sample_by.interval)?;
+        let schema = self.db.get_table_schema(table)?;
+        let ts_col = schema
+            .timeseries_column
+            .clone()
+            .or_else(|| {
+                schema
+                    .columns
+                    .iter()
+                    .find(|c| c.col_type == ColumnType::Timestamp)
+                    .map(|c| c.name.clone())
+            })
+            .ok_or_else(|| {
+                MoteDBError::Query(format!(
+                    "SAMPLE BY requires a TIMESTAMP column in table '{}'",
+                    table
+                ))
+            })?;
+
+        let mut bucket_alias = None;
+        let mut group_by = Vec::new();
+        let mut columns = Vec::with_capacity(stmt.columns.len());
+        for col in &stmt.columns {
+            match col {
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _)
+                    if name.rsplit('.').next() == Some(ts_col.as_str()) =>
+                {
+                    let alias = match col {
+                        SelectColumn::ColumnWithAlias(_, a) => a.clone(),
+                        _ => ts_col.clone(),
+                    };
+                    columns.push(SelectColumn::Expr(
+                        Expr::FunctionCall {
+                            name: "TIME_BUCKET".to_string(),
+                            args: vec![
+                                Expr::Literal(Value::text_from(sample_by.interval.as_str())),
+                                Expr::Column(name.clone()),
+                            ],
+                            distinct: false,
+                        },
+                        Some(alias.clone()),
+                    ));
+                    group_by.insert(0, alias.clone());
+                    bucket_alias = Some(alias);
+                }
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _) => {
+                    group_by.push(name.clone());
+                    columns.push(col.clone());
+                }
+                SelectColumn::Star => {
+                    return Err(MoteDBError::InvalidArgument(
+                        "SELECT * not allowed with SAMPLE BY".to_string(),
+                    ))
+                }
+                SelectColumn::Expr(..) => columns.push(col.clone()),
+            }
+        }
+        let bucket_alias = bucket_alias.ok_or_else(|| {
+            MoteDBError::InvalidArgument(format!(
+                "SAMPLE BY requires the timestamp column '{}' in the select list",
+                ts_col
+            ))
+        })?;
+
+        let mut out = stmt.clone();
+        out.columns = columns;
+        out.group_by = Some(group_by);
+        out.sample_by = None;
+        if out.order_by.is_none() {
+            out.order_by = Some(vec![OrderByExpr {
+                expr: Expr::Column(bucket_alias),
+                asc: true,
+            }]);
+        }
+        Ok(out)
+    }
+
+    /// Streaming fast path of `execute_time_bucket_select`: one accumulator
+    /// set per (bucket, group key), so memory is bounded by the number of
+    /// buckets rather than the number of rows. Returns `Ok(None)` when the
+    /// query needs the general GROUP BY machinery.
+    fn try_time_bucket_aggregate(
+        &self,
+        stmt: &SelectStmt,
+        bucket_alias: &str,
+        interval_micros: i64,
+        ts_col: &str,
+    ) -> Result<Option<QueryResult>> {
+        let table = match &stmt.from {
+            Some(TableRef::Table { name, .. }) => name,
+            _ => return Ok(None),
+        };
+        if stmt.distinct || stmt.having.is_some() || stmt.latest_by.is_some() {
+            return Ok(None);
+        }
+        if let Some(ref where_clause) = stmt.where_clause {
+            if !Self::can_eval_positional(where_clause)
+                || Self::expr_contains_subquery(where_clause)
+            {
+                return Ok(None);
+            }
+        }
+        let bare = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+        let schema = self.db.get_table_schema(table)?;
+        let ts_pos = match schema.get_column_position(&bare(ts_col)) {
+            Some(pos) if schema.columns[pos].col_type == ColumnType::Timestamp => pos,
+            _ => return Ok(None),
+        };
+
+        // Group keys besides the bucket
+        let mut key_positions = Vec::new();
+        for name in stmt.group_by.iter().flatten() {
+            if name == bucket_alias {
+                continue;
+            }
+            match schema.get_column_position(&bare(name)) {
+                Some(pos) => key_positions.push(pos),
+                None => return Ok(None),
+            }
+        }
+
+        enum Output {
+            Bucket,
+            Key(usize),
+            Agg(AggregateInfo),
+        }
+        let mut outputs = Vec::with_capacity(stmt.columns.len());
+        let mut column_names = Vec::with_capacity(stmt.columns.len());
+        for col in &stmt.columns {
+            let (output, name) = match col {
+                SelectColumn::Expr(expr, Some(a))
+                    if a == bucket_alias && Self::time_bucket_call(expr).is_some() =>
+                {
+                    (Output::Bucket, a.clone())
+                }
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _) => {
+                    let key = schema
+                        .get_column_position(&bare(name))
+                        .and_then(|pos| key_positions.iter().position(|p| *p == pos));
+                    let Some(key) = key else {
+                        return Ok(None);
+                    };
+                    let out_name = match col {
+                        SelectColumn::ColumnWithAlias(_, a) => a.clone(),
+                        _ => name.clone(),
+                    };
+                    (Output::Key(key), out_name)
+                }
+                SelectColumn::Expr(expr, alias) => {
+                    let agg = match self.try_parse_aggregate(expr, &schema) {
+                        Some(agg) if !agg.distinct => agg,
+                        _ => return Ok(None),
+                    };
+                    let numeric = agg.col_pos.is_some_and(|p| {
+                        matches!(
+                            schema.columns[p].col_type,
+                            ColumnType::Integer | ColumnType::Float
+                        )
+                    });
+                    match agg.func.as_str() {
+                        "COUNT" | "MIN" | "MAX" => {}
+                        "SUM" | "AVG" if numeric => {}
+                        _ => return Ok(None),
+                    }
+                    let name = alias
+                        .clone()
+                        .unwrap_or_else(|| Self::expr_to_column_name(expr));
+                    (Output::Agg(agg), name)
+                }
+                SelectColumn::Star => return Ok(None),
+            };
+            outputs.push(output);
+            column_names.push(name);
+        }
+        // ORDER BY must name output columns (or positions) to be applied here
+        if let Some(ref order_by) = stmt.order_by {
+            let resolvable = order_by.iter().all(|ob| match &ob.expr {
+                Expr::Column(name) => column_names.iter().any(|c| *c == bare(name)),
+                Expr::Literal(Value::Integer(_)) => true,
+                _ => false,
+            });
+            if !resolvable {
+                return Ok(None);
+            }
+        }
+
+        #[derive(Default)]
+        struct BucketAcc {
+            count: i64,
+            sum: crate::types::NumericSum,
+            min: Option<Value>,
+            max: Option<Value>,
+        }
+        let aggs: Vec<&AggregateInfo> = outputs
+            .iter()
+            .filter_map(|o| match o {
+                Output::Agg(agg) => Some(agg),
+                _ => None,
+            })
+            .collect();
+        // Bucket start (None for a NULL timestamp) + group key → accumulators
+        use std::collections::HashMap;
+        let mut groups: HashMap<(Option<i64>, Vec<Value>), Vec<BucketAcc>> = HashMap::new();
+        let where_clause = stmt.where_clause.as_ref();
+        let mut fold = |row: &[Value]| -> Result<()> {
+            if let Some(clause) = where_clause {
+                if !matches!(
+                    Self::eval_expr_on_row(clause, row, &schema)?,
+                    Value::Bool(true)
+                ) {
+                    return Ok(());
+                }
+            }
+            let bucket = match row.get(ts_pos) {
+                Some(Value::Timestamp(ts)) => {
+                    Some(ts.as_micros().div_euclid(interval_micros) * interval_micros)
+                }
+                Some(Value::Integer(micros)) => {
+                    Some(micros.div_euclid(interval_micros) * interval_micros)
+                }
+                _ => None,
+            };
+            let key: Vec<Value> = key_positions
+                .iter()
+                .map(|&p| row.get(p).cloned().unwrap_or(Value::Null))
+                .collect();
+            let accs = groups
+                .entry((bucket, key))
+                .or_insert_with(|| aggs.iter().map(|_| BucketAcc::default()).collect());
+            for (acc, agg) in accs.iter_mut().zip(&aggs) {
+                let Some(pos) = agg.col_pos else {
+                    acc.count += 1; // COUNT(*)
+                    continue;
+                };
+                let val = row.get(pos).unwrap_or(&Value::Null);
+                if matches!(val, Value::Null) {
+                    continue;
+                }
+                acc.count += 1;
+                acc.sum.add(val);
+                if acc
+                    .min
+                    .as_ref()
+                    .is_none_or(|m| val.partial_cmp(m) == Some(Ordering::Less))
+                {
+                    acc.min = Some(val.clone());
+                }
+                if acc
+                    .max
+                    .as_ref()
+                    .is_none_or(|m| val.partial_cmp(m) == Some(Ordering::Greater))
+                {
+                    acc.max = Some(val.clone());
+                }
+            }
+            Ok(())
+        };
+
+        let range = self.extract_time_range(&stmt.where_clause, ts_col);
+        if schema.table_type == crate::types::TableType::TimeSeries {
+            // Zone maps + sorted timestamps prune segments outside the range
+            let (start, end) = range.unwrap_or((i64::MIN, i64::MAX));
+            let rows = self
+                .db
+                .columnar_store
+                .query_time_range(table, start, end, &[])?;
+            for (_, sql_row) in rows {
+                let row: Vec<Value> = schema
+                    .columns
+                    .iter()
+                    .map(|c| sql_row.get(&c.name).cloned().unwrap_or(Value::Null))
+                    .collect();
+                fold(&row)?;
+            }
+        } else {
+            // Timestamp index keys are big-endian i64, so only a non-negative
+            // lower bound gives a contiguous range scan.
+            let index_ids = match range {
+                Some((start, end)) if start >= 0 && start <= end => {
+                    let index_name = format!("{}.{}", table, bare(ts_col));
+                    let index = self
+                        .db
+                        .column_indexes
+                        .get(&index_name)
+                        .map(|r| r.value().clone());
+                    match index {
+                        Some(index) => {
+                            let ids = index.query_between(
+                                &Value::Timestamp(crate::types::Timestamp::from_micros(start)),
+                                true,
+                                &Value::Timestamp(crate::types::Timestamp::from_micros(end)),
+                                true,
+                            )?;
+                            // The async index pipeline may still be catching up
+                            if ids.is_empty() && self.db.is_async_index_pipeline_active() {
+                                None
+                            } else {
+                                Some(ids)
+                            }
+                        }
+                        None => None,
+                    }
+                }
+                _ => None,
+            };
+            match index_ids {
+                Some(ids) => {
+                    for chunk in ids.chunks(1000) {
+                        for (_, row) in self.db.get_table_rows_batch(table, chunk)? {
+                            if let Some(row) = row {
+                                fold(&row)?;
+                            }
+                        }
+                    }
+                }
+                None => {
+                    for item in self.db.scan_table_rows_streaming(table)? {
+                        let (_, row) = item?;
+                        fold(&row)?;
+                    }
+                }
+            }
+        }
+
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_by(|((a, ka), _), ((b, kb), _)| {
+            a.cmp(b).then_with(|| {
+                ka.iter()
+                    .zip(kb)
+                    .map(|(x, y)| StreamingQueryResult::compare_values(x, y))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        });
+        let mut rows: Vec<Vec<Value>> = groups
+            .into_iter()
+            .map(|((bucket, key), accs)| {
+                let mut accs = accs.into_iter().zip(&aggs);
+                outputs
+                    .iter()
+                    .map(|o| match o {
+                        Output::Bucket => Ok(bucket
+                            .map(|b| Value::Timestamp(crate::types::Timestamp::from_micros(b)))
+                            .unwrap_or(Value::Null)),
+                        Output::Key(k) => Ok(key[*k].clone()),
+                        Output::Agg(_) => {
+                            let (acc, agg) = accs.next().expect("one accumulator per aggregate");
+                            Ok(match agg.func.as_str() {
+                                "COUNT" => Value::Integer(acc.count),
+                                _ if acc.count == 0 => Value::Null,
+                                "SUM" => acc.sum.sum()?,
+                                "AVG" => acc.sum.avg(),
+                                "MIN" => acc.min.unwrap_or(Value::Null),
+                                _ => acc.max.unwrap_or(Value::Null),
+                            })
+                        }
+                    })
+                    .collect::<Result<Vec<Value>>>()
+            })
+            .collect::<Result<_>>()?;
+
+        if let Some(ref order_by) = stmt.order_by {
+            StreamingQueryResult::apply_order_by(&mut rows, &column_names, order_by)?;
+        }
+        let rows = rows
+            .into_iter()
+            .skip(stmt.offset.unwrap_or(0))
+            .take(stmt.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(Some(QueryResult::Select {
+            columns: column_names,
+            rows,
+        }))
+    }
+
+    /// Produce the buckets missing from a bucketed result (`FILL(...)`).
+    ///
+    /// Every group key gets the same grid of buckets: the WHERE time range
+    /// when it is bounded, otherwise the first through the last bucket seen.
+    /// The bucket and group key columns of a filled row are set; the other
+    /// columns are NULL, the previous bucket's value, or interpolated
+    /// between the nearest non-NULL neighbours. Rows with a NULL bucket are
+    /// kept as they are.
+    fn fill_time_buckets(
+        stmt: &SelectStmt,
+        columns: &[String],
+        rows: Vec<Vec<Value>>,
+        bucket_alias: &str,
+        interval_micros: i64,
+        range: Option<(i64, i64)>,
+        mode: FillMode,
+    ) -> Result<Vec<Vec<Value>>> {
+        /// Upper bound on buckets generated per group key
+        const MAX_FILL_BUCKETS: i64 = 1_000_000;
+
+        let bucket_pos = columns
+            .iter()
+            .position(|c| c == bucket_alias)
+            .ok_or_else(|| {
+                MoteDBError::InvalidArgument(format!(
+                    "FILL requires the bucket column '{}' in the select list",
+                    bucket_alias
+                ))
+            })?;
+        let group_by = stmt.group_by.as_deref().unwrap_or(&[]);
+        let key_positions: Vec<usize> = stmt
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, col)| match col {
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _)
+                    if group_by.contains(name) =>
+                {
+                    Some(i)
+                }
+                _ => None,
+            })
+            .collect();
+        let micros = |v: &Value| match v {
+            Value::Timestamp(ts) => Some(ts.as_micros()),
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        };
+        let as_f64 = |v: &Value| match v {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            Value::Decimal(d) => Some(d.to_f64()),
+            _ => None,
+        };
+
+        // Rows per group key, in first-seen order, keyed by bucket start
+        let mut keys: Vec<Vec<Value>> = Vec::new();
+        let mut groups: Vec<std::collections::BTreeMap<i64, Vec<Value>>> = Vec::new();
+        let mut null_buckets = Vec::new();
+        let (mut lo, mut hi) = (i64::MAX, i64::MIN);
+        for row in rows {
+            let Some(bucket) = micros(&row[bucket_pos]) else {
+                null_buckets.push(row);
+                continue;
+            };
+            lo = lo.min(bucket);
+            hi = hi.max(bucket);
+            let key: Vec<Value> = key_positions.iter().map(|&p| row[p].clone()).collect();
+            let idx = match keys.iter().position(|k| *k == key) {
+                Some(idx) => idx,
+                None => {
+                    keys.push(key);
+                    groups.push(Default::default());
+                    keys.len() - 1
+                }
+            };
+            groups[idx].insert(bucket, row);
+        }
+        let floor = |t: i64| t.div_euclid(interval_micros) * interval_micros;
+        if let Some((start, end)) = range {
+            if start != i64::MIN {
+                lo = floor(start);
+            }
+            if end != i64::MAX {
+                hi = floor(end);
+            }
+        }
+        if groups.is_empty() || lo > hi {
+            return Ok(null_buckets);
+        }
+        if (hi - lo) / interval_micros >= MAX_FILL_BUCKETS {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "FILL would produce more than {} buckets; narrow the time range",
+                MAX_FILL_BUCKETS
+            )));
+        }
+
+        let mut out = Vec::new();
+        for (key, present) in keys.iter().zip(groups) {
+            let mut filled: Vec<Vec<Value>> = Vec::new();
+            let mut missing: Vec<bool> = Vec::new();
+            let mut bucket = lo;
+            while bucket <= hi {
+                match present.get(&bucket) {
+                    Some(row) => {
+                        filled.push(row.clone());
+                        missing.push(false);
+                    }
+                    None => {
+                        let mut row = vec![Value::Null; columns.len()];
+                        row[bucket_pos] =
+                            Value::Timestamp(crate::types::Timestamp::from_micros(bucket));
+                        for (k, &p) in key_positions.iter().enumerate() {
+                            row[p] = key[k].clone();
+                        }
+                        filled.push(row);
+                        missing.push(true);
+                    }
+                }
+                bucket += interval_micros;
+            }
+
+            let value_cols: Vec<usize> = (0..columns.len())
+                .filter(|c| *c != bucket_pos && !key_positions.contains(c))
+                .collect();
+            match mode {
+                FillMode::Null => {}
+                FillMode::Previous => {
+                    for i in 1..filled.len() {
+                        if missing[i] {
+                            for &c in &value_cols {
+                                filled[i][c] = filled[i - 1][c].clone();
+                            }
+                        }
+                    }
+                }
+                FillMode::Linear => {
+                    for &c in &value_cols {
+                        let known: Vec<usize> = (0..filled.len())
+                            .filter(|i| !matches!(filled[*i][c], Value::Null))
+                            .collect();
+                        for pair in known.windows(2) {
+                            let (a, b) = (pair[0], pair[1]);
+                            for i in a + 1..b {
+                                if !missing[i] {
+                                    continue;
+                                }
+                                let t = (i - a) as f64 / (b - a) as f64;
+                                filled[i][c] = match (&filled[a][c], &filled[b][c]) {
+                                    (Value::Integer(x), Value::Integer(y)) => Value::Integer(
+                                        (*x as f64 + (*y - *x) as f64 * t).round() as i64,
+                                    ),
+                                    (x, y) => match (as_f64(x), as_f64(y)) {
+                                        (Some(x), Some(y)) => Value::Float(x + (y - x) * t),
+                                        _ => Value::Null,
+                                    },
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+            out.extend(filled);
+        }
+        out.extend(null_buckets);
+        Ok(out)
+    }
+
+    /// Whether any SELECT column is a top-level `UNNEST(...)` call
+    fn select_has_unnest(columns: &[SelectColumn]) -> bool {
+        columns.iter().any(|col| {
+            matches!(col, SelectColumn::Expr(Expr::FunctionCall { name, .. }, _)
+                if name.eq_ignore_ascii_case("unnest"))
+        })
+    }
+
+    /// SELECT with `UNNEST(array)` columns: one output row per array element
+    ///
+    /// A plain projection runs with each `UNNEST(e)` replaced by `e`, then
+    /// expands the array columns. Several UNNESTs are zipped, padding the
+    /// shorter arrays with NULL; a NULL or empty array produces no rows.
+    /// When DISTINCT, GROUP BY, aggregates, ORDER BY or LIMIT are present,
+    /// the expansion becomes a derived table and the statement runs over it,
+    /// so `GROUP BY label` sees one row per element.
+    fn execute_unnest_select(&self, stmt: &SelectStmt) -> Result<QueryResult> {
+        let unnest_arg = |col: &SelectColumn| -> Option<Result<(Expr, Option<String>)>> {
+            match col {
+                SelectColumn::Expr(Expr::FunctionCall { name, args, .. }, alias)
+                    if name.eq_ignore_ascii_case("unnest") =>
+                {
+                    Some(match args.as_slice() {
+                        [arg] => Ok((arg.clone(), alias.clone())),
+                        _ => Err(MoteDBError::InvalidArgument(
+                            "UNNEST() takes 1 argument".to_string(),
+                        )),
+                    })
+                }
+                _ => None,
+            }
+        };
+
+        let post_projection = stmt.distinct
+            || stmt.group_by.is_some()
+            || stmt.having.is_some()
+            || stmt.order_by.is_some()
+            || stmt.limit.is_some()
+            || stmt.offset.is_some()
+            || stmt.latest_by.is_some()
+            || self.has_aggregates(&stmt.columns)
+            || stmt
+                .columns
+                .iter()
+                .any(|c| matches!(c, SelectColumn::Expr(Expr::WindowFunction { .. }, _)));
+
+        if post_projection {
+            let from_alias = match &stmt.from {
+                Some(TableRef::Table { name, alias }) => alias.as_ref().unwrap_or(name).clone(),
+                Some(TableRef::Subquery { alias, .. }) => alias.clone(),
+                _ => {
+                    return Err(MoteDBError::InvalidArgument(
+                        "UNNEST() with GROUP BY/ORDER BY/LIMIT needs one table in FROM".to_string(),
+                    ))
+                }
+            };
+            // Derived table: the source columns plus one column per UNNEST
+            let mut unnested = Vec::new();
+            let mut outer_columns = Vec::with_capacity(stmt.columns.len());
+            for col in &stmt.columns {
+                match unnest_arg(col) {
+                    Some(arg) => {
+                        let (expr, alias) = arg?;
+                        let name = alias.unwrap_or_else(|| "unnest".to_string());
+                        outer_columns.push(SelectColumn::Column(name.clone()));
+                        unnested.push(SelectColumn::Expr(
+                            Expr::FunctionCall {
+                                name: "unnest".to_string(),
+                                args: vec![expr],
+                                distinct: false,
+                            },
+                            Some(name),
+                        ));
+                    }
+                    None => outer_columns.push(col.clone()),
+                }
+            }
+            let mut inner_columns = match &stmt.from {
+                Some(TableRef::Table { name, .. }) => {
+                    let schema = self.db.get_table_schema(name)?;
+                    schema
+                        .columns
+                        .iter()
+                        .filter(|c| {
+                            !unnested.iter().any(
+                                |u| matches!(u, SelectColumn::Expr(_, Some(n)) if *n == c.name),
+                            )
+                        })
+                        .map(|c| SelectColumn::Column(c.name.clone()))
+                        .collect()
+                }
+                _ => vec![SelectColumn::Star],
+            };
+            inner_columns.extend(unnested);
+            let inner = SelectStmt {
+                distinct: false,
+                columns: inner_columns,
+                from: stmt.from.clone(),
+                where_clause: stmt.where_clause.clone(),
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+                offset: None,
+                after_cursor: None,
+                latest_by: None,
+                sample_by: None,
+                fill: None,
+            };
+            let mut outer = stmt.clone();
+            outer.columns = outer_columns;
+            outer.where_clause = None;
+            outer.from = Some(TableRef::Subquery {
+                query: Box::new(inner),
+                alias: from_alias,
+            });
+            return self.execute_select_internal(&outer);
+        }
+
+        // Plain projection: evaluate the array expressions, then expand
+        let mut projected = stmt.clone();
+        let mut targets = Vec::new(); // (placeholder alias, output name)
+        for (i, col) in projected.columns.iter_mut().enumerate() {
+            if let Some(arg) = unnest_arg(col) {
+                let (expr, alias) = arg?;
+                let placeholder = format!("unnest${}", i);
+                targets.push((
+                    placeholder.clone(),
+                    alias.unwrap_or_else(|| "unnest".to_string()),
+                ));
+                // Bare columns keep the form the parser produces for `col AS x`
+                *col = match expr {
+                    Expr::Column(name) => SelectColumn::ColumnWithAlias(name, placeholder),
+                    expr => SelectColumn::Expr(expr, Some(placeholder)),
+                };
+            }
+        }
+        let (mut columns, rows) = match self.execute_select_internal(&projected)? {
+            QueryResult::Select { columns, rows } => (columns, rows),
+            other => return Ok(other),
+        };
+        let mut positions = Vec::with_capacity(targets.len());
+        for (idx, col) in columns.iter_mut().enumerate() {
+            if let Some((_, name)) = targets.iter().find(|(p, _)| p == col) {
+                *col = name.clone();
+                positions.push(idx);
+            }
+        }
+
+        let mut expanded = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut arrays = Vec::with_capacity(positions.len());
+            for &p in &positions {
+                arrays.push(match &row[p] {
+                    Value::Array(items) => items.to_vec(),
+                    Value::Vector(v) => v.iter().map(|f| Value::Float(*f as f64)).collect(),
+                    Value::Null => Vec::new(),
+                    other => {
+                        return Err(MoteDBError::TypeError(format!(
+                            "UNNEST() requires an array, got {:?}",
+                            other
+                        )))
+                    }
+                });
+            }
+            let n = arrays.iter().map(Vec::len).max().unwrap_or(0);
+            for k in 0..n {
+                let mut out = row.clone();
+                for (&p, items) in positions.iter().zip(&arrays) {
+                    out[p] = items.get(k).cloned().unwrap_or(Value::Null);
+                }
+                expanded.push(out);
+            }
+        }
+        Ok(QueryResult::Select {
+            columns,
+            rows: expanded,
         })
     }
 
@@ -11664,6 +13221,14 @@ impl QueryExecutor {
             stmt
         };
 
+        if Self::select_has_unnest(&stmt.columns) {
+            return self.execute_unnest_select(stmt);
+        }
+
+        if Self::is_time_bucket_select(stmt) {
+            return self.execute_time_bucket_select(stmt);
+        }
+
         // Validate SELECT column references against the table schema (when a
         // single table is named). A bare column that doesn't exist in the
         // table is a query error, not a silent NULL/value from another column.
@@ -11725,6 +13290,31 @@ impl QueryExecutor {
         // From here on, we know stmt.from is Some. Extracted once below.
         let from = stmt.from.as_ref().unwrap();
 
+        // COUNT_ESTIMATE(): answered from storage metadata, no scan
+        if Self::select_calls_function(&stmt.columns, "COUNT_ESTIMATE") {
+            return self.execute_count_estimate(stmt, from);
+        }
+
+        // AFTER CURSOR: resume the ORDER BY column index walk
+        if stmt.after_cursor.is_some() {
+            let page = self.execute_select_page(stmt)?;
+            return Ok(QueryResult::Select {
+                columns: page.columns,
+                rows: page.rows,
+            });
+        }
+
+        // LATEST BY over one table: reduce to the newest row per group first
+        // (via indexes when possible), then project/sort as usual.
+        if stmt.latest_by.is_some() {
+            if let TableRef::Table {
+                name: table_name, ..
+            } = from
+            {
+                return self.execute_latest_by_select(stmt, table_name);
+            }
+        }
+
         // 🚀 FAST PATH -3b: Positional INNER JOIN (equi-join) for two tables.
         // Bypasses SqlRow(HashMap) entirely — scans both tables as Vec<Value>,
         // builds a hash table on the join column, probes, and concatenates.
@@ -11747,6 +13337,31 @@ impl QueryExecutor {
                 },
             ) = (left.as_ref(), right.as_ref())
             {
+                // A filter on the build side (dim) lets the operator tree push
+                // a bloom filter of the surviving join keys into the probe
+                // scan, skipping fact rows before they're decoded. That beats
+                // decoding every row and applying WHERE after the join.
+                if stmt.where_clause.is_some() && !self.has_aggregates(&stmt.columns) {
+                    let planner = super::physical::PhysicalPlanner::new(
+                        &self.db,
+                        &self.optimizer,
+                        self,
+                        self.vector_search_options(),
+                    );
+                    if let Some(plan) = planner.plan_select(stmt)? {
+                        if !plan.runtime_filters().is_empty() {
+                            debug_log!("[Executor] physical plan:\n{}", plan.explain());
+                            let filters = plan.runtime_filters().to_vec();
+                            let (columns, rows) = plan.execute()?;
+                            debug_log!(
+                                "[Executor] runtime filters skipped {} probe rows",
+                                filters.iter().map(|f| f.rejected()).sum::<u64>()
+                            );
+                            return Ok(QueryResult::Select { columns, rows });
+                        }
+                    }
+                }
+
                 // Only for equi-join: a.col = b.col
                 if let Some((lcol_full, rcol_full)) = self.extract_equi_join_columns(on_condition) {
                     // Only when no GROUP BY / HAVING / aggregates (simple projection JOIN)
@@ -11787,6 +13402,17 @@ impl QueryExecutor {
             }
         }
 
+        // The columnar and positional paths below evaluate expressions on Vec<Value> rows,
+        // which can't run a correlated subquery: leave those to the SqlRow paths.
+        let correlated = stmt
+            .where_clause
+            .as_ref()
+            .is_some_and(Self::contains_correlated)
+            || stmt
+                .columns
+                .iter()
+                .any(|c| matches!(c, SelectColumn::Expr(e, _) if Self::contains_correlated(e)));
+
         // S9: ColSegmentStore tables — route ALL non-aggregate queries (with or
         // without WHERE) through the multi-segment full-scan path. The
         // PointQuery/index fast paths below fetch rows via lsm_engine.scan_range,
@@ -11806,6 +13432,7 @@ impl QueryExecutor {
                     // scan — they need the index pushdown paths below
                     // (FAST PATH 0a/0b/-1/-1b).
                     && stmt.where_clause.as_ref().is_none_or(|w| !Self::expr_needs_materialized_path(w))
+                    && !correlated
                 {
                     // 🔑 PERF: PK point query fast path — `WHERE pk = literal`
                     // should use binary search in the segment's row_map (O(log N)),
@@ -11859,10 +13486,10 @@ impl QueryExecutor {
             return Ok(result);
         }
 
-        // 🚀 FAST PATH -2: ORDER BY primary key optimization (P0)
+        // 🚀 FAST PATH -2: ORDER BY indexed column optimization (P0)
         // Pattern: SELECT * FROM table ORDER BY id [ASC/DESC] [LIMIT k]
-        // → Use primary key index scan (600x faster, 280x less memory!)
-        if let Some(result) = self.try_optimize_primary_key_order_by(stmt)? {
+        // → Walk the column index in order (600x faster, 280x less memory!)
+        if let Some(result) = self.try_optimize_index_order_by(stmt)? {
             return Ok(result);
         }
 
@@ -11897,7 +13524,11 @@ impl QueryExecutor {
                         crate::database::index_metadata::IndexType::Vector,
                     )
                     .unwrap_or_else(|| format!("{}_{}", table_name, col_name));
-                match self.db.vector_search(&index_name, &query_vector, k) {
+                let options = self.vector_search_options();
+                match self
+                    .db
+                    .vector_search_with_options(&index_name, &query_vector, k, options)
+                {
                     Ok(results) => {
                         // Load rows for the result row_ids
                         let schema = self.db.get_table_schema(&table_name)?;
@@ -11931,6 +13562,20 @@ impl QueryExecutor {
             }
         }
 
+        // 🚀 FAST PATH 0-mm: Mixed-modal WHERE
+        // Pattern: MATCH / KNN_SEARCH / ST_*_3D AND-ed with other predicates
+        // → Drive with the index the optimizer costs cheapest, filter the rest
+        if let Some(ref where_clause) = stmt.where_clause {
+            if let TableRef::Table {
+                name: table_name, ..
+            } = from
+            {
+                if let Some(result) = self.try_multimodal_driver(stmt, where_clause, table_name)? {
+                    return Ok(result);
+                }
+            }
+        }
+
         // 🚀 FAST PATH 0a: Text Search (MATCH AGAINST) optimization
         // Pattern: SELECT ... FROM table WHERE MATCH(col) AGAINST('query') [ORDER BY score] [LIMIT k]
         // → Use text index directly (50x faster than full table scan + per-row search_ranked)
@@ -12027,7 +13672,8 @@ impl QueryExecutor {
         // Handles: SELECT COUNT(*), SUM(x), AVG(y), MIN(z), MAX(w) FROM t [WHERE ...]
         // Accumulates directly into inline counters — O(1) memory, no grouping overhead.
         // When WHERE is present, reuses decoded row for aggregate extraction.
-        if stmt.group_by.is_none()
+        if !correlated
+            && stmt.group_by.is_none()
             && !stmt.distinct
             && stmt.having.is_none()
             && stmt.order_by.is_none()
@@ -12047,7 +13693,7 @@ impl QueryExecutor {
 
         // 🚀 FAST PATH 1b: Positional GROUP BY — skip HashMap conversion entirely.
         // Works directly on Vec<Value> rows for simple single-table GROUP BY / aggregate queries.
-        if stmt.group_by.is_some() || self.has_aggregates(&stmt.columns) {
+        if !correlated && (stmt.group_by.is_some() || self.has_aggregates(&stmt.columns)) {
             if let TableRef::Table {
                 name: table_name, ..
             } = from
@@ -12067,7 +13713,7 @@ impl QueryExecutor {
 
         // 🚀 FAST PATH 1c: Positional ORDER BY / DISTINCT — skip HashMap conversion entirely.
         // Works directly on Vec<Value> rows for simple single-table ORDER BY / DISTINCT queries.
-        if (stmt.order_by.is_some() || stmt.distinct) && stmt.group_by.is_none() {
+        if !correlated && (stmt.order_by.is_some() || stmt.distinct) && stmt.group_by.is_none() {
             if let TableRef::Table {
                 name: table_name, ..
             } = from
@@ -12218,6 +13864,26 @@ impl QueryExecutor {
             // Fall through to general path for unsupported patterns
         }
 
+        // Plain SELECT-FROM-WHERE-ORDER BY-LIMIT over one table or an inner
+        // equi-join that none of the specialized paths above claimed: run as
+        // a physical operator tree. Those paths (PK point lookups, ORDER BY
+        // PK, vector / text / spatial searches, positional scans, SELECT *)
+        // still take precedence; the paths below serve what the planner
+        // declines (DISTINCT, GROUP BY, outer joins, ...).
+        if !self.has_aggregates(&stmt.columns) {
+            let planner = super::physical::PhysicalPlanner::new(
+                &self.db,
+                &self.optimizer,
+                self,
+                self.vector_search_options(),
+            );
+            if let Some(plan) = planner.plan_select(stmt)? {
+                debug_log!("[Executor] physical plan:\n{}", plan.explain());
+                let (columns, rows) = plan.execute()?;
+                return Ok(QueryResult::Select { columns, rows });
+            }
+        }
+
         // 🚀 FAST PATH 2: Try to use column index for WHERE optimization
         // 🆕 P0 OPTIMIZATION: Extract LIMIT early and pass to storage layer
         let storage_limit = self.calculate_storage_limit(stmt);
@@ -12669,6 +14335,25 @@ impl QueryExecutor {
             all_sql_rows
         };
 
+        self.finish_select(stmt, filtered_rows, &combined_schema, storage_limit)
+    }
+
+    /// Shared tail of the general SELECT path: LATEST BY, aggregation or
+    /// projection, ORDER BY, DISTINCT and LIMIT/OFFSET over the filtered rows.
+    fn finish_select(
+        &self,
+        stmt: &SelectStmt,
+        filtered_rows: Vec<(u64, SqlRow)>,
+        combined_schema: &TableSchema,
+        storage_limit: Option<usize>,
+    ) -> Result<QueryResult> {
+        // Apply LATEST BY (time-series deduplication) before anything is projected
+        let filtered_rows = if let Some(ref latest_by_cols) = stmt.latest_by {
+            self.apply_latest_by(filtered_rows, latest_by_cols, combined_schema)?
+        } else {
+            filtered_rows
+        };
+
         // 🚀 P0 OPTIMIZATION: Apply storage_limit early to reduce memory usage
         // This prevents loading all rows when LIMIT is small and no ORDER BY/GROUP BY/DISTINCT
         //
@@ -12711,7 +14396,7 @@ impl QueryExecutor {
             self.apply_group_by(&stmt.columns, &filtered_rows, &[], stmt.having.as_ref())?
         } else {
             // No aggregation - simple projection
-            self.project_columns(&stmt.columns, &filtered_rows, &combined_schema)?
+            self.project_columns(&stmt.columns, &filtered_rows, combined_schema)?
         };
 
         // Order by (with alias resolution)
@@ -12791,6 +14476,12 @@ impl QueryExecutor {
                     sort_keys.map(|keys| (keys, proj_row))
                 })
                 .collect::<Result<Vec<_>>>()?;
+            if query_memory::is_limited() {
+                for (keys, row) in &rows_with_keys {
+                    query_memory::charge_row(keys, "ORDER BY")?;
+                    query_memory::charge_row(row, "ORDER BY")?;
+                }
+            }
 
             // Sort
             rows_with_keys.sort_by(|a, b| {
@@ -12808,23 +14499,12 @@ impl QueryExecutor {
             sorted_rows = rows_with_keys.into_iter().map(|(_, row)| row).collect();
         }
 
-        // Apply LATEST BY (time-series deduplication)
-        let final_sorted_rows = if let Some(ref latest_by_cols) = stmt.latest_by {
-            self.apply_latest_by(
-                sorted_rows,
-                &filtered_rows,
-                latest_by_cols,
-                &combined_schema,
-            )?
-        } else {
-            sorted_rows
-        };
-
         // Apply DISTINCT (deduplication)
         let deduplicated_rows = if stmt.distinct {
-            self.apply_distinct(final_sorted_rows)
+            query_memory::charge_rows(&sorted_rows, "DISTINCT")?;
+            self.apply_distinct(sorted_rows)
         } else {
-            final_sorted_rows
+            sorted_rows
         };
 
         // Apply LIMIT and OFFSET
@@ -12922,8 +14602,8 @@ impl QueryExecutor {
             return None;
         }
 
-        // If there's GROUP BY, we need all rows
-        if stmt.group_by.is_some() {
+        // If there's GROUP BY or LATEST BY, we need all rows
+        if stmt.group_by.is_some() || stmt.latest_by.is_some() {
             return None;
         }
 
@@ -12956,7 +14636,14 @@ impl QueryExecutor {
             Expr::FunctionCall { name, .. } => {
                 matches!(
                     name.to_uppercase().as_str(),
-                    "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE"
+                    "COUNT"
+                        | "SUM"
+                        | "AVG"
+                        | "MIN"
+                        | "MAX"
+                        | "STDDEV"
+                        | "VARIANCE"
+                        | "APPROX_COUNT_DISTINCT"
                 )
             }
             Expr::BinaryOp { left, right, .. } => {
@@ -13028,9 +14715,22 @@ impl QueryExecutor {
                                         Value::Text(_) | Value::TextDoc(_) => ColumnType::Text,
                                         Value::Bool(_) => ColumnType::Boolean,
                                         Value::Timestamp(_) => ColumnType::Timestamp,
+                                        Value::Tensor(t) if t.rank() > 1 => {
+                                            ColumnType::ShapedTensor(t.shape().to_vec())
+                                        }
                                         Value::Tensor(t) => ColumnType::Tensor(t.dimension()),
                                         Value::Spatial(_) => ColumnType::Spatial,
                                         Value::Vector(v) => ColumnType::Tensor(v.len()),
+                                        Value::Decimal(d) => ColumnType::Decimal {
+                                            precision: crate::types::DECIMAL_MAX_PRECISION,
+                                            scale: d.scale(),
+                                        },
+                                        Value::Uuid(_) => ColumnType::Uuid,
+                                        Value::Date(_) => ColumnType::Date,
+                                        Value::Time(_) => ColumnType::Time,
+                                        Value::Array(items) => ColumnType::Array(Box::new(
+                                            crate::types::array::element_type(items),
+                                        )),
                                         Value::Null => ColumnType::Text, // Default for NULL
                                     }
                                 } else {
@@ -13090,6 +14790,17 @@ impl QueryExecutor {
                 // Recursive: evaluate left and right
                 let (left_rows, left_schema) = self.execute_from(left)?;
                 let (right_rows, right_schema) = self.execute_from(right)?;
+                // Both inputs stay buffered for the join (rows of a nested
+                // join were charged when it produced them)
+                if query_memory::is_limited() {
+                    for (side, rows) in [(left, &left_rows), (right, &right_rows)] {
+                        if !matches!(side.as_ref(), TableRef::Join { .. }) {
+                            for (_, row) in rows {
+                                query_memory::charge_sql_row(row, "JOIN")?;
+                            }
+                        }
+                    }
+                }
 
                 // Combine schemas
                 let mut combined_schema = (*left_schema).clone();
@@ -13417,6 +15128,7 @@ impl QueryExecutor {
                     let mut combined = Vec::with_capacity(lncol + rncol);
                     combined.extend_from_slice(lrow);
                     combined.extend_from_slice(&rrow);
+                    query_memory::charge_row(&combined, "JOIN")?;
                     joined.push(combined);
                 }
             }
@@ -13478,7 +15190,9 @@ impl QueryExecutor {
                     }
                 }
                 Value::Float(f) => Some(JoinKey::Numeric(f.to_bits())),
-                Value::Text(s) => Some(JoinKey::Text(s.to_string())),
+                Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => {
+                    v.canonical_text().map(|t| JoinKey::Text(t.into_owned()))
+                }
                 Value::Bool(b) => Some(JoinKey::Bool(*b)),
                 _ => None,
             }
@@ -13512,6 +15226,7 @@ impl QueryExecutor {
                         let mut combined = Vec::with_capacity(lncol + rrow.len());
                         combined.extend_from_slice(lrow);
                         combined.extend_from_slice(rrow);
+                        query_memory::charge_row(&combined, "JOIN")?;
                         joined.push(combined);
                     }
                 }
@@ -13572,6 +15287,7 @@ impl QueryExecutor {
                     .and_then(|val| self.to_bool(&val))
                     .unwrap_or(false)
                 {
+                    query_memory::charge_sql_row(&combined_row, "JOIN")?;
                     result.push((next_id, combined_row));
                     next_id += 1;
                 }
@@ -13631,7 +15347,9 @@ impl QueryExecutor {
                 // 0.0 == -0.0, but their bit patterns differ). Adding 0.0 turns
                 // -0.0 into +0.0; non-zero values are unchanged.
                 Value::Float(f) => Some(HashKey::Numeric((f + 0.0).to_bits())),
-                Value::Text(s) => Some(HashKey::Text(s.to_string())),
+                Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => value
+                    .canonical_text()
+                    .map(|t| HashKey::Text(t.into_owned())),
                 Value::Bool(b) => Some(HashKey::Bool(*b)),
                 Value::Null => None, // SQL: NULL != NULL in joins
                 // 🚨 Timestamp: hash on micros (matches Integer with the same
@@ -13676,6 +15394,7 @@ impl QueryExecutor {
                     if let Some(matching_right_rows) = hash_table.get(&key) {
                         for right_row in matching_right_rows {
                             let combined_row = self.combine_rows(left_row, right_row);
+                            query_memory::charge_sql_row(&combined_row, "JOIN")?;
                             result.push((next_id, combined_row));
                             next_id += 1;
                         }
@@ -13797,6 +15516,7 @@ impl QueryExecutor {
                     .and_then(|val| self.to_bool(&val))
                     .unwrap_or(false)
                 {
+                    query_memory::charge_sql_row(&combined_row, "JOIN")?;
                     result.push((next_id, combined_row));
                     next_id += 1;
                     matched = true;
@@ -13805,6 +15525,7 @@ impl QueryExecutor {
 
             if !matched {
                 let combined_row = self.combine_rows(left_row, &null_right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
                 result.push((next_id, combined_row));
                 next_id += 1;
             }
@@ -13856,7 +15577,9 @@ impl QueryExecutor {
                 // 0.0 == -0.0, but their bit patterns differ). Adding 0.0 turns
                 // -0.0 into +0.0; non-zero values are unchanged.
                 Value::Float(f) => Some(HashKey::Numeric((f + 0.0).to_bits())),
-                Value::Text(s) => Some(HashKey::Text(s.to_string())),
+                Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => value
+                    .canonical_text()
+                    .map(|t| HashKey::Text(t.into_owned())),
                 Value::Bool(b) => Some(HashKey::Bool(*b)),
                 Value::Null => None, // SQL: NULL != NULL in joins
                 // 🚨 Timestamp: hash on micros (matches Integer). Without this,
@@ -13892,7 +15615,9 @@ impl QueryExecutor {
                 if let Some(key) = to_hash_key(key_val) {
                     if let Some(matching) = hash_table.get(&key) {
                         for right_row in matching {
-                            result.push((next_id, self.combine_rows(left_row, right_row)));
+                            let combined_row = self.combine_rows(left_row, right_row);
+                            query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                            result.push((next_id, combined_row));
                             next_id += 1;
                         }
                         true
@@ -13907,7 +15632,9 @@ impl QueryExecutor {
             };
 
             if !matched {
-                result.push((next_id, self.combine_rows(left_row, null_right_row)));
+                let combined_row = self.combine_rows(left_row, null_right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                result.push((next_id, combined_row));
                 next_id += 1;
             }
         }
@@ -13957,6 +15684,7 @@ impl QueryExecutor {
                     .and_then(|val| self.to_bool(&val))
                     .unwrap_or(false)
                 {
+                    query_memory::charge_sql_row(&combined_row, "JOIN")?;
                     result.push((next_id, combined_row));
                     next_id += 1;
                     matched = true;
@@ -13965,6 +15693,7 @@ impl QueryExecutor {
 
             if !matched {
                 let combined_row = self.combine_rows(&null_left_row, right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
                 result.push((next_id, combined_row));
                 next_id += 1;
             }
@@ -14016,7 +15745,9 @@ impl QueryExecutor {
                 // 0.0 == -0.0, but their bit patterns differ). Adding 0.0 turns
                 // -0.0 into +0.0; non-zero values are unchanged.
                 Value::Float(f) => Some(HashKey::Numeric((f + 0.0).to_bits())),
-                Value::Text(s) => Some(HashKey::Text(s.to_string())),
+                Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => value
+                    .canonical_text()
+                    .map(|t| HashKey::Text(t.into_owned())),
                 Value::Bool(b) => Some(HashKey::Bool(*b)),
                 Value::Null => None, // SQL: NULL != NULL in joins
                 // 🚨 Timestamp: hash on micros (matches Integer). Without this,
@@ -14052,7 +15783,9 @@ impl QueryExecutor {
                 if let Some(key) = to_hash_key(key_val) {
                     if let Some(matching) = hash_table.get(&key) {
                         for left_row in matching {
-                            result.push((next_id, self.combine_rows(left_row, right_row)));
+                            let combined_row = self.combine_rows(left_row, right_row);
+                            query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                            result.push((next_id, combined_row));
                             next_id += 1;
                         }
                         true
@@ -14067,7 +15800,9 @@ impl QueryExecutor {
             };
 
             if !matched {
-                result.push((next_id, self.combine_rows(null_left_row, right_row)));
+                let combined_row = self.combine_rows(null_left_row, right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                result.push((next_id, combined_row));
                 next_id += 1;
             }
         }
@@ -14125,6 +15860,7 @@ impl QueryExecutor {
                     .and_then(|val| self.to_bool(&val))
                     .unwrap_or(false)
                 {
+                    query_memory::charge_sql_row(&combined_row, "JOIN")?;
                     result.push((next_id, combined_row));
                     next_id += 1;
                     left_matched = true;
@@ -14134,6 +15870,7 @@ impl QueryExecutor {
 
             if !left_matched {
                 let combined_row = self.combine_rows(left_row, &null_right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
                 result.push((next_id, combined_row));
                 next_id += 1;
             }
@@ -14142,6 +15879,7 @@ impl QueryExecutor {
         for (right_idx, (_, right_row)) in right_rows.iter().enumerate() {
             if !right_matched[right_idx] {
                 let combined_row = self.combine_rows(&null_left_row, right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
                 result.push((next_id, combined_row));
                 next_id += 1;
             }
@@ -14194,7 +15932,9 @@ impl QueryExecutor {
                 // 0.0 == -0.0, but their bit patterns differ). Adding 0.0 turns
                 // -0.0 into +0.0; non-zero values are unchanged.
                 Value::Float(f) => Some(HashKey::Numeric((f + 0.0).to_bits())),
-                Value::Text(s) => Some(HashKey::Text(s.to_string())),
+                Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => value
+                    .canonical_text()
+                    .map(|t| HashKey::Text(t.into_owned())),
                 Value::Bool(b) => Some(HashKey::Bool(*b)),
                 Value::Null => None, // SQL: NULL != NULL in joins
                 // 🚨 Timestamp: hash on micros (matches Integer). Without this,
@@ -14232,7 +15972,9 @@ impl QueryExecutor {
                 if let Some(key) = to_hash_key(key_val) {
                     if let Some(matching) = hash_table.get(&key) {
                         for &(idx, right_row) in matching {
-                            result.push((next_id, self.combine_rows(left_row, right_row)));
+                            let combined_row = self.combine_rows(left_row, right_row);
+                            query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                            result.push((next_id, combined_row));
                             next_id += 1;
                             right_matched[idx] = true;
                         }
@@ -14248,7 +15990,9 @@ impl QueryExecutor {
             };
 
             if !left_matched {
-                result.push((next_id, self.combine_rows(left_row, null_right_row)));
+                let combined_row = self.combine_rows(left_row, null_right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                result.push((next_id, combined_row));
                 next_id += 1;
             }
         }
@@ -14256,7 +16000,9 @@ impl QueryExecutor {
         // Add unmatched right rows
         for (idx, (_, right_row)) in right_rows.iter().enumerate() {
             if !right_matched[idx] {
-                result.push((next_id, self.combine_rows(null_left_row, right_row)));
+                let combined_row = self.combine_rows(null_left_row, right_row);
+                query_memory::charge_sql_row(&combined_row, "JOIN")?;
+                result.push((next_id, combined_row));
                 next_id += 1;
             }
         }
@@ -14403,6 +16149,24 @@ impl QueryExecutor {
             (set, has_null)
         };
 
+        // Both scans hand UUID, DATE and TIME columns back as their stored
+        // text; the set must hold the typed values so non-canonical text
+        // still matches
+        let inner_type = &col_types[inner_col_pos];
+        let set = if matches!(
+            inner_type,
+            ColumnType::Uuid | ColumnType::Date | ColumnType::Time
+        ) {
+            set.into_iter()
+                .map(|v| match v {
+                    Value::Text(t) => inner_type.value_from_text(&t),
+                    v => v,
+                })
+                .collect()
+        } else {
+            set
+        };
+
         if set.is_empty() && !has_null {
             // Empty set (no NULLs) means outer IN should match nothing
             return Some((set, false));
@@ -14485,28 +16249,20 @@ impl QueryExecutor {
     fn materialize_subqueries(&self, expr: &Expr) -> Result<Expr> {
         match expr {
             Expr::Subquery(subquery) => {
-                // Execute subquery
+                if let Some(correlated) = self.correlate(CorrelatedKind::Scalar, subquery) {
+                    return Ok(correlated);
+                }
                 let result = self.execute_select_internal(subquery)?;
+                Ok(Expr::Literal(Self::scalar_subquery_value(result)?))
+            }
 
-                match result {
-                    QueryResult::Select { rows, .. } => {
-                        // Scalar subquery: return single value
-                        if rows.len() == 1 && rows[0].len() == 1 {
-                            Ok(Expr::Literal(rows[0][0].clone()))
-                        } else if rows.is_empty() {
-                            Ok(Expr::Literal(Value::Null))
-                        } else {
-                            // Non-scalar subquery error (should be used with IN)
-                            Err(MoteDBError::Query(
-                                "Subquery returns more than one row/column (use IN instead of =)"
-                                    .into(),
-                            ))
-                        }
-                    }
-                    _ => Err(MoteDBError::Query(
-                        "Subquery must return SELECT result".into(),
-                    )),
+            Expr::Exists(subquery) => {
+                if let Some(correlated) = self.correlate(CorrelatedKind::Exists, subquery) {
+                    return Ok(correlated);
                 }
+                Ok(Expr::Literal(Value::Bool(
+                    self.subquery_has_rows(subquery)?,
+                )))
             }
 
             Expr::In {
@@ -14517,6 +16273,13 @@ impl QueryExecutor {
                 // Check if list contains a subquery
                 if list.len() == 1 {
                     if let Expr::Subquery(subquery) = &list[0] {
+                        let kind = CorrelatedKind::In {
+                            expr: Box::new(self.materialize_subqueries(expr)?),
+                            negated: *negated,
+                        };
+                        if let Some(correlated) = self.correlate(kind, subquery) {
+                            return Ok(correlated);
+                        }
                         // 🚀 Fast path: if the outer column is a simple Column reference,
                         // stream the subquery result directly into a HashSet, avoiding
                         // the Vec<Vec<Value>> + Vec<Expr::Literal> double materialization.
@@ -14585,89 +16348,332 @@ impl QueryExecutor {
                 })
             }
 
-            // InHashset: set is already pre-built. Only recurse into the expr
-            // sub-tree (it may contain its own subqueries in rare cases).
+            // InHashset: set is already pre-built. Only recurse into the expr
+            // sub-tree (it may contain its own subqueries in rare cases).
+            Expr::InHashset {
+                expr,
+                set,
+                negated,
+                has_null,
+            } => Ok(Expr::InHashset {
+                expr: Box::new(self.materialize_subqueries(expr)?),
+                set: set.clone(),
+                negated: *negated,
+                has_null: *has_null,
+            }),
+
+            Expr::BinaryOp { left, op, right } => Ok(Expr::BinaryOp {
+                left: Box::new(self.materialize_subqueries(left)?),
+                op: op.clone(),
+                right: Box::new(self.materialize_subqueries(right)?),
+            }),
+
+            Expr::UnaryOp { op, expr } => Ok(Expr::UnaryOp {
+                op: op.clone(),
+                expr: Box::new(self.materialize_subqueries(expr)?),
+            }),
+
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => Ok(Expr::Between {
+                expr: Box::new(self.materialize_subqueries(expr)?),
+                low: Box::new(self.materialize_subqueries(low)?),
+                high: Box::new(self.materialize_subqueries(high)?),
+                negated: *negated,
+            }),
+
+            Expr::Like {
+                expr,
+                pattern,
+                negated,
+            } => Ok(Expr::Like {
+                expr: Box::new(self.materialize_subqueries(expr)?),
+                pattern: Box::new(self.materialize_subqueries(pattern)?),
+                negated: *negated,
+            }),
+
+            Expr::IsNull { expr, negated } => Ok(Expr::IsNull {
+                expr: Box::new(self.materialize_subqueries(expr)?),
+                negated: *negated,
+            }),
+
+            Expr::FunctionCall {
+                name,
+                args,
+                distinct,
+            } => {
+                let materialized_args: Result<Vec<Expr>> = args
+                    .iter()
+                    .map(|arg| self.materialize_subqueries(arg))
+                    .collect();
+
+                Ok(Expr::FunctionCall {
+                    name: name.clone(),
+                    args: materialized_args?,
+                    distinct: *distinct,
+                })
+            }
+
+            // Leaf nodes - no subqueries to materialize
+            Expr::Column(_)
+            | Expr::Literal(_)
+            | Expr::Parameter(_)
+            | Expr::Correlated(_)
+            | Expr::Match { .. }
+            | Expr::KnnSearch { .. }
+            | Expr::KnnDistance { .. }
+            | Expr::StWithin3D { .. }
+            | Expr::StDistance3D { .. }
+            | Expr::StKnn3D { .. }
+            | Expr::StRadius3D { .. }
+            | Expr::WindowFunction { .. }
+            | Expr::Case { .. } => Ok(expr.clone()),
+        }
+    }
+
+    /// Value of a scalar subquery: its single column of its single row, NULL
+    /// without rows
+    fn scalar_subquery_value(result: QueryResult) -> Result<Value> {
+        match result {
+            QueryResult::Select { mut rows, .. } => {
+                if rows.len() == 1 && rows[0].len() == 1 {
+                    Ok(rows.swap_remove(0).swap_remove(0))
+                } else if rows.is_empty() {
+                    Ok(Value::Null)
+                } else {
+                    // Non-scalar subquery error (should be used with IN)
+                    Err(MoteDBError::Query(
+                        "Subquery returns more than one row/column (use IN instead of =)".into(),
+                    ))
+                }
+            }
+            _ => Err(MoteDBError::Query(
+                "Subquery must return SELECT result".into(),
+            )),
+        }
+    }
+
+    /// EXISTS: run the subquery for at most one row
+    fn subquery_has_rows(&self, subquery: &SelectStmt) -> Result<bool> {
+        let mut probe = subquery.clone();
+        probe.limit = Some(probe.limit.map_or(1, |n| n.min(1)));
+        match self.execute_select_internal(&probe)? {
+            QueryResult::Select { rows, .. } => Ok(!rows.is_empty()),
+            _ => Err(MoteDBError::Query(
+                "Subquery must return SELECT result".into(),
+            )),
+        }
+    }
+
+    /// Column names of a table, for telling a subquery's own columns from
+    /// outer references
+    fn table_column_names(&self, table: &str) -> Option<Vec<String>> {
+        let schema = self.db.get_table_schema(table).ok()?;
+        Some(schema.columns.iter().map(|c| c.name.clone()).collect())
+    }
+
+    /// Wrap `subquery` for per-row evaluation if it names outer columns
+    fn correlate(&self, kind: CorrelatedKind, subquery: &SelectStmt) -> Option<Expr> {
+        let lookup = |t: &str| self.table_column_names(t);
+        let refs = correlated::outer_refs(subquery, &lookup);
+        if refs.is_empty() {
+            return None;
+        }
+        Some(Expr::Correlated(Arc::new(CorrelatedSubquery::new(
+            kind,
+            subquery.clone(),
+            refs,
+        ))))
+    }
+
+    /// Evaluate a correlated subquery for one outer row, re-running it
+    /// unless a row with the same outer values already did
+    fn eval_correlated(&self, sub: &CorrelatedSubquery, row: &SqlRow) -> Result<Value> {
+        let values: Vec<Option<Value>> = sub
+            .outer_refs
+            .iter()
+            .map(|name| {
+                self.get_column_value(row, name).or_else(|| {
+                    let (_, column) = name.rsplit_once('.')?;
+                    self.get_column_value(row, column)
+                })
+            })
+            .collect();
+        let key: Vec<Value> = values
+            .iter()
+            .map(|v| v.clone().unwrap_or(Value::Null))
+            .collect();
+        let memo = match sub.memoized(&key) {
+            Some(memo) => memo,
+            None => {
+                let lookup = |t: &str| self.table_column_names(t);
+                let query =
+                    correlated::bind_outer_refs(&sub.query, &sub.outer_refs, &values, &lookup);
+                let memo = match &sub.kind {
+                    CorrelatedKind::Scalar => {
+                        let result = self.execute_select_internal(&query)?;
+                        Memo::Value(Self::scalar_subquery_value(result)?)
+                    }
+                    CorrelatedKind::Exists => {
+                        Memo::Value(Value::Bool(self.subquery_has_rows(&query)?))
+                    }
+                    CorrelatedKind::In { .. } => match self.execute_select_internal(&query)? {
+                        QueryResult::Select { rows, .. } => {
+                            let mut set = std::collections::HashSet::new();
+                            let mut has_null = false;
+                            for v in rows.into_iter().filter_map(|r| r.into_iter().next()) {
+                                if matches!(v, Value::Null) {
+                                    has_null = true;
+                                } else {
+                                    set.insert(v);
+                                }
+                            }
+                            Memo::Set { set, has_null }
+                        }
+                        _ => {
+                            return Err(MoteDBError::Query(
+                                "Subquery must return SELECT result".into(),
+                            ))
+                        }
+                    },
+                };
+                sub.memoize(key, memo)
+            }
+        };
+        match (&sub.kind, &*memo) {
+            (CorrelatedKind::In { expr, negated }, Memo::Set { set, has_null }) => {
+                let val = self.eval_with_materialized(expr, row)?;
+                Ok(truth_value(in_set_truth(&val, set, *negated, *has_null)))
+            }
+            (_, Memo::Value(v)) => Ok(v.clone()),
+            (_, Memo::Set { .. }) => Err(MoteDBError::Query(
+                "Subquery must return a single value".into(),
+            )),
+        }
+    }
+
+    /// Whether an expression holds a correlated subquery
+    fn contains_correlated(expr: &Expr) -> bool {
+        match expr {
+            Expr::Correlated(_) => true,
+            Expr::BinaryOp { left, right, .. } => {
+                Self::contains_correlated(left) || Self::contains_correlated(right)
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::InHashset { expr, .. } => Self::contains_correlated(expr),
+            Expr::In { expr, list, .. } => {
+                Self::contains_correlated(expr) || list.iter().any(Self::contains_correlated)
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => [expr, low, high]
+                .into_iter()
+                .any(|e| Self::contains_correlated(e)),
+            Expr::Like { expr, pattern, .. } => {
+                Self::contains_correlated(expr) || Self::contains_correlated(pattern)
+            }
+            Expr::FunctionCall { args, .. } => args.iter().any(Self::contains_correlated),
+            Expr::Case { whens, else_expr } => {
+                whens
+                    .iter()
+                    .any(|(c, r)| Self::contains_correlated(c) || Self::contains_correlated(r))
+                    || else_expr.as_deref().is_some_and(Self::contains_correlated)
+            }
+            _ => false,
+        }
+    }
+
+    /// `expr` with its correlated subqueries replaced by their values for `row`
+    fn bind_correlated(&self, expr: &Expr, row: &SqlRow) -> Result<Expr> {
+        let bind = |e: &Expr| -> Result<Box<Expr>> { Ok(Box::new(self.bind_correlated(e, row)?)) };
+        Ok(match expr {
+            Expr::Correlated(sub) => Expr::Literal(self.eval_correlated(sub, row)?),
+            Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+                left: bind(left)?,
+                op: op.clone(),
+                right: bind(right)?,
+            },
+            Expr::UnaryOp { op, expr: inner } => Expr::UnaryOp {
+                op: op.clone(),
+                expr: bind(inner)?,
+            },
+            Expr::IsNull {
+                expr: inner,
+                negated,
+            } => Expr::IsNull {
+                expr: bind(inner)?,
+                negated: *negated,
+            },
             Expr::InHashset {
-                expr,
+                expr: inner,
                 set,
                 negated,
                 has_null,
-            } => Ok(Expr::InHashset {
-                expr: Box::new(self.materialize_subqueries(expr)?),
+            } => Expr::InHashset {
+                expr: bind(inner)?,
                 set: set.clone(),
                 negated: *negated,
                 has_null: *has_null,
-            }),
-
-            Expr::BinaryOp { left, op, right } => Ok(Expr::BinaryOp {
-                left: Box::new(self.materialize_subqueries(left)?),
-                op: op.clone(),
-                right: Box::new(self.materialize_subqueries(right)?),
-            }),
-
-            Expr::UnaryOp { op, expr } => Ok(Expr::UnaryOp {
-                op: op.clone(),
-                expr: Box::new(self.materialize_subqueries(expr)?),
-            }),
-
+            },
+            Expr::In {
+                expr: inner,
+                list,
+                negated,
+            } => Expr::In {
+                expr: bind(inner)?,
+                list: list
+                    .iter()
+                    .map(|e| self.bind_correlated(e, row))
+                    .collect::<Result<_>>()?,
+                negated: *negated,
+            },
             Expr::Between {
-                expr,
+                expr: inner,
                 low,
                 high,
                 negated,
-            } => Ok(Expr::Between {
-                expr: Box::new(self.materialize_subqueries(expr)?),
-                low: Box::new(self.materialize_subqueries(low)?),
-                high: Box::new(self.materialize_subqueries(high)?),
+            } => Expr::Between {
+                expr: bind(inner)?,
+                low: bind(low)?,
+                high: bind(high)?,
                 negated: *negated,
-            }),
-
+            },
             Expr::Like {
-                expr,
+                expr: inner,
                 pattern,
                 negated,
-            } => Ok(Expr::Like {
-                expr: Box::new(self.materialize_subqueries(expr)?),
-                pattern: Box::new(self.materialize_subqueries(pattern)?),
-                negated: *negated,
-            }),
-
-            Expr::IsNull { expr, negated } => Ok(Expr::IsNull {
-                expr: Box::new(self.materialize_subqueries(expr)?),
+            } => Expr::Like {
+                expr: bind(inner)?,
+                pattern: bind(pattern)?,
                 negated: *negated,
-            }),
-
+            },
             Expr::FunctionCall {
                 name,
                 args,
                 distinct,
-            } => {
-                let materialized_args: Result<Vec<Expr>> = args
+            } => Expr::FunctionCall {
+                name: name.clone(),
+                args: args
                     .iter()
-                    .map(|arg| self.materialize_subqueries(arg))
-                    .collect();
-
-                Ok(Expr::FunctionCall {
-                    name: name.clone(),
-                    args: materialized_args?,
-                    distinct: *distinct,
-                })
-            }
-
-            // Leaf nodes - no subqueries to materialize
-            Expr::Column(_)
-            | Expr::Literal(_)
-            | Expr::Parameter(_)
-            | Expr::Match { .. }
-            | Expr::KnnSearch { .. }
-            | Expr::KnnDistance { .. }
-            | Expr::StWithin3D { .. }
-            | Expr::StDistance3D { .. }
-            | Expr::StKnn3D { .. }
-            | Expr::StRadius3D { .. }
-            | Expr::WindowFunction { .. }
-            | Expr::Case { .. } => Ok(expr.clone()),
-        }
+                    .map(|e| self.bind_correlated(e, row))
+                    .collect::<Result<_>>()?,
+                distinct: *distinct,
+            },
+            Expr::Case { whens, else_expr } => Expr::Case {
+                whens: whens
+                    .iter()
+                    .map(|(c, r)| {
+                        Ok((self.bind_correlated(c, row)?, self.bind_correlated(r, row)?))
+                    })
+                    .collect::<Result<_>>()?,
+                else_expr: else_expr.as_deref().map(bind).transpose()?,
+            },
+            _ => expr.clone(),
+        })
     }
 
     /// Helper: Get column value from row, trying both exact match and table-prefixed match
@@ -14684,10 +16690,21 @@ impl QueryExecutor {
         })
     }
 
+    /// Hand an expression the executor has no special handling for to the
+    /// evaluator, after running the correlated subqueries it holds
+    fn eval_unmaterialized(&self, expr: &Expr, row: &SqlRow) -> Result<Value> {
+        if Self::contains_correlated(expr) {
+            return self.evaluator.eval(&self.bind_correlated(expr, row)?, row);
+        }
+        self.evaluator.eval(expr, row)
+    }
+
     /// Evaluate expression with materialized subqueries
     fn eval_with_materialized(&self, expr: &Expr, row: &SqlRow) -> Result<Value> {
         // Special handling for MATCH and KNN expressions
         match expr {
+            Expr::Correlated(sub) => self.eval_correlated(sub, row),
+
             // 🔧 Recursively handle Binary Operations (e.g., ST_DISTANCE(...) < 10)
             Expr::BinaryOp { left, op, right } => {
                 let left_val = self.eval_with_materialized(left, row)?;
@@ -14696,25 +16713,18 @@ impl QueryExecutor {
                 // The previous code used Rust's `PartialOrd for Value` which
                 // orders Null below all values — so `NULL < 5` returned `true`
                 // and `NULL > 5` returned `false`, letting unmatched LEFT-JOIN
-                // NULLs pass WHERE filters incorrectly. Defer NULL handling to
-                // the evaluator's eval_binary_op, which correctly returns
-                // Bool(false) for any NULL comparison (filtering the row out).
+                // NULLs pass WHERE filters incorrectly.
                 if matches!(left_val, Value::Null) || matches!(right_val, Value::Null) {
-                    // For comparison ops, NULL → false (UNKNOWN treated as not-true
-                    // for WHERE filtering). For AND/OR, defer to evaluator for
-                    // proper three-valued logic.
                     match op {
                         BinaryOperator::Lt
                         | BinaryOperator::Le
                         | BinaryOperator::Gt
                         | BinaryOperator::Ge
                         | BinaryOperator::Eq
-                        | BinaryOperator::Ne => return Ok(Value::Bool(false)),
-                        BinaryOperator::And | BinaryOperator::Or => {
-                            // Fall through to evaluator for proper 3VL.
-                            return self.evaluator.eval(expr, row);
-                        }
-                        _ => return self.evaluator.eval(expr, row),
+                        | BinaryOperator::Ne => return Ok(Value::Null),
+                        // AND/OR combine UNKNOWN below
+                        BinaryOperator::And | BinaryOperator::Or => {}
+                        _ => return self.eval_unmaterialized(expr, row),
                     }
                 }
                 // Use simple comparison logic
@@ -14725,37 +16735,15 @@ impl QueryExecutor {
                     BinaryOperator::Ge => Ok(Value::Bool(left_val >= right_val)),
                     BinaryOperator::Eq => Ok(Value::Bool(left_val == right_val)),
                     BinaryOperator::Ne => Ok(Value::Bool(left_val != right_val)),
-                    BinaryOperator::And => {
-                        let left_bool = match left_val {
-                            Value::Bool(b) => b,
-                            Value::Integer(i) => i != 0,
-                            Value::Float(f) => f != 0.0 && !f.is_nan(),
-                            _ => false,
-                        };
-                        let right_bool = match right_val {
-                            Value::Bool(b) => b,
-                            Value::Integer(i) => i != 0,
-                            Value::Float(f) => f != 0.0 && !f.is_nan(),
-                            _ => false,
-                        };
-                        Ok(Value::Bool(left_bool && right_bool))
-                    }
-                    BinaryOperator::Or => {
-                        let left_bool = match left_val {
-                            Value::Bool(b) => b,
-                            Value::Integer(i) => i != 0,
-                            Value::Float(f) => f != 0.0 && !f.is_nan(),
-                            _ => false,
-                        };
-                        let right_bool = match right_val {
-                            Value::Bool(b) => b,
-                            Value::Integer(i) => i != 0,
-                            Value::Float(f) => f != 0.0 && !f.is_nan(),
-                            _ => false,
-                        };
-                        Ok(Value::Bool(left_bool || right_bool))
-                    }
-                    _ => self.evaluator.eval(expr, row), // Fall back to evaluator for complex ops
+                    BinaryOperator::And => Ok(truth_value(truth_and(
+                        Self::sql_truth(&left_val),
+                        Self::sql_truth(&right_val),
+                    ))),
+                    BinaryOperator::Or => Ok(truth_value(truth_or(
+                        Self::sql_truth(&left_val),
+                        Self::sql_truth(&right_val),
+                    ))),
+                    _ => self.eval_unmaterialized(expr, row), // Fall back to evaluator for complex ops
                 }
             }
 
@@ -14787,7 +16775,8 @@ impl QueryExecutor {
                     );
                     if let Some(index_name) = index_name {
                         if let Some(index_ref) = self.db.text_indexes.get(&index_name) {
-                            let results = index_ref.value().read().search_ranked(query, 1000)?;
+                            let limit = self.text_search_limit();
+                            let results = index_ref.value().read().search_ranked(query, limit)?;
                             let score = results
                                 .iter()
                                 .find(|(doc_id, _)| *doc_id == row_id)
@@ -14858,9 +16847,12 @@ impl QueryExecutor {
                     })?;
 
                 // Perform KNN search using public API
-                let results = self
-                    .db
-                    .vector_search(&index_name, query_vector.as_slice(), *k)?;
+                let results = self.db.vector_search_with_options(
+                    &index_name,
+                    query_vector.as_slice(),
+                    *k,
+                    self.vector_search_options(),
+                )?;
 
                 // Check if row_id is in results
                 let in_results = results.iter().any(|(id, _)| *id == row_id);
@@ -15056,7 +17048,7 @@ impl QueryExecutor {
                 Ok(Value::Bool(dist <= *radius))
             }
 
-            _ => self.evaluator.eval(expr, row),
+            _ => self.eval_unmaterialized(expr, row),
         }
     }
 
@@ -15075,30 +17067,323 @@ impl QueryExecutor {
         result
     }
 
+    /// `SELECT COUNT_ESTIMATE() FROM t`: the table's row count estimated from
+    /// segment / SSTable metadata (see [`MoteDB::estimate_row_count`])
+    fn execute_count_estimate(&self, stmt: &SelectStmt, from: &TableRef) -> Result<QueryResult> {
+        let misuse = || {
+            MoteDBError::InvalidArgument(
+                "COUNT_ESTIMATE() takes no arguments and must be the only column of an \
+                 unfiltered, ungrouped single-table SELECT; use COUNT(*) otherwise"
+                    .to_string(),
+            )
+        };
+        let (
+            TableRef::Table { name, .. },
+            [SelectColumn::Expr(Expr::FunctionCall { args, .. }, alias)],
+        ) = (from, stmt.columns.as_slice())
+        else {
+            return Err(misuse());
+        };
+        if !args.is_empty()
+            || stmt.where_clause.is_some()
+            || stmt.group_by.is_some()
+            || stmt.having.is_some()
+        {
+            return Err(misuse());
+        }
+
+        let estimate = self.db.estimate_row_count(name)?;
+        Ok(QueryResult::Select {
+            columns: vec![alias
+                .clone()
+                .unwrap_or_else(|| "COUNT_ESTIMATE()".to_string())],
+            rows: vec![vec![Value::Integer(estimate as i64)]],
+        })
+    }
+
+    /// Keyset pagination: `SELECT ... FROM t [WHERE ...] ORDER BY col LIMIT n
+    /// [AFTER CURSOR 'token']`
+    ///
+    /// Walks the column index of the ORDER BY column from the decoded cursor
+    /// (or from the start), filters with WHERE and stops after `n` matches,
+    /// so later pages cost the same as the first. Ties are broken by row id.
+    /// `next_cursor` encodes the last emitted index key and is `None` once
+    /// the scan is exhausted. Rows whose sort key is NULL are not indexed and
+    /// never appear.
+    pub fn execute_select_page(&self, stmt: &SelectStmt) -> Result<QueryPage> {
+        const PAGE: usize = 256;
+        if self.access_hook.lock().is_some() {
+            self.check_access(&Statement::Select {
+                stmt: stmt.clone(),
+                ctes: Vec::new(),
+            })?;
+        }
+        self.apply_session_time_zone();
+        query_memory::begin(self.query_memory_limit());
+
+        let unsupported =
+            |why: &str| MoteDBError::InvalidArgument(format!("Keyset pagination {}", why));
+        let Some(TableRef::Table {
+            name: table_name,
+            alias: None,
+        }) = &stmt.from
+        else {
+            return Err(unsupported("requires a single table without alias"));
+        };
+        let (sort_col, descending) = match stmt.order_by.as_deref() {
+            Some(
+                [OrderByExpr {
+                    expr: Expr::Column(col),
+                    asc,
+                }],
+            ) => (col.rsplit('.').next().unwrap_or(col), !asc),
+            _ => return Err(unsupported("requires ORDER BY on exactly one column")),
+        };
+        let Some(limit) = stmt.limit.filter(|&n| n > 0) else {
+            return Err(unsupported("requires a positive LIMIT"));
+        };
+        if stmt.offset.is_some()
+            || stmt.distinct
+            || stmt.group_by.is_some()
+            || stmt.having.is_some()
+            || stmt.latest_by.is_some()
+            || stmt.sample_by.is_some()
+            || self.has_aggregates(&stmt.columns)
+        {
+            return Err(unsupported(
+                "cannot be combined with OFFSET, DISTINCT, GROUP BY, aggregates, LATEST BY or SAMPLE BY",
+            ));
+        }
+
+        let schema = self.db.get_table_schema(table_name)?;
+        let index = self
+            .db
+            .index_registry
+            .find_by_column(
+                table_name,
+                sort_col,
+                crate::database::index_metadata::IndexType::Column,
+            )
+            .and_then(|index_name| self.db.column_indexes.get(&index_name))
+            .map(|r| r.value().clone())
+            .filter(|index| !index.is_multi_key())
+            .ok_or_else(|| {
+                unsupported(&format!(
+                    "requires a column index on '{}.{}'",
+                    table_name, sort_col
+                ))
+            })?;
+
+        let mut cursor = stmt
+            .after_cursor
+            .as_deref()
+            .map(crate::index::column_value::IndexCursor::from_token)
+            .transpose()?;
+        let where_clause = match &stmt.where_clause {
+            Some(w) => Some(self.materialize_subqueries(w)?),
+            None => None,
+        };
+
+        let mut rows = Vec::with_capacity(limit);
+        let last = 'scan: loop {
+            let (page, next) = index.scan_page(cursor.as_ref(), PAGE, descending)?;
+            let row_ids: Vec<u64> = page.iter().map(|c| c.row_id()).collect();
+            let fetched = self.db.get_table_rows_batch_arc(table_name, &row_ids)?;
+            for (key, (row_id, row)) in page.into_iter().zip(fetched) {
+                let Some(row) = row else { continue };
+                let sql_row = row_to_sql_row(&row, &schema)?;
+                if let Some(ref w) = where_clause {
+                    let keep = self
+                        .eval_with_materialized(w, &sql_row)
+                        .and_then(|val| self.to_bool(&val))
+                        .unwrap_or(false);
+                    if !keep {
+                        continue;
+                    }
+                }
+                rows.push((row_id, sql_row));
+                if rows.len() == limit {
+                    break 'scan Some(key);
+                }
+            }
+            match next {
+                Some(c) => cursor = Some(c),
+                None => break None,
+            }
+        };
+
+        // Index order is already the requested order
+        let mut rest = stmt.clone();
+        rest.order_by = None;
+        rest.limit = None;
+        rest.after_cursor = None;
+        let (columns, rows) = match self.finish_select(&rest, rows, &schema, None)? {
+            QueryResult::Select { columns, rows } => (columns, rows),
+            _ => {
+                return Err(MoteDBError::Query(
+                    "Keyset pagination expects a SELECT".into(),
+                ))
+            }
+        };
+        Ok(QueryPage {
+            columns,
+            rows,
+            next_cursor: last.map(|c| c.to_token()),
+        })
+    }
+
+    /// LATEST BY over a single table
+    ///
+    /// Picks the newest row per group (index walk when possible, otherwise
+    /// a filtered scan grouped in memory) and hands the survivors to the
+    /// regular projection / ORDER BY / LIMIT tail.
+    fn execute_latest_by_select(&self, stmt: &SelectStmt, table_name: &str) -> Result<QueryResult> {
+        let schema = self.db.get_table_schema(table_name)?;
+        let latest_by_cols = stmt.latest_by.as_deref().unwrap_or_default();
+
+        let latest_rows = match self.try_latest_by_via_index(stmt, table_name, &schema)? {
+            Some(rows) => rows,
+            None => {
+                // Filtered rows through the normal SELECT paths, all columns kept
+                let mut base = stmt.clone();
+                base.columns = vec![SelectColumn::Star];
+                base.distinct = false;
+                base.group_by = None;
+                base.having = None;
+                base.order_by = None;
+                base.limit = None;
+                base.offset = None;
+                base.latest_by = None;
+                let (columns, rows) = match self.execute_select_internal(&base)? {
+                    QueryResult::Select { columns, rows } => (columns, rows),
+                    _ => return Err(MoteDBError::Query("LATEST BY expects a SELECT".into())),
+                };
+                let rows = rows
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, row)| (i as u64, columns.iter().cloned().zip(row).collect()))
+                    .collect();
+                self.apply_latest_by(rows, latest_by_cols, &schema)?
+            }
+        };
+
+        let mut rest = stmt.clone();
+        rest.latest_by = None;
+        self.finish_select(&rest, latest_rows, &schema, None)
+    }
+
+    /// LATEST BY via indexes: walk the timestamp column index newest-first and
+    /// keep the first (newest) matching row of every group key, stopping once
+    /// each distinct key of the indexed grouping column has been seen.
+    ///
+    /// Returns None when the shape isn't supported (several grouping columns,
+    /// table alias, missing indexes) so the caller falls back to a scan.
+    fn try_latest_by_via_index(
+        &self,
+        stmt: &SelectStmt,
+        table_name: &str,
+        schema: &TableSchema,
+    ) -> Result<Option<Vec<(u64, SqlRow)>>> {
+        const PAGE: usize = 256;
+
+        let group_col = match (stmt.latest_by.as_deref(), &stmt.from) {
+            (Some([col]), Some(TableRef::Table { alias: None, .. })) => {
+                col.rsplit('.').next().unwrap_or(col)
+            }
+            _ => return Ok(None),
+        };
+        let Some(group_pos) = schema.get_column_position(group_col) else {
+            return Ok(None);
+        };
+        let ts_col = Self::latest_by_timestamp_column(schema)?;
+        let column_index = |col: &str| {
+            let index_name = self.db.index_registry.find_by_column(
+                table_name,
+                col,
+                crate::database::index_metadata::IndexType::Column,
+            )?;
+            self.db
+                .column_indexes
+                .get(&index_name)
+                .map(|r| r.value().clone())
+                .filter(|index| !index.is_multi_key())
+        };
+        let (Some(group_index), Some(ts_index)) =
+            (column_index(group_col), column_index(&ts_col.name))
+        else {
+            return Ok(None);
+        };
+
+        // Number of groups to find. Long text keys are truncated in the index,
+        // so distinct values could share a key and end the walk too early.
+        let keys = group_index.all_keys(&schema.columns[group_pos].col_type)?;
+        if keys.is_empty()
+            || keys
+                .iter()
+                .any(|k| matches!(k, Value::Text(s) if s.as_str().len() >= 64))
+        {
+            return Ok(None);
+        }
+        let wanted = keys.len();
+
+        let where_clause = match &stmt.where_clause {
+            Some(w) => Some(self.materialize_subqueries(w)?),
+            None => None,
+        };
+
+        // NULL keys form a group of their own, counted in `keys` like any other
+        let mut seen: std::collections::HashSet<Value> = std::collections::HashSet::new();
+        let mut found = 0;
+        let mut latest = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = ts_index.scan_page(cursor.as_ref(), PAGE, true)?;
+            let row_ids: Vec<u64> = page.iter().map(|c| c.row_id()).collect();
+            for (row_id, row) in self.db.get_table_rows_batch_arc(table_name, &row_ids)? {
+                let Some(row) = row else { continue };
+                let key = row.get(group_pos).cloned().unwrap_or(Value::Null);
+                if seen.contains(&key) {
+                    continue;
+                }
+                let sql_row = row_to_sql_row(&row, schema)?;
+                if let Some(ref w) = where_clause {
+                    let keep = self
+                        .eval_with_materialized(w, &sql_row)
+                        .and_then(|val| self.to_bool(&val))
+                        .unwrap_or(false);
+                    if !keep {
+                        continue;
+                    }
+                }
+                found += 1;
+                seen.insert(key);
+                latest.push((row_id, sql_row));
+            }
+            match next {
+                Some(c) if found < wanted => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(Some(latest))
+    }
+
     /// Apply LATEST BY clause - keep only the latest record per group
+    ///
+    /// Survivors keep their relative order so ORDER BY and projection still
+    /// line up with the remaining rows.
     fn apply_latest_by(
         &self,
-        projected_rows: Vec<Vec<Value>>,
-        filtered_rows: &[(u64, SqlRow)],
+        filtered_rows: Vec<(u64, SqlRow)>,
         latest_by_cols: &[String],
         schema: &TableSchema,
-    ) -> Result<Vec<Vec<Value>>> {
+    ) -> Result<Vec<(u64, SqlRow)>> {
         use std::collections::HashMap;
 
-        // Find timestamp column (must exist in schema)
-        let timestamp_col = schema
-            .columns
-            .iter()
-            .find(|c| c.col_type == ColumnType::Timestamp)
-            .ok_or_else(|| {
-                MoteDBError::Query("LATEST BY requires a TIMESTAMP column in the table".to_string())
-            })?;
-
-        let timestamp_col_name = &timestamp_col.name;
+        let timestamp_col_name = &Self::latest_by_timestamp_column(schema)?.name;
 
-        // Build grouping key -> (max_timestamp, projected_row) map
+        // Build grouping key -> (max_timestamp, row position) map
         // Use Vec<Value> keys to avoid per-row String allocation from to_string()/format!()
-        let mut groups: HashMap<Vec<Value>, (i64, Vec<Value>)> = HashMap::new();
+        let mut groups: HashMap<Vec<Value>, (i64, usize)> = HashMap::new();
 
         for (i, (_, full_row)) in filtered_rows.iter().enumerate() {
             // Extract grouping key as Vec<Value> — zero String allocation
@@ -15130,20 +17415,37 @@ impl QueryExecutor {
             };
 
             // Update group if this is a newer record
-            let projected_row = projected_rows[i].clone();
             groups
                 .entry(group_key)
-                .and_modify(|(max_ts, row)| {
+                .and_modify(|(max_ts, pos)| {
                     if ts_value > *max_ts {
                         *max_ts = ts_value;
-                        *row = projected_row.clone();
+                        *pos = i;
                     }
                 })
-                .or_insert((ts_value, projected_row));
+                .or_insert((ts_value, i));
         }
 
         // Extract all latest records
-        Ok(groups.into_values().map(|(_, row)| row).collect())
+        let keep: std::collections::HashSet<usize> =
+            groups.into_values().map(|(_, pos)| pos).collect();
+        Ok(filtered_rows
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| keep.contains(i))
+            .map(|(_, row)| row)
+            .collect())
+    }
+
+    /// The column LATEST BY orders by: the table's first TIMESTAMP column
+    fn latest_by_timestamp_column(schema: &TableSchema) -> Result<&crate::types::ColumnDef> {
+        schema
+            .columns
+            .iter()
+            .find(|c| c.col_type == ColumnType::Timestamp)
+            .ok_or_else(|| {
+                MoteDBError::Query("LATEST BY requires a TIMESTAMP column in the table".to_string())
+            })
     }
 
     /// Apply GROUP BY aggregation
@@ -15207,7 +17509,13 @@ impl QueryExecutor {
                 .collect();
             let group_key = group_key?;
 
-            groups.entry(group_key).or_default().push(row);
+            match groups.entry(group_key) {
+                std::collections::hash_map::Entry::Occupied(mut e) => e.get_mut().push(row),
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    query_memory::charge_row(e.key(), "GROUP BY")?;
+                    e.insert(vec![row]);
+                }
+            }
         }
 
         // Compute aggregates for each group
@@ -15384,8 +17692,23 @@ impl QueryExecutor {
                                     count += 1;
                                 }
                             }
-                            Ok(Value::Integer(count))
+                            Ok(Value::Integer(count))
+                        }
+                    }
+                    "APPROX_COUNT_DISTINCT" => {
+                        if args.len() != 1 || matches!(args[0], Expr::Column(ref c) if c == "*") {
+                            return Err(MoteDBError::InvalidArgument(
+                                "APPROX_COUNT_DISTINCT requires one argument".to_string(),
+                            ));
+                        }
+                        let mut sketch = super::hll::HyperLogLog::new();
+                        for row in rows {
+                            let val = self.evaluator.eval(&args[0], row)?;
+                            if !matches!(val, Value::Null) {
+                                sketch.add(&val);
+                            }
                         }
+                        Ok(Value::Integer(sketch.estimate() as i64))
                     }
                     "SUM" => {
                         if args.is_empty() {
@@ -15408,46 +17731,15 @@ impl QueryExecutor {
                         } else {
                             all_vals
                         };
-                        let mut int_sum: i64 = 0;
-                        let mut float_sum: f64 = 0.0;
-                        let mut has_float = false;
-                        let mut has_value = false;
+                        let mut acc = crate::types::NumericSum::new();
                         for val in &vals {
-                            match val {
-                                Value::Integer(i) => {
-                                    has_value = true;
-                                    if has_float {
-                                        float_sum += *i as f64;
-                                    } else if let Some(s) = int_sum.checked_add(*i) {
-                                        int_sum = s;
-                                    } else {
-                                        has_float = true;
-                                        float_sum = int_sum as f64 + *i as f64;
-                                    }
-                                }
-                                Value::Float(f) => {
-                                    has_value = true;
-                                    if !has_float {
-                                        has_float = true;
-                                        float_sum = int_sum as f64;
-                                    }
-                                    float_sum += *f;
-                                }
-                                Value::Null => {}
-                                _ => {
-                                    return Err(MoteDBError::TypeError(
-                                        "SUM requires numeric values".to_string(),
-                                    ))
-                                }
+                            if !acc.add(val) {
+                                return Err(MoteDBError::TypeError(
+                                    "SUM requires numeric values".to_string(),
+                                ));
                             }
                         }
-                        if !has_value {
-                            Ok(Value::Null)
-                        } else if has_float {
-                            Ok(Value::Float(float_sum))
-                        } else {
-                            Ok(Value::Integer(int_sum))
-                        }
+                        acc.sum()
                     }
                     "AVG" => {
                         if args.is_empty() {
@@ -15469,31 +17761,15 @@ impl QueryExecutor {
                         } else {
                             all_vals
                         };
-                        let mut sum = 0.0;
-                        let mut count = 0;
+                        let mut acc = crate::types::NumericSum::new();
                         for val in &vals {
-                            match val {
-                                Value::Integer(i) => {
-                                    sum += *i as f64;
-                                    count += 1;
-                                }
-                                Value::Float(f) => {
-                                    sum += *f;
-                                    count += 1;
-                                }
-                                Value::Null => {}
-                                _ => {
-                                    return Err(MoteDBError::TypeError(
-                                        "AVG requires numeric values".to_string(),
-                                    ))
-                                }
+                            if !acc.add(val) {
+                                return Err(MoteDBError::TypeError(
+                                    "AVG requires numeric values".to_string(),
+                                ));
                             }
                         }
-                        if count == 0 {
-                            Ok(Value::Null)
-                        } else {
-                            Ok(Value::Float(sum / count as f64))
-                        }
+                        Ok(acc.avg())
                     }
                     "MIN" => {
                         if args.is_empty() {
@@ -15610,7 +17886,14 @@ impl QueryExecutor {
             Expr::FunctionCall { name, .. }
                 if matches!(
                     name.to_uppercase().as_str(),
-                    "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE"
+                    "COUNT"
+                        | "SUM"
+                        | "AVG"
+                        | "MIN"
+                        | "MAX"
+                        | "STDDEV"
+                        | "VARIANCE"
+                        | "APPROX_COUNT_DISTINCT"
                 ) =>
             {
                 let val = self.eval_aggregate(expr, rows)?;
@@ -15662,6 +17945,28 @@ impl QueryExecutor {
     }
 
     /// Check if column list contains any aggregate functions
+    /// Whether any SELECT expression calls `func` (case-insensitive), at any depth
+    fn select_calls_function(columns: &[SelectColumn], func: &str) -> bool {
+        fn calls(expr: &Expr, func: &str) -> bool {
+            match expr {
+                Expr::FunctionCall { name, args, .. } => {
+                    name.eq_ignore_ascii_case(func) || args.iter().any(|a| calls(a, func))
+                }
+                Expr::BinaryOp { left, right, .. } => calls(left, func) || calls(right, func),
+                Expr::UnaryOp { expr, .. } => calls(expr, func),
+                Expr::Case { whens, else_expr } => {
+                    whens.iter().any(|(c, v)| calls(c, func) || calls(v, func))
+                        || else_expr.as_deref().is_some_and(|e| calls(e, func))
+                }
+                _ => false,
+            }
+        }
+        columns.iter().any(|col| match col {
+            SelectColumn::Expr(expr, _) => calls(expr, func),
+            _ => false,
+        })
+    }
+
     fn has_aggregates(&self, columns: &[SelectColumn]) -> bool {
         columns.iter().any(|col| match col {
             SelectColumn::Expr(expr, _) => self.is_aggregate_expr(expr),
@@ -15809,7 +18114,14 @@ impl QueryExecutor {
                     // to the aggregate path, and VARIANCE/STDDEV evaluate
                     // per-row (returning NULL for every row instead of one
                     // aggregated value).
-                    "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE"
+                    "COUNT"
+                        | "SUM"
+                        | "AVG"
+                        | "MIN"
+                        | "MAX"
+                        | "STDDEV"
+                        | "VARIANCE"
+                        | "APPROX_COUNT_DISTINCT"
                 );
                 if is_agg_top {
                     return true;
@@ -15856,7 +18168,14 @@ impl QueryExecutor {
             Expr::FunctionCall { name, args, .. }
                 if matches!(
                     name.to_uppercase().as_str(),
-                    "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE"
+                    "COUNT"
+                        | "SUM"
+                        | "AVG"
+                        | "MIN"
+                        | "MAX"
+                        | "STDDEV"
+                        | "VARIANCE"
+                        | "APPROX_COUNT_DISTINCT"
                 ) =>
             {
                 out.push(expr.clone());
@@ -16063,10 +18382,7 @@ impl QueryExecutor {
         // ── Inline accumulators (zero-allocation) ──
         struct Acc {
             count: u64,
-            int_sum: i64,
-            float_sum: f64,
-            has_float: bool,
-            has_value: bool,
+            sum: crate::types::NumericSum,
             min_val: Option<Value>,
             max_val: Option<Value>,
         }
@@ -16074,10 +18390,7 @@ impl QueryExecutor {
             fn new() -> Self {
                 Self {
                     count: 0,
-                    int_sum: 0,
-                    float_sum: 0.0,
-                    has_float: false,
-                    has_value: false,
+                    sum: crate::types::NumericSum::new(),
                     min_val: None,
                     max_val: None,
                 }
@@ -16091,72 +18404,26 @@ impl QueryExecutor {
                         self.count += 1;
                     }
                     "SUM" | "AVG" => {
-                        self.has_value = true;
-                        self.count += 1;
-                        match val {
-                            Value::Integer(i) => {
-                                if self.has_float {
-                                    self.float_sum += *i as f64;
-                                } else if let Some(s) = self.int_sum.checked_add(*i) {
-                                    self.int_sum = s;
-                                } else {
-                                    self.has_float = true;
-                                    self.float_sum = self.int_sum as f64 + *i as f64;
-                                }
-                            }
-                            Value::Float(f) => {
-                                if !self.has_float {
-                                    self.has_float = true;
-                                    self.float_sum = self.int_sum as f64;
-                                }
-                                self.float_sum += *f;
-                            }
-                            _ => {}
-                        }
+                        self.sum.add(val);
                     }
-                    "MIN" => {
-                        self.has_value = true;
-                        if self.min_val.is_none() || val < self.min_val.as_ref().unwrap() {
-                            self.min_val = Some(val.clone());
-                        }
+                    "MIN" if self.min_val.as_ref().is_none_or(|m| val < m) => {
+                        self.min_val = Some(val.clone());
                     }
-                    "MAX" => {
-                        self.has_value = true;
-                        if self.max_val.is_none() || val > self.max_val.as_ref().unwrap() {
-                            self.max_val = Some(val.clone());
-                        }
+                    "MAX" if self.max_val.as_ref().is_none_or(|m| val > m) => {
+                        self.max_val = Some(val.clone());
                     }
                     _ => {}
                 }
             }
-            fn finalize(&self, func: &str) -> Value {
-                match func {
+            fn finalize(&self, func: &str) -> Result<Value> {
+                Ok(match func {
                     "COUNT" => Value::Integer(self.count as i64),
-                    "SUM" => {
-                        if !self.has_value {
-                            return Value::Null;
-                        }
-                        if self.has_float {
-                            Value::Float(self.float_sum)
-                        } else {
-                            Value::Integer(self.int_sum)
-                        }
-                    }
-                    "AVG" => {
-                        if self.count == 0 {
-                            return Value::Null;
-                        }
-                        let sum = if self.has_float {
-                            self.float_sum
-                        } else {
-                            self.int_sum as f64
-                        };
-                        Value::Float(sum / self.count as f64)
-                    }
+                    "SUM" => self.sum.sum()?,
+                    "AVG" => self.sum.avg(),
                     "MIN" => self.min_val.clone().unwrap_or(Value::Null),
                     "MAX" => self.max_val.clone().unwrap_or(Value::Null),
                     _ => Value::Null,
-                }
+                })
             }
         }
 
@@ -16292,7 +18559,7 @@ impl QueryExecutor {
             .iter()
             .enumerate()
             .map(|(i, (_, agg))| accumulators[i].finalize(&agg.func))
-            .collect();
+            .collect::<Result<_>>()?;
 
         Ok(Some(QueryResult::Select {
             columns: column_names,
@@ -16430,7 +18697,10 @@ impl QueryExecutor {
             for result in row_iter {
                 let (_row_id, row) = result?;
                 match Self::eval_expr_on_row(where_clause, &row, schema) {
-                    Ok(Value::Bool(true)) => matching.push(row),
+                    Ok(Value::Bool(true)) => {
+                        query_memory::charge_row(&row, "GROUP BY")?;
+                        matching.push(row)
+                    }
                     Ok(_) => {}                // false or null -> skip
                     Err(_) => return Ok(None), // can't evaluate positionally
                 }
@@ -16440,6 +18710,7 @@ impl QueryExecutor {
             let mut matching = Vec::new();
             for result in row_iter {
                 let (_row_id, row) = result?;
+                query_memory::charge_row(&row, "GROUP BY")?;
                 matching.push(row);
             }
             matching
@@ -16616,10 +18887,7 @@ impl QueryExecutor {
         // Pre-compute which select columns are aggregates and their positions
         struct AggAccumulator {
             count: u64,
-            int_sum: i64,
-            float_sum: f64,
-            has_float: bool,
-            has_value: bool,
+            sum: crate::types::NumericSum,
             min_val: Option<Value>,
             max_val: Option<Value>,
         }
@@ -16627,10 +18895,7 @@ impl QueryExecutor {
             fn new() -> Self {
                 Self {
                     count: 0,
-                    int_sum: 0,
-                    float_sum: 0.0,
-                    has_float: false,
-                    has_value: false,
+                    sum: crate::types::NumericSum::new(),
                     min_val: None,
                     max_val: None,
                 }
@@ -16644,72 +18909,26 @@ impl QueryExecutor {
                         self.count += 1;
                     }
                     "SUM" | "AVG" => {
-                        self.has_value = true;
-                        self.count += 1;
-                        match val {
-                            Value::Integer(i) => {
-                                if self.has_float {
-                                    self.float_sum += *i as f64;
-                                } else if let Some(s) = self.int_sum.checked_add(*i) {
-                                    self.int_sum = s;
-                                } else {
-                                    self.has_float = true;
-                                    self.float_sum = self.int_sum as f64 + *i as f64;
-                                }
-                            }
-                            Value::Float(f) => {
-                                if !self.has_float {
-                                    self.has_float = true;
-                                    self.float_sum = self.int_sum as f64;
-                                }
-                                self.float_sum += *f;
-                            }
-                            _ => {}
-                        }
+                        self.sum.add(val);
                     }
-                    "MIN" => {
-                        self.has_value = true;
-                        if self.min_val.is_none() || val < self.min_val.as_ref().unwrap() {
-                            self.min_val = Some(val.clone());
-                        }
+                    "MIN" if self.min_val.as_ref().is_none_or(|m| val < m) => {
+                        self.min_val = Some(val.clone());
                     }
-                    "MAX" => {
-                        self.has_value = true;
-                        if self.max_val.is_none() || val > self.max_val.as_ref().unwrap() {
-                            self.max_val = Some(val.clone());
-                        }
+                    "MAX" if self.max_val.as_ref().is_none_or(|m| val > m) => {
+                        self.max_val = Some(val.clone());
                     }
                     _ => {}
                 }
             }
-            fn finalize(&self, func: &str) -> Value {
-                match func {
+            fn finalize(&self, func: &str) -> Result<Value> {
+                Ok(match func {
                     "COUNT" => Value::Integer(self.count as i64),
-                    "SUM" => {
-                        if !self.has_value {
-                            return Value::Null;
-                        }
-                        if self.has_float {
-                            Value::Float(self.float_sum)
-                        } else {
-                            Value::Integer(self.int_sum)
-                        }
-                    }
-                    "AVG" => {
-                        if self.count == 0 {
-                            return Value::Null;
-                        }
-                        let sum = if self.has_float {
-                            self.float_sum
-                        } else {
-                            self.int_sum as f64
-                        };
-                        Value::Float(sum / self.count as f64)
-                    }
+                    "SUM" => self.sum.sum()?,
+                    "AVG" => self.sum.avg(),
                     "MIN" => self.min_val.clone().unwrap_or(Value::Null),
                     "MAX" => self.max_val.clone().unwrap_or(Value::Null),
                     _ => Value::Null,
-                }
+                })
             }
         }
 
@@ -16860,10 +19079,19 @@ impl QueryExecutor {
                 .collect();
 
             // Find or create group
-            let entry = groups.entry(group_key.clone()).or_insert_with(|| {
-                let accums = (0..num_aggs).map(|_| AggAccumulator::new()).collect();
-                (group_key, accums)
-            });
+            let entry = match groups.entry(group_key.clone()) {
+                std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    // Key is held twice (map key and output values)
+                    query_memory::charge(
+                        2 * group_key.iter().map(Value::memory_size).sum::<usize>()
+                            + num_aggs * std::mem::size_of::<AggAccumulator>(),
+                        "GROUP BY",
+                    )?;
+                    let accums = (0..num_aggs).map(|_| AggAccumulator::new()).collect();
+                    e.insert((group_key, accums))
+                }
+            };
 
             // Update each aggregate accumulator using the decoded row.
             for (agg_idx, &select_idx) in agg_indices.iter().enumerate() {
@@ -16906,7 +19134,7 @@ impl QueryExecutor {
                     }
                 } else if let Some(agg) = agg_info {
                     let accum = agg_iter.next().unwrap();
-                    result_row.push(accum.finalize(&agg.func));
+                    result_row.push(accum.finalize(&agg.func)?);
                 } else {
                     result_row.push(Value::Null);
                 }
@@ -17004,10 +19232,6 @@ impl QueryExecutor {
                 }
             }
             "SUM" => {
-                let mut int_sum: i64 = 0;
-                let mut float_sum: f64 = 0.0;
-                let mut has_float = false;
-                let mut has_value = false;
                 // DISTINCT: dedup non-NULL values first.
                 let distinct_vals: Vec<Value> = if agg.distinct {
                     collect_distinct_positional(agg.col_pos, rows)
@@ -17020,46 +19244,17 @@ impl QueryExecutor {
                 } else {
                     Box::new(rows.iter().filter_map(|r| agg.col_pos.and_then(|p| r.get(p).cloned())))
                 };
+                let mut acc = crate::types::NumericSum::new();
                 for val in iter {
-                    match val {
-                        Value::Integer(i) => {
-                            has_value = true;
-                            if has_float {
-                                float_sum += i as f64;
-                            } else if let Some(s) = int_sum.checked_add(i) {
-                                int_sum = s;
-                            } else {
-                                has_float = true;
-                                float_sum = int_sum as f64 + i as f64;
-                            }
-                        }
-                        Value::Float(f) => {
-                            has_value = true;
-                            if !has_float {
-                                has_float = true;
-                                float_sum = int_sum as f64;
-                            }
-                            float_sum += f;
-                        }
-                        Value::Null => {}
-                        _ => {
-                            return Err(MoteDBError::TypeError(
-                                "SUM requires numeric values".to_string(),
-                            ))
-                        }
+                    if !acc.add(&val) {
+                        return Err(MoteDBError::TypeError(
+                            "SUM requires numeric values".to_string(),
+                        ));
                     }
                 }
-                if !has_value {
-                    Ok(Value::Null)
-                } else if has_float {
-                    Ok(Value::Float(float_sum))
-                } else {
-                    Ok(Value::Integer(int_sum))
-                }
+                acc.sum()
             }
             "AVG" => {
-                let mut sum = 0.0;
-                let mut count = 0;
                 let distinct_vals: Vec<Value> = if agg.distinct {
                     collect_distinct_positional(agg.col_pos, rows)
                 } else {
@@ -17070,29 +19265,15 @@ impl QueryExecutor {
                 } else {
                     Box::new(rows.iter().filter_map(|r| agg.col_pos.and_then(|p| r.get(p).cloned())))
                 };
+                let mut acc = crate::types::NumericSum::new();
                 for val in iter {
-                    match val {
-                        Value::Integer(i) => {
-                            sum += i as f64;
-                            count += 1;
-                        }
-                        Value::Float(f) => {
-                            sum += f;
-                            count += 1;
-                        }
-                        Value::Null => {}
-                        _ => {
-                            return Err(MoteDBError::TypeError(
-                                "AVG requires numeric values".to_string(),
-                            ))
-                        }
+                    if !acc.add(&val) {
+                        return Err(MoteDBError::TypeError(
+                            "AVG requires numeric values".to_string(),
+                        ));
                     }
                 }
-                if count > 0 {
-                    Ok(Value::Float(sum / count as f64))
-                } else {
-                    Ok(Value::Null)
-                }
+                Ok(acc.avg())
             }
             "MIN" => {
                 let mut min_val: Option<Value> = None;
@@ -17406,7 +19587,12 @@ impl QueryExecutor {
 
         let limit = stmt.limit.unwrap_or(usize::MAX);
         let offset = stmt.offset.unwrap_or(0);
-        let cap_hint = limit.min(self.db.fast_row_count(table_name).unwrap_or(1024) as usize);
+        let cap = self.streaming_config().materialize_capacity;
+        let cap_hint = limit.min(
+            self.db
+                .fast_row_count(table_name)
+                .map_or(cap, |n| (n as usize).min(cap)),
+        );
 
         // Scan → filter → project in a single pass
         let row_iter = self.db.scan_table_rows_streaming(table_name)?;
@@ -17624,6 +19810,10 @@ impl QueryExecutor {
             top.into_iter().skip(offset).collect()
         } else {
             // Full sort path (no LIMIT, or DISTINCT requires full dedup)
+            if !order_positions.is_empty() || has_distinct {
+                let operator = if has_distinct { "DISTINCT" } else { "ORDER BY" };
+                query_memory::charge_rows(&projected_rows, operator)?;
+            }
             if !order_positions.is_empty() {
                 projected_rows.sort_by(|a, b| {
                     for &(col_idx, asc) in &order_positions {
@@ -17653,21 +19843,31 @@ impl QueryExecutor {
         }))
     }
 
-    /// Execute INSERT statement (owned, for execute() path)
-    fn execute_insert(&self, stmt: InsertStmt) -> Result<QueryResult> {
-        self.execute_insert_ref(&stmt)
-    }
-
     /// Execute INSERT statement (borrowed, avoids clone in streaming path)
     fn execute_insert_ref(&self, stmt: &InsertStmt) -> Result<QueryResult> {
         let schema = self.db.get_table_schema(&stmt.table)?;
+        Self::reject_materialized_view_write(&schema)?;
 
         // Determine column order
         let columns = if let Some(ref cols) = stmt.columns {
+            if let Some(c) = cols
+                .iter()
+                .find(|c| schema.get_column(c).is_some_and(|d| d.generated.is_some()))
+            {
+                return Err(MoteDBError::InvalidArgument(format!(
+                    "Cannot INSERT into generated column '{}'",
+                    c
+                )));
+            }
             cols.clone()
         } else {
-            // Use schema order
-            schema.columns.iter().map(|c| c.name.clone()).collect()
+            // Use schema order; generated columns are computed, not supplied
+            schema
+                .columns
+                .iter()
+                .filter(|c| c.generated.is_none())
+                .map(|c| c.name.clone())
+                .collect()
         };
 
         // Route TimeSeries INSERT to columnar store
@@ -17678,7 +19878,7 @@ impl QueryExecutor {
         let has_vector_column = schema
             .columns
             .iter()
-            .any(|col| matches!(col.col_type, crate::types::ColumnType::Tensor(_)));
+            .any(|col| col.col_type.vector_dim().is_some());
 
         // Prepare all rows — resolve expressions to Values, build Row directly
         let mut prepared_rows = Vec::new();
@@ -17697,12 +19897,13 @@ impl QueryExecutor {
                 .iter()
                 .map(|expr| match expr {
                     Expr::Literal(v) => Ok(v.clone()),
-                    Expr::Parameter(_) => {
+                    // Parameters and row-independent calls such as GEN_UUID()
+                    Expr::Parameter(_) | Expr::FunctionCall { .. } => {
                         let empty_row = SqlRow::new();
                         self.evaluator.eval(expr, &empty_row)
                     }
                     other => Err(MoteDBError::InvalidArgument(format!(
-                        "INSERT VALUES must be literals or parameters, got {:?}",
+                        "INSERT VALUES must be literals, parameters or function calls, got {:?}",
                         other
                     ))),
                 })
@@ -17735,7 +19936,7 @@ impl QueryExecutor {
                 last_row_id = Some(row_id);
 
                 for (idx, col_def) in schema.columns.iter().enumerate() {
-                    if let crate::types::ColumnType::Tensor(_dim) = col_def.col_type {
+                    if col_def.col_type.vector_dim().is_some() {
                         if let Some(Value::Vector(vec)) = row.get(idx) {
                             let index_name = format!("{}_{}", stmt.table, col_def.name);
                             vector_batches
@@ -17790,26 +19991,124 @@ impl QueryExecutor {
         Ok(QueryResult::Modification { affected_rows })
     }
 
+    /// Execute a single-row parameterized INSERT once per tuple in `param_rows`
+    ///
+    /// The tuples are bound into one multi-row INSERT, so the whole batch is
+    /// one write (one WAL record, one index pass per index) outside a
+    /// transaction, and the statement is parsed only once by the caller.
+    pub fn execute_insert_batch(
+        &self,
+        statement: &Statement,
+        param_rows: &[Vec<Value>],
+    ) -> Result<QueryResult> {
+        let template = match statement {
+            Statement::Insert(stmt) if stmt.values.len() == 1 => stmt,
+            _ => {
+                return Err(MoteDBError::InvalidArgument(
+                    "batch execution requires a single-row INSERT ... VALUES (...)".to_string(),
+                ))
+            }
+        };
+        self.check_access(statement)?;
+        if param_rows.is_empty() {
+            return Ok(QueryResult::Modification { affected_rows: 0 });
+        }
+        let values = param_rows
+            .iter()
+            .map(|params| {
+                template.values[0]
+                    .iter()
+                    .map(|e| Self::substitute_expr(e, params))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.reset_last_insert_id();
+        self.execute_insert_ref(&InsertStmt {
+            table: template.table.clone(),
+            columns: template.columns.clone(),
+            values,
+        })
+    }
+
     /// Execute UPDATE statement
     fn execute_update(&self, stmt: UpdateStmt) -> Result<QueryResult> {
         let schema = self.db.get_table_schema(&stmt.table)?;
+        Self::reject_materialized_view_write(&schema)?;
+        let stmt = if schema.has_virtual_columns() {
+            let virtuals = Self::virtual_column_exprs(&schema)?;
+            UpdateStmt {
+                assignments: stmt
+                    .assignments
+                    .iter()
+                    .map(|(c, e)| (c.clone(), Self::inline_virtual_refs(e, &virtuals)))
+                    .collect(),
+                where_clause: stmt
+                    .where_clause
+                    .as_ref()
+                    .map(|wc| Self::inline_virtual_refs(wc, &virtuals)),
+                table: stmt.table,
+            }
+        } else {
+            stmt
+        };
+        // Bound `?` values become literals so `WHERE pk = ?` reaches the PK
+        // fast path and SET/WHERE can be evaluated positionally
+        let stmt = self.substitute_params_update(stmt)?;
+        // Resolve subqueries in WHERE like DELETE does; correlated ones stay
+        // and run per row below
+        let stmt = match stmt.where_clause {
+            Some(ref wc) if Self::expr_contains_subquery(wc) => UpdateStmt {
+                where_clause: Some(self.materialize_subqueries(wc)?),
+                ..stmt
+            },
+            _ => stmt,
+        };
+        let correlated_where = stmt
+            .where_clause
+            .as_ref()
+            .is_some_and(Self::contains_correlated);
 
         // Validate all assignment columns exist before modifying any rows
         for (col_name, _) in &stmt.assignments {
-            if schema.get_column(col_name).is_none() {
-                return Err(StorageError::ColumnNotFound(format!(
-                    "'{}' in table '{}'",
-                    col_name, stmt.table
-                )));
+            match schema.get_column(col_name) {
+                None => {
+                    return Err(StorageError::ColumnNotFound(format!(
+                        "'{}' in table '{}'",
+                        col_name, stmt.table
+                    )))
+                }
+                Some(cd) if cd.generated.is_some() => {
+                    return Err(MoteDBError::InvalidArgument(format!(
+                        "Cannot UPDATE generated column '{}'",
+                        col_name
+                    )))
+                }
+                Some(_) => {}
             }
         }
+        // Text assigned to a TIMESTAMP column is read in the session time zone
+        let stmt = UpdateStmt {
+            assignments: stmt
+                .assignments
+                .into_iter()
+                .map(|(col, expr)| match (schema.get_column(&col), expr) {
+                    (Some(cd), Expr::Literal(v)) => {
+                        let v = super::row_converter::timestamp_text(&cd.col_type, v)?;
+                        Ok((col, Expr::Literal(v)))
+                    }
+                    (_, expr) => Ok((col, expr)),
+                })
+                .collect::<Result<_>>()?,
+            ..stmt
+        };
 
         // 🚀 PK fast path: skip full table scan for WHERE pk = value
         if let Some(ref where_clause) = stmt.where_clause {
             if let Some((col_name, target_value)) = self.try_extract_point_query(where_clause) {
+                let bare_col = col_name.rsplit('.').next().unwrap_or(&col_name);
                 let is_pk = schema
                     .primary_key()
-                    .map(|pk| pk == col_name)
+                    .map(|pk| pk == col_name || pk == bare_col)
                     .unwrap_or(false);
 
                 if is_pk {
@@ -17822,7 +20121,12 @@ impl QueryExecutor {
                     &col_name,
                     crate::database::index_metadata::IndexType::Column,
                 ) {
-                    if let Some(index) = self.db.column_indexes.get(&index_name) {
+                    if let Some(index) = self
+                        .db
+                        .column_indexes
+                        .get(&index_name)
+                        .filter(|index| !index.value().is_multi_key())
+                    {
                         let matching_row_ids = index
                             .value()
                             .get_arc(&target_value)
@@ -17846,15 +20150,27 @@ impl QueryExecutor {
         let row_iter = self.db.scan_table_rows_streaming(&stmt.table)?;
 
         let mut affected_rows = 0;
+        let mut writer = RowWriter::with_batching(
+            self,
+            &stmt.table,
+            &schema,
+            !Self::update_assigns_pk(&stmt, &schema),
+        );
 
         for result in row_iter {
             let (row_id, row) = result?;
 
             // WHERE filter using positional evaluation (no HashMap)
             let should_update = if let Some(ref where_clause) = stmt.where_clause {
-                Self::eval_expr_on_row(where_clause, &row, &schema)
-                    .map(|v| Self::is_truthy(&v))
-                    .unwrap_or(false)
+                if correlated_where {
+                    self.eval_unmaterialized(where_clause, &row_to_sql_row(&row, &schema)?)
+                        .and_then(|v| self.to_bool(&v))
+                        .unwrap_or(false)
+                } else {
+                    Self::eval_expr_on_row(where_clause, &row, &schema)
+                        .map(|v| Self::is_truthy(&v))
+                        .unwrap_or(false)
+                }
             } else {
                 true
             };
@@ -17874,13 +20190,7 @@ impl QueryExecutor {
                         // (e.g., `UPDATE t SET v = (SELECT MAX(v) FROM t)`).
                         // Without this, eval_expr_on_row returns NULL for
                         // Subquery nodes (it doesn't execute them).
-                        let materialized = self.materialize_subqueries(expr)?;
-                        if let Expr::Literal(v) = materialized {
-                            v
-                        } else {
-                            Self::eval_expr_on_row(&materialized, &row, &schema)
-                                .unwrap_or(Value::Null)
-                        }
+                        self.eval_set_subquery(expr, &row, &schema)?
                     } else {
                         Self::eval_expr_on_row(expr, &row, &schema).unwrap_or(Value::Null)
                     };
@@ -17891,28 +20201,38 @@ impl QueryExecutor {
                 }
             }
 
-            // 🔑 Record undo delta for transactional UPDATE (so ROLLBACK can restore).
-            let txn_id = self.current_txn_id();
-            if let Some(tid) = txn_id {
-                let _ = self.db.txn_coordinator.record_write_delta(
-                    tid,
-                    crate::txn::coordinator::DeltaOperation::Update(
-                        row_id,
-                        stmt.table.clone(),
-                        Arc::new(row.clone()),
-                    ),
-                );
-            }
-
-            self.db
-                .update_row_in_table_with_schema(&stmt.table, row_id, row, new_row, &schema)?;
-
+            writer.update(row_id, row, new_row)?;
             affected_rows += 1;
         }
+        writer.finish()?;
 
         Ok(QueryResult::Modification { affected_rows })
     }
+
+    /// Whether an UPDATE's SET list assigns the primary key column
+    fn update_assigns_pk(stmt: &UpdateStmt, schema: &TableSchema) -> bool {
+        schema
+            .primary_key()
+            .is_some_and(|pk| stmt.assignments.iter().any(|(c, _)| c == pk))
+    }
+
+    /// Value of a SET expression holding subqueries for one row. A
+    /// correlated subquery reads the row's columns, so it runs per row.
+    fn eval_set_subquery(&self, expr: &Expr, row: &Row, schema: &TableSchema) -> Result<Value> {
+        match self.materialize_subqueries(expr)? {
+            Expr::Literal(v) => Ok(v),
+            materialized if Self::contains_correlated(&materialized) => {
+                self.eval_unmaterialized(&materialized, &row_to_sql_row(row, schema)?)
+            }
+            materialized => {
+                Ok(Self::eval_expr_on_row(&materialized, row, schema).unwrap_or(Value::Null))
+            }
+        }
+    }
     fn execute_delete(&self, stmt: DeleteStmt) -> Result<QueryResult> {
+        if let Ok(schema) = self.db.get_table_schema(&stmt.table) {
+            Self::reject_materialized_view_write(&schema)?;
+        }
         // 🔑 Resolve subqueries in WHERE clause before evaluation. Without this,
         // DELETE ... WHERE id NOT IN (SELECT ...) silently matches no rows
         // (the evaluator can't execute subqueries against an SqlRow).
@@ -17929,6 +20249,10 @@ impl QueryExecutor {
             stmt
         };
         let schema = self.db.get_table_schema(&stmt.table)?;
+        let stmt = DeleteStmt {
+            where_clause: Self::inline_virtual_where(stmt.where_clause, &schema)?,
+            ..stmt
+        };
 
         // 🚀 PK fast path: skip full table scan for WHERE pk = value
         if let Some(ref where_clause) = stmt.where_clause {
@@ -17941,30 +20265,24 @@ impl QueryExecutor {
                 if is_pk {
                     return self.execute_delete_pk(&stmt, &schema, &target_value);
                 }
+            }
+        }
 
-                // 🚀 Column index fast path: use index to find matching rows
-                if let Some(index_name) = self.db.index_registry.find_by_column(
-                    &stmt.table,
-                    &col_name,
-                    crate::database::index_metadata::IndexType::Column,
-                ) {
-                    if let Some(index) = self.db.column_indexes.get(&index_name) {
-                        let matching_row_ids = index
-                            .value()
-                            .get_arc(&target_value)
-                            .unwrap_or_else(|_| Arc::new(Vec::new()));
-                        if matching_row_ids.is_empty() {
-                            return Ok(QueryResult::Modification { affected_rows: 0 });
-                        }
-                        return self.execute_delete_by_row_ids(
-                            &stmt,
-                            &schema,
-                            &matching_row_ids,
-                            &col_name,
-                            &target_value,
-                        );
-                    }
-                }
+        // 🚀 Index-selected row set: same scan-method choice as SELECT, so
+        // `DELETE ... WHERE ts < x` on an indexed column reads only its victims
+        if stmt.where_clause.is_some() {
+            let params = if stmt
+                .where_clause
+                .as_ref()
+                .is_some_and(Self::contains_parameter)
+            {
+                self.evaluator.get_params()
+            } else {
+                Vec::new()
+            };
+            let plan = self.optimizer.optimize_delete(&stmt, &params)?;
+            if let Some(row_ids) = self.plan_candidate_row_ids(&schema, &plan.scan_method)? {
+                return self.execute_delete_row_set(&stmt, &schema, &row_ids);
             }
         }
 
@@ -17972,6 +20290,7 @@ impl QueryExecutor {
         let row_iter = self.db.scan_table_rows_streaming(&stmt.table)?;
 
         let mut affected_rows = 0;
+        let mut writer = RowWriter::new(self, &stmt.table, &schema);
 
         for result in row_iter {
             let (row_id, row) = result?;
@@ -17979,8 +20298,7 @@ impl QueryExecutor {
 
             // Filter rows (WHERE clause)
             let should_delete = if let Some(ref where_clause) = stmt.where_clause {
-                self.evaluator
-                    .eval(where_clause, &sql_row)
+                self.eval_unmaterialized(where_clause, &sql_row)
                     .and_then(|val| self.to_bool(&val))
                     .unwrap_or(false)
             } else {
@@ -17991,23 +20309,11 @@ impl QueryExecutor {
                 continue;
             }
 
-            // 🔑 Record undo delta for transactional DELETE (so ROLLBACK can restore).
-            let txn_id = self.current_txn_id();
-            if let Some(tid) = txn_id {
-                let _ = self.db.txn_coordinator.record_write_delta(
-                    tid,
-                    crate::txn::coordinator::DeltaOperation::Delete(
-                        row_id,
-                        stmt.table.clone(),
-                        Arc::new(row.clone()),
-                    ),
-                );
-            }
-
             // Delete row - 底层已实现增量索引维护，传入 old_row 避免重复加载
-            self.db.delete_row_from_table(&stmt.table, row_id, row)?;
+            writer.delete(row_id, row)?;
             affected_rows += 1;
         }
+        writer.finish()?;
 
         Ok(QueryResult::Modification { affected_rows })
     }
@@ -18120,6 +20426,10 @@ impl QueryExecutor {
                 if let Some(cd) = schema.get_column(col_name) {
                     let new_val = if let Expr::Literal(v) = expr {
                         v.clone()
+                    } else if Self::expr_contains_subquery(expr) {
+                        // Same as the scan path: scalar subqueries in SET
+                        // must be executed, not evaluated positionally
+                        self.eval_set_subquery(expr, &row, schema)?
                     } else {
                         Self::eval_expr_on_row(expr, &row, schema).unwrap_or(Value::Null)
                     };
@@ -18206,6 +20516,12 @@ impl QueryExecutor {
         where_val: &crate::types::Value,
     ) -> Result<QueryResult> {
         let mut affected_rows = 0;
+        let mut writer = RowWriter::with_batching(
+            self,
+            &stmt.table,
+            schema,
+            !Self::update_assigns_pk(stmt, schema),
+        );
         for &row_id in row_ids {
             let row = match self.db.get_table_row(&stmt.table, row_id)? {
                 Some(r) => r,
@@ -18239,75 +20555,143 @@ impl QueryExecutor {
                 }
             }
 
-            // 🔑 Record undo delta for transactional UPDATE (PK/index fast path).
-            let txn_id = self.current_txn_id();
-            if let Some(tid) = txn_id {
-                let _ = self.db.txn_coordinator.record_write_delta(
-                    tid,
-                    crate::txn::coordinator::DeltaOperation::Update(
-                        row_id,
-                        stmt.table.clone(),
-                        Arc::new(row.clone()),
-                    ),
-                );
-            }
-
-            self.db
-                .update_row_in_table_with_schema(&stmt.table, row_id, row, new_row, schema)?;
+            writer.update(row_id, row, new_row)?;
             affected_rows += 1;
         }
+        writer.finish()?;
 
         Ok(QueryResult::Modification { affected_rows })
     }
 
-    /// Column index fast path for DELETE: lookup by row_ids from index
-    fn execute_delete_by_row_ids(
+    /// Candidate row ids of an index plan, or `None` when the plan scans
+    fn plan_candidate_row_ids(
+        &self,
+        schema: &crate::types::TableSchema,
+        scan_method: &super::optimizer::ScanMethod,
+    ) -> Result<Option<Vec<RowId>>> {
+        use super::optimizer::ScanMethod;
+
+        let row_ids = match scan_method {
+            ScanMethod::PointQuery {
+                table,
+                column,
+                value,
+            } => {
+                if schema.primary_key() == Some(column.as_str()) {
+                    self.resolve_pk_row_ids(table, schema, value)?
+                } else {
+                    self.db.query_by_column(table, column, value)?
+                }
+            }
+            ScanMethod::RangeQuery {
+                table,
+                column,
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+            } => self.db.query_by_column_between(
+                table,
+                column,
+                start,
+                *start_inclusive,
+                end,
+                *end_inclusive,
+            )?,
+            ScanMethod::IndexIntersection {
+                table,
+                column1,
+                probe1,
+                column2,
+                probe2,
+            } => self
+                .intersect_index_probes(table, (column1, probe1), (column2, probe2))?
+                .iter()
+                .collect(),
+            ScanMethod::ArrayContains {
+                table,
+                column,
+                value,
+            } => self.db.query_by_array_element(table, column, value)?,
+            _ => return Ok(None),
+        };
+
+        // A lagging async index may not list fresh rows yet; let the scan decide
+        if row_ids.is_empty() && self.db.is_async_index_pipeline_active() {
+            return Ok(None);
+        }
+        Ok(Some(row_ids))
+    }
+
+    /// DELETE over the rows an index selected; the full WHERE is re-checked
+    /// on each row since the index only narrows the candidates
+    fn execute_delete_row_set(
         &self,
         stmt: &DeleteStmt,
         schema: &crate::types::TableSchema,
         row_ids: &[RowId],
-        where_col: &str,
-        where_val: &crate::types::Value,
     ) -> Result<QueryResult> {
+        let mut row_ids = row_ids.to_vec();
+        row_ids.sort_unstable();
+        row_ids.dedup();
+
         let mut affected_rows = 0;
-        for &row_id in row_ids {
+        let mut writer = RowWriter::new(self, &stmt.table, schema);
+        for row_id in row_ids {
             let row = match self.db.get_table_row(&stmt.table, row_id)? {
                 Some(r) => r,
                 None => continue,
             };
 
-            // Re-check WHERE condition against actual row data
-            if let Some(col) = schema.get_column(where_col) {
-                if let Some(actual_val) = row.get(col.position) {
-                    if actual_val != where_val {
-                        continue;
-                    }
-                } else {
+            if let Some(ref where_clause) = stmt.where_clause {
+                let sql_row = row_to_sql_row(&row, schema)?;
+                let matches = self
+                    .evaluator
+                    .eval(where_clause, &sql_row)
+                    .and_then(|val| self.to_bool(&val))
+                    .unwrap_or(false);
+                if !matches {
                     continue;
                 }
             }
 
-            // 🔑 Record undo delta for transactional DELETE (PK/index fast path).
-            let txn_id = self.current_txn_id();
-            if let Some(tid) = txn_id {
-                let _ = self.db.txn_coordinator.record_write_delta(
-                    tid,
-                    crate::txn::coordinator::DeltaOperation::Delete(
-                        row_id,
-                        stmt.table.clone(),
-                        Arc::new(row.clone()),
-                    ),
-                );
-            }
-
-            self.db.delete_row_from_table(&stmt.table, row_id, row)?;
+            writer.delete(row_id, row)?;
             affected_rows += 1;
         }
+        writer.finish()?;
 
         Ok(QueryResult::Modification { affected_rows })
     }
 
     /// Execute CREATE TABLE statement
+    /// Storage type for a declared SQL column type
+    fn column_type_for(data_type: &DataType) -> ColumnType {
+        match data_type {
+            DataType::Integer => ColumnType::Integer,
+            DataType::BigInt => ColumnType::Integer, // 🚀 Phase 4: Map BIGINT to Integer (both i64)
+            DataType::Float => ColumnType::Float,
+            DataType::Text => ColumnType::Text,
+            DataType::Boolean => ColumnType::Boolean,
+            DataType::Timestamp => ColumnType::Timestamp,
+            DataType::Vector(dim, None) => ColumnType::Tensor(dim.unwrap_or(128)),
+            DataType::Vector(dim, Some(encoding)) => ColumnType::EncodedVector {
+                dim: dim.unwrap_or(128),
+                encoding: *encoding,
+            },
+            DataType::Geometry => ColumnType::Spatial,
+            DataType::Decimal(precision, scale) => ColumnType::Decimal {
+                precision: *precision,
+                scale: *scale,
+            },
+            DataType::Uuid => ColumnType::Uuid,
+            DataType::Date => ColumnType::Date,
+            DataType::Time => ColumnType::Time,
+            DataType::Array(elem) => ColumnType::Array(Box::new(Self::column_type_for(elem))),
+            DataType::Tensor(shape) => ColumnType::ShapedTensor(shape.clone()),
+            DataType::Enum(labels) => ColumnType::Enum(labels.clone()),
+        }
+    }
+
     fn execute_create_table(&self, stmt: CreateTableStmt) -> Result<QueryResult> {
         // 🆕 IF NOT EXISTS: if the table already exists, silently no-op.
         if stmt.if_not_exists && self.db.get_table_schema(&stmt.table).is_ok() {
@@ -18320,16 +20704,7 @@ impl QueryExecutor {
             .iter()
             .enumerate()
             .map(|(pos, col)| {
-                let column_type = match col.data_type {
-                    DataType::Integer => ColumnType::Integer,
-                    DataType::BigInt => ColumnType::Integer, // 🚀 Phase 4: Map BIGINT to Integer (both i64)
-                    DataType::Float => ColumnType::Float,
-                    DataType::Text => ColumnType::Text,
-                    DataType::Boolean => ColumnType::Boolean,
-                    DataType::Timestamp => ColumnType::Timestamp,
-                    DataType::Vector(dim) => ColumnType::Tensor(dim.unwrap_or(128)),
-                    DataType::Geometry => ColumnType::Spatial,
-                };
+                let column_type = Self::column_type_for(&col.data_type);
 
                 let mut col_def = crate::types::ColumnDef::new(col.name.clone(), column_type, pos);
                 if !col.nullable {
@@ -18385,6 +20760,9 @@ impl QueryExecutor {
         if let Some(ref ttl) = stmt.ttl {
             schema = schema.with_ttl(*ttl);
         }
+        if stmt.max_rows.is_some() || stmt.max_bytes.is_some() {
+            schema = schema.with_ring_buffer(stmt.max_rows, stmt.max_bytes);
+        }
 
         self.db.create_table(schema.clone())?;
 
@@ -18420,11 +20798,18 @@ impl QueryExecutor {
             Some(ttl) => format!(", TTL {}", ttl),
             None => String::new(),
         };
+        let mut ring_info = String::new();
+        if let Some(max_rows) = stmt.max_rows {
+            ring_info.push_str(&format!(", MAX_ROWS {}", max_rows));
+        }
+        if let Some(max_bytes) = stmt.max_bytes {
+            ring_info.push_str(&format!(", MAX_BYTES {}", max_bytes));
+        }
 
         Ok(QueryResult::Definition {
             message: format!(
-                "Table '{}' created successfully{}{}{}",
-                stmt.table, pk_info, ts_info, ttl_info
+                "Table '{}' created successfully{}{}{}{}",
+                stmt.table, pk_info, ts_info, ttl_info, ring_info
             ),
         })
     }
@@ -18438,6 +20823,12 @@ impl QueryExecutor {
             .iter()
             .find(|c| c.name == stmt.column)
             .ok_or_else(|| MoteDBError::ColumnNotFound(stmt.column.clone()))?;
+        if column.generated.as_ref().is_some_and(|g| !g.stored) {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot index VIRTUAL column '{}'; only STORED generated columns can be indexed",
+                column.name
+            )));
+        }
 
         // Determine index type: use explicit type from AST, or infer from column type
         let index_type = match stmt.index_type {
@@ -18453,7 +20844,7 @@ impl QueryExecutor {
             }
             IndexType::Vector => {
                 // Verify column is tensor/vector
-                if let ColumnType::Tensor(_dim) = column.col_type {
+                if column.col_type.vector_dim().is_some() {
                     IndexType::Vector
                 } else {
                     return Err(MoteDBError::TypeError(format!(
@@ -18483,7 +20874,14 @@ impl QueryExecutor {
                 IndexType::Octree
             }
             IndexType::BTree | IndexType::Column => {
-                // B-Tree/Column index can be used for any comparable type
+                // B-Tree/Column index can be used for any comparable type.
+                // An ARRAY column is indexed per element (for ARRAY_CONTAINS)
+                if matches!(column.col_type, ColumnType::ShapedTensor(_)) {
+                    return Err(MoteDBError::TypeError(format!(
+                        "Cannot index TENSOR column '{}'",
+                        column.name
+                    )));
+                }
                 stmt.index_type.clone()
             }
         };
@@ -18502,71 +20900,19 @@ impl QueryExecutor {
                 // 1️⃣ Create empty text index
                 self.db.create_text_index(&index_name)?;
 
-                // 2️⃣ 🚀 Columnar fast path: bulk build from TextSegment
+                // 2️⃣ 🚀 Backfill from existing rows (columnar fast path, batched row scan fallback)
                 let column_pos = schema
                     .get_column_position(&stmt.column)
                     .ok_or_else(|| MoteDBError::ColumnNotFound(stmt.column.clone()))?;
                 let start_time = std::time::Instant::now();
-                let mut backfill_count = 0;
-
-                if let Ok(count) =
-                    self.db
-                        .build_text_index_from_columnar(&index_name, &stmt.table, column_pos)
-                {
-                    debug_log!(
-                        "[CREATE TEXT INDEX] Columnar build: {} docs in {:?}",
-                        count,
-                        start_time.elapsed()
-                    );
-                } else {
-                    // ✅ Fallback: 批量流式扫描（每批10000行，避免内存爆炸）
-                    let batch_iter = self.db.scan_table_rows_batched(&stmt.table, 10000)?;
-
-                    for batch_result in batch_iter {
-                        let batch = batch_result?;
-
-                        // 收集本批次的文本数据
-                        let texts_in_batch: Vec<_> = batch
-                            .iter()
-                            .filter_map(|(row_id, row)| {
-                                row.get(column_pos).and_then(|v| {
-                                    if let Value::Text(text) = v {
-                                        Some((*row_id, text.as_str()))
-                                    } else {
-                                        None
-                                    }
-                                })
-                            })
-                            .collect();
-
-                        // ✅ 一次写锁，批量插入整个batch
-                        if !texts_in_batch.is_empty() {
-                            if let Some(index_arc) = self.db.text_indexes.get(&index_name) {
-                                let mut index = index_arc.write();
-                                for (row_id, text) in texts_in_batch {
-                                    if let Err(e) = index.insert(row_id, text) {
-                                        debug_log!(
-                                            "⚠️ Failed to backfill text index for row {}: {}",
-                                            row_id,
-                                            e
-                                        );
-                                    } else {
-                                        backfill_count += 1;
-                                    }
-                                }
-                                // 锁在此处释放（每10000条释放一次，允许并发查询）
-                            }
-                        }
-                    }
-
-                    if backfill_count > 0 {
-                        debug_log!(
-                            "Built text index in {:?}, indexed {} rows",
-                            start_time.elapsed(),
-                            backfill_count
-                        );
-                    }
-                } // end else (columnar build failed, used row-based fallback)
+                let _count = self
+                    .db
+                    .populate_text_index(&index_name, &stmt.table, column_pos)?;
+                debug_log!(
+                    "Built text index in {:?}, indexed {} rows",
+                    start_time.elapsed(),
+                    _count
+                );
 
                 // 3️⃣ Register metadata
                 let metadata = crate::database::index_metadata::IndexMetadata::new(
@@ -18578,10 +20924,16 @@ impl QueryExecutor {
                 self.db.index_registry.register(metadata)?;
             }
             IndexType::Vector => {
-                // create_vector_index already scans existing data and builds the index
-                if let ColumnType::Tensor(dim) = column.col_type {
-                    self.db
-                        .create_vector_index(&index_name, dim, stmt.metric.as_deref())?;
+                // create_vector_index_on already scans existing data and builds the
+                // index. The source is passed explicitly: the index is not registered
+                // yet, so a custom name would not resolve to its table and column.
+                if let Some(dim) = column.col_type.vector_dim() {
+                    self.db.create_vector_index_on(
+                        &index_name,
+                        dim,
+                        stmt.metric.as_deref(),
+                        Some((&stmt.table, &stmt.column)),
+                    )?;
 
                     let mut metadata = crate::database::index_metadata::IndexMetadata::new(
                         index_name.clone(),
@@ -18590,6 +20942,7 @@ impl QueryExecutor {
                         crate::database::index_metadata::IndexType::Vector,
                     );
                     metadata.metric = stmt.metric.clone();
+                    metadata.rerank = stmt.rerank.unwrap_or(false);
                     self.db.index_registry.register(metadata)?;
                 } else {
                     unreachable!("Already validated column type");
@@ -18607,40 +20960,9 @@ impl QueryExecutor {
                 let column_pos = schema
                     .get_column_position(&stmt.column)
                     .ok_or_else(|| MoteDBError::ColumnNotFound(stmt.column.clone()))?;
-                let mut backfill_count = 0;
-
-                // Columnar fast path returns Ok(0) for ColSegmentStore-backed tables
-                // (not in legacy columnar_sstables); in that case fall back to row scan.
-                match self
-                    .db
-                    .build_ioctree_from_columnar(&index_name, &stmt.table, column_pos)
-                {
-                    Ok(count) if count > 0 => {
-                        backfill_count = count;
-                    }
-                    _ => {
-                        let iter = self.db.scan_table_rows_streaming(&stmt.table)?;
-                        for result in iter {
-                            let (row_id, row) = result?;
-                            if let Some(Value::Spatial(geometry)) = row.get(column_pos) {
-                                if geometry.is_3d() {
-                                    if let Err(e) =
-                                        self.db.insert_ioctree_point(row_id, &index_name, geometry)
-                                    {
-                                        debug_log!(
-                                            "⚠️ Failed to backfill ioctree index for row {}: {}",
-                                            row_id,
-                                            e
-                                        );
-                                    } else {
-                                        backfill_count += 1;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
+                let backfill_count =
+                    self.db
+                        .populate_ioctree_index(&index_name, &stmt.table, column_pos)?;
                 if backfill_count > 0 {
                     debug_log!(
                         "Backfilled {} rows into ioctree index '{}'",
@@ -18668,13 +20990,23 @@ impl QueryExecutor {
 
                 // 🔥 OPTIMIZATION FIX: Also register with standard "{table}.{column}" name
                 // This allows WHERE optimization to find the index
-                let standard_name = format!("{}.{}", stmt.table, stmt.column);
+                // (ARRAY element indexes use "{table}.{column}[]")
+                let standard_name = crate::database::indexes::column::column_index_key(
+                    &stmt.table,
+                    &stmt.column,
+                    &column.col_type,
+                );
                 if index_name != standard_name {
                     // Clone the index reference and register with standard name
-                    if let Some(index_ref) = self.db.column_indexes.get(&index_name) {
-                        self.db
-                            .column_indexes
-                            .insert(standard_name.clone(), index_ref.clone());
+                    // Clone out before inserting: holding the shard guard
+                    // across insert() deadlocks when both keys share a shard.
+                    let index_ref = self
+                        .db
+                        .column_indexes
+                        .get(&index_name)
+                        .map(|r| r.value().clone());
+                    if let Some(index_ref) = index_ref {
+                        self.db.column_indexes.insert(standard_name, index_ref);
                     }
                 }
 
@@ -18701,6 +21033,14 @@ impl QueryExecutor {
     fn execute_drop_table(&self, stmt: DropTableStmt) -> Result<QueryResult> {
         let table_name = &stmt.table;
 
+        // A drop that failed half-way left the table hidden: finish it
+        if self.db.table_registry.pending_drop(table_name).is_some() {
+            self.db.drop_table(table_name)?;
+            return Ok(QueryResult::Definition {
+                message: format!("Table '{}' dropped successfully", table_name),
+            });
+        }
+
         // Verify table exists (or skip if IF EXISTS)
         let schema = match self.db.get_table_schema(table_name) {
             Ok(s) => s,
@@ -18711,156 +21051,310 @@ impl QueryExecutor {
             }
             Err(e) => return Err(e),
         };
-
-        // 1. Drop column indexes for this table
-        let prefix = format!("{}.", table_name);
-        let index_names: Vec<String> = self
-            .db
-            .column_indexes
-            .iter()
-            .filter(|entry| entry.key().starts_with(&prefix))
-            .map(|entry| entry.key().clone())
-            .collect();
-        for idx_name in index_names {
-            self.db.column_indexes.remove(&idx_name);
+        if schema.continuous_aggregate.is_some() {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "'{}' is a materialized view; use DROP MATERIALIZED VIEW",
+                table_name
+            )));
         }
+        self.drop_table_storage(table_name)?;
 
-        // 2. Drop vector indexes for this table
-        let vector_idx_names: Vec<String> = self
-            .db
-            .vector_indexes
-            .iter()
-            .filter(|entry| {
-                entry.key().starts_with(&prefix)
-                    || entry.key().contains(&format!("_{}", table_name))
-            })
-            .map(|entry| entry.key().clone())
-            .collect();
-        for idx_name in vector_idx_names {
-            self.db.vector_indexes.remove(&idx_name);
-        }
+        Ok(QueryResult::Definition {
+            message: format!("Table '{}' dropped successfully", table_name),
+        })
+    }
 
-        // 3. Drop text indexes for this table
-        let text_idx_names: Vec<String> = self
-            .db
-            .text_indexes
-            .iter()
-            .filter(|entry| {
-                entry.key().starts_with(&prefix)
-                    || entry.key().contains(&format!("_{}", table_name))
-            })
-            .map(|entry| entry.key().clone())
-            .collect();
-        for idx_name in text_idx_names {
-            self.db.text_indexes.remove(&idx_name);
+    /// Drop a table's indexes, metadata and data
+    fn drop_table_storage(&self, table_name: &str) -> Result<()> {
+        let views = self.db.continuous_aggregate_views(table_name);
+        if !views.is_empty() {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot drop table '{}': materialized views depend on it ({})",
+                table_name,
+                views.join(", ")
+            )));
         }
 
-        // 4. Drop i-Octree indexes for this table
-        let ioctree_idx_names: Vec<String> = self
-            .db
-            .ioctree_indexes
-            .iter()
-            .filter(|entry| {
-                entry.key().starts_with(&prefix)
-                    || entry.key().contains(&format!("_{}", table_name))
-            })
-            .map(|entry| entry.key().clone())
-            .collect();
-        for idx_name in ioctree_idx_names {
-            self.db.ioctree_indexes.remove(&idx_name);
-        }
+        // Catalog entry, LSM rows, column segments, indexes and their files
+        self.db.drop_table(table_name)
+    }
 
-        // 5. Drop table metadata (schema, auto_increment, pk_lookup)
-        self.db.drop_table(table_name)?;
+    /// Execute DROP INDEX statement
+    fn execute_drop_index(&self, stmt: DropIndexStmt) -> Result<QueryResult> {
+        let index_name = &stmt.index_name;
+        self.db.drop_index(index_name)?;
 
-        // 6. Remove index registry entries
-        self.db.index_registry.remove_by_table(table_name);
+        Ok(QueryResult::Definition {
+            message: format!("Index '{}' dropped", index_name),
+        })
+    }
 
-        // 7. Delete data from LSM using range delete (best effort)
-        // Composite key = (table_id << 32) | row_id
-        // We scan the entire range for this table_id
-        let table_id = self.db.table_registry.get_table_id(table_name).unwrap_or(0);
-        let start_key = (table_id as u64) << 32;
-        let end_key = start_key | 0xFFFFFFFF;
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_micros() as u64;
+    /// Execute CREATE MATERIALIZED VIEW: a continuous aggregate over one
+    /// table, bucketed by `SAMPLE BY` or by GROUP BY on a TIME_BUCKET alias.
+    ///
+    /// Every group key must be selected, and the other columns must be
+    /// COUNT / SUM / AVG / MIN / MAX of a plain column. Unaliased aggregates
+    /// are named `<func>_<column>` (`count` for COUNT(*)).
+    fn execute_create_materialized_view(
+        &self,
+        stmt: &CreateMaterializedViewStmt,
+    ) -> Result<QueryResult> {
+        use crate::types::{AggregateOutput, ContinuousAggregate};
 
-        if let Err(e) = self
-            .db
-            .lsm_engine
-            .delete_range(start_key, end_key, timestamp)
-        {
-            debug_log!("[DROP TABLE] Warning: LSM range delete failed: {}", e);
+        let unsupported = |what: &str| {
+            MoteDBError::InvalidArgument(format!("Materialized views do not support {}", what))
+        };
+        let q = &stmt.query;
+        for (present, clause) in [
+            (q.where_clause.is_some(), "WHERE"),
+            (q.having.is_some(), "HAVING"),
+            (q.order_by.is_some(), "ORDER BY"),
+            (q.limit.is_some() || q.offset.is_some(), "LIMIT / OFFSET"),
+            (q.distinct, "DISTINCT"),
+            (q.latest_by.is_some(), "LATEST BY"),
+            (q.fill.is_some(), "FILL"),
+        ] {
+            if present {
+                return Err(unsupported(clause));
+            }
+        }
+        let query = if q.sample_by.is_some() {
+            self.desugar_sample_by(q)?
+        } else {
+            q.clone()
+        };
+        let (alias, interval, ts_col) = Self::time_bucket_group(&query).ok_or_else(|| {
+            MoteDBError::InvalidArgument(
+                "Materialized views need SAMPLE BY or GROUP BY on a TIME_BUCKET alias".to_string(),
+            )
+        })?;
+        let source = match &query.from {
+            Some(TableRef::Table { name, .. }) => name.clone(),
+            _ => return Err(unsupported("joins or subqueries in FROM")),
+        };
+        let schema = self.db.get_table_schema(&source)?;
+        let interval_micros = crate::sql::evaluator::parse_interval_to_micros(&interval)?;
+
+        let bare = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+        let column = |name: &str| {
+            let def = schema
+                .get_column(&bare(name))
+                .ok_or_else(|| MoteDBError::ColumnNotFound(format!("{}.{}", source, bare(name))))?;
+            if def.generated.as_ref().is_some_and(|g| !g.stored) {
+                return Err(unsupported("VIRTUAL generated columns"));
+            }
+            Ok(def)
+        };
+        let ts_def = column(&ts_col)?;
+        let ts_column = ts_def.name.clone();
+        if ts_def.col_type != ColumnType::Timestamp {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "TIME_BUCKET column '{}' must be a TIMESTAMP",
+                ts_col
+            )));
         }
+        let group_keys: Vec<String> = query
+            .group_by
+            .iter()
+            .flatten()
+            .filter(|g| **g != alias)
+            .map(|g| bare(g))
+            .collect();
 
-        // 8. Drop the ColSegmentStore (columnar source of truth) and its on-disk
-        // segment files. Without this, recreating a same-named table sees stale
-        // rows from the dropped one (test_create_drop_recreate returned 2 rows).
-        // Also clear the columnar_write_bufs entry and the synced columnar_sstables
-        // alias so reads don't fall back to the dropped data.
-        if let Some(entry) = self.db.col_segment_stores.get(table_name) {
-            // Best-effort: delete on-disk segment files + manifest.
-            let _ = entry.value().drop_all();
+        let mut outputs = Vec::with_capacity(query.columns.len());
+        let mut columns: Vec<crate::types::ColumnDef> = Vec::with_capacity(query.columns.len());
+        for col in &query.columns {
+            let (output, name, col_type) = match col {
+                SelectColumn::Expr(expr, Some(a))
+                    if *a == alias && Self::time_bucket_call(expr).is_some() =>
+                {
+                    (AggregateOutput::Bucket, a.clone(), ColumnType::Timestamp)
+                }
+                SelectColumn::Column(name) | SelectColumn::ColumnWithAlias(name, _) => {
+                    let def = column(name)?;
+                    if !group_keys.contains(&def.name) {
+                        return Err(MoteDBError::InvalidArgument(format!(
+                            "Column '{}' must appear in GROUP BY",
+                            name
+                        )));
+                    }
+                    let out_name = match col {
+                        SelectColumn::ColumnWithAlias(_, a) => a.clone(),
+                        _ => def.name.clone(),
+                    };
+                    (
+                        AggregateOutput::Key(def.name.clone()),
+                        out_name,
+                        def.col_type.clone(),
+                    )
+                }
+                SelectColumn::Expr(expr, alias) => {
+                    let expr_name = Self::expr_to_column_name(expr);
+                    let agg = self
+                        .try_parse_aggregate(expr, &schema)
+                        .filter(|agg| !agg.distinct)
+                        .ok_or_else(|| unsupported(&expr_name))?;
+                    let src = match agg.col_pos {
+                        Some(pos) => Some(column(&schema.columns[pos].name)?),
+                        None => None,
+                    };
+                    let name = alias.clone().unwrap_or_else(|| match src {
+                        Some(c) => format!("{}_{}", agg.func.to_lowercase(), c.name),
+                        None => "count".to_string(),
+                    });
+                    let numeric = src.is_some_and(|c| {
+                        matches!(c.col_type, ColumnType::Integer | ColumnType::Float)
+                    });
+                    match (agg.func.as_str(), src) {
+                        ("COUNT", c) => (
+                            AggregateOutput::Count(c.map(|c| c.name.clone())),
+                            name,
+                            ColumnType::Integer,
+                        ),
+                        ("SUM", Some(c)) if numeric => (
+                            AggregateOutput::Sum(c.name.clone()),
+                            name,
+                            c.col_type.clone(),
+                        ),
+                        ("AVG", Some(c)) if numeric => (
+                            AggregateOutput::Avg(c.name.clone()),
+                            name,
+                            ColumnType::Float,
+                        ),
+                        ("MIN", Some(c)) => (
+                            AggregateOutput::Min(c.name.clone()),
+                            name,
+                            c.col_type.clone(),
+                        ),
+                        ("MAX", Some(c)) => (
+                            AggregateOutput::Max(c.name.clone()),
+                            name,
+                            c.col_type.clone(),
+                        ),
+                        _ => return Err(unsupported(&expr_name)),
+                    }
+                }
+                SelectColumn::Star => return Err(unsupported("SELECT *")),
+            };
+            if columns.iter().any(|c| c.name == name) {
+                return Err(MoteDBError::InvalidArgument(format!(
+                    "Duplicate column name '{}' in materialized view",
+                    name
+                )));
+            }
+            outputs.push(output);
+            columns.push(crate::types::ColumnDef::new(name, col_type, columns.len()));
+        }
+        let selected_keys = outputs
+            .iter()
+            .filter(|o| matches!(o, AggregateOutput::Key(_)))
+            .count();
+        if selected_keys != group_keys.len() {
+            return Err(MoteDBError::InvalidArgument(
+                "Every GROUP BY column of a materialized view must be selected".to_string(),
+            ));
         }
-        self.db.col_segment_stores.remove(table_name);
-        self.db.columnar_write_bufs.remove(table_name);
-        self.db.columnar_sstables.remove(table_name);
-
-        let _ = schema; // used above for validation
 
+        let definition = ContinuousAggregate {
+            source,
+            ts_column,
+            interval_micros,
+            outputs,
+            query: stmt.query_sql.clone(),
+        };
+        self.db.create_continuous_aggregate(
+            TableSchema::new(stmt.name.clone(), columns).with_continuous_aggregate(definition),
+        )?;
         Ok(QueryResult::Definition {
-            message: format!("Table '{}' dropped successfully", table_name),
+            message: format!("Materialized view '{}' created", stmt.name),
         })
     }
 
-    /// Execute DROP INDEX statement
-    fn execute_drop_index(&self, stmt: DropIndexStmt) -> Result<QueryResult> {
-        use crate::database::index_metadata::IndexType;
-
-        // Look up index metadata to know which collection to remove from
-        let meta = self
-            .db
-            .index_registry
-            .get(&stmt.index_name)
-            .ok_or_else(|| MoteDBError::IndexNotFound(stmt.index_name.clone()))?;
-
-        let index_name = &stmt.index_name;
-
-        // Remove from the appropriate DashMap collection
-        match meta.index_type {
-            IndexType::Vector => {
-                self.db.vector_indexes.remove(index_name);
+    /// Execute DROP MATERIALIZED VIEW
+    fn execute_drop_materialized_view(&self, stmt: &DropTableStmt) -> Result<QueryResult> {
+        match self.db.get_table_schema(&stmt.table) {
+            Ok(schema) if schema.continuous_aggregate.is_some() => {}
+            Ok(_) => {
+                return Err(MoteDBError::InvalidArgument(format!(
+                    "'{}' is not a materialized view",
+                    stmt.table
+                )))
             }
-            IndexType::Text => {
-                self.db.text_indexes.remove(index_name);
+            Err(_) if stmt.if_exists => {
+                return Ok(QueryResult::Definition {
+                    message: format!(
+                        "Materialized view '{}' does not exist (IF EXISTS)",
+                        stmt.table
+                    ),
+                })
             }
-            IndexType::Column => {
-                self.db.column_indexes.remove(index_name);
-                // Also remove the "table.column" alias if it exists
-                let alias = format!("{}.{}", meta.table_name, meta.column_name);
-                if alias != *index_name {
-                    self.db.column_indexes.remove(&alias);
+            Err(e) => return Err(e),
+        }
+        self.drop_table_storage(&stmt.table)?;
+        Ok(QueryResult::Definition {
+            message: format!("Materialized view '{}' dropped", stmt.table),
+        })
+    }
+
+    /// Execute REFRESH MATERIALIZED VIEW: recompute it from its source
+    fn execute_refresh_materialized_view(&self, name: &str) -> Result<QueryResult> {
+        self.db.refresh_continuous_aggregate(name)?;
+        Ok(QueryResult::Definition {
+            message: format!("Materialized view '{}' refreshed", name),
+        })
+    }
+
+    /// Execute REINDEX: rebuild an index from table data and swap it in
+    fn execute_reindex(&self, name: &str) -> Result<QueryResult> {
+        self.db.rebuild_index(name)?;
+        Ok(QueryResult::Definition {
+            message: format!("Index '{}' rebuilt", name),
+        })
+    }
+
+    /// COPY table FROM | TO a CSV file (see `MoteDB::import_csv`)
+    fn execute_copy(&self, stmt: &CopyStmt) -> Result<QueryResult> {
+        let affected_rows = match stmt.direction {
+            CopyDirection::From => {
+                // Rows go straight to storage in batches, outside any
+                // transaction's write set
+                if self.current_txn_id().is_some() {
+                    return Err(MoteDBError::InvalidArgument(
+                        "COPY FROM cannot run inside a transaction".into(),
+                    ));
                 }
+                let schema = self.db.get_table_schema(&stmt.table)?;
+                Self::reject_materialized_view_write(&schema)?;
+                let path = self.db.resolve_copy_path(&stmt.path, false)?;
+                self.db.import_csv(&stmt.table, path, &stmt.options)?
             }
-            IndexType::Octree => {
-                self.db.ioctree_indexes.remove(index_name);
+            CopyDirection::To => {
+                let path = self.db.resolve_copy_path(&stmt.path, true)?;
+                self.db.export_csv(&stmt.table, path, &stmt.options)?
             }
-        }
-
-        // Remove from index registry (also persists)
-        self.db.index_registry.remove(index_name)?;
+        };
+        Ok(QueryResult::Modification { affected_rows })
+    }
 
-        Ok(QueryResult::Definition {
-            message: format!("Index '{}' dropped", index_name),
-        })
+    /// Materialized views are only written by their own maintenance
+    fn reject_materialized_view_write(schema: &TableSchema) -> Result<()> {
+        if schema.continuous_aggregate.is_some() {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot modify materialized view '{}'",
+                schema.name
+            )));
+        }
+        Ok(())
     }
 
     /// 🆕 Execute ALTER TABLE statement
     fn execute_alter_table(&self, stmt: AlterTableStmt) -> Result<QueryResult> {
         use super::ast::AlterTableAction;
 
+        if let Ok(schema) = self.db.get_table_schema(&stmt.table) {
+            Self::reject_materialized_view_write(&schema)?;
+        }
+
         match stmt.action {
             AlterTableAction::SetAutoIncrement(new_value) => {
                 // Verify table exists and has AUTO_INCREMENT primary key
@@ -18882,16 +21376,7 @@ impl QueryExecutor {
             }
             AlterTableAction::AddColumn { name, data_type, default_value } => {
                 // Convert DataType to ColumnType (same mapping as CREATE TABLE).
-                let col_type = match data_type {
-                    super::ast::DataType::Integer => ColumnType::Integer,
-                    super::ast::DataType::BigInt => ColumnType::Integer,
-                    super::ast::DataType::Float => ColumnType::Float,
-                    super::ast::DataType::Text => ColumnType::Text,
-                    super::ast::DataType::Boolean => ColumnType::Boolean,
-                    super::ast::DataType::Timestamp => ColumnType::Timestamp,
-                    super::ast::DataType::Vector(dim) => ColumnType::Tensor(dim.unwrap_or(128)),
-                    super::ast::DataType::Geometry => ColumnType::Spatial,
-                };
+                let col_type = Self::column_type_for(&data_type);
                 // Verify table exists.
                 let _schema = self.db.get_table_schema(&stmt.table)?;
                 // Mutate schema in registry. col_type is moved here, so clone
@@ -18901,6 +21386,7 @@ impl QueryExecutor {
                     &name,
                     col_type.clone(),
                     default_value.as_ref(),
+                    None,
                 )?;
                 // 🔑 Extend the store's col_types so post-ALTER INSERTs preserve
                 // the new column's value. Without this, the in-memory write_buf
@@ -18933,12 +21419,335 @@ impl QueryExecutor {
                     message: format!("Added column '{}' to table '{}'", name, stmt.table),
                 })
             }
+            AlterTableAction::AddGeneratedColumn {
+                name,
+                data_type,
+                expr,
+                expr_sql,
+                stored,
+            } => {
+                let schema = self.db.get_table_schema(&stmt.table)?;
+                if schema.table_type == crate::types::TableType::TimeSeries {
+                    return Err(MoteDBError::InvalidArgument(format!(
+                        "Generated columns are not supported on TIMESERIES table '{}'",
+                        stmt.table
+                    )));
+                }
+                self.check_generated_expr(&expr, &schema)?;
+                let col_type = match &data_type {
+                    Some(dt) => Self::column_type_for(dt),
+                    None => Self::generated_column_type(&expr, &schema).ok_or_else(|| {
+                        MoteDBError::InvalidArgument(format!(
+                            "Cannot infer the type of generated column '{}'; declare it, e.g. ADD COLUMN {} FLOAT AS (...)",
+                            name, name
+                        ))
+                    })?,
+                };
+                let generated = crate::types::GeneratedColumn {
+                    expr: expr_sql,
+                    stored,
+                };
+                self.db.table_registry.add_column(
+                    &stmt.table,
+                    &name,
+                    col_type.clone(),
+                    None,
+                    Some(generated),
+                )?;
+                // Same store / read-cache refresh as a plain ADD COLUMN
+                if let Some(store) = self.db.col_segment_stores.get(&stmt.table) {
+                    store.add_column_type(col_type)?;
+                }
+                self.db.columnar_sstables.remove(&stmt.table);
+
+                // Existing rows get their STORED value by being rewritten
+                // unchanged: the write path fills in generated columns.
+                if stored {
+                    self.execute_update(UpdateStmt {
+                        table: stmt.table.clone(),
+                        assignments: Vec::new(),
+                        where_clause: None,
+                    })?;
+                }
+
+                Ok(QueryResult::Definition {
+                    message: format!(
+                        "Added {} generated column '{}' to table '{}'",
+                        if stored { "STORED" } else { "VIRTUAL" },
+                        name,
+                        stmt.table
+                    ),
+                })
+            }
+            AlterTableAction::DropColumn(name) => self.alter_drop_column(&stmt.table, &name),
+            AlterTableAction::RenameColumn { from, to } => {
+                self.alter_rename_column(&stmt.table, &from, &to)
+            }
+        }
+    }
+
+    /// Reasons a column can't be dropped or renamed: it anchors the table
+    /// (primary key, TIMESERIES column), a generated column or a
+    /// materialized view reads it, or a transaction may hold rows in the
+    /// current layout.
+    fn check_column_alterable(
+        &self,
+        schema: &TableSchema,
+        column: &str,
+        action: &str,
+    ) -> Result<usize> {
+        let pos = schema.get_column_position(column).ok_or_else(|| {
+            StorageError::ColumnNotFound(format!("'{}' in table '{}'", column, schema.name))
+        })?;
+        let refuse = |why: String| {
+            Err(MoteDBError::InvalidArgument(format!(
+                "Cannot {} column '{}' of table '{}': {}",
+                action, column, schema.name, why
+            )))
+        };
+        for col in &schema.columns {
+            let Some(generated) = &col.generated else {
+                continue;
+            };
+            let expr = crate::database::generated::parse_generated_expr(&generated.expr)?;
+            if col.position != pos && Self::expr_referenced_columns(&expr, schema).contains(&pos) {
+                return refuse(format!("generated column '{}' depends on it", col.name));
+            }
+        }
+        let views = self.db.continuous_aggregate_views(&schema.name);
+        if !views.is_empty() {
+            return refuse(format!(
+                "materialized views depend on it ({})",
+                views.join(", ")
+            ));
+        }
+        if self.db.transaction_stats().active_transactions > 0 {
+            return refuse("transactions are in progress".to_string());
+        }
+        Ok(pos)
+    }
+
+    /// ALTER TABLE DROP COLUMN: drop the column's indexes, remove it from
+    /// the schema and rewrite the stored rows without it.
+    fn alter_drop_column(&self, table: &str, name: &str) -> Result<QueryResult> {
+        let schema = self.db.get_table_schema(table)?;
+        let pos = self.check_column_alterable(&schema, name, "drop")?;
+        if schema.primary_key() == Some(name) {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot drop primary key column '{}' of table '{}'",
+                name, table
+            )));
+        }
+        if schema.timeseries_column.as_deref() == Some(name) {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot drop the TIMESERIES column '{}' of table '{}'",
+                name, table
+            )));
+        }
+        if schema.columns.len() == 1 {
+            return Err(MoteDBError::InvalidArgument(format!(
+                "Cannot drop '{}', the only column of table '{}'",
+                name, table
+            )));
+        }
+
+        // Persist buffered rows and reset the WAL first: recovery would
+        // otherwise replay rows in the old layout against the new schema.
+        self.db.checkpoint()?;
+
+        for index in self.db.index_registry.list_table_indexes(table) {
+            if index.column_name == name {
+                self.execute_drop_index(DropIndexStmt {
+                    index_name: index.name,
+                })?;
+            }
+        }
+        let col_type = &schema.columns[pos].col_type;
+        self.db
+            .column_indexes
+            .remove(&crate::database::indexes::column::column_index_key(
+                table, name, col_type,
+            ));
+
+        self.db.table_registry.drop_column(table, name)?;
+        if let Some(store) = self.db.col_segment_stores.get(table) {
+            store.drop_column_type(pos)?;
+        }
+        // Cached rows and the legacy columnar snapshot hold the old layout.
+        self.db.columnar_sstables.remove(table);
+        self.db.row_cache.invalidate_table(table);
+
+        Ok(QueryResult::Definition {
+            message: format!("Dropped column '{}' from table '{}'", name, table),
+        })
+    }
+
+    /// ALTER TABLE RENAME COLUMN: rename the column in the schema and move
+    /// its indexes to the new name. Stored rows are positional and unchanged.
+    fn alter_rename_column(&self, table: &str, from: &str, to: &str) -> Result<QueryResult> {
+        use crate::database::index_metadata::{IndexMetadata, IndexType};
+        use crate::database::indexes::column::column_index_key;
+
+        let schema = self.db.get_table_schema(table)?;
+        let pos = self.check_column_alterable(&schema, from, "rename")?;
+
+        self.db.table_registry.rename_column(table, from, to)?;
+        self.db.index_registry.rename_column(table, from, to)?;
+
+        // Column indexes are looked up as "table.column"; move that key to
+        // the new name.
+        let col_type = &schema.columns[pos].col_type;
+        let old_key = column_index_key(table, from, col_type);
+        let new_key = column_index_key(table, to, col_type);
+        let named = self
+            .db
+            .index_registry
+            .find_by_column(table, to, IndexType::Column)
+            .and_then(|index_name| self.db.column_indexes.get(&index_name))
+            .map(|r| r.value().clone());
+        let keyed = self
+            .db
+            .column_indexes
+            .get(&old_key)
+            .map(|r| r.value().clone());
+        match keyed {
+            // Alias of a named index (see CREATE INDEX): the alias moves.
+            Some(index) if named.as_ref().is_some_and(|n| Arc::ptr_eq(n, &index)) => {
+                if self.db.index_registry.get(&old_key).is_none() {
+                    self.db.column_indexes.remove(&old_key);
+                }
+                self.db.column_indexes.insert(new_key, index);
+            }
+            // Index stored as "table.column" itself (`create_column_index`,
+            // natural primary keys). Its file keeps the old name, so register
+            // it under that name for a reopen to resolve the new column.
+            Some(index) => {
+                if self.db.index_registry.get(&old_key).is_none() {
+                    self.db.index_registry.register(IndexMetadata::new(
+                        old_key,
+                        table.to_string(),
+                        to.to_string(),
+                        IndexType::Column,
+                    ))?;
+                }
+                self.db.column_indexes.insert(new_key, index);
+            }
+            None => {
+                if let Some(index) = named {
+                    self.db.column_indexes.insert(new_key, index);
+                }
+            }
+        }
+
+        Ok(QueryResult::Definition {
+            message: format!("Renamed column '{}' to '{}' in table '{}'", from, to, table),
+        })
+    }
+
+    /// Reject generated column expressions the write path can't evaluate:
+    /// unknown columns, bind parameters, aggregates and subqueries.
+    fn check_generated_expr(&self, expr: &Expr, schema: &TableSchema) -> Result<()> {
+        let unsupported = |what: &str| {
+            Err(MoteDBError::InvalidArgument(format!(
+                "{} not allowed in a generated column expression",
+                what
+            )))
+        };
+        match expr {
+            Expr::Column(name) => match schema.get_column(name) {
+                None => Err(StorageError::ColumnNotFound(format!(
+                    "'{}' in table '{}'",
+                    name, schema.name
+                ))),
+                Some(c) if c.generated.as_ref().is_some_and(|g| !g.stored) => {
+                    unsupported("VIRTUAL column references are")
+                }
+                Some(_) => Ok(()),
+            },
+            Expr::Literal(_) => Ok(()),
+            Expr::Parameter(_) => unsupported("Bind parameters are"),
+            Expr::BinaryOp { left, right, .. } => {
+                self.check_generated_expr(left, schema)?;
+                self.check_generated_expr(right, schema)
+            }
+            Expr::UnaryOp { expr, .. } | Expr::IsNull { expr, .. } => {
+                self.check_generated_expr(expr, schema)
+            }
+            Expr::FunctionCall { args, .. } => {
+                if self.is_aggregate_expr(expr) {
+                    return unsupported("Aggregate functions are");
+                }
+                args.iter()
+                    .try_for_each(|a| self.check_generated_expr(a, schema))
+            }
+            Expr::In { expr, list, .. } => {
+                self.check_generated_expr(expr, schema)?;
+                list.iter()
+                    .try_for_each(|e| self.check_generated_expr(e, schema))
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.check_generated_expr(expr, schema)?;
+                self.check_generated_expr(low, schema)?;
+                self.check_generated_expr(high, schema)
+            }
+            Expr::Like { expr, pattern, .. } => {
+                self.check_generated_expr(expr, schema)?;
+                self.check_generated_expr(pattern, schema)
+            }
+            Expr::Subquery(_) => unsupported("Subqueries are"),
+            _ => unsupported("This expression is"),
+        }
+    }
+
+    /// Column type of a generated column declared without one, when it
+    /// follows from the expression alone
+    fn generated_column_type(expr: &Expr, schema: &TableSchema) -> Option<ColumnType> {
+        use super::ast::BinaryOperator as Op;
+        match expr {
+            Expr::Column(name) => schema.get_column(name).map(|c| c.col_type.clone()),
+            Expr::Literal(Value::Integer(_)) => Some(ColumnType::Integer),
+            Expr::Literal(Value::Float(_)) => Some(ColumnType::Float),
+            Expr::Literal(Value::Bool(_)) => Some(ColumnType::Boolean),
+            Expr::Literal(Value::Text(_)) => Some(ColumnType::Text),
+            Expr::BinaryOp { left, op, right } => match op {
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod => {
+                    match (
+                        Self::generated_column_type(left, schema)?,
+                        Self::generated_column_type(right, schema)?,
+                    ) {
+                        (ColumnType::Integer, ColumnType::Integer) => Some(ColumnType::Integer),
+                        (
+                            ColumnType::Integer | ColumnType::Float,
+                            ColumnType::Integer | ColumnType::Float,
+                        ) => Some(ColumnType::Float),
+                        _ => None,
+                    }
+                }
+                Op::Eq | Op::Ne | Op::Lt | Op::Gt | Op::Le | Op::Ge | Op::And | Op::Or => {
+                    Some(ColumnType::Boolean)
+                }
+                _ => None,
+            },
+            Expr::UnaryOp {
+                op: super::ast::UnaryOperator::Not,
+                ..
+            } => Some(ColumnType::Boolean),
+            Expr::UnaryOp { expr, .. } => Self::generated_column_type(expr, schema),
+            Expr::IsNull { .. } | Expr::In { .. } | Expr::Between { .. } | Expr::Like { .. } => {
+                Some(ColumnType::Boolean)
+            }
+            _ => None,
         }
     }
 
     /// Execute SHOW TABLES
     fn execute_show_tables(&self) -> Result<QueryResult> {
-        let tables = self.db.list_tables()?;
+        let mut tables = self.db.list_tables()?;
+        if let Some(hook) = self.access_hook.lock().clone() {
+            tables.retain(|t| hook(t, TableAccess::Read));
+        }
 
         let columns = vec!["Tables".to_string()];
         let rows = tables
@@ -18949,6 +21758,139 @@ impl QueryExecutor {
         Ok(QueryResult::Select { columns, rows })
     }
 
+    /// Execute SHOW TRANSACTIONS: one row per open transaction, oldest first
+    fn execute_show_transactions(&self) -> Result<QueryResult> {
+        let columns = [
+            "txn_id",
+            "isolation",
+            "start_ts",
+            "age_ms",
+            "pending_writes",
+            "undo_ops",
+            "savepoints",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+
+        let rows = self
+            .db
+            .active_transactions()
+            .into_iter()
+            .map(|txn| {
+                vec![
+                    Value::Integer(txn.txn_id as i64),
+                    Value::text(format!("{:?}", txn.isolation_level)),
+                    Value::Integer(txn.start_ts as i64),
+                    Value::Integer(txn.age.as_millis() as i64),
+                    Value::Integer(txn.pending_writes as i64),
+                    Value::Integer(txn.undo_ops as i64),
+                    Value::Integer(txn.savepoints as i64),
+                ]
+            })
+            .collect();
+
+        Ok(QueryResult::Select { columns, rows })
+    }
+
+    /// Execute SHOW TRANSACTION STATS: a single row of MVCC counters
+    fn execute_show_transaction_stats(&self) -> Result<QueryResult> {
+        let stats = self.db.transaction_stats();
+        let columns = [
+            "active_transactions",
+            "total_committed",
+            "total_versions",
+            "rows_with_versions",
+            "avg_versions_per_row",
+            "current_ts",
+            "oldest_snapshot_ts",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+
+        let rows = vec![vec![
+            Value::Integer(stats.active_transactions as i64),
+            Value::Integer(stats.total_committed as i64),
+            Value::Integer(stats.total_versions as i64),
+            Value::Integer(stats.total_rows_with_versions as i64),
+            Value::Float(stats.avg_versions_per_row),
+            Value::Integer(stats.current_ts as i64),
+            stats
+                .oldest_snapshot_ts
+                .map_or(Value::Null, |ts| Value::Integer(ts as i64)),
+        ]];
+
+        Ok(QueryResult::Select { columns, rows })
+    }
+
+    /// Execute SET [SESSION] name = value (None = DEFAULT)
+    fn execute_set_variable(&self, name: &str, value: Option<String>) -> Result<QueryResult> {
+        let mut settings = self.settings.lock();
+        match value {
+            Some(value) => settings.set(name, &value)?,
+            None => settings.reset(name)?,
+        }
+        let current = settings.get(name).unwrap_or_default();
+        Ok(QueryResult::Definition {
+            message: format!("{} = {}", name.to_ascii_lowercase(), current),
+        })
+    }
+
+    /// Execute SHOW name: one row holding the setting's current value
+    fn execute_show_variable(&self, name: &str) -> Result<QueryResult> {
+        let value = self.settings.lock().get(name).ok_or_else(|| {
+            StorageError::InvalidArgument(format!("unknown session setting '{}'", name))
+        })?;
+        Ok(QueryResult::Select {
+            columns: vec![name.to_ascii_lowercase()],
+            rows: vec![vec![Value::Text(value.into())]],
+        })
+    }
+
+    /// Execute EXPLAIN SELECT: the optimizer's access path and estimates,
+    /// then the operator tree when the physical planner takes the query
+    fn execute_explain(&self, stmt: &Statement) -> Result<QueryResult> {
+        let Statement::Select { stmt: s, ctes } = stmt else {
+            return Err(StorageError::InvalidArgument(
+                "EXPLAIN supports SELECT statements only".to_string(),
+            ));
+        };
+        let s = self.apply_ctes_for_select(s.clone(), ctes)?;
+        let s = self.resolve_select_list(s);
+        let s = self.inline_virtual_columns(s)?;
+
+        let plan = self.optimizer.optimize_select(&s, &[])?;
+        let mut lines = vec![
+            format!("Access: {}", plan.scan_method.describe()),
+            format!(
+                "Estimated rows: {}, cost: {:.1}",
+                plan.estimated_rows, plan.estimated_cost
+            ),
+        ];
+        if !plan.post_filters.is_empty() {
+            lines.push(format!("Post-filters: {}", plan.post_filters.len()));
+        }
+        let planner = super::physical::PhysicalPlanner::new(
+            &self.db,
+            &self.optimizer,
+            self,
+            self.vector_search_options(),
+        );
+        if let Some(physical) = planner.plan_select(&s)? {
+            lines.push("Operators:".to_string());
+            lines.extend(physical.explain().lines().map(|line| format!("  {}", line)));
+        }
+
+        Ok(QueryResult::Select {
+            columns: vec!["plan".to_string()],
+            rows: lines
+                .into_iter()
+                .map(|line| vec![Value::text(line)])
+                .collect(),
+        })
+    }
+
     /// Execute DESCRIBE TABLE
     fn execute_describe_table(&self, table_name: String) -> Result<QueryResult> {
         let schema = self.db.get_table_schema(&table_name)?;
@@ -19005,6 +21947,11 @@ impl QueryExecutor {
     fn execute_rollback_transaction(&self) -> Result<QueryResult> {
         let _txn_id_opt = self.current_txn_id();
         if let Some(txn_id) = _txn_id_opt {
+            // 🔑 Replay undo log BEFORE clearing the transaction context.
+            // execute_update/execute_delete recorded old values for rows
+            // they modified directly in storage. We replay those here to
+            // restore the pre-transaction state.
+            self.replay_undo_log(txn_id);
             self.db.rollback_transaction(txn_id)?;
             self.clear_txn_context();
             Ok(QueryResult::Definition {
@@ -19171,9 +22118,12 @@ impl QueryExecutor {
             Expr::BinaryOp { left, op, right } => {
                 // Only optimize simple equality: col = value
                 if *op == BinaryOperator::Eq {
-                    // Pattern 1: Column = Literal
+                    // Pattern 1: Column = Literal (`= NULL` is never true: no lookup)
                     if let (Expr::Column(col), Expr::Literal(val)) = (left.as_ref(), right.as_ref())
                     {
+                        if matches!(val, Value::Null) {
+                            return None;
+                        }
                         // 注意: 列名可能没有表前缀 (例如 "id"),但 SqlRow 中的键有前缀 ("users.id")
                         // 我们返回不带前缀的列名,在过滤时需要匹配任何表前缀
                         return Some((col.clone(), val.clone()));
@@ -19181,6 +22131,9 @@ impl QueryExecutor {
                     // Pattern 2: Literal = Column (reversed)
                     if let (Expr::Literal(val), Expr::Column(col)) = (left.as_ref(), right.as_ref())
                     {
+                        if matches!(val, Value::Null) {
+                            return None;
+                        }
                         return Some((col.clone(), val.clone()));
                     }
                 }
@@ -19197,6 +22150,14 @@ impl QueryExecutor {
         use crate::sql::ast::{BinaryOperator, Expr};
 
         match expr {
+            // A comparison with NULL is never true: no range to scan
+            Expr::BinaryOp { left, right, .. }
+                if [left, right]
+                    .iter()
+                    .any(|e| matches!(e.as_ref(), Expr::Literal(Value::Null))) =>
+            {
+                None
+            }
             Expr::BinaryOp { left, op, right } => {
                 // Check for <, >, <=, >=
                 match op {
@@ -19265,22 +22226,216 @@ impl QueryExecutor {
                     Expr::Literal(Value::Vector(vec)) => vec.clone(),
                     _ => return None,
                 };
-
-                // Extract k (reject non-positive values to prevent OOM)
-                let k = match &args[2] {
-                    Expr::Literal(Value::Integer(k)) => {
-                        if *k <= 0 {
-                            return None;
-                        }
-                        (*k).min(10000) as usize
+
+                // Extract k (reject non-positive values to prevent OOM)
+                let k = match &args[2] {
+                    Expr::Literal(Value::Integer(k)) => {
+                        if *k <= 0 {
+                            return None;
+                        }
+                        (*k).min(10000) as usize
+                    }
+                    _ => return None,
+                };
+
+                Some((table_name, column, query_vector.to_vec(), k))
+            }
+            _ => None,
+        }
+    }
+
+    /// 🚀 FAST PATH 0-mm: Mixed-modal conjunction — cheapest index first
+    ///
+    /// For `WHERE a AND b AND ...` with at least one MATCH / KNN_SEARCH /
+    /// ST_*_3D conjunct, asks the optimizer which conjunct's index is cheapest.
+    /// When that is a text, vector or octree index, only its candidate rows are
+    /// fetched and the remaining conjuncts are evaluated on them. Returns
+    /// `None` when a B-tree index or a full scan wins.
+    fn try_multimodal_driver(
+        &self,
+        stmt: &SelectStmt,
+        where_clause: &Expr,
+        table_name: &str,
+    ) -> Result<Option<QueryResult>> {
+        use super::optimizer::{QueryOptimizer, ScanMethod};
+        use crate::database::index_metadata::IndexType;
+
+        let mut conjuncts = QueryOptimizer::conjuncts(where_clause);
+        if conjuncts.len() < 2 || !conjuncts.iter().any(Self::expr_needs_multimodal_index) {
+            return Ok(None);
+        }
+        let params = self.evaluator.get_params();
+        let Some((driver, plan)) = self
+            .optimizer
+            .choose_driving_predicate(table_name, &conjuncts, &params)?
+        else {
+            return Ok(None);
+        };
+        let driver = conjuncts.remove(driver);
+        let index = |column: &str, index_type| {
+            self.db
+                .index_registry
+                .find_by_column(table_name, column, index_type)
+        };
+
+        // (row_id, BM25 score) for a MATCH driver, (row_id, None) otherwise
+        let candidates: Vec<(RowId, Option<f64>)> = match (&plan.scan_method, &driver) {
+            (
+                ScanMethod::TextSearch { .. },
+                Expr::Match {
+                    column,
+                    query,
+                    phrase,
+                },
+            ) => {
+                let Some(index_name) = index(column, IndexType::Text) else {
+                    return Ok(None);
+                };
+                let found = if *phrase {
+                    self.db
+                        .text_search_phrase(&index_name, query)
+                        .map(|ids| ids.into_iter().map(|id| (id, Some(1.0))).collect())
+                } else {
+                    // Same candidate cap as per-row MATCH evaluation
+                    self.db
+                        .text_search_ranked(&index_name, query, self.text_search_limit())
+                        .map(|hits| {
+                            hits.into_iter()
+                                .map(|(id, score)| (id, Some(score as f64)))
+                                .collect()
+                        })
+                };
+                match found {
+                    Ok(found) => found,
+                    Err(_) => return Ok(None),
+                }
+            }
+            (
+                ScanMethod::VectorSearch { .. },
+                Expr::KnnSearch {
+                    column,
+                    query_vector,
+                    k,
+                },
+            ) => {
+                let Some(index_name) = index(column, IndexType::Vector) else {
+                    return Ok(None);
+                };
+                match self.db.vector_search_with_options(
+                    &index_name,
+                    query_vector.as_slice(),
+                    *k,
+                    self.vector_search_options(),
+                ) {
+                    Ok(hits) => hits.into_iter().map(|(id, _)| (id, None)).collect(),
+                    Err(_) => return Ok(None),
+                }
+            }
+            (ScanMethod::SpatialSearch { column, .. }, _) => {
+                let Some(index_name) = index(column, IndexType::Octree) else {
+                    return Ok(None);
+                };
+                let found = match &driver {
+                    Expr::StWithin3D {
+                        min_x,
+                        min_y,
+                        min_z,
+                        max_x,
+                        max_y,
+                        max_z,
+                        ..
+                    } => {
+                        let bbox = crate::types::BoundingBox3D::new(
+                            *min_x, *min_y, *min_z, *max_x, *max_y, *max_z,
+                        );
+                        self.db.ioctree_range_query(&index_name, &bbox)
                     }
-                    _ => return None,
+                    Expr::StKnn3D { x, y, z, k, .. } => {
+                        let point = crate::types::Point3D::new(*x, *y, *z);
+                        self.db
+                            .ioctree_knn_query(&index_name, &point, *k)
+                            .map(|hits| hits.into_iter().map(|(id, _)| id).collect())
+                    }
+                    Expr::StRadius3D {
+                        x, y, z, radius, ..
+                    } => {
+                        let center = crate::types::Point3D::new(*x, *y, *z);
+                        self.db
+                            .ioctree_radius_search(&index_name, &center, *radius)
+                            .map(|hits| hits.into_iter().map(|(id, _)| id).collect())
+                    }
+                    _ => return Ok(None),
                 };
+                match found {
+                    Ok(ids) => ids.into_iter().map(|id| (id, None)).collect(),
+                    Err(_) => return Ok(None),
+                }
+            }
+            // B-tree index or a nested predicate: the general path handles it
+            _ => return Ok(None),
+        };
 
-                Some((table_name, column, query_vector.to_vec(), k))
+        debug_log!(
+            "[Executor] mixed-modal WHERE driven by {:?}: {} candidates (estimated {})",
+            plan.scan_method,
+            candidates.len(),
+            plan.estimated_rows
+        );
+
+        let schema = self.db.get_table_schema(table_name)?;
+        let row_ids: Vec<RowId> = candidates.iter().map(|(id, _)| *id).collect();
+        let mut sql_rows = Vec::with_capacity(row_ids.len());
+        for (row_id, row_opt) in self.db.get_table_rows_batch(table_name, &row_ids)? {
+            if let Some(row) = row_opt {
+                sql_rows.push((row_id, row_to_sql_row(&row, &schema)?));
+            }
+        }
+        prefix_rows(&mut sql_rows, table_name, table_name);
+
+        // MATCH scores for projection / ORDER BY without another index probe
+        if let Expr::Match { column, .. } = &driver {
+            let score_key = format!("__text_score_{}__", column);
+            let scores: std::collections::HashMap<RowId, f64> = candidates
+                .into_iter()
+                .filter_map(|(id, score)| score.map(|s| (id, s)))
+                .collect();
+            for (row_id, row) in &mut sql_rows {
+                if let Some(score) = scores.get(row_id) {
+                    row.insert(score_key.clone(), Value::Float(*score));
+                }
             }
-            _ => None,
         }
+
+        let residual = conjuncts
+            .iter()
+            .map(|conjunct| self.materialize_subqueries(conjunct))
+            .collect::<Result<Vec<_>>>()?;
+        let filtered_rows: Vec<(u64, SqlRow)> = sql_rows
+            .into_iter()
+            .filter(|(_, row)| {
+                residual.iter().all(|predicate| {
+                    self.eval_with_materialized(predicate, row)
+                        .and_then(|val| self.to_bool(&val))
+                        .unwrap_or(false)
+                })
+            })
+            .collect();
+
+        let prefixed_schema = prefix_schema(&schema, table_name);
+        self.finish_select(stmt, filtered_rows, &prefixed_schema, None)
+            .map(Some)
+    }
+
+    /// Predicates answered by a text, vector or octree index
+    fn expr_needs_multimodal_index(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Match { .. }
+                | Expr::KnnSearch { .. }
+                | Expr::StWithin3D { .. }
+                | Expr::StKnn3D { .. }
+                | Expr::StRadius3D { .. }
+        )
     }
 
     /// 🚀 FAST PATH 0a: Text search (MATCH AGAINST) — single index lookup
@@ -19293,38 +22448,14 @@ impl QueryExecutor {
         where_clause: &Expr,
         table_name: &str,
     ) -> Result<Option<QueryResult>> {
-        // Extract MATCH expression from WHERE clause
+        // Extract MATCH expression from WHERE clause. MATCH AND-ed with other
+        // predicates goes through try_multimodal_driver, which filters the rest.
         let (column, query, phrase) = match where_clause {
             Expr::Match {
                 column,
                 query,
                 phrase,
             } => (column.clone(), query.clone(), *phrase),
-            // Handle AND: MATCH(...) AND other_conditions — only if MATCH is the dominant filter
-            Expr::BinaryOp {
-                left,
-                op: BinaryOperator::And,
-                right,
-            } => {
-                // Try both sides for a MATCH expression
-                if let Expr::Match {
-                    column,
-                    query,
-                    phrase,
-                } = left.as_ref()
-                {
-                    (column.clone(), query.clone(), *phrase)
-                } else if let Expr::Match {
-                    column,
-                    query,
-                    phrase,
-                } = right.as_ref()
-                {
-                    (column.clone(), query.clone(), *phrase)
-                } else {
-                    return Ok(None);
-                }
-            }
             _ => return Ok(None),
         };
 
@@ -19342,8 +22473,8 @@ impl QueryExecutor {
             return Ok(None);
         }
 
-        // Determine limit (use LIMIT from query, or default to top 1000 for scoring)
-        let limit = stmt.limit.unwrap_or(1000);
+        // Determine limit (use LIMIT from query, or the session's text_search_limit)
+        let limit = stmt.limit.unwrap_or_else(|| self.text_search_limit());
 
         // Phrase search or ranked search depending on query type
         // 🚀 Carry (row_id, score) through — don't discard BM25 scores and
@@ -19828,10 +22959,15 @@ impl QueryExecutor {
         );
 
         // Single index lookup → sorted (row_id, distance) pairs.
-        let results = match self.db.vector_search(&index_name, query_vector, k) {
-            Ok(r) => r,
-            Err(_) => return Ok(None),
-        };
+        let options = self.vector_search_options();
+        let results =
+            match self
+                .db
+                .vector_search_with_options(&index_name, query_vector, k, options)
+            {
+                Ok(r) => r,
+                Err(_) => return Ok(None),
+            };
 
         if results.is_empty() {
             let schema = self.db.get_table_schema(table_name)?;
@@ -20083,10 +23219,17 @@ impl QueryExecutor {
     fn compare_values(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
         match (left, right) {
             (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
-            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
             (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
-            (Value::Integer(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
-            (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Float(_), Value::Float(_) | Value::Integer(_))
+            | (Value::Integer(_), Value::Float(_))
+            | (Value::Decimal(_), _)
+            | (_, Value::Decimal(_))
+            | (Value::Uuid(_), _)
+            | (_, Value::Uuid(_))
+            | (Value::Date(_), _)
+            | (_, Value::Date(_))
+            | (Value::Time(_), _)
+            | (_, Value::Time(_)) => left.partial_cmp(right),
             _ => None,
         }
     }
@@ -20210,13 +23353,14 @@ impl QueryExecutor {
             }
 
             // 🚀 Direct get: ColSegmentStore first (new path), then LSM (legacy).
+            let epoch = self.db.row_cache.fill_epoch();
             let composite_key = self.db.make_composite_key(table_name, row_id);
             if self.db.has_col_segment_store(table_name) {
                 if let Some(store) = self.db.col_segment_stores.get(table_name) {
                     if let Some(row) = store.get(composite_key) {
                         self.db
                             .row_cache
-                            .put(table_name.to_string(), row_id, row.clone());
+                            .fill(table_name, row_id, Arc::new(row.clone()), epoch);
                         let sql_row = row_to_sql_row(&row, &schema)?;
                         let mut prefixed_row = SqlRow::new();
                         prefixed_row
@@ -20269,7 +23413,7 @@ impl QueryExecutor {
                     // Populate row_cache for future hot-path lookups
                     self.db
                         .row_cache
-                        .put(table_name.to_string(), row_id, row.clone());
+                        .fill(table_name, row_id, Arc::new(row.clone()), epoch);
 
                     // 🚀 Fast path for SELECT *: skip HashMap conversion entirely
                     //     Direct positional projection from Vec<Value> — saves 2*N HashMap
@@ -20429,17 +23573,24 @@ impl QueryExecutor {
 
     // 🚀 P0 FIX: Primary Key ORDER BY optimization
 
-    /// Try to optimize ORDER BY primary_key [ASC/DESC] [LIMIT k]
+    /// Try to optimize ORDER BY indexed_column [ASC/DESC] [LIMIT k]
     ///
     /// Detects patterns like:
     /// - `SELECT * FROM table ORDER BY id LIMIT 10`
     /// - `SELECT * FROM table ORDER BY id DESC`
+    /// - `SELECT * FROM events ORDER BY ts DESC LIMIT 10`
+    ///
+    /// The column index is walked in the requested direction and the walk
+    /// stops after OFFSET + LIMIT rows. NULLs are not indexed, so the column
+    /// must be the primary key or NOT NULL.
     ///
     /// Benefits:
     /// - 600x faster: 1ms vs 611ms (300K rows)
     /// - 280x less memory: 0.1MB vs 28MB
     /// - O(k) complexity instead of O(n log n)
-    fn try_optimize_primary_key_order_by(&self, stmt: &SelectStmt) -> Result<Option<QueryResult>> {
+    fn try_optimize_index_order_by(&self, stmt: &SelectStmt) -> Result<Option<QueryResult>> {
+        const BATCH: usize = 256;
+
         // Must have ORDER BY with single column
         let order_by = match &stmt.order_by {
             Some(o) if o.len() == 1 => &o[0],
@@ -20448,7 +23599,7 @@ impl QueryExecutor {
 
         // ORDER BY must be a simple column reference
         let order_column = match &order_by.expr {
-            Expr::Column(col) => col,
+            Expr::Column(col) => col.rsplit('.').next().unwrap_or(col),
             _ => return Ok(None),
         };
 
@@ -20458,19 +23609,14 @@ impl QueryExecutor {
             _ => return Ok(None),
         };
 
-        // Check if this column is the primary key
-        let schema = self.db.get_table_schema(table_name)?;
-        let is_primary_key = schema
-            .primary_key()
-            .map(|pk| pk == order_column)
-            .unwrap_or(false);
-
-        if !is_primary_key {
-            return Ok(None);
-        }
-
-        // Check that there's no WHERE clause (for now)
-        if stmt.where_clause.is_some() {
+        // Check that there's no WHERE clause (for now), nor anything that
+        // reshapes rows after the sort
+        if stmt.where_clause.is_some()
+            || stmt.group_by.is_some()
+            || stmt.having.is_some()
+            || stmt.latest_by.is_some()
+            || stmt.sample_by.is_some()
+        {
             return Ok(None);
         }
 
@@ -20487,73 +23633,61 @@ impl QueryExecutor {
             }
         }
 
-        // Get primary key column index
-        let pk_index_name = format!("{}.{}", table_name, order_column);
-
-        // Check if index exists
-        if !self.db.column_indexes.contains_key(&pk_index_name) {
-            // No index, fallback to normal execution
+        // The index must hold every row in sort order: primary key or NOT
+        // NULL column of a type whose index keys sort like its values
+        let schema = self.db.get_table_schema(table_name)?;
+        let descending = match Self::index_order_direction(stmt, &schema, order_column) {
+            Some(descending) => descending,
+            None => return Ok(None),
+        };
+        let is_primary_key = schema.primary_key() == Some(order_column);
+        let nullable = schema
+            .get_column(order_column)
+            .is_none_or(|col| col.nullable);
+        if !is_primary_key && nullable {
             return Ok(None);
         }
 
-        // Scan primary key index to get row_ids in order
-        let index_arc = self
-            .db
-            .column_indexes
-            .get(&pk_index_name)
-            .ok_or_else(|| {
-                crate::StorageError::Index(format!(
-                    "Primary key index not found: {}",
-                    pk_index_name
-                ))
-            })?
-            .clone(); // Clone Arc<ColumnValueIndex>
-
-        // Calculate how many entries we need to scan
-        let offset = stmt.offset.unwrap_or(0);
-        let limit = stmt.limit.unwrap_or(usize::MAX);
-        let scan_limit = if limit == usize::MAX {
-            None // No limit, scan all
-        } else {
-            Some(offset + limit) // Scan enough to cover offset + limit
+        // Check if index exists
+        let index_name = format!("{}.{}", table_name, order_column);
+        let index = match self.db.column_indexes.get(&index_name) {
+            Some(index) if !index.is_multi_key() => index.value().clone(),
+            // No index, fallback to normal execution
+            _ => return Ok(None),
         };
 
-        let row_ids = index_arc.scan_row_ids_with_limit(scan_limit)?;
+        // Walk the index in ORDER BY direction, fetching rows a batch at a
+        // time until OFFSET + LIMIT rows are found
+        let offset = stmt.offset.unwrap_or(0);
+        let wanted = offset.saturating_add(stmt.limit.unwrap_or(usize::MAX));
+        let mut row_ids = index.scan_iter(descending)?;
+        let mut found = 0;
+        let mut indexed_any = false;
+        let mut sql_rows = Vec::new();
+        while found < wanted {
+            let batch = row_ids
+                .by_ref()
+                .take((wanted - found).min(BATCH))
+                .collect::<Result<Vec<_>>>()?;
+            if batch.is_empty() {
+                break;
+            }
+            indexed_any = true;
+            for (row_id, row) in self.db.get_table_rows_batch_arc(table_name, &batch)? {
+                let Some(row) = row else { continue };
+                found += 1;
+                if found > offset {
+                    sql_rows.push((row_id, row_to_sql_row(&row, &schema)?));
+                }
+            }
+        }
 
         // If the column index is empty (async pipeline may not have built it yet),
         // fall back to full scan to avoid returning wrong empty results.
-        if row_ids.is_empty() {
+        if !indexed_any {
             return Ok(None);
         }
 
-        // Apply sort order (ascending or descending)
-        let sorted_row_ids = if order_by.asc {
-            row_ids
-        } else {
-            let mut rev = row_ids;
-            rev.reverse();
-            rev
-        };
-
-        // Apply LIMIT and OFFSET
-        let limit = stmt.limit.unwrap_or(usize::MAX);
-        let offset = stmt.offset.unwrap_or(0);
-
-        let limited_row_ids: Vec<_> = sorted_row_ids
-            .into_iter()
-            .skip(offset)
-            .take(limit)
-            .collect();
-
-        // Load rows
-        let mut sql_rows = Vec::with_capacity(limited_row_ids.len());
-        for row_id in limited_row_ids {
-            if let Ok(Some(row)) = self.db.get_table_row(table_name, row_id) {
-                let sql_row = row_to_sql_row(&row, &schema)?;
-                sql_rows.push((row_id, sql_row));
-            }
-        }
-
         // Add table prefix
         prefix_rows(&mut sql_rows, table_name, table_name);
 
@@ -20598,6 +23732,7 @@ impl QueryExecutor {
             // Top-K heap (max-heap by distance → pop largest to keep smallest k).
             let mut heap: std::collections::BinaryHeap<std::cmp::Reverse<(OrderedF32, u64)>> =
                 std::collections::BinaryHeap::with_capacity(k + 1);
+            let mut row_scratch = Vec::with_capacity(qdim);
             for seg in &segs {
                 if col_pos >= seg.sst.column_tags.len() {
                     continue;
@@ -20629,16 +23764,22 @@ impl QueryExecutor {
                         continue;
                     }
                     let base = data_start + i * stride;
-                    // 🚀 SIMD distance: reinterpret the row's f32 bytes as a
-                    // &[f32] slice and call the NEON/AVX2 euclidean_distance_squared.
+                    // 🚀 SIMD distance: view the row's f32 bytes as a &[f32]
+                    // slice (zero-copy when aligned on little-endian) and call
+                    // the NEON/AVX2 euclidean_distance_squared.
                     // This is 4-8x faster than the scalar per-element loop.
-                    let row_vec: &[f32] = unsafe {
-                        std::slice::from_raw_parts(data[base..base + stride].as_ptr() as *const f32, dim)
-                    };
-                    let dist = crate::distance::euclidean::euclidean_distance_squared(query, row_vec);
+                    let row_vec = crate::storage::lsm::columnar::le_f32s(
+                        &data[base..base + stride],
+                        &mut row_scratch,
+                    );
+                    let dist =
+                        crate::distance::euclidean::euclidean_distance_squared(query, row_vec);
                     // Maintain top-K max-heap.
                     if heap.len() < k {
-                        heap.push(std::cmp::Reverse((OrderedF32(dist), seg.sst.row_map.key(i))));
+                        heap.push(std::cmp::Reverse((
+                            OrderedF32(dist),
+                            seg.sst.row_map.key(i),
+                        )));
                     } else if let Some(&std::cmp::Reverse((worst, _))) = heap.peek() {
                         if OrderedF32(dist) < worst {
                             heap.pop();
@@ -20674,15 +23815,15 @@ impl QueryExecutor {
         };
 
         // 解析 ORDER BY 表达式
-        let (column, query_vector, asc) = match &order_by.expr {
+        let (column, query_vector, op, asc) = match &order_by.expr {
             // 匹配: column <-> [vector] (L2Distance)
             Expr::BinaryOp {
-                op: BinaryOperator::L2Distance | BinaryOperator::CosineDistance,
+                op: op @ (BinaryOperator::L2Distance | BinaryOperator::CosineDistance),
                 left,
                 right,
             } => match (&**left, &**right) {
                 (Expr::Column(col), Expr::Literal(Value::Vector(vec))) => {
-                    (col.clone(), vec.clone(), order_by.asc)
+                    (col.clone(), vec.clone(), op.clone(), order_by.asc)
                 }
                 (Expr::Column(_col), _other) => {
                     return Ok(None);
@@ -20718,7 +23859,14 @@ impl QueryExecutor {
                 crate::database::index_metadata::IndexType::Vector,
             )
             .unwrap_or_else(|| format!("{}_{}", table_name, column));
-        if !self.db.has_vector_index(&index_name) {
+        // The index only orders by its own metric; `<=>` over an L2 index
+        // (or `<->` over a cosine one) must be sorted by the generic path.
+        let metric_matches = match self.db.vector_index_metric(&index_name) {
+            Some(crate::distance::DistanceKind::Euclidean) => op == BinaryOperator::L2Distance,
+            Some(crate::distance::DistanceKind::Cosine) => op == BinaryOperator::CosineDistance,
+            None => return Ok(None),
+        };
+        if !metric_matches {
             return Ok(None);
         }
 
@@ -20726,6 +23874,7 @@ impl QueryExecutor {
             table: table_name,
             column,
             query_vector: query_vector.to_vec(),
+            op,
             k: limit,
         }))
     }
@@ -20761,8 +23910,12 @@ impl QueryExecutor {
         // fallback for tables that have no vector index built yet.
         let has_index = self.db.has_vector_index(&index_name);
         let candidates = if has_index {
-            self.db
-                .vector_search(&index_name, &plan.query_vector, plan.k)?
+            self.db.vector_search_with_options(
+                &index_name,
+                &plan.query_vector,
+                plan.k,
+                self.vector_search_options(),
+            )?
         } else {
             // No index built (e.g. data not yet flushed) — brute-force scan.
             self.brute_force_vector_knn(&plan.table, &plan.column, &plan.query_vector, plan.k)?
@@ -20774,6 +23927,30 @@ impl QueryExecutor {
 
         let row_ids: Vec<u64> = candidates.iter().map(|(id, _dist)| *id).collect();
 
+        // Distances as SQL reports them, straight from the search results:
+        // Euclidean searches (index and brute force) yield squared distances.
+        let metric = if has_index {
+            self.db.vector_index_metric(&index_name)
+        } else {
+            Some(crate::distance::DistanceKind::Euclidean)
+        };
+        let distances: std::collections::HashMap<u64, Value> = match (metric, &plan.op) {
+            (Some(crate::distance::DistanceKind::Euclidean), BinaryOperator::L2Distance) => {
+                candidates
+                    .iter()
+                    .map(|&(id, d)| (id, Value::Float(d.max(0.0).sqrt() as f64)))
+                    .collect()
+            }
+            (Some(crate::distance::DistanceKind::Cosine), BinaryOperator::CosineDistance) => {
+                candidates
+                    .iter()
+                    .map(|&(id, d)| (id, Value::Float(d as f64)))
+                    .collect()
+            }
+            // Operator and index metric differ: evaluate per row instead
+            _ => std::collections::HashMap::new(),
+        };
+
         if !row_ids.is_empty() {
             debug_log!(
                 "[Executor] 🔍 row_ids前5个: {:?}",
@@ -20852,7 +24029,7 @@ impl QueryExecutor {
 
         let projected_rows: Vec<Vec<Value>> = filtered_rows
             .iter()
-            .map(|(_, row)| {
+            .map(|(row_id, row)| {
                 if stmt.columns.len() == 1 && matches!(stmt.columns[0], SelectColumn::Star) {
                     // SELECT * - return all columns in schema order
                     schema
@@ -20870,6 +24047,11 @@ impl QueryExecutor {
                                     row.get(name).cloned().unwrap_or(Value::Null)
                                 }
                                 SelectColumn::Expr(expr, _) => {
+                                    if plan.is_distance_expr(expr) {
+                                        if let Some(d) = distances.get(row_id) {
+                                            return d.clone();
+                                        }
+                                    }
                                     // ⚠️ 只对简单表达式求值，避免递归
                                     self.evaluator.eval(expr, row).unwrap_or(Value::Null)
                                 }
@@ -20980,6 +24162,7 @@ impl QueryExecutor {
 
         // P1: Handle ORDER BY for columnar results
         if let Some(ref order_by) = stmt.order_by {
+            query_memory::charge_rows(&rows, "ORDER BY")?;
             for order_item in order_by.iter().rev() {
                 let col_name = match &order_item.expr {
                     Expr::Column(name) => name.clone(),
@@ -21331,15 +24514,15 @@ impl QueryExecutor {
             for (i, col_name) in columns.iter().enumerate() {
                 let val = match &value_row[i] {
                     Expr::Literal(v) => v.clone(),
-                    Expr::Parameter(_) => {
+                    Expr::Parameter(_) | Expr::FunctionCall { .. } => {
                         let empty_row = SqlRow::new();
                         self.evaluator.eval(&value_row[i], &empty_row)?
                     }
                     expr => {
                         return Err(MoteDBError::InvalidArgument(format!(
-                            "INSERT VALUES must be literals or parameters, got {:?}",
-                            expr
-                        )))
+                        "INSERT VALUES must be literals, parameters or function calls, got {:?}",
+                        expr
+                    )))
                     }
                 };
                 sql_row.insert(col_name.clone(), val);
@@ -21350,26 +24533,161 @@ impl QueryExecutor {
             rows.push(row);
         }
 
-        let result = self.db.columnar_store.ingest(&stmt.table, rows)?;
+        let result = if self.db.has_continuous_aggregates(&stmt.table) {
+            let source_rows = rows.clone();
+            self.db
+                .maintain_continuous_aggregates(&stmt.table, &source_rows, || {
+                    self.db.columnar_store.ingest(&stmt.table, rows)
+                })?
+        } else {
+            self.db.columnar_store.ingest(&stmt.table, rows)?
+        };
         Ok(QueryResult::Modification {
             affected_rows: result.row_ids.len(),
         })
     }
 }
 
+/// Rows per `WriteBatch` when an UPDATE/DELETE touches many rows
+const BULK_WRITE_CHUNK: usize = 1024;
+
+/// Row writes of one UPDATE/DELETE statement
+///
+/// Outside a transaction the writes are grouped into `WriteBatch`es of
+/// [`BULK_WRITE_CHUNK`] rows, so each chunk costs one WAL record and one
+/// index pass per index instead of per-row index maintenance. Inside a
+/// transaction (or when the primary key changes, which a batch refuses) every
+/// row is written on its own and logged for ROLLBACK.
+struct RowWriter<'a> {
+    executor: &'a QueryExecutor,
+    table: &'a str,
+    schema: &'a TableSchema,
+    txn_id: Option<u64>,
+    batch: Option<crate::database::WriteBatch<'a>>,
+}
+
+impl<'a> RowWriter<'a> {
+    fn new(executor: &'a QueryExecutor, table: &'a str, schema: &'a TableSchema) -> Self {
+        Self::with_batching(executor, table, schema, true)
+    }
+
+    fn with_batching(
+        executor: &'a QueryExecutor,
+        table: &'a str,
+        schema: &'a TableSchema,
+        allow_batch: bool,
+    ) -> Self {
+        let txn_id = executor.current_txn_id();
+        let batch = (allow_batch && txn_id.is_none()).then(|| executor.db.write_batch());
+        Self {
+            executor,
+            table,
+            schema,
+            txn_id,
+            batch,
+        }
+    }
+
+    fn update(&mut self, row_id: RowId, old: Row, new: Row) -> Result<()> {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.update_from(self.table, row_id, old, new);
+            return self.commit_if_full();
+        }
+        // 🔑 Record undo delta for transactional UPDATE (so ROLLBACK can restore).
+        if let Some(tid) = self.txn_id {
+            let _ = self.executor.db.txn_coordinator.record_write_delta(
+                tid,
+                crate::txn::coordinator::DeltaOperation::Update(
+                    row_id,
+                    self.table.to_string(),
+                    Arc::new(old.clone()),
+                ),
+            );
+        }
+        self.executor
+            .db
+            .update_row_in_table_with_schema(self.table, row_id, old, new, self.schema)
+    }
+
+    fn delete(&mut self, row_id: RowId, old: Row) -> Result<()> {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.delete_from(self.table, row_id, old);
+            return self.commit_if_full();
+        }
+        // 🔑 Record undo delta for transactional DELETE (so ROLLBACK can restore).
+        if let Some(tid) = self.txn_id {
+            let _ = self.executor.db.txn_coordinator.record_write_delta(
+                tid,
+                crate::txn::coordinator::DeltaOperation::Delete(
+                    row_id,
+                    self.table.to_string(),
+                    Arc::new(old.clone()),
+                ),
+            );
+        }
+        self.executor
+            .db
+            .delete_row_from_table(self.table, row_id, old)
+    }
+
+    fn commit_if_full(&mut self) -> Result<()> {
+        if self
+            .batch
+            .as_ref()
+            .is_some_and(|b| b.len() >= BULK_WRITE_CHUNK)
+        {
+            let full = self.batch.replace(self.executor.db.write_batch());
+            full.map_or(Ok(()), |b| b.commit().map(|_| ()))?;
+        }
+        Ok(())
+    }
+
+    /// Apply the writes still queued
+    fn finish(self) -> Result<()> {
+        match self.batch {
+            Some(batch) => batch.commit().map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Helper struct for vector ORDER BY plan
 struct VectorOrderByPlan {
     table: String,
     column: String,
     query_vector: Vec<f32>,
+    /// `<->` or `<=>`, as written in ORDER BY
+    op: BinaryOperator,
     k: usize,
 }
 
+impl VectorOrderByPlan {
+    /// Whether `expr` is the plan's own distance expression
+    /// (`column <op> [query_vector]`), whose value the search already produced
+    fn is_distance_expr(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::BinaryOp { left, op, right } if *op == self.op => matches!(
+                (&**left, &**right),
+                (Expr::Column(c), Expr::Literal(Value::Vector(v)))
+                    if *c == self.column && v.as_slice() == self.query_vector.as_slice()
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl super::physical::RowEvaluator for QueryExecutor {
+    fn eval(&self, expr: &Expr, row: &SqlRow) -> Result<Value> {
+        self.eval_with_materialized(expr, row)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{ColumnDef, ColumnType, TableSchema, Value};
     use std::cmp::Ordering;
+    use std::sync::atomic::AtomicUsize;
 
     fn make_schema() -> TableSchema {
         let columns = vec![
@@ -21381,6 +24699,7 @@ mod tests {
                 auto_increment: false,
                 auto_increment_start: None,
                 default_value: None,
+                generated: None,
             },
             ColumnDef {
                 name: "name".into(),
@@ -21390,6 +24709,7 @@ mod tests {
                 auto_increment: false,
                 auto_increment_start: None,
                 default_value: None,
+                generated: None,
             },
             ColumnDef {
                 name: "score".into(),
@@ -21399,6 +24719,7 @@ mod tests {
                 auto_increment: false,
                 auto_increment_start: None,
                 default_value: None,
+                generated: None,
             },
             ColumnDef {
                 name: "active".into(),
@@ -21408,6 +24729,7 @@ mod tests {
                 auto_increment: false,
                 auto_increment_start: None,
                 default_value: None,
+                generated: None,
             },
         ];
         TableSchema::new("t".into(), columns)
@@ -21735,11 +25057,142 @@ mod tests {
             distinct: false,
             group_by: None,
             having: None,
+            after_cursor: None,
             latest_by: None,
+            sample_by: None,
+            fill: None,
         }));
         assert!(
             QueryExecutor::eval_expr_on_row(&sub, &r, &schema).is_err(),
             "Unsupported expression should return Err for fallback path"
         );
     }
+
+    // ━━━ Materialize size hint ━━━
+
+    fn streaming(max_result_rows: Option<usize>) -> StreamingQueryResult {
+        StreamingQueryResult::SelectStreaming {
+            columns: vec!["id".into()],
+            rows: Box::new(std::iter::empty()),
+            order_by: None,
+            limit: None,
+            offset: None,
+            distinct: false,
+            max_result_rows,
+            size_hint: None,
+        }
+    }
+
+    fn size_hint(result: StreamingQueryResult) -> Option<usize> {
+        match result {
+            StreamingQueryResult::SelectStreaming { size_hint, .. } => size_hint,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_size_hint_capped() {
+        assert_eq!(
+            size_hint(streaming(None).with_size_hint(Some(10), 64)),
+            Some(10)
+        );
+        assert_eq!(
+            size_hint(streaming(None).with_size_hint(Some(5000), 64)),
+            Some(64)
+        );
+        // No statistics (or a zero estimate) falls back to the cap
+        assert_eq!(
+            size_hint(streaming(None).with_size_hint(Some(0), 64)),
+            Some(64)
+        );
+        assert_eq!(
+            size_hint(streaming(Some(8)).with_size_hint(None, 64)),
+            Some(8)
+        );
+        // A hint set earlier is kept
+        let planned = streaming(None).with_size_hint(Some(10), 64);
+        assert_eq!(size_hint(planned.with_size_hint(None, 64)), Some(10));
+    }
+
+    // ━━━ Result mode ━━━
+
+    fn counting(n: i64, limit: Option<usize>, pulled: Arc<AtomicUsize>) -> StreamingQueryResult {
+        StreamingQueryResult::SelectStreaming {
+            columns: vec!["id".into()],
+            rows: Box::new((0..n).map(move |i| {
+                pulled.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Ok(vec![Value::Integer(i)])
+            })),
+            order_by: None,
+            limit,
+            offset: Some(1),
+            distinct: false,
+            max_result_rows: None,
+            size_hint: None,
+        }
+    }
+
+    fn ids(result: StreamingQueryResult) -> Vec<i64> {
+        match result.materialize().unwrap() {
+            QueryResult::Select { rows, .. } => rows
+                .into_iter()
+                .map(|r| match r[0] {
+                    Value::Integer(i) => i,
+                    _ => unreachable!(),
+                })
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_prefetch_small_result_is_ready() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let result = counting(5, None, pulled.clone()).prefetch(8).unwrap();
+        assert!(matches!(result, StreamingQueryResult::SelectReady { .. }));
+        assert_eq!(ids(result), vec![1, 2, 3, 4]);
+
+        // LIMIT + OFFSET bounds the rows pulled from a long input
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let result = counting(1000, Some(2), pulled.clone()).prefetch(8).unwrap();
+        assert_eq!(pulled.load(std::sync::atomic::Ordering::Relaxed), 3);
+        assert_eq!(ids(result), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_prefetch_large_result_keeps_streaming() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let result = counting(1000, None, pulled.clone()).prefetch(8).unwrap();
+        assert!(matches!(
+            result,
+            StreamingQueryResult::SelectStreaming { .. }
+        ));
+        assert_eq!(pulled.load(std::sync::atomic::Ordering::Relaxed), 8);
+        assert_eq!(ids(result), (1..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_expects_small_result() {
+        let select = |sql: &str| match crate::sql::Parser::new(
+            crate::sql::Lexer::new(sql).tokenize().unwrap(),
+        )
+        .parse()
+        .unwrap()
+        {
+            Statement::Select { stmt, .. } => stmt,
+            _ => unreachable!(),
+        };
+        let plain = select("SELECT id FROM t");
+        assert!(QueryExecutor::expects_small_result(&plain, 10, 100));
+        assert!(!QueryExecutor::expects_small_result(&plain, 500, 100));
+        // No statistics
+        assert!(!QueryExecutor::expects_small_result(&plain, 0, 100));
+        let limited = select("SELECT id FROM t LIMIT 10 OFFSET 5");
+        assert!(QueryExecutor::expects_small_result(&limited, 0, 100));
+        assert!(QueryExecutor::expects_small_result(&limited, 500, 100));
+        // LIMIT after a sort says nothing about the input size
+        let sorted = select("SELECT id FROM t ORDER BY id LIMIT 10");
+        assert!(!QueryExecutor::expects_small_result(&sorted, 500, 100));
+        assert!(QueryExecutor::expects_small_result(&sorted, 50, 100));
+    }
 }
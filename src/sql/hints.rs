@@ -0,0 +1,124 @@
+//! Statement hints (`/*+ ... */`)
+//!
+//! Hints are block comments starting with `+`. The lexer skips them like any
+//! other comment; `Database::execute` pulls out the ones it understands
+//! before dispatching the statement. Unknown hints are ignored.
+//!
+//! Supported:
+//! - `ASYNC_COMMIT` / `SYNC_COMMIT`: per-statement [`CommitMode`]
+//! - `BEST_EFFORT`: the statement may be rejected when the database is at
+//!   its hard memory cap (`DBConfig::memory_limit`)
+
+use crate::config::CommitMode;
+use std::borrow::Cow;
+
+/// Hints attached to one statement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatementHints {
+    /// Durability override (the last commit hint wins)
+    pub commit_mode: Option<CommitMode>,
+    /// Statement may be shed under memory pressure
+    pub best_effort: bool,
+}
+
+/// Strip the `/*+ ... */` hints from `sql` and return the ones understood.
+/// Comments inside string literals are left alone, as is an unterminated
+/// hint (the lexer reports it).
+pub fn take_hints(sql: &str) -> (StatementHints, Cow<'_, str>) {
+    let mut hints = StatementHints::default();
+    if !sql.contains("/*+") {
+        return (hints, Cow::Borrowed(sql));
+    }
+
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut copied = 0;
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if let Some(q) = quote {
+            if b == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        match b {
+            b'\'' | b'"' => {
+                quote = Some(b);
+                i += 1;
+            }
+            b'/' if sql[i..].starts_with("/*+") => {
+                let Some(len) = sql[i + 3..].find("*/") else {
+                    break;
+                };
+                let words = sql[i + 3..i + 3 + len]
+                    .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+                for word in words {
+                    if word.eq_ignore_ascii_case("ASYNC_COMMIT") {
+                        hints.commit_mode = Some(CommitMode::Async);
+                    } else if word.eq_ignore_ascii_case("SYNC_COMMIT") {
+                        hints.commit_mode = Some(CommitMode::Sync);
+                    } else if word.eq_ignore_ascii_case("BEST_EFFORT") {
+                        hints.best_effort = true;
+                    }
+                }
+                out.push_str(&sql[copied..i]);
+                out.push(' ');
+                i += 3 + len + 2;
+                copied = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if copied == 0 {
+        return (hints, Cow::Borrowed(sql));
+    }
+    out.push_str(&sql[copied..]);
+    (hints, Cow::Owned(out))
+}
+
+/// [`take_hints`], keeping only the commit mode
+pub fn take_commit_hint(sql: &str) -> (Option<CommitMode>, Cow<'_, str>) {
+    let (hints, sql) = take_hints(sql);
+    (hints.commit_mode, sql)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_commit_hint() {
+        let (mode, sql) = take_commit_hint("INSERT /*+ ASYNC_COMMIT */ INTO t VALUES (1)");
+        assert_eq!(mode, Some(CommitMode::Async));
+        assert_eq!(
+            sql.split_whitespace().collect::<Vec<_>>().join(" "),
+            "INSERT INTO t VALUES (1)"
+        );
+
+        let (mode, _) = take_commit_hint("/*+ foo, sync_commit */ DELETE FROM t");
+        assert_eq!(mode, Some(CommitMode::Sync));
+
+        // Plain comments, literals and unknown hints
+        let (mode, sql) = take_commit_hint("INSERT INTO t VALUES ('/*+ ASYNC_COMMIT */')");
+        assert_eq!(mode, None);
+        assert!(matches!(sql, Cow::Borrowed(_)));
+        assert_eq!(take_commit_hint("SELECT 1 /* ASYNC_COMMIT */").0, None);
+        let (mode, sql) = take_commit_hint("SELECT /*+ INDEX(t) */ 1");
+        assert_eq!(mode, None);
+        assert!(!sql.contains("/*+"));
+        assert_eq!(take_commit_hint("SELECT 1 /*+ ASYNC_COMMIT").0, None);
+    }
+
+    #[test]
+    fn test_best_effort_hint() {
+        let (hints, sql) = take_hints("SELECT /*+ BEST_EFFORT, ASYNC_COMMIT */ * FROM t");
+        assert!(hints.best_effort);
+        assert_eq!(hints.commit_mode, Some(CommitMode::Async));
+        assert!(!sql.contains("/*+"));
+        assert!(!take_hints("SELECT 'x /*+ BEST_EFFORT */'").0.best_effort);
+    }
+}
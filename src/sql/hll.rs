@@ -0,0 +1,115 @@
+//! HyperLogLog sketch behind `APPROX_COUNT_DISTINCT`
+//!
+//! 2^14 one-byte registers (16 KiB per sketch) give a standard error of about
+//! 0.8% regardless of how many distinct values are added. Small cardinalities
+//! fall back to linear counting, which is close to exact.
+
+use crate::types::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const PRECISION: u32 = 14;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// Cardinality sketch over `Value`s
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+        }
+    }
+
+    /// Add a value (callers skip NULLs)
+    pub fn add(&mut self, value: &Value) {
+        // DefaultHasher::new() uses fixed keys, so sketches are reproducible
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        self.add_hash(hasher.finish());
+    }
+
+    fn add_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Sentinel bit caps the rank when the remaining bits are all zero
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Fold another sketch into this one (union of the two value sets)
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    /// Estimated number of distinct values added
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(estimate: u64, actual: u64) {
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(error < 0.03, "estimate {} vs actual {}", estimate, actual);
+    }
+
+    #[test]
+    fn test_estimate_accuracy() {
+        assert_eq!(HyperLogLog::new().estimate(), 0);
+
+        let mut small = HyperLogLog::new();
+        for i in 0..100 {
+            small.add(&Value::Integer(i % 10));
+        }
+        assert_eq!(small.estimate(), 10);
+
+        for n in [1_000i64, 50_000, 200_000] {
+            let mut sketch = HyperLogLog::new();
+            for i in 0..n {
+                sketch.add(&Value::text(format!("user-{}", i)));
+                sketch.add(&Value::text(format!("user-{}", i / 2)));
+            }
+            assert_close(sketch.estimate(), n as u64);
+        }
+    }
+
+    #[test]
+    fn test_merge_is_union() {
+        let mut a = HyperLogLog::new();
+        let mut b = HyperLogLog::new();
+        for i in 0..30_000 {
+            a.add(&Value::Integer(i));
+            b.add(&Value::Integer(i + 20_000));
+        }
+        a.merge(&b);
+        assert_close(a.estimate(), 50_000);
+    }
+}
@@ -0,0 +1,238 @@
+//! JSON encoding of query results
+//!
+//! One format for every consumer (FFI, HTTP layers, the CLI), so none of
+//! them has to invent its own:
+//!
+//! ```text
+//! {"columns":["id","at","pos"],
+//!  "types":["INTEGER","TIMESTAMP","GEOMETRY"],
+//!  "rows":[[1,"2023-11-14T22:13:20Z",{"type":"Point","coordinates":[1.0,2.0]}]]}
+//! ```
+//!
+//! Modifications encode as `{"affected_rows":n}` and definitions as
+//! `{"message":"..."}`. Values map as follows:
+//!
+//! - INTEGER, FLOAT, BOOLEAN, TEXT: JSON numbers, booleans and strings;
+//!   a NaN or infinite float is `null`
+//! - VECTOR and TENSOR: arrays of numbers (nested by the tensor shape)
+//! - GEOMETRY: a GeoJSON geometry object
+//! - TIMESTAMP: an RFC 3339 string (or microseconds, see [`JsonOptions`])
+//! - DECIMAL, UUID, DATE, TIME: their SQL text (decimals keep full precision)
+//! - NULL: `null`
+//!
+//! A column's type is that of its first non-NULL value among the first
+//! [`TYPE_SAMPLE_ROWS`] rows (`null` when there is none), so a streamed
+//! result and its materialized form encode identically.
+
+use std::io::Write;
+
+use crate::session::{SessionSettings, TimestampFormat};
+use crate::types::{UtcOffset, Value};
+use crate::{QueryResult, Result, StreamingQueryResult};
+
+/// Rows inspected to name the column types
+pub const TYPE_SAMPLE_ROWS: usize = 64;
+
+/// How values that have more than one common JSON form are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// TIMESTAMP as an RFC 3339 string (`Iso`) or epoch microseconds
+    pub timestamp_format: TimestampFormat,
+    /// Offset of RFC 3339 timestamps
+    pub time_zone: UtcOffset,
+}
+
+impl Default for JsonOptions {
+    /// RFC 3339 timestamps in UTC
+    fn default() -> Self {
+        Self {
+            timestamp_format: TimestampFormat::Iso,
+            time_zone: UtcOffset::UTC,
+        }
+    }
+}
+
+impl From<&SessionSettings> for JsonOptions {
+    /// The session's `timestamp_format` and `time_zone`
+    fn from(settings: &SessionSettings) -> Self {
+        Self {
+            timestamp_format: settings.timestamp_format,
+            time_zone: settings.time_zone,
+        }
+    }
+}
+
+/// Encode one value
+pub fn value_to_json(value: &Value, options: &JsonOptions) -> serde_json::Value {
+    match value {
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Text(s) => serde_json::Value::String(s.to_string()),
+        Value::Vector(v) => serde_json::Value::from(v.as_slice()),
+        Value::Tensor(t) => t.to_json(),
+        Value::Spatial(g) => g.to_geojson(),
+        Value::TextDoc(t) => serde_json::Value::String(t.content().to_string()),
+        Value::Timestamp(ts) => match options.timestamp_format {
+            TimestampFormat::Iso => {
+                serde_json::Value::String(options.time_zone.format_timestamp(*ts))
+            }
+            TimestampFormat::Micros => serde_json::Value::from(ts.as_micros()),
+        },
+        // A string keeps every digit (JSON numbers are read back as f64)
+        Value::Decimal(d) => serde_json::Value::String(d.to_string()),
+        Value::Uuid(u) => serde_json::Value::String(u.to_string()),
+        Value::Date(d) => serde_json::Value::String(d.to_string()),
+        Value::Time(t) => serde_json::Value::String(t.to_string()),
+        Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| value_to_json(item, options))
+                .collect(),
+        ),
+        Value::Null => serde_json::Value::Null,
+    }
+}
+
+/// SQL type name of a value (`VECTOR(3)`, `TENSOR(2, 2)`, ...); None for NULL
+pub fn type_name(value: &Value) -> Option<String> {
+    let name = match value {
+        Value::Integer(_) => "INTEGER",
+        Value::Float(_) => "FLOAT",
+        Value::Bool(_) => "BOOLEAN",
+        Value::Text(_) | Value::TextDoc(_) => "TEXT",
+        Value::Vector(v) => return Some(format!("VECTOR({})", v.len())),
+        Value::Tensor(t) => {
+            let dims: Vec<String> = t.shape().iter().map(|d| d.to_string()).collect();
+            return Some(format!("TENSOR({})", dims.join(", ")));
+        }
+        Value::Spatial(_) => "GEOMETRY",
+        Value::Timestamp(_) => "TIMESTAMP",
+        Value::Decimal(_) => "DECIMAL",
+        Value::Uuid(_) => "UUID",
+        Value::Date(_) => "DATE",
+        Value::Time(_) => "TIME",
+        Value::Array(items) => {
+            return Some(match items.iter().find_map(type_name) {
+                Some(element) => format!("ARRAY<{}>", element),
+                None => "ARRAY".to_string(),
+            })
+        }
+        Value::Null => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Column types named from the sampled rows
+fn column_types<R: AsRef<[Value]>>(width: usize, sample: &[R]) -> Vec<Option<String>> {
+    (0..width)
+        .map(|i| {
+            sample
+                .iter()
+                .take(TYPE_SAMPLE_ROWS)
+                .find_map(|row| row.as_ref().get(i).and_then(type_name))
+        })
+        .collect()
+}
+
+/// Write a SELECT result: the header typed from `head`, then `head` and
+/// `rest` as rows. Returns the number of rows written.
+fn write_select<W: Write, R: AsRef<[Value]>>(
+    out: &mut W,
+    columns: &[String],
+    head: &[R],
+    rest: impl Iterator<Item = Result<R>>,
+    options: &JsonOptions,
+) -> Result<usize> {
+    let types = column_types(columns.len(), head);
+    write!(
+        out,
+        "{{\"columns\":{},\"types\":{},\"rows\":[",
+        serde_json::Value::from(columns),
+        serde_json::to_value(&types).unwrap_or_default()
+    )?;
+    let mut written = 0usize;
+    let mut write_row = |out: &mut W, row: &[Value]| -> Result<()> {
+        if written > 0 {
+            out.write_all(b",")?;
+        }
+        let row: Vec<serde_json::Value> = row.iter().map(|v| value_to_json(v, options)).collect();
+        write!(out, "{}", serde_json::Value::Array(row))?;
+        written += 1;
+        Ok(())
+    };
+    for row in head {
+        write_row(out, row.as_ref())?;
+    }
+    for row in rest {
+        write_row(out, row?.as_ref())?;
+    }
+    out.write_all(b"]}")?;
+    Ok(written)
+}
+
+impl QueryResult {
+    /// Encode as JSON with RFC 3339 timestamps (see [`crate::sql::json`])
+    pub fn to_json(&self) -> String {
+        self.to_json_with(&JsonOptions::default())
+    }
+
+    /// Encode as JSON with `options`
+    pub fn to_json_with(&self, options: &JsonOptions) -> String {
+        match self {
+            QueryResult::Select { columns, rows } => {
+                let mut out = Vec::new();
+                // Writing to a Vec cannot fail and there are no row errors
+                let _ = write_select(&mut out, columns, rows, std::iter::empty(), options);
+                String::from_utf8(out).unwrap_or_default()
+            }
+            QueryResult::Modification { affected_rows } => {
+                serde_json::json!({ "affected_rows": affected_rows }).to_string()
+            }
+            QueryResult::Definition { message } => {
+                serde_json::json!({ "message": message }).to_string()
+            }
+        }
+    }
+}
+
+impl StreamingQueryResult {
+    /// Write the result to `out` as JSON with RFC 3339 timestamps, row by
+    /// row; the same text [`QueryResult::to_json`] produces. Returns the
+    /// number of rows written.
+    pub fn write_json<W: Write>(self, out: W) -> Result<usize> {
+        self.write_json_with(out, &JsonOptions::default())
+    }
+
+    /// Write the result to `out` as JSON with `options`
+    ///
+    /// Only the first [`TYPE_SAMPLE_ROWS`] rows are buffered (to name the
+    /// column types); queries that must sort or deduplicate materialize
+    /// first, as with [`into_row_iter`](Self::into_row_iter).
+    pub fn write_json_with<W: Write>(self, mut out: W, options: &JsonOptions) -> Result<usize> {
+        let message = match self {
+            Self::Modification { affected_rows } => {
+                serde_json::json!({ "affected_rows": affected_rows })
+            }
+            Self::Definition { message } => serde_json::json!({ "message": message }),
+            select => {
+                let (columns, mut rows) = select.into_row_iter()?;
+                let mut head = Vec::new();
+                while head.len() < TYPE_SAMPLE_ROWS {
+                    match rows.next() {
+                        Some(row) => head.push(row?),
+                        None => break,
+                    }
+                }
+                let written = write_select(&mut out, &columns, &head, rows, options)?;
+                out.flush()?;
+                return Ok(written);
+            }
+        };
+        write!(out, "{}", message)?;
+        out.flush()?;
+        Ok(0)
+    }
+}
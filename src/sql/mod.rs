@@ -1,9 +1,18 @@
+pub mod access;
 pub mod ast;
+pub mod builder;
+pub mod correlated;
 pub mod evaluator;
 pub mod executor;
+pub mod hints;
+pub mod hll;
+pub mod json;
 pub mod lexer;
+pub(crate) mod normalize;
 pub mod optimizer;
 pub mod parser;
+pub mod physical;
+pub(crate) mod query_memory;
 pub mod row_converter;
 /// MoteDB Lightweight SQL Engine
 ///
@@ -16,14 +25,18 @@ pub mod row_converter;
 /// - Optimizer: Query optimization (future)
 pub mod token;
 
+pub use access::{AccessHook, TableAccess};
 pub use ast::{BinaryOperator, CreateTableStmt, Expr, InsertStmt, SelectStmt, Statement};
 pub use evaluator::ExprEvaluator;
 pub use executor::{
-    ForEachResult, QueryExecutor, QueryResult, StreamingControl, StreamingQueryResult,
+    ForEachResult, QueryExecutor, QueryPage, QueryResult, RowIter, StreamingControl,
+    StreamingQueryResult,
 };
+pub use json::JsonOptions;
 pub use lexer::Lexer;
-pub use optimizer::{IndexStats, QueryOptimizer, QueryPlan, ScanMethod};
-pub use parser::Parser;
+pub use optimizer::{IndexStats, OptimizerStats, ProbeKind, QueryOptimizer, QueryPlan, ScanMethod};
+pub use parser::{parse_script, Parser};
+pub use physical::{PhysicalOperator, PhysicalPlan, PhysicalPlanner};
 pub use row_converter::{row_to_sql_row, rows_to_sql_rows, sql_row_to_row};
 pub use token::{Token, TokenType};
 
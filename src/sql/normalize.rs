@@ -0,0 +1,554 @@
+//! WHERE clause normalization ahead of access path selection
+//!
+//! The optimizer matches index patterns against single conjuncts, so a
+//! predicate is first rewritten into an equivalent list of them:
+//!
+//! 1. bind parameters are substituted and `BETWEEN` becomes a pair of
+//!    comparisons;
+//! 2. `NOT` is pushed down to the leaves (De Morgan, inverted comparisons);
+//! 3. subexpressions without columns are folded to literals, and `AND` /
+//!    `OR` with a constant operand are simplified;
+//! 4. the result is converted to conjunctive normal form, giving up on `OR`s
+//!    whose expansion would exceed [`MAX_CNF_CLAUSES`];
+//! 5. the comparisons bounding one column are merged into its tightest range.
+//!
+//! With `NOT` pushed to the leaves every predicate sits in a positive
+//! position, where UNKNOWN filters a row out exactly like FALSE; NULL
+//! constants are folded on that basis. The result only steers index
+//! selection: plans keep the original WHERE clause as their post filter.
+
+use super::ast::{BinaryOperator, Expr, UnaryOperator};
+use super::evaluator::ExprEvaluator;
+use crate::types::{SqlRow, Value};
+use std::cmp::Ordering;
+
+/// `OR`s whose CNF expansion would produce more clauses are kept whole
+pub const MAX_CNF_CLAUSES: usize = 16;
+
+/// Rewrite `expr` into conjuncts as described in the module docs. A
+/// predicate no row can satisfy comes back as a single `FALSE` literal; one
+/// every row satisfies as no conjuncts at all.
+pub fn normalize_conjuncts(expr: &Expr, params: &[Value]) -> Vec<Expr> {
+    let evaluator = ExprEvaluator::new();
+    let expr = fold(push_not(bind(expr, params), false), true, &evaluator);
+    match constant_truth(&expr) {
+        Some(true) => Vec::new(),
+        Some(false) => vec![Expr::Literal(Value::Bool(false))],
+        None => merge_ranges(cnf(expr)),
+    }
+}
+
+fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+    Expr::BinaryOp {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+/// `a AND b AND ...`; `TRUE` for no operands
+fn and_all(parts: Vec<Expr>) -> Expr {
+    parts
+        .into_iter()
+        .reduce(|l, r| binary(l, BinaryOperator::And, r))
+        .unwrap_or(Expr::Literal(Value::Bool(true)))
+}
+
+/// Substitute bound parameters and expand `BETWEEN`
+fn bind(expr: &Expr, params: &[Value]) -> Expr {
+    match expr {
+        Expr::Parameter(idx) if *idx > 0 => params
+            .get(idx - 1)
+            .map_or_else(|| expr.clone(), |v| Expr::Literal(v.clone())),
+        Expr::BinaryOp { left, op, right } => {
+            binary(bind(left, params), op.clone(), bind(right, params))
+        }
+        Expr::UnaryOp { op, expr } => Expr::UnaryOp {
+            op: op.clone(),
+            expr: Box::new(bind(expr, params)),
+        },
+        Expr::Between {
+            expr,
+            low,
+            high,
+            negated,
+        } => {
+            let e = bind(expr, params);
+            let (low, high) = (bind(low, params), bind(high, params));
+            if *negated {
+                binary(
+                    binary(e.clone(), BinaryOperator::Lt, low),
+                    BinaryOperator::Or,
+                    binary(e, BinaryOperator::Gt, high),
+                )
+            } else {
+                binary(
+                    binary(e.clone(), BinaryOperator::Ge, low),
+                    BinaryOperator::And,
+                    binary(e, BinaryOperator::Le, high),
+                )
+            }
+        }
+        Expr::IsNull { expr, negated } => Expr::IsNull {
+            expr: Box::new(bind(expr, params)),
+            negated: *negated,
+        },
+        other => other.clone(),
+    }
+}
+
+fn inverse(op: &BinaryOperator) -> Option<BinaryOperator> {
+    Some(match op {
+        BinaryOperator::Eq => BinaryOperator::Ne,
+        BinaryOperator::Ne => BinaryOperator::Eq,
+        BinaryOperator::Lt => BinaryOperator::Ge,
+        BinaryOperator::Ge => BinaryOperator::Lt,
+        BinaryOperator::Gt => BinaryOperator::Le,
+        BinaryOperator::Le => BinaryOperator::Gt,
+        _ => return None,
+    })
+}
+
+/// Negation normal form: `NOT` only directly above predicates it can't be
+/// pushed into. `negate` is whether an enclosing `NOT` is being pushed.
+fn push_not(expr: Expr, negate: bool) -> Expr {
+    match expr {
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr,
+        } => push_not(*expr, !negate),
+        Expr::BinaryOp {
+            left,
+            op: op @ (BinaryOperator::And | BinaryOperator::Or),
+            right,
+        } => {
+            let op = match (op, negate) {
+                (BinaryOperator::And, true) => BinaryOperator::Or,
+                (BinaryOperator::Or, true) => BinaryOperator::And,
+                (op, _) => op,
+            };
+            binary(push_not(*left, negate), op, push_not(*right, negate))
+        }
+        Expr::BinaryOp { left, op, right } if negate => match inverse(&op) {
+            Some(op) => binary(*left, op, *right),
+            None => not(binary(*left, op, *right)),
+        },
+        Expr::IsNull { expr, negated } if negate => Expr::IsNull {
+            expr,
+            negated: !negated,
+        },
+        expr if negate => not(expr),
+        expr => expr,
+    }
+}
+
+fn not(expr: Expr) -> Expr {
+    Expr::UnaryOp {
+        op: UnaryOperator::Not,
+        expr: Box::new(expr),
+    }
+}
+
+/// Built only from literals and operators, so it evaluates the same for
+/// every row
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) => true,
+        Expr::BinaryOp { left, right, .. } => is_constant(left) && is_constant(right),
+        Expr::UnaryOp { expr, .. } => is_constant(expr),
+        Expr::IsNull { expr, .. } => is_constant(expr),
+        _ => false,
+    }
+}
+
+/// Filter truth of a folded constant: UNKNOWN counts as FALSE
+fn constant_truth(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Literal(Value::Bool(b)) => Some(*b),
+        Expr::Literal(Value::Null) => Some(false),
+        _ => None,
+    }
+}
+
+/// Fold constant subexpressions. Ones that fail to evaluate are left for
+/// execution to report. `filter` is whether `expr` is a WHERE conjunct or
+/// disjunct, where `AND` / `OR` may treat a NULL operand as FALSE.
+fn fold(expr: Expr, filter: bool, evaluator: &ExprEvaluator) -> Expr {
+    let operand = |e: Box<Expr>| Box::new(fold(*e, false, evaluator));
+    match expr {
+        Expr::Literal(_) => expr,
+        expr if is_constant(&expr) => match evaluator.eval(&expr, &SqlRow::new()) {
+            Ok(value) => Expr::Literal(value),
+            Err(_) => expr,
+        },
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } if filter => {
+            let (left, right) = (fold(*left, true, evaluator), fold(*right, true, evaluator));
+            match (constant_truth(&left), constant_truth(&right)) {
+                (Some(false), _) | (_, Some(false)) => Expr::Literal(Value::Bool(false)),
+                (Some(true), _) => right,
+                (_, Some(true)) => left,
+                _ => binary(left, BinaryOperator::And, right),
+            }
+        }
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Or,
+            right,
+        } if filter => {
+            let (left, right) = (fold(*left, true, evaluator), fold(*right, true, evaluator));
+            match (constant_truth(&left), constant_truth(&right)) {
+                (Some(true), _) | (_, Some(true)) => Expr::Literal(Value::Bool(true)),
+                (Some(false), _) => right,
+                (_, Some(false)) => left,
+                _ => binary(left, BinaryOperator::Or, right),
+            }
+        }
+        Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+            left: operand(left),
+            op,
+            right: operand(right),
+        },
+        Expr::UnaryOp { op, expr } => Expr::UnaryOp {
+            op,
+            expr: operand(expr),
+        },
+        Expr::IsNull { expr, negated } => Expr::IsNull {
+            expr: operand(expr),
+            negated,
+        },
+        Expr::In {
+            expr,
+            list,
+            negated,
+        } => Expr::In {
+            expr: operand(expr),
+            list: list
+                .into_iter()
+                .map(|e| fold(e, false, evaluator))
+                .collect(),
+            negated,
+        },
+        other => other,
+    }
+}
+
+/// Structural equality for the expression shapes normalization produces
+fn same(a: &Expr, b: &Expr) -> bool {
+    match (a, b) {
+        (Expr::Column(a), Expr::Column(b)) => a == b,
+        (Expr::Literal(a), Expr::Literal(b)) => a == b,
+        (Expr::Parameter(a), Expr::Parameter(b)) => a == b,
+        (
+            Expr::BinaryOp {
+                left: l1,
+                op: op1,
+                right: r1,
+            },
+            Expr::BinaryOp {
+                left: l2,
+                op: op2,
+                right: r2,
+            },
+        ) => op1 == op2 && same(l1, l2) && same(r1, r2),
+        (Expr::UnaryOp { op: o1, expr: e1 }, Expr::UnaryOp { op: o2, expr: e2 }) => {
+            o1 == o2 && same(e1, e2)
+        }
+        (
+            Expr::IsNull {
+                expr: e1,
+                negated: n1,
+            },
+            Expr::IsNull {
+                expr: e2,
+                negated: n2,
+            },
+        ) => n1 == n2 && same(e1, e2),
+        _ => false,
+    }
+}
+
+fn push_unique(parts: &mut Vec<Expr>, expr: Expr) {
+    if !parts.iter().any(|p| same(p, &expr)) {
+        parts.push(expr);
+    }
+}
+
+/// CNF clauses of a negation-normal-form expression, each clause as its
+/// list of disjuncts
+fn clauses(expr: Expr) -> Vec<Vec<Expr>> {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut out = clauses(*left);
+            out.extend(clauses(*right));
+            out
+        }
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Or,
+            right,
+        } => {
+            let (left, right) = (clauses(*left), clauses(*right));
+            if left.len() * right.len() > MAX_CNF_CLAUSES {
+                let or = |parts: Vec<Vec<Expr>>| and_all(parts.into_iter().map(or_all).collect());
+                return vec![vec![binary(or(left), BinaryOperator::Or, or(right))]];
+            }
+            let mut out = Vec::with_capacity(left.len() * right.len());
+            for l in &left {
+                for r in &right {
+                    let mut clause = l.clone();
+                    for e in r {
+                        push_unique(&mut clause, e.clone());
+                    }
+                    out.push(clause);
+                }
+            }
+            out
+        }
+        other => vec![vec![other]],
+    }
+}
+
+/// `a OR b OR ...` of one clause
+fn or_all(parts: Vec<Expr>) -> Expr {
+    parts
+        .into_iter()
+        .reduce(|l, r| binary(l, BinaryOperator::Or, r))
+        .unwrap_or(Expr::Literal(Value::Bool(false)))
+}
+
+fn cnf(expr: Expr) -> Vec<Expr> {
+    let mut out = Vec::new();
+    for clause in clauses(expr) {
+        push_unique(&mut out, or_all(clause));
+    }
+    out
+}
+
+/// `col <op> value` with the column on the left, from either operand order
+fn column_bound(expr: &Expr) -> Option<(&str, BinaryOperator, &Value)> {
+    let Expr::BinaryOp { left, op, right } = expr else {
+        return None;
+    };
+    let (col, op, val) = match (left.as_ref(), right.as_ref()) {
+        (Expr::Column(c), Expr::Literal(v)) => (c, op.clone(), v),
+        (Expr::Literal(v), Expr::Column(c)) => {
+            let flipped = match op {
+                BinaryOperator::Lt => BinaryOperator::Gt,
+                BinaryOperator::Le => BinaryOperator::Ge,
+                BinaryOperator::Gt => BinaryOperator::Lt,
+                BinaryOperator::Ge => BinaryOperator::Le,
+                _ => return None,
+            };
+            (c, flipped, v)
+        }
+        _ => return None,
+    };
+    match op {
+        BinaryOperator::Lt | BinaryOperator::Le | BinaryOperator::Gt | BinaryOperator::Ge
+            if !matches!(val, Value::Null) =>
+        {
+            Some((col.as_str(), op, val))
+        }
+        _ => None,
+    }
+}
+
+/// One side of a column range: value and inclusive
+type Bound = (Value, bool);
+
+/// Whether `new` excludes more than `old`; `lower` selects which end
+fn tighter(new: &Bound, old: &Bound, lower: bool) -> bool {
+    match new.0.partial_cmp(&old.0) {
+        Some(Ordering::Equal) => old.1 && !new.1,
+        Some(Ordering::Greater) => lower,
+        Some(Ordering::Less) => !lower,
+        None => false,
+    }
+}
+
+#[derive(Default)]
+struct ColumnRange {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+/// A conjunct kept as is, or the place of a column's merged range
+enum Slot {
+    Conjunct(Expr),
+    Range(usize),
+}
+
+/// Replace the single-sided comparisons on each column with its tightest
+/// range: `col > lo AND col <= hi`, or the one remaining comparison, in the
+/// position of the column's first bound
+fn merge_ranges(conjuncts: Vec<Expr>) -> Vec<Expr> {
+    let mut ranges: Vec<(String, ColumnRange)> = Vec::new();
+    let mut slots = Vec::new();
+    for conjunct in conjuncts {
+        let Some((col, op, val)) = column_bound(&conjunct) else {
+            slots.push(Slot::Conjunct(conjunct));
+            continue;
+        };
+        let pos = match ranges.iter().position(|(c, _)| c == col) {
+            Some(pos) => pos,
+            None => {
+                ranges.push((col.to_string(), ColumnRange::default()));
+                slots.push(Slot::Range(ranges.len() - 1));
+                ranges.len() - 1
+            }
+        };
+        let range = &mut ranges[pos].1;
+        let lower = matches!(op, BinaryOperator::Gt | BinaryOperator::Ge);
+        let bound = (
+            val.clone(),
+            matches!(op, BinaryOperator::Ge | BinaryOperator::Le),
+        );
+        let side = if lower {
+            &mut range.lower
+        } else {
+            &mut range.upper
+        };
+        if side.as_ref().is_none_or(|old| tighter(&bound, old, lower)) {
+            *side = Some(bound);
+        }
+    }
+
+    slots
+        .into_iter()
+        .map(|slot| match slot {
+            Slot::Conjunct(expr) => expr,
+            Slot::Range(pos) => {
+                let (col, range) = &ranges[pos];
+                let side = |(val, inclusive): &Bound, lower: bool| {
+                    let op = match (lower, inclusive) {
+                        (true, true) => BinaryOperator::Ge,
+                        (true, false) => BinaryOperator::Gt,
+                        (false, true) => BinaryOperator::Le,
+                        (false, false) => BinaryOperator::Lt,
+                    };
+                    binary(Expr::Column(col.clone()), op, Expr::Literal(val.clone()))
+                };
+                let parts = [
+                    range.lower.as_ref().map(|b| side(b, true)),
+                    range.upper.as_ref().map(|b| side(b, false)),
+                ];
+                and_all(parts.into_iter().flatten().collect())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::ast::Statement;
+    use crate::sql::{Lexer, Parser};
+
+    fn conjuncts(sql: &str, params: &[Value]) -> Vec<String> {
+        let tokens = Lexer::new(&format!("SELECT * FROM t WHERE {}", sql))
+            .tokenize()
+            .unwrap();
+        let Statement::Select { stmt, .. } = Parser::new(tokens).parse().unwrap() else {
+            panic!("Expected SELECT");
+        };
+        normalize_conjuncts(stmt.where_clause.as_ref().unwrap(), params)
+            .iter()
+            .map(show)
+            .collect()
+    }
+
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Column(c) => c.clone(),
+            Expr::Literal(Value::Integer(i)) => i.to_string(),
+            Expr::Literal(Value::Bool(b)) => b.to_string().to_uppercase(),
+            Expr::Literal(v) => format!("{:?}", v),
+            Expr::BinaryOp { left, op, right } => {
+                let op = match op {
+                    BinaryOperator::Eq => "=",
+                    BinaryOperator::Ne => "!=",
+                    BinaryOperator::Lt => "<",
+                    BinaryOperator::Le => "<=",
+                    BinaryOperator::Gt => ">",
+                    BinaryOperator::Ge => ">=",
+                    BinaryOperator::And => "AND",
+                    BinaryOperator::Or => "OR",
+                    other => panic!("unexpected {:?}", other),
+                };
+                format!("({} {} {})", show(left), op, show(right))
+            }
+            Expr::UnaryOp { expr, .. } => format!("NOT {}", show(expr)),
+            Expr::IsNull { expr, negated } => {
+                format!(
+                    "{} IS {}NULL",
+                    show(expr),
+                    if *negated { "NOT " } else { "" }
+                )
+            }
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ranges_merge_across_the_chain() {
+        assert_eq!(
+            conjuncts("a >= 1 AND a <= 9 AND b = 3", &[]),
+            ["((a >= 1) AND (a <= 9))", "(b = 3)"]
+        );
+        assert_eq!(
+            conjuncts("b = 3 AND a BETWEEN 1 AND 9 AND a > 4 AND 7 >= a", &[]),
+            ["(b = 3)", "((a > 4) AND (a <= 7))"]
+        );
+        assert_eq!(
+            conjuncts(
+                "a < 5 AND a <= 5 AND a > ? AND a >= ?",
+                &[Value::Integer(2), Value::Integer(2)]
+            ),
+            ["((a > 2) AND (a < 5))"]
+        );
+    }
+
+    #[test]
+    fn test_not_and_constants() {
+        assert_eq!(
+            conjuncts("NOT (a < 1 OR a > 9) AND 1 = 1", &[]),
+            ["((a >= 1) AND (a <= 9))"]
+        );
+        assert_eq!(
+            conjuncts("a NOT BETWEEN 1 AND 9", &[]),
+            ["((a < 1) OR (a > 9))"]
+        );
+        assert_eq!(conjuncts("a > 10 - 4 * 2", &[]), ["(a > 2)"]);
+        assert_eq!(
+            conjuncts("NOT (a IS NULL) AND (2 > 1 OR b = 1)", &[]),
+            ["a IS NOT NULL"]
+        );
+        assert_eq!(conjuncts("a = 1 AND (1 = 2 OR NULL)", &[]), ["FALSE"]);
+        assert!(conjuncts("1 = 1 OR a = 2", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_cnf() {
+        assert_eq!(
+            conjuncts("(a = 1 AND b = 2) OR (a = 1 AND c = 3)", &[]),
+            [
+                "(a = 1)",
+                "((a = 1) OR (c = 3))",
+                "((b = 2) OR (a = 1))",
+                "((b = 2) OR (c = 3))"
+            ]
+        );
+        // 5 x 5 clauses: too many, kept as one OR
+        let wide = "(a = 1 AND a = 2 AND a = 3 AND a = 4 AND a = 5) \
+                    OR (b = 1 AND b = 2 AND b = 3 AND b = 4 AND b = 5)";
+        assert_eq!(conjuncts(wide, &[]).len(), 1);
+    }
+}
@@ -12,13 +12,52 @@
 ///              ↓
 ///      Selected plan: Use status_idx, then filter by age in-memory
 /// ```
+///
+/// # Adaptive Re-optimization
+/// Index statistics are estimates and go stale as data changes. Once an index
+/// probe knows how many rows it matched, the executor reports back through
+/// [`QueryOptimizer::reoptimize_after_probe`]: the observed cardinality is
+/// folded into the cached [`IndexStats`], and if the probe matched
+/// [`REOPTIMIZE_MISESTIMATE_FACTOR`]× more rows than planned (and enough of
+/// the table that a sequential pass wins) the executor drops the index fetch
+/// and streams a scan instead.
+///
+/// # Multi-modal Predicates
+/// `MATCH`, `KNN_SEARCH` and `ST_*_3D` predicates are costed like any other
+/// index access: KNN returns an estimated k rows, spatial selectivity comes
+/// from the query box volume against the i-Octree's occupied octants, and
+/// text selectivity from the document frequency of each query term. Probing
+/// predicates evaluated row by row (MATCH, KNN) charge an index lookup per
+/// row, so a mixed-modal WHERE clause is driven by its cheapest index and the
+/// other predicates only see the surviving rows
+/// ([`QueryOptimizer::choose_driving_predicate`]).
+///
+/// # Predicate Normalization
+/// Index patterns are matched against the conjuncts of the normalized WHERE
+/// clause ([`normalize_conjuncts`]), so `a >= 1 AND a <= 9 AND b = 3`,
+/// `a BETWEEN 1 AND 9 AND b = 3` and `NOT (a < 1 OR a > 9) AND b = 3` all
+/// offer the range `[1, 9]` on `a` alongside the point lookup on `b`.
 use super::ast::*;
+use super::normalize::normalize_conjuncts;
+use crate::database::index_metadata::IndexType;
+use crate::database::indexes::column::ELEMENT_INDEX_SUFFIX;
 use crate::database::MoteDB;
-use crate::types::{TableSchema, Value};
+use crate::types::{ColumnType, TableSchema, Value};
 use crate::Result;
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// An index probe that matches this many times the estimated rows is
+/// abandoned for a streaming scan
+pub const REOPTIMIZE_MISESTIMATE_FACTOR: usize = 50;
+
+/// Index fetches stop paying off above 1 / N of the table (5%)
+const FULL_SCAN_SEL_DENOM: usize = 20;
+
+/// Observations within this factor of the estimate leave statistics alone
+const FEEDBACK_TOLERANCE: f64 = 4.0;
+
 /// Query execution plan
 #[derive(Debug, Clone)]
 pub struct QueryPlan {
@@ -84,6 +123,14 @@ pub enum ScanMethod {
         max_y: f64,
     },
 
+    /// 3D spatial query using the i-Octree index
+    /// (`ST_WITHIN_3D` / `ST_RADIUS_3D` / `ST_KNN_3D` predicate)
+    SpatialSearch {
+        table: String,
+        column: String,
+        predicate: Expr,
+    },
+
     /// Primary key index scan (ordered by primary key)
     ///
     /// Used when:
@@ -101,14 +148,38 @@ pub enum ScanMethod {
     },
 
     /// Multi-index intersection: use two column indexes and intersect row IDs.
-    /// For `WHERE col1 = v1 AND col2 = v2`, look up both indexes and take
-    /// the intersection of matching row IDs, then batch-fetch only those rows.
+    /// For `WHERE col1 = v1 AND col2 BETWEEN lo AND hi`, read the matching
+    /// row IDs from both indexes and take their intersection, then
+    /// batch-fetch only those rows.
     IndexIntersection {
         table: String,
         column1: String,
-        value1: Value,
+        probe1: IndexProbe,
         column2: String,
-        value2: Value,
+        probe2: IndexProbe,
+    },
+
+    /// Element lookup in an ARRAY column's element index:
+    /// `ARRAY_CONTAINS(col, value)`
+    ArrayContains {
+        table: String,
+        column: String,
+        value: Value,
+    },
+}
+
+/// The row IDs one column's index contributes to an
+/// [`ScanMethod::IndexIntersection`]
+#[derive(Debug, Clone)]
+pub enum IndexProbe {
+    /// `col = value`
+    Point(Value),
+    /// Bounds with the same semantics as [`ScanMethod::RangeQuery`]
+    Range {
+        start: Value,
+        start_inclusive: bool,
+        end: Value,
+        end_inclusive: bool,
     },
 }
 
@@ -121,8 +192,124 @@ impl ScanMethod {
             | ScanMethod::TextSearch { table, .. }
             | ScanMethod::VectorSearch { table, .. }
             | ScanMethod::SpatialRange { table, .. }
+            | ScanMethod::SpatialSearch { table, .. }
             | ScanMethod::PrimaryKeyScan { table, .. }
-            | ScanMethod::IndexIntersection { table, .. } => table,
+            | ScanMethod::IndexIntersection { table, .. }
+            | ScanMethod::ArrayContains { table, .. } => table,
+        }
+    }
+
+    /// One-line summary for EXPLAIN
+    pub fn describe(&self) -> String {
+        use super::physical::{fmt_expr, fmt_value};
+        fn range(
+            column: &str,
+            start: &Value,
+            start_inclusive: bool,
+            end: &Value,
+            end_inclusive: bool,
+        ) -> String {
+            format!(
+                "{}{}, {}{} on {}",
+                if start_inclusive { '[' } else { '(' },
+                fmt_value(start),
+                fmt_value(end),
+                if end_inclusive { ']' } else { ')' },
+                column
+            )
+        }
+        fn probe(column: &str, probe: &IndexProbe) -> String {
+            match probe {
+                IndexProbe::Point(value) => format!("{} = {}", column, fmt_value(value)),
+                IndexProbe::Range {
+                    start,
+                    start_inclusive,
+                    end,
+                    end_inclusive,
+                } => range(column, start, *start_inclusive, end, *end_inclusive),
+            }
+        }
+
+        match self {
+            ScanMethod::FullScan { table } => format!("full scan of {}", table),
+            ScanMethod::PointQuery {
+                table,
+                column,
+                value,
+            } => format!("index lookup {}.{} = {}", table, column, fmt_value(value)),
+            ScanMethod::RangeQuery {
+                table,
+                column,
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+            } => format!(
+                "index range {} of {}",
+                range(column, start, *start_inclusive, end, *end_inclusive),
+                table
+            ),
+            ScanMethod::TextSearch {
+                table,
+                column,
+                query,
+            } => format!("text index {}.{} MATCH '{}'", table, column, query),
+            ScanMethod::VectorSearch {
+                table, column, k, ..
+            } => format!("vector index {}.{} (k = {})", table, column, k),
+            ScanMethod::SpatialRange {
+                table,
+                column,
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            } => format!(
+                "spatial index {}.{} within ({}, {}) - ({}, {})",
+                table, column, min_x, min_y, max_x, max_y
+            ),
+            ScanMethod::SpatialSearch {
+                table,
+                column,
+                predicate,
+            } => format!("octree index {}.{}: {}", table, column, fmt_expr(predicate)),
+            ScanMethod::PrimaryKeyScan {
+                table,
+                ascending,
+                limit,
+            } => {
+                let mut out = format!(
+                    "primary key scan of {} {}",
+                    table,
+                    if *ascending { "ASC" } else { "DESC" }
+                );
+                if let Some(limit) = limit {
+                    out.push_str(&format!(" LIMIT {}", limit));
+                }
+                out
+            }
+            ScanMethod::IndexIntersection {
+                table,
+                column1,
+                probe1,
+                column2,
+                probe2,
+            } => format!(
+                "index intersection on {}: {} AND {}",
+                table,
+                probe(column1, probe1),
+                probe(column2, probe2)
+            ),
+            ScanMethod::ArrayContains {
+                table,
+                column,
+                value,
+            } => format!(
+                "array element index {}.{} contains {}",
+                table,
+                column,
+                fmt_value(value)
+            ),
         }
     }
 }
@@ -138,6 +325,8 @@ pub struct IndexStats {
     pub size_bytes: usize,
     /// Whether the index is unique
     pub is_unique: bool,
+    /// Correction applied to range estimates, learned from observed scans
+    pub range_scale: f64,
 }
 
 impl IndexStats {
@@ -161,10 +350,30 @@ impl IndexStats {
 
     /// Estimate rows for a range query
     pub fn estimate_range_query(&self, range_fraction: f64) -> usize {
-        (self.total_rows as f64 * range_fraction) as usize
+        (self.total_rows as f64 * range_fraction * self.range_scale).min(self.total_rows as f64)
+            as usize
     }
 }
 
+/// Kind of index probe an observed cardinality comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeKind {
+    Point,
+    Range,
+}
+
+/// Adaptive re-optimization and scan pruning counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizerStats {
+    /// Index probes abandoned mid-query for a streaming scan
+    pub reoptimizations: u64,
+    /// Observed cardinalities folded back into index statistics
+    pub cardinality_feedback: u64,
+    /// Rows that filtered scans skipped because a zone map (per-block
+    /// min/max) ruled out the predicate
+    pub zone_skipped_rows: u64,
+}
+
 /// Query optimizer
 pub struct QueryOptimizer {
     /// Database reference
@@ -175,6 +384,9 @@ pub struct QueryOptimizer {
 
     /// Cost model parameters
     cost_params: CostParameters,
+
+    reoptimizations: AtomicU64,
+    cardinality_feedback: AtomicU64,
 }
 
 /// Cost model parameters
@@ -207,14 +419,116 @@ impl QueryOptimizer {
             db,
             index_stats: DashMap::new(),
             cost_params: CostParameters::default(),
+            reoptimizations: AtomicU64::new(0),
+            cardinality_feedback: AtomicU64::new(0),
+        }
+    }
+
+    /// Adaptive re-optimization counters
+    pub fn stats(&self) -> OptimizerStats {
+        OptimizerStats {
+            reoptimizations: self.reoptimizations.load(Ordering::Relaxed),
+            cardinality_feedback: self.cardinality_feedback.load(Ordering::Relaxed),
+            zone_skipped_rows: self
+                .db
+                .col_segment_stores
+                .iter()
+                .map(|store| store.zone_skipped_rows())
+                .sum(),
+        }
+    }
+
+    /// Cached statistics of a column index (`"{table}.{column}"`), if planned
+    pub fn cached_index_stats(&self, index_name: &str) -> Option<IndexStats> {
+        self.index_stats.get(index_name).map(|s| s.clone())
+    }
+
+    /// Called by the executor once an index probe on `table.column` knows
+    /// how many rows it matched. Records the observation and returns true
+    /// when the plan misjudged the probe badly enough that the executor
+    /// should stream a scan instead of fetching the matched rows.
+    pub fn reoptimize_after_probe(
+        &self,
+        table: &str,
+        column: &str,
+        kind: ProbeKind,
+        estimated_rows: usize,
+        actual_rows: usize,
+    ) -> bool {
+        self.record_observed_rows(table, column, kind, estimated_rows, actual_rows);
+
+        let total_rows = self.estimate_table_size(table);
+        let tolerated = estimated_rows
+            .max(1)
+            .saturating_mul(REOPTIMIZE_MISESTIMATE_FACTOR);
+        if actual_rows <= tolerated || actual_rows < total_rows / FULL_SCAN_SEL_DENOM {
+            return false;
+        }
+        self.reoptimizations.fetch_add(1, Ordering::Relaxed);
+        debug_log!(
+            "[Optimizer] {}.{} probe matched {} rows (estimated {}), switching to scan",
+            table,
+            column,
+            actual_rows,
+            estimated_rows
+        );
+        true
+    }
+
+    /// Matched-row count past which [`Self::reoptimize_after_probe`] always
+    /// switches to a scan, so a streamed probe can stop counting there
+    pub fn reoptimize_probe_limit(&self, table: &str, estimated_rows: usize) -> usize {
+        let tolerated = estimated_rows
+            .max(1)
+            .saturating_mul(REOPTIMIZE_MISESTIMATE_FACTOR);
+        tolerated.max((self.estimate_table_size(table) / FULL_SCAN_SEL_DENOM).saturating_sub(1))
+    }
+
+    /// Fold an observed probe cardinality into the cached index statistics
+    pub fn record_observed_rows(
+        &self,
+        table: &str,
+        column: &str,
+        kind: ProbeKind,
+        estimated_rows: usize,
+        actual_rows: usize,
+    ) {
+        let ratio = actual_rows.max(1) as f64 / estimated_rows.max(1) as f64;
+        if (1.0 / FEEDBACK_TOLERANCE..=FEEDBACK_TOLERANCE).contains(&ratio) {
+            return;
         }
+        let index_name = format!("{}.{}", table, column);
+        let Ok(mut stats) = self.get_index_stats(&index_name) else {
+            return;
+        };
+        match kind {
+            ProbeKind::Point => {
+                // Average with the old per-key estimate so one skewed key
+                // doesn't dictate the plan for every other key
+                let per_key = (stats.estimate_point_query() + actual_rows).div_ceil(2);
+                stats.total_rows = self.estimate_table_size(table);
+                stats.cardinality = (stats.total_rows / per_key.max(1)).max(1);
+                stats.is_unique &= actual_rows <= 1;
+            }
+            ProbeKind::Range => {
+                stats.total_rows = self.estimate_table_size(table);
+                stats.range_scale = (stats.range_scale * ratio).clamp(1e-3, 1e3);
+            }
+        }
+        self.index_stats.insert(index_name, stats);
+        self.cardinality_feedback.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Returns a type-appropriate "positive infinity" sentinel for range bounds.
     fn positive_inf(val: &Value) -> Value {
         match val {
-            Value::Float(_) => Value::Float(f64::MAX),
+            // NaN sorts after +inf, so the open end covers every float
+            Value::Float(_) => Value::Float(f64::NAN),
             Value::Timestamp(_) => Value::Timestamp(crate::types::Timestamp::from_micros(i64::MAX)),
+            // Index keys are a 64-byte prefix; U+10FFFF encodes as the
+            // largest 4 UTF-8 bytes
+            Value::Text(_) => Value::text("\u{10FFFF}".repeat(16)),
+            Value::Uuid(_) => Value::uuid(crate::types::Uuid::from_bytes([0xFF; 16])),
             _ => Value::Integer(i64::MAX),
         }
     }
@@ -222,24 +536,54 @@ impl QueryOptimizer {
     /// Returns a type-appropriate "negative infinity" sentinel for range bounds.
     fn negative_inf(val: &Value) -> Value {
         match val {
-            Value::Float(_) => Value::Float(f64::MIN),
+            Value::Float(_) => Value::Float(f64::NEG_INFINITY),
             Value::Timestamp(_) => Value::Timestamp(crate::types::Timestamp::from_micros(i64::MIN)),
+            Value::Text(_) => Value::text(String::new()),
+            Value::Uuid(_) => Value::uuid(crate::types::Uuid::from_bytes([0; 16])),
             _ => Value::Integer(i64::MIN),
         }
     }
 
+    /// A range bound on a UUID column as a UUID. Anything that doesn't
+    /// parse (the open end of a one-sided range) becomes the smallest or
+    /// largest UUID; the WHERE clause is re-checked on fetched rows.
+    fn uuid_bound(val: Value, upper: bool) -> Value {
+        let parsed = match &val {
+            Value::Uuid(_) => return val,
+            Value::Text(s) => s.as_str().parse().ok(),
+            _ => None,
+        };
+        Value::uuid(parsed.unwrap_or_else(|| {
+            crate::types::Uuid::from_bytes(if upper { [0xFF; 16] } else { [0; 16] })
+        }))
+    }
+
+    /// A range bound on a FLOAT column. An integer literal gets integer
+    /// sentinels, which would stop short of the infinities and NaN; widen
+    /// them to the float ones.
+    fn float_bound(val: Value, upper: bool) -> Value {
+        match val {
+            Value::Integer(i64::MAX) if upper => Value::Float(f64::NAN),
+            Value::Integer(i64::MIN) if !upper => Value::Float(f64::NEG_INFINITY),
+            _ => val,
+        }
+    }
+
     /// Resolve an expression to a literal Value if possible.
     /// Handles Literal directly and Parameter(idx) via bound params.
+    /// NULL resolves to nothing: a comparison with NULL is never true, so it
+    /// must not drive an index lookup (IS NULL is planned separately).
     fn resolve_to_value(
         params: &[crate::types::Value],
         expr: &crate::sql::ast::Expr,
     ) -> Option<crate::types::Value> {
         use crate::sql::ast::Expr;
-        match expr {
+        let value = match expr {
             Expr::Literal(v) => Some(v.clone()),
             Expr::Parameter(idx) if *idx > 0 => params.get(idx - 1).cloned(),
             _ => None,
-        }
+        };
+        value.filter(|v| !matches!(v, crate::types::Value::Null))
     }
 
     /// Optimize SELECT statement and generate execution plan
@@ -286,12 +630,38 @@ impl QueryOptimizer {
             }
         };
 
+        self.optimize_where(&table_name, stmt.where_clause.as_ref(), params)
+    }
+
+    /// Choose how a DELETE locates its rows
+    ///
+    /// Same cost-based choice as a single-table SELECT (point, range or
+    /// intersected index lookups, otherwise a full scan), so a retention
+    /// `DELETE ... WHERE ts < ?` on an indexed column only reads the rows it
+    /// removes. Index plans still carry the full WHERE as a post filter.
+    pub fn optimize_delete(
+        &self,
+        stmt: &DeleteStmt,
+        params: &[crate::types::Value],
+    ) -> Result<QueryPlan> {
+        self.optimize_where(&stmt.table, stmt.where_clause.as_ref(), params)
+    }
+
+    /// Pick the cheapest access path for `where_clause` on a single table
+    fn optimize_where(
+        &self,
+        table_name: &str,
+        where_clause: Option<&Expr>,
+        params: &[crate::types::Value],
+    ) -> Result<QueryPlan> {
+        let table_name = table_name.to_string();
+
         // Get table schema for row count estimation
         let schema = self.db.get_table_schema(&table_name)?;
         let total_rows = self.estimate_table_size(&table_name);
 
         // Extract WHERE clause
-        let where_clause = match &stmt.where_clause {
+        let where_clause = match where_clause {
             Some(expr) => expr,
             None => {
                 // No WHERE clause - full table scan
@@ -351,8 +721,24 @@ impl QueryOptimizer {
             post_filters: vec![where_clause.clone()],
         });
 
-        // Analyze WHERE clause for index opportunities
-        self.analyze_where_clause(table_name, where_clause, params, &mut plans)?;
+        // Analyze each normalized conjunct for index opportunities, and each
+        // pair of them for an index intersection
+        let conjuncts = normalize_conjuncts(where_clause, params);
+        for conjunct in &conjuncts {
+            self.analyze_where_clause(table_name, conjunct, params, &mut plans)?;
+        }
+        for (i, left) in conjuncts.iter().enumerate() {
+            for right in &conjuncts[i + 1..] {
+                self.try_index_intersection(table_name, left, right, params, &mut plans)?;
+            }
+        }
+
+        // Probing predicates not served by the plan's index run per fetched row
+        let probes = Self::count_index_probes(where_clause);
+        for plan in &mut plans {
+            let residual = probes.saturating_sub(Self::plan_probe_count(&plan.scan_method));
+            plan.estimated_cost += self.probe_cost(plan.estimated_rows, residual);
+        }
 
         // Ensure all index plans carry the full WHERE clause as post_filter.
         // For simple predicates (e.g., `col = 5`) the index scan covers the full
@@ -408,17 +794,12 @@ impl QueryOptimizer {
                 self.try_index_intersection(table_name, left, right, params, plans)?;
             }
 
-            // OR: Must evaluate all branches
+            // OR: a probe for one branch would miss the other's rows, and
+            // there is no union plan, so it's left to the scan
             Expr::BinaryOp {
-                left,
                 op: BinaryOperator::Or,
-                right,
-            } => {
-                // ORs typically can't use indexes efficiently
-                // Just analyze for completeness
-                self.analyze_where_clause(table_name, left, params, plans)?;
-                self.analyze_where_clause(table_name, right, params, plans)?;
-            }
+                ..
+            } => {}
 
             // Point query: col = value (supports Literal AND Parameter)
             Expr::BinaryOp {
@@ -577,6 +958,38 @@ impl QueryOptimizer {
                 }
             }
 
+            // NULL bucket of a column index: col IS NULL
+            Expr::IsNull {
+                expr: inner,
+                negated: false,
+            } => {
+                if let Expr::Column(col) = inner.as_ref() {
+                    self.try_point_query_plan(table_name, col, Value::Null, plans)?;
+                }
+            }
+
+            // Element lookup: ARRAY_CONTAINS(col, value)
+            Expr::FunctionCall { name, args, .. }
+                if name.eq_ignore_ascii_case("array_contains") && args.len() == 2 =>
+            {
+                if let (Expr::Column(col), Some(val)) =
+                    (&args[0], Self::resolve_to_value(params, &args[1]))
+                {
+                    self.try_array_contains_plan(table_name, col, val, plans)?;
+                }
+            }
+
+            // Text / vector / spatial predicates
+            Expr::Match { .. }
+            | Expr::KnnSearch { .. }
+            | Expr::StWithin3D { .. }
+            | Expr::StKnn3D { .. }
+            | Expr::StRadius3D { .. } => {
+                if let Some(plan) = self.try_multimodal_plan(table_name, expr)? {
+                    plans.push(plan);
+                }
+            }
+
             _ => {
                 // Other expressions: no index optimization
             }
@@ -585,6 +998,219 @@ impl QueryOptimizer {
         Ok(())
     }
 
+    /// Pick the conjunct whose index should drive a conjunctive WHERE clause.
+    ///
+    /// Every conjunct is costed on its own, plus the per-row index probes the
+    /// remaining conjuncts would cost on the rows it fetches. Returns the
+    /// position of the winning conjunct and its plan, or `None` when a full
+    /// scan is cheapest.
+    pub fn choose_driving_predicate(
+        &self,
+        table_name: &str,
+        conjuncts: &[Expr],
+        params: &[Value],
+    ) -> Result<Option<(usize, QueryPlan)>> {
+        let total_rows = self.estimate_table_size(table_name);
+        let probes: usize = conjuncts.iter().map(Self::count_index_probes).sum();
+
+        let mut best_cost = self.cost_full_scan(total_rows) + self.probe_cost(total_rows, probes);
+        let mut best = None;
+        for (pos, conjunct) in conjuncts.iter().enumerate() {
+            let mut plans = Vec::new();
+            self.analyze_where_clause(table_name, conjunct, params, &mut plans)?;
+            let residual = probes.saturating_sub(Self::count_index_probes(conjunct));
+            for mut plan in plans {
+                plan.estimated_cost += self.probe_cost(plan.estimated_rows, residual);
+                if plan.estimated_cost < best_cost {
+                    best_cost = plan.estimated_cost;
+                    best = Some((pos, plan));
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// Split a WHERE clause into its top-level AND operands
+    pub fn conjuncts(expr: &Expr) -> Vec<Expr> {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                let mut parts = Self::conjuncts(left);
+                parts.extend(Self::conjuncts(right));
+                parts
+            }
+            other => vec![other.clone()],
+        }
+    }
+
+    /// Number of predicates that need an index probe for every row they are
+    /// evaluated on (MATCH, KNN_SEARCH, ST_KNN_3D)
+    fn count_index_probes(expr: &Expr) -> usize {
+        match expr {
+            Expr::Match { .. } | Expr::KnnSearch { .. } | Expr::StKnn3D { .. } => 1,
+            Expr::BinaryOp { left, right, .. } => {
+                Self::count_index_probes(left) + Self::count_index_probes(right)
+            }
+            Expr::UnaryOp { expr, .. } => Self::count_index_probes(expr),
+            _ => 0,
+        }
+    }
+
+    /// Probing predicates answered by the plan's own index
+    fn plan_probe_count(scan_method: &ScanMethod) -> usize {
+        match scan_method {
+            ScanMethod::TextSearch { .. } | ScanMethod::VectorSearch { .. } => 1,
+            ScanMethod::SpatialSearch { predicate, .. } => Self::count_index_probes(predicate),
+            _ => 0,
+        }
+    }
+
+    fn probe_cost(&self, rows: usize, probes: usize) -> f64 {
+        rows as f64 * probes as f64 * self.cost_params.index_lookup_cost
+    }
+
+    /// Plan a MATCH / KNN_SEARCH / ST_*_3D predicate through its index
+    fn try_multimodal_plan(&self, table_name: &str, expr: &Expr) -> Result<Option<QueryPlan>> {
+        let (scan_method, estimated_rows) = match expr {
+            Expr::Match {
+                column,
+                query,
+                phrase,
+            } => {
+                let Some(index) = self.find_index(table_name, column, IndexType::Text) else {
+                    return Ok(None);
+                };
+                let rows = self.estimate_text_rows(&index, query, *phrase)?;
+                let scan = ScanMethod::TextSearch {
+                    table: table_name.to_string(),
+                    column: column.clone(),
+                    query: query.clone(),
+                };
+                (scan, rows)
+            }
+            Expr::KnnSearch {
+                column,
+                query_vector,
+                k,
+            } => {
+                let Some(index) = self.find_index(table_name, column, IndexType::Vector) else {
+                    return Ok(None);
+                };
+                // Estimated k: the index can't return more vectors than it holds
+                let total = self.db.vector_index_stats(&index)?.total_vectors;
+                let scan = ScanMethod::VectorSearch {
+                    table: table_name.to_string(),
+                    column: column.clone(),
+                    query_vector: query_vector.clone(),
+                    k: *k,
+                };
+                (scan, (*k).min(total))
+            }
+            Expr::StWithin3D {
+                column,
+                min_x,
+                min_y,
+                min_z,
+                max_x,
+                max_y,
+                max_z,
+            } => {
+                let Some(index) = self.find_index(table_name, column, IndexType::Octree) else {
+                    return Ok(None);
+                };
+                let bbox = crate::types::BoundingBox3D::new(
+                    *min_x, *min_y, *min_z, *max_x, *max_y, *max_z,
+                );
+                (
+                    self.spatial_scan(table_name, column, expr),
+                    self.db.ioctree_estimate_range(&index, &bbox)?,
+                )
+            }
+            Expr::StRadius3D {
+                column,
+                x,
+                y,
+                z,
+                radius,
+            } => {
+                let Some(index) = self.find_index(table_name, column, IndexType::Octree) else {
+                    return Ok(None);
+                };
+                let bbox = crate::types::BoundingBox3D::new(
+                    x - radius,
+                    y - radius,
+                    z - radius,
+                    x + radius,
+                    y + radius,
+                    z + radius,
+                );
+                // The sphere fills π/6 of its bounding cube
+                let in_cube = self.db.ioctree_estimate_range(&index, &bbox)? as f64;
+                let rows = (in_cube * std::f64::consts::PI / 6.0).ceil() as usize;
+                (self.spatial_scan(table_name, column, expr), rows)
+            }
+            Expr::StKnn3D { column, k, .. } => {
+                let Some(index) = self.find_index(table_name, column, IndexType::Octree) else {
+                    return Ok(None);
+                };
+                let total = self.db.ioctree_point_count(&index)?;
+                (self.spatial_scan(table_name, column, expr), (*k).min(total))
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(QueryPlan {
+            scan_method,
+            estimated_cost: self.cost_params.index_lookup_cost
+                + (estimated_rows as f64 * self.cost_params.lsm_point_read_cost),
+            estimated_rows,
+            post_filters: vec![],
+        }))
+    }
+
+    fn spatial_scan(&self, table_name: &str, column: &str, predicate: &Expr) -> ScanMethod {
+        ScanMethod::SpatialSearch {
+            table: table_name.to_string(),
+            column: column.to_string(),
+            predicate: predicate.clone(),
+        }
+    }
+
+    /// Name of a loaded text / vector / octree index on `table.column`
+    fn find_index(&self, table_name: &str, column: &str, index_type: IndexType) -> Option<String> {
+        let name = self
+            .db
+            .index_registry
+            .find_by_column(table_name, column, index_type.clone())?;
+        let loaded = match index_type {
+            IndexType::Text => self.db.text_indexes.contains_key(&name),
+            IndexType::Vector => self.db.has_vector_index(&name),
+            IndexType::Octree => self.db.ioctree_indexes.contains_key(&name),
+            IndexType::Column => false,
+        };
+        loaded.then_some(name)
+    }
+
+    /// Term-frequency text selectivity, treating terms as independent: a
+    /// ranked MATCH hits documents holding any query term, a phrase needs all
+    fn estimate_text_rows(&self, index_name: &str, query: &str, phrase: bool) -> Result<usize> {
+        let total_docs = self.db.text_index_stats(index_name)?.total_docs as f64;
+        let freqs = self.db.text_term_doc_frequencies(index_name, query)?;
+        if total_docs == 0.0 || freqs.is_empty() {
+            return Ok(0);
+        }
+        let fractions = freqs.iter().map(|&df| (df as f64 / total_docs).min(1.0));
+        let selectivity = if phrase {
+            fractions.product()
+        } else {
+            1.0 - fractions.map(|f| 1.0 - f).product::<f64>()
+        };
+        Ok((total_docs * selectivity).round() as usize)
+    }
+
     /// Try to create a point query plan if index exists
     fn try_point_query_plan(
         &self,
@@ -607,7 +1233,7 @@ impl QueryOptimizer {
             })
             .unwrap_or(false);
 
-        if is_auto_increment_pk {
+        if is_auto_increment_pk && !matches!(value, Value::Null) {
             // Direct LSM get: O(1) cost, exactly 1 estimated row
             plans.push(QueryPlan {
                 scan_method: ScanMethod::PointQuery {
@@ -622,9 +1248,10 @@ impl QueryOptimizer {
             return Ok(());
         }
 
-        // Check if column index exists
-        if !self.db.column_indexes.contains_key(&index_name) {
-            return Ok(()); // No index available
+        // Check if column index exists (element indexes hold no NULLs)
+        match self.db.column_indexes.get(&index_name) {
+            Some(index) if !(index.is_multi_key() && matches!(value, Value::Null)) => {}
+            _ => return Ok(()), // No index available
         }
 
         // Get or estimate index statistics
@@ -635,10 +1262,9 @@ impl QueryOptimizer {
         // Above this, FullScan (single sequential pass) is cheaper than
         // individual LSM point lookups for each matching row.
         // Also respects a minimum threshold to avoid rejecting PointQuery for tiny tables.
-        const PQ_SEL_DENOM: usize = 20; // 1/20 = 5% threshold
         const MIN_EST_FOR_FULLSCAN: usize = 10; // always accept PointQuery for <10 estimated rows
         if stats.total_rows > 0
-            && estimated_rows >= stats.total_rows / PQ_SEL_DENOM
+            && estimated_rows >= stats.total_rows / FULL_SCAN_SEL_DENOM
             && estimated_rows >= MIN_EST_FOR_FULLSCAN
         {
             return Ok(());
@@ -662,6 +1288,58 @@ impl QueryOptimizer {
         Ok(())
     }
 
+    /// Try to create an element lookup plan if the ARRAY column has an index.
+    /// The WHERE clause is re-checked on fetched rows (long text keys are
+    /// truncated in the index).
+    fn try_array_contains_plan(
+        &self,
+        table_name: &str,
+        column: &str,
+        value: Value,
+        plans: &mut Vec<QueryPlan>,
+    ) -> Result<()> {
+        let index_name = format!("{}.{}{}", table_name, column, ELEMENT_INDEX_SUFFIX);
+        if !self.db.column_indexes.contains_key(&index_name) {
+            return Ok(());
+        }
+        let elem_type = self
+            .db
+            .table_registry
+            .get_table(table_name)
+            .ok()
+            .and_then(|schema| match &schema.get_column(column)?.col_type {
+                ColumnType::Array(elem) => Some((**elem).clone()),
+                _ => None,
+            });
+        // Key the literal like the stored elements; NULL never matches
+        let value = match (elem_type, value) {
+            (_, Value::Null) | (None, _) => return Ok(()),
+            (Some(ColumnType::Float), Value::Integer(i)) => Value::Float(i as f64),
+            (Some(ColumnType::Integer), Value::Float(f)) if f.fract() == 0.0 => {
+                Value::Integer(f as i64)
+            }
+            (_, value) => value,
+        };
+
+        let stats = self.get_index_stats(&index_name)?;
+        let estimated_rows = stats.estimate_point_query();
+        let cost = self.cost_params.index_lookup_cost
+            + (estimated_rows as f64 * self.cost_params.lsm_point_read_cost);
+
+        plans.push(QueryPlan {
+            scan_method: ScanMethod::ArrayContains {
+                table: table_name.to_string(),
+                column: column.to_string(),
+                value,
+            },
+            estimated_cost: cost,
+            estimated_rows,
+            post_filters: vec![],
+        });
+
+        Ok(())
+    }
+
     /// Try to create a range query plan if index exists
     ///
     /// ## 边界语义
@@ -689,7 +1367,7 @@ impl QueryOptimizer {
         let stats = self.get_index_stats(&index_name)?;
 
         // Estimate range selectivity from value bounds
-        let range_fraction = Self::estimate_range_fraction(&start, &end);
+        let (start, end, range_fraction) = self.index_range(table_name, column, start, end);
         let estimated_rows = stats.estimate_range_query(range_fraction);
 
         // Calculate cost: index range scan + row fetch
@@ -713,100 +1391,176 @@ impl QueryOptimizer {
         Ok(())
     }
 
-    /// Extract range query pattern from WHERE clause
-    ///
-    /// ## 返回格式
-    /// `Some((column_name, start_value, start_inclusive, end_value, end_inclusive))`
-    ///
-    /// ## 示例
-    /// - `id >= 100 AND id < 200` → `("id", 100, true, 200, false)`
-    /// - `id > 100 AND id <= 200` → `("id", 100, false, 200, true)`
-    /// Try to create an index intersection plan for `AND` conditions.
-    /// If both sides of AND are simple `col = value` with column indexes,
-    /// intersect the row IDs from both indexes to reduce the result set.
+    /// Range bounds keyed like `column`'s index, with the fraction of rows
+    /// they are estimated to cover
+    fn index_range(
+        &self,
+        table_name: &str,
+        column: &str,
+        start: Value,
+        end: Value,
+    ) -> (Value, Value, f64) {
+        // UUID literals arrive as text, so a one-sided range gets text
+        // sentinels; key both bounds as UUIDs
+        let col_type = self
+            .db
+            .table_registry
+            .get_table(table_name)
+            .ok()
+            .and_then(|schema| schema.get_column(column).map(|c| c.col_type.clone()));
+        let (start, end) = match col_type {
+            Some(ColumnType::Uuid) => (Self::uuid_bound(start, false), Self::uuid_bound(end, true)),
+            _ => (start, end),
+        };
+        let range_fraction = Self::estimate_range_fraction(&start, &end);
+        let (start, end) = match col_type {
+            Some(ColumnType::Float) => (
+                Self::float_bound(start, false),
+                Self::float_bound(end, true),
+            ),
+            _ => (start, end),
+        };
+        (start, end, range_fraction)
+    }
+
+    /// Try to create an index intersection plan for two AND-ed conditions.
+    /// Each side is a point (`col = v`) or a range (`col > v`,
+    /// `col BETWEEN lo AND hi`) on its own indexed column; both row ID sets
+    /// are read from the indexes and intersected before any row is fetched,
+    /// instead of fetching every row one index matches and filtering.
     fn try_index_intersection(
         &self,
         table_name: &str,
         left: &Expr,
         right: &Expr,
-        _params: &[crate::types::Value],
+        params: &[crate::types::Value],
         plans: &mut Vec<QueryPlan>,
     ) -> Result<()> {
-        // Extract (column, value) from both sides of AND
-        let left_cv = Self::extract_eq_column_value(left);
-        let right_cv = Self::extract_eq_column_value(right);
-
-        if let (Some((col1, val1)), Some((col2, val2))) = (left_cv, right_cv) {
-            // Both sides are simple col = value — check for indexes on both
-            let idx1 = format!("{}.{}", table_name, col1);
-            let idx2 = format!("{}.{}", table_name, col2);
-
-            if col1 != col2
-                && self.db.column_indexes.contains_key(&idx1)
-                && self.db.column_indexes.contains_key(&idx2)
-            {
-                // Estimate: intersection is roughly the product of selectivities
-                let stats1 = self.get_index_stats(&idx1).unwrap_or(IndexStats {
-                    cardinality: 100,
-                    total_rows: 10000,
-                    size_bytes: 0,
-                    is_unique: false,
-                });
-                let stats2 = self.get_index_stats(&idx2).unwrap_or(IndexStats {
-                    cardinality: 100,
-                    total_rows: 10000,
-                    size_bytes: 0,
-                    is_unique: false,
-                });
+        let (Some((col1, probe1)), Some((col2, probe2))) = (
+            self.extract_index_probe(table_name, left, params),
+            self.extract_index_probe(table_name, right, params),
+        ) else {
+            return Ok(());
+        };
+        let idx1 = format!("{}.{}", table_name, col1);
+        let idx2 = format!("{}.{}", table_name, col2);
+        if col1 == col2
+            || !self.db.column_indexes.contains_key(&idx1)
+            || !self.db.column_indexes.contains_key(&idx2)
+        {
+            return Ok(());
+        }
 
-                let sel1 = stats1.selectivity();
-                let sel2 = stats2.selectivity();
-                let combined_sel = sel1 * sel2;
-                let estimated_rows = ((stats1.total_rows as f64) * combined_sel).max(1.0) as usize;
-
-                // Cost: two index lookups + intersection + row fetch
-                let cost = self.cost_params.index_lookup_cost * 2.0
-                    + (estimated_rows as f64 * self.cost_params.lsm_point_read_cost);
-
-                // Only use intersection if it's cheaper than a single index + full scan
-                // Heuristic: intersection estimated_rows < total_rows * 0.3
-                if estimated_rows < stats1.total_rows / 3 {
-                    plans.push(QueryPlan {
-                        scan_method: ScanMethod::IndexIntersection {
-                            table: table_name.to_string(),
-                            column1: col1,
-                            value1: val1,
-                            column2: col2,
-                            value2: val2,
-                        },
-                        estimated_cost: cost,
-                        estimated_rows,
-                        post_filters: vec![],
-                    });
-                }
-            }
+        // Estimate: intersection is roughly the product of selectivities
+        let stats1 = self.get_index_stats(&idx1)?;
+        let stats2 = self.get_index_stats(&idx2)?;
+        let rows1 = Self::estimate_probe_rows(&stats1, &probe1);
+        let rows2 = Self::estimate_probe_rows(&stats2, &probe2);
+        let total_rows = stats1.total_rows.max(1) as f64;
+        let combined_sel = (rows1 as f64 / total_rows) * (rows2 as f64 / total_rows);
+        let estimated_rows = (total_rows * combined_sel).max(1.0) as usize;
+
+        // Cost: two index lookups + reading both row ID sets + row fetch
+        let cost = self.cost_params.index_lookup_cost * 2.0
+            + self.cost_params.index_lookup_cost * ((rows1 + rows2) as f64 * 0.1)
+            + (estimated_rows as f64 * self.cost_params.lsm_point_read_cost);
+
+        // Only use intersection if it's cheaper than a single index + full scan
+        // Heuristic: intersection estimated_rows < total_rows * 0.3
+        if estimated_rows < stats1.total_rows / 3 {
+            plans.push(QueryPlan {
+                scan_method: ScanMethod::IndexIntersection {
+                    table: table_name.to_string(),
+                    column1: col1,
+                    probe1,
+                    column2: col2,
+                    probe2,
+                },
+                estimated_cost: cost,
+                estimated_rows,
+                post_filters: vec![],
+            });
         }
 
         Ok(())
     }
 
-    /// Extract (column_name, value) from a simple `col = literal` expression.
-    fn extract_eq_column_value(expr: &Expr) -> Option<(String, Value)> {
-        if let Expr::BinaryOp {
-            left,
-            op: BinaryOperator::Eq,
-            right,
-        } = expr
-        {
-            if let Expr::Column(col) = left.as_ref() {
-                if let Expr::Literal(val) = right.as_ref() {
-                    return Some((col.clone(), val.clone()));
-                }
+    /// Estimated rows one side of an index intersection selects
+    fn estimate_probe_rows(stats: &IndexStats, probe: &IndexProbe) -> usize {
+        match probe {
+            IndexProbe::Point(_) => stats.estimate_point_query(),
+            IndexProbe::Range { start, end, .. } => {
+                stats.estimate_range_query(Self::estimate_range_fraction(start, end))
             }
         }
-        None
     }
 
+    /// The index probe one conjunct offers: `col = v`, a one-sided
+    /// comparison, or a two-sided range on one column (supports Literal and
+    /// Parameter). Range bounds are keyed like the column's index.
+    fn extract_index_probe(
+        &self,
+        table_name: &str,
+        expr: &Expr,
+        params: &[crate::types::Value],
+    ) -> Option<(String, IndexProbe)> {
+        let (column, start, start_inclusive, end, end_inclusive) =
+            match self.try_extract_range_query(expr, params) {
+                Some(range) => range,
+                None => {
+                    let Expr::BinaryOp { left, op, right } = expr else {
+                        return None;
+                    };
+                    // Read `v < col` as `col > v`
+                    let (column, op, val) = match (left.as_ref(), right.as_ref()) {
+                        (Expr::Column(c), other) => {
+                            (c, op.clone(), Self::resolve_to_value(params, other)?)
+                        }
+                        (other, Expr::Column(c)) => {
+                            let op = match op {
+                                BinaryOperator::Lt => BinaryOperator::Gt,
+                                BinaryOperator::Le => BinaryOperator::Ge,
+                                BinaryOperator::Gt => BinaryOperator::Lt,
+                                BinaryOperator::Ge => BinaryOperator::Le,
+                                op => op.clone(),
+                            };
+                            (c, op, Self::resolve_to_value(params, other)?)
+                        }
+                        _ => return None,
+                    };
+                    let (lo, hi) = (Self::negative_inf(&val), Self::positive_inf(&val));
+                    match op {
+                        BinaryOperator::Eq => {
+                            return Some((column.clone(), IndexProbe::Point(val)));
+                        }
+                        BinaryOperator::Gt => (column.clone(), val, false, hi, true),
+                        BinaryOperator::Ge => (column.clone(), val, true, hi, true),
+                        BinaryOperator::Lt => (column.clone(), lo, true, val, false),
+                        BinaryOperator::Le => (column.clone(), lo, true, val, true),
+                        _ => return None,
+                    }
+                }
+            };
+        let (start, end, _) = self.index_range(table_name, &column, start, end);
+        Some((
+            column,
+            IndexProbe::Range {
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+            },
+        ))
+    }
+
+    /// Extract range query pattern from WHERE clause
+    ///
+    /// ## 返回格式
+    /// `Some((column_name, start_value, start_inclusive, end_value, end_inclusive))`
+    ///
+    /// ## 示例
+    /// - `id >= 100 AND id < 200` → `("id", 100, true, 200, false)`
+    /// - `id > 100 AND id <= 200` → `("id", 100, false, 200, true)`
     fn try_extract_range_query(
         &self,
         expr: &Expr,
@@ -1002,6 +1756,7 @@ impl QueryOptimizer {
             total_rows: table_rows,
             size_bytes: cardinality * 64,
             is_unique: false,
+            range_scale: 1.0,
         };
 
         self.index_stats
@@ -1009,11 +1764,12 @@ impl QueryOptimizer {
         Ok(stats)
     }
 
-    /// Estimate table size from LSM metadata
+    /// Estimate table size from storage metadata: rows in column segments
+    /// and write buffers as well as LSM entries
     fn estimate_table_size(&self, table_name: &str) -> usize {
         self.db
-            .estimate_table_row_count(table_name)
-            .unwrap_or(1_000)
+            .estimate_row_count(table_name)
+            .map_or(1_000, |rows| rows as usize)
             .max(1) // Floor of 1 to avoid cost=0 for FullScan
     }
 
@@ -1040,6 +1796,10 @@ impl QueryOptimizer {
             }
             (Value::Float(s), Value::Float(e)) => {
                 let range = (e - s).abs();
+                if range.is_nan() {
+                    // A NaN bound is the open end of a one-sided range
+                    return 0.5;
+                }
                 // Heuristic: assume float domain ~[-1e6, +1e6]
                 ((range / 2_000_000.0) * 2.0).clamp(0.001, 0.5)
             }
@@ -1066,11 +1826,350 @@ mod tests {
             total_rows: 10000,
             size_bytes: 100_000,
             is_unique: false,
+            range_scale: 1.0,
         };
 
         assert_eq!(stats.selectivity(), 0.001);
         assert_eq!(stats.estimate_point_query(), 10);
         assert_eq!(stats.estimate_range_query(0.1), 1000);
+
+        let corrected = IndexStats {
+            range_scale: 20.0,
+            ..stats
+        };
+        assert_eq!(corrected.estimate_range_query(0.1), 10000);
+    }
+
+    fn parse(sql: &str) -> Statement {
+        let tokens = crate::sql::Lexer::new(sql).tokenize().unwrap();
+        crate::sql::Parser::new(tokens).parse().unwrap()
+    }
+
+    fn where_clause(sql: &str) -> Expr {
+        match parse(sql) {
+            Statement::Select { stmt, .. } => stmt.where_clause.unwrap(),
+            _ => panic!("Expected SELECT"),
+        }
+    }
+
+    #[test]
+    fn test_multimodal_cardinality_estimates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+        let executor = crate::sql::QueryExecutor::new(db.clone());
+        let run = |sql: &str| {
+            executor.execute(parse(sql)).unwrap();
+        };
+        run("CREATE TABLE docs (id INTEGER PRIMARY KEY, body TEXT, pt GEOMETRY, emb VECTOR(4))");
+        for i in 0..200i64 {
+            let body = match i {
+                _ if i % 10 == 0 => "robot lidar",
+                _ if i % 2 == 0 => "robot arm sensor",
+                _ => "robot wheel",
+            };
+            let f = i as f64;
+            run(&format!(
+                "INSERT INTO docs VALUES ({}, '{}', POINT3D({}, {}, {}), [{}, 0.5, 0.25, 1.0])",
+                i, body, f, f, f, f
+            ));
+        }
+        run("CREATE TEXT INDEX docs_body ON docs(body)");
+        run("CREATE OCTREE INDEX docs_pt ON docs(pt)");
+        run("CREATE VECTOR INDEX docs_emb ON docs(emb)");
+
+        let optimizer = QueryOptimizer::new(db);
+        let estimate = |sql: &str| {
+            optimizer
+                .try_multimodal_plan("docs", &where_clause(sql))
+                .unwrap()
+                .unwrap()
+                .estimated_rows
+        };
+
+        // Term frequency: any term for ranked MATCH, all terms for a phrase
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE MATCH(body) AGAINST('robot')"),
+            200
+        );
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE MATCH(body) AGAINST('lidar')"),
+            20
+        );
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE MATCH(body) AGAINST('lidar wheel')"),
+            110
+        );
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE MATCH(body) AGAINST('\"robot lidar\"')"),
+            20
+        );
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE MATCH(body) AGAINST('drone')"),
+            0
+        );
+
+        // Estimated k, capped by the index size
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE KNN_SEARCH(emb, [1.0, 0.5, 0.25, 1.0], 5)"),
+            5
+        );
+        assert_eq!(
+            estimate("SELECT * FROM docs WHERE ST_KNN_3D(pt, 0, 0, 0, 500)"),
+            200
+        );
+
+        // Box volume against occupied octants
+        let everything = "SELECT * FROM docs WHERE ST_WITHIN_3D(pt, 0, 0, 0, 300, 300, 300)";
+        assert_eq!(estimate(everything), 200);
+        let corner = estimate("SELECT * FROM docs WHERE ST_WITHIN_3D(pt, 0, 0, 0, 9.5, 9.5, 9.5)");
+        assert!((1..=40).contains(&corner), "corner estimate {}", corner);
+        let radius = estimate("SELECT * FROM docs WHERE ST_RADIUS_3D(pt, 0, 0, 0, 9.5)");
+        assert!(radius <= corner, "radius {} > box {}", radius, corner);
+
+        // Mixed-modal WHERE: the most selective index drives
+        let choose = |sql: &str| {
+            let conjuncts = QueryOptimizer::conjuncts(&where_clause(sql));
+            optimizer
+                .choose_driving_predicate("docs", &conjuncts, &[])
+                .unwrap()
+                .map(|(pos, plan)| (pos, plan.scan_method))
+        };
+        let spatial_first = choose(
+            "SELECT * FROM docs WHERE MATCH(body) AGAINST('robot') \
+             AND ST_WITHIN_3D(pt, 0, 0, 0, 9.5, 9.5, 9.5)",
+        );
+        assert!(
+            matches!(spatial_first, Some((1, ScanMethod::SpatialSearch { .. }))),
+            "{:?}",
+            spatial_first
+        );
+        let text_first = choose(
+            "SELECT * FROM docs WHERE ST_WITHIN_3D(pt, 0, 0, 0, 300, 300, 300) \
+             AND MATCH(body) AGAINST('lidar')",
+        );
+        assert!(
+            matches!(text_first, Some((1, ScanMethod::TextSearch { .. }))),
+            "{:?}",
+            text_first
+        );
+        let vector_first = choose(
+            "SELECT * FROM docs WHERE MATCH(body) AGAINST('robot') \
+             AND KNN_SEARCH(emb, [1.0, 0.5, 0.25, 1.0], 5)",
+        );
+        assert!(
+            matches!(
+                vector_first,
+                Some((1, ScanMethod::VectorSearch { k: 5, .. }))
+            ),
+            "{:?}",
+            vector_first
+        );
+    }
+
+    #[test]
+    fn test_normalized_ranges_reach_the_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+        let executor = crate::sql::QueryExecutor::new(db.clone());
+        let run = |sql: &str| {
+            executor.execute(parse(sql)).unwrap();
+        };
+        run("CREATE TABLE t (id INTEGER PRIMARY KEY, a INTEGER, b INTEGER)");
+        for i in 0..1000i64 {
+            run(&format!("INSERT INTO t VALUES ({}, {}, {})", i, i, i % 7));
+        }
+        run("CREATE INDEX t_a ON t(a)");
+        db.wait_for_indexes_ready();
+
+        let optimizer = QueryOptimizer::new(db);
+        let plan = |sql: &str, params: &[Value]| {
+            let Statement::Select { stmt, .. } = parse(sql) else {
+                panic!("Expected SELECT");
+            };
+            optimizer.optimize_select(&stmt, params).unwrap()
+        };
+        let range = |sql: &str, params: &[Value]| match plan(sql, params).scan_method {
+            ScanMethod::RangeQuery {
+                column,
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+                ..
+            } => (column, start, start_inclusive, end, end_inclusive),
+            other => panic!("{}: {:?}", sql, other),
+        };
+        let a_1_to_9 = (
+            "a".to_string(),
+            Value::Integer(1),
+            true,
+            Value::Integer(9),
+            true,
+        );
+        for sql in [
+            "SELECT * FROM t WHERE a >= 1 AND a <= 9 AND b = 3",
+            "SELECT * FROM t WHERE b = 3 AND a >= 1 AND a <= 9",
+            "SELECT * FROM t WHERE a BETWEEN 1 AND 9 AND b = 3",
+            "SELECT * FROM t WHERE b = 3 AND NOT (a < 1 OR a > 9)",
+            "SELECT * FROM t WHERE a >= 0 + 1 AND a <= 3 * 3 AND 1 = 1",
+        ] {
+            assert_eq!(range(sql, &[]), a_1_to_9, "{}", sql);
+        }
+        assert_eq!(
+            range(
+                "SELECT * FROM t WHERE a BETWEEN ? AND ? AND b = 3",
+                &[Value::Integer(1), Value::Integer(9)]
+            ),
+            a_1_to_9
+        );
+        assert_eq!(
+            range(
+                "SELECT * FROM t WHERE a > 2 AND a BETWEEN 1 AND 9 AND a < 5",
+                &[]
+            ),
+            (
+                "a".to_string(),
+                Value::Integer(2),
+                false,
+                Value::Integer(5),
+                false
+            )
+        );
+
+        // Only one branch of an OR would be probed: no index plan
+        let or = plan("SELECT * FROM t WHERE a = 1 OR b = 2", &[]);
+        assert!(
+            matches!(or.scan_method, ScanMethod::FullScan { .. }),
+            "{:?}",
+            or.scan_method
+        );
+        let factored = plan(
+            "SELECT * FROM t WHERE (a = 1 AND b = 2) OR (a = 1 AND b = 3)",
+            &[],
+        );
+        assert!(
+            matches!(
+                factored.scan_method,
+                ScanMethod::PointQuery { ref column, .. } if column == "a"
+            ),
+            "{:?}",
+            factored.scan_method
+        );
+    }
+
+    #[test]
+    fn test_point_and_range_indexes_intersect() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+        let executor = crate::sql::QueryExecutor::new(db.clone());
+        let run = |sql: &str| {
+            executor.execute(parse(sql)).unwrap();
+        };
+        run("CREATE TABLE logs (id INTEGER PRIMARY KEY, status TEXT, ts INTEGER)");
+        for i in 0..2000i64 {
+            run(&format!(
+                "INSERT INTO logs VALUES ({}, 's{}', {})",
+                i,
+                i % 40,
+                i * 1_000_000
+            ));
+        }
+        db.flush().unwrap();
+        run("CREATE INDEX logs_status ON logs(status)");
+        run("CREATE INDEX logs_ts ON logs(ts)");
+        db.wait_for_indexes_ready();
+
+        // 40 statuses, as a probe would have taught the optimizer
+        let optimizer = QueryOptimizer::new(db);
+        let status = optimizer.get_index_stats("logs.status").unwrap();
+        optimizer.index_stats.insert(
+            "logs.status".to_string(),
+            IndexStats {
+                cardinality: 40,
+                ..status
+            },
+        );
+        let probes = |sql: &str, params: &[Value]| {
+            let Statement::Select { stmt, .. } = parse(sql) else {
+                panic!("Expected SELECT");
+            };
+            let plan = optimizer.optimize_select(&stmt, params).unwrap();
+            match plan.scan_method {
+                ScanMethod::IndexIntersection {
+                    column1,
+                    probe1,
+                    column2,
+                    probe2,
+                    ..
+                } => {
+                    let mut sides = [(column1, probe1), (column2, probe2)];
+                    sides.sort_by(|x, y| x.0.cmp(&y.0));
+                    sides
+                }
+                other => panic!("{}: {:?}", sql, other),
+            }
+        };
+
+        for (sql, params) in [
+            (
+                "SELECT * FROM logs WHERE status = 's3' AND ts BETWEEN 0 AND 100000000",
+                vec![],
+            ),
+            (
+                "SELECT * FROM logs WHERE ts >= 0 AND ts <= 100000000 AND status = 's3'",
+                vec![],
+            ),
+            (
+                "SELECT * FROM logs WHERE status = ? AND ts BETWEEN ? AND ?",
+                vec![
+                    Value::text_from("s3"),
+                    Value::Integer(0),
+                    Value::Integer(100_000_000),
+                ],
+            ),
+        ] {
+            let [(status, point), (ts, range)] = probes(sql, &params);
+            assert_eq!((status.as_str(), ts.as_str()), ("status", "ts"), "{}", sql);
+            assert!(
+                matches!(point, IndexProbe::Point(Value::Text(ref s)) if s.as_str() == "s3"),
+                "{}: {:?}",
+                sql,
+                point
+            );
+            assert!(
+                matches!(
+                    range,
+                    IndexProbe::Range {
+                        start: Value::Integer(0),
+                        start_inclusive: true,
+                        end: Value::Integer(100_000_000),
+                        end_inclusive: true,
+                    }
+                ),
+                "{}: {:?}",
+                sql,
+                range
+            );
+        }
+
+        // A one-sided comparison, written either way round, is an open range
+        let [_, (_, open)] = probes(
+            "SELECT * FROM logs WHERE 100000000 > ts AND status = 's3'",
+            &[],
+        );
+        assert!(
+            matches!(
+                open,
+                IndexProbe::Range {
+                    start: Value::Integer(i64::MIN),
+                    start_inclusive: true,
+                    end: Value::Integer(100_000_000),
+                    end_inclusive: false,
+                }
+            ),
+            "{:?}",
+            open
+        );
     }
 }
 
@@ -21,6 +21,26 @@ const MAX_RECURSION_DEPTH: usize = 64;
 /// Maximum identifier length (table/column names) — prevents DoS via memory exhaustion
 const MAX_IDENTIFIER_LENGTH: usize = 4096;
 
+/// Parse a script of `;`-separated statements (e.g. a `.dump`), in order.
+/// Semicolons inside string literals and comments don't split.
+pub fn parse_script(sql: &str) -> Result<Vec<Statement>> {
+    let tokens = super::lexer::Lexer::new(sql).tokenize()?;
+    let mut statements = Vec::new();
+    let mut current = Vec::new();
+    for token in tokens {
+        match token.token_type {
+            TokenType::Semicolon | TokenType::Eof => {
+                if !current.is_empty() {
+                    current.push(Token::new(TokenType::Eof, token.line, token.column));
+                    statements.push(Parser::new(std::mem::take(&mut current)).parse()?);
+                }
+            }
+            _ => current.push(token),
+        }
+    }
+    Ok(statements)
+}
+
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
@@ -81,8 +101,17 @@ impl Parser {
             TokenType::Commit => self.parse_commit()?,
             TokenType::Rollback => self.parse_rollback()?,
             TokenType::Show => self.parse_show()?,
+            TokenType::Set => self.parse_set_variable()?,
             TokenType::Describe | TokenType::Desc => self.parse_describe()?,
-            _ => return Err(self.error("Expected SELECT, INSERT, UPDATE, DELETE, CREATE, DROP, ALTER, SHOW, DESCRIBE, BEGIN, COMMIT, or ROLLBACK")),
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("REFRESH") => self.parse_refresh()?,
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("REINDEX") => self.parse_reindex()?,
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("COPY") => self.parse_copy()?,
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("EXPLAIN") && ctes.is_empty() => {
+                self.advance();
+                // The explained statement consumes the semicolon and EOF
+                return Ok(Statement::Explain(Box::new(self.parse()?)));
+            }
+            _ => return Err(self.error("Expected SELECT, INSERT, UPDATE, DELETE, CREATE, DROP, ALTER, SET, SHOW, DESCRIBE, EXPLAIN, BEGIN, COMMIT, or ROLLBACK")),
         };
 
         // Reject WITH attached to a non-query statement. (Also catches the
@@ -106,6 +135,15 @@ impl Parser {
         Ok(stmt)
     }
 
+    /// Parse a lone expression, e.g. a generated column's stored definition
+    pub fn parse_standalone_expr(&mut self) -> Result<Expr> {
+        let expr = self.parse_expr(0)?;
+        if !matches!(self.current().token_type, TokenType::Eof) {
+            return Err(self.error("Unexpected input after expression"));
+        }
+        Ok(expr)
+    }
+
     /// Parse SELECT statement
     fn parse_select(&mut self) -> Result<SelectStmt> {
         self.expect(TokenType::Select)?;
@@ -130,6 +168,17 @@ impl Parser {
             None
         };
 
+        // SAMPLE BY clause (optional)
+        let sample_by = if self.at_sample_by() {
+            self.advance(); // SAMPLE
+            self.advance(); // BY
+            Some(SampleBy {
+                interval: self.parse_interval()?,
+            })
+        } else {
+            None
+        };
+
         // GROUP BY clause (optional)
         let group_by = if self.match_token(TokenType::Group) {
             self.expect(TokenType::By)?;
@@ -138,6 +187,13 @@ impl Parser {
             None
         };
 
+        // FILL clause (optional, for SAMPLE BY / TIME_BUCKET grouping)
+        let fill = if self.at_fill() {
+            Some(self.parse_fill()?)
+        } else {
+            None
+        };
+
         // HAVING clause (optional, requires GROUP BY)
         let having = if self.match_token(TokenType::Having) {
             Some(self.parse_expr(0)?)
@@ -167,6 +223,22 @@ impl Parser {
             None
         };
 
+        // AFTER CURSOR 'token' clause (optional, keyset pagination)
+        let after_cursor = if self.at_after_cursor() {
+            self.advance(); // AFTER
+            self.advance(); // CURSOR
+            match &self.current().token_type {
+                TokenType::String(s) => {
+                    let token = s.clone();
+                    self.advance();
+                    Some(token)
+                }
+                _ => return Err(self.error("Expected cursor token string after AFTER CURSOR")),
+            }
+        } else {
+            None
+        };
+
         // LATEST BY clause (optional)
         let latest_by = if self.match_token(TokenType::Latest) {
             self.expect(TokenType::By)?;
@@ -185,10 +257,73 @@ impl Parser {
             order_by,
             limit,
             offset,
+            after_cursor,
             latest_by,
+            sample_by,
+            fill,
         })
     }
 
+    /// `SAMPLE BY` (SAMPLE is not reserved, so it can still name a column)
+    fn at_sample_by(&self) -> bool {
+        matches!(&self.current().token_type, TokenType::Identifier(w) if w.eq_ignore_ascii_case("SAMPLE"))
+            && matches!(self.peek_token_type(), TokenType::By)
+    }
+
+    /// `AFTER CURSOR` (neither word is reserved)
+    fn at_after_cursor(&self) -> bool {
+        matches!(&self.current().token_type, TokenType::Identifier(w) if w.eq_ignore_ascii_case("AFTER"))
+            && matches!(self.peek_token_type(), TokenType::Identifier(w) if w.eq_ignore_ascii_case("CURSOR"))
+    }
+
+    /// `FILL(` (FILL is not reserved either)
+    fn at_fill(&self) -> bool {
+        matches!(&self.current().token_type, TokenType::Identifier(w) if w.eq_ignore_ascii_case("FILL"))
+            && matches!(self.peek_token_type(), TokenType::LParen)
+    }
+
+    /// `FILL(NULL | PREVIOUS | LINEAR)`; `PREV` is accepted for PREVIOUS
+    fn parse_fill(&mut self) -> Result<FillMode> {
+        self.advance(); // FILL
+        self.expect(TokenType::LParen)?;
+        let mode = match &self.current().token_type {
+            TokenType::Null => FillMode::Null,
+            TokenType::Identifier(w)
+                if w.eq_ignore_ascii_case("PREVIOUS") || w.eq_ignore_ascii_case("PREV") =>
+            {
+                FillMode::Previous
+            }
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("LINEAR") => FillMode::Linear,
+            _ => return Err(self.error("Expected FILL(NULL | PREVIOUS | LINEAR)")),
+        };
+        self.advance();
+        self.expect(TokenType::RParen)?;
+        Ok(mode)
+    }
+
+    /// Interval such as `1s`, `100ms` or `'5m'`; a bare number means seconds
+    fn parse_interval(&mut self) -> Result<String> {
+        match self.current().token_type.clone() {
+            TokenType::String(s) => {
+                self.advance();
+                Ok(s)
+            }
+            TokenType::Number(n) if n > 0.0 && n.fract() == 0.0 => {
+                self.advance();
+                let unit = match &self.current().token_type {
+                    TokenType::Identifier(u) => {
+                        let u = u.to_lowercase();
+                        self.advance();
+                        u
+                    }
+                    _ => "s".to_string(),
+                };
+                Ok(format!("{}{}", n as i64, unit))
+            }
+            _ => Err(self.error("Expected an interval like 1s, 100ms or '5m'")),
+        }
+    }
+
     /// Parse a WITH clause: `WITH [RECURSIVE] name [(col, ...)] AS ( SELECT ... ), ...`
     ///
     /// Returns `(Vec<CteDef>, is_recursive)`. The caller is responsible for
@@ -390,7 +525,9 @@ impl Parser {
         // Check for optional AS alias
         let alias = if self.match_token(TokenType::As) {
             Some(self.parse_identifier()?)
-        } else if matches!(self.current().token_type, TokenType::Identifier(_)) {
+        } else if matches!(self.current().token_type, TokenType::Identifier(_))
+            && !self.at_sample_by()
+        {
             // Allow implicit alias (without AS keyword)
             Some(self.parse_identifier()?)
         } else {
@@ -555,6 +692,8 @@ impl Parser {
                 let id_upper = id.to_uppercase();
                 if id_upper == "SPATIAL" || id_upper == "OCTREE" {
                     Ok(Statement::CreateIndex(self.parse_create_index()?))
+                } else if id_upper == "MATERIALIZED" {
+                    self.parse_create_materialized_view()
                 } else {
                     Err(self.error("Expected TABLE or INDEX after CREATE"))
                 }
@@ -615,16 +754,69 @@ impl Parser {
             ttl = Some(self.parse_ttl_duration()?);
         }
 
+        // Parse optional ring-buffer limits: MAX_ROWS = 10000 / MAX_BYTES = 64MB
+        let mut max_rows = None;
+        let mut max_bytes = None;
+        loop {
+            if self.match_keyword("MAX_ROWS") {
+                self.expect(TokenType::Eq)?;
+                let value = self.parse_i64()?;
+                if value <= 0 {
+                    return Err(self.error("MAX_ROWS must be positive"));
+                }
+                max_rows = Some(value as u64);
+            } else if self.match_keyword("MAX_BYTES") {
+                self.expect(TokenType::Eq)?;
+                max_bytes = Some(self.parse_byte_size()?);
+            } else {
+                break;
+            }
+            self.match_token(TokenType::Comma);
+        }
+
         Ok(CreateTableStmt {
             table,
             columns,
             table_type,
             timeseries_column,
             ttl,
+            max_rows,
+            max_bytes,
             if_not_exists,
         })
     }
 
+    /// Parse a byte size: NUMBER with an optional B/KB/MB/GB suffix
+    /// Examples: 4096, 512KB, 64MB
+    fn parse_byte_size(&mut self) -> Result<u64> {
+        let value = self.parse_i64()?;
+        if value <= 0 {
+            return Err(self.error("MAX_BYTES must be positive"));
+        }
+
+        let unit = match &self.current().token_type {
+            TokenType::Identifier(id) => match id.to_uppercase().as_str() {
+                "B" => Some(1),
+                "K" | "KB" => Some(1 << 10),
+                "M" | "MB" => Some(1 << 20),
+                "G" | "GB" => Some(1 << 30),
+                _ => None,
+            },
+            _ => None,
+        };
+        let multiplier: u64 = match unit {
+            Some(multiplier) => {
+                self.advance();
+                multiplier
+            }
+            None => 1,
+        };
+
+        (value as u64)
+            .checked_mul(multiplier)
+            .ok_or_else(|| self.error("MAX_BYTES out of range"))
+    }
+
     /// Parse TTL duration: NUMBER followed by s/m/h/d suffix
     /// Examples: 7d, 24h, 30m, 3600s
     fn parse_ttl_duration(&mut self) -> Result<crate::types::TTLDuration> {
@@ -757,15 +949,54 @@ impl Parser {
             TokenType::Timestamp => DataType::Timestamp,
             TokenType::Geometry => DataType::Geometry,
             TokenType::Identifier(name) if name.to_uppercase() == "POINT3D" => DataType::Geometry,
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case("UUID") => DataType::Uuid,
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case("DATE") => DataType::Date,
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case("TIME") => DataType::Time,
+            TokenType::Identifier(name)
+                if name.eq_ignore_ascii_case("DECIMAL") || name.eq_ignore_ascii_case("NUMERIC") =>
+            {
+                self.advance();
+                let (precision, scale) = self.parse_decimal_params()?;
+                return Ok(DataType::Decimal(precision, scale));
+            }
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case("TENSOR") => {
+                self.advance();
+                return self.parse_tensor_shape().map(DataType::Tensor);
+            }
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case("ENUM") => {
+                self.advance();
+                return self.parse_enum_labels().map(DataType::Enum);
+            }
             TokenType::Vector => {
                 self.advance();
                 if self.match_token(TokenType::LParen) {
                     let dim = self.parse_usize()?;
+                    let encoding = if self.match_token(TokenType::Comma) {
+                        self.parse_vector_encoding()?
+                    } else {
+                        None
+                    };
                     self.expect(TokenType::RParen)?;
-                    return Ok(DataType::Vector(Some(dim)));
+                    return Ok(DataType::Vector(Some(dim), encoding));
                 } else {
-                    return Ok(DataType::Vector(None));
+                    return Ok(DataType::Vector(None, None));
+                }
+            }
+            TokenType::Array => {
+                self.advance();
+                self.expect(TokenType::Lt)?;
+                let elem = self.parse_data_type()?;
+                if matches!(
+                    elem,
+                    DataType::Vector(..)
+                        | DataType::Geometry
+                        | DataType::Array(_)
+                        | DataType::Tensor(_)
+                ) {
+                    return Err(self.error("ARRAY element type must be a scalar type"));
                 }
+                self.expect(TokenType::Gt)?;
+                return Ok(DataType::Array(Box::new(elem)));
             }
             _ => return Err(self.error("Expected data type")),
         };
@@ -774,6 +1005,92 @@ impl Parser {
         Ok(data_type)
     }
 
+    /// Optional `(precision[, scale])` after DECIMAL/NUMERIC. Defaults to (10, 0).
+    fn parse_decimal_params(&mut self) -> Result<(u8, u8)> {
+        if !self.match_token(TokenType::LParen) {
+            return Ok((10, 0));
+        }
+        let precision = self.parse_usize()?;
+        let scale = if self.match_token(TokenType::Comma) {
+            self.parse_usize()?
+        } else {
+            0
+        };
+        self.expect(TokenType::RParen)?;
+        if precision == 0 || precision > crate::types::DECIMAL_MAX_PRECISION as usize {
+            return Err(self.error(&format!(
+                "DECIMAL precision must be between 1 and {}",
+                crate::types::DECIMAL_MAX_PRECISION
+            )));
+        }
+        if scale > precision {
+            return Err(self.error("DECIMAL scale cannot exceed precision"));
+        }
+        Ok((precision as u8, scale as u8))
+    }
+
+    /// Element type after `VECTOR(dim, `: f32 (the default layout), f16 or int8
+    fn parse_vector_encoding(&mut self) -> Result<Option<crate::types::VectorEncoding>> {
+        let name = self.parse_identifier()?;
+        if name.eq_ignore_ascii_case("f32") {
+            return Ok(None);
+        }
+        crate::types::VectorEncoding::from_name(&name)
+            .map(Some)
+            .ok_or_else(|| {
+                self.error(&format!(
+                    "Unknown VECTOR element type '{}' (expected f32, f16 or int8)",
+                    name
+                ))
+            })
+    }
+
+    /// `(d1, d2, ...)` after TENSOR. Tensors are stored flat in the vector
+    /// layout, so the element count is capped at 65535.
+    fn parse_tensor_shape(&mut self) -> Result<Vec<usize>> {
+        self.expect(TokenType::LParen)?;
+        let mut shape = vec![self.parse_usize()?];
+        while self.match_token(TokenType::Comma) {
+            shape.push(self.parse_usize()?);
+        }
+        self.expect(TokenType::RParen)?;
+        crate::types::tensor::check_shape(&shape).map_err(|e| self.error(&e))?;
+        let elements = shape.iter().try_fold(1usize, |n, &d| n.checked_mul(d));
+        if elements.is_none_or(|n| n > u16::MAX as usize) {
+            return Err(self.error(&format!(
+                "TENSOR shape {} exceeds the maximum of {} elements",
+                crate::types::tensor::format_shape(&shape),
+                u16::MAX
+            )));
+        }
+        Ok(shape)
+    }
+
+    /// `('label', ...)` after ENUM. Labels are non-empty, distinct strings.
+    fn parse_enum_labels(&mut self) -> Result<Vec<String>> {
+        self.expect(TokenType::LParen)?;
+        let mut labels: Vec<String> = Vec::new();
+        loop {
+            let label = match &self.current().token_type {
+                TokenType::String(s) => s.clone(),
+                _ => return Err(self.error("Expected string label in ENUM")),
+            };
+            if label.is_empty() {
+                return Err(self.error("ENUM labels cannot be empty"));
+            }
+            if labels.contains(&label) {
+                return Err(self.error(&format!("Duplicate ENUM label '{}'", label)));
+            }
+            labels.push(label);
+            self.advance();
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RParen)?;
+        Ok(labels)
+    }
+
     fn parse_create_index(&mut self) -> Result<CreateIndexStmt> {
         // Parse optional index type: TEXT/VECTOR/SPATIAL/TIMESTAMP
         let index_type = match &self.current().token_type {
@@ -869,50 +1186,75 @@ impl Parser {
             index_type
         };
 
-        // Parse optional WITH clause: WITH (metric = 'l2' | 'cosine')
+        // Parse optional WITH clause: WITH (metric = 'l2' | 'cosine', rerank = true | false)
         let mut metric = None;
-        let token_type = self.current().token_type.clone();
-        if let TokenType::Identifier(ref id) = token_type {
-            if id.to_uppercase() == "WITH" {
-                self.advance(); // consume WITH
-                self.expect(TokenType::LParen)?;
+        let mut rerank = None;
+        if self.match_token(TokenType::With) {
+            self.expect(TokenType::LParen)?;
 
-                // Parse key = value pairs
-                loop {
-                    let key = self.parse_identifier()?;
-                    let key_upper = key.to_uppercase();
-                    self.expect(TokenType::Eq)?;
-
-                    match key_upper.as_str() {
-                        "METRIC" => {
-                            let value = self.parse_identifier()?;
-                            let value_lower = value.to_lowercase();
-                            match value_lower.as_str() {
-                                "l2" | "euclidean" => metric = Some("l2".to_string()),
-                                "cosine" => metric = Some("cosine".to_string()),
-                                _ => {
-                                    return Err(MoteDBError::ParseError(format!(
-                                        "Unknown metric '{}'. Use 'l2' or 'cosine'",
-                                        value
-                                    )))
-                                }
+            // Parse key = value pairs
+            loop {
+                let key = self.parse_identifier()?;
+                let key_upper = key.to_uppercase();
+                self.expect(TokenType::Eq)?;
+
+                match key_upper.as_str() {
+                    "METRIC" => {
+                        // Accept both `metric = cosine` and `metric = 'cosine'`
+                        let value = match &self.current().token_type {
+                            TokenType::String(s) => {
+                                let s = s.clone();
+                                self.advance();
+                                s
+                            }
+                            _ => self.parse_identifier()?,
+                        };
+                        let value_lower = value.to_lowercase();
+                        match value_lower.as_str() {
+                            "l2" | "euclidean" => metric = Some("l2".to_string()),
+                            "cosine" => metric = Some("cosine".to_string()),
+                            _ => {
+                                return Err(MoteDBError::ParseError(format!(
+                                    "Unknown metric '{}'. Use 'l2' or 'cosine'",
+                                    value
+                                )))
                             }
-                        }
-                        _ => {
-                            return Err(MoteDBError::ParseError(format!(
-                                "Unknown WITH option '{}'. Supported: metric",
-                                key
-                            )))
                         }
                     }
-
-                    if !self.match_token(TokenType::Comma) {
-                        break;
+                    "RERANK" => {
+                        let value = match self.current().token_type.clone() {
+                            TokenType::True => "true".to_string(),
+                            TokenType::False => "false".to_string(),
+                            TokenType::On => "on".to_string(),
+                            TokenType::String(s) | TokenType::Identifier(s) => s,
+                            _ => String::new(),
+                        };
+                        rerank = match value.to_lowercase().as_str() {
+                            "true" | "on" => Some(true),
+                            "false" | "off" => Some(false),
+                            _ => {
+                                return Err(MoteDBError::ParseError(format!(
+                                    "Invalid rerank value '{}'. Use true or false",
+                                    value
+                                )))
+                            }
+                        };
+                        self.advance();
+                    }
+                    _ => {
+                        return Err(MoteDBError::ParseError(format!(
+                            "Unknown WITH option '{}'. Supported: metric, rerank",
+                            key
+                        )))
                     }
                 }
 
-                self.expect(TokenType::RParen)?;
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
             }
+
+            self.expect(TokenType::RParen)?;
         }
 
         Ok(CreateIndexStmt {
@@ -921,6 +1263,7 @@ impl Parser {
             column,
             index_type: final_index_type,
             metric,
+            rerank,
         })
     }
 
@@ -957,10 +1300,190 @@ impl Parser {
                 }
                 Ok(Statement::DropIndex(DropIndexStmt { index_name }))
             }
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("MATERIALIZED") => {
+                self.advance();
+                self.expect_view_keyword()?;
+                let if_exists = if matches!(&self.current().token_type, TokenType::Identifier(ref w) if w.eq_ignore_ascii_case("IF"))
+                {
+                    self.advance();
+                    match &self.current().token_type {
+                        TokenType::Identifier(ref w) if w.eq_ignore_ascii_case("EXISTS") => {
+                            self.advance();
+                            true
+                        }
+                        _ => return Err(self.error("Expected EXISTS after IF")),
+                    }
+                } else {
+                    false
+                };
+                let table = self.parse_identifier()?;
+                Ok(Statement::DropMaterializedView(DropTableStmt {
+                    table,
+                    if_exists,
+                }))
+            }
             _ => Err(self.error("Expected TABLE or INDEX after DROP")),
         }
     }
 
+    /// `CREATE MATERIALIZED VIEW name AS SELECT ...`
+    fn parse_create_materialized_view(&mut self) -> Result<Statement> {
+        self.advance(); // MATERIALIZED
+        self.expect_view_keyword()?;
+        let name = self.parse_identifier()?;
+        self.expect(TokenType::As)?;
+        let start = self.position;
+        let query = self.parse_select()?;
+        let query_sql = self.tokens[start..self.position]
+            .iter()
+            .map(|t| t.token_type.to_sql())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(Statement::CreateMaterializedView(
+            CreateMaterializedViewStmt {
+                name,
+                query,
+                query_sql,
+            },
+        ))
+    }
+
+    /// `REFRESH MATERIALIZED VIEW name`
+    fn parse_refresh(&mut self) -> Result<Statement> {
+        self.advance(); // REFRESH
+        match &self.current().token_type {
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("MATERIALIZED") => self.advance(),
+            _ => return Err(self.error("Expected MATERIALIZED after REFRESH")),
+        }
+        self.expect_view_keyword()?;
+        Ok(Statement::RefreshMaterializedView(self.parse_identifier()?))
+    }
+
+    /// `REINDEX [INDEX] name`
+    fn parse_reindex(&mut self) -> Result<Statement> {
+        self.advance(); // REINDEX
+        self.match_token(TokenType::Index);
+        Ok(Statement::Reindex(self.parse_identifier()?))
+    }
+
+    /// `COPY table FROM | TO 'file.csv' [WITH] [(option [=] value, ...)]`
+    ///
+    /// Options: `FORMAT csv`, `DELIMITER 'c'`, `HEADER [true | false]`,
+    /// `NULL 'text'`
+    fn parse_copy(&mut self) -> Result<Statement> {
+        self.advance(); // COPY
+        let table = self.parse_identifier()?;
+        let direction = if self.match_token(TokenType::From) {
+            CopyDirection::From
+        } else if self.match_keyword("TO") {
+            CopyDirection::To
+        } else {
+            return Err(self.error("Expected FROM or TO after COPY table"));
+        };
+        let path = match self.current().token_type.clone() {
+            TokenType::String(path) => path,
+            _ => return Err(self.error("Expected a quoted file path")),
+        };
+        self.advance();
+
+        let mut options = crate::database::CsvOptions::default();
+        let with = self.match_token(TokenType::With);
+        if self.match_token(TokenType::LParen) {
+            loop {
+                let key = match self.current().token_type.clone() {
+                    TokenType::Null => "NULL".to_string(),
+                    TokenType::Identifier(key) => key.to_uppercase(),
+                    _ => return Err(self.error("Expected a COPY option")),
+                };
+                self.advance();
+                self.match_token(TokenType::Eq);
+                match key.as_str() {
+                    "FORMAT" => {
+                        let format = match self.current().token_type.clone() {
+                            TokenType::String(f) | TokenType::Identifier(f) => f,
+                            _ => String::new(),
+                        };
+                        if !format.eq_ignore_ascii_case("csv") {
+                            return Err(MoteDBError::ParseError(format!(
+                                "Unsupported COPY format '{}'. Only csv is supported",
+                                format
+                            )));
+                        }
+                        self.advance();
+                    }
+                    "DELIMITER" => {
+                        let delimiter = match self.current().token_type.clone() {
+                            TokenType::String(d) => d,
+                            _ => return Err(self.error("Expected a quoted DELIMITER")),
+                        };
+                        options.delimiter = match delimiter.as_str() {
+                            "\\t" => b'\t',
+                            d if d.len() == 1 && d.is_ascii() => d.as_bytes()[0],
+                            _ => {
+                                return Err(MoteDBError::ParseError(format!(
+                                    "Invalid DELIMITER '{}'. Use a single ASCII character",
+                                    delimiter
+                                )))
+                            }
+                        };
+                        self.advance();
+                    }
+                    "HEADER" => {
+                        let value = match self.current().token_type.clone() {
+                            TokenType::True | TokenType::On => Some(true),
+                            TokenType::False => Some(false),
+                            TokenType::Identifier(v) if v.eq_ignore_ascii_case("off") => {
+                                Some(false)
+                            }
+                            _ => None,
+                        };
+                        // Bare HEADER turns it on
+                        if value.is_some() {
+                            self.advance();
+                        }
+                        options.header = value.unwrap_or(true);
+                    }
+                    "NULL" => {
+                        options.null = match self.current().token_type.clone() {
+                            TokenType::String(null) => null,
+                            _ => return Err(self.error("Expected a quoted NULL string")),
+                        };
+                        self.advance();
+                    }
+                    _ => {
+                        return Err(MoteDBError::ParseError(format!(
+                            "Unknown COPY option '{}'. Supported: format, delimiter, header, null",
+                            key
+                        )))
+                    }
+                }
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenType::RParen)?;
+        } else if with {
+            return Err(self.error("Expected ( after WITH"));
+        }
+
+        Ok(Statement::Copy(CopyStmt {
+            table,
+            direction,
+            path,
+            options,
+        }))
+    }
+
+    fn expect_view_keyword(&mut self) -> Result<()> {
+        match &self.current().token_type {
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("VIEW") => {
+                self.advance();
+                Ok(())
+            }
+            _ => Err(self.error("Expected VIEW after MATERIALIZED")),
+        }
+    }
+
     /// Parse BEGIN [TRANSACTION]
     fn parse_begin(&mut self) -> Result<Statement> {
         self.expect(TokenType::Begin)?;
@@ -984,17 +1507,53 @@ impl Parser {
         Ok(Statement::RollbackTransaction)
     }
 
-    /// Parse SHOW statement
+    /// Parse SHOW TABLES | SHOW TRANSACTIONS | SHOW TRANSACTION STATS | SHOW name
     fn parse_show(&mut self) -> Result<Statement> {
         self.expect(TokenType::Show)?;
 
         if self.match_token(TokenType::Tables) {
             Ok(Statement::ShowTables)
+        } else if self.match_keyword("TRANSACTIONS") {
+            Ok(Statement::ShowTransactions)
+        } else if self.match_keyword("TRANSACTION") {
+            if self.match_keyword("STATS") {
+                Ok(Statement::ShowTransactionStats)
+            } else {
+                Err(self.error("Expected STATS after SHOW TRANSACTION"))
+            }
+        } else if let TokenType::Identifier(_) = self.current().token_type {
+            Ok(Statement::ShowVariable(self.parse_identifier()?))
         } else {
-            Err(self.error("Expected TABLES after SHOW"))
+            Err(self.error(
+                "Expected TABLES, TRANSACTIONS, TRANSACTION STATS or a setting name after SHOW",
+            ))
         }
     }
 
+    /// Parse SET [SESSION] name { = | TO } { value | DEFAULT }
+    ///
+    /// The value may be a string, a number, TRUE/FALSE or a bare word.
+    fn parse_set_variable(&mut self) -> Result<Statement> {
+        self.expect(TokenType::Set)?;
+        self.match_keyword("SESSION");
+        let name = self.parse_identifier()?;
+        if !self.match_token(TokenType::Eq) && !self.match_keyword("TO") {
+            return Err(self.error("Expected = or TO after the setting name"));
+        }
+
+        let value = match self.current().token_type.clone() {
+            TokenType::Default => None,
+            TokenType::Number(n) => Some(n.to_string()),
+            TokenType::String(s) | TokenType::Identifier(s) => Some(s),
+            TokenType::True => Some("true".to_string()),
+            TokenType::False => Some("false".to_string()),
+            TokenType::On => Some("on".to_string()),
+            _ => return Err(self.error("Expected a value or DEFAULT for SET")),
+        };
+        self.advance();
+        Ok(Statement::SetVariable { name, value })
+    }
+
     /// Parse DESCRIBE statement
     fn parse_describe(&mut self) -> Result<Statement> {
         // Accept both DESC and DESCRIBE
@@ -1040,6 +1599,19 @@ impl Parser {
                 continue;
             }
 
+            // Array subscript: arr[i] (1-based) → element_at(arr, i)
+            if matches!(self.current().token_type, TokenType::LBracket) {
+                self.advance();
+                let index = self.parse_expr(0)?;
+                self.expect(TokenType::RBracket)?;
+                left = Expr::FunctionCall {
+                    name: "element_at".to_string(),
+                    args: vec![left, index],
+                    distinct: false,
+                };
+                continue;
+            }
+
             break;
         }
 
@@ -1221,47 +1793,65 @@ impl Parser {
                 Ok(Expr::Parameter(idx))
             }
 
-            // ARRAY[...] literal for vectors
+            // ARRAY[...]: all-numeric literals stay a vector (as before);
+            // anything else builds an array value
             TokenType::Array => {
                 self.advance();
                 self.expect(TokenType::LBracket)?;
+                let elements = if matches!(self.current().token_type, TokenType::RBracket) {
+                    Vec::new()
+                } else {
+                    self.parse_expr_list()?
+                };
+                self.expect(TokenType::RBracket)?;
 
-                // Parse array elements
-                let mut elements = Vec::new();
-                if !matches!(self.current().token_type, TokenType::RBracket) {
-                    loop {
-                        // Parse numeric literal
-                        let elem = match &self.current().token_type {
-                            TokenType::Number(n) => {
-                                let n = *n;
-                                self.advance();
-                                n as f32
-                            }
-                            TokenType::Minus => {
-                                self.advance();
-                                if let TokenType::Number(n) = self.current().token_type {
-                                    self.advance();
-                                    -(n as f32)
-                                } else {
-                                    return Err(
-                                        self.error("Expected number after minus sign in ARRAY")
-                                    );
-                                }
-                            }
-                            _ => return Err(self.error("ARRAY elements must be numeric literals")),
-                        };
-                        elements.push(elem);
-
-                        if !self.match_token(TokenType::Comma) {
-                            break;
-                        }
+                let numeric = |e: &Expr| -> Option<f64> {
+                    match e {
+                        Expr::Literal(Value::Integer(i)) => Some(*i as f64),
+                        Expr::Literal(Value::Float(f)) => Some(*f),
+                        Expr::UnaryOp {
+                            op: UnaryOperator::Minus,
+                            expr,
+                        } => match **expr {
+                            Expr::Literal(Value::Integer(i)) => Some(-(i as f64)),
+                            Expr::Literal(Value::Float(f)) => Some(-f),
+                            _ => None,
+                        },
+                        _ => None,
                     }
+                };
+                if let Some(floats) = elements
+                    .iter()
+                    .map(|e| numeric(e).map(|f| f as f32))
+                    .collect::<Option<Vec<f32>>>()
+                {
+                    return Ok(Expr::Literal(Value::Vector(crate::types::ArcVec::new(
+                        floats,
+                    ))));
+                }
+                let literals = elements
+                    .iter()
+                    .map(|e| match e {
+                        Expr::Literal(v) => Some(v.clone()),
+                        Expr::UnaryOp {
+                            op: UnaryOperator::Minus,
+                            expr,
+                        } => match **expr {
+                            Expr::Literal(Value::Integer(i)) => Some(Value::Integer(-i)),
+                            Expr::Literal(Value::Float(f)) => Some(Value::Float(-f)),
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<Value>>>();
+                match literals {
+                    Some(items) => Ok(Expr::Literal(Value::array(items))),
+                    None => Ok(Expr::FunctionCall {
+                        name: "array".to_string(),
+                        args: elements,
+                        distinct: false,
+                    }),
                 }
-
-                self.expect(TokenType::RBracket)?;
-                Ok(Expr::Literal(Value::Vector(crate::types::ArcVec::new(
-                    elements,
-                ))))
             }
 
             // Identifier or function call or qualified column
@@ -1276,6 +1866,22 @@ impl Parser {
                     return Ok(Expr::Column(qualified_name));
                 }
 
+                // Typed literals: DATE '2024-01-31', TIME '08:00:00'
+                if let TokenType::String(ref s) = self.current().token_type {
+                    let literal = if name.eq_ignore_ascii_case("DATE") {
+                        Some(s.parse().map(Value::Date))
+                    } else if name.eq_ignore_ascii_case("TIME") {
+                        Some(s.parse().map(Value::Time))
+                    } else {
+                        None
+                    };
+                    if let Some(literal) = literal {
+                        let value = literal.map_err(|e| self.error(&e))?;
+                        self.advance();
+                        return Ok(Expr::Literal(value));
+                    }
+                }
+
                 // Check for function call
                 if matches!(self.current().token_type, TokenType::LParen) {
                     self.advance();
@@ -1283,6 +1889,16 @@ impl Parser {
                     // Check for DISTINCT keyword (COUNT(DISTINCT column))
                     let distinct = self.match_token(TokenType::Distinct);
 
+                    // EXISTS (SELECT ...)
+                    if name.eq_ignore_ascii_case("EXISTS")
+                        && !distinct
+                        && matches!(self.current().token_type, TokenType::Select)
+                    {
+                        let subquery = self.parse_select()?;
+                        self.expect(TokenType::RParen)?;
+                        return Ok(Expr::Exists(Box::new(subquery)));
+                    }
+
                     // 🔑 CAST(expr AS type) — standard SQL syntax. Parse the
                     // value expression, expect AS, then read the type name, and
                     // lower to the existing cast(value, 'TYPE') function form.
@@ -1306,6 +1922,12 @@ impl Parser {
                                 _ => return Err(self.error("Expected type name in CAST")),
                             };
                             self.advance();
+                            let type_name = if type_name == "DECIMAL" || type_name == "NUMERIC" {
+                                let (precision, scale) = self.parse_decimal_params()?;
+                                format!("DECIMAL({},{})", precision, scale)
+                            } else {
+                                type_name
+                            };
                             self.expect(TokenType::RParen)?;
                             return Ok(Expr::FunctionCall {
                                 name: "cast".to_string(),
@@ -1363,33 +1985,15 @@ impl Parser {
                                 self.error("POINT3D() requires exactly 3 arguments (x, y, z)")
                             );
                         }
-                        let x = match &args[0] {
-                            Expr::Literal(Value::Float(f)) => *f,
-                            Expr::Literal(Value::Integer(i)) => *i as f64,
-                            _ => {
-                                return Err(
-                                    self.error("POINT3D() arguments must be numeric literals")
-                                )
-                            }
-                        };
-                        let y = match &args[1] {
-                            Expr::Literal(Value::Float(f)) => *f,
-                            Expr::Literal(Value::Integer(i)) => *i as f64,
-                            _ => {
-                                return Err(
-                                    self.error("POINT3D() arguments must be numeric literals")
-                                )
-                            }
-                        };
-                        let z = match &args[2] {
-                            Expr::Literal(Value::Float(f)) => *f,
-                            Expr::Literal(Value::Integer(i)) => *i as f64,
-                            _ => {
-                                return Err(
-                                    self.error("POINT3D() arguments must be numeric literals")
-                                )
-                            }
-                        };
+                        let x = self.eval_num(&args[0]).map_err(|_| {
+                            self.error("POINT3D() arguments must be numeric literals")
+                        })?;
+                        let y = self.eval_num(&args[1]).map_err(|_| {
+                            self.error("POINT3D() arguments must be numeric literals")
+                        })?;
+                        let z = self.eval_num(&args[2]).map_err(|_| {
+                            self.error("POINT3D() arguments must be numeric literals")
+                        })?;
                         use crate::types::{Geometry as G3, Point3D};
                         Ok(Expr::Literal(Value::spatial(G3::Point3D(Point3D::new(
                             x, y, z,
@@ -1802,6 +2406,30 @@ impl Parser {
                 }
             }
 
+            // Typed literal TIMESTAMP '2024-01-31 08:00:00'. Text naming its
+            // own offset is resolved now; otherwise it's read in the session
+            // time zone when the statement runs
+            TokenType::Timestamp if matches!(self.peek_token_type(), TokenType::String(_)) => {
+                self.advance();
+                let TokenType::String(text) = self.current().token_type.clone() else {
+                    unreachable!("peeked a string literal")
+                };
+                let (local, zone) =
+                    crate::types::parse_timestamp(&text).map_err(|e| self.error(&e))?;
+                self.advance();
+                Ok(match zone {
+                    Some(zone) => Expr::Literal(Value::Timestamp(zone.from_local(local))),
+                    None => Expr::FunctionCall {
+                        name: "cast".to_string(),
+                        args: vec![
+                            Expr::Literal(Value::text(text)),
+                            Expr::Literal(Value::text("TIMESTAMP".to_string())),
+                        ],
+                        distinct: false,
+                    },
+                })
+            }
+
             // Vector literal [1.0, 2.0, 3.0]
             TokenType::LBracket => {
                 self.advance();
@@ -1859,11 +2487,16 @@ impl Parser {
                 )
             }
             TokenType::Like | TokenType::In | TokenType::Between => true,
+            TokenType::Identifier(id) if id.eq_ignore_ascii_case("AT") => matches!(
+                self.peek_token_type(),
+                TokenType::Identifier(next) if next.eq_ignore_ascii_case("TIME")
+            ),
             _ => false,
         }
     }
 
-    /// Parse a single postfix operator (IS NULL, IN, LIKE, BETWEEN, NOT IN/LIKE/BETWEEN).
+    /// Parse a single postfix operator (IS NULL, IN, LIKE, BETWEEN, NOT IN/LIKE/BETWEEN,
+    /// AT TIME ZONE).
     fn parse_single_postfix(&mut self, expr: Expr) -> Result<Expr> {
         match &self.current().token_type {
             TokenType::Is => {
@@ -1960,6 +2593,21 @@ impl Parser {
                     negated: false,
                 })
             }
+            TokenType::Identifier(_) => {
+                // AT TIME ZONE zone → at_time_zone(expr, zone)
+                self.advance(); // consume AT
+                self.advance(); // consume TIME
+                match &self.current().token_type {
+                    TokenType::Identifier(id) if id.eq_ignore_ascii_case("ZONE") => self.advance(),
+                    _ => return Err(self.error("Expected ZONE after AT TIME")),
+                }
+                let zone = self.parse_prefix_expr()?;
+                Ok(Expr::FunctionCall {
+                    name: "at_time_zone".to_string(),
+                    args: vec![expr, zone],
+                    distinct: false,
+                })
+            }
             _ => unreachable!("can_parse_postfix should prevent this"),
         }
     }
@@ -2146,12 +2794,35 @@ impl Parser {
     ///
     /// Syntax: ALTER TABLE table_name AUTO_INCREMENT = value
     ///      |  ALTER TABLE table_name ADD [COLUMN] name type [DEFAULT value]
+    ///      |  ALTER TABLE table_name DROP [COLUMN] name
+    ///      |  ALTER TABLE table_name RENAME [COLUMN] old TO new
     fn parse_alter_table(&mut self) -> Result<AlterTableStmt> {
         self.expect(TokenType::Alter)?;
         self.expect(TokenType::Table)?;
 
         let table = self.parse_identifier()?;
 
+        if self.match_token(TokenType::Drop) {
+            self.match_keyword("COLUMN");
+            let name = self.parse_identifier()?;
+            return Ok(AlterTableStmt {
+                table,
+                action: AlterTableAction::DropColumn(name),
+            });
+        }
+        if self.match_keyword("RENAME") {
+            self.match_keyword("COLUMN");
+            let from = self.parse_identifier()?;
+            if !self.match_keyword("TO") {
+                return Err(self.error("Expected TO after RENAME COLUMN name"));
+            }
+            let to = self.parse_identifier()?;
+            return Ok(AlterTableStmt {
+                table,
+                action: AlterTableAction::RenameColumn { from, to },
+            });
+        }
+
         // Branch on ADD vs AUTO_INCREMENT
         if matches!(self.current().token_type, TokenType::Add) {
             self.advance();
@@ -2163,7 +2834,15 @@ impl Parser {
                 }
             }
             let col_name = self.parse_identifier()?;
-            let data_type = self.parse_data_type()?;
+            let data_type = if self.at_generated_clause() {
+                None
+            } else {
+                Some(self.parse_data_type()?)
+            };
+            if self.at_generated_clause() {
+                return self.parse_generated_column(table, col_name, data_type);
+            }
+            let data_type = data_type.expect("type parsed unless AS follows the name");
             // Optional DEFAULT value
             let default_value = if matches!(self.current().token_type, TokenType::Default) {
                 self.advance();
@@ -2225,6 +2904,62 @@ impl Parser {
             })
         }
     }
+
+    /// `AS (` or `GENERATED ALWAYS AS (` after a column name/type
+    fn at_generated_clause(&self) -> bool {
+        match &self.current().token_type {
+            TokenType::As => true,
+            TokenType::Identifier(name) => name.eq_ignore_ascii_case("GENERATED"),
+            _ => false,
+        }
+    }
+
+    /// `[GENERATED ALWAYS] AS (expr) [STORED | VIRTUAL]`; VIRTUAL is the default
+    fn parse_generated_column(
+        &mut self,
+        table: String,
+        name: String,
+        data_type: Option<DataType>,
+    ) -> Result<AlterTableStmt> {
+        if !self.match_token(TokenType::As) {
+            self.advance(); // GENERATED
+            match &self.current().token_type {
+                TokenType::Identifier(w) if w.eq_ignore_ascii_case("ALWAYS") => self.advance(),
+                _ => return Err(self.error("Expected ALWAYS after GENERATED")),
+            }
+            self.expect(TokenType::As)?;
+        }
+        self.expect(TokenType::LParen)?;
+        let start = self.position;
+        let expr = self.parse_expr(0)?;
+        let expr_sql = self.tokens[start..self.position]
+            .iter()
+            .map(|t| t.token_type.to_sql())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.expect(TokenType::RParen)?;
+        let stored = match &self.current().token_type {
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("STORED") => {
+                self.advance();
+                true
+            }
+            TokenType::Identifier(w) if w.eq_ignore_ascii_case("VIRTUAL") => {
+                self.advance();
+                false
+            }
+            _ => false,
+        };
+        Ok(AlterTableStmt {
+            table,
+            action: AlterTableAction::AddGeneratedColumn {
+                name,
+                data_type,
+                expr,
+                expr_sql,
+                stored,
+            },
+        })
+    }
 }
 
 #[cfg(test)]
@@ -2319,4 +3054,26 @@ mod tests {
             _ => panic!("Expected CREATE TABLE statement"),
         }
     }
+
+    #[test]
+    fn test_parse_alter_table_drop_and_rename() {
+        match parse_sql("ALTER TABLE users DROP COLUMN age").unwrap() {
+            Statement::AlterTable(a) => {
+                assert_eq!(a.table, "users");
+                assert!(matches!(a.action, AlterTableAction::DropColumn(ref c) if c == "age"));
+            }
+            _ => panic!("Expected ALTER TABLE statement"),
+        }
+        match parse_sql("ALTER TABLE users RENAME name TO full_name").unwrap() {
+            Statement::AlterTable(a) => match a.action {
+                AlterTableAction::RenameColumn { from, to } => {
+                    assert_eq!(from, "name");
+                    assert_eq!(to, "full_name");
+                }
+                _ => panic!("Expected RENAME COLUMN"),
+            },
+            _ => panic!("Expected ALTER TABLE statement"),
+        }
+        assert!(parse_sql("ALTER TABLE users RENAME COLUMN name full_name").is_err());
+    }
 }
@@ -0,0 +1,121 @@
+//! Row-at-a-time operators: predicate filtering and projection
+
+use super::{fmt_expr, is_true, BoxedOperator, PhysicalOperator, RowEvaluator};
+use crate::sql::ast::Expr;
+use crate::types::{SqlRow, Value};
+use crate::Result;
+
+/// Passes through the input rows the predicate holds for
+///
+/// A predicate that fails to evaluate rejects the row, like an unknown
+/// comparison does.
+pub struct Filter<'a> {
+    input: BoxedOperator<'a>,
+    predicate: Expr,
+    evaluator: &'a dyn RowEvaluator,
+}
+
+impl<'a> Filter<'a> {
+    pub fn new(input: BoxedOperator<'a>, predicate: Expr, evaluator: &'a dyn RowEvaluator) -> Self {
+        Self {
+            input,
+            predicate,
+            evaluator,
+        }
+    }
+}
+
+impl PhysicalOperator for Filter<'_> {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        while let Some(row) = self.input.next()? {
+            if self
+                .evaluator
+                .eval(&self.predicate, &row)
+                .is_ok_and(|value| is_true(&value))
+            {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn describe(&self) -> String {
+        format!("Filter({})", fmt_expr(&self.predicate))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// Computes the output columns of each input row
+///
+/// Column references resolve by exact key first, then by `.{name}` suffix,
+/// so `id` finds `users.id`. Missing columns and expressions that fail to
+/// evaluate produce NULL.
+pub struct Project<'a> {
+    input: BoxedOperator<'a>,
+    items: Vec<(String, Expr)>,
+    evaluator: &'a dyn RowEvaluator,
+}
+
+impl<'a> Project<'a> {
+    /// `items` are `(output name, expression)` pairs with distinct names
+    pub fn new(
+        input: BoxedOperator<'a>,
+        items: Vec<(String, Expr)>,
+        evaluator: &'a dyn RowEvaluator,
+    ) -> Self {
+        Self {
+            input,
+            items,
+            evaluator,
+        }
+    }
+
+    fn column(row: &SqlRow, name: &str) -> Value {
+        if let Some(value) = row.get(name) {
+            return value.clone();
+        }
+        if name.contains('.') {
+            return Value::Null;
+        }
+        let suffix = format!(".{}", name);
+        row.iter()
+            .find(|(key, _)| key.ends_with(&suffix))
+            .map_or(Value::Null, |(_, value)| value.clone())
+    }
+}
+
+impl PhysicalOperator for Project<'_> {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        let Some(row) = self.input.next()? else {
+            return Ok(None);
+        };
+        let mut out = SqlRow::with_capacity(self.items.len());
+        for (name, expr) in &self.items {
+            let value = match expr {
+                Expr::Column(column) => Self::column(&row, column),
+                expr => self.evaluator.eval(expr, &row).unwrap_or(Value::Null),
+            };
+            out.insert(name.clone(), value);
+        }
+        Ok(Some(out))
+    }
+
+    fn describe(&self) -> String {
+        let items: Vec<String> = self
+            .items
+            .iter()
+            .map(|(name, expr)| match expr {
+                Expr::Column(column) if column == name => name.clone(),
+                expr => format!("{} AS {}", fmt_expr(expr), name),
+            })
+            .collect();
+        format!("Project({})", items.join(", "))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
@@ -0,0 +1,151 @@
+//! Equi-join operator
+
+use super::{BoxedOperator, PhysicalOperator, RuntimeFilter};
+use crate::types::{SqlRow, Value};
+use crate::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Join key; integers within f64's exact range share the numeric space with
+/// floats so `1 = 1.0` matches
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum JoinKey {
+    Numeric(u64),
+    Integer(i64),
+    Text(String),
+    Bool(bool),
+}
+
+impl JoinKey {
+    /// `None` for NULL and unhashable values, which never join
+    pub(super) fn from_value(value: &Value) -> Option<Self> {
+        const EXACT_MAX: i64 = 1i64 << 53;
+        let integer = |i: i64| {
+            if (-EXACT_MAX..=EXACT_MAX).contains(&i) {
+                JoinKey::Numeric((i as f64).to_bits())
+            } else {
+                JoinKey::Integer(i)
+            }
+        };
+        match value {
+            Value::Integer(i) => Some(integer(*i)),
+            // +0.0 folds -0.0 into 0.0
+            Value::Float(f) => Some(JoinKey::Numeric((f + 0.0).to_bits())),
+            Value::Timestamp(t) => Some(integer(t.as_micros())),
+            Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => value
+                .canonical_text()
+                .map(|t| JoinKey::Text(t.into_owned())),
+            Value::Bool(b) => Some(JoinKey::Bool(*b)),
+            _ => None,
+        }
+    }
+
+    /// 64-bit hash used as the runtime filter's bloom key
+    pub(super) fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Inner hash join on `probe.probe_key = build.build_key`
+///
+/// The build input is read completely into a hash table on the first call;
+/// probe rows then stream through, each emitted once per matching build row.
+/// Output rows hold the columns of both sides. With a [`RuntimeFilter`]
+/// attached, the build keys are published to it before the first probe row
+/// is pulled.
+pub struct HashJoin<'a> {
+    probe: BoxedOperator<'a>,
+    build: BoxedOperator<'a>,
+    probe_key: String,
+    build_key: String,
+    table: Option<HashMap<JoinKey, Vec<SqlRow>>>,
+    /// Probe row and its matches not yet emitted
+    pending: Option<(SqlRow, Vec<SqlRow>)>,
+    runtime_filter: Option<Arc<RuntimeFilter>>,
+}
+
+impl<'a> HashJoin<'a> {
+    pub fn new(
+        probe: BoxedOperator<'a>,
+        build: BoxedOperator<'a>,
+        probe_key: &str,
+        build_key: &str,
+    ) -> Self {
+        Self {
+            probe,
+            build,
+            probe_key: probe_key.to_string(),
+            build_key: build_key.to_string(),
+            table: None,
+            pending: None,
+            runtime_filter: None,
+        }
+    }
+
+    /// Publish the build keys to `filter`, which the probe side reads
+    pub fn with_runtime_filter(mut self, filter: Arc<RuntimeFilter>) -> Self {
+        self.runtime_filter = Some(filter);
+        self
+    }
+
+    fn build_table(&mut self) -> Result<HashMap<JoinKey, Vec<SqlRow>>> {
+        let mut table: HashMap<JoinKey, Vec<SqlRow>> = HashMap::new();
+        while let Some(row) = self.build.next()? {
+            if let Some(key) = row.get(&self.build_key).and_then(JoinKey::from_value) {
+                table.entry(key).or_default().push(row);
+            }
+        }
+        if let Some(filter) = &self.runtime_filter {
+            filter.publish(table.keys());
+        }
+        Ok(table)
+    }
+}
+
+impl PhysicalOperator for HashJoin<'_> {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.table.is_none() {
+            self.table = Some(self.build_table()?);
+        }
+        loop {
+            if let Some((probe_row, matches)) = self.pending.as_mut() {
+                if let Some(build_row) = matches.pop() {
+                    let mut joined = probe_row.clone();
+                    joined.extend(build_row);
+                    return Ok(Some(joined));
+                }
+                self.pending = None;
+            }
+            let Some(probe_row) = self.probe.next()? else {
+                return Ok(None);
+            };
+            let matches = probe_row
+                .get(&self.probe_key)
+                .and_then(JoinKey::from_value)
+                .and_then(|key| self.table.as_ref().and_then(|t| t.get(&key)));
+            if let Some(matches) = matches {
+                // Reversed so pop() emits matches in build order
+                let matches = matches.iter().rev().cloned().collect();
+                self.pending = Some((probe_row, matches));
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.runtime_filter {
+            Some(_) => format!(
+                "HashJoin({} = {}, runtime filter)",
+                self.probe_key, self.build_key
+            ),
+            None => format!("HashJoin({} = {})", self.probe_key, self.build_key),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.probe.as_ref(), self.build.as_ref()]
+    }
+}
@@ -0,0 +1,401 @@
+//! Physical operators (Volcano / iterator model)
+//!
+//! [`PhysicalPlanner`] compiles a SELECT into a tree of operators. Each
+//! operator pulls rows from its children one at a time through
+//! [`PhysicalOperator::next`], so a `Limit` above a `Scan` stops reading
+//! storage as soon as it has enough rows:
+//!
+//! ```text
+//! Limit(10)
+//!   Project(id, name)
+//!     Sort(ts DESC)
+//!       Filter(status = 'ok')
+//!         IndexScan(events.zone = 3)
+//! ```
+//!
+//! Rows flowing between operators are [`SqlRow`]s keyed by `{prefix}.{column}`
+//! plus the `__row_id__` / `__table__` markers, the same shape the executor's
+//! materialized path works on; `Project` switches to output column names.
+//!
+//! Expressions are evaluated through [`RowEvaluator`]. The executor plugs in
+//! its index-aware evaluation (MATCH, KNN_SEARCH, ST_*_3D per row); tests can
+//! use a plain [`ExprEvaluator`] and feed operators from a [`Values`] source,
+//! so every operator is testable without storage.
+
+mod filter;
+mod join;
+mod planner;
+mod runtime_filter;
+mod scan;
+mod sort;
+
+pub use filter::{Filter, Project};
+pub use join::HashJoin;
+pub use planner::PhysicalPlanner;
+pub use runtime_filter::RuntimeFilter;
+pub use scan::{IndexProbe, IndexScan, KnnScan, Scan, Values};
+pub use sort::{Limit, Sort};
+
+use crate::sql::ast::{BinaryOperator, Expr, UnaryOperator};
+use crate::sql::evaluator::ExprEvaluator;
+use crate::types::{SqlRow, Value};
+use crate::Result;
+use std::sync::Arc;
+
+/// A pull-based physical operator
+pub trait PhysicalOperator {
+    /// Next output row, or `None` once the operator is exhausted
+    fn next(&mut self) -> Result<Option<SqlRow>>;
+
+    /// One-line description used in plan output, e.g. `Filter(x > 1)`
+    fn describe(&self) -> String;
+
+    /// Input operators, in execution order
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        Vec::new()
+    }
+}
+
+/// Owned operator tree node
+pub type BoxedOperator<'a> = Box<dyn PhysicalOperator + 'a>;
+
+/// Evaluates expressions against the rows flowing through an operator tree
+pub trait RowEvaluator {
+    fn eval(&self, expr: &Expr, row: &SqlRow) -> Result<Value>;
+}
+
+impl RowEvaluator for ExprEvaluator {
+    fn eval(&self, expr: &Expr, row: &SqlRow) -> Result<Value> {
+        ExprEvaluator::eval(self, expr, row)
+    }
+}
+
+/// A compiled SELECT: operator tree plus output column names
+pub struct PhysicalPlan<'a> {
+    root: BoxedOperator<'a>,
+    columns: Vec<String>,
+    runtime_filters: Vec<Arc<RuntimeFilter>>,
+}
+
+impl<'a> PhysicalPlan<'a> {
+    pub fn new(root: BoxedOperator<'a>, columns: Vec<String>) -> Self {
+        Self {
+            root,
+            columns,
+            runtime_filters: Vec::new(),
+        }
+    }
+
+    /// Record the runtime filters wired between the plan's operators
+    pub fn with_runtime_filters(mut self, filters: Vec<Arc<RuntimeFilter>>) -> Self {
+        self.runtime_filters = filters;
+        self
+    }
+
+    /// Runtime filters pushed from joins into scans
+    pub fn runtime_filters(&self) -> &[Arc<RuntimeFilter>] {
+        &self.runtime_filters
+    }
+
+    /// Output column names, in SELECT order
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Operator tree, one operator per line, children indented
+    pub fn explain(&self) -> String {
+        explain(self.root.as_ref())
+    }
+
+    /// Run the plan to completion
+    pub fn execute(mut self) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+        let mut rows = Vec::new();
+        while let Some(mut row) = self.root.next()? {
+            rows.push(
+                self.columns
+                    .iter()
+                    .map(|name| row.remove(name).unwrap_or(Value::Null))
+                    .collect(),
+            );
+        }
+        Ok((self.columns, rows))
+    }
+}
+
+/// Render an operator tree, one operator per line, children indented
+pub fn explain(op: &dyn PhysicalOperator) -> String {
+    fn walk(op: &dyn PhysicalOperator, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&op.describe());
+        out.push('\n');
+        for child in op.children() {
+            walk(child, depth + 1, out);
+        }
+    }
+    let mut out = String::new();
+    walk(op, 0, &mut out);
+    out
+}
+
+/// Drain an operator
+pub fn collect(op: &mut dyn PhysicalOperator) -> Result<Vec<SqlRow>> {
+    let mut rows = Vec::new();
+    while let Some(row) = op.next()? {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// WHERE-clause truthiness: NULL and non-boolean values reject the row
+fn is_true(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Integer(i) => *i != 0,
+        Value::Float(f) => *f != 0.0 && !f.is_nan(),
+        _ => false,
+    }
+}
+
+/// Compact SQL-ish rendering of an expression for plan output
+pub(crate) fn fmt_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Column(name) => name.clone(),
+        Expr::Literal(value) => fmt_value(value),
+        Expr::Parameter(idx) => format!("?{}", idx),
+        Expr::BinaryOp { left, op, right } => {
+            let op = match op {
+                BinaryOperator::Eq => "=",
+                BinaryOperator::Ne => "!=",
+                BinaryOperator::Lt => "<",
+                BinaryOperator::Gt => ">",
+                BinaryOperator::Le => "<=",
+                BinaryOperator::Ge => ">=",
+                BinaryOperator::And => "AND",
+                BinaryOperator::Or => "OR",
+                BinaryOperator::Add => "+",
+                BinaryOperator::Sub => "-",
+                BinaryOperator::Mul => "*",
+                BinaryOperator::Div => "/",
+                BinaryOperator::Mod => "%",
+                BinaryOperator::L2Distance => "<->",
+                BinaryOperator::CosineDistance => "<=>",
+                BinaryOperator::DotProduct => "<#>",
+            };
+            format!("{} {} {}", fmt_expr(left), op, fmt_expr(right))
+        }
+        Expr::UnaryOp { op, expr } => match op {
+            UnaryOperator::Not => format!("NOT {}", fmt_expr(expr)),
+            UnaryOperator::Minus => format!("-{}", fmt_expr(expr)),
+            UnaryOperator::Plus => fmt_expr(expr),
+        },
+        Expr::FunctionCall { name, args, .. } => format!(
+            "{}({})",
+            name,
+            args.iter().map(fmt_expr).collect::<Vec<_>>().join(", ")
+        ),
+        Expr::IsNull { expr, negated } => format!(
+            "{} IS {}NULL",
+            fmt_expr(expr),
+            if *negated { "NOT " } else { "" }
+        ),
+        Expr::Match { column, query, .. } => format!("MATCH({}) AGAINST('{}')", column, query),
+        Expr::KnnSearch { column, k, .. } => format!("KNN_SEARCH({}, k={})", column, k),
+        other => format!("{:?}", other),
+    }
+}
+
+pub(crate) fn fmt_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Text(s) => format!("'{}'", s),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{Lexer, Parser, Statement};
+
+    fn expr(sql: &str) -> Expr {
+        let tokens = Lexer::new(&format!("SELECT * FROM t WHERE {}", sql))
+            .tokenize()
+            .unwrap();
+        match Parser::new(tokens).parse().unwrap() {
+            Statement::Select { stmt, .. } => stmt.where_clause.unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn rows(table: &str, data: &[(i64, &str)]) -> Vec<SqlRow> {
+        data.iter()
+            .map(|(id, name)| {
+                SqlRow::from([
+                    (format!("{}.id", table), Value::Integer(*id)),
+                    (format!("{}.name", table), Value::text(name.to_string())),
+                ])
+            })
+            .collect()
+    }
+
+    fn ids(op: &mut dyn PhysicalOperator, key: &str) -> Vec<i64> {
+        collect(op)
+            .unwrap()
+            .iter()
+            .map(|row| match row[key] {
+                Value::Integer(id) => id,
+                _ => panic!("{} is not an integer", key),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_sort_limit() {
+        let eval = ExprEvaluator::new();
+        let input = Values::new(rows(
+            "t",
+            &[(3, "c"), (1, "a"), (4, "d"), (2, "b"), (5, "e")],
+        ));
+        let filter = Filter::new(Box::new(input), expr("t.id > 1"), &eval);
+        let sort = Sort::new(Box::new(filter), vec![(expr("id"), false)], &eval);
+        let mut limit = Limit::new(Box::new(sort), Some(2), 1);
+        assert_eq!(ids(&mut limit, "t.id"), vec![4, 3]);
+        assert_eq!(
+            explain(&limit),
+            "Limit(2 OFFSET 1)\n  Sort(id DESC)\n    Filter(t.id > 1)\n      Values(5 rows)\n"
+        );
+
+        // Rows the predicate can't be evaluated for are dropped
+        let mut filter = Filter::new(
+            Box::new(Values::new(rows("t", &[(1, "a")]))),
+            expr("missing = 1"),
+            &eval,
+        );
+        assert!(collect(&mut filter).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_limit_stops_pulling() {
+        struct Counting(usize);
+        impl PhysicalOperator for Counting {
+            fn next(&mut self) -> Result<Option<SqlRow>> {
+                self.0 += 1;
+                Ok(Some(SqlRow::from([(
+                    "n".to_string(),
+                    Value::Integer(self.0 as i64),
+                )])))
+            }
+            fn describe(&self) -> String {
+                "Counting".to_string()
+            }
+        }
+        let mut limit = Limit::new(Box::new(Counting(0)), Some(3), 2);
+        assert_eq!(ids(&mut limit, "n"), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_project() {
+        let eval = ExprEvaluator::new();
+        let items = vec![
+            ("name".to_string(), Expr::Column("name".to_string())),
+            ("double".to_string(), expr("t.id * 2")),
+            ("gone".to_string(), Expr::Column("missing".to_string())),
+        ];
+        let mut project =
+            Project::new(Box::new(Values::new(rows("t", &[(21, "x")]))), items, &eval);
+        let out = collect(&mut project).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0]["name"], Value::text("x".to_string()));
+        assert_eq!(out[0]["double"], Value::Integer(42));
+        assert_eq!(out[0]["gone"], Value::Null);
+    }
+
+    #[test]
+    fn test_hash_join() {
+        let orders = Values::new(
+            [(10, 1), (11, 2), (12, 1), (13, 9)]
+                .iter()
+                .map(|(id, user)| {
+                    SqlRow::from([
+                        ("o.id".to_string(), Value::Integer(*id)),
+                        ("o.user_id".to_string(), Value::Integer(*user)),
+                    ])
+                })
+                .collect(),
+        );
+        let mut users = rows("u", &[(1, "ann"), (2, "bob"), (1, "ann again")]);
+        users.push(SqlRow::from([("u.id".to_string(), Value::Null)]));
+        let mut join = HashJoin::new(
+            Box::new(orders),
+            Box::new(Values::new(users)),
+            "o.user_id",
+            "u.id",
+        );
+        let joined = collect(&mut join).unwrap();
+        let pairs: Vec<(i64, String)> = joined
+            .iter()
+            .map(|row| match (&row["o.id"], &row["u.name"]) {
+                (Value::Integer(id), Value::Text(name)) => (*id, name.to_string()),
+                other => panic!("unexpected row {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (10, "ann".to_string()),
+                (10, "ann again".to_string()),
+                (11, "bob".to_string()),
+                (12, "ann".to_string()),
+                (12, "ann again".to_string()),
+            ]
+        );
+
+        // Integer and float keys of equal value match
+        let mut join = HashJoin::new(
+            Box::new(Values::new(vec![SqlRow::from([(
+                "a.k".to_string(),
+                Value::Float(2.0),
+            )])])),
+            Box::new(Values::new(rows("b", &[(2, "two")]))),
+            "a.k",
+            "b.id",
+        );
+        assert_eq!(collect(&mut join).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_runtime_filter() {
+        let filter = RuntimeFilter::new();
+        // Unpublished: everything may match
+        assert!(filter.may_match(&Value::Integer(7)));
+        assert!(!filter.is_active());
+
+        let eval = ExprEvaluator::new();
+        let build = Filter::new(
+            Box::new(Values::new(rows("d", &[(1, "a"), (2, "b"), (3, "c")]))),
+            expr("d.id >= 2"),
+            &eval,
+        );
+        let probe = Values::new(rows("f", &[(1, "x"), (2, "y"), (3, "z")]));
+        let mut join = HashJoin::new(Box::new(probe), Box::new(build), "f.id", "d.id")
+            .with_runtime_filter(filter.clone());
+        assert_eq!(ids(&mut join, "f.id"), vec![2, 3]);
+        assert_eq!(
+            explain(&join),
+            "HashJoin(f.id = d.id, runtime filter)\n  Values(3 rows)\n  Filter(d.id >= 2)\n    Values(3 rows)\n"
+        );
+
+        assert!(filter.is_active());
+        assert!(filter.may_match(&Value::Integer(2)));
+        assert!(filter.may_match(&Value::Float(3.0)));
+        assert!(!filter.may_match(&Value::Null));
+        let misses = (100..1100)
+            .filter(|i| !filter.may_match(&Value::Integer(*i)))
+            .count();
+        assert!(misses > 900, "bloom let through {} of 1000", 1000 - misses);
+        assert_eq!(filter.rejected(), 1 + misses as u64);
+    }
+}
@@ -0,0 +1,538 @@
+//! Compiles a SELECT into a physical operator tree
+//!
+//! The planner covers plain `SELECT .. FROM .. [WHERE] [ORDER BY] [LIMIT]`
+//! over one table or an inner equi-join of two tables. Anything else —
+//! aggregation, DISTINCT, outer joins, derived tables, subqueries, window
+//! functions — yields `None` and stays on the executor's existing paths.
+
+use super::{
+    BoxedOperator, Filter, HashJoin, IndexProbe, IndexScan, KnnScan, Limit, PhysicalPlan, Project,
+    RowEvaluator, RuntimeFilter, Scan, Sort,
+};
+use crate::database::index_metadata::IndexType;
+use crate::database::{MoteDB, VectorSearchOptions};
+use crate::sql::ast::{BinaryOperator, Expr, JoinType, SelectColumn, SelectStmt, TableRef};
+use crate::sql::optimizer::{QueryOptimizer, ScanMethod};
+use crate::types::{TableSchema, Value};
+use crate::Result;
+use std::sync::Arc;
+
+/// Functions the operators cannot evaluate row by row
+const NON_SCALAR_FUNCTIONS: &[&str] = &[
+    "COUNT",
+    "SUM",
+    "AVG",
+    "MIN",
+    "MAX",
+    "STDDEV",
+    "VARIANCE",
+    "APPROX_COUNT_DISTINCT",
+    "COUNT_ESTIMATE",
+    "UNNEST",
+];
+
+/// A table in the FROM clause
+struct Source {
+    table: String,
+    prefix: String,
+    schema: Arc<TableSchema>,
+    /// Columns the query reads; `None` reads every column
+    columns: Option<Vec<String>>,
+}
+
+pub struct PhysicalPlanner<'a> {
+    db: &'a Arc<MoteDB>,
+    optimizer: &'a QueryOptimizer,
+    evaluator: &'a dyn RowEvaluator,
+    /// Search options given to KNN scans
+    vector_search: VectorSearchOptions,
+}
+
+impl<'a> PhysicalPlanner<'a> {
+    pub fn new(
+        db: &'a Arc<MoteDB>,
+        optimizer: &'a QueryOptimizer,
+        evaluator: &'a dyn RowEvaluator,
+        vector_search: VectorSearchOptions,
+    ) -> Self {
+        Self {
+            db,
+            optimizer,
+            evaluator,
+            vector_search,
+        }
+    }
+
+    /// Compile `stmt` (with bind parameters already substituted), or `None`
+    /// when it uses something the operators don't cover
+    pub fn plan_select(&self, stmt: &SelectStmt) -> Result<Option<PhysicalPlan<'a>>> {
+        if !Self::is_supported(stmt) {
+            return Ok(None);
+        }
+        let Some((mut sources, join_keys)) = self.sources(stmt.from.as_ref())? else {
+            return Ok(None);
+        };
+        let Some(items) = Self::project_items(&stmt.columns, &sources) else {
+            return Ok(None);
+        };
+
+        let mut conjuncts = stmt
+            .where_clause
+            .as_ref()
+            .map(QueryOptimizer::conjuncts)
+            .unwrap_or_default();
+        let read = items
+            .iter()
+            .map(|(_, expr)| expr)
+            .chain(&conjuncts)
+            .chain(stmt.order_by.iter().flatten().map(|key| &key.expr));
+        let join_columns = join_keys.iter().flat_map(|(probe, build)| [probe, build]);
+        Self::project_sources(&mut sources, read, join_columns);
+        let mut runtime_filters = Vec::new();
+        let mut input: BoxedOperator<'a> = match (&sources[..], join_keys) {
+            ([source], None) => self.access_path(stmt, source, &mut conjuncts)?,
+            ([left, right], Some((probe_key, build_key))) => self.hash_join(
+                left,
+                right,
+                &probe_key,
+                &build_key,
+                &mut conjuncts,
+                &mut runtime_filters,
+            )?,
+            _ => return Ok(None),
+        };
+
+        input = self.filtered(input, conjuncts);
+        if let Some(order_by) = &stmt.order_by {
+            let keys = order_by
+                .iter()
+                .map(|key| (Self::sort_key(&key.expr, &items), key.asc))
+                .collect();
+            input = Box::new(Sort::new(input, keys, self.evaluator));
+        }
+        let columns = items.iter().map(|(name, _)| name.clone()).collect();
+        input = Box::new(Project::new(input, items, self.evaluator));
+        if stmt.limit.is_some() || stmt.offset.unwrap_or(0) > 0 {
+            input = Box::new(Limit::new(input, stmt.limit, stmt.offset.unwrap_or(0)));
+        }
+        Ok(Some(
+            PhysicalPlan::new(input, columns).with_runtime_filters(runtime_filters),
+        ))
+    }
+
+    fn is_supported(stmt: &SelectStmt) -> bool {
+        let columns_ok = stmt.columns.iter().all(|col| match col {
+            SelectColumn::Expr(expr, _) => Self::is_row_expr(expr),
+            _ => true,
+        });
+        let order_ok = stmt
+            .order_by
+            .iter()
+            .flatten()
+            .all(|key| Self::is_row_expr(&key.expr));
+        !stmt.distinct
+            && stmt.group_by.is_none()
+            && stmt.having.is_none()
+            && stmt.latest_by.is_none()
+            && stmt.sample_by.is_none()
+            && stmt.fill.is_none()
+            && stmt.after_cursor.is_none()
+            && columns_ok
+            && order_ok
+            && stmt.where_clause.as_ref().is_none_or(Self::is_row_expr)
+    }
+
+    /// Whether `expr` evaluates from a single row (no aggregates, window
+    /// functions or subqueries)
+    fn is_row_expr(expr: &Expr) -> bool {
+        match expr {
+            Expr::Subquery(_)
+            | Expr::Exists(_)
+            | Expr::Correlated(_)
+            | Expr::WindowFunction { .. } => false,
+            Expr::FunctionCall { name, args, .. } => {
+                !NON_SCALAR_FUNCTIONS.contains(&name.to_uppercase().as_str())
+                    && args.iter().all(Self::is_row_expr)
+            }
+            Expr::BinaryOp { left, right, .. } => {
+                Self::is_row_expr(left) && Self::is_row_expr(right)
+            }
+            Expr::UnaryOp { expr, .. } | Expr::IsNull { expr, .. } => Self::is_row_expr(expr),
+            Expr::In { expr, list, .. } => {
+                Self::is_row_expr(expr) && list.iter().all(Self::is_row_expr)
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => Self::is_row_expr(expr) && Self::is_row_expr(low) && Self::is_row_expr(high),
+            Expr::Like { expr, pattern, .. } => {
+                Self::is_row_expr(expr) && Self::is_row_expr(pattern)
+            }
+            Expr::Case { whens, else_expr } => {
+                whens
+                    .iter()
+                    .all(|(cond, value)| Self::is_row_expr(cond) && Self::is_row_expr(value))
+                    && else_expr.as_deref().is_none_or(Self::is_row_expr)
+            }
+            _ => true,
+        }
+    }
+
+    /// FROM tables, plus `(probe key, build key)` for a join
+    #[allow(clippy::type_complexity)]
+    fn sources(
+        &self,
+        from: Option<&TableRef>,
+    ) -> Result<Option<(Vec<Source>, Option<(String, String)>)>> {
+        let source = |table_ref: &TableRef| -> Result<Option<Source>> {
+            match table_ref {
+                TableRef::Table { name, alias } => Ok(Some(Source {
+                    table: name.clone(),
+                    prefix: alias.clone().unwrap_or_else(|| name.clone()),
+                    schema: self.db.get_table_schema(name)?,
+                    columns: None,
+                })),
+                _ => Ok(None),
+            }
+        };
+        match from {
+            Some(TableRef::Join {
+                left,
+                right,
+                join_type: JoinType::Inner,
+                on_condition,
+            }) => {
+                let (Some(left), Some(right)) = (source(left)?, source(right)?) else {
+                    return Ok(None);
+                };
+                if left.prefix == right.prefix {
+                    return Ok(None);
+                }
+                let keys = match on_condition {
+                    Expr::BinaryOp {
+                        left: a,
+                        op: BinaryOperator::Eq,
+                        right: b,
+                    } => match (a.as_ref(), b.as_ref()) {
+                        (Expr::Column(a), Expr::Column(b)) => {
+                            match (
+                                Self::resolve(a, &left, &right),
+                                Self::resolve(b, &left, &right),
+                            ) {
+                                (Some((true, a)), Some((false, b))) => Some((a, b)),
+                                (Some((false, a)), Some((true, b))) => Some((b, a)),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                Ok(keys.map(|keys| (vec![left, right], Some(keys))))
+            }
+            Some(table_ref) => Ok(source(table_ref)?.map(|s| (vec![s], None))),
+            None => Ok(None),
+        }
+    }
+
+    /// `input` filtered by the conjunction of `conjuncts`
+    fn filtered(&self, input: BoxedOperator<'a>, conjuncts: Vec<Expr>) -> BoxedOperator<'a> {
+        let predicate = conjuncts.into_iter().reduce(|left, right| Expr::BinaryOp {
+            left: Box::new(left),
+            op: BinaryOperator::And,
+            right: Box::new(right),
+        });
+        match predicate {
+            Some(predicate) => Box::new(Filter::new(input, predicate, self.evaluator)),
+            None => input,
+        }
+    }
+
+    /// Hash join of `left` (probe) and `right` (build). Conjuncts reading only
+    /// one side move below the join; when the build side is filtered, its
+    /// keys are pushed into the probe scan as a [`RuntimeFilter`].
+    fn hash_join(
+        &self,
+        left: &Source,
+        right: &Source,
+        probe_key: &str,
+        build_key: &str,
+        conjuncts: &mut Vec<Expr>,
+        runtime_filters: &mut Vec<Arc<RuntimeFilter>>,
+    ) -> Result<BoxedOperator<'a>> {
+        let (mut left_preds, mut right_preds) = (Vec::new(), Vec::new());
+        conjuncts.retain(|conjunct| match Self::side(conjunct, left, right) {
+            Some(true) => {
+                left_preds.push(conjunct.clone());
+                false
+            }
+            Some(false) => {
+                right_preds.push(conjunct.clone());
+                false
+            }
+            None => true,
+        });
+
+        let runtime_filter = (!right_preds.is_empty()).then(RuntimeFilter::new);
+        let mut probe = self.scan(left)?;
+        if let Some(filter) = &runtime_filter {
+            probe = probe.with_runtime_filter(probe_key, filter.clone());
+        }
+        let build = self.scan(right)?;
+        let mut join = HashJoin::new(
+            self.filtered(Box::new(probe), left_preds),
+            self.filtered(Box::new(build), right_preds),
+            probe_key,
+            build_key,
+        );
+        if let Some(filter) = runtime_filter {
+            runtime_filters.push(filter.clone());
+            join = join.with_runtime_filter(filter);
+        }
+        Ok(Box::new(join))
+    }
+
+    /// Join input a conjunct reads from (`true` for left); `None` when it
+    /// reads both, neither, or isn't a plain scalar expression
+    fn side(expr: &Expr, left: &Source, right: &Source) -> Option<bool> {
+        let mut columns = Vec::new();
+        if !Self::column_refs(expr, true, &mut columns) {
+            return None;
+        }
+        let mut sides = columns
+            .into_iter()
+            .map(|column| Self::resolve(column, left, right).map(|(is_left, _)| is_left));
+        let first = sides.next()??;
+        sides.all(|side| side == Some(first)).then_some(first)
+    }
+
+    /// Collect the columns `expr` reads; `false` if it contains anything but
+    /// columns, literals, operators and scalar function calls, or with
+    /// `scalar_only` unset, also index-backed predicates (MATCH, KNN, ST_*)
+    fn column_refs<'e>(expr: &'e Expr, scalar_only: bool, out: &mut Vec<&'e str>) -> bool {
+        let mut refs = |expr: &'e Expr| Self::column_refs(expr, scalar_only, out);
+        match expr {
+            Expr::Column(name) => {
+                out.push(name);
+                true
+            }
+            Expr::Literal(_) | Expr::Parameter(_) => true,
+            Expr::BinaryOp { left, right, .. } => refs(left) && refs(right),
+            Expr::UnaryOp { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::InHashset { expr, .. } => refs(expr),
+            Expr::In { expr, list, .. } => refs(expr) && list.iter().all(refs),
+            Expr::Between {
+                expr, low, high, ..
+            } => refs(expr) && refs(low) && refs(high),
+            Expr::Like { expr, pattern, .. } => refs(expr) && refs(pattern),
+            Expr::FunctionCall { args, .. } => args.iter().all(refs),
+            Expr::Case { whens, else_expr } => {
+                whens.iter().all(|(cond, value)| refs(cond) && refs(value))
+                    && else_expr.as_deref().is_none_or(refs)
+            }
+            _ if scalar_only => false,
+            Expr::Match { column, .. }
+            | Expr::KnnSearch { column, .. }
+            | Expr::KnnDistance { column, .. }
+            | Expr::StWithin3D { column, .. }
+            | Expr::StDistance3D { column, .. }
+            | Expr::StKnn3D { column, .. }
+            | Expr::StRadius3D { column, .. } => {
+                out.push(column);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Narrow each source to the columns `exprs` and the join keys read.
+    /// Sources stay unprojected when an expression can't be analysed.
+    fn project_sources<'e>(
+        sources: &mut [Source],
+        exprs: impl Iterator<Item = &'e Expr>,
+        join_columns: impl Iterator<Item = &'e String>,
+    ) {
+        let mut names: Vec<&str> = join_columns.map(String::as_str).collect();
+        for expr in exprs {
+            if !Self::column_refs(expr, false, &mut names) {
+                return;
+            }
+        }
+        for source in sources.iter_mut() {
+            let mut columns: Vec<String> = Vec::new();
+            for name in &names {
+                let column = match name.split_once('.') {
+                    Some((qualifier, column))
+                        if qualifier == source.prefix || qualifier == source.table =>
+                    {
+                        column
+                    }
+                    Some(_) => continue,
+                    None => name,
+                };
+                if source.schema.get_column(column).is_some()
+                    && !columns.iter().any(|c| c == column)
+                {
+                    columns.push(column.to_string());
+                }
+            }
+            if columns.len() < source.schema.columns.len() {
+                source.columns = Some(columns);
+            }
+        }
+    }
+
+    /// Full scan of `source`, reading only the columns the query needs
+    fn scan(&self, source: &Source) -> Result<Scan> {
+        let scan = Scan::new(self.db.clone(), &source.table, &source.prefix)?;
+        Ok(match &source.columns {
+            Some(columns) => scan.with_projection(columns),
+            None => scan,
+        })
+    }
+
+    /// Qualify a join column: `(is_left, "{prefix}.{column}")`
+    fn resolve(column: &str, left: &Source, right: &Source) -> Option<(bool, String)> {
+        let in_source = |source: &Source, name: &str| source.schema.get_column(name).is_some();
+        if let Some((prefix, name)) = column.split_once('.') {
+            return [(true, left), (false, right)]
+                .into_iter()
+                .find(|(_, s)| s.prefix == prefix && in_source(s, name))
+                .map(|(is_left, _)| (is_left, column.to_string()));
+        }
+        match (in_source(left, column), in_source(right, column)) {
+            (true, false) => Some((true, format!("{}.{}", left.prefix, column))),
+            (false, true) => Some((false, format!("{}.{}", right.prefix, column))),
+            _ => None,
+        }
+    }
+
+    /// Leaf operator for a single table. A top-level KNN_SEARCH conjunct
+    /// becomes a [`KnnScan`] and leaves `conjuncts`; otherwise the optimizer's
+    /// point or range probe becomes an [`IndexScan`] (the probed conjunct
+    /// stays as a filter).
+    fn access_path(
+        &self,
+        stmt: &SelectStmt,
+        source: &Source,
+        conjuncts: &mut Vec<Expr>,
+    ) -> Result<BoxedOperator<'a>> {
+        let (db, table, prefix) = (self.db.clone(), &source.table, &source.prefix);
+        let knn = conjuncts.iter().enumerate().find_map(|(pos, conjunct)| {
+            let Expr::KnnSearch {
+                column,
+                query_vector,
+                k,
+            } = conjunct
+            else {
+                return None;
+            };
+            self.db
+                .index_registry
+                .find_by_column(table, column, IndexType::Vector)
+                .filter(|index_name| self.db.has_vector_index(index_name))
+                .map(|index_name| (pos, index_name, query_vector.clone(), *k))
+        });
+        if let Some((pos, index_name, query_vector, k)) = knn {
+            conjuncts.remove(pos);
+            return Ok(Box::new(KnnScan::new(
+                db,
+                table,
+                prefix,
+                &index_name,
+                query_vector,
+                k,
+                self.vector_search,
+            )?));
+        }
+
+        if conjuncts.is_empty() || self.db.is_async_index_pipeline_active() {
+            return Ok(Box::new(self.scan(source)?));
+        }
+        let probe = match self.optimizer.optimize_select(stmt, &[])?.scan_method {
+            ScanMethod::PointQuery { column, value, .. } => {
+                Some((column, IndexProbe::Point(value)))
+            }
+            ScanMethod::RangeQuery {
+                column,
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+                ..
+            } => Some((
+                column,
+                IndexProbe::Range {
+                    start,
+                    start_inclusive,
+                    end,
+                    end_inclusive,
+                },
+            )),
+            _ => None,
+        };
+        match probe {
+            Some((column, probe))
+                if self
+                    .db
+                    .column_indexes
+                    .contains_key(&format!("{}.{}", table, column)) =>
+            {
+                Ok(Box::new(IndexScan::new(db, table, prefix, &column, probe)?))
+            }
+            _ => Ok(Box::new(self.scan(source)?)),
+        }
+    }
+
+    /// `(output name, expression)` per output column; `None` for a star mixed
+    /// with other columns or duplicate output names
+    fn project_items(columns: &[SelectColumn], sources: &[Source]) -> Option<Vec<(String, Expr)>> {
+        let items: Vec<(String, Expr)> = match columns {
+            [SelectColumn::Star] => sources
+                .iter()
+                .flat_map(|source| {
+                    source.schema.columns.iter().map(|col| {
+                        (
+                            col.name.clone(),
+                            Expr::Column(format!("{}.{}", source.prefix, col.name)),
+                        )
+                    })
+                })
+                .collect(),
+            columns => columns
+                .iter()
+                .map(|col| match col {
+                    SelectColumn::Star => None,
+                    SelectColumn::Column(name) => Some((name.clone(), Expr::Column(name.clone()))),
+                    SelectColumn::ColumnWithAlias(name, alias) => {
+                        Some((alias.clone(), Expr::Column(name.clone())))
+                    }
+                    SelectColumn::Expr(expr, alias) => Some((
+                        alias.clone().unwrap_or_else(|| format!("{:?}", expr)),
+                        expr.clone(),
+                    )),
+                })
+                .collect::<Option<_>>()?,
+        };
+        let mut names: Vec<&String> = items.iter().map(|(name, _)| name).collect();
+        names.sort();
+        names.dedup();
+        (names.len() == items.len()).then_some(items)
+    }
+
+    /// ORDER BY key as an expression over input rows: output names and
+    /// aliases resolve to their SELECT expression, `ORDER BY 2` to the second
+    /// output column
+    fn sort_key(expr: &Expr, items: &[(String, Expr)]) -> Expr {
+        match expr {
+            Expr::Column(name) => items
+                .iter()
+                .find(|(out, _)| out == name || out.rsplit('.').next() == Some(name.as_str()))
+                .map_or_else(|| expr.clone(), |(_, item)| item.clone()),
+            Expr::Literal(Value::Integer(n)) if *n >= 1 && (*n as usize) <= items.len() => {
+                items[*n as usize - 1].1.clone()
+            }
+            _ => expr.clone(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Runtime filters: join keys pushed from a hash join's build side into the
+//! probe-side scan
+//!
+//! For `fact JOIN dim ON fact.k = dim.k WHERE dim.x = ..` the build side is
+//! usually small after its filter while the probe side is the wide table.
+//! Once [`HashJoin`](super::HashJoin) has read its build input it publishes a
+//! bloom filter of the build keys; the probe [`Scan`](super::Scan) checks each
+//! row's join key against it straight from the encoded row and skips
+//! non-matching rows before deserializing the rest of their columns.
+
+use super::join::JoinKey;
+use crate::storage::lsm::BloomFilter;
+use crate::types::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Bits per build key (~1% false positives)
+const BITS_PER_KEY: usize = 10;
+
+/// Build sides larger than this don't publish a filter: the bloom would cost
+/// more memory than the rows it could skip are worth
+const MAX_BUILD_KEYS: usize = 1 << 20;
+
+/// Bloom filter over a join's build keys, shared by the join and the scan it
+/// filters
+#[derive(Debug, Default)]
+pub struct RuntimeFilter {
+    bloom: OnceLock<Option<BloomFilter>>,
+    rejected: AtomicU64,
+}
+
+impl RuntimeFilter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Publish the build keys; only the first call takes effect. Passing more
+    /// than [`MAX_BUILD_KEYS`] keys publishes "no filter", which lets every
+    /// row through.
+    pub(super) fn publish<'k>(&self, keys: impl ExactSizeIterator<Item = &'k JoinKey>) {
+        let bloom = (keys.len() <= MAX_BUILD_KEYS).then(|| {
+            let mut bloom = BloomFilter::new(keys.len(), BITS_PER_KEY);
+            for key in keys {
+                bloom.insert(&key.fingerprint().to_le_bytes());
+            }
+            bloom
+        });
+        let _ = self.bloom.set(bloom);
+    }
+
+    /// Whether the filter has been published and can reject rows
+    pub fn is_active(&self) -> bool {
+        matches!(self.bloom.get(), Some(Some(_)))
+    }
+
+    /// `false` only for values that certainly match no build key. NULLs never
+    /// join, so they are rejected too once the filter is active.
+    pub fn may_match(&self, value: &Value) -> bool {
+        let Some(Some(bloom)) = self.bloom.get() else {
+            return true;
+        };
+        let hit = JoinKey::from_value(value)
+            .is_some_and(|key| bloom.may_contain(&key.fingerprint().to_le_bytes()));
+        if !hit {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    }
+
+    /// Probe rows skipped so far
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
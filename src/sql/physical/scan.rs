@@ -0,0 +1,389 @@
+//! Leaf operators: table scans, index probes and literal row sources
+
+use super::{fmt_value, PhysicalOperator, RuntimeFilter};
+use crate::database::crud::TableRowStreamingIterator;
+use crate::database::{MoteDB, VectorSearchOptions};
+use crate::types::{ArcVec, Row, RowId, SqlRow, Value};
+use crate::Result;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Rows fetched per batch by the index-driven scans
+const FETCH_BATCH: usize = 256;
+
+/// Maps storage rows of one table to `{prefix}.{column}` keyed rows
+struct RowShape {
+    keys: Vec<String>,
+    table: Value,
+    /// Schema positions to emit, ascending; `None` emits every column
+    projection: Option<Vec<usize>>,
+}
+
+impl RowShape {
+    fn new(db: &MoteDB, table: &str, prefix: &str) -> Result<Self> {
+        let schema = db.get_table_schema(table)?;
+        Ok(Self {
+            keys: schema
+                .columns
+                .iter()
+                .map(|c| format!("{}.{}", prefix, c.name))
+                .collect(),
+            table: Value::text(table.to_string()),
+            projection: None,
+        })
+    }
+
+    fn to_sql_row(&self, row_id: RowId, row: Row) -> SqlRow {
+        let mut sql_row = SqlRow::with_capacity(self.keys.len() + 2);
+        sql_row.insert("__row_id__".to_string(), Value::Integer(row_id as i64));
+        sql_row.insert("__table__".to_string(), self.table.clone());
+        let mut values = row.into_iter();
+        match &self.projection {
+            Some(positions) => {
+                let mut next = 0;
+                for &pos in positions {
+                    let value = values.nth(pos - next).unwrap_or(Value::Null);
+                    next = pos + 1;
+                    sql_row.insert(self.keys[pos].clone(), value);
+                }
+            }
+            None => {
+                for key in &self.keys {
+                    sql_row.insert(key.clone(), values.next().unwrap_or(Value::Null));
+                }
+            }
+        }
+        sql_row
+    }
+}
+
+/// Fetches rows for a stream of row ids in batches, keeping the id order
+struct RowFetcher {
+    ids: Box<dyn Iterator<Item = Result<RowId>>>,
+    buffer: VecDeque<SqlRow>,
+}
+
+impl RowFetcher {
+    fn new(ids: Vec<RowId>) -> Self {
+        Self::streaming(ids.into_iter().map(Ok))
+    }
+
+    /// Pull ids lazily, one batch per fetch
+    fn streaming(ids: impl Iterator<Item = Result<RowId>> + 'static) -> Self {
+        Self {
+            ids: Box::new(ids),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    fn next(&mut self, db: &MoteDB, table: &str, shape: &RowShape) -> Result<Option<SqlRow>> {
+        while self.buffer.is_empty() {
+            let batch = self
+                .ids
+                .by_ref()
+                .take(FETCH_BATCH)
+                .collect::<Result<Vec<_>>>()?;
+            if batch.is_empty() {
+                break;
+            }
+            for (row_id, row) in db.get_table_rows_batch(table, &batch)? {
+                if let Some(row) = row {
+                    self.buffer.push_back(shape.to_sql_row(row_id, row));
+                }
+            }
+        }
+        Ok(self.buffer.pop_front())
+    }
+}
+
+/// Literal rows, e.g. an already materialized input or a test fixture
+pub struct Values {
+    rows: std::vec::IntoIter<SqlRow>,
+    len: usize,
+}
+
+impl Values {
+    pub fn new(rows: Vec<SqlRow>) -> Self {
+        Self {
+            len: rows.len(),
+            rows: rows.into_iter(),
+        }
+    }
+}
+
+impl PhysicalOperator for Values {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        Ok(self.rows.next())
+    }
+
+    fn describe(&self) -> String {
+        format!("Values({} rows)", self.len)
+    }
+}
+
+/// Sequential scan over every row of a table
+pub struct Scan {
+    db: Arc<MoteDB>,
+    table: String,
+    prefix: String,
+    shape: RowShape,
+    rows: Option<TableRowStreamingIterator>,
+    /// Schema position of the filtered column and the filter it checks
+    runtime_filter: Option<(usize, Arc<RuntimeFilter>)>,
+}
+
+impl Scan {
+    /// Scan `table`, naming its columns `{prefix}.{column}`
+    pub fn new(db: Arc<MoteDB>, table: &str, prefix: &str) -> Result<Self> {
+        let shape = RowShape::new(&db, table, prefix)?;
+        Ok(Self {
+            db,
+            table: table.to_string(),
+            prefix: prefix.to_string(),
+            shape,
+            rows: None,
+            runtime_filter: None,
+        })
+    }
+
+    /// Only read `columns` (schema names) of each row; the other columns are
+    /// neither decoded nor emitted. Unknown names are ignored.
+    pub fn with_projection(mut self, columns: &[String]) -> Self {
+        let mut positions: Vec<usize> = columns
+            .iter()
+            .filter_map(|name| {
+                let key = format!("{}.{}", self.prefix, name);
+                self.shape.keys.iter().position(|k| *k == key)
+            })
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        self.shape.projection = Some(positions);
+        self
+    }
+
+    /// Skip rows whose `column` (a `{prefix}.{column}` key) can't pass
+    /// `filter`, checked before the row is deserialized. Ignored for unknown
+    /// columns.
+    pub fn with_runtime_filter(mut self, column: &str, filter: Arc<RuntimeFilter>) -> Self {
+        self.runtime_filter = self
+            .shape
+            .keys
+            .iter()
+            .position(|key| key == column)
+            .map(|pos| (pos, filter));
+        self
+    }
+}
+
+impl PhysicalOperator for Scan {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.rows.is_none() {
+            let mut rows = self
+                .db
+                .scan_table_columns_streaming(&self.table, self.shape.projection.as_deref())?;
+            // The join publishes its filter before pulling the first probe row
+            if let Some((col_idx, filter)) = &self.runtime_filter {
+                if filter.is_active() {
+                    let filter = filter.clone();
+                    rows = rows.with_prefilter(*col_idx, move |value| filter.may_match(value));
+                }
+            }
+            self.rows = Some(rows);
+        }
+        match self.rows.as_mut().and_then(Iterator::next) {
+            Some(row) => {
+                let (row_id, row) = row?;
+                Ok(Some(self.shape.to_sql_row(row_id, row)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut out = if self.prefix == self.table {
+            format!("Scan({}", self.table)
+        } else {
+            format!("Scan({} AS {}", self.table, self.prefix)
+        };
+        if let Some(positions) = &self.shape.projection {
+            let names: Vec<&str> = positions
+                .iter()
+                .map(|&pos| {
+                    let key = &self.shape.keys[pos];
+                    &key[self.prefix.len() + 1..]
+                })
+                .collect();
+            out.push_str(&format!(" [{}]", names.join(", ")));
+        }
+        if let Some((col_idx, _)) = &self.runtime_filter {
+            out.push_str(&format!(
+                ", runtime filter on {}",
+                self.shape.keys[*col_idx]
+            ));
+        }
+        out.push(')');
+        out
+    }
+}
+
+/// Lookup performed by an [`IndexScan`]
+#[derive(Debug, Clone)]
+pub enum IndexProbe {
+    /// `column = value`
+    Point(Value),
+    /// `column` between two bounds
+    Range {
+        start: Value,
+        start_inclusive: bool,
+        end: Value,
+        end_inclusive: bool,
+    },
+}
+
+/// Rows matched by a column index probe, in index order
+pub struct IndexScan {
+    db: Arc<MoteDB>,
+    table: String,
+    column: String,
+    probe: IndexProbe,
+    shape: RowShape,
+    fetcher: Option<RowFetcher>,
+}
+
+impl IndexScan {
+    pub fn new(
+        db: Arc<MoteDB>,
+        table: &str,
+        prefix: &str,
+        column: &str,
+        probe: IndexProbe,
+    ) -> Result<Self> {
+        let shape = RowShape::new(&db, table, prefix)?;
+        Ok(Self {
+            db,
+            table: table.to_string(),
+            column: column.to_string(),
+            probe,
+            shape,
+            fetcher: None,
+        })
+    }
+}
+
+impl PhysicalOperator for IndexScan {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.fetcher.is_none() {
+            let fetcher = match &self.probe {
+                IndexProbe::Point(value) => {
+                    RowFetcher::new(self.db.query_by_column(&self.table, &self.column, value)?)
+                }
+                IndexProbe::Range {
+                    start,
+                    start_inclusive,
+                    end,
+                    end_inclusive,
+                } => RowFetcher::streaming(self.db.query_by_column_between_iter(
+                    &self.table,
+                    &self.column,
+                    start,
+                    *start_inclusive,
+                    end,
+                    *end_inclusive,
+                )?),
+            };
+            self.fetcher = Some(fetcher);
+        }
+        match self.fetcher.as_mut() {
+            Some(fetcher) => fetcher.next(&self.db, &self.table, &self.shape),
+            None => Ok(None),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.probe {
+            IndexProbe::Point(value) => {
+                format!(
+                    "IndexScan({}.{} = {})",
+                    self.table,
+                    self.column,
+                    fmt_value(value)
+                )
+            }
+            IndexProbe::Range {
+                start,
+                start_inclusive,
+                end,
+                end_inclusive,
+            } => format!(
+                "IndexScan({}.{} in {}{}, {}{})",
+                self.table,
+                self.column,
+                if *start_inclusive { "[" } else { "(" },
+                fmt_value(start),
+                fmt_value(end),
+                if *end_inclusive { "]" } else { ")" },
+            ),
+        }
+    }
+}
+
+/// The `k` nearest rows from a vector index, closest first
+pub struct KnnScan {
+    db: Arc<MoteDB>,
+    table: String,
+    index_name: String,
+    query: ArcVec,
+    k: usize,
+    options: VectorSearchOptions,
+    shape: RowShape,
+    fetcher: Option<RowFetcher>,
+}
+
+impl KnnScan {
+    pub fn new(
+        db: Arc<MoteDB>,
+        table: &str,
+        prefix: &str,
+        index_name: &str,
+        query: ArcVec,
+        k: usize,
+        options: VectorSearchOptions,
+    ) -> Result<Self> {
+        let shape = RowShape::new(&db, table, prefix)?;
+        Ok(Self {
+            db,
+            table: table.to_string(),
+            index_name: index_name.to_string(),
+            query,
+            k,
+            options,
+            shape,
+            fetcher: None,
+        })
+    }
+}
+
+impl PhysicalOperator for KnnScan {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.fetcher.is_none() {
+            let hits = self.db.vector_search_with_options(
+                &self.index_name,
+                self.query.as_slice(),
+                self.k,
+                self.options,
+            )?;
+            self.fetcher = Some(RowFetcher::new(
+                hits.into_iter().map(|(id, _)| id).collect(),
+            ));
+        }
+        match self.fetcher.as_mut() {
+            Some(fetcher) => fetcher.next(&self.db, &self.table, &self.shape),
+            None => Ok(None),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("KnnScan({}, k={})", self.index_name, self.k)
+    }
+}
@@ -0,0 +1,131 @@
+//! Ordering operators: ORDER BY and LIMIT / OFFSET
+
+use super::{fmt_expr, BoxedOperator, PhysicalOperator, RowEvaluator};
+use crate::sql::ast::Expr;
+use crate::types::{SqlRow, Value};
+use crate::Result;
+use std::cmp::Ordering;
+
+/// Sorts its whole input by `(expression, ascending)` keys
+///
+/// The sort is stable, so rows with equal keys keep their input order.
+pub struct Sort<'a> {
+    input: BoxedOperator<'a>,
+    keys: Vec<(Expr, bool)>,
+    evaluator: &'a dyn RowEvaluator,
+    sorted: Option<std::vec::IntoIter<SqlRow>>,
+}
+
+impl<'a> Sort<'a> {
+    pub fn new(
+        input: BoxedOperator<'a>,
+        keys: Vec<(Expr, bool)>,
+        evaluator: &'a dyn RowEvaluator,
+    ) -> Self {
+        Self {
+            input,
+            keys,
+            evaluator,
+            sorted: None,
+        }
+    }
+
+    fn sort_input(&mut self) -> Result<Vec<SqlRow>> {
+        let mut keyed: Vec<(Vec<Value>, SqlRow)> = Vec::new();
+        while let Some(row) = self.input.next()? {
+            let key = self
+                .keys
+                .iter()
+                .map(|(expr, _)| self.evaluator.eval(expr, &row))
+                .collect::<Result<Vec<_>>>()?;
+            keyed.push((key, row));
+        }
+        keyed.sort_by(|(a, _), (b, _)| {
+            for ((x, y), (_, asc)) in a.iter().zip(b).zip(&self.keys) {
+                let ord = x.partial_cmp(y).unwrap_or(Ordering::Equal);
+                if ord != Ordering::Equal {
+                    return if *asc { ord } else { ord.reverse() };
+                }
+            }
+            Ordering::Equal
+        });
+        Ok(keyed.into_iter().map(|(_, row)| row).collect())
+    }
+}
+
+impl PhysicalOperator for Sort<'_> {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.sorted.is_none() {
+            self.sorted = Some(self.sort_input()?.into_iter());
+        }
+        Ok(self.sorted.as_mut().and_then(Iterator::next))
+    }
+
+    fn describe(&self) -> String {
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|(expr, asc)| format!("{}{}", fmt_expr(expr), if *asc { "" } else { " DESC" }))
+            .collect();
+        format!("Sort({})", keys.join(", "))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// Skips `offset` rows, then passes through at most `limit` rows and stops
+/// pulling from its input
+pub struct Limit<'a> {
+    input: BoxedOperator<'a>,
+    limit: Option<usize>,
+    offset: usize,
+    skipped: bool,
+    emitted: usize,
+}
+
+impl<'a> Limit<'a> {
+    pub fn new(input: BoxedOperator<'a>, limit: Option<usize>, offset: usize) -> Self {
+        Self {
+            input,
+            limit,
+            offset,
+            skipped: false,
+            emitted: 0,
+        }
+    }
+}
+
+impl PhysicalOperator for Limit<'_> {
+    fn next(&mut self) -> Result<Option<SqlRow>> {
+        if self.limit.is_some_and(|limit| self.emitted >= limit) {
+            return Ok(None);
+        }
+        if !self.skipped {
+            self.skipped = true;
+            for _ in 0..self.offset {
+                if self.input.next()?.is_none() {
+                    return Ok(None);
+                }
+            }
+        }
+        let row = self.input.next()?;
+        if row.is_some() {
+            self.emitted += 1;
+        }
+        Ok(row)
+    }
+
+    fn describe(&self) -> String {
+        match (self.limit, self.offset) {
+            (Some(limit), 0) => format!("Limit({})", limit),
+            (Some(limit), offset) => format!("Limit({} OFFSET {})", limit, offset),
+            (None, offset) => format!("Limit(OFFSET {})", offset),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
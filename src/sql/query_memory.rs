@@ -0,0 +1,118 @@
+//! Per-query memory accounting
+//!
+//! Joins, sorts, GROUP BY and DISTINCT buffer rows in memory. When a query
+//! memory limit is set (`DBConfig::query_memory_limit` or the
+//! `query_memory_limit` session setting), each of them charges the rows it
+//! buffers to the running statement, and the statement fails with
+//! `StorageError::QueryMemoryLimitExceeded` once the total passes the limit,
+//! instead of one analytical query evicting every cache and stalling writers.
+//!
+//! Sizes are estimates (see [`Value::memory_size`]) and charges are never
+//! returned while the statement runs, so the total is an upper bound of what
+//! the statement held at once. The budget lives on the executing thread,
+//! like the session time zone: statements start one with [`begin`], and
+//! subqueries charge to the statement that runs them.
+
+use crate::error::{MoteDBError, Result};
+use crate::types::{SqlRow, Value};
+use std::cell::Cell;
+
+#[derive(Clone, Copy)]
+struct Budget {
+    limit: Option<usize>,
+    used: usize,
+}
+
+thread_local! {
+    static BUDGET: Cell<Budget> = const { Cell::new(Budget { limit: None, used: 0 }) };
+}
+
+/// Start accounting for a new statement on this thread
+pub(crate) fn begin(limit: Option<usize>) {
+    BUDGET.with(|b| b.set(Budget { limit, used: 0 }));
+}
+
+/// Whether the running statement has a limit (charges are free otherwise)
+#[inline]
+pub(crate) fn is_limited() -> bool {
+    BUDGET.with(|b| b.get().limit.is_some())
+}
+
+/// Charge `bytes` buffered by `operator`; fails once the statement's total
+/// passes its limit
+pub(crate) fn charge(bytes: usize, operator: &str) -> Result<()> {
+    BUDGET.with(|b| {
+        let mut budget = b.get();
+        let Some(limit) = budget.limit else {
+            return Ok(());
+        };
+        budget.used = budget.used.saturating_add(bytes);
+        b.set(budget);
+        if budget.used > limit {
+            return Err(MoteDBError::QueryMemoryLimitExceeded(format!(
+                "{} needs more than the {}-byte query memory limit",
+                operator, limit
+            )));
+        }
+        Ok(())
+    })
+}
+
+/// Charge a buffered row of values
+#[inline]
+pub(crate) fn charge_row(row: &[Value], operator: &str) -> Result<()> {
+    if !is_limited() {
+        return Ok(());
+    }
+    charge(row_size(row), operator)
+}
+
+/// Charge a buffered column-name → value row
+#[inline]
+pub(crate) fn charge_sql_row(row: &SqlRow, operator: &str) -> Result<()> {
+    if !is_limited() {
+        return Ok(());
+    }
+    let bytes = row
+        .iter()
+        .map(|(k, v)| std::mem::size_of::<String>() + k.len() + v.memory_size())
+        .sum();
+    charge(bytes, operator)
+}
+
+/// Charge every row of a buffer at once
+pub(crate) fn charge_rows(rows: &[Vec<Value>], operator: &str) -> Result<()> {
+    if !is_limited() {
+        return Ok(());
+    }
+    charge(rows.iter().map(|r| row_size(r)).sum(), operator)
+}
+
+fn row_size(row: &[Value]) -> usize {
+    std::mem::size_of::<Vec<Value>>() + row.iter().map(Value::memory_size).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charges_add_up_to_the_limit() {
+        let row = vec![Value::Integer(1), Value::text("abc".to_string())];
+        let size = row_size(&row);
+
+        begin(None);
+        assert!(!is_limited());
+        charge(usize::MAX, "sort").unwrap();
+
+        begin(Some(2 * size));
+        charge_row(&row, "sort").unwrap();
+        charge_row(&row, "sort").unwrap();
+        let err = charge_row(&row, "hash join").unwrap_err();
+        assert!(matches!(err, MoteDBError::QueryMemoryLimitExceeded(m) if m.contains("hash join")));
+
+        // A new statement starts from zero
+        begin(Some(2 * size));
+        charge_rows(&[row.clone(), row], "GROUP BY").unwrap();
+    }
+}
@@ -2,6 +2,18 @@ use crate::error::Result;
 /// Row conversion utilities - converts between storage Row and SQL SqlRow
 use crate::types::{ColumnType, Row, SqlRow, TableSchema, Value};
 
+/// Text written to a TIMESTAMP column is read in the session time zone unless
+/// it names its own offset (storage alone would read it as UTC)
+pub fn timestamp_text(col_type: &ColumnType, value: Value) -> Result<Value> {
+    match (col_type, value) {
+        (ColumnType::Timestamp, Value::Text(s)) => super::evaluator::session_time_zone()
+            .parse_timestamp(&s)
+            .map(Value::Timestamp)
+            .map_err(crate::error::MoteDBError::InvalidArgument),
+        (_, value) => Ok(value),
+    }
+}
+
 /// Convert storage Row (Vec<Value>) to SQL SqlRow (HashMap<String, Value>)
 pub fn row_to_sql_row(row: &Row, schema: &TableSchema) -> Result<SqlRow> {
     let mut sql_row = SqlRow::with_capacity(schema.columns.len());
@@ -36,6 +48,8 @@ pub fn sql_row_to_row(sql_row: &SqlRow, schema: &TableSchema) -> Result<Row> {
             }
             // Integer to Float conversion
             (ColumnType::Float, Value::Integer(i)) => Value::Float(*i as f64),
+            // Text to Timestamp in the session time zone
+            (ColumnType::Timestamp, Value::Text(_)) => timestamp_text(&col_def.col_type, value)?,
             // Pass through
             _ => value,
         };
@@ -92,6 +106,11 @@ pub fn values_to_row_by_columns(
                     Value::Timestamp(crate::types::Timestamp::from_micros(*ts))
                 }
                 (ColumnType::Float, Value::Integer(i)) => Value::Float(*i as f64),
+                (ColumnType::Timestamp, Value::Text(_)) => timestamp_text(&col_def.col_type, val)?,
+                (ct, _) if ct.is_coerced() => col_def
+                    .col_type
+                    .coerce(val)
+                    .map_err(crate::error::MoteDBError::InvalidArgument)?,
                 _ => val,
             };
             row[col_def.position] = coerced;
@@ -133,6 +152,11 @@ pub fn values_to_row_schema_order(values: &[Value], schema: &TableSchema) -> Res
                 Value::Timestamp(crate::types::Timestamp::from_micros(*ts))
             }
             (ColumnType::Float, Value::Integer(i)) => Value::Float(*i as f64),
+            (ColumnType::Timestamp, Value::Text(_)) => timestamp_text(&col_def.col_type, val)?,
+            (ct, _) if ct.is_coerced() => col_def
+                .col_type
+                .coerce(val)
+                .map_err(crate::error::MoteDBError::InvalidArgument)?,
             _ => val,
         };
         row.push(coerced);
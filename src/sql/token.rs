@@ -243,4 +243,48 @@ impl TokenType {
         let lower = unsafe { std::str::from_utf8_unchecked(&buf[..len]) };
         KEYWORDS.get(lower).cloned()
     }
+
+    /// SQL text that lexes back to this token (used to persist parsed
+    /// expressions, e.g. generated column definitions)
+    pub fn to_sql(&self) -> String {
+        let symbol = match self {
+            TokenType::Number(n) => return n.to_string(),
+            TokenType::OverflowInteger(n) => return n.to_string(),
+            TokenType::String(s) => {
+                return format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''"))
+            }
+            TokenType::Identifier(name) => return name.clone(),
+            TokenType::Parameter(i) => return format!("?{}", i),
+            TokenType::Eq => "=",
+            TokenType::Ne => "!=",
+            TokenType::Lt => "<",
+            TokenType::Gt => ">",
+            TokenType::Le => "<=",
+            TokenType::Ge => ">=",
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Star => "*",
+            TokenType::Slash => "/",
+            TokenType::Percent => "%",
+            TokenType::L2Distance => "<->",
+            TokenType::CosineDistance => "<=>",
+            TokenType::DotProduct => "<#>",
+            TokenType::LParen => "(",
+            TokenType::RParen => ")",
+            TokenType::LBracket => "[",
+            TokenType::RBracket => "]",
+            TokenType::Comma => ",",
+            TokenType::Semicolon => ";",
+            TokenType::Dot => ".",
+            TokenType::Eof => "",
+            keyword => {
+                return KEYWORDS
+                    .entries()
+                    .find(|(_, t)| *t == keyword)
+                    .map(|(k, _)| k.to_uppercase())
+                    .unwrap_or_default()
+            }
+        };
+        symbol.to_string()
+    }
 }
@@ -0,0 +1,783 @@
+//! Pluggable storage backend (VFS)
+//!
+//! Everything MoteDB persists is ultimately a named byte stream under the
+//! database directory. [`StorageBackend`] abstracts the handful of operations
+//! the engine needs on those streams so that callers can swap the host
+//! filesystem for something else — an encrypted container, an object-store
+//! shim, or the [`MemoryBackend`] used by tests — without patching every
+//! module. wasm builds also get `HostVfsBackend`, which hands every call to
+//! the embedding page (browser OPFS via `wasm/motedb-vfs.js`).
+//!
+//! ## Coverage
+//! The WAL partitions, the table catalog (`catalog.bin`) and the index
+//! metadata registry (`index_metadata.bin`) are handed the configured backend
+//! directly. Modules that only know a file's path — SSTables, columnar
+//! segments and their manifest, blob files, and the files of every index
+//! kind (B-Tree, full-text, DiskANN, i-Octree), including REINDEX swaps —
+//! resolve it with [`open_file`] / [`backend_for`], which look the path up
+//! in the mount table: a database mounts its configured backend over its
+//! directory for as long as it is open (see [`mount`]). Paths outside every
+//! mount go to the host filesystem. SSTables and vector files are
+//! memory-mapped through [`BackendFile::map`], which falls back to reading
+//! the whole file on backends that cannot map.
+//!
+//! The bookkeeping files (LSN counter, cache warm-up state and the version
+//! log of the index manifest, `CURRENT` / `MANIFEST-*`) resolve the same
+//! way. Only the directory lock (`.lock`) stays on `std::fs`: a database on
+//! a non-filesystem backend still creates its directory on the host to hold
+//! it.
+//!
+//! [`FaultInjectionBackend`](super::fault::FaultInjectionBackend) wraps any
+//! backend to simulate power loss with torn, partially synced writes.
+//! [`SyncPolicyBackend`](super::sync_policy::SyncPolicyBackend) wraps the
+//! database's backend to apply the per-component `DBConfig::fsync` policy.
+//!
+//! ## Usage
+//! ```ignore
+//! use std::sync::Arc;
+//! use motedb::{DBConfig, storage::backend::MemoryBackend};
+//!
+//! let config = DBConfig {
+//!     storage_backend: Some(Arc::new(MemoryBackend::new())),
+//!     ..Default::default()
+//! };
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use memmap2::Mmap;
+use parking_lot::{Mutex, RwLock};
+
+/// How a file should be opened (mirrors the subset of [`OpenOptions`] the
+/// engine uses).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpenFlags {
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub create: bool,
+    pub truncate: bool,
+}
+
+impl OpenFlags {
+    /// Read-only access to an existing file
+    pub fn read_only() -> Self {
+        Self {
+            read: true,
+            ..Default::default()
+        }
+    }
+
+    /// Read + append, creating the file if missing (WAL style)
+    pub fn append() -> Self {
+        Self {
+            read: true,
+            append: true,
+            create: true,
+            ..Default::default()
+        }
+    }
+
+    /// Write-only, created or truncated to zero length
+    pub fn create_truncate() -> Self {
+        Self {
+            write: true,
+            create: true,
+            truncate: true,
+            ..Default::default()
+        }
+    }
+
+    /// Read + write an existing file without truncating it
+    pub fn read_write() -> Self {
+        Self {
+            read: true,
+            write: true,
+            ..Default::default()
+        }
+    }
+
+    /// Same flags but fail if the file does not exist
+    pub fn no_create(mut self) -> Self {
+        self.create = false;
+        self
+    }
+
+    /// Same flags but create the file if it does not exist
+    pub fn or_create(mut self) -> Self {
+        self.create = true;
+        self
+    }
+}
+
+/// The whole contents of a file, memory-mapped where the backend allows it
+pub enum FileMap {
+    Mapped(Mmap),
+    Loaded(Vec<u8>),
+}
+
+impl Deref for FileMap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileMap::Mapped(m) => m,
+            FileMap::Loaded(v) => v,
+        }
+    }
+}
+
+impl FileMap {
+    /// Hint that `len` bytes at `offset` will be read soon, so the kernel
+    /// starts reading them in (no-op for loaded files)
+    pub fn advise_willneed(&self, offset: usize, len: usize) {
+        #[cfg(unix)]
+        if let FileMap::Mapped(m) = self {
+            let len = len.min(m.len().saturating_sub(offset));
+            let _ = m.advise_range(memmap2::Advice::WillNeed, offset, len);
+        }
+        #[cfg(not(unix))]
+        let _ = (offset, len);
+    }
+}
+
+impl fmt::Debug for FileMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            FileMap::Mapped(_) => "Mapped",
+            FileMap::Loaded(_) => "Loaded",
+        };
+        write!(f, "FileMap::{}({} bytes)", kind, self.len())
+    }
+}
+
+/// An open file handed out by a [`StorageBackend`]
+pub trait BackendFile: Read + Write + Seek + Send + Sync {
+    /// Flush data and metadata to durable storage
+    fn sync_all(&self) -> io::Result<()>;
+
+    /// Current length in bytes
+    fn len(&self) -> io::Result<u64>;
+
+    /// Whether the file is empty
+    fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Truncate or extend the file
+    fn set_len(&self, size: u64) -> io::Result<()>;
+
+    /// Read exactly `buf.len()` bytes at `offset` without moving the cursor
+    /// that `Read`/`Seek` use, so concurrent readers need no exclusive lock
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+
+    /// The whole file as one byte slice. The default reads it into memory;
+    /// host files are memory-mapped instead.
+    fn map(&self) -> io::Result<FileMap> {
+        let mut buf = vec![0u8; self.len()? as usize];
+        self.read_exact_at(&mut buf, 0)?;
+        Ok(FileMap::Loaded(buf))
+    }
+
+    /// Hint that the file's cached pages will not be needed soon (no-op by
+    /// default)
+    fn advise_dontneed(&self) {}
+
+    /// Whether every write to this file will fail (e.g. a file inside a
+    /// packed image)
+    fn is_read_only(&self) -> bool {
+        false
+    }
+}
+
+impl<F: BackendFile + ?Sized> BackendFile for Box<F> {
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        (**self).len()
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        (**self).set_len(size)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    fn map(&self) -> io::Result<FileMap> {
+        (**self).map()
+    }
+
+    fn advise_dontneed(&self) {
+        (**self).advise_dontneed()
+    }
+
+    fn is_read_only(&self) -> bool {
+        (**self).is_read_only()
+    }
+}
+
+/// File-system abstraction used by the storage layer
+pub trait StorageBackend: Send + Sync + fmt::Debug {
+    /// Open (or create, per `flags`) the file at `path`
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>>;
+
+    /// Recursively create a directory
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Atomically replace `to` with `from`
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Delete a file
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Whether a file or directory exists at `path`
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Whether `path` is a directory
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Direct children of a directory (full paths, sorted)
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Make a preceding rename of `path` durable (no-op by default)
+    fn sync_parent_dir(&self, _path: &Path) {}
+
+    /// Delete a directory and the files below it
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        for child in self.list_dir(path)? {
+            if self.is_dir(&child) {
+                self.remove_dir_all(&child)?;
+            } else {
+                self.remove_file(&child)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of the file at `path`
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        self.open(path, OpenFlags::read_only())?.len()
+    }
+
+    /// Last modification time, if the backend tracks one. Without it,
+    /// callers fingerprinting a file fall back to hashing its contents.
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        None
+    }
+
+    /// Read a whole file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut f = self.open(path, OpenFlags::read_only())?;
+        let mut buf = Vec::new();
+        f.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Replace a file's contents atomically (write temp, sync, rename)
+    fn write_atomic(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut f = self.open(&tmp, OpenFlags::create_truncate())?;
+            f.write_all(data)?;
+            f.flush()?;
+            f.sync_all()?;
+        }
+        self.rename(&tmp, path)?;
+        self.sync_parent_dir(path);
+        Ok(())
+    }
+}
+
+/// Backend used when no custom one is configured
+pub fn default_backend() -> Arc<dyn StorageBackend> {
+    Arc::new(StdFsBackend)
+}
+
+// ==================== Mount table ====================
+
+struct MountEntry {
+    id: u64,
+    root: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+}
+
+static MOUNTS: RwLock<Vec<MountEntry>> = RwLock::new(Vec::new());
+static NEXT_MOUNT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Serve every path under `root` from `backend` until the returned guard is
+/// dropped. When mounts nest, the deepest root wins; when the same root is
+/// mounted twice, the newer mount wins.
+pub fn mount(root: &Path, backend: Arc<dyn StorageBackend>) -> Mount {
+    let id = NEXT_MOUNT_ID.fetch_add(1, Ordering::Relaxed);
+    MOUNTS.write().push(MountEntry {
+        id,
+        root: root.to_path_buf(),
+        backend,
+    });
+    Mount { id }
+}
+
+/// Keeps a [`mount`] in place
+#[derive(Debug)]
+pub struct Mount {
+    id: u64,
+}
+
+impl Drop for Mount {
+    fn drop(&mut self) {
+        MOUNTS.write().retain(|m| m.id != self.id);
+    }
+}
+
+/// Backend that serves `path`: the mount with the deepest root containing it,
+/// else the host filesystem
+pub fn backend_for(path: &Path) -> Arc<dyn StorageBackend> {
+    let mounts = MOUNTS.read();
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.root))
+        .max_by_key(|m| (m.root.components().count(), m.id))
+        .map(|m| m.backend.clone())
+        .unwrap_or_else(default_backend)
+}
+
+/// Open `path` on the backend that serves it
+pub fn open_file(path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+    backend_for(path).open(path, flags)
+}
+
+// ==================== Host filesystem ====================
+
+/// Host filesystem via `std::fs`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdFsBackend;
+
+impl BackendFile for File {
+    fn sync_all(&self) -> io::Result<()> {
+        File::sync_all(self)
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        File::set_len(self, size)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        super::platform::PositionalRead::read_exact_at(self, buf, offset)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn advise_dontneed(&self) {
+        use std::os::unix::io::AsRawFd;
+        unsafe {
+            libc::posix_fadvise(self.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+        }
+    }
+
+    fn map(&self) -> io::Result<FileMap> {
+        // SAFETY: mapped files are either immutable (SSTables) or only
+        // appended to or patched in place while the owner keeps readers off
+        // its map, and the owner drops its map before it replaces or
+        // truncates the file.
+        unsafe { Mmap::map(self) }.map(FileMap::Mapped)
+    }
+}
+
+impl StorageBackend for StdFsBackend {
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+        let file = OpenOptions::new()
+            .read(flags.read)
+            .write(flags.write)
+            .append(flags.append)
+            .create(flags.create)
+            .truncate(flags.truncate)
+            .open(path)?;
+        Ok(Box::new(file))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut out = std::fs::read_dir(path)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        out.sort();
+        Ok(out)
+    }
+
+    fn sync_parent_dir(&self, path: &Path) {
+        crate::fsync_dir(path);
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir_all(path)
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        Ok(std::fs::metadata(path)?.len())
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+// ==================== In-memory ====================
+
+type SharedBytes = Arc<RwLock<Vec<u8>>>;
+
+#[derive(Default)]
+struct MemoryState {
+    files: BTreeMap<PathBuf, SharedBytes>,
+    dirs: BTreeSet<PathBuf>,
+}
+
+/// Volatile in-memory backend (test harnesses, ephemeral databases)
+///
+/// Clones share the same namespace, so a "reopen" in tests sees prior writes.
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Paths of all files currently stored
+    pub fn file_paths(&self) -> Vec<PathBuf> {
+        self.state.lock().files.keys().cloned().collect()
+    }
+
+    /// Total bytes held across all files
+    pub fn total_bytes(&self) -> u64 {
+        self.state
+            .lock()
+            .files
+            .values()
+            .map(|f| f.read().len() as u64)
+            .sum()
+    }
+}
+
+impl fmt::Debug for MemoryBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("MemoryBackend")
+            .field("files", &state.files.len())
+            .field("dirs", &state.dirs.len())
+            .finish()
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{}: no such file", path.display()),
+    )
+}
+
+impl StorageBackend for MemoryBackend {
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+        let mut state = self.state.lock();
+        let data = match state.files.get(path) {
+            Some(data) => data.clone(),
+            None if flags.create => {
+                let data = SharedBytes::default();
+                state.files.insert(path.to_path_buf(), data.clone());
+                data
+            }
+            None => return Err(not_found(path)),
+        };
+        drop(state);
+
+        if flags.truncate {
+            data.write().clear();
+        }
+        Ok(Box::new(MemoryFile {
+            data,
+            pos: 0,
+            readable: flags.read,
+            writable: flags.write || flags.append,
+            append: flags.append,
+        }))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.state.lock();
+        for dir in path.ancestors() {
+            if dir.as_os_str().is_empty() {
+                break;
+            }
+            state.dirs.insert(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut state = self.state.lock();
+        if let Some(data) = state.files.remove(from) {
+            state.files.insert(to.to_path_buf(), data);
+            return Ok(());
+        }
+        // Directory: move everything beneath it
+        let moved: Vec<PathBuf> = state
+            .files
+            .keys()
+            .filter(|p| p.starts_with(from))
+            .cloned()
+            .collect();
+        let dirs: Vec<PathBuf> = state
+            .dirs
+            .iter()
+            .filter(|p| p.starts_with(from))
+            .cloned()
+            .collect();
+        if moved.is_empty() && dirs.is_empty() {
+            return Err(not_found(from));
+        }
+        for path in moved {
+            let data = state.files.remove(&path).expect("listed above");
+            let rest = path.strip_prefix(from).expect("listed above");
+            state.files.insert(to.join(rest), data);
+        }
+        for dir in dirs {
+            state.dirs.remove(&dir);
+            let rest = dir.strip_prefix(from).expect("listed above");
+            state.dirs.insert(to.join(rest));
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.state
+            .lock()
+            .files
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let state = self.state.lock();
+        state.files.contains_key(path)
+            || state.dirs.contains(path)
+            || state.files.keys().any(|p| p.starts_with(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let state = self.state.lock();
+        state.dirs.contains(path) || state.files.keys().any(|p| p != path && p.starts_with(path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.state.lock();
+        state.files.retain(|p, _| !p.starts_with(path));
+        state.dirs.retain(|p| !p.starts_with(path));
+        Ok(())
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let state = self.state.lock();
+        let children: BTreeSet<PathBuf> = state
+            .files
+            .keys()
+            .chain(state.dirs.iter())
+            .filter_map(|p| {
+                let first = p.strip_prefix(path).ok()?.components().next()?;
+                Some(path.join(first))
+            })
+            .collect();
+        Ok(children.into_iter().collect())
+    }
+}
+
+/// Handle into a [`MemoryBackend`] file. Each handle keeps its own cursor;
+/// the bytes are shared with every other handle on the same path.
+struct MemoryFile {
+    data: SharedBytes,
+    pos: u64,
+    readable: bool,
+    writable: bool,
+    append: bool,
+}
+
+impl Read for MemoryFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.readable {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "file not opened for reading",
+            ));
+        }
+        let data = self.data.read();
+        let start = (self.pos as usize).min(data.len());
+        let n = buf.len().min(data.len() - start);
+        buf[..n].copy_from_slice(&data[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for MemoryFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.writable {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "file not opened for writing",
+            ));
+        }
+        let mut data = self.data.write();
+        if self.append {
+            self.pos = data.len() as u64;
+        }
+        let start = self.pos as usize;
+        let end = start + buf.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[start..end].copy_from_slice(buf);
+        self.pos = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MemoryFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let len = self.data.read().len() as i64;
+        let new_pos = match pos {
+            SeekFrom::Start(p) => p as i64,
+            SeekFrom::End(off) => len + off,
+            SeekFrom::Current(off) => self.pos as i64 + off,
+        };
+        if new_pos < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before start of file",
+            ));
+        }
+        self.pos = new_pos as u64;
+        Ok(self.pos)
+    }
+}
+
+impl BackendFile for MemoryFile {
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.data.read().len() as u64)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        self.data.write().resize(size as usize, 0);
+        Ok(())
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let data = self.data.read();
+        let start = offset as usize;
+        match data.get(start..start + buf.len()) {
+            Some(src) => {
+                buf.copy_from_slice(src);
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(backend: &dyn StorageBackend, root: &Path) {
+        let dir = root.join("sub");
+        backend.create_dir_all(&dir).unwrap();
+        let path = dir.join("a.bin");
+
+        let mut f = backend.open(&path, OpenFlags::append()).unwrap();
+        f.write_all(b"hello ").unwrap();
+        f.write_all(b"world").unwrap();
+        f.flush().unwrap();
+        f.sync_all().unwrap();
+        assert_eq!(f.len().unwrap(), 11);
+
+        f.seek(SeekFrom::Start(6)).unwrap();
+        let mut buf = String::new();
+        f.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "world");
+        drop(f);
+
+        backend.write_atomic(&path, b"replaced").unwrap();
+        assert_eq!(backend.read(&path).unwrap(), b"replaced");
+        assert_eq!(backend.list_dir(&dir).unwrap(), vec![path.clone()]);
+
+        let moved = dir.join("b.bin");
+        backend.rename(&path, &moved).unwrap();
+        assert!(!backend.exists(&path));
+        assert!(backend.exists(&moved));
+
+        backend.remove_file(&moved).unwrap();
+        assert!(!backend.exists(&moved));
+        let err = backend.open(&moved, OpenFlags::read_only()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_std_fs_backend() {
+        let tmp = tempfile::tempdir().unwrap();
+        roundtrip(&StdFsBackend, tmp.path());
+    }
+
+    #[test]
+    fn test_memory_backend() {
+        let backend = MemoryBackend::new();
+        roundtrip(&backend, Path::new("/mem"));
+        assert_eq!(backend.total_bytes(), 0);
+    }
+}
@@ -0,0 +1,108 @@
+//! Write capture for online index rebuilds.
+//!
+//! A rebuild scans the store in key order into a staging index while writes
+//! keep landing on the live one. A `WriteCapture` attached to the store
+//! records every key written meanwhile, together with the row the staging
+//! index holds for it, so the rebuild can replay exactly the difference
+//! between that row and the current one.
+//!
+//! The scan hands each batch to [`WriteCapture::stage`] before indexing it:
+//! keys written before that point are dropped from the batch (their rows are
+//! replayed instead), and keys written after it record their pre-write row,
+//! which is what the batch staged.
+
+use crate::types::Value;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// A key written while a capture was attached
+struct Touched {
+    /// Row the staging index holds for the key (`None`: not indexed)
+    staged: Option<Vec<Value>>,
+    /// Written since it was last replayed
+    dirty: bool,
+}
+
+#[derive(Default)]
+struct CaptureState {
+    /// Keys up to this one have been staged by the scan
+    scanned_to: Option<u64>,
+    touched: HashMap<u64, Touched>,
+}
+
+/// Keys written to a store while an index rebuild runs
+#[derive(Default)]
+pub struct WriteCapture {
+    state: Mutex<CaptureState>,
+}
+
+impl WriteCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a write to `key`. Called by the store before the write is
+    /// applied; `current` reads the row the write replaces.
+    pub(super) fn touch(&self, key: u64, current: impl FnOnce() -> Option<Vec<Value>>) {
+        let mut state = self.state.lock();
+        let scanned = state.scanned_to.is_some_and(|to| key <= to);
+        state
+            .touched
+            .entry(key)
+            .and_modify(|t| t.dirty = true)
+            .or_insert_with(|| Touched {
+                // The scan staged what the key held before its first write
+                staged: if scanned { current() } else { None },
+                dirty: true,
+            });
+    }
+
+    /// Admit a scanned batch (ascending keys) into the staging index: drops
+    /// keys already written, which are replayed instead, and marks the rest
+    /// as staged. Rows must be read before the call.
+    pub fn stage(&self, mut batch: Vec<(u64, Vec<Value>)>) -> Vec<(u64, Vec<Value>)> {
+        let mut state = self.state.lock();
+        if let Some(&(last, _)) = batch.last() {
+            state.scanned_to = Some(state.scanned_to.map_or(last, |to| to.max(last)));
+        }
+        batch.retain(|(key, _)| !state.touched.contains_key(key));
+        batch
+    }
+
+    /// The scan is complete: every later first write records its pre-image
+    pub fn finish_scan(&self) {
+        self.state.lock().scanned_to = Some(u64::MAX);
+    }
+
+    /// Keys written since they were last replayed, with the row the staging
+    /// index holds for each. Pass the rows applied to [`Self::replayed`].
+    pub fn take_dirty(&self) -> Vec<(u64, Option<Vec<Value>>)> {
+        let mut state = self.state.lock();
+        state
+            .touched
+            .iter_mut()
+            .filter(|(_, t)| t.dirty)
+            .map(|(&key, t)| {
+                t.dirty = false;
+                (key, t.staged.clone())
+            })
+            .collect()
+    }
+
+    /// Record what the staging index now holds for `key`
+    pub fn replayed(&self, key: u64, row: Option<Vec<Value>>) {
+        if let Some(t) = self.state.lock().touched.get_mut(&key) {
+            t.staged = row;
+        }
+    }
+
+    /// Number of keys written since they were last replayed
+    pub fn dirty_count(&self) -> usize {
+        self.state
+            .lock()
+            .touched
+            .values()
+            .filter(|t| t.dirty)
+            .count()
+    }
+}
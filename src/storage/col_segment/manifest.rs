@@ -4,8 +4,8 @@
 //! Distinct from `storage::manifest` (the LSM manifest) — this is per-table,
 //! binary, lives at `columnar_ms/<table>/MANIFEST`.
 
+use crate::storage::backend::{self, BackendFile, OpenFlags};
 use crate::Result;
-use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 
@@ -59,21 +59,18 @@ impl Record {
 
 pub struct Manifest {
     path: PathBuf,
-    writer: BufWriter<File>,
+    writer: BufWriter<Box<dyn BackendFile>>,
 }
 
 impl Manifest {
     pub fn create(path: &Path) -> Result<Self> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(path)?;
+        let backend = backend::backend_for(path);
+        let mut file = backend.open(path, OpenFlags::create_truncate())?;
         file.write_all(MAGIC)?;
         file.write_all(&VERSION.to_le_bytes())?;
         file.write_all(&0u32.to_le_bytes())?; // record_count placeholder
         file.sync_all()?;
-        crate::fsync_dir(path);
+        backend.sync_parent_dir(path);
         Ok(Self {
             path: path.to_path_buf(),
             writer: BufWriter::new(file),
@@ -84,7 +81,7 @@ impl Manifest {
     /// writes after the existing records (NOT at offset 0, which would
     /// overwrite the MAGIC header — the v0.5.0 WAL-recovery-gap bug).
     pub fn open(path: &Path) -> Result<Self> {
-        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut file = backend::open_file(path, OpenFlags::read_write())?;
         // Position the write cursor at end-of-file so append() extends the log
         // instead of clobbering the header.
         use std::io::Seek;
@@ -122,7 +119,7 @@ impl Manifest {
     /// Replay all records to reconstruct state. Used at recovery.
     pub fn replay(&self) -> ManifestState {
         let mut data = Vec::new();
-        if let Ok(mut f) = File::open(&self.path) {
+        if let Ok(mut f) = backend::open_file(&self.path, OpenFlags::read_only()) {
             let _ = f.read_to_end(&mut data);
         }
         if data.len() < 10 || &data[..4] != MAGIC {
@@ -80,6 +80,8 @@ impl SegmentCursor {
                 && matches!(seg.sst.column_tags[ci], ColumnTypeTag::Vector)
             {
                 // Map read_vectors (row_id, vec) pairs to per-row-index options.
+                // NULL rows have no pair, so a row only takes the next pair
+                // when the row ids match.
                 let decoded = seg.sst.read_vectors(ci).unwrap_or_default();
                 let mut per = vec![None; n];
                 let mut di = 0usize;
@@ -88,10 +90,7 @@ impl SegmentCursor {
                         continue;
                     }
                     let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                    while di < decoded.len() && decoded[di].0 != ek {
-                        di += 1;
-                    }
-                    if di < decoded.len() {
+                    if decoded.get(di).is_some_and(|d| d.0 == ek) {
                         per[i] = Some(decoded[di].1.clone());
                         di += 1;
                     }
@@ -108,10 +107,7 @@ impl SegmentCursor {
                         continue;
                     }
                     let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                    while di < decoded.len() && decoded[di].0 != ek {
-                        di += 1;
-                    }
-                    if di < decoded.len() {
+                    if decoded.get(di).is_some_and(|d| d.0 == ek) {
                         per[i] = Some(decoded[di].1.clone());
                         di += 1;
                     }
@@ -168,12 +164,12 @@ impl SegmentCursor {
                         .map(|v| Value::Timestamp(crate::types::Timestamp::from_micros(v))),
                     _ => None,
                 },
-                Some(ColData::Text(t)) => t.get_str(i).map(|s| Value::Text(s.into())),
+                Some(ColData::Text(t)) => t.get_str(i).map(|s| ct.value_from_text(s)),
                 Some(ColData::Vector(cols)) => cols
                     .get(i)
                     .cloned()
                     .flatten()
-                    .map(|v| Value::Vector(crate::types::ArcVec(Arc::new(v)))),
+                    .map(|v| ct.value_from_vector(v)),
                 Some(ColData::Spatial(cols)) => cols
                     .get(i)
                     .cloned()
@@ -9,11 +9,13 @@
 //! time-series store). This module serves tables using the v0.3.0 columnar
 //! SSTable format with multi-segment + compaction semantics.
 
+mod capture;
 mod manifest;
 mod merge;
 mod segment;
 mod store;
 
+pub use capture::WriteCapture;
 pub use manifest::{Manifest, ManifestState};
 pub use merge::MergeCursor;
 pub use segment::Segment;
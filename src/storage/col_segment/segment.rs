@@ -188,6 +188,35 @@ impl Segment {
         Some(seg)
     }
 
+    /// Column indexes currently held in the decode cache, most recent first.
+    pub fn cached_columns(&self) -> Vec<usize> {
+        self.col_cache
+            .lock()
+            .entries
+            .iter()
+            .map(|(col_idx, _)| *col_idx)
+            .collect()
+    }
+
+    /// Decode a column into the cache ahead of the first query that needs it.
+    /// Vector/spatial columns are never cached and are skipped.
+    pub fn warm_column(&self, col_idx: usize) {
+        match self.sst.column_tags.get(col_idx) {
+            Some(ColumnTypeTag::Text) => {
+                self.read_text_cached(col_idx);
+            }
+            Some(
+                ColumnTypeTag::Integer
+                | ColumnTypeTag::Float
+                | ColumnTypeTag::Bool
+                | ColumnTypeTag::Timestamp,
+            ) => {
+                self.read_fixed_cached(col_idx);
+            }
+            _ => {}
+        }
+    }
+
     /// Release mmap pages from RSS via MADV_DONTNEED. The OS will re-fault
     /// pages on next access. Call after bulk reads (e.g. compaction) to keep
     /// peak RSS low on memory-constrained embedded devices.
@@ -269,87 +298,120 @@ impl Segment {
         col_types: &[crate::types::ColumnType],
         point_query: bool,
     ) -> Vec<crate::types::Value> {
+        col_types
+            .iter()
+            .enumerate()
+            .map(|(ci, ct)| self.decode_value_at(idx, ci, ct, point_query))
+            .collect()
+    }
+
+    /// Decode a single column of the row at a known index, leaving the other
+    /// columns untouched. Caller MUST verify the row is not deleted.
+    pub fn get_value_at_idx(
+        &self,
+        idx: usize,
+        ci: usize,
+        ct: &crate::types::ColumnType,
+    ) -> crate::types::Value {
+        self.decode_value_at(idx, ci, ct, true)
+    }
+
+    fn decode_value_at(
+        &self,
+        idx: usize,
+        ci: usize,
+        ct: &crate::types::ColumnType,
+        point_query: bool,
+    ) -> crate::types::Value {
         use crate::types::Value;
 
-        let mut row = Vec::with_capacity(col_types.len());
-        for (ci, ct) in col_types.iter().enumerate() {
-            let tag = self.sst.column_tags.get(ci).copied();
-
-            if matches!(tag, Some(t) if t.is_fixed()) {
-                // Try O(1) direct byte read first. For uncompressed segments
-                // (flag=0) this succeeds without touching the rest of the column.
-                // For Snappy-compressed segments (flag=1) it falls back to a
-                // full-column decode — so we cache the decoded column in col_cache
-                // to avoid re-decompressing on every point query.
-                match self.sst.read_fixed_i64_at(ci, idx) {
-                    Ok(Some(v)) => {
-                        push_fixed_value(&mut row, v, ct);
-                        continue;
-                    }
-                    Ok(None) => {
-                        row.push(Value::Null);
-                        continue;
-                    }
-                    Err(_) => {
-                        // Compressed segment or read error — fall through to
-                        // cached full-column decode below.
-                    }
+        let tag = self.sst.column_tags.get(ci).copied();
+
+        if matches!(tag, Some(t) if t.is_fixed()) {
+            // Try O(1) direct byte read first. For uncompressed segments
+            // (flag=0) this succeeds without touching the rest of the column.
+            // For Snappy-compressed segments (flag=1) it falls back to a
+            // full-column decode — so we cache the decoded column in col_cache
+            // to avoid re-decompressing on every point query.
+            match self.sst.read_fixed_i64_at(ci, idx) {
+                Ok(Some(v)) => return fixed_value(v, ct),
+                Ok(None) => return Value::Null,
+                Err(_) => {
+                    // Compressed segment or read error — fall through to
+                    // cached full-column decode below.
                 }
-                // Cached full-column decode (same path as scan).
-                {
-                    let mut cache = self.col_cache.lock();
-                    if let Some(cached) = cache.get(ci) {
-                        row.push(decode_cached_value(cached, idx, ct));
-                        continue;
-                    }
+            }
+            // Cached full-column decode (same path as scan).
+            {
+                let mut cache = self.col_cache.lock();
+                if let Some(cached) = cache.get(ci) {
+                    return decode_cached_value(cached, idx, ct);
                 }
-                if let Ok(seg) = self.sst.read_fixed_i64(ci) {
+            }
+            return match self.sst.read_fixed_i64(ci) {
+                Ok(seg) => {
                     let cached = CachedCol::Fixed(seg);
-                    row.push(decode_cached_value(&cached, idx, ct));
+                    let v = decode_cached_value(&cached, idx, ct);
                     self.col_cache.lock().insert(ci, cached);
-                } else {
-                    row.push(Value::Null);
+                    v
                 }
-                continue;
-            }
+                Err(_) => Value::Null,
+            };
+        }
 
-            // Text column.
-            if matches!(tag, Some(ColumnTypeTag::Text)) {
-                if point_query {
-                    // 🚀 Page-level cache: read a small window of offsets
-                    // (2KB for 512 rows) + string data via a single batch
-                    // read, instead of caching the entire text column (~31MB).
-                    // This keeps peak RSS low (<5MB per text column) while
-                    // serving sequential point queries at 6µs latency.
-                    let val = self.read_text_paged(ci, idx);
-                    match val {
-                        Some(s) => row.push(Value::Text(s.into())),
-                        None => row.push(Value::Null),
-                    }
-                    continue;
-                }
-                // Scan path: use col_cache (full-column decode, reused).
-                {
-                    let mut cache = self.col_cache.lock();
-                    if let Some(cached) = cache.get(ci) {
-                        row.push(decode_cached_value(cached, idx, ct));
-                        continue;
-                    }
+        // Text column.
+        if matches!(tag, Some(ColumnTypeTag::Text)) {
+            if point_query {
+                // 🚀 Page-level cache: read a small window of offsets
+                // (2KB for 512 rows) + string data via a single batch
+                // read, instead of caching the entire text column (~31MB).
+                // This keeps peak RSS low (<5MB per text column) while
+                // serving sequential point queries at 6µs latency.
+                return match self.read_text_paged(ci, idx) {
+                    Some(s) => ct.value_from_text(&s),
+                    None => Value::Null,
+                };
+            }
+            // Scan path: use col_cache (full-column decode, reused).
+            {
+                let mut cache = self.col_cache.lock();
+                if let Some(cached) = cache.get(ci) {
+                    return decode_cached_value(cached, idx, ct);
                 }
-                let decoded = self.sst.read_text(ci).ok().map(CachedCol::Text);
-                if let Some(d) = decoded {
-                    row.push(decode_cached_value(&d, idx, ct));
+            }
+            return match self.sst.read_text(ci).ok().map(CachedCol::Text) {
+                Some(d) => {
+                    let v = decode_cached_value(&d, idx, ct);
                     self.col_cache.lock().insert(ci, d);
-                } else {
-                    row.push(Value::Null);
+                    v
                 }
-                continue;
-            }
+                None => Value::Null,
+            };
+        }
 
-            // Unknown column type.
-            row.push(Value::Null);
+        // Vector column (VECTOR/TENSOR): decode just this row.
+        if matches!(tag, Some(ColumnTypeTag::Vector)) {
+            return self
+                .sst
+                .read_vector_at(ci, idx)
+                .ok()
+                .flatten()
+                .map_or(Value::Null, |v| ct.value_from_vector(v));
         }
-        row
+
+        // Spatial column: geometries are keyed by row id, not row index.
+        if matches!(tag, Some(ColumnTypeTag::Spatial)) {
+            let row_id = self.sst.row_map.key(idx) & 0xFFFFFFFF;
+            return self
+                .sst
+                .read_spatial(ci)
+                .ok()
+                .and_then(|geoms| geoms.into_iter().find(|(id, _)| *id == row_id))
+                .map_or(Value::Null, |(_, g)| Value::Spatial(Box::new(g)));
+        }
+
+        // Unknown column type.
+        Value::Null
     }
 
     /// Read a text value using page-level caching. Reads a small window of
@@ -525,17 +587,15 @@ impl Segment {
     }
 }
 
-/// Push a decoded fixed-width value into a row based on the column type.
-fn push_fixed_value(row: &mut Vec<crate::types::Value>, v: i64, ct: &crate::types::ColumnType) {
+/// Decode a fixed-width value based on the column type.
+fn fixed_value(v: i64, ct: &crate::types::ColumnType) -> crate::types::Value {
     use crate::types::{ColumnType, Value};
     match ct {
-        ColumnType::Integer => row.push(Value::Integer(v)),
-        ColumnType::Float => row.push(Value::Float(f64::from_bits(v as u64))),
-        ColumnType::Boolean => row.push(Value::Bool(v != 0)),
-        ColumnType::Timestamp => {
-            row.push(Value::Timestamp(crate::types::Timestamp::from_micros(v)))
-        }
-        _ => row.push(Value::Null),
+        ColumnType::Integer => Value::Integer(v),
+        ColumnType::Float => Value::Float(f64::from_bits(v as u64)),
+        ColumnType::Boolean => Value::Bool(v != 0),
+        ColumnType::Timestamp => Value::Timestamp(crate::types::Timestamp::from_micros(v)),
+        _ => Value::Null,
     }
 }
 
@@ -559,9 +619,9 @@ fn decode_cached_value(
             .get_i64(idx)
             .map(|v| Value::Timestamp(crate::types::Timestamp::from_micros(v)))
             .unwrap_or(Value::Null),
-        (CachedCol::Text(t), ColumnType::Text) => t
+        (CachedCol::Text(t), _) if matches!(ct, ColumnType::Text) || ct.is_text_encoded() => t
             .get_str(idx)
-            .map(|s| Value::Text(s.into()))
+            .map(|s| ct.value_from_text(s))
             .unwrap_or(Value::Null),
         _ => Value::Null,
     }
@@ -1,14 +1,18 @@
+use super::capture::WriteCapture;
 use super::manifest::Manifest;
 use super::merge::MergeCursor;
 use super::segment::Segment;
+use crate::cache::NegativeCache;
 use crate::storage::lsm::columnar::{ColumnTypeTag, ColumnarSSTableBuilder};
-use crate::types::{ArcString, ColumnType, Value};
+use crate::types::{
+    float_cmp, float_max, float_min, ArcString, ColumnType, NumericSum, Timestamp, Value,
+};
 use crate::Result;
 use arc_swap::ArcSwap;
 use parking_lot::{Mutex, RwLock};
 use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Result of a single-pass aggregate scan (SUM/AVG/MIN/MAX/COUNT).
@@ -17,8 +21,7 @@ use std::sync::Arc;
 pub struct AggregateResult {
     pub count: i64,      // non-NULL values (for COUNT(col))
     pub null_count: i64, // NULL values (for COUNT(*) = count + null_count)
-    pub int_sum: i64,
-    pub float_sum: f64,
+    pub sum: NumericSum,
     pub has_float: bool,
     pub min_int: i64,
     pub max_int: i64,
@@ -55,9 +58,24 @@ fn cmp_opt<T: Copy + PartialEq + PartialOrd>(
     }
 }
 
+/// Floats compare in total order (NaN after every number), like `Value`
 #[inline]
 fn cmp_opt_f64(v: Option<f64>, target: Option<f64>, op: &crate::sql::ast::BinaryOperator) -> bool {
-    cmp_opt(v, target, op)
+    use crate::sql::ast::BinaryOperator;
+    use std::cmp::Ordering;
+    let ord = match (v, target) {
+        (Some(a), Some(b)) => float_cmp(a, b),
+        _ => return false,
+    };
+    match op {
+        BinaryOperator::Eq => ord == Ordering::Equal,
+        BinaryOperator::Ne => ord != Ordering::Equal,
+        BinaryOperator::Lt => ord == Ordering::Less,
+        BinaryOperator::Gt => ord == Ordering::Greater,
+        BinaryOperator::Le => ord != Ordering::Greater,
+        BinaryOperator::Ge => ord != Ordering::Less,
+        _ => false,
+    }
 }
 
 #[inline]
@@ -78,6 +96,24 @@ fn cmp_str(v: Option<&str>, target: Option<&str>, op: &crate::sql::ast::BinaryOp
     }
 }
 
+/// Zone-map verdict for one segment: which blocks of rows may satisfy the
+/// filter comparison.
+struct ZoneMask {
+    blocks: Vec<bool>,
+    block_rows: usize,
+}
+
+impl ZoneMask {
+    fn none_match(&self) -> bool {
+        !self.blocks.contains(&true)
+    }
+
+    #[inline]
+    fn may_match(&self, row_idx: usize) -> bool {
+        self.blocks[row_idx / self.block_rows]
+    }
+}
+
 /// Decode a single value from a ColumnarSSTableBuilder's raw column buffer.
 /// Used by ColSegmentStore::get() to read buffered (unflushed) rows.
 /// Format matches add_values: Integer/Timestamp = [8B i64 LE], Float = [8B f64 LE],
@@ -86,7 +122,7 @@ fn decode_buffered_value(
     buf: &crate::storage::lsm::columnar::ColumnarSSTableBuilder,
     col_idx: usize,
     row_idx: usize,
-    _col_type: &ColumnType,
+    col_type: &ColumnType,
 ) -> Value {
     use crate::storage::lsm::columnar::ColumnTypeTag;
     // Check NULL flag first.
@@ -137,15 +173,59 @@ fn decode_buffered_value(
                     if len == 0xFFFF || pos + len > raw.len() {
                         return Value::Null;
                     }
-                    return Value::Text(ArcString(std::sync::Arc::from(
-                        std::str::from_utf8(&raw[pos..pos + len]).unwrap_or(""),
-                    )));
+                    let s = std::str::from_utf8(&raw[pos..pos + len]).unwrap_or("");
+                    if col_type.is_text_encoded() {
+                        return col_type.value_from_text(s);
+                    }
+                    return Value::Text(ArcString(std::sync::Arc::from(s)));
                 }
                 pos += if len == 0xFFFF { 0 } else { len };
                 r += 1;
             }
             Value::Null
         }
+        Some(ColumnTypeTag::Vector) => {
+            // Vector rows are [dim:u16][f32×dim], concatenated (dim 0 = NULL).
+            let mut pos = 0usize;
+            for _ in 0..row_idx {
+                if pos + 2 > raw.len() {
+                    return Value::Null;
+                }
+                pos += 2 + u16::from_le_bytes([raw[pos], raw[pos + 1]]) as usize * 4;
+            }
+            if pos + 2 > raw.len() {
+                return Value::Null;
+            }
+            let dim = u16::from_le_bytes([raw[pos], raw[pos + 1]]) as usize;
+            let data = &raw[pos + 2..];
+            if dim == 0 || dim * 4 > data.len() {
+                return Value::Null;
+            }
+            let v = data[..dim * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            col_type.value_from_vector(v)
+        }
+        Some(ColumnTypeTag::Spatial) => {
+            // Spatial rows are [len:u16][bincode(Geometry)], concatenated (len 0 = NULL).
+            let mut pos = 0usize;
+            for _ in 0..row_idx {
+                if pos + 2 > raw.len() {
+                    return Value::Null;
+                }
+                pos += 2 + u16::from_le_bytes([raw[pos], raw[pos + 1]]) as usize;
+            }
+            if pos + 2 > raw.len() {
+                return Value::Null;
+            }
+            let len = u16::from_le_bytes([raw[pos], raw[pos + 1]]) as usize;
+            if len == 0 || pos + 2 + len > raw.len() {
+                return Value::Null;
+            }
+            bincode::deserialize::<crate::types::Geometry>(&raw[pos + 2..pos + 2 + len])
+                .map_or(Value::Null, |g| Value::Spatial(std::boxed::Box::new(g)))
+        }
         _ => Value::Null,
     }
 }
@@ -153,6 +233,13 @@ fn decode_buffered_value(
 /// Compaction trigger: merge when segment count reaches this.
 const COMPACTION_SEGMENT_THRESHOLD: usize = 3;
 
+/// A write partition merges its staged rows into the write buffer once it
+/// holds this many, so concurrent writers take the buffer lock once per batch.
+const STAGED_MERGE_ROWS: usize = 64;
+
+/// Rows appended to one write partition but not yet in the write buffer.
+type StagedRows = Vec<(u64, u64, Vec<Value>)>;
+
 /// Append-only multi-segment store for one columnar table.
 pub struct ColSegmentStore {
     #[allow(dead_code)]
@@ -162,6 +249,15 @@ pub struct ColSegmentStore {
     segments: RwLock<VecDeque<Arc<Segment>>>,
     /// In-memory write buffer. Flushed as a delta segment (does not read old data).
     write_buf: Mutex<ColumnarSSTableBuilder>,
+    /// Per-partition staging for single-row appends, routed like the WAL
+    /// (`key % partitions`). Every row of a key lands in the same partition,
+    /// so merging partitions in any order keeps each key's versions ordered.
+    /// Empty for an unpartitioned store (appends go straight to `write_buf`).
+    staged: Box<[Mutex<StagedRows>]>,
+    /// Rows staged across all partitions (changed under the partition lock).
+    staged_rows: AtomicU64,
+    /// Estimated heap bytes of the staged rows.
+    staged_bytes: AtomicUsize,
     /// Write lock serializing flush_buffer + merge_segments. Without this, a
     /// concurrent flush (triggered by ensure_query_visibility during a query)
     /// can create a segment that force_compact_all then misses or clobbers
@@ -191,6 +287,16 @@ pub struct ColSegmentStore {
     /// common steady-state case after flush). Without this, every point query
     /// pays ~20-40ns of Mutex lock/unlock even when the buffer is empty.
     buffered_count: AtomicU64,
+    /// Keys recently confirmed absent by `get()`. Appends invalidate their
+    /// keys after the rows are buffered; dedup-style "exists?" probes for
+    /// new ids then skip the segment fence-index lookups.
+    negative_cache: NegativeCache,
+    /// Rows ruled out by segment zone maps without evaluating the predicate.
+    zone_skipped_rows: AtomicU64,
+    /// Write captures of index rebuilds running on this table. `capturing`
+    /// mirrors `!captures.is_empty()` so writes skip the lock otherwise.
+    captures: RwLock<Vec<Arc<WriteCapture>>>,
+    capturing: AtomicBool,
 }
 
 /// Clear col_cache after this many point queries to bound memory. At 2M rows,
@@ -208,11 +314,24 @@ impl ColSegmentStore {
         base_dir: &Path,
         table_name: &str,
         col_types: Vec<ColumnType>,
+    ) -> Result<Arc<Self>> {
+        Self::create_partitioned(base_dir, table_name, col_types, 1)
+    }
+
+    /// Create a store whose single-row appends are staged in `partitions`
+    /// write partitions, so writers on different partitions do not serialize
+    /// on the write buffer. `partitions <= 1` appends to the buffer directly.
+    pub fn create_partitioned(
+        base_dir: &Path,
+        table_name: &str,
+        col_types: Vec<ColumnType>,
+        partitions: u8,
     ) -> Result<Arc<Self>> {
         let dir = base_dir.join("columnar_ms").join(table_name);
-        std::fs::create_dir_all(&dir)?;
+        let backend = crate::storage::backend::backend_for(&dir);
+        backend.create_dir_all(&dir)?;
         let manifest_path = dir.join("MANIFEST");
-        let manifest_exists = manifest_path.exists();
+        let manifest_exists = backend.exists(&manifest_path);
         let manifest = if manifest_exists {
             Manifest::open(&manifest_path)?
         } else {
@@ -225,6 +344,13 @@ impl ColSegmentStore {
             dir,
             segments: RwLock::new(VecDeque::new()),
             write_buf: Mutex::new(write_buf),
+            staged: if partitions > 1 {
+                (0..partitions).map(|_| Mutex::new(Vec::new())).collect()
+            } else {
+                Box::default()
+            },
+            staged_rows: AtomicU64::new(0),
+            staged_bytes: AtomicUsize::new(0),
             flush_merge_lock: parking_lot::Mutex::new(()),
             next_segment_id: AtomicU64::new(1),
             manifest: Mutex::new(manifest),
@@ -233,6 +359,10 @@ impl ColSegmentStore {
             in_hash_cache: RwLock::new(std::collections::HashMap::new()),
             point_query_count: AtomicU64::new(0),
             buffered_count: AtomicU64::new(0),
+            negative_cache: NegativeCache::default(),
+            zone_skipped_rows: AtomicU64::new(0),
+            captures: RwLock::new(Vec::new()),
+            capturing: AtomicBool::new(false),
         });
         // 🔥 Auto-recover segments from disk if the MANIFEST has active entries.
         // This handles the restart case: get_or_create_col_segment_store is called
@@ -247,18 +377,25 @@ impl ColSegmentStore {
     /// 🔥 Stability: auto-compacts when segments exceed threshold, preventing
     /// unbounded segment accumulation from repeated writes.
     pub fn append_rows(&self, rows: &[(u64, u64, Vec<Value>)]) -> Result<()> {
+        if self.capturing.load(Ordering::Acquire) {
+            for (key, _, _) in rows {
+                self.capture_write(*key);
+            }
+        }
         // Invalidate caches on write.
         if !rows.is_empty() {
             self.groupby_cache.write().clear();
             self.in_hash_cache.write().clear();
         }
-        let mut buf = self.write_buf.lock();
+        let mut buf = self.lock_write_buf();
         for (key, ts, row) in rows {
             buf.add_values(*key, *ts, false, row)?;
         }
         let n = buf.num_rows as u64;
         drop(buf);
         self.buffered_count.store(n, Ordering::Relaxed);
+        self.negative_cache
+            .invalidate_keys(rows.iter().map(|(key, _, _)| *key));
         // Auto-compaction disabled during append_rows — it can deadlock
         // when merge_segments reads column data while holding write locks.
         // Compaction runs on demand via ensure_query_visibility or compact_once.
@@ -269,22 +406,91 @@ impl ColSegmentStore {
     /// append_rows requires (it takes &[(.., Vec<Value>)]). This is the hot
     /// path for single-row INSERT (saves one heap allocation per INSERT).
     pub fn append_row_ref(&self, key: u64, ts: u64, row: &[Value]) -> Result<()> {
-        self.groupby_cache.write().clear();
-        self.in_hash_cache.write().clear();
-        let mut buf = self.write_buf.lock();
+        if self.capturing.load(Ordering::Acquire) {
+            self.capture_write(key);
+        }
+        self.clear_query_caches();
+        // Values too large for the columnar layout skip staging, so that
+        // add_values reports the error to this caller rather than a merge
+        if !self.staged.is_empty() && row.iter().all(|v| v.memory_size() <= u16::MAX as usize) {
+            let staged = {
+                let mut part = self.staged[(key % self.staged.len() as u64) as usize].lock();
+                part.push((key, ts, row.to_vec()));
+                self.staged_rows.fetch_add(1, Ordering::Relaxed);
+                self.staged_bytes.fetch_add(
+                    16 + row.iter().map(Value::memory_size).sum::<usize>(),
+                    Ordering::Relaxed,
+                );
+                part.len()
+            };
+            self.negative_cache.invalidate(key);
+            if staged >= STAGED_MERGE_ROWS {
+                drop(self.lock_write_buf());
+            }
+            return Ok(());
+        }
+        let mut buf = self.lock_write_buf();
         buf.add_values(key, ts, false, row)?;
         let n = buf.num_rows as u64;
         drop(buf);
         self.buffered_count.store(n, Ordering::Relaxed);
+        self.negative_cache.invalidate(key);
         Ok(())
     }
 
+    /// Clear the GROUP BY / IN-hash caches, taking their write locks only
+    /// when they hold something (the common insert case takes none).
+    fn clear_query_caches(&self) {
+        if !self.groupby_cache.read().is_empty() {
+            self.groupby_cache.write().clear();
+        }
+        if !self.in_hash_cache.read().is_empty() {
+            self.in_hash_cache.write().clear();
+        }
+    }
+
+    /// Lock the write buffer after merging every partition's staged rows
+    /// into it, so the guard sees all appends that have returned.
+    fn lock_write_buf(&self) -> parking_lot::MutexGuard<'_, ColumnarSSTableBuilder> {
+        let mut buf = self.write_buf.lock();
+        if self.staged_rows.load(Ordering::Relaxed) == 0 {
+            return buf;
+        }
+        for part in self.staged.iter() {
+            let mut part = part.lock();
+            if part.is_empty() {
+                continue;
+            }
+            let rows = std::mem::take(&mut *part);
+            self.staged_rows
+                .fetch_sub(rows.len() as u64, Ordering::Relaxed);
+            drop(part);
+            for (key, ts, row) in &rows {
+                // Oversized values never reach staging, so this cannot fail
+                let _ = buf.add_values(*key, *ts, false, row);
+            }
+        }
+        self.staged_bytes.store(0, Ordering::Relaxed);
+        self.buffered_count
+            .store(buf.num_rows as u64, Ordering::Relaxed);
+        buf
+    }
+
+    /// Whether the write buffer or a write partition holds rows
+    fn has_buffered(&self) -> bool {
+        self.buffered_count.load(Ordering::Relaxed) > 0
+            || self.staged_rows.load(Ordering::Relaxed) > 0
+    }
+
     /// Append a tombstone (deletion marker) for a key. The tombstone suppresses
     /// the row in multi-segment scans (newest-version-wins with deleted=true).
     /// 🔥 Stability: auto-compacts when segments exceed threshold.
     pub fn append_tombstone(&self, key: u64, ts: u64) -> Result<()> {
+        if self.capturing.load(Ordering::Acquire) {
+            self.capture_write(key);
+        }
         let col_types = self.col_types.load();
-        let mut buf = self.write_buf.lock();
+        let mut buf = self.lock_write_buf();
         // Write placeholder values for each column (keeps column_buffers in sync
         // with num_rows). The actual values are never read for deleted rows.
         let placeholder: Vec<Value> = col_types.iter().map(|_| Value::Null).collect();
@@ -295,6 +501,29 @@ impl ColSegmentStore {
         Ok(())
     }
 
+    /// Record writes in `capture` until [`Self::detach_capture`]. Writes
+    /// already running may be missed: the caller keeps them out while
+    /// attaching.
+    pub fn attach_capture(&self, capture: Arc<WriteCapture>) {
+        let mut captures = self.captures.write();
+        captures.push(capture);
+        self.capturing.store(true, Ordering::Release);
+    }
+
+    pub fn detach_capture(&self, capture: &Arc<WriteCapture>) {
+        let mut captures = self.captures.write();
+        captures.retain(|c| !Arc::ptr_eq(c, capture));
+        self.capturing
+            .store(!captures.is_empty(), Ordering::Release);
+    }
+
+    /// Report a write to `key` to the attached captures, before it applies
+    fn capture_write(&self, key: u64) {
+        for capture in self.captures.read().iter() {
+            capture.touch(key, || self.get(key));
+        }
+    }
+
     /// Extend `col_types` to support `ALTER TABLE ADD COLUMN`.
     ///
     /// Without this call, a post-ALTER INSERT silently drops the new column's
@@ -359,6 +588,70 @@ impl ColSegmentStore {
         Ok(())
     }
 
+    /// Remove column `pos` from the store (ALTER TABLE DROP COLUMN).
+    ///
+    /// Unlike `add_column_type`, old segments can't be NULL-padded into the
+    /// new layout: every later column shifts down one position. All live
+    /// rows are read through a merged scan and rewritten, without the
+    /// dropped column, into a single segment that replaces the old ones.
+    pub fn drop_column_type(&self, pos: usize) -> Result<()> {
+        let _guard = self.flush_merge_lock.lock();
+        // Drain the buffer so the scan below sees every row.
+        self.flush_buffer_locked()?;
+        let old_types = self.col_types.load_full();
+        if pos >= old_types.len() {
+            return Err(crate::StorageError::InvalidData(format!(
+                "Column position {} out of range ({} columns)",
+                pos,
+                old_types.len()
+            )));
+        }
+        let old_segs: Vec<Arc<Segment>> = self.segments.read().iter().cloned().collect();
+        let mut new_types = (*old_types).clone();
+        new_types.remove(pos);
+
+        let rewritten = if old_segs.is_empty() {
+            None
+        } else {
+            let id = self.next_segment_id.fetch_add(1, Ordering::Relaxed);
+            let path = self.dir.join(format!("{:010}.sst", id));
+            let mut builder = ColumnarSSTableBuilder::new(&path, new_types.clone());
+            // MergeCursor yields live rows in ascending key order, as the
+            // builder's row_map requires.
+            for (key, ts, mut row) in MergeCursor::new(&old_segs, &old_types) {
+                if pos < row.len() {
+                    row.remove(pos);
+                }
+                builder.add_values(key, ts, false, &row)?;
+            }
+            builder.finish()?;
+            Some((id, Arc::new(Segment::open(&path, id)?)))
+        };
+
+        self.col_types.store(Arc::new(new_types));
+        let buf_path = self.dir.join(".writebuf.tmp");
+        *self.write_buf.lock() =
+            ColumnarSSTableBuilder::new(&buf_path, (**self.col_types.load()).clone());
+
+        if let Some((id, new_seg)) = rewritten {
+            let old_ids: Vec<u64> = old_segs.iter().map(|s| s.id).collect();
+            self.manifest.lock().record_compaction(id, &old_ids)?;
+            {
+                let mut segs = self.segments.write();
+                segs.clear();
+                segs.push_back(new_seg);
+            }
+            let backend = crate::storage::backend::backend_for(&self.dir);
+            for oid in &old_ids {
+                let p = self.dir.join(format!("{:010}.sst", oid));
+                let _ = backend.remove_file(&p);
+            }
+            self.manifest.lock().record_gc(&old_ids)?;
+        }
+        self.clear_query_caches();
+        Ok(())
+    }
+
     /// Flush the buffer to a new delta segment on disk. Does NOT read old segments.
     /// O(this batch). Writes the file (no fsync — durability via WAL/manifest).
     pub fn flush_buffer(&self) -> Result<()> {
@@ -379,7 +672,7 @@ impl ColSegmentStore {
         // Take buffer contents out, replace with a fresh builder, release the lock fast.
         let buf_path = self.dir.join(".writebuf.tmp");
         let mut old_buf = {
-            let mut guard = self.write_buf.lock();
+            let mut guard = self.lock_write_buf();
             let fresh = ColumnarSSTableBuilder::new(&buf_path, (**col_types).clone());
             std::mem::replace(&mut *guard, fresh)
         };
@@ -395,7 +688,9 @@ impl ColSegmentStore {
         // Record in manifest (fsync'd) BEFORE exposing in memory.
         self.manifest.lock().add_segment(id)?;
         self.segments.write().push_back(seg);
-        // Invalidate all query caches (data changed).
+        // Invalidate all query caches (data changed). A `get` racing this
+        // flush may have seen neither the old buffer nor the new segment.
+        self.negative_cache.clear();
         self.groupby_cache.write().clear();
         self.in_hash_cache.write().clear();
         self.buffered_count.store(0, Ordering::Relaxed);
@@ -418,25 +713,28 @@ impl ColSegmentStore {
         let segs = self.segments_snapshot();
         let seg_ids: Vec<u64> = segs.iter().map(|s| s.id).collect();
         self.segments.write().clear();
+        self.negative_cache.clear();
         // Clear the write buffer by finishing (no-op if empty) then draining.
         // The builder has no public clear(); we just leave it — the store is
         // being removed from the registry anyway, so a new store is created on
         // recreate. Delete on-disk files so the old data can't be recovered.
+        let backend = crate::storage::backend::backend_for(&self.dir);
+        // Delete the manifest file first so a reopen finds no manifest →
+        // creates a fresh one with no segments. Once it is gone nothing
+        // references the segments, so a crash below only leaves orphans.
+        let manifest_path = self.dir.join("MANIFEST");
+        let _ = backend.remove_file(&manifest_path);
         for id in &seg_ids {
             let path = self.dir.join(format!("{:010}.sst", id));
-            let _ = std::fs::remove_file(&path);
+            let _ = backend.remove_file(&path);
         }
-        // Delete the manifest file so a reopen finds no manifest → creates a
-        // fresh one with no segments.
-        let manifest_path = self.dir.join("MANIFEST");
-        let _ = std::fs::remove_file(&manifest_path);
         Ok(())
     }
 
     /// SegData slices held by in-flight SelectColumnar queries (use-after-free).
     pub fn ensure_query_visibility(&self) -> Result<()> {
         // 🚀 Use the atomic buffered_count (avoids Mutex lock when empty).
-        if self.buffered_count.load(Ordering::Relaxed) > 0 {
+        if self.has_buffered() {
             self.flush_buffer()?;
         }
         Ok(())
@@ -446,7 +744,7 @@ impl ColSegmentStore {
     /// Called at query entry points to bound memory: N segments × ~18MB
     /// → 1 segment × ~18MB. This is what makes RSS stabilize after bulk insert.
     pub fn prepare_for_query(&self) -> Result<()> {
-        if self.buffered_count.load(Ordering::Relaxed) > 0 {
+        if self.has_buffered() {
             self.flush_buffer()?;
         }
         let segs = self.segments.read();
@@ -475,17 +773,21 @@ impl ColSegmentStore {
     /// `get` fell through to an older segment holding the live row and
     /// returned stale data after a DELETE.
     pub fn get(&self, key: u64) -> Option<Vec<Value>> {
+        if self.negative_cache.contains(key) {
+            return None;
+        }
+        let negative_epoch = self.negative_cache.epoch();
         // Snapshot col_types once — used by both buffer-decode and segment-decode.
         let col_types = self.col_types.load();
         // 🚀 Fast path: if the write buffer is empty (common steady-state after
         // flush), skip the Mutex lock + rposition scan entirely. Saves ~20-40ns
         // per point query (the lock acquire/release + iterator setup overhead).
-        if self.buffered_count.load(Ordering::Relaxed) > 0 {
+        if self.has_buffered() {
             // 🔑 Check the write buffer FIRST — it may hold a newer version (UPDATE)
             // or a tombstone (DELETE) that supersedes the segment data. Without this,
             // a DELETE whose tombstone is still in the buffer (lazy flush) would be
             // invisible to get(), which would return the stale live row from a segment.
-            let buf = self.write_buf.lock();
+            let buf = self.lock_write_buf();
             if let Some(idx) = buf.keys.iter().rposition(|&k| k == key) {
                 // Found in buffer — newest version (rposition = last occurrence).
                 // If deleted, return None.
@@ -531,6 +833,53 @@ impl ColSegmentStore {
                 return Some(result);
             }
         }
+        self.negative_cache.insert(key, negative_epoch);
+        None
+    }
+
+    /// Point lookup of selected columns only: same visibility rules as
+    /// [`get`](Self::get) (buffer first, then segments newest → oldest,
+    /// tombstones stop the search), but only `cols` are decoded. Reading an
+    /// embedding this way never touches the row's other columns.
+    pub fn get_columns(&self, key: u64, cols: &[usize]) -> Option<Vec<Value>> {
+        if self.negative_cache.contains(key) {
+            return None;
+        }
+        let col_types = self.col_types.load();
+        if self.has_buffered() {
+            let buf = self.lock_write_buf();
+            if let Some(idx) = buf.keys.iter().rposition(|&k| k == key) {
+                if buf.deleted[idx] {
+                    return None;
+                }
+                return Some(
+                    cols.iter()
+                        .map(|&ci| match col_types.get(ci) {
+                            Some(ct) if ci < buf.column_buffers.len() => {
+                                decode_buffered_value(&buf, ci, idx, ct)
+                            }
+                            _ => Value::Null,
+                        })
+                        .collect(),
+                );
+            }
+        }
+        let segs = self.segments.read();
+        for seg in segs.iter().rev() {
+            if let Some(idx) = seg.sst.find_row_by_key(key) {
+                if seg.sst.row_map.is_deleted(idx) {
+                    return None;
+                }
+                return Some(
+                    cols.iter()
+                        .map(|&ci| match col_types.get(ci) {
+                            Some(ct) => seg.get_value_at_idx(idx, ci, ct),
+                            None => Value::Null,
+                        })
+                        .collect(),
+                );
+            }
+        }
         None
     }
 
@@ -578,6 +927,37 @@ impl ColSegmentStore {
         project_cols: &[usize],
         predicate: &dyn Fn(Option<&Value>) -> bool,
         max_results: usize,
+    ) -> Vec<(u64, Vec<Value>)> {
+        self.scan_projected_pruned(filter_col, project_cols, predicate, max_results, None)
+    }
+
+    /// Same as scan_projected_filtered, for a predicate that is exactly
+    /// `filter_col <op> target`: blocks whose zone map rules the comparison
+    /// out are skipped without decoding the filter value.
+    pub fn scan_projected_compare(
+        &self,
+        filter_col: usize,
+        op: &crate::sql::ast::BinaryOperator,
+        target: &Value,
+        project_cols: &[usize],
+        predicate: &dyn Fn(Option<&Value>) -> bool,
+    ) -> Vec<(u64, Vec<Value>)> {
+        self.scan_projected_pruned(
+            Some(filter_col),
+            project_cols,
+            predicate,
+            usize::MAX,
+            Some((op, target)),
+        )
+    }
+
+    fn scan_projected_pruned(
+        &self,
+        filter_col: Option<usize>,
+        project_cols: &[usize],
+        predicate: &dyn Fn(Option<&Value>) -> bool,
+        max_results: usize,
+        compare: Option<(&crate::sql::ast::BinaryOperator, &Value)>,
     ) -> Vec<(u64, Vec<Value>)> {
         // Snapshot col_types once for the whole scan — guards against a
         // concurrent ALTER swapping in a new layout mid-scan.
@@ -615,6 +995,16 @@ impl ColSegmentStore {
             // Descending index order within a segment: rows are appended old→new,
             // so iterating n→0 visits the newest (largest index) version of a key
             // first. Combined with `seen`, this keeps the newest version.
+            let zone = filter_col
+                .zip(compare)
+                .and_then(|(fc, (op, target))| self.zone_mask(seg, fc, op, target));
+            if zone.as_ref().is_some_and(ZoneMask::none_match) {
+                // Nothing here matches, but its keys still shadow older versions.
+                if need_dedup {
+                    seen.extend((0..n).map(|i| seg.sst.row_map.key(i)));
+                }
+                continue;
+            }
             let order: Vec<usize> = if need_dedup {
                 (0..n).rev().collect()
             } else {
@@ -696,10 +1086,7 @@ impl ColSegmentStore {
                                     continue;
                                 }
                                 let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                                while di < decoded.len() && decoded[di].0 != ek {
-                                    di += 1;
-                                }
-                                if di < decoded.len() {
+                                if decoded.get(di).is_some_and(|d| d.0 == ek) {
                                     per[i] = Some(decoded[di].1.clone());
                                     di += 1;
                                 }
@@ -731,10 +1118,7 @@ impl ColSegmentStore {
                                     continue;
                                 }
                                 let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                                while di < decoded.len() && decoded[di].0 != ek {
-                                    di += 1;
-                                }
-                                if di < decoded.len() {
+                                if decoded.get(di).is_some_and(|d| d.0 == ek) {
                                     per[i] = Some(decoded[di].1.clone());
                                     di += 1;
                                 }
@@ -762,18 +1146,25 @@ impl ColSegmentStore {
                 if seg.sst.row_map.is_deleted(i) {
                     continue;
                 }
+                if zone.as_ref().is_some_and(|z| !z.may_match(i)) {
+                    continue;
+                }
 
                 // Decode filter value only (cheap: single column lookup).
                 let fval: Option<Value> = if filter_col.is_some() {
                     let v = if let Some(ref f) = fcol_fixed {
                         match fcol_type {
                             Some(ColumnType::Integer) => f.get_i64(i).map(Value::Integer),
+                            Some(ColumnType::Timestamp) => f
+                                .get_i64(i)
+                                .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                             Some(ColumnType::Float) => f.get_f64(i).map(Value::Float),
                             Some(ColumnType::Boolean) => f.get_bool(i).map(Value::Bool),
                             _ => None,
                         }
                     } else if let Some(ref t) = fcol_text {
-                        t.get_str(i).map(|s| Value::Text(s.into()))
+                        t.get_str(i)
+                            .map(|s| fcol_type.unwrap_or(&ColumnType::Text).value_from_text(s))
                     } else {
                         None
                     };
@@ -812,6 +1203,12 @@ impl ColSegmentStore {
                                         .ok()
                                         .and_then(|f| f.get_bool(i))
                                         .map(Value::Bool),
+                                    ColumnType::Timestamp => seg
+                                        .sst
+                                        .read_fixed_i64(pc)
+                                        .ok()
+                                        .and_then(|f| f.get_i64(i))
+                                        .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                                     _ => seg
                                         .sst
                                         .read_fixed_i64(pc)
@@ -819,6 +1216,19 @@ impl ColSegmentStore {
                                         .and_then(|f| f.get_i64(i))
                                         .map(Value::Integer),
                                 }
+                            } else if matches!(seg.sst.column_tags[pc], ColumnTypeTag::Vector) {
+                                // Vector layout is not Text; decode just this row
+                                Some(
+                                    seg.sst
+                                        .read_vector_at(pc, i)
+                                        .ok()
+                                        .flatten()
+                                        .map_or(Value::Null, |v| {
+                                            col_types[pc].value_from_vector(v)
+                                        }),
+                                )
+                            } else if matches!(seg.sst.column_tags[pc], ColumnTypeTag::Spatial) {
+                                Some(seg.get_value_at_idx(i, pc, &col_types[pc]))
                             } else {
                                 match seg
                                     .sst
@@ -826,7 +1236,7 @@ impl ColSegmentStore {
                                     .ok()
                                     .and_then(|t| t.get_str(i).map(|s| s.to_string()))
                                 {
-                                    Some(s) => Some(Value::Text(s.into())),
+                                    Some(s) => Some(col_types[pc].value_from_text(&s)),
                                     None => Some(Value::Null),
                                 }
                             }
@@ -842,6 +1252,9 @@ impl ColSegmentStore {
                                 (Some(Some(f)), _, ColumnType::Integer) => {
                                     f.get_i64(i).map(Value::Integer)
                                 }
+                                (Some(Some(f)), _, ColumnType::Timestamp) => f
+                                    .get_i64(i)
+                                    .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                                 (Some(Some(f)), _, ColumnType::Float) => {
                                     f.get_f64(i).map(Value::Float)
                                 }
@@ -854,14 +1267,21 @@ impl ColSegmentStore {
                                     .cloned()
                                     .flatten()
                                     .map(|g| Value::Spatial(std::boxed::Box::new(g))),
-                                (_, _, ColumnType::Tensor(_)) => pvector
+                                (
+                                    _,
+                                    _,
+                                    ct @ (ColumnType::Tensor(_)
+                                    | ColumnType::ShapedTensor(_)
+                                    | ColumnType::EncodedVector { .. }),
+                                ) => pvector
                                     .get(pi)
                                     .and_then(|p| p.get(i))
                                     .cloned()
                                     .flatten()
-                                    .map(|v| {
-                                        Value::Vector(crate::types::ArcVec(std::sync::Arc::new(v)))
-                                    }),
+                                    .map(|v| ct.value_from_vector(v)),
+                                (_, Some(Some(t)), ct) if ct.is_text_encoded() => {
+                                    t.get_str(i).map(|s| ct.value_from_text(s))
+                                }
                                 (_, Some(Some(t)), ColumnType::Text) => {
                                     if !ptext_interned.is_empty() {
                                         ptext_interned
@@ -1369,18 +1789,24 @@ impl ColSegmentStore {
                         let v = if pc < col_types.len() {
                             if matches!(
                                 col_types[pc],
-                                ColumnType::Spatial | ColumnType::Tensor(_)
+                                ColumnType::Spatial
+                                    | ColumnType::Tensor(_)
+                                    | ColumnType::ShapedTensor(_)
+                                    | ColumnType::EncodedVector { .. }
                             ) {
                                 Some(Value::Null)
                             } else if let Some(Some(ref f)) = pfixed.get(pi) {
                                 match col_types[pc] {
                                     ColumnType::Integer => f.get_i64(i).map(Value::Integer),
+                                    ColumnType::Timestamp => f
+                                        .get_i64(i)
+                                        .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                                     ColumnType::Float => f.get_f64(i).map(Value::Float),
                                     ColumnType::Boolean => f.get_bool(i).map(Value::Bool),
                                     _ => None,
                                 }
                             } else if let Some(Some(ref t)) = ptext_cols.get(pi) {
-                                t.get_str(i).map(|s| Value::Text(s.into()))
+                                t.get_str(i).map(|s| col_types[pc].value_from_text(s))
                             } else {
                                 None
                             }
@@ -1490,10 +1916,20 @@ impl ColSegmentStore {
                         let v = if let Some(Some(ref f)) = fixed_cols.get(pi) {
                             match col_types.get(pc) {
                                 Some(ColumnType::Integer) => f.get_i64(i).map(Value::Integer),
+                                Some(ColumnType::Timestamp) => f
+                                    .get_i64(i)
+                                    .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                                 Some(ColumnType::Float) => f.get_f64(i).map(Value::Float),
                                 Some(ColumnType::Boolean) => f.get_bool(i).map(Value::Bool),
                                 _ => None,
                             }
+                        } else if let Some(ct) = col_types.get(pc).filter(|ct| ct.is_text_encoded())
+                        {
+                            text_cols
+                                .get(pi)
+                                .and_then(|t| t.as_ref())
+                                .and_then(|t| t.get_str(i))
+                                .map(|s| ct.value_from_text(s))
                         } else if let Some(Some(ref t)) = text_cols.get(pi) {
                             t.get_str(i).map(|s| {
                                 let arc = str_pool.get(s).cloned().unwrap_or_else(|| {
@@ -1535,10 +1971,19 @@ impl ColSegmentStore {
                     let v = if let Some(Some(ref f)) = fixed_cols.get(pi) {
                         match col_types.get(pc) {
                             Some(ColumnType::Integer) => f.get_i64(i).map(Value::Integer),
+                            Some(ColumnType::Timestamp) => f
+                                .get_i64(i)
+                                .map(|v| Value::Timestamp(Timestamp::from_micros(v))),
                             Some(ColumnType::Float) => f.get_f64(i).map(Value::Float),
                             Some(ColumnType::Boolean) => f.get_bool(i).map(Value::Bool),
                             _ => None,
                         }
+                    } else if let Some(ct) = col_types.get(pc).filter(|ct| ct.is_text_encoded()) {
+                        text_cols
+                            .get(pi)
+                            .and_then(|t| t.as_ref())
+                            .and_then(|t| t.get_str(i))
+                            .map(|s| ct.value_from_text(s))
                     } else if let Some(Some(ref t)) = text_cols.get(pi) {
                         t.get_str(i).map(|s| {
                             let arc = str_pool.get(s).cloned().unwrap_or_else(|| {
@@ -1583,12 +2028,12 @@ impl ColSegmentStore {
             Some(std::collections::HashSet::with_capacity(total_rows))
         };
 
-        // Wrap f64 for total ordering (NaN-safe).
+        // Wrap f64 for total ordering (NaN-safe); `.1` reverses it for DESC.
         #[derive(Clone)]
-        struct OrdF64(f64);
+        struct OrdF64(f64, bool);
         impl PartialEq for OrdF64 {
             fn eq(&self, o: &Self) -> bool {
-                self.0 == o.0
+                self.cmp(o) == std::cmp::Ordering::Equal
             }
         }
         impl Eq for OrdF64 {}
@@ -1599,9 +2044,12 @@ impl ColSegmentStore {
         }
         impl Ord for OrdF64 {
             fn cmp(&self, o: &Self) -> std::cmp::Ordering {
-                self.0
-                    .partial_cmp(&o.0)
-                    .unwrap_or(std::cmp::Ordering::Equal)
+                let ord = float_cmp(self.0, o.0);
+                if self.1 {
+                    ord.reverse()
+                } else {
+                    ord
+                }
             }
         }
 
@@ -1630,13 +2078,8 @@ impl ColSegmentStore {
                     .get_f64(i)
                     .or_else(|| fseg.get_i64(i).map(|x| x as f64))
                 {
-                    let entry = if ascending {
-                        (OrdF64(v), key)
-                    } else {
-                        // For descending: use negated value so max-heap keeps largest.
-                        (OrdF64(-v), key)
-                    };
-                    heap.push(entry);
+                    // For descending the reversed order makes the max-heap keep the largest.
+                    heap.push((OrdF64(v, !ascending), key));
                     if heap.len() > k {
                         heap.pop();
                     }
@@ -1644,20 +2087,12 @@ impl ColSegmentStore {
             }
         }
 
-        let mut result: Vec<(f64, u64)> = heap
+        // Sorted in the heap's order: by value descending (for DESC) or
+        // ascending (for ASC).
+        heap.into_sorted_vec()
             .into_iter()
-            .map(|(of, key)| {
-                let v = if ascending { of.0 } else { -of.0 };
-                (v, key)
-            })
-            .collect();
-        // Sort by value descending (for DESC) or ascending (for ASC).
-        if ascending {
-            result.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-        } else {
-            result.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        }
-        result.into_iter().map(|(_, key)| key).collect()
+            .map(|(_, key)| key)
+            .collect()
     }
 
     /// Snapshot of active segments (oldest→newest). Callers iterate directly
@@ -1666,6 +2101,62 @@ impl ColSegmentStore {
         self.segments.read().iter().cloned().collect()
     }
 
+    /// Rows skipped by zone maps so far (blocks whose min/max rule out the
+    /// filter comparison).
+    pub fn zone_skipped_rows(&self) -> u64 {
+        self.zone_skipped_rows.load(Ordering::Relaxed)
+    }
+
+    /// Zone-map mask of `seg` for `filter_col <op> target`; `None` when the
+    /// segment records no ranges for the column.
+    fn zone_mask(
+        &self,
+        seg: &Segment,
+        filter_col: usize,
+        op: &crate::sql::ast::BinaryOperator,
+        target: &Value,
+    ) -> Option<ZoneMask> {
+        let blocks = seg.sst.zone_blocks(filter_col, op, target)?;
+        let block_rows = seg.sst.zone_map.as_ref()?.block_rows();
+        let n = seg.sst.num_rows;
+        let skipped: usize = (0..blocks.len())
+            .filter(|&b| !blocks[b])
+            .map(|b| block_rows.min(n - b * block_rows))
+            .sum();
+        self.zone_skipped_rows
+            .fetch_add(skipped as u64, Ordering::Relaxed);
+        Some(ZoneMask { blocks, block_rows })
+    }
+
+    /// Counters of the negative lookup cache.
+    pub fn negative_cache_stats(&self) -> crate::cache::NegativeCacheStats {
+        self.negative_cache.stats()
+    }
+
+    /// Columns decoded in any segment's cache, most recently used first.
+    pub fn hot_columns(&self) -> Vec<usize> {
+        let mut cols: Vec<usize> = Vec::new();
+        for seg in self.segments_snapshot() {
+            for col_idx in seg.cached_columns() {
+                if !cols.contains(&col_idx) {
+                    cols.push(col_idx);
+                }
+            }
+        }
+        cols
+    }
+
+    /// Pre-decode `cols` in every segment (cache warm-up after reopen).
+    /// Warms least recent first so the LRU order survives the round trip.
+    pub fn warm_columns(&self, cols: &[usize]) {
+        let n_cols = self.col_types.load().len();
+        for seg in self.segments_snapshot() {
+            for &col_idx in cols.iter().rev().filter(|&&c| c < n_cols) {
+                seg.warm_column(col_idx);
+            }
+        }
+    }
+
     /// Release mmap pages + clear col caches to reduce RSS after queries.
     /// Call after batch queries to keep memory low.
     /// Clear column decode caches to reduce heap memory. Does NOT release
@@ -1734,7 +2225,7 @@ impl ColSegmentStore {
         // values). Conservative: dedup whenever there's buffered data OR 2+
         // segments. A single compacted segment with empty buffer is the only
         // safe no-dedup case.
-        let buf_n = self.write_buf.lock().num_rows;
+        let buf_n = self.lock_write_buf().num_rows;
         let seg_count = self.segments.read().len();
         buf_n > 0 && seg_count >= 1 || seg_count >= 2
     }
@@ -1761,7 +2252,7 @@ impl ColSegmentStore {
         // Determine dedup need BEFORE locking write_buf (may_have_duplicate_keys
         // also locks write_buf — parking_lot Mutex is not reentrant → deadlock).
         let need_dedup = self.may_have_duplicate_keys();
-        let buf = self.write_buf.lock();
+        let buf = self.lock_write_buf();
         let segs = self.segments.read();
         let mut seen: std::collections::HashSet<u64> = if need_dedup {
             std::collections::HashSet::with_capacity(segs.iter().map(|s| s.sst.num_rows).sum())
@@ -1813,6 +2304,14 @@ impl ColSegmentStore {
                 continue;
             }
             let tag = seg.sst.column_tags[filter_col];
+            let zone = self.zone_mask(seg, filter_col, op, target);
+            if zone.as_ref().is_some_and(ZoneMask::none_match) {
+                // Nothing here matches, but its keys still shadow older versions.
+                if need_dedup {
+                    seen.extend((0..n).map(|i| seg.sst.row_map.key(i)));
+                }
+                continue;
+            }
 
             // Pre-decode the filter column once per segment.
             let fcol_fixed = if tag.is_fixed() {
@@ -1844,6 +2343,9 @@ impl ColSegmentStore {
                 Vec::new()
             };
             let process_row = |i: usize, count: &mut usize| {
+                if zone.as_ref().is_some_and(|z| !z.may_match(i)) {
+                    return;
+                }
                 let matches = if let Some(ref f) = fcol_fixed {
                     match tag {
                         ColumnTypeTag::Integer | ColumnTypeTag::Timestamp => {
@@ -1913,8 +2415,8 @@ impl ColSegmentStore {
 
     /// Single-pass aggregate over a filtered column — computes COUNT/SUM/AVG/
     /// MIN/MAX in one scan without materializing Value objects per row.
-    /// Returns (count, int_sum, float_sum, has_float, min_int, max_int,
-    /// min_float, max_float). The caller picks the relevant fields per aggregate.
+    /// Returns count, typed sum and int/float min/max in an [`AggregateResult`];
+    /// the caller picks the relevant fields per aggregate.
     ///
     /// 🔑 PERF: scan_projected_filtered materialized a Vec<Value> per row then
     /// did multi-pass collect()+sum(). This folds directly over raw i64/f64
@@ -1973,6 +2475,14 @@ impl ColSegmentStore {
             if agg_col >= seg.sst.column_tags.len() {
                 continue;
             }
+            let zone = filter_col.and_then(|fc| self.zone_mask(seg, fc, op, target));
+            if zone.as_ref().is_some_and(ZoneMask::none_match) {
+                // Nothing here matches, but its keys still shadow older versions.
+                if need_dedup {
+                    seen.extend((0..n).map(|i| seg.sst.row_map.key(i)));
+                }
+                continue;
+            }
             // Pre-decode filter + aggregate columns once per segment.
             let fcol_fixed = if !no_filter
                 && fc < seg.sst.column_tags.len()
@@ -2007,6 +2517,9 @@ impl ColSegmentStore {
 
             let has_deletions = seg.sst.row_map.has_any_deleted();
             let process_agg = |i: usize, result: &mut AggregateResult| {
+                if zone.as_ref().is_some_and(|z| !z.may_match(i)) {
+                    return;
+                }
                 // Apply filter predicate (zero-alloc, same as count_filtered).
                 let passes = if no_filter {
                     true
@@ -2052,14 +2565,14 @@ impl ColSegmentStore {
                         match af.get_f64(i) {
                             Some(v) => {
                                 result.count += 1;
-                                result.float_sum += v;
+                                result.sum.add_float(v);
                                 result.has_float = true;
                                 if result.count == 1 {
                                     result.min_float = v;
                                     result.max_float = v;
                                 } else {
-                                    result.min_float = result.min_float.min(v);
-                                    result.max_float = result.max_float.max(v);
+                                    result.min_float = float_min(result.min_float, v);
+                                    result.max_float = float_max(result.max_float, v);
                                 }
                             }
                             None => {
@@ -2070,18 +2583,7 @@ impl ColSegmentStore {
                         match af.get_i64(i) {
                             Some(v) => {
                                 result.count += 1;
-                                // 🚨 Use checked_add, not wrapping_add: silent
-                                // wraparound on SUM overflow returned wrong
-                                // (negative) totals for large i64 columns.
-                                // On overflow, promote to float accumulator.
-                                if result.has_float {
-                                    result.float_sum += v as f64;
-                                } else if let Some(s) = result.int_sum.checked_add(v) {
-                                    result.int_sum = s;
-                                } else {
-                                    result.has_float = true;
-                                    result.float_sum = result.int_sum as f64 + v as f64;
-                                }
+                                result.sum.add_int(v);
                                 if result.count == 1 {
                                     result.min_int = v;
                                     result.max_int = v;
@@ -2137,14 +2639,14 @@ impl ColSegmentStore {
                                 continue;
                             }
                             result.count += 1;
-                            result.float_sum += v;
+                            result.sum.add_float(v);
                             result.has_float = true;
                             if result.count == 1 {
                                 result.min_float = v;
                                 result.max_float = v;
                             } else {
-                                result.min_float = result.min_float.min(v);
-                                result.max_float = result.max_float.max(v);
+                                result.min_float = float_min(result.min_float, v);
+                                result.max_float = float_max(result.max_float, v);
                             }
                         }
                     } else {
@@ -2156,16 +2658,7 @@ impl ColSegmentStore {
                                 continue;
                             }
                             result.count += 1;
-                            // 🚨 checked_add + float promotion on overflow
-                            // (see matching site above for rationale).
-                            if result.has_float {
-                                result.float_sum += v as f64;
-                            } else if let Some(s) = result.int_sum.checked_add(v) {
-                                result.int_sum = s;
-                            } else {
-                                result.has_float = true;
-                                result.float_sum = result.int_sum as f64 + v as f64;
-                            }
+                            result.sum.add_int(v);
                             if result.count == 1 {
                                 result.min_int = v;
                                 result.max_int = v;
@@ -2188,10 +2681,91 @@ impl ColSegmentStore {
         result
     }
 
+    /// Combined COUNT + SUM + MIN + MAX with a text equality filter in a
+    /// SINGLE pass (`WHERE filter_col = 'filter_val'`). Skips the zone-map
+    /// and operator dispatch of [`Self::aggregate_filtered`]; the sum is a
+    /// typed [`NumericSum`], so INTEGER columns stay exact.
+    pub fn count_sum_min_max_text_filter(
+        &self,
+        filter_col: usize,
+        filter_val: &str,
+        agg_col: usize,
+    ) -> AggregateResult {
+        let _ = self.flush_buffer();
+        let agg_is_float = matches!(self.col_types.load().get(agg_col), Some(ColumnType::Float));
+        let segs = self.segments_snapshot();
+        let mut seen: Option<std::collections::HashSet<u64>> = if self.may_have_duplicate_keys() {
+            Some(std::collections::HashSet::new())
+        } else {
+            None
+        };
+        let mut result = AggregateResult::default();
+        for seg in segs.iter().rev() {
+            let n = seg.sst.num_rows;
+            if seen.is_some() {
+                let _ = seg.sst.load_full_keys();
+            }
+            let ftext = seg.read_text_cached(filter_col);
+            let fagg = seg.read_fixed_cached(agg_col);
+            let Some(tseg) = ftext.as_ref() else {
+                // Nothing here matches, but its keys still shadow older versions.
+                if let Some(ref mut s) = seen {
+                    s.extend((0..n).map(|i| seg.sst.row_map.key(i)));
+                }
+                continue;
+            };
+            for i in 0..n {
+                if let Some(ref mut s) = seen {
+                    if !s.insert(seg.sst.row_map.key(i)) {
+                        continue;
+                    }
+                }
+                if seg.sst.row_map.is_deleted(i) || !tseg.eq_bytes(i, filter_val.as_bytes()) {
+                    continue;
+                }
+                let Some(ref f) = fagg else {
+                    result.null_count += 1;
+                    continue;
+                };
+                if agg_is_float {
+                    let Some(v) = f.get_f64(i) else {
+                        result.null_count += 1;
+                        continue;
+                    };
+                    result.count += 1;
+                    result.sum.add_float(v);
+                    result.has_float = true;
+                    if result.count == 1 {
+                        result.min_float = v;
+                        result.max_float = v;
+                    } else {
+                        result.min_float = float_min(result.min_float, v);
+                        result.max_float = float_max(result.max_float, v);
+                    }
+                } else {
+                    let Some(v) = f.get_i64(i) else {
+                        result.null_count += 1;
+                        continue;
+                    };
+                    result.count += 1;
+                    result.sum.add_int(v);
+                    if result.count == 1 {
+                        result.min_int = v;
+                        result.max_int = v;
+                    } else {
+                        result.min_int = result.min_int.min(v);
+                        result.max_int = result.max_int.max(v);
+                    }
+                }
+            }
+        }
+        result
+    }
+
     pub fn count_live_rows(&self) -> usize {
         // Fast path: single segment, no buffer, no deletions → just return num_rows.
         // This covers the common case (fresh insert, no UPDATE/DELETE history).
-        let buf = self.write_buf.lock();
+        let buf = self.lock_write_buf();
         let buf_count = buf.num_rows;
         let segs = self.segments.read();
         if segs.len() == 1 && buf_count == 0 {
@@ -2214,7 +2788,7 @@ impl ColSegmentStore {
         // Slow path: multi-segment with UPDATE/DELETE history.
         // Newest-version-wins across buffer + segments.
         let mut liveness: std::collections::HashMap<u64, bool> = {
-            let buf = self.write_buf.lock();
+            let buf = self.lock_write_buf();
             buf.latest_entries().into_iter().collect()
         };
         // Newest-version-wins: iterate segments newest→oldest.
@@ -2231,165 +2805,6 @@ impl ColSegmentStore {
         liveness.values().filter(|&&deleted| !deleted).count()
     }
 
-    /// Group-by scan: iterate the group column directly (TextSegment), returning
-    /// Count + Sum with a text filter: iterate filter col (TextSegment) + sum col
-    /// (FixedSegment) directly. Returns (count, sum). Zero Vec<Value> allocation.
-    /// Optimized for SELECT COUNT(*), SUM(col) WHERE text_col = 'val'.
-    pub fn count_sum_text_filter(
-        &self,
-        filter_col: usize,
-        filter_val: &str,
-        sum_col: usize,
-    ) -> (i64, f64) {
-        let segs = self.segments_snapshot();
-        let single_seg = segs.len() <= 1;
-        let mut seen: Option<std::collections::HashSet<u64>> = if single_seg {
-            None
-        } else {
-            Some(std::collections::HashSet::new())
-        };
-        let mut count = 0i64;
-        let mut sum = 0.0f64;
-        for seg in segs.iter().rev() {
-            let n = seg.sst.num_rows;
-            let _ = seg.sst.load_full_keys();
-            let ftext = seg.sst.read_text(filter_col).ok();
-            let fsum = seg.sst.read_fixed_i64(sum_col).ok();
-            if let Some(tseg) = ftext.as_ref() {
-                for i in 0..n {
-                    let key = seg.sst.row_map.key(i);
-                    if let Some(ref mut s) = seen {
-                        if !s.insert(key) {
-                            continue;
-                        }
-                    }
-                    if seg.sst.row_map.is_deleted(i) {
-                        continue;
-                    }
-                    if tseg.get_str(i) == Some(filter_val) {
-                        count += 1;
-                        if let Some(ref f) = fsum {
-                            if let Some(v) = f.get_f64(i) {
-                                sum += v;
-                            } else if let Some(v) = f.get_i64(i) {
-                                sum += v as f64;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        (count, sum)
-    }
-
-    /// Count + Min + Max with a text filter. Returns (count, min, max).
-    pub fn count_min_max_text_filter(
-        &self,
-        filter_col: usize,
-        filter_val: &str,
-        agg_col: usize,
-    ) -> (i64, f64, f64) {
-        let segs = self.segments_snapshot();
-        let single_seg = segs.len() <= 1;
-        let mut seen: Option<std::collections::HashSet<u64>> = if single_seg {
-            None
-        } else {
-            Some(std::collections::HashSet::new())
-        };
-        let mut count = 0i64;
-        let mut min = f64::INFINITY;
-        let mut max = f64::NEG_INFINITY;
-        for seg in segs.iter().rev() {
-            let n = seg.sst.num_rows;
-            let _ = seg.sst.load_full_keys();
-            let ftext = seg.sst.read_text(filter_col).ok();
-            let fagg = seg.sst.read_fixed_i64(agg_col).ok();
-            if let Some(tseg) = ftext.as_ref() {
-                for i in 0..n {
-                    let key = seg.sst.row_map.key(i);
-                    if let Some(ref mut s) = seen {
-                        if !s.insert(key) {
-                            continue;
-                        }
-                    }
-                    if seg.sst.row_map.is_deleted(i) {
-                        continue;
-                    }
-                    if tseg.get_str(i) == Some(filter_val) {
-                        count += 1;
-                        if let Some(ref f) = fagg {
-                            let v = f
-                                .get_f64(i)
-                                .unwrap_or_else(|| f.get_i64(i).map(|i| i as f64).unwrap_or(0.0));
-                            min = min.min(v);
-                            max = max.max(v);
-                        }
-                    }
-                }
-            }
-        }
-        (count, min.max(f64::NEG_INFINITY), max.min(f64::INFINITY))
-    }
-
-    /// Combined COUNT + SUM + MIN + MAX with a text filter in a SINGLE pass.
-    /// Returns (count, sum, min, max). Replaces the old 2-scan approach
-    /// (count_min_max_text_filter + count_sum_text_filter) which doubled latency.
-    pub fn count_sum_min_max_text_filter(
-        &self,
-        filter_col: usize,
-        filter_val: &str,
-        agg_col: usize,
-    ) -> (i64, f64, f64, f64) {
-        let segs = self.segments_snapshot();
-        let single_seg = segs.len() <= 1;
-        let mut seen: Option<std::collections::HashSet<u64>> = if single_seg {
-            None
-        } else {
-            Some(std::collections::HashSet::new())
-        };
-        let mut count = 0i64;
-        let mut sum = 0.0f64;
-        let mut min = f64::INFINITY;
-        let mut max = f64::NEG_INFINITY;
-        for seg in segs.iter().rev() {
-            let n = seg.sst.num_rows;
-            let _ = seg.sst.load_full_keys();
-            let ftext = seg.sst.read_text(filter_col).ok();
-            let fagg = seg.sst.read_fixed_i64(agg_col).ok();
-            if let Some(tseg) = ftext.as_ref() {
-                for i in 0..n {
-                    let key = seg.sst.row_map.key(i);
-                    if let Some(ref mut s) = seen {
-                        if !s.insert(key) {
-                            continue;
-                        }
-                    }
-                    if seg.sst.row_map.is_deleted(i) {
-                        continue;
-                    }
-                    if tseg.get_str(i) == Some(filter_val) {
-                        count += 1;
-                        if let Some(ref f) = fagg {
-                            let v = f
-                                .get_f64(i)
-                                .unwrap_or_else(|| f.get_i64(i).map(|i| i as f64).unwrap_or(0.0));
-                            sum += v;
-                            if v < min {
-                                min = v;
-                            }
-                            if v > max {
-                                max = v;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        let min = if count == 0 { 0.0 } else { min };
-        let max = if count == 0 { 0.0 } else { max };
-        (count, sum, min, max)
-    }
-
     /// Find the row indices of the top-K rows by a single fixed (numeric)
     /// column, without materializing any Vec<Value> rows. Returns
     /// (segment_index, local_row_idx) pairs for the K rows with the largest
@@ -2474,8 +2889,20 @@ impl ColSegmentStore {
                         _ => None,
                     }
                 } else if let Some(ref t) = pre_text.get(&(seg_idx, ci)) {
-                    t.get_str(row_idx)
-                        .map(|s| Value::Text(ArcString(std::sync::Arc::from(s))))
+                    t.get_str(row_idx).map(|s| match col_types.get(ci) {
+                        Some(ct) if ct.is_text_encoded() => ct.value_from_text(s),
+                        _ => Value::Text(ArcString(std::sync::Arc::from(s))),
+                    })
+                } else if matches!(seg.sst.column_tags.get(ci), Some(ColumnTypeTag::Vector)) {
+                    let ct = col_types.get(ci).cloned().unwrap_or(ColumnType::Tensor(0));
+                    seg.sst
+                        .read_vector_at(ci, row_idx)
+                        .ok()
+                        .flatten()
+                        .map(|v| ct.value_from_vector(v))
+                } else if matches!(seg.sst.column_tags.get(ci), Some(ColumnTypeTag::Spatial)) {
+                    let ct = col_types.get(ci).cloned().unwrap_or(ColumnType::Spatial);
+                    Some(seg.get_value_at_idx(row_idx, ci, &ct))
                 } else {
                     None
                 };
@@ -2517,8 +2944,9 @@ impl ColSegmentStore {
         // for ASC a MAX-heap of the smallest K (store !bits evicts largest).
         let to_ord = |v: f64| -> u64 {
             // IEEE 754 total-order bits: flip sign bit for normal ordering, flip
-            // all bits for negative numbers.
-            let bits = v.to_bits();
+            // all bits for negative numbers. Every NaN becomes the positive
+            // quiet NaN first, so NaNs sort after every number.
+            let bits = if v.is_nan() { f64::NAN } else { v }.to_bits();
             if bits & (1u64 << 63) != 0 {
                 !bits
             } else {
@@ -2831,13 +3259,13 @@ impl ColSegmentStore {
     }
 
     pub fn buffered_row_count(&self) -> usize {
-        self.write_buf.lock().num_rows
+        self.lock_write_buf().num_rows
     }
 
     /// Estimated heap bytes consumed by the write buffer. Used to trigger
     /// memory-aware flushes so RSS doesn't grow with buffered row count.
     pub fn buffered_bytes(&self) -> usize {
-        self.write_buf.lock().buffered_bytes()
+        self.write_buf.lock().buffered_bytes() + self.staged_bytes.load(Ordering::Relaxed)
     }
 
     /// Get cached IN-hash row indices for (col_pos, set_signature).
@@ -2997,8 +3425,9 @@ impl ColSegmentStore {
     /// Ensures no data loss on crash (ACID durability).
     pub fn recover_from_disk(&self) {
         // Read MANIFEST to get active segment ids.
+        let backend = crate::storage::backend::backend_for(&self.dir);
         let manifest_path = self.dir.join("MANIFEST");
-        if !manifest_path.exists() {
+        if !backend.exists(&manifest_path) {
             return;
         }
         let manifest = match crate::storage::col_segment::manifest::Manifest::open(&manifest_path) {
@@ -3013,9 +3442,9 @@ impl ColSegmentStore {
             max_id = max_id.max(id);
         }
         // Also check files on disk (in case MANIFEST lags).
-        if let Ok(entries) = std::fs::read_dir(&self.dir) {
-            for entry in entries.flatten() {
-                if let Some(name) = entry.file_name().to_str() {
+        if let Ok(entries) = backend.list_dir(&self.dir) {
+            for entry in entries {
+                if let Some(name) = entry.file_name().and_then(|n| n.to_str()) {
                     if name.ends_with(".sst") {
                         if let Ok(id) = name.trim_end_matches(".sst").parse::<u64>() {
                             max_id = max_id.max(id);
@@ -3035,7 +3464,7 @@ impl ColSegmentStore {
         let mut loaded_ids: Vec<u64> = Vec::new();
         for &id in &state.active_segments {
             let path = self.dir.join(format!("{:010}.sst", id));
-            if path.exists() {
+            if backend.exists(&path) {
                 if let Ok(seg) = Segment::open(&path, id) {
                     segs.push_back(Arc::new(seg));
                     loaded_ids.push(id);
@@ -3045,12 +3474,13 @@ impl ColSegmentStore {
         // Clean up obsolete files (superseded by compaction but not yet GC'd).
         for &id in &state.obsolete_files {
             let path = self.dir.join(format!("{:010}.sst", id));
-            let _ = std::fs::remove_file(&path);
+            let _ = backend.remove_file(&path);
         }
 
         // Sort segments by id (creation order).
         segs.make_contiguous();
         // Already in push order (ascending id) — correct.
+        self.negative_cache.clear();
     }
 
     /// Returns a cheap `Arc<Vec<ColumnType>>` snapshot of the current column
@@ -3097,7 +3527,7 @@ impl ColSegmentStore {
     /// row_id from a previous session (which would collide with existing data).
     pub fn max_row_id(&self) -> u64 {
         let mut max = 0u64;
-        for (key, _) in self.write_buf.lock().latest_entries() {
+        for (key, _) in self.lock_write_buf().latest_entries() {
             max = max.max(key & 0xFFFFFFFF);
         }
         for seg in self.segments.read().iter() {
@@ -3270,10 +3700,7 @@ impl ColSegmentStore {
                                     continue;
                                 }
                                 let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                                while di < decoded.len() && decoded[di].0 != ek {
-                                    di += 1;
-                                }
-                                if di < decoded.len() {
+                                if decoded.get(di).is_some_and(|d| d.0 == ek) {
                                     per_row[i] = Some(decoded[di].1.clone());
                                     di += 1;
                                 }
@@ -3298,10 +3725,7 @@ impl ColSegmentStore {
                                     continue;
                                 }
                                 let ek = seg.sst.row_map.key(i) & 0xFFFFFFFF;
-                                while di < decoded.len() && decoded[di].0 != ek {
-                                    di += 1;
-                                }
-                                if di < decoded.len() {
+                                if decoded.get(di).is_some_and(|d| d.0 == ek) {
                                     per_row[i] = Some(decoded[di].1.clone());
                                     di += 1;
                                 }
@@ -3463,9 +3887,10 @@ impl ColSegmentStore {
             }
         }
         // GC: delete old files, record in manifest.
+        let backend = crate::storage::backend::backend_for(&self.dir);
         for oid in &old_ids {
             let p = self.dir.join(format!("{:010}.sst", oid));
-            let _ = std::fs::remove_file(p);
+            let _ = backend.remove_file(&p);
         }
         self.manifest.lock().record_gc(&old_ids)?;
         Ok(())
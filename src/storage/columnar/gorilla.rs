@@ -171,6 +171,27 @@ pub fn encode_timestamps(timestamps: &[i64]) -> Vec<u8> {
     writer.finish()
 }
 
+/// Encode timestamps losslessly, or return `None` if the sequence cannot
+/// round-trip through [`decode_timestamps`].
+///
+/// [`encode_timestamps`] keeps only the low 32 bits of a wide
+/// delta-of-delta; this variant rejects such inputs (and overflowing
+/// deltas) so on-disk formats can fall back to their raw layout.
+pub fn try_encode_timestamps(timestamps: &[i64]) -> Option<Vec<u8>> {
+    if timestamps.len() > 1 {
+        let mut prev_delta = timestamps[1].checked_sub(timestamps[0])?;
+        for pair in timestamps[1..].windows(2) {
+            let delta = pair[1].checked_sub(pair[0])?;
+            let dod = delta.checked_sub(prev_delta)?;
+            if i32::try_from(dod).is_err() {
+                return None;
+            }
+            prev_delta = delta;
+        }
+    }
+    Some(encode_timestamps(timestamps))
+}
+
 /// Decode timestamps from delta-of-delta compressed data.
 pub fn decode_timestamps(data: &[u8], count: usize) -> Vec<i64> {
     if count == 0 {
@@ -190,13 +211,13 @@ pub fn decode_timestamps(data: &[u8], count: usize) -> Vec<i64> {
 
     // Second value: delta
     let delta1 = read_zigzag(&mut reader);
-    result.push(first + delta1);
+    result.push(first.wrapping_add(delta1));
 
     let mut prev_delta = delta1;
     for _ in 2..count {
         let dod = decode_dod(&mut reader);
-        let delta = prev_delta + dod;
-        result.push(result.last().unwrap() + delta);
+        let delta = prev_delta.wrapping_add(dod);
+        result.push(result.last().unwrap().wrapping_add(delta));
         prev_delta = delta;
     }
 
@@ -592,6 +613,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_encode_timestamps() {
+        let timestamps: Vec<i64> = (0..500)
+            .map(|i| 1_700_000_000_000_000 + i * 1_000)
+            .collect();
+        let encoded = try_encode_timestamps(&timestamps).unwrap();
+        assert_eq!(encoded, encode_timestamps(&timestamps));
+        assert_eq!(decode_timestamps(&encoded, timestamps.len()), timestamps);
+
+        // A jump wider than 32 bits would be truncated by encode_timestamps
+        let jump = vec![0i64, 1, 2, 1 << 40, (1 << 40) + 1];
+        assert_ne!(
+            decode_timestamps(&encode_timestamps(&jump), jump.len()),
+            jump
+        );
+        assert!(try_encode_timestamps(&jump).is_none());
+        assert!(try_encode_timestamps(&[i64::MIN, i64::MAX]).is_none());
+
+        let edge = vec![i64::MIN, i64::MIN + 1, i64::MIN + 2];
+        let encoded = try_encode_timestamps(&edge).unwrap();
+        assert_eq!(decode_timestamps(&encoded, edge.len()), edge);
+    }
+
     // --- XOR Float Encoding ---
 
     #[test]
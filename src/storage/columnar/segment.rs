@@ -41,8 +41,8 @@
 //! +========================================+
 //! ```
 
+use crate::storage::backend::{self, BackendFile, FileMap, OpenFlags};
 use crate::{Result, StorageError};
-use memmap2::Mmap;
 use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
@@ -218,7 +218,7 @@ pub fn raw_bytes_compare_bytes(a: &[u8; 8], b: &[u8; 8]) -> std::cmp::Ordering {
 
 /// Writes a new columnar segment file.
 pub struct SegmentBuilder {
-    writer: BufWriter<std::fs::File>,
+    writer: BufWriter<Box<dyn BackendFile>>,
     path: PathBuf,
     table_id: u32,
     column_count: u16,
@@ -236,7 +236,8 @@ impl SegmentBuilder {
     /// Create a new segment builder. Writes to `path.tmp`, renamed on `finish()`.
     pub fn new(path: &Path, table_id: u32, column_count: u16) -> Result<Self> {
         let tmp_path = path.with_extension("mcdb.tmp");
-        let file = std::fs::File::create(&tmp_path).map_err(StorageError::Io)?;
+        let file = backend::open_file(&tmp_path, OpenFlags::create_truncate())
+            .map_err(StorageError::Io)?;
         let mut writer = BufWriter::with_capacity(64 * 1024, file);
 
         // Reserve space for header (64 bytes)
@@ -437,7 +438,9 @@ impl SegmentBuilder {
 
         // Atomic rename
         let tmp_path = self.path.with_extension("mcdb.tmp");
-        std::fs::rename(&tmp_path, &self.path).map_err(StorageError::Io)?;
+        backend::backend_for(&tmp_path)
+            .rename(&tmp_path, &self.path)
+            .map_err(StorageError::Io)?;
 
         Ok(())
     }
@@ -458,15 +461,16 @@ pub struct SegmentReader {
     /// Offset to bloom filter block (0 if none).
     bloom_block_offset: u64,
     /// mmap of the entire segment file (zero-syscall reads).
-    mmap: Option<Mmap>,
+    mmap: Option<FileMap>,
     /// Cached file handle (fallback when mmap unavailable).
-    file: Mutex<std::fs::File>,
+    file: Mutex<Box<dyn BackendFile>>,
 }
 
 impl SegmentReader {
     /// Open a segment file and read header + footer.
     pub fn open(path: &Path) -> Result<Self> {
-        let mut file = std::fs::File::open(path).map_err(StorageError::Io)?;
+        let mut file =
+            backend::open_file(path, OpenFlags::read_only()).map_err(StorageError::Io)?;
 
         // Read header
         let mut header_bytes = [0u8; HEADER_SIZE];
@@ -513,7 +517,7 @@ impl SegmentReader {
         let has_row_id_col = header.flags & FLAG_HAS_ROW_ID_COLUMN != 0;
         let total_col_offsets = column_count + if has_row_id_col { 1 } else { 0 };
 
-        let file_size = file.metadata().map_err(StorageError::Io)?.len();
+        let file_size = file.len().map_err(StorageError::Io)?;
 
         let (column_offsets, min_row_id, max_row_id, stats_block_offset, bloom_block_offset) =
             if version == SEGMENT_VERSION_V1 {
@@ -575,7 +579,7 @@ impl SegmentReader {
             };
 
         // mmap for zero-syscall column reads
-        let mmap = unsafe { Mmap::map(&file).ok() };
+        let mmap = file.map().ok();
 
         Ok(Self {
             path: path.to_path_buf(),
@@ -597,7 +601,9 @@ impl SegmentReader {
 
     /// Get lightweight metadata for pruning.
     pub fn metadata(&self) -> SegmentMetadata {
-        let file_size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        let file_size = backend::backend_for(&self.path)
+            .file_len(&self.path)
+            .unwrap_or(0);
         let has_row_id_column = self.column_offsets.len() > self.header.column_count as usize;
         let is_timestamp_sorted = self.header.flags & FLAG_TIMESTAMP_SORTED != 0;
         let has_bloom_filters = self.header.flags & FLAG_HAS_BLOOM_FILTERS != 0;
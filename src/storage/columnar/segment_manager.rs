@@ -1,6 +1,7 @@
 //! Segment manager: tracks segment files, handles pruning, column projection, and TTL GC.
 
 use super::segment::{raw_bytes_compare_bytes, ColumnBlock, SegmentMetadata, SegmentReader};
+use crate::storage::backend::{self, OpenFlags};
 use crate::storage::lsm::BloomFilter;
 use crate::types::Value;
 use crate::{Result, StorageError};
@@ -54,7 +55,8 @@ impl SegmentManager {
     /// Also recovers from interrupted merge operations by checking for a
     /// `merge_manifest.json` left behind by a crash during `replace_segments()`.
     pub fn open(directory: &Path, table_id: u32) -> Result<Self> {
-        std::fs::create_dir_all(directory).map_err(StorageError::Io)?;
+        let fs = backend::backend_for(directory);
+        fs.create_dir_all(directory).map_err(StorageError::Io)?;
 
         // Recover from interrupted merges BEFORE scanning for segments
         Self::recover_merge_manifest(directory);
@@ -62,9 +64,8 @@ impl SegmentManager {
         let mut segments = Vec::new();
 
         // Scan for existing segment files
-        if let Ok(entries) = std::fs::read_dir(directory) {
-            for entry in entries.flatten() {
-                let path = entry.path();
+        if let Ok(entries) = fs.list_dir(directory) {
+            for path in entries {
                 if path.extension().is_some_and(|e| e == "mcdb") {
                     match SegmentReader::open(&path) {
                         Ok(reader) => {
@@ -95,47 +96,48 @@ impl SegmentManager {
     /// - If the new file exists AND old files exist: delete old files (resume merge).
     /// - If the new file is missing: the merge was incomplete; just delete the manifest.
     fn recover_merge_manifest(directory: &Path) {
+        let fs = backend::backend_for(directory);
         let manifest_path = directory.join(MERGE_MANIFEST_NAME);
-        if !manifest_path.exists() {
+        if !fs.exists(&manifest_path) {
             return;
         }
 
-        let data = match std::fs::read_to_string(&manifest_path) {
+        let data = match fs.read(&manifest_path) {
             Ok(d) => d,
             Err(e) => {
                 eprintln!(
                     "[WARN] Failed to read merge manifest {:?}: {}",
                     manifest_path, e
                 );
-                let _ = std::fs::remove_file(&manifest_path);
+                let _ = fs.remove_file(&manifest_path);
                 return;
             }
         };
 
-        let manifest: MergeManifest = match serde_json::from_str(&data) {
+        let manifest: MergeManifest = match serde_json::from_slice(&data) {
             Ok(m) => m,
             Err(e) => {
                 eprintln!("[WARN] Failed to parse merge manifest: {}", e);
-                let _ = std::fs::remove_file(&manifest_path);
+                let _ = fs.remove_file(&manifest_path);
                 return;
             }
         };
 
         // Check whether the new segment file was written
-        let new_exists = manifest.new.iter().any(|p| Path::new(p).exists());
+        let new_exists = manifest.new.iter().any(|p| fs.exists(Path::new(p)));
 
         if new_exists {
             // The new segment exists — clean up old segments that were meant to be replaced
             for old_path in &manifest.old {
-                if Path::new(old_path).exists() {
-                    if let Err(e) = std::fs::remove_file(Path::new(old_path)) {
+                if fs.exists(Path::new(old_path)) {
+                    if let Err(e) = fs.remove_file(Path::new(old_path)) {
                         eprintln!("[WARN] Failed to delete old segment {:?}: {}", old_path, e);
                     }
                 }
             }
         }
         // In any case, remove the manifest — the merge is either complete or abandoned
-        let _ = std::fs::remove_file(&manifest_path);
+        let _ = fs.remove_file(&manifest_path);
     }
 
     /// Register a newly written segment file.
@@ -333,9 +335,10 @@ impl SegmentManager {
         }
 
         // Delete files (I/O outside of locks)
+        let fs = backend::backend_for(&self.directory);
         for meta in &expired {
-            if meta.path.exists() {
-                std::fs::remove_file(&meta.path).map_err(StorageError::Io)?;
+            if fs.exists(&meta.path) {
+                fs.remove_file(&meta.path).map_err(StorageError::Io)?;
             }
         }
 
@@ -354,11 +357,12 @@ impl SegmentManager {
     /// Delete all segments (used by DROP TABLE).
     pub fn delete_all(&self) -> Result<usize> {
         // Lock ordering: segments first, then reader_cache.
+        let fs = backend::backend_for(&self.directory);
         let paths: Vec<PathBuf> = {
             let mut segments = self.segments.write();
             let paths: Vec<PathBuf> = segments
                 .iter()
-                .filter(|s| s.path.exists())
+                .filter(|s| fs.exists(&s.path))
                 .map(|s| s.path.clone())
                 .collect();
             segments.clear();
@@ -369,7 +373,7 @@ impl SegmentManager {
 
         // Delete files (I/O outside of locks)
         for path in &paths {
-            let _ = std::fs::remove_file(path);
+            let _ = fs.remove_file(path);
         }
 
         Ok(paths.len())
@@ -409,6 +413,7 @@ impl SegmentManager {
         let new_meta = Arc::new(new_reader.metadata());
 
         // Step 1: Write merge manifest and fsync it for crash safety
+        let fs = backend::backend_for(&self.directory);
         let manifest_path = self.directory.join(MERGE_MANIFEST_NAME);
         let manifest = MergeManifest {
             new: vec![new_segment_path.to_string_lossy().into_owned()],
@@ -420,14 +425,16 @@ impl SegmentManager {
         {
             let json = serde_json::to_string_pretty(&manifest)
                 .map_err(|e| StorageError::Io(std::io::Error::other(e)))?;
-            let mut file = std::fs::File::create(&manifest_path).map_err(StorageError::Io)?;
+            let mut file = fs
+                .open(&manifest_path, OpenFlags::create_truncate())
+                .map_err(StorageError::Io)?;
             file.write_all(json.as_bytes()).map_err(StorageError::Io)?;
             file.sync_all().map_err(StorageError::Io)?;
         }
 
         // Collect file deletions, perform I/O outside of locks
         let paths_to_delete: Vec<PathBuf> =
-            old_paths.iter().filter(|p| p.exists()).cloned().collect();
+            old_paths.iter().filter(|p| fs.exists(p)).cloned().collect();
 
         // Step 2: Lock ordering: always segments first, then reader_cache
         {
@@ -446,11 +453,11 @@ impl SegmentManager {
 
         // Step 3: Delete old files (I/O after locks released)
         for path in &paths_to_delete {
-            let _ = std::fs::remove_file(path);
+            let _ = fs.remove_file(path);
         }
 
         // Step 4: Delete the merge manifest
-        let _ = std::fs::remove_file(&manifest_path);
+        let _ = fs.remove_file(&manifest_path);
 
         Ok(())
     }
@@ -11,6 +11,7 @@ use super::segment::{ColumnBlock, ColumnEncoding, SegmentBuilder};
 use super::segment_manager::SegmentManager;
 use super::write_buffer::{BufferedBatch, ColumnBuffer, ColumnarWriteBuffer, FlushDecision};
 use crate::catalog::TableRegistry;
+use crate::storage::backend;
 use crate::storage::columnar::gorilla;
 use crate::txn::wal::WALManager;
 use crate::types::{ColumnType, RowId, SqlRow, TableSchema, Value};
@@ -49,7 +50,9 @@ impl ColumnarStore {
         next_row_id: Arc<AtomicU64>,
         table_registry: Arc<TableRegistry>,
     ) -> Result<Self> {
-        std::fs::create_dir_all(base_dir).map_err(StorageError::Io)?;
+        backend::backend_for(base_dir)
+            .create_dir_all(base_dir)
+            .map_err(StorageError::Io)?;
 
         Ok(Self {
             base_dir: base_dir.to_path_buf(),
@@ -561,7 +564,9 @@ impl ColumnarStore {
     fn flush_batch(&self, batch: &mut BufferedBatch) -> Result<()> {
         let table_id = batch.table_id;
         let dir = self.base_dir.join(table_id.to_string());
-        std::fs::create_dir_all(&dir).map_err(StorageError::Io)?;
+        backend::backend_for(&dir)
+            .create_dir_all(&dir)
+            .map_err(StorageError::Io)?;
 
         let seg_id = self.next_segment_id.fetch_add(1, Ordering::Relaxed);
         let path = dir.join(format!("seg_{:020}.mcdb", seg_id));
@@ -1372,7 +1377,12 @@ impl ColumnarStore {
             .table_registry
             .get_table_id(table_name)
             .map_err(|_| StorageError::TableNotFound(table_name.to_string()))?;
+        self.drop_table_id(table_id)
+    }
 
+    /// Drop all data stored under `table_id`, registered or not (a table
+    /// already out of the catalog, whose drop is being finished).
+    pub fn drop_table_id(&self, table_id: u32) -> Result<usize> {
         // Remove buffer
         self.buffers.remove(&table_id);
 
@@ -1383,9 +1393,14 @@ impl ColumnarStore {
             let dir = mgr.directory().to_path_buf();
             drop(mgr);
             self.managers.remove(&table_id);
-            let _ = std::fs::remove_dir_all(&dir);
+            let _ = backend::backend_for(&dir).remove_dir_all(&dir);
             c
         } else {
+            let dir = self.base_dir.join(table_id.to_string());
+            let backend = backend::backend_for(&dir);
+            if backend.exists(&dir) {
+                backend.remove_dir_all(&dir)?;
+            }
             0
         };
 
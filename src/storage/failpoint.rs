@@ -0,0 +1,257 @@
+//! Storage failpoints (feature `failpoints`)
+//!
+//! Named hooks at fixed points of the WAL, LSM and manifest write paths
+//! (see [`POINTS`]). A test arms a point with a [`FailPoint`] and the next
+//! time the engine reaches it, it injects the configured fault:
+//! - [`FailAction::Error`]: the operation fails with an I/O error before
+//!   touching the file (at a `*.sync` point: a failed fsync)
+//! - [`FailAction::ShortWrite`]: a write point persists only a prefix of
+//!   its buffer, then fails
+//! - [`FailAction::Delay`]: the operation stalls, then proceeds normally
+//!
+//! Points are process-global, so tests that arm them should hold a
+//! [`FailScenario`] to run one at a time and start from a clean slate:
+//!
+//! ```ignore
+//! use motedb::storage::failpoint::{self, FailAction, FailPoint, FailScenario};
+//!
+//! let _scenario = FailScenario::setup();
+//! failpoint::set(failpoint::WAL_SYNC, FailPoint::new(FailAction::error()).times(1))?;
+//! assert!(db.execute("INSERT INTO t VALUES (1)").is_err());
+//! assert_eq!(failpoint::fired(failpoint::WAL_SYNC), 1);
+//! ```
+//!
+//! Without the feature the hooks compile to nothing.
+
+use std::io::{self, Write};
+
+/// Appending framed records to a WAL partition
+pub const WAL_WRITE: &str = "wal.write";
+/// fsync of a WAL partition
+pub const WAL_SYNC: &str = "wal.sync";
+/// Swapping a checkpointed WAL partition for an empty one
+pub const WAL_CHECKPOINT: &str = "wal.checkpoint";
+/// Rotating the memtable into a new SSTable (`LSMEngine::flush`)
+pub const LSM_FLUSH: &str = "lsm.flush";
+/// Starting a compaction round that has picked its input files
+pub const LSM_COMPACTION: &str = "lsm.compaction";
+/// Writing a data block, index, bloom filter or footer of an SSTable
+pub const SSTABLE_WRITE: &str = "sstable.write";
+/// fsync of a finished SSTable before it is renamed into place
+pub const SSTABLE_SYNC: &str = "sstable.sync";
+/// Appending a record to the manifest log
+pub const MANIFEST_WRITE: &str = "manifest.write";
+/// fsync of the manifest log (edit records, then the commit record)
+pub const MANIFEST_SYNC: &str = "manifest.sync";
+
+/// Every defined failpoint
+pub const POINTS: [&str; 9] = [
+    WAL_WRITE,
+    WAL_SYNC,
+    WAL_CHECKPOINT,
+    LSM_FLUSH,
+    LSM_COMPACTION,
+    SSTABLE_WRITE,
+    SSTABLE_SYNC,
+    MANIFEST_WRITE,
+    MANIFEST_SYNC,
+];
+
+#[cfg(feature = "failpoints")]
+pub use armed::*;
+
+/// Reach failpoint `name`: fails or stalls when it is armed
+#[inline]
+pub(crate) fn check(name: &'static str) -> io::Result<()> {
+    #[cfg(feature = "failpoints")]
+    armed::trigger(name, None::<(&mut io::Sink, &[u8])>)?;
+    let _ = name;
+    Ok(())
+}
+
+/// `writer.write_all(buf)` behind failpoint `name`
+#[inline]
+pub(crate) fn write_all<W: Write + ?Sized>(
+    name: &'static str,
+    writer: &mut W,
+    buf: &[u8],
+) -> io::Result<()> {
+    #[cfg(feature = "failpoints")]
+    armed::trigger(name, Some((&mut *writer, buf)))?;
+    let _ = name;
+    writer.write_all(buf)
+}
+
+#[cfg(feature = "failpoints")]
+mod armed {
+    use std::collections::BTreeMap;
+    use std::io::{self, Write};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    use parking_lot::{Mutex, MutexGuard};
+
+    use super::POINTS;
+    use crate::{Result, StorageError};
+
+    /// Fault injected when an armed point is reached
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum FailAction {
+        /// Fail with an I/O error of this kind
+        Error(io::ErrorKind),
+        /// Write the first `n` bytes of the buffer, then fail. At points
+        /// without a buffer this is a plain error.
+        ShortWrite(usize),
+        /// Sleep, then carry on
+        Delay(Duration),
+    }
+
+    impl FailAction {
+        /// A generic I/O error
+        pub fn error() -> Self {
+            FailAction::Error(io::ErrorKind::Other)
+        }
+    }
+
+    /// An armed failpoint: which action, and on which hits
+    #[derive(Debug, Clone)]
+    pub struct FailPoint {
+        action: FailAction,
+        skip: u64,
+        times: Option<u64>,
+    }
+
+    impl FailPoint {
+        /// Fire `action` on every hit
+        pub fn new(action: FailAction) -> Self {
+            Self {
+                action,
+                skip: 0,
+                times: None,
+            }
+        }
+
+        /// Let the first `hits` hits pass untouched
+        pub fn skip(mut self, hits: u64) -> Self {
+            self.skip = hits;
+            self
+        }
+
+        /// Fire at most `times` times, then pass again
+        pub fn times(mut self, times: u64) -> Self {
+            self.times = Some(times);
+            self
+        }
+    }
+
+    struct Armed {
+        point: FailPoint,
+        hits: u64,
+        fired: u64,
+    }
+
+    /// Armed points; `ACTIVE` lets unarmed builds skip the lock
+    static REGISTRY: Mutex<BTreeMap<&'static str, Armed>> = Mutex::new(BTreeMap::new());
+    static FIRED: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+    static ACTIVE: AtomicBool = AtomicBool::new(false);
+    static SCENARIO: Mutex<()> = Mutex::new(());
+
+    /// Arm failpoint `name` (one of [`POINTS`](super::POINTS)), replacing
+    /// any previous configuration
+    pub fn set(name: &str, point: FailPoint) -> Result<()> {
+        let name = POINTS.iter().find(|p| **p == name).ok_or_else(|| {
+            StorageError::InvalidArgument(format!("unknown failpoint '{}'", name))
+        })?;
+        let mut registry = REGISTRY.lock();
+        registry.insert(
+            name,
+            Armed {
+                point,
+                hits: 0,
+                fired: 0,
+            },
+        );
+        ACTIVE.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Disarm failpoint `name`
+    pub fn remove(name: &str) {
+        let mut registry = REGISTRY.lock();
+        registry.remove(name);
+        ACTIVE.store(!registry.is_empty(), Ordering::Release);
+    }
+
+    /// Disarm every failpoint and reset the [`fired`] counters
+    pub fn clear() {
+        let mut registry = REGISTRY.lock();
+        registry.clear();
+        ACTIVE.store(false, Ordering::Release);
+        FIRED.lock().clear();
+    }
+
+    /// How many times failpoint `name` has injected its fault since the
+    /// last [`clear`]
+    pub fn fired(name: &str) -> u64 {
+        FIRED.lock().get(name).copied().unwrap_or(0)
+    }
+
+    /// Serializes failpoint tests: holds a process-wide lock and starts and
+    /// ends with every point disarmed
+    pub struct FailScenario {
+        _guard: MutexGuard<'static, ()>,
+    }
+
+    impl FailScenario {
+        pub fn setup() -> Self {
+            let guard = SCENARIO.lock();
+            clear();
+            Self { _guard: guard }
+        }
+    }
+
+    impl Drop for FailScenario {
+        fn drop(&mut self) {
+            clear();
+        }
+    }
+
+    pub(super) fn trigger<W: Write + ?Sized>(
+        name: &'static str,
+        write: Option<(&mut W, &[u8])>,
+    ) -> io::Result<()> {
+        if !ACTIVE.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let action = {
+            let mut registry = REGISTRY.lock();
+            let Some(armed) = registry.get_mut(name) else {
+                return Ok(());
+            };
+            armed.hits += 1;
+            if armed.hits <= armed.point.skip
+                || armed.point.times.is_some_and(|times| armed.fired >= times)
+            {
+                return Ok(());
+            }
+            armed.fired += 1;
+            *FIRED.lock().entry(name).or_insert(0) += 1;
+            armed.point.action.clone()
+        };
+
+        let injected = |kind| io::Error::new(kind, format!("failpoint {} injected", name));
+        match action {
+            FailAction::Error(kind) => Err(injected(kind)),
+            FailAction::ShortWrite(n) => {
+                if let Some((writer, buf)) = write {
+                    writer.write_all(&buf[..n.min(buf.len())])?;
+                }
+                Err(injected(io::ErrorKind::WriteZero))
+            }
+            FailAction::Delay(duration) => {
+                std::thread::sleep(duration);
+                Ok(())
+            }
+        }
+    }
+}
@@ -13,17 +13,18 @@
 //! - Automatic cleanup when last reference is dropped
 //! - No manual file lifecycle management needed
 
+use crate::storage::backend;
 use crate::{Result, StorageError};
 use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 
 /// File reference with metadata
 struct FileRef {
-    /// File handle
-    file: Arc<File>,
+    /// File handle (unset while the file is only pinned)
+    file: OnceLock<Arc<File>>,
 
     /// Reference count (number of active handles)
     ref_count: AtomicUsize,
@@ -59,13 +60,16 @@ impl FileRefManager {
 
         let file_ref = if let Some(existing) = refs.get(&path) {
             // File already open, increment ref count
+            if existing.file.get().is_none() {
+                let _ = existing.file.set(Arc::new(File::open(&path)?));
+            }
             existing.ref_count.fetch_add(1, Ordering::SeqCst);
             existing.clone()
         } else {
             // Open new file
             let file = File::open(&path)?;
             let file_ref = Arc::new(FileRef {
-                file: Arc::new(file),
+                file: OnceLock::from(Arc::new(file)),
                 ref_count: AtomicUsize::new(1),
                 delete_pending: AtomicBool::new(false),
             });
@@ -74,13 +78,44 @@ impl FileRefManager {
         };
 
         Ok(FileHandle {
-            file: file_ref.file.clone(),
+            file: file_ref.file.get().cloned().expect("file opened above"),
             path: path.clone(),
             file_ref,
             manager: self.clone(),
         })
     }
 
+    /// Pin a file without opening it
+    ///
+    /// The file is not deleted by [`Self::delete_when_unused`] until the
+    /// returned pin (and every other reference) is dropped. Pinning a file
+    /// that does not exist succeeds; the pin then protects nothing.
+    pub fn pin<P: AsRef<Path>>(&self, path: P) -> Result<FilePin> {
+        let path = path.as_ref().to_path_buf();
+        let mut refs = self
+            .refs
+            .write()
+            .map_err(|_| StorageError::Lock("FileRefManager lock poisoned".into()))?;
+
+        let file_ref = refs
+            .entry(path.clone())
+            .or_insert_with(|| {
+                Arc::new(FileRef {
+                    file: OnceLock::new(),
+                    ref_count: AtomicUsize::new(0),
+                    delete_pending: AtomicBool::new(false),
+                })
+            })
+            .clone();
+        file_ref.ref_count.fetch_add(1, Ordering::SeqCst);
+
+        Ok(FilePin {
+            path,
+            file_ref,
+            manager: self.clone(),
+        })
+    }
+
     /// Acquire a reference to a file (alias for open)
     pub fn acquire<P: AsRef<Path>>(&self, path: P) -> Result<FileHandle> {
         self.open(path)
@@ -103,19 +138,46 @@ impl FileRefManager {
         Ok(())
     }
 
+    /// Delete a file now if nothing references it, otherwise once the last
+    /// reference is dropped
+    ///
+    /// Returns `true` if the file was deleted now. Checking for references
+    /// and deleting happen under one lock, so a concurrent `open` or `pin`
+    /// either sees the file or keeps it alive.
+    pub fn delete_when_unused<P: AsRef<Path>>(&self, path: P) -> Result<bool> {
+        let path = path.as_ref();
+        let refs = self
+            .refs
+            .write()
+            .map_err(|_| StorageError::Lock("FileRefManager lock poisoned".into()))?;
+
+        if let Some(file_ref) = refs.get(path) {
+            file_ref.delete_pending.store(true, Ordering::SeqCst);
+            return Ok(false);
+        }
+        match backend::backend_for(path).remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Close a file (decrement ref count, delete if pending)
     fn close(&self, path: &Path, file_ref: &Arc<FileRef>) {
+        // Hold the map lock across the decrement so a concurrent `open`
+        // cannot revive a file that is about to be deleted
+        let mut refs = self.refs.write().unwrap_or_else(|e| e.into_inner());
         let count = file_ref.ref_count.fetch_sub(1, Ordering::SeqCst);
 
-        // If this was the last reference and deletion is pending
-        if count == 1 && file_ref.delete_pending.load(Ordering::SeqCst) {
-            // Remove from map
-            if let Ok(mut refs) = self.refs.write() {
+        // Last reference: forget the file, and delete it if pending
+        if count == 1 {
+            if refs.get(path).is_some_and(|r| Arc::ptr_eq(r, file_ref)) {
                 refs.remove(path);
             }
-
-            // Delete file (best effort)
-            let _ = std::fs::remove_file(path);
+            if file_ref.delete_pending.load(Ordering::SeqCst) {
+                // Delete file (best effort)
+                let _ = backend::backend_for(path).remove_file(path);
+            }
         }
     }
 
@@ -182,6 +244,31 @@ impl FileHandle {
     }
 }
 
+/// Pin on a file (RAII-managed) that keeps it from being deleted
+pub struct FilePin {
+    /// File path
+    path: PathBuf,
+
+    /// File ref (for ref counting)
+    file_ref: Arc<FileRef>,
+
+    /// Manager reference
+    manager: FileRefManager,
+}
+
+impl Drop for FilePin {
+    fn drop(&mut self) {
+        self.manager.close(&self.path, &self.file_ref);
+    }
+}
+
+impl FilePin {
+    /// Get path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,4 +381,29 @@ mod tests {
         drop(handle2);
         assert_eq!(manager.ref_count(&path), 0);
     }
+
+    #[test]
+    fn test_pin_defers_delete_when_unused() {
+        let manager = FileRefManager::new();
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.into_temp_path();
+        std::fs::write(&path, b"test data").unwrap();
+
+        let pin = manager.pin(&path).unwrap();
+        let handle = manager.open(&path).unwrap();
+        assert_eq!(manager.ref_count(&path), 2);
+
+        assert!(!manager.delete_when_unused(&path).unwrap());
+        drop(pin);
+        assert!(path.exists());
+        drop(handle);
+        assert!(!path.exists());
+        assert_eq!(manager.ref_count(&path), 0);
+
+        // Unreferenced (and already missing) files are deleted right away
+        std::fs::write(&path, b"test data").unwrap();
+        assert!(manager.delete_when_unused(&path).unwrap());
+        assert!(!path.exists());
+        assert!(manager.delete_when_unused(&path).unwrap());
+    }
 }
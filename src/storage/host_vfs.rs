@@ -0,0 +1,316 @@
+//! Storage backend provided by the wasm host
+//!
+//! [`HostVfsBackend`] forwards every file operation to functions the
+//! embedding page imports under the `motedb_vfs` module, so a browser build
+//! can keep its database in the Origin Private File System (or IndexedDB, or
+//! anything else the page can read and write synchronously) instead of
+//! whatever filesystem the WASI shim emulates. `wasm/motedb-vfs.js` in the
+//! repository implements the imports on top of OPFS.
+//!
+//! ## Import contract
+//! Paths are UTF-8 byte strings, `/`-separated; the host resolves them
+//! relative to its storage root. Calls block until the operation has
+//! finished. A negative return value is an error code:
+//!
+//! | code | meaning |
+//! |------|---------|
+//! | -1 | not found |
+//! | -2 | already exists |
+//! | -3 | permission denied (e.g. the file is locked by another tab) |
+//! | -4 | unsupported |
+//! | -5 | invalid input (e.g. a file where a directory was expected) |
+//! | -6 | storage full |
+//! | other | any other I/O error |
+//!
+//! | import | returns |
+//! |--------|---------|
+//! | `vfs_open(path, len, flags) -> i32` | file handle; `flags` is a bit set of [`FLAG_READ`] .. [`FLAG_TRUNCATE`] |
+//! | `vfs_close(fd)` | |
+//! | `vfs_read_at(fd, buf, len, offset: i64) -> i64` | bytes read, 0 at end of file |
+//! | `vfs_write_at(fd, buf, len, offset: i64) -> i64` | bytes written |
+//! | `vfs_size(fd) -> i64` | file length |
+//! | `vfs_truncate(fd, size: i64) -> i32` | 0 |
+//! | `vfs_flush(fd) -> i32` | 0 once the data is durable |
+//! | `vfs_stat(path, len) -> i32` | 0 missing, 1 file, 2 directory |
+//! | `vfs_mkdir_all(path, len) -> i32` | 0 |
+//! | `vfs_rename(from, from_len, to, to_len) -> i32` | 0; replaces `to` |
+//! | `vfs_remove_file(path, len) -> i32` | 0 |
+//! | `vfs_remove_dir_all(path, len) -> i32` | 0 |
+//! | `vfs_list_dir(path, len, out, cap) -> i64` | byte length of the child names, `\n`-separated; only written to `out` when it fits in `cap` |
+//!
+//! Files have no cursor on the host side: the cursor of `Read`/`Write`/`Seek`
+//! lives here, and appends ask the host for the current length first.
+
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::backend::{BackendFile, OpenFlags, StorageBackend};
+
+/// `vfs_open` flag: readable
+pub const FLAG_READ: u32 = 1;
+/// `vfs_open` flag: writable
+pub const FLAG_WRITE: u32 = 2;
+/// `vfs_open` flag: every write goes to the end of the file
+pub const FLAG_APPEND: u32 = 4;
+/// `vfs_open` flag: create the file if it is missing
+pub const FLAG_CREATE: u32 = 8;
+/// `vfs_open` flag: truncate the file to zero length
+pub const FLAG_TRUNCATE: u32 = 16;
+
+#[link(wasm_import_module = "motedb_vfs")]
+extern "C" {
+    fn vfs_open(path: *const u8, path_len: usize, flags: u32) -> i32;
+    fn vfs_close(fd: i32);
+    fn vfs_read_at(fd: i32, buf: *mut u8, len: usize, offset: i64) -> i64;
+    fn vfs_write_at(fd: i32, buf: *const u8, len: usize, offset: i64) -> i64;
+    fn vfs_size(fd: i32) -> i64;
+    fn vfs_truncate(fd: i32, size: i64) -> i32;
+    fn vfs_flush(fd: i32) -> i32;
+    fn vfs_stat(path: *const u8, path_len: usize) -> i32;
+    fn vfs_mkdir_all(path: *const u8, path_len: usize) -> i32;
+    fn vfs_rename(from: *const u8, from_len: usize, to: *const u8, to_len: usize) -> i32;
+    fn vfs_remove_file(path: *const u8, path_len: usize) -> i32;
+    fn vfs_remove_dir_all(path: *const u8, path_len: usize) -> i32;
+    fn vfs_list_dir(path: *const u8, path_len: usize, out: *mut u8, cap: usize) -> i64;
+}
+
+/// Map a host return value to `io::Result`
+fn check(ret: i64, what: &str, path: &Path) -> io::Result<u64> {
+    if ret >= 0 {
+        return Ok(ret as u64);
+    }
+    let kind = match ret {
+        -1 => io::ErrorKind::NotFound,
+        -2 => io::ErrorKind::AlreadyExists,
+        -3 => io::ErrorKind::PermissionDenied,
+        -4 => io::ErrorKind::Unsupported,
+        -5 => io::ErrorKind::InvalidInput,
+        -6 => io::ErrorKind::StorageFull,
+        _ => io::ErrorKind::Other,
+    };
+    Err(io::Error::new(
+        kind,
+        format!("{} {}: host error {}", what, path.display(), ret),
+    ))
+}
+
+fn path_bytes(path: &Path) -> io::Result<&[u8]> {
+    path.to_str().map(str::as_bytes).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}: path is not UTF-8", path.display()),
+        )
+    })
+}
+
+/// Backend whose files live wherever the embedding page keeps them (see the
+/// module docs for the imports it needs)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HostVfsBackend;
+
+impl HostVfsBackend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn stat(&self, path: &Path) -> i32 {
+        match path_bytes(path) {
+            Ok(p) => unsafe { vfs_stat(p.as_ptr(), p.len()) },
+            Err(_) => 0,
+        }
+    }
+
+    fn path_call(
+        &self,
+        path: &Path,
+        what: &str,
+        call: unsafe extern "C" fn(*const u8, usize) -> i32,
+    ) -> io::Result<()> {
+        let p = path_bytes(path)?;
+        check(unsafe { call(p.as_ptr(), p.len()) } as i64, what, path).map(|_| ())
+    }
+}
+
+impl StorageBackend for HostVfsBackend {
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+        let p = path_bytes(path)?;
+        let mut bits = 0;
+        for (set, bit) in [
+            (flags.read, FLAG_READ),
+            (flags.write, FLAG_WRITE),
+            (flags.append, FLAG_APPEND),
+            (flags.create, FLAG_CREATE),
+            (flags.truncate, FLAG_TRUNCATE),
+        ] {
+            if set {
+                bits |= bit;
+            }
+        }
+        let fd = check(
+            unsafe { vfs_open(p.as_ptr(), p.len(), bits) } as i64,
+            "open",
+            path,
+        )?;
+        Ok(Box::new(HostFile {
+            fd: fd as i32,
+            path: path.to_path_buf(),
+            pos: 0,
+            append: flags.append,
+        }))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.path_call(path, "mkdir", vfs_mkdir_all)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (f, t) = (path_bytes(from)?, path_bytes(to)?);
+        let ret = unsafe { vfs_rename(f.as_ptr(), f.len(), t.as_ptr(), t.len()) };
+        check(ret as i64, "rename", from).map(|_| ())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.path_call(path, "remove", vfs_remove_file)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.stat(path) > 0
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.stat(path) == 2
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let p = path_bytes(path)?;
+        let mut buf = vec![0u8; 4096];
+        loop {
+            let ret = unsafe { vfs_list_dir(p.as_ptr(), p.len(), buf.as_mut_ptr(), buf.len()) };
+            let len = check(ret, "list", path)? as usize;
+            if len > buf.len() {
+                // The listing may have grown in between; ask again
+                buf.resize(len, 0);
+                continue;
+            }
+            let names = std::str::from_utf8(&buf[..len])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let mut out: Vec<PathBuf> = names
+                .split('\n')
+                .filter(|name| !name.is_empty())
+                .map(|name| path.join(name))
+                .collect();
+            out.sort();
+            return Ok(out);
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.path_call(path, "remove", vfs_remove_dir_all)
+    }
+}
+
+/// An open host file; the cursor is kept on this side
+struct HostFile {
+    fd: i32,
+    path: PathBuf,
+    pos: u64,
+    append: bool,
+}
+
+impl HostFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let ret = unsafe { vfs_read_at(self.fd, buf.as_mut_ptr(), buf.len(), offset as i64) };
+        check(ret, "read", &self.path).map(|n| n as usize)
+    }
+}
+
+impl fmt::Debug for HostFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostFile")
+            .field("fd", &self.fd)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Drop for HostFile {
+    fn drop(&mut self) {
+        unsafe { vfs_close(self.fd) }
+    }
+}
+
+impl Read for HostFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for HostFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.append {
+            self.pos = self.len()?;
+        }
+        let ret = unsafe { vfs_write_at(self.fd, buf.as_ptr(), buf.len(), self.pos as i64) };
+        let n = check(ret, "write", &self.path)?;
+        self.pos += n;
+        Ok(n as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for HostFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(p) => p as i64,
+            SeekFrom::End(off) => self.len()? as i64 + off,
+            SeekFrom::Current(off) => self.pos as i64 + off,
+        };
+        if new_pos < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before start of file",
+            ));
+        }
+        self.pos = new_pos as u64;
+        Ok(self.pos)
+    }
+}
+
+impl BackendFile for HostFile {
+    fn sync_all(&self) -> io::Result<()> {
+        check(unsafe { vfs_flush(self.fd) } as i64, "flush", &self.path).map(|_| ())
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        check(unsafe { vfs_size(self.fd) }, "size", &self.path)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        let ret = unsafe { vfs_truncate(self.fd, size as i64) };
+        check(ret as i64, "truncate", &self.path).map(|_| ())
+    }
+
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(buf, offset)? {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                n => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -18,8 +18,9 @@
 //! ```
 
 use super::BlobRef;
+use crate::storage::backend::{self, BackendFile, OpenFlags};
+use crate::storage::platform::zstd;
 use crate::{Result, StorageError};
-use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
@@ -54,7 +55,7 @@ pub struct BlobStore {
 /// Single blob file (immutable after close)
 struct BlobFile {
     file_id: u32,
-    writer: BufWriter<File>, // 🚀 使用 BufWriter 减少系统调用
+    writer: BufWriter<Box<dyn BackendFile>>, // 🚀 使用 BufWriter 减少系统调用
     offset: u64,
 }
 
@@ -62,7 +63,7 @@ impl BlobStore {
     /// Create new blob store
     pub fn new<P: AsRef<Path>>(dir: P, max_file_size: usize) -> Result<Self> {
         let dir = dir.as_ref().to_path_buf();
-        std::fs::create_dir_all(&dir)?;
+        backend::backend_for(&dir).create_dir_all(&dir)?;
 
         // Crash recovery: validate and truncate the last blob file to the
         // last valid entry. This handles partial writes from crashes.
@@ -113,13 +114,25 @@ impl BlobStore {
     /// Read blob data by reference (supports V1 and V2 formats)
     pub fn get(&self, blob_ref: &BlobRef) -> Result<Vec<u8>> {
         let path = self.blob_file_path(blob_ref.file_id);
-        let mut file = File::open(&path)?;
+        let mut file = backend::open_file(&path, OpenFlags::read_only())?;
 
-        // Read version from header to determine format
-        file.seek(SeekFrom::Start(4))?;
-        let mut ver_buf = [0u8; 4];
-        file.read_exact(&mut ver_buf)?;
-        let version = u32::from_le_bytes(ver_buf);
+        // Validate the header and read the version to determine format
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)?;
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        if magic != BLOB_MAGIC {
+            return Err(StorageError::InvalidData(format!(
+                "Bad blob file magic: 0x{:08X}",
+                magic
+            )));
+        }
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if version == 0 || version > BLOB_VERSION_V2 {
+            return Err(StorageError::InvalidData(format!(
+                "Unsupported blob file version: {}",
+                version
+            )));
+        }
 
         // Seek to blob entry offset
         file.seek(SeekFrom::Start(blob_ref.offset))?;
@@ -200,9 +213,9 @@ impl BlobStore {
     fn find_next_file_id(dir: &Path) -> Result<u32> {
         let mut max_id = 0u32;
 
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                if let Some(name) = entry.file_name().to_str() {
+        if let Ok(entries) = backend::backend_for(dir).list_dir(dir) {
+            for entry in entries {
+                if let Some(name) = entry.file_name().and_then(|n| n.to_str()) {
                     if name.ends_with(".blob") {
                         if let Some(id_str) = name.strip_suffix(".blob") {
                             if let Ok(id) = id_str.parse::<u32>() {
@@ -250,11 +263,12 @@ impl BlobStore {
         }
 
         let path = dir.join(format!("{:08}.blob", last_file_id));
-        if !path.exists() {
+        let backend = backend::backend_for(&path);
+        if !backend.exists(&path) {
             return Ok(());
         }
 
-        let mut file = match File::open(&path) {
+        let mut file = match backend.open(&path, OpenFlags::read_only()) {
             Ok(f) => f,
             Err(_) => return Ok(()),
         };
@@ -262,7 +276,7 @@ impl BlobStore {
         // Read header to determine version
         let mut header = [0u8; 8];
         if file.read_exact(&mut header).is_err() {
-            let _ = std::fs::remove_file(&path);
+            let _ = backend.remove_file(&path);
             return Ok(());
         }
         let version = u32::from_le_bytes(header[4..8].try_into().unwrap_or([0, 0, 0, 0]));
@@ -375,10 +389,10 @@ impl BlobStore {
         }
 
         // Truncate file to valid_offset if it was longer
-        let file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let file_size = file.len().unwrap_or(0);
         if valid_offset < file_size {
             drop(file);
-            let file = OpenOptions::new().write(true).open(&path)?;
+            let file = backend.open(&path, OpenFlags::read_write())?;
             file.set_len(valid_offset)?;
             debug_log!(
                 "[BlobStore] Recovered blob file {}: truncated from {} to {} bytes",
@@ -410,9 +424,9 @@ impl BlobStore {
         drop(state);
 
         let mut deleted = 0;
-        if let Ok(entries) = std::fs::read_dir(&self.dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
+        let backend = backend::backend_for(&self.dir);
+        if let Ok(entries) = backend.list_dir(&self.dir) {
+            for path in entries {
                 let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
                 if let Ok(file_id) = u32::from_str_radix(name, 10) {
                     // Never delete the current active file
@@ -422,7 +436,7 @@ impl BlobStore {
                     // Check if any live reference points to this file
                     let has_ref = live_blob_refs.iter().any(|(fid, _)| *fid == file_id);
                     if !has_ref {
-                        if let Err(e) = std::fs::remove_file(&path) {
+                        if let Err(e) = backend.remove_file(&path) {
                             warn_log!("[BlobStore::gc] Failed to delete {}: {}", path.display(), e);
                         } else {
                             deleted += 1;
@@ -438,11 +452,7 @@ impl BlobStore {
 impl BlobFile {
     fn create(dir: &Path, file_id: u32) -> Result<Self> {
         let path = dir.join(format!("{:08}.blob", file_id));
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)?;
+        let mut file = backend::open_file(&path, OpenFlags::create_truncate())?;
 
         // Write header: V2 with compression support
         file.write_all(&BLOB_MAGIC.to_le_bytes())?;
@@ -460,7 +470,7 @@ impl BlobFile {
     /// Flush buffered data to disk
     fn flush(&mut self) -> Result<()> {
         self.writer.flush()?;
-        self.writer.get_mut().sync_data()?;
+        self.writer.get_mut().sync_all()?;
         Ok(())
     }
 
@@ -509,7 +519,7 @@ impl BlobFile {
 
         // Flush and sync to disk (guarantee persistence)
         self.writer.flush()?;
-        self.writer.get_mut().sync_data()?;
+        self.writer.get_mut().sync_all()?;
 
         // Update offset: 4 (orig_size) + 1 (flag) + 4 (data_len) + data + 4 (crc)
         self.offset += 4 + 1 + 4 + data_len as u64 + 4;
@@ -541,6 +551,27 @@ mod tests {
         assert_eq!(data, retrieved);
     }
 
+    #[test]
+    fn test_blob_header_is_validated() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BlobStore::new(temp_dir.path(), 1024 * 1024).unwrap();
+        let blob_ref = store.put(b"header check").unwrap();
+        store.flush().unwrap();
+
+        let path = store.blob_file_path(blob_ref.file_id);
+        let mut bytes = std::fs::read(&path).unwrap();
+        // Header is little-endian on every architecture
+        assert_eq!(&bytes[..8], &[0x42, 0x4F, 0x4C, 0x42, 2, 0, 0, 0]);
+
+        bytes[4..8].copy_from_slice(&(BLOB_VERSION_V2 + 1).to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(store.get(&blob_ref).is_err());
+
+        bytes[..4].copy_from_slice(&BLOB_MAGIC.to_be_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(store.get(&blob_ref).is_err());
+    }
+
     #[test]
     fn test_large_blob() {
         let temp_dir = TempDir::new().unwrap();
@@ -41,6 +41,10 @@
 //!   timestamps: u64 × num_rows
 //!   deleted: u8 × ceil(num_rows/8)
 //!
+//! [Zone Map]  (present when header flag bit 0 is set; see [`ZoneMap`])
+//!   ranges: (kind: u8, min: 8B, max: 8B) × num_blocks × num_columns
+//!   block_rows: u32
+//!
 //! [Footer: 16 bytes]
 //!   column_index_offset: u64
 //!   row_map_offset: u64
@@ -56,6 +60,12 @@
 //! [data: T × num_rows]  (T = i64, f64, u8, i64)
 //! ```
 //!
+//! **Timestamp, delta-of-delta encoded (segment flag 2):**
+//! ```text
+//! [null_bitmap: u8 × ceil(num_rows/8)]
+//! [gorilla delta-of-delta bits]  (decoded back to i64 × num_rows on read)
+//! ```
+//!
 //! **Variable-width (Text):**
 //! ```text
 //! [null_bitmap: u8 × ceil(num_rows/8)]
@@ -69,10 +79,18 @@
 //! [dim: u16] [stride: u16]
 //! [data: f32 × num_rows × stride]
 //! ```
+//!
+//! **Vector with a compact encoding (VECTOR(n, f16|int8)):**
+//! ```text
+//! [null_bitmap: u8 × ceil(num_rows/8)]
+//! [0: u16] [dim: u16] [encoding: u8]
+//! [data: encoded_len(dim) × num_rows]  (f16 × dim, or f32 scale + i8 × dim)
+//! ```
 
-use crate::types::{ColumnType, RowId, Value};
+use crate::storage::backend::{self, BackendFile, OpenFlags};
+use crate::types::{ColumnType, RowId, Value, VectorEncoding};
 use crate::{Result, StorageError};
-use std::fs::{File, OpenOptions};
+use std::borrow::Cow;
 use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -84,13 +102,27 @@ use memmap2::{Mmap, MmapOptions};
 
 const COLUMNAR_MAGIC: u32 = 0x434D5442; // "BTMC"
 const COLUMNAR_VERSION: u32 = 2; // v2: MAX_COLUMNS 16 → 128, header grew to 144 bytes
-const HEADER_SIZE: usize = 144; // 14 (fixed prefix) + 128 (column_tags) + 2 (reserved)
+const HEADER_SIZE: usize = 144; // 14 (fixed prefix) + 128 (column_tags) + 1 (flags) + 1 (reserved)
 const FOOTER_SIZE: usize = 20;
 /// Maximum number of columns supported by the columnar SSTable format.
 /// The on-disk header reserves a fixed-width slot per column, so this is a
 /// hard format limit. CREATE TABLE rejects tables exceeding it.
 pub const MAX_COLUMNS: usize = 128;
 
+/// Segment flag byte: payload stored as-is
+const SEG_FLAG_RAW: u8 = 0;
+/// Segment flag byte: payload is Snappy compressed
+const SEG_FLAG_SNAPPY: u8 = 1;
+/// Segment flag byte: Timestamp payload with delta-of-delta encoded values
+const SEG_FLAG_DELTA: u8 = 2;
+
+/// Header flag (byte 142): a zone map precedes the footer
+const HEADER_FLAG_ZONE_MAP: u8 = 1;
+/// Zone map entry: kind byte + min + max
+const ZONE_ENTRY_SIZE: usize = 17;
+/// Rows per zone-map block
+const ZONE_BLOCK_ROWS: usize = 4096;
+
 /// Column type tags for the columnar format (compact u8 representation).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -105,14 +137,36 @@ pub enum ColumnTypeTag {
 }
 
 impl ColumnTypeTag {
+    fn from_u8(tag: u8) -> Option<Self> {
+        Some(match tag {
+            0 => Self::Integer,
+            1 => Self::Float,
+            2 => Self::Bool,
+            3 => Self::Timestamp,
+            4 => Self::Text,
+            5 => Self::Vector,
+            6 => Self::Spatial,
+            _ => return None,
+        })
+    }
+
     fn from_column_type(ct: &ColumnType) -> Self {
         match ct {
             ColumnType::Integer => Self::Integer,
             ColumnType::Float => Self::Float,
             ColumnType::Boolean => Self::Bool,
             ColumnType::Timestamp => Self::Timestamp,
-            ColumnType::Text => Self::Text,
-            ColumnType::Tensor(_) => Self::Vector,
+            // DECIMAL/UUID/DATE/TIME/ARRAY 以规范化字符串存储，ENUM 存标签序号，读取时按 schema 还原
+            ColumnType::Text
+            | ColumnType::Decimal { .. }
+            | ColumnType::Uuid
+            | ColumnType::Date
+            | ColumnType::Time
+            | ColumnType::Array(_)
+            | ColumnType::Enum(_) => Self::Text,
+            ColumnType::Tensor(_)
+            | ColumnType::ShapedTensor(_)
+            | ColumnType::EncodedVector { .. } => Self::Vector,
             ColumnType::Spatial => Self::Spatial,
         }
     }
@@ -153,6 +207,7 @@ pub(crate) struct ColumnarHeader {
     num_rows: u32,
     num_columns: u16,
     column_tags: [u8; MAX_COLUMNS],
+    flags: u8,
 }
 
 impl ColumnarHeader {
@@ -163,7 +218,8 @@ impl ColumnarHeader {
         buf[8..12].copy_from_slice(&self.num_rows.to_le_bytes());
         buf[12..14].copy_from_slice(&self.num_columns.to_le_bytes());
         buf[14..14 + MAX_COLUMNS].copy_from_slice(&self.column_tags);
-        // bytes 142-143: reserved
+        buf[14 + MAX_COLUMNS] = self.flags;
+        // byte 143: reserved
         buf
     }
 
@@ -189,12 +245,28 @@ impl ColumnarHeader {
         }
         let num_rows = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
         let num_columns = u16::from_le_bytes([data[12], data[13]]);
+        if num_columns as usize > MAX_COLUMNS {
+            return Err(StorageError::InvalidData(format!(
+                "Columnar column count {} exceeds {}",
+                num_columns, MAX_COLUMNS
+            )));
+        }
         let mut column_tags = [0u8; MAX_COLUMNS];
         column_tags.copy_from_slice(&data[14..14 + MAX_COLUMNS]);
+        if let Some(&tag) = column_tags[..num_columns as usize]
+            .iter()
+            .find(|&&tag| ColumnTypeTag::from_u8(tag).is_none())
+        {
+            return Err(StorageError::InvalidData(format!(
+                "Unknown columnar column tag: {}",
+                tag
+            )));
+        }
         Ok(Self {
             num_rows,
             num_columns,
             column_tags,
+            flags: data[14 + MAX_COLUMNS],
         })
     }
 }
@@ -209,6 +281,227 @@ pub struct ColumnIndexEntry {
 
 const COLUMN_INDEX_ENTRY_SIZE: usize = 16; // (offset: u64, size: u64)
 
+// ── Zone Map ───────────────────────────────────────────────────────
+
+/// Value range of one numeric column within a block of rows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoneRange {
+    /// Every row is NULL
+    AllNull,
+    /// Integer / Timestamp (micros) column
+    Int { min: i64, max: i64 },
+    /// Float column without NaNs
+    Float { min: f64, max: f64 },
+}
+
+/// Largest magnitude an i64 can have and still round-trip through f64
+const F64_EXACT_INT: i64 = 1 << 53;
+
+impl ZoneRange {
+    /// Compute the range of rows `rows` of a fixed-width column buffer
+    /// (`elem: 8B × num_rows`). `None` for non-numeric columns and Float
+    /// columns containing NaN.
+    fn compute(
+        tag: ColumnTypeTag,
+        raw: &[u8],
+        nulls: &[bool],
+        rows: std::ops::Range<usize>,
+    ) -> Option<Self> {
+        let values = raw
+            .get(rows.start * 8..rows.end * 8)?
+            .chunks_exact(8)
+            .zip(rows)
+            .filter(|(_, i)| nulls.get(*i) != Some(&true))
+            .map(|(b, _)| <[u8; 8]>::try_from(b).unwrap());
+        match tag {
+            ColumnTypeTag::Integer | ColumnTypeTag::Timestamp => Some(
+                values
+                    .map(i64::from_le_bytes)
+                    .fold(Self::AllNull, |range, v| match range {
+                        Self::Int { min, max } => Self::Int {
+                            min: min.min(v),
+                            max: max.max(v),
+                        },
+                        _ => Self::Int { min: v, max: v },
+                    }),
+            ),
+            ColumnTypeTag::Float => {
+                let mut range = Self::AllNull;
+                for v in values.map(f64::from_le_bytes) {
+                    if v.is_nan() {
+                        return None;
+                    }
+                    range = match range {
+                        Self::Float { min, max } => Self::Float {
+                            min: min.min(v),
+                            max: max.max(v),
+                        },
+                        _ => Self::Float { min: v, max: v },
+                    };
+                }
+                Some(range)
+            }
+            _ => None,
+        }
+    }
+
+    fn encode(range: Option<&Self>) -> [u8; ZONE_ENTRY_SIZE] {
+        let mut buf = [0u8; ZONE_ENTRY_SIZE];
+        let (kind, min, max) = match range {
+            None => (0, [0; 8], [0; 8]),
+            Some(Self::AllNull) => (1, [0; 8], [0; 8]),
+            Some(Self::Int { min, max }) => (2, min.to_le_bytes(), max.to_le_bytes()),
+            Some(Self::Float { min, max }) => (3, min.to_le_bytes(), max.to_le_bytes()),
+        };
+        buf[0] = kind;
+        buf[1..9].copy_from_slice(&min);
+        buf[9..17].copy_from_slice(&max);
+        buf
+    }
+
+    fn decode(entry: &[u8]) -> Option<Self> {
+        let min: [u8; 8] = entry.get(1..9)?.try_into().ok()?;
+        let max: [u8; 8] = entry.get(9..17)?.try_into().ok()?;
+        match entry[0] {
+            1 => Some(Self::AllNull),
+            2 => Some(Self::Int {
+                min: i64::from_le_bytes(min),
+                max: i64::from_le_bytes(max),
+            }),
+            3 => Some(Self::Float {
+                min: f64::from_le_bytes(min),
+                max: f64::from_le_bytes(max),
+            }),
+            _ => None,
+        }
+    }
+
+    /// `false` only if no row in the range can satisfy `col <op> target`.
+    /// NULLs never satisfy a comparison; `!=` is only ruled out for all-NULL
+    /// segments, and operators and targets this can't reason about always
+    /// return `true`.
+    pub fn may_match(&self, op: &crate::sql::ast::BinaryOperator, target: &Value) -> bool {
+        use crate::sql::ast::BinaryOperator;
+        use std::cmp::Ordering;
+        if !matches!(
+            op,
+            BinaryOperator::Eq
+                | BinaryOperator::Ne
+                | BinaryOperator::Lt
+                | BinaryOperator::Le
+                | BinaryOperator::Gt
+                | BinaryOperator::Ge
+        ) {
+            return true;
+        }
+        // Order of `min` and `max` relative to the target
+        let (lo, hi) = match (self, target) {
+            (Self::AllNull, _) => return false,
+            (Self::Int { min, max }, Value::Integer(t)) => (min.cmp(t), max.cmp(t)),
+            (Self::Int { min, max }, Value::Timestamp(t)) => {
+                (min.cmp(&t.as_micros()), max.cmp(&t.as_micros()))
+            }
+            (Self::Int { min, max }, Value::Float(t))
+                if min.abs() < F64_EXACT_INT && max.abs() < F64_EXACT_INT =>
+            {
+                match ((*min as f64).partial_cmp(t), (*max as f64).partial_cmp(t)) {
+                    (Some(lo), Some(hi)) => (lo, hi),
+                    _ => return true,
+                }
+            }
+            (Self::Float { min, max }, Value::Float(t)) => {
+                match (min.partial_cmp(t), max.partial_cmp(t)) {
+                    (Some(lo), Some(hi)) => (lo, hi),
+                    _ => return true,
+                }
+            }
+            (Self::Float { min, max }, Value::Integer(t)) if t.abs() < F64_EXACT_INT => {
+                let t = *t as f64;
+                match (min.partial_cmp(&t), max.partial_cmp(&t)) {
+                    (Some(lo), Some(hi)) => (lo, hi),
+                    _ => return true,
+                }
+            }
+            _ => return true,
+        };
+        match op {
+            BinaryOperator::Eq => lo != Ordering::Greater && hi != Ordering::Less,
+            BinaryOperator::Lt => lo == Ordering::Less,
+            BinaryOperator::Le => lo != Ordering::Greater,
+            BinaryOperator::Gt => hi == Ordering::Greater,
+            BinaryOperator::Ge => hi != Ordering::Less,
+            _ => true,
+        }
+    }
+}
+
+/// Per-block value ranges of an SSTable's numeric columns, recorded at write
+/// time so range predicates on non-indexed columns can skip whole segments or
+/// blocks of rows during scans without an index.
+#[derive(Clone, Debug)]
+pub struct ZoneMap {
+    block_rows: usize,
+    num_blocks: usize,
+    /// Column-major: `ranges[col * num_blocks + block]`
+    ranges: Vec<Option<ZoneRange>>,
+}
+
+impl ZoneMap {
+    fn num_blocks(num_rows: usize, block_rows: usize) -> usize {
+        num_rows.div_ceil(block_rows.max(1))
+    }
+
+    fn encoded_len(num_rows: usize, num_columns: usize, block_rows: usize) -> usize {
+        Self::num_blocks(num_rows, block_rows) * num_columns * ZONE_ENTRY_SIZE + 4
+    }
+
+    fn decode(data: &[u8], num_rows: usize, num_columns: usize) -> Option<Self> {
+        let (ranges, block_rows) = data.split_at(data.len().checked_sub(4)?);
+        let block_rows = u32::from_le_bytes(block_rows.try_into().ok()?) as usize;
+        let num_blocks = Self::num_blocks(num_rows, block_rows);
+        if block_rows == 0 || ranges.len() != num_blocks * num_columns * ZONE_ENTRY_SIZE {
+            return None;
+        }
+        Some(Self {
+            block_rows,
+            num_blocks,
+            ranges: ranges
+                .chunks_exact(ZONE_ENTRY_SIZE)
+                .map(ZoneRange::decode)
+                .collect(),
+        })
+    }
+
+    /// Rows per block; row `i` belongs to block `i / block_rows()`
+    pub fn block_rows(&self) -> usize {
+        self.block_rows
+    }
+
+    /// Which blocks may hold a row satisfying `column <op> target`. `None` when
+    /// the column has no recorded ranges (non-numeric, or added by a later
+    /// ALTER), so every row must be checked.
+    pub fn matching_blocks(
+        &self,
+        col_idx: usize,
+        op: &crate::sql::ast::BinaryOperator,
+        target: &Value,
+    ) -> Option<Vec<bool>> {
+        let ranges = self
+            .ranges
+            .get(col_idx * self.num_blocks..(col_idx + 1) * self.num_blocks)?;
+        if ranges.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(
+            ranges
+                .iter()
+                .flatten()
+                .map(|range| range.may_match(op, target))
+                .collect(),
+        )
+    }
+}
+
 // ── Row Map ────────────────────────────────────────────────────────
 
 /// Row Map: MVCC metadata for each row in columnar order.
@@ -680,28 +973,25 @@ impl FixedSegment {
         self.data.as_bytes()
     }
 
-    /// Returns the raw data bytes as a typed i64 slice (zero-copy).
+    /// Returns the raw data bytes as a typed i64 slice (zero-copy on
+    /// little-endian targets when the data is aligned).
     /// Used by aggregate scans to avoid per-row get_i64() overhead.
     #[inline]
-    pub fn raw_i64_slice(&self) -> &[i64] {
+    pub fn raw_i64_slice(&self) -> Cow<'_, [i64]> {
         debug_assert!(self.elem_size == 8, "raw_i64_slice on non-8-byte column");
-        let bytes = self.data.as_bytes();
-        // SAFETY: i64 is #[repr(C)] and the data is num_rows*8 bytes aligned
-        // within a Vec<u8>. The builder writes i64 in little-endian, which
-        // matches the platform native representation.
-        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const i64, bytes.len() / 8) }
+        le_i64s(self.data.as_bytes())
     }
 
-    /// Returns the raw data bytes as a typed f64 slice (zero-copy).
+    /// Returns the raw data bytes as a typed f64 slice (zero-copy on
+    /// little-endian targets when the data is aligned).
     /// Enables auto-vectorization in float aggregate loops.
     #[inline]
-    pub fn raw_f64_typed_slice(&self) -> &[f64] {
+    pub fn raw_f64_typed_slice(&self) -> Cow<'_, [f64]> {
         debug_assert!(
             self.elem_size == 8,
             "raw_f64_typed_slice on non-8-byte column"
         );
-        let bytes = self.data.as_bytes();
-        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const f64, bytes.len() / 8) }
+        le_f64s(self.data.as_bytes())
     }
 
     /// Returns the null bitmap as raw bytes for batch null-checking.
@@ -1423,13 +1713,15 @@ pub struct ColumnarSSTable {
     pub(crate) file_data: Vec<u8>,
     #[allow(dead_code)]
     mmap: Option<Arc<Mmap>>,
-    file: Option<parking_lot::Mutex<File>>,
+    file: Option<parking_lot::Mutex<Box<dyn BackendFile>>>,
     #[allow(dead_code)]
     header: ColumnarHeader,
     pub column_index: Vec<ColumnIndexEntry>,
     pub row_map: RowMap,
     pub column_tags: Vec<ColumnTypeTag>,
     pub num_rows: usize,
+    /// Block value ranges; `None` for files written before zone maps
+    pub zone_map: Option<ZoneMap>,
     /// LRU cache for key blocks (used by find_row_by_key). Each entry is a
     /// ~16KB block of keys. Caching the last 4 blocks covers 8K rows — enough
     /// for sequential PK scans. Total memory: 4 × 16KB = 64KB (FIXED).
@@ -1477,6 +1769,7 @@ impl ColumnarSSTable {
     /// Release mmap pages from RSS (MADV_DONTNEED). Pages are re-faulted
     /// on next access. No-op for heap-backed segments.
     pub fn release_pages(&self) {
+        #[cfg(unix)]
         if let Some(ref m) = self.mmap {
             unsafe {
                 libc::madvise(m.as_ptr() as *mut _, m.len(), libc::MADV_DONTNEED);
@@ -1487,9 +1780,8 @@ impl ColumnarSSTable {
     /// Check if a file is a columnar SSTable by reading its magic.
     pub fn is_columnar<P: AsRef<Path>>(path: P) -> bool {
         let path = path.as_ref();
-        if let Ok(mut file) = OpenOptions::new().read(true).open(path) {
-            if let Ok(metadata) = file.metadata() {
-                let file_len = metadata.len();
+        if let Ok(mut file) = backend::open_file(path, OpenFlags::read_only()) {
+            if let Ok(file_len) = file.len() {
                 if file_len >= FOOTER_SIZE as u64
                     && file.seek(SeekFrom::End(-(FOOTER_SIZE as i64))).is_ok()
                 {
@@ -1508,8 +1800,8 @@ impl ColumnarSSTable {
     /// Open a columnar SSTable file.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
-        let mut file = OpenOptions::new().read(true).open(&path)?;
-        let file_len = file.metadata()?.len();
+        let mut file = backend::open_file(&path, OpenFlags::read_only())?;
+        let file_len = file.len()?;
 
         // Read footer
         if file_len < FOOTER_SIZE as u64 {
@@ -1702,12 +1994,41 @@ impl ColumnarSSTable {
 
         let column_tags: Vec<ColumnTypeTag> = header.column_tags[..num_columns]
             .iter()
-            .map(|&t| unsafe { std::mem::transmute(t) })
+            .map(|&t| ColumnTypeTag::from_u8(t).expect("validated by ColumnarHeader::deserialize"))
             .collect();
 
+        // Zone map: right before the footer, ending in its block size.
+        let zone_map = if header.flags & HEADER_FLAG_ZONE_MAP != 0 {
+            let mut block_rows = [0u8; 4];
+            let block_rows_at = file_len
+                .checked_sub((FOOTER_SIZE + 4) as u64)
+                .ok_or_else(|| StorageError::InvalidData("Truncated columnar zone map".into()))?;
+            file.seek(SeekFrom::Start(block_rows_at))?;
+            file.read_exact(&mut block_rows)?;
+            let zone_size = ZoneMap::encoded_len(
+                num_rows,
+                num_columns,
+                u32::from_le_bytes(block_rows) as usize,
+            );
+            let zone_start = (file_len as usize)
+                .checked_sub(FOOTER_SIZE + zone_size)
+                .ok_or_else(|| StorageError::InvalidData("Truncated columnar zone map".into()))?;
+            let zone_buf = if !file_data.is_empty() {
+                file_data[zone_start..zone_start + zone_size].to_vec()
+            } else {
+                let mut b = vec![0u8; zone_size];
+                file.seek(SeekFrom::Start(zone_start as u64))?;
+                file.read_exact(&mut b)?;
+                b
+            };
+            ZoneMap::decode(&zone_buf, num_rows, num_columns)
+        } else {
+            None
+        };
+
         // Cache the file handle for column data reads (seek+read on demand).
         let file = if file_data.is_empty() && mmap.is_none() {
-            std::fs::File::open(&path).ok().map(parking_lot::Mutex::new)
+            Some(parking_lot::Mutex::new(file))
         } else {
             None
         };
@@ -1722,10 +2043,22 @@ impl ColumnarSSTable {
             row_map,
             column_tags,
             num_rows,
+            zone_map,
             key_block_cache: parking_lot::Mutex::new(KeyBlockCache::new()),
         })
     }
 
+    /// Blocks that may hold a row satisfying `column <op> target`; see
+    /// [`ZoneMap::matching_blocks`]. `None` also for files without a zone map.
+    pub fn zone_blocks(
+        &self,
+        col_idx: usize,
+        op: &crate::sql::ast::BinaryOperator,
+        target: &Value,
+    ) -> Option<Vec<bool>> {
+        self.zone_map.as_ref()?.matching_blocks(col_idx, op, target)
+    }
+
     /// Load all timestamps from the file into the RowMap's lazy buffer.
     /// Called by the merge cursor before reading timestamps. This is the
     /// only time timestamps are read from disk — normal query paths never
@@ -1759,7 +2092,7 @@ impl ColumnarSSTable {
             return Ok(()); // Already loaded (small file or previously called).
         }
         let file_len = match &self.file {
-            Some(f) => f.lock().metadata().map(|m| m.len() as usize).unwrap_or(0),
+            Some(f) => f.lock().len().map(|len| len as usize).unwrap_or(0),
             None => 0,
         };
         if file_len == 0 {
@@ -1892,18 +2225,19 @@ impl ColumnarSSTable {
 
     /// Read a fixed column as an i64 array (zero-copy from mmap).
     /// Decompress segment data if needed. Format: [flag: u8] [data].
-    fn decompress_segment(data: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    fn decompress_segment(data: &[u8], num_rows: usize) -> std::borrow::Cow<'_, [u8]> {
         if data.is_empty() {
             return std::borrow::Cow::Borrowed(data);
         }
         match data[0] {
-            1 => {
+            SEG_FLAG_SNAPPY => {
                 // Snappy compressed
                 match snap::raw::Decoder::new().decompress_vec(&data[1..]) {
                     Ok(v) => std::borrow::Cow::Owned(v),
                     Err(_) => std::borrow::Cow::Borrowed(&data[1..]), // fallback: use as-is
                 }
             }
+            SEG_FLAG_DELTA => std::borrow::Cow::Owned(decode_delta_segment(&data[1..], num_rows)),
             _ => std::borrow::Cow::Borrowed(&data[1..]), // uncompressed, skip flag
         }
     }
@@ -1942,6 +2276,14 @@ impl ColumnarSSTable {
             buf[0]
         };
 
+        // Delta-encoded values have no fixed offset; the caller falls back to
+        // the cached full-column decode.
+        if flag == SEG_FLAG_DELTA {
+            return Err(StorageError::InvalidData(
+                "delta-encoded segment — use full-column decode".into(),
+            ));
+        }
+
         // Data starts after the flag byte.
         let data_start = seg_start + 1;
         // Null bitmap: data_start .. data_start + null_bytes
@@ -2020,15 +2362,8 @@ impl ColumnarSSTable {
     /// DONTNEED). This reduces RSS after heavy column scans. On macOS it's a
     /// no-op (no per-file fadvise), but the OS reclaims pages under pressure.
     pub fn advise_dontneed(&self) {
-        #[cfg(target_os = "linux")]
-        {
-            use std::os::unix::io::AsRawFd;
-            if let Some(ref cached) = self.file {
-                let f = cached.lock();
-                unsafe {
-                    libc::posix_fadvise(f.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
-                }
-            }
+        if let Some(ref cached) = self.file {
+            cached.lock().advise_dontneed();
         }
     }
 
@@ -2084,7 +2419,7 @@ impl ColumnarSSTable {
             let ok = if let Some(ref cached) = self.file {
                 let mut f = cached.lock();
                 f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
-            } else if let Ok(mut f) = std::fs::File::open(&self.path) {
+            } else if let Ok(mut f) = backend::open_file(&self.path, OpenFlags::read_only()) {
                 f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
             } else {
                 false
@@ -2095,12 +2430,14 @@ impl ColumnarSSTable {
             buf
         };
         // Check flag byte and decompress if needed.
-        if raw[0] == 1 {
+        if raw[0] == SEG_FLAG_SNAPPY {
             // Snappy compressed.
             match snap::raw::Decoder::new().decompress_vec(&raw[1..]) {
                 Ok(decompressed) => Ok(decompressed),
                 Err(_) => Ok(raw[1..].to_vec()),
             }
+        } else if raw[0] == SEG_FLAG_DELTA {
+            Ok(decode_delta_segment(&raw[1..], self.num_rows))
         } else {
             // Uncompressed — skip flag byte via drain (no realloc).
             let mut data = raw;
@@ -2195,7 +2532,7 @@ impl ColumnarSSTable {
         let ok = if let Some(ref cached) = self.file {
             let mut f = cached.lock();
             f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
-        } else if let Ok(mut f) = std::fs::File::open(&self.path) {
+        } else if let Ok(mut f) = backend::open_file(&self.path, OpenFlags::read_only()) {
             f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
         } else {
             false
@@ -2213,7 +2550,7 @@ impl ColumnarSSTable {
     pub fn read_segment_bytes(&self, start: usize, end: usize) -> std::borrow::Cow<'_, [u8]> {
         // If file_data is populated (small files), use it directly.
         if !self.file_data.is_empty() {
-            return Self::decompress_segment(&self.file_data[start..end]);
+            return Self::decompress_segment(&self.file_data[start..end], self.num_rows);
         }
         // If mmap available and the range is within bounds, use it (zero-copy).
         // mmap is preferable to seek+read because the OS manages page cache
@@ -2221,7 +2558,7 @@ impl ColumnarSSTable {
         // allocates heap buffers that jemalloc retains.
         if let Some(ref mmap) = self.mmap {
             if end <= mmap.len() {
-                return Self::decompress_segment(&mmap[start..end]);
+                return Self::decompress_segment(&mmap[start..end], self.num_rows);
             }
         }
         // Seek+read fallback: use cached file handle if available.
@@ -2231,13 +2568,15 @@ impl ColumnarSSTable {
         let ok = if let Some(ref cached) = self.file {
             let mut f = cached.lock();
             f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
-        } else if let Ok(mut f) = std::fs::File::open(&self.path) {
+        } else if let Ok(mut f) = backend::open_file(&self.path, OpenFlags::read_only()) {
             f.seek(SeekFrom::Start(start as u64)).is_ok() && f.read_exact(&mut buf).is_ok()
         } else {
             false
         };
         if ok {
-            Self::decompress_segment(&buf).into_owned().into()
+            Self::decompress_segment(&buf, self.num_rows)
+                .into_owned()
+                .into()
         } else {
             std::borrow::Cow::Owned(Vec::new())
         }
@@ -2351,6 +2690,7 @@ impl ColumnarSSTable {
 
     /// Read vector data from column segment.
     /// Format: [flag: u8] [null_bitmap] [dim: u16 LE] [f32×dim per row]
+    /// (compact encodings are decoded back to f32)
     pub fn read_vectors(&self, col_idx: usize) -> Result<Vec<(RowId, Vec<f32>)>> {
         let entry = &self.column_index[col_idx];
         // Use read_segment_bytes (handles file_data / mmap / seek+read fallbacks
@@ -2361,15 +2701,10 @@ impl ColumnarSSTable {
             self.read_segment_bytes(entry.offset as usize, (entry.offset + entry.size) as usize);
         let data = seg_bytes.as_ref();
         let null_bytes = self.num_rows.div_ceil(8);
-        if null_bytes + 2 > data.len() {
+        let Some((dim, data_start, encoding)) = vector_segment_layout(data, null_bytes) else {
             return Ok(Vec::new());
-        }
-        let dim = u16::from_le_bytes([data[null_bytes], data[null_bytes + 1]]) as usize;
-        if dim == 0 || dim > 65536 {
-            return Ok(Vec::new());
-        }
-        let stride = dim * 4;
-        let data_start = null_bytes + 2;
+        };
+        let stride = encoding.map_or(dim * 4, |e| e.encoded_len(dim));
         let n = ((data.len() - data_start) / stride).min(self.num_rows);
         let mut result = Vec::with_capacity(n);
         let _ = self.load_full_keys();
@@ -2381,21 +2716,171 @@ impl ColumnarSSTable {
                 continue;
             }
             let row_id = (self.row_map.key(i) & 0xFFFFFFFF) as RowId;
-            let mut v = Vec::with_capacity(dim);
             let base = data_start + i * stride;
-            for j in 0..dim {
-                let off = base + j * 4;
-                v.push(f32::from_le_bytes([
-                    data[off],
-                    data[off + 1],
-                    data[off + 2],
-                    data[off + 3],
-                ]));
-            }
-            result.push((row_id, v));
+            result.push((
+                row_id,
+                decode_vector_row(&data[base..base + stride], dim, encoding),
+            ));
         }
         Ok(result)
     }
+
+    /// Read a single vector at a specific row index (NULL → None). Decodes
+    /// only that row, for the lazy point-read projection path.
+    pub fn read_vector_at(&self, col_idx: usize, row_idx: usize) -> Result<Option<Vec<f32>>> {
+        let entry = &self.column_index[col_idx];
+        let seg_bytes =
+            self.read_segment_bytes(entry.offset as usize, (entry.offset + entry.size) as usize);
+        let data = seg_bytes.as_ref();
+        let null_bytes = self.num_rows.div_ceil(8);
+        if row_idx >= self.num_rows {
+            return Ok(None);
+        }
+        let Some((dim, data_start, encoding)) = vector_segment_layout(data, null_bytes) else {
+            return Ok(None);
+        };
+        if (data[row_idx / 8] >> (row_idx % 8)) & 1 != 0 {
+            return Ok(None);
+        }
+        let stride = encoding.map_or(dim * 4, |e| e.encoded_len(dim));
+        let start = data_start + row_idx * stride;
+        if start + stride > data.len() {
+            return Ok(None);
+        }
+        Ok(Some(decode_vector_row(
+            &data[start..start + stride],
+            dim,
+            encoding,
+        )))
+    }
+}
+
+/// Header of a Vector segment: (dim, offset of the first row, encoding).
+/// `None` when the segment holds no vectors.
+/// Delta-of-delta encode a Timestamp segment (`[null_bitmap][i64 × num_rows]`)
+/// as `[null_bitmap][gorilla bits]`, or `None` if that would not be smaller or
+/// the values cannot round-trip exactly. Only non-NULL values are encoded, so
+/// NULL sentinels don't break up an otherwise regular series.
+fn encode_delta_segment(seg: &[u8], num_rows: usize) -> Option<Vec<u8>> {
+    let null_bytes = num_rows.div_ceil(8);
+    let nulls = seg.get(..null_bytes)?;
+    let values = seg.get(null_bytes..null_bytes + num_rows * 8)?;
+    let timestamps: Vec<i64> = values
+        .chunks_exact(8)
+        .enumerate()
+        .filter(|(i, _)| (nulls[i / 8] >> (i % 8)) & 1 == 0)
+        .map(|(_, c)| i64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+        .collect();
+    let encoded = crate::storage::columnar::gorilla::try_encode_timestamps(&timestamps)?;
+    if null_bytes + encoded.len() >= seg.len() {
+        return None;
+    }
+    let mut out = Vec::with_capacity(null_bytes + encoded.len());
+    out.extend_from_slice(nulls);
+    out.extend_from_slice(&encoded);
+    Some(out)
+}
+
+/// Inverse of [`encode_delta_segment`]: rebuild the raw fixed-width payload,
+/// writing the usual `i64::MIN` sentinel into NULL slots.
+fn decode_delta_segment(payload: &[u8], num_rows: usize) -> Vec<u8> {
+    let null_bytes = num_rows.div_ceil(8).min(payload.len());
+    let nulls = &payload[..null_bytes];
+    let is_null = |i: usize| nulls.get(i / 8).is_some_and(|b| (b >> (i % 8)) & 1 != 0);
+    let non_null = (0..num_rows).filter(|&i| !is_null(i)).count();
+    let mut timestamps =
+        crate::storage::columnar::gorilla::decode_timestamps(&payload[null_bytes..], non_null)
+            .into_iter();
+    let mut out = Vec::with_capacity(null_bytes + num_rows * 8);
+    out.extend_from_slice(nulls);
+    for i in 0..num_rows {
+        let ts = if is_null(i) {
+            i64::MIN
+        } else {
+            timestamps.next().unwrap_or(i64::MIN)
+        };
+        out.extend_from_slice(&ts.to_le_bytes());
+    }
+    out
+}
+
+fn vector_segment_layout(
+    data: &[u8],
+    null_bytes: usize,
+) -> Option<(usize, usize, Option<VectorEncoding>)> {
+    if null_bytes + 2 > data.len() {
+        return None;
+    }
+    let dim = u16::from_le_bytes([data[null_bytes], data[null_bytes + 1]]) as usize;
+    if dim > 0 {
+        return Some((dim, null_bytes + 2, None));
+    }
+    // A zero dim followed by [dim:u16][encoding:u8] marks a compact encoding
+    if null_bytes + 5 > data.len() {
+        return None;
+    }
+    let dim = u16::from_le_bytes([data[null_bytes + 2], data[null_bytes + 3]]) as usize;
+    let encoding = VectorEncoding::from_tag(data[null_bytes + 4])?;
+    (dim > 0).then_some((dim, null_bytes + 5, Some(encoding)))
+}
+
+fn decode_vector_row(bytes: &[u8], dim: usize, encoding: Option<VectorEncoding>) -> Vec<f32> {
+    match encoding {
+        Some(enc) => enc.decode(bytes, dim),
+        None => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    }
+}
+
+// Fixed-width values are stored little-endian. On little-endian targets an
+// aligned payload is borrowed as a typed slice; otherwise (big-endian, or an
+// mmap offset that is not a multiple of the element size) it is decoded.
+
+pub(crate) fn le_i64s(bytes: &[u8]) -> Cow<'_, [i64]> {
+    #[cfg(target_endian = "little")]
+    // SAFETY: every bit pattern is a valid i64
+    if let ([], values, []) = unsafe { bytes.align_to::<i64>() } {
+        return Cow::Borrowed(values);
+    }
+    Cow::Owned(
+        bytes
+            .chunks_exact(8)
+            .map(|c| i64::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+pub(crate) fn le_f64s(bytes: &[u8]) -> Cow<'_, [f64]> {
+    #[cfg(target_endian = "little")]
+    // SAFETY: every bit pattern is a valid f64
+    if let ([], values, []) = unsafe { bytes.align_to::<f64>() } {
+        return Cow::Borrowed(values);
+    }
+    Cow::Owned(
+        bytes
+            .chunks_exact(8)
+            .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+/// Like [`le_i64s`] for f32, decoding into `scratch` when the payload
+/// cannot be borrowed so per-row callers don't allocate
+pub(crate) fn le_f32s<'a>(bytes: &'a [u8], scratch: &'a mut Vec<f32>) -> &'a [f32] {
+    #[cfg(target_endian = "little")]
+    // SAFETY: every bit pattern is a valid f32
+    if let ([], values, []) = unsafe { bytes.align_to::<f32>() } {
+        return values;
+    }
+    scratch.clear();
+    scratch.extend(
+        bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap())),
+    );
+    scratch
 }
 
 // ── Columnar SSTable Builder ───────────────────────────────────────
@@ -2554,7 +3039,10 @@ impl ColumnarSSTableBuilder {
                             buf.extend_from_slice(&0xFFFFu16.to_le_bytes());
                         }
                         Value::Text(t) => {
-                            let s = t.as_str();
+                            let s = match self.column_types.get(col_idx) {
+                                Some(ct) => ct.text_for_storage(t.as_str()),
+                                None => t.as_str().into(),
+                            };
                             // 🔑 The columnar Text format uses a u16 length prefix
                             // (0xFFFF is reserved as the NULL sentinel), so the
                             // maximum storable text value is 65534 bytes. The
@@ -2572,6 +3060,22 @@ impl ColumnarSSTableBuilder {
                             buf.extend_from_slice(&len.to_le_bytes());
                             buf.extend_from_slice(s.as_bytes());
                         }
+                        Value::Decimal(_)
+                        | Value::Uuid(_)
+                        | Value::Date(_)
+                        | Value::Time(_)
+                        | Value::Array(_) => {
+                            // DECIMAL/UUID/DATE/TIME/ARRAY share the Text layout (canonical string)
+                            let s = value.encoded_text().unwrap_or_default();
+                            if s.len() > 65534 {
+                                return Err(StorageError::InvalidData(format!(
+                                    "Encoded value of {} bytes exceeds the columnar maximum of 65534 bytes",
+                                    s.len()
+                                )));
+                            }
+                            buf.extend_from_slice(&(s.len() as u16).to_le_bytes());
+                            buf.extend_from_slice(s.as_bytes());
+                        }
                         _ => {
                             buf.extend_from_slice(&0xFFFFu16.to_le_bytes());
                         }
@@ -2590,9 +3094,17 @@ impl ColumnarSSTableBuilder {
                             }
                         }
                         Value::Tensor(t) => {
-                            let floats = t.to_f32();
+                            // Shape comes back from the schema; only the data is stored
+                            let floats = t.as_f32();
+                            if floats.len() > u16::MAX as usize {
+                                return Err(StorageError::InvalidData(format!(
+                                    "Tensor of {} elements exceeds the columnar maximum of {}",
+                                    floats.len(),
+                                    u16::MAX
+                                )));
+                            }
                             buf.extend_from_slice(&(floats.len() as u16).to_le_bytes());
-                            for f in &floats {
+                            for f in floats {
                                 buf.extend_from_slice(&f.to_le_bytes());
                             }
                         }
@@ -2760,9 +3272,12 @@ impl ColumnarSSTableBuilder {
                 }
                 ColumnTypeTag::Text => {
                     let s = match value {
-                        Value::Text(t) => t.as_str().to_string(),
+                        Value::Text(t) => match self.column_types.get(col_idx) {
+                            Some(ct) => ct.text_for_storage(t.as_str()).into_owned(),
+                            None => t.as_str().to_string(),
+                        },
                         Value::Null => String::new(),
-                        _ => String::new(),
+                        other => other.encoded_text().unwrap_or_default(),
                     };
                     // Store length-prefixed: [len: u16 LE] [bytes]. The u16 prefix
                     // caps text at 65535 bytes; previously larger values were
@@ -2977,9 +3492,13 @@ impl ColumnarSSTableBuilder {
                                 if len == 0xFFFF {
                                     found = Some(Value::Null);
                                 } else if p + len <= buf.len() {
-                                    found = Some(Value::text(
-                                        String::from_utf8_lossy(&buf[p..p + len]).into_owned(),
-                                    ));
+                                    let s = String::from_utf8_lossy(&buf[p..p + len]);
+                                    // ENUM codes go back to labels so re-adding
+                                    // encodes them again rather than storing the code
+                                    found = Some(match &col_types[ci] {
+                                        ct @ ColumnType::Enum(_) => ct.value_from_text(&s),
+                                        _ => Value::text(s.into_owned()),
+                                    });
                                 } else {
                                     found = Some(Value::Null);
                                 }
@@ -3209,8 +3728,19 @@ impl ColumnarSSTableBuilder {
                     }
                 }
                 seg.extend_from_slice(&nulls);
+                // Compact columns get a zero-dim marker, then the real header
+                let encoding = match self.column_types.get(col_idx) {
+                    Some(ColumnType::EncodedVector { encoding, .. }) if col_dim > 0 => {
+                        seg.extend_from_slice(&0u16.to_le_bytes());
+                        Some(*encoding)
+                    }
+                    _ => None,
+                };
                 seg.extend_from_slice(&(col_dim as u16).to_le_bytes());
-                // Second pass: emit col_dim f32 per row (pad shorter/missing).
+                if let Some(enc) = encoding {
+                    seg.push(enc.tag());
+                }
+                // Second pass: emit col_dim values per row (pad shorter/missing).
                 let mut pos = 0usize;
                 for row_idx in 0..num_rows {
                     let d = row_dims[row_idx];
@@ -3230,8 +3760,13 @@ impl ColumnarSSTableBuilder {
                             }
                         }
                     }
-                    for v in &vals {
-                        seg.extend_from_slice(&v.to_le_bytes());
+                    match encoding {
+                        Some(enc) => enc.encode(&vals, &mut seg),
+                        None => {
+                            for v in &vals {
+                                seg.extend_from_slice(&v.to_le_bytes());
+                            }
+                        }
                     }
                     pos += 2 + d * 4;
                 }
@@ -3290,10 +3825,22 @@ impl ColumnarSSTableBuilder {
             let is_text = col_idx < self.column_tags.len()
                 && matches!(self.column_tags[col_idx], ColumnTypeTag::Text);
             let store_uncompressed = is_fixed || is_text;
-            let seg_data: Vec<u8> = if store_uncompressed {
+            // Timestamp columns from periodic sensors are delta-of-delta
+            // encoded when that beats the raw layout; point reads then go
+            // through the cached full-column decode.
+            let delta_encoded = match self.column_tags.get(col_idx) {
+                Some(ColumnTypeTag::Timestamp) => encode_delta_segment(seg, num_rows),
+                _ => None,
+            };
+            let seg_data: Vec<u8> = if let Some(encoded) = delta_encoded {
+                let mut out = Vec::with_capacity(1 + encoded.len());
+                out.push(SEG_FLAG_DELTA);
+                out.extend_from_slice(&encoded);
+                out
+            } else if store_uncompressed {
                 // Store uncompressed — enables O(1)/page-level reads.
                 let mut out = Vec::with_capacity(1 + seg.len());
-                out.push(0u8); // flag: uncompressed
+                out.push(SEG_FLAG_RAW); // flag: uncompressed
                 out.extend_from_slice(seg);
                 out
             } else {
@@ -3303,12 +3850,12 @@ impl ColumnarSSTableBuilder {
                     .unwrap_or_else(|_| seg.clone());
                 if compressed.len() + 1 < seg.len() {
                     let mut out = Vec::with_capacity(1 + compressed.len());
-                    out.push(1u8); // flag: Snappy compressed
+                    out.push(SEG_FLAG_SNAPPY); // flag: Snappy compressed
                     out.extend_from_slice(&compressed);
                     out
                 } else {
                     let mut out = Vec::with_capacity(1 + seg.len());
-                    out.push(0u8); // flag: uncompressed
+                    out.push(SEG_FLAG_RAW); // flag: uncompressed
                     out.extend_from_slice(seg);
                     out
                 }
@@ -3323,8 +3870,21 @@ impl ColumnarSSTableBuilder {
         }
         let row_map_offset = current_offset;
 
+        // Zone map over the (deduplicated) column buffers
+        let mut zone_map =
+            Vec::with_capacity(ZoneMap::encoded_len(num_rows, num_cols, ZONE_BLOCK_ROWS));
+        for (col_idx, tag) in self.column_tags.iter().enumerate() {
+            let nulls = self.null_flags.get(col_idx).map_or(&[][..], |f| &f[..]);
+            for start in (0..num_rows).step_by(ZONE_BLOCK_ROWS) {
+                let rows = start..(start + ZONE_BLOCK_ROWS).min(num_rows);
+                let range = ZoneRange::compute(*tag, &self.column_buffers[col_idx], nulls, rows);
+                zone_map.extend_from_slice(&ZoneRange::encode(range.as_ref()));
+            }
+        }
+        zone_map.extend_from_slice(&(ZONE_BLOCK_ROWS as u32).to_le_bytes());
+
         // Pre-compute total size and allocate buffer
-        let total_size = row_map_offset as usize + row_map.len() + FOOTER_SIZE;
+        let total_size = row_map_offset as usize + row_map.len() + zone_map.len() + FOOTER_SIZE;
         let mut buf = Vec::with_capacity(total_size);
 
         // Header
@@ -3336,6 +3896,7 @@ impl ColumnarSSTableBuilder {
             num_rows: num_rows as u32,
             num_columns: num_cols as u16,
             column_tags: header_tags,
+            flags: HEADER_FLAG_ZONE_MAP,
         };
         buf.extend_from_slice(&header.serialize());
 
@@ -3353,6 +3914,9 @@ impl ColumnarSSTableBuilder {
         // Row map
         buf.extend_from_slice(&row_map);
 
+        // Zone map
+        buf.extend_from_slice(&zone_map);
+
         // Footer
         let mut footer = [0u8; FOOTER_SIZE];
         footer[0..8].copy_from_slice(&ci_offset.to_le_bytes());
@@ -3380,11 +3944,8 @@ impl ColumnarSSTableBuilder {
                 .and_then(|n| n.to_str())
                 .unwrap_or("col.tmp")
         ));
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&tmp_path)?;
+        let backend = backend::backend_for(&final_path);
+        let file = backend.open(&tmp_path, OpenFlags::create_truncate())?;
         let mut writer = BufWriter::new(file);
         writer.write_all(&buf)?;
         writer.flush()?;
@@ -3393,9 +3954,9 @@ impl ColumnarSSTableBuilder {
         drop(writer);
         // Atomic publish. On POSIX, rename guarantees readers see the new file
         // in its entirety once the syscall returns.
-        std::fs::rename(&tmp_path, &final_path)?;
+        backend.rename(&tmp_path, &final_path)?;
         // 🔑 fsync parent directory to make the rename durable across crashes.
-        crate::fsync_dir(&final_path);
+        backend.sync_parent_dir(&final_path);
 
         // Success: clear internal state (data is now on disk)
         // Reset finished to false so the builder can be reused for new data.
@@ -3427,6 +3988,36 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_fixed_width_payloads_decode_little_endian() {
+        let mut bytes = vec![0u8];
+        for v in [1i64, -2, i64::MAX] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        // Offset by one byte: never borrowable, always decoded
+        assert_eq!(&*le_i64s(&bytes[1..]), &[1, -2, i64::MAX]);
+
+        let mut bytes = vec![0u8];
+        for v in [0.5f32, -3.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        let mut scratch = Vec::new();
+        assert_eq!(le_f32s(&bytes[1..], &mut scratch), &[0.5, -3.0]);
+    }
+
+    #[test]
+    fn test_header_rejects_unknown_column_tag() {
+        let mut header = ColumnarHeader {
+            num_rows: 1,
+            num_columns: 2,
+            column_tags: [0u8; MAX_COLUMNS],
+            flags: 0,
+        };
+        assert!(ColumnarHeader::deserialize(&header.serialize()).is_ok());
+        header.column_tags[1] = 200;
+        assert!(ColumnarHeader::deserialize(&header.serialize()).is_err());
+    }
+
     #[test]
     #[cfg_attr(
         target_os = "macos",
@@ -3493,6 +4084,114 @@ mod tests {
         assert_eq!(reg_seg.get_str(3), Some("EU"));
     }
 
+    #[test]
+    fn test_columnar_timestamp_delta_encoding() {
+        use crate::types::Timestamp;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("ts.col.sst");
+        let col_types = vec![ColumnType::Timestamp, ColumnType::Timestamp];
+        let n = 5_000u64;
+
+        // Column 0: 1 kHz samples with one NULL; column 1: jumps too wide
+        // for a lossless delta-of-delta encoding
+        let mut builder = ColumnarSSTableBuilder::new(&path, col_types.clone());
+        for i in 0..n {
+            let periodic = if i == 7 {
+                Value::Null
+            } else {
+                Value::Timestamp(Timestamp::from_micros(
+                    1_700_000_000_000_000 + i as i64 * 1_000,
+                ))
+            };
+            let jumpy = Value::Timestamp(Timestamp::from_micros(((i * i) as i64) << 40));
+            let encoded =
+                crate::storage::row_format::encode(&[periodic, jumpy], &col_types).unwrap();
+            builder.add_row(i + 1, i, false, &encoded).unwrap();
+        }
+        builder.finish().unwrap();
+
+        let sst = ColumnarSSTable::open(&path).unwrap();
+        let raw_size = 1 + n.div_ceil(8) + n * 8;
+        assert!(sst.column_index[0].size * 10 < raw_size);
+        assert_eq!(sst.column_index[1].size, raw_size);
+
+        let seg = sst.read_fixed_i64(0).unwrap();
+        assert_eq!(seg.get_i64(0), Some(1_700_000_000_000_000));
+        assert_eq!(seg.get_i64(7), None);
+        assert_eq!(seg.get_i64(4_999), Some(1_700_000_000_000_000 + 4_999_000));
+        assert!(sst.read_fixed_i64_at(0, 1).is_err());
+        assert_eq!(sst.read_fixed_i64_at(1, 3).unwrap(), Some(9 << 40));
+
+        sst.load_full_keys().unwrap();
+        let row = sst.get_row(9, &col_types).unwrap();
+        assert_eq!(
+            row[0],
+            Value::Timestamp(Timestamp::from_micros(1_700_000_000_000_000 + 8_000))
+        );
+    }
+
+    #[test]
+    fn test_columnar_zone_map() {
+        use crate::sql::ast::BinaryOperator;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("zone.col.sst");
+        let col_types = vec![
+            ColumnType::Integer,
+            ColumnType::Float,
+            ColumnType::Text,
+            ColumnType::Integer,
+        ];
+        let n = 10_000u64;
+
+        // Three blocks: rows 0..4096, 4096..8192, 8192..10000
+        let mut builder = ColumnarSSTableBuilder::new(&path, col_types.clone());
+        for i in 0..n {
+            let row = [
+                Value::Integer(i as i64),
+                Value::Float(if i < 4096 { -1.0 } else { i as f64 / 2.0 }),
+                Value::Text(format!("t{i}").into()),
+                Value::Null,
+            ];
+            let encoded = crate::storage::row_format::encode(&row, &col_types).unwrap();
+            builder.add_row(i + 1, i, false, &encoded).unwrap();
+        }
+        builder.finish().unwrap();
+
+        let sst = ColumnarSSTable::open(&path).unwrap();
+        let blocks = |col, op, target| sst.zone_blocks(col, &op, &Value::Integer(target));
+        assert_eq!(
+            blocks(0, BinaryOperator::Lt, 100).unwrap(),
+            [true, false, false]
+        );
+        assert_eq!(
+            blocks(0, BinaryOperator::Ge, 8192).unwrap(),
+            [false, false, true]
+        );
+        assert_eq!(
+            blocks(0, BinaryOperator::Eq, 4096).unwrap(),
+            [false, true, false]
+        );
+        assert_eq!(
+            blocks(0, BinaryOperator::Gt, 9999).unwrap(),
+            [false, false, false]
+        );
+        assert_eq!(
+            blocks(0, BinaryOperator::Ne, 5).unwrap(),
+            [true, true, true]
+        );
+        assert_eq!(
+            blocks(1, BinaryOperator::Lt, 0).unwrap(),
+            [true, false, false]
+        );
+        assert_eq!(blocks(2, BinaryOperator::Eq, 1), None);
+        assert_eq!(
+            blocks(3, BinaryOperator::Ne, 1).unwrap(),
+            [false, false, false]
+        );
+        let float_blocks = sst.zone_blocks(1, &BinaryOperator::Ge, &Value::Float(4500.5));
+        assert_eq!(float_blocks.unwrap(), [false, false, true]);
+    }
+
     #[test]
     fn test_columnar_roundtrip_300k() {
         // Test with a larger dataset to verify no data corruption
@@ -13,10 +13,12 @@
 
 use super::bloom::BloomFilter;
 use super::{Key, LSMConfig, SSTable, SSTableBuilder};
+use crate::storage::backend;
+use crate::storage::failpoint;
+use crate::storage::file_manager::FileRefManager;
 use crate::{Result, StorageError};
 use parking_lot::RwLock;
 use std::collections::HashSet;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -121,12 +123,14 @@ pub struct SSTableMeta {
 impl Level {
     /// Create a new level
     pub fn new(level: usize, config: &LSMConfig) -> Self {
-        let base_size = 10 * 1024 * 1024; // L1: 10MB
+        // u64 and saturating: the deepest levels overflow usize on 32-bit targets
+        let base_size: u64 = 10 * 1024 * 1024; // L1: 10MB
         let size_threshold = if level == 0 {
             base_size // L0: 10MB
         } else {
-            base_size * config.level_multiplier.pow(level as u32 - 1)
-        } as u64;
+            base_size
+                .saturating_mul((config.level_multiplier as u64).saturating_pow(level as u32 - 1))
+        };
 
         // Initialize L0 sublevels for tiered compaction
         let sublevels = if level == 0 {
@@ -315,6 +319,10 @@ pub struct CompactionWorker {
     /// Deferred by one cycle so in-flight scans finish before files are removed.
     pending_deletions: Mutex<Vec<PathBuf>>,
 
+    /// Pins held by streaming scans on the SSTables they read. A pinned
+    /// file outlives its deferral and is deleted when the last pin drops.
+    file_refs: FileRefManager,
+
     /// Cached snapshot of all SSTable metadata
     /// Readers access this via cheap Arc clone (no Mutex contention).
     /// Updated atomically after register_sstable() and run_compaction().
@@ -341,6 +349,7 @@ impl CompactionWorker {
             stats: Arc::new(Mutex::new(CompactionStats::default())),
             post_compaction_cb: Arc::new(std::sync::RwLock::new(None)),
             pending_deletions: Mutex::new(Vec::new()),
+            file_refs: FileRefManager::new(),
             sstable_snapshot: RwLock::new(None),
             compaction_epoch: Arc::new(std::sync::atomic::AtomicU64::new(0)),
         };
@@ -376,15 +385,14 @@ impl CompactionWorker {
     /// Discover existing .sst files in the storage directory and register them.
     /// Called during startup so that previously flushed data is visible to scans.
     fn discover_sstables(&self) -> Result<()> {
-        let entries = match std::fs::read_dir(&self.storage_dir) {
+        let entries = match backend::backend_for(&self.storage_dir).list_dir(&self.storage_dir) {
             Ok(e) => e,
             Err(_) => return Ok(()), // Directory doesn't exist yet — nothing to discover
         };
 
         let mut discovered: Vec<(usize, SSTableMeta)> = Vec::new();
 
-        for entry in entries.flatten() {
-            let path = entry.path();
+        for path in entries {
             if path.extension().and_then(|e| e.to_str()) == Some("sst") {
                 // Parse level from filename: "l{level}_*.sst"
                 let file_name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
@@ -476,7 +484,8 @@ impl CompactionWorker {
 
     /// Delete SST files deferred from a previous compaction cycle.
     /// Called at the start of each compaction so in-flight scans from the
-    /// last cycle have finished by now.
+    /// last cycle have finished by now; files still pinned by a longer scan
+    /// are handed to the pins and deleted when the scan drops them.
     pub fn flush_pending_deletions(&self) {
         let pending = {
             let mut guard = self
@@ -486,7 +495,7 @@ impl CompactionWorker {
             std::mem::take(&mut *guard)
         };
         for path in &pending {
-            if let Err(e) = fs::remove_file(path) {
+            if let Err(e) = self.file_refs.delete_when_unused(path) {
                 debug_log!(
                     "[compaction] Failed to delete SST {:?}: {}, will retry next cycle",
                     path,
@@ -932,6 +941,11 @@ impl CompactionWorker {
         *snap = None;
     }
 
+    /// Pins on SSTable files, taken by scans that outlive a compaction cycle
+    pub fn file_refs(&self) -> &FileRefManager {
+        &self.file_refs
+    }
+
     /// Access the compaction epoch (for scan consistency checks)
     pub fn compaction_epoch(&self) -> &Arc<std::sync::atomic::AtomicU64> {
         &self.compaction_epoch
@@ -964,16 +978,17 @@ impl CompactionWorker {
         let overlapping = levels[level_idx].get_overlapping(&levels[level_idx + 1], &sources);
 
         drop(levels); // Release lock during I/O
+        failpoint::check(failpoint::LSM_COMPACTION)?;
 
         // ✅ 检查文件是否存在
         let valid_sources: Vec<_> = sources
             .iter()
-            .filter(|s| s.path.exists())
+            .filter(|s| backend::backend_for(&s.path).exists(&s.path))
             .cloned()
             .collect();
         let valid_overlapping: Vec<_> = overlapping
             .iter()
-            .filter(|s| s.path.exists())
+            .filter(|s| backend::backend_for(&s.path).exists(&s.path))
             .cloned()
             .collect();
 
@@ -4,10 +4,14 @@ use super::{
     BlobStore, BloomFilter, CompactionWorker, Key, LSMConfig, SSTable, SSTableBuilder,
     UnifiedMemTable, Value, ValueData,
 };
+use crate::cache::{CacheCounters, NegativeCache};
+use crate::storage::backend;
+use crate::storage::failpoint;
+use crate::storage::platform;
 use crate::{Result, StorageError};
 use parking_lot::RwLock;
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{self, JoinHandle};
@@ -32,6 +36,9 @@ struct CachedSSTable {
 struct SSTableCache {
     cache: RwLock<lru::LruCache<PathBuf, CachedSSTable>>,
     max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
 }
 
 impl SSTableCache {
@@ -42,6 +49,9 @@ impl SSTableCache {
                 NonZeroUsize::new(max_size.max(1)).unwrap(),
             )),
             max_entries: max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
@@ -50,6 +60,7 @@ impl SSTableCache {
         {
             let cache = self.cache.read();
             if let Some(cached) = cache.peek(path) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Ok(CachedSSTable {
                     bloom: cached.bloom.clone(),
                     handle: cached.handle.clone(),
@@ -60,6 +71,7 @@ impl SSTableCache {
         // Slow path: write lock
         let mut cache = self.cache.write();
         if let Some(cached) = cache.get(path) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
             return Ok(CachedSSTable {
                 bloom: cached.bloom.clone(),
                 handle: cached.handle.clone(),
@@ -67,6 +79,7 @@ impl SSTableCache {
         }
 
         // Open new SSTable
+        self.misses.fetch_add(1, Ordering::Relaxed);
         let sstable = SSTable::open(path)?;
         let bloom = Arc::new(sstable.bloom_filter().clone());
         let sstable_arc = Arc::new(RwLock::new(sstable));
@@ -77,8 +90,8 @@ impl SSTableCache {
         };
 
         // Evict old entries if at capacity
-        while cache.len() >= self.max_entries {
-            cache.pop_lru();
+        while cache.len() >= self.max_entries && cache.pop_lru().is_some() {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
         }
 
         cache.put(
@@ -96,6 +109,16 @@ impl SSTableCache {
         self.cache.write().clear();
     }
 
+    fn counters(&self) -> CacheCounters {
+        CacheCounters {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            size: self.cache.read().len(),
+            capacity: self.max_entries,
+        }
+    }
+
     fn evict(&self, removed_paths: &[PathBuf]) {
         if removed_paths.is_empty() {
             return;
@@ -177,6 +200,10 @@ pub struct LSMEngine {
     compaction_thread: Option<JoinHandle<()>>,
     flush_thread: Option<JoinHandle<()>>,
 
+    /// No background threads on this target: writers flush and compact
+    /// themselves (see `flush_queue_inline`)
+    inline_flush: bool,
+
     /// 🚀 Edge optimization: Condvar for event-driven flush (replaces 10ms polling)
     flush_wakeup: Arc<(Mutex<bool>, Condvar)>,
 
@@ -209,6 +236,10 @@ pub struct LSMEngine {
     rotation_epoch: Arc<AtomicU64>,
     /// Reset to 0 on any successful flush.
     consecutive_flush_errors: Arc<std::sync::atomic::AtomicU32>,
+
+    /// Keys recently confirmed absent, so repeated misses skip the
+    /// bloom / SSTable probes. Invalidated by every write path below.
+    negative_cache: Arc<NegativeCache>,
 }
 
 impl LSMEngine {
@@ -246,14 +277,15 @@ impl LSMEngine {
         config: LSMConfig,
         vector_dimension: Option<usize>,
     ) -> Result<Self> {
-        std::fs::create_dir_all(&storage_dir)?;
+        let backend = backend::backend_for(&storage_dir);
+        backend.create_dir_all(&storage_dir)?;
 
         // Clean up leftover .sst.tmp files from interrupted flushes
-        if let Ok(entries) = std::fs::read_dir(&storage_dir) {
-            for entry in entries.flatten() {
-                if let Some(name) = entry.file_name().to_str() {
+        if let Ok(entries) = backend.list_dir(&storage_dir) {
+            for path in entries {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     if name.ends_with(".sst.tmp") {
-                        let _ = std::fs::remove_file(entry.path());
+                        let _ = backend.remove_file(&path);
                     }
                 }
             }
@@ -268,10 +300,10 @@ impl LSMEngine {
         // Also clean up old lost+found files to prevent unbounded disk growth.
         {
             let lost_found = storage_dir.join("lost+found");
-            if lost_found.exists() {
-                if let Ok(entries) = std::fs::read_dir(&lost_found) {
-                    for entry in entries.flatten() {
-                        let _ = std::fs::remove_file(entry.path());
+            if backend.exists(&lost_found) {
+                if let Ok(entries) = backend.list_dir(&lost_found) {
+                    for path in entries {
+                        let _ = backend.remove_file(&path);
                     }
                 }
             }
@@ -282,17 +314,16 @@ impl LSMEngine {
                 .map(|metas| metas.iter().map(|m| m.path.clone()).collect())
                 .unwrap_or_default();
 
-            if let Ok(entries) = std::fs::read_dir(&storage_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
+            if let Ok(entries) = backend.list_dir(&storage_dir) {
+                for path in entries {
                     if path.extension().and_then(|e| e.to_str()) == Some("sst")
                         && !known_paths.contains(&path)
                     {
                         debug_log!("[LSM] Moving orphan SSTable to lost+found: {:?}", path);
                         let lost_found = storage_dir.join("lost+found");
-                        let _ = std::fs::create_dir_all(&lost_found);
+                        let _ = backend.create_dir_all(&lost_found);
                         let dest = lost_found.join(path.file_name().unwrap_or_default());
-                        let _ = std::fs::rename(&path, &dest);
+                        let _ = backend.rename(&path, &dest);
                     }
                 }
             }
@@ -343,6 +374,7 @@ impl LSMEngine {
             blob_store,
             compaction_thread: None,
             flush_thread: None,
+            inline_flush: !platform::threads_available(),
             flush_callback: Arc::new(RwLock::new(None)),
             rotation_epoch: Arc::new(AtomicU64::new(0)),
             flush_wakeup: Arc::new((Mutex::new(false), Condvar::new())),
@@ -350,6 +382,7 @@ impl LSMEngine {
             compaction_paused: Arc::new(AtomicBool::new(false)),
             flush_paused: Arc::new(AtomicBool::new(false)),
             consecutive_flush_errors: Arc::new(std::sync::atomic::AtomicU32::new(0)),
+            negative_cache: Arc::new(NegativeCache::default()),
         };
 
         // Wire post-compaction callback to evict only removed SSTables from cache
@@ -362,6 +395,10 @@ impl LSMEngine {
             ));
         }
 
+        if engine.inline_flush {
+            return Ok(engine);
+        }
+
         // 🔥 Start background compaction thread with Weak references
         let compaction_worker_weak = Arc::downgrade(&engine.compaction_worker);
         let shutdown_weak = Arc::downgrade(&engine.shutdown);
@@ -525,7 +562,7 @@ impl LSMEngine {
                                     let sst_path = storage_dir_clone.join(format!("l0_{:06}.sst", sst_id));
 
                                     // 🔧 Ensure storage directory exists
-                                    if !storage_dir_clone.exists() {
+                                    if !backend::backend_for(&storage_dir_clone).exists(&storage_dir_clone) {
                                         debug_log!("[LSM Flush] ⚠️  Storage directory deleted, skipping flush");
                                         return false;
                                     }
@@ -533,53 +570,20 @@ impl LSMEngine {
                                     // Build SSTable with retry on failure (data loss prevention)
                                     let mut flush_success = false;
                                     for attempt in 0..3 {
-                                        match SSTableBuilder::new(&sst_path, config_clone.clone(), memtable_len) {
-                                            Ok(mut builder) => {
-                                                let immutable_guard = immutable.read();
-                                                let mut add_failed = false;
-                                                if let Some(front_mt) = immutable_guard.front() {
-                                                    for (key, entry) in front_mt.iter() {
-                                                        let value = Value {
-                                                            data: entry.data,
-                                                            timestamp: entry.timestamp,
-                                                            deleted: entry.deleted,
-                                                        };
-                                                        if let Err(e) = builder.add(key, value) {
-                                                            debug_log!("[LSM Flush] Error adding key {}: {:?}", key, e);
-                                                            add_failed = true;
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                                drop(immutable_guard);
-                                                if add_failed {
-                                                    continue; // retry this attempt
-                                                }
-
-                                                match builder.finish() {
-                                                    Ok(meta) => {
-                                                        if let Some(worker) = compaction_worker_weak.upgrade() {
-                                                            if let Err(e) = worker.register_sstable(meta) {
-                                                                debug_log!("[LSM Flush] ❌ CRITICAL: register_sstable failed: {:?}. SSTable on disk but not tracked.", e);
-                                                                continue;
-                                                            }
-                                                        }
-                                                        // 🚀 Wake compaction thread (new SSTable registered)
-                                                        {
-                                                            let (lock, cvar) = &*compaction_wakeup_for_flush;
-                                                            if let Ok(mut guard) = lock.lock() { *guard = true; }
-                                                            cvar.notify_all();
-                                                        }
-                                                        flush_success = true;
-                                                        break;
-                                                    }
-                                                    Err(e) => {
-                                                        debug_log!("[LSM Flush] ❌ Failed to finish SSTable_{} (attempt {}): {:?}", sst_id, attempt + 1, e);
-                                                    }
+                                        let worker = compaction_worker_weak.upgrade();
+                                        match Self::write_front_memtable(&immutable, &sst_path, &config_clone, memtable_len, worker.as_deref()) {
+                                            Ok(()) => {
+                                                // 🚀 Wake compaction thread (new SSTable registered)
+                                                {
+                                                    let (lock, cvar) = &*compaction_wakeup_for_flush;
+                                                    if let Ok(mut guard) = lock.lock() { *guard = true; }
+                                                    cvar.notify_all();
                                                 }
+                                                flush_success = true;
+                                                break;
                                             }
                                             Err(e) => {
-                                                debug_log!("[LSM Flush] ❌ Failed to create SSTable builder (attempt {}): {:?}", attempt + 1, e);
+                                                debug_log!("[LSM Flush] ❌ Failed to write SSTable_{} (attempt {}): {:?}", sst_id, attempt + 1, e);
                                             }
                                         }
                                         // Wait before retry
@@ -720,6 +724,7 @@ impl LSMEngine {
                 if !memtable.should_flush() {
                     // Fast path: active has space, insert while holding the lock
                     memtable.put(key, value)?;
+                    self.negative_cache.invalidate(key);
                     return Ok(());
                 }
                 // Slow path: memtable is full, drop lock and handle rotation
@@ -739,6 +744,11 @@ impl LSMEngine {
                 }
             }
 
+            if self.inline_flush {
+                self.flush_queue_inline()?;
+                continue;
+            }
+
             // Queue is full, apply backpressure
             backpressure_count += 1;
             if backpressure_count == 1 {
@@ -769,6 +779,12 @@ impl LSMEngine {
 
     /// Get a value by key (LSM查询: MemTable -> Immutable -> SSTables -> Blob)
     pub fn get(&self, key: Key) -> Result<Option<Value>> {
+        // 0. Known-missing key: skip every probe below
+        if self.negative_cache.contains(key) {
+            return Ok(None);
+        }
+        let negative_epoch = self.negative_cache.epoch();
+
         // 1. Check active memtable (newest data)
         let epoch_before = self.rotation_epoch.load(Ordering::Acquire);
         let active_result = {
@@ -909,12 +925,12 @@ impl LSMEngine {
         }
 
         if let Some(value) = best {
-            if value.deleted {
-                return Ok(None);
+            if !value.deleted {
+                return Ok(Some(value));
             }
-            return Ok(Some(value));
         }
 
+        self.negative_cache.insert(key, negative_epoch);
         Ok(None)
     }
 
@@ -1210,6 +1226,10 @@ impl LSMEngine {
                 if queue_len < self.max_immutable_slots && self.try_rotate_memtable().is_ok() {
                     break;
                 }
+                if self.inline_flush {
+                    self.flush_queue_inline()?;
+                    continue;
+                }
 
                 backpressure_count += 1;
                 if backpressure_count > 10000 {
@@ -1222,6 +1242,8 @@ impl LSMEngine {
 
             let memtable = self.memtable.read();
             memtable.batch_put(chunk)?;
+            self.negative_cache
+                .invalidate_keys(chunk.iter().map(|(k, _)| *k));
         }
 
         Ok(())
@@ -1252,7 +1274,12 @@ impl LSMEngine {
 
         let meta = builder.finish()?;
         self.compaction_worker.register_sstable(meta)?;
+        self.negative_cache
+            .invalidate_keys(kvs.iter().map(|(k, _)| *k));
 
+        if self.inline_flush {
+            return self.compact_inline();
+        }
         // Wake compaction thread (new SSTable at L0)
         if let Ok(mut guard) = self.compaction_wakeup.0.lock() {
             *guard = true;
@@ -1285,6 +1312,8 @@ impl LSMEngine {
 
         let memtable = self.memtable.read();
         memtable.batch_put_fast(&processed)?;
+        self.negative_cache
+            .invalidate_keys(processed.iter().map(|(k, _)| *k));
         Ok(())
     }
 
@@ -1371,6 +1400,7 @@ impl LSMEngine {
 
                 if !memtable.should_flush() {
                     memtable.put_with_vector(key, data, vector, timestamp)?;
+                    self.negative_cache.invalidate(key);
                     return Ok(());
                 }
             }
@@ -1407,13 +1437,14 @@ impl LSMEngine {
     pub fn flush_with_paths(&self) -> Result<Vec<PathBuf>> {
         debug_log!("💾 [flush] 开始flush操作...");
         // 🔧 检查存储目录是否存在（防止在数据库关闭后flush）
-        if !self.storage_dir.exists() {
+        if !backend::backend_for(&self.storage_dir).exists(&self.storage_dir) {
             debug_log!(
                 "⚠️  [flush] 存储目录不存在，跳过flush: {:?}",
                 self.storage_dir
             );
             return Ok(Vec::new());
         }
+        failpoint::check(failpoint::LSM_FLUSH)?;
 
         // 0. Sync blob store — ensures large values are durable BEFORE
         //    any SSTable referencing them is created and synced.
@@ -1439,6 +1470,10 @@ impl LSMEngine {
                 self.rotate_memtable()?;
             }
         }
+        if self.inline_flush {
+            self.flush_queue_inline()?;
+            return Ok(Vec::new());
+        }
 
         // 2. Wait for background thread to flush the queue using condvar
         let start_wait = std::time::Instant::now();
@@ -1613,6 +1648,82 @@ impl LSMEngine {
         Ok(())
     }
 
+    /// Write the oldest immutable memtable (`len` entries) to the L0 SSTable
+    /// `sst_path` and register it for compaction. The caller pops the
+    /// memtable once this succeeds.
+    fn write_front_memtable(
+        immutable: &RwLock<VecDeque<UnifiedMemTable>>,
+        sst_path: &Path,
+        config: &LSMConfig,
+        len: usize,
+        worker: Option<&CompactionWorker>,
+    ) -> Result<()> {
+        let mut builder = SSTableBuilder::new(sst_path, config.clone(), len)?;
+        {
+            let immutable_guard = immutable.read();
+            if let Some(front_mt) = immutable_guard.front() {
+                for (key, entry) in front_mt.iter() {
+                    let value = Value {
+                        data: entry.data,
+                        timestamp: entry.timestamp,
+                        deleted: entry.deleted,
+                    };
+                    builder.add(key, value)?;
+                }
+            }
+        }
+        let meta = builder.finish()?;
+        if let Some(worker) = worker {
+            worker.register_sstable(meta)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the whole immutable queue on the calling thread, then run the
+    /// compaction that makes due. Does the flush and compaction threads'
+    /// work where there are no threads.
+    fn flush_queue_inline(&self) -> Result<()> {
+        loop {
+            let len = match self.immutable.read().front() {
+                Some(memtable) => memtable.len(),
+                None => break,
+            };
+            if len > 0 {
+                let sst_id = self.next_sst_id.fetch_add(1, Ordering::Relaxed);
+                let sst_path = self.storage_dir.join(format!("l0_{:06}.sst", sst_id));
+                Self::write_front_memtable(
+                    &self.immutable,
+                    &sst_path,
+                    &self.config,
+                    len,
+                    Some(&self.compaction_worker),
+                )?;
+            }
+            let memtable = self.immutable.write().pop_front();
+            let callback = self.flush_callback.read().clone();
+            if let (Some(memtable), Some(callback)) = (memtable, callback) {
+                if let Err(_e) = callback(&memtable) {
+                    debug_log!("[LSM Flush] ⚠️  Callback error: {:?}", _e);
+                }
+            }
+        }
+        self.compact_inline()
+    }
+
+    /// Run up to the compaction thread's 10 rounds on the calling thread
+    fn compact_inline(&self) -> Result<()> {
+        if self.compaction_paused.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        for _ in 0..10 {
+            if !self.compaction_worker.needs_compaction()? {
+                break;
+            }
+            self.compaction_worker.run_compaction()?;
+        }
+        Ok(())
+    }
+
     /// Force rotate (blocking, used by flush())
     fn rotate_memtable(&self) -> Result<()> {
         // Wait until queue has space
@@ -1632,6 +1743,10 @@ impl LSMEngine {
                     );
                 }
             }
+            if self.inline_flush {
+                self.flush_queue_inline()?;
+                continue;
+            }
             // Sleep briefly to avoid busy loop
             thread::sleep(Duration::from_millis(1));
             wait_count += 1;
@@ -1918,6 +2033,33 @@ impl LSMEngine {
         Ok(estimated_count)
     }
 
+    /// Hit/miss/eviction counters of the SSTable handle cache
+    pub fn sstable_cache_stats(&self) -> CacheCounters {
+        self.sstable_cache.counters()
+    }
+
+    /// Counters of the negative lookup cache
+    pub fn negative_cache_stats(&self) -> crate::cache::NegativeCacheStats {
+        self.negative_cache.stats()
+    }
+
+    /// Count live MemTable entries (active + immutable) in `[start, end)`
+    ///
+    /// Complements [`Self::estimate_key_count_in_range`] for data that has
+    /// not been flushed yet. A key rewritten in several MemTables is counted
+    /// once per table, so this is an estimate as well.
+    pub fn memtable_key_count_in_range(&self, start: Key, end: Key) -> Result<usize> {
+        let live = |mem: &UnifiedMemTable| {
+            mem.scan_arcs(start, end)
+                .iter()
+                .filter(|(_, entry)| !entry.deleted)
+                .count()
+        };
+        let mut count: usize = self.immutable.read().iter().map(&live).sum();
+        count += live(&self.memtable.read());
+        Ok(count)
+    }
+
     /// 🚀 流式范围扫描（批量迭代器，内存友好）
     ///
     /// 返回一个迭代器，每次产出一批数据（默认 1000 条），而不是一次性加载全部。
@@ -1998,7 +2140,31 @@ impl LSMEngine {
     /// }
     /// ```
     pub fn scan_range_streaming(&self, start: Key, end: Key) -> Result<super::MergingIterator> {
+        self.scan_range_streaming_ordered(start, end, false)
+    }
+
+    /// Streaming range scan over `[start, end)`, largest key first
+    ///
+    /// Same snapshot and MVCC rules as [`Self::scan_range_streaming`]; lets
+    /// "newest N" lookups stop after N keys instead of reading the whole
+    /// range ascending and reversing it.
+    pub fn scan_range_streaming_rev(&self, start: Key, end: Key) -> Result<super::MergingIterator> {
+        self.scan_range_streaming_ordered(start, end, true)
+    }
+
+    fn scan_range_streaming_ordered(
+        &self,
+        start: Key,
+        end: Key,
+        descending: bool,
+    ) -> Result<super::MergingIterator> {
         let mut sources: Vec<KVIterator> = Vec::new();
+        // SSTable iterators, newest first; they start reading ahead only
+        // once the snapshot is known to be consistent
+        let mut sst_iters: Vec<crate::storage::lsm::sstable::SSTableIterator> = Vec::new();
+        // Pins on those SSTables' files, held by the returned iterator so
+        // compaction defers deleting them until the scan is dropped
+        let mut pins = Vec::new();
 
         // Loop until we get a consistent snapshot (epoch stable across the entire snapshot).
         // This prevents data loss when auto-flush rotates MemTable → Immutable → SSTable
@@ -2007,6 +2173,8 @@ impl LSMEngine {
         let mut retries = 0;
         loop {
             sources.clear();
+            sst_iters.clear();
+            pins.clear();
 
             let rot_epoch_before = self.rotation_epoch.load(Ordering::Acquire);
             let cmp_epoch_before = self
@@ -2021,7 +2189,10 @@ impl LSMEngine {
 
                 // Source 1: Active MemTable — zero-copy Arc<DataEntry>
                 {
-                    let entries = memtable.scan_arcs(start, end);
+                    let mut entries = memtable.scan_arcs(start, end);
+                    if descending {
+                        entries.reverse();
+                    }
                     if !entries.is_empty() {
                         let iter = entries.into_iter().map(|(k, arc)| {
                             Ok((
@@ -2039,7 +2210,10 @@ impl LSMEngine {
 
                 // Source 2-N: Immutable queue — zero-copy Arc<DataEntry>
                 for mt in immutable.iter() {
-                    let entries = mt.scan_arcs(start, end);
+                    let mut entries = mt.scan_arcs(start, end);
+                    if descending {
+                        entries.reverse();
+                    }
                     if !entries.is_empty() {
                         let iter = entries.into_iter().map(|(k, arc)| {
                             Ok((
@@ -2064,6 +2238,9 @@ impl LSMEngine {
                     continue;
                 }
 
+                // Pin before the epoch check below: a file deleted by a
+                // compaction this snapshot does not see forces a retry
+                pins.push(self.compaction_worker.file_refs().pin(&meta.path)?);
                 let cached = match self.sstable_cache.get_or_open(&meta.path) {
                     Ok(cached) => cached,
                     Err(e) => {
@@ -2079,11 +2256,20 @@ impl LSMEngine {
                 // 🚀 Streaming SSTable scan — reads blocks on demand, O(1) memory
                 let mut sst_iter = {
                     let sstable = cached.handle.read();
-                    match crate::storage::lsm::sstable::SSTableIterator::with_range(
-                        &sstable,
-                        Some(start),
-                        Some(end),
-                    ) {
+                    let iter = if descending {
+                        crate::storage::lsm::sstable::SSTableIterator::with_range_rev(
+                            &sstable,
+                            Some(start),
+                            Some(end),
+                        )
+                    } else {
+                        crate::storage::lsm::sstable::SSTableIterator::with_range(
+                            &sstable,
+                            Some(start),
+                            Some(end),
+                        )
+                    };
+                    match iter {
                         Ok(iter) => iter,
                         Err(e) => {
                             debug_log!("[scan_range_streaming] Failed to create SSTable iterator {:?}: {:?}", meta.path, e);
@@ -2094,7 +2280,7 @@ impl LSMEngine {
                 // Skip CRC for sequential scans — data integrity is guaranteed by mmap
                 // and CRC was verified when the SSTable was first written/compacted.
                 sst_iter.set_verify_crc(false);
-                sources.push(Box::new(sst_iter.map(|(k, v)| Ok((k, v)))));
+                sst_iters.push(sst_iter);
             }
 
             // Phase 3: Validate consistency — if either epoch changed during our
@@ -2106,7 +2292,16 @@ impl LSMEngine {
                 .load(Ordering::Acquire);
             if rot_epoch_after == rot_epoch_before && cmp_epoch_after == cmp_epoch_before {
                 // 🚀 Fast path: single SSTable, no memtable data — use raw (zero-Arc) iterator
-                if sources.is_empty() && sstable_metas.len() == 1 {
+                if !descending
+                    && sources.is_empty()
+                    && sst_iters.is_empty()
+                    && sstable_metas.len() == 1
+                {
+                    pins.push(
+                        self.compaction_worker
+                            .file_refs()
+                            .pin(&sstable_metas[0].path)?,
+                    );
                     if let Ok(cached) = self.sstable_cache.get_or_open(&sstable_metas[0].path) {
                         let sstable = cached.handle.read();
                         if let Ok(mut sst_iter) =
@@ -2117,7 +2312,13 @@ impl LSMEngine {
                             )
                         {
                             sst_iter.set_verify_crc(false); // Skip CRC for sequential scan
-                            return Ok(super::MergingIterator::new_raw_sst(sst_iter));
+                            sst_iter.set_readahead(
+                                self.config.scan_readahead_blocks,
+                                self.config.scan_prefetch,
+                            );
+                            return Ok(
+                                super::MergingIterator::new_raw_sst(sst_iter).with_pins(pins)
+                            );
                         }
                     }
                 }
@@ -2132,7 +2333,14 @@ impl LSMEngine {
             }
         }
 
-        Ok(super::MergingIterator::new(sources))
+        for mut sst_iter in sst_iters {
+            sst_iter.set_readahead(self.config.scan_readahead_blocks, self.config.scan_prefetch);
+            sources.push(Box::new(sst_iter.map(|(k, v)| Ok((k, v)))));
+        }
+        if descending {
+            return Ok(super::MergingIterator::new_descending(sources).with_pins(pins));
+        }
+        Ok(super::MergingIterator::new(sources).with_pins(pins))
     }
 }
 
@@ -2337,6 +2545,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scan_range_rev() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = LSMConfig {
+            memtable_size: 1024,
+            ..Default::default()
+        };
+        let engine = LSMEngine::new(temp_dir.path().to_path_buf(), config).unwrap();
+
+        // Several SSTables plus unflushed MemTable data
+        for i in 0..300u64 {
+            engine.put(i, Value::new(vec![0u8; 20], i)).unwrap();
+        }
+        engine.flush().unwrap();
+        for i in (0..300u64).step_by(3) {
+            engine.put(i, Value::new(vec![1u8; 20], 1000 + i)).unwrap();
+        }
+        engine.delete(150, 5000).unwrap();
+
+        let keys: Vec<Key> = engine
+            .scan_range_streaming_rev(100, 200)
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        let expected: Vec<Key> = (100..200u64).rev().filter(|&k| k != 150).collect();
+        assert_eq!(keys, expected);
+
+        // Newest version wins in either direction
+        let first = engine
+            .scan_range_streaming_rev(0, 100)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!((first.0, first.1.timestamp), (99, 1099));
+    }
+
     #[test]
     fn test_scan_streaming_empty_range() {
         let temp_dir = TempDir::new().unwrap();
@@ -2396,6 +2641,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scan_pins_sstables_across_compaction() {
+        let temp_dir = TempDir::new().unwrap();
+        let engine = LSMEngine::new(temp_dir.path().to_path_buf(), LSMConfig::default()).unwrap();
+        for round in 0..2u64 {
+            for i in 0..100u64 {
+                let key = round * 100 + i;
+                engine
+                    .put(key, Value::new(key.to_le_bytes().to_vec(), key))
+                    .unwrap();
+            }
+            engine.flush().unwrap();
+        }
+        let old: Vec<PathBuf> = engine
+            .compaction_worker
+            .get_all_sstables()
+            .unwrap()
+            .iter()
+            .map(|m| m.path.clone())
+            .collect();
+        assert_eq!(old.len(), 2);
+
+        let mut iter = engine.scan_range_streaming(0, 200).unwrap();
+        assert_eq!(iter.next().unwrap().unwrap().0, 0);
+
+        // The next compaction would delete the merged files; the scan keeps them
+        engine.compact_full().unwrap();
+        engine.compaction_worker.flush_pending_deletions();
+        assert!(old.iter().all(|p| p.exists()));
+        assert_eq!(iter.by_ref().filter_map(|r| r.ok()).count(), 199);
+
+        drop(iter);
+        assert!(old.iter().all(|p| !p.exists()));
+        let results: Vec<_> = engine
+            .scan_range_streaming(0, 200)
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(results.len(), 200);
+    }
+
     #[test]
     fn test_data_survives_multiple_flushes() {
         let temp_dir = TempDir::new().unwrap();
@@ -16,6 +16,7 @@
 
 use super::sstable::SSTableIterator;
 use super::{Key, Value};
+use crate::storage::file_manager::FilePin;
 use crate::Result;
 use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
@@ -30,6 +31,7 @@ struct HeapItem {
     key: Key,
     value: Value,
     source_id: usize, // 数据源 ID（用于去重后重新填充）
+    descending: bool, // 降序扫描：largest key first
 }
 
 impl PartialEq for HeapItem {
@@ -48,12 +50,16 @@ impl PartialOrd for HeapItem {
 
 impl Ord for HeapItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        // 1. Key ascending (smallest first)
+        // 1. Key ascending (smallest first), or descending for reverse scans
         // 2. Same key: timestamp descending (newest first) so the freshest
         //    version is yielded first and older duplicates are skipped by dedup.
         // 3. Same key + timestamp: source_id ascending (MemTable first)
-        self.key
-            .cmp(&other.key)
+        let by_key = if self.descending {
+            other.key.cmp(&self.key)
+        } else {
+            self.key.cmp(&other.key)
+        };
+        by_key
             .then(other.value.timestamp.cmp(&self.value.timestamp)) // newest first
             .then(self.source_id.cmp(&other.source_id))
     }
@@ -94,11 +100,28 @@ pub struct MergingIterator {
     /// 🚀 Unboxed SSTable iterator for zero-Arc single-source scans.
     /// Set when sources is empty and this is the sole data source.
     raw_sst: Option<SSTableIterator>,
+
+    /// Sources yield largest key first; merge in descending key order
+    descending: bool,
+
+    /// Pins on the SSTable files being read, so compaction cannot delete
+    /// them before this iterator is dropped
+    pins: Vec<FilePin>,
 }
 
 impl MergingIterator {
     /// Create a new merging iterator
     pub fn new(sources: Vec<KVIterator>) -> Self {
+        Self::with_order(sources, false)
+    }
+
+    /// Create a merging iterator over sources sorted by descending key,
+    /// yielding the largest key first
+    pub fn new_descending(sources: Vec<KVIterator>) -> Self {
+        Self::with_order(sources, true)
+    }
+
+    fn with_order(sources: Vec<KVIterator>, descending: bool) -> Self {
         let single = sources.len() == 1;
         let mut iter = Self {
             heap: BinaryHeap::new(),
@@ -108,6 +131,8 @@ impl MergingIterator {
             first_error: None,
             single_source: single,
             raw_sst: None,
+            descending,
+            pins: Vec::new(),
         };
 
         if !single {
@@ -128,9 +153,17 @@ impl MergingIterator {
             first_error: None,
             single_source: true,
             raw_sst: Some(sst),
+            descending: false,
+            pins: Vec::new(),
         }
     }
 
+    /// Keep the given file pins until this iterator is dropped
+    pub fn with_pins(mut self, pins: Vec<FilePin>) -> Self {
+        self.pins = pins;
+        self
+    }
+
     /// Zero-copy scan: returns (key, timestamp, deleted, value_bytes) where
     /// value_bytes shares the decompressed block's Arc<Vec<u8>> (no per-row memcpy).
     /// Works for both single and multi-SSTable raw paths.
@@ -181,6 +214,7 @@ impl MergingIterator {
                         key,
                         value,
                         source_id,
+                        descending: self.descending,
                     }));
                 }
                 Some(Err(e)) => {
@@ -201,6 +235,7 @@ impl MergingIterator {
                         key,
                         value,
                         source_id,
+                        descending: self.descending,
                     }));
                 }
                 Some(Err(e)) => {
@@ -383,6 +418,38 @@ mod tests {
         assert_eq!(results[0].1, vec![1, 0, 0]); // v3
     }
 
+    #[test]
+    fn test_merging_iterator_descending() {
+        // 两个降序数据源，key=3 有两个版本
+        let source1: Vec<Result<(Key, Value)>> = vec![
+            Ok((5, Value::new(vec![5], 100))),
+            Ok((3, Value::new(vec![3, 1], 200))), // newest
+            Ok((1, Value::new(vec![1], 100))),
+        ];
+
+        let source2: Vec<Result<(Key, Value)>> = vec![
+            Ok((4, Value::new(vec![4], 100))),
+            Ok((3, Value::new(vec![3], 100))),
+            Ok((2, Value::new(vec![2], 100))),
+        ];
+
+        let sources: Vec<BoxedIter> =
+            vec![Box::new(source1.into_iter()), Box::new(source2.into_iter())];
+
+        let iter = MergingIterator::new_descending(sources);
+        let results: Vec<(Key, u64)> = iter
+            .map(|r| {
+                let (k, v) = r.unwrap();
+                (k, v.timestamp)
+            })
+            .collect();
+
+        assert_eq!(
+            results,
+            vec![(5, 100), (4, 100), (3, 200), (2, 100), (1, 100)]
+        );
+    }
+
     #[test]
     fn test_merging_iterator_tombstone() {
         // 测试 tombstone 过滤
@@ -226,6 +226,18 @@ pub struct LSMConfig {
     /// 0 = drop all tombstones immediately during compaction.
     /// Default: 86400 (24 hours).
     pub tombstone_ttl_secs: u64,
+
+    /// SSTable writer buffer (default 64KB; the erase block size in flash mode)
+    pub write_buffer_size: usize,
+
+    // --- Sequential scans ---
+    /// SSTable blocks a range scan reads ahead of the block it is decoding
+    /// (default 4; 0 reads each block on demand)
+    pub scan_readahead_blocks: usize,
+
+    /// Decompress the read-ahead blocks on a background thread while the
+    /// scan decodes the current one (default true)
+    pub scan_prefetch: bool,
 }
 
 impl Default for LSMConfig {
@@ -249,6 +261,9 @@ impl Default for LSMConfig {
             compaction_yield_every_n_blocks: 4,
             compaction_idle_only: false,
             tombstone_ttl_secs: 86400, // 24 hours
+            write_buffer_size: 64 * 1024,
+            scan_readahead_blocks: 4,
+            scan_prefetch: true,
         }
     }
 }
@@ -284,6 +299,10 @@ impl LSMConfig {
             tombstone_ttl_secs: db_config
                 .tombstone_ttl_secs
                 .unwrap_or(defaults.tombstone_ttl_secs),
+            scan_readahead_blocks: db_config
+                .scan_readahead_blocks
+                .unwrap_or(defaults.scan_readahead_blocks),
+            scan_prefetch: db_config.scan_prefetch.unwrap_or(defaults.scan_prefetch),
             ..defaults
         }
     }
@@ -4,7 +4,7 @@
 //! - 数据和向量分离存储：DataEntry 只含 row data，无 Option<Vec> 开销
 //! - 向量数据单独 BTreeMap，仅向量表创建
 //! - 集成 FreshVamanaGraph 用于向量搜索
-//! - 16 分片 BTreeMap 减少写入锁竞争
+//! - 16 分片 BTreeMap（key 经哈希路由）减少写入锁竞争，向量数据同样分片
 //!
 //! ## 性能优化
 //! - Arc<DataEntry> 避免每次 get() 的 clone（8 bytes vs 全行 memcpy）
@@ -21,10 +21,38 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Type alias for the vector storage map
-type VectorMap = Arc<RwLock<BTreeMap<Key, Vec<f32>>>>;
+type VectorMap = Arc<VectorShards>;
 
 const SHARD_COUNT: usize = 16;
-const SHARD_MASK: usize = SHARD_COUNT - 1;
+
+/// Vector storage, sharded like the row data so vector inserts on
+/// different keys do not contend on one map lock
+struct VectorShards([RwLock<BTreeMap<Key, Vec<f32>>>; SHARD_COUNT]);
+
+impl VectorShards {
+    fn new() -> Self {
+        Self(core::array::from_fn(|_| RwLock::new(BTreeMap::new())))
+    }
+
+    fn get(&self, key: Key) -> Option<Vec<f32>> {
+        self.0[UnifiedMemTable::shard_index(key)]
+            .read()
+            .get(&key)
+            .cloned()
+    }
+
+    fn insert(&self, key: Key, vector: Vec<f32>) {
+        self.0[UnifiedMemTable::shard_index(key)]
+            .write()
+            .insert(key, vector);
+    }
+
+    fn remove(&self, key: Key) -> Option<Vec<f32>> {
+        self.0[UnifiedMemTable::shard_index(key)]
+            .write()
+            .remove(&key)
+    }
+}
 
 /// Data entry (row data only, no vector overhead)
 #[derive(Clone, Debug)]
@@ -95,7 +123,7 @@ impl From<Arc<DataEntry>> for UnifiedEntry {
 
 /// Unified MemTable (数据 + 向量) — 16-shard concurrent design
 pub struct UnifiedMemTable {
-    /// 分片存储：16 个独立 BTreeMap，按 key & 0xF 路由
+    /// 分片存储：16 个独立 BTreeMap，按 key 的哈希路由（见 shard_index）
     shards: [RwLock<BTreeMap<Key, Arc<DataEntry>>>; SHARD_COUNT],
 
     /// 🚀 Batch write buffer: Vec-based fast path for bulk INSERTs.
@@ -123,9 +151,12 @@ pub struct UnifiedMemTable {
 }
 
 impl UnifiedMemTable {
+    /// Shard for `key`. Keys are mixed first (Fibonacci hashing) so strided
+    /// keys, e.g. row ids that step by 16 or share low bits, still spread
+    /// over every shard instead of serializing writers on one.
     #[inline]
     fn shard_index(key: Key) -> usize {
-        (key as usize) & SHARD_MASK
+        (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - SHARD_COUNT.trailing_zeros())) as usize
     }
 
     /// 创建不支持向量的 MemTable（兼容旧代码）
@@ -158,7 +189,7 @@ impl UnifiedMemTable {
         Self {
             shards: core::array::from_fn(|_| RwLock::new(BTreeMap::new())),
             batch_buffer: RwLock::new(Vec::new()),
-            vectors: Some(Arc::new(RwLock::new(BTreeMap::new()))),
+            vectors: Some(Arc::new(VectorShards::new())),
             vector_graph: Some(Arc::new(vector_graph)),
             vector_dimension: Some(dimension),
             size: AtomicUsize::new(0),
@@ -206,7 +237,7 @@ impl UnifiedMemTable {
         if let Some(ref vec_map) = self.vectors {
             let vec_size = vector.len() * 4;
             self.size.fetch_add(vec_size, Ordering::Relaxed);
-            vec_map.write().insert(key, vector.clone());
+            vec_map.insert(key, vector.clone());
         }
 
         if let Some(ref graph) = self.vector_graph {
@@ -340,10 +371,7 @@ impl UnifiedMemTable {
             return Ok(None);
         };
 
-        let vector = self
-            .vectors
-            .as_ref()
-            .and_then(|vm| vm.read().get(&key).cloned());
+        let vector = self.vectors.as_ref().and_then(|vm| vm.get(key));
 
         Ok(Some(UnifiedEntry {
             data: arc_entry.data.clone(),
@@ -374,7 +402,7 @@ impl UnifiedMemTable {
 
         // Remove vector if present
         if let Some(ref vec_map) = self.vectors {
-            if let Some(old_vec) = vec_map.write().remove(&key) {
+            if let Some(old_vec) = vec_map.remove(key) {
                 let vec_size = old_vec.len() * 4;
                 self.size.fetch_sub(vec_size, Ordering::Relaxed);
             }
@@ -427,7 +455,7 @@ impl UnifiedMemTable {
             let shard = self.shards[Self::shard_index(candidate.id)].read();
             if let Some(arc_entry) = shard.get(&candidate.id) {
                 if !arc_entry.deleted {
-                    let vector = vec_map.and_then(|vm| vm.read().get(&candidate.id).cloned());
+                    let vector = vec_map.and_then(|vm| vm.get(candidate.id));
                     results.push((
                         candidate.id,
                         UnifiedEntry {
@@ -457,7 +485,7 @@ impl UnifiedMemTable {
         let items: Vec<(Key, UnifiedEntry)> = all
             .into_iter()
             .map(|(k, arc)| {
-                let vector = vec_map.as_ref().and_then(|vm| vm.read().get(&k).cloned());
+                let vector = vec_map.as_ref().and_then(|vm| vm.get(k));
                 (
                     k,
                     UnifiedEntry {
@@ -486,7 +514,7 @@ impl UnifiedMemTable {
         let vec_map = self.vectors.as_ref();
         all.into_iter()
             .map(|(k, arc)| {
-                let vector = vec_map.and_then(|vm| vm.read().get(&k).cloned());
+                let vector = vec_map.and_then(|vm| vm.get(k));
                 (
                     k,
                     UnifiedEntry {
@@ -516,7 +544,7 @@ impl UnifiedMemTable {
         let results: Vec<(Key, UnifiedEntry)> = all
             .into_iter()
             .map(|(k, arc)| {
-                let vector = vec_map.and_then(|vm| vm.read().get(&k).cloned());
+                let vector = vec_map.and_then(|vm| vm.get(k));
                 (
                     k,
                     UnifiedEntry {
@@ -558,7 +586,7 @@ impl UnifiedMemTable {
         let vec_map = self.vectors.as_ref();
         all.into_iter()
             .map(|(k, arc)| {
-                let vector = vec_map.and_then(|vm| vm.read().get(&k).cloned());
+                let vector = vec_map.and_then(|vm| vm.get(k));
                 (k, arc, vector)
             })
             .collect()
@@ -578,7 +606,7 @@ impl UnifiedMemTable {
         let results: Vec<(Key, UnifiedEntry)> = all
             .into_iter()
             .map(|(k, arc)| {
-                let vector = vec_map.and_then(|vm| vm.read().get(&k).cloned());
+                let vector = vec_map.and_then(|vm| vm.get(k));
                 (
                     k,
                     UnifiedEntry {
@@ -754,6 +782,24 @@ mod tests {
         assert_eq!(memtable.len(), 160);
     }
 
+    #[test]
+    fn test_shard_distribution_strided_keys() {
+        let memtable = create_vector_memtable(2);
+        // Keys sharing their low bits (table_id << 32 | row_id * 16)
+        for i in 0..160u64 {
+            let key = (7 << 32) | (i * 16);
+            let data = ValueData::Inline(Arc::new(vec![i as u8]));
+            memtable
+                .put_with_vector(key, data, vec![i as f32, 1.0], i)
+                .unwrap();
+        }
+        for (i, shard) in memtable.shards.iter().enumerate() {
+            assert!(!shard.read().is_empty(), "Shard {} is empty", i);
+        }
+        let entry = memtable.get((7 << 32) | (5 * 16)).unwrap().unwrap();
+        assert_eq!(entry.vector, Some(vec![5.0, 1.0]));
+    }
+
     #[test]
     fn test_scan_ordering() {
         let memtable = create_memtable();
@@ -1,12 +1,12 @@
 //! Manifest 文件管理和持久化
 
 use super::version::{FileMetadata, FileType, Version, VersionEdit};
+use crate::storage::backend::{self, BackendFile, OpenFlags};
+use crate::storage::failpoint;
 use crate::{Result, StorageError};
 use crc32fast::Hasher;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -28,26 +28,36 @@ pub struct Manifest {
     /// 当前版本
     current_version: Arc<Mutex<Version>>,
     /// Manifest 文件
-    manifest_file: Arc<Mutex<File>>,
+    manifest_file: Arc<Mutex<Box<dyn BackendFile>>>,
     /// 下一个版本号
     next_version: Arc<Mutex<u64>>,
+    /// 当前 Manifest 文件编号 (MANIFEST-xxxxxx)
+    manifest_number: Arc<Mutex<u64>>,
 }
 
 impl Manifest {
     /// 创建或加载 Manifest
     pub fn open(data_dir: impl AsRef<Path>) -> Result<Self> {
         let data_dir = data_dir.as_ref().to_path_buf();
-        fs::create_dir_all(&data_dir)?;
+        let fs = backend::backend_for(&data_dir);
+        fs.create_dir_all(&data_dir)?;
 
         let current_path = data_dir.join("CURRENT");
 
         // 读取 CURRENT 文件获取当前 Manifest
-        let (manifest_number, version) = if current_path.exists() {
-            let manifest_name = fs::read_to_string(&current_path)?;
+        let (manifest_number, version) = if fs.exists(&current_path) {
+            let manifest_name = String::from_utf8(fs.read(&current_path)?)
+                .map_err(|e| StorageError::Corruption(format!("CURRENT: {}", e)))?;
             let manifest_path = data_dir.join(manifest_name.trim());
 
-            // 恢复版本信息
-            let version = Self::recover_version(&manifest_path)?;
+            // 恢复版本信息；最后一个提交之后的残留记录（崩溃时写了一半的编辑）
+            // 必须截掉，否则后续追加的提交会把它们一并生效
+            let (version, committed_len) = Self::recover_version(&manifest_path)?;
+            let file = fs.open(&manifest_path, OpenFlags::read_write())?;
+            if file.len()? > committed_len {
+                file.set_len(committed_len)?;
+                file.sync_all()?;
+            }
 
             // 提取 Manifest 编号
             let manifest_number = manifest_name
@@ -63,15 +73,10 @@ impl Manifest {
         };
 
         let manifest_path = data_dir.join(format!("MANIFEST-{:06}", manifest_number));
-        let manifest_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&manifest_path)?;
+        let manifest_file = fs.open(&manifest_path, OpenFlags::append())?;
 
         // 更新 CURRENT 文件
-        let mut current_file = File::create(&current_path)?;
-        writeln!(current_file, "MANIFEST-{:06}", manifest_number)?;
-        current_file.sync_all()?;
+        Self::write_current(&data_dir, manifest_number)?;
 
         let next_version = version.version_number + 1;
 
@@ -80,17 +85,71 @@ impl Manifest {
             current_version: Arc::new(Mutex::new(version)),
             manifest_file: Arc::new(Mutex::new(manifest_file)),
             next_version: Arc::new(Mutex::new(next_version)),
+            manifest_number: Arc::new(Mutex::new(manifest_number)),
         })
     }
 
-    /// 从 Manifest 文件恢复版本
-    fn recover_version(manifest_path: &Path) -> Result<Version> {
-        let mut file = File::open(manifest_path)?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+    /// 原子更新 CURRENT（临时文件 + rename）
+    fn write_current(data_dir: &Path, manifest_number: u64) -> Result<()> {
+        let current_path = data_dir.join("CURRENT");
+        backend::backend_for(&current_path).write_atomic(
+            &current_path,
+            format!("MANIFEST-{:06}\n", manifest_number).as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Manifest 日志当前大小（字节），用于决定何时 [`Self::compact`]
+    pub fn log_size(&self) -> Result<u64> {
+        Ok(self.manifest_file.lock().len()?)
+    }
+
+    /// 压缩 Manifest 日志
+    ///
+    /// 日志只追加不回收；压缩把当前版本写成新的 `MANIFEST-{n+1}` 快照，
+    /// 切换 CURRENT 后删除旧日志。崩溃时 CURRENT 仍指向某个完整的日志。
+    pub fn compact(&self) -> Result<()> {
+        let version = self.current_version.lock();
+        let mut file = self.manifest_file.lock();
+        let mut number = self.manifest_number.lock();
+
+        let new_number = *number + 1;
+        let new_path = self.data_dir.join(format!("MANIFEST-{:06}", new_number));
+        let fs = backend::backend_for(&new_path);
+        let mut new_file = fs.open(&new_path, OpenFlags::create_truncate())?;
+
+        let mut records: Vec<ManifestRecord> = version
+            .files
+            .values()
+            .flatten()
+            .map(|meta| ManifestRecord::AddFile(meta.clone()))
+            .collect();
+        records.push(ManifestRecord::VersionCommit {
+            version: version.version_number,
+        });
+        for record in &records {
+            Self::append_record(&mut new_file, record)?;
+        }
+        failpoint::check(failpoint::MANIFEST_SYNC)?;
+        new_file.sync_all()?;
+        drop(new_file);
+
+        // CURRENT 切换是提交点
+        Self::write_current(&self.data_dir, new_number)?;
+        let _ = fs.remove_file(&self.data_dir.join(format!("MANIFEST-{:06}", *number)));
+
+        *file = fs.open(&new_path, OpenFlags::append().no_create())?;
+        *number = new_number;
+        Ok(())
+    }
+
+    /// 从 Manifest 文件恢复版本，同时返回最后一个提交记录的结束位置
+    fn recover_version(manifest_path: &Path) -> Result<(Version, u64)> {
+        let buffer = backend::backend_for(manifest_path).read(manifest_path)?;
 
         let mut current_version = Version::new(0);
         let mut last_committed_version = Version::new(0);
+        let mut committed_len = 0usize;
 
         // 使用 bincode 反序列化记录列表
         // 格式：每条记录的长度(u32) + 记录数据
@@ -128,6 +187,7 @@ impl Manifest {
                         // 提交当前版本
                         current_version.version_number = *version;
                         last_committed_version = current_version.clone();
+                        committed_len = offset + len;
                     }
                 }
             }
@@ -135,7 +195,7 @@ impl Manifest {
         }
 
         // 返回最后一个提交的版本（崩溃前的完整版本）
-        Ok(last_committed_version)
+        Ok((last_committed_version, committed_len as u64))
     }
 
     /// 获取当前版本（只读）
@@ -154,12 +214,13 @@ impl Manifest {
             let file_path = self.data_dir.join(&meta.path);
 
             // 检查文件存在
-            if !file_path.exists() {
+            let backend = backend::backend_for(&file_path);
+            if !backend.exists(&file_path) {
                 return Err(StorageError::FileNotFound(file_path));
             }
 
             // 验证文件大小
-            let actual_size = fs::metadata(&file_path).map_err(StorageError::Io)?.len();
+            let actual_size = backend.file_len(&file_path).map_err(StorageError::Io)?;
 
             if actual_size != meta.size {
                 return Err(StorageError::Corruption(format!(
@@ -175,18 +236,49 @@ impl Manifest {
             }
         }
 
+        self.apply_verified_edit(edit)
+    }
+
+    /// 应用调用方已校验过的版本编辑
+    ///
+    /// 调用方在持锁期间自行计算了文件大小和校验码（锁释放后文件可能再次变化）；
+    /// 此处不再重新读取文件，之后的变化由恢复时的校验发现。
+    pub fn apply_verified_edit(&self, edit: VersionEdit) -> Result<u64> {
+        if edit.is_empty() {
+            return Ok(self.current_version.lock().version_number);
+        }
+
         let mut version = self.current_version.lock();
         let mut file = self.manifest_file.lock();
         let mut next_ver = self.next_version.lock();
 
+        // 写入失败时截回编辑前的长度，否则残留记录会随下一次提交一并生效
+        let committed_len = file.len()?;
+        if let Err(e) = Self::write_edit(&mut **file, &edit, *next_ver) {
+            let _ = file.set_len(committed_len);
+            return Err(e);
+        }
+
+        // Step 7: 更新内存中的版本
+        for meta in &edit.add_files {
+            version.add_file(meta.clone());
+        }
+        for (file_id, file_type) in &edit.delete_files {
+            version.delete_file(*file_id, file_type);
+        }
+        version.version_number = *next_ver;
+
+        let committed_version = *next_ver;
+        *next_ver += 1;
+
+        Ok(committed_version)
+    }
+
+    /// 写入编辑记录与提交记录（Step 2-6）
+    fn write_edit(file: &mut dyn BackendFile, edit: &VersionEdit, version: u64) -> Result<()> {
         // Step 2: 写入添加文件记录
         for meta in &edit.add_files {
-            let record = ManifestRecord::AddFile(meta.clone());
-            let data = bincode::serialize(&record)
-                .map_err(|e| StorageError::Serialization(e.to_string()))?;
-            // 写入记录长度 + 数据
-            file.write_all(&(data.len() as u32).to_le_bytes())?;
-            file.write_all(&data)?;
+            Self::append_record(file, &ManifestRecord::AddFile(meta.clone()))?;
         }
 
         // Step 3: 写入删除文件记录
@@ -195,43 +287,38 @@ impl Manifest {
                 file_id: *file_id,
                 file_type: file_type.clone(),
             };
-            let data = bincode::serialize(&record)
-                .map_err(|e| StorageError::Serialization(e.to_string()))?;
-            file.write_all(&(data.len() as u32).to_le_bytes())?;
-            file.write_all(&data)?;
+            Self::append_record(file, &record)?;
         }
 
         // Step 4: fsync（确保元数据写入）
+        failpoint::check(failpoint::MANIFEST_SYNC)?;
         file.sync_all()?;
 
         // Step 5: 写入版本提交标记（原子性边界）
-        let commit_record = ManifestRecord::VersionCommit { version: *next_ver };
-        let data = bincode::serialize(&commit_record)
-            .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        file.write_all(&(data.len() as u32).to_le_bytes())?;
-        file.write_all(&data)?;
+        let commit_record = ManifestRecord::VersionCommit { version };
+        Self::append_record(file, &commit_record)?;
 
         // Step 6: fsync 提交记录
+        failpoint::check(failpoint::MANIFEST_SYNC)?;
         file.sync_all()?;
 
-        // Step 7: 更新内存中的版本
-        for meta in &edit.add_files {
-            version.add_file(meta.clone());
-        }
-        for (file_id, file_type) in &edit.delete_files {
-            version.delete_file(*file_id, file_type);
-        }
-        version.version_number = *next_ver;
-
-        let committed_version = *next_ver;
-        *next_ver += 1;
+        Ok(())
+    }
 
-        Ok(committed_version)
+    /// 写入一条记录：长度（u32 LE）+ bincode 数据
+    fn append_record(file: &mut dyn BackendFile, record: &ManifestRecord) -> Result<()> {
+        let data =
+            bincode::serialize(record).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let mut buf = Vec::with_capacity(4 + data.len());
+        buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&data);
+        failpoint::write_all(failpoint::MANIFEST_WRITE, file, &buf)?;
+        Ok(())
     }
 
     /// 计算文件的 CRC32 校验码
-    fn calculate_checksum(path: &Path) -> Result<u32> {
-        let mut file = File::open(path)?;
+    pub fn calculate_checksum(path: &Path) -> Result<u32> {
+        let mut file = backend::open_file(path, OpenFlags::read_only())?;
         let mut hasher = Hasher::new();
         let mut buffer = vec![0u8; 65536]; // 64KB buffer
 
@@ -282,6 +369,7 @@ mod tests {
             min_key: Some(0),
             max_key: Some(100),
             level: Some(0),
+            modified: None,
         });
         edit.add_file(FileMetadata {
             file_id: 1,
@@ -292,6 +380,7 @@ mod tests {
             min_key: None,
             max_key: None,
             level: None,
+            modified: None,
         });
         edit.add_file(FileMetadata {
             file_id: 1,
@@ -302,6 +391,7 @@ mod tests {
             min_key: None,
             max_key: None,
             level: None,
+            modified: None,
         });
 
         let v1 = manifest.apply_edit(edit).unwrap();
@@ -336,6 +426,7 @@ mod tests {
                 min_key: Some(0),
                 max_key: Some(100),
                 level: Some(0),
+                modified: None,
             });
             manifest.apply_edit(edit).unwrap();
         }
@@ -350,4 +441,107 @@ mod tests {
             assert_eq!(version.files[&FileType::SSTable].len(), 1);
         }
     }
+
+    #[test]
+    fn test_compact_keeps_committed_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("column_idx.idx");
+        let manifest = Manifest::open(temp_dir.path()).unwrap();
+
+        // 同一文件反复替换：日志增长，但版本中只有最后一个
+        for i in 0..5u64 {
+            std::fs::write(&path, vec![i as u8; 64]).unwrap();
+            let mut edit = VersionEdit::new();
+            if i > 0 {
+                edit.delete_file(i - 1, FileType::BTreeIndex);
+            }
+            edit.add_file(FileMetadata {
+                file_id: i,
+                file_type: FileType::BTreeIndex,
+                path: "column_idx.idx".to_string(),
+                size: 64,
+                checksum: Manifest::calculate_checksum(&path).unwrap(),
+                min_key: None,
+                max_key: None,
+                level: None,
+                modified: None,
+            });
+            manifest.apply_edit(edit).unwrap();
+        }
+        let before = manifest.log_size().unwrap();
+        manifest.compact().unwrap();
+        assert!(manifest.log_size().unwrap() < before);
+        assert!(!temp_dir.path().join("MANIFEST-000001").exists());
+
+        // 压缩后继续追加，重新打开仍是最新版本
+        std::fs::write(&path, vec![9u8; 64]).unwrap();
+        let mut edit = VersionEdit::new();
+        edit.delete_file(4, FileType::BTreeIndex);
+        edit.add_file(FileMetadata {
+            file_id: 5,
+            file_type: FileType::BTreeIndex,
+            path: "column_idx.idx".to_string(),
+            size: 64,
+            checksum: Manifest::calculate_checksum(&path).unwrap(),
+            min_key: None,
+            max_key: None,
+            level: None,
+            modified: None,
+        });
+        manifest.apply_edit(edit).unwrap();
+        drop(manifest);
+
+        let manifest = Manifest::open(temp_dir.path()).unwrap();
+        let version = manifest.current_version();
+        assert_eq!(version.version_number, 6);
+        let files = &version.files[&FileType::BTreeIndex];
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file_id, 5);
+    }
+
+    #[test]
+    fn test_uncommitted_tail_is_discarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let meta = |id: u64, name: &str| FileMetadata {
+            file_id: id,
+            file_type: FileType::VectorIndex,
+            path: name.to_string(),
+            size: 8,
+            checksum: 0,
+            min_key: None,
+            max_key: None,
+            level: None,
+            modified: None,
+        };
+
+        // 第一次运行：一个已提交的编辑，之后崩溃在半个编辑上（没有提交记录）
+        {
+            let manifest = Manifest::open(temp_dir.path()).unwrap();
+            let mut edit = VersionEdit::new();
+            edit.add_file(meta(1, "a"));
+            manifest.apply_verified_edit(edit).unwrap();
+
+            let data = bincode::serialize(&ManifestRecord::AddFile(meta(2, "torn"))).unwrap();
+            let mut file = manifest.manifest_file.lock();
+            file.write_all(&(data.len() as u32).to_le_bytes()).unwrap();
+            file.write_all(&data).unwrap();
+        }
+
+        // 重启后再提交：残留的记录不能随之生效
+        {
+            let manifest = Manifest::open(temp_dir.path()).unwrap();
+            let mut edit = VersionEdit::new();
+            edit.add_file(meta(3, "b"));
+            manifest.apply_verified_edit(edit).unwrap();
+        }
+
+        let manifest = Manifest::open(temp_dir.path()).unwrap();
+        let mut paths: Vec<String> = manifest
+            .current_version()
+            .all_file_names()
+            .into_iter()
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["a", "b"]);
+    }
 }
@@ -45,6 +45,8 @@ pub struct FileMetadata {
     pub max_key: Option<u64>,
     /// LSM Level (对于 SSTable)
     pub level: Option<u32>,
+    /// 修改时间（UNIX 纳秒），大小与其都未变时可跳过校验和重算
+    pub modified: Option<u64>,
 }
 
 impl FileMetadata {
@@ -2,17 +2,38 @@
 //!
 //! Manages physical data storage using LSM-Tree architecture
 //! plus Columnar Segment Store for time-series data.
+//!
+//! Every on-disk format is architecture independent: multi-byte integers
+//! and floats are little-endian (LSM keys and index keys big-endian, so
+//! they sort bytewise), sizes are fixed-width rather than `usize`, and each
+//! file starts with a magic and a format version that readers check before
+//! trusting the rest. A database written on an ARM robot opens on an x86
+//! workstation and vice versa.
 
+pub mod backend;
 pub mod checksum;
 pub mod col_segment;
 pub mod columnar;
+pub mod failpoint;
+pub mod fault;
 pub mod file_manager;
+#[cfg(target_family = "wasm")]
+pub mod host_vfs;
 pub mod lsm;
 pub mod manifest;
+pub mod packed;
+pub mod platform;
 pub mod row_format;
+pub mod sync_policy;
+pub mod value_codec;
 
+pub use backend::{MemoryBackend, StdFsBackend, StorageBackend};
 pub use checksum::{Checksum, ChecksumError, ChecksumType};
 pub use columnar::ColumnarStore;
-pub use file_manager::{FileHandle, FileRefManager};
+pub use fault::FaultInjectionBackend;
+pub use file_manager::{FileHandle, FilePin, FileRefManager};
+#[cfg(target_family = "wasm")]
+pub use host_vfs::HostVfsBackend;
 pub use lsm::{LSMConfig, LSMEngine, MemTable, SSTable};
 pub use manifest::{FileMetadata, FileType, Manifest};
+pub use sync_policy::SyncPolicyBackend;
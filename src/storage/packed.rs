@@ -0,0 +1,535 @@
+//! Packed database images
+//!
+//! A packed image bundles every file of a database directory (catalog, WAL,
+//! SSTables, blob files, index files) into one immutable `.motedb` file so a
+//! map or an embedding corpus can be shipped as a single firmware asset.
+//!
+//! ## Layout
+//! ```text
+//! [header, 4 KiB]  magic "MOTEPACK" | version u32 | entries u32
+//!                  | toc offset u64 | toc length u64 | toc crc32 u32
+//! [file data]      each file starts on a 4 KiB boundary
+//! [toc]            bincode Vec<PackedEntry> (relative path, offset, len, crc32)
+//! ```
+//!
+//! [`PackedImage`] memory-maps an image and hands out zero-copy slices of
+//! its files. [`PackedBackend`] serves the files routed through the
+//! [`StorageBackend`] (WAL, catalog, index metadata) straight from the
+//! mapping and refuses to modify them; see
+//! [`MoteDB::open_packed`](crate::MoteDB::open_packed).
+
+use super::backend::{BackendFile, OpenFlags, StdFsBackend, StorageBackend};
+use crate::{Result, StorageError};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const MAGIC: &[u8; 8] = b"MOTEPACK";
+const VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 4096;
+const ALIGN: u64 = 4096;
+
+/// One file inside a packed image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PackedEntry {
+    /// Path relative to the database directory, `/`-separated
+    pub path: String,
+    pub offset: u64,
+    pub len: u64,
+    pub crc32: u32,
+}
+
+/// What [`pack_directory`] wrote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackStats {
+    pub files: usize,
+    /// Total size of the packed files
+    pub data_bytes: u64,
+    /// Size of the image including header, alignment and table of contents
+    pub image_bytes: u64,
+}
+
+/// Bundle every file under `dir` (recursively, skipping names for which
+/// `skip` returns true) into an image at `dest`. The image is written to a
+/// temporary file and renamed into place.
+pub fn pack_directory(dir: &Path, dest: &Path, skip: impl Fn(&Path) -> bool) -> Result<PackStats> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &skip, &mut files)?;
+    files.sort();
+
+    let tmp = dest.with_extension("motedb.tmp");
+    let mut out = File::create(&tmp)?;
+    let mut entries = Vec::with_capacity(files.len());
+    let mut offset = HEADER_SIZE;
+    let mut stats = PackStats::default();
+    for rel in files {
+        let data = std::fs::read(dir.join(&rel))?;
+        out.seek(SeekFrom::Start(offset))?;
+        out.write_all(&data)?;
+        entries.push(PackedEntry {
+            path: rel.to_string_lossy().replace('\\', "/"),
+            offset,
+            len: data.len() as u64,
+            crc32: crc32fast::hash(&data),
+        });
+        stats.files += 1;
+        stats.data_bytes += data.len() as u64;
+        offset = (offset + data.len() as u64).div_ceil(ALIGN) * ALIGN;
+    }
+
+    let toc = bincode::serialize(&entries)
+        .map_err(|e| StorageError::Serialization(format!("packed image toc: {}", e)))?;
+    out.seek(SeekFrom::Start(offset))?;
+    out.write_all(&toc)?;
+
+    let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    header.extend_from_slice(&offset.to_le_bytes());
+    header.extend_from_slice(&(toc.len() as u64).to_le_bytes());
+    header.extend_from_slice(&crc32fast::hash(&toc).to_le_bytes());
+    header.resize(HEADER_SIZE as usize, 0);
+    out.seek(SeekFrom::Start(0))?;
+    out.write_all(&header)?;
+    out.sync_all()?;
+    drop(out);
+
+    std::fs::rename(&tmp, dest)?;
+    crate::fsync_dir(dest);
+    stats.image_bytes = offset + toc.len() as u64;
+    Ok(stats)
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    skip: &impl Fn(&Path) -> bool,
+    out: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if skip(&rel) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(root, &path, skip, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// A memory-mapped packed image
+pub struct PackedImage {
+    path: PathBuf,
+    mmap: Mmap,
+    entries: Vec<PackedEntry>,
+}
+
+impl PackedImage {
+    /// Map an image and read its table of contents
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        // SAFETY: images are immutable once written (pack renames a
+        // complete file into place and nothing writes to it afterwards)
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let corrupt =
+            |what: &str| StorageError::InvalidData(format!("{}: {}", path.display(), what));
+        if mmap.len() < HEADER_SIZE as usize || &mmap[..8] != MAGIC {
+            return Err(corrupt("not a packed MoteDB image"));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(mmap[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(mmap[at..at + 8].try_into().unwrap());
+        if u32_at(8) != VERSION {
+            return Err(corrupt(&format!("unsupported image version {}", u32_at(8))));
+        }
+        let (count, toc_offset, toc_len, toc_crc) =
+            (u32_at(12), u64_at(16), u64_at(24), u32_at(32));
+        let toc = toc_offset
+            .checked_add(toc_len)
+            .filter(|end| *end <= mmap.len() as u64)
+            .map(|end| &mmap[toc_offset as usize..end as usize])
+            .ok_or_else(|| corrupt("truncated image"))?;
+        if crc32fast::hash(toc) != toc_crc {
+            return Err(corrupt("table of contents checksum mismatch"));
+        }
+        let entries: Vec<PackedEntry> = bincode::deserialize(toc)
+            .map_err(|e| StorageError::Serialization(format!("packed image toc: {}", e)))?;
+        if entries.len() != count as usize
+            || entries.iter().any(|e| {
+                e.offset
+                    .checked_add(e.len)
+                    .is_none_or(|end| end > toc_offset)
+            })
+        {
+            return Err(corrupt("table of contents does not match the image"));
+        }
+        Ok(Self {
+            path,
+            mmap,
+            entries,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[PackedEntry] {
+        &self.entries
+    }
+
+    /// Contents of the file at `path` (relative, `/`-separated), borrowed
+    /// from the mapping
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        self.entry(path).map(|e| self.data(e))
+    }
+
+    fn entry(&self, path: &str) -> Option<&PackedEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    fn data(&self, entry: &PackedEntry) -> &[u8] {
+        &self.mmap[entry.offset as usize..(entry.offset + entry.len) as usize]
+    }
+
+    /// Check every file against its checksum
+    pub fn verify(&self) -> Result<()> {
+        for entry in &self.entries {
+            if crc32fast::hash(self.data(entry)) != entry.crc32 {
+                return Err(StorageError::InvalidData(format!(
+                    "{}: checksum mismatch in '{}'",
+                    self.path.display(),
+                    entry.path
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the files for which `filter` returns true under `dir`. Files
+    /// already there with the same size and checksum are left alone, so a
+    /// directory unpacked on a previous boot is reused.
+    pub fn unpack_to(&self, dir: &Path, filter: impl Fn(&str) -> bool) -> Result<usize> {
+        let mut written = 0;
+        for entry in self.entries.iter().filter(|e| filter(&e.path)) {
+            let target = dir.join(&entry.path);
+            let data = self.data(entry);
+            let current = std::fs::read(&target).ok();
+            if current.is_some_and(|c| c.len() == data.len() && crc32fast::hash(&c) == entry.crc32)
+            {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, data)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+impl fmt::Debug for PackedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackedImage")
+            .field("path", &self.path)
+            .field("files", &self.entries.len())
+            .finish()
+    }
+}
+
+/// Whether a database file is routed through the [`StorageBackend`] (and so
+/// can be served from the image instead of the directory)
+pub(crate) fn is_backend_file(rel: &str) -> bool {
+    rel.starts_with("wal/")
+        || rel.starts_with("catalog.bin")
+        || rel.starts_with("index_metadata.bin")
+}
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "packed database image is read-only",
+    )
+}
+
+/// Read-only [`StorageBackend`] over a [`PackedImage`] mounted at `root`.
+/// Backend-routed files under `root` come from the image; anything else
+/// goes to the host filesystem.
+#[derive(Debug)]
+pub struct PackedBackend {
+    image: Arc<PackedImage>,
+    root: PathBuf,
+    fs: StdFsBackend,
+}
+
+impl PackedBackend {
+    pub fn new(image: Arc<PackedImage>, root: impl Into<PathBuf>) -> Self {
+        Self {
+            image,
+            root: root.into(),
+            fs: StdFsBackend,
+        }
+    }
+
+    /// Path relative to the mount point if it belongs to the image
+    fn packed_path(&self, path: &Path) -> Option<String> {
+        let rel = path.strip_prefix(&self.root).ok()?;
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        (is_backend_file(&rel) || rel == "wal").then_some(rel)
+    }
+}
+
+impl StorageBackend for PackedBackend {
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+        let Some(rel) = self.packed_path(path) else {
+            return self.fs.open(path, flags);
+        };
+        match self.image.entry(&rel) {
+            Some(entry) if !flags.truncate => Ok(Box::new(PackedFile {
+                image: self.image.clone(),
+                offset: entry.offset,
+                len: entry.len,
+                pos: 0,
+            })),
+            Some(_) => Err(read_only_error()),
+            None if flags.create => Err(read_only_error()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{}: not in packed image", path.display()),
+            )),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        match self.packed_path(path) {
+            Some(_) => Ok(()),
+            None => self.fs.create_dir_all(path),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if self.packed_path(from).is_some() || self.packed_path(to).is_some() {
+            return Err(read_only_error());
+        }
+        self.fs.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        match self.packed_path(path) {
+            Some(_) => Err(read_only_error()),
+            None => self.fs.remove_file(path),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        match self.packed_path(path) {
+            Some(rel) => {
+                let dir = format!("{}/", rel);
+                self.image
+                    .entries
+                    .iter()
+                    .any(|e| e.path == rel || e.path.starts_with(&dir))
+            }
+            None => self.fs.exists(path),
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        match self.packed_path(path) {
+            Some(rel) => {
+                let dir = format!("{}/", rel);
+                self.image.entries.iter().any(|e| e.path.starts_with(&dir))
+            }
+            None => self.fs.is_dir(path),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        match self.packed_path(path) {
+            Some(_) => Err(read_only_error()),
+            None => self.fs.remove_dir_all(path),
+        }
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        match self.packed_path(path) {
+            Some(rel) => self
+                .image
+                .entry(&rel)
+                .map(|e| e.len)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, rel)),
+            None => self.fs.file_len(path),
+        }
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        match self.packed_path(path) {
+            Some(_) => None,
+            None => self.fs.modified(path),
+        }
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let Some(rel) = self.packed_path(path) else {
+            return self.fs.list_dir(path);
+        };
+        let dir = format!("{}/", rel);
+        let mut out: Vec<PathBuf> = self
+            .image
+            .entries
+            .iter()
+            .filter_map(|e| e.path.strip_prefix(&dir))
+            .map(|child| path.join(child.split('/').next().unwrap_or(child)))
+            .collect();
+        out.sort();
+        out.dedup();
+        Ok(out)
+    }
+}
+
+/// A file inside the image; reads come from the mapping, writes fail
+struct PackedFile {
+    image: Arc<PackedImage>,
+    offset: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl Read for PackedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = self.pos.min(self.len);
+        let n = buf.len().min((self.len - start) as usize);
+        let at = (self.offset + start) as usize;
+        buf[..n].copy_from_slice(&self.image.mmap[at..at + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for PackedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        Err(read_only_error())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for PackedFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(p) => p as i64,
+            SeekFrom::End(off) => self.len as i64 + off,
+            SeekFrom::Current(off) => self.pos as i64 + off,
+        };
+        if new_pos < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before start of file",
+            ));
+        }
+        self.pos = new_pos as u64;
+        Ok(self.pos)
+    }
+}
+
+impl BackendFile for PackedFile {
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.len)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        if size == self.len {
+            return Ok(());
+        }
+        Err(read_only_error())
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if offset + buf.len() as u64 > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        let at = (self.offset + offset) as usize;
+        buf.copy_from_slice(&self.image.mmap[at..at + buf.len()]);
+        Ok(())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pack_and_map_files() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("db.mote");
+        std::fs::create_dir_all(src.join("wal")).unwrap();
+        std::fs::write(src.join("catalog.bin"), b"catalog").unwrap();
+        std::fs::write(src.join("wal/partition_0.wal"), vec![7u8; 5000]).unwrap();
+        std::fs::write(src.join(".lock"), b"").unwrap();
+
+        let dest = dir.path().join("db.motedb");
+        let stats = pack_directory(&src, &dest, |rel| rel == Path::new(".lock")).unwrap();
+        assert_eq!((stats.files, stats.data_bytes), (2, 5007));
+
+        let image = Arc::new(PackedImage::open(&dest).unwrap());
+        image.verify().unwrap();
+        assert_eq!(image.file("catalog.bin"), Some(&b"catalog"[..]));
+        assert!(image.entries().iter().all(|e| e.offset % ALIGN == 0));
+        assert!(image.file(".lock").is_none());
+
+        let root = dir.path().join("mount");
+        let backend = PackedBackend::new(image, &root);
+        assert!(backend.exists(&root.join("wal")));
+        assert_eq!(
+            backend.list_dir(&root.join("wal")).unwrap(),
+            vec![root.join("wal/partition_0.wal")]
+        );
+        assert_eq!(backend.read(&root.join("catalog.bin")).unwrap(), b"catalog");
+        let mut wal = backend
+            .open(&root.join("wal/partition_0.wal"), OpenFlags::append())
+            .unwrap();
+        assert_eq!(wal.len().unwrap(), 5000);
+        assert_eq!(
+            wal.write_all(b"x").unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        assert!(backend
+            .write_atomic(&root.join("catalog.bin"), b"new")
+            .is_err());
+
+        // A damaged header is rejected
+        let mut bytes = std::fs::read(&dest).unwrap();
+        bytes[32] ^= 0xFF;
+        std::fs::write(&dest, bytes).unwrap();
+        assert!(PackedImage::open(&dest).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Platform shims for file I/O
+//!
+//! The default storage backend is plain `std::fs`, which on
+//! `wasm32-wasip1(-threads)` is provided by the WASI host. The few
+//! OS-specific calls we rely on are funnelled through here so that the
+//! `std::os::unix` imports don't leak into index code.
+//!
+//! CI builds both wasm targets. Browser storage (OPFS, IndexedDB) is reached
+//! through the wasm-only `storage::HostVfsBackend` rather than through the
+//! WASI shim.
+//!
+//! ## Positional reads
+//! B+Tree and i-Octree page reads use `pread` so concurrent readers never
+//! disturb each other's file cursor. `std::os::unix::fs::FileExt` provides
+//! that on Unix, `std::os::windows::fs::FileExt::seek_read` on Windows and
+//! wasi-libc's `pread` (`fd_pread`) on WASI. Any other target falls back to
+//! seek + read on the shared handle under a process-wide mutex; that
+//! fallback moves the handle's cursor, so a handle read through both `Read`
+//! and `read_exact_at` must not rely on its cursor between calls.
+//!
+//! ## Threads
+//! `wasm32-wasip1` has no threads, and whether `wasm32-wasip1-threads` has
+//! them depends on the host implementing `wasi-threads`. Every background
+//! worker (memtable flush, compaction, WAL group commit, index builds,
+//! auto-checkpoint, fsync sweeper) asks [`threads_available`] first and,
+//! when it says no, does its work inline on the calling thread instead.
+//!
+//! ## Compression
+//! libzstd is C and needs a wasm-capable clang to cross-compile, so wasm
+//! builds leave it out and [`zstd`] reports `Unsupported` instead. Every
+//! writer already falls back to storing a block uncompressed (or as
+//! Snappy) when compression fails, so wasm databases simply never contain
+//! zstd blocks; opening a zstd-compressed file written by a native build
+//! fails with a clear error rather than returning garbage.
+
+use std::fs::File;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Portable `read_exact_at` (positional read that does not rely on the
+/// caller owning the file cursor).
+pub trait PositionalRead {
+    /// Read exactly `buf.len()` bytes starting at `offset`.
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+}
+
+impl PositionalRead for File {
+    #[cfg(unix)]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "wasi")]
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+        while !buf.is_empty() {
+            // wasi-libc's pread maps onto `fd_pread`, which leaves the cursor alone
+            let n = unsafe {
+                libc::pread(
+                    self.as_raw_fd(),
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                    offset as libc::off_t,
+                )
+            };
+            match n {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                n if n > 0 => {
+                    buf = &mut buf[n as usize..];
+                    offset += n as u64;
+                }
+                _ => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(unix, windows, target_os = "wasi")))]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        use std::io::{Read, Seek, SeekFrom};
+        // Without pread another reader could seek between our seek and read
+        static SEEK_READ: parking_lot::Mutex<()> = parking_lot::Mutex::new(());
+        let _guard = SEEK_READ.lock();
+        // `&File` implements Read + Seek, so a shared handle is enough.
+        let mut f = self;
+        f.seek(SeekFrom::Start(offset))?;
+        f.read_exact(buf)
+    }
+}
+
+static THREADS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether this process can start threads. Native targets always can; on
+/// wasm the first call probes with a no-op thread and the answer is cached.
+pub fn threads_available() -> bool {
+    if THREADS_DISABLED.load(Ordering::Relaxed) {
+        return false;
+    }
+    #[cfg(target_family = "wasm")]
+    {
+        static PROBED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *PROBED.get_or_init(|| {
+            std::thread::Builder::new()
+                .spawn(|| {})
+                .is_ok_and(|h| h.join().is_ok())
+        })
+    }
+    #[cfg(not(target_family = "wasm"))]
+    true
+}
+
+/// Run every background worker started from now on inline, as on a wasm
+/// host without threads. Workers that are already running keep running;
+/// this is meant for tests that exercise the wasm code paths natively.
+pub fn disable_threads() {
+    THREADS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// The subset of the `zstd` crate the storage layer uses
+#[cfg(not(target_family = "wasm"))]
+pub mod zstd {
+    pub use ::zstd::{bulk, decode_all, encode_all};
+}
+
+/// Stand-in for the `zstd` crate on wasm, where libzstd is not linked
+#[cfg(target_family = "wasm")]
+pub mod zstd {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "zstd is not available in wasm builds",
+        )
+    }
+
+    pub fn encode_all<R: io::Read>(_source: R, _level: i32) -> io::Result<Vec<u8>> {
+        Err(unsupported())
+    }
+
+    pub fn decode_all<R: io::Read>(_source: R) -> io::Result<Vec<u8>> {
+        Err(unsupported())
+    }
+
+    pub mod bulk {
+        use std::io;
+
+        pub fn compress(_data: &[u8], _level: i32) -> io::Result<Vec<u8>> {
+            Err(super::unsupported())
+        }
+
+        pub fn decompress(_data: &[u8], _capacity: usize) -> io::Result<Vec<u8>> {
+            Err(super::unsupported())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_read_exact_at_offsets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pread.bin");
+        let mut f = File::create(&path).unwrap();
+        f.write_all(&(0u8..64).collect::<Vec<_>>()).unwrap();
+        drop(f);
+
+        let f = File::open(&path).unwrap();
+        let mut buf = [0u8; 4];
+        f.read_exact_at(&mut buf, 10).unwrap();
+        assert_eq!(buf, [10, 11, 12, 13]);
+        f.read_exact_at(&mut buf, 0).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3]);
+
+        let err = f.read_exact_at(&mut buf, 62).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
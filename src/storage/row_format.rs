@@ -14,7 +14,12 @@
 //! [var_col_entries]               — (col_idx: u16, offset: u16, len: u16) per var col
 //! [var_data_pool]                 — actual bytes for Text/Vector/etc
 //! ```
+//!
+//! Text is stored as UTF-8 and Vector as `[dim: u16][f32; dim]`; any other
+//! value uses the versioned encoding in [`super::value_codec`], as do whole
+//! rows that do not fit this layout.
 
+use super::value_codec;
 use crate::types::ColumnType;
 use crate::types::{ArcString, ArcVec, Row, Timestamp, Value};
 use crate::{Result, StorageError};
@@ -191,7 +196,16 @@ impl SchemaDecodeContext {
                     var_col_count += 1;
                     col_decoders.push(ColDecoder::VarText);
                 }
-                ColumnType::Tensor(_) | ColumnType::Spatial => {
+                ColumnType::Tensor(_)
+                | ColumnType::Spatial
+                | ColumnType::Decimal { .. }
+                | ColumnType::Uuid
+                | ColumnType::Date
+                | ColumnType::Time
+                | ColumnType::Array(_)
+                | ColumnType::ShapedTensor(_)
+                | ColumnType::EncodedVector { .. }
+                | ColumnType::Enum(_) => {
                     var_col_count += 1;
                     col_decoders.push(ColDecoder::VarGeneric);
                 }
@@ -232,8 +246,7 @@ impl SchemaDecodeContext {
         if !self.skip_magic_check {
             if data.len() < HEADER_SIZE || !is_rawrow(data) {
                 // Fallback to bincode
-                let row: Vec<Value> = bincode::deserialize(data)
-                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                let row: Vec<Value> = value_codec::decode_row(data)?;
                 *out = row;
                 return Ok(());
             }
@@ -374,13 +387,12 @@ impl SchemaDecodeContext {
     }
 
     /// Decode a VarGeneric column value (Tensor/Vector/Spatial).
-    /// Tries in order: 0xFF-tagged bincode → vector format (dim+floats) → bincode fallback.
+    /// Tries in order: tagged value (current or legacy 0xFF) → vector format
+    /// (dim+floats) → legacy bincode fallback.
     pub(crate) fn decode_var_generic(var_data: &[u8]) -> Result<Value> {
-        // 1. Tagged bincode (0xFF prefix)
-        if !var_data.is_empty() && var_data[0] == 0xFF {
-            if let Ok(v) = bincode::deserialize::<Value>(&var_data[1..]) {
-                return Ok(v);
-            }
+        // 1. Tagged value
+        if let Some(v) = value_codec::decode_generic(var_data) {
+            return v;
         }
         // 2. Vector format: [dim: u16] + f32 array
         if var_data.len() >= 2 {
@@ -403,8 +415,7 @@ impl SchemaDecodeContext {
             }
         }
         // 3. Fallback: plain bincode
-        bincode::deserialize::<Value>(var_data)
-            .map_err(|e| StorageError::Serialization(e.to_string()))
+        value_codec::decode_legacy_value(var_data)
     }
 
     /// - Pre-computed `fixed_idx_map` avoids per-column fixed_idx counter
@@ -413,8 +424,7 @@ impl SchemaDecodeContext {
         // Fast path: skip magic check when data is from our own encode()
         if !self.skip_magic_check {
             if data.len() < HEADER_SIZE || !is_rawrow(data) {
-                return bincode::deserialize(data)
-                    .map_err(|e| StorageError::Serialization(e.to_string()));
+                return value_codec::decode_row(data);
             }
         } else if data.len() < HEADER_SIZE {
             return Err(StorageError::InvalidData("Row data too short".into()));
@@ -632,7 +642,16 @@ impl ColumnarRowSet {
                 ColumnType::Text => ColumnArray::Texts(Vec::new()),
                 ColumnType::Timestamp => ColumnArray::Timestamps(Vec::new()),
                 ColumnType::Boolean => ColumnArray::Bools(Vec::new()),
-                ColumnType::Tensor(_) | ColumnType::Spatial => ColumnArray::Values(Vec::new()),
+                ColumnType::Tensor(_)
+                | ColumnType::Spatial
+                | ColumnType::Decimal { .. }
+                | ColumnType::Uuid
+                | ColumnType::Date
+                | ColumnType::Time
+                | ColumnType::Array(_)
+                | ColumnType::ShapedTensor(_)
+                | ColumnType::EncodedVector { .. }
+                | ColumnType::Enum(_) => ColumnArray::Values(Vec::new()),
             })
             .collect();
         Self {
@@ -716,8 +735,7 @@ pub fn decode_row_into_columns(
     // Fast path: skip magic check when data is from our own encode()
     if !ctx.skip_magic_check {
         if data.len() < HEADER_SIZE || !is_rawrow(data) {
-            let row: Vec<Value> = bincode::deserialize(data)
-                .map_err(|e| StorageError::Serialization(e.to_string()))?;
+            let row: Vec<Value> = value_codec::decode_row(data)?;
             for (i, val) in row.into_iter().enumerate() {
                 push_value_to_column(&mut col_data[i], val);
             }
@@ -837,8 +855,9 @@ pub fn decode_row_into_columns(
                     var_idx += 1;
                     let abs_off = var_data_start + v_off;
                     if abs_off + v_len <= data.len() {
-                        let val: Value = bincode::deserialize(&data[abs_off..abs_off + v_len])
-                            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                        let val = SchemaDecodeContext::decode_var_generic(
+                            &data[abs_off..abs_off + v_len],
+                        )?;
                         if let ColumnArray::Values(ref mut v) = col_arr {
                             v.push(val);
                         }
@@ -935,11 +954,8 @@ pub fn encode(row: &[Value], col_types: &[ColumnType]) -> Result<Vec<u8>> {
                 var_entries.push((i, encoded));
             }
             (value, _) => {
-                let mut encoded = vec![0xFF];
-                encoded.extend_from_slice(
-                    &bincode::serialize(value)
-                        .map_err(|e| StorageError::Serialization(e.to_string()))?,
-                );
+                let mut encoded = Vec::new();
+                value_codec::encode_tagged(value, &mut encoded);
                 var_entries.push((i, encoded));
             }
         }
@@ -1040,11 +1056,8 @@ pub fn encode_into(row: &[Value], col_types: &[ColumnType], buf: &mut Vec<u8>) -
                 var_entries.push((i, encoded));
             }
             (value, _) => {
-                let mut encoded = vec![0xFF];
-                encoded.extend_from_slice(
-                    &bincode::serialize(value)
-                        .map_err(|e| StorageError::Serialization(e.to_string()))?,
-                );
+                let mut encoded = Vec::new();
+                value_codec::encode_tagged(value, &mut encoded);
                 var_entries.push((i, encoded));
             }
         }
@@ -1076,7 +1089,7 @@ pub fn encode_into(row: &[Value], col_types: &[ColumnType], buf: &mut Vec<u8>) -
 /// Decode bytes into a Row. Falls back to bincode for old-format data.
 pub fn decode(data: &[u8], col_types: &[ColumnType]) -> Result<Row> {
     if !is_rawrow(data) {
-        return bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()));
+        return value_codec::decode_row(data);
     }
     decode_raw(data, col_types)
 }
@@ -1084,7 +1097,7 @@ pub fn decode(data: &[u8], col_types: &[ColumnType]) -> Result<Row> {
 /// Fast decode with pre-computed fixed_count (avoids per-row O(C) scan).
 pub fn decode_fast(data: &[u8], col_types: &[ColumnType], fixed_count: usize) -> Result<Row> {
     if !is_rawrow(data) {
-        return bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()));
+        return value_codec::decode_row(data);
     }
     decode_raw_fast(data, col_types, fixed_count)
 }
@@ -1097,8 +1110,7 @@ pub fn decode_fast_into(
     buf: &mut Vec<Value>,
 ) -> Result<()> {
     if !is_rawrow(data) {
-        *buf =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        *buf = value_codec::decode_row(data)?;
         return Ok(());
     }
     decode_raw_fast_into(data, col_types, fixed_count, buf)
@@ -1114,8 +1126,7 @@ pub fn decode_fast_into_with_pool(
     pool: Option<&mut StringPool>,
 ) -> Result<()> {
     if !is_rawrow(data) {
-        *buf =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        *buf = value_codec::decode_row(data)?;
         return Ok(());
     }
     decode_raw_fast_into_with_pool(data, col_types, fixed_count, buf, pool)
@@ -1332,7 +1343,7 @@ impl RowParseContext {
 /// Tries RawRow first (with generic type inference), falls back to bincode.
 pub fn decode_any(data: &[u8]) -> Result<Row> {
     if !is_rawrow(data) {
-        return bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()));
+        return value_codec::decode_row(data);
     }
     // For RawRow without schema, try to decode with best-effort column type inference
     decode_raw_any(data)
@@ -1341,7 +1352,7 @@ pub fn decode_any(data: &[u8]) -> Result<Row> {
 /// Like `decode_any` but with optional `StringPool` for Text column interning.
 pub fn decode_any_with_pool(data: &[u8], pool: Option<&mut StringPool>) -> Result<Row> {
     if !is_rawrow(data) {
-        return bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()));
+        return value_codec::decode_row(data);
     }
     decode_raw_any_with_pool(data, pool)
 }
@@ -1349,8 +1360,7 @@ pub fn decode_any_with_pool(data: &[u8], pool: Option<&mut StringPool>) -> Resul
 /// Get a single column value without deserializing the whole row.
 pub fn get_column(data: &[u8], col_types: &[ColumnType], col_idx: usize) -> Result<Value> {
     if !is_rawrow(data) {
-        let row: Row =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let row: Row = value_codec::decode_row(data)?;
         return Ok(row.get(col_idx).cloned().unwrap_or(Value::Null));
     }
 
@@ -1413,6 +1423,11 @@ pub fn get_column(data: &[u8], col_types: &[ColumnType], col_idx: usize) -> Resu
     Ok(Value::Null)
 }
 
+/// Column count recorded in a RawRow header; `None` for bincode rows
+pub fn rawrow_column_count(data: &[u8]) -> Option<usize> {
+    (is_rawrow(data) && data.len() >= 4).then(|| u16::from_le_bytes([data[2], data[3]]) as usize)
+}
+
 fn is_rawrow(data: &[u8]) -> bool {
     data.len() >= 2 && u16::from_le_bytes([data[0], data[1]]) == RAWROW_MAGIC
 }
@@ -1624,7 +1639,7 @@ fn decode_raw(data: &[u8], col_types: &[ColumnType]) -> Result<Row> {
 
     if col_count != col_types.len() {
         // Schema mismatch — fall back to bincode
-        return bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()));
+        return value_codec::decode_row(data);
     }
 
     let fixed_count = col_types.iter().filter(|t| is_fixed(t)).count();
@@ -1710,8 +1725,7 @@ pub fn decode_fast_partial_into(
     buf: &mut Vec<Value>,
 ) -> Result<()> {
     if !is_rawrow(data) {
-        *buf =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        *buf = value_codec::decode_row(data)?;
         let projected: Vec<Value> = col_positions
             .iter()
             .map(|&p| buf.get(p).cloned().unwrap_or(Value::Null))
@@ -1732,8 +1746,7 @@ pub fn decode_fast_partial_into_with_pool(
     pool: Option<&mut StringPool>,
 ) -> Result<()> {
     if !is_rawrow(data) {
-        *buf =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        *buf = value_codec::decode_row(data)?;
         let projected: Vec<Value> = col_positions
             .iter()
             .map(|&p| buf.get(p).cloned().unwrap_or(Value::Null))
@@ -1771,8 +1784,7 @@ fn decode_raw_fast_into_with_pool(
     ]);
 
     if col_count != col_types.len() {
-        *row =
-            bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        *row = value_codec::decode_row(data)?;
         return Ok(());
     }
 
@@ -2044,18 +2056,10 @@ fn decode_raw_any_with_pool(data: &[u8], mut pool: Option<&mut StringPool>) -> R
                         row.push(Value::Null);
                     } else {
                         let var_data = &data[abs_off..abs_off + v_len];
-                        // Check for tagged bincode value (0xFF prefix)
-                        if !var_data.is_empty() && var_data[0] == 0xFF {
-                            match bincode::deserialize::<Value>(&var_data[1..]) {
-                                Ok(v) => {
-                                    row.push(v);
-                                    continue;
-                                }
-                                Err(_) => {
-                                    row.push(Value::Null);
-                                    continue;
-                                }
-                            }
+                        // Check for a tagged value (current or legacy 0xFF)
+                        if let Some(v) = value_codec::decode_generic(var_data) {
+                            row.push(v.unwrap_or(Value::Null));
+                            continue;
                         }
                         // Try vector first: [dim: u16] + f32 array
                         if var_data.len() >= 2 {
@@ -2085,10 +2089,9 @@ fn decode_raw_any_with_pool(data: &[u8], mut pool: Option<&mut StringPool>) -> R
                             }
                         } else {
                             // Fallback: bincode
-                            match bincode::deserialize::<Value>(var_data) {
-                                Ok(v) => row.push(v),
-                                Err(_) => row.push(Value::Null),
-                            }
+                            row.push(
+                                value_codec::decode_legacy_value(var_data).unwrap_or(Value::Null),
+                            );
                         }
                     }
                 } else {
@@ -2110,7 +2113,7 @@ fn decode_raw_any_with_pool(data: &[u8], mut pool: Option<&mut StringPool>) -> R
     }
 
     // Absolute fallback
-    bincode::deserialize(data).map_err(|e| StorageError::Serialization(e.to_string()))
+    value_codec::decode_row(data)
 }
 
 fn decode_fixed(bytes: &[u8], col_type: &ColumnType) -> Value {
@@ -2146,10 +2149,9 @@ fn decode_var_with_pool(
             }
         }
         _ => {
-            // Check for tagged bincode value (0xFF prefix)
-            if !bytes.is_empty() && bytes[0] == 0xFF {
-                return bincode::deserialize(&bytes[1..])
-                    .map_err(|e| StorageError::Serialization(e.to_string()));
+            // Check for a tagged value (current or legacy 0xFF)
+            if let Some(v) = value_codec::decode_generic(bytes) {
+                return v;
             }
             // Try vector format: [dim: u16] + f32 array
             if bytes.len() >= 2 {
@@ -2170,7 +2172,7 @@ fn decode_var_with_pool(
                 }
             }
             // Fallback: bincode
-            bincode::deserialize(bytes).map_err(|e| StorageError::Serialization(e.to_string()))
+            value_codec::decode_legacy_value(bytes)
         }
     }
 }
@@ -0,0 +1,486 @@
+//! Per-component fsync policy
+//!
+//! [`SyncPolicyBackend`] wraps another [`StorageBackend`] and decides, per
+//! file, whether a `sync_all` reaches the device. Files are assigned to a
+//! component by their directory under the database root:
+//! - `wal/` → [`FsyncConfig::wal`]
+//! - `lsm/blobs/` → [`FsyncConfig::blob`]
+//! - the rest of `lsm/`, `columnar/` and `columnar_ms/` → [`FsyncConfig::sstable`]
+//! - `indexes/` → [`FsyncConfig::index`]
+//!
+//! Anything else (catalog, index metadata) is always synced.
+//!
+//! Under [`FsyncPolicy::Interval`] a sync only marks the file pending; a
+//! background thread reopens pending files and syncs them once their
+//! interval has passed, and the rest are synced when the backend is dropped
+//! (i.e. when the database closes). Where there are no threads (see
+//! [`platform::threads_available`]) due syncs are instead swept whenever
+//! another sync is deferred. [`FsyncPolicy::Never`] drops the sync,
+//! and the directory sync after a rename, altogether.
+//!
+//! The database wraps its backend only when some component is relaxed, so
+//! the default configuration pays nothing for it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+use parking_lot::Mutex;
+
+use super::backend::{BackendFile, FileMap, OpenFlags, StorageBackend};
+use super::platform;
+use crate::config::{FsyncConfig, FsyncPolicy};
+
+/// How often the background thread looks for due syncs (at most)
+const SWEEP_TICK: Duration = Duration::from_millis(100);
+
+struct SyncState {
+    inner: Arc<dyn StorageBackend>,
+    /// Files whose sync was deferred → when it was first requested
+    pending: Mutex<HashMap<PathBuf, (Instant, Duration)>>,
+}
+
+impl SyncState {
+    /// Sync pending files whose interval has passed (all of them with
+    /// `now = None`)
+    fn sync_due(&self, now: Option<Instant>) {
+        let due: Vec<PathBuf> = {
+            let mut pending = self.pending.lock();
+            let due: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, (since, interval))| {
+                    now.is_none_or(|now| now.duration_since(*since) >= *interval)
+                })
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in &due {
+                pending.remove(path);
+            }
+            due
+        };
+        for path in due {
+            // A file removed or renamed away since needs no sync
+            let synced = self
+                .inner
+                .open(&path, OpenFlags::read_only())
+                .and_then(|file| file.sync_all());
+            if let Err(e) = synced {
+                if e.kind() != io::ErrorKind::NotFound {
+                    warn_log!("[Fsync] Deferred sync of {:?} failed: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SyncState {
+    fn drop(&mut self) {
+        self.sync_due(None);
+    }
+}
+
+/// Storage backend applying a [`FsyncConfig`] to the files under `root`
+#[derive(Clone)]
+pub struct SyncPolicyBackend {
+    root: PathBuf,
+    config: FsyncConfig,
+    state: Arc<SyncState>,
+}
+
+impl SyncPolicyBackend {
+    /// Wrap `inner` for the database directory `root`
+    pub fn new(inner: Arc<dyn StorageBackend>, root: &Path, config: FsyncConfig) -> Self {
+        let state = Arc::new(SyncState {
+            inner,
+            pending: Mutex::new(HashMap::new()),
+        });
+        let policies = [config.wal, config.sstable, config.blob, config.index];
+        if policies
+            .iter()
+            .any(|p| matches!(p, FsyncPolicy::Interval { .. }))
+            && platform::threads_available()
+        {
+            Self::start_sweeper(Arc::downgrade(&state));
+        }
+        Self {
+            root: root.to_path_buf(),
+            config,
+            state,
+        }
+    }
+
+    /// Sync every deferred file now
+    pub fn sync_pending(&self) {
+        self.state.sync_due(None);
+    }
+
+    /// Number of files with a deferred sync
+    pub fn pending_len(&self) -> usize {
+        self.state.pending.lock().len()
+    }
+
+    fn start_sweeper(state: Weak<SyncState>) {
+        std::thread::Builder::new()
+            .name("motedb-fsync".into())
+            .spawn(move || loop {
+                std::thread::sleep(SWEEP_TICK);
+                match state.upgrade() {
+                    Some(state) => state.sync_due(Some(Instant::now())),
+                    None => break,
+                }
+            })
+            .expect("failed to spawn fsync thread");
+    }
+
+    /// Policy of the component `path` belongs to
+    fn policy(&self, path: &Path) -> FsyncPolicy {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return FsyncPolicy::Always;
+        };
+        let mut parts = rel.components().filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        });
+        match (parts.next(), parts.next()) {
+            (Some("wal"), _) => self.config.wal,
+            (Some("lsm"), Some("blobs")) => self.config.blob,
+            (Some("lsm" | "columnar" | "columnar_ms"), _) => self.config.sstable,
+            (Some("indexes"), _) => self.config.index,
+            _ => FsyncPolicy::Always,
+        }
+    }
+
+    /// Apply `policy` to a sync of `path`; `sync` performs the real one
+    fn sync_with(
+        &self,
+        path: &Path,
+        policy: FsyncPolicy,
+        sync: impl FnOnce() -> io::Result<()>,
+    ) -> io::Result<()> {
+        match policy {
+            FsyncPolicy::Always => sync(),
+            FsyncPolicy::Interval { interval_ms } => {
+                self.state
+                    .pending
+                    .lock()
+                    .entry(path.to_path_buf())
+                    .or_insert((Instant::now(), Duration::from_millis(interval_ms)));
+                if !platform::threads_available() {
+                    self.state.sync_due(Some(Instant::now()));
+                }
+                Ok(())
+            }
+            FsyncPolicy::Never => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for SyncPolicyBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncPolicyBackend")
+            .field("inner", &self.state.inner)
+            .field("root", &self.root)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl StorageBackend for SyncPolicyBackend {
+    fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+        let file = self.state.inner.open(path, flags)?;
+        let policy = self.policy(path);
+        if policy == FsyncPolicy::Always {
+            return Ok(file);
+        }
+        Ok(Box::new(PolicyFile {
+            inner: file,
+            path: path.to_path_buf(),
+            policy,
+            backend: self.clone(),
+        }))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.state.inner.create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.state.inner.rename(from, to)?;
+        let mut pending = self.state.pending.lock();
+        match pending.remove(from) {
+            Some(since) => pending.insert(to.to_path_buf(), since),
+            None => pending.remove(to),
+        };
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.state.inner.remove_file(path)?;
+        self.state.pending.lock().remove(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.state.inner.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.state.inner.is_dir(path)
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.state.inner.list_dir(path)
+    }
+
+    fn sync_parent_dir(&self, path: &Path) {
+        if self.policy(path) != FsyncPolicy::Never {
+            self.state.inner.sync_parent_dir(path)
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.state.inner.remove_dir_all(path)?;
+        self.state
+            .pending
+            .lock()
+            .retain(|pending, _| !pending.starts_with(path));
+        Ok(())
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        self.state.inner.file_len(path)
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        self.state.inner.modified(path)
+    }
+}
+
+/// File handle whose syncs follow its component's policy
+struct PolicyFile {
+    inner: Box<dyn BackendFile>,
+    path: PathBuf,
+    policy: FsyncPolicy,
+    backend: SyncPolicyBackend,
+}
+
+impl Read for PolicyFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Write for PolicyFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Seek for PolicyFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl BackendFile for PolicyFile {
+    fn sync_all(&self) -> io::Result<()> {
+        self.backend
+            .sync_with(&self.path, self.policy, || self.inner.sync_all())
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        self.inner.len()
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        self.inner.set_len(size)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.inner.read_exact_at(buf, offset)
+    }
+
+    fn map(&self) -> io::Result<FileMap> {
+        self.inner.map()
+    }
+
+    fn advise_dontneed(&self) {
+        self.inner.advise_dontneed()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::MemoryBackend;
+
+    /// Memory backend recording every sync that reaches it
+    #[derive(Debug, Clone, Default)]
+    struct CountingBackend {
+        mem: MemoryBackend,
+        synced: Arc<Mutex<Vec<PathBuf>>>,
+    }
+
+    struct CountingFile {
+        inner: Box<dyn BackendFile>,
+        path: PathBuf,
+        synced: Arc<Mutex<Vec<PathBuf>>>,
+    }
+
+    impl Read for CountingFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Write for CountingFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl Seek for CountingFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    impl BackendFile for CountingFile {
+        fn sync_all(&self) -> io::Result<()> {
+            self.synced.lock().push(self.path.clone());
+            self.inner.sync_all()
+        }
+
+        fn len(&self) -> io::Result<u64> {
+            self.inner.len()
+        }
+
+        fn set_len(&self, size: u64) -> io::Result<()> {
+            self.inner.set_len(size)
+        }
+
+        fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+            self.inner.read_exact_at(buf, offset)
+        }
+    }
+
+    impl StorageBackend for CountingBackend {
+        fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Box<dyn BackendFile>> {
+            Ok(Box::new(CountingFile {
+                inner: self.mem.open(path, flags)?,
+                path: path.to_path_buf(),
+                synced: self.synced.clone(),
+            }))
+        }
+
+        fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+            self.mem.create_dir_all(path)
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+            self.mem.rename(from, to)
+        }
+
+        fn remove_file(&self, path: &Path) -> io::Result<()> {
+            self.mem.remove_file(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.mem.exists(path)
+        }
+
+        fn is_dir(&self, path: &Path) -> bool {
+            self.mem.is_dir(path)
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            self.mem.list_dir(path)
+        }
+    }
+
+    fn write_and_sync(backend: &SyncPolicyBackend, path: &Path) {
+        let mut file = backend.open(path, OpenFlags::append()).unwrap();
+        file.write_all(b"data").unwrap();
+        file.sync_all().unwrap();
+    }
+
+    #[test]
+    fn test_policies_per_component() {
+        let counting = CountingBackend::default();
+        let root = Path::new("/db.mote");
+        let backend = SyncPolicyBackend::new(
+            Arc::new(counting.clone()),
+            root,
+            FsyncConfig {
+                blob: FsyncPolicy::Never,
+                index: FsyncPolicy::Never,
+                ..Default::default()
+            },
+        );
+
+        let wal = root.join("wal/partition_0.wal");
+        let sst = root.join("lsm/000001.sst");
+        let blob = root.join("lsm/blobs/00000001.blob");
+        let index = root.join("indexes/timestamp.idx");
+        let catalog = root.join("catalog.bin");
+        for path in [&wal, &sst, &blob, &index, &catalog] {
+            write_and_sync(&backend, path);
+        }
+
+        assert_eq!(*counting.synced.lock(), vec![wal, sst, catalog]);
+        assert_eq!(backend.pending_len(), 0);
+    }
+
+    #[test]
+    fn test_interval_defers_sync() {
+        let counting = CountingBackend::default();
+        let root = Path::new("/db.mote");
+        let backend = SyncPolicyBackend::new(
+            Arc::new(counting.clone()),
+            root,
+            FsyncConfig {
+                sstable: FsyncPolicy::Interval { interval_ms: 50 },
+                index: FsyncPolicy::Interval {
+                    interval_ms: 3_600_000,
+                },
+                ..Default::default()
+            },
+        );
+
+        let sst = root.join("lsm/000001.sst");
+        let tmp = root.join("indexes/col.idx.tmp");
+        let index = root.join("indexes/col.idx");
+        write_and_sync(&backend, &sst);
+        write_and_sync(&backend, &tmp);
+        backend.rename(&tmp, &index).unwrap();
+        assert!(counting.synced.lock().is_empty());
+        assert_eq!(backend.pending_len(), 2);
+
+        // The background thread picks up the short interval only
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while backend.pending_len() > 1 {
+            assert!(Instant::now() < deadline, "deferred sync never ran");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*counting.synced.lock(), vec![sst.clone()]);
+
+        backend.sync_pending();
+        assert_eq!(backend.pending_len(), 0);
+        assert_eq!(*counting.synced.lock(), vec![sst, index]);
+    }
+}
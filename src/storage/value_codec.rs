@@ -0,0 +1,530 @@
+//! Versioned, self-describing value encoding
+//!
+//! RawRow stores Integer/Float/Bool/Timestamp columns inline and Text/Vector
+//! columns as plain bytes. Every other value (DECIMAL, UUID, DATE, TIME,
+//! arrays, tensors, geometries, ...) used to be written as `0xFF` followed by
+//! the bincode of [`Value`]. Bincode identifies an enum variant by its
+//! position, so such rows could only be read back by a build whose `Value`
+//! enum had the exact same variant order.
+//!
+//! Those values are now written as:
+//! ```text
+//! [0xFE][version: u8][kind: u8][payload]
+//! ```
+//! where `kind` is a stable tag assigned here (independent of the enum
+//! order) and the payload layout is fixed per kind, little-endian. A reader
+//! rejects a `version` it does not know instead of misreading the bytes.
+//!
+//! Upgrade on read: `0xFF` values and whole rows written as bincode (before
+//! RawRow) are decoded through a frozen copy of the original `Value` layout,
+//! so they stay readable whatever variants `Value` gains. They are rewritten
+//! in the current format the next time the row is written, or all at once by
+//! [`MoteDB::migrate_row_format`](crate::MoteDB::migrate_row_format).
+
+use crate::types::{
+    ArcString, ArcVec, Date, Decimal, Geometry, Point, Point3D, Row, Tensor, Text, Time, Timestamp,
+    Uuid, Value,
+};
+use crate::{Result, StorageError};
+use serde::Deserialize;
+
+/// Marks a value in the current encoding
+pub(crate) const TAGGED_VALUE: u8 = 0xFE;
+
+/// Marks a value written as bincode of the original `Value` layout
+pub(crate) const LEGACY_VALUE: u8 = 0xFF;
+
+/// Version of the tagged value encoding written by this build
+pub const VALUE_FORMAT_VERSION: u8 = 1;
+
+const KIND_NULL: u8 = 0;
+const KIND_INTEGER: u8 = 1;
+const KIND_FLOAT: u8 = 2;
+const KIND_BOOL: u8 = 3;
+const KIND_TEXT: u8 = 4;
+const KIND_VECTOR: u8 = 5;
+const KIND_TIMESTAMP: u8 = 6;
+const KIND_DECIMAL: u8 = 7;
+const KIND_UUID: u8 = 8;
+const KIND_DATE: u8 = 9;
+const KIND_TIME: u8 = 10;
+const KIND_ARRAY: u8 = 11;
+const KIND_TENSOR: u8 = 12;
+const KIND_SPATIAL: u8 = 13;
+const KIND_TEXT_DOC: u8 = 14;
+
+const GEOMETRY_POINT: u8 = 0;
+const GEOMETRY_POINT_3D: u8 = 1;
+const GEOMETRY_LINE_STRING: u8 = 2;
+const GEOMETRY_POLYGON: u8 = 3;
+
+/// Append `value` in the current tagged encoding
+pub fn encode_tagged(value: &Value, out: &mut Vec<u8>) {
+    out.push(TAGGED_VALUE);
+    out.push(VALUE_FORMAT_VERSION);
+    encode_value(value, out);
+}
+
+fn encode_len(len: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn encode_f32s(values: &[f32], out: &mut Vec<u8>) {
+    encode_len(values.len(), out);
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+fn encode_points(points: &[Point], out: &mut Vec<u8>) {
+    encode_len(points.len(), out);
+    for p in points {
+        out.extend_from_slice(&p.x.to_le_bytes());
+        out.extend_from_slice(&p.y.to_le_bytes());
+    }
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(KIND_NULL),
+        Value::Integer(i) => {
+            out.push(KIND_INTEGER);
+            out.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::Float(f) => {
+            out.push(KIND_FLOAT);
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+        Value::Bool(b) => out.extend_from_slice(&[KIND_BOOL, *b as u8]),
+        Value::Text(s) => {
+            out.push(KIND_TEXT);
+            encode_len(s.len(), out);
+            out.extend_from_slice(s.as_bytes());
+        }
+        Value::Vector(v) => {
+            out.push(KIND_VECTOR);
+            encode_f32s(&v.0, out);
+        }
+        Value::Timestamp(ts) => {
+            out.push(KIND_TIMESTAMP);
+            out.extend_from_slice(&ts.as_micros().to_le_bytes());
+        }
+        Value::Decimal(d) => {
+            out.push(KIND_DECIMAL);
+            out.extend_from_slice(&d.mantissa().to_le_bytes());
+            out.push(d.scale());
+        }
+        Value::Uuid(u) => {
+            out.push(KIND_UUID);
+            out.extend_from_slice(u.as_bytes());
+        }
+        Value::Date(d) => {
+            out.push(KIND_DATE);
+            out.extend_from_slice(&d.days().to_le_bytes());
+        }
+        Value::Time(t) => {
+            out.push(KIND_TIME);
+            out.extend_from_slice(&t.as_micros().to_le_bytes());
+        }
+        Value::Array(items) => {
+            out.push(KIND_ARRAY);
+            encode_len(items.len(), out);
+            for item in items.iter() {
+                encode_value(item, out);
+            }
+        }
+        Value::Tensor(t) => {
+            out.push(KIND_TENSOR);
+            encode_len(t.shape().len(), out);
+            for &extent in t.shape() {
+                out.extend_from_slice(&(extent as u64).to_le_bytes());
+            }
+            encode_f32s(t.as_f32(), out);
+        }
+        Value::Spatial(g) => {
+            out.push(KIND_SPATIAL);
+            match &**g {
+                Geometry::Point(p) => {
+                    out.push(GEOMETRY_POINT);
+                    out.extend_from_slice(&p.x.to_le_bytes());
+                    out.extend_from_slice(&p.y.to_le_bytes());
+                }
+                Geometry::Point3D(p) => {
+                    out.push(GEOMETRY_POINT_3D);
+                    out.extend_from_slice(&p.x.to_le_bytes());
+                    out.extend_from_slice(&p.y.to_le_bytes());
+                    out.extend_from_slice(&p.z.to_le_bytes());
+                }
+                Geometry::LineString(points) => {
+                    out.push(GEOMETRY_LINE_STRING);
+                    encode_points(points, out);
+                }
+                Geometry::Polygon(points) => {
+                    out.push(GEOMETRY_POLYGON);
+                    encode_points(points, out);
+                }
+            }
+        }
+        Value::TextDoc(t) => {
+            out.push(KIND_TEXT_DOC);
+            encode_len(t.content().len(), out);
+            out.extend_from_slice(t.content().as_bytes());
+        }
+    }
+}
+
+/// Encode a whole row that does not fit the RawRow layout (more than 64
+/// columns, or values that do not match the schema) as a tagged array
+pub fn encode_row(row: &[Value]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + row.len() * 9);
+    out.extend_from_slice(&[TAGGED_VALUE, VALUE_FORMAT_VERSION, KIND_ARRAY]);
+    encode_len(row.len(), &mut out);
+    for value in row {
+        encode_value(value, &mut out);
+    }
+    out
+}
+
+/// Decode a tagged value. `None` when `bytes` is not one, so the caller can
+/// try its other layouts (a `[dim: u16]` vector may start with `0xFE`).
+pub fn decode_tagged(bytes: &[u8]) -> Option<Result<Value>> {
+    if bytes.len() < 3 || bytes[0] != TAGGED_VALUE {
+        return None;
+    }
+    let version = bytes[1];
+    if version == 0 || version > VALUE_FORMAT_VERSION {
+        if is_plain_vector(bytes) {
+            return None;
+        }
+        return Some(Err(StorageError::InvalidData(format!(
+            "value encoded with format version {} (this build reads up to {}); \
+             upgrade MoteDB to read this database",
+            version, VALUE_FORMAT_VERSION
+        ))));
+    }
+    let mut reader = Reader {
+        data: bytes,
+        pos: 2,
+    };
+    match reader.value() {
+        Ok(value) if reader.pos == bytes.len() => Some(Ok(value)),
+        _ if is_plain_vector(bytes) => None,
+        Ok(_) => Some(Err(StorageError::Serialization(
+            "trailing bytes after tagged value".into(),
+        ))),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// Whether `bytes` is exactly a RawRow `[dim: u16][f32; dim]` vector
+fn is_plain_vector(bytes: &[u8]) -> bool {
+    let dim = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+    dim > 0 && bytes.len() == 2 + dim * 4
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| StorageError::Serialization("truncated tagged value".into()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = u32::from_le_bytes(self.array()?) as usize;
+        // Every element takes at least one byte: reject lengths the rest of
+        // the buffer cannot hold before allocating for them
+        if len > self.data.len() - self.pos {
+            return Err(StorageError::Serialization(
+                "tagged value length exceeds its data".into(),
+            ));
+        }
+        Ok(len)
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn str(&mut self) -> Result<&'a str> {
+        let len = self.len()?;
+        std::str::from_utf8(self.take(len)?)
+            .map_err(|_| StorageError::Serialization("invalid UTF-8 in tagged value".into()))
+    }
+
+    fn f32s(&mut self) -> Result<Vec<f32>> {
+        let len = self.len()?;
+        Ok(self
+            .take(len.saturating_mul(4))?
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect())
+    }
+
+    fn points(&mut self) -> Result<Vec<Point>> {
+        let len = self.len()?;
+        (0..len)
+            .map(|_| Ok(Point::new(self.f64()?, self.f64()?)))
+            .collect()
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let invalid = |what: &str| StorageError::Serialization(format!("invalid tagged {}", what));
+        Ok(match self.u8()? {
+            KIND_NULL => Value::Null,
+            KIND_INTEGER => Value::Integer(self.i64()?),
+            KIND_FLOAT => Value::Float(self.f64()?),
+            KIND_BOOL => Value::Bool(self.u8()? != 0),
+            KIND_TEXT => Value::text_from(self.str()?),
+            KIND_VECTOR => Value::Vector(ArcVec::new(self.f32s()?)),
+            KIND_TIMESTAMP => Value::Timestamp(Timestamp::from_micros(self.i64()?)),
+            KIND_DECIMAL => {
+                let mantissa = i128::from_le_bytes(self.array()?);
+                let decimal =
+                    Decimal::new(mantissa, self.u8()?).ok_or_else(|| invalid("DECIMAL"))?;
+                Value::Decimal(Box::new(decimal))
+            }
+            KIND_UUID => Value::Uuid(Box::new(Uuid::from_bytes(self.array()?))),
+            KIND_DATE => {
+                let days = i32::from_le_bytes(self.array()?);
+                Value::Date(Date::from_days(days as i64).ok_or_else(|| invalid("DATE"))?)
+            }
+            KIND_TIME => {
+                Value::Time(Time::from_micros(self.i64()?).ok_or_else(|| invalid("TIME"))?)
+            }
+            KIND_ARRAY => {
+                let len = self.len()?;
+                let items = (0..len).map(|_| self.value()).collect::<Result<Vec<_>>>()?;
+                Value::Array(Box::new(items))
+            }
+            KIND_TENSOR => {
+                let rank = self.len()?;
+                let shape = (0..rank)
+                    .map(|_| Ok(u64::from_le_bytes(self.array()?) as usize))
+                    .collect::<Result<Vec<_>>>()?;
+                let tensor = Tensor::with_shape(shape, self.f32s()?)
+                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                Value::Tensor(Box::new(tensor))
+            }
+            KIND_SPATIAL => {
+                let geometry = match self.u8()? {
+                    GEOMETRY_POINT => Geometry::Point(Point::new(self.f64()?, self.f64()?)),
+                    GEOMETRY_POINT_3D => {
+                        Geometry::Point3D(Point3D::new(self.f64()?, self.f64()?, self.f64()?))
+                    }
+                    GEOMETRY_LINE_STRING => Geometry::LineString(self.points()?),
+                    GEOMETRY_POLYGON => Geometry::Polygon(self.points()?),
+                    _ => return Err(invalid("geometry")),
+                };
+                Value::Spatial(Box::new(geometry))
+            }
+            KIND_TEXT_DOC => Value::TextDoc(Box::new(Text::new(self.str()?.to_string()))),
+            kind => {
+                return Err(StorageError::Serialization(format!(
+                    "unknown value kind {} (written by a newer MoteDB?)",
+                    kind
+                )))
+            }
+        })
+    }
+}
+
+/// `Value` as laid out when values and rows were written with bincode.
+/// Frozen: bincode addresses variants by position, so this must keep the
+/// original order even as `Value` changes.
+#[derive(Deserialize)]
+enum LegacyValue {
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Text(ArcString),
+    Vector(ArcVec),
+    Tensor(Box<Tensor>),
+    Spatial(Box<Geometry>),
+    TextDoc(Box<Text>),
+    Timestamp(Timestamp),
+    Null,
+    Decimal(Box<Decimal>),
+    Uuid(Box<Uuid>),
+    Date(Date),
+    Time(Time),
+    Array(Vec<LegacyValue>),
+}
+
+impl From<LegacyValue> for Value {
+    fn from(value: LegacyValue) -> Self {
+        match value {
+            LegacyValue::Integer(i) => Value::Integer(i),
+            LegacyValue::Float(f) => Value::Float(f),
+            LegacyValue::Bool(b) => Value::Bool(b),
+            LegacyValue::Text(s) => Value::Text(s),
+            LegacyValue::Vector(v) => Value::Vector(v),
+            LegacyValue::Tensor(t) => Value::Tensor(t),
+            LegacyValue::Spatial(g) => Value::Spatial(g),
+            LegacyValue::TextDoc(t) => Value::TextDoc(t),
+            LegacyValue::Timestamp(ts) => Value::Timestamp(ts),
+            LegacyValue::Null => Value::Null,
+            LegacyValue::Decimal(d) => Value::Decimal(d),
+            LegacyValue::Uuid(u) => Value::Uuid(u),
+            LegacyValue::Date(d) => Value::Date(d),
+            LegacyValue::Time(t) => Value::Time(t),
+            LegacyValue::Array(items) => {
+                Value::Array(Box::new(items.into_iter().map(Value::from).collect()))
+            }
+        }
+    }
+}
+
+/// Decode a value written as bincode of the original `Value` layout
+pub(crate) fn decode_legacy_value(bytes: &[u8]) -> Result<Value> {
+    bincode::deserialize::<LegacyValue>(bytes)
+        .map(Value::from)
+        .map_err(|e| StorageError::Serialization(e.to_string()))
+}
+
+/// Decode a whole row that is not in the RawRow layout: a tagged array
+/// written by [`encode_row`], or legacy bincode
+pub(crate) fn decode_row(bytes: &[u8]) -> Result<Row> {
+    match decode_tagged(bytes) {
+        Some(Ok(Value::Array(row))) => Ok(*row),
+        Some(Ok(_)) => Err(StorageError::Serialization(
+            "tagged row is not an array".into(),
+        )),
+        Some(Err(e)) => Err(e),
+        None => decode_legacy_row(bytes),
+    }
+}
+
+/// Decode a whole row written as bincode (before RawRow)
+fn decode_legacy_row(bytes: &[u8]) -> Result<Row> {
+    bincode::deserialize::<Vec<LegacyValue>>(bytes)
+        .map(|row| row.into_iter().map(Value::from).collect())
+        .map_err(|e| StorageError::Serialization(e.to_string()))
+}
+
+/// Decode a generic RawRow value in any encoding this build can read;
+/// `None` when it is neither a tagged nor a legacy `0xFF` value
+pub(crate) fn decode_generic(bytes: &[u8]) -> Option<Result<Value>> {
+    if let Some(value) = decode_tagged(bytes) {
+        return Some(value);
+    }
+    if bytes.first() == Some(&LEGACY_VALUE) {
+        if let Ok(value) = decode_legacy_value(&bytes[1..]) {
+            return Some(Ok(value));
+        }
+    }
+    None
+}
+
+/// What [`MoteDB::migrate_row_format`](crate::MoteDB::migrate_row_format) did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationStats {
+    pub tables: usize,
+    pub rows_scanned: u64,
+    /// Rows that were in an older encoding and have been rewritten
+    pub rows_rewritten: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Value> {
+        vec![
+            Value::Null,
+            Value::Integer(-7),
+            Value::Float(2.5),
+            Value::Bool(true),
+            Value::text_from("héllo"),
+            Value::Vector(ArcVec::new(vec![1.0, -2.0])),
+            Value::Timestamp(Timestamp::from_micros(1_700_000_000_000_000)),
+            Value::Decimal(Box::new(Decimal::new(-12345, 2).unwrap())),
+            Value::Uuid(Box::new(Uuid::from_bytes([7; 16]))),
+            Value::Date(Date::from_days(19_000).unwrap()),
+            Value::Time(Time::from_micros(3_600_000_000).unwrap()),
+            Value::Array(Box::new(vec![Value::Integer(1), Value::Null])),
+            Value::Tensor(Box::new(
+                Tensor::with_shape(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap(),
+            )),
+            Value::Spatial(Box::new(Geometry::Polygon(vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(0.0, 0.0),
+            ]))),
+            Value::TextDoc(Box::new(Text::new("doc".into()))),
+        ]
+    }
+
+    #[test]
+    fn test_tagged_roundtrip() {
+        for value in samples() {
+            let mut buf = Vec::new();
+            encode_tagged(&value, &mut buf);
+            let decoded = decode_tagged(&buf).unwrap().unwrap();
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", value));
+        }
+    }
+
+    #[test]
+    fn test_legacy_bincode_upgrades_on_read() {
+        for value in samples() {
+            let mut legacy = vec![LEGACY_VALUE];
+            legacy.extend(bincode::serialize(&value).unwrap());
+            let decoded = decode_generic(&legacy).unwrap().unwrap();
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", value));
+        }
+
+        let row = samples();
+        let decoded = decode_row(&bincode::serialize(&row).unwrap()).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", row));
+    }
+
+    #[test]
+    fn test_row_roundtrip() {
+        let row = samples();
+        let decoded = decode_row(&encode_row(&row)).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", row));
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let mut buf = Vec::new();
+        encode_tagged(&Value::Integer(1), &mut buf);
+        buf[1] = VALUE_FORMAT_VERSION + 1;
+        assert!(decode_tagged(&buf).unwrap().is_err());
+
+        // An unknown kind is an error too, not a misread
+        let buf = [TAGGED_VALUE, VALUE_FORMAT_VERSION, 200];
+        assert!(decode_tagged(&buf).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_plain_vector_is_not_tagged() {
+        // dim = 0x01FE starts with the tag byte and a valid version
+        let dim = 0x01FE;
+        let mut bytes = (dim as u16).to_le_bytes().to_vec();
+        bytes.extend(std::iter::repeat_n(0u8, dim * 4));
+        assert!(decode_tagged(&bytes).is_none());
+    }
+}
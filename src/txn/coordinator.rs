@@ -11,6 +11,7 @@ use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Delta operations for incremental snapshot
 ///
@@ -99,6 +100,9 @@ pub struct TransactionContext {
     /// Savepoint stack (for partial rollback)
     /// Savepoints are stacked: [sp1, sp2, sp3] where sp3 is the most recent
     pub savepoints: RwLock<Vec<Savepoint>>,
+
+    /// Wall-clock start, so introspection can report how long it has been open
+    pub started_at: Instant,
 }
 
 impl TransactionContext {}
@@ -143,6 +147,7 @@ impl TransactionCoordinator {
             undo_log: RwLock::new(Vec::new()),
             snapshot,
             savepoints: RwLock::new(Vec::new()), // Initialize empty savepoint stack
+            started_at: Instant::now(),
         });
 
         self.active_txns.insert(txn_id, ctx);
@@ -475,7 +480,7 @@ impl TransactionCoordinator {
                 let head = chain.head.read();
                 if let Some(version) = head.as_ref() {
                     if version.begin_ts > ctx.snapshot.timestamp && version.txn_id != ctx.txn_id {
-                        return Err(StorageError::Transaction(format!(
+                        return Err(StorageError::Conflict(format!(
                             "Read-write conflict on row {} in txn {}",
                             row_id, ctx.txn_id
                         )));
@@ -495,6 +500,25 @@ impl TransactionCoordinator {
             .unwrap_or(self.version_store.current_timestamp())
     }
 
+    /// Snapshot of every active transaction, oldest first
+    pub fn active_transactions(&self) -> Vec<Arc<TransactionContext>> {
+        let mut txns: Vec<_> = self
+            .active_txns
+            .iter()
+            .map(|entry| Arc::clone(entry.value()))
+            .collect();
+        txns.sort_by_key(|ctx| ctx.start_ts);
+        txns
+    }
+
+    /// Start timestamp of the oldest active transaction, if any
+    pub fn oldest_active_timestamp(&self) -> Option<Timestamp> {
+        self.active_txns
+            .iter()
+            .map(|entry| entry.value().start_ts)
+            .min()
+    }
+
     /// Get statistics
     pub fn stats(&self) -> TransactionCoordinatorStats {
         let next_txn_id = self.txn_id_gen.load(Ordering::Relaxed);
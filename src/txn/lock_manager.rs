@@ -179,7 +179,7 @@ impl LockManager {
                 return Ok(());
             }
         }
-        Err(StorageError::Transaction(format!(
+        Err(StorageError::Conflict(format!(
             "Lock timeout after {} retries: txn {} cannot acquire {:?} lock on row {}",
             MAX_LOCK_RETRIES, txn_id, mode, row_id
         )))
@@ -200,7 +200,7 @@ impl LockManager {
         }
 
         // Cannot upgrade - other transactions hold locks
-        Err(StorageError::Transaction(format!(
+        Err(StorageError::Conflict(format!(
             "Cannot upgrade lock: txn {} on row {}, other transactions hold locks",
             txn_id, row_id
         )))
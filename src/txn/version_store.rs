@@ -159,7 +159,7 @@ impl VersionStore {
                     || snapshot.active_txns.contains(&version.txn_id))
                     && version.txn_id != txn_id
                 {
-                    return Err(StorageError::Transaction(format!(
+                    return Err(StorageError::Conflict(format!(
                         "Write-write conflict on row {} in txn {}",
                         row_id, txn_id
                     )));
@@ -217,7 +217,7 @@ impl VersionStore {
                         || snap.active_txns.contains(&version.txn_id))
                         && version.txn_id != txn_id
                     {
-                        return Err(StorageError::Transaction(format!(
+                        return Err(StorageError::Conflict(format!(
                             "Write-write conflict on row {} in txn {}",
                             row_id, txn_id
                         )));
@@ -279,7 +279,7 @@ impl VersionStore {
                         || snap.active_txns.contains(&version.txn_id))
                         && version.txn_id != txn_id
                     {
-                        return Err(StorageError::Transaction(format!(
+                        return Err(StorageError::Conflict(format!(
                             "Write-write conflict on row {} in txn {}",
                             row_id, txn_id
                         )));
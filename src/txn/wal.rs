@@ -7,9 +7,35 @@
 //! - Every WAL record has CRC32C checksum
 //! - Detects corruption during crash recovery
 //! - Partial writes are detected and skipped
-
-use crate::config::DurabilityLevel;
+//!
+//! ## Torn-Write Protection
+//! Each WAL file starts with a 16-byte header (`"MWAL"` magic, format
+//! version, random salt). A frame's stored checksum chains its payload CRC
+//! onto the previous frame's stored checksum, starting from the salt, so a
+//! sector left half-written by a power cut — or a stale frame surviving from
+//! an earlier file generation — breaks the chain. Replay stops at the first
+//! broken link and reopening truncates the torn tail before appending.
+//! Files without a header (older versions) keep plain per-frame checksums
+//! until their next checkpoint. `storage::fault::FaultInjectionBackend`
+//! simulates power loss to exercise this path.
+//!
+//! ## Flash Write Mode
+//! With `DBConfig::flash_write`, each coalesced fsync (periodic, group
+//! commit, checkpoint, shutdown) pads the log to a flash page boundary with a
+//! padding frame (LSN `u64::MAX`, skipped on replay), so the next append
+//! starts on a fresh page instead of reprogramming a partial one. Syncs of a
+//! single commit (`Synchronous`, `SYNC_COMMIT`) are not padded: a page per
+//! commit would multiply the bytes written rather than save erase cycles.
+//! A torn tail found on open is dropped by writing the intact prefix to a
+//! new file and renaming it over the log, as a checkpoint does, instead of
+//! truncating the file in place.
+
+use crate::config::{CommitMode, DurabilityLevel, FlashWriteConfig};
+use crate::storage::backend::{default_backend, BackendFile, OpenFlags, StorageBackend};
 use crate::storage::checksum::{Checksum, ChecksumType};
+use crate::storage::failpoint;
+use crate::storage::platform::zstd;
+use crate::storage::value_codec;
 use crate::txn::version_store::{Timestamp, TransactionId};
 use crate::types::{PartitionId, Row, RowId};
 use crate::{Result, StorageError};
@@ -17,9 +43,9 @@ use dashmap::DashMap;
 use parking_lot::Condvar as PlCondvar;
 use parking_lot::Mutex as PlMutex;
 use serde::Deserialize;
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::io::{self, BufWriter, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -34,14 +60,88 @@ pub type LogSequenceNumber = u64;
 pub struct WALConfig {
     /// 持久性级别
     pub durability_level: DurabilityLevel,
+
+    /// Flash page alignment and erase-block sized writes (None = off)
+    pub flash: Option<FlashWriteConfig>,
 }
 
 impl From<crate::config::WALConfig> for WALConfig {
     fn from(config: crate::config::WALConfig) -> Self {
         Self {
             durability_level: config.durability_level,
+            flash: None,
+        }
+    }
+}
+
+/// LSN of padding frames written to align syncs to flash pages
+const PADDING_LSN: LogSequenceNumber = u64::MAX;
+
+/// Frame header: [u32 total_len][u64 lsn][u32 checksum][u32 payload_len]
+const FRAME_HEADER_SIZE: usize = 20;
+
+/// File header: [u32 magic][u32 version][u32 salt][u32 reserved]. The magic
+/// exceeds any sane frame length, so headerless files are unambiguous.
+const WAL_MAGIC: u32 = 0x4C41_574D; // "MWAL"
+const WAL_FORMAT_VERSION: u32 = 1;
+const WAL_HEADER_SIZE: u64 = 16;
+
+/// Chain a frame's payload CRC onto the previous frame's stored checksum
+fn chain_checksum(prev: u32, crc: u32) -> u32 {
+    let mut buf = [0u8; 8];
+    buf[..4].copy_from_slice(&prev.to_le_bytes());
+    buf[4..].copy_from_slice(&crc.to_le_bytes());
+    Checksum::compute(ChecksumType::CRC32C, &buf)
+}
+
+/// Layout of a WAL file, from its first bytes
+enum WalHeader {
+    /// Empty file, or a header cut short by a crash
+    Missing,
+    /// Pre-header file with plain per-frame checksums
+    Legacy,
+    /// Chained checksums seeded with `salt`
+    Chained { salt: u32 },
+}
+
+/// Outcome of scanning a WAL file front to back
+struct WalScan {
+    header: WalHeader,
+    /// Stored checksum of the last intact frame (the salt if none)
+    chain: u32,
+    /// Offset just past the last intact frame
+    valid_end: u64,
+    file_len: u64,
+    /// Legacy frames skipped for a checksum mismatch
+    corrupted: usize,
+}
+
+thread_local! {
+    // Commit mode of the write running on this thread. Thread-local (like the
+    // executor's current transaction) so the override reaches the WAL without
+    // threading a parameter through every insert/update/delete path.
+    static COMMIT_MODE: Cell<Option<CommitMode>> = const { Cell::new(None) };
+}
+
+/// Commit mode override of the current thread, if any
+pub(crate) fn commit_mode() -> Option<CommitMode> {
+    COMMIT_MODE.with(|m| m.get())
+}
+
+/// Run `f` with WAL writes on this thread using `mode` instead of the
+/// configured `DurabilityLevel`. `None` keeps the current setting.
+pub(crate) fn with_commit_mode<T>(mode: Option<CommitMode>, f: impl FnOnce() -> T) -> T {
+    let Some(mode) = mode else {
+        return f();
+    };
+    struct Restore(Option<CommitMode>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            COMMIT_MODE.with(|m| m.set(self.0));
         }
     }
+    let _restore = Restore(COMMIT_MODE.with(|m| m.replace(Some(mode))));
+    f()
 }
 
 /// WAL record types
@@ -117,6 +217,16 @@ pub enum WALRecord {
         txn_id: TransactionId,
     },
 
+    /// Range delete: deletes every row of the table whose row_id is in
+    /// `start_row_id..end_row_id` (ring-buffer eviction). Replayed with
+    /// `LSMEngine::delete_range`, one tombstone per existing key.
+    DeleteRange {
+        table_name: String,
+        start_row_id: RowId,
+        end_row_id: RowId,
+        txn_id: TransactionId,
+    },
+
     /// Transaction begin marker
     Begin {
         txn_id: TransactionId,
@@ -134,6 +244,11 @@ pub enum WALRecord {
 
     /// Checkpoint marker (all records before this LSN are persisted)
     Checkpoint { lsn: LogSequenceNumber },
+
+    /// Several records written as one frame (`WriteBatch`). The frame is
+    /// checksummed as a whole, so recovery replays all of them or none;
+    /// `recover()` returns the inner records in place of the batch.
+    Batch { records: Vec<WALRecord> },
 }
 
 // Native binary format type tags
@@ -144,6 +259,8 @@ const TAG_BEGIN: u8 = 0x04;
 const TAG_COMMIT: u8 = 0x05;
 const TAG_ROLLBACK: u8 = 0x06;
 const TAG_CHECKPOINT: u8 = 0x07;
+const TAG_BATCH: u8 = 0x08;
+const TAG_DELETE_RANGE: u8 = 0x09;
 /// Compression marker tag (0x00 never used by record types, backward compatible)
 const TAG_COMPRESSED: u8 = 0x00;
 /// Minimum payload size (bytes) to consider compression. Small records aren't worth it.
@@ -196,7 +313,7 @@ impl WALRecord {
                 encode_str(&mut buf, table_name);
                 buf.extend_from_slice(&row_id.to_le_bytes());
                 buf.extend_from_slice(&(*partition as u16).to_le_bytes());
-                let bytes = bincode::serialize(data)?;
+                let bytes = value_codec::encode_row(data);
                 buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
                 buf.extend_from_slice(&bytes);
             }
@@ -231,10 +348,10 @@ impl WALRecord {
                 encode_str(&mut buf, table_name);
                 buf.extend_from_slice(&row_id.to_le_bytes());
                 buf.extend_from_slice(&(*partition as u16).to_le_bytes());
-                let old_bytes = bincode::serialize(old_data)?;
+                let old_bytes = value_codec::encode_row(old_data);
                 buf.extend_from_slice(&(old_bytes.len() as u32).to_le_bytes());
                 buf.extend_from_slice(&old_bytes);
-                let new_bytes = bincode::serialize(new_data)?;
+                let new_bytes = value_codec::encode_row(new_data);
                 buf.extend_from_slice(&(new_bytes.len() as u32).to_le_bytes());
                 buf.extend_from_slice(&new_bytes);
             }
@@ -269,10 +386,22 @@ impl WALRecord {
                 buf.extend_from_slice(&row_id.to_le_bytes());
                 buf.extend_from_slice(&(*partition as u16).to_le_bytes());
                 buf.extend_from_slice(&timestamp.to_le_bytes());
-                let bytes = bincode::serialize(old_data)?;
+                let bytes = value_codec::encode_row(old_data);
                 buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
                 buf.extend_from_slice(&bytes);
             }
+            WALRecord::DeleteRange {
+                table_name,
+                start_row_id,
+                end_row_id,
+                txn_id,
+            } => {
+                buf.push(TAG_DELETE_RANGE);
+                buf.extend_from_slice(&txn_id.to_le_bytes());
+                encode_str(&mut buf, table_name);
+                buf.extend_from_slice(&start_row_id.to_le_bytes());
+                buf.extend_from_slice(&end_row_id.to_le_bytes());
+            }
             WALRecord::Begin {
                 txn_id,
                 isolation_level,
@@ -294,6 +423,21 @@ impl WALRecord {
                 buf.push(TAG_CHECKPOINT);
                 buf.extend_from_slice(&lsn.to_le_bytes());
             }
+            WALRecord::Batch { records } => {
+                // [tag][u32 count]([u32 len][native record])*
+                buf.push(TAG_BATCH);
+                buf.extend_from_slice(&(records.len() as u32).to_le_bytes());
+                for record in records {
+                    if matches!(record, WALRecord::Batch { .. }) {
+                        return Err(StorageError::Serialization(
+                            "WAL: nested batch records are not supported".into(),
+                        ));
+                    }
+                    let bytes = record.encode_native()?;
+                    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                    buf.extend_from_slice(&bytes);
+                }
+            }
         }
         Ok(buf)
     }
@@ -371,6 +515,18 @@ impl WALRecord {
                     txn_id,
                 }))
             }
+            TAG_DELETE_RANGE => {
+                let txn_id = read_u64(data, &mut pos)?;
+                let table_name = read_str(data, &mut pos)?;
+                let start_row_id = read_u64(data, &mut pos)?;
+                let end_row_id = read_u64(data, &mut pos)?;
+                Some(Ok(WALRecord::DeleteRange {
+                    table_name,
+                    start_row_id,
+                    end_row_id,
+                    txn_id,
+                }))
+            }
             TAG_BEGIN => {
                 let txn_id = read_u64(data, &mut pos)?;
                 if pos >= data.len() {
@@ -395,6 +551,26 @@ impl WALRecord {
                 let lsn = read_u64(data, &mut pos)?;
                 Some(Ok(WALRecord::Checkpoint { lsn }))
             }
+            TAG_BATCH => {
+                let count = read_u32(data, &mut pos)? as usize;
+                let mut records = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    let len = read_u32(data, &mut pos)? as usize;
+                    if pos + len > data.len() {
+                        return None;
+                    }
+                    let inner = &data[pos..pos + len];
+                    if inner.first() == Some(&TAG_BATCH) {
+                        return None;
+                    }
+                    match Self::decode_native(inner)? {
+                        Ok(record) => records.push(record),
+                        Err(e) => return Some(Err(e)),
+                    }
+                    pos += len;
+                }
+                Some(Ok(WALRecord::Batch { records }))
+            }
             _ => None, // Unknown tag — likely bincode data
         }
     }
@@ -460,7 +636,8 @@ impl WALRecord {
             | WALRecord::Update { table_name, .. }
             | WALRecord::UpdateRaw { table_name, .. }
             | WALRecord::Delete { table_name, .. }
-            | WALRecord::DeleteRaw { table_name, .. } => Some(table_name),
+            | WALRecord::DeleteRaw { table_name, .. }
+            | WALRecord::DeleteRange { table_name, .. } => Some(table_name),
             _ => None,
         }
     }
@@ -488,10 +665,11 @@ impl WALRecord {
             | WALRecord::UpdateRaw { txn_id, .. }
             | WALRecord::Delete { txn_id, .. }
             | WALRecord::DeleteRaw { txn_id, .. }
+            | WALRecord::DeleteRange { txn_id, .. }
             | WALRecord::Begin { txn_id, .. }
             | WALRecord::Commit { txn_id, .. }
             | WALRecord::Rollback { txn_id } => *txn_id,
-            WALRecord::Checkpoint { .. } => 0,
+            WALRecord::Checkpoint { .. } | WALRecord::Batch { .. } => 0,
         }
     }
 }
@@ -651,7 +829,10 @@ struct PartitionWAL {
     path: PathBuf,
 
     /// Buffered WAL file (BufWriter amortizes syscalls for Periodic/GroupCommit modes)
-    file: BufWriter<File>,
+    file: BufWriter<Box<dyn BackendFile>>,
+
+    /// Storage backend the WAL file lives on
+    backend: Arc<dyn StorageBackend>,
 
     /// Current LSN
     next_lsn: LogSequenceNumber,
@@ -661,37 +842,165 @@ struct PartitionWAL {
 
     /// WAL configuration
     config: WALConfig,
+
+    /// File length including buffered bytes (for page alignment)
+    write_offset: u64,
+
+    /// Bytes written since the last fsync
+    unsynced: bool,
+
+    /// Frames carry chained checksums (file starts with a header)
+    chained: bool,
+
+    /// Stored checksum of the last frame written (the salt before any)
+    chain: u32,
 }
 
 impl PartitionWAL {
+    /// Wrap the WAL file in a write buffer (one erase block in flash mode)
+    fn buffered(file: Box<dyn BackendFile>, config: &WALConfig) -> BufWriter<Box<dyn BackendFile>> {
+        match config.flash {
+            Some(flash) => BufWriter::with_capacity(flash.erase_block_bytes, file),
+            None => BufWriter::new(file),
+        }
+    }
+
     /// Create a new partition WAL with config
-    fn create_with_config(path: PathBuf, config: WALConfig) -> Result<Self> {
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .read(true)
-            .open(&path)?;
+    fn create_with_config(
+        path: PathBuf,
+        config: WALConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let file = backend.open(&path, OpenFlags::append())?;
+        Self::from_file(path, file, config, backend)
+    }
+
+    /// Open existing partition WAL with config
+    fn open_with_config(
+        path: PathBuf,
+        config: WALConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let file = backend.open(&path, OpenFlags::append().no_create())?;
+        Self::from_file(path, file, config, backend)
+    }
+
+    /// Scan an opened WAL file for the next LSN, repair a torn tail and
+    /// position the writer after the last intact frame
+    fn from_file(
+        path: PathBuf,
+        mut file: Box<dyn BackendFile>,
+        config: WALConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let mut next_lsn = 0;
+        let mut last_checkpoint = 0;
+        let mut scan = Self::scan(file.as_mut(), |lsn, record_data| {
+            next_lsn = lsn + 1;
+            // Deserialize record to check for Checkpoint
+            if let Ok(WALRecord::Checkpoint { lsn: cp_lsn }) =
+                WALRecord::decode_with_fallback(record_data)
+            {
+                last_checkpoint = cp_lsn;
+            }
+        })?;
 
+        if scan.corrupted > 0 {
+            debug_log!(
+                "WAL open: Found {} corrupted records (will skip during recovery)",
+                scan.corrupted
+            );
+        }
+        if !file.is_read_only() {
+            let torn = scan.valid_end < scan.file_len;
+            if config.flash.is_some() && torn && matches!(scan.header, WalHeader::Chained { .. }) {
+                file = Self::rewrite_prefix(&path, file, &mut scan, backend.as_ref())?;
+            }
+            Self::repair(&path, file.as_mut(), &mut scan)?;
+        }
+
+        let chained = matches!(scan.header, WalHeader::Chained { .. });
         Ok(Self {
             path,
-            file: BufWriter::new(file),
-            next_lsn: 0,
-            last_checkpoint: 0,
+            file: Self::buffered(file, &config),
+            backend,
+            next_lsn,
+            last_checkpoint,
             config,
+            write_offset: scan.valid_end,
+            unsynced: false,
+            chained,
+            chain: scan.chain,
         })
     }
 
-    /// Open existing partition WAL with config
-    fn open_with_config(path: PathBuf, config: WALConfig) -> Result<Self> {
-        let mut file = OpenOptions::new().append(true).read(true).open(&path)?;
+    /// Write a fresh file header with a random salt; returns the salt
+    fn write_header(file: &mut dyn BackendFile) -> Result<u32> {
+        let salt: u32 = rand::random();
+        let mut header = [0u8; WAL_HEADER_SIZE as usize];
+        header[0..4].copy_from_slice(&WAL_MAGIC.to_le_bytes());
+        header[4..8].copy_from_slice(&WAL_FORMAT_VERSION.to_le_bytes());
+        header[8..12].copy_from_slice(&salt.to_le_bytes());
+        file.write_all(&header)?;
+        file.sync_all()?;
+        Ok(salt)
+    }
 
-        // Scan to find next LSN and verify checksums
-        let mut next_lsn = 0;
-        let mut last_checkpoint = 0;
-        let mut corrupted_count = 0;
+    /// Read the file header, leaving the cursor on the first frame
+    fn read_header(file: &mut dyn BackendFile, file_len: u64) -> Result<WalHeader> {
+        if file_len < 4 {
+            return Ok(WalHeader::Missing);
+        }
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if u32::from_le_bytes(magic) != WAL_MAGIC {
+            file.seek(SeekFrom::Start(0))?;
+            return Ok(WalHeader::Legacy);
+        }
+        if file_len < WAL_HEADER_SIZE {
+            return Ok(WalHeader::Missing);
+        }
+        let mut rest = [0u8; WAL_HEADER_SIZE as usize - 4];
+        file.read_exact(&mut rest)?;
+        let version = u32::from_le_bytes(rest[0..4].try_into().unwrap());
+        if version > WAL_FORMAT_VERSION {
+            return Err(StorageError::InvalidData(format!(
+                "unsupported WAL format version {}",
+                version
+            )));
+        }
+        Ok(WalHeader::Chained {
+            salt: u32::from_le_bytes(rest[4..8].try_into().unwrap()),
+        })
+    }
 
-        // Simple recovery: read all records with new header format
+    /// Walk the frames of a WAL file, handing each intact record to `visit`.
+    ///
+    /// Scanning stops at a bad length or a truncated frame. In a chained file
+    /// it also stops at the first checksum mismatch, since nothing after a
+    /// torn sector can be trusted; legacy files skip such frames instead.
+    fn scan(
+        file: &mut dyn BackendFile,
+        mut visit: impl FnMut(LogSequenceNumber, &[u8]),
+    ) -> Result<WalScan> {
+        let file_len = file.len()?;
         file.seek(SeekFrom::Start(0))?;
+        let header = Self::read_header(file, file_len)?;
+        let (mut chain, mut pos) = match header {
+            WalHeader::Missing => {
+                return Ok(WalScan {
+                    header,
+                    chain: 0,
+                    valid_end: 0,
+                    file_len,
+                    corrupted: 0,
+                })
+            }
+            WalHeader::Legacy => (0, 0),
+            WalHeader::Chained { salt } => (salt, WAL_HEADER_SIZE),
+        };
+        let chained = matches!(header, WalHeader::Chained { .. });
+        let mut corrupted = 0;
 
         loop {
             // Read total length prefix
@@ -705,101 +1014,198 @@ impl PartitionWAL {
             let total_len = u32::from_le_bytes(len_buf) as usize;
 
             // Sanity check: reject obviously corrupted total_len
-            if !(20..=Self::MAX_WAL_FRAME_SIZE).contains(&total_len) {
-                debug_log!("WAL open: Corrupted total_len={}, stopping", total_len);
+            if !(FRAME_HEADER_SIZE..=Self::MAX_WAL_FRAME_SIZE).contains(&total_len) {
+                debug_log!(
+                    "WAL scan: Corrupted total_len={} at offset {}, stopping",
+                    total_len,
+                    pos
+                );
                 break;
             }
 
-            // Read header: [u64 lsn][u32 checksum][u32 record_len] = 16 bytes
-            let mut header = [0u8; 16];
-            match file.read_exact(&mut header) {
+            // Rest of the frame: [u64 lsn][u32 checksum][u32 record_len][record]
+            let mut frame = vec![0u8; total_len - 4];
+            match file.read_exact(&mut frame) {
                 Ok(_) => {}
                 Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                    debug_log!("WAL open: Detected partial write (header)");
+                    debug_log!("WAL scan: Detected partial write at offset {}", pos);
                     break;
                 }
                 Err(e) => return Err(e.into()),
             }
 
-            let lsn = u64::from_le_bytes(header[0..8].try_into().unwrap());
-            let checksum = u32::from_le_bytes(header[8..12].try_into().unwrap());
-            let record_len = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
+            let lsn = u64::from_le_bytes(frame[0..8].try_into().unwrap());
+            let checksum = u32::from_le_bytes(frame[8..12].try_into().unwrap());
+            let record_len = u32::from_le_bytes(frame[12..16].try_into().unwrap()) as usize;
 
             // record_len must fit inside total_len
-            if record_len > total_len.saturating_sub(20) || record_len > Self::MAX_WAL_FRAME_SIZE {
+            if record_len > total_len - FRAME_HEADER_SIZE {
                 debug_log!(
-                    "WAL open: Corrupted record_len={} (total_len={}), stopping",
+                    "WAL scan: Corrupted record_len={} (total_len={}), stopping",
                     record_len,
                     total_len
                 );
                 break;
             }
-
-            // Read record data
-            let mut record_data = vec![0u8; record_len];
-            match file.read_exact(&mut record_data) {
-                Ok(_) => {}
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                    debug_log!("WAL open: Detected partial write at end of file");
+            let record_data = &frame[16..16 + record_len];
+
+            if chained {
+                let link =
+                    chain_checksum(chain, Checksum::compute(ChecksumType::CRC32C, record_data));
+                if link != checksum {
+                    debug_log!(
+                        "WAL scan: Checksum chain broken at LSN {} (offset {}), stopping",
+                        lsn,
+                        pos
+                    );
                     break;
                 }
-                Err(e) => return Err(e.into()),
-            }
-
-            // Verify checksum (directly on record_data, no re-serialization)
-            if Checksum::verify(ChecksumType::CRC32C, &record_data, checksum).is_err() {
-                corrupted_count += 1;
-                // Seek to next record boundary: total_len includes the 4-byte length prefix
-                // and 16-byte header (lsn+checksum+record_len). We've already read all of those
-                // plus record_data, but record_len may be wrong if the header was corrupted.
-                // Use total_len to seek to the correct position.
-                // We've read 4 (total_len) + 16 (header) = 20 bytes of the frame so far.
-                // The record data portion is total_len - 20 bytes.
-                if total_len > 20 {
-                    let seek_offset = (total_len - 20) as i64 - record_len as i64;
-                    if seek_offset != 0 {
-                        if let Err(e) = file.seek(SeekFrom::Current(seek_offset)) {
-                            debug_log!("WAL open: Failed to seek past corrupted record: {}", e);
-                            break;
-                        }
-                    }
-                }
+                chain = link;
+            } else if lsn != PADDING_LSN
+                && Checksum::verify(ChecksumType::CRC32C, record_data, checksum).is_err()
+            {
+                debug_log!("WAL scan: Checksum verification failed for LSN {}", lsn);
+                corrupted += 1;
+                pos += total_len as u64;
                 continue;
             }
+            pos += total_len as u64;
 
-            next_lsn = lsn + 1;
-            // Deserialize record to check for Checkpoint
-            if let Ok(WALRecord::Checkpoint { lsn: cp_lsn }) =
-                WALRecord::decode_with_fallback(&record_data)
-            {
-                last_checkpoint = cp_lsn;
+            // Flash page padding carries no record
+            if lsn != PADDING_LSN {
+                visit(lsn, record_data);
             }
         }
 
-        if corrupted_count > 0 {
-            debug_log!(
-                "WAL open: Found {} corrupted records (will skip during recovery)",
-                corrupted_count
+        Ok(WalScan {
+            header,
+            chain,
+            valid_end: pos,
+            file_len,
+            corrupted,
+        })
+    }
+
+    /// Cut a torn tail off a writable WAL so appends continue the intact
+    /// log. A file with nothing intact is restarted with a fresh header.
+    fn repair(path: &Path, file: &mut dyn BackendFile, scan: &mut WalScan) -> Result<()> {
+        let empty = match scan.header {
+            WalHeader::Missing => true,
+            WalHeader::Legacy => scan.valid_end == 0,
+            WalHeader::Chained { .. } => false,
+        };
+        if scan.valid_end < scan.file_len {
+            warn_log!(
+                "[WAL] {}: discarding {} torn bytes at offset {}",
+                path.display(),
+                scan.file_len - scan.valid_end,
+                scan.valid_end
             );
         }
+        if empty {
+            file.set_len(0)?;
+            let salt = Self::write_header(file)?;
+            scan.header = WalHeader::Chained { salt };
+            scan.chain = salt;
+            scan.valid_end = WAL_HEADER_SIZE;
+        } else if scan.valid_end < scan.file_len {
+            file.set_len(scan.valid_end)?;
+            file.sync_all()?;
+        }
+        scan.file_len = scan.valid_end;
+        Ok(())
+    }
 
-        Ok(Self {
-            path,
-            file: BufWriter::new(file),
-            next_lsn,
-            last_checkpoint,
-            config,
-        })
+    /// Flash mode: drop a torn tail by copying the intact prefix to a new
+    /// file and renaming it over the log, so the partly programmed page at
+    /// the tear is never rewritten in place. Returns the reopened file.
+    fn rewrite_prefix(
+        path: &Path,
+        file: Box<dyn BackendFile>,
+        scan: &mut WalScan,
+        backend: &dyn StorageBackend,
+    ) -> Result<Box<dyn BackendFile>> {
+        warn_log!(
+            "[WAL] {}: discarding {} torn bytes at offset {} (rewriting log)",
+            path.display(),
+            scan.file_len - scan.valid_end,
+            scan.valid_end
+        );
+        let mut prefix = vec![0u8; scan.valid_end as usize];
+        file.read_exact_at(&mut prefix, 0)?;
+        drop(file);
+
+        let tmp_path = path.with_extension("wal.tmp");
+        {
+            let mut tmp_file = backend.open(&tmp_path, OpenFlags::create_truncate())?;
+            tmp_file.write_all(&prefix)?;
+            tmp_file.sync_all()?;
+        }
+        backend.rename(&tmp_path, path)?;
+        backend.sync_parent_dir(path);
+        scan.file_len = scan.valid_end;
+        Ok(backend.open(path, OpenFlags::append().no_create())?)
     }
 
-    /// Flush BufWriter to OS buffer + fsync (for durability)
+    /// Flush BufWriter to OS buffer + fsync (for durability). A no-op when
+    /// nothing was written since the last sync.
     fn sync_flush(&mut self) -> Result<()> {
+        self.sync(true)
+    }
+
+    /// fsync for a single commit: never padded to a flash page
+    fn sync_commit(&mut self) -> Result<()> {
+        self.sync(false)
+    }
+
+    fn sync(&mut self, pad: bool) -> Result<()> {
+        if !self.unsynced {
+            return Ok(());
+        }
+        if let Some(flash) = self.config.flash.filter(|_| pad) {
+            self.pad_to_page(flash.page_bytes as u64)?;
+        }
         self.file.flush()?;
+        failpoint::check(failpoint::WAL_SYNC)?;
         // fsync: flush both data and metadata (file size) for durability on all platforms
         self.file.get_ref().sync_all()?;
+        self.unsynced = false;
         Ok(())
     }
 
+    /// Append a padding frame so the log ends on a `page` boundary
+    fn pad_to_page(&mut self, page: u64) -> Result<()> {
+        let rem = self.write_offset % page;
+        if rem == 0 {
+            return Ok(());
+        }
+        let mut len = page - rem;
+        if len < FRAME_HEADER_SIZE as u64 {
+            len += page;
+        }
+        let payload = vec![0u8; len as usize - FRAME_HEADER_SIZE];
+        let checksum = Checksum::compute(ChecksumType::CRC32C, &payload);
+
+        let mut buf = Vec::with_capacity(len as usize);
+        buf.extend_from_slice(&(len as u32).to_le_bytes());
+        buf.extend_from_slice(&PADDING_LSN.to_le_bytes());
+        buf.extend_from_slice(&checksum.to_le_bytes());
+        buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&payload);
+        self.write_frames(&mut buf)
+    }
+
+    /// Apply a per-write commit mode override. Returns false when there is
+    /// none and the configured durability level applies.
+    fn apply_commit_mode(&mut self) -> Result<bool> {
+        match commit_mode() {
+            Some(CommitMode::Sync) => self.sync_commit()?,
+            Some(CommitMode::Async) => self.file.flush()?,
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
     /// Append a record to WAL
     fn append(&mut self, record: WALRecord) -> Result<LogSequenceNumber> {
         let lsn = self.next_lsn;
@@ -808,9 +1214,12 @@ impl PartitionWAL {
         let record_data = record.encode_native()?;
         self.write_record(lsn, &record_data)?;
 
+        if self.apply_commit_mode()? {
+            return Ok(lsn);
+        }
         match self.config.durability_level {
             DurabilityLevel::Synchronous => {
-                self.sync_flush()?;
+                self.sync_commit()?;
             }
             DurabilityLevel::GroupCommit { .. } => {
                 // Flush BufWriter to OS buffers; group commit thread handles fsync
@@ -869,10 +1278,12 @@ impl PartitionWAL {
         let checksum = Checksum::compute(ChecksumType::CRC32C, &write_buf[record_start..]);
         write_buf[checksum_offset..checksum_offset + 4].copy_from_slice(&checksum.to_le_bytes());
 
-        self.file.write_all(&write_buf)?;
+        self.write_frames(&mut write_buf)?;
 
-        if self.config.durability_level == DurabilityLevel::Synchronous {
-            self.sync_flush()?;
+        if !self.apply_commit_mode()?
+            && self.config.durability_level == DurabilityLevel::Synchronous
+        {
+            self.sync_commit()?;
         }
 
         Ok(lsn)
@@ -921,10 +1332,12 @@ impl PartitionWAL {
         let checksum = Checksum::compute(ChecksumType::CRC32C, &write_buf[record_start..]);
         write_buf[checksum_offset..checksum_offset + 4].copy_from_slice(&checksum.to_le_bytes());
 
-        self.file.write_all(&write_buf)?;
+        self.write_frames(&mut write_buf)?;
 
-        if self.config.durability_level == DurabilityLevel::Synchronous {
-            self.sync_flush()?;
+        if !self.apply_commit_mode()?
+            && self.config.durability_level == DurabilityLevel::Synchronous
+        {
+            self.sync_commit()?;
         }
 
         Ok(lsn)
@@ -950,6 +1363,60 @@ impl PartitionWAL {
         data.to_vec()
     }
 
+    /// Append framed records to the file, chaining their checksums first.
+    /// Refused up front on a read-only file, since the BufWriter would
+    /// otherwise accept them and fail later.
+    fn write_frames(&mut self, frames: &mut [u8]) -> Result<()> {
+        if self.file.get_ref().is_read_only() {
+            return Err(StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("WAL {} is read-only", self.path.display()),
+            )));
+        }
+        let mut chain = self.chain;
+        if self.chained {
+            let mut pos = 0;
+            while pos < frames.len() {
+                let total_len = u32::from_le_bytes(frames[pos..pos + 4].try_into().unwrap());
+                let sum = pos + 12..pos + 16;
+                let crc = u32::from_le_bytes(frames[sum.clone()].try_into().unwrap());
+                chain = chain_checksum(chain, crc);
+                frames[sum].copy_from_slice(&chain.to_le_bytes());
+                pos += total_len as usize;
+            }
+        }
+        if let Err(e) = failpoint::write_all(failpoint::WAL_WRITE, &mut self.file, frames) {
+            // A torn frame would end the log at recovery and hide every
+            // record appended after it
+            self.discard_partial_write()?;
+            return Err(e.into());
+        }
+        self.chain = chain;
+        self.write_offset += frames.len() as u64;
+        self.unsynced = true;
+        Ok(())
+    }
+
+    /// Drop whatever part of a failed write reached the buffer or the file,
+    /// so the log again ends on the last whole frame (`write_offset`)
+    fn discard_partial_write(&mut self) -> Result<()> {
+        let reopened = self
+            .backend
+            .open(&self.path, OpenFlags::append().no_create())?;
+        let (mut file, buffered) =
+            std::mem::replace(&mut self.file, Self::buffered(reopened, &self.config)).into_parts();
+        let buffered = buffered.unwrap_or_else(|panicked| panicked.into_inner());
+        let len = file.len()?;
+        if len >= self.write_offset {
+            file.set_len(self.write_offset)?;
+        } else {
+            // Whole frames still buffered ahead of the torn one
+            let missing = ((self.write_offset - len) as usize).min(buffered.len());
+            file.write_all(&buffered[..missing])?;
+        }
+        Ok(())
+    }
+
     /// Write a pre-serialized record with framing (single buffer).
     fn write_record(&mut self, lsn: u64, record_data: &[u8]) -> Result<()> {
         let payload = Self::compress_if_worthwhile(record_data);
@@ -963,7 +1430,7 @@ impl PartitionWAL {
         buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
         buf.extend_from_slice(&payload);
 
-        self.file.write_all(&buf)?;
+        self.write_frames(&mut buf)?;
 
         Ok(())
     }
@@ -1013,11 +1480,17 @@ impl PartitionWAL {
         }
 
         // 2. Single write operation (append 模式自动追加)
-        self.file.write_all(&buffer)?;
+        self.write_frames(&mut buffer)?;
 
-        // 3. Fsync based on durability level
+        // 3. Fsync based on durability level (unless this write overrides it)
+        if self.apply_commit_mode()? {
+            return Ok(lsns);
+        }
         match self.config.durability_level {
-            DurabilityLevel::Synchronous | DurabilityLevel::GroupCommit { .. } => {
+            DurabilityLevel::Synchronous => {
+                self.sync_commit()?;
+            }
+            DurabilityLevel::GroupCommit { .. } => {
                 self.sync_flush()?;
             }
             DurabilityLevel::Periodic { .. } => {
@@ -1040,7 +1513,8 @@ impl PartitionWAL {
     ///
     /// If crash occurs at any point, the original WAL is intact.
     fn checkpoint(&mut self) -> Result<()> {
-        if self.next_lsn == 0 {
+        // A read-only WAL never gained records to checkpoint
+        if self.next_lsn == 0 || self.file.get_ref().is_read_only() {
             return Ok(());
         }
 
@@ -1049,35 +1523,34 @@ impl PartitionWAL {
         self.last_checkpoint = lsn;
 
         // Ensure checkpoint record is durable BEFORE truncating.
-        self.file.flush()?;
-        self.file.get_ref().sync_all()?;
+        self.sync_flush()?;
 
         // Atomic truncation: write-new-rename pattern
-        // Create a fresh empty WAL file at a temp path
+        // Create a fresh WAL file (header only, new salt) at a temp path
         let tmp_path = self.path.with_extension("wal.tmp");
-        {
-            let tmp_file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&tmp_path)?;
-            tmp_file.sync_all()?;
-        }
+        let salt = {
+            let mut tmp_file = self.backend.open(&tmp_path, OpenFlags::create_truncate())?;
+            Self::write_header(tmp_file.as_mut())?
+        };
 
         // Atomic rename: temp → original (on same filesystem, this is atomic)
-        std::fs::rename(&tmp_path, &self.path)?;
-        crate::fsync_dir(&self.path);
+        failpoint::check(failpoint::WAL_CHECKPOINT)?;
+        self.backend.rename(&tmp_path, &self.path)?;
+        self.backend.sync_parent_dir(&self.path);
 
         // Reopen the new empty file
-        let file = OpenOptions::new()
-            .read(true)
-            .append(true)
-            .open(&self.path)?;
-        self.file = BufWriter::new(file);
+        let file = self
+            .backend
+            .open(&self.path, OpenFlags::append().no_create())?;
+        self.file = Self::buffered(file, &self.config);
 
         // Reset counters
         self.next_lsn = 0;
         self.last_checkpoint = 0;
+        self.write_offset = WAL_HEADER_SIZE;
+        self.unsynced = false;
+        self.chained = true;
+        self.chain = salt;
 
         Ok(())
     }
@@ -1093,122 +1566,33 @@ impl PartitionWAL {
 
     fn recover(&mut self) -> Result<Vec<WALRecord>> {
         let mut records = Vec::new();
-        let mut file = File::open(&self.path)?;
-        file.seek(SeekFrom::Start(0))?;
-
+        let mut file = self.backend.open(&self.path, OpenFlags::read_only())?;
+        let last_checkpoint = self.last_checkpoint;
         let mut skipped_corrupted = 0;
 
-        loop {
-            // Read total length prefix
-            let mut len_buf = [0u8; 4];
-            match file.read_exact(&mut len_buf) {
-                Ok(_) => {}
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
-                Err(e) => return Err(e.into()),
-            }
-
-            let total_len = u32::from_le_bytes(len_buf) as usize;
-
-            // Sanity check: reject obviously corrupted total_len
-            if !(20..=Self::MAX_WAL_FRAME_SIZE).contains(&total_len) {
-                debug_log!("WAL recovery: Corrupted total_len={}, stopping", total_len);
-                break;
-            }
-
-            // Read header: [u64 lsn][u32 checksum][u32 record_len] = 16 bytes
-            let mut header = [0u8; 16];
-            match file.read_exact(&mut header) {
-                Ok(_) => {}
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                    debug_log!("WAL recovery: Detected partial write (header), skipping");
-                    break;
-                }
-                Err(e) => return Err(e.into()),
-            }
-
-            let lsn = u64::from_le_bytes(header[0..8].try_into().unwrap());
-            let checksum = u32::from_le_bytes(header[8..12].try_into().unwrap());
-            let record_len = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
-
-            // record_len must fit inside total_len (20 bytes = 4 len prefix + 16 header)
-            if record_len > total_len.saturating_sub(20) || record_len > Self::MAX_WAL_FRAME_SIZE {
-                debug_log!(
-                    "WAL recovery: Corrupted record_len={} (total_len={}), stopping",
-                    record_len,
-                    total_len
-                );
-                break;
-            }
-
-            // Read record data
-            let mut record_data = vec![0u8; record_len];
-            match file.read_exact(&mut record_data) {
-                Ok(_) => {}
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                    debug_log!("WAL recovery: Detected partial write (record), skipping");
-                    break;
-                }
-                Err(e) => return Err(e.into()),
-            }
-
-            // Verify checksum directly on record_data (no re-serialization needed!)
-            if let Err(e) = Checksum::verify(ChecksumType::CRC32C, &record_data, checksum) {
-                debug_log!(
-                    "WAL recovery: Checksum verification failed for LSN {}: {}",
-                    lsn,
-                    e
-                );
-                skipped_corrupted += 1;
-                // Seek to next record boundary using total_len to correct for
-                // potentially corrupted record_len in the header.
-                if total_len > 20 {
-                    let seek_offset = (total_len - 20) as i64 - record_len as i64;
-                    if seek_offset != 0 {
-                        if let Err(seek_err) = file.seek(SeekFrom::Current(seek_offset)) {
-                            debug_log!(
-                                "WAL recovery: Failed to seek past corrupted record: {}",
-                                seek_err
-                            );
-                            break;
-                        }
-                    }
-                }
-                continue;
-            }
-
+        let scan = Self::scan(file.as_mut(), |lsn, record_data| {
             // Deserialize record (native binary with bincode fallback)
-            let record: WALRecord = match WALRecord::decode_with_fallback(&record_data) {
+            let record = match WALRecord::decode_with_fallback(record_data) {
                 Ok(r) => r,
                 Err(e) => {
                     debug_log!("WAL recovery: Failed to deserialize record: {}", e);
                     skipped_corrupted += 1;
-                    // Checksum was valid but deser failed — cursor is already past record data,
-                    // but correct using total_len in case record_len was inconsistent.
-                    if total_len > 20 {
-                        let seek_offset = (total_len - 20) as i64 - record_len as i64;
-                        if seek_offset != 0 {
-                            if let Err(seek_err) = file.seek(SeekFrom::Current(seek_offset)) {
-                                debug_log!(
-                                    "WAL recovery: Failed to seek past corrupted record: {}",
-                                    seek_err
-                                );
-                                break;
-                            }
-                        }
-                    }
-                    continue;
+                    return;
                 }
             };
 
             // Only include records after last checkpoint (>= for LSN starting at 0)
-            if lsn >= self.last_checkpoint {
-                // Skip the checkpoint record itself
-                if !matches!(record, WALRecord::Checkpoint { .. }) {
-                    records.push(record);
+            if lsn >= last_checkpoint {
+                match record {
+                    // Skip the checkpoint record itself
+                    WALRecord::Checkpoint { .. } => {}
+                    WALRecord::Batch { records: batch } => records.extend(batch),
+                    record => records.push(record),
                 }
             }
-        }
+        })?;
 
+        skipped_corrupted += scan.corrupted;
         if skipped_corrupted > 0 {
             debug_log!(
                 "WAL recovery: Skipped {} corrupted records",
@@ -1299,13 +1683,24 @@ impl WALManager {
         num_partitions: u8,
         config: WALConfig,
     ) -> Result<Self> {
+        Self::create_with_backend(base_path, num_partitions, config, default_backend())
+    }
+
+    /// Create a new WAL manager whose partition files live on `backend`
+    pub fn create_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        num_partitions: u8,
+        config: WALConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let config = Self::without_background_sync(config);
         let base_path = base_path.as_ref().to_path_buf();
-        std::fs::create_dir_all(&base_path)?;
+        backend.create_dir_all(&base_path)?;
 
         let partitions = DashMap::new();
         for partition_id in 0..num_partitions {
             let wal_path = base_path.join(format!("partition_{}.wal", partition_id));
-            let wal = PartitionWAL::create_with_config(wal_path, config.clone())?;
+            let wal = PartitionWAL::create_with_config(wal_path, config.clone(), backend.clone())?;
             partitions.insert(partition_id, parking_lot::Mutex::new(wal));
         }
 
@@ -1339,16 +1734,29 @@ impl WALManager {
         num_partitions: u8,
         config: WALConfig,
     ) -> Result<Self> {
+        Self::open_with_backend(base_path, num_partitions, config, default_backend())
+    }
+
+    /// Open an existing WAL manager whose partition files live on `backend`
+    pub fn open_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        num_partitions: u8,
+        config: WALConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let config = Self::without_background_sync(config);
         let base_path = base_path.as_ref().to_path_buf();
 
         let partitions = DashMap::new();
         for partition_id in 0..num_partitions {
             let wal_path = base_path.join(format!("partition_{}.wal", partition_id));
-            if wal_path.exists() {
-                let wal = PartitionWAL::open_with_config(wal_path, config.clone())?;
+            if backend.exists(&wal_path) {
+                let wal =
+                    PartitionWAL::open_with_config(wal_path, config.clone(), backend.clone())?;
                 partitions.insert(partition_id, parking_lot::Mutex::new(wal));
             } else {
-                let wal = PartitionWAL::create_with_config(wal_path, config.clone())?;
+                let wal =
+                    PartitionWAL::create_with_config(wal_path, config.clone(), backend.clone())?;
                 partitions.insert(partition_id, parking_lot::Mutex::new(wal));
             }
         }
@@ -1372,6 +1780,19 @@ impl WALManager {
         })
     }
 
+    /// Group commit and periodic sync both fsync from a background thread.
+    /// Where there are no threads, every commit syncs itself instead.
+    fn without_background_sync(mut config: WALConfig) -> WALConfig {
+        if !crate::storage::platform::threads_available() {
+            if let DurabilityLevel::GroupCommit { .. } | DurabilityLevel::Periodic { .. } =
+                config.durability_level
+            {
+                config.durability_level = DurabilityLevel::Synchronous;
+            }
+        }
+        config
+    }
+
     /// Start background flush thread (Periodic mode) with adaptive backoff.
     ///
     /// When no writes are detected, the sleep interval doubles up to `max_idle_ms`
@@ -1597,7 +2018,11 @@ impl WALManager {
     ) -> Result<LogSequenceNumber> {
         self.periodic_new_writes.store(true, Ordering::Relaxed);
 
-        if let Some(ref gc) = self.group_commit {
+        let group_commit = self
+            .group_commit
+            .as_ref()
+            .filter(|_| self.uses_group_commit());
+        if let Some(gc) = group_commit {
             // Check for prior flush errors before enqueuing
             if let Some(e) = gc.state.last_error.lock().take() {
                 return Err(e);
@@ -1669,6 +2094,12 @@ impl WALManager {
         }
     }
 
+    /// Whether writes on this thread go through the group-commit queue.
+    /// Async writes don't wait for the group fsync; they append directly.
+    fn uses_group_commit(&self) -> bool {
+        self.group_commit.is_some() && commit_mode() != Some(CommitMode::Async)
+    }
+
     /// Check for any background flush errors accumulated by the group commit thread.
     /// Returns and clears the last error if one exists.
     pub fn check_flush_errors(&self) -> Option<StorageError> {
@@ -1772,7 +2203,7 @@ impl WALManager {
     ) -> Result<LogSequenceNumber> {
         self.periodic_new_writes.store(true, Ordering::Relaxed);
 
-        if self.group_commit.is_some() {
+        if self.uses_group_commit() {
             // GroupCommit: fall back to owned variant (needs the record in the queue)
             return self.log_insert_raw(table_name, partition, row_id, raw_data.to_vec(), txn_id);
         }
@@ -1798,7 +2229,7 @@ impl WALManager {
     ) -> Result<LogSequenceNumber> {
         self.periodic_new_writes.store(true, Ordering::Relaxed);
 
-        if self.group_commit.is_some() {
+        if self.uses_group_commit() {
             // GroupCommit: use the owned record path
             let record = WALRecord::UpdateRaw {
                 table_name: table_name.to_string(),
@@ -1833,7 +2264,7 @@ impl WALManager {
     ) -> Result<LogSequenceNumber> {
         self.periodic_new_writes.store(true, Ordering::Relaxed);
 
-        if self.group_commit.is_some() {
+        if self.uses_group_commit() {
             // GroupCommit needs owned data — clone here (rare path)
             let record = WALRecord::UpdateRaw {
                 table_name: table_name.to_string(),
@@ -1983,6 +2414,14 @@ impl WALManager {
         wal.checkpoint()
     }
 
+    /// Bytes held by all partition logs, including writes still buffered
+    pub fn size_bytes(&self) -> u64 {
+        self.partitions
+            .iter()
+            .map(|entry| entry.value().lock().write_offset)
+            .sum()
+    }
+
     /// Checkpoint all partitions
     pub fn checkpoint_all(&self) -> Result<()> {
         for entry in self.partitions.iter() {
@@ -2244,11 +2683,227 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_wal_batch_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal = WALManager::create(temp_dir.path(), 2).unwrap();
+
+        let insert = |row_id| WALRecord::InsertRaw {
+            table_name: "a".into(),
+            row_id,
+            partition: 0,
+            raw_data: vec![7; 200],
+            txn_id: 0,
+        };
+        let batch = WALRecord::Batch {
+            records: vec![
+                insert(1),
+                insert(2),
+                WALRecord::DeleteRaw {
+                    table_name: "b".into(),
+                    row_id: 9,
+                    partition: 1,
+                    raw_old: vec![1, 2, 3],
+                    timestamp: 42,
+                    txn_id: 0,
+                },
+                WALRecord::DeleteRange {
+                    table_name: "c".into(),
+                    start_row_id: 100,
+                    end_row_id: 164,
+                    txn_id: 0,
+                },
+            ],
+        };
+
+        // Round trip, including a truncated frame
+        let bytes = batch.encode_native().unwrap();
+        match WALRecord::decode_native(&bytes).unwrap().unwrap() {
+            WALRecord::Batch { records } => assert_eq!(records.len(), 4),
+            other => panic!("expected batch, got {:?}", other),
+        }
+        assert!(WALRecord::decode_native(&bytes[..bytes.len() - 1]).is_none());
+        let nested = WALRecord::Batch {
+            records: vec![batch.clone()],
+        };
+        assert!(nested.encode_native().is_err());
+
+        // Recovery returns the inner records in place of the batch
+        wal.batch_append(0, vec![batch]).unwrap();
+        let recovered = wal.recover().unwrap();
+        let records = recovered.get(&0).unwrap();
+        assert_eq!(records.len(), 4);
+        assert!(matches!(records[1], WALRecord::InsertRaw { row_id: 2, .. }));
+        assert!(matches!(
+            records[2],
+            WALRecord::DeleteRaw {
+                row_id: 9,
+                timestamp: 42,
+                ..
+            }
+        ));
+        assert!(matches!(
+            records[3],
+            WALRecord::DeleteRange {
+                start_row_id: 100,
+                end_row_id: 164,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_wal_commit_mode_override() {
+        assert_eq!(commit_mode(), None);
+        with_commit_mode(Some(CommitMode::Async), || {
+            assert_eq!(commit_mode(), Some(CommitMode::Async));
+            with_commit_mode(Some(CommitMode::Sync), || {
+                assert_eq!(commit_mode(), Some(CommitMode::Sync));
+            });
+            with_commit_mode(None, || {
+                assert_eq!(commit_mode(), Some(CommitMode::Async));
+            });
+        });
+        assert_eq!(commit_mode(), None);
+
+        // Async writes skip the group-commit queue but are still logged
+        let temp_dir = TempDir::new().unwrap();
+        let config = WALConfig {
+            durability_level: DurabilityLevel::group_commit(),
+            ..Default::default()
+        };
+        let wal = WALManager::create_with_config(temp_dir.path(), 1, config).unwrap();
+        with_commit_mode(Some(CommitMode::Async), || {
+            wal.log_insert_raw_ref("t", 0, 1, &[1, 2, 3], 0).unwrap();
+            wal.log_commit(0, 0, 1).unwrap();
+        });
+        let recovered = wal.recover().unwrap();
+        assert_eq!(recovered.get(&0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_wal_flash_page_alignment() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = WALConfig {
+            durability_level: DurabilityLevel::Synchronous,
+            flash: Some(FlashWriteConfig::default()),
+        };
+        let wal_path = temp_dir.path().join("partition_0.wal");
+        {
+            let wal = WALManager::create_with_config(temp_dir.path(), 1, config.clone()).unwrap();
+            for row_id in 0..5 {
+                wal.log_insert_raw_ref("t", 0, row_id, &[7; 100], 0)
+                    .unwrap();
+                // A per-commit sync is never padded to a page
+                assert_ne!(std::fs::metadata(&wal_path).unwrap().len() % 4096, 0);
+            }
+            assert!(std::fs::metadata(&wal_path).unwrap().len() < 4096);
+        }
+
+        // A coalesced sync ends the log on a page boundary
+        let periodic = WALConfig {
+            durability_level: DurabilityLevel::Periodic {
+                interval_ms: 60_000,
+            },
+            ..config.clone()
+        };
+        {
+            let wal = WALManager::open_with_config(temp_dir.path(), 1, periodic).unwrap();
+            wal.log_insert_raw_ref("t", 0, 5, &[7; 100], 0).unwrap();
+            wal.shutdown();
+            assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 4096);
+        }
+
+        // Padding frames are skipped on reopen and replay
+        let wal = WALManager::open_with_config(temp_dir.path(), 1, config).unwrap();
+        assert_eq!(wal.log_insert_raw_ref("t", 0, 6, &[7; 100], 0).unwrap(), 6);
+        let recovered = wal.recover().unwrap();
+        assert_eq!(recovered.get(&0).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_wal_chain_stops_at_torn_frame() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = WALConfig {
+            durability_level: DurabilityLevel::Synchronous,
+            ..Default::default()
+        };
+        let wal_path = temp_dir.path().join("partition_0.wal");
+        let mut frame_ends = Vec::new();
+        {
+            let wal = WALManager::create_with_config(temp_dir.path(), 1, config.clone()).unwrap();
+            for row_id in 0..3 {
+                wal.log_insert_raw_ref("t", 0, row_id, &[row_id as u8; 40], 0)
+                    .unwrap();
+                frame_ends.push(std::fs::metadata(&wal_path).unwrap().len());
+            }
+        }
+        let data = std::fs::read(&wal_path).unwrap();
+        assert_eq!(
+            u32::from_le_bytes(data[0..4].try_into().unwrap()),
+            WAL_MAGIC
+        );
+
+        // Tear the second frame: later frames are unreachable even though
+        // their own bytes are intact
+        let mut torn = data.clone();
+        torn[frame_ends[0] as usize + FRAME_HEADER_SIZE + 1] ^= 0xFF;
+        std::fs::write(&wal_path, &torn).unwrap();
+
+        let wal = WALManager::open_with_config(temp_dir.path(), 1, config).unwrap();
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), frame_ends[0]);
+        assert_eq!(wal.recover().unwrap().get(&0).unwrap().len(), 1);
+
+        // Appends continue the chain from the last intact frame
+        assert_eq!(wal.log_insert_raw_ref("t", 0, 9, &[9; 40], 0).unwrap(), 1);
+        let recovered = wal.recover().unwrap();
+        let row_ids: Vec<_> = recovered[&0].iter().filter_map(|r| r.row_id()).collect();
+        assert_eq!(row_ids, vec![0, 9]);
+    }
+
+    #[test]
+    fn test_wal_legacy_file_upgraded_at_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = WALConfig {
+            durability_level: DurabilityLevel::Synchronous,
+            ..Default::default()
+        };
+        let wal_path = temp_dir.path().join("partition_0.wal");
+
+        // Headerless file with plain per-frame checksums
+        let payload = WALRecord::Begin {
+            txn_id: 7,
+            isolation_level: 0,
+        }
+        .encode_native()
+        .unwrap();
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&((FRAME_HEADER_SIZE + payload.len()) as u32).to_le_bytes());
+        frame.extend_from_slice(&0u64.to_le_bytes());
+        frame.extend_from_slice(&Checksum::compute(ChecksumType::CRC32C, &payload).to_le_bytes());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        std::fs::write(&wal_path, &frame).unwrap();
+
+        let wal = WALManager::open_with_config(temp_dir.path(), 1, config).unwrap();
+        assert_eq!(wal.log_begin(0, 8, 0).unwrap(), 1);
+        assert_eq!(wal.recover().unwrap()[&0].len(), 2);
+
+        wal.checkpoint(0).unwrap();
+        let data = std::fs::read(&wal_path).unwrap();
+        assert_eq!(data.len() as u64, WAL_HEADER_SIZE);
+        assert_eq!(
+            u32::from_le_bytes(data[0..4].try_into().unwrap()),
+            WAL_MAGIC
+        );
+    }
+
     #[test]
     fn test_wal_transaction_boundaries() {
         let temp_dir = TempDir::new().unwrap();
         let config = WALConfig {
             durability_level: DurabilityLevel::Synchronous,
+            ..Default::default()
         };
         let wal = WALManager::create_with_config(temp_dir.path(), 2, config).unwrap();
 
@@ -2284,6 +2939,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let config = WALConfig {
             durability_level: DurabilityLevel::Synchronous,
+            ..Default::default()
         };
         let wal = WALManager::create_with_config(temp_dir.path(), 2, config).unwrap();
 
@@ -2594,13 +3250,15 @@ mod tests {
         let wal_path = path.join("partition_0.wal");
         let mut data = std::fs::read(&wal_path).unwrap();
 
-        if data.len() < 24 {
+        let start = WAL_HEADER_SIZE as usize;
+        if data.len() < start + 24 {
             eprintln!("WAL data too short for corruption test, skipping");
             return;
         }
-        let first_total_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+        let first_total_len =
+            u32::from_le_bytes(data[start..start + 4].try_into().unwrap()) as usize;
         // Corrupt the total_len of the 2nd record
-        let corrupt_offset = 4 + first_total_len;
+        let corrupt_offset = start + first_total_len;
         if corrupt_offset + 4 <= data.len() {
             data[corrupt_offset] = 0xFF;
             data[corrupt_offset + 1] = 0xFF;
@@ -2636,10 +3294,11 @@ mod tests {
         let wal_path = path.join("partition_0.wal");
         let mut data = std::fs::read(&wal_path).unwrap();
 
-        if data.len() >= 20 {
-            let total_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+        let start = WAL_HEADER_SIZE as usize;
+        if data.len() >= start + 20 {
+            let total_len = u32::from_le_bytes(data[start..start + 4].try_into().unwrap()) as usize;
             let bogus_record_len = (total_len + 1000) as u32;
-            data[16..20].copy_from_slice(&bogus_record_len.to_le_bytes());
+            data[start + 16..start + 20].copy_from_slice(&bogus_record_len.to_le_bytes());
             std::fs::write(&wal_path, &data).unwrap();
         }
 
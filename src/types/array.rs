@@ -0,0 +1,148 @@
+//! ARRAY values: storage encoding and element coercion
+//!
+//! Arrays are kept in the columnar Text layout as a JSON array. Elements
+//! that JSON can't represent exactly (DECIMAL, UUID, DATE, TIME and
+//! non-finite floats) are written as their canonical strings and parsed
+//! back using the column's declared element type.
+
+use crate::types::{ColumnType, Timestamp, Value};
+use serde_json::Value as Json;
+
+/// Canonical storage string of an array's elements
+pub fn encode(items: &[Value]) -> String {
+    Json::Array(items.iter().map(element_to_json).collect()).to_string()
+}
+
+fn element_to_json(v: &Value) -> Json {
+    match v {
+        Value::Integer(i) => Json::from(*i),
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map_or_else(|| Json::String(f.to_string()), Json::Number),
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Text(s) => Json::String(s.as_str().to_string()),
+        Value::Timestamp(ts) => Json::from(ts.as_micros()),
+        Value::Null => Json::Null,
+        other => other
+            .encoded_text()
+            .map_or_else(|| Json::String(format!("{:?}", other)), Json::String),
+    }
+}
+
+/// Parse a storage string (or user-supplied JSON text) into elements of `elem`
+pub fn decode(s: &str, elem: &ColumnType) -> Result<Vec<Value>, String> {
+    let items = match serde_json::from_str::<Json>(s) {
+        Ok(Json::Array(items)) => items,
+        _ => return Err(format!("invalid ARRAY: '{}'", s)),
+    };
+    items
+        .into_iter()
+        .map(|j| {
+            let v = match j {
+                Json::Null => Value::Null,
+                Json::Bool(b) => Value::Bool(b),
+                Json::Number(n) => match n.as_i64() {
+                    Some(i) => Value::Integer(i),
+                    None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+                },
+                Json::String(s) => match elem {
+                    ColumnType::Float => s
+                        .parse()
+                        .map(Value::Float)
+                        .map_err(|_| format!("invalid FLOAT element: '{}'", s))?,
+                    _ => Value::text(s),
+                },
+                other => return Err(format!("unsupported ARRAY element: {}", other)),
+            };
+            coerce_element(elem, v)
+        })
+        .collect()
+}
+
+/// Convert one element to the declared element type
+pub fn coerce_element(elem: &ColumnType, v: Value) -> Result<Value, String> {
+    let converted = match (elem, v) {
+        (_, Value::Null) => return Ok(Value::Null),
+        (ColumnType::Integer, Value::Integer(i)) => Value::Integer(i),
+        (ColumnType::Integer, Value::Float(f)) if f.fract() == 0.0 && f.abs() < 9.0e18 => {
+            Value::Integer(f as i64)
+        }
+        (ColumnType::Float, Value::Float(f)) => Value::Float(f),
+        (ColumnType::Float, Value::Integer(i)) => Value::Float(i as f64),
+        (ColumnType::Float, Value::Decimal(d)) => Value::Float(d.to_f64()),
+        (ColumnType::Boolean, Value::Bool(b)) => Value::Bool(b),
+        (ColumnType::Text, Value::Text(s)) => Value::Text(s),
+        (ColumnType::Timestamp, Value::Timestamp(ts)) => Value::Timestamp(ts),
+        (ColumnType::Timestamp, Value::Integer(i)) => Value::Timestamp(Timestamp::from_micros(i)),
+        (ct, v) if ct.is_text_encoded() => {
+            let v = ct.coerce(v)?;
+            // ENUM elements stay Text (their labels)
+            if v.encoded_text().is_none() && !matches!(v, Value::Text(_)) {
+                return Err(format!("expected {:?} element, got {:?}", ct, v));
+            }
+            v
+        }
+        (ct, v) => return Err(format!("expected {:?} element, got {:?}", ct, v)),
+    };
+    Ok(converted)
+}
+
+/// Element type of an array without a declared one (derived tables),
+/// taken from its first non-NULL element
+pub fn element_type(items: &[Value]) -> ColumnType {
+    match items.iter().find(|v| !matches!(v, Value::Null)) {
+        Some(Value::Integer(_)) => ColumnType::Integer,
+        Some(Value::Float(_)) => ColumnType::Float,
+        Some(Value::Bool(_)) => ColumnType::Boolean,
+        Some(Value::Timestamp(_)) => ColumnType::Timestamp,
+        Some(Value::Decimal(d)) => ColumnType::Decimal {
+            precision: crate::types::DECIMAL_MAX_PRECISION,
+            scale: d.scale(),
+        },
+        Some(Value::Uuid(_)) => ColumnType::Uuid,
+        Some(Value::Date(_)) => ColumnType::Date,
+        Some(Value::Time(_)) => ColumnType::Time,
+        _ => ColumnType::Text,
+    }
+}
+
+/// Widen f32 vector components without exposing binary noise
+/// (0.9f32 becomes 0.9, not 0.8999999761581421)
+pub fn f32_to_f64(f: f32) -> f64 {
+    f.to_string().parse().unwrap_or(f as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let items = vec![Value::Float(0.5), Value::Null, Value::Float(f64::NAN)];
+        let s = encode(&items);
+        assert_eq!(s, r#"[0.5,null,"NaN"]"#);
+        let back = decode(&s, &ColumnType::Float).unwrap();
+        assert_eq!(back[0], Value::Float(0.5));
+        assert_eq!(back[1], Value::Null);
+        assert!(matches!(back[2], Value::Float(f) if f.is_nan()));
+
+        let labels = vec![Value::text_from("car"), Value::text_from("say \"hi\"")];
+        assert_eq!(decode(&encode(&labels), &ColumnType::Text).unwrap(), labels);
+
+        let days = vec![Value::Date("2024-03-01".parse().unwrap())];
+        assert_eq!(encode(&days), r#"["2024-03-01"]"#);
+        assert_eq!(decode(&encode(&days), &ColumnType::Date).unwrap(), days);
+    }
+
+    #[test]
+    fn test_element_coercion() {
+        assert_eq!(
+            coerce_element(&ColumnType::Integer, Value::Float(3.0)),
+            Ok(Value::Integer(3))
+        );
+        assert!(coerce_element(&ColumnType::Integer, Value::Float(3.5)).is_err());
+        assert!(coerce_element(&ColumnType::Text, Value::Integer(1)).is_err());
+        assert!(decode("[1, 2", &ColumnType::Integer).is_err());
+        assert!(decode(r#"["x"]"#, &ColumnType::Integer).is_err());
+        assert_eq!(f32_to_f64(0.9), 0.9);
+    }
+}
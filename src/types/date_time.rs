@@ -0,0 +1,518 @@
+//! Calendar DATE and wall-clock TIME types, and fixed UTC offsets
+//!
+//! Both are timezone-free, like `Timestamp` (which is UTC). Their canonical
+//! strings (`YYYY-MM-DD`, `HH:MM:SS[.ffffff]`) sort in the same order as the
+//! values, so they can live in the columnar Text layout unchanged.
+//! [`UtcOffset`] never changes a stored timestamp: it decides how one is
+//! rendered and how wall-clock text without its own offset is read.
+
+use crate::types::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Days from 1970-01-01 to 0000-01-01 and to 9999-12-31
+const MIN_DAYS: i32 = -719_528;
+const MAX_DAYS: i32 = 2_932_896;
+
+/// Calendar date (days since 1970-01-01), years 0000 through 9999
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    days: i32,
+}
+
+impl Date {
+    /// Date from days since 1970-01-01; `None` outside years 0000-9999
+    pub fn from_days(days: i64) -> Option<Self> {
+        if (MIN_DAYS as i64..=MAX_DAYS as i64).contains(&days) {
+            Some(Self { days: days as i32 })
+        } else {
+            None
+        }
+    }
+
+    /// Date from a year, month (1-12) and day (1-31); `None` if invalid
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Self::from_days(days_from_civil(year, month, day))
+    }
+
+    /// UTC calendar date of a timestamp
+    pub fn from_timestamp(ts: Timestamp) -> Option<Self> {
+        Self::from_days(ts.as_micros().div_euclid(MICROS_PER_DAY))
+    }
+
+    /// Today's UTC date
+    pub fn today() -> Self {
+        Self::from_timestamp(Timestamp::now()).unwrap_or(Self { days: 0 })
+    }
+
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Big-endian key with the sign bit flipped, so byte order is date order
+    pub fn sort_key(&self) -> [u8; 4] {
+        ((self.days as u32) ^ 0x8000_0000).to_be_bytes()
+    }
+
+    pub fn from_sort_key(key: [u8; 4]) -> Option<Self> {
+        Self::from_days((u32::from_be_bytes(key) ^ 0x8000_0000) as i32 as i64)
+    }
+
+    /// (year, month, day)
+    pub fn ymd(&self) -> (i64, u32, u32) {
+        civil_from_days(self.days as i64)
+    }
+
+    /// ISO day of week: 1 = Monday … 7 = Sunday
+    pub fn day_of_week(&self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (self.days as i64 + 3).rem_euclid(7) + 1
+    }
+
+    /// Shift by a number of days; `None` if the result leaves 0000-9999
+    pub fn add_days(&self, days: i64) -> Option<Self> {
+        Self::from_days((self.days as i64).checked_add(days)?)
+    }
+
+    /// Midnight UTC at the start of this date, optionally plus a time of day
+    pub fn to_timestamp(&self, time: Option<Time>) -> Timestamp {
+        Timestamp::from_micros(
+            self.days as i64 * MICROS_PER_DAY + time.map_or(0, |t| t.as_micros()),
+        )
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (y, m, d) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", y, m, d)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Accepts `YYYY-MM-DD`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid DATE: '{}'", s);
+        let b = s.trim().as_bytes();
+        if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+            return Err(invalid());
+        }
+        let year = parse_digits(&b[..4]).ok_or_else(invalid)?;
+        let month = parse_digits(&b[5..7]).ok_or_else(invalid)?;
+        let day = parse_digits(&b[8..]).ok_or_else(invalid)?;
+        Self::from_ymd(year as i64, month, day).ok_or_else(invalid)
+    }
+}
+
+/// Time of day with microsecond precision, 00:00:00 through 23:59:59.999999
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Time {
+    /// Microseconds since midnight
+    micros: i64,
+}
+
+impl Time {
+    /// Time from microseconds since midnight; `None` outside one day
+    pub fn from_micros(micros: i64) -> Option<Self> {
+        if (0..MICROS_PER_DAY).contains(&micros) {
+            Some(Self { micros })
+        } else {
+            None
+        }
+    }
+
+    pub fn from_hms_micro(hour: u32, minute: u32, second: u32, micro: u32) -> Option<Self> {
+        if hour > 23 || minute > 59 || second > 59 || micro > 999_999 {
+            return None;
+        }
+        let secs = (hour * 3600 + minute * 60 + second) as i64;
+        Self::from_micros(secs * 1_000_000 + micro as i64)
+    }
+
+    /// UTC time of day of a timestamp
+    pub fn from_timestamp(ts: Timestamp) -> Self {
+        Self {
+            micros: ts.as_micros().rem_euclid(MICROS_PER_DAY),
+        }
+    }
+
+    /// Current UTC time of day
+    pub fn now() -> Self {
+        Self::from_timestamp(Timestamp::now())
+    }
+
+    pub fn as_micros(&self) -> i64 {
+        self.micros
+    }
+
+    pub fn hour(&self) -> i64 {
+        self.micros / 3_600_000_000
+    }
+
+    pub fn minute(&self) -> i64 {
+        self.micros / 60_000_000 % 60
+    }
+
+    pub fn second(&self) -> i64 {
+        self.micros / 1_000_000 % 60
+    }
+}
+
+impl fmt::Display for Time {
+    /// `HH:MM:SS`, plus the fraction with trailing zeros trimmed when non-zero
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.hour(),
+            self.minute(),
+            self.second()
+        )?;
+        let frac = self.micros % 1_000_000;
+        if frac != 0 {
+            let digits = format!("{:06}", frac);
+            write!(f, ".{}", digits.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Time {
+    type Err = String;
+
+    /// Accepts `HH:MM`, `HH:MM:SS` and `HH:MM:SS.f` with up to 6 fraction digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid TIME: '{}'", s);
+        let b = s.trim().as_bytes();
+        if b.len() < 5 || b[2] != b':' {
+            return Err(invalid());
+        }
+        let hour = parse_digits(&b[..2]).ok_or_else(invalid)?;
+        let minute = parse_digits(&b[3..5]).ok_or_else(invalid)?;
+        let (second, micro) = match &b[5..] {
+            [] => (0, 0),
+            [b':', rest @ ..] if rest.len() >= 2 => {
+                let second = parse_digits(&rest[..2]).ok_or_else(invalid)?;
+                let micro = match &rest[2..] {
+                    [] => 0,
+                    [b'.', frac @ ..] if (1..=6).contains(&frac.len()) => {
+                        parse_digits(frac).ok_or_else(invalid)? * 10u32.pow(6 - frac.len() as u32)
+                    }
+                    _ => return Err(invalid()),
+                };
+                (second, micro)
+            }
+            _ => return Err(invalid()),
+        };
+        Self::from_hms_micro(hour, minute, second, micro).ok_or_else(invalid)
+    }
+}
+
+/// Fixed offset from UTC, -18:00 through +18:00 with minute precision
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UtcOffset {
+    secs: i32,
+}
+
+impl UtcOffset {
+    pub const UTC: Self = Self { secs: 0 };
+
+    /// Offset of `secs` seconds east of UTC; `None` beyond ±18 hours
+    pub fn from_secs(secs: i32) -> Option<Self> {
+        if secs.abs() <= 18 * 3600 {
+            Some(Self { secs })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_secs(&self) -> i32 {
+        self.secs
+    }
+
+    /// Wall-clock time in this offset of a UTC instant
+    pub fn to_local(&self, ts: Timestamp) -> Timestamp {
+        Timestamp::from_micros(ts.as_micros().saturating_add(self.secs as i64 * 1_000_000))
+    }
+
+    /// UTC instant of a wall-clock time in this offset
+    pub fn from_local(&self, local: Timestamp) -> Timestamp {
+        Timestamp::from_micros(
+            local
+                .as_micros()
+                .saturating_sub(self.secs as i64 * 1_000_000),
+        )
+    }
+
+    /// Parse a timestamp (see [`parse_timestamp`]), reading it in this
+    /// offset unless the text names its own
+    pub fn parse_timestamp(&self, s: &str) -> Result<Timestamp, String> {
+        let (local, zone) = parse_timestamp(s)?;
+        Ok(zone.unwrap_or(*self).from_local(local))
+    }
+
+    /// `YYYY-MM-DDTHH:MM:SS[.ffffff]` in this offset, suffixed `Z` or `±HH:MM`
+    pub fn format_timestamp(&self, ts: Timestamp) -> String {
+        let local = self.to_local(ts);
+        // Beyond year 9999 there is no calendar form; fall back to raw micros
+        let Some(date) = Date::from_timestamp(local) else {
+            return ts.as_micros().to_string();
+        };
+        let suffix = if self.secs == 0 {
+            "Z".to_string()
+        } else {
+            self.to_string()
+        };
+        format!("{}T{}{}", date, Time::from_timestamp(local), suffix)
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    /// `UTC` for a zero offset, otherwise `±HH:MM`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.secs == 0 {
+            return f.write_str("UTC");
+        }
+        let sign = if self.secs < 0 { '-' } else { '+' };
+        let mins = self.secs.abs() / 60;
+        write!(f, "{}{:02}:{:02}", sign, mins / 60, mins % 60)
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = String;
+
+    /// Accepts `UTC`, `GMT`, `Z` and `±HH`, `±HHMM` or `±HH:MM`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid time zone: '{}'", s);
+        let s = s.trim();
+        if ["UTC", "GMT", "Z"]
+            .iter()
+            .any(|z| s.eq_ignore_ascii_case(z))
+        {
+            return Ok(Self::UTC);
+        }
+        let (sign, rest) = match s.as_bytes().first() {
+            Some(b'+') => (1, &s.as_bytes()[1..]),
+            Some(b'-') => (-1, &s.as_bytes()[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest {
+            [h1, h2] => (parse_digits(&[*h1, *h2]), Some(0)),
+            [h1, h2, m1, m2] | [h1, h2, b':', m1, m2] => {
+                (parse_digits(&[*h1, *h2]), parse_digits(&[*m1, *m2]))
+            }
+            _ => return Err(invalid()),
+        };
+        match (hours, minutes) {
+            (Some(h), Some(m)) if m < 60 => {
+                Self::from_secs(sign * (h * 3600 + m * 60) as i32).ok_or_else(invalid)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Parse `YYYY-MM-DD[( |T)HH:MM[:SS[.f]]]` with an optional zone suffix
+/// (`Z`, `UTC`, `±HH[:MM]`, optionally after a space). Returns the wall-clock
+/// time, encoded as if it were UTC, and the zone the text named, if any.
+pub fn parse_timestamp(s: &str) -> Result<(Timestamp, Option<UtcOffset>), String> {
+    let invalid = || format!("invalid TIMESTAMP: '{}'", s);
+    let s = s.trim();
+    let (Some(date), Some(rest)) = (s.get(..10), s.get(10..)) else {
+        return Err(invalid());
+    };
+    let date: Date = date.parse().map_err(|_| invalid())?;
+    let (time, zone) = match rest.as_bytes().first() {
+        None => (None, ""),
+        Some(b' ' | b'T' | b't') => {
+            let rest = &rest[1..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '.'))
+                .unwrap_or(rest.len());
+            let time: Time = rest[..end].parse().map_err(|_| invalid())?;
+            (Some(time), rest[end..].trim_start())
+        }
+        _ => return Err(invalid()),
+    };
+    let zone = match zone {
+        "" => None,
+        zone => Some(zone.parse::<UtcOffset>().map_err(|_| invalid())?),
+    };
+    Ok((date.to_timestamp(time), zone))
+}
+
+fn parse_digits(b: &[u8]) -> Option<u32> {
+    if b.is_empty() || !b.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(b.iter().fold(0, |acc, &c| acc * 10 + (c - b'0') as u32))
+}
+
+fn is_leap_year(y: i64) -> bool {
+    (y % 4 == 0 && y % 100 != 0) || y % 400 == 0
+}
+
+fn days_in_month(y: i64, m: u32) -> u32 {
+    match m {
+        2 if is_leap_year(y) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400; // [0, 399]
+    let mp = (m as i64 + 9) % 12; // March = 0
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_parse_and_display() {
+        let d: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(d.to_string(), "2024-02-29");
+        assert_eq!(d.ymd(), (2024, 2, 29));
+        assert_eq!(Date::from_days(0).unwrap().to_string(), "1970-01-01");
+        assert_eq!("1969-12-31".parse::<Date>().unwrap().days(), -1);
+        assert_eq!("0000-01-01".parse::<Date>().unwrap().days(), MIN_DAYS);
+        assert_eq!("9999-12-31".parse::<Date>().unwrap().days(), MAX_DAYS);
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2024-13-01".parse::<Date>().is_err());
+        assert!("2024-1-01".parse::<Date>().is_err());
+        assert!(Date::from_days(MAX_DAYS as i64 + 1).is_none());
+
+        let before: Date = "1969-07-20".parse().unwrap();
+        assert!(before.sort_key() < d.sort_key());
+        assert_eq!(Date::from_sort_key(before.sort_key()), Some(before));
+    }
+
+    #[test]
+    fn test_date_timestamp_conversion() {
+        let d: Date = "2024-03-15".parse().unwrap();
+        assert_eq!(d.day_of_week(), 5);
+        let ts = d.to_timestamp(Some("08:30:00".parse().unwrap()));
+        assert_eq!(Date::from_timestamp(ts), Some(d));
+        assert_eq!(Time::from_timestamp(ts).to_string(), "08:30:00");
+        // Pre-epoch instants belong to the previous day, not day 0
+        let before = Timestamp::from_micros(-1);
+        assert_eq!(
+            Date::from_timestamp(before).unwrap().to_string(),
+            "1969-12-31"
+        );
+        assert_eq!(Time::from_timestamp(before).to_string(), "23:59:59.999999");
+    }
+
+    #[test]
+    fn test_utc_offset_parse_and_format() {
+        let cst: UtcOffset = "+08:00".parse().unwrap();
+        assert_eq!(cst.as_secs(), 8 * 3600);
+        assert_eq!("+0800".parse::<UtcOffset>(), Ok(cst));
+        assert_eq!("-05".parse::<UtcOffset>().unwrap().to_string(), "-05:00");
+        assert_eq!("utc".parse::<UtcOffset>(), Ok(UtcOffset::UTC));
+        assert_eq!(UtcOffset::UTC.to_string(), "UTC");
+        assert!("+19:00".parse::<UtcOffset>().is_err());
+        assert!("+08:60".parse::<UtcOffset>().is_err());
+        assert!("Asia/Shanghai".parse::<UtcOffset>().is_err());
+
+        let d: Date = "2024-03-15".parse().unwrap();
+        let ts = d.to_timestamp(Some("20:30:00.5".parse().unwrap()));
+        assert_eq!(
+            UtcOffset::UTC.format_timestamp(ts),
+            "2024-03-15T20:30:00.5Z"
+        );
+        assert_eq!(cst.format_timestamp(ts), "2024-03-16T04:30:00.5+08:00");
+    }
+
+    #[test]
+    fn test_timestamp_parse_with_zones() {
+        let (local, zone) = parse_timestamp("2024-03-15 08:30:00").unwrap();
+        assert_eq!(zone, None);
+        assert_eq!(
+            UtcOffset::UTC.format_timestamp(local),
+            "2024-03-15T08:30:00Z"
+        );
+
+        let cst: UtcOffset = "+08:00".parse().unwrap();
+        let ts = cst.parse_timestamp("2024-03-15T08:30").unwrap();
+        assert_eq!(cst.to_local(ts), local);
+        assert_eq!(UtcOffset::UTC.format_timestamp(ts), "2024-03-15T00:30:00Z");
+        // An explicit offset wins over the default one
+        for text in [
+            "2024-03-15T00:30:00Z",
+            "2024-03-15 00:30:00 UTC",
+            "2024-03-14T19:30:00-05:00",
+            "2024-03-14 19:30-0500",
+        ] {
+            assert_eq!(cst.parse_timestamp(text), Ok(ts), "{}", text);
+        }
+        assert_eq!(
+            UtcOffset::UTC.parse_timestamp("2024-03-15").unwrap(),
+            "2024-03-15".parse::<Date>().unwrap().to_timestamp(None)
+        );
+        for bad in [
+            "2024-03-15 25:00",
+            "2024-03-15X08:00",
+            "2024-03-15 08:00 Mars",
+            "soon",
+        ] {
+            assert!(parse_timestamp(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_time_parse_and_order() {
+        let t: Time = "07:05".parse().unwrap();
+        assert_eq!(t.to_string(), "07:05:00");
+        let t: Time = "23:59:59.25".parse().unwrap();
+        assert_eq!(t.as_micros() % 1_000_000, 250_000);
+        assert_eq!(t.to_string(), "23:59:59.25");
+        assert!("24:00:00".parse::<Time>().is_err());
+        assert!("12:60".parse::<Time>().is_err());
+        assert!("12:00:00.1234567".parse::<Time>().is_err());
+
+        // Canonical strings sort like the values
+        let mut times: Vec<Time> = ["12:00:01", "12:00:00.5", "12:00:00", "12:00:00.05"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let mut strings: Vec<String> = times.iter().map(|t| t.to_string()).collect();
+        times.sort();
+        strings.sort();
+        assert_eq!(
+            times.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            strings
+        );
+    }
+}
@@ -0,0 +1,656 @@
+//! Fixed-point decimal type (DECIMAL / NUMERIC)
+//!
+//! A value is `mantissa × 10^-scale` with an i128 mantissa, so up to 38
+//! significant digits are exact. Arithmetic is checked: operations return
+//! `None` on overflow instead of silently losing digits the way f64 does.
+
+use super::Value;
+use crate::StorageError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum number of significant digits (and maximum scale)
+pub const MAX_PRECISION: u8 = 38;
+
+/// Extra fractional digits kept by division beyond the operands' scale
+const DIV_EXTRA_SCALE: u8 = 6;
+
+fn pow10(exp: u8) -> Option<i128> {
+    10i128.checked_pow(exp as u32)
+}
+
+/// Divide rounding half away from zero
+fn div_round(n: i128, d: i128) -> i128 {
+    let q = n / d;
+    let r = n % d;
+    if r.unsigned_abs() >= d.unsigned_abs() - r.unsigned_abs() {
+        if (n < 0) == (d < 0) {
+            q + 1
+        } else {
+            q - 1
+        }
+    } else {
+        q
+    }
+}
+
+/// Exact fixed-point decimal number
+///
+/// Equality, ordering and hashing are by numeric value, so `1.5` and `1.50`
+/// compare equal.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u8,
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    /// Create from a raw mantissa and scale; `None` if scale exceeds 38
+    pub fn new(mantissa: i128, scale: u8) -> Option<Self> {
+        (scale <= MAX_PRECISION).then_some(Self { mantissa, scale })
+    }
+
+    pub fn from_i64(i: i64) -> Self {
+        Self {
+            mantissa: i as i128,
+            scale: 0,
+        }
+    }
+
+    /// Convert from f64 via its shortest round-trip representation, so
+    /// `0.1` becomes exactly `0.1`. `None` for NaN, infinities and values
+    /// beyond 38 digits.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        format!("{}", f).parse().ok()
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// Number of digits in the mantissa (at least 1)
+    pub fn digits(&self) -> u8 {
+        let mut m = self.mantissa.unsigned_abs();
+        let mut n = 1;
+        while m >= 10 {
+            m /= 10;
+            n += 1;
+        }
+        n
+    }
+
+    /// Same value with trailing fractional zeros removed
+    pub fn normalize(&self) -> Self {
+        let mut d = *self;
+        while d.scale > 0 && d.mantissa % 10 == 0 {
+            d.mantissa /= 10;
+            d.scale -= 1;
+        }
+        d
+    }
+
+    /// Change the scale, rounding half away from zero when digits are dropped
+    pub fn rescale(&self, scale: u8) -> Option<Self> {
+        if scale > MAX_PRECISION {
+            return None;
+        }
+        let mantissa = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.mantissa,
+            Ordering::Greater => self.mantissa.checked_mul(pow10(scale - self.scale)?)?,
+            Ordering::Less => match pow10(self.scale - scale) {
+                Some(p) => div_round(self.mantissa, p),
+                // Dropping more than 38 digits always rounds to zero
+                None => 0,
+            },
+        };
+        Some(Self { mantissa, scale })
+    }
+
+    /// Rescale to `scale` and check the result fits DECIMAL(precision, scale)
+    pub fn fit(&self, precision: u8, scale: u8) -> Option<Self> {
+        let d = self.rescale(scale)?;
+        (d.is_zero() || d.digits() <= precision).then_some(d)
+    }
+
+    fn align(&self, other: &Self) -> Option<(i128, i128, u8)> {
+        let scale = self.scale.max(other.scale);
+        Some((
+            self.rescale(scale)?.mantissa,
+            other.rescale(scale)?.mantissa,
+            scale,
+        ))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self {
+            mantissa: a.checked_add(b)?,
+            scale,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self {
+            mantissa: a.checked_sub(b)?,
+            scale,
+        })
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let (a, b) = (self.normalize(), other.normalize());
+        let mantissa = a.mantissa.checked_mul(b.mantissa)?;
+        let scale = a.scale as u16 + b.scale as u16;
+        if scale <= MAX_PRECISION as u16 {
+            return Some(Self {
+                mantissa,
+                scale: scale as u8,
+            });
+        }
+        // Too many fractional digits: drop the excess with rounding
+        let excess = (scale - MAX_PRECISION as u16) as u8;
+        Some(Self {
+            mantissa: pow10(excess).map_or(0, |p| div_round(mantissa, p)),
+            scale: MAX_PRECISION,
+        })
+    }
+
+    /// Quotient with `max(scale) + 6` fractional digits (capped at 38),
+    /// rounded half away from zero. `None` on division by zero or overflow.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut scale = (self.scale.max(other.scale) + DIV_EXTRA_SCALE).min(MAX_PRECISION);
+        loop {
+            // self / other at `scale` = self.m × 10^(scale - self.s + other.s) / other.m
+            let shift = scale as i32 - self.scale as i32 + other.scale as i32;
+            let result = if shift >= 0 {
+                pow10(shift as u8)
+                    .and_then(|p| self.mantissa.checked_mul(p))
+                    .map(|n| div_round(n, other.mantissa))
+            } else {
+                pow10((-shift) as u8)
+                    .and_then(|p| other.mantissa.checked_mul(p))
+                    .map(|d| div_round(self.mantissa, d))
+            };
+            match result {
+                Some(mantissa) => return Some(Self { mantissa, scale }),
+                // Trade fractional digits for headroom before giving up
+                None if scale > 0 => scale -= 1,
+                None => return None,
+            }
+        }
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_neg()?,
+            scale: self.scale,
+        })
+    }
+
+    /// Nearest f64 (inexact for more than ~15 significant digits)
+    pub fn to_f64(&self) -> f64 {
+        let d = self.normalize();
+        d.mantissa as f64 / 10f64.powi(d.scale as i32)
+    }
+
+    /// Integer part (truncated toward zero); `None` if it does not fit in i64
+    pub fn to_i64(&self) -> Option<i64> {
+        let int = match pow10(self.scale) {
+            Some(p) => self.mantissa / p,
+            None => 0,
+        };
+        i64::try_from(int).ok()
+    }
+
+    /// Order-preserving key for the column index (at most 41 bytes)
+    ///
+    /// Layout: sign byte, decimal exponent, then one byte per significant
+    /// digit. Negative values store the exponent and digits inverted and pad
+    /// with 0xFF, so byte-wise comparison matches numeric order regardless of
+    /// scale.
+    pub fn write_sort_key(&self, buf: &mut [u8]) {
+        let d = self.normalize();
+        if d.is_zero() {
+            buf[0] = 0x80;
+            return;
+        }
+        let digits = d.mantissa.unsigned_abs().to_string();
+        let exponent = (digits.len() as i32 - d.scale as i32 + 64) as u8;
+        let negative = d.is_negative();
+        let flip = |b: u8| if negative { !b } else { b };
+        buf[0] = if negative { 0x01 } else { 0xFE };
+        buf[1] = flip(exponent);
+        for (i, c) in digits.bytes().enumerate() {
+            buf[2 + i] = flip(c - b'0' + 1);
+        }
+        if negative {
+            for b in &mut buf[2 + digits.len()..] {
+                *b = 0xFF;
+            }
+        }
+    }
+
+    /// Decode a key written by [`write_sort_key`](Self::write_sort_key)
+    pub fn from_sort_key(buf: &[u8]) -> Option<Self> {
+        let negative = match *buf.first()? {
+            0x80 => return Some(Self::ZERO),
+            0x01 => true,
+            0xFE => false,
+            _ => return None,
+        };
+        let flip = |b: u8| if negative { !b } else { b };
+        let exponent = flip(*buf.get(1)?) as i32 - 64;
+        let mut mantissa: i128 = 0;
+        let mut n = 0i32;
+        for &b in &buf[2..] {
+            let b = flip(b);
+            if b == 0 {
+                break;
+            }
+            mantissa = mantissa.checked_mul(10)?.checked_add((b - 1) as i128)?;
+            n += 1;
+        }
+        let mut d = match n - exponent {
+            s if s >= 0 => Self::new(mantissa, s as u8)?,
+            s => Self {
+                mantissa: mantissa.checked_mul(pow10((-s) as u8)?)?,
+                scale: 0,
+            },
+        };
+        if negative {
+            d.mantissa = -d.mantissa;
+        }
+        Some(d)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.scale == other.scale {
+            return self.mantissa.cmp(&other.mantissa);
+        }
+        // Split into integer part and fraction (floor semantics), so values
+        // at different scales compare without risking i128 overflow.
+        let split = |d: &Self, scale: u8| -> (i128, i128) {
+            match pow10(d.scale) {
+                Some(p) => (
+                    d.mantissa.div_euclid(p),
+                    d.mantissa.rem_euclid(p) * pow10(scale - d.scale).unwrap_or(1),
+                ),
+                None => (0, d.mantissa),
+            }
+        };
+        let scale = self.scale.max(other.scale);
+        split(self, scale).cmp(&split(other, scale))
+    }
+}
+
+impl std::hash::Hash for Decimal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let d = self.normalize();
+        d.mantissa.hash(state);
+        d.scale.hash(state);
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.is_negative() { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, int, frac)
+        } else {
+            write!(f, "{}0.{:0>width$}", sign, digits, width = scale)
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    /// Parse `[+-]digits[.digits][e[+-]exp]`; more than 38 fractional digits
+    /// are rounded away
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid decimal value '{}'", s);
+        let t = s.trim();
+        let (negative, t) = match t.as_bytes().first() {
+            Some(b'-') => (true, &t[1..]),
+            Some(b'+') => (false, &t[1..]),
+            _ => (false, t),
+        };
+        let (number, exponent) = match t.find(['e', 'E']) {
+            Some(i) => (&t[..i], t[i + 1..].parse::<i32>().map_err(|_| invalid())?),
+            None => (t, 0),
+        };
+        let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+        if (int.is_empty() && frac.is_empty())
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let overflow = || format!("decimal value '{}' exceeds {} digits", s, MAX_PRECISION);
+        let mut mantissa: i128 = 0;
+        let mut scale = frac.len() as i32 - exponent;
+        let mut rounding_digit = None;
+        for (i, b) in int.bytes().chain(frac.bytes()).enumerate() {
+            let digit = (b - b'0') as i128;
+            // Digits past MAX_PRECISION fractional places only affect rounding
+            let place = i as i32 - int.len() as i32 + 1;
+            if place - exponent > MAX_PRECISION as i32 {
+                rounding_digit.get_or_insert(digit);
+                scale -= 1;
+                continue;
+            }
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(digit))
+                .ok_or_else(overflow)?;
+        }
+        if rounding_digit.is_some_and(|d| d >= 5) {
+            mantissa = mantissa.checked_add(1).ok_or_else(overflow)?;
+        }
+        if mantissa == 0 {
+            scale = scale.max(0);
+        } else if scale < 0 {
+            mantissa = pow10((-scale).min(u8::MAX as i32) as u8)
+                .and_then(|p| mantissa.checked_mul(p))
+                .ok_or_else(overflow)?;
+            scale = 0;
+        }
+        if negative {
+            mantissa = -mantissa;
+        }
+        Ok(Self {
+            mantissa,
+            scale: scale as u8,
+        })
+    }
+}
+
+/// Binary operator for [`decimal_arith`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum DecimalOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Arithmetic involving at least one DECIMAL operand, shared by the row and
+/// positional evaluators. Returns `None` when neither side is a Decimal, or
+/// either side is NULL, so the caller keeps its own rules.
+///
+/// Decimal with Decimal or Integer stays exact (overflow is an error rather
+/// than a silent fallback to f64); Decimal with Float degrades to Float.
+pub(crate) fn decimal_arith(
+    op: DecimalOp,
+    left: &Value,
+    right: &Value,
+) -> Option<crate::Result<Value>> {
+    let exact = |v: &Value| match v {
+        Value::Decimal(d) => Some(**d),
+        Value::Integer(i) => Some(Decimal::from_i64(*i)),
+        _ => None,
+    };
+    if !matches!(left, Value::Decimal(_)) && !matches!(right, Value::Decimal(_))
+        || matches!(left, Value::Null)
+        || matches!(right, Value::Null)
+    {
+        return None;
+    }
+    if let (Some(l), Some(r)) = (exact(left), exact(right)) {
+        let result = match op {
+            DecimalOp::Add => l.checked_add(&r),
+            DecimalOp::Sub => l.checked_sub(&r),
+            DecimalOp::Mul => l.checked_mul(&r),
+            DecimalOp::Div if r.is_zero() => return Some(Err(StorageError::DivisionByZero)),
+            DecimalOp::Div => l.checked_div(&r),
+        };
+        return Some(result.map(Value::decimal).ok_or_else(|| {
+            StorageError::TypeError("numeric overflow in DECIMAL arithmetic".to_string())
+        }));
+    }
+    let as_f64 = |v: &Value| match v {
+        Value::Decimal(d) => Some(d.to_f64()),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    };
+    let (Some(l), Some(r)) = (as_f64(left), as_f64(right)) else {
+        return Some(Err(StorageError::TypeError(format!(
+            "Cannot apply {:?} to {:?} and {:?}",
+            op, left, right
+        ))));
+    };
+    Some(match op {
+        DecimalOp::Add => Ok(Value::Float(l + r)),
+        DecimalOp::Sub => Ok(Value::Float(l - r)),
+        DecimalOp::Mul => Ok(Value::Float(l * r)),
+        DecimalOp::Div if r == 0.0 => Err(StorageError::DivisionByZero),
+        DecimalOp::Div => Ok(Value::Float(l / r)),
+    })
+}
+
+/// Running DECIMAL total for SUM/AVG accumulators
+///
+/// [`NumericSum`](super::NumericSum) keeps the Integer/Float partial sums and
+/// feeds only DECIMAL inputs here; `sum`/`avg` merge the two. The result stays exact
+/// unless a Float was seen (or the exact total overflowed), in which case it
+/// degrades to Float like mixed Integer/Float input does.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DecimalSum {
+    exact: Option<Decimal>,
+    overflow: Option<f64>,
+}
+
+impl DecimalSum {
+    pub(crate) fn add(&mut self, d: &Decimal) {
+        if let Some(f) = self.overflow.as_mut() {
+            *f += d.to_f64();
+            return;
+        }
+        let total = self.exact.unwrap_or(Decimal::ZERO);
+        match total.checked_add(d) {
+            Some(t) => self.exact = Some(t),
+            None => self.overflow = Some(total.to_f64() + d.to_f64()),
+        }
+    }
+
+    /// True until the first DECIMAL input
+    pub(crate) fn is_empty(&self) -> bool {
+        self.exact.is_none() && self.overflow.is_none()
+    }
+
+    /// Folds in a total kept over other rows
+    pub(crate) fn merge(&mut self, other: &DecimalSum) {
+        match (other.exact, other.overflow) {
+            (_, Some(f)) => {
+                let total = self
+                    .overflow
+                    .unwrap_or_else(|| self.exact.map_or(0.0, |d| d.to_f64()));
+                self.overflow = Some(total + f);
+            }
+            (Some(d), None) => self.add(&d),
+            (None, None) => {}
+        }
+    }
+
+    fn exact_total(&self, int_sum: i128, has_float: bool) -> Option<Decimal> {
+        if has_float || self.overflow.is_some() {
+            return None;
+        }
+        self.exact
+            .unwrap_or(Decimal::ZERO)
+            .checked_add(&Decimal::new(int_sum, 0)?)
+    }
+
+    fn float_total(&self, int_sum: i128, float_sum: f64, has_float: bool) -> f64 {
+        let base = if has_float { float_sum } else { int_sum as f64 };
+        base + self
+            .overflow
+            .unwrap_or_else(|| self.exact.map_or(0.0, |d| d.to_f64()))
+    }
+
+    /// SUM result, given the accumulator's Integer/Float partial sums
+    pub(crate) fn sum(&self, int_sum: i128, float_sum: f64, has_float: bool) -> Value {
+        match self.exact_total(int_sum, has_float) {
+            Some(d) => Value::decimal(d),
+            None => Value::Float(self.float_total(int_sum, float_sum, has_float)),
+        }
+    }
+
+    /// AVG result over `count` non-NULL inputs
+    pub(crate) fn avg(&self, int_sum: i128, float_sum: f64, has_float: bool, count: u64) -> Value {
+        if count == 0 {
+            return Value::Null;
+        }
+        let exact = self.exact_total(int_sum, has_float).and_then(|d| {
+            i64::try_from(count)
+                .ok()
+                .and_then(|c| d.checked_div(&Decimal::from_i64(c)))
+        });
+        match exact {
+            Some(d) => Value::decimal(d),
+            None => Value::Float(self.float_total(int_sum, float_sum, has_float) / count as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(dec("12.50").to_string(), "12.50");
+        assert_eq!(dec("-0.05").to_string(), "-0.05");
+        assert_eq!(dec("+7").to_string(), "7");
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("1.5e3").to_string(), "1500");
+        assert_eq!(dec("25e-3").to_string(), "0.025");
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("abc".parse::<Decimal>().is_err());
+        assert!("".parse::<Decimal>().is_err());
+        assert_eq!(Decimal::from_f64(0.1).unwrap(), dec("0.1"));
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let sum = (0..10).fold(Decimal::ZERO, |acc, _| {
+            acc.checked_add(&dec("0.1")).unwrap()
+        });
+        assert_eq!(sum, dec("1"));
+        assert_eq!(
+            dec("1.25").checked_sub(&dec("2")).unwrap().to_string(),
+            "-0.75"
+        );
+        assert_eq!(
+            dec("1.5").checked_mul(&dec("-0.2")).unwrap().to_string(),
+            "-0.30"
+        );
+        assert_eq!(
+            dec("1").checked_div(&dec("3")).unwrap().to_string(),
+            "0.333333"
+        );
+        assert_eq!(
+            dec("2.00").checked_div(&dec("3")).unwrap().to_string(),
+            "0.66666667"
+        );
+        assert!(dec("1").checked_div(&Decimal::ZERO).is_none());
+        let max = Decimal::new(i128::MAX, 0).unwrap();
+        assert!(max.checked_add(&dec("1")).is_none());
+    }
+
+    #[test]
+    fn test_rescale_and_fit() {
+        assert_eq!(dec("2.345").rescale(2).unwrap().to_string(), "2.35");
+        assert_eq!(dec("-2.345").rescale(2).unwrap().to_string(), "-2.35");
+        assert_eq!(dec("2.344").rescale(2).unwrap().to_string(), "2.34");
+        assert_eq!(dec("7").rescale(3).unwrap().to_string(), "7.000");
+        assert_eq!(dec("999.99").fit(5, 2).unwrap().to_string(), "999.99");
+        assert!(dec("1000").fit(5, 2).is_none());
+        assert!(dec("999.995").fit(5, 2).is_none());
+    }
+
+    #[test]
+    fn test_ordering_across_scales() {
+        assert_eq!(dec("1.5"), dec("1.500"));
+        assert!(dec("1.05") < dec("1.5"));
+        assert!(dec("-1.5") < dec("-1.05"));
+        assert!(dec("-0.5") < dec("0.25"));
+        assert_eq!(dec("10").to_i64(), Some(10));
+        assert_eq!(dec("-2.9").to_i64(), Some(-2));
+    }
+
+    #[test]
+    fn test_sort_key_order() {
+        let values = [
+            "-1000", "-12.5", "-12.25", "-1", "-0.001", "0", "0.001", "0.5", "1", "1.05", "1.5",
+            "12", "1000.01",
+        ];
+        let keys: Vec<[u8; 64]> = values
+            .iter()
+            .map(|s| {
+                let mut buf = [0u8; 64];
+                dec(s).write_sort_key(&mut buf);
+                assert_eq!(Decimal::from_sort_key(&buf), Some(dec(s)));
+                buf
+            })
+            .collect();
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        let (mut a, mut b) = ([0u8; 64], [0u8; 64]);
+        dec("1.5").write_sort_key(&mut a);
+        dec("1.500").write_sort_key(&mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -1,18 +1,34 @@
 //! Multi-modal data types for MoteDB
 
+pub(crate) mod array;
+mod date_time;
+mod decimal;
 mod spatial;
+mod sum;
 mod table;
-mod tensor;
+pub(crate) mod tensor;
 mod text;
 mod timestamp;
+mod uuid;
+mod vector_encoding;
 
+pub use date_time::{parse_timestamp, Date, Time, UtcOffset};
+pub(crate) use decimal::{decimal_arith, DecimalOp, DecimalSum};
+pub use decimal::{Decimal, MAX_PRECISION as DECIMAL_MAX_PRECISION};
 pub use spatial::{BoundingBox, BoundingBox3D, Geometry, Point, Point3D};
-pub use table::{ColumnDef, ColumnType, IndexDef, IndexType, TTLDuration, TableSchema, TableType};
+pub use sum::NumericSum;
+pub use table::{
+    AggregateOutput, ColumnDef, ColumnType, ContinuousAggregate, GeneratedColumn, IndexDef,
+    IndexType, TTLDuration, TableSchema, TableType,
+};
 pub use tensor::Tensor;
 pub use text::{Text, TextDoc};
 pub use timestamp::Timestamp;
+pub use uuid::Uuid;
+pub use vector_encoding::VectorEncoding;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Wrapper for Arc<Vec<f32>> with custom serde implementation
@@ -133,7 +149,7 @@ impl PartialEq<&str> for ArcString {
 
 /// Unified value type supporting all data modalities
 ///
-/// Size optimization: large variants (Text, Tensor, Spatial, TextDoc) are
+/// Size optimization: large variants (Text, Tensor, Spatial, TextDoc, Decimal, Uuid) are
 /// boxed to keep the enum at 16 bytes instead of 40 bytes. This reduces
 /// memory amplification for scalar-heavy rows from 8.6x to ~3.5x.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,29 +183,75 @@ pub enum Value {
 
     /// Null value
     Null,
+
+    /// Exact fixed-point decimal (boxed to reduce enum size)
+    Decimal(Box<Decimal>),
+
+    /// UUID (boxed to reduce enum size)
+    Uuid(Box<Uuid>),
+
+    /// Calendar date
+    Date(Date),
+
+    /// Time of day
+    Time(Time),
+
+    /// Variable-length list of scalars (boxed to reduce enum size)
+    Array(Box<Vec<Value>>),
+}
+
+/// Total order on f64: NaN sorts after every number (all NaNs are equal,
+/// whatever their sign or payload) and -0.0 equals 0.0, matching `float_eq`.
+pub(crate) fn float_cmp(a: f64, b: f64) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Smaller of two floats under `float_cmp` (NaN only if both are NaN)
+pub(crate) fn float_min(a: f64, b: f64) -> f64 {
+    if float_cmp(b, a) == std::cmp::Ordering::Less {
+        b
+    } else {
+        a
+    }
+}
+
+/// Larger of two floats under `float_cmp` (NaN if either is NaN)
+pub(crate) fn float_max(a: f64, b: f64) -> f64 {
+    if float_cmp(b, a) == std::cmp::Ordering::Greater {
+        b
+    } else {
+        a
+    }
 }
 
 /// Precise integer-vs-float comparison that avoids precision loss for |i| > 2^53.
 ///
 /// Strategy:
+/// - NaN is greater than every integer.
 /// - If the float has a fractional part, compare via f64 (safe: integer is exact in f64 range).
 /// - If the float is an exact integer, compare via i64 arithmetic (avoids f64 precision loss).
-fn int_float_cmp(i: i64, f: f64) -> Option<std::cmp::Ordering> {
-    // If f has a fractional part, the integer can be compared as f64
-    // because the fractional part differentiates them regardless.
+fn int_float_cmp(i: i64, f: f64) -> std::cmp::Ordering {
+    // If f has a fractional part (or is NaN), the integer can be compared as
+    // f64 because the fractional part differentiates them regardless.
     let f_trunc = f.trunc();
     if f != f_trunc {
-        return (i as f64).partial_cmp(&f);
+        return float_cmp(i as f64, f);
     }
     // f is an exact integer. Convert to i64 and compare precisely.
     // f.trunc() is guaranteed to be in i64 range because it came from a valid f64
     // that equals an integer value (no overflow since f64 can represent up to 2^1023).
     if f_trunc >= i64::MIN as f64 && f_trunc <= i64::MAX as f64 {
         let f_as_i = f_trunc as i64;
-        return Some(i.cmp(&f_as_i));
+        return i.cmp(&f_as_i);
     }
     // f is astronomically large/small — fall back to f64 comparison
-    (i as f64).partial_cmp(&f)
+    float_cmp(i as f64, f)
 }
 
 impl PartialOrd for Value {
@@ -199,19 +261,46 @@ impl PartialOrd for Value {
             (Value::Null, _) => Some(std::cmp::Ordering::Less),
             (_, Value::Null) => Some(std::cmp::Ordering::Greater),
             (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
-            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Float(a), Value::Float(b)) => Some(float_cmp(*a, *b)),
             (Value::Text(a), Value::Text(b)) => a.partial_cmp(b),
             (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
             (Value::Timestamp(a), Value::Timestamp(b)) => a.partial_cmp(b),
-            (Value::Integer(a), Value::Float(b)) => int_float_cmp(*a, *b),
-            (Value::Float(a), Value::Integer(b)) => int_float_cmp(*b, *a).map(|o| o.reverse()),
+            (Value::Integer(a), Value::Float(b)) => Some(int_float_cmp(*a, *b)),
+            (Value::Float(a), Value::Integer(b)) => Some(int_float_cmp(*b, *a).reverse()),
             // Timestamp vs Integer: compare timestamp micros to integer value
             (Value::Timestamp(a), Value::Integer(b)) => a.as_micros().partial_cmp(b),
             (Value::Integer(a), Value::Timestamp(b)) => a.partial_cmp(&b.as_micros()),
             // Timestamp vs Float: compare timestamp micros to float value
-            (Value::Timestamp(a), Value::Float(b)) => int_float_cmp(a.as_micros(), *b),
+            (Value::Timestamp(a), Value::Float(b)) => Some(int_float_cmp(a.as_micros(), *b)),
             (Value::Float(a), Value::Timestamp(b)) => {
-                int_float_cmp(b.as_micros(), *a).map(|o| o.reverse())
+                Some(int_float_cmp(b.as_micros(), *a).reverse())
+            }
+            // Decimal vs Decimal/Integer is exact; vs Float goes through f64
+            (Value::Decimal(a), Value::Decimal(b)) => a.partial_cmp(b),
+            (Value::Decimal(a), Value::Integer(b)) => Some((**a).cmp(&Decimal::from_i64(*b))),
+            (Value::Integer(a), Value::Decimal(b)) => Some(Decimal::from_i64(*a).cmp(b)),
+            (Value::Decimal(a), Value::Float(b)) => Some(float_cmp(a.to_f64(), *b)),
+            (Value::Float(a), Value::Decimal(b)) => Some(float_cmp(*a, b.to_f64())),
+            // UUID vs Text parses the text, so string literals work in WHERE
+            (Value::Uuid(a), Value::Uuid(b)) => a.partial_cmp(b),
+            (Value::Uuid(a), Value::Text(b)) => Some((**a).cmp(&b.parse().ok()?)),
+            (Value::Text(a), Value::Uuid(b)) => Some(a.parse::<Uuid>().ok()?.cmp(b)),
+            // DATE/TIME likewise accept their ISO strings
+            (Value::Date(a), Value::Date(b)) => a.partial_cmp(b),
+            (Value::Date(a), Value::Text(b)) => Some(a.cmp(&b.parse().ok()?)),
+            (Value::Text(a), Value::Date(b)) => Some(a.parse::<Date>().ok()?.cmp(b)),
+            (Value::Time(a), Value::Time(b)) => a.partial_cmp(b),
+            (Value::Time(a), Value::Text(b)) => Some(a.cmp(&b.parse().ok()?)),
+            (Value::Text(a), Value::Time(b)) => Some(a.parse::<Time>().ok()?.cmp(b)),
+            // Arrays compare element by element, then by length
+            (Value::Array(a), Value::Array(b)) => {
+                for (x, y) in a.iter().zip(b.iter()) {
+                    match x.partial_cmp(y)? {
+                        std::cmp::Ordering::Equal => {}
+                        ord => return Some(ord),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
             }
             _ => None,
         }
@@ -252,6 +341,27 @@ fn int_float_eq(i: i64, f: f64) -> bool {
     float_eq(i as f64, f)
 }
 
+/// Canonical text of a TEXT value that compares equal to a UUID, DATE or
+/// TIME, so the text hashes like the typed value it equals. Cheap shape
+/// checks run first to keep hashing ordinary text fast.
+fn typed_text_canonical(s: &str) -> Option<String> {
+    let t = s.trim();
+    let b = t.as_bytes();
+    if b.len() == 10 && b[4] == b'-' && b[7] == b'-' {
+        return t.parse::<Date>().ok().map(|d| d.to_string());
+    }
+    if b.len() >= 5 && b[2] == b':' {
+        return t.parse::<Time>().ok().map(|t| t.to_string());
+    }
+    if matches!(b.len(), 32 | 34 | 36 | 38)
+        && b.iter()
+            .all(|c| c.is_ascii_hexdigit() || matches!(c, b'-' | b'{' | b'}'))
+    {
+        return t.parse::<Uuid>().ok().map(|u| u.to_string());
+    }
+    None
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -267,6 +377,21 @@ impl PartialEq for Value {
             (Value::Integer(a), Value::Timestamp(b)) => *a == b.as_micros(),
             (Value::Timestamp(a), Value::Float(b)) => float_eq(a.as_micros() as f64, *b),
             (Value::Float(a), Value::Timestamp(b)) => float_eq(*a, b.as_micros() as f64),
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
+            (Value::Decimal(a), Value::Integer(b)) => **a == Decimal::from_i64(*b),
+            (Value::Integer(a), Value::Decimal(b)) => Decimal::from_i64(*a) == **b,
+            (Value::Decimal(a), Value::Float(b)) => float_eq(a.to_f64(), *b),
+            (Value::Float(a), Value::Decimal(b)) => float_eq(*a, b.to_f64()),
+            (Value::Uuid(a), Value::Uuid(b)) => a == b,
+            (Value::Uuid(a), Value::Text(b)) => b.parse::<Uuid>().is_ok_and(|b| **a == b),
+            (Value::Text(a), Value::Uuid(b)) => a.parse::<Uuid>().is_ok_and(|a| a == **b),
+            (Value::Date(a), Value::Date(b)) => a == b,
+            (Value::Date(a), Value::Text(b)) => b.parse::<Date>().is_ok_and(|b| *a == b),
+            (Value::Text(a), Value::Date(b)) => a.parse::<Date>().is_ok_and(|a| a == *b),
+            (Value::Time(a), Value::Time(b)) => a == b,
+            (Value::Time(a), Value::Text(b)) => b.parse::<Time>().is_ok_and(|b| *a == b),
+            (Value::Text(a), Value::Time(b)) => a.parse::<Time>().is_ok_and(|a| a == *b),
+            (Value::Array(a), Value::Array(b)) => a == b,
             _ => false,
         }
     }
@@ -285,9 +410,11 @@ impl std::hash::Hash for Value {
                 state.write_u8(0); // numeric discriminant
                 canonical_float_bits(*f).hash(state);
             }
-            Value::Text(s) => {
+            Value::Text(_) | Value::Uuid(_) | Value::Date(_) | Value::Time(_) => {
                 state.write_u8(1);
-                s.hash(state);
+                if let Some(text) = self.canonical_text() {
+                    (*text).hash(state);
+                }
             }
             Value::Bool(b) => {
                 state.write_u8(2);
@@ -302,6 +429,20 @@ impl std::hash::Hash for Value {
                 state.write_u8(0); // numeric discriminant (same as Integer/Float)
                 canonical_float_bits(f).hash(state);
             }
+            Value::Decimal(d) => {
+                // to_f64 normalizes first, so equal decimals at different
+                // scales hash alike, and an integral decimal hashes like
+                // the Integer it equals
+                state.write_u8(0); // numeric discriminant
+                canonical_float_bits(d.to_f64()).hash(state);
+            }
+            Value::Array(items) => {
+                state.write_u8(6);
+                items.len().hash(state);
+                for item in items.iter() {
+                    item.hash(state);
+                }
+            }
             other => {
                 state.write_u8(5);
                 format!("{:?}", other).hash(state);
@@ -336,6 +477,70 @@ impl Value {
         Value::TextDoc(Box::new(t))
     }
 
+    /// Create a Decimal value
+    pub fn decimal(d: Decimal) -> Self {
+        Value::Decimal(Box::new(d))
+    }
+
+    /// Create a Uuid value
+    pub fn uuid(u: Uuid) -> Self {
+        Value::Uuid(Box::new(u))
+    }
+
+    /// Create an Array value
+    pub fn array(items: Vec<Value>) -> Self {
+        Value::Array(Box::new(items))
+    }
+
+    /// Text a TEXT, UUID, DATE or TIME value hashes and joins by. The typed
+    /// values, and text that parses as one, use the canonical form, so
+    /// values that compare equal across those types line up.
+    pub(crate) fn canonical_text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Text(s) => Some(typed_text_canonical(s).map_or(Cow::Borrowed(&**s), Cow::Owned)),
+            Value::Uuid(u) => Some(Cow::Owned(u.to_string())),
+            Value::Date(d) => Some(Cow::Owned(d.to_string())),
+            Value::Time(t) => Some(Cow::Owned(t.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Approximate bytes held by this value, including heap data. Shared
+    /// (`Arc`) text and vectors are counted in full.
+    pub fn memory_size(&self) -> usize {
+        let heap = match self {
+            Value::Text(s) => s.0.len(),
+            Value::Vector(v) => v.0.len() * std::mem::size_of::<f32>(),
+            Value::Tensor(t) => std::mem::size_of::<Tensor>() + t.memory_size(),
+            Value::Spatial(_) => std::mem::size_of::<Geometry>(),
+            Value::TextDoc(t) => std::mem::size_of::<Text>() + t.len(),
+            Value::Decimal(_) => std::mem::size_of::<Decimal>(),
+            Value::Uuid(_) => std::mem::size_of::<Uuid>(),
+            Value::Array(items) => items.iter().map(Value::memory_size).sum(),
+            Value::Integer(_)
+            | Value::Float(_)
+            | Value::Bool(_)
+            | Value::Timestamp(_)
+            | Value::Null
+            | Value::Date(_)
+            | Value::Time(_) => 0,
+        };
+        std::mem::size_of::<Value>() + heap
+    }
+
+    /// Canonical string of a type stored in the columnar Text layout
+    /// (see `ColumnType::is_text_encoded`)
+    pub fn encoded_text(&self) -> Option<String> {
+        match self {
+            Value::Decimal(d) => Some(d.to_string()),
+            Value::Uuid(u) => Some(u.to_string()),
+            Value::Date(d) => Some(d.to_string()),
+            Value::Time(t) => Some(t.to_string()),
+            Value::Array(items) => Some(array::encode(items)),
+            _ => None,
+        }
+    }
+
     /// Convert to a hashable string key for use in HashMap/DashMap lookups.
     /// Handles f64 by converting to bits (lossless).
     pub fn to_hash_key(&self) -> String {
@@ -345,6 +550,11 @@ impl Value {
             Value::Text(s) => format!("t:{}", s),
             Value::Bool(b) => format!("b:{}", b),
             Value::Timestamp(t) => format!("ts:{}", t.as_micros()),
+            Value::Decimal(d) => format!("d:{}", d.normalize()),
+            Value::Uuid(u) => format!("t:{}", u),
+            Value::Date(d) => format!("t:{}", d),
+            Value::Time(t) => format!("t:{}", t),
+            Value::Array(items) => format!("a:{}", array::encode(items)),
             _ => format!("{:?}", self),
         }
     }
@@ -359,5 +569,10 @@ pub type SqlRow = std::collections::HashMap<String, Value>;
 /// Row identifier (unique across the database)
 pub type RowId = u64;
 
+/// Compressed set of row IDs, the representation index lookups hand to
+/// the executor. Iterates in ascending order, and intersections and unions
+/// combine whole bitmap containers instead of hashing every ID.
+pub type RowIdSet = roaring::RoaringTreemap;
+
 /// Partition identifier for parallel writes
 pub type PartitionId = u8;
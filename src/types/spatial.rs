@@ -226,6 +226,34 @@ impl Geometry {
     pub fn intersects_bbox(&self, bbox: &BoundingBox) -> bool {
         self.bounding_box().intersects(bbox)
     }
+
+    /// GeoJSON geometry object (RFC 7946); polygon rings are closed
+    pub fn to_geojson(&self) -> serde_json::Value {
+        fn position(p: &Point) -> serde_json::Value {
+            serde_json::Value::from(vec![p.x, p.y])
+        }
+        let (kind, coordinates) = match self {
+            Geometry::Point(p) => ("Point", position(p)),
+            Geometry::Point3D(p) => ("Point", serde_json::Value::from(vec![p.x, p.y, p.z])),
+            Geometry::LineString(points) => (
+                "LineString",
+                serde_json::Value::Array(points.iter().map(position).collect()),
+            ),
+            Geometry::Polygon(points) => {
+                let mut ring: Vec<_> = points.iter().map(position).collect();
+                if let (Some(first), Some(last)) = (points.first(), points.last()) {
+                    if first != last {
+                        ring.push(position(first));
+                    }
+                }
+                (
+                    "Polygon",
+                    serde_json::Value::Array(vec![serde_json::Value::Array(ring)]),
+                )
+            }
+        };
+        serde_json::json!({ "type": kind, "coordinates": coordinates })
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,200 @@
+//! Typed SUM/AVG accumulator
+
+use super::{Decimal, DecimalSum, Value};
+use crate::{Result, StorageError};
+
+/// Running SUM/AVG total that keeps each input type exact for as long as it
+/// can: integers add up in an `i128` (no i64 counter can overflow it), floats
+/// use Neumaier's compensated (Kahan) summation, DECIMALs go to
+/// [`DecimalSum`]. An all-integer total that doesn't fit INTEGER is an error
+/// rather than a silently rounded float.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NumericSum {
+    int: i128,
+    float: f64,
+    comp: f64,
+    has_float: bool,
+    count: u64,
+    dec: DecimalSum,
+}
+
+impl NumericSum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn add_int(&mut self, i: i64) {
+        self.int += i as i128;
+        self.count += 1;
+    }
+
+    #[inline]
+    pub fn add_float(&mut self, f: f64) {
+        let t = self.float + f;
+        if self.float.abs() >= f.abs() {
+            self.comp += (self.float - t) + f;
+        } else {
+            self.comp += (f - t) + self.float;
+        }
+        self.float = t;
+        self.has_float = true;
+        self.count += 1;
+    }
+
+    pub fn add_decimal(&mut self, d: &Decimal) {
+        self.dec.add(d);
+        self.count += 1;
+    }
+
+    /// Adds a numeric value; NULL is skipped. Returns false for anything
+    /// else, leaving the total unchanged.
+    pub fn add(&mut self, val: &Value) -> bool {
+        match val {
+            Value::Integer(i) => self.add_int(*i),
+            Value::Float(f) => self.add_float(*f),
+            Value::Decimal(d) => self.add_decimal(d),
+            Value::Null => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Folds in a partial total computed over other rows
+    pub fn merge(&mut self, other: &NumericSum) {
+        self.int += other.int;
+        if other.has_float {
+            let count = self.count;
+            self.add_float(other.float);
+            self.comp += other.comp;
+            self.count = count;
+        }
+        self.dec.merge(&other.dec);
+        self.count += other.count;
+    }
+
+    /// Non-NULL values added so far
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The total as a float, whatever the input types
+    pub fn float_total(&self) -> f64 {
+        // A non-finite running total leaves the compensation NaN; ignore it
+        let float = if self.float.is_finite() {
+            self.float + self.comp
+        } else {
+            self.float
+        };
+        if self.has_float {
+            float + self.int as f64
+        } else {
+            self.int as f64
+        }
+    }
+
+    /// SUM result: NULL over no values, INTEGER for integer input, FLOAT once
+    /// a float was added, DECIMAL while DECIMAL input stays exact
+    pub fn sum(&self) -> Result<Value> {
+        if self.count == 0 {
+            return Ok(Value::Null);
+        }
+        if !self.dec.is_empty() {
+            return Ok(self.dec.sum(self.int, self.float_total(), self.has_float));
+        }
+        if self.has_float {
+            return Ok(Value::Float(self.float_total()));
+        }
+        i64::try_from(self.int).map(Value::Integer).map_err(|_| {
+            StorageError::NumericOverflow(format!("SUM {} is out of INTEGER range", self.int))
+        })
+    }
+
+    /// AVG result: NULL over no values, DECIMAL for exact DECIMAL input,
+    /// FLOAT otherwise
+    pub fn avg(&self) -> Value {
+        if self.count == 0 {
+            return Value::Null;
+        }
+        if !self.dec.is_empty() {
+            return self
+                .dec
+                .avg(self.int, self.float_total(), self.has_float, self.count);
+        }
+        Value::Float(self.float_total() / self.count as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_sum_is_exact() {
+        let mut s = NumericSum::new();
+        s.add_int(i64::MAX);
+        s.add_int(i64::MAX);
+        s.add_int(-i64::MAX);
+        assert_eq!(s.sum().unwrap(), Value::Integer(i64::MAX));
+
+        s.add_int(1);
+        assert!(matches!(s.sum(), Err(StorageError::NumericOverflow(_))));
+        assert_eq!(s.avg(), Value::Float(i64::MAX as f64 / 4.0));
+    }
+
+    #[test]
+    fn test_float_sum_is_compensated() {
+        let mut s = NumericSum::new();
+        s.add_float(1e16);
+        for _ in 0..1000 {
+            s.add_float(1.0);
+        }
+        s.add_float(-1e16);
+        assert_eq!(s.sum().unwrap(), Value::Float(1000.0));
+
+        let mut naive = 1e16;
+        for _ in 0..1000 {
+            naive += 1.0;
+        }
+        assert_ne!(naive - 1e16, 1000.0);
+    }
+
+    #[test]
+    fn test_mixed_and_special_values() {
+        let mut s = NumericSum::new();
+        assert!(s.add(&Value::Integer(2)));
+        assert!(s.add(&Value::Null));
+        assert!(s.add(&Value::Float(0.5)));
+        assert!(!s.add(&Value::Text("x".into())));
+        assert_eq!(s.count(), 2);
+        assert_eq!(s.sum().unwrap(), Value::Float(2.5));
+
+        s.add_float(f64::INFINITY);
+        assert_eq!(s.sum().unwrap(), Value::Float(f64::INFINITY));
+        s.add_float(f64::NAN);
+        assert!(matches!(s.sum().unwrap(), Value::Float(f) if f.is_nan()));
+
+        assert_eq!(NumericSum::new().sum().unwrap(), Value::Null);
+        assert_eq!(NumericSum::new().avg(), Value::Null);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = NumericSum::new();
+        let mut b = NumericSum::new();
+        a.add_int(i64::MAX);
+        b.add_int(i64::MAX);
+        b.add_int(-i64::MAX);
+        b.add_float(0.25);
+        a.merge(&b);
+        assert_eq!(a.count(), 4);
+        assert_eq!(a.sum().unwrap(), Value::Float(i64::MAX as f64 + 0.25));
+
+        let mut c = NumericSum::new();
+        c.add_decimal(&"1.5".parse().unwrap());
+        let mut d = NumericSum::new();
+        d.add_int(2);
+        d.merge(&c);
+        assert_eq!(d.sum().unwrap(), Value::decimal("3.5".parse().unwrap()));
+    }
+}
@@ -85,6 +85,292 @@ pub enum ColumnType {
     Boolean,
     /// Spatial (Geometry type for 2D/3D points, polygons, etc.)
     Spatial,
+    /// Exact fixed-point number: `precision` total digits, `scale` after the point
+    Decimal { precision: u8, scale: u8 },
+    /// 128-bit UUID
+    Uuid,
+    /// Calendar date (no time zone)
+    Date,
+    /// Time of day (no time zone)
+    Time,
+    /// Variable-length list of a scalar element type, e.g. ARRAY<FLOAT>
+    Array(Box<ColumnType>),
+    /// Float32 tensor with a fixed shape, e.g. TENSOR(3, 224, 224); stored
+    /// flat in the Vector layout (at most 65535 elements)
+    ShapedTensor(Vec<usize>),
+    /// Float32 vector stored as f16 or int8, e.g. VECTOR(768, f16); values
+    /// are rounded through the encoding on write and read back as f32
+    EncodedVector {
+        dim: usize,
+        encoding: crate::types::VectorEncoding,
+    },
+    /// One of a fixed list of labels, e.g. ENUM('idle', 'moving'); stored
+    /// as the label's position in the list and read back as Text
+    Enum(Vec<String>),
+}
+
+impl ColumnType {
+    /// Typed columns kept as their canonical string in the columnar Text
+    /// layout (DECIMAL, UUID, DATE, TIME, ARRAY, ENUM). Segment-level byte
+    /// comparisons on these don't match value semantics, so fast paths must
+    /// decode or bail.
+    pub fn is_text_encoded(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Decimal { .. }
+                | ColumnType::Uuid
+                | ColumnType::Date
+                | ColumnType::Time
+                | ColumnType::Array(_)
+                | ColumnType::Enum(_)
+        )
+    }
+
+    /// Types whose values go through `coerce` before being stored
+    pub fn is_coerced(&self) -> bool {
+        self.is_text_encoded()
+            || matches!(
+                self,
+                ColumnType::Timestamp
+                    | ColumnType::ShapedTensor(_)
+                    | ColumnType::EncodedVector { .. }
+            )
+    }
+
+    /// Types stored in the columnar Vector layout (VECTOR / TENSOR)
+    pub fn is_vector(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Tensor(_) | ColumnType::ShapedTensor(_) | ColumnType::EncodedVector { .. }
+        )
+    }
+
+    /// Dimension of a VECTOR column (any element encoding); `None` for
+    /// other types, including shaped TENSOR columns
+    pub fn vector_dim(&self) -> Option<usize> {
+        match *self {
+            ColumnType::Tensor(dim) | ColumnType::EncodedVector { dim, .. } => Some(dim),
+            _ => None,
+        }
+    }
+
+    /// Decode a cell stored in the columnar Vector layout. TENSOR columns
+    /// get their declared shape back; every other type stays a Vector.
+    pub fn value_from_vector(&self, v: Vec<f32>) -> crate::types::Value {
+        use crate::types::Value;
+        match self {
+            ColumnType::ShapedTensor(shape) => crate::types::Tensor::with_shape(shape.clone(), v)
+                .map(Value::tensor)
+                .unwrap_or(Value::Null),
+            _ => Value::Vector(crate::types::ArcVec(std::sync::Arc::new(v))),
+        }
+    }
+
+    /// Decode a cell stored in the columnar Text layout. Text-encoded types
+    /// are parsed back (unparsable → NULL); every other type stays Text.
+    pub fn value_from_text(&self, s: &str) -> crate::types::Value {
+        use crate::types::Value;
+        match self {
+            ColumnType::Decimal { .. } => s
+                .parse::<crate::types::Decimal>()
+                .map(Value::decimal)
+                .unwrap_or(Value::Null),
+            ColumnType::Uuid => s
+                .parse::<crate::types::Uuid>()
+                .map(Value::uuid)
+                .unwrap_or(Value::Null),
+            ColumnType::Date => s.parse().map(Value::Date).unwrap_or(Value::Null),
+            ColumnType::Time => s.parse().map(Value::Time).unwrap_or(Value::Null),
+            ColumnType::Array(elem) => crate::types::array::decode(s, elem)
+                .map(Value::array)
+                .unwrap_or(Value::Null),
+            ColumnType::Enum(labels) => s
+                .parse::<usize>()
+                .ok()
+                .and_then(|code| labels.get(code))
+                .map_or(Value::Null, |label| Value::text(label.clone())),
+            _ => Value::Text(s.into()),
+        }
+    }
+
+    /// Inverse of `value_from_text` for Text values: an ENUM label becomes
+    /// its position in the list, anything else is stored unchanged
+    pub fn text_for_storage<'a>(&self, s: &'a str) -> std::borrow::Cow<'a, str> {
+        match self {
+            ColumnType::Enum(labels) => match labels.iter().position(|l| l == s) {
+                Some(code) => code.to_string().into(),
+                None => s.into(),
+            },
+            _ => s.into(),
+        }
+    }
+
+    /// Convert a value into this column's storage form
+    ///
+    /// DECIMAL: numbers and numeric text are rescaled to the column's scale
+    /// (rounding half away from zero) and rejected if they exceed its
+    /// precision. UUID: text is parsed. DATE/TIME: text is parsed and
+    /// timestamps are split into their UTC date or time of day. TIMESTAMP:
+    /// text is parsed, as UTC unless it names an offset. ARRAY:
+    /// elements are converted to the element type; vectors and JSON text
+    /// are accepted too. TENSOR: vectors, tensors and (nested) JSON text
+    /// are given the column's shape if their element count matches.
+    /// VECTOR(n, f16|int8): vectors are rounded through the encoding. ENUM:
+    /// text must be one of the labels. Other values pass through unchanged.
+    pub fn coerce(&self, value: crate::types::Value) -> Result<crate::types::Value, String> {
+        use crate::types::{Decimal, Value};
+        let (precision, scale) = match *self {
+            ColumnType::Decimal { precision, scale } => (precision, scale),
+            ColumnType::Uuid => {
+                return match &value {
+                    Value::Text(s) => s.parse::<crate::types::Uuid>().map(Value::uuid),
+                    _ => Ok(value),
+                }
+            }
+            ColumnType::Date => {
+                return match &value {
+                    Value::Text(s) => s.parse().map(Value::Date),
+                    Value::Timestamp(ts) => crate::types::Date::from_timestamp(*ts)
+                        .map(Value::Date)
+                        .ok_or_else(|| {
+                            format!("timestamp {} is out of DATE range", ts.as_micros())
+                        }),
+                    _ => Ok(value),
+                }
+            }
+            ColumnType::Timestamp => {
+                return match &value {
+                    Value::Text(s) => crate::types::UtcOffset::UTC
+                        .parse_timestamp(s)
+                        .map(Value::Timestamp),
+                    _ => Ok(value),
+                }
+            }
+            ColumnType::Time => {
+                return match &value {
+                    Value::Text(s) => s.parse().map(Value::Time),
+                    Value::Timestamp(ts) => {
+                        Ok(Value::Time(crate::types::Time::from_timestamp(*ts)))
+                    }
+                    _ => Ok(value),
+                }
+            }
+            ColumnType::Array(ref elem) => {
+                use crate::types::array;
+                let items = match value {
+                    Value::Array(items) => *items,
+                    Value::Vector(v) => v
+                        .iter()
+                        .map(|f| Value::Float(array::f32_to_f64(*f)))
+                        .collect(),
+                    Value::Text(s) => array::decode(&s, elem)?,
+                    other => return Ok(other),
+                };
+                return items
+                    .into_iter()
+                    .map(|v| array::coerce_element(elem, v))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::array);
+            }
+            ColumnType::ShapedTensor(ref shape) => {
+                use crate::types::tensor::{self, Tensor};
+                let t = match value {
+                    Value::Vector(v) => Tensor::new(v.to_vec()),
+                    Value::Tensor(t) => *t,
+                    Value::Text(s) => tensor::parse_json(&s)?,
+                    other => return Ok(other),
+                };
+                // Flat input takes the declared shape; anything else must match it
+                if t.shape() == shape.as_slice() {
+                    return Ok(Value::tensor(t));
+                }
+                if t.rank() > 1 {
+                    return Err(format!(
+                        "expected tensor of shape {}, got {}",
+                        tensor::format_shape(shape),
+                        tensor::format_shape(t.shape())
+                    ));
+                }
+                return t.reshape(shape.clone()).map(Value::tensor);
+            }
+            ColumnType::EncodedVector { encoding, .. } => {
+                let rounded = match &value {
+                    Value::Vector(v) => encoding.round_trip(v.as_slice())?,
+                    Value::Tensor(t) if t.rank() == 1 => encoding.round_trip(t.as_f32())?,
+                    _ => return Ok(value),
+                };
+                return Ok(Value::Vector(crate::types::ArcVec::new(rounded)));
+            }
+            ColumnType::Enum(ref labels) => {
+                return match &value {
+                    Value::Null => Ok(value),
+                    Value::Text(s) if labels.iter().any(|l| l == s.as_str()) => Ok(value),
+                    Value::Text(s) => Err(format!(
+                        "'{}' is not one of the ENUM labels ({})",
+                        s,
+                        labels
+                            .iter()
+                            .map(|l| format!("'{}'", l))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                    other => Err(format!("expected ENUM label, got {:?}", other)),
+                };
+            }
+            _ => return Ok(value),
+        };
+        let d = match &value {
+            Value::Decimal(d) => **d,
+            Value::Integer(i) => Decimal::from_i64(*i),
+            Value::Float(f) => {
+                Decimal::from_f64(*f).ok_or_else(|| format!("cannot convert {} to DECIMAL", f))?
+            }
+            Value::Text(s) => s.parse::<Decimal>()?,
+            _ => return Ok(value),
+        };
+        d.fit(precision, scale).map(Value::decimal).ok_or_else(|| {
+            format!(
+                "numeric overflow: {} does not fit DECIMAL({}, {})",
+                d, precision, scale
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for ColumnType {
+    /// The type as written in CREATE TABLE, e.g. `VECTOR(768, f16)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnType::Timestamp => f.write_str("TIMESTAMP"),
+            ColumnType::Text => f.write_str("TEXT"),
+            ColumnType::Tensor(dim) => write!(f, "VECTOR({})", dim),
+            ColumnType::Integer => f.write_str("INTEGER"),
+            ColumnType::Float => f.write_str("FLOAT"),
+            ColumnType::Boolean => f.write_str("BOOLEAN"),
+            ColumnType::Spatial => f.write_str("GEOMETRY"),
+            ColumnType::Decimal { precision, scale } => {
+                write!(f, "DECIMAL({}, {})", precision, scale)
+            }
+            ColumnType::Uuid => f.write_str("UUID"),
+            ColumnType::Date => f.write_str("DATE"),
+            ColumnType::Time => f.write_str("TIME"),
+            ColumnType::Array(element) => write!(f, "ARRAY<{}>", element),
+            ColumnType::ShapedTensor(shape) => {
+                let dims: Vec<String> = shape.iter().map(|d| d.to_string()).collect();
+                write!(f, "TENSOR({})", dims.join(", "))
+            }
+            ColumnType::EncodedVector { dim, encoding } => {
+                write!(f, "VECTOR({}, {})", dim, encoding)
+            }
+            ColumnType::Enum(labels) => {
+                let labels: Vec<String> = labels
+                    .iter()
+                    .map(|label| format!("'{}'", label.replace('\'', "''")))
+                    .collect();
+                write!(f, "ENUM({})", labels.join(", "))
+            }
+        }
+    }
 }
 
 /// Column definition
@@ -113,6 +399,19 @@ pub struct ColumnDef {
     /// DEFAULT x to backfill existing rows on read).
     #[serde(default)]
     pub default_value: Option<crate::types::Value>,
+    /// Set for generated columns (ALTER TABLE ADD COLUMN c AS (expr))
+    #[serde(default)]
+    pub generated: Option<GeneratedColumn>,
+}
+
+/// Definition of a generated column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedColumn {
+    /// SQL text of the expression, re-parsed when the column is computed
+    pub expr: String,
+    /// STORED columns are computed on write and kept like any other column
+    /// (and can be indexed); VIRTUAL ones are computed when queried
+    pub stored: bool,
 }
 
 impl ColumnDef {
@@ -125,6 +424,7 @@ impl ColumnDef {
             auto_increment: false,
             auto_increment_start: None,
             default_value: None,
+            generated: None,
         }
     }
 
@@ -231,6 +531,48 @@ pub struct TableSchema {
     /// TTL retention policy (None = keep forever)
     #[serde(default)]
     pub ttl: Option<TTLDuration>,
+    /// Set on the backing table of a continuous aggregate
+    /// (`CREATE MATERIALIZED VIEW`)
+    #[serde(default)]
+    pub continuous_aggregate: Option<ContinuousAggregate>,
+    /// Ring-buffer limit on live rows; the oldest rows are evicted on insert
+    #[serde(default)]
+    pub max_rows: Option<u64>,
+    /// Ring-buffer limit on the encoded size of live rows
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+/// Definition of a continuous aggregate: a bucketed aggregation over one
+/// source table whose result rows are kept in a regular table and updated
+/// as rows are inserted into the source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContinuousAggregate {
+    /// Table the aggregate reads from
+    pub source: String,
+    /// Timestamp column of the source that is bucketed
+    pub ts_column: String,
+    /// Bucket width in microseconds
+    pub interval_micros: i64,
+    /// What each column of the backing table holds, in column order
+    pub outputs: Vec<AggregateOutput>,
+    /// SQL text of the defining query
+    pub query: String,
+}
+
+/// One column of a continuous aggregate; column names refer to the source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AggregateOutput {
+    /// Start of the time bucket
+    Bucket,
+    /// Group key column
+    Key(String),
+    /// COUNT(*) when `None`, otherwise COUNT(col)
+    Count(Option<String>),
+    Sum(String),
+    Avg(String),
+    Min(String),
+    Max(String),
 }
 
 impl TableSchema {
@@ -269,6 +611,9 @@ impl TableSchema {
             table_type: TableType::Standard,
             timeseries_column: None,
             ttl: None,
+            continuous_aggregate: None,
+            max_rows: None,
+            max_bytes: None,
         }
     }
 
@@ -321,6 +666,25 @@ impl TableSchema {
         self
     }
 
+    /// Make this a ring-buffer table: inserts evict the oldest rows once
+    /// either limit is exceeded
+    pub fn with_ring_buffer(mut self, max_rows: Option<u64>, max_bytes: Option<u64>) -> Self {
+        self.max_rows = max_rows;
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Whether inserts evict old rows (`MAX_ROWS` / `MAX_BYTES`)
+    pub fn is_ring_buffer(&self) -> bool {
+        self.max_rows.is_some() || self.max_bytes.is_some()
+    }
+
+    /// Mark as the backing table of a continuous aggregate
+    pub fn with_continuous_aggregate(mut self, definition: ContinuousAggregate) -> Self {
+        self.continuous_aggregate = Some(definition);
+        self
+    }
+
     /// 🚀 Phase 4: Mark primary key as AUTO_INCREMENT with custom start value
     pub fn with_auto_increment_start(mut self, start: i64) -> Self {
         self.primary_key_auto_increment = true;
@@ -352,6 +716,15 @@ impl TableSchema {
         self.primary_key_auto_increment
     }
 
+    /// Whether the primary key is a natural TEXT or UUID key. Integer keys
+    /// double as row ids; natural keys are resolved through an ordered
+    /// column index on the key instead.
+    pub fn has_natural_primary_key(&self) -> bool {
+        self.primary_key()
+            .and_then(|pk| self.get_column(pk))
+            .is_some_and(|c| matches!(c.col_type, ColumnType::Text | ColumnType::Uuid))
+    }
+
     /// Add an index to the table
     pub fn add_index(&mut self, index: IndexDef) {
         self.indexes.push(index);
@@ -367,6 +740,13 @@ impl TableSchema {
         self.column_map.get(name).copied()
     }
 
+    /// Whether any column is a VIRTUAL generated column
+    pub fn has_virtual_columns(&self) -> bool {
+        self.columns
+            .iter()
+            .any(|c| c.generated.as_ref().is_some_and(|g| !g.stored))
+    }
+
     /// Get number of columns
     pub fn column_count(&self) -> usize {
         self.columns.len()
@@ -390,6 +770,22 @@ impl TableSchema {
         self.cached_col_types = self.columns.iter().map(|c| c.col_type.clone()).collect();
     }
 
+    /// Coerce values in place to their declared column types (DECIMAL
+    /// rounding to the column scale, UUID/DATE/TIME/TIMESTAMP parsing, ARRAY element
+    /// conversion, TENSOR shaping). Runs before
+    /// `validate_row`.
+    pub fn coerce_row(&self, row: &mut [crate::types::Value]) -> Result<(), String> {
+        for (col, value) in self.columns.iter().zip(row.iter_mut()) {
+            if col.col_type.is_coerced() && !matches!(value, crate::types::Value::Null) {
+                *value = col
+                    .col_type
+                    .coerce(std::mem::replace(value, crate::types::Value::Null))
+                    .map_err(|e| format!("Column '{}': {}", col.name, e))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Validate a row against this schema
     pub fn validate_row(&self, row: &[crate::types::Value]) -> Result<(), String> {
         if row.len() != self.columns.len() {
@@ -421,11 +817,27 @@ impl TableSchema {
                 (ColumnType::Boolean, crate::types::Value::Bool(_)) => true,
                 (ColumnType::Text, crate::types::Value::Text(_)) => true,
                 (ColumnType::Spatial, crate::types::Value::Spatial(_)) => true,
+                (ColumnType::Decimal { precision, scale }, crate::types::Value::Decimal(d)) => {
+                    d.scale() == *scale && d.fit(*precision, *scale).is_some()
+                }
+                (ColumnType::Uuid, crate::types::Value::Uuid(_)) => true,
+                (ColumnType::Date, crate::types::Value::Date(_)) => true,
+                (ColumnType::Time, crate::types::Value::Time(_)) => true,
+                (ColumnType::Array(_), crate::types::Value::Array(_)) => true,
+                (ColumnType::Enum(labels), crate::types::Value::Text(s)) => {
+                    labels.iter().any(|l| l == s.as_str())
+                }
+                (ColumnType::ShapedTensor(shape), crate::types::Value::Tensor(t)) => {
+                    t.shape() == shape.as_slice()
+                }
 
                 // Legacy types
                 (ColumnType::Timestamp, crate::types::Value::Timestamp(_)) => true,
                 (ColumnType::Tensor(dim), crate::types::Value::Tensor(t)) => t.dimension() == *dim,
                 (ColumnType::Tensor(dim), crate::types::Value::Vector(v)) => v.len() == *dim,
+                (ColumnType::EncodedVector { dim, .. }, crate::types::Value::Vector(v)) => {
+                    v.len() == *dim
+                }
 
                 // Backward compatibility
                 (ColumnType::Integer, crate::types::Value::Timestamp(_)) => true,
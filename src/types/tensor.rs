@@ -4,14 +4,19 @@ use serde::{Deserialize, Serialize};
 
 /// Tensor data type for storing high-dimensional vectors
 ///
-/// Stored as Float32 for compatibility with SQ8 quantization
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Stored as Float32 for compatibility with SQ8 quantization. Data is laid
+/// out row-major according to `shape`; a plain vector has shape `[dimension]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tensor {
-    /// Vector dimension
+    /// Vector dimension (total number of elements)
     dimension: usize,
 
     /// Data stored as Float32
     data: Vec<f32>,
+
+    /// Extent of each axis, e.g. `[3, 224, 224]` (empty = `[dimension]`)
+    #[serde(default)]
+    shape: Vec<usize>,
 }
 
 impl Tensor {
@@ -21,7 +26,27 @@ impl Tensor {
         Self {
             dimension,
             data: values,
+            shape: vec![dimension],
+        }
+    }
+
+    /// Create a tensor with an explicit shape (row-major data)
+    pub fn with_shape(shape: Vec<usize>, values: Vec<f32>) -> Result<Self, String> {
+        check_shape(&shape)?;
+        let expected: usize = shape.iter().product();
+        if expected != values.len() {
+            return Err(format!(
+                "tensor of shape {} needs {} values, got {}",
+                format_shape(&shape),
+                expected,
+                values.len()
+            ));
         }
+        Ok(Self {
+            dimension: values.len(),
+            data: values,
+            shape,
+        })
     }
 
     /// Get dimension
@@ -29,6 +54,113 @@ impl Tensor {
         self.dimension
     }
 
+    /// Extent of each axis
+    pub fn shape(&self) -> &[usize] {
+        if self.shape.is_empty() {
+            std::slice::from_ref(&self.dimension)
+        } else {
+            &self.shape
+        }
+    }
+
+    /// Number of axes
+    pub fn rank(&self) -> usize {
+        self.shape().len()
+    }
+
+    /// Same data viewed with another shape of equal element count
+    pub fn reshape(self, shape: Vec<usize>) -> Result<Self, String> {
+        Self::with_shape(shape, self.data)
+    }
+
+    /// Element at a full multi-dimensional index (one entry per axis)
+    pub fn get(&self, index: &[usize]) -> Option<f32> {
+        let shape = self.shape();
+        if index.len() != shape.len() {
+            return None;
+        }
+        let mut offset = 0;
+        for (&i, &extent) in index.iter().zip(shape) {
+            if i >= extent {
+                return None;
+            }
+            offset = offset * extent + i;
+        }
+        self.data.get(offset).copied()
+    }
+
+    /// The `i`-th sub-tensor along the first axis, dropping that axis
+    /// (`[3, 224, 224]` → `[224, 224]`). A rank-1 tensor yields shape `[1]`.
+    pub fn index(&self, i: usize) -> Option<Tensor> {
+        let shape = self.shape();
+        if i >= shape[0] {
+            return None;
+        }
+        let inner: usize = shape[1..].iter().product();
+        let sub_shape = if shape.len() > 1 {
+            shape[1..].to_vec()
+        } else {
+            vec![1]
+        };
+        let data = self.data[i * inner..(i + 1) * inner].to_vec();
+        Some(Tensor {
+            dimension: data.len(),
+            data,
+            shape: sub_shape,
+        })
+    }
+
+    /// Elements `start..end` along `axis`, keeping every other axis whole
+    /// (`slice(0, 0, 1)` of a `[3, 224, 224]` map is its first channel as
+    /// `[1, 224, 224]`)
+    pub fn slice(&self, axis: usize, start: usize, end: usize) -> Result<Tensor, String> {
+        let shape = self.shape();
+        if axis >= shape.len() {
+            return Err(format!(
+                "axis {} out of range for tensor of shape {}",
+                axis,
+                format_shape(shape)
+            ));
+        }
+        if start >= end || end > shape[axis] {
+            return Err(format!(
+                "slice {}..{} out of range for axis {} of extent {}",
+                start, end, axis, shape[axis]
+            ));
+        }
+        let outer: usize = shape[..axis].iter().product();
+        let inner: usize = shape[axis + 1..].iter().product();
+        let block = shape[axis] * inner;
+        let mut data = Vec::with_capacity(outer * (end - start) * inner);
+        for o in 0..outer {
+            let base = o * block;
+            data.extend_from_slice(&self.data[base + start * inner..base + end * inner]);
+        }
+        let mut sub_shape = shape.to_vec();
+        sub_shape[axis] = end - start;
+        Ok(Tensor {
+            dimension: data.len(),
+            data,
+            shape: sub_shape,
+        })
+    }
+
+    /// Nested JSON lists following the shape (inverse of `parse_json`)
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        fn nest(shape: &[usize], data: &[f32]) -> serde_json::Value {
+            match shape {
+                [] | [_] => serde_json::Value::from(data),
+                [n, rest @ ..] => {
+                    let chunk = data.len() / n;
+                    serde_json::Value::Array(
+                        data.chunks(chunk.max(1)).map(|c| nest(rest, c)).collect(),
+                    )
+                }
+            }
+        }
+        nest(self.shape(), &self.data)
+    }
+
     /// Get data as Float32 slice (zero-copy)
     pub fn as_f32(&self) -> &[f32] {
         &self.data
@@ -77,6 +209,69 @@ impl Tensor {
     }
 }
 
+impl PartialEq for Tensor {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape() == other.shape() && self.data == other.data
+    }
+}
+
+/// Reject empty shapes and zero-length axes
+pub fn check_shape(shape: &[usize]) -> Result<(), String> {
+    if shape.is_empty() || shape.contains(&0) {
+        return Err(format!("invalid tensor shape {}", format_shape(shape)));
+    }
+    Ok(())
+}
+
+/// Parse a (nested) JSON number list such as `[[1, 2], [3, 4]]`; nesting
+/// depth and lengths give the shape and must be regular
+pub fn parse_json(s: &str) -> Result<Tensor, String> {
+    fn walk(
+        j: &serde_json::Value,
+        depth: usize,
+        shape: &mut Vec<usize>,
+        data: &mut Vec<f32>,
+    ) -> Result<(), ()> {
+        match j {
+            serde_json::Value::Array(items) => {
+                if depth == shape.len() {
+                    // First list at a new depth fixes its extent; numbers
+                    // already seen means an earlier sibling was a scalar
+                    if !data.is_empty() && depth > 0 {
+                        return Err(());
+                    }
+                    shape.push(items.len());
+                } else if depth > shape.len() || shape[depth] != items.len() {
+                    return Err(());
+                }
+                items
+                    .iter()
+                    .try_for_each(|item| walk(item, depth + 1, shape, data))
+            }
+            serde_json::Value::Number(n) if depth == shape.len() => {
+                data.push(n.as_f64().ok_or(())? as f32);
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+    let err = || format!("invalid TENSOR: '{}'", s);
+    let json: serde_json::Value = serde_json::from_str(s).map_err(|_| err())?;
+    let mut shape = Vec::new();
+    let mut data = Vec::new();
+    walk(&json, 0, &mut shape, &mut data).map_err(|_| err())?;
+    Tensor::with_shape(shape, data)
+}
+
+/// `[3, 224, 224]` → `3x224x224`
+pub fn format_shape(shape: &[usize]) -> String {
+    shape
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join("x")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,6 +282,7 @@ mod tests {
         let tensor = Tensor::new(values.clone());
 
         assert_eq!(tensor.dimension(), 3);
+        assert_eq!(tensor.shape(), &[3]);
 
         let reconstructed = tensor.to_f32();
         for (a, b) in values.iter().zip(reconstructed.iter()) {
@@ -94,6 +290,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shaped_tensor_access() {
+        // 2 channels of 2x3
+        let t = Tensor::with_shape(vec![2, 2, 3], (0..12).map(|i| i as f32).collect()).unwrap();
+        assert_eq!(t.rank(), 3);
+        assert_eq!(t.get(&[1, 0, 2]), Some(8.0));
+        assert_eq!(t.get(&[1, 2, 0]), None);
+
+        let ch = t.index(1).unwrap();
+        assert_eq!(ch.shape(), &[2, 3]);
+        assert_eq!(ch.as_f32(), &[6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+
+        let cols = t.slice(2, 1, 3).unwrap();
+        assert_eq!(cols.shape(), &[2, 2, 2]);
+        assert_eq!(cols.as_f32(), &[1.0, 2.0, 4.0, 5.0, 7.0, 8.0, 10.0, 11.0]);
+        assert!(t.slice(3, 0, 1).is_err());
+        assert!(t.slice(0, 1, 3).is_err());
+
+        assert!(Tensor::with_shape(vec![2, 2], vec![1.0; 3]).is_err());
+        assert!(Tensor::with_shape(vec![0, 2], vec![]).is_err());
+        assert_eq!(parse_json(&ch.to_json().to_string()).unwrap(), ch);
+        assert!(parse_json("[[1, 2], [3]]").is_err());
+        assert!(parse_json("[1, [2, 3]]").is_err());
+        assert_eq!(t.reshape(vec![4, 3]).unwrap().shape(), &[4, 3]);
+    }
+
     #[test]
     fn test_cosine_similarity() {
         let t1 = Tensor::new(vec![1.0, 0.0, 0.0]);
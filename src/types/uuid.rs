@@ -0,0 +1,197 @@
+//! UUID type (RFC 9562)
+//!
+//! Stored as 16 raw bytes. Ordering is byte-wise, which for version 7 ids
+//! is creation order — new keys append to the end of an index instead of
+//! landing at random positions.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 128-bit universally unique identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Uuid([u8; 16]);
+
+/// Last (unix_ms, counter) handed out by `new_v7`, so ids generated by this
+/// process are strictly increasing even within one millisecond
+static V7_STATE: Mutex<(u64, u16)> = Mutex::new((0, 0));
+
+/// 12-bit counter in the `rand_a` field
+const V7_COUNTER_MAX: u16 = 0x0FFF;
+
+impl Uuid {
+    pub const NIL: Uuid = Uuid([0; 16]);
+
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub fn from_u128(v: u128) -> Self {
+        Self(v.to_be_bytes())
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        u128::from_be_bytes(self.0)
+    }
+
+    /// Random (version 4) UUID
+    pub fn new_v4() -> Self {
+        let mut bytes: [u8; 16] = rand::random();
+        bytes[6] = (bytes[6] & 0x0F) | 0x40;
+        bytes[8] = (bytes[8] & 0x3F) | 0x80;
+        Self(bytes)
+    }
+
+    /// Time-ordered (version 7) UUID: 48-bit unix milliseconds, a 12-bit
+    /// per-process counter, then 62 random bits
+    ///
+    /// Needs no coordination between devices; ids from one process sort in
+    /// generation order, ids from different devices sort by wall clock.
+    pub fn new_v7() -> Self {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let (ms, counter) = {
+            let mut state = V7_STATE.lock();
+            let (last_ms, last_counter) = *state;
+            let next = if now_ms > last_ms {
+                (now_ms, 0)
+            } else if last_counter < V7_COUNTER_MAX {
+                // Same millisecond (or the clock went backwards)
+                (last_ms, last_counter + 1)
+            } else {
+                (last_ms + 1, 0)
+            };
+            *state = next;
+            next
+        };
+        Self::v7_from_parts(ms, counter, rand::random())
+    }
+
+    fn v7_from_parts(unix_ms: u64, counter: u16, rand_b: u64) -> Self {
+        let mut bytes = [0u8; 16];
+        bytes[..6].copy_from_slice(&unix_ms.to_be_bytes()[2..]);
+        bytes[6] = 0x70 | ((counter >> 8) as u8 & 0x0F);
+        bytes[7] = counter as u8;
+        bytes[8..].copy_from_slice(&rand_b.to_be_bytes());
+        bytes[8] = (bytes[8] & 0x3F) | 0x80;
+        Self(bytes)
+    }
+
+    /// Version nibble (4 = random, 7 = time-ordered, 0 for NIL)
+    pub fn version(&self) -> u8 {
+        self.0[6] >> 4
+    }
+
+    /// Creation time of a version 7 UUID in unix milliseconds
+    pub fn timestamp_millis(&self) -> Option<u64> {
+        if self.version() != 7 {
+            return None;
+        }
+        let mut ms = [0u8; 8];
+        ms[2..].copy_from_slice(&self.0[..6]);
+        Some(u64::from_be_bytes(ms))
+    }
+}
+
+impl fmt::Display for Uuid {
+    /// Lowercase hyphenated form, e.g. `0190c6a2-7b3e-7f00-8a1b-2c3d4e5f6a7b`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = String;
+
+    /// Accepts hyphenated or 32-digit forms, in either case, optionally
+    /// wrapped in braces
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid UUID: '{}'", s);
+        let trimmed = s.trim();
+        let body = trimmed
+            .strip_prefix('{')
+            .and_then(|t| t.strip_suffix('}'))
+            .unwrap_or(trimmed);
+        let hex: Vec<u8> = match body.len() {
+            32 => body.bytes().collect(),
+            36 => {
+                let b = body.as_bytes();
+                if [8, 13, 18, 23].iter().any(|&i| b[i] != b'-') {
+                    return Err(invalid());
+                }
+                b.iter().copied().filter(|&c| c != b'-').collect()
+            }
+            _ => return Err(invalid()),
+        };
+        if hex.len() != 32 {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 16];
+        for (i, pair) in hex.chunks(2).enumerate() {
+            let hi = (pair[0] as char).to_digit(16).ok_or_else(invalid)?;
+            let lo = (pair[1] as char).to_digit(16).ok_or_else(invalid)?;
+            bytes[i] = (hi * 16 + lo) as u8;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let s = "0190c6a2-7b3e-7f00-8a1b-2c3d4e5f6a7b";
+        let u: Uuid = s.parse().unwrap();
+        assert_eq!(u.to_string(), s);
+        assert_eq!(
+            "0190C6A27B3E7F008A1B2C3D4E5F6A7B".parse::<Uuid>().unwrap(),
+            u
+        );
+        assert_eq!(format!("{{{}}}", s).parse::<Uuid>().unwrap(), u);
+        assert!("0190c6a2-7b3e-7f00-8a1b-2c3d4e5f6a7"
+            .parse::<Uuid>()
+            .is_err());
+        assert!("0190c6a2x7b3e-7f00-8a1b-2c3d4e5f6a7b"
+            .parse::<Uuid>()
+            .is_err());
+        assert!("zz90c6a2-7b3e-7f00-8a1b-2c3d4e5f6a7b"
+            .parse::<Uuid>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_v4_layout() {
+        let u = Uuid::new_v4();
+        assert_eq!(u.version(), 4);
+        assert_eq!(u.as_bytes()[8] & 0xC0, 0x80);
+        assert_eq!(u.timestamp_millis(), None);
+        assert_ne!(u, Uuid::new_v4());
+    }
+
+    #[test]
+    fn test_v7_is_time_ordered() {
+        let ids: Vec<Uuid> = (0..5000).map(|_| Uuid::new_v7()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ids[0].version(), 7);
+        assert_eq!(ids[0].as_bytes()[8] & 0xC0, 0x80);
+
+        let u = Uuid::v7_from_parts(1_700_000_000_123, 0x0ABC, u64::MAX);
+        assert_eq!(u.timestamp_millis(), Some(1_700_000_000_123));
+        assert_eq!(u.to_string()[..18], *"018bcfe5-687b-7abc");
+    }
+}
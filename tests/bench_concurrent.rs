@@ -1,5 +1,6 @@
 //! Concurrent Workload Benchmark — read-heavy, mixed read/write, concurrent
-//! transactions, concurrent checkpoint, concurrent prepared statements
+//! transactions, concurrent checkpoint, concurrent prepared statements,
+//! MemTable put scaling across writer threads
 //!
 //! Run: cargo test --test bench_concurrent --release -- --nocapture --test-threads=1
 
@@ -727,3 +728,67 @@ fn bench_concurrent_write_read_consistency() {
         db.close().ok();
     }
 }
+
+// ═══════════════════════════════════════════════════════════════
+// Test 9: MemTable Multi-threaded Put (sharded vs single-lock)
+// ═══════════════════════════════════════════════════════════════
+
+#[test]
+#[ignore = "bench/stress/perf: slow in debug, run with --ignored or via bench examples"]
+fn bench_memtable_concurrent_put() {
+    use motedb::storage::lsm::{LSMConfig, MemTable, UnifiedMemTable, Value as LsmValue};
+
+    print_separator();
+
+    let puts_per_thread = if is_ci() { 20_000 } else { 200_000 };
+    let config = LSMConfig {
+        memtable_size: usize::MAX,
+        ..Default::default()
+    };
+
+    for n_threads in [1, 2, 4, 8] {
+        let total = n_threads * puts_per_thread;
+        let key = |t: usize, i: usize| ((t * puts_per_thread + i) as u64) * 16;
+
+        let single = Arc::new(MemTable::new(&config));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for t in 0..n_threads {
+                let mt = Arc::clone(&single);
+                s.spawn(move || {
+                    for i in 0..puts_per_thread {
+                        let k = key(t, i);
+                        mt.put(k, LsmValue::new(k.to_le_bytes().to_vec(), k))
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        print_result(
+            &format!("Single-lock MemTable put, {} threads", n_threads),
+            total,
+            start.elapsed().as_millis() as u64,
+        );
+
+        let sharded = Arc::new(UnifiedMemTable::new(&config));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for t in 0..n_threads {
+                let mt = Arc::clone(&sharded);
+                s.spawn(move || {
+                    for i in 0..puts_per_thread {
+                        let k = key(t, i);
+                        mt.put(k, LsmValue::new(k.to_le_bytes().to_vec(), k))
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        print_result(
+            &format!("Sharded UnifiedMemTable put, {} threads", n_threads),
+            total,
+            start.elapsed().as_millis() as u64,
+        );
+        assert_eq!(sharded.len(), total);
+    }
+}
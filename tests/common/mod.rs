@@ -116,6 +116,22 @@ pub fn get_rss_kb() -> u64 {
         .unwrap_or(0)
 }
 
+// ─── Storage ────────────────────────────────────────────────────────────
+
+/// Bytes of flushed segment files belonging to `table` under `dir`.
+pub fn table_bytes(dir: &std::path::Path, table: &str) -> u64 {
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            total += table_bytes(&path, table);
+        } else if path.parent().and_then(|p| p.file_name()) == Some(table.as_ref()) {
+            total += path.metadata().unwrap().len();
+        }
+    }
+    total
+}
+
 // ─── Timing ─────────────────────────────────────────────────────────────
 
 /// Run a closure N times and return (p50, p99) in microseconds.
@@ -0,0 +1,196 @@
+//! Per-session table access hooks: reads and writes checked before a
+//! statement runs, SHOW TABLES filtering, pool reset and the FFI callback.
+
+use motedb::ffi::*;
+use motedb::sql::{QueryExecutor, Statement};
+use motedb::types::Value;
+use motedb::{ErrorCode, MoteDB, QueryResult, Session, SessionPool, StorageError, TableAccess};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn open_db(dir: &TempDir) -> Arc<MoteDB> {
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db.clone());
+    for sql in [
+        "CREATE TABLE telemetry (id INT PRIMARY KEY, v INT)",
+        "CREATE TABLE plugin_state (id INT PRIMARY KEY, v INT)",
+        "CREATE TABLE secrets (id INT PRIMARY KEY, v INT)",
+        "CREATE INDEX idx_secrets_v ON secrets (v)",
+        "INSERT INTO telemetry VALUES (1, 10)",
+        "INSERT INTO secrets VALUES (1, 42)",
+    ] {
+        s.execute(sql).unwrap();
+    }
+    db
+}
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+/// Reads telemetry, owns plugin_state, nothing else
+fn plugin_session(db: Arc<MoteDB>) -> Session {
+    let mut s = Session::new(db);
+    s.set_access_hook(Some(Arc::new(|table: &str, access| match table {
+        "telemetry" => access == TableAccess::Read,
+        "plugin_state" => true,
+        _ => false,
+    })));
+    s
+}
+
+fn assert_denied(s: &mut Session, sql: &str) {
+    match s.execute(sql) {
+        Err(e @ StorageError::PermissionDenied(_)) => {
+            assert_eq!(e.code(), ErrorCode::PermissionDenied);
+            assert_eq!(e.code().sqlstate(), "42501");
+        }
+        other => panic!("{}: expected PermissionDenied, got {:?}", sql, other),
+    }
+}
+
+#[test]
+fn test_hook_checks_every_table() {
+    let dir = TempDir::new().unwrap();
+    let db = open_db(&dir);
+    let mut s = plugin_session(db.clone());
+
+    assert_eq!(rows(s.execute("SELECT v FROM telemetry").unwrap()).len(), 1);
+    s.execute("INSERT INTO plugin_state VALUES (1, 1)").unwrap();
+
+    for sql in [
+        "SELECT * FROM secrets",
+        "SELECT t.v FROM telemetry t JOIN secrets s ON t.id = s.id",
+        "SELECT v FROM telemetry WHERE id IN (SELECT id FROM secrets)",
+        "WITH s AS (SELECT * FROM secrets) SELECT * FROM s",
+        "SELECT v FROM telemetry UNION SELECT v FROM secrets",
+        "DESCRIBE secrets",
+        "DELETE FROM telemetry",
+        "UPDATE plugin_state SET v = (SELECT MAX(v) FROM secrets)",
+        "DROP TABLE secrets",
+        "DROP INDEX idx_secrets_v",
+        "CREATE TABLE scratch (id INT PRIMARY KEY)",
+    ] {
+        assert_denied(&mut s, sql);
+    }
+
+    // Denied statements had no effect
+    let mut admin = Session::new(db);
+    assert_eq!(
+        rows(admin.execute("SELECT * FROM secrets").unwrap()).len(),
+        1
+    );
+    assert_eq!(
+        rows(admin.execute("SELECT * FROM telemetry").unwrap()).len(),
+        1
+    );
+    assert_eq!(
+        rows(admin.execute("SELECT * FROM plugin_state").unwrap()).len(),
+        1
+    );
+}
+
+#[test]
+fn test_show_tables_lists_readable_tables() {
+    let dir = TempDir::new().unwrap();
+    let executor = QueryExecutor::new(open_db(&dir));
+    assert_eq!(
+        rows(executor.execute(Statement::ShowTables).unwrap()).len(),
+        3
+    );
+
+    executor.set_access_hook(Some(Arc::new(|table: &str, access| {
+        table == "telemetry" || (table == "plugin_state" && access == TableAccess::Write)
+    })));
+    assert_eq!(
+        rows(executor.execute(Statement::ShowTables).unwrap()),
+        vec![vec![Value::text("telemetry".into())]]
+    );
+}
+
+#[test]
+fn test_prepared_batch_and_pool_reset() {
+    let dir = TempDir::new().unwrap();
+    let db = open_db(&dir);
+    let pool = SessionPool::new(db, 1);
+
+    let mut s = pool.acquire(None).unwrap();
+    s.set_access_hook(Some(Arc::new(|table: &str, _| table != "secrets")));
+    s.prepare("ins", "INSERT INTO secrets VALUES (?, ?)")
+        .unwrap();
+    let err = s
+        .execute_prepared_batch("ins", &[vec![Value::Integer(2), Value::Integer(1)]])
+        .unwrap_err();
+    assert!(matches!(err, StorageError::PermissionDenied(_)));
+    pool.release(s).unwrap();
+
+    // A recycled session starts without the previous owner's hook
+    let mut s = pool.acquire(None).unwrap();
+    assert_eq!(rows(s.execute("SELECT * FROM secrets").unwrap()).len(), 1);
+    s.set_access_hook(Some(Arc::new(|_: &str, _| false)));
+    assert_denied(&mut s, "SELECT * FROM secrets");
+    s.set_access_hook(None);
+    assert_eq!(rows(s.execute("SELECT * FROM secrets").unwrap()).len(), 1);
+    pool.release(s).unwrap();
+}
+
+extern "C" fn read_only_telemetry(
+    user_data: *mut c_void,
+    table: *const c_char,
+    write: bool,
+) -> bool {
+    let calls = unsafe { &*(user_data as *const AtomicUsize) };
+    calls.fetch_add(1, Ordering::Relaxed);
+    let table = unsafe { CStr::from_ptr(table) }.to_str().unwrap();
+    table == "telemetry" && !write
+}
+
+#[test]
+fn test_ffi_access_callback() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let calls = AtomicUsize::new(0);
+    unsafe {
+        let h = motedb_open(path.as_ptr());
+        let pool = motedb_pool_create(h, 1);
+        motedb_close(h);
+        let s = motedb_pool_acquire(pool, -1);
+
+        let sql = CString::new("CREATE TABLE telemetry (id INT PRIMARY KEY)").unwrap();
+        motedb_free_string(motedb_session_execute(s, sql.as_ptr()));
+        assert!(motedb_session_set_access_callback(
+            s,
+            read_only_telemetry,
+            &calls as *const AtomicUsize as *mut c_void,
+        ));
+
+        let select = CString::new("SELECT * FROM telemetry").unwrap();
+        motedb_free_string(motedb_session_execute(s, select.as_ptr()));
+        assert_eq!(motedb_session_last_error(s, ptr::null_mut()), 0);
+
+        let insert = CString::new("INSERT INTO telemetry VALUES (1)").unwrap();
+        motedb_free_string(motedb_session_execute(s, insert.as_ptr()));
+        assert_eq!(
+            motedb_session_last_error(s, ptr::null_mut()),
+            ErrorCode::PermissionDenied as i32
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        motedb_session_clear_access_callback(s);
+        motedb_free_string(motedb_session_execute(s, insert.as_ptr()));
+        assert_eq!(motedb_session_last_error(s, ptr::null_mut()), 0);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        motedb_session_release(s);
+        motedb_pool_destroy(pool);
+    }
+}
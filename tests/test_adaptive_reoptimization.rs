@@ -0,0 +1,84 @@
+//! Adaptive re-optimization: index probes that blow past the planned row
+//! estimate switch to a streaming scan and correct the statistics
+
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn row_count(db: &Database, sql: &str) -> usize {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows.len(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn insert_rows(db: &Database, ids: std::ops::Range<i64>, status: impl Fn(i64) -> String) {
+    let ids: Vec<i64> = ids.collect();
+    for chunk in ids.chunks(500) {
+        let values: Vec<String> = chunk
+            .iter()
+            .map(|id| format!("({}, '{}', {})", id, status(*id), id % 7))
+            .collect();
+        db.execute(&format!("INSERT INTO events VALUES {}", values.join(", ")))
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_misestimated_probe_switches_to_scan() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE events (id INT PRIMARY KEY, status TEXT, zone INT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_status ON events (status)")
+        .unwrap();
+
+    // Plan once while every status is distinct: statistics say ~1 row per key
+    insert_rows(&db, 0..100, |id| format!("s{}", id));
+    assert_eq!(
+        row_count(&db, "SELECT * FROM events WHERE status = 's1' AND zone = 1"),
+        1
+    );
+    assert_eq!(db.optimizer_stats().reoptimizations, 0);
+
+    // The data drifts: one status now covers almost the whole table
+    insert_rows(&db, 100..5100, |_| "hot".to_string());
+    assert_eq!(
+        row_count(&db, "SELECT * FROM events WHERE status = 'hot'"),
+        5000
+    );
+    assert_eq!(
+        row_count(
+            &db,
+            "SELECT id FROM events WHERE status = 'hot' AND zone = 3"
+        ),
+        (100..5100).filter(|id| id % 7 == 3).count()
+    );
+    let stats = db.optimizer_stats();
+    assert_eq!(stats.reoptimizations, 1);
+    assert!(stats.cardinality_feedback >= 1);
+
+    // Fed-back cardinality makes the planner pick the scan up front
+    assert_eq!(
+        row_count(&db, "SELECT * FROM events WHERE status = 'hot'"),
+        5000
+    );
+    assert_eq!(db.optimizer_stats().reoptimizations, 1);
+
+    // Selective keys still go through the index and stay correct
+    assert_eq!(
+        row_count(&db, "SELECT * FROM events WHERE status = 's42'"),
+        1
+    );
+    let prepared = db
+        .execute_prepared(
+            "SELECT * FROM events WHERE status = ?",
+            vec![motedb::types::Value::text("hot".to_string())],
+        )
+        .unwrap()
+        .materialize()
+        .unwrap();
+    match prepared {
+        QueryResult::Select { rows, .. } => assert_eq!(rows.len(), 5000),
+        _ => panic!("Expected Select result"),
+    }
+}
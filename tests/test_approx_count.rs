@@ -0,0 +1,104 @@
+//! Row count estimation (estimate_row_count / COUNT_ESTIMATE) and
+//! APPROX_COUNT_DISTINCT
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn int(v: &Value) -> i64 {
+    match v {
+        Value::Integer(i) => *i,
+        other => panic!("Expected integer, got {:?}", other),
+    }
+}
+
+fn assert_close(estimate: i64, actual: i64) {
+    let error = (estimate - actual).abs() as f64 / actual as f64;
+    assert!(error < 0.03, "estimate {} vs actual {}", estimate, actual);
+}
+
+/// 3000 visits from 1200 users across 4 pages
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE visits (id INT PRIMARY KEY, user_id INT, page TEXT)")
+        .unwrap();
+    for i in 0..3000 {
+        db.execute(&format!(
+            "INSERT INTO visits VALUES ({}, {}, 'p{}')",
+            i,
+            (i * 7) % 1200,
+            i % 4
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_estimate_row_count() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    assert_eq!(db.estimate_row_count("visits").unwrap(), 3000);
+    db.flush().unwrap();
+    assert_eq!(db.estimate_row_count("visits").unwrap(), 3000);
+    db.execute("INSERT INTO visits VALUES (3000, 1, 'p0')")
+        .unwrap();
+    assert_eq!(
+        rows(&db, "SELECT COUNT_ESTIMATE() FROM visits"),
+        vec![vec![Value::Integer(3001)]]
+    );
+
+    assert!(db.estimate_row_count("missing").is_err());
+    for sql in [
+        "SELECT COUNT_ESTIMATE() FROM visits WHERE page = 'p1'",
+        "SELECT COUNT_ESTIMATE(id) FROM visits",
+        "SELECT id, COUNT_ESTIMATE() FROM visits",
+    ] {
+        assert!(
+            db.execute(sql).and_then(|r| r.materialize()).is_err(),
+            "{}",
+            sql
+        );
+    }
+}
+
+#[test]
+fn test_approx_count_distinct() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let r = rows(
+        &db,
+        "SELECT APPROX_COUNT_DISTINCT(user_id), COUNT(DISTINCT user_id) FROM visits",
+    );
+    assert_close(int(&r[0][0]), int(&r[0][1]));
+    assert_close(int(&r[0][0]), 1200);
+
+    let r = rows(
+        &db,
+        "SELECT APPROX_COUNT_DISTINCT(page) FROM visits WHERE user_id < 600",
+    );
+    assert_eq!(r, vec![vec![Value::Integer(4)]]);
+
+    let r = rows(
+        &db,
+        "SELECT page, APPROX_COUNT_DISTINCT(user_id) AS users FROM visits GROUP BY page ORDER BY page",
+    );
+    assert_eq!(r.len(), 4);
+    for row in &r {
+        assert_close(int(&row[1]), 300);
+    }
+
+    assert!(db
+        .execute("SELECT APPROX_COUNT_DISTINCT(*) FROM visits")
+        .and_then(|r| r.materialize())
+        .is_err());
+}
@@ -0,0 +1,219 @@
+//! ARRAY column type: storage, element access, ARRAY_CONTAINS, UNNEST and GROUP BY
+
+use motedb::types::Value;
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::text_from(s)
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE frames (id INT PRIMARY KEY, labels ARRAY<TEXT>, scores ARRAY<FLOAT>)")
+        .unwrap();
+    db.execute("INSERT INTO frames VALUES (1, ARRAY['car', 'person'], ARRAY[0.9, 0.75])")
+        .unwrap();
+    db.execute("INSERT INTO frames VALUES (2, ARRAY['person'], ARRAY[0.6])")
+        .unwrap();
+    // JSON text is accepted as well
+    db.execute(r#"INSERT INTO frames VALUES (3, '["car", "dog", "car"]', '[0.8, 0.5, 0.4]')"#)
+        .unwrap();
+    db.execute("INSERT INTO frames VALUES (4, ARRAY[], ARRAY[])")
+        .unwrap();
+    db.execute("INSERT INTO frames VALUES (5, NULL, NULL)")
+        .unwrap();
+    db
+}
+
+#[test]
+fn test_array_roundtrip_and_element_access() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    let r = rows(
+        db.execute("SELECT labels, scores FROM frames ORDER BY id")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 5);
+    assert_eq!(r[0][0], Value::array(vec![text("car"), text("person")]));
+    assert_eq!(
+        r[0][1],
+        Value::array(vec![Value::Float(0.9), Value::Float(0.75)])
+    );
+    assert_eq!(r[3][0], Value::array(vec![]));
+    assert_eq!(r[4][0], Value::Null);
+
+    let r = rows(
+        db.execute("SELECT labels[1], labels[-1], labels[5], ARRAY_LENGTH(scores) FROM frames WHERE id = 3")
+            .unwrap(),
+    );
+    assert_eq!(
+        r[0],
+        vec![text("car"), text("car"), Value::Null, Value::Integer(3)]
+    );
+
+    let r = rows(
+        db.execute("SELECT scores[2] * 2 FROM frames WHERE id = 1")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Float(1.5));
+
+    assert!(db
+        .execute("INSERT INTO frames VALUES (6, ARRAY['x'], ARRAY['high'])")
+        .is_err());
+    assert!(db
+        .execute("INSERT INTO frames VALUES (6, 'not json', NULL)")
+        .is_err());
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, v ARRAY<VECTOR(3)>)")
+        .is_err());
+}
+
+#[test]
+fn test_array_contains_filter() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    let ids = |sql: &str| -> Vec<Value> {
+        rows(db.execute(sql).unwrap())
+            .into_iter()
+            .map(|r| r[0].clone())
+            .collect()
+    };
+    assert_eq!(
+        ids("SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'car') ORDER BY id"),
+        vec![Value::Integer(1), Value::Integer(3)]
+    );
+    assert_eq!(
+        ids("SELECT id FROM frames WHERE ARRAY_LENGTH(labels) = 0"),
+        vec![Value::Integer(4)]
+    );
+    assert_eq!(
+        ids("SELECT id FROM frames WHERE scores[1] > 0.7 ORDER BY id"),
+        vec![Value::Integer(1), Value::Integer(3)]
+    );
+    assert_eq!(
+        ids("SELECT ARRAY_CONTAINS(ARRAY[1, 2, 3], 2.0) FROM frames WHERE id = 1"),
+        vec![Value::Bool(true)]
+    );
+}
+
+#[test]
+fn test_unnest_expands_rows() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    let r = rows(
+        db.execute("SELECT id, UNNEST(labels) AS label, UNNEST(scores) FROM frames WHERE id = 1")
+            .unwrap(),
+    );
+    assert_eq!(
+        r,
+        vec![
+            vec![Value::Integer(1), text("car"), Value::Float(0.9)],
+            vec![Value::Integer(1), text("person"), Value::Float(0.75)],
+        ]
+    );
+
+    // Empty and NULL arrays produce no rows
+    let r = rows(db.execute("SELECT UNNEST(labels) FROM frames").unwrap());
+    assert_eq!(r.len(), 6);
+
+    let r = rows(
+        db.execute("SELECT id, UNNEST(scores) AS s FROM frames ORDER BY s DESC LIMIT 2")
+            .unwrap(),
+    );
+    assert_eq!(
+        r,
+        vec![
+            vec![Value::Integer(1), Value::Float(0.9)],
+            vec![Value::Integer(3), Value::Float(0.8)],
+        ]
+    );
+}
+
+#[test]
+fn test_array_group_by() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    let expected = vec![
+        vec![text("car"), Value::Integer(3)],
+        vec![text("dog"), Value::Integer(1)],
+        vec![text("person"), Value::Integer(2)],
+    ];
+    let r = rows(
+        db.execute(
+            "SELECT UNNEST(labels) AS label, COUNT(*) FROM frames GROUP BY label ORDER BY label",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r, expected);
+    let r = rows(
+        db.execute(
+            "SELECT label, COUNT(*) FROM (SELECT UNNEST(labels) AS label FROM frames) AS d \
+             GROUP BY label ORDER BY label",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r, expected);
+
+    db.execute("INSERT INTO frames VALUES (6, ARRAY['person'], ARRAY[0.1])")
+        .unwrap();
+    let r = rows(
+        db.execute("SELECT labels, COUNT(*) FROM frames WHERE id < 7 GROUP BY labels")
+            .unwrap(),
+    );
+    let person = r
+        .iter()
+        .find(|row| row[0] == Value::array(vec![text("person")]))
+        .unwrap();
+    assert_eq!(person[1], Value::Integer(2));
+}
+
+#[test]
+fn test_array_survives_flush_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = setup(&dir);
+        db.flush().unwrap();
+        db.execute("CREATE TABLE hits (id INT PRIMARY KEY, ids ARRAY<INT>, days ARRAY<DATE>)")
+            .unwrap();
+        db.execute("INSERT INTO hits VALUES (1, ARRAY[3, -1, 7], ARRAY['2024-03-01', NULL])")
+            .unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    let r = rows(
+        db.execute("SELECT labels FROM frames WHERE id = 3")
+            .unwrap(),
+    );
+    assert_eq!(
+        r[0][0],
+        Value::array(vec![text("car"), text("dog"), text("car")])
+    );
+    let r = rows(db.execute("SELECT ids, days FROM hits").unwrap());
+    assert_eq!(
+        r[0][0],
+        Value::array(vec![
+            Value::Integer(3),
+            Value::Integer(-1),
+            Value::Integer(7)
+        ])
+    );
+    assert_eq!(
+        r[0][1],
+        Value::array(vec![
+            Value::Date("2024-03-01".parse().unwrap()),
+            Value::Null
+        ])
+    );
+}
@@ -0,0 +1,205 @@
+//! Element indexes on ARRAY columns: CREATE INDEX on an ARRAY column indexes
+//! every element, and ARRAY_CONTAINS filters, UPDATE and DELETE use it.
+
+use motedb::types::Value;
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = rows(db.execute(sql).unwrap())
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref other => panic!("expected id, got {:?}", other),
+        })
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+fn indexed_ids(db: &Database, column: &str, element: Value) -> usize {
+    db.query_by_array_element("frames", column, &element)
+        .unwrap()
+        .len()
+}
+
+fn insert_frames(db: &Database, range: std::ops::Range<i64>) {
+    for i in range {
+        let tag = ["car", "person", "dog"][(i % 3) as usize];
+        db.execute(&format!(
+            "INSERT INTO frames VALUES ({}, ARRAY['{}', 'sensor-{}'], ARRAY[{}, 0.5])",
+            i,
+            tag,
+            i % 4,
+            i % 5
+        ))
+        .unwrap();
+    }
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE frames (id INT PRIMARY KEY, labels ARRAY<TEXT>, scores ARRAY<FLOAT>)")
+        .unwrap();
+    db
+}
+
+#[test]
+fn test_array_index_backfill_and_maintenance() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    insert_frames(&db, 0..30);
+    db.flush().unwrap();
+    db.execute("CREATE INDEX idx_labels ON frames (labels)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_scores ON frames (scores)")
+        .unwrap();
+    insert_frames(&db, 30..40);
+    db.execute("INSERT INTO frames VALUES (40, NULL, ARRAY[])")
+        .unwrap();
+
+    assert_eq!(indexed_ids(&db, "labels", Value::text_from("dog")), 13);
+    assert_eq!(indexed_ids(&db, "scores", Value::Float(0.5)), 40);
+
+    let expected: Vec<i64> = (0..40).filter(|i| i % 3 == 2).collect();
+    for flushed in [false, true] {
+        if flushed {
+            db.flush().unwrap();
+        }
+        assert_eq!(
+            ids(
+                &db,
+                "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'dog')"
+            ),
+            expected
+        );
+        // Other conjuncts still apply; integer literals match FLOAT elements
+        assert_eq!(
+            ids(
+                &db,
+                "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'sensor-1') AND id > 20"
+            ),
+            vec![21, 25, 29, 33, 37]
+        );
+        assert_eq!(
+            ids(&db, "SELECT id FROM frames WHERE ARRAY_CONTAINS(scores, 3)"),
+            vec![3, 8, 13, 18, 23, 28, 33, 38]
+        );
+        assert_eq!(
+            rows(
+                db.execute("SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'car') LIMIT 3")
+                    .unwrap()
+            )
+            .len(),
+            3
+        );
+        assert!(ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'cat')"
+        )
+        .is_empty());
+    }
+
+    // Updates move the row between elements, deletes remove it
+    db.execute("UPDATE frames SET labels = ARRAY['cat', 'dog'] WHERE id = 0")
+        .unwrap();
+    db.execute("DELETE FROM frames WHERE id = 2").unwrap();
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'cat')"
+        ),
+        vec![0]
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'dog')"
+        )[..2],
+        [0, 5]
+    );
+    assert_eq!(indexed_ids(&db, "labels", Value::text_from("car")), 13);
+
+    // UPDATE / DELETE filtered by containment
+    db.execute("DELETE FROM frames WHERE ARRAY_CONTAINS(labels, 'cat')")
+        .unwrap();
+    db.execute("UPDATE frames SET labels = ARRAY['seen'] WHERE ARRAY_CONTAINS(labels, 'person')")
+        .unwrap();
+    assert!(ids(
+        &db,
+        "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'person')"
+    )
+    .is_empty());
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'seen')"
+        )
+        .len(),
+        13
+    );
+    assert_eq!(indexed_ids(&db, "labels", Value::text_from("cat")), 0);
+}
+
+#[test]
+fn test_array_index_does_not_serve_scalar_predicates() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    insert_frames(&db, 0..6);
+    db.execute("CREATE INDEX idx_labels ON frames (labels)")
+        .unwrap();
+
+    // An element is not the whole array
+    assert!(ids(&db, "SELECT id FROM frames WHERE labels = 'car'").is_empty());
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE labels = ARRAY['car', 'sensor-3']"
+        ),
+        vec![3]
+    );
+
+    db.execute("DROP INDEX idx_labels").unwrap();
+    assert!(db
+        .query_by_array_element("frames", "labels", &Value::text_from("car"))
+        .is_err());
+    db.execute("INSERT INTO frames VALUES (6, ARRAY['car'], ARRAY[])")
+        .unwrap();
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'car')"
+        ),
+        vec![0, 3, 6]
+    );
+}
+
+#[test]
+fn test_array_index_survives_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = setup(&dir);
+        insert_frames(&db, 0..12);
+        db.execute("CREATE INDEX idx_labels ON frames (labels)")
+            .unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    insert_frames(&db, 12..15);
+    assert_eq!(indexed_ids(&db, "labels", Value::text_from("person")), 5);
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM frames WHERE ARRAY_CONTAINS(labels, 'person')"
+        ),
+        vec![1, 4, 7, 10, 13]
+    );
+}
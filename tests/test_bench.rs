@@ -0,0 +1,100 @@
+//! Benchmark harness: workload runs, percentiles and target checks
+#![cfg(feature = "bench")]
+
+use motedb::bench::{self, BenchConfig, LatencyStats, Targets, Workload, WorkloadReport};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn small_config() -> BenchConfig {
+    BenchConfig {
+        rows: 300,
+        operations: 20,
+        batch_size: 100,
+        dim: 8,
+        k: 5,
+        ..BenchConfig::default()
+    }
+}
+
+#[test]
+fn test_every_workload_runs() {
+    let dir = TempDir::new().unwrap();
+    let config = small_config();
+    for workload in Workload::ALL {
+        let report = bench::run(workload, &config, dir.path()).unwrap();
+        assert_eq!(report.workload, workload);
+        assert_eq!(report.rows_loaded, config.rows, "{}", workload);
+        assert_eq!(report.latency.count, config.operations, "{}", workload);
+        assert!(report.latency.p50 <= report.latency.p99, "{}", workload);
+        assert!(report.rows_per_sec > 0.0, "{}", workload);
+        if workload != Workload::MixedWrite {
+            assert!(report.rows_returned > 0, "{} returned no rows", workload);
+        }
+        let json = report.to_json();
+        assert_eq!(json["workload"], workload.name());
+        assert!(report.to_string().starts_with(workload.name()));
+    }
+    // Each workload cleans up its database
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_runs_are_reproducible() {
+    let dir = TempDir::new().unwrap();
+    let config = small_config();
+    // Same seed, same data and queries
+    for workload in [Workload::TextSearch, Workload::SpatialRange] {
+        let first = bench::run(workload, &config, dir.path()).unwrap();
+        let second = bench::run(workload, &config, dir.path()).unwrap();
+        assert_eq!(first.rows_returned, second.rows_returned, "{}", workload);
+    }
+}
+
+#[test]
+fn test_latency_percentiles() {
+    let samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+    let stats = LatencyStats::from_samples(samples);
+    assert_eq!(stats.count, 100);
+    assert_eq!(stats.p50, Duration::from_millis(50));
+    assert_eq!(stats.p95, Duration::from_millis(95));
+    assert_eq!(stats.p99, Duration::from_millis(99));
+    assert_eq!(stats.max, Duration::from_millis(100));
+    assert_eq!(stats.mean, Duration::from_micros(50_500));
+    assert_eq!(
+        LatencyStats::from_samples(Vec::new()),
+        LatencyStats::default()
+    );
+}
+
+#[test]
+fn test_violations_and_workload_names() {
+    let report = WorkloadReport {
+        workload: Workload::VectorKnn,
+        rows_loaded: 1000,
+        load_time: Duration::from_secs(10),
+        latency: LatencyStats {
+            p99: Duration::from_millis(80),
+            ..LatencyStats::default()
+        },
+        rows_returned: 0,
+        rows_per_sec: 100.0,
+        peak_memory_bytes: Some(20 * 1024 * 1024),
+    };
+    let missed = report.violations(&Targets::default());
+    assert_eq!(missed.len(), 2, "{:?}", missed);
+    assert!(missed[0].starts_with("vector-knn: P99 80.00 ms"));
+    assert!(missed[1].contains("100 rows/s < 200 rows/s"));
+
+    let lenient = Targets {
+        p99: Duration::from_millis(100),
+        min_rows_per_sec: 50.0,
+        max_memory_bytes: 10 * 1024 * 1024,
+    };
+    let missed = report.violations(&lenient);
+    assert_eq!(missed, vec!["vector-knn: peak memory 20.0 MB > 10.0 MB"]);
+
+    for workload in Workload::ALL {
+        assert_eq!(workload.name().parse::<Workload>().unwrap(), workload);
+    }
+    assert!("vector".parse::<Workload>().is_err());
+}
@@ -356,75 +356,46 @@ fn text_concatenation_not_supported() {
 // ═══════════════════════════════════════════════════════════════════════════
 // SECTION D: Correlated subquery
 //
-// ⚠️ KNOWN LIMITATION: MoteDB v0.5.x does not implement correlated subqueries.
 // A subquery that references an outer-query column (e.g.
 // `SELECT ... WHERE col = (SELECT ... FROM t2 WHERE t2.x = outer.col)`) is
-// executed as if it were uncorrelated — the outer reference is silently
-// unresolved, producing a single result used for every outer row. This is
-// wrong, but documented here so the behavior is at least known. These tests
-// are written to verify the *current* behavior so a future fix breaks them.
+// re-run for each outer row with the outer column bound to that row's value.
 // ═══════════════════════════════════════════════════════════════════════════
 
 #[test]
-fn correlated_subquery_in_select_currently_uncorrelated() {
+fn correlated_subquery_in_select() {
     let (db, _dir) = emp_db();
-    // CORRECT SQL behavior: each emp should see the max of their own dept.
-    // dept 10 max=200 (alice,bob), dept 20 max=150 (carol; dave NULL skipped),
-    // NULL dept max=300 (eve alone).
-    //
-    // CURRENT (BUGGY) BEHAVIOR: the subquery's outer reference
-    // (`emp.dept_id`) is unresolved, so the subquery returns the SAME value
-    // for every outer row. The exact value depends on how the unresolved
-    // column is interpreted, but the key observation is: all 5 rows get the
-    // same value, which is wrong (correct result varies per dept).
+    // Each emp sees the max of their own dept: dept 10 max=200 (alice, bob),
+    // dept 20 max=150 (carol; dave NULL skipped). eve's NULL dept matches
+    // no row, so her subquery is NULL.
     let r = rows(
         &db,
         "SELECT id, (SELECT MAX(salary) FROM emp e2 WHERE e2.dept_id = emp.dept_id) AS m \
          FROM emp ORDER BY id",
     );
-    assert_eq!(r.len(), 5);
-    let first_val = match &r[0][1] {
-        Value::Integer(n) => Some(*n),
-        Value::Null => None,
-        _ => panic!("unexpected value type"),
-    };
-    // Every row has the same (wrong) value — proof that correlation is broken.
-    for (i, row) in r.iter().enumerate() {
-        let this_val = match &row[1] {
-            Value::Integer(n) => Some(*n),
-            Value::Null => None,
-            _ => panic!("row {}: unexpected value type", i),
-        };
-        assert_eq!(
-            this_val, first_val,
-            "correlated subquery currently returns same value for all rows (known bug). row {} differs: {:?}",
-            i, row
-        );
-    }
+    let maxes: Vec<Value> = r.iter().map(|row| row[1].clone()).collect();
+    assert_eq!(
+        maxes,
+        vec![
+            Value::Integer(200),
+            Value::Integer(200),
+            Value::Integer(150),
+            Value::Integer(150),
+            Value::Null,
+        ]
+    );
 }
 
 #[test]
-fn correlated_subquery_in_where_currently_uncorrelated() {
+fn correlated_subquery_in_where() {
     let (db, _dir) = emp_db();
-    // CORRECT SQL: only bob (id=2, dept 10, salary 200 > avg 150) qualifies.
-    // CURRENT (BUGGY): outer ref unresolved → subquery computes global AVG
-    // = (100+200+150+300)/4 = 187.5. WHERE salary > 187.5 → bob(200), eve(300).
+    // Only bob (id=2, dept 10, salary 200 > dept avg 150) qualifies: carol
+    // equals her dept's avg, and eve's NULL dept has no avg.
     let r = rows(
         &db,
         "SELECT id FROM emp e \
          WHERE salary > (SELECT AVG(salary) FROM emp e2 WHERE e2.dept_id = e.dept_id)",
     );
-    let ids: Vec<i64> = r
-        .iter()
-        .filter_map(|row| match row.first() {
-            Some(Value::Integer(n)) => Some(*n),
-            _ => None,
-        })
-        .collect();
-    let mut ids_sorted = ids.clone();
-    ids_sorted.sort();
-    // Document current bug: returns global-AVG-based filter, not per-dept.
-    assert_eq!(ids_sorted, vec![2, 5], "correlated subquery in WHERE returns global-AVG result (known bug)");
+    assert_eq!(r, vec![vec![Value::Integer(2)]]);
 }
 
 /// Sanity check: UNCORRELATED subqueries (no outer reference) DO work
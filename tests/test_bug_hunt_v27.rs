@@ -433,31 +433,19 @@ fn null_inequality_in_projection() {
 
 #[test]
 fn sum_near_i64_max() {
-    // SUM(i64::MAX - 1, 2) overflows → must promote to Float (not wrap around
-    // to a negative Integer, which was the v27 bug: store.aggregate_filtered
-    // used wrapping_add).
+    // SUM(i64::MAX - 1, 2) overflows INTEGER → must be an error (not wrap
+    // around to a negative Integer, which was the v27 bug: store.aggregate_filtered
+    // used wrapping_add, nor a silently rounded Float).
     let (db, _dir) = new_db();
     exec(&db, "CREATE TABLE t (id INT PRIMARY KEY, v INT)");
     exec(&db, "INSERT INTO t VALUES (1, 9223372036854775806)");
     exec(&db, "INSERT INTO t VALUES (2, 2)");
+    let err = try_rows(&db, "SELECT SUM(v) FROM t").unwrap_err();
+    assert!(err.contains("NumericOverflow"), "got {}", err);
+    // Back in range once a negative value joins: exact, not rounded
+    exec(&db, "INSERT INTO t VALUES (3, -5)");
     let r = rows(&db, "SELECT SUM(v) FROM t");
-    // Exact sum is i64::MAX + 1 = 2^63 = 9.223372036854776e18.
-    // The wrapping_add bug returned Integer(-9223372036854775808) (i64::MIN) —
-    // a silent wrong result. After the fix, SUM promotes to Float on overflow.
-    // We assert it's NOT the wrapped negative Integer (the bug signature).
-    match &r[0][0] {
-        Value::Integer(n) if *n < 0 => panic!(
-            "SUM overflow wrapped to negative Integer {} (wrapping_add bug)",
-            n
-        ),
-        Value::Integer(_) => {} // exact, no overflow (unexpected but not wrong)
-        Value::Float(f) => {
-            // Should be ~9.22e18 (2^63). Accept any positive Float.
-            assert!(*f > 1e18, "SUM overflow Float should be ~9.22e18, got {}", f);
-        }
-        Value::Null => panic!("SUM of non-null should not be NULL"),
-        o => panic!("got {:?}", o),
-    }
+    assert_eq!(r[0][0], Value::Integer(i64::MAX - 4));
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
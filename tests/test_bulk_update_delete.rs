@@ -0,0 +1,129 @@
+//! Bulk UPDATE/DELETE: row writes grouped into write batches keep indexes consistent
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+const ROWS: i64 = 3000;
+
+fn count(db: &Database, sql: &str) -> i64 {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => match rows[0][0] {
+            Value::Integer(n) => n,
+            ref v => panic!("Expected integer count, got {:?}", v),
+        },
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn matches(db: &Database, term: &str) -> usize {
+    // Explicit LIMIT: a bare MATCH returns at most the top 1000 hits
+    let sql = format!(
+        "SELECT id FROM items WHERE MATCH(note) AGAINST('{}') LIMIT 10000",
+        term
+    );
+    match db.execute(&sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows.len(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup() -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE items (id INT PRIMARY KEY, grp INT, note TEXT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_grp ON items (grp)").unwrap();
+    db.execute("CREATE TEXT INDEX idx_note ON items (note)")
+        .unwrap();
+    for i in 0..ROWS {
+        db.execute(&format!(
+            "INSERT INTO items VALUES ({}, {}, 'item alpha {}')",
+            i,
+            i % 3,
+            i
+        ))
+        .unwrap();
+    }
+    (db, dir)
+}
+
+#[test]
+fn test_bulk_update_keeps_indexes_consistent() {
+    let (db, _dir) = setup();
+    let result = db
+        .execute("UPDATE items SET grp = 7, note = 'item beta' WHERE grp = 0")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    assert_eq!(result.affected_rows(), 1000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 0"), 0);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 7"), 1000);
+    assert_eq!(matches(&db, "beta"), 1000);
+    assert_eq!(matches(&db, "alpha"), 2000);
+
+    // Full-scan path (no usable index on the predicate)
+    let result = db
+        .execute("UPDATE items SET grp = 8 WHERE id >= 0")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    assert_eq!(result.affected_rows(), ROWS as usize);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 8"), ROWS);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 7"), 0);
+}
+
+#[test]
+fn test_bulk_delete_keeps_indexes_consistent() {
+    let (db, _dir) = setup();
+    let result = db
+        .execute("DELETE FROM items WHERE grp <> 1")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    assert_eq!(result.affected_rows(), 2000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items"), 1000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 0"), 0);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 1"), 1000);
+    assert_eq!(matches(&db, "alpha"), 1000);
+
+    // Index-driven row set
+    let result = db
+        .execute("DELETE FROM items WHERE grp = 1")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    assert_eq!(result.affected_rows(), 1000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items"), 0);
+}
+
+#[test]
+fn test_bulk_writes_in_transaction_roll_back() {
+    let (db, _dir) = setup();
+    db.execute("BEGIN").unwrap();
+    db.execute("UPDATE items SET grp = 9 WHERE grp = 2")
+        .unwrap();
+    db.execute("DELETE FROM items WHERE grp = 0").unwrap();
+    db.execute("ROLLBACK").unwrap();
+
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items"), ROWS);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 9"), 0);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE grp = 2"), 1000);
+}
+
+#[test]
+fn test_bulk_update_of_primary_key() {
+    let (db, _dir) = setup();
+    let result = db
+        .execute("UPDATE items SET id = id + 100000 WHERE grp = 1")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    assert_eq!(result.affected_rows(), 1000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items WHERE id = 1"), 0);
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM items WHERE id = 100001"),
+        1
+    );
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM items"), ROWS);
+}
@@ -0,0 +1,134 @@
+//! Unified cache statistics (db.cache_stats), per-table row-cache quotas
+//! and the row-cache write policy
+
+use motedb::types::Value;
+use motedb::{DBConfig, Database, QueryResult, RowCacheWritePolicy};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn insert(db: &Database, table: &str, ids: std::ops::Range<i64>) {
+    for i in ids {
+        db.execute(&format!("INSERT INTO {} VALUES ({}, {})", table, i, i * 2))
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_cache_stats_report_every_cache() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    insert(&db, "t", 0..100);
+    db.execute("CREATE INDEX idx_v ON t (v)").unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(rows(&db, "SELECT id FROM t WHERE id = 7").len(), 1);
+        assert_eq!(rows(&db, "SELECT id FROM t WHERE v = 14").len(), 1);
+        assert!(rows(&db, "SELECT id FROM t WHERE id = 5000").is_empty());
+    }
+
+    let stats = db.cache_stats();
+    assert!(stats.row_cache.size > 0);
+    assert!(stats.row_cache.hits > 0);
+    assert!(stats.index_cache.capacity > 0);
+    assert!(stats.negative_cache.hits > 0);
+    assert!(stats.row_cache_quotas.is_empty());
+}
+
+#[test]
+fn test_row_cache_quota_protects_other_tables() {
+    let dir = TempDir::new().unwrap();
+    let config = DBConfig {
+        row_cache_size: Some(100),
+        row_cache_quotas: [("chatty".to_string(), 20)].into_iter().collect(),
+        ..Default::default()
+    };
+    let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+    db.execute("CREATE TABLE quiet (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    db.execute("CREATE TABLE chatty (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    insert(&db, "quiet", 0..50);
+    insert(&db, "chatty", 0..500);
+
+    let stats = db.cache_stats();
+    assert_eq!(stats.row_cache_quotas.len(), 1);
+    assert_eq!(stats.row_cache_quotas[0].table, "chatty");
+    assert_eq!(stats.row_cache_quotas[0].used, 20);
+    // 50 quiet + 20 chatty rows fit without touching the quiet table
+    assert_eq!(stats.row_cache.size, 70);
+    assert_eq!(stats.row_cache.evictions, 480);
+
+    // Lifting the quota lets the chatty table take over the cache again
+    db.set_row_cache_quota("chatty", None).unwrap();
+    insert(&db, "chatty", 500..600);
+    let stats = db.cache_stats();
+    assert!(stats.row_cache_quotas.is_empty());
+    assert_eq!(stats.row_cache.size, 100);
+
+    assert!(db.set_row_cache_quota("chatty", Some(0)).is_err());
+    let config = DBConfig {
+        row_cache_quotas: [("t".to_string(), 0)].into_iter().collect(),
+        ..Default::default()
+    };
+    assert!(Database::create_with_config(dir.path().join("other.mote"), config).is_err());
+}
+
+#[test]
+fn test_row_cache_write_policies_never_serve_stale_rows() {
+    for policy in [
+        RowCacheWritePolicy::WriteThrough,
+        RowCacheWritePolicy::InvalidateOnCommit,
+    ] {
+        let dir = TempDir::new().unwrap();
+        let config = DBConfig {
+            row_cache_write_policy: policy,
+            ..Default::default()
+        };
+        let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+            .unwrap();
+        insert(&db, "t", 0..10);
+        for id in [1, 2, 3] {
+            rows(&db, &format!("SELECT * FROM t WHERE id = {}", id));
+        }
+
+        db.execute("UPDATE t SET v = 100 WHERE id = 1").unwrap();
+        db.execute("DELETE FROM t WHERE id = 2").unwrap();
+        // A row whose PK changes moves to another row id
+        db.execute("UPDATE t SET id = 50 WHERE id = 3").unwrap();
+        let mut batch = db.write_batch();
+        batch.update("t", 4, vec![Value::Integer(4), Value::Integer(44)]);
+        batch.commit().unwrap();
+
+        let v = |id: i64| rows(&db, &format!("SELECT v FROM t WHERE id = {}", id));
+        assert_eq!(v(1), vec![vec![Value::Integer(100)]], "{:?}", policy);
+        assert!(v(2).is_empty(), "{:?}", policy);
+        assert!(v(3).is_empty(), "{:?}", policy);
+        assert_eq!(v(50), vec![vec![Value::Integer(6)]], "{:?}", policy);
+        assert_eq!(v(4), vec![vec![Value::Integer(44)]], "{:?}", policy);
+        assert_eq!(db.get_row("t", 3).unwrap(), None, "{:?}", policy);
+
+        let writes = db.cache_stats().row_cache_writes;
+        assert_eq!(writes.policy, policy);
+        match policy {
+            RowCacheWritePolicy::WriteThrough => {
+                // 10 inserts, 2 updates, the batch update; the delete and
+                // the moved row's old id are dropped
+                assert_eq!(writes.write_through, 13);
+                assert_eq!(writes.invalidations, 2);
+            }
+            RowCacheWritePolicy::InvalidateOnCommit => {
+                assert_eq!(writes.write_through, 0);
+                assert_eq!(writes.invalidations, 15);
+            }
+        }
+    }
+}
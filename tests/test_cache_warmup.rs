@@ -0,0 +1,85 @@
+//! Cache state persistence: hot keys saved at close, caches warmed at open
+
+use motedb::types::Value;
+use motedb::{DBConfig, Database, QueryResult};
+use tempfile::TempDir;
+
+fn config() -> DBConfig {
+    DBConfig {
+        persist_cache_state: true,
+        ..Default::default()
+    }
+}
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, sensor TEXT, value INT)")
+        .unwrap();
+    for i in 0..200 {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, 's{}', {})",
+            i,
+            i % 5,
+            i * 10
+        ))
+        .unwrap();
+    }
+    db.flush().unwrap();
+}
+
+#[test]
+fn test_cache_state_survives_restart() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create_with_config(&path, config()).unwrap();
+        setup(&db);
+        rows(&db, "SELECT id FROM readings WHERE sensor = 's3'");
+        db.close().unwrap();
+    }
+    assert!(path.join("cache_state.bin").exists());
+
+    let db = Database::open_with_config(&path, config()).unwrap();
+    // Open already warmed the caches; a second pass finds the same state
+    let stats = db.warm_caches().unwrap();
+    assert_eq!(stats.rows, 200);
+    assert!(stats.columns > 0);
+    assert_eq!(
+        rows(&db, "SELECT value FROM readings WHERE id = 42"),
+        vec![vec![Value::Integer(420)]]
+    );
+
+    // Rows deleted since the save are skipped, not resurrected
+    db.execute("DELETE FROM readings WHERE id < 50").unwrap();
+    db.save_cache_state().unwrap();
+    let stats = db.warm_caches().unwrap();
+    assert_eq!(stats.rows, 150);
+    assert!(rows(&db, "SELECT * FROM readings WHERE id = 10").is_empty());
+}
+
+#[test]
+fn test_cache_state_optional_and_tolerant() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup(&db);
+        db.close().unwrap();
+    }
+    assert!(!path.join("cache_state.bin").exists());
+
+    // A corrupt state file warms nothing and doesn't block open
+    std::fs::write(path.join("cache_state.bin"), b"not a cache state").unwrap();
+    let db = Database::open_with_config(&path, config()).unwrap();
+    assert_eq!(db.warm_caches().unwrap(), Default::default());
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM readings"),
+        vec![vec![Value::Integer(200)]]
+    );
+}
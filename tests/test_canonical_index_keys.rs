@@ -0,0 +1,151 @@
+//! Column index keys share one order-preserving encoding: negative
+//! numbers, mixed integer/float literals and index files written with the
+//! old key encoding
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn ints(db: &Database, sql: &str) -> Vec<i64> {
+    let rows = match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    };
+    let mut ids: Vec<i64> = rows
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref v => panic!("unexpected {:?}", v),
+        })
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Format version in the superblock of a column index file
+/// (`[len: u32][magic: u32][version: u32]...`)
+fn index_version(path: &Path) -> u32 {
+    let bytes = std::fs::read(path).unwrap();
+    u32::from_le_bytes(bytes[8..12].try_into().unwrap())
+}
+
+fn set_index_version(path: &Path, version: u32) {
+    let mut bytes = std::fs::read(path).unwrap();
+    bytes[8..12].copy_from_slice(&version.to_le_bytes());
+    std::fs::write(path, bytes).unwrap();
+}
+
+/// Readings -20..20 with level = id and ratio = id / 4, half flushed
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, level INT, ratio FLOAT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_level ON readings(level)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_ratio ON readings(ratio)")
+        .unwrap();
+    for id in -20..20 {
+        if id == 0 {
+            db.flush().unwrap();
+        }
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, {}, {})",
+            id,
+            id,
+            id as f64 / 4.0
+        ))
+        .unwrap();
+    }
+}
+
+fn check(db: &Database) {
+    assert_eq!(
+        ints(db, "SELECT id FROM readings WHERE level < -17"),
+        vec![-20, -19, -18]
+    );
+    assert_eq!(
+        ints(db, "SELECT id FROM readings WHERE level BETWEEN -2 AND 1"),
+        vec![-2, -1, 0, 1]
+    );
+    assert_eq!(
+        ints(db, "SELECT id FROM readings WHERE level > 16.5"),
+        vec![17, 18, 19]
+    );
+    assert_eq!(
+        ints(
+            db,
+            "SELECT id FROM readings WHERE ratio >= -1 AND ratio < 0"
+        ),
+        vec![-4, -3, -2, -1]
+    );
+    assert_eq!(ints(db, "SELECT id FROM readings WHERE ratio = 2"), vec![8]);
+    assert_eq!(
+        ints(db, "SELECT id FROM readings ORDER BY level LIMIT 2"),
+        vec![-20, -19]
+    );
+}
+
+#[test]
+fn test_negative_and_mixed_numeric_ranges() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    check(&db);
+
+    // The indexes themselves, whatever plan the optimizer picks
+    let between = |column: &str, lo: Value, hi: Value| {
+        db.query_by_column_between("readings", column, &lo, false, &hi, false)
+            .unwrap()
+            .len()
+    };
+    assert_eq!(between("level", Value::Integer(-3), Value::Integer(3)), 5);
+    assert_eq!(
+        between("level", Value::Float(16.5), Value::Float(f64::INFINITY)),
+        3
+    );
+    assert_eq!(between("ratio", Value::Integer(-1), Value::Float(0.5)), 5);
+    assert_eq!(
+        db.query_by_column("readings", "ratio", &Value::Integer(-2))
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_legacy_index_files_are_rebuilt() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup(&db);
+        // Natural key indexes are created without SQL metadata
+        db.execute("CREATE TABLE devices (serial TEXT PRIMARY KEY, seq INT)")
+            .unwrap();
+        db.execute("INSERT INTO devices VALUES ('SN-1', 1), ('SN-2', 2)")
+            .unwrap();
+        db.close().unwrap();
+    }
+
+    let indexes = path.join("indexes");
+    let files = [
+        indexes.join("column_idx_level.idx"),
+        indexes.join("column_devices.serial.idx"),
+    ];
+    for file in &files {
+        assert_eq!(index_version(file), 5);
+        set_index_version(file, 3);
+    }
+
+    let db = Database::open(&path).unwrap();
+    db.wait_for_indexes_ready();
+    check(&db);
+    assert_eq!(
+        ints(&db, "SELECT seq FROM devices WHERE serial = 'SN-2'"),
+        vec![2]
+    );
+    db.close().unwrap();
+    for file in &files {
+        assert_eq!(index_version(file), 5, "{:?}", file);
+    }
+}
@@ -0,0 +1,90 @@
+//! Checkpoint API and the WAL size bound
+
+use motedb::config::{AutoCheckpointConfig, WALConfig};
+use motedb::types::Value;
+use motedb::{DBConfig, Database, QueryResult};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn count(db: &Database, table: &str) -> i64 {
+    match db
+        .execute(&format!("SELECT COUNT(*) FROM {}", table))
+        .unwrap()
+        .materialize()
+        .unwrap()
+    {
+        QueryResult::Select { rows, .. } => match rows[0][0] {
+            Value::Integer(n) => n,
+            ref other => panic!("Expected integer count, got {:?}", other),
+        },
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn insert(db: &Database, ids: std::ops::Range<i64>) {
+    for i in ids {
+        db.execute(&format!(
+            "INSERT INTO t VALUES ({}, 'payload-{:0>64}')",
+            i, i
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_checkpoint_truncates_wal_and_keeps_data() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, s TEXT)")
+            .unwrap();
+        let empty = db.wal_size();
+        insert(&db, 0..500);
+        assert!(db.wal_size() > empty);
+
+        db.checkpoint().unwrap();
+        assert_eq!(db.wal_size(), empty);
+        assert_eq!(count(&db, "t"), 500);
+
+        insert(&db, 500..600);
+        db.close().unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    assert_eq!(count(&db, "t"), 600);
+}
+
+#[test]
+fn test_auto_checkpoint_enforces_max_wal_size() {
+    let dir = TempDir::new().unwrap();
+    let max_wal_size = 64 * 1024;
+    let config = DBConfig {
+        wal_config: WALConfig {
+            max_wal_size,
+            ..Default::default()
+        },
+        // Neither the soft threshold nor the interval would fire on their own
+        auto_checkpoint: Some(AutoCheckpointConfig {
+            max_wal_size_bytes: u64::MAX,
+            min_interval_secs: 3600,
+        }),
+        ..Default::default()
+    };
+    let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, s TEXT)")
+        .unwrap();
+    insert(&db, 0..2000);
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while db.wal_size() >= max_wal_size {
+        assert!(
+            Instant::now() < deadline,
+            "WAL stayed at {} bytes, bound is {}",
+            db.wal_size(),
+            max_wal_size
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(count(&db, "t"), 2000);
+}
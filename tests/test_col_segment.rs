@@ -186,3 +186,76 @@ fn s5_compaction_dedups_same_key() {
     assert_eq!(rows.len(), 1, "compaction dedups");
     assert_eq!(rows[0].1[0], Value::Integer(300), "keeps newest version");
 }
+
+#[test]
+fn s6_zone_map_pruned_segment_shadows_older_versions() {
+    use motedb::sql::ast::BinaryOperator;
+    let dir = TempDir::new().unwrap();
+    let store = ColSegmentStore::create(dir.path(), "t", col_types()).unwrap();
+    let rows: Vec<(u64, u64, Vec<Value>)> = (0..10_000u64)
+        .map(|k| {
+            (
+                k,
+                100,
+                vec![Value::Integer(k as i64), Value::Text("a".into())],
+            )
+        })
+        .collect();
+    store.append_rows(&rows).unwrap();
+    store.flush_buffer().unwrap();
+
+    // Newer version of key 5 in a segment whose range excludes `< 10`
+    store
+        .append_rows(&[(
+            5,
+            200,
+            vec![Value::Integer(90_000), Value::Text("b".into())],
+        )])
+        .unwrap();
+    store.flush_buffer().unwrap();
+    assert_eq!(store.segment_count(), 2);
+
+    let lt = BinaryOperator::Lt;
+    assert_eq!(store.count_filtered(0, &lt, &Value::Integer(10)), 9);
+    assert_eq!(store.zone_skipped_rows(), 1 + 4_096 + 1_808);
+    let agg = store.aggregate_filtered(Some(0), 0, &lt, &Value::Integer(10));
+    assert_eq!(
+        (agg.count, agg.sum.sum().unwrap()),
+        (9, Value::Integer(45 - 5))
+    );
+    let pred = |v: Option<&Value>| v.is_some_and(|v| v < &Value::Integer(10));
+    let scanned = store.scan_projected_compare(0, &lt, &Value::Integer(10), &[0], &pred);
+    assert_eq!(scanned.len(), 9);
+    assert!(scanned.iter().all(|(key, _)| *key != 5));
+}
+
+#[test]
+fn s7_partitioned_appends_from_concurrent_writers() {
+    let dir = TempDir::new().unwrap();
+    let store = ColSegmentStore::create_partitioned(dir.path(), "t", col_types(), 4).unwrap();
+    std::thread::scope(|s| {
+        for t in 0..4u64 {
+            let store = &store;
+            s.spawn(move || {
+                for i in 0..500u64 {
+                    let key = t * 1_000 + i;
+                    let row = [Value::Integer(key as i64), Value::Text("a".into())];
+                    store.append_row_ref(key, 100, &row).unwrap();
+                }
+            });
+        }
+    });
+
+    // Staged rows are visible before any flush, and later versions win
+    assert_eq!(store.get(3_007).unwrap()[0], Value::Integer(3_007));
+    store
+        .append_row_ref(7, 200, &[Value::Integer(-7), Value::Text("b".into())])
+        .unwrap();
+    assert_eq!(store.get(7).unwrap()[0], Value::Integer(-7));
+    store.append_tombstone(8, 200).unwrap();
+    assert!(store.get(8).is_none());
+
+    store.flush_buffer().unwrap();
+    assert_eq!(store.count_live_rows(), 1_999);
+    assert_eq!(store.get(7).unwrap()[0], Value::Integer(-7));
+}
@@ -0,0 +1,167 @@
+//! Vector collections: upsert/query/delete of `(id, vector, metadata)`
+//! records with metadata filters, without SQL.
+
+use motedb::distance::DistanceKind;
+use motedb::types::{ColumnType, Value};
+use motedb::{CollectionConfig, CollectionRecord, Database, MetadataFilter, StorageError};
+use tempfile::TempDir;
+
+fn filter(field: &str, value: Value) -> MetadataFilter {
+    MetadataFilter::from([(field.to_string(), value)])
+}
+
+fn ids(hits: &[motedb::CollectionHit]) -> Vec<&str> {
+    hits.iter().map(|h| h.id.as_str()).collect()
+}
+
+fn config() -> CollectionConfig {
+    CollectionConfig::new(2)
+        .metadata_field("source", ColumnType::Text)
+        .metadata_field("page", ColumnType::Integer)
+}
+
+/// 100 records on the x axis; only every tenth comes from "pdf"
+fn records() -> Vec<CollectionRecord> {
+    (0..100)
+        .map(|i| {
+            let source = if i % 10 == 0 { "pdf" } else { "web" };
+            CollectionRecord::new(format!("doc-{}", i), vec![i as f32, 0.0])
+                .with("source", Value::text_from(source))
+                .with("page", Value::Integer(i))
+        })
+        .collect()
+}
+
+#[test]
+fn test_collection_upsert_query_delete() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    let docs = db.create_collection("docs", config()).unwrap();
+    assert_eq!(docs.upsert(records()).unwrap(), 100);
+
+    let hits = docs.query(&[3.2, 0.0], 3, &MetadataFilter::new()).unwrap();
+    assert_eq!(ids(&hits), vec!["doc-3", "doc-4", "doc-2"]);
+    assert!((hits[0].distance - 0.2).abs() < 0.05);
+    assert_eq!(hits[0].metadata["page"], Value::Integer(3));
+
+    // The nearest "pdf" records are far down the unfiltered ranking
+    let pdf = filter("source", Value::text_from("pdf"));
+    let hits = docs.query(&[3.2, 0.0], 3, &pdf).unwrap();
+    assert_eq!(ids(&hits), vec!["doc-0", "doc-10", "doc-20"]);
+    let hits = docs.query(&[3.2, 0.0], 50, &pdf).unwrap();
+    assert_eq!(hits.len(), 10);
+    let mut both = pdf.clone();
+    both.insert("page".into(), Value::Integer(40));
+    assert_eq!(
+        ids(&docs.query(&[3.2, 0.0], 5, &both).unwrap()),
+        vec!["doc-40"]
+    );
+
+    // Replacing a record replaces its vector and metadata
+    docs.upsert(vec![
+        CollectionRecord::new("doc-3", vec![500.0, 0.0]).with("source", Value::text_from("pdf")),
+        CollectionRecord::new("new", vec![3.0, 0.1]),
+    ])
+    .unwrap();
+    let doc3 = docs.get("doc-3").unwrap().unwrap();
+    assert_eq!(doc3.vector, vec![500.0, 0.0]);
+    assert_eq!(doc3.metadata.get("page"), None);
+    let hits = docs.query(&[3.2, 0.0], 2, &MetadataFilter::new()).unwrap();
+    assert_eq!(ids(&hits), vec!["new", "doc-4"]);
+    assert_eq!(
+        ids(&docs.query(&[499.0, 0.0], 1, &pdf).unwrap()),
+        vec!["doc-3"]
+    );
+
+    // Deletes by id and by filter
+    assert_eq!(docs.delete(&["doc-4", "doc-4", "missing"]).unwrap(), 1);
+    assert!(docs.get("doc-4").unwrap().is_none());
+    assert_eq!(docs.delete_where(&pdf).unwrap(), 11);
+    assert!(docs.query(&[3.2, 0.0], 5, &pdf).unwrap().is_empty());
+    let hits = docs.query(&[3.2, 0.0], 3, &MetadataFilter::new()).unwrap();
+    assert_eq!(ids(&hits), vec!["new", "doc-2", "doc-5"]);
+
+    // The collection is an ordinary table
+    let count = db
+        .execute("SELECT COUNT(*) FROM docs WHERE source = 'web'")
+        .unwrap()
+        .materialize()
+        .unwrap();
+    match count {
+        motedb::QueryResult::Select { rows, .. } => assert_eq!(rows[0][0], Value::Integer(88)),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_collection_reopen_and_drop() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = Database::create(dir.path()).unwrap();
+        let docs = db
+            .create_collection("docs", config().metric(DistanceKind::Cosine))
+            .unwrap();
+        // Spread over directions: on a single ray every cosine distance ties
+        let fanned = records().into_iter().map(|mut r| {
+            r.vector = vec![1.0, r.vector[0] / 100.0];
+            r
+        });
+        docs.upsert(fanned.collect()).unwrap();
+        db.flush().unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    let docs = db.collection("docs").unwrap();
+    assert_eq!(docs.dimension(), 2);
+    docs.upsert(vec![CollectionRecord::new("up", vec![0.0, 1.0])])
+        .unwrap();
+    let hits = docs.query(&[0.0, 5.0], 1, &MetadataFilter::new()).unwrap();
+    assert_eq!(ids(&hits), vec!["up"]);
+    assert!(hits[0].distance.abs() < 1e-3);
+    let hits = docs
+        .query(&[1.0, 0.0], 2, &filter("page", Value::Integer(20)))
+        .unwrap();
+    assert_eq!(ids(&hits), vec!["doc-20"]);
+
+    db.drop_collection("docs").unwrap();
+    assert!(db.collection("docs").is_err());
+    let docs = db.create_collection("docs", config()).unwrap();
+    assert!(docs.get("doc-20").unwrap().is_none());
+}
+
+#[test]
+fn test_collection_rejects_bad_input() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE plain (id TEXT PRIMARY KEY, body TEXT)")
+        .unwrap();
+    assert!(matches!(
+        db.collection("plain"),
+        Err(StorageError::InvalidArgument(_))
+    ));
+    assert!(db.drop_collection("plain").is_err());
+    assert!(db
+        .create_collection(
+            "bad",
+            CollectionConfig::new(2).metadata_field("embedding", ColumnType::Text)
+        )
+        .is_err());
+
+    let docs = db.create_collection("docs", config()).unwrap();
+    let err = docs
+        .upsert(vec![
+            CollectionRecord::new("a", vec![1.0, 0.0]),
+            CollectionRecord::new("b", vec![1.0, 0.0]).with("author", Value::text_from("x")),
+        ])
+        .unwrap_err();
+    assert!(matches!(err, StorageError::InvalidArgument(_)), "{:?}", err);
+    assert!(docs
+        .upsert(vec![CollectionRecord::new("a", vec![1.0])])
+        .is_err());
+    assert!(docs.get("a").unwrap().is_none());
+    assert!(docs
+        .query(&[1.0, 0.0], 1, &filter("author", Value::Null))
+        .is_err());
+    assert!(docs.query(&[1.0], 1, &MetadataFilter::new()).is_err());
+    assert!(docs.delete_where(&MetadataFilter::new()).is_err());
+}
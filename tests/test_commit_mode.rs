@@ -0,0 +1,94 @@
+//! Per-statement / per-batch durability overrides (ASYNC_COMMIT, SYNC_COMMIT)
+
+use motedb::types::Value;
+use motedb::{CommitMode, DBConfig, Database, DurabilityLevel, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn config(durability_level: DurabilityLevel) -> DBConfig {
+    let mut config = DBConfig::default();
+    config.wal_config.durability_level = durability_level;
+    config
+}
+
+#[test]
+fn test_commit_hints_across_durability_levels() {
+    for level in [
+        DurabilityLevel::synchronous(),
+        DurabilityLevel::group_commit(),
+        DurabilityLevel::periodic(50),
+    ] {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.mote");
+        {
+            let db = Database::create_with_config(&path, config(level)).unwrap();
+            db.execute("CREATE TABLE readings (id INT PRIMARY KEY, v FLOAT)")
+                .unwrap();
+            db.execute("CREATE TABLE settings (k TEXT PRIMARY KEY, v TEXT)")
+                .unwrap();
+
+            for i in 0..50 {
+                db.execute(&format!(
+                    "INSERT /*+ ASYNC_COMMIT */ INTO readings VALUES ({}, {}.5)",
+                    i, i
+                ))
+                .unwrap();
+            }
+            db.execute("/*+ SYNC_COMMIT */ INSERT INTO settings VALUES ('rate', '100hz')")
+                .unwrap();
+            db.execute("UPDATE /*+ ASYNC_COMMIT */ readings SET v = 0.0 WHERE id = 3")
+                .unwrap();
+            db.execute_prepared(
+                "DELETE /*+ ASYNC_COMMIT */ FROM readings WHERE id = ?",
+                vec![Value::Integer(4)],
+            )
+            .unwrap();
+
+            let mut batch = db.write_batch();
+            batch
+                .commit_mode(CommitMode::Async)
+                .insert("readings", vec![Value::Integer(100), Value::Float(1.0)]);
+            batch.commit().unwrap();
+
+            assert_eq!(
+                rows(&db, "SELECT COUNT(*) FROM readings"),
+                vec![vec![Value::Integer(50)]]
+            );
+            db.close().unwrap();
+        }
+
+        let db = Database::open_with_config(&path, config(level)).unwrap();
+        assert_eq!(
+            rows(&db, "SELECT COUNT(*) FROM readings"),
+            vec![vec![Value::Integer(50)]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT v FROM readings WHERE id = 3"),
+            vec![vec![Value::Float(0.0)]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT v FROM settings WHERE k = 'rate'"),
+            vec![vec![Value::Text("100hz".into())]]
+        );
+    }
+}
+
+#[test]
+fn test_commit_hint_inside_literal_is_data() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE notes (id INT PRIMARY KEY, body TEXT)")
+        .unwrap();
+    db.execute("INSERT INTO notes VALUES (1, 'keep /*+ ASYNC_COMMIT */ as text')")
+        .unwrap();
+    assert_eq!(
+        rows(&db, "SELECT body FROM notes WHERE id = 1"),
+        vec![vec![Value::Text("keep /*+ ASYNC_COMMIT */ as text".into())]]
+    );
+}
@@ -0,0 +1,210 @@
+//! Continuous aggregates (CREATE MATERIALIZED VIEW ... SAMPLE BY)
+
+use motedb::types::{Timestamp, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn as_f64(v: &Value) -> f64 {
+    match v {
+        Value::Float(f) => *f,
+        Value::Integer(i) => *i as f64,
+        other => panic!("Expected number, got {:?}", other),
+    }
+}
+
+fn as_micros(v: &Value) -> i64 {
+    match v {
+        Value::Timestamp(ts) => ts.as_micros(),
+        Value::Integer(i) => *i,
+        other => panic!("Expected timestamp, got {:?}", other),
+    }
+}
+
+const MINUTE: i64 = 60_000_000;
+
+/// Insert one reading per second for `sensor` over `[from_s, to_s)`, value = second
+fn insert_readings(db: &Database, sensor: &str, from_s: i64, to_s: i64) {
+    for s in from_s..to_s {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, '{}', {}.0)",
+            s * 1_000_000,
+            sensor,
+            s
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_materialized_view_maintained_on_insert() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE readings (ts TIMESTAMP, sensor TEXT, v FLOAT)")
+        .unwrap();
+    insert_readings(&db, "a", 0, 60);
+
+    db.execute(
+        "CREATE MATERIALIZED VIEW per_minute AS \
+         SELECT ts, sensor, MIN(v), MAX(v), AVG(v) AS mean, COUNT(*) FROM readings SAMPLE BY 1m",
+    )
+    .unwrap();
+    let query = "SELECT ts, sensor, min_v, max_v, mean, count FROM per_minute ORDER BY ts, sensor";
+    let r = rows(db.execute(query).unwrap());
+    assert_eq!(r.len(), 1);
+    assert_eq!(as_micros(&r[0][0]), 0);
+    assert_eq!(as_f64(&r[0][2]), 0.0);
+    assert_eq!(as_f64(&r[0][3]), 59.0);
+    assert_eq!(as_f64(&r[0][4]), 29.5);
+    assert_eq!(r[0][5], Value::Integer(60));
+
+    // New rows extend the open bucket and start new ones
+    insert_readings(&db, "a", 60, 90);
+    insert_readings(&db, "b", 30, 40);
+    let r = rows(db.execute(query).unwrap());
+    assert_eq!(r.len(), 3);
+    assert_eq!(as_micros(&r[1][0]), 0);
+    assert_eq!(r[1][1], Value::text("b".to_string()));
+    assert_eq!(as_f64(&r[1][4]), 34.5);
+    assert_eq!(as_micros(&r[2][0]), MINUTE);
+    assert_eq!(as_f64(&r[2][2]), 60.0);
+    assert_eq!(as_f64(&r[2][3]), 89.0);
+    assert_eq!(r[2][5], Value::Integer(30));
+
+    // Batch inserts through the API are folded in too
+    db.batch_insert(
+        "readings",
+        (0..10)
+            .map(|i| {
+                vec![
+                    Value::Timestamp(Timestamp::from_micros(MINUTE + i * 1_000_000)),
+                    Value::text("a".to_string()),
+                    Value::Float(1000.0),
+                ]
+            })
+            .collect(),
+    )
+    .unwrap();
+    let r = rows(db.execute(query).unwrap());
+    assert_eq!(as_f64(&r[2][3]), 1000.0);
+    assert_eq!(r[2][5], Value::Integer(40));
+
+    // The view is read-only
+    assert!(db.execute("DELETE FROM per_minute").is_err());
+    assert!(db
+        .execute("INSERT INTO per_minute VALUES (0, 'c', 1.0, 1.0, 1.0, 1)")
+        .is_err());
+    assert!(db.execute("DROP TABLE readings").is_err());
+}
+
+#[test]
+fn test_materialized_view_refresh_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        db.execute("CREATE TABLE readings (ts TIMESTAMP, sensor TEXT, v FLOAT)")
+            .unwrap();
+        insert_readings(&db, "a", 0, 120);
+        db.execute(
+            "CREATE MATERIALIZED VIEW totals AS \
+             SELECT TIME_BUCKET('1m', ts) AS minute, SUM(v) AS total FROM readings GROUP BY minute",
+        )
+        .unwrap();
+
+        // DELETE invalidates the view; it is rebuilt before the next statement
+        db.execute("DELETE FROM readings WHERE v >= 60").unwrap();
+        let r = rows(db.execute("SELECT minute, total FROM totals").unwrap());
+        assert_eq!(r.len(), 1);
+        assert_eq!(as_f64(&r[0][1]), 1770.0);
+
+        insert_readings(&db, "a", 60, 62);
+        db.execute("REFRESH MATERIALIZED VIEW totals").unwrap();
+        db.flush().unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    let r = rows(
+        db.execute("SELECT minute, total FROM totals ORDER BY minute")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_f64(&r[1][1]), 121.0);
+
+    // Maintenance resumes after reopen without duplicating rows
+    insert_readings(&db, "a", 62, 63);
+    let r = rows(
+        db.execute("SELECT minute, total FROM totals ORDER BY minute")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_micros(&r[1][0]), MINUTE);
+    assert_eq!(as_f64(&r[1][1]), 183.0);
+
+    db.execute("DROP MATERIALIZED VIEW totals").unwrap();
+    assert!(db.execute("SELECT * FROM totals").is_err());
+    db.execute("DROP MATERIALIZED VIEW IF EXISTS totals")
+        .unwrap();
+    db.execute("DROP TABLE readings").unwrap();
+}
+
+#[test]
+fn test_materialized_view_over_timeseries() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE accel (ts TIMESTAMP, x FLOAT) TIMESERIES(ts)")
+        .unwrap();
+    db.execute(
+        "CREATE MATERIALIZED VIEW accel_1s AS SELECT ts, AVG(x), COUNT(x) FROM accel SAMPLE BY 1s",
+    )
+    .unwrap();
+    for i in 0..2000i64 {
+        db.execute(&format!(
+            "INSERT INTO accel VALUES ({}, {}.0)",
+            1_000_000 + i * 1_000,
+            i
+        ))
+        .unwrap();
+    }
+    let r = rows(
+        db.execute("SELECT ts, avg_x, count_x FROM accel_1s ORDER BY ts")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_micros(&r[0][0]), 1_000_000);
+    assert_eq!(as_f64(&r[0][1]), 499.5);
+    assert_eq!(r[1][2], Value::Integer(1000));
+}
+
+#[test]
+fn test_materialized_view_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE readings (ts TIMESTAMP, sensor TEXT, v FLOAT)")
+        .unwrap();
+
+    for sql in [
+        // not bucketed
+        "CREATE MATERIALIZED VIEW v1 AS SELECT sensor, AVG(v) FROM readings GROUP BY sensor",
+        // WHERE is not maintained incrementally
+        "CREATE MATERIALIZED VIEW v2 AS SELECT ts, AVG(v) FROM readings WHERE v > 0 SAMPLE BY 1m",
+        // expressions other than plain aggregates
+        "CREATE MATERIALIZED VIEW v3 AS SELECT ts, AVG(v * 2) FROM readings SAMPLE BY 1m",
+        // AVG of a TEXT column
+        "CREATE MATERIALIZED VIEW v4 AS SELECT ts, AVG(sensor) FROM readings SAMPLE BY 1m",
+        // group key not selected
+        "CREATE MATERIALIZED VIEW v5 AS SELECT TIME_BUCKET('1m', ts) AS b, AVG(v) \
+         FROM readings GROUP BY b, sensor",
+    ] {
+        assert!(db.execute(sql).is_err(), "{}", sql);
+    }
+    assert!(db.execute("DROP MATERIALIZED VIEW readings").is_err());
+    assert!(db.execute("REFRESH MATERIALIZED VIEW readings").is_err());
+}
@@ -0,0 +1,220 @@
+//! EXISTS / NOT EXISTS and correlated subqueries: run per outer row in
+//! SELECT, WHERE, UPDATE and DELETE
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn names(db: &Database, sql: &str) -> Vec<String> {
+    rows(db, sql)
+        .into_iter()
+        .map(|r| match &r[0] {
+            Value::Text(s) => s.to_string(),
+            other => panic!("expected text, got {:?}", other),
+        })
+        .collect()
+}
+
+fn affected(db: &Database, sql: &str) -> usize {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Modification { affected_rows } => affected_rows,
+        _ => panic!("Expected Modification result"),
+    }
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE users (id INT PRIMARY KEY, name TEXT, dept INT)")
+        .unwrap();
+    db.execute("CREATE TABLE orders (id INT PRIMARY KEY, user_id INT, total INT)")
+        .unwrap();
+    db.execute("INSERT INTO users VALUES (1, 'ann', 1), (2, 'bob', 1), (3, 'cy', 2), (4, 'di', 2)")
+        .unwrap();
+    db.execute("INSERT INTO orders VALUES (10, 1, 50), (11, 1, 70), (12, 3, 20), (13, NULL, 5)")
+        .unwrap();
+    db
+}
+
+#[test]
+fn test_exists_and_not_exists() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    assert_eq!(
+        names(
+            &db,
+            "SELECT name FROM users u WHERE EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = u.id) ORDER BY name"
+        ),
+        vec!["ann", "cy"]
+    );
+    assert_eq!(
+        names(
+            &db,
+            "SELECT name FROM users u WHERE NOT EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = u.id) ORDER BY name"
+        ),
+        vec!["bob", "di"]
+    );
+    // Qualified by table name, and a bare outer column the subquery lacks
+    assert_eq!(
+        names(
+            &db,
+            "SELECT name FROM users WHERE EXISTS \
+             (SELECT 1 FROM orders WHERE user_id = users.id AND total > dept * 60)"
+        ),
+        vec!["ann"]
+    );
+    // Uncorrelated: evaluated once
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT COUNT(*) FROM users WHERE EXISTS (SELECT 1 FROM orders)"
+        ),
+        vec![vec![Value::Integer(4)]]
+    );
+    assert!(rows(
+        &db,
+        "SELECT id FROM users WHERE EXISTS (SELECT 1 FROM orders WHERE total > 1000)"
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_correlated_scalar_and_in() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT name, (SELECT SUM(total) FROM orders o WHERE o.user_id = u.id) AS spent \
+             FROM users u ORDER BY name"
+        ),
+        vec![
+            vec![Value::text("ann".to_string()), Value::Integer(120)],
+            vec![Value::text("bob".to_string()), Value::Null],
+            vec![Value::text("cy".to_string()), Value::Integer(20)],
+            vec![Value::text("di".to_string()), Value::Null],
+        ]
+    );
+    assert_eq!(
+        names(
+            &db,
+            "SELECT name FROM users WHERE \
+             (SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id) >= 2"
+        ),
+        vec!["ann"]
+    );
+    assert_eq!(
+        names(
+            &db,
+            "SELECT name FROM users u WHERE id IN \
+             (SELECT user_id FROM orders o WHERE o.total > u.dept * 30)"
+        ),
+        vec!["ann"]
+    );
+    // Same table inside and out, told apart by alias
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT o.id FROM orders o WHERE o.total > \
+             (SELECT AVG(total) FROM orders i WHERE i.user_id = o.user_id)"
+        ),
+        vec![vec![Value::Integer(11)]]
+    );
+}
+
+#[test]
+fn test_correlated_aggregate_and_group_by() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT COUNT(*) FROM users u WHERE EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = u.id)"
+        ),
+        vec![vec![Value::Integer(2)]]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT dept, COUNT(*) FROM users u WHERE NOT EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = u.id) GROUP BY dept ORDER BY dept"
+        ),
+        vec![
+            vec![Value::Integer(1), Value::Integer(1)],
+            vec![Value::Integer(2), Value::Integer(1)],
+        ]
+    );
+}
+
+#[test]
+fn test_correlated_update_and_delete() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    assert_eq!(
+        affected(
+            &db,
+            "UPDATE users SET dept = (SELECT COUNT(*) FROM orders o WHERE o.user_id = users.id)"
+        ),
+        4
+    );
+    assert_eq!(
+        rows(&db, "SELECT id, dept FROM users ORDER BY id"),
+        vec![
+            vec![Value::Integer(1), Value::Integer(2)],
+            vec![Value::Integer(2), Value::Integer(0)],
+            vec![Value::Integer(3), Value::Integer(1)],
+            vec![Value::Integer(4), Value::Integer(0)],
+        ]
+    );
+    assert_eq!(
+        affected(
+            &db,
+            "UPDATE users SET name = 'big' WHERE EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = users.id AND o.total > 60)"
+        ),
+        1
+    );
+    assert_eq!(
+        affected(
+            &db,
+            "DELETE FROM users WHERE NOT EXISTS \
+             (SELECT 1 FROM orders o WHERE o.user_id = users.id)"
+        ),
+        2
+    );
+    assert_eq!(
+        names(&db, "SELECT name FROM users ORDER BY id"),
+        vec!["big", "cy"]
+    );
+}
+
+#[test]
+fn test_alias_qualified_equality_filter() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT id FROM orders o WHERE o.user_id = 1 ORDER BY id"
+        ),
+        vec![vec![Value::Integer(10)], vec![Value::Integer(11)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT id FROM orders WHERE orders.total = 20"),
+        vec![vec![Value::Integer(12)]]
+    );
+}
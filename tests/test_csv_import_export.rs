@@ -0,0 +1,273 @@
+//! CSV import/export: db.import_csv / db.export_csv and COPY FROM / TO
+
+use motedb::types::Value;
+use motedb::{CsvOptions, DBConfig, Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn affected(db: &Database, sql: &str) -> usize {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Modification { affected_rows } => affected_rows,
+        _ => panic!("Expected Modification result"),
+    }
+}
+
+/// A database whose SQL COPY is confined to `dir`
+fn copy_db(dir: &TempDir) -> Database {
+    let config = DBConfig {
+        copy_dir: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    Database::create_with_config(dir.path().join("test.mote"), config).unwrap()
+}
+
+fn readings_table(db: &Database, name: &str) {
+    db.execute(&format!(
+        "CREATE TABLE {} (id INT PRIMARY KEY, label TEXT, temp FLOAT, ok BOOLEAN, \
+         at TIMESTAMP, pos VECTOR(2))",
+        name
+    ))
+    .unwrap();
+}
+
+#[test]
+fn test_export_then_import_round_trips() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    readings_table(&db, "src");
+    db.execute(
+        "INSERT INTO src VALUES \
+         (1, 'plain', 20.5, TRUE, '2024-01-31T08:00:00Z', [1.0, 2.0]), \
+         (2, 'a, \"quoted\"\nvalue', 3.25, FALSE, '2024-02-01T00:00:00.5Z', [0.5, 1.5]), \
+         (3, '', NULL, NULL, NULL, NULL), \
+         (4, NULL, 0.0, TRUE, '1999-12-31T23:59:59Z', [3.0, 4.0])",
+    )
+    .unwrap();
+    let path = dir.path().join("src.csv");
+    assert_eq!(
+        db.export_csv("src", &path, &CsvOptions::default()).unwrap(),
+        4
+    );
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        text.starts_with("id,label,temp,ok,at,pos\n1,plain,20.5,true,2024-01-31T08:00:00Z,"),
+        "{}",
+        text
+    );
+
+    readings_table(&db, "dst");
+    db.execute("CREATE INDEX dst_label ON dst (label)").unwrap();
+    assert_eq!(
+        db.import_csv("dst", &path, &CsvOptions::default()).unwrap(),
+        4
+    );
+    // Vectors compare by pointer, so compare the rendered rows
+    assert_eq!(
+        format!("{:?}", rows(&db, "SELECT * FROM dst ORDER BY id")),
+        format!("{:?}", rows(&db, "SELECT * FROM src ORDER BY id"))
+    );
+    // Empty text stays text, NULL stays NULL
+    assert_eq!(
+        rows(&db, "SELECT id FROM dst WHERE label = ''"),
+        vec![vec![Value::Integer(3)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT id FROM dst WHERE label IS NULL"),
+        vec![vec![Value::Integer(4)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT id FROM dst WHERE label = 'plain'"),
+        vec![vec![Value::Integer(1)]]
+    );
+}
+
+#[test]
+fn test_copy_with_options() {
+    let dir = TempDir::new().unwrap();
+    let db = copy_db(&dir);
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, reading FLOAT, at TIMESTAMP)")
+        .unwrap();
+    db.execute("ALTER TABLE readings ADD COLUMN sensor TEXT DEFAULT 'unknown'")
+        .unwrap();
+    let input = dir.path().join("in.tsv");
+    std::fs::write(
+        &input,
+        "id\treading\tat\n1\t1.5\t1700000000000000\n\n2\tNA\t2024-01-31 08:00:00\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        affected(
+            &db,
+            &format!(
+                "COPY readings FROM '{}' WITH (FORMAT csv, DELIMITER '\t', NULL 'NA')",
+                input.display()
+            )
+        ),
+        2
+    );
+    assert_eq!(
+        rows(&db, "SELECT id, sensor, reading FROM readings ORDER BY id"),
+        vec![
+            vec![
+                Value::Integer(1),
+                Value::text("unknown".to_string()),
+                Value::Float(1.5)
+            ],
+            vec![
+                Value::Integer(2),
+                Value::text("unknown".to_string()),
+                Value::Null
+            ],
+        ]
+    );
+
+    let output = dir.path().join("out.csv");
+    assert_eq!(
+        affected(
+            &db,
+            &format!(
+                "COPY readings TO '{}' WITH (DELIMITER ';', HEADER false)",
+                output.display()
+            )
+        ),
+        2
+    );
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        "1;1.5;2023-11-14T22:13:20Z;unknown\n2;;2024-01-31T08:00:00Z;unknown\n"
+    );
+
+    // Without a header, fields follow the column order
+    db.execute("DELETE FROM readings").unwrap();
+    assert_eq!(
+        affected(
+            &db,
+            &format!(
+                "COPY readings FROM '{}' (DELIMITER ';', HEADER false)",
+                output.display()
+            )
+        ),
+        2
+    );
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM readings WHERE reading IS NULL"),
+        vec![vec![Value::Integer(1)]]
+    );
+}
+
+#[test]
+fn test_import_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = copy_db(&dir);
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, n INT)")
+        .unwrap();
+    let path = dir.path().join("bad.csv");
+
+    std::fs::write(&path, "id,n\n1,10\n2,ten\n3,30\n").unwrap();
+    let err = db
+        .import_csv("t", &path, &CsvOptions::default())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("CSV line 3: column 'n'"),
+        "{}",
+        err
+    );
+    // Rows before the bad line stay inserted
+    assert_eq!(rows(&db, "SELECT id FROM t"), vec![vec![Value::Integer(1)]]);
+
+    std::fs::write(&path, "id,missing\n5,1\n").unwrap();
+    let err = db
+        .import_csv("t", &path, &CsvOptions::default())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown column 'missing'"),
+        "{}",
+        err
+    );
+
+    std::fs::write(&path, "id,n\n6,1,2\n").unwrap();
+    let err = db
+        .import_csv("t", &path, &CsvOptions::default())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("expected 2 fields, got 3"),
+        "{}",
+        err
+    );
+
+    let sql = format!("COPY t FROM '{}'", path.display());
+    db.execute("BEGIN").unwrap();
+    let err = db.execute(&sql).err().expect("COPY FROM inside BEGIN");
+    assert!(err.to_string().contains("inside a transaction"), "{}", err);
+    db.execute("ROLLBACK").unwrap();
+
+    assert!(db
+        .execute("COPY t FROM 'x.csv' WITH (FORMAT json)")
+        .is_err());
+    assert!(db
+        .execute("COPY t FROM 'x.csv' WITH (DELIMITER ';;')")
+        .is_err());
+    assert!(db.execute("COPY t INTO 'x.csv'").is_err());
+}
+
+#[test]
+fn test_copy_confined_to_copy_dir() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY)").unwrap();
+    let path = dir.path().join("t.csv");
+    std::fs::write(&path, "id\n1\n").unwrap();
+
+    // Without copy_dir, COPY is refused in both directions
+    for sql in [
+        format!("COPY t FROM '{}'", path.display()),
+        format!("COPY t TO '{}'", path.display()),
+    ] {
+        let err = db.execute(&sql).err().expect("COPY without copy_dir");
+        assert!(err.to_string().contains("COPY is disabled"), "{}", err);
+    }
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "id\n1\n");
+    drop(db);
+
+    let dir = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let db = copy_db(&dir);
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY)").unwrap();
+    std::fs::write(dir.path().join("in.csv"), "id\n1\n2\n").unwrap();
+    std::fs::write(outside.path().join("secret.csv"), "id\n9\n").unwrap();
+
+    // Relative paths resolve inside copy_dir
+    assert_eq!(affected(&db, "COPY t FROM 'in.csv'"), 2);
+    assert_eq!(affected(&db, "COPY t TO 'out.csv'"), 2);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("out.csv")).unwrap(),
+        "id\n1\n2\n"
+    );
+
+    let secret = outside.path().join("secret.csv");
+    let escape = format!(
+        "../{}/secret.csv",
+        outside.path().file_name().unwrap().to_str().unwrap()
+    );
+    for target in [secret.display().to_string(), escape] {
+        for sql in [
+            format!("COPY t FROM '{}'", target),
+            format!("COPY t TO '{}'", target),
+        ] {
+            let err = db.execute(&sql).err().expect("COPY outside copy_dir");
+            assert!(err.to_string().contains("outside"), "{}: {}", sql, err);
+        }
+    }
+    assert_eq!(std::fs::read_to_string(&secret).unwrap(), "id\n9\n");
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM t"),
+        vec![vec![Value::Integer(2)]]
+    );
+}
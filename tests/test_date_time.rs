@@ -0,0 +1,300 @@
+//! DATE and TIME types: literals, comparisons, indexing and conversions
+
+use motedb::types::{Date, Time, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn col(db: &Database, sql: &str) -> Vec<String> {
+    rows(db.execute(sql).unwrap())
+        .into_iter()
+        .map(|r| match &r[0] {
+            Value::Date(d) => d.to_string(),
+            Value::Time(t) => t.to_string(),
+            other => format!("{:?}", other),
+        })
+        .collect()
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE shifts (id INT PRIMARY KEY, day DATE, starts TIME, ends TIME)")
+        .unwrap();
+    for (id, day, starts, ends) in [
+        (1, "2024-03-01", "06:00", "14:00"),
+        (2, "2024-03-01", "14:00", "22:00"),
+        (3, "2024-02-29", "22:00", "23:59:59.5"),
+        (4, "1999-12-31", "08:30:15", "17:00"),
+    ] {
+        db.execute(&format!(
+            "INSERT INTO shifts VALUES ({}, '{}', '{}', '{}')",
+            id, day, starts, ends
+        ))
+        .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_date_time_insert_and_order() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    assert_eq!(
+        col(&db, "SELECT day FROM shifts ORDER BY day, id"),
+        vec!["1999-12-31", "2024-02-29", "2024-03-01", "2024-03-01"]
+    );
+    assert_eq!(
+        col(&db, "SELECT ends FROM shifts ORDER BY ends DESC"),
+        vec!["23:59:59.5", "22:00:00", "17:00:00", "14:00:00"]
+    );
+    let r = rows(
+        db.execute("SELECT day, starts FROM shifts WHERE id = 4")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Date(Date::from_ymd(1999, 12, 31).unwrap()));
+    assert_eq!(
+        r[0][1],
+        Value::Time(Time::from_hms_micro(8, 30, 15, 0).unwrap())
+    );
+
+    assert!(db
+        .execute("INSERT INTO shifts VALUES (5, '2023-02-29', '06:00', '07:00')")
+        .is_err());
+    assert!(db
+        .execute("INSERT INTO shifts VALUES (5, '2023-02-28', '25:00', '07:00')")
+        .is_err());
+}
+
+#[test]
+fn test_date_time_literals_and_filters() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    assert_eq!(
+        col(
+            &db,
+            "SELECT id FROM shifts WHERE day = DATE '2024-03-01' AND starts >= TIME '12:00' \
+             ORDER BY id"
+        ),
+        vec!["Integer(2)"]
+    );
+    assert_eq!(
+        col(
+            &db,
+            "SELECT id FROM shifts WHERE day BETWEEN '2000-01-01' AND '2024-02-29' ORDER BY id"
+        ),
+        vec!["Integer(3)"]
+    );
+    assert_eq!(
+        col(&db, "SELECT COUNT(*) FROM shifts WHERE ends > starts"),
+        vec!["Integer(4)"]
+    );
+    assert_eq!(col(&db, "SELECT MIN(day) FROM shifts"), vec!["1999-12-31"]);
+    assert!(db
+        .execute("SELECT id FROM shifts WHERE day = DATE '2024-02-30'")
+        .is_err());
+}
+
+#[test]
+fn test_date_time_index_range_queries() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE log (id INT PRIMARY KEY, day DATE, at TIME)")
+        .unwrap();
+    let base = Date::from_ymd(1969, 12, 20).unwrap();
+    for i in 0..30 {
+        db.execute(&format!(
+            "INSERT INTO log VALUES ({}, '{}', '{:02}:00')",
+            i,
+            base.add_days(i).unwrap(),
+            i % 24
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_day ON log (day)").unwrap();
+    db.execute("CREATE INDEX idx_at ON log (at)").unwrap();
+    db.execute("INSERT INTO log VALUES (100, '1969-12-25', '23:30')")
+        .unwrap();
+
+    let ids = |sql: &str| -> Vec<i64> {
+        rows(db.execute(sql).unwrap())
+            .into_iter()
+            .map(|r| match r[0] {
+                Value::Integer(i) => i,
+                ref v => panic!("unexpected {:?}", v),
+            })
+            .collect()
+    };
+    // Spans the epoch, so negative day numbers must sort before positive ones
+    assert_eq!(
+        ids("SELECT id FROM log WHERE day < '1969-12-23' ORDER BY id"),
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        ids("SELECT id FROM log WHERE day >= DATE '1970-01-16' ORDER BY id"),
+        vec![27, 28, 29]
+    );
+    assert_eq!(
+        ids("SELECT id FROM log WHERE day = '1969-12-25' ORDER BY id"),
+        vec![5, 100]
+    );
+    assert_eq!(
+        ids("SELECT id FROM log WHERE at >= '23:00' ORDER BY id"),
+        vec![23, 100]
+    );
+}
+
+#[test]
+fn test_date_time_conversions() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    // 2024-03-01 10:15:30 UTC
+    let ts = Date::from_ymd(2024, 3, 1)
+        .unwrap()
+        .to_timestamp(Some(Time::from_hms_micro(10, 15, 30, 0).unwrap()));
+    db.execute("CREATE TABLE ev (id INT PRIMARY KEY, ts TIMESTAMP)")
+        .unwrap();
+    db.execute(&format!(
+        "INSERT INTO ev VALUES (1, TIMESTAMP_MICROS({}))",
+        ts.as_micros()
+    ))
+    .unwrap();
+
+    assert_eq!(
+        col(&db, "SELECT DATE(ts) FROM ev WHERE id = 1"),
+        vec!["2024-03-01"]
+    );
+    assert_eq!(
+        col(&db, "SELECT TIME(ts) FROM ev WHERE id = 1"),
+        vec!["10:15:30"]
+    );
+    assert_eq!(
+        col(&db, "SELECT id FROM ev WHERE DATE(ts) = DATE '2024-03-01'"),
+        vec!["Integer(1)"]
+    );
+    assert_eq!(
+        col(
+            &db,
+            "SELECT id FROM shifts WHERE day = (SELECT DATE(ts) FROM ev WHERE id = 1) ORDER BY id"
+        ),
+        vec!["Integer(1)", "Integer(2)"]
+    );
+    let r = rows(
+        db.execute("SELECT TO_TIMESTAMP(day, starts) FROM shifts WHERE id = 1")
+            .unwrap(),
+    );
+    let six_am = Date::from_ymd(2024, 3, 1)
+        .unwrap()
+        .to_timestamp(Some(Time::from_hms_micro(6, 0, 0, 0).unwrap()));
+    assert_eq!(r[0][0], Value::Timestamp(six_am));
+
+    assert_eq!(
+        col(&db, "SELECT YEAR(day) FROM shifts WHERE id = 4"),
+        vec!["Integer(1999)"]
+    );
+    assert_eq!(
+        col(&db, "SELECT DAY_OF_WEEK(day) FROM shifts WHERE id = 1"),
+        vec!["Integer(5)"]
+    );
+    assert_eq!(
+        col(&db, "SELECT MINUTE(starts) FROM shifts WHERE id = 4"),
+        vec!["Integer(30)"]
+    );
+    assert_eq!(
+        col(&db, "SELECT DATE_ADD(day, 1) FROM shifts WHERE id = 3"),
+        vec!["2024-03-01"]
+    );
+    assert_eq!(
+        col(
+            &db,
+            "SELECT DATE_DIFF(DATE '2024-03-01', DATE '2023-03-01') FROM shifts WHERE id = 1"
+        ),
+        vec!["Integer(366)"]
+    );
+    assert_eq!(
+        col(&db, "SELECT CAST(day AS TEXT) FROM shifts WHERE id = 4"),
+        vec!["Text(ArcString(\"1999-12-31\"))"]
+    );
+    assert_eq!(
+        col(&db, "SELECT CAST('07:05' AS TIME) FROM shifts WHERE id = 4"),
+        vec!["07:05:00"]
+    );
+    assert!(db
+        .execute("SELECT id FROM shifts WHERE starts = TIME '7:05'")
+        .is_err());
+}
+
+#[test]
+fn test_date_time_survive_flush_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = setup(&dir);
+        db.flush().unwrap();
+        db.execute("INSERT INTO shifts VALUES (5, '2025-01-01', '00:00', NULL)")
+            .unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    assert_eq!(
+        col(&db, "SELECT day FROM shifts ORDER BY id"),
+        vec![
+            "2024-03-01",
+            "2024-03-01",
+            "2024-02-29",
+            "1999-12-31",
+            "2025-01-01"
+        ]
+    );
+    assert_eq!(
+        col(&db, "SELECT ends FROM shifts ORDER BY id"),
+        vec!["14:00:00", "22:00:00", "23:59:59.5", "17:00:00", "Null"]
+    );
+    assert_eq!(
+        col(&db, "SELECT id FROM shifts WHERE starts = '08:30:15'"),
+        vec!["Integer(4)"]
+    );
+}
+
+#[test]
+fn test_date_time_join_non_canonical_text() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    db.execute("CREATE TABLE notes (id INT PRIMARY KEY, day TEXT, at TEXT)")
+        .unwrap();
+    // '14:00' and ' 2024-03-01' equal TIME 14:00:00 / DATE 2024-03-01 but
+    // are not their canonical spelling
+    for (id, day, at) in [
+        (1, " 2024-03-01", "14:00"),
+        (2, "2024-03-01", "14:00:00.000"),
+        (3, "1999-12-31 ", "08:30:15"),
+    ] {
+        db.execute(&format!(
+            "INSERT INTO notes VALUES ({}, '{}', '{}')",
+            id, day, at
+        ))
+        .unwrap();
+    }
+
+    let count = |sql: &str| match &rows(db.execute(sql).unwrap())[0][0] {
+        Value::Integer(n) => *n,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(
+        count("SELECT COUNT(*) FROM shifts s JOIN notes n ON s.day = n.day"),
+        5
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM notes n JOIN shifts s ON n.at = s.starts"),
+        3
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM notes WHERE at IN (SELECT starts FROM shifts)"),
+        3
+    );
+}
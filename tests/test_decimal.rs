@@ -0,0 +1,220 @@
+//! DECIMAL / NUMERIC: exact storage, arithmetic, aggregates and indexing
+
+use motedb::types::{Decimal, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn dec(s: &str) -> Value {
+    Value::decimal(s.parse::<Decimal>().unwrap())
+}
+
+fn col(db: &Database, sql: &str) -> Vec<String> {
+    rows(db.execute(sql).unwrap())
+        .into_iter()
+        .map(|r| match &r[0] {
+            Value::Decimal(d) => d.to_string(),
+            other => format!("{:?}", other),
+        })
+        .collect()
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE acct (id INT PRIMARY KEY, grp INT, amount DECIMAL(12, 2))")
+        .unwrap();
+    db
+}
+
+#[test]
+fn test_decimal_insert_rounds_to_column_scale() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    db.execute("INSERT INTO acct VALUES (1, 1, 12.5)").unwrap();
+    db.execute("INSERT INTO acct VALUES (2, 1, '0.125')")
+        .unwrap();
+    db.execute("INSERT INTO acct VALUES (3, 1, -7)").unwrap();
+    db.execute("INSERT INTO acct VALUES (4, 1, NULL)").unwrap();
+
+    assert_eq!(
+        col(&db, "SELECT amount FROM acct ORDER BY id"),
+        vec!["12.50", "0.13", "-7.00", "Null"]
+    );
+    let r = rows(db.execute("SELECT amount FROM acct WHERE id = 1").unwrap());
+    assert_eq!(r[0][0], dec("12.50"));
+}
+
+#[test]
+fn test_decimal_precision_overflow_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, v NUMERIC(4, 2))")
+        .unwrap();
+    db.execute("INSERT INTO t VALUES (1, 99.99)").unwrap();
+    assert!(db.execute("INSERT INTO t VALUES (2, 100)").is_err());
+    assert!(db.execute("INSERT INTO t VALUES (3, 'abc')").is_err());
+    assert!(db.execute("UPDATE t SET v = 1000 WHERE id = 1").is_err());
+    assert_eq!(col(&db, "SELECT v FROM t"), vec!["99.99"]);
+
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, v DECIMAL(3, 5))")
+        .is_err());
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, v DECIMAL(39, 0))")
+        .is_err());
+}
+
+#[test]
+fn test_decimal_sum_and_avg_are_exact() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    for i in 0..10 {
+        db.execute(&format!("INSERT INTO acct VALUES ({}, {}, 0.1)", i, i % 2))
+            .unwrap();
+    }
+    assert_eq!(col(&db, "SELECT SUM(amount) FROM acct"), vec!["1.00"]);
+    assert_eq!(col(&db, "SELECT AVG(amount) FROM acct"), vec!["0.10000000"]);
+    assert_eq!(
+        col(&db, "SELECT SUM(amount) FROM acct WHERE grp = 1"),
+        vec!["0.50"]
+    );
+
+    let groups = rows(
+        db.execute("SELECT grp, SUM(amount) FROM acct GROUP BY grp ORDER BY grp")
+            .unwrap(),
+    );
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0][1], dec("0.5"));
+    assert_eq!(groups[1][1], dec("0.5"));
+}
+
+#[test]
+fn test_decimal_arithmetic_and_cast() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    db.execute("INSERT INTO acct VALUES (1, 1, 10.25)").unwrap();
+
+    assert_eq!(col(&db, "SELECT amount + 1 FROM acct"), vec!["11.25"]);
+    assert_eq!(
+        col(&db, "SELECT amount * amount FROM acct"),
+        vec!["105.0625"]
+    );
+    assert_eq!(col(&db, "SELECT -amount FROM acct"), vec!["-10.25"]);
+    assert_eq!(
+        col(&db, "SELECT amount + 0.5 FROM acct"),
+        vec!["Float(10.75)"]
+    );
+    assert_eq!(col(&db, "SELECT amount / 0 FROM acct"), vec!["Null"]);
+
+    assert_eq!(
+        col(&db, "SELECT CAST('3.14159' AS DECIMAL(5, 3)) FROM acct"),
+        vec!["3.142"]
+    );
+    assert_eq!(
+        col(&db, "SELECT CAST(amount AS INTEGER) FROM acct"),
+        vec!["Integer(10)"]
+    );
+}
+
+#[test]
+fn test_decimal_ordering_and_filters() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    for (id, v) in [(1, "2.5"), (2, "-1.25"), (3, "10"), (4, "2.49")] {
+        db.execute(&format!("INSERT INTO acct VALUES ({}, 0, '{}')", id, v))
+            .unwrap();
+    }
+    assert_eq!(
+        col(&db, "SELECT amount FROM acct ORDER BY amount"),
+        vec!["-1.25", "2.49", "2.50", "10.00"]
+    );
+    assert_eq!(
+        col(
+            &db,
+            "SELECT amount FROM acct WHERE amount > 2.49 ORDER BY amount"
+        ),
+        vec!["2.50", "10.00"]
+    );
+    assert_eq!(
+        col(&db, "SELECT amount FROM acct WHERE amount = 10"),
+        vec!["10.00"]
+    );
+    assert_eq!(col(&db, "SELECT MAX(amount) FROM acct"), vec!["10.00"]);
+}
+
+#[test]
+fn test_decimal_index_range_queries() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    for i in 0..50 {
+        db.execute(&format!(
+            "INSERT INTO acct VALUES ({}, 0, {}.{:02})",
+            i,
+            i - 25,
+            i
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_amount ON acct (amount)")
+        .unwrap();
+    db.execute("INSERT INTO acct VALUES (100, 0, -30.5)")
+        .unwrap();
+
+    let ids = |sql: &str| -> Vec<i64> {
+        rows(db.execute(sql).unwrap())
+            .into_iter()
+            .map(|r| match r[0] {
+                Value::Integer(i) => i,
+                ref v => panic!("unexpected {:?}", v),
+            })
+            .collect()
+    };
+    assert_eq!(
+        ids("SELECT id FROM acct WHERE amount < -23 ORDER BY id"),
+        vec![0, 1, 2, 100]
+    );
+    assert_eq!(
+        ids("SELECT id FROM acct WHERE amount >= 23.48 ORDER BY id"),
+        vec![48, 49]
+    );
+    assert_eq!(ids("SELECT id FROM acct WHERE amount = 0.25"), vec![25]);
+    assert_eq!(
+        ids("SELECT id FROM acct WHERE amount BETWEEN -1 AND 1.26 ORDER BY id"),
+        vec![25, 26]
+    );
+}
+
+#[test]
+fn test_decimal_survives_flush_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = setup(&dir);
+        db.execute("INSERT INTO acct VALUES (1, 0, 123456789.99)")
+            .unwrap();
+        db.execute("INSERT INTO acct VALUES (2, 0, '-0.01')")
+            .unwrap();
+        db.flush().unwrap();
+        db.execute("INSERT INTO acct VALUES (3, 0, 5)").unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    assert_eq!(
+        col(&db, "SELECT amount FROM acct ORDER BY id"),
+        vec!["123456789.99", "-0.01", "5.00"]
+    );
+    assert_eq!(
+        col(&db, "SELECT amount FROM acct WHERE id = 2"),
+        vec!["-0.01"]
+    );
+    assert_eq!(
+        col(&db, "SELECT SUM(amount) FROM acct"),
+        vec!["123456794.98"]
+    );
+}
@@ -0,0 +1,104 @@
+//! DELETE ... WHERE routed through the optimizer's index scan methods
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn count(db: &Database, sql: &str) -> i64 {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => match rows[0][0] {
+            Value::Integer(n) => n,
+            ref v => panic!("Expected integer count, got {:?}", v),
+        },
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(indexed: bool) -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, ts TIMESTAMP, sensor INT, val FLOAT)")
+        .unwrap();
+    if indexed {
+        db.execute("CREATE INDEX idx_ts ON readings (ts)").unwrap();
+        db.execute("CREATE INDEX idx_sensor ON readings (sensor)")
+            .unwrap();
+    }
+    for i in 0..500 {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, {}, {}, {}.5)",
+            i,
+            1_700_000_000_000_000i64 + i * 1_000_000,
+            i % 10,
+            i
+        ))
+        .unwrap();
+    }
+    (db, dir)
+}
+
+#[test]
+fn test_retention_delete_by_timestamp_range() {
+    for indexed in [false, true] {
+        let (db, _dir) = setup(indexed);
+        let cutoff = 1_700_000_000_000_000i64 + 100 * 1_000_000;
+        let result = db
+            .execute(&format!("DELETE FROM readings WHERE ts < {}", cutoff))
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(result.affected_rows(), 100, "indexed = {}", indexed);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM readings"), 400);
+        assert_eq!(
+            count(
+                &db,
+                &format!("SELECT COUNT(*) FROM readings WHERE ts < {}", cutoff)
+            ),
+            0
+        );
+    }
+}
+
+#[test]
+fn test_indexed_delete_rechecks_full_where() {
+    for indexed in [false, true] {
+        let (db, _dir) = setup(indexed);
+        let result = db
+            .execute("DELETE FROM readings WHERE sensor = 3 AND val > 250")
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(result.affected_rows(), 25, "indexed = {}", indexed);
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) FROM readings WHERE sensor = 3"),
+            25
+        );
+
+        let result = db
+            .execute_prepared(
+                "DELETE FROM readings WHERE sensor = ? AND id BETWEEN ? AND ?",
+                vec![Value::Integer(4), Value::Integer(0), Value::Integer(99)],
+            )
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(result.affected_rows(), 10, "indexed = {}", indexed);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM readings"), 465);
+    }
+}
+
+#[test]
+fn test_indexed_delete_rolls_back() {
+    let (db, _dir) = setup(true);
+    db.execute("BEGIN").unwrap();
+    db.execute("DELETE FROM readings WHERE sensor = 7").unwrap();
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM readings WHERE sensor = 7"),
+        0
+    );
+    db.execute("ROLLBACK").unwrap();
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM readings WHERE sensor = 7"),
+        50
+    );
+}
@@ -0,0 +1,112 @@
+//! On-insert embedding hooks (db.register_embedder)
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult, StorageError};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+/// Toy embedding: [byte length, number of spaces, 1]
+fn embed(value: &Value) -> motedb::Result<Vec<f32>> {
+    match value {
+        Value::Text(text) => Ok(vec![
+            text.as_str().len() as f32,
+            text.as_str().matches(' ').count() as f32,
+            1.0,
+        ]),
+        other => Err(StorageError::InvalidData(format!("not text: {:?}", other))),
+    }
+}
+
+fn vector(db: &Database, id: i64) -> Option<Vec<f32>> {
+    match &rows(db, &format!("SELECT emb FROM docs WHERE id = {}", id))[0][0] {
+        Value::Vector(v) => Some(v.as_slice().to_vec()),
+        Value::Null => None,
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_embedder_fills_vector_column() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, body TEXT, emb VECTOR(3))")
+        .unwrap();
+    db.register_embedder("docs", "body", "emb", embed).unwrap();
+
+    db.execute("INSERT INTO docs (id, body) VALUES (1, 'arm joint overheat')")
+        .unwrap();
+    db.batch_insert(
+        "docs",
+        vec![
+            vec![Value::Integer(2), Value::Text("ok".into()), Value::Null],
+            vec![Value::Integer(3), Value::Null, Value::Null],
+        ],
+    )
+    .unwrap();
+    let mut batch = db.write_batch();
+    batch.insert("docs", vec![Value::Integer(4), Value::Text("a b".into())]);
+    batch.commit().unwrap();
+
+    assert_eq!(vector(&db, 1), Some(vec![18.0, 2.0, 1.0]));
+    assert_eq!(vector(&db, 2), Some(vec![2.0, 0.0, 1.0]));
+    assert_eq!(vector(&db, 3), None);
+    assert_eq!(vector(&db, 4), Some(vec![3.0, 1.0, 1.0]));
+
+    // An explicit vector wins over the hook
+    db.execute("INSERT INTO docs VALUES (5, 'x', [9.0, 9.0, 9.0])")
+        .unwrap();
+    assert_eq!(vector(&db, 5), Some(vec![9.0, 9.0, 9.0]));
+
+    // Changing the source recomputes the vector
+    db.execute("UPDATE docs SET body = 'left gripper' WHERE id = 2")
+        .unwrap();
+    assert_eq!(vector(&db, 2), Some(vec![12.0, 1.0, 1.0]));
+
+    // Vector index sees the computed values
+    db.execute("CREATE VECTOR INDEX idx_emb ON docs (emb)")
+        .unwrap();
+    db.execute("INSERT INTO docs (id, body) VALUES (6, 'camera')")
+        .unwrap();
+    let nearest = rows(
+        &db,
+        "SELECT id FROM docs ORDER BY emb <-> [6.0, 0.0, 1.0] LIMIT 1",
+    );
+    assert_eq!(nearest, vec![vec![Value::Integer(6)]]);
+
+    assert!(db.unregister_embedder("docs", "emb"));
+    db.execute("INSERT INTO docs (id, body) VALUES (7, 'no hook')")
+        .unwrap();
+    assert_eq!(vector(&db, 7), None);
+}
+
+#[test]
+fn test_embedder_errors_abort_write() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, body TEXT, n INT, emb VECTOR(3))")
+        .unwrap();
+
+    assert!(db.register_embedder("docs", "body", "n", embed).is_err());
+    assert!(db.register_embedder("docs", "nope", "emb", embed).is_err());
+    db.register_embedder("docs", "n", "emb", embed).unwrap();
+
+    // The hook fails on a non-text source: nothing is written
+    assert!(db
+        .execute("INSERT INTO docs (id, body, n) VALUES (1, 'x', 5)")
+        .is_err());
+    assert!(rows(&db, "SELECT id FROM docs").is_empty());
+
+    // Wrong dimension is rejected too
+    db.register_embedder("docs", "body", "emb", |_| Ok(vec![1.0]))
+        .unwrap();
+    assert!(db
+        .execute("INSERT INTO docs (id, body) VALUES (2, 'x')")
+        .is_err());
+    assert!(rows(&db, "SELECT id FROM docs").is_empty());
+}
@@ -0,0 +1,160 @@
+//! ENUM columns: label validation, text rendering, compact storage
+
+#[path = "common/mod.rs"]
+mod common;
+
+use common::table_bytes;
+use motedb::types::Value;
+use motedb::Database;
+use std::path::Path;
+use tempfile::TempDir;
+
+const STATES: [&str; 3] = ["idle", "moving", "charging"];
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::text_from(s)
+}
+
+fn setup(path: &Path, n: usize) -> Database {
+    let db = Database::create(path).unwrap();
+    db.execute(
+        "CREATE TABLE robots (id INT PRIMARY KEY, state ENUM('idle', 'moving', 'charging'))",
+    )
+    .unwrap();
+    for i in 0..n {
+        db.execute(&format!(
+            "INSERT INTO robots VALUES ({}, '{}')",
+            i,
+            STATES[i % 3]
+        ))
+        .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_enum_validation_and_rendering() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("test.mote"), 30);
+
+    assert!(db
+        .execute("INSERT INTO robots VALUES (100, 'flying')")
+        .is_err());
+    assert!(db.execute("INSERT INTO robots VALUES (101, 2)").is_err());
+    db.execute("INSERT INTO robots VALUES (102, NULL)").unwrap();
+    assert!(db
+        .execute("UPDATE robots SET state = 'flying' WHERE id = 1")
+        .is_err());
+    db.execute("UPDATE robots SET state = 'charging' WHERE id = 1")
+        .unwrap();
+
+    for flushed in [false, true] {
+        if flushed {
+            db.flush().unwrap();
+        }
+        let r = rows(
+            db.execute("SELECT id, state FROM robots WHERE id < 3 OR id = 102 ORDER BY id")
+                .unwrap(),
+        );
+        assert_eq!(
+            r,
+            vec![
+                vec![Value::Integer(0), text("idle")],
+                vec![Value::Integer(1), text("charging")],
+                vec![Value::Integer(2), text("charging")],
+                vec![Value::Integer(102), Value::Null],
+            ]
+        );
+        let r = rows(
+            db.execute("SELECT state, COUNT(*) FROM robots WHERE state IS NOT NULL GROUP BY state ORDER BY state")
+                .unwrap(),
+        );
+        assert_eq!(
+            r,
+            vec![
+                vec![text("charging"), Value::Integer(11)],
+                vec![text("idle"), Value::Integer(10)],
+                vec![text("moving"), Value::Integer(9)],
+            ]
+        );
+        // Stored codes never leak into comparisons
+        let r = rows(
+            db.execute("SELECT COUNT(*) FROM robots WHERE state = '0'")
+                .unwrap(),
+        );
+        assert_eq!(r[0][0], Value::Integer(0));
+    }
+
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, s ENUM())")
+        .is_err());
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, s ENUM('a', 'a'))")
+        .is_err());
+}
+
+#[test]
+fn test_enum_is_stored_compactly() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = setup(&path, 2000);
+        db.execute("CREATE TABLE labels (id INT PRIMARY KEY, state TEXT)")
+            .unwrap();
+        for i in 0..2000 {
+            db.execute(&format!(
+                "INSERT INTO labels VALUES ({}, '{}')",
+                i,
+                STATES[i % 3]
+            ))
+            .unwrap();
+        }
+        db.flush().unwrap();
+        db.close().unwrap();
+    }
+    let as_enum = table_bytes(dir.path(), "robots");
+    let as_text = table_bytes(dir.path(), "labels");
+    assert!(as_enum < as_text, "ENUM {} vs TEXT {}", as_enum, as_text);
+
+    // The dictionary comes back from the catalog
+    let db = Database::open(&path).unwrap();
+    let r = rows(
+        db.execute("SELECT state FROM robots WHERE id = 1999")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], text("moving"));
+    assert!(db
+        .execute("INSERT INTO robots VALUES (5000, 'docked')")
+        .is_err());
+}
+
+#[test]
+fn test_enum_index_lookup() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("test.mote"), 30);
+    db.flush().unwrap();
+    db.execute("CREATE INDEX idx_state ON robots(state)")
+        .unwrap();
+    db.wait_for_indexes_ready();
+    db.execute("INSERT INTO robots VALUES (30, 'charging')")
+        .unwrap();
+
+    let r = rows(
+        db.execute("SELECT id FROM robots WHERE state = 'charging' ORDER BY id")
+            .unwrap(),
+    );
+    let ids: Vec<Value> = (2..=29)
+        .step_by(3)
+        .chain([30])
+        .map(|i| Value::Integer(i as i64))
+        .collect();
+    assert_eq!(r.into_iter().map(|r| r[0].clone()).collect::<Vec<_>>(), ids);
+}
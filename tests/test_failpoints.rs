@@ -0,0 +1,295 @@
+//! Storage failpoints: injected I/O errors, short writes, fsync failures
+//! and delays in the WAL, SSTable and manifest write paths
+#![cfg(feature = "failpoints")]
+
+use motedb::sql::QueryResult;
+use motedb::storage::backend::{MemoryBackend, StorageBackend};
+use motedb::storage::failpoint::{self, FailAction, FailPoint, FailScenario};
+use motedb::storage::lsm::{LSMConfig, LSMEngine, Value, ValueData};
+use motedb::storage::manifest::{FileMetadata, FileType, Manifest, VersionEdit};
+use motedb::txn::wal::{WALConfig, WALManager};
+use motedb::{Database, DurabilityLevel};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const WAL_DIR: &str = "/db.mote/wal";
+
+fn wal_config() -> WALConfig {
+    WALConfig {
+        durability_level: DurabilityLevel::Synchronous,
+        flash: None,
+    }
+}
+
+fn create_wal(mem: &MemoryBackend) -> WALManager {
+    let backend: Arc<dyn StorageBackend> = Arc::new(mem.clone());
+    WALManager::create_with_backend(WAL_DIR, 1, wal_config(), backend).unwrap()
+}
+
+fn log_row(wal: &WALManager, row_id: u64) -> motedb::Result<()> {
+    wal.log_insert_raw_ref("t", 0, row_id, &[row_id as u8; 64], 0)
+        .map(|_| ())
+}
+
+fn recovered_rows(wal: &WALManager) -> Vec<u64> {
+    wal.recover().unwrap()[&0]
+        .iter()
+        .filter_map(|r| r.row_id())
+        .collect()
+}
+
+#[test]
+fn test_failpoint_triggers() {
+    let _scenario = FailScenario::setup();
+    assert!(failpoint::set("wal.nowhere", FailPoint::new(FailAction::error())).is_err());
+
+    let mem = MemoryBackend::new();
+    let wal = create_wal(&mem);
+    // Pass two syncs, fail the next two, then pass again
+    failpoint::set(
+        failpoint::WAL_SYNC,
+        FailPoint::new(FailAction::error()).skip(2).times(2),
+    )
+    .unwrap();
+    let results: Vec<bool> = (0..6).map(|row| log_row(&wal, row).is_ok()).collect();
+    assert_eq!(results, [true, true, false, false, true, true]);
+    assert_eq!(failpoint::fired(failpoint::WAL_SYNC), 2);
+
+    failpoint::remove(failpoint::WAL_SYNC);
+    log_row(&wal, 6).unwrap();
+    assert_eq!(failpoint::fired(failpoint::WAL_SYNC), 2);
+}
+
+#[test]
+fn test_wal_write_error_leaves_log_usable() {
+    let _scenario = FailScenario::setup();
+    let mem = MemoryBackend::new();
+    let wal = create_wal(&mem);
+    for row in 0..3 {
+        log_row(&wal, row).unwrap();
+    }
+
+    failpoint::set(
+        failpoint::WAL_WRITE,
+        FailPoint::new(FailAction::Error(std::io::ErrorKind::StorageFull)).times(1),
+    )
+    .unwrap();
+    let err = log_row(&wal, 3).unwrap_err();
+    assert!(err.to_string().contains("failpoint wal.write"), "{}", err);
+    log_row(&wal, 4).unwrap();
+    drop(wal);
+
+    let backend: Arc<dyn StorageBackend> = Arc::new(mem);
+    let wal = WALManager::open_with_backend(WAL_DIR, 1, wal_config(), backend).unwrap();
+    assert_eq!(recovered_rows(&wal), vec![0, 1, 2, 4]);
+}
+
+#[test]
+fn test_wal_short_write_is_cut_off() {
+    let _scenario = FailScenario::setup();
+    for keep in [0, 1, 7, 40] {
+        let mem = MemoryBackend::new();
+        let wal = create_wal(&mem);
+        for row in 0..3 {
+            log_row(&wal, row).unwrap();
+        }
+
+        failpoint::set(
+            failpoint::WAL_WRITE,
+            FailPoint::new(FailAction::ShortWrite(keep)).times(1),
+        )
+        .unwrap();
+        assert!(log_row(&wal, 3).is_err());
+        // The torn frame must not hide records acknowledged after it
+        log_row(&wal, 4).unwrap();
+        drop(wal);
+
+        let backend: Arc<dyn StorageBackend> = Arc::new(mem);
+        let wal = WALManager::open_with_backend(WAL_DIR, 1, wal_config(), backend).unwrap();
+        assert_eq!(recovered_rows(&wal), vec![0, 1, 2, 4], "keep {}", keep);
+    }
+}
+
+#[test]
+fn test_delay() {
+    let _scenario = FailScenario::setup();
+    let wal = create_wal(&MemoryBackend::new());
+    failpoint::set(
+        failpoint::WAL_WRITE,
+        FailPoint::new(FailAction::Delay(Duration::from_millis(50))).times(1),
+    )
+    .unwrap();
+    let start = Instant::now();
+    log_row(&wal, 0).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(recovered_rows(&wal), vec![0]);
+}
+
+#[test]
+fn test_sstable_failures_are_retried() {
+    let _scenario = FailScenario::setup();
+    let dir = TempDir::new().unwrap();
+    let engine = LSMEngine::new(dir.path().to_path_buf(), LSMConfig::default()).unwrap();
+    for key in 0..200u64 {
+        engine
+            .put(key, Value::new(key.to_le_bytes().to_vec(), key))
+            .unwrap();
+    }
+
+    // The background flush keeps the memtable until an SSTable is durable,
+    // so a failed fsync and a torn block only cost a retry
+    failpoint::set(
+        failpoint::SSTABLE_SYNC,
+        FailPoint::new(FailAction::error()).times(1),
+    )
+    .unwrap();
+    failpoint::set(
+        failpoint::SSTABLE_WRITE,
+        FailPoint::new(FailAction::ShortWrite(5)).times(1),
+    )
+    .unwrap();
+    engine.flush().unwrap();
+    assert_eq!(failpoint::fired(failpoint::SSTABLE_WRITE), 1);
+    assert_eq!(failpoint::fired(failpoint::SSTABLE_SYNC), 1);
+    for key in [0u64, 99, 199] {
+        let value = engine.get(key).unwrap().expect("key survives the flush");
+        assert_eq!(
+            value.data,
+            ValueData::Inline(Arc::new(key.to_le_bytes().to_vec()))
+        );
+    }
+
+    // A flush that cannot start reports the failure to the caller
+    failpoint::set(failpoint::LSM_FLUSH, FailPoint::new(FailAction::error())).unwrap();
+    engine.put(500, Value::new(vec![1], 500)).unwrap();
+    assert!(engine.flush().is_err());
+    failpoint::remove(failpoint::LSM_FLUSH);
+    engine.flush().unwrap();
+    assert!(engine.get(500).unwrap().is_some());
+}
+
+#[test]
+fn test_manifest_commit_failure_is_rolled_back() {
+    let _scenario = FailScenario::setup();
+    let dir = TempDir::new().unwrap();
+    let file = |name: &str| {
+        std::fs::write(dir.path().join(name), vec![1u8; 128]).unwrap();
+        FileMetadata {
+            file_id: name.len() as u64,
+            file_type: FileType::SSTable,
+            path: name.to_string(),
+            size: 128,
+            checksum: Manifest::calculate_checksum(&dir.path().join(name)).unwrap(),
+            min_key: None,
+            max_key: None,
+            level: Some(0),
+            modified: None,
+        }
+    };
+    let edit = |meta: FileMetadata| {
+        let mut edit = VersionEdit::new();
+        edit.add_file(meta);
+        edit
+    };
+
+    let manifest = Manifest::open(dir.path()).unwrap();
+    manifest.apply_edit(edit(file("a.sst"))).unwrap();
+
+    // The edit record is written, its commit record is not
+    failpoint::set(
+        failpoint::MANIFEST_WRITE,
+        FailPoint::new(FailAction::ShortWrite(3)).skip(1).times(1),
+    )
+    .unwrap();
+    assert!(manifest.apply_edit(edit(file("bb.sst"))).is_err());
+    assert_eq!(failpoint::fired(failpoint::MANIFEST_WRITE), 1);
+    // A failed fsync of the next edit fails that edit only
+    failpoint::set(
+        failpoint::MANIFEST_SYNC,
+        FailPoint::new(FailAction::error()).times(1),
+    )
+    .unwrap();
+    assert!(manifest.apply_edit(edit(file("ccc.sst"))).is_err());
+    manifest.apply_edit(edit(file("dddd.sst"))).unwrap();
+    drop(manifest);
+
+    let reopened = Manifest::open(dir.path()).unwrap();
+    let mut paths: Vec<String> = reopened.current_version().files[&FileType::SSTable]
+        .iter()
+        .map(|meta| meta.path.clone())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["a.sst", "dddd.sst"]);
+}
+
+#[test]
+fn test_drop_table_failing_half_way_is_finished() {
+    let _scenario = FailScenario::setup();
+    let dir = TempDir::new().unwrap();
+    let count = |db: &Database, table: &str| -> usize {
+        match db
+            .execute(&format!("SELECT * FROM {}", table))
+            .unwrap()
+            .materialize()
+            .unwrap()
+        {
+            QueryResult::Select { rows, .. } => rows.len(),
+            other => panic!("expected rows, got {:?}", other),
+        }
+    };
+    let fail_flush = || {
+        failpoint::set(
+            failpoint::LSM_FLUSH,
+            FailPoint::new(FailAction::error()).times(1),
+        )
+        .unwrap()
+    };
+
+    let db = Database::create(dir.path()).unwrap();
+    for table in ["t", "u"] {
+        db.execute(&format!(
+            "CREATE TABLE {} (id INT PRIMARY KEY, v INT)",
+            table
+        ))
+        .unwrap();
+        db.execute(&format!("CREATE INDEX idx_{}_v ON {}(v)", table, table))
+            .unwrap();
+        for i in 0..20 {
+            db.execute(&format!("INSERT INTO {} VALUES ({}, {})", table, i, i))
+                .unwrap();
+        }
+    }
+    db.checkpoint().unwrap();
+
+    // The rows are tombstoned, then the flush fails: the drop stays pending
+    // with the table hidden and its name taken
+    fail_flush();
+    assert!(db.execute("DROP TABLE t").is_err());
+    assert_eq!(failpoint::fired(failpoint::LSM_FLUSH), 1);
+    assert!(db.execute("SELECT * FROM t").is_err());
+    assert!(db
+        .execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+        .is_err());
+
+    // DROP TABLE again finishes it
+    fail_flush();
+    assert!(db.execute("DROP TABLE u").is_err());
+    db.execute("DROP TABLE u").unwrap();
+    db.execute("CREATE TABLE u (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    assert_eq!(count(&db, "u"), 0);
+    db.close().unwrap();
+    drop(db);
+
+    // Reopening finishes the other
+    let db = Database::open(dir.path()).unwrap();
+    assert!(db.execute("SELECT * FROM t").is_err());
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_t_v ON t(v)").unwrap();
+    assert_eq!(count(&db, "t"), 0);
+    db.execute("INSERT INTO t VALUES (1, 1)").unwrap();
+    assert_eq!(count(&db, "t"), 1);
+    assert_eq!(count(&db, "u"), 0);
+}
@@ -0,0 +1,96 @@
+//! FFI cursor API: open / next_batch / close over streaming results.
+
+use motedb::ffi::*;
+use std::ffi::{CStr, CString};
+use tempfile::TempDir;
+
+/// Create an empty database on disk (`motedb_open` only opens existing ones).
+fn create_db(dir: &TempDir) -> CString {
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    CString::new(path.to_str().unwrap()).unwrap()
+}
+
+unsafe fn next_batch(cursor: *mut MoteDBCursor, n: usize) -> serde_json::Value {
+    let ptr = motedb_cursor_next_batch(cursor, n);
+    assert!(!ptr.is_null());
+    let json = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+    motedb_free_string(ptr);
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_cursor_batches_cover_all_rows() {
+    let dir = TempDir::new().unwrap();
+    let path = create_db(&dir);
+    unsafe {
+        let h = motedb_open(path.as_ptr());
+        assert!(!h.is_null());
+        for sql in [
+            "CREATE TABLE t (id INT PRIMARY KEY, name TEXT)".to_string(),
+            "INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e')".to_string(),
+        ] {
+            let s = CString::new(sql).unwrap();
+            motedb_free_string(motedb_execute(h, s.as_ptr()));
+        }
+
+        let q = CString::new("SELECT id, name FROM t").unwrap();
+        let cur = motedb_cursor_open(h, q.as_ptr());
+        assert!(!cur.is_null());
+        assert_eq!(motedb_cursor_column_count(cur), 2);
+        let name = CStr::from_ptr(motedb_cursor_column_name(cur, 1));
+        assert_eq!(name.to_str().unwrap(), "name");
+        assert!(motedb_cursor_column_name(cur, 2).is_null());
+
+        let mut ids = Vec::new();
+        loop {
+            let batch = next_batch(cur, 2);
+            let rows = batch.as_array().unwrap();
+            assert!(rows.len() <= 2);
+            if rows.is_empty() {
+                break;
+            }
+            for row in rows {
+                ids.push(row[0].as_i64().unwrap());
+            }
+        }
+        assert!(motedb_cursor_is_exhausted(cur));
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        motedb_cursor_close(cur);
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_cursor_respects_order_and_limit() {
+    let dir = TempDir::new().unwrap();
+    let path = create_db(&dir);
+    unsafe {
+        let h = motedb_open(path.as_ptr());
+        assert!(!h.is_null());
+        for sql in [
+            "CREATE TABLE t (id INT PRIMARY KEY, v INT)",
+            "INSERT INTO t VALUES (1, 30), (2, 10), (3, 20)",
+        ] {
+            let s = CString::new(sql).unwrap();
+            motedb_free_string(motedb_execute(h, s.as_ptr()));
+        }
+        let q = CString::new("SELECT v FROM t ORDER BY v DESC LIMIT 2").unwrap();
+        let cur = motedb_cursor_open(h, q.as_ptr());
+        assert!(!cur.is_null());
+        let batch = next_batch(cur, 100);
+        let vals: Vec<i64> = batch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r[0].as_i64().unwrap())
+            .collect();
+        assert_eq!(vals, vec![30, 20]);
+        motedb_cursor_close(cur);
+
+        let bad = CString::new("SELECT * FROM missing").unwrap();
+        assert!(motedb_cursor_open(h, bad.as_ptr()).is_null());
+        motedb_close(h);
+    }
+}
@@ -0,0 +1,137 @@
+//! FFI error model: numeric error codes, SQLSTATE-like categories and the
+//! per-handle last-error slot.
+
+use motedb::ffi::*;
+use motedb::ErrorCode;
+use std::ffi::{CStr, CString};
+use std::ptr;
+use tempfile::TempDir;
+
+fn open(dir: &TempDir) -> *mut MoteDBHandle {
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let h = unsafe { motedb_open(path.as_ptr()) };
+    assert!(!h.is_null());
+    h
+}
+
+unsafe fn last_error(h: *const MoteDBHandle) -> (i32, String, String) {
+    let mut info = MoteDBErrorInfo {
+        code: -1,
+        message: ptr::null(),
+        sqlstate: ptr::null(),
+    };
+    let code = motedb_last_error(h, &mut info);
+    assert_eq!(code, info.code);
+    (
+        code,
+        CStr::from_ptr(info.message).to_str().unwrap().to_string(),
+        CStr::from_ptr(info.sqlstate).to_str().unwrap().to_string(),
+    )
+}
+
+unsafe fn exec(h: *mut MoteDBHandle, sql: &str) {
+    let sql = CString::new(sql).unwrap();
+    motedb_free_string(motedb_execute(h, sql.as_ptr()));
+}
+
+#[test]
+fn test_last_error_per_handle() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        assert_eq!(last_error(h), (0, String::new(), "00000".to_string()));
+
+        exec(h, "SELECT * FROM missing");
+        let (code, message, sqlstate) = last_error(h);
+        assert_ne!(code, 0);
+        assert!(message.contains("missing"), "{}", message);
+        assert_eq!(sqlstate.len(), 5);
+
+        // A successful call clears the slot
+        exec(h, "CREATE TABLE t (id INT PRIMARY KEY, name TEXT)");
+        assert_eq!(motedb_last_error(h, ptr::null_mut()), 0);
+
+        let garbage = CString::new("SELEKT nonsense").unwrap();
+        assert!(motedb_prepare(h, garbage.as_ptr()).is_null());
+        let (code, _, sqlstate) = last_error(h);
+        assert_eq!(code, ErrorCode::ParseError as i32);
+        assert_eq!(sqlstate, "42601");
+
+        motedb_clear_error(h);
+        assert_eq!(motedb_last_error(h, ptr::null_mut()), 0);
+
+        assert!(motedb_cursor_open(h, ptr::null()).is_null());
+        assert_eq!(
+            motedb_last_error(h, ptr::null_mut()),
+            ErrorCode::InvalidArgument as i32
+        );
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_statement_errors_surface_on_handle() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        exec(h, "CREATE TABLE t (id INT PRIMARY KEY, emb VECTOR(2))");
+
+        let sql = CString::new("SELECT nope FROM t").unwrap();
+        let stmt = motedb_prepare(h, sql.as_ptr());
+        assert!(!stmt.is_null());
+        let out = motedb_stmt_execute(stmt);
+        let json: serde_json::Value =
+            serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+        motedb_free_string(out);
+
+        let (code, _, sqlstate) = last_error(h);
+        assert_ne!(code, 0);
+        assert_eq!(json["code"], code);
+        assert_eq!(json["sqlstate"], sqlstate);
+        assert!(json["error"].is_string());
+
+        assert!(!motedb_stmt_bind_int(stmt, 0, 1));
+        assert_eq!(
+            motedb_last_error(h, ptr::null_mut()),
+            ErrorCode::InvalidArgument as i32
+        );
+        motedb_stmt_finalize(stmt);
+
+        let table = CString::new("t").unwrap();
+        let column = CString::new("nope").unwrap();
+        let mut buf = [0f32; 2];
+        assert_eq!(
+            motedb_get_vector(h, table.as_ptr(), 1, column.as_ptr(), buf.as_mut_ptr(), 2),
+            -1
+        );
+        assert_eq!(
+            motedb_last_error(h, ptr::null_mut()),
+            ErrorCode::ColumnNotFound as i32
+        );
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_open_failure_uses_thread_error() {
+    let dir = TempDir::new().unwrap();
+    // A regular file where the database directory should be
+    let path = dir.path().join("not_a_db.mote");
+    std::fs::write(&path, b"junk").unwrap();
+    let path = CString::new(dir.path().join("not_a_db").to_str().unwrap()).unwrap();
+    unsafe {
+        motedb_clear_error(ptr::null());
+        assert!(motedb_open(path.as_ptr()).is_null());
+        let (code, message, _) = last_error(ptr::null());
+        assert_ne!(code, 0);
+        assert!(!message.is_empty());
+
+        assert!(motedb_open(ptr::null()).is_null());
+        assert_eq!(
+            motedb_last_error(ptr::null(), ptr::null_mut()),
+            ErrorCode::InvalidArgument as i32
+        );
+    }
+}
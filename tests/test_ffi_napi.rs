@@ -0,0 +1,236 @@
+//! N-API oriented FFI layer: prepared statements, parameter binding and
+//! callback-based async execution.
+
+use motedb::ffi::*;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::sync::mpsc;
+use tempfile::TempDir;
+
+fn open(dir: &TempDir) -> *mut MoteDBHandle {
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let h = unsafe { motedb_open(path.as_ptr()) };
+    assert!(!h.is_null());
+    h
+}
+
+unsafe fn take_json(ptr: *mut c_char) -> serde_json::Value {
+    assert!(!ptr.is_null());
+    let s = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+    motedb_free_string(ptr);
+    serde_json::from_str(&s).unwrap()
+}
+
+unsafe fn prepare(h: *mut MoteDBHandle, sql: &str) -> *mut MoteDBStatement {
+    let s = CString::new(sql).unwrap();
+    let stmt = motedb_prepare(h, s.as_ptr());
+    assert!(!stmt.is_null(), "prepare failed: {}", sql);
+    stmt
+}
+
+#[test]
+fn test_prepared_statement_bind_and_execute() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        let create = prepare(
+            h,
+            "CREATE TABLE t (id INT PRIMARY KEY, name TEXT, score FLOAT)",
+        );
+        take_json(motedb_stmt_execute(create));
+        motedb_stmt_finalize(create);
+
+        let insert = prepare(h, "INSERT INTO t VALUES (?, ?, ?)");
+        for i in 1..=3 {
+            let name = CString::new(format!("robot{}", i)).unwrap();
+            assert!(motedb_stmt_bind_int(insert, 1, i));
+            assert!(motedb_stmt_bind_text(insert, 2, name.as_ptr()));
+            assert!(motedb_stmt_bind_float(insert, 3, i as f64 * 1.5));
+            let r = take_json(motedb_stmt_execute(insert));
+            assert_eq!(r["affected_rows"], 1);
+        }
+        assert!(!motedb_stmt_bind_int(insert, 0, 1), "index is 1-based");
+        motedb_stmt_finalize(insert);
+
+        let select = prepare(h, "SELECT name, score FROM t WHERE id = ?");
+        motedb_stmt_bind_int(select, 1, 2);
+        let r = take_json(motedb_stmt_execute(select));
+        assert_eq!(r["columns"], serde_json::json!(["name", "score"]));
+        assert_eq!(r["rows"][0][0], "robot2");
+        assert_eq!(r["rows"][0][1], 3.0);
+        motedb_stmt_finalize(select);
+
+        let bad = prepare(h, "SELECT * FROM missing");
+        let r = take_json(motedb_stmt_execute(bad));
+        assert!(r["error"].is_string());
+        motedb_stmt_finalize(bad);
+
+        let garbage = CString::new("SELEKT nonsense").unwrap();
+        assert!(motedb_prepare(h, garbage.as_ptr()).is_null());
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_bind_vector_from_buffer() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        let create = prepare(h, "CREATE TABLE v (id INT PRIMARY KEY, emb VECTOR(3))");
+        take_json(motedb_stmt_execute(create));
+        motedb_stmt_finalize(create);
+
+        let insert = prepare(h, "INSERT INTO v VALUES (?, ?)");
+        let emb = [0.5f32, 1.5, 2.5];
+        motedb_stmt_bind_int(insert, 1, 7);
+        assert!(motedb_stmt_bind_vector(insert, 2, emb.as_ptr(), 3));
+        assert_eq!(take_json(motedb_stmt_execute(insert))["affected_rows"], 1);
+        motedb_stmt_finalize(insert);
+
+        let select = prepare(h, "SELECT * FROM v");
+        let r = take_json(motedb_stmt_execute(select));
+        assert_eq!(r["rows"][0][1], serde_json::json!([0.5, 1.5, 2.5]));
+        motedb_stmt_finalize(select);
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_stmt_batch_insert() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        let create = prepare(h, "CREATE TABLE log (id INT PRIMARY KEY, msg TEXT)");
+        take_json(motedb_stmt_execute(create));
+        motedb_stmt_finalize(create);
+        let index = prepare(h, "CREATE INDEX idx_msg ON log (msg)");
+        take_json(motedb_stmt_execute(index));
+        motedb_stmt_finalize(index);
+
+        let insert = prepare(h, "INSERT INTO log VALUES (?, ?)");
+        for i in 0..500 {
+            let msg = CString::new(format!("m{}", i % 5)).unwrap();
+            motedb_stmt_bind_int(insert, 1, i);
+            motedb_stmt_bind_text(insert, 2, msg.as_ptr());
+            assert_eq!(motedb_stmt_add_batch(insert), i as usize + 1);
+        }
+        let r = take_json(motedb_stmt_execute_batch(insert));
+        assert_eq!(r["affected_rows"], 500);
+        // The batch is consumed; an empty batch writes nothing
+        assert_eq!(
+            take_json(motedb_stmt_execute_batch(insert))["affected_rows"],
+            0
+        );
+
+        // A duplicate key fails the whole batch
+        motedb_stmt_bind_int(insert, 1, 1000);
+        motedb_stmt_add_batch(insert);
+        motedb_stmt_bind_int(insert, 1, 0);
+        motedb_stmt_add_batch(insert);
+        let r = take_json(motedb_stmt_execute_batch(insert));
+        assert!(r["error"].is_string(), "{}", r);
+        motedb_stmt_finalize(insert);
+
+        let select = prepare(h, "SELECT COUNT(*) FROM log WHERE msg = 'm3'");
+        assert_eq!(take_json(motedb_stmt_execute(select))["rows"][0][0], 100);
+        motedb_stmt_finalize(select);
+        let select = prepare(h, "SELECT COUNT(*) FROM log");
+        assert_eq!(take_json(motedb_stmt_execute(select))["rows"][0][0], 500);
+        motedb_stmt_finalize(select);
+        motedb_close(h);
+    }
+}
+
+extern "C" fn on_complete(user_data: *mut c_void, result: *mut c_char, is_error: bool) {
+    let tx = unsafe { Box::from_raw(user_data as *mut mpsc::Sender<(serde_json::Value, bool)>) };
+    let json = unsafe { take_json(result) };
+    tx.send((json, is_error)).unwrap();
+}
+
+fn sender_ptr(tx: &mpsc::Sender<(serde_json::Value, bool)>) -> *mut c_void {
+    Box::into_raw(Box::new(tx.clone())) as *mut c_void
+}
+
+#[test]
+fn test_async_execution_invokes_callback() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    let (tx, rx) = mpsc::channel();
+    unsafe {
+        let sql = CString::new("CREATE TABLE a (id INT PRIMARY KEY, v INT)").unwrap();
+        assert!(motedb_execute_async(
+            h,
+            sql.as_ptr(),
+            on_complete,
+            sender_ptr(&tx)
+        ));
+        let (_, is_error) = rx.recv().unwrap();
+        assert!(!is_error);
+
+        let insert = prepare(h, "INSERT INTO a VALUES (?, ?)");
+        motedb_stmt_bind_int(insert, 1, 1);
+        motedb_stmt_bind_int(insert, 2, 42);
+        assert!(motedb_stmt_execute_async(
+            h,
+            insert,
+            on_complete,
+            sender_ptr(&tx)
+        ));
+        // Rebinding right after submission must not affect the in-flight call.
+        motedb_stmt_bind_int(insert, 2, 99);
+        let (r, is_error) = rx.recv().unwrap();
+        assert!(!is_error);
+        assert_eq!(r["affected_rows"], 1);
+        motedb_stmt_finalize(insert);
+
+        let sql = CString::new("SELECT v FROM a WHERE id = 1").unwrap();
+        motedb_execute_async(h, sql.as_ptr(), on_complete, sender_ptr(&tx));
+        let (r, _) = rx.recv().unwrap();
+        assert_eq!(r["rows"][0][0], 42);
+
+        let sql = CString::new("SELECT * FROM nowhere").unwrap();
+        motedb_execute_async(h, sql.as_ptr(), on_complete, sender_ptr(&tx));
+        let (r, is_error) = rx.recv().unwrap();
+        assert!(is_error);
+        assert!(r["error"].is_string());
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_async_burst_runs_on_bounded_pool() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    let (tx, rx) = mpsc::channel();
+    unsafe {
+        let sql = CString::new("CREATE TABLE b (id INT PRIMARY KEY, v INT)").unwrap();
+        assert!(motedb_execute_async(
+            h,
+            sql.as_ptr(),
+            on_complete,
+            sender_ptr(&tx)
+        ));
+        assert!(!rx.recv().unwrap().1);
+
+        // More requests than workers: all queue up and each calls back once
+        let sql = CString::new("SELECT COUNT(*) FROM b").unwrap();
+        for _ in 0..64 {
+            assert!(motedb_execute_async(
+                h,
+                sql.as_ptr(),
+                on_complete,
+                sender_ptr(&tx)
+            ));
+        }
+        for _ in 0..64 {
+            let (r, is_error) = rx.recv().unwrap();
+            assert!(!is_error, "{}", r);
+        }
+        assert!(rx
+            .recv_timeout(std::time::Duration::from_millis(100))
+            .is_err());
+        motedb_close(h);
+    }
+}
@@ -0,0 +1,217 @@
+//! FFI float32 buffer API: batch vector execute, KNN into caller buffers,
+//! and direct vector reads (the NumPy zero-copy exchange path).
+
+use motedb::ffi::*;
+use std::ffi::CString;
+use tempfile::TempDir;
+
+fn open(dir: &TempDir) -> *mut MoteDBHandle {
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let h = unsafe { motedb_open(path.as_ptr()) };
+    assert!(!h.is_null());
+    h
+}
+
+unsafe fn exec(h: *mut MoteDBHandle, sql: &str) {
+    let s = CString::new(sql).unwrap();
+    motedb_free_string(motedb_execute(h, s.as_ptr()));
+}
+
+#[test]
+fn test_execute_with_vectors_and_read_back() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        exec(
+            h,
+            "CREATE TABLE docs (id INT PRIMARY KEY AUTO_INCREMENT, emb VECTOR(4))",
+        );
+        let matrix: Vec<f32> = (0..40).map(|i| i as f32).collect();
+        let sql = CString::new("INSERT INTO docs (emb) VALUES (?)").unwrap();
+        let n = motedb_execute_with_vectors(h, sql.as_ptr(), matrix.as_ptr(), 10, 4);
+        assert_eq!(n, 10);
+
+        let table = CString::new("docs").unwrap();
+        let col = CString::new("emb").unwrap();
+        let mut out = [0f32; 4];
+        let mut found = 0;
+        for row_id in 0..=10u64 {
+            let dim =
+                motedb_get_vector(h, table.as_ptr(), row_id, col.as_ptr(), out.as_mut_ptr(), 4);
+            assert!(dim == 0 || dim == 4);
+            if dim == 4 {
+                assert_eq!(out[1], out[0] + 1.0);
+                assert_eq!(out[0] % 4.0, 0.0);
+                found += 1;
+            }
+        }
+        assert_eq!(found, 10);
+
+        // Too-small buffer: dimension is reported, nothing is copied.
+        let mut small = [-1f32; 2];
+        let mut reported = 0;
+        for row_id in 0..=10u64 {
+            let d = motedb_get_vector(
+                h,
+                table.as_ptr(),
+                row_id,
+                col.as_ptr(),
+                small.as_mut_ptr(),
+                2,
+            );
+            reported = reported.max(d);
+        }
+        assert_eq!(reported, 4);
+        assert_eq!(small, [-1.0, -1.0]);
+
+        let bad_col = CString::new("nope").unwrap();
+        assert_eq!(
+            motedb_get_vector(h, table.as_ptr(), 1, bad_col.as_ptr(), out.as_mut_ptr(), 4),
+            -1
+        );
+        motedb_close(h);
+    }
+}
+
+#[test]
+fn test_vector_search_into_buffers() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    unsafe {
+        exec(
+            h,
+            "CREATE TABLE docs (id INT PRIMARY KEY AUTO_INCREMENT, emb VECTOR(4))",
+        );
+        let matrix: Vec<f32> = (0..80).map(|i| (i / 4) as f32).collect();
+        let sql = CString::new("INSERT INTO docs (emb) VALUES (?)").unwrap();
+        assert_eq!(
+            motedb_execute_with_vectors(h, sql.as_ptr(), matrix.as_ptr(), 20, 4),
+            20
+        );
+        exec(h, "CREATE VECTOR INDEX idx_emb ON docs(emb)");
+
+        let idx = CString::new("idx_emb").unwrap();
+        let query = [5.0f32; 4];
+        let mut ids = [0u64; 3];
+        let mut dists = [0f32; 3];
+        let n = motedb_vector_search(
+            h,
+            idx.as_ptr(),
+            query.as_ptr(),
+            4,
+            3,
+            ids.as_mut_ptr(),
+            dists.as_mut_ptr(),
+        );
+        assert_eq!(n, 3);
+        assert_eq!(dists[0], 0.0);
+        for i in 1..n as usize {
+            assert!(dists[i] >= dists[i - 1]);
+        }
+
+        // The sixth inserted row holds exactly [5.0; 4]
+        let table = CString::new("docs").unwrap();
+        let col = CString::new("emb").unwrap();
+        let mut out = [0f32; 4];
+        let exact = (0..=20u64)
+            .find(|&row_id| {
+                motedb_get_vector(h, table.as_ptr(), row_id, col.as_ptr(), out.as_mut_ptr(), 4) == 4
+                    && out == query
+            })
+            .unwrap();
+        assert_eq!(ids[0], exact);
+
+        let missing = CString::new("no_such_index").unwrap();
+        assert_eq!(
+            motedb_vector_search(
+                h,
+                missing.as_ptr(),
+                query.as_ptr(),
+                4,
+                3,
+                ids.as_mut_ptr(),
+                dists.as_mut_ptr()
+            ),
+            -1
+        );
+        motedb_close(h);
+    }
+}
+
+/// Embeds text as [len, count of 'a', 0, 0]; "fail" makes the callback fail
+extern "C" fn embed_text(
+    user_data: *mut std::os::raw::c_void,
+    text: *const std::os::raw::c_char,
+    out: *mut f32,
+    dim: usize,
+) -> bool {
+    let calls = unsafe { &*(user_data as *const std::sync::atomic::AtomicUsize) };
+    calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let text = unsafe { std::ffi::CStr::from_ptr(text) }.to_str().unwrap();
+    if text == "fail" {
+        return false;
+    }
+    let out = unsafe { std::slice::from_raw_parts_mut(out, dim) };
+    out.fill(0.0);
+    out[0] = text.len() as f32;
+    out[1] = text.matches('a').count() as f32;
+    true
+}
+
+#[test]
+fn test_register_embedder_callback() {
+    let dir = TempDir::new().unwrap();
+    let h = open(&dir);
+    let calls = std::sync::atomic::AtomicUsize::new(0);
+    let user_data = &calls as *const _ as *mut std::os::raw::c_void;
+    unsafe {
+        exec(
+            h,
+            "CREATE TABLE docs (id INT PRIMARY KEY, body TEXT, emb VECTOR(4))",
+        );
+        let table = CString::new("docs").unwrap();
+        let body = CString::new("body").unwrap();
+        let emb = CString::new("emb").unwrap();
+        assert!(motedb_register_embedder(
+            h,
+            table.as_ptr(),
+            body.as_ptr(),
+            emb.as_ptr(),
+            embed_text,
+            user_data
+        ));
+        // The target must be a vector column
+        assert!(!motedb_register_embedder(
+            h,
+            table.as_ptr(),
+            emb.as_ptr(),
+            body.as_ptr(),
+            embed_text,
+            user_data
+        ));
+
+        exec(h, "INSERT INTO docs (id, body) VALUES (1, 'banana')");
+        let mut out = [0f32; 4];
+        assert_eq!(
+            motedb_get_vector(h, table.as_ptr(), 1, emb.as_ptr(), out.as_mut_ptr(), 4),
+            4
+        );
+        assert_eq!(out, [6.0, 3.0, 0.0, 0.0]);
+
+        // A failing callback aborts the insert
+        exec(h, "INSERT INTO docs (id, body) VALUES (2, 'fail')");
+        assert_ne!(motedb_last_error(h, std::ptr::null_mut()), 0);
+
+        assert!(motedb_unregister_embedder(h, table.as_ptr(), emb.as_ptr()));
+        assert!(!motedb_unregister_embedder(h, table.as_ptr(), emb.as_ptr()));
+        exec(h, "INSERT INTO docs (id, body) VALUES (3, 'cat')");
+        assert_eq!(
+            motedb_get_vector(h, table.as_ptr(), 3, emb.as_ptr(), out.as_mut_ptr(), 4),
+            0
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+        motedb_close(h);
+    }
+}
@@ -0,0 +1,72 @@
+//! Flash-friendly write mode: page-aligned WAL syncs, coalesced fsyncs
+
+use motedb::{DBConfig, Database, FlashWriteConfig, QueryResult};
+use tempfile::TempDir;
+
+fn count(db: &Database, sql: &str) -> usize {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows.len(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_flash_mode_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("telemetry");
+    let wal = dir.path().join("telemetry.mote/wal/partition_0.wal");
+    {
+        let db = Database::create_with_config(&path, DBConfig::for_flash()).unwrap();
+        db.execute("CREATE TABLE imu (id INT PRIMARY KEY, ax FLOAT, note TEXT)")
+            .unwrap();
+        for id in 0..50 {
+            db.execute(&format!(
+                "INSERT INTO imu VALUES ({}, {}, 'sample')",
+                id,
+                id as f64 * 0.1
+            ))
+            .unwrap();
+        }
+        // A synchronous commit is durable but not padded to a page
+        db.execute("INSERT /*+ SYNC_COMMIT */ INTO imu VALUES (50, 5.0, 'sync')")
+            .unwrap();
+        let len = std::fs::metadata(&wal).unwrap().len();
+        assert!(len > 0);
+        assert_ne!(len % 4096, 0);
+
+        // The periodic sync coalesces later commits and ends on a page boundary
+        for id in 51..56 {
+            db.execute(&format!("INSERT INTO imu VALUES ({}, 0.0, 'late')", id))
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+        let len = std::fs::metadata(&wal).unwrap().len();
+        assert_eq!(len % 4096, 0);
+        db.close().unwrap();
+    }
+
+    let db = Database::open_with_config(&path, DBConfig::for_flash()).unwrap();
+    assert_eq!(count(&db, "SELECT * FROM imu"), 56);
+    assert_eq!(count(&db, "SELECT * FROM imu WHERE note = 'sync'"), 1);
+}
+
+#[test]
+fn test_flash_geometry_validated() {
+    let dir = TempDir::new().unwrap();
+    for flash in [
+        FlashWriteConfig {
+            page_bytes: 100,
+            erase_block_bytes: 128 * 1024,
+        },
+        FlashWriteConfig {
+            page_bytes: 4096,
+            erase_block_bytes: 6000,
+        },
+    ] {
+        let config = DBConfig {
+            flash_write: Some(flash),
+            ..DBConfig::for_flash()
+        };
+        assert!(Database::create_with_config(dir.path().join("bad"), config).is_err());
+    }
+}
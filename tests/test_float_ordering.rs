@@ -0,0 +1,127 @@
+//! Floats order totally: NaN after +inf (so last in ASC, first in DESC),
+//! -0.0 equal to 0.0, in ORDER BY, MIN/MAX, WHERE and index range scans
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    rows(db, sql)
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref v => panic!("unexpected {:?}", v),
+        })
+        .collect()
+}
+
+fn sorted_ids(db: &Database, sql: &str) -> Vec<i64> {
+    let mut ids = ids(db, sql);
+    ids.sort();
+    ids
+}
+
+fn insert(db: &Database, id: i64, x: f64) {
+    db.execute_prepared(
+        "INSERT INTO t VALUES (?, ?, ?)",
+        vec![
+            Value::Integer(id),
+            Value::Float(x),
+            Value::text_from(if id % 2 == 1 { "a" } else { "b" }),
+        ],
+    )
+    .unwrap();
+}
+
+/// Ids 1, 2, 3 flushed; 4, 5, 6 in the memtable. Odd ids are in group 'a'
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, x FLOAT, g TEXT)")
+        .unwrap();
+    insert(db, 1, 2.5);
+    insert(db, 2, f64::NAN);
+    insert(db, 3, f64::INFINITY);
+    db.flush().unwrap();
+    insert(db, 4, f64::NEG_INFINITY);
+    insert(db, 5, -0.0);
+    insert(db, 6, 10.0);
+}
+
+fn check(db: &Database) {
+    assert_eq!(
+        ids(db, "SELECT id FROM t ORDER BY x"),
+        vec![4, 5, 1, 6, 3, 2]
+    );
+    assert_eq!(
+        ids(db, "SELECT id FROM t ORDER BY x DESC"),
+        vec![2, 3, 6, 1, 5, 4]
+    );
+    assert_eq!(ids(db, "SELECT id FROM t ORDER BY x LIMIT 2"), vec![4, 5]);
+    assert_eq!(
+        ids(db, "SELECT id FROM t ORDER BY x DESC LIMIT 2"),
+        vec![2, 3]
+    );
+
+    let agg = rows(db, "SELECT MIN(x), MAX(x) FROM t");
+    assert_eq!(agg[0][0], Value::Float(f64::NEG_INFINITY));
+    assert!(matches!(agg[0][1], Value::Float(f) if f.is_nan()));
+    let agg = rows(db, "SELECT MIN(x), MAX(x), MAX(id) FROM t");
+    assert_eq!(agg[0][0], Value::Float(f64::NEG_INFINITY));
+    assert!(matches!(agg[0][1], Value::Float(f) if f.is_nan()));
+    let mut groups = rows(db, "SELECT g, MIN(x), MAX(x) FROM t GROUP BY g");
+    groups.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap());
+    assert_eq!(
+        groups[0][1..],
+        [Value::Float(-0.0), Value::Float(f64::INFINITY)]
+    );
+    assert_eq!(groups[1][1], Value::Float(f64::NEG_INFINITY));
+    assert!(matches!(groups[1][2], Value::Float(f) if f.is_nan()));
+
+    assert_eq!(
+        sorted_ids(db, "SELECT id FROM t WHERE x > 5"),
+        vec![2, 3, 6]
+    );
+    assert_eq!(
+        sorted_ids(db, "SELECT id FROM t WHERE x >= 5.5"),
+        vec![2, 3, 6]
+    );
+    assert_eq!(
+        sorted_ids(db, "SELECT id FROM t WHERE x < 5"),
+        vec![1, 4, 5]
+    );
+    assert_eq!(sorted_ids(db, "SELECT id FROM t WHERE x = 0"), vec![5]);
+    assert_eq!(
+        sorted_ids(db, "SELECT id FROM t WHERE x BETWEEN 0 AND 100"),
+        vec![1, 5, 6]
+    );
+}
+
+#[test]
+fn test_nan_and_infinities_in_scans() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    check(&db);
+}
+
+#[test]
+fn test_nan_and_infinities_through_index() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    db.execute("CREATE INDEX idx_x ON t(x)").unwrap();
+    db.wait_for_indexes_ready();
+    check(&db);
+    assert_eq!(
+        db.query_by_column("t", "x", &Value::Float(f64::NAN))
+            .unwrap()
+            .len(),
+        1
+    );
+}
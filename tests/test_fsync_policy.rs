@@ -0,0 +1,63 @@
+//! Per-component fsync policy (DBConfig::fsync)
+
+use motedb::{DBConfig, Database, FsyncConfig, FsyncPolicy};
+use tempfile::TempDir;
+
+fn relaxed() -> DBConfig {
+    DBConfig {
+        fsync: FsyncConfig {
+            wal: FsyncPolicy::Always,
+            sstable: FsyncPolicy::Interval { interval_ms: 50 },
+            blob: FsyncPolicy::Never,
+            index: FsyncPolicy::Never,
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_relaxed_components_keep_data_across_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+
+    let db = Database::create_with_config(&path, relaxed()).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, cat INT, body TEXT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_cat ON docs(cat)").unwrap();
+    let big = "x".repeat(32 * 1024);
+    for i in 0..100 {
+        let body = if i == 0 { big.as_str() } else { "small" };
+        db.execute(&format!(
+            "INSERT INTO docs VALUES ({}, {}, '{}')",
+            i,
+            i % 5,
+            body
+        ))
+        .unwrap();
+    }
+    db.flush().unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+
+    let db = Database::open_with_config(&path, relaxed()).unwrap();
+    assert_eq!(db.query("SELECT id FROM docs").unwrap().len(), 100);
+    assert_eq!(
+        db.query("SELECT id FROM docs WHERE cat = 2").unwrap().len(),
+        20
+    );
+    let rows = db.query("SELECT body FROM docs WHERE id = 0").unwrap();
+    assert_eq!(rows.len(), 1);
+    db.close().unwrap();
+}
+
+#[test]
+fn test_zero_interval_is_rejected() {
+    let config = DBConfig {
+        fsync: FsyncConfig {
+            blob: FsyncPolicy::Interval { interval_ms: 0 },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
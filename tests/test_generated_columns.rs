@@ -0,0 +1,147 @@
+//! Generated columns: STORED maintenance and indexing, VIRTUAL evaluation
+
+use motedb::types::Value;
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(path: &std::path::Path) -> Database {
+    let db = Database::create(path).unwrap();
+    db.execute("CREATE TABLE imu (id INT PRIMARY KEY, speed_ms FLOAT)")
+        .unwrap();
+    for i in 0..5 {
+        db.execute(&format!("INSERT INTO imu VALUES ({}, {}.5)", i, i))
+            .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_stored_generated_column() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = setup(&path);
+        db.execute("ALTER TABLE imu ADD COLUMN speed_kmh AS (speed_ms * 3.6) STORED")
+            .unwrap();
+        // Existing rows are backfilled, new ones computed on write
+        db.execute("INSERT INTO imu VALUES (5, 10.0)").unwrap();
+        db.execute("INSERT INTO imu (id, speed_ms) VALUES (6, 2.5), (7, 0.5)")
+            .unwrap();
+        db.execute("UPDATE imu SET speed_ms = 20.5 WHERE id = 0")
+            .unwrap();
+
+        let r = rows(
+            db.execute("SELECT id, speed_kmh FROM imu ORDER BY id")
+                .unwrap(),
+        );
+        let kmh: Vec<Value> = r.iter().map(|r| r[1].clone()).collect();
+        let expected: Vec<Value> = [20.5, 1.5, 2.5, 3.5, 4.5, 10.0, 2.5, 0.5]
+            .iter()
+            .map(|v| Value::Float(v * 3.6))
+            .collect();
+        assert_eq!(kmh, expected);
+
+        db.execute("CREATE INDEX idx_kmh ON imu(speed_kmh)")
+            .unwrap();
+        db.wait_for_indexes_ready();
+        let r = rows(
+            db.execute("SELECT id FROM imu WHERE speed_kmh = 9.0 ORDER BY id")
+                .unwrap(),
+        );
+        assert_eq!(r, vec![vec![Value::Integer(2)], vec![Value::Integer(6)]]);
+
+        assert!(db
+            .execute("INSERT INTO imu (id, speed_kmh) VALUES (8, 1.0)")
+            .is_err());
+        assert!(db
+            .execute("UPDATE imu SET speed_kmh = 1.0 WHERE id = 1")
+            .is_err());
+        db.flush().unwrap();
+        db.close().unwrap();
+    }
+
+    // The definition survives a reopen and keeps maintaining the column
+    let db = Database::open(&path).unwrap();
+    db.execute("INSERT INTO imu VALUES (9, 1.0)").unwrap();
+    let r = rows(
+        db.execute("SELECT speed_kmh FROM imu WHERE id = 9")
+            .unwrap(),
+    );
+    assert_eq!(r, vec![vec![Value::Float(3.6)]]);
+}
+
+#[test]
+fn test_virtual_generated_column() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("test.mote"));
+    db.execute(
+        "ALTER TABLE imu ADD COLUMN moving BOOLEAN GENERATED ALWAYS AS (speed_ms > 2.0) VIRTUAL",
+    )
+    .unwrap();
+    db.execute("INSERT INTO imu VALUES (5, 0.5)").unwrap();
+
+    let r = rows(
+        db.execute("SELECT id, moving FROM imu ORDER BY id")
+            .unwrap(),
+    );
+    let moving: Vec<Value> = r.iter().map(|r| r[1].clone()).collect();
+    assert_eq!(
+        moving,
+        [false, false, true, true, true, false]
+            .into_iter()
+            .map(Value::Bool)
+            .collect::<Vec<_>>()
+    );
+
+    let r = rows(
+        db.execute("SELECT COUNT(*) FROM imu AS i WHERE i.moving")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Integer(3));
+
+    // UPDATE and DELETE filters see the computed value too
+    db.execute("UPDATE imu SET speed_ms = 9.5 WHERE moving = FALSE AND id < 2")
+        .unwrap();
+    db.execute("DELETE FROM imu WHERE NOT moving").unwrap();
+    let r = rows(db.execute("SELECT id FROM imu ORDER BY id").unwrap());
+    assert_eq!(r.len(), 5);
+
+    assert!(db
+        .execute("CREATE INDEX idx_moving ON imu(moving)")
+        .is_err());
+}
+
+#[test]
+fn test_generated_column_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("test.mote"));
+
+    for sql in [
+        "ALTER TABLE imu ADD COLUMN g AS (missing * 2)",
+        "ALTER TABLE imu ADD COLUMN g AS (SUM(speed_ms)) STORED",
+        "ALTER TABLE imu ADD COLUMN g AS (?)",
+        "ALTER TABLE imu ADD COLUMN g AS (UPPER('x'))",
+        "ALTER TABLE imu ADD COLUMN g AS speed_ms",
+    ] {
+        assert!(db.execute(sql).is_err(), "{}", sql);
+    }
+
+    db.execute("ALTER TABLE imu ADD COLUMN v AS (speed_ms + 1.0)")
+        .unwrap();
+    assert!(db
+        .execute("ALTER TABLE imu ADD COLUMN w AS (v * 2.0) STORED")
+        .is_err());
+    // An explicit type makes any expression acceptable
+    db.execute("ALTER TABLE imu ADD COLUMN tag TEXT AS (UPPER('x')) STORED")
+        .unwrap();
+    let r = rows(db.execute("SELECT v, tag FROM imu WHERE id = 1").unwrap());
+    assert_eq!(r, vec![vec![Value::Float(2.5), Value::text_from("X")]]);
+}
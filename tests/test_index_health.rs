@@ -0,0 +1,105 @@
+//! `db.index_health()`: per-index write amplification, buffered updates,
+//! tombstone/fragmentation ratios and last rebuild time
+
+use motedb::database::IndexType;
+use motedb::types::{Tensor, Value};
+use motedb::{Database, IndexHealth};
+use tempfile::TempDir;
+
+fn setup(path: &std::path::Path) -> Database {
+    let db = Database::create(path).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, cat INT, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    for i in 0..100i64 {
+        db.insert_row(
+            "docs",
+            vec![
+                Value::Integer(i),
+                Value::Integer(i % 5),
+                Value::Text(format!("document number {}", i).into()),
+                Value::tensor(Tensor::new(vec![i as f32, 1.0, 0.0, 0.0])),
+            ],
+        )
+        .unwrap();
+    }
+    db.execute("CREATE INDEX docs_cat ON docs (cat)").unwrap();
+    db.execute("CREATE TEXT INDEX docs_body ON docs (body)")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX docs_emb ON docs (emb)")
+        .unwrap();
+    db.flush().unwrap();
+    db
+}
+
+fn health_of(db: &Database, name: &str) -> IndexHealth {
+    db.index_health()
+        .unwrap()
+        .into_iter()
+        .find(|h| h.name == name)
+        .unwrap_or_else(|| panic!("no health entry for {}", name))
+}
+
+#[test]
+fn test_index_health_reports_every_index() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+
+    let health = db.index_health().unwrap();
+    let names: Vec<&str> = health.iter().map(|h| h.name.as_str()).collect();
+    assert_eq!(names, vec!["docs_body", "docs_cat", "docs_emb"]);
+
+    for h in &health {
+        assert_eq!(h.table_name, "docs");
+        assert!(!h.stale);
+        assert!(h.last_rebuilt_at.is_none());
+        assert!(h.bytes_indexed > 0, "{:?}", h);
+        assert!(h.bytes_written > 0, "{:?}", h);
+        assert!(h.write_amplification.unwrap() > 0.0, "{:?}", h);
+    }
+
+    let emb = health_of(&db, "docs_emb");
+    assert_eq!(emb.index_type, IndexType::Vector);
+    assert_eq!(emb.entries, 100);
+    assert!(emb.fragmentation.is_some());
+    assert_eq!(health_of(&db, "docs_body").entries, 100);
+    assert!(health_of(&db, "docs_cat").fragmentation.is_none());
+}
+
+#[test]
+fn test_index_health_tracks_deletes_and_rebuilds() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+    db.execute("DELETE FROM docs WHERE id >= 60").unwrap();
+
+    let body = health_of(&db, "docs_body");
+    assert_eq!(body.tombstones, 40);
+    assert!((body.tombstone_ratio - 0.4).abs() < 1e-9, "{:?}", body);
+
+    let emb = health_of(&db, "docs_emb");
+    assert_eq!(emb.entries, 60);
+    assert_eq!(emb.tombstones, 40);
+    assert!(emb.fragmentation.unwrap() > 0.0, "{:?}", emb);
+
+    db.execute("REINDEX docs_emb").unwrap();
+    db.execute("REINDEX docs_body").unwrap();
+    let emb = health_of(&db, "docs_emb");
+    assert_eq!(emb.entries, 60);
+    assert_eq!(emb.tombstones, 0);
+    assert!(emb.last_rebuilt_at.is_some());
+    let body = health_of(&db, "docs_body");
+    assert_eq!(body.tombstones, 0);
+    assert!(body.last_rebuilt_at.is_some());
+    assert!(health_of(&db, "docs_cat").last_rebuilt_at.is_none());
+}
+
+#[test]
+fn test_index_health_counts_buffered_updates() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+    let before = health_of(&db, "docs_cat").pending_updates;
+
+    db.execute("INSERT INTO docs (id, cat, body) VALUES (1000, 3, 'late arrival')")
+        .unwrap();
+    assert_eq!(health_of(&db, "docs_cat").pending_updates, before + 1);
+    assert!(health_of(&db, "docs_body").pending_updates >= 1);
+}
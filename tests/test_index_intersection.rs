@@ -0,0 +1,182 @@
+//! AND-ed predicates on two indexed columns — points or ranges — are
+//! answered by intersecting the row IDs of both indexes
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn ids(db: &Database, sql: &str, params: Vec<Value>) -> Vec<i64> {
+    let result = if params.is_empty() {
+        db.execute(sql)
+    } else {
+        db.execute_prepared(sql, params)
+    };
+    let mut ids: Vec<i64> = match result.unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .into_iter()
+            .map(|r| match r[0] {
+                Value::Integer(i) => i,
+                ref v => panic!("unexpected {:?}", v),
+            })
+            .collect(),
+        _ => panic!("Expected Select result"),
+    };
+    ids.sort();
+    ids
+}
+
+/// Ids 0..2000 with status = 's{id % 40}', ts = id * 1000, level = id % 9;
+/// the first half flushed, indexes on all three
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE logs (id INT PRIMARY KEY, status TEXT, ts INT, level INT)")
+        .unwrap();
+    for id in 0..2000 {
+        db.execute(&format!(
+            "INSERT INTO logs VALUES ({}, 's{}', {}, {})",
+            id,
+            id % 40,
+            id * 1000,
+            id % 9
+        ))
+        .unwrap();
+        if id == 999 {
+            db.flush().unwrap();
+        }
+    }
+    db.execute("CREATE INDEX logs_status ON logs(status)")
+        .unwrap();
+    db.execute("CREATE INDEX logs_ts ON logs(ts)").unwrap();
+    db.execute("CREATE INDEX logs_level ON logs(level)")
+        .unwrap();
+    db.wait_for_indexes_ready();
+}
+
+fn expected(pred: impl Fn(i64, i64, i64) -> bool) -> Vec<i64> {
+    (0..2000)
+        .filter(|id| pred(id % 40, id * 1000, id % 9))
+        .collect()
+}
+
+#[test]
+fn test_point_and_range_intersection() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    // Teach the optimizer how many rows one status has
+    assert_eq!(
+        ids(&db, "SELECT id FROM logs WHERE status = 's3'", vec![]),
+        expected(|s, _, _| s == 3)
+    );
+
+    let in_window = expected(|s, ts, _| s == 3 && (100_000..=900_000).contains(&ts));
+    for sql in [
+        "SELECT id FROM logs WHERE status = 's3' AND ts BETWEEN 100000 AND 900000",
+        "SELECT id FROM logs WHERE ts >= 100000 AND ts <= 900000 AND status = 's3'",
+        "SELECT id FROM logs WHERE 100000 <= ts AND status = 's3' AND 900000 >= ts",
+    ] {
+        assert_eq!(ids(&db, sql, vec![]), in_window, "{}", sql);
+    }
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM logs WHERE status = ? AND ts BETWEEN ? AND ?",
+            vec![
+                Value::text_from("s3"),
+                Value::Integer(100_000),
+                Value::Integer(900_000)
+            ]
+        ),
+        in_window
+    );
+
+    // One-sided ranges, range with range, and residual predicates
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM logs WHERE status = 's7' AND ts > 1500000",
+            vec![]
+        ),
+        expected(|s, ts, _| s == 7 && ts > 1_500_000)
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM logs WHERE level BETWEEN 2 AND 3 AND ts < 300000",
+            vec![]
+        ),
+        expected(|_, ts, l| (2..=3).contains(&l) && ts < 300_000)
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM logs WHERE status = 's3' AND level = 3 AND ts <= 1000000",
+            vec![]
+        ),
+        expected(|s, ts, l| s == 3 && l == 3 && ts <= 1_000_000)
+    );
+    assert!(ids(
+        &db,
+        "SELECT id FROM logs WHERE status = 's3' AND ts BETWEEN 4000 AND 42000",
+        vec![]
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_delete_through_intersection() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    db.execute("DELETE FROM logs WHERE status = 's5' AND ts BETWEEN 0 AND 1000000")
+        .unwrap();
+    assert_eq!(
+        ids(&db, "SELECT id FROM logs WHERE status = 's5'", vec![]),
+        expected(|s, ts, _| s == 5 && ts > 1_000_000)
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM logs WHERE ts <= 1000000 AND level = 0",
+            vec![]
+        ),
+        expected(|s, ts, l| s != 5 && ts <= 1_000_000 && l == 0)
+    );
+}
+
+#[test]
+fn test_row_id_sets_through_api() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let mut ids = db
+        .query_by_column_set("logs", "status", &Value::text_from("s3"))
+        .unwrap();
+    assert_eq!(ids.len(), 50);
+    ids &= db
+        .query_by_column_between_set(
+            "logs",
+            "ts",
+            &Value::Integer(100_000),
+            true,
+            &Value::Integer(900_000),
+            true,
+        )
+        .unwrap();
+    let mut expected = db
+        .query_by_column_between(
+            "logs",
+            "ts",
+            &Value::Integer(100_000),
+            true,
+            &Value::Integer(900_000),
+            true,
+        )
+        .unwrap();
+    expected.retain(|id| ids.contains(*id));
+    expected.sort_unstable();
+    assert_eq!(ids.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(expected.len(), 20);
+}
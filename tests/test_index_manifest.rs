@@ -0,0 +1,310 @@
+//! Checkpoints commit every index's files through the Manifest; on open an
+//! index whose files are not the committed ones is rebuilt from table data
+//! in the background
+
+use motedb::types::{RowId, Tensor, Value};
+use motedb::{Database, QueryResult};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .into_iter()
+            .map(|row| match row[0] {
+                Value::Integer(id) => id,
+                ref v => panic!("expected INTEGER id, got {:?}", v),
+            })
+            .collect(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn rebuilt(db: &Database, name: &str) -> bool {
+    db.index_health()
+        .unwrap()
+        .into_iter()
+        .find(|h| h.name == name)
+        .unwrap()
+        .last_rebuilt_at
+        .is_some()
+}
+
+/// Unit vectors 0.06 rad apart (all within one turn)
+fn direction(i: i64) -> Vec<f32> {
+    let angle = i as f32 * 0.06;
+    vec![angle.cos(), angle.sin(), 0.0, 0.0]
+}
+
+fn setup(path: &Path) -> Database {
+    let db = Database::create(path).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, cat INT, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    for i in 0..100i64 {
+        let body = if i % 10 == 0 {
+            "rust storage"
+        } else {
+            "plain text"
+        };
+        db.insert_row(
+            "docs",
+            vec![
+                Value::Integer(i),
+                Value::Integer(i % 7),
+                Value::Text(body.into()),
+                Value::tensor(Tensor::new(direction(i))),
+            ],
+        )
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_cat ON docs (cat)").unwrap();
+    db.execute("CREATE TEXT INDEX idx_body ON docs (body)")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX docs_emb ON docs (emb)")
+        .unwrap();
+    db
+}
+
+/// Copy a database directory as a crash would leave it (minus the lock)
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else if entry.file_name() != ".lock" {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+fn check_queries(db: &Database) {
+    assert_eq!(
+        ids(db, "SELECT id FROM docs WHERE cat = 3 ORDER BY id"),
+        (0..100).filter(|i| i % 7 == 3).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        ids(
+            db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        ),
+        (0..100).step_by(10).collect::<Vec<_>>()
+    );
+}
+
+fn nearest(db: &Database) -> Vec<(RowId, f32)> {
+    db.vector_search("docs_emb", &direction(42), 5).unwrap()
+}
+
+#[test]
+fn test_clean_close_publishes_every_index() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    let hits = nearest(&db);
+    db.close().unwrap();
+    drop(db);
+
+    let mote = path.with_extension("mote");
+    assert!(mote.join("CURRENT").exists());
+
+    let db = Database::open(&path).unwrap();
+    for name in ["idx_cat", "idx_body", "docs_emb"] {
+        assert!(!rebuilt(&db, name), "{} was rebuilt", name);
+    }
+    check_queries(&db);
+    assert_eq!(nearest(&db), hits);
+}
+
+#[test]
+fn test_index_torn_after_commit_is_rebuilt() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    let hits = nearest(&db);
+    db.close().unwrap();
+    drop(db);
+
+    // A crash between flushing indexes and committing the next checkpoint:
+    // the files no longer match the committed version
+    let indexes = path.with_extension("mote").join("indexes");
+    let column = indexes.join("column_idx_cat.idx");
+    let mut bytes = std::fs::read(&column).unwrap();
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0xFF;
+    std::fs::write(&column, bytes).unwrap();
+    let postings = indexes.join("text_idx_body.fts.d").join("postings.gbtree");
+    let len = std::fs::metadata(&postings).unwrap().len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&postings)
+        .unwrap()
+        .set_len(len / 2)
+        .unwrap();
+
+    let db = Database::open(&path).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    assert!(rebuilt(&db, "idx_cat"));
+    assert!(rebuilt(&db, "idx_body"));
+    assert!(!rebuilt(&db, "docs_emb"));
+    check_queries(&db);
+    assert_eq!(nearest(&db), hits);
+
+    // The rebuilt files are committed by the next checkpoint
+    db.close().unwrap();
+    drop(db);
+    let db = Database::open(&path).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    let stale = db
+        .index_health()
+        .unwrap()
+        .into_iter()
+        .filter(|h| h.stale)
+        .count();
+    assert_eq!(stale, 0);
+    check_queries(&db);
+}
+
+#[test]
+fn test_index_never_checkpointed_is_rebuilt_after_crash() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    let hits = nearest(&db);
+    db.close().unwrap();
+    drop(db);
+
+    // Created after the last checkpoint; the process dies before the next
+    // one, leaving the directory as it is at that moment
+    let db = Database::open(&path).unwrap();
+    db.execute("CREATE INDEX idx_id ON docs (id)").unwrap();
+    let crashed = dir.path().join("crashed");
+    copy_dir(
+        &path.with_extension("mote"),
+        &crashed.with_extension("mote"),
+    );
+    drop(db);
+
+    let db = Database::open(&crashed).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    assert!(rebuilt(&db, "idx_id"));
+    assert!(!rebuilt(&db, "idx_cat"));
+    assert_eq!(ids(&db, "SELECT id FROM docs WHERE id = 57"), vec![57]);
+    check_queries(&db);
+    assert_eq!(nearest(&db), hits);
+}
+
+#[test]
+fn test_vector_index_recovers_in_place_after_crash() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.close().unwrap();
+    drop(db);
+
+    // Vector index writes after the last checkpoint go through its own log;
+    // the process dies before the next checkpoint
+    let db = Database::open(&path).unwrap();
+    db.execute("DELETE FROM docs WHERE id = 42").unwrap();
+    db.execute("UPDATE docs SET emb = [0.0, 0.0, 1.0, 0.0] WHERE id = 7")
+        .unwrap();
+    let hits = nearest(&db);
+    assert!(hits.iter().all(|&(id, _)| id != 42));
+    let crashed = dir.path().join("crashed");
+    copy_dir(
+        &path.with_extension("mote"),
+        &crashed.with_extension("mote"),
+    );
+    drop(db);
+
+    // Replayed on load instead of discarded and rebuilt
+    let db = Database::open(&crashed).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    assert!(!rebuilt(&db, "docs_emb"));
+    assert_eq!(nearest(&db), hits);
+    let moved = db
+        .vector_search("docs_emb", &[0.0, 0.0, 1.0, 0.0], 1)
+        .unwrap();
+    assert!(moved[0].1 < 1e-3);
+    check_queries(&db);
+}
+
+fn insert_rust_docs(db: &Database, ids: std::ops::Range<i64>) {
+    for i in ids {
+        db.insert_row(
+            "docs",
+            vec![
+                Value::Integer(i),
+                Value::Integer(i % 7),
+                Value::Text("rust storage".into()),
+                Value::tensor(Tensor::new(direction(i))),
+            ],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_text_index_checkpointed_documents_survive_crash() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.close().unwrap();
+    drop(db);
+
+    // Documents indexed since the last checkpoint are published by the next
+    let db = Database::open(&path).unwrap();
+    insert_rust_docs(&db, 100..120);
+    db.checkpoint().unwrap();
+    let crashed = dir.path().join("crashed");
+    copy_dir(
+        &path.with_extension("mote"),
+        &crashed.with_extension("mote"),
+    );
+    drop(db);
+
+    let db = Database::open(&crashed).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    assert!(!rebuilt(&db, "idx_body"));
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        ),
+        (0..100).step_by(10).chain(100..120).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_text_index_is_rebuilt_for_replayed_rows() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.close().unwrap();
+    drop(db);
+
+    // The rows are durable (flushed, and in the WAL) but their documents
+    // were only indexed in memory when the process dies
+    let db = Database::open(&path).unwrap();
+    insert_rust_docs(&db, 100..120);
+    db.flush().unwrap();
+    let crashed = dir.path().join("crashed");
+    copy_dir(
+        &path.with_extension("mote"),
+        &crashed.with_extension("mote"),
+    );
+    drop(db);
+
+    let db = Database::open(&crashed).unwrap();
+    assert!(db.wait_for_indexes_ready());
+    assert!(rebuilt(&db, "idx_body"));
+    assert!(!rebuilt(&db, "idx_cat"));
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        ),
+        (0..100).step_by(10).chain(100..120).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,122 @@
+//! ORDER BY an indexed column served by walking the index (or the primary
+//! key range) in order, ascending or descending, instead of sorting
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+/// The same 400 rows in `indexed` (column indexes on score, ts and opt)
+/// and `plain` (no secondary indexes). Scores are unique and straddle
+/// zero; `opt` is nullable. Part of the data is flushed, a few rows are
+/// deleted afterwards.
+fn setup(db: &Database) {
+    for table in ["indexed", "plain"] {
+        db.execute(&format!(
+            "CREATE TABLE {} (id INT PRIMARY KEY, score INT NOT NULL, \
+             ts TIMESTAMP NOT NULL, opt INT, note TEXT)",
+            table
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_score ON indexed (score)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_ts ON indexed (ts)").unwrap();
+    db.execute("CREATE INDEX idx_opt ON indexed (opt)").unwrap();
+    for i in 0..400i64 {
+        if i == 200 {
+            db.flush().unwrap();
+        }
+        let opt = if i % 5 == 0 {
+            "NULL".to_string()
+        } else {
+            (i % 17).to_string()
+        };
+        for table in ["indexed", "plain"] {
+            db.execute(&format!(
+                "INSERT INTO {} VALUES ({}, {}, {}, {}, 'n{}')",
+                table,
+                i,
+                (i * 919) % 1000 - 500,
+                1_700_000_000_000_000 + i * 1_000_000,
+                opt,
+                i
+            ))
+            .unwrap();
+        }
+    }
+    for table in ["indexed", "plain"] {
+        db.execute(&format!(
+            "DELETE FROM {} WHERE id = 260 OR id = 7 OR id = 150",
+            table
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_order_by_index_matches_sort() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    for clause in [
+        "ORDER BY id DESC LIMIT 5",
+        "ORDER BY id LIMIT 5 OFFSET 3",
+        "ORDER BY score DESC LIMIT 10",
+        "ORDER BY score LIMIT 10 OFFSET 4",
+        "ORDER BY ts DESC LIMIT 3",
+        "ORDER BY opt DESC, id LIMIT 8",
+        "WHERE score BETWEEN -40 AND 60 ORDER BY score DESC LIMIT 6",
+        "WHERE score > -300 AND score <= 200 ORDER BY score LIMIT 6 OFFSET 2",
+        "WHERE score < 0 AND score >= -100 ORDER BY score DESC",
+        "WHERE id > 100 AND id <= 250 ORDER BY id DESC LIMIT 5",
+        "WHERE id >= 390 ORDER BY id DESC",
+        "WHERE id < 20 ORDER BY id DESC LIMIT 4 OFFSET 2",
+    ] {
+        let indexed = rows(&db, &format!("SELECT id, score FROM indexed {}", clause));
+        let plain = rows(&db, &format!("SELECT id, score FROM plain {}", clause));
+        assert!(!plain.is_empty(), "{}", clause);
+        assert_eq!(indexed, plain, "{}", clause);
+    }
+}
+
+#[test]
+fn test_latest_n_by_primary_key_and_timestamp() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let ids =
+        |sql: &str| -> Vec<Value> { rows(&db, sql).into_iter().map(|r| r[0].clone()).collect() };
+    let expected: Vec<Value> = [399, 398, 397, 396, 395]
+        .into_iter()
+        .map(Value::Integer)
+        .collect();
+    assert_eq!(
+        ids("SELECT id FROM indexed ORDER BY id DESC LIMIT 5"),
+        expected
+    );
+    assert_eq!(
+        ids("SELECT id FROM indexed ORDER BY ts DESC LIMIT 5"),
+        expected
+    );
+    assert_eq!(
+        ids("SELECT id FROM indexed WHERE id > 300 ORDER BY id DESC LIMIT 5"),
+        expected
+    );
+
+    // Rows written after the query plan was first used show up on top
+    db.execute("INSERT INTO indexed VALUES (1000, 999, 1800000000000000, 1, 'new')")
+        .unwrap();
+    assert_eq!(
+        ids("SELECT id FROM indexed ORDER BY ts DESC LIMIT 2"),
+        vec![Value::Integer(1000), Value::Integer(399)]
+    );
+}
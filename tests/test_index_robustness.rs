@@ -338,6 +338,125 @@ fn test_drop_spatial_index() {
     assert_eq!(result.len(), 5, "Table data should survive DROP INDEX");
 }
 
+/// Files under `indexes/` whose name mentions `name`
+fn index_files_named(dir: &TempDir, name: &str) -> Vec<String> {
+    std::fs::read_dir(dir.path().with_extension("mote").join("indexes"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|file| file.contains(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_drop_index_deletes_files_across_reopen() {
+    let (db, dir) = create_db();
+    exec(
+        &db,
+        "CREATE TABLE notes (id INTEGER PRIMARY KEY, tag TEXT, body TEXT, v VECTOR(2))",
+    );
+    exec(&db, "CREATE INDEX notes_tag ON notes(tag)");
+    exec(&db, "CREATE TEXT INDEX notes_body ON notes(body)");
+    exec(&db, "CREATE VECTOR INDEX notes_v ON notes(v)");
+    for i in 1..=5i64 {
+        exec(
+            &db,
+            &format!(
+                "INSERT INTO notes VALUES ({}, 't{}', 'note {}', [{}, 1.0])",
+                i, i, i, i
+            ),
+        );
+    }
+    db.checkpoint().expect("checkpoint");
+    db.wait_for_indexes_ready();
+    for name in ["notes_tag", "notes_body", "notes_v"] {
+        assert!(
+            !index_files_named(&dir, name).is_empty(),
+            "{} has files",
+            name
+        );
+    }
+
+    exec(&db, "DROP INDEX notes_tag ON notes");
+    exec(&db, "DROP INDEX notes_body ON notes");
+    db.drop_index("notes_v").expect("drop_index");
+    for name in ["notes_tag", "notes_body", "notes_v"] {
+        assert!(
+            index_files_named(&dir, name).is_empty(),
+            "{} has files",
+            name
+        );
+    }
+    assert!(db.drop_index("notes_v").is_err());
+    db.close().expect("close");
+
+    let db = Database::open(dir.path()).expect("reopen");
+    db.wait_for_indexes_ready();
+    for name in ["notes_tag", "notes_body", "notes_v"] {
+        assert!(
+            index_files_named(&dir, name).is_empty(),
+            "{} has files",
+            name
+        );
+    }
+    assert!(db.vector_search("notes_v", &[1.0, 1.0], 1).is_err());
+    assert_eq!(rows(&db, "SELECT * FROM notes WHERE tag = 't3'").len(), 1);
+
+    // The names are free again
+    exec(&db, "CREATE TEXT INDEX notes_body ON notes(body)");
+    db.wait_for_indexes_ready();
+    assert_eq!(
+        rows(&db, "SELECT id FROM notes WHERE MATCH(body, 'note')").len(),
+        5
+    );
+}
+
+#[test]
+fn test_drop_table_deletes_index_files() {
+    let (db, dir) = create_db();
+    exec(
+        &db,
+        "CREATE TABLE pings (id INTEGER PRIMARY KEY, host TEXT, v VECTOR(2))",
+    );
+    exec(&db, "CREATE INDEX pings_host ON pings(host)");
+    exec(&db, "CREATE VECTOR INDEX pings_v ON pings(v)");
+    for i in 1..=5i64 {
+        exec(
+            &db,
+            &format!("INSERT INTO pings VALUES ({}, 'h{}', [{}, 0.0])", i, i, i),
+        );
+    }
+    db.checkpoint().expect("checkpoint");
+    db.wait_for_indexes_ready();
+    assert!(!index_files_named(&dir, "pings_host").is_empty());
+    assert!(!index_files_named(&dir, "pings_v").is_empty());
+
+    exec(&db, "DROP TABLE pings");
+    assert!(index_files_named(&dir, "pings").is_empty());
+    assert!(db.drop_index("pings_host").is_err());
+
+    // A same-named table starts without rows or indexes, also after reopen
+    exec(
+        &db,
+        "CREATE TABLE pings (id INTEGER PRIMARY KEY, host TEXT, v VECTOR(2))",
+    );
+    exec(&db, "CREATE VECTOR INDEX pings_v ON pings(v)");
+    exec(&db, "INSERT INTO pings VALUES (9, 'h9', [9.0, 0.0])");
+    db.close().expect("close");
+
+    let db = Database::open(dir.path()).expect("reopen");
+    db.wait_for_indexes_ready();
+    assert_eq!(rows(&db, "SELECT * FROM pings").len(), 1);
+    assert_eq!(rows(&db, "SELECT * FROM pings WHERE host = 'h1'").len(), 0);
+    let hits = db
+        .vector_search("pings_v", &[1.0, 0.0], 5)
+        .expect("vector search");
+    assert_eq!(hits.len(), 1);
+}
+
 // ============================================================================
 // 4. Multiple indexes on same table
 // ============================================================================
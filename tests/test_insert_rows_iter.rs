@@ -0,0 +1,159 @@
+//! Streaming inserts from a Rust iterator (db.insert_rows_iter)
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE telemetry (id INT PRIMARY KEY, sensor INT, reading FLOAT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_sensor ON telemetry (sensor)")
+        .unwrap();
+}
+
+fn reading(i: i64) -> Vec<Value> {
+    vec![
+        Value::Integer(i),
+        Value::Integer(i % 7),
+        Value::Float(i as f64 * 0.5),
+    ]
+}
+
+#[test]
+fn test_insert_rows_iter_streams_and_indexes() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let n = db
+        .insert_rows_iter("telemetry", (0..10_000).map(reading))
+        .unwrap();
+    assert_eq!(n, 10_000);
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry"),
+        vec![vec![Value::Integer(10_000)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry WHERE sensor = 3"),
+        vec![vec![Value::Integer(1_429)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT reading FROM telemetry WHERE id = 9999"),
+        vec![vec![Value::Float(4999.5)]]
+    );
+
+    // Each chunk was added to the index; nothing was rebuilt
+    let health = db.index_health().unwrap();
+    let idx = health.iter().find(|h| h.name == "idx_sensor").unwrap();
+    assert!(!idx.stale);
+    assert!(idx.last_rebuilt_at.is_none());
+
+    assert_eq!(
+        db.insert_rows_iter("telemetry", std::iter::empty())
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_insert_rows_iter_indexes_each_chunk() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    // While the second chunk is being pulled, the first is already indexed
+    let mut seen_mid_ingest = None;
+    let source = (0..6_000).map(|i| {
+        if i == 5_000 {
+            seen_mid_ingest = Some(rows(&db, "SELECT COUNT(*) FROM telemetry WHERE sensor = 0"));
+        }
+        reading(i)
+    });
+    db.insert_rows_iter("telemetry", source).unwrap();
+    assert_eq!(seen_mid_ingest, Some(vec![vec![Value::Integer(586)]]));
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry WHERE sensor = 0"),
+        vec![vec![Value::Integer(858)]]
+    );
+}
+
+#[test]
+fn test_insert_rows_iter_indexes_auto_increment_tables() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE logs (id INT PRIMARY KEY AUTO_INCREMENT, body TEXT)")
+        .unwrap();
+    db.execute("CREATE TEXT INDEX idx_body ON logs (body)")
+        .unwrap();
+
+    let source = (0..5_000).map(|i| {
+        let body = if i % 100 == 0 {
+            "motor fault"
+        } else {
+            "nominal"
+        };
+        vec![Value::Null, Value::text(body.to_string())]
+    });
+    assert_eq!(db.insert_rows_iter("logs", source).unwrap(), 5_000);
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT id FROM logs WHERE MATCH(body) AGAINST('fault')"
+        )
+        .len(),
+        50
+    );
+}
+
+#[test]
+fn test_insert_rows_iter_keeps_batches_before_a_failure() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    // The second batch repeats id 0
+    let err = db
+        .insert_rows_iter("telemetry", (0..5_000).chain(0..1).map(reading))
+        .unwrap_err();
+    assert!(err.to_string().contains("duplicate primary key"), "{}", err);
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry"),
+        vec![vec![Value::Integer(4_096)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry WHERE sensor = 0"),
+        vec![vec![Value::Integer(586)]]
+    );
+}
+
+#[test]
+fn test_insert_rows_iter_survives_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup(&db);
+        db.insert_rows_iter("telemetry", (0..5_000).map(reading))
+            .unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM telemetry"),
+        vec![vec![Value::Integer(5_000)]]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "SELECT id FROM telemetry WHERE sensor = 6 AND id > 4990"
+        ),
+        vec![vec![Value::Integer(4_997)]]
+    );
+}
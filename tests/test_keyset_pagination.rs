@@ -0,0 +1,132 @@
+//! Keyset pagination: ORDER BY an indexed column, resumed with AFTER CURSOR
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+/// 300 events; scores repeat every 37 rows so pages split runs of ties
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE events (id INT PRIMARY KEY, score INT, kind TEXT)")
+        .unwrap();
+    for i in 0..300 {
+        db.execute(&format!(
+            "INSERT INTO events VALUES ({}, {}, '{}')",
+            i,
+            (i * 7) % 37,
+            if i % 3 == 0 { "a" } else { "b" }
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_score ON events (score)")
+        .unwrap();
+}
+
+/// Collect every page of `base LIMIT n`, following cursors to the end
+fn page_through(db: &Database, base: &str, n: usize) -> Vec<Vec<Value>> {
+    let mut all = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let sql = match &cursor {
+            Some(c) => format!("{} LIMIT {} AFTER CURSOR '{}'", base, n, c),
+            None => format!("{} LIMIT {}", base, n),
+        };
+        let page = db.query_page(&sql).unwrap();
+        assert!(page.rows.len() <= n);
+        all.extend(page.rows);
+        match page.next_cursor {
+            Some(c) => cursor = Some(c),
+            None => break,
+        }
+    }
+    all
+}
+
+#[test]
+fn test_keyset_pages_match_full_scan() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    for (base, expected_len) in [
+        ("SELECT score, id FROM events ORDER BY score", 300),
+        ("SELECT score, id FROM events ORDER BY score DESC", 300),
+        (
+            "SELECT score, id FROM events WHERE kind = 'a' ORDER BY score",
+            100,
+        ),
+        (
+            "SELECT score, id FROM events WHERE kind = 'b' ORDER BY score DESC",
+            200,
+        ),
+    ] {
+        let paged = page_through(&db, base, 25);
+        assert_eq!(paged.len(), expected_len, "{}", base);
+
+        // Same scores in the same order as a full ORDER BY, and no row twice
+        let full = rows(&db, base);
+        let scores = |r: &[Vec<Value>]| r.iter().map(|row| row[0].clone()).collect::<Vec<_>>();
+        assert_eq!(scores(&paged), scores(&full), "{}", base);
+        let mut ids: Vec<_> = paged.iter().map(|row| format!("{:?}", row[1])).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), expected_len, "{}", base);
+    }
+}
+
+#[test]
+fn test_keyset_cursor_survives_writes() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let first = db
+        .query_page("SELECT id FROM events ORDER BY score LIMIT 10")
+        .unwrap();
+    assert_eq!(first.rows.len(), 10);
+    let cursor = first.next_cursor.unwrap();
+
+    // Rows before the cursor don't shift the next page; new rows past it show up
+    db.execute("DELETE FROM events WHERE id = 0").unwrap();
+    db.execute("INSERT INTO events VALUES (1000, 36, 'a')")
+        .unwrap();
+    let rest = rows(
+        &db,
+        &format!(
+            "SELECT id FROM events ORDER BY score LIMIT 1000 AFTER CURSOR '{}'",
+            cursor
+        ),
+    );
+    assert_eq!(rest.len(), 291);
+    assert_eq!(rest.last(), Some(&vec![Value::Integer(1000)]));
+}
+
+#[test]
+fn test_keyset_pagination_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    for sql in [
+        // No index on kind
+        "SELECT * FROM events ORDER BY kind LIMIT 5",
+        // LIMIT and a single ORDER BY column are required
+        "SELECT * FROM events ORDER BY score",
+        "SELECT * FROM events ORDER BY score, id LIMIT 5",
+        "SELECT * FROM events ORDER BY score LIMIT 5 OFFSET 5",
+        "SELECT COUNT(*) FROM events ORDER BY score LIMIT 5",
+        "SELECT * FROM events ORDER BY score LIMIT 5 AFTER CURSOR 'not-a-cursor'",
+    ] {
+        assert!(db.query_page(sql).is_err(), "{}", sql);
+    }
+    assert!(db
+        .execute("SELECT * FROM events ORDER BY score LIMIT 5 AFTER CURSOR 'zz'")
+        .and_then(|r| r.materialize())
+        .is_err());
+}
@@ -0,0 +1,104 @@
+//! LATEST BY, with and without the grouping/timestamp index walk
+
+use motedb::types::Value;
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+/// 50 devices × 40 readings; reading `i` of device `d` has ts = i * 1000 + d
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, device TEXT, ts TIMESTAMP, v FLOAT)")
+        .unwrap();
+    let mut id = 0;
+    for i in 0..40 {
+        for d in 0..50 {
+            db.execute(&format!(
+                "INSERT INTO readings VALUES ({}, 'dev{:02}', {}, {}.0)",
+                id,
+                d,
+                i * 1000 + d,
+                i
+            ))
+            .unwrap();
+            id += 1;
+        }
+    }
+}
+
+const QUERIES: &[&str] = &[
+    "SELECT device, v FROM readings ORDER BY device LATEST BY device",
+    "SELECT device, ts FROM readings WHERE v < 10 ORDER BY device LATEST BY device",
+    "SELECT device, v FROM readings WHERE device = 'dev07' LATEST BY device",
+    "SELECT device, v FROM readings ORDER BY ts DESC LIMIT 3 LATEST BY device",
+    "SELECT COUNT(*) FROM readings LATEST BY device",
+];
+
+#[test]
+fn test_latest_by_index_matches_scan() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let scanned: Vec<_> = QUERIES.iter().map(|q| rows(&db, q)).collect();
+    assert_eq!(scanned[0].len(), 50);
+    assert_eq!(
+        scanned[0][0],
+        vec![Value::text("dev00".to_string()), Value::Float(39.0)]
+    );
+    assert_eq!(scanned[1][49][0], Value::text("dev49".to_string()));
+    assert_eq!(
+        scanned[2],
+        vec![vec![Value::text("dev07".to_string()), Value::Float(39.0)]]
+    );
+    assert_eq!(scanned[3].len(), 3);
+    assert_eq!(scanned[3][0][0], Value::text("dev49".to_string()));
+    assert_eq!(scanned[4], vec![vec![Value::Integer(50)]]);
+
+    db.execute("CREATE INDEX idx_device ON readings (device)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_ts ON readings (ts)").unwrap();
+    for (q, expected) in QUERIES.iter().zip(&scanned) {
+        assert_eq!(&rows(&db, q), expected, "{}", q);
+    }
+
+    // Later writes are picked up by both indexes
+    db.execute("INSERT INTO readings VALUES (5000, 'dev03', 100000, 99.0)")
+        .unwrap();
+    db.execute("INSERT INTO readings VALUES (5001, 'dev50', 1, 1.0)")
+        .unwrap();
+    db.execute("DELETE FROM readings WHERE id = 1999").unwrap();
+    let r = rows(&db, QUERIES[0]);
+    assert_eq!(r.len(), 51);
+    assert_eq!(
+        r[3],
+        vec![Value::text("dev03".to_string()), Value::Float(99.0)]
+    );
+    assert_eq!(
+        r[49],
+        vec![Value::text("dev49".to_string()), Value::Float(38.0)]
+    );
+    assert_eq!(
+        r[50],
+        vec![Value::text("dev50".to_string()), Value::Float(1.0)]
+    );
+}
+
+#[test]
+fn test_latest_by_requires_timestamp() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, device TEXT)")
+        .unwrap();
+    db.execute("INSERT INTO t VALUES (1, 'a')").unwrap();
+    assert!(db
+        .execute("SELECT * FROM t LATEST BY device")
+        .and_then(|r| r.materialize())
+        .is_err());
+}
@@ -0,0 +1,124 @@
+//! Hard memory cap: cache shedding, narrower vector search, best-effort rejection
+
+use motedb::{
+    DBConfig, Database, ErrorCode, MemoryLimitConfig, MemoryPressure, QueryResult, StorageError,
+};
+use tempfile::TempDir;
+
+fn open(dir: &TempDir, limit: Option<MemoryLimitConfig>) -> Database {
+    let config = DBConfig {
+        memory_limit: limit,
+        ..Default::default()
+    };
+    Database::create_with_config(dir.path().join("db"), config).unwrap()
+}
+
+fn row_count(db: &Database, sql: &str) -> usize {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows.len(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn load(db: &Database) {
+    db.execute("CREATE TABLE poses (id INT PRIMARY KEY, label TEXT, emb VECTOR(4))")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX idx_emb ON poses (emb)")
+        .unwrap();
+    for id in 0..100 {
+        db.execute(&format!(
+            "INSERT INTO poses VALUES ({}, 'p{}', [{}.0, 1.0, 0.5, 0.25])",
+            id, id, id
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_uncapped_by_default() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir, None);
+    assert!(db.memory_budget_stats().is_none());
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY)").unwrap();
+    assert_eq!(row_count(&db, "SELECT /*+ BEST_EFFORT */ * FROM t"), 0);
+}
+
+#[test]
+fn test_best_effort_rejected_at_cap() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir, Some(MemoryLimitConfig::new(1)));
+    load(&db);
+
+    // Critical: caches dropped, best-effort work shed with a specific error
+    let err = db
+        .execute("SELECT /*+ BEST_EFFORT */ * FROM poses")
+        .err()
+        .expect("best-effort query must be rejected at the cap");
+    assert!(matches!(err, StorageError::MemoryLimitExceeded(_)));
+    assert_eq!(err.code(), ErrorCode::MemoryLimitExceeded);
+    assert!(db
+        .execute_prepared(
+            "SELECT /*+ BEST_EFFORT */ * FROM poses WHERE id = ?",
+            vec![motedb::types::Value::Integer(1)]
+        )
+        .is_err());
+
+    // Everything else still runs
+    assert_eq!(row_count(&db, "SELECT * FROM poses"), 100);
+    db.execute("INSERT INTO poses VALUES (100, 'p100', [1.0, 1.0, 1.0, 1.0])")
+        .unwrap();
+
+    let stats = db.memory_budget_stats().unwrap();
+    assert_eq!(stats.pressure, MemoryPressure::Critical);
+    assert_eq!(stats.limit_bytes, 1);
+    assert!(stats.usage_bytes > 1);
+    assert!(stats.degradations >= 1);
+    assert_eq!(stats.rejected_statements, 2);
+}
+
+#[test]
+fn test_pressure_degrades_but_serves_queries() {
+    let dir = TempDir::new().unwrap();
+    // Far below the cap but above the pressure threshold
+    let limit = MemoryLimitConfig {
+        pressure_ratio: 1e-9,
+        degraded_search_width: 8,
+        ..MemoryLimitConfig::new(1 << 50)
+    };
+    let db = open(&dir, Some(limit));
+    load(&db);
+
+    let stats = db.memory_budget_stats().unwrap();
+    assert_eq!(stats.pressure, MemoryPressure::Pressure);
+    assert!(stats.degradations >= 1);
+
+    // Narrower DiskANN search and best-effort queries still answer
+    assert_eq!(
+        row_count(
+            &db,
+            "SELECT /*+ BEST_EFFORT */ id FROM poses ORDER BY emb <-> [3.0, 1.0, 0.5, 0.25] LIMIT 5"
+        ),
+        5
+    );
+    let row_cache = db.cache_stats().row_cache;
+    assert!(row_cache.size <= row_cache.capacity / 4);
+    assert_eq!(db.memory_budget_stats().unwrap().rejected_statements, 0);
+}
+
+#[test]
+fn test_invalid_memory_limit() {
+    let dir = TempDir::new().unwrap();
+    for limit in [
+        MemoryLimitConfig::new(0),
+        MemoryLimitConfig {
+            pressure_ratio: 1.5,
+            ..MemoryLimitConfig::new(1 << 20)
+        },
+    ] {
+        let config = DBConfig {
+            memory_limit: Some(limit),
+            ..Default::default()
+        };
+        assert!(Database::create_with_config(dir.path().join("bad"), config).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Mixed-modal WHERE clauses: MATCH / KNN_SEARCH / ST_*_3D combined with
+//! other predicates return exactly the rows satisfying every conjunct,
+//! whichever index the optimizer lets drive the query
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => {
+            let mut ids: Vec<i64> = rows
+                .iter()
+                .map(|row| match row[0] {
+                    Value::Integer(id) => id,
+                    ref other => panic!("Expected integer id, got {:?}", other),
+                })
+                .collect();
+            ids.sort();
+            ids
+        }
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup() -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE docs (id INTEGER PRIMARY KEY, body TEXT, pt GEOMETRY, emb VECTOR(4))")
+        .unwrap();
+    for i in 0..200i64 {
+        let body = match i {
+            _ if i % 10 == 0 => "robot lidar",
+            _ if i % 2 == 0 => "robot arm sensor",
+            _ => "robot wheel",
+        };
+        let f = i as f64;
+        db.execute(&format!(
+            "INSERT INTO docs VALUES ({}, '{}', POINT3D({}, {}, {}), [{}, 0.5, 0.25, 1.0])",
+            i, body, f, f, f, f
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE TEXT INDEX docs_body ON docs(body)")
+        .unwrap();
+    db.execute("CREATE OCTREE INDEX docs_pt ON docs(pt)")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX docs_emb ON docs(emb)")
+        .unwrap();
+    (db, dir)
+}
+
+#[test]
+fn test_text_and_spatial_predicates() {
+    let (db, _dir) = setup();
+
+    // Selective box, broad term: the octree drives
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body) AGAINST('robot') \
+             AND ST_WITHIN_3D(pt, 0, 0, 0, 9.5, 9.5, 9.5)"
+        ),
+        (0..10).collect::<Vec<_>>()
+    );
+
+    // Broad box, rare term: the text index drives
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE ST_WITHIN_3D(pt, 0, 0, 0, 300, 300, 300) \
+             AND MATCH(body) AGAINST('lidar')"
+        ),
+        (0..200).step_by(10).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE ST_KNN_3D(pt, 0, 0, 0, 6) \
+             AND MATCH(body) AGAINST('wheel')"
+        ),
+        vec![1, 3, 5]
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE ST_RADIUS_3D(pt, 0, 0, 0, 9) \
+             AND MATCH(body) AGAINST('sensor')"
+        ),
+        vec![2, 4]
+    );
+}
+
+#[test]
+fn test_modal_predicate_with_scalar_filter() {
+    let (db, _dir) = setup();
+
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body) AGAINST('robot') AND id < 10"
+        ),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body) AGAINST('lidar') AND id >= 150"
+        ),
+        vec![150, 160, 170, 180, 190]
+    );
+    // KNN_SEARCH is approximate: check the residual filter, not exact ids
+    let knn = ids(
+        &db,
+        "SELECT id FROM docs WHERE KNN_SEARCH(emb, [0.0, 0.5, 0.25, 1.0], 5)",
+    );
+    let knn_arm = ids(
+        &db,
+        "SELECT id FROM docs WHERE KNN_SEARCH(emb, [0.0, 0.5, 0.25, 1.0], 5) \
+         AND MATCH(body) AGAINST('arm')",
+    );
+    assert!(knn_arm.len() <= 5);
+    for id in knn_arm {
+        assert!(id % 2 == 0 && id % 10 != 0, "id {} is not an 'arm' row", id);
+        assert!(knn.contains(&id), "id {} is not among {:?}", id, knn);
+    }
+
+    // Scores still come from the driving text index
+    match db
+        .execute(
+            "SELECT id, MATCH(body) AGAINST('lidar') AS score FROM docs \
+             WHERE MATCH(body) AGAINST('lidar') AND id < 30 ORDER BY id",
+        )
+        .unwrap()
+        .materialize()
+        .unwrap()
+    {
+        QueryResult::Select { rows, .. } => {
+            assert_eq!(rows.len(), 3);
+            for row in rows {
+                assert!(matches!(row[1], Value::Float(score) if score > 0.0));
+            }
+        }
+        _ => panic!("Expected Select result"),
+    }
+}
@@ -0,0 +1,174 @@
+//! TEXT and UUID primary keys: point lookups and key ranges served by the
+//! key's ordered column index, before and after a reopen
+
+use motedb::types::{Uuid, Value};
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ints(db: &Database, sql: &str) -> Vec<i64> {
+    rows(db, sql)
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref v => panic!("unexpected {:?}", v),
+        })
+        .collect()
+}
+
+/// 300 devices keyed by serial, half of them flushed; two long serials
+/// share their first 64 bytes. A few devices are deleted or updated.
+fn setup_devices(db: &Database) {
+    db.execute("CREATE TABLE devices (serial TEXT PRIMARY KEY, seq INT)")
+        .unwrap();
+    for i in 0..300 {
+        if i == 150 {
+            db.flush().unwrap();
+        }
+        db.execute(&format!(
+            "INSERT INTO devices VALUES ('SN-{:05}', {})",
+            i, i
+        ))
+        .unwrap();
+    }
+    let long = "Z".repeat(64);
+    db.execute(&format!("INSERT INTO devices VALUES ('{}a', 1001)", long))
+        .unwrap();
+    db.execute(&format!("INSERT INTO devices VALUES ('{}b', 1002)", long))
+        .unwrap();
+    db.execute("DELETE FROM devices WHERE serial = 'SN-00103'")
+        .unwrap();
+    db.execute("UPDATE devices SET seq = 9999 WHERE serial = 'SN-00042'")
+        .unwrap();
+}
+
+fn check_devices(db: &Database) {
+    assert_eq!(
+        ints(db, "SELECT seq FROM devices WHERE serial = 'SN-00042'"),
+        vec![9999]
+    );
+    assert_eq!(
+        ints(db, "SELECT seq FROM devices WHERE serial = 'SN-00201'"),
+        vec![201]
+    );
+    assert!(ints(db, "SELECT seq FROM devices WHERE serial = 'SN-00103'").is_empty());
+    assert!(ints(db, "SELECT seq FROM devices WHERE serial = 'SN-99999'").is_empty());
+    let long = "Z".repeat(64);
+    assert_eq!(
+        ints(
+            db,
+            &format!("SELECT seq FROM devices WHERE serial = '{}b'", long)
+        ),
+        vec![1002]
+    );
+
+    let mut range = ints(
+        db,
+        "SELECT seq FROM devices WHERE serial >= 'SN-00100' AND serial < 'SN-00106'",
+    );
+    range.sort();
+    assert_eq!(range, vec![100, 101, 102, 104, 105]);
+    let mut below = ints(db, "SELECT seq FROM devices WHERE serial < 'SN-00003'");
+    below.sort();
+    assert_eq!(below, vec![0, 1, 2]);
+    let mut above = ints(db, "SELECT seq FROM devices WHERE serial > 'SN-00298'");
+    above.sort();
+    assert_eq!(above, vec![299, 1001, 1002]);
+    let mut long_range = ints(
+        db,
+        &format!(
+            "SELECT seq FROM devices WHERE serial > '{}a' AND serial <= '{}z'",
+            long, long
+        ),
+    );
+    long_range.sort();
+    assert_eq!(long_range, vec![1002]);
+}
+
+#[test]
+fn test_text_primary_key_lookups() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup_devices(&db);
+        check_devices(&db);
+        db.close().unwrap();
+    }
+    // The PK lookup cache starts empty: every lookup goes to the index
+    let db = Database::open(&path).unwrap();
+    check_devices(&db);
+    assert!(db
+        .execute("INSERT INTO devices VALUES ('SN-00201', 0)")
+        .is_err());
+    db.execute("INSERT INTO devices VALUES ('SN-00103', 5)")
+        .unwrap();
+    assert_eq!(
+        ints(&db, "SELECT seq FROM devices WHERE serial = 'SN-00103'"),
+        vec![5]
+    );
+}
+
+#[test]
+fn test_uuid_primary_key_ranges() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE events (id UUID PRIMARY KEY, seq INT)")
+        .unwrap();
+    let ids: Vec<Uuid> = (0..200).map(|_| Uuid::new_v4()).collect();
+    for (i, u) in ids.iter().enumerate() {
+        if i == 100 {
+            db.flush().unwrap();
+        }
+        db.execute(&format!("INSERT INTO events VALUES ('{}', {})", u, i))
+            .unwrap();
+    }
+    let seq_of = |u: &Uuid| ids.iter().position(|x| x == u).unwrap() as i64;
+    for u in [ids[7], ids[150]] {
+        assert_eq!(
+            ints(
+                &db,
+                &format!(
+                    "SELECT seq FROM events WHERE id = '{}'",
+                    u.to_string().to_uppercase()
+                )
+            ),
+            vec![seq_of(&u)]
+        );
+    }
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    let (lo, hi) = (sorted[40], sorted[60]);
+    let mut range = ints(
+        &db,
+        &format!(
+            "SELECT seq FROM events WHERE id > '{}' AND id <= '{}'",
+            lo, hi
+        ),
+    );
+    range.sort();
+    let mut expected: Vec<i64> = sorted[41..=60].iter().map(seq_of).collect();
+    expected.sort();
+    assert_eq!(range, expected);
+
+    let mut tail = ints(
+        &db,
+        &format!("SELECT seq FROM events WHERE id >= '{}'", sorted[195]),
+    );
+    tail.sort();
+    let mut expected: Vec<i64> = sorted[195..].iter().map(seq_of).collect();
+    expected.sort();
+    assert_eq!(tail, expected);
+
+    assert_eq!(
+        ints(&db, "SELECT seq FROM events ORDER BY id DESC LIMIT 3"),
+        sorted[197..].iter().rev().map(seq_of).collect::<Vec<_>>()
+    );
+}
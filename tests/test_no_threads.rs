@@ -0,0 +1,133 @@
+//! Targets without threads (wasm32-wasip1): with `platform::disable_threads`
+//! every background worker runs inline on the writer's thread. The switch is
+//! process-wide, so these tests live in their own binary.
+
+use motedb::config::{DurabilityLevel, FsyncConfig, FsyncPolicy, WALConfig};
+use motedb::storage::lsm::{LSMConfig, LSMEngine, Value, ValueData};
+use motedb::storage::platform;
+use motedb::types::Value as SqlValue;
+use motedb::{DBConfig, Database, QueryResult};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Names of this process's threads that belong to motedb workers
+#[cfg(target_os = "linux")]
+fn worker_threads() -> Vec<String> {
+    std::fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(|task| std::fs::read_to_string(task.ok()?.path().join("comm")).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| {
+            ["motedb-", "lsm-flush", "index-builder", "index-rebuild"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .collect()
+}
+
+fn count(db: &Database, sql: &str) -> i64 {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => match rows[0][0] {
+            SqlValue::Integer(n) => n,
+            ref other => panic!("Expected integer, got {:?}", other),
+        },
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_database_without_threads() {
+    platform::disable_threads();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let config = || DBConfig {
+        wal_config: WALConfig {
+            durability_level: DurabilityLevel::group_commit(),
+            ..Default::default()
+        },
+        fsync: FsyncConfig {
+            sstable: FsyncPolicy::Interval { interval_ms: 10 },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    {
+        let db = Database::create_with_config(&path, config()).unwrap();
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, name TEXT)")
+            .unwrap();
+        db.execute("CREATE INDEX t_name ON t (name)").unwrap();
+        // 25K rows crosses the auto-flush threshold twice
+        for chunk in 0..25 {
+            let values: Vec<String> = (0..1000)
+                .map(|i| {
+                    let id = chunk * 1000 + i;
+                    format!("({}, 'n{}')", id, id % 100)
+                })
+                .collect();
+            db.execute(&format!("INSERT INTO t VALUES {}", values.join(", ")))
+                .unwrap();
+        }
+        db.execute("DELETE FROM t WHERE id >= 24000").unwrap();
+        #[cfg(target_os = "linux")]
+        assert_eq!(worker_threads(), Vec::<String>::new());
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM t"), 24_000);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM t WHERE name = 'n42'"), 240);
+    }
+
+    let db = Database::open_with_config(&path, config()).unwrap();
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM t"), 24_000);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM t WHERE name = 'n42'"), 240);
+    #[cfg(target_os = "linux")]
+    assert_eq!(worker_threads(), Vec::<String>::new());
+}
+
+#[test]
+fn test_lsm_flushes_and_compacts_on_writer_thread() {
+    platform::disable_threads();
+    let dir = TempDir::new().unwrap();
+    let lsm_dir = dir.path().join("lsm");
+    let config = LSMConfig {
+        memtable_size: 16 * 1024,
+        l0_compaction_trigger: 2,
+        ..Default::default()
+    };
+
+    {
+        let engine = LSMEngine::new(lsm_dir.clone(), config.clone()).unwrap();
+        for key in 0..5_000u64 {
+            engine
+                .put(key, Value::new(key.to_le_bytes().to_vec(), key))
+                .unwrap();
+        }
+        // Full memtables were written out by `put` itself
+        let ssts = std::fs::read_dir(&lsm_dir)
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext == "sst")
+            })
+            .count();
+        assert!(ssts > 0);
+        let levels = engine.level_stats().unwrap();
+        assert!(
+            levels
+                .iter()
+                .any(|&(level, files, _)| level > 0 && files > 0),
+            "{:?}",
+            levels
+        );
+    }
+
+    let engine = LSMEngine::new(lsm_dir, config).unwrap();
+    for key in [0u64, 2_500, 4_999] {
+        let value = engine.get(key).unwrap().expect("key survives reopen");
+        assert_eq!(
+            value.data,
+            ValueData::Inline(Arc::new(key.to_le_bytes().to_vec()))
+        );
+    }
+}
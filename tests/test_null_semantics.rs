@@ -0,0 +1,130 @@
+//! NULL in WHERE: three-valued logic for comparisons, NOT, IN, BETWEEN and
+//! LIKE, and IS NULL answered from a column index's NULL bucket
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn ints(db: &Database, sql: &str) -> Vec<i64> {
+    let rows = match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    };
+    let mut ids: Vec<i64> = rows
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref v => panic!("unexpected {:?}", v),
+        })
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Ids 1..=6; level is NULL for 3 and 6, name is NULL for 2
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, level INT, name TEXT)")
+        .unwrap();
+    db.execute(
+        "INSERT INTO t VALUES (1, 1, 'ant'), (2, 4, NULL), (3, NULL, 'bee'), \
+         (4, 8, 'cat'), (5, 10, 'ape'), (6, NULL, 'dog')",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_unknown_filters_rows_out() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+
+    let cases: &[(&str, &[i64])] = &[
+        ("level > 5", &[4, 5]),
+        ("NOT (level > 5)", &[1, 2]),
+        ("level = NULL", &[]),
+        ("level <> NULL", &[]),
+        ("NOT (level = NULL)", &[]),
+        ("level IN (1, 8)", &[1, 4]),
+        ("level NOT IN (1, 8)", &[2, 5]),
+        ("level IN (1, NULL)", &[1]),
+        ("level NOT IN (1, NULL)", &[]),
+        ("level BETWEEN 2 AND 9", &[2, 4]),
+        ("level NOT BETWEEN 2 AND 9", &[1, 5]),
+        ("name LIKE 'a%'", &[1, 5]),
+        ("name NOT LIKE 'a%'", &[3, 4, 6]),
+        ("level > 5 OR name = 'bee'", &[3, 4, 5]),
+        ("NOT (level > 5 AND name = 'bee')", &[1, 2, 4, 5, 6]),
+        ("level IS NULL", &[3, 6]),
+        ("level IS NOT NULL", &[1, 2, 4, 5]),
+    ];
+    for (filter, expected) in cases {
+        assert_eq!(
+            ints(&db, &format!("SELECT id FROM t WHERE {}", filter)),
+            expected.to_vec(),
+            "WHERE {}",
+            filter
+        );
+    }
+    assert_eq!(
+        ints(&db, "SELECT COUNT(*) FROM t WHERE NOT (level < 5)"),
+        vec![2]
+    );
+    assert_eq!(
+        ints(&db, "SELECT COUNT(*) FROM t WHERE level = NULL"),
+        vec![0]
+    );
+    assert_eq!(
+        ints(&db, "SELECT COUNT(*) FROM t WHERE level <> NULL"),
+        vec![0]
+    );
+}
+
+#[test]
+fn test_is_null_through_index() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup(&db);
+        db.execute("CREATE INDEX idx_level ON t(level)").unwrap();
+        db.flush().unwrap();
+        db.execute("INSERT INTO t VALUES (7, NULL, 'eel'), (8, 3, 'fox')")
+            .unwrap();
+        assert_eq!(
+            db.query_by_column("t", "level", &Value::Null)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            ints(&db, "SELECT id FROM t WHERE level IS NULL"),
+            vec![3, 6, 7]
+        );
+        assert_eq!(
+            ints(&db, "SELECT id FROM t WHERE level = NULL"),
+            Vec::<i64>::new()
+        );
+        assert_eq!(ints(&db, "SELECT id FROM t WHERE level < 5"), vec![1, 2, 8]);
+        db.execute("UPDATE t SET level = NULL WHERE id = 1")
+            .unwrap();
+        db.execute("UPDATE t SET level = 2 WHERE id = 3").unwrap();
+        db.execute("DELETE FROM t WHERE id = 6").unwrap();
+        assert_eq!(
+            ints(&db, "SELECT id FROM t WHERE level IS NULL"),
+            vec![1, 7]
+        );
+        db.close().unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    db.wait_for_indexes_ready();
+    assert_eq!(
+        ints(&db, "SELECT id FROM t WHERE level IS NULL"),
+        vec![1, 7]
+    );
+    assert_eq!(
+        ints(&db, "SELECT id FROM t WHERE level IS NULL AND name = 'eel'"),
+        vec![7]
+    );
+    assert_eq!(ints(&db, "SELECT id FROM t WHERE level < 5"), vec![2, 3, 8]);
+}
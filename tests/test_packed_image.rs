@@ -0,0 +1,80 @@
+//! Single-file packed read-only database images
+
+use motedb::storage::packed::PackedImage;
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn select(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_pack_and_open_read_only() {
+    let dir = TempDir::new().unwrap();
+    let image = dir.path().join("map.motedb");
+    {
+        let db = Database::create(dir.path().join("src")).unwrap();
+        db.execute("CREATE TABLE landmarks (id INT PRIMARY KEY, kind TEXT, height FLOAT)")
+            .unwrap();
+        db.execute("CREATE INDEX idx_kind ON landmarks (kind)")
+            .unwrap();
+        for id in 0..200 {
+            db.execute(&format!(
+                "INSERT INTO landmarks VALUES ({}, '{}', {})",
+                id,
+                if id % 4 == 0 { "door" } else { "wall" },
+                id as f64 * 0.5
+            ))
+            .unwrap();
+        }
+        // Rows still in the WAL / write buffers end up in the image too
+        db.execute("UPDATE landmarks SET height = 99.5 WHERE id = 7")
+            .unwrap();
+        db.execute("DELETE FROM landmarks WHERE id = 8").unwrap();
+
+        let stats = db.pack(&image).unwrap();
+        assert!(stats.files > 0);
+        assert_eq!(stats.image_bytes, std::fs::metadata(&image).unwrap().len());
+        db.close().unwrap();
+    }
+    std::fs::remove_dir_all(dir.path().join("src.mote")).unwrap();
+
+    let packed = PackedImage::open(&image).unwrap();
+    packed.verify().unwrap();
+    assert!(packed.file("catalog.bin").is_some());
+    assert!(packed.entries().iter().all(|e| e.path != ".lock"));
+    drop(packed);
+
+    let work = dir.path().join("work");
+    for _ in 0..2 {
+        let db = Database::open_packed(&image, &work).unwrap();
+        assert_eq!(select(&db, "SELECT id FROM landmarks").len(), 199);
+        assert_eq!(
+            select(&db, "SELECT height FROM landmarks WHERE id = 7"),
+            vec![vec![Value::Float(99.5)]]
+        );
+        assert_eq!(
+            select(&db, "SELECT id FROM landmarks WHERE kind = 'door'").len(),
+            49
+        );
+
+        // The image is immutable
+        assert!(db
+            .execute("INSERT INTO landmarks VALUES (500, 'door', 1.0)")
+            .is_err());
+        assert!(db
+            .execute("CREATE TABLE extra (id INT PRIMARY KEY)")
+            .is_err());
+        assert_eq!(select(&db, "SELECT id FROM landmarks").len(), 199);
+        db.close().unwrap();
+    }
+
+    // Not an image
+    let bogus = dir.path().join("bogus.motedb");
+    std::fs::write(&bogus, vec![0u8; 8192]).unwrap();
+    assert!(Database::open_packed(&bogus, dir.path().join("work2")).is_err());
+}
@@ -0,0 +1,168 @@
+//! SELECTs compiled into physical operator trees: scans, index probes,
+//! hash joins, sort and limit, checked end to end through SQL
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn select(db: &Database, sql: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { columns, rows } => (columns, rows),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ints(rows: &[Vec<Value>], col: usize) -> Vec<i64> {
+    rows.iter()
+        .map(|row| match row[col] {
+            Value::Integer(i) => i,
+            ref other => panic!("expected integer, got {:?}", other),
+        })
+        .collect()
+}
+
+fn setup() -> (TempDir, Database) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE users (id INT PRIMARY KEY, name TEXT, zone INT)")
+        .unwrap();
+    db.execute("CREATE TABLE orders (id INT PRIMARY KEY, user_id INT, amount INT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_zone ON users (zone)").unwrap();
+    let users: Vec<String> = (1..=20)
+        .map(|id| format!("({}, 'u{}', {})", id, id, id % 4))
+        .collect();
+    db.execute(&format!("INSERT INTO users VALUES {}", users.join(", ")))
+        .unwrap();
+    let orders: Vec<String> = (1..=60)
+        .map(|id| format!("({}, {}, {})", id, id % 25 + 1, id * 10))
+        .collect();
+    db.execute(&format!("INSERT INTO orders VALUES {}", orders.join(", ")))
+        .unwrap();
+    (dir, db)
+}
+
+#[test]
+fn test_filter_project_sort_limit() {
+    let (_dir, db) = setup();
+    let (columns, rows) = select(
+        &db,
+        "SELECT id, amount * 2 AS twice FROM orders WHERE amount > 100 \
+         ORDER BY amount DESC LIMIT 3 OFFSET 1",
+    );
+    assert_eq!(columns, vec!["id".to_string(), "twice".to_string()]);
+    assert_eq!(ints(&rows, 0), vec![59, 58, 57]);
+    assert_eq!(ints(&rows, 1), vec![1180, 1160, 1140]);
+
+    // Index probe on zone plus a residual filter on id
+    let (_, rows) = select(
+        &db,
+        "SELECT id FROM users WHERE zone = 2 AND id > 5 ORDER BY id",
+    );
+    assert_eq!(ints(&rows, 0), vec![6, 10, 14, 18]);
+}
+
+#[test]
+fn test_inner_hash_join() {
+    let (_dir, db) = setup();
+    let (columns, rows) = select(
+        &db,
+        "SELECT o.id, u.name FROM orders o JOIN users u ON o.user_id = u.id \
+         WHERE u.zone = 1 ORDER BY o.id",
+    );
+    assert_eq!(columns.len(), 2);
+    let expected: Vec<i64> = (1..=60)
+        .filter(|id| {
+            let user = id % 25 + 1;
+            user <= 20 && user % 4 == 1
+        })
+        .collect();
+    assert_eq!(ints(&rows, 0), expected);
+    for row in &rows {
+        let Value::Integer(order_id) = row[0] else {
+            panic!("expected integer order id");
+        };
+        assert_eq!(row[1], Value::text(format!("u{}", order_id % 25 + 1)));
+    }
+
+    // Orders whose user doesn't exist never join
+    let (_, rows) = select(
+        &db,
+        "SELECT o.id FROM orders o JOIN users u ON u.id = o.user_id",
+    );
+    assert_eq!(rows.len(), (1..=60).filter(|id| id % 25 < 20).count());
+}
+
+#[test]
+fn test_join_with_filtered_build_side() {
+    let (_dir, db) = setup();
+    // Orders with a NULL or dangling user never reach the join
+    db.execute("INSERT INTO orders VALUES (100, NULL, 5), (101, 99, 5)")
+        .unwrap();
+    let (_, rows) = select(
+        &db,
+        "SELECT o.id, u.zone FROM orders o JOIN users u ON o.user_id = u.id \
+         WHERE u.name = 'u3' AND o.amount >= 100 ORDER BY o.id",
+    );
+    let expected: Vec<i64> = (10..=60).filter(|id| id % 25 + 1 == 3).collect();
+    assert_eq!(ints(&rows, 0), expected);
+    assert!(rows.iter().all(|row| row[1] == Value::Integer(3)));
+
+    // A build-side filter matching nothing empties the join
+    let (_, rows) = select(
+        &db,
+        "SELECT o.id FROM orders o JOIN users u ON o.user_id = u.id WHERE u.zone = 9",
+    );
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn test_projection_skips_wide_columns() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE frames (id INT PRIMARY KEY, ts INT, emb VECTOR(64), label TEXT)")
+        .unwrap();
+    let emb: Vec<String> = (0..64).map(|i| format!("{}.5", i)).collect();
+    for id in 0..50 {
+        db.execute(&format!(
+            "INSERT INTO frames VALUES ({}, {}, [{}], 'frame {}')",
+            id,
+            1000 + id,
+            emb.join(", "),
+            id
+        ))
+        .unwrap();
+    }
+    db.flush().unwrap();
+
+    let (columns, rows) = select(
+        &db,
+        "SELECT id, ts FROM frames WHERE label LIKE 'frame 1%' ORDER BY ts DESC LIMIT 3",
+    );
+    assert_eq!(columns, vec!["id".to_string(), "ts".to_string()]);
+    assert_eq!(ints(&rows, 0), vec![19, 18, 17]);
+    assert_eq!(ints(&rows, 1), vec![1019, 1018, 1017]);
+
+    // Star still reads every column
+    let (_, rows) = select(&db, "SELECT * FROM frames WHERE id = 7");
+    assert_eq!(rows.len(), 1);
+    match &rows[0][2] {
+        Value::Vector(v) => assert_eq!(v.len(), 64),
+        other => panic!("expected vector, got {:?}", other),
+    }
+    assert_eq!(rows[0][3], Value::text("frame 7".to_string()));
+
+    // Join sides read only their key, filter and output columns
+    let (_, rows) = select(
+        &db,
+        "SELECT a.label, b.ts FROM frames a JOIN frames b ON a.id = b.id \
+         WHERE b.ts < 1003 ORDER BY b.ts",
+    );
+    assert_eq!(
+        rows.iter().map(|row| row[0].clone()).collect::<Vec<_>>(),
+        (0..3)
+            .map(|id| Value::text(format!("frame {}", id)))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(ints(&rows, 1), vec![1000, 1001, 1002]);
+}
@@ -0,0 +1,108 @@
+//! WHERE clauses are normalized before index selection: BETWEEN, longer AND
+//! chains, NOT and constant subexpressions reach the column index, and ORs
+//! never probe just one branch
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn ids(db: &Database, sql: &str, params: Vec<Value>) -> Vec<i64> {
+    let result = if params.is_empty() {
+        db.execute(sql)
+    } else {
+        db.execute_prepared(sql, params)
+    };
+    let mut ids: Vec<i64> = match result.unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .into_iter()
+            .map(|r| match r[0] {
+                Value::Integer(i) => i,
+                ref v => panic!("unexpected {:?}", v),
+            })
+            .collect(),
+        _ => panic!("Expected Select result"),
+    };
+    ids.sort();
+    ids
+}
+
+/// Ids 0..400 with a = id % 50, b = id % 7; the first half flushed
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)")
+        .unwrap();
+    for id in 0..400 {
+        db.execute(&format!(
+            "INSERT INTO t VALUES ({}, {}, {})",
+            id,
+            id % 50,
+            id % 7
+        ))
+        .unwrap();
+        if id == 199 {
+            db.flush().unwrap();
+        }
+    }
+}
+
+fn expected(pred: impl Fn(i64, i64) -> bool) -> Vec<i64> {
+    (0..400).filter(|id| pred(id % 50, id % 7)).collect()
+}
+
+fn check(db: &Database) {
+    let in_range = expected(|a, b| (3..=9).contains(&a) && b == 2);
+    for sql in [
+        "SELECT id FROM t WHERE a >= 3 AND a <= 9 AND b = 2",
+        "SELECT id FROM t WHERE b = 2 AND a >= 3 AND a <= 9",
+        "SELECT id FROM t WHERE a BETWEEN 3 AND 9 AND b = 2",
+        "SELECT id FROM t WHERE b = 2 AND NOT (a < 3 OR a > 9)",
+        "SELECT id FROM t WHERE a > 1 AND a >= 3 AND a < 10 AND a <= 12 AND b = 2",
+        "SELECT id FROM t WHERE a >= 1 + 2 AND a <= 3 * 3 AND b = 2 AND 1 = 1",
+    ] {
+        assert_eq!(ids(db, sql, vec![]), in_range, "{}", sql);
+    }
+    assert_eq!(
+        ids(
+            db,
+            "SELECT id FROM t WHERE a BETWEEN ? AND ? AND b = ?",
+            vec![Value::Integer(3), Value::Integer(9), Value::Integer(2)]
+        ),
+        in_range
+    );
+
+    assert_eq!(
+        ids(db, "SELECT id FROM t WHERE a NOT BETWEEN 3 AND 45", vec![]),
+        expected(|a, _| !(3..=45).contains(&a))
+    );
+    assert_eq!(
+        ids(db, "SELECT id FROM t WHERE a = 1 OR b = 2", vec![]),
+        expected(|a, b| a == 1 || b == 2)
+    );
+    assert_eq!(
+        ids(
+            db,
+            "SELECT id FROM t WHERE (a = 1 AND b = 2) OR (a = 1 AND b = 3)",
+            vec![]
+        ),
+        expected(|a, b| a == 1 && (b == 2 || b == 3))
+    );
+    assert!(ids(db, "SELECT id FROM t WHERE a BETWEEN 9 AND 3", vec![]).is_empty());
+    assert!(ids(db, "SELECT id FROM t WHERE a = 1 AND 1 = 2", vec![]).is_empty());
+}
+
+#[test]
+fn test_normalized_predicates_by_scan() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    check(&db);
+}
+
+#[test]
+fn test_normalized_predicates_through_index() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    db.execute("CREATE INDEX idx_a ON t(a)").unwrap();
+    db.wait_for_indexes_ready();
+    check(&db);
+}
@@ -0,0 +1,88 @@
+//! Multi-row parameterized INSERT: one parse, one batch write
+
+use motedb::types::Value;
+use motedb::{Database, MoteDB, QueryResult, Session};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn count(db: &Database, sql: &str) -> i64 {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => match rows[0][0] {
+            Value::Integer(n) => n,
+            ref v => panic!("Expected integer count, got {:?}", v),
+        },
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn log_rows(range: std::ops::Range<i64>) -> Vec<Vec<Value>> {
+    range
+        .map(|i| vec![Value::Integer(i), Value::text(format!("event {}", i % 4))])
+        .collect()
+}
+
+#[test]
+fn test_execute_prepared_batch() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE log (id INT PRIMARY KEY, msg TEXT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_msg ON log (msg)").unwrap();
+
+    let sql = "INSERT INTO log (id, msg) VALUES (?, ?)";
+    assert_eq!(
+        db.execute_prepared_batch(sql, log_rows(0..400)).unwrap(),
+        400
+    );
+    assert_eq!(db.execute_prepared_batch(sql, Vec::new()).unwrap(), 0);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM log"), 400);
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM log WHERE msg = 'event 2'"),
+        100
+    );
+
+    // One bad tuple rejects the whole batch
+    let mut rows = log_rows(400..410);
+    rows.push(vec![Value::Integer(5), Value::text("dup".into())]);
+    assert!(db.execute_prepared_batch(sql, rows).is_err());
+    assert!(db
+        .execute_prepared_batch(sql, vec![vec![Value::Integer(500)]])
+        .is_err());
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM log"), 400);
+
+    // Only single-row INSERTs can be batched
+    assert!(db
+        .execute_prepared_batch(
+            "DELETE FROM log WHERE id = ?",
+            vec![vec![Value::Integer(1)]]
+        )
+        .is_err());
+}
+
+#[test]
+fn test_session_prepared_batch_in_transaction() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut session = Session::new(db);
+    session
+        .execute("CREATE TABLE log (id INT PRIMARY KEY, msg TEXT)")
+        .unwrap();
+    session
+        .prepare("ins", "INSERT INTO log VALUES (?, ?)")
+        .unwrap();
+
+    session.execute("BEGIN").unwrap();
+    let r = session
+        .execute_prepared_batch("ins", &log_rows(0..50))
+        .unwrap();
+    assert_eq!(r.affected_rows(), 50);
+    session.execute("ROLLBACK").unwrap();
+
+    session
+        .execute_prepared_batch("ins", &log_rows(0..20))
+        .unwrap();
+    match session.execute("SELECT COUNT(*) FROM log").unwrap() {
+        QueryResult::Select { rows, .. } => assert_eq!(rows[0][0], Value::Integer(20)),
+        _ => panic!("Expected Select result"),
+    }
+}
@@ -1,6 +1,7 @@
 //! Tests for uncovered public API methods:
 //! query_by_column_range, query_by_column_between, release_savepoint,
-//! vector_index_stats, transaction_stats, close + operations-after-close
+//! vector_index_stats, transaction_stats, close + operations-after-close,
+//! transaction closures and read snapshots
 
 use motedb::{types::Value, Database};
 use tempfile::TempDir;
@@ -388,3 +389,138 @@ fn test_get_row_map() {
     assert_eq!(map.get("id"), Some(&Value::Integer(1)));
     assert_eq!(map.get("val"), Some(&Value::Integer(42)));
 }
+
+// === transaction(|tx| ...) / snapshot() ===
+
+#[test]
+fn test_transaction_closure_commits_or_rolls_back() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, val INT)")
+        .unwrap();
+
+    let n = db
+        .transaction(|tx| {
+            tx.execute("INSERT INTO t VALUES (1, 10)")?;
+            let row_id = tx.insert_row("t", vec![Value::Integer(2), Value::Integer(20)])?;
+            assert!(tx.get_row("t", row_id)?.is_some());
+            Ok(2)
+        })
+        .unwrap();
+    assert_eq!(n, 2);
+
+    let err = db
+        .transaction(|tx| -> motedb::Result<()> {
+            tx.execute("INSERT INTO t VALUES (3, 30)")?;
+            Err(motedb::StorageError::InvalidArgument("abort".into()))
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("abort"));
+
+    let ids = rows(db.execute("SELECT id FROM t ORDER BY id").unwrap());
+    assert_eq!(ids, vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]);
+    assert_eq!(db.transaction_stats().active_transactions, 0);
+}
+
+#[test]
+fn test_snapshot_hides_later_commits() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, val INT)")
+        .unwrap();
+
+    let first = db
+        .transaction(|tx| tx.insert_row("t", vec![Value::Integer(1), Value::Integer(10)]))
+        .unwrap();
+    let snap = db.snapshot().unwrap();
+    let second = db
+        .transaction(|tx| tx.insert_row("t", vec![Value::Integer(2), Value::Integer(20)]))
+        .unwrap();
+
+    assert!(snap.get_row("t", first).unwrap().is_some());
+    assert!(snap.get_row("t", second).unwrap().is_none());
+    assert!(db.get_row("t", second).unwrap().is_some());
+    assert!(db
+        .snapshot()
+        .unwrap()
+        .get_row("t", second)
+        .unwrap()
+        .is_some());
+
+    drop(snap);
+    assert_eq!(db.transaction_stats().active_transactions, 0);
+}
+
+#[test]
+fn test_readonly_view_outlives_borrow_and_vacuum() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, val INT)")
+        .unwrap();
+
+    let first = db
+        .transaction(|tx| tx.insert_row("t", vec![Value::Integer(1), Value::Integer(10)]))
+        .unwrap();
+    let view = db.clone_readonly_view().unwrap();
+    let second = db
+        .transaction(|tx| tx.insert_row("t", vec![Value::Integer(2), Value::Integer(20)]))
+        .unwrap();
+    // Compaction runs while the view is held
+    db.vacuum().unwrap();
+
+    let shared = view.clone();
+    let scanned = std::thread::spawn(move || shared.scan_table("t").unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(
+        scanned,
+        vec![(first, vec![Value::Integer(1), Value::Integer(10)])]
+    );
+    assert!(view.get_row("t", second).unwrap().is_none());
+    assert_eq!(
+        view.get_row_map("t", first).unwrap().unwrap().get("val"),
+        Some(&Value::Integer(10))
+    );
+    assert_eq!(rows(db.execute("SELECT id FROM t").unwrap()).len(), 2);
+    assert_eq!(db.transaction_stats().active_transactions, 1);
+
+    drop(view);
+    assert_eq!(db.transaction_stats().active_transactions, 0);
+}
+
+#[test]
+fn test_readonly_view_keeps_rows_deleted_after_it() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, val INT)")
+        .unwrap();
+    let ids: Vec<_> = (1..=3)
+        .map(|i| {
+            db.transaction(|tx| tx.insert_row("t", vec![Value::Integer(i), Value::Integer(i * 10)]))
+                .unwrap()
+        })
+        .collect();
+
+    let view = db.clone_readonly_view().unwrap();
+    db.delete_row("t", ids[0]).unwrap();
+    db.execute("DELETE FROM t WHERE id = 2").unwrap();
+    db.vacuum().unwrap();
+
+    let scanned = view.scan_table("t").unwrap();
+    assert_eq!(scanned.iter().map(|(id, _)| *id).collect::<Vec<_>>(), ids);
+    assert_eq!(scanned[1].1, vec![Value::Integer(2), Value::Integer(20)]);
+    assert_eq!(
+        view.get_row("t", ids[0]).unwrap(),
+        Some(vec![Value::Integer(1), Value::Integer(10)])
+    );
+
+    // A view opened after the deletes does not see the rows
+    let later = db.clone_readonly_view().unwrap();
+    assert_eq!(later.scan_table("t").unwrap().len(), 1);
+    assert!(later.get_row("t", ids[0]).unwrap().is_none());
+    drop(later);
+
+    drop(view);
+    let fresh = db.clone_readonly_view().unwrap();
+    assert_eq!(fresh.scan_table("t").unwrap().len(), 1);
+}
@@ -0,0 +1,189 @@
+//! Tests for the query builder: Database::table and sql::builder::col
+
+use motedb::sql::builder::col;
+use motedb::types::Value;
+use motedb::Database;
+use tempfile::TempDir;
+
+fn setup() -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, sensor TEXT, temp FLOAT, note TEXT, emb VECTOR(3))")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX readings_emb ON readings(emb)")
+        .unwrap();
+    for i in 1..=20 {
+        let note = if i % 5 == 0 { "'checked'" } else { "NULL" };
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, 's{}', {}, {}, [{}.0, {}.5, 1.0])",
+            i,
+            i % 3,
+            i as f64 * 1.5,
+            note,
+            i,
+            i % 4
+        ))
+        .unwrap();
+    }
+    (db, dir)
+}
+
+fn ids(rows: &[Vec<Value>]) -> Vec<i64> {
+    rows.iter()
+        .map(|row| match row[0] {
+            Value::Integer(id) => id,
+            ref other => panic!("unexpected id {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_builder_matches_sql() {
+    let (db, _dir) = setup();
+
+    let built = db
+        .table("readings")
+        .select(["id", "temp"])
+        .filter(col("temp").gt(6.0).and(col("sensor").eq("s1")))
+        .order_by_desc("temp")
+        .limit(3)
+        .all()
+        .unwrap();
+    let sql = db
+        .query("SELECT id, temp FROM readings WHERE temp > 6.0 AND sensor = 's1' ORDER BY temp DESC LIMIT 3")
+        .unwrap();
+    assert_eq!(built, sql);
+    assert_eq!(ids(&built), vec![19, 16, 13]);
+
+    let (text, params) = db
+        .table("readings")
+        .select(["id"])
+        .filter(col("id").between(2, 4))
+        .to_sql()
+        .unwrap();
+    assert_eq!(text, "SELECT id FROM readings WHERE id BETWEEN ? AND ?");
+    assert_eq!(params, vec![Value::Integer(2), Value::Integer(4)]);
+}
+
+#[test]
+fn test_builder_knn() {
+    let (db, _dir) = setup();
+    db.flush().unwrap();
+
+    let query = [7.0f32, 3.5, 1.0];
+    let built = db
+        .table("readings")
+        .select(["id"])
+        .knn("emb", &query, 3)
+        .limit(20)
+        .all()
+        .unwrap();
+    let sql = db
+        .query("SELECT id FROM readings ORDER BY emb <-> [7.0, 3.5, 1.0] LIMIT 3")
+        .unwrap();
+    assert_eq!(built.len(), 3);
+    assert_eq!(built, sql);
+    assert_eq!(ids(&built)[0], 7);
+
+    // Filters combine with the nearest-neighbour ordering like the SQL does
+    let built = db
+        .table("readings")
+        .select(["id"])
+        .filter(col("sensor").eq("s0"))
+        .knn("emb", &query, 2)
+        .all()
+        .unwrap();
+    let sql = db
+        .query(
+            "SELECT id FROM readings WHERE sensor = 's0' ORDER BY emb <-> [7.0, 3.5, 1.0] LIMIT 2",
+        )
+        .unwrap();
+    assert_eq!(built, sql);
+
+    let err = db
+        .table("readings")
+        .knn("emb", &[f32::NAN, 0.0, 0.0], 1)
+        .all();
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_builder_conditions_and_terminals() {
+    let (db, _dir) = setup();
+    let readings = db.table("readings");
+
+    assert_eq!(readings.count().unwrap(), 20);
+    assert_eq!(
+        readings
+            .clone()
+            .filter(col("id").is_in([3, 4, 99]))
+            .count()
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        readings
+            .clone()
+            .filter(col("id").is_in(Vec::<i64>::new()))
+            .count()
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        readings
+            .clone()
+            .filter(col("note").is_not_null())
+            .count()
+            .unwrap(),
+        4
+    );
+    assert_eq!(
+        readings
+            .clone()
+            .filter(col("sensor").like("s2%").and(col("note").is_null()))
+            .count()
+            .unwrap(),
+        5
+    );
+    assert_eq!(
+        readings
+            .clone()
+            .filter(col("id").lt(3).or(col("id").ge(19)))
+            .count()
+            .unwrap(),
+        4
+    );
+
+    let page = readings
+        .clone()
+        .select(["id"])
+        .order_by("id")
+        .limit(2)
+        .offset(5)
+        .all()
+        .unwrap();
+    assert_eq!(ids(&page), vec![6, 7]);
+
+    let first = readings
+        .clone()
+        .filter(col("sensor").ne("s0"))
+        .order_by_desc("id")
+        .first()
+        .unwrap()
+        .unwrap();
+    assert_eq!(first[0], Value::Integer(20));
+    assert!(readings
+        .clone()
+        .filter(col("id").gt(100))
+        .first()
+        .unwrap()
+        .is_none());
+
+    // Parameters are bound, not spliced into the SQL text
+    let quoted = readings
+        .clone()
+        .filter(col("sensor").eq("s1' OR '1' = '1"))
+        .count()
+        .unwrap();
+    assert_eq!(quoted, 0);
+}
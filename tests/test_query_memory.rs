@@ -0,0 +1,103 @@
+//! Per-query memory limits: joins, sorts, aggregations and DISTINCT fail with
+//! QueryMemoryLimitExceeded past the configured or session limit.
+
+use motedb::types::Value;
+use motedb::{DBConfig, ErrorCode, MoteDB, QueryResult, Session, StorageError};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const QUERIES: [&str; 4] = [
+    "SELECT a.id, b.id FROM items a JOIN items b ON a.grp = b.grp",
+    "SELECT id, label FROM items ORDER BY label DESC",
+    "SELECT label, grp, COUNT(*) FROM items GROUP BY label, grp",
+    "SELECT DISTINCT grp, label FROM items",
+];
+
+fn open_db(dir: &TempDir, config: DBConfig) -> Arc<MoteDB> {
+    let db = Arc::new(MoteDB::create_with_config(dir.path().join("db"), config).unwrap());
+    let mut s = Session::new(db.clone());
+    s.execute("CREATE TABLE items (id INT PRIMARY KEY, grp INT, label TEXT)")
+        .unwrap();
+    for i in 0..200 {
+        s.execute(&format!(
+            "INSERT INTO items VALUES ({}, {}, 'label-{:04}-{}')",
+            i,
+            i % 4,
+            i,
+            "x".repeat(64)
+        ))
+        .unwrap();
+    }
+    db
+}
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn assert_over_limit(s: &mut Session, sql: &str) {
+    match s.execute(sql) {
+        Err(e @ StorageError::QueryMemoryLimitExceeded(_)) => {
+            assert_eq!(e.code(), ErrorCode::QueryMemoryLimitExceeded);
+            assert_eq!(e.code().sqlstate(), "53200");
+        }
+        other => panic!(
+            "{}: expected QueryMemoryLimitExceeded, got {:?}",
+            sql, other
+        ),
+    }
+}
+
+#[test]
+fn test_session_limit_rejects_large_queries() {
+    let dir = TempDir::new().unwrap();
+    let mut s = Session::new(open_db(&dir, DBConfig::default()));
+
+    let expected: Vec<usize> = QUERIES
+        .iter()
+        .map(|sql| rows(s.execute(sql).unwrap()).len())
+        .collect();
+    assert_eq!(expected, vec![10000, 200, 200, 200]);
+
+    s.execute("SET query_memory_limit = 4096").unwrap();
+    for sql in QUERIES {
+        assert_over_limit(&mut s, sql);
+    }
+    // Small statements still fit, and the limit does not carry over
+    assert_eq!(
+        rows(s.execute("SELECT COUNT(*) FROM items").unwrap()),
+        vec![vec![Value::Integer(200)]]
+    );
+    assert_eq!(
+        rows(s.execute("SELECT label FROM items WHERE id = 7").unwrap()).len(),
+        1
+    );
+
+    s.execute("SET query_memory_limit = 1073741824").unwrap();
+    for (sql, n) in QUERIES.iter().zip(&expected) {
+        assert_eq!(rows(s.execute(sql).unwrap()).len(), *n);
+    }
+}
+
+#[test]
+fn test_config_limit_and_session_override() {
+    let dir = TempDir::new().unwrap();
+    let config = DBConfig {
+        query_memory_limit: Some(4096),
+        ..Default::default()
+    };
+    let mut s = Session::new(open_db(&dir, config));
+
+    for sql in QUERIES {
+        assert_over_limit(&mut s, sql);
+    }
+
+    s.execute("SET query_memory_limit = 1073741824").unwrap();
+    assert_eq!(rows(s.execute(QUERIES[0]).unwrap()).len(), 10000);
+
+    s.execute("SET query_memory_limit = none").unwrap();
+    assert_over_limit(&mut s, QUERIES[0]);
+}
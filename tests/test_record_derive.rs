@@ -0,0 +1,202 @@
+//! Tests for typed tables: #[derive(MoteRecord)] and Database::records
+
+use motedb::types::{ColumnType, Geometry, Point, Timestamp};
+use motedb::{Database, MoteRecord};
+use tempfile::TempDir;
+
+#[derive(MoteRecord, Debug, Clone, PartialEq)]
+#[mote(table = "readings")]
+struct Reading {
+    #[mote(primary_key)]
+    id: i64,
+    sensor: String,
+    #[mote(rename = "temp_c")]
+    temperature: f64,
+    ok: bool,
+    at: Timestamp,
+    #[mote(dim = 3)]
+    embedding: Vec<f32>,
+    position: Option<Geometry>,
+    note: Option<String>,
+}
+
+#[derive(MoteRecord, Debug, PartialEq)]
+struct RobotPose {
+    arm: u8,
+    joints: [f32; 2],
+}
+
+fn reading(id: i64, temperature: f64) -> Reading {
+    Reading {
+        id,
+        sensor: format!("s{}", id % 2),
+        temperature,
+        ok: id % 3 != 0,
+        at: Timestamp::from_micros(1_700_000_000_000_000 + id),
+        embedding: vec![id as f32, 0.5, -1.0],
+        position: (id % 2 == 0).then(|| Geometry::Point(Point::new(id as f64, 2.0))),
+        note: None,
+    }
+}
+
+#[test]
+fn test_record_schema() {
+    assert_eq!(Reading::TABLE, "readings");
+    assert_eq!(
+        Reading::COLUMNS,
+        [
+            "id",
+            "sensor",
+            "temp_c",
+            "ok",
+            "at",
+            "embedding",
+            "position",
+            "note"
+        ]
+    );
+    assert_eq!(Reading::primary_key(), Some("id"));
+    assert_eq!(Reading::TEMPERATURE.name(), "temp_c");
+
+    let schema = Reading::schema();
+    assert_eq!(schema.columns[2].col_type, ColumnType::Float);
+    assert_eq!(schema.columns[5].col_type, ColumnType::Tensor(3));
+    assert_eq!(schema.columns[6].col_type, ColumnType::Spatial);
+    assert!(!schema.columns[1].nullable);
+    assert!(schema.columns[7].nullable);
+
+    assert_eq!(RobotPose::TABLE, "robot_pose");
+    assert_eq!(RobotPose::primary_key(), None);
+    assert_eq!(RobotPose::columns()[1].col_type, ColumnType::Tensor(2));
+}
+
+#[test]
+fn test_record_crud() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.create_table_for::<Reading>().unwrap();
+    let readings = db.records::<Reading>().unwrap();
+
+    let first = reading(1, 21.5);
+    let row_id = readings.insert(&first).unwrap();
+    assert_eq!(readings.get(row_id).unwrap(), Some(first.clone()));
+
+    let mut changed = reading(1, 23.0);
+    changed.note = Some("recalibrated".into());
+    readings.update(row_id, &changed).unwrap();
+    assert_eq!(readings.get(row_id).unwrap(), Some(changed));
+
+    readings.delete(row_id).unwrap();
+    assert_eq!(readings.get(row_id).unwrap(), None);
+
+    // Plain SQL sees the same table
+    db.execute("INSERT INTO readings (id, sensor, temp_c, ok, at, embedding) VALUES (9, 's9', 1.0, true, 5, [1.0, 2.0, 3.0])")
+        .unwrap();
+    let from_sql = readings.query().first().unwrap().unwrap();
+    assert_eq!(from_sql.sensor, "s9");
+    assert_eq!(from_sql.embedding, vec![1.0, 2.0, 3.0]);
+    assert_eq!(from_sql.position, None);
+}
+
+#[test]
+fn test_record_query_builder() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.create_table_for::<Reading>().unwrap();
+    let readings = db.records::<Reading>().unwrap();
+
+    let all: Vec<Reading> = (1..=10).map(|i| reading(i, i as f64 * 5.0)).collect();
+    assert_eq!(readings.insert_many(&all).unwrap().len(), 10);
+
+    let hot = readings
+        .query()
+        .filter(Reading::TEMPERATURE.gt(30.0))
+        .order_by_desc(Reading::TEMPERATURE)
+        .limit(2)
+        .all()
+        .unwrap();
+    assert_eq!(hot, vec![all[9].clone(), all[8].clone()]);
+
+    let s0 = readings
+        .query()
+        .filter(Reading::SENSOR.eq("s0"))
+        .filter(Reading::ID.le(6))
+        .order_by(Reading::ID)
+        .all()
+        .unwrap();
+    let ids: Vec<i64> = s0.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![2, 4, 6]);
+
+    let either = readings
+        .query()
+        .filter(Reading::ID.eq(1).or(Reading::ID.eq(10)))
+        .count()
+        .unwrap();
+    assert_eq!(either, 2);
+    assert_eq!(
+        readings
+            .query()
+            .filter(Reading::POSITION.is_null())
+            .count()
+            .unwrap(),
+        5
+    );
+    assert_eq!(
+        readings
+            .query()
+            .filter(Reading::NOTE.is_not_null())
+            .count()
+            .unwrap(),
+        0
+    );
+    assert!(readings
+        .query()
+        .filter(Reading::ID.gt(100))
+        .first()
+        .unwrap()
+        .is_none());
+
+    let nearest = readings
+        .query()
+        .knn(Reading::EMBEDDING, &[3.2, 0.5, -1.0], 2)
+        .all()
+        .unwrap();
+    let ids: Vec<i64> = nearest.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![3, 4]);
+}
+
+#[test]
+fn test_record_fixed_vector_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    let pose = RobotPose {
+        arm: 3,
+        joints: [0.25, -1.5],
+    };
+    {
+        let db = Database::create(dir.path()).unwrap();
+        db.create_table_for::<RobotPose>().unwrap();
+        db.records::<RobotPose>().unwrap().insert(&pose).unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    let poses = db.records::<RobotPose>().unwrap();
+    assert_eq!(poses.query().all().unwrap(), vec![pose]);
+}
+
+#[test]
+fn test_records_rejects_mismatched_table() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE robot_pose (arm INT, joints VECTOR(2), extra INT)")
+        .unwrap();
+    assert!(db.records::<RobotPose>().is_err());
+    assert!(db.records::<Reading>().is_err());
+
+    // A value that doesn't fit the field type is a type error, not a panic
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, sensor TEXT, temp_c FLOAT, ok BOOLEAN, at TIMESTAMP, embedding VECTOR(3), position GEOMETRY, note INT)")
+        .unwrap();
+    db.execute("INSERT INTO readings VALUES (1, 'a', 1.0, true, 0, [1.0, 2.0, 3.0], NULL, 7)")
+        .unwrap();
+    let readings = db.records::<Reading>().unwrap();
+    assert!(readings.query().all().is_err());
+}
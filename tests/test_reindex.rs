@@ -0,0 +1,284 @@
+//! REINDEX / `db.rebuild_index`: indexes are rebuilt from table data and
+//! swapped in, and survive a reopen
+
+use motedb::types::{Tensor, Value};
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn query(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    query(db, sql)
+        .into_iter()
+        .map(|row| match row[0] {
+            Value::Integer(id) => id,
+            ref v => panic!("expected INTEGER id, got {:?}", v),
+        })
+        .collect()
+}
+
+fn setup(path: &std::path::Path) -> Database {
+    let db = Database::create(path).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, cat INT, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    for i in 0..200i64 {
+        let body = if i % 10 == 0 {
+            "rust storage engine"
+        } else {
+            "plain text"
+        };
+        db.insert_row(
+            "docs",
+            vec![
+                Value::Integer(i),
+                Value::Integer(i % 7),
+                Value::Text(body.into()),
+                Value::tensor(Tensor::new(vec![i as f32, 0.0, 0.0, 1.0])),
+            ],
+        )
+        .unwrap();
+    }
+    db.execute("CREATE INDEX idx_cat ON docs (cat)").unwrap();
+    db.execute("CREATE TEXT INDEX idx_body ON docs (body)")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX idx_emb ON docs (emb)")
+        .unwrap();
+    db.flush().unwrap();
+    db
+}
+
+#[test]
+fn test_reindex_keeps_every_index_type_queryable() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.execute("DELETE FROM docs WHERE id >= 100").unwrap();
+
+    let by_cat = ids(&db, "SELECT id FROM docs WHERE cat = 3 ORDER BY id");
+    let by_text = ids(
+        &db,
+        "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id",
+    );
+    assert_eq!(by_cat.len(), 14);
+    assert_eq!(by_text, (0..100).step_by(10).collect::<Vec<_>>());
+
+    db.execute("REINDEX idx_cat").unwrap();
+    db.execute("REINDEX INDEX idx_body").unwrap();
+    db.rebuild_index("idx_emb").unwrap();
+
+    assert_eq!(
+        ids(&db, "SELECT id FROM docs WHERE cat = 3 ORDER BY id"),
+        by_cat
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        ),
+        by_text
+    );
+    // Deleted rows are gone from the rebuilt graph
+    let hits = db
+        .vector_search("idx_emb", &[150.0, 0.0, 0.0, 1.0], 3)
+        .unwrap();
+    let hit_ids: Vec<u64> = hits.iter().map(|(id, _)| *id).collect();
+    assert_eq!(hit_ids.len(), 3);
+    assert!(hit_ids.iter().all(|&id| id < 100), "{:?}", hit_ids);
+
+    // Rows written after the rebuild keep flowing into the new indexes
+    db.execute("INSERT INTO docs (id, cat, body) VALUES (500, 3, 'rust again')")
+        .unwrap();
+    assert_eq!(
+        ids(&db, "SELECT id FROM docs WHERE cat = 3 ORDER BY id").last(),
+        Some(&500)
+    );
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        )
+        .last(),
+        Some(&500)
+    );
+
+    db.close().unwrap();
+    drop(db);
+    let db = Database::open(&path).unwrap();
+    assert_eq!(
+        ids(&db, "SELECT id FROM docs WHERE cat = 3 ORDER BY id").len(),
+        by_cat.len() + 1
+    );
+    assert_eq!(
+        db.vector_search("idx_emb", &[1.0, 0.0, 0.0, 1.0], 1)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_reindex_unknown_index() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+    assert!(db.execute("REINDEX no_such_index").is_err());
+    assert!(db.rebuild_index("no_such_index").is_err());
+}
+
+#[test]
+fn test_interrupted_rebuild_is_resolved_on_open() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.close().unwrap();
+    drop(db);
+
+    // A crash mid-swap: the live text index was moved aside and the staging
+    // copy never renamed into place; a staging column index was half-written.
+    let indexes = path.with_extension("mote").join("indexes");
+    std::fs::rename(
+        indexes.join("text_idx_body.fts.d"),
+        indexes.join("text_idx_body.fts.d@replaced"),
+    )
+    .unwrap();
+    std::fs::write(indexes.join("column_idx_cat@reindex.idx"), b"partial").unwrap();
+
+    let db = Database::open(&path).unwrap();
+    assert!(indexes.join("text_idx_body.fts.d").exists());
+    assert!(!indexes.join("text_idx_body.fts.d@replaced").exists());
+    assert!(!indexes.join("column_idx_cat@reindex.idx").exists());
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        )
+        .len(),
+        20
+    );
+}
+
+#[test]
+fn test_repeated_text_reindex_keeps_matches() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+    let expected: Vec<i64> = (0..200).step_by(10).collect();
+
+    // Each rebuild flushes and reopens the text index; ranked matches must
+    // survive every round, not just the first.
+    for round in 0..5 {
+        db.execute("REINDEX INDEX idx_body").unwrap();
+        assert_eq!(
+            ids(
+                &db,
+                "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+            ),
+            expected,
+            "round {}",
+            round
+        );
+    }
+}
+
+#[test]
+fn test_reindex_keeps_writes_made_during_the_rebuild() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("db"));
+
+    // Insert, update and delete rows while the column and text indexes are
+    // rebuilt; every write must reach the indexes that get swapped in.
+    std::thread::scope(|s| {
+        let writer = s.spawn(|| {
+            for i in 0..200i64 {
+                db.execute(&format!(
+                    "INSERT INTO docs (id, cat, body) VALUES ({}, 3, 'rust written')",
+                    1000 + i
+                ))
+                .unwrap();
+                if i < 50 {
+                    db.execute(&format!("UPDATE docs SET cat = 3 WHERE id = {}", i * 2))
+                        .unwrap();
+                    db.execute(&format!("DELETE FROM docs WHERE id = {}", i * 2 + 1))
+                        .unwrap();
+                }
+            }
+        });
+        for _ in 0..3 {
+            db.execute("REINDEX idx_cat").unwrap();
+            db.execute("REINDEX INDEX idx_body").unwrap();
+        }
+        writer.join().unwrap();
+    });
+
+    let scanned = |sql: &str| -> Vec<i64> {
+        let mut all = ids(&db, sql);
+        all.sort();
+        all
+    };
+    let mut expected: Vec<i64> = (0..100)
+        .step_by(2)
+        .chain((100..200).filter(|i| i % 7 == 3))
+        .chain(1000..1200)
+        .collect();
+    expected.sort();
+    assert_eq!(
+        scanned("SELECT id FROM docs WHERE cat = 3 ORDER BY id"),
+        expected
+    );
+
+    let mut expected: Vec<i64> = (0..200)
+        .step_by(10)
+        .filter(|i| i >= &100 || i % 2 == 0)
+        .chain(1000..1200)
+        .collect();
+    expected.sort();
+    assert_eq!(
+        scanned("SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"),
+        expected
+    );
+}
+
+#[test]
+fn test_interrupted_text_swap_is_rolled_back_as_a_whole() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let db = setup(&path);
+    db.close().unwrap();
+    drop(db);
+
+    // A crash mid-swap of the two text roots: both live roots were moved
+    // aside behind the marker, one replacement is in place and the other is
+    // still staged.
+    let indexes = path.with_extension("mote").join("indexes");
+    std::fs::write(
+        indexes.join("text_idx_body@swap"),
+        "+text_idx_body.fts.d\n+text_idx_body.dict.d\n",
+    )
+    .unwrap();
+    for root in ["text_idx_body.fts.d", "text_idx_body.dict.d"] {
+        std::fs::rename(
+            indexes.join(root),
+            indexes.join(format!("{}@replaced", root)),
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all(indexes.join("text_idx_body.fts.d")).unwrap();
+    std::fs::create_dir_all(indexes.join("text_idx_body@reindex.dict.d")).unwrap();
+
+    let db = Database::open(&path).unwrap();
+    assert!(!indexes.join("text_idx_body@swap").exists());
+    assert!(!indexes.join("text_idx_body.fts.d@replaced").exists());
+    assert!(!indexes.join("text_idx_body.dict.d@replaced").exists());
+    assert!(!indexes.join("text_idx_body@reindex.dict.d").exists());
+    assert_eq!(
+        ids(
+            &db,
+            "SELECT id FROM docs WHERE MATCH(body, 'rust') ORDER BY id"
+        )
+        .len(),
+        20
+    );
+}
@@ -0,0 +1,158 @@
+//! Tests for the shared JSON encoding of results (QueryResult::to_json,
+//! StreamingQueryResult::write_json)
+
+use motedb::sql::json::{type_name, value_to_json};
+use motedb::sql::JsonOptions;
+use motedb::types::{Geometry, Point, Point3D, Timestamp, UtcOffset, Value};
+use motedb::{Database, QueryResult, TimestampFormat};
+use serde_json::json;
+use tempfile::TempDir;
+
+fn setup() -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE sightings (id INT PRIMARY KEY, label TEXT, score FLOAT, at TIMESTAMP, pos GEOMETRY, emb VECTOR(2))")
+        .unwrap();
+    // 2023-11-14T22:13:20Z plus id seconds
+    db.execute("INSERT INTO sightings VALUES (1, NULL, 0.5, 1700000001000000, POINT(1.5, 2.0), [1.0, 0.5])")
+        .unwrap();
+    db.execute(
+        "INSERT INTO sightings VALUES (2, 'gull', NULL, 1700000002000000, NULL, [0.0, -2.5])",
+    )
+    .unwrap();
+    (db, dir)
+}
+
+fn select(db: &Database, sql: &str) -> QueryResult {
+    db.execute(sql).unwrap().materialize().unwrap()
+}
+
+#[test]
+fn test_select_to_json() {
+    let (db, _dir) = setup();
+    let result = select(
+        &db,
+        "SELECT id, label, score, at, pos, emb FROM sightings ORDER BY id",
+    );
+    let encoded: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+    assert_eq!(
+        encoded,
+        json!({
+            "columns": ["id", "label", "score", "at", "pos", "emb"],
+            "types": ["INTEGER", "TEXT", "FLOAT", "TIMESTAMP", "GEOMETRY", "VECTOR(2)"],
+            "rows": [
+                // POINT(x, y) is stored as a 3D point with z = 0
+                [1, null, 0.5, "2023-11-14T22:13:21Z",
+                 {"type": "Point", "coordinates": [1.5, 2.0, 0.0]}, [1.0, 0.5]],
+                [2, "gull", null, "2023-11-14T22:13:22Z", null, [0.0, -2.5]],
+            ],
+        })
+    );
+
+    // Epoch microseconds and other offsets on request
+    let options = JsonOptions {
+        timestamp_format: TimestampFormat::Micros,
+        ..JsonOptions::default()
+    };
+    let encoded: serde_json::Value = serde_json::from_str(&result.to_json_with(&options)).unwrap();
+    assert_eq!(encoded["rows"][0][3], json!(1_700_000_001_000_000i64));
+    let options = JsonOptions {
+        time_zone: "+08:00".parse::<UtcOffset>().unwrap(),
+        ..JsonOptions::default()
+    };
+    let encoded: serde_json::Value = serde_json::from_str(&result.to_json_with(&options)).unwrap();
+    assert_eq!(encoded["rows"][1][3], json!("2023-11-15T06:13:22+08:00"));
+
+    let empty = select(&db, "SELECT id, label FROM sightings WHERE id > 9");
+    let encoded: serde_json::Value = serde_json::from_str(&empty.to_json()).unwrap();
+    assert_eq!(
+        encoded,
+        json!({"columns": ["id", "label"], "types": [null, null], "rows": []})
+    );
+}
+
+#[test]
+fn test_streaming_json_matches_materialized() {
+    let (db, _dir) = setup();
+    for i in 3..=200 {
+        db.execute(&format!(
+            "INSERT INTO sightings (id, emb) VALUES ({}, [{}.0, 1.0])",
+            i, i
+        ))
+        .unwrap();
+    }
+
+    for sql in [
+        "SELECT * FROM sightings",
+        "SELECT id, label FROM sightings WHERE id > 150",
+        "SELECT label, COUNT(*) FROM sightings GROUP BY label ORDER BY label",
+    ] {
+        let mut out = Vec::new();
+        let written = db.execute(sql).unwrap().write_json(&mut out).unwrap();
+        let materialized = select(&db, sql);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            materialized.to_json(),
+            "{}",
+            sql
+        );
+        assert_eq!(written, materialized.row_count(), "{}", sql);
+    }
+
+    let mut out = Vec::new();
+    let written = db
+        .execute("DELETE FROM sightings WHERE id > 100")
+        .unwrap()
+        .write_json(&mut out)
+        .unwrap();
+    assert_eq!(written, 0);
+    assert_eq!(String::from_utf8(out).unwrap(), r#"{"affected_rows":100}"#);
+    assert_eq!(
+        QueryResult::Definition {
+            message: "Table created".into()
+        }
+        .to_json(),
+        r#"{"message":"Table created"}"#
+    );
+}
+
+#[test]
+fn test_value_encoding() {
+    let options = JsonOptions::default();
+    let encode = |value: Value| value_to_json(&value, &options);
+
+    assert_eq!(encode(Value::Float(f64::NAN)), json!(null));
+    assert_eq!(encode(Value::Float(f64::INFINITY)), json!(null));
+    assert_eq!(
+        encode(Value::Timestamp(Timestamp::from_micros(1_500))),
+        json!("1970-01-01T00:00:00.0015Z")
+    );
+    assert_eq!(
+        encode(Value::spatial(Geometry::Point3D(Point3D::new(
+            1.0, 2.0, 3.0
+        )))),
+        json!({"type": "Point", "coordinates": [1.0, 2.0, 3.0]})
+    );
+    assert_eq!(
+        encode(Value::spatial(Geometry::LineString(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+        ]))),
+        json!({"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]})
+    );
+    // GeoJSON rings are closed even when the stored polygon is not
+    assert_eq!(
+        encode(Value::spatial(Geometry::Polygon(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+        ]))),
+        json!({"type": "Polygon",
+               "coordinates": [[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.0, 0.0]]]})
+    );
+
+    let list = Value::Array(Box::new(vec![Value::Null, Value::Integer(4)]));
+    assert_eq!(encode(list.clone()), json!([null, 4]));
+    assert_eq!(type_name(&list).as_deref(), Some("ARRAY<INTEGER>"));
+    assert_eq!(type_name(&Value::Null), None);
+}
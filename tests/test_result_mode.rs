@@ -0,0 +1,92 @@
+//! One execution entry point: `QueryExecutor::execute` and the streaming
+//! entry return the same results, including SHOW / DESCRIBE rows and
+//! transaction control.
+
+use motedb::sql::{Lexer, Parser, QueryExecutor, Statement};
+use motedb::types::Value;
+use motedb::{Database, MoteDB, QueryResult, Session};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn parse(sql: &str) -> Statement {
+    Parser::new(Lexer::new(sql).tokenize().unwrap())
+        .parse()
+        .unwrap()
+}
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_show_and_describe_return_rows_on_every_path() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE alpha (id INT PRIMARY KEY, name TEXT)")
+        .unwrap();
+    db.execute("CREATE TABLE beta (id INT PRIMARY KEY)")
+        .unwrap();
+
+    let tables = rows(db.execute("SHOW TABLES").unwrap().materialize().unwrap());
+    assert_eq!(tables.len(), 2);
+    let columns = rows(db.execute("DESCRIBE alpha").unwrap().materialize().unwrap());
+    assert_eq!(columns.len(), 2);
+}
+
+#[test]
+fn test_executor_execute_matches_streaming_entry() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db.clone());
+    s.execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    for i in 0..50 {
+        s.execute(&format!("INSERT INTO t VALUES ({}, {})", i, i % 7))
+            .unwrap();
+    }
+
+    let executor = QueryExecutor::new(db);
+    for sql in [
+        "SELECT * FROM t WHERE v = 3",
+        "SELECT id FROM t WHERE id >= 40 ORDER BY id DESC LIMIT 3",
+        "SELECT v, COUNT(*) FROM t GROUP BY v ORDER BY v",
+        "SHOW TABLES",
+        "DESCRIBE t",
+    ] {
+        let stmt = parse(sql);
+        let streamed = executor.execute_streaming_ref(&stmt).unwrap();
+        assert_eq!(
+            rows(executor.execute(stmt).unwrap()),
+            rows(streamed.materialize().unwrap()),
+            "{}",
+            sql
+        );
+    }
+}
+
+#[test]
+fn test_executor_rollback_restores_updates() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let executor = QueryExecutor::new(db);
+    for sql in [
+        "CREATE TABLE t (id INT PRIMARY KEY, v INT)",
+        "INSERT INTO t VALUES (1, 10)",
+        "BEGIN",
+        "UPDATE t SET v = 20 WHERE id = 1",
+        "ROLLBACK",
+    ] {
+        executor.execute(parse(sql)).unwrap();
+    }
+    assert_eq!(
+        rows(
+            executor
+                .execute(parse("SELECT v FROM t WHERE id = 1"))
+                .unwrap()
+        ),
+        vec![vec![Value::Integer(10)]]
+    );
+}
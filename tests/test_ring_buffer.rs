@@ -0,0 +1,194 @@
+//! Ring-buffer tables (MAX_ROWS / MAX_BYTES)
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn ids(db: &Database, table: &str) -> Vec<i64> {
+    let sql = format!("SELECT id FROM {} ORDER BY id", table);
+    match db.execute(&sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .into_iter()
+            .map(|row| match row[0] {
+                Value::Integer(id) => id,
+                ref other => panic!("unexpected id {:?}", other),
+            })
+            .collect(),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_max_rows_evicts_oldest_rows() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        db.execute(
+            "CREATE TABLE flight_log (id INT PRIMARY KEY, level INT, msg TEXT) MAX_ROWS = 5",
+        )
+        .unwrap();
+        db.execute("CREATE INDEX idx_level ON flight_log (level)")
+            .unwrap();
+
+        // Insertion order, not key order, decides what is evicted
+        for id in [10, 3, 7, 1, 8, 2] {
+            db.execute(&format!(
+                "INSERT INTO flight_log VALUES ({}, {}, 'event')",
+                id,
+                id % 2
+            ))
+            .unwrap();
+        }
+        assert_eq!(ids(&db, "flight_log"), vec![1, 2, 3, 7, 8]);
+
+        // A deleted row frees its slot
+        db.execute("DELETE FROM flight_log WHERE id = 8").unwrap();
+        db.execute("INSERT INTO flight_log VALUES (20, 0, 'event')")
+            .unwrap();
+        assert_eq!(ids(&db, "flight_log"), vec![1, 2, 3, 7, 20]);
+
+        // Multi-row inserts evict as a whole
+        db.execute("INSERT INTO flight_log VALUES (30, 0, 'a'), (31, 1, 'b'), (32, 0, 'c')")
+            .unwrap();
+        assert_eq!(ids(&db, "flight_log"), vec![2, 20, 30, 31, 32]);
+        assert_eq!(db.row_count("flight_log").unwrap(), 5);
+
+        // Evicted rows leave the indexes too
+        match db
+            .execute("SELECT id FROM flight_log WHERE level = 1")
+            .unwrap()
+            .materialize()
+            .unwrap()
+        {
+            QueryResult::Select { rows, .. } => assert_eq!(rows, vec![vec![Value::Integer(31)]]),
+            _ => panic!("Expected Select result"),
+        }
+        db.close().unwrap();
+    }
+
+    // Limits and insertion order survive a restart
+    let db = Database::open(&path).unwrap();
+    db.execute("INSERT INTO flight_log VALUES (40, 0, 'after restart')")
+        .unwrap();
+    assert_eq!(ids(&db, "flight_log"), vec![20, 30, 31, 32, 40]);
+}
+
+#[test]
+fn test_max_bytes_and_write_batches() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute(
+        "CREATE TABLE samples (id INT PRIMARY KEY AUTO_INCREMENT, payload TEXT) MAX_BYTES = 1KB",
+    )
+    .unwrap();
+
+    let payload = "x".repeat(200);
+    for _ in 0..20 {
+        db.execute(&format!(
+            "INSERT INTO samples (payload) VALUES ('{}')",
+            payload
+        ))
+        .unwrap();
+    }
+    let kept = ids(&db, "samples");
+    assert!(
+        kept.len() >= 3 && kept.len() <= 5,
+        "kept {} rows",
+        kept.len()
+    );
+    assert_eq!(*kept.last().unwrap(), 20);
+    assert_eq!(kept, ((21 - kept.len() as i64)..=20).collect::<Vec<_>>());
+
+    // Write batches are tracked too
+    let mut batch = db.write_batch();
+    for _ in 0..10 {
+        batch.insert(
+            "samples",
+            vec![Value::Null, Value::Text(payload.as_str().into())],
+        );
+    }
+    batch.commit().unwrap();
+    let kept = ids(&db, "samples");
+    assert!(kept.len() <= 5);
+    assert_eq!(*kept.last().unwrap(), 30);
+
+    // The newest row is kept even when it alone exceeds the limit
+    db.execute(&format!(
+        "INSERT INTO samples (payload) VALUES ('{}')",
+        "y".repeat(4096)
+    ))
+    .unwrap();
+    assert_eq!(ids(&db, "samples"), vec![31]);
+}
+
+#[test]
+fn test_transactional_inserts_are_evicted_at_commit() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        db.execute(
+            "CREATE TABLE events (id INT PRIMARY KEY AUTO_INCREMENT, kind INT) MAX_ROWS = 10",
+        )
+        .unwrap();
+        for _ in 0..5 {
+            db.execute("INSERT INTO events (kind) VALUES (0)").unwrap();
+        }
+
+        db.execute("BEGIN").unwrap();
+        for i in 0..20 {
+            db.execute(&format!("INSERT INTO events (kind) VALUES ({})", i % 2))
+                .unwrap();
+        }
+        db.execute("COMMIT").unwrap();
+        assert_eq!(ids(&db, "events"), (16..=25).collect::<Vec<_>>());
+        assert_eq!(db.row_count("events").unwrap(), 10);
+
+        // Rows dropped by ROLLBACK TO SAVEPOINT are not tracked
+        let row = || vec![Value::Null, Value::Integer(1)];
+        let txn = db.begin_transaction().unwrap();
+        db.insert_row_with_txn("events", txn, row()).unwrap();
+        db.savepoint(txn, "sp").unwrap();
+        for _ in 0..10 {
+            db.insert_row_with_txn("events", txn, row()).unwrap();
+        }
+        db.rollback_to_savepoint(txn, "sp").unwrap();
+        db.commit_transaction(txn).unwrap();
+        assert_eq!(ids(&db, "events"), (17..=26).collect::<Vec<_>>());
+        db.close().unwrap();
+    }
+
+    // The evicted ranges stay deleted after a restart
+    let db = Database::open(&path).unwrap();
+    assert_eq!(ids(&db, "events"), (17..=26).collect::<Vec<_>>());
+    assert_eq!(db.row_count("events").unwrap(), 10);
+}
+
+#[test]
+fn test_ring_buffer_options_parse() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    match db
+        .execute("CREATE TABLE t (id INT PRIMARY KEY, v INT) MAX_ROWS = 100, MAX_BYTES = 64MB")
+        .unwrap()
+        .materialize()
+        .unwrap()
+    {
+        QueryResult::Definition { message } => {
+            assert!(
+                message.ends_with(", MAX_ROWS 100, MAX_BYTES 67108864"),
+                "{}",
+                message
+            )
+        }
+        _ => panic!("Expected Definition result"),
+    }
+
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY) MAX_ROWS = 0")
+        .is_err());
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY) MAX_BYTES = 0")
+        .is_err());
+}
@@ -0,0 +1,223 @@
+//! ROS 2 ingestion adapter: rosbridge socket, field mapping and backpressure
+#![cfg(feature = "ros2-bridge")]
+
+use motedb::ros2::{
+    Backpressure, IngestConfig, MessageSource, Received, Ros2Ingest, RosMessage, RosbridgeSource,
+    TopicMapping,
+};
+use motedb::types::{Geometry, Value};
+use motedb::{MoteDB, QueryResult, Result, Session};
+use serde_json::json;
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+use tungstenite::protocol::frame::coding::{Data, OpCode};
+use tungstenite::protocol::frame::Frame;
+use tungstenite::Message;
+
+fn select(db: &Arc<MoteDB>, sql: &str) -> Vec<Vec<Value>> {
+    match Session::new(db.clone()).execute(sql).unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_rosbridge_maps_poses_images_and_embeddings() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    Session::new(db.clone())
+        .execute(
+            "CREATE TABLE frames (id INT PRIMARY KEY AUTO_INCREMENT, ts TIMESTAMP, \
+             pose GEOMETRY, frame_id TEXT, image TEXT, embedding VECTOR(3))",
+        )
+        .unwrap();
+
+    // Fake rosbridge: check both subscriptions, publish, then hang up
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let bridge = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut ws = tungstenite::accept(stream).unwrap();
+        let mut subscribed = Vec::new();
+        for _ in 0..2 {
+            let Message::Text(text) = ws.read().unwrap() else {
+                panic!("expected a text message");
+            };
+            let op: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(op["op"], "subscribe");
+            subscribed.push(op["topic"].as_str().unwrap().to_string());
+        }
+        let mut send = |text: String| ws.send(Message::Text(text)).unwrap();
+        for i in 0..3 {
+            let pose = json!({"op": "publish", "topic": "/camera/pose", "msg": {
+                "header": {"stamp": {"sec": 100 + i, "nanosec": 500_000}, "frame_id": "map"},
+                "pose": {"position": {"x": i as f64, "y": 2.0, "z": 0.5},
+                         "orientation": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}},
+                "image": {"encoding": "mono8", "data": [0, 127, 255]},
+                "embedding": [0.1, 0.2, i as f64]
+            }});
+            send(pose.to_string());
+        }
+        // Ignored: status ops, unmapped topics, garbage, and a bad embedding
+        send(r#"{"op": "status", "level": "info", "msg": "ok"}"#.into());
+        send(r#"{"op": "publish", "topic": "/other", "msg": {}}"#.into());
+        send("not json".into());
+        let bad = json!({"op": "publish", "topic": "/camera/pose", "msg": {"embedding": ["x"]}});
+        send(bad.to_string());
+        // A ping, then a message split across two frames
+        ws.send(Message::Ping(b"alive".to_vec())).unwrap();
+        let split = json!({"op": "publish", "topic": "/camera/pose", "msg": {
+            "header": {"stamp": {"sec": 200, "nanosec": 0}, "frame_id": "odom"},
+            "pose": {"position": {"x": 9.0, "y": 9.0, "z": 9.0}}
+        }})
+        .to_string();
+        let (head, tail) = split.split_at(split.len() / 2);
+        let text = OpCode::Data(Data::Text);
+        let rest = OpCode::Data(Data::Continue);
+        ws.send(Message::Frame(Frame::message(head.into(), text, false)))
+            .unwrap();
+        ws.send(Message::Frame(Frame::message(tail.into(), rest, true)))
+            .unwrap();
+        ws.close(None).unwrap();
+        // Drain until the client answers the close
+        while ws.read().is_ok() {}
+        subscribed
+    });
+
+    let mapping = TopicMapping::new("/camera/pose", "custom_msgs/msg/Frame", "frames")
+        .field("ts", "header")
+        .field("pose", "")
+        .field("frame_id", "header.frame_id")
+        .field("image", "image.data")
+        .field("embedding", "embedding");
+    let other = TopicMapping::new("/camera/info", "sensor_msgs/msg/CameraInfo", "frames");
+    let ingest = Ros2Ingest::start(
+        db.clone(),
+        RosbridgeSource::connect(addr).unwrap(),
+        vec![mapping, other],
+        IngestConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(bridge.join().unwrap(), vec!["/camera/pose", "/camera/info"]);
+    while ingest.is_running() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let stats = ingest.stop().unwrap();
+    assert_eq!(stats.received, 5);
+    assert_eq!(stats.inserted, 4);
+    assert_eq!(stats.rejected, 1);
+
+    let rows = select(
+        &db,
+        "SELECT ts, pose, frame_id, image, embedding FROM frames ORDER BY id",
+    );
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[3][2], Value::text_from("odom"));
+    let Value::Timestamp(ts) = &rows[2][0] else {
+        panic!("expected timestamp, got {:?}", rows[2][0]);
+    };
+    assert_eq!(ts.as_micros(), 102_000_500);
+    match &rows[2][1] {
+        Value::Spatial(g) => match **g {
+            Geometry::Point3D(p) => assert_eq!((p.x, p.y, p.z), (2.0, 2.0, 0.5)),
+            ref other => panic!("expected 3D point, got {:?}", other),
+        },
+        other => panic!("expected geometry, got {:?}", other),
+    }
+    assert_eq!(rows[2][2], Value::text_from("map"));
+    assert_eq!(rows[2][3], Value::text_from("AH//"));
+    match &rows[2][4] {
+        Value::Vector(v) => assert_eq!(v.as_slice(), &[0.1, 0.2, 2.0]),
+        other => panic!("expected vector, got {:?}", other),
+    }
+}
+
+/// In-process source fed from a channel
+struct ChannelSource(Receiver<serde_json::Value>);
+
+impl MessageSource for ChannelSource {
+    fn subscribe(&mut self, _topic: &str, _msg_type: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Received> {
+        Ok(match self.0.recv_timeout(timeout) {
+            Ok(msg) => Received::Message(RosMessage {
+                topic: "/imu".into(),
+                msg,
+            }),
+            Err(mpsc::RecvTimeoutError::Timeout) => Received::Timeout,
+            Err(mpsc::RecvTimeoutError::Disconnected) => Received::Closed,
+        })
+    }
+}
+
+#[test]
+fn test_backpressure_policies() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    Session::new(db.clone())
+        .execute("CREATE TABLE imu (seq INT PRIMARY KEY, accel FLOAT)")
+        .unwrap();
+    Session::new(db.clone())
+        .execute("CREATE TABLE imu_lossy (seq INT PRIMARY KEY, accel FLOAT)")
+        .unwrap();
+
+    let run = |table: &str, backpressure| {
+        let (tx, rx) = mpsc::channel();
+        for seq in 0..2000 {
+            tx.send(json!({"seq": seq, "accel": seq as f64 * 0.5}))
+                .unwrap();
+        }
+        drop(tx);
+        let mapping = TopicMapping::new("/imu", "sensor_msgs/msg/Imu", table)
+            .field("seq", "seq")
+            .field("accel", "accel");
+        let config = IngestConfig {
+            batch_size: 64,
+            flush_interval: Duration::from_millis(20),
+            queue_capacity: 8,
+            backpressure,
+        };
+        let ingest =
+            Ros2Ingest::start(db.clone(), ChannelSource(rx), vec![mapping], config).unwrap();
+        while ingest.is_running() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        ingest.stop().unwrap()
+    };
+
+    // Blocking never loses a row, however small the queue
+    let stats = run("imu", Backpressure::Block);
+    assert_eq!(
+        (stats.received, stats.inserted, stats.dropped),
+        (2000, 2000, 0)
+    );
+    assert!(stats.batches >= 2000 / 64);
+    assert_eq!(select(&db, "SELECT seq FROM imu").len(), 2000);
+
+    // Dropping accounts for every row it does not insert
+    let stats = run("imu_lossy", Backpressure::DropNewest);
+    assert_eq!(stats.received, 2000);
+    assert_eq!(stats.inserted + stats.dropped, 2000);
+    assert_eq!(
+        select(&db, "SELECT seq FROM imu_lossy").len() as u64,
+        stats.inserted
+    );
+
+    // Unknown columns are rejected up front
+    let (_tx, rx) = mpsc::channel();
+    let mapping = TopicMapping::new("/imu", "sensor_msgs/msg/Imu", "imu").field("gyro", "gyro");
+    assert!(Ros2Ingest::start(
+        db.clone(),
+        ChannelSource(rx),
+        vec![mapping],
+        IngestConfig::default()
+    )
+    .is_err());
+}
@@ -0,0 +1,67 @@
+//! Versioned row encoding: upgrade-on-read and migrate_row_format
+
+use motedb::storage::row_format;
+use motedb::types::{ColumnType, Decimal, Value};
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_legacy_bincode_row_decodes() {
+    let row = vec![
+        Value::Integer(7),
+        Value::decimal(Decimal::new(-4250, 2).unwrap()),
+        Value::Array(Box::new(vec![Value::Integer(1), Value::Integer(2)])),
+    ];
+    let col_types = [
+        ColumnType::Integer,
+        ColumnType::Decimal {
+            precision: 10,
+            scale: 2,
+        },
+        ColumnType::Array(Box::new(ColumnType::Integer)),
+    ];
+    let legacy = bincode::serialize(&row).unwrap();
+    assert_eq!(row_format::decode(&legacy, &col_types).unwrap(), row);
+
+    let current = row_format::encode(&row, &col_types).unwrap();
+    assert_eq!(row_format::decode(&current, &col_types).unwrap(), row);
+}
+
+#[test]
+fn test_generic_values_survive_reopen_and_migrate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    {
+        let db = Database::create(&path).unwrap();
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, amount DECIMAL(10, 2), ids ARRAY<INT>)")
+            .unwrap();
+        db.execute("INSERT INTO t VALUES (1, 12.50, ARRAY[1, 2])")
+            .unwrap();
+        db.execute("INSERT INTO t VALUES (2, 3.25, ARRAY[3])")
+            .unwrap();
+        db.execute("UPDATE t SET amount = 99.99 WHERE id = 2")
+            .unwrap();
+        db.close().unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    let before = rows(&db, "SELECT id, amount, ids FROM t ORDER BY id");
+    assert_eq!(before.len(), 2);
+    assert_eq!(before[1][1], Value::decimal(Decimal::new(9999, 2).unwrap()));
+
+    // Everything was written by this build, so nothing needs rewriting
+    let stats = db.migrate_row_format().unwrap();
+    assert_eq!(stats.tables, 1);
+    assert_eq!(stats.rows_rewritten, 0);
+    assert_eq!(
+        rows(&db, "SELECT id, amount, ids FROM t ORDER BY id"),
+        before
+    );
+}
@@ -0,0 +1,216 @@
+//! Sessions and the session pool: per-session transactions, settings and
+//! prepared statements, plus the FFI pool API.
+
+use motedb::ffi::*;
+use motedb::types::Value;
+use motedb::{MoteDB, QueryResult, SessionPool};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn open_db(dir: &TempDir) -> Arc<MoteDB> {
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let pool = SessionPool::new(db.clone(), 1);
+    let mut s = pool.acquire(None).unwrap();
+    s.execute("CREATE TABLE t (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    pool.release(s).unwrap();
+    db
+}
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_sessions_have_independent_transactions() {
+    let dir = TempDir::new().unwrap();
+    let pool = SessionPool::new(open_db(&dir), 4);
+
+    let mut a = pool.acquire(None).unwrap();
+    let mut b = pool.acquire(None).unwrap();
+    a.execute("BEGIN").unwrap();
+    a.execute("INSERT INTO t VALUES (1, 10)").unwrap();
+    assert!(a.in_transaction());
+    // Same thread, different session: not inside a's transaction
+    assert!(!b.in_transaction());
+    b.execute("INSERT INTO t VALUES (2, 20)").unwrap();
+    assert!(rows(b.execute("SELECT id FROM t WHERE id = 1").unwrap()).is_empty());
+
+    a.execute("COMMIT").unwrap();
+    assert!(!a.in_transaction());
+    assert_eq!(rows(b.execute("SELECT id FROM t").unwrap()).len(), 2);
+
+    // Releasing a session with an open transaction rolls it back
+    b.execute("BEGIN").unwrap();
+    b.execute("INSERT INTO t VALUES (3, 30)").unwrap();
+    pool.release(b).unwrap();
+    assert_eq!(rows(a.execute("SELECT id FROM t").unwrap()).len(), 2);
+    pool.release(a).unwrap();
+    assert_eq!(pool.stats(), (2, 0));
+}
+
+#[test]
+fn test_session_settings_and_prepared_statements() {
+    let dir = TempDir::new().unwrap();
+    let pool = SessionPool::new(open_db(&dir), 1);
+    let mut s = pool.acquire(None).unwrap();
+
+    s.prepare("ins", "INSERT INTO t VALUES (?, ?)").unwrap();
+    for i in 0..5 {
+        s.execute_prepared("ins", vec![Value::Integer(i), Value::Integer(i * 2)])
+            .unwrap();
+    }
+    assert!(s.execute_prepared("missing", vec![]).is_err());
+
+    s.settings_mut().set("max_rows", "3").unwrap();
+    assert_eq!(rows(s.execute("SELECT * FROM t").unwrap()).len(), 3);
+    s.settings_mut().set("read_only", "on").unwrap();
+    assert!(s.execute("DELETE FROM t").is_err());
+    assert!(s.settings_mut().set("bogus", "1").is_err());
+
+    // Reset on release: settings and statements do not leak to the next user
+    pool.release(s).unwrap();
+    let mut s = pool.acquire(None).unwrap();
+    assert_eq!(s.settings().get("read_only").as_deref(), Some("false"));
+    assert!(!s.deallocate("ins"));
+    assert_eq!(rows(s.execute("SELECT * FROM t").unwrap()).len(), 5);
+    pool.release(s).unwrap();
+}
+
+#[test]
+fn test_pool_bounds_and_threads() {
+    let dir = TempDir::new().unwrap();
+    let pool = Arc::new(SessionPool::new(open_db(&dir), 2));
+
+    let a = pool.acquire(None).unwrap();
+    let _b = pool.acquire(None).unwrap();
+    assert!(pool.acquire(Some(Duration::from_millis(20))).is_err());
+    pool.release(a).unwrap();
+    assert!(pool.acquire(Some(Duration::from_millis(20))).is_ok());
+
+    let dir = TempDir::new().unwrap();
+    let pool = Arc::new(SessionPool::new(open_db(&dir), 2));
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let pool = pool.clone();
+            std::thread::spawn(move || {
+                for i in 0..10 {
+                    let mut s = pool.acquire(None).unwrap();
+                    s.execute("BEGIN").unwrap();
+                    s.execute(&format!("INSERT INTO t VALUES ({}, {})", t * 100 + i, i))
+                        .unwrap();
+                    s.execute("COMMIT").unwrap();
+                    pool.release(s).unwrap();
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    let mut s = pool.acquire(None).unwrap();
+    assert_eq!(rows(s.execute("SELECT * FROM t").unwrap()).len(), 40);
+    pool.release(s).unwrap();
+}
+
+#[test]
+fn test_dropped_session_returns_to_pool() {
+    let dir = TempDir::new().unwrap();
+    let db = open_db(&dir);
+    let pool = SessionPool::new(db.clone(), 1);
+
+    // Dropped without release: the slot and the open transaction go back too
+    let mut s = pool.acquire(None).unwrap();
+    s.execute("BEGIN").unwrap();
+    s.execute("INSERT INTO t VALUES (1, 10)").unwrap();
+    drop(s);
+    assert_eq!(pool.stats(), (1, 0));
+    let mut s = pool.acquire(Some(Duration::from_millis(20))).unwrap();
+    assert!(!s.in_transaction());
+    assert!(rows(s.execute("SELECT * FROM t").unwrap()).is_empty());
+
+    // A session from another pool is rejected and returns to its own pool
+    let other = SessionPool::new(db, 1);
+    let foreign = other.acquire(None).unwrap();
+    assert!(pool.release(foreign).is_err());
+    assert_eq!(other.stats(), (1, 0));
+    assert_eq!(pool.stats(), (0, 1));
+    pool.release(s).unwrap();
+    assert_eq!(pool.stats(), (1, 0));
+}
+
+unsafe fn take_json(ptr: *mut c_char) -> serde_json::Value {
+    assert!(!ptr.is_null());
+    let s = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+    motedb_free_string(ptr);
+    serde_json::from_str(&s).unwrap()
+}
+
+#[test]
+fn test_ffi_pool() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    motedb::Database::create(&path).unwrap().close().unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    unsafe {
+        let h = motedb_open(path.as_ptr());
+        let pool = motedb_pool_create(h, 1);
+        // The pool keeps the database alive on its own
+        motedb_close(h);
+
+        let s = motedb_pool_acquire(pool, -1);
+        assert!(!s.is_null());
+        assert!(motedb_pool_acquire(pool, 10).is_null());
+        assert_eq!(
+            motedb_last_error(ptr::null(), ptr::null_mut()),
+            motedb::ErrorCode::ResourceExhausted as i32
+        );
+
+        let sql = CString::new("CREATE TABLE t (id INT PRIMARY KEY, emb VECTOR(2))").unwrap();
+        take_json(motedb_session_execute(s, sql.as_ptr()));
+        let name = CString::new("ins").unwrap();
+        let sql = CString::new("INSERT INTO t VALUES (?, ?)").unwrap();
+        assert!(motedb_session_prepare(s, name.as_ptr(), sql.as_ptr()));
+        let params = CString::new("[1, [0.5, 1.5]]").unwrap();
+        let r = take_json(motedb_session_execute_prepared(
+            s,
+            name.as_ptr(),
+            params.as_ptr(),
+        ));
+        assert_eq!(r["affected_rows"], 1);
+
+        let begin = CString::new("BEGIN").unwrap();
+        take_json(motedb_session_execute(s, begin.as_ptr()));
+        assert!(motedb_session_in_transaction(s));
+
+        let key = CString::new("read_only").unwrap();
+        let value = CString::new("maybe").unwrap();
+        assert!(!motedb_session_set(s, key.as_ptr(), value.as_ptr()));
+        assert_eq!(
+            motedb_session_last_error(s, ptr::null_mut()),
+            motedb::ErrorCode::InvalidArgument as i32
+        );
+
+        let sql = CString::new("SELECT emb FROM t").unwrap();
+        let r = take_json(motedb_session_execute(s, sql.as_ptr()));
+        assert_eq!(r["rows"][0][0], serde_json::json!([0.5, 1.5]));
+        assert_eq!(motedb_session_last_error(s, ptr::null_mut()), 0);
+        motedb_session_release(s);
+
+        let (mut idle, mut in_use) = (0, 0);
+        motedb_pool_stats(pool, &mut idle, &mut in_use);
+        assert_eq!((idle, in_use), (1, 0));
+
+        let s = motedb_pool_acquire(pool, 0);
+        assert!(!motedb_session_in_transaction(s));
+        motedb_session_release(s);
+        motedb_pool_destroy(pool);
+    }
+}
@@ -0,0 +1,160 @@
+//! SET / SHOW session variables: each Session (and Database handle) carries
+//! its own search and output settings
+
+use motedb::ffi::*;
+use motedb::types::Value;
+use motedb::{Database, MoteDB, QueryResult, Session, TimestampFormat};
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn show(s: &mut Session, name: &str) -> String {
+    match &rows(s.execute(&format!("SHOW {}", name)).unwrap())[0][0] {
+        Value::Text(v) => v.to_string(),
+        other => panic!("expected text, got {:?}", other),
+    }
+}
+
+fn open(dir: &TempDir) -> Arc<MoteDB> {
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db.clone());
+    s.execute("CREATE TABLE notes (id INT PRIMARY KEY, body TEXT, emb VECTOR(2))")
+        .unwrap();
+    s.execute("CREATE TEXT INDEX idx_body ON notes (body)")
+        .unwrap();
+    for i in 0..30 {
+        s.execute(&format!(
+            "INSERT INTO notes VALUES ({}, 'sensor log {}', [{}.0, 1.0])",
+            i, i, i
+        ))
+        .unwrap();
+    }
+    s.execute("CREATE VECTOR INDEX notes_emb ON notes (emb) WITH (metric = 'l2')")
+        .unwrap();
+    db.flush().unwrap();
+    db
+}
+
+#[test]
+fn test_set_and_show_are_per_session() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir);
+    let mut a = Session::new(db.clone());
+    let mut b = Session::new(db);
+
+    a.execute("SET SESSION search_beam_width = 200").unwrap();
+    a.execute("SET time_zone TO '+08:00'").unwrap();
+    a.execute("SET timestamp_format = iso").unwrap();
+    assert_eq!(show(&mut a, "search_beam_width"), "200");
+    assert_eq!(show(&mut a, "TIME_ZONE"), "+08:00");
+    assert_eq!(a.settings().timestamp_format, TimestampFormat::Iso);
+
+    // Another session on the same database keeps its defaults
+    assert_eq!(show(&mut b, "search_beam_width"), "none");
+    assert_eq!(show(&mut b, "time_zone"), "UTC");
+
+    a.execute("SET search_beam_width = DEFAULT").unwrap();
+    assert_eq!(a.settings().search_beam_width, None);
+    assert!(a.execute("SET bogus = 1").is_err());
+    assert!(a.execute("SHOW bogus").is_err());
+    assert!(a.execute("SET time_zone = 'Mars/Olympus'").is_err());
+
+    // A read-only session may tune settings but not lift read_only
+    a.settings_mut().read_only = true;
+    a.execute("SET text_search_limit = 10").unwrap();
+    assert!(a.execute("SET read_only = off").is_err());
+    // COPY TO writes a host file, so it is a write too
+    let err = a.execute("COPY notes TO 'notes.csv'").err().unwrap();
+    assert!(err.to_string().contains("read-only"), "{}", err);
+
+    // Pool reset restores defaults
+    a.reset().unwrap();
+    assert_eq!(show(&mut a, "text_search_limit"), "none");
+}
+
+#[test]
+fn test_search_settings_shape_results() {
+    let dir = TempDir::new().unwrap();
+    let mut s = Session::new(open(&dir));
+
+    let matches = |s: &mut Session| {
+        rows(
+            s.execute("SELECT id FROM notes WHERE MATCH(body) AGAINST('sensor')")
+                .unwrap(),
+        )
+        .len()
+    };
+    assert_eq!(matches(&mut s), 30);
+    s.execute("SET text_search_limit = 5").unwrap();
+    assert_eq!(matches(&mut s), 5);
+
+    // A narrow beam still returns k neighbours
+    s.execute("SET search_beam_width = 1").unwrap();
+    let knn = rows(
+        s.execute("SELECT id FROM notes ORDER BY emb <-> [3.0, 1.0] LIMIT 4")
+            .unwrap(),
+    );
+    assert_eq!(knn[0][0], Value::Integer(3));
+    assert_eq!(knn.len(), 4);
+}
+
+#[test]
+fn test_database_handle_keeps_its_settings() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("SET text_search_limit = 7").unwrap();
+    match db
+        .execute("SHOW text_search_limit")
+        .unwrap()
+        .materialize()
+        .unwrap()
+    {
+        QueryResult::Select { rows, .. } => assert_eq!(rows[0][0], Value::Text("7".into())),
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ffi_session_renders_iso_timestamps() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    Database::create(&path).unwrap().close().unwrap();
+
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    unsafe {
+        let h = motedb_open(path.as_ptr());
+        let pool = motedb_pool_create(h, 1);
+        motedb_close(h);
+        let s = motedb_pool_acquire(pool, -1);
+
+        let query = |sql: &str| -> serde_json::Value {
+            let sql = CString::new(sql).unwrap();
+            let ptr = motedb_session_execute(s, sql.as_ptr());
+            let json = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+            motedb_free_string(ptr);
+            serde_json::from_str(&json).unwrap()
+        };
+        let ts = |r: serde_json::Value| r["rows"][0][0].clone();
+
+        // 2023-11-14T22:13:20Z
+        let sql = "SELECT TIMESTAMP_MICROS(1700000000000000)";
+        assert_eq!(ts(query(sql)), serde_json::json!(1_700_000_000_000_000i64));
+        query("SET timestamp_format = 'iso'");
+        assert_eq!(ts(query(sql)), serde_json::json!("2023-11-14T22:13:20Z"));
+        query("SET time_zone = '+08:00'");
+        assert_eq!(
+            ts(query(sql)),
+            serde_json::json!("2023-11-15T06:13:20+08:00")
+        );
+
+        motedb_session_release(s);
+        motedb_pool_destroy(pool);
+    }
+}
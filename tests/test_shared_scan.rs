@@ -0,0 +1,101 @@
+//! Concurrent full scans of one table share a single pass over it and
+//! still return exactly the rows a scan of their own would
+
+use motedb::types::Value;
+use motedb::{DBConfig, Database, QueryResult};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn ids(db: &Database, sql: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .into_iter()
+            .map(|r| match r[0] {
+                Value::Integer(i) => i,
+                ref v => panic!("unexpected {:?}", v),
+            })
+            .collect(),
+        _ => panic!("Expected Select result"),
+    };
+    ids.sort();
+    ids
+}
+
+/// Ids 0..5000 with v = id * 3, the first half flushed
+fn setup(config: DBConfig) -> (Arc<Database>, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, v INT, tag TEXT)")
+        .unwrap();
+    for id in 0..5000 {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, {}, 't{}')",
+            id,
+            id * 3,
+            id % 10
+        ))
+        .unwrap();
+        if id == 2499 {
+            db.flush().unwrap();
+        }
+    }
+    (Arc::new(db), dir)
+}
+
+const QUERIES: [&str; 3] = [
+    "SELECT id FROM readings WHERE v % 7 = 3",
+    "SELECT id FROM readings WHERE v + id > 19000 OR tag = 't4'",
+    "SELECT id FROM readings WHERE v % 7 = 3 AND id > 100",
+];
+
+fn expected(query: usize) -> Vec<i64> {
+    (0..5000)
+        .filter(|&id| match query {
+            0 => id * 3 % 7 == 3,
+            1 => id * 4 > 19000 || id % 10 == 4,
+            _ => id * 3 % 7 == 3 && id > 100,
+        })
+        .collect()
+}
+
+fn run_concurrently(db: &Arc<Database>) {
+    std::thread::scope(|s| {
+        for t in 0..9 {
+            let db = db.clone();
+            s.spawn(move || {
+                for _ in 0..3 {
+                    assert_eq!(
+                        ids(&db, QUERIES[t % 3]),
+                        expected(t % 3),
+                        "{}",
+                        QUERIES[t % 3]
+                    );
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn test_concurrent_scans_return_their_own_rows() {
+    let (db, _dir) = setup(DBConfig::default());
+    run_concurrently(&db);
+
+    // Rows written after a scan started are seen by the next one
+    db.execute("INSERT INTO readings VALUES (5000, 15004, 'x')")
+        .unwrap();
+    db.execute("DELETE FROM readings WHERE id = 1").unwrap();
+    let mut want = expected(0);
+    want.retain(|&id| id != 1);
+    want.push(5000);
+    assert_eq!(ids(&db, QUERIES[0]), want);
+}
+
+#[test]
+fn test_scans_without_sharing() {
+    let (db, _dir) = setup(DBConfig {
+        shared_scans: false,
+        ..Default::default()
+    });
+    run_concurrently(&db);
+}
@@ -0,0 +1,72 @@
+//! SHOW TRANSACTIONS / SHOW TRANSACTION STATS introspection
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn select(db: &Database, sql: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { columns, rows } => (columns, rows),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_show_transactions_lists_open_transactions() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, name TEXT)")
+        .unwrap();
+
+    let (columns, rows) = select(&db, "SHOW TRANSACTIONS");
+    assert_eq!(columns[0], "txn_id");
+    assert!(rows.is_empty());
+
+    let txn = db.begin_transaction().unwrap();
+    db.insert_row_with_txn("t", txn, vec![Value::Integer(1), Value::text("a".into())])
+        .unwrap();
+    db.insert_row_with_txn("t", txn, vec![Value::Integer(2), Value::text("b".into())])
+        .unwrap();
+
+    let (columns, rows) = select(&db, "show transactions");
+    assert_eq!(rows.len(), 1);
+    let pending = columns.iter().position(|c| c == "pending_writes").unwrap();
+    assert_eq!(rows[0][0], Value::Integer(txn as i64));
+    assert_eq!(rows[0][pending], Value::Integer(2));
+    assert_eq!(db.active_transactions()[0].txn_id, txn);
+
+    db.commit_transaction(txn).unwrap();
+    assert!(select(&db, "SHOW TRANSACTIONS").1.is_empty());
+}
+
+#[test]
+fn test_show_transaction_stats_reports_oldest_snapshot() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+
+    let (columns, rows) = select(&db, "SHOW TRANSACTION STATS");
+    let oldest = columns
+        .iter()
+        .position(|c| c == "oldest_snapshot_ts")
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][oldest], Value::Null);
+
+    let txn = db.begin_transaction().unwrap();
+    let start_ts = db.active_transactions()[0].start_ts;
+    let (_, rows) = select(&db, "SHOW TRANSACTION STATS");
+    assert_eq!(rows[0][0], Value::Integer(1));
+    assert_eq!(rows[0][oldest], Value::Integer(start_ts as i64));
+    assert_eq!(db.transaction_stats().oldest_snapshot_ts, Some(start_ts));
+
+    db.rollback_transaction(txn).unwrap();
+    assert_eq!(db.transaction_stats().oldest_snapshot_ts, None);
+}
+
+#[test]
+fn test_show_rejects_unknown_target() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    assert!(db.execute("SHOW TRANSACTION").is_err());
+    assert!(db.execute("SHOW LOCKS").is_err());
+}
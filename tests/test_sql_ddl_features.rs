@@ -21,8 +21,6 @@ fn msg(result: motedb::StreamingQueryResult) -> String {
 
 // === SHOW TABLES ===
 
-// TODO: SHOW TABLES currently returns Definition in streaming mode, not Select.
-// Test that it doesn't error and returns something.
 #[test]
 fn test_show_tables() {
     let dir = TempDir::new().unwrap();
@@ -33,8 +31,8 @@ fn test_show_tables() {
     db.execute("CREATE TABLE beta (id INT PRIMARY KEY)")
         .unwrap();
 
-    let result = db.execute("SHOW TABLES");
-    assert!(result.is_ok(), "SHOW TABLES should not error");
+    let tables = rows(db.execute("SHOW TABLES").unwrap());
+    assert_eq!(tables.len(), 2);
 }
 
 #[test]
@@ -42,13 +40,11 @@ fn test_show_tables_empty() {
     let dir = TempDir::new().unwrap();
     let db = Database::create(dir.path()).unwrap();
 
-    let result = db.execute("SHOW TABLES");
-    assert!(result.is_ok(), "SHOW TABLES should not error on empty db");
+    assert!(rows(db.execute("SHOW TABLES").unwrap()).is_empty());
 }
 
 // === DESCRIBE ===
 
-// TODO: DESCRIBE currently returns Definition in streaming mode
 #[test]
 fn test_describe_table() {
     let dir = TempDir::new().unwrap();
@@ -57,8 +53,8 @@ fn test_describe_table() {
     db.execute("CREATE TABLE users (id INT PRIMARY KEY, name TEXT, age INT, score FLOAT)")
         .unwrap();
 
-    let result = db.execute("DESCRIBE users");
-    assert!(result.is_ok(), "DESCRIBE should not error");
+    let columns = rows(db.execute("DESCRIBE users").unwrap());
+    assert_eq!(columns.len(), 4);
 }
 
 #[test]
@@ -132,6 +128,143 @@ fn test_alter_table_no_auto_increment() {
     );
 }
 
+#[test]
+fn test_alter_table_drop_column() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = Database::create(dir.path()).unwrap();
+        db.execute("CREATE TABLE readings (id INT PRIMARY KEY, raw TEXT, temp FLOAT, zone INT)")
+            .unwrap();
+        db.execute("CREATE INDEX readings_raw ON readings (raw)")
+            .unwrap();
+        db.execute("CREATE INDEX readings_zone ON readings (zone)")
+            .unwrap();
+        for i in 0..20 {
+            db.execute(&format!(
+                "INSERT INTO readings VALUES ({}, 'r{}', {}.5, {})",
+                i,
+                i,
+                i,
+                i % 3
+            ))
+            .unwrap();
+        }
+        db.flush().unwrap();
+        db.execute("INSERT INTO readings VALUES (20, 'buffered', 20.5, 2)")
+            .unwrap();
+
+        db.execute("ALTER TABLE readings DROP COLUMN raw").unwrap();
+        assert_eq!(rows(db.execute("DESCRIBE readings").unwrap()).len(), 3);
+        assert!(db.execute("SELECT raw FROM readings").is_err());
+
+        // Later columns moved down a position, rows and indexes included
+        let r = rows(db.execute("SELECT * FROM readings WHERE id = 20").unwrap());
+        assert_eq!(
+            r[0],
+            vec![Value::Integer(20), Value::Float(20.5), Value::Integer(2)]
+        );
+        assert_eq!(
+            rows(
+                db.execute("SELECT id FROM readings WHERE zone = 1")
+                    .unwrap()
+            )
+            .len(),
+            7
+        );
+
+        let indexes: Vec<String> = db
+            .index_health()
+            .unwrap()
+            .into_iter()
+            .map(|h| h.name)
+            .collect();
+        assert_eq!(indexes, vec!["readings_zone".to_string()]);
+
+        db.execute("INSERT INTO readings VALUES (21, 21.5, 0)")
+            .unwrap();
+    }
+
+    let db = Database::open(dir.path()).unwrap();
+    let r = rows(
+        db.execute("SELECT temp, zone FROM readings WHERE id = 21")
+            .unwrap(),
+    );
+    assert_eq!(r[0], vec![Value::Float(21.5), Value::Integer(0)]);
+    let r = rows(
+        db.execute("SELECT temp FROM readings WHERE id = 3")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Float(3.5));
+    assert_eq!(
+        rows(db.execute("SELECT * FROM readings").unwrap()).len(),
+        22
+    );
+}
+
+#[test]
+fn test_alter_table_drop_column_refusals() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, a INT)")
+        .unwrap();
+    db.execute("ALTER TABLE t ADD COLUMN b INT AS (a * 2) STORED")
+        .unwrap();
+
+    assert!(
+        db.execute("ALTER TABLE t DROP COLUMN id").is_err(),
+        "primary key"
+    );
+    assert!(
+        db.execute("ALTER TABLE t DROP COLUMN a").is_err(),
+        "used by b"
+    );
+    assert!(db.execute("ALTER TABLE t DROP COLUMN missing").is_err());
+    db.execute("ALTER TABLE t DROP COLUMN b").unwrap();
+    db.execute("ALTER TABLE t DROP COLUMN a").unwrap();
+}
+
+#[test]
+fn test_alter_table_rename_column() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = Database::create(dir.path()).unwrap();
+        db.execute("CREATE TABLE devices (id INT PRIMARY KEY, name TEXT, zone INT)")
+            .unwrap();
+        db.execute("CREATE INDEX devices_zone ON devices (zone)")
+            .unwrap();
+        for i in 0..10 {
+            db.execute(&format!(
+                "INSERT INTO devices VALUES ({}, 'd{}', {})",
+                i,
+                i,
+                i % 2
+            ))
+            .unwrap();
+        }
+
+        db.execute("ALTER TABLE devices RENAME COLUMN zone TO area")
+            .unwrap();
+        assert!(db.execute("SELECT zone FROM devices").is_err());
+        assert!(db
+            .execute("ALTER TABLE devices RENAME name TO area")
+            .is_err());
+
+        db.execute("INSERT INTO devices VALUES (10, 'd10', 1)")
+            .unwrap();
+        let r = rows(db.execute("SELECT id FROM devices WHERE area = 1").unwrap());
+        assert_eq!(r.len(), 6);
+    }
+
+    let db = Database::open(dir.path()).unwrap();
+    db.execute("INSERT INTO devices VALUES (11, 'd11', 1)")
+        .unwrap();
+    let r = rows(db.execute("SELECT id FROM devices WHERE area = 1").unwrap());
+    assert_eq!(r.len(), 7);
+    let health = db.index_health().unwrap();
+    assert_eq!(health.len(), 1);
+    assert_eq!(health[0].column_name, "area");
+}
+
 // === Multi-row INSERT ===
 
 #[test]
@@ -379,3 +512,35 @@ fn test_select_column_alias() {
     let result = db.execute("SELECT val AS value, id AS identifier FROM t");
     assert!(result.is_ok(), "SELECT with aliases should not error");
 }
+
+#[test]
+fn test_alter_table_rename_natural_primary_key() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = Database::create(dir.path()).unwrap();
+        db.execute("CREATE TABLE parts (code TEXT PRIMARY KEY, qty INT)")
+            .unwrap();
+        db.execute("INSERT INTO parts VALUES ('a1', 5), ('b2', 7)")
+            .unwrap();
+        db.execute("ALTER TABLE parts RENAME COLUMN code TO sku")
+            .unwrap();
+        let r = rows(
+            db.execute("SELECT qty FROM parts WHERE sku = 'b2'")
+                .unwrap(),
+        );
+        assert_eq!(r[0][0], Value::Integer(7));
+    }
+
+    let db = Database::open(dir.path()).unwrap();
+    db.execute("INSERT INTO parts VALUES ('c3', 9)").unwrap();
+    let r = rows(
+        db.execute("SELECT qty FROM parts WHERE sku = 'c3'")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Integer(9));
+    let r = rows(
+        db.execute("SELECT qty FROM parts WHERE sku = 'a1'")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Integer(5));
+}
@@ -0,0 +1,150 @@
+//! Statement retry: with `statement_retries` set, a session re-runs a
+//! transaction whose COMMIT lost a write conflict
+
+use motedb::types::Value;
+use motedb::{ErrorCode, MoteDB, QueryResult, Session, StorageError};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn open(dir: &TempDir) -> Arc<MoteDB> {
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db.clone());
+    // Both tables key rows by id, so their row versions share one chain
+    s.execute("CREATE TABLE a (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    s.execute("CREATE TABLE b (id INT PRIMARY KEY, v INT)")
+        .unwrap();
+    s.execute("CREATE TABLE counter (id INT PRIMARY KEY, n INT)")
+        .unwrap();
+    s.execute("INSERT INTO counter VALUES (100, 0)").unwrap();
+    db
+}
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+/// Start a transaction in each session writing row id 1, and commit `first`
+fn race(first: &mut Session, second: &mut Session) {
+    first.execute("BEGIN").unwrap();
+    second.execute("BEGIN").unwrap();
+    first.execute("INSERT INTO a VALUES (1, 10)").unwrap();
+    second.execute("INSERT INTO b VALUES (1, 20)").unwrap();
+    second
+        .execute("UPDATE counter SET n = n + 1 WHERE id = 100")
+        .unwrap();
+    first.execute("COMMIT").unwrap();
+}
+
+#[test]
+fn test_conflict_is_transient_without_retries() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir);
+    let mut first = Session::new(db.clone());
+    let mut second = Session::new(db.clone());
+    race(&mut first, &mut second);
+
+    let err = second.execute("COMMIT").unwrap_err();
+    assert!(matches!(err, StorageError::Conflict(_)), "{:?}", err);
+    assert!(err.is_transient());
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert_eq!(err.code().sqlstate(), "40001");
+    // Without retries the caller still owns the failed transaction
+    assert!(second.in_transaction());
+    second.execute("ROLLBACK").unwrap();
+    assert!(rows(second.execute("SELECT * FROM b").unwrap()).is_empty());
+    assert_eq!(
+        rows(
+            second
+                .execute("SELECT n FROM counter WHERE id = 100")
+                .unwrap()
+        ),
+        vec![vec![Value::Integer(0)]]
+    );
+}
+
+#[test]
+fn test_commit_conflict_reruns_transaction() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir);
+    let mut first = Session::new(db.clone());
+    let mut second = Session::new(db.clone());
+    second.execute("SET statement_retries = 3").unwrap();
+    second.execute("SET retry_backoff_ms = 1").unwrap();
+    second
+        .prepare("ins", "INSERT INTO b VALUES (?, ?)")
+        .unwrap();
+    first.execute("BEGIN").unwrap();
+    second.execute("BEGIN").unwrap();
+    second
+        .execute_prepared_batch("ins", &[vec![Value::Integer(2), Value::Integer(21)]])
+        .unwrap();
+    first.execute("INSERT INTO a VALUES (1, 10)").unwrap();
+    second.execute("INSERT INTO b VALUES (1, 20)").unwrap();
+    second
+        .execute("UPDATE counter SET n = n + 1 WHERE id = 100")
+        .unwrap();
+    first.execute("COMMIT").unwrap();
+
+    second.execute("COMMIT").unwrap();
+    assert!(!second.in_transaction());
+    assert_eq!(
+        rows(second.execute("SELECT id, v FROM b ORDER BY id").unwrap()),
+        vec![
+            vec![Value::Integer(1), Value::Integer(20)],
+            vec![Value::Integer(2), Value::Integer(21)],
+        ]
+    );
+    // The first run's update was rolled back before the re-run applied it
+    assert_eq!(
+        rows(
+            second
+                .execute("SELECT n FROM counter WHERE id = 100")
+                .unwrap()
+        ),
+        vec![vec![Value::Integer(1)]]
+    );
+    assert_eq!(
+        rows(first.execute("SELECT v FROM a WHERE id = 1").unwrap()),
+        vec![vec![Value::Integer(10)]]
+    );
+}
+
+#[test]
+fn test_retries_apply_to_transactions_begun_with_them() {
+    let dir = TempDir::new().unwrap();
+    let db = open(&dir);
+    let mut first = Session::new(db.clone());
+    let mut second = Session::new(db.clone());
+    race(&mut first, &mut second);
+    // Too late: the transaction's statements were not kept
+    second.execute("SET statement_retries = 3").unwrap();
+
+    let err = second.execute("COMMIT").unwrap_err();
+    assert!(err.is_transient());
+    second.execute("ROLLBACK").unwrap();
+}
+
+#[test]
+fn test_retry_settings() {
+    let dir = TempDir::new().unwrap();
+    let mut s = Session::new(open(&dir));
+    assert_eq!(s.settings().get("statement_retries").unwrap(), "none");
+    assert_eq!(s.settings().get("retry_backoff_ms").unwrap(), "none");
+    s.execute("SET SESSION statement_retries = 5").unwrap();
+    s.settings_mut().set("retry_backoff_ms", "20").unwrap();
+    assert_eq!(s.settings().statement_retries, Some(5));
+    assert_eq!(s.settings().retry_backoff_ms, Some(20));
+    s.execute("SET statement_retries = DEFAULT").unwrap();
+    assert_eq!(s.settings().statement_retries, None);
+    assert!(s.settings_mut().set("statement_retries", "many").is_err());
+
+    // Errors that can't go away on their own are returned at once
+    s.execute("SET statement_retries = 3").unwrap();
+    s.execute("INSERT INTO a VALUES (7, 1)").unwrap();
+    let err = s.execute("INSERT INTO a VALUES (7, 2)").unwrap_err();
+    assert!(!err.is_transient());
+}
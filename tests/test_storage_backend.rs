@@ -0,0 +1,188 @@
+//! Pluggable storage backend: WAL and catalog files go through `DBConfig::storage_backend`,
+//! and LSM SSTables are listed and cleaned up through the mounted backend.
+
+use motedb::storage::backend::{self, MemoryBackend, StorageBackend};
+use motedb::storage::lsm::{LSMConfig, LSMEngine, Value, ValueData};
+use motedb::{DBConfig, Database};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn config(backend: &MemoryBackend) -> DBConfig {
+    DBConfig {
+        storage_backend: Some(Arc::new(backend.clone())),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_wal_and_catalog_live_on_backend() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let backend = MemoryBackend::new();
+
+    let db = Database::create_with_config(&path, config(&backend)).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, name TEXT)")
+        .unwrap();
+    db.execute("INSERT INTO t VALUES (1, 'a'), (2, 'b')")
+        .unwrap();
+
+    let files = backend.file_paths();
+    let db_dir = path.with_extension("mote");
+    assert!(files.contains(&db_dir.join("catalog.bin")));
+    assert!(files.contains(&db_dir.join("wal").join("partition_0.wal")));
+    assert!(!db_dir.join("catalog.bin").exists());
+    assert!(!db_dir.join("wal").exists());
+    db.close().unwrap();
+
+    // Reopening with the same backend sees the catalog and the data
+    let db = Database::open_with_config(&path, config(&backend)).unwrap();
+    let rows = db.query("SELECT name FROM t ORDER BY id").unwrap();
+    assert_eq!(rows.len(), 2);
+    db.close().unwrap();
+}
+
+#[test]
+fn test_fresh_backend_has_empty_catalog() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+
+    let backend = MemoryBackend::new();
+    let db = Database::create_with_config(&path, config(&backend)).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY)").unwrap();
+    db.close().unwrap();
+
+    // A different backend does not share the catalog
+    let other = MemoryBackend::new();
+    let db = Database::open_with_config(&path, config(&other)).unwrap();
+    assert!(db.query("SELECT * FROM t").is_err());
+    db.close().unwrap();
+}
+
+#[test]
+fn test_table_and_index_files_live_on_backend() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let backend = MemoryBackend::new();
+
+    let db = Database::create_with_config(&path, config(&backend)).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, cat INT, body TEXT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_cat ON docs(cat)").unwrap();
+    db.execute("CREATE TEXT INDEX idx_body ON docs(body)")
+        .unwrap();
+    for i in 0..200 {
+        db.execute(&format!(
+            "INSERT INTO docs VALUES ({}, {}, 'note number {}')",
+            i,
+            i % 7,
+            i
+        ))
+        .unwrap();
+    }
+    db.flush().unwrap();
+    db.checkpoint().unwrap();
+
+    let db_dir = path.with_extension("mote");
+    let files = backend.file_paths();
+    assert!(files
+        .iter()
+        .any(|p| p.starts_with(db_dir.join("columnar_ms"))));
+    assert!(files.iter().any(|p| p.starts_with(db_dir.join("indexes"))));
+    assert!(!db_dir.join("columnar_ms").exists());
+
+    // The REINDEX swap renames directories on the backend too
+    db.execute("REINDEX idx_cat").unwrap();
+    db.execute("REINDEX INDEX idx_body").unwrap();
+    let postings = db_dir.join("indexes").join("text_idx_body.fts.d");
+    assert!(backend
+        .file_paths()
+        .iter()
+        .any(|p| p.starts_with(&postings)));
+    assert!(!postings.exists());
+    db.close().unwrap();
+
+    let db = Database::open_with_config(&path, config(&backend)).unwrap();
+    let health = db.index_health().unwrap();
+    assert!(health.iter().any(|h| h.name == "idx_cat" && h.entries > 0));
+    let rows = db.query("SELECT id FROM docs WHERE cat = 3").unwrap();
+    assert_eq!(rows.len(), 29);
+    let rows = db
+        .query("SELECT id FROM docs WHERE MATCH(body, 'number')")
+        .unwrap();
+    assert_eq!(rows.len(), 200);
+    db.close().unwrap();
+}
+
+#[test]
+fn test_lsm_sstables_are_rediscovered_on_backend() {
+    let dir = TempDir::new().unwrap();
+    let lsm_dir = dir.path().join("lsm");
+    let backend = MemoryBackend::new();
+    let _mount = backend::mount(dir.path(), Arc::new(backend.clone()));
+
+    {
+        let engine = LSMEngine::new(lsm_dir.clone(), LSMConfig::default()).unwrap();
+        for key in 0..200u64 {
+            engine
+                .put(key, Value::new(key.to_le_bytes().to_vec(), key))
+                .unwrap();
+        }
+        engine.flush().unwrap();
+    }
+    let is_sst = |p: &std::path::PathBuf| p.extension().is_some_and(|e| e == "sst");
+    assert!(backend.file_paths().iter().any(is_sst));
+    assert!(!lsm_dir.exists());
+
+    // Startup drops a leftover flush temp file from the backend
+    let tmp = lsm_dir.join("l0_999.sst.tmp");
+    backend.write_atomic(&tmp, b"partial").unwrap();
+
+    let engine = LSMEngine::new(lsm_dir.clone(), LSMConfig::default()).unwrap();
+    for key in [0u64, 99, 199] {
+        let value = engine.get(key).unwrap().expect("key survives reopen");
+        assert_eq!(
+            value.data,
+            ValueData::Inline(Arc::new(key.to_le_bytes().to_vec()))
+        );
+    }
+    assert!(!backend.exists(&tmp));
+}
+
+#[test]
+fn test_only_lock_file_stays_on_host() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    let backend = MemoryBackend::new();
+
+    let db = Database::create_with_config(&path, config(&backend)).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, name TEXT)")
+        .unwrap();
+    db.execute("CREATE INDEX t_name ON t(name)").unwrap();
+    db.execute("INSERT INTO t VALUES (1, 'a'), (2, 'b')")
+        .unwrap();
+    db.execute("CREATE TABLE accel (ts TIMESTAMP, x FLOAT) TIMESERIES(ts)")
+        .unwrap();
+    db.execute("INSERT INTO accel VALUES (1000, 0.5), (2000, 1.5)")
+        .unwrap();
+    db.flush().unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+
+    let db_dir = path.with_extension("mote");
+    let host: Vec<_> = std::fs::read_dir(&db_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(host, vec![std::ffi::OsString::from(".lock")]);
+    assert!(backend.file_paths().contains(&db_dir.join("lsn_counter")));
+
+    // Creating over an existing database is still refused
+    assert!(Database::create_with_config(&path, config(&backend)).is_err());
+    let db = Database::open_with_config(&path, config(&backend)).unwrap();
+    assert_eq!(
+        db.query("SELECT id FROM t WHERE name = 'b'").unwrap().len(),
+        1
+    );
+    assert_eq!(db.query("SELECT x FROM accel").unwrap().len(), 2);
+    db.close().unwrap();
+}
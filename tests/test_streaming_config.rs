@@ -0,0 +1,95 @@
+//! Streaming batch size and materialize capacity (DBConfig::streaming and
+//! session overrides)
+
+use motedb::types::Value;
+use motedb::{DBConfig, Database, MoteDB, QueryResult, Session, StreamingConfig};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn config(batch_size: usize, materialize_capacity: usize) -> DBConfig {
+    DBConfig {
+        streaming: StreamingConfig {
+            batch_size,
+            materialize_capacity,
+        },
+        ..Default::default()
+    }
+}
+
+fn fill(db: &Database) {
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, sensor INT, v INT)")
+        .unwrap();
+    db.execute("CREATE INDEX idx_sensor ON readings(sensor)")
+        .unwrap();
+    for i in 0..200 {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, {}, {})",
+            i,
+            i % 50,
+            i * 10
+        ))
+        .unwrap();
+    }
+}
+
+fn ids(rows: Vec<Vec<Value>>) -> Vec<i64> {
+    let mut ids: Vec<i64> = rows
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(id) => id,
+            ref other => panic!("expected id, got {:?}", other),
+        })
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_small_batches_return_every_row() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create_with_config(dir.path().join("db"), config(7, 4)).unwrap();
+    fill(&db);
+    assert_eq!(db.streaming_config().batch_size, 7);
+
+    let rows = db
+        .query("SELECT id FROM readings WHERE sensor BETWEEN 10 AND 19 AND v > 500")
+        .unwrap();
+    let expected: Vec<i64> = (0..200)
+        .filter(|i| (10..=19).contains(&(i % 50)) && i * 10 > 500)
+        .collect();
+    assert_eq!(ids(rows), expected);
+}
+
+#[test]
+fn test_session_overrides_streaming_config() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("db");
+    fill(&Database::create(&path).unwrap());
+    let db = Arc::new(MoteDB::open(&path).unwrap());
+    let mut session = Session::new(db);
+
+    let settings = session.settings_mut();
+    settings.set("stream_batch_size", "3").unwrap();
+    settings.set("materialize_capacity", "8").unwrap();
+    assert_eq!(settings.get("stream_batch_size").as_deref(), Some("3"));
+    assert!(settings.set("stream_batch_size", "many").is_err());
+    settings.set("materialize_capacity", "none").unwrap();
+    assert_eq!(settings.materialize_capacity, None);
+
+    let rows = match session
+        .execute("SELECT id FROM readings WHERE sensor BETWEEN 0 AND 4")
+        .unwrap()
+    {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    };
+    let expected: Vec<i64> = (0..200).filter(|i| i % 50 < 5).collect();
+    assert_eq!(ids(rows), expected);
+}
+
+#[test]
+fn test_zero_sizes_are_rejected() {
+    assert!(config(0, 1024).validate().is_err());
+    assert!(config(1000, 0).validate().is_err());
+    assert!(DBConfig::for_edge().validate().is_ok());
+}
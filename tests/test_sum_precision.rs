@@ -0,0 +1,150 @@
+//! SUM/AVG keep their input type exact: INTEGER totals are exact (an
+//! overflow is an error, not a rounded float), FLOAT totals are compensated
+
+use motedb::types::Value;
+use motedb::{Database, ErrorCode, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn error_code(db: &Database, sql: &str) -> ErrorCode {
+    match db.execute(sql).and_then(|r| r.materialize()) {
+        Ok(r) => panic!("Expected an error for {}, got {:?}", sql, r),
+        Err(e) => e.code(),
+    }
+}
+
+/// Totals through the scan, WHERE and GROUP BY paths, as (a, b) group sums
+fn check_sums(db: &Database, all: Value, a: Value, b: Value) {
+    assert_eq!(rows(db, "SELECT SUM(q) FROM t")[0][0], all);
+    assert_eq!(
+        rows(db, "SELECT COUNT(*), SUM(q) FROM t WHERE g = 'a'")[0][1],
+        a
+    );
+    assert_eq!(
+        rows(db, "SELECT SUM(q), MAX(id) FROM t WHERE g = 'b'")[0][0],
+        b
+    );
+    let mut groups = rows(db, "SELECT g, SUM(q) FROM t GROUP BY g");
+    groups.sort_by(|x, y| x[0].partial_cmp(&y[0]).unwrap());
+    assert_eq!(groups[0][1], a);
+    assert_eq!(groups[1][1], b);
+}
+
+#[test]
+fn test_integer_sum_is_exact_and_overflow_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, q INT, g TEXT)")
+        .unwrap();
+    db.execute(&format!(
+        "INSERT INTO t VALUES (1, {}, 'a'), (2, 3, 'b'), (3, 7, 'a')",
+        i64::MAX - 20
+    ))
+    .unwrap();
+    db.flush().unwrap();
+    db.execute("INSERT INTO t VALUES (4, -2, 'a'), (5, 4, 'b')")
+        .unwrap();
+
+    // Beyond 2^53 a float total would round these
+    check_sums(
+        &db,
+        Value::Integer(i64::MAX - 8),
+        Value::Integer(i64::MAX - 15),
+        Value::Integer(7),
+    );
+
+    // The total leaves INTEGER range
+    db.execute("INSERT INTO t VALUES (6, 20, 'a')").unwrap();
+    for sql in [
+        "SELECT SUM(q) FROM t",
+        "SELECT COUNT(*), SUM(q) FROM t WHERE g = 'a'",
+        "SELECT g, SUM(q) FROM t GROUP BY g",
+    ] {
+        assert_eq!(error_code(&db, sql), ErrorCode::NumericOverflow, "{}", sql);
+    }
+    assert_eq!(ErrorCode::NumericOverflow.sqlstate(), "22003");
+
+    // Intermediate overflow is fine as long as the total fits
+    db.execute(&format!("INSERT INTO t VALUES (7, {}, 'a')", -i64::MAX))
+        .unwrap();
+    assert_eq!(rows(&db, "SELECT SUM(q) FROM t")[0][0], Value::Integer(12));
+    let avg = rows(&db, "SELECT AVG(q) FROM t WHERE g = 'b'");
+    assert_eq!(avg[0][0], Value::Float(3.5));
+}
+
+#[test]
+fn test_float_sum_is_compensated() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, q FLOAT, g TEXT)")
+        .unwrap();
+    let insert = |id: i64, q: f64, g: &str| {
+        db.execute_prepared(
+            "INSERT INTO t VALUES (?, ?, ?)",
+            vec![Value::Integer(id), Value::Float(q), Value::text_from(g)],
+        )
+        .unwrap();
+    };
+    // Each 1.0 vanishes when added to 1e16 one at a time
+    insert(0, 1e16, "a");
+    insert(1, 1e16, "b");
+    for id in 2..102 {
+        insert(id, 1.0, if id % 2 == 0 { "a" } else { "b" });
+        if id == 50 {
+            db.flush().unwrap();
+        }
+    }
+    insert(102, -1e16, "a");
+    insert(103, -1e16, "b");
+
+    check_sums(
+        &db,
+        Value::Float(100.0),
+        Value::Float(50.0),
+        Value::Float(50.0),
+    );
+    let avg = rows(&db, "SELECT AVG(q) FROM t");
+    assert_eq!(avg[0][0], Value::Float(100.0 / 104.0));
+}
+
+#[test]
+fn test_text_filter_aggregates_keep_types() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, q INT, g TEXT)")
+        .unwrap();
+    db.execute(&format!(
+        "INSERT INTO t VALUES (1, {}, 'a'), (2, 5, 'a'), (3, NULL, 'a'), (4, 9, 'b')",
+        i64::MAX - 20
+    ))
+    .unwrap();
+    db.flush().unwrap();
+    // Newer versions in a second segment shadow the flushed ones
+    db.execute("UPDATE t SET q = -4 WHERE id = 2").unwrap();
+    db.execute("UPDATE t SET g = 'a' WHERE id = 4").unwrap();
+    db.execute("DELETE FROM t WHERE id = 1").unwrap();
+
+    let r = rows(
+        &db,
+        "SELECT COUNT(*), COUNT(q), SUM(q), MIN(q), MAX(q), AVG(q) FROM t WHERE g = 'a'",
+    );
+    assert_eq!(
+        r[0],
+        vec![
+            Value::Integer(3),
+            Value::Integer(2),
+            Value::Integer(5),
+            Value::Integer(-4),
+            Value::Integer(9),
+            Value::Float(2.5),
+        ]
+    );
+    let r = rows(&db, "SELECT COUNT(*), SUM(q), MIN(q) FROM t WHERE g = 'b'");
+    assert_eq!(r[0], vec![Value::Integer(0), Value::Null, Value::Null]);
+}
@@ -0,0 +1,177 @@
+//! Shape-aware TENSOR columns: shape validation, element access and slicing
+
+use motedb::types::{Tensor, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn tensor(v: &Value) -> &Tensor {
+    match v {
+        Value::Tensor(t) => t,
+        other => panic!("expected tensor, got {:?}", other),
+    }
+}
+
+/// 2 channels of 3x4, element (c, y, x) = c*100 + y*10 + x
+fn feature_map(offset: f32) -> Tensor {
+    let mut data = Vec::new();
+    for c in 0..2 {
+        for y in 0..3 {
+            for x in 0..4 {
+                data.push(offset + (c * 100 + y * 10 + x) as f32);
+            }
+        }
+    }
+    Tensor::with_shape(vec![2, 3, 4], data).unwrap()
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE maps (id INT PRIMARY KEY, fmap TENSOR(2, 3, 4))")
+        .unwrap();
+    for id in 1..=3 {
+        db.insert_row(
+            "maps",
+            vec![
+                Value::Integer(id),
+                Value::tensor(feature_map(id as f32 * 1000.0)),
+            ],
+        )
+        .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_tensor_shape_validation() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    // Flat input of the right size takes the declared shape
+    let flat = (0..24).map(|i| i.to_string()).collect::<Vec<_>>();
+    db.execute(&format!(
+        "INSERT INTO maps VALUES (4, [{}])",
+        flat.join(", ")
+    ))
+    .unwrap();
+    db.execute(
+        "INSERT INTO maps VALUES (5, '[[[1,2,3,4],[5,6,7,8],[9,10,11,12]],\
+         [[13,14,15,16],[17,18,19,20],[21,22,23,24]]]')",
+    )
+    .unwrap();
+    let r = rows(
+        db.execute("SELECT fmap FROM maps WHERE id >= 4 ORDER BY id")
+            .unwrap(),
+    );
+    assert_eq!(tensor(&r[0][0]).shape(), &[2, 3, 4]);
+    assert_eq!(tensor(&r[1][0]).get(&[1, 2, 3]), Some(24.0));
+
+    // Wrong element count, wrong shape, ragged JSON
+    assert!(db
+        .execute("INSERT INTO maps VALUES (6, [1.0, 2.0])")
+        .is_err());
+    let wrong = Tensor::with_shape(vec![4, 3, 2], vec![0.0; 24]).unwrap();
+    assert!(db
+        .insert_row("maps", vec![Value::Integer(6), Value::tensor(wrong)])
+        .is_err());
+    assert!(db
+        .execute("INSERT INTO maps VALUES (6, '[[1, 2], [3]]')")
+        .is_err());
+
+    // Zero-length axes and tensors beyond the storage limit are rejected up front
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, t TENSOR(3, 0))")
+        .is_err());
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, t TENSOR(3, 224, 224))")
+        .is_err());
+    assert!(db.execute("CREATE INDEX idx_fmap ON maps (fmap)").is_err());
+}
+
+#[test]
+fn test_tensor_element_access_and_slicing() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+
+    let r = rows(
+        db.execute(
+            "SELECT TENSOR_SHAPE(fmap), fmap[2][3][4], fmap[-1][1][1], fmap[3] \
+             FROM maps WHERE id = 1",
+        )
+        .unwrap(),
+    );
+    assert_eq!(
+        r[0][0],
+        Value::array(vec![
+            Value::Integer(2),
+            Value::Integer(3),
+            Value::Integer(4)
+        ])
+    );
+    assert_eq!(r[0][1], Value::Float(1123.0));
+    assert_eq!(r[0][2], Value::Float(1100.0));
+    assert_eq!(r[0][3], Value::Null);
+
+    // Second channel, rows 2..3 only
+    let r = rows(
+        db.execute("SELECT TENSOR_SLICE(fmap[2], 1, 2, 3) FROM maps WHERE id = 2")
+            .unwrap(),
+    );
+    let t = tensor(&r[0][0]);
+    assert_eq!(t.shape(), &[2, 4]);
+    assert_eq!(
+        t.as_f32(),
+        &[2110.0, 2111.0, 2112.0, 2113.0, 2120.0, 2121.0, 2122.0, 2123.0]
+    );
+
+    // Last column of every channel and row keeps the rank
+    let r = rows(
+        db.execute("SELECT TENSOR_SLICE(fmap, 3, 4, 4) FROM maps WHERE id = 3")
+            .unwrap(),
+    );
+    let t = tensor(&r[0][0]);
+    assert_eq!(t.shape(), &[2, 3, 1]);
+    assert_eq!(t.get(&[1, 2, 0]), Some(3123.0));
+
+    let r = rows(
+        db.execute("SELECT id FROM maps WHERE fmap[1][1][2] > 2000 ORDER BY id")
+            .unwrap(),
+    );
+    assert_eq!(r, vec![vec![Value::Integer(2)], vec![Value::Integer(3)]]);
+
+    // Out-of-range slices evaluate to NULL like other projection errors
+    let r = rows(
+        db.execute(
+            "SELECT TENSOR_SLICE(fmap, 4, 1, 1), TENSOR_SLICE(fmap, 1, 2, 3) FROM maps WHERE id = 1",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r[0], vec![Value::Null, Value::Null]);
+}
+
+#[test]
+fn test_tensor_survives_flush_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = setup(&dir);
+        db.flush().unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    let r = rows(db.execute("SELECT id, fmap FROM maps ORDER BY id").unwrap());
+    assert_eq!(r.len(), 3);
+    assert_eq!(tensor(&r[1][1]), &feature_map(2000.0));
+
+    let r = rows(
+        db.execute("SELECT fmap[1][2] FROM maps WHERE id = 3")
+            .unwrap(),
+    );
+    assert_eq!(tensor(&r[0][0]).as_f32(), &[3010.0, 3011.0, 3012.0, 3013.0]);
+}
@@ -0,0 +1,228 @@
+//! TIME_BUCKET grouping and SAMPLE BY downsampling
+
+use motedb::types::{Timestamp, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn as_f64(v: &Value) -> f64 {
+    match v {
+        Value::Float(f) => *f,
+        Value::Integer(i) => *i as f64,
+        other => panic!("Expected number, got {:?}", other),
+    }
+}
+
+fn as_micros(v: &Value) -> i64 {
+    match v {
+        Value::Timestamp(ts) => ts.as_micros(),
+        Value::Integer(i) => *i,
+        other => panic!("Expected timestamp, got {:?}", other),
+    }
+}
+
+/// 3 seconds of 1 kHz samples starting at t = 10s; `v` is the sample index.
+fn setup_imu(db: &Database) {
+    db.execute("CREATE TABLE imu (id INT PRIMARY KEY, ts TIMESTAMP, v FLOAT, axis TEXT)")
+        .unwrap();
+    for i in 0..3000i64 {
+        let axis = if i % 2 == 0 { "x" } else { "y" };
+        db.execute(&format!(
+            "INSERT INTO imu VALUES ({}, {}, {}.0, '{}')",
+            i,
+            10_000_000 + i * 1_000,
+            i,
+            axis
+        ))
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_time_bucket_group_by() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup_imu(&db);
+
+    let r = rows(
+        db.execute(
+            "SELECT TIME_BUCKET('1s', ts) AS b, AVG(v), COUNT(*) FROM imu GROUP BY b ORDER BY b",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 3);
+    for (i, row) in r.iter().enumerate() {
+        let i = i as i64;
+        assert_eq!(as_micros(&row[0]), 10_000_000 + i * 1_000_000);
+        assert_eq!(as_f64(&row[1]), (i * 1000) as f64 + 499.5);
+        assert_eq!(row[2], Value::Integer(1000));
+    }
+
+    // Extra group keys and a time range over the timestamp index
+    db.execute("CREATE INDEX idx_imu_ts ON imu (ts)").unwrap();
+    let r = rows(
+        db.execute(
+            "SELECT TIME_BUCKET('500ms', ts) AS b, axis, MAX(v) FROM imu \
+             WHERE ts >= 11000000 AND ts < 12000000 GROUP BY b, axis ORDER BY b, axis",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 4);
+    assert_eq!(as_micros(&r[0][0]), 11_000_000);
+    assert_eq!(r[0][1], Value::text("x".to_string()));
+    assert_eq!(as_f64(&r[0][2]), 1498.0);
+    assert_eq!(r[1][1], Value::text("y".to_string()));
+    assert_eq!(as_f64(&r[1][2]), 1499.0);
+    assert_eq!(as_micros(&r[3][0]), 11_500_000);
+    assert_eq!(as_f64(&r[3][2]), 1999.0);
+
+    // HAVING goes through the general grouping path
+    let r = rows(
+        db.execute(
+            "SELECT TIME_BUCKET('1s', ts) AS b, SUM(v) FROM imu \
+             GROUP BY b HAVING SUM(v) > 1000000 ORDER BY b",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_micros(&r[0][0]), 11_000_000);
+}
+
+#[test]
+fn test_sample_by_timeseries() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE accel (ts TIMESTAMP, x FLOAT, sensor TEXT) TIMESERIES(ts)")
+        .unwrap();
+    let mut data = Vec::new();
+    for i in 0..2000i64 {
+        data.push(vec![
+            Value::Timestamp(Timestamp::from_micros(1_000_000 + i * 1_000)),
+            Value::Float(i as f64),
+            Value::text(if i < 1000 { "a" } else { "b" }.to_string()),
+        ]);
+    }
+    db.columnar_store().ingest("accel", data).unwrap();
+    db.flush().unwrap();
+
+    let r = rows(
+        db.execute("SELECT ts, AVG(x), COUNT(*) FROM accel SAMPLE BY 1s")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_micros(&r[0][0]), 1_000_000);
+    assert_eq!(as_f64(&r[0][1]), 499.5);
+    assert_eq!(r[1][2], Value::Integer(1000));
+
+    let r = rows(
+        db.execute(
+            "SELECT ts, sensor, MIN(x) FROM accel \
+             WHERE ts >= 1500000 AND ts < 2500000 SAMPLE BY 250ms",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 4);
+    assert_eq!(as_micros(&r[0][0]), 1_500_000);
+    assert_eq!(r[0][1], Value::text("a".to_string()));
+    assert_eq!(as_f64(&r[0][2]), 500.0);
+    assert_eq!(as_micros(&r[3][0]), 2_250_000);
+    assert_eq!(r[3][1], Value::text("b".to_string()));
+}
+
+#[test]
+fn test_sample_by_errors() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup_imu(&db);
+
+    assert!(db
+        .execute("SELECT ts, AVG(v) FROM imu SAMPLE BY 1s GROUP BY axis")
+        .and_then(|r| r.materialize())
+        .is_err());
+    assert!(db
+        .execute("SELECT ts, AVG(v) FROM imu SAMPLE BY '3 fortnights'")
+        .and_then(|r| r.materialize())
+        .is_err());
+}
+
+#[test]
+fn test_sample_by_fill() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE temp (id INT PRIMARY KEY, ts TIMESTAMP, sensor TEXT, c FLOAT)")
+        .unwrap();
+    // sensor a reports at 10s and 13s, sensor b only at 11s
+    db.execute("INSERT INTO temp VALUES (1, 10000000, 'a', 10.0)")
+        .unwrap();
+    db.execute("INSERT INTO temp VALUES (2, 13000000, 'a', 40.0)")
+        .unwrap();
+    db.execute("INSERT INTO temp VALUES (3, 11000000, 'b', 5.0)")
+        .unwrap();
+
+    let values = |sql: &str| -> Vec<Option<f64>> {
+        rows(db.execute(sql).unwrap())
+            .iter()
+            .map(|r| match &r[1] {
+                Value::Null => None,
+                v => Some(as_f64(v)),
+            })
+            .collect()
+    };
+    let a = "SELECT ts, AVG(c) FROM temp WHERE sensor = 'a' SAMPLE BY 1s";
+    assert_eq!(values(a), vec![Some(10.0), Some(40.0)]);
+    assert_eq!(
+        values(&format!("{} FILL(NULL)", a)),
+        vec![Some(10.0), None, None, Some(40.0)]
+    );
+    assert_eq!(
+        values(&format!("{} FILL(PREVIOUS)", a)),
+        vec![Some(10.0), Some(10.0), Some(10.0), Some(40.0)]
+    );
+    assert_eq!(
+        values(&format!("{} FILL(LINEAR)", a)),
+        vec![Some(10.0), Some(20.0), Some(30.0), Some(40.0)]
+    );
+
+    // A bounded WHERE range extends the grid past the data
+    let r = rows(
+        db.execute(
+            "SELECT ts, sensor, MAX(c) FROM temp \
+             WHERE ts >= 9000000 AND ts < 15000000 SAMPLE BY 1s FILL(PREV) \
+             ORDER BY sensor, ts",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 12);
+    assert_eq!(as_micros(&r[0][0]), 9_000_000);
+    assert_eq!(r[0][2], Value::Null);
+    assert_eq!(as_f64(&r[3][2]), 10.0);
+    assert_eq!(as_f64(&r[5][2]), 40.0);
+    assert_eq!(r[6][1], Value::text("b".to_string()));
+    assert_eq!(r[7][2], Value::Null);
+    assert_eq!(as_f64(&r[11][2]), 5.0);
+
+    // TIME_BUCKET grouping takes FILL after GROUP BY; LIMIT applies to the filled rows
+    let r = rows(
+        db.execute(
+            "SELECT TIME_BUCKET('1s', ts) AS b, COUNT(*) FROM temp \
+             GROUP BY b FILL(NULL) ORDER BY b DESC LIMIT 2",
+        )
+        .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(as_micros(&r[0][0]), 13_000_000);
+    assert_eq!(as_micros(&r[1][0]), 12_000_000);
+    assert_eq!(r[1][1], Value::Null);
+
+    assert!(db
+        .execute("SELECT sensor, COUNT(*) FROM temp GROUP BY sensor FILL(NULL)")
+        .and_then(|r| r.materialize())
+        .is_err());
+}
@@ -0,0 +1,136 @@
+//! Session time zone: reading timestamp text, TIMESTAMP literals, CAST to
+//! text and AT TIME ZONE
+
+use motedb::types::{Timestamp, Value};
+use motedb::{Database, MoteDB, QueryResult, Session};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// 2024-03-15T00:00:00Z
+const MARCH_15: i64 = 1_710_460_800_000_000;
+const HOUR: i64 = 3_600_000_000;
+
+fn rows(r: QueryResult) -> Vec<Vec<Value>> {
+    match r {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn text(v: &Value) -> String {
+    match v {
+        Value::Text(s) => s.to_string(),
+        other => panic!("expected text, got {:?}", other),
+    }
+}
+
+fn session(dir: &TempDir) -> Session {
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db);
+    s.execute("CREATE TABLE trips (id INT PRIMARY KEY, started TIMESTAMP)")
+        .unwrap();
+    s
+}
+
+#[test]
+fn test_text_is_read_in_session_zone() {
+    let dir = TempDir::new().unwrap();
+    let mut s = session(&dir);
+    s.execute("SET time_zone = '+08:00'").unwrap();
+    s.execute("INSERT INTO trips VALUES (1, '2024-03-15 08:00:00')")
+        .unwrap();
+    // An explicit offset wins over the session zone
+    s.execute("INSERT INTO trips VALUES (2, '2024-03-15T08:00:00Z')")
+        .unwrap();
+    s.execute("INSERT INTO trips VALUES (3, TIMESTAMP '2024-03-16 08:00')")
+        .unwrap();
+    assert!(s
+        .execute("INSERT INTO trips VALUES (4, 'yesterday')")
+        .is_err());
+
+    let mut got = rows(
+        s.execute("SELECT id, started, CAST(started AS TEXT) FROM trips")
+            .unwrap(),
+    );
+    got.sort_by_key(|r| format!("{:?}", r[0]));
+    let expect = [
+        (MARCH_15, "2024-03-15T08:00:00+08:00"),
+        (MARCH_15 + 8 * HOUR, "2024-03-15T16:00:00+08:00"),
+        (MARCH_15 + 24 * HOUR, "2024-03-16T08:00:00+08:00"),
+    ];
+    for (row, (micros, rendered)) in got.iter().zip(expect) {
+        assert_eq!(row[1], Value::Timestamp(Timestamp::from_micros(micros)));
+        assert_eq!(text(&row[2]), rendered);
+    }
+
+    let late = rows(
+        s.execute("SELECT id FROM trips WHERE started >= TIMESTAMP '2024-03-15 16:00'")
+            .unwrap(),
+    );
+    assert_eq!(late.len(), 2);
+
+    s.execute("UPDATE trips SET started = '2024-03-17 08:00' WHERE id = 2")
+        .unwrap();
+    let got = rows(
+        s.execute("SELECT CAST(started AS TEXT) FROM trips WHERE id = 2")
+            .unwrap(),
+    );
+    assert_eq!(text(&got[0][0]), "2024-03-17T08:00:00+08:00");
+}
+
+#[test]
+fn test_at_time_zone() {
+    let dir = TempDir::new().unwrap();
+    let mut s = session(&dir);
+    s.execute("INSERT INTO trips VALUES (1, '2024-03-15 00:00:00')")
+        .unwrap();
+
+    // Instant → wall-clock time in the given zone
+    let got = rows(
+        s.execute(
+            "SELECT HOUR(started AT TIME ZONE '-05:00'), \
+             DATE(started AT TIME ZONE '-05:00') FROM trips",
+        )
+        .unwrap(),
+    );
+    assert_eq!(got[0][0], Value::Integer(19));
+    assert_eq!(got[0][1], Value::Date("2024-03-14".parse().unwrap()));
+
+    // Wall-clock text in the given zone → instant
+    let got = rows(
+        s.execute(
+            "SELECT '2024-03-15 08:00' AT TIME ZONE '+08:00', \
+             TIMESTAMP '2024-03-15 02:00+02:00'",
+        )
+        .unwrap(),
+    );
+    let instant = Value::Timestamp(Timestamp::from_micros(MARCH_15));
+    assert_eq!(got[0], vec![instant.clone(), instant]);
+
+    assert!(s
+        .execute("SELECT TIMESTAMP '2024-03-15' AT TIME ZONE 'Mars/Olympus'")
+        .is_err());
+    assert!(s.execute("SELECT TIMESTAMP 'soon'").is_err());
+}
+
+#[test]
+fn test_database_handle_zone_reaches_fast_paths() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, ts TIMESTAMP)")
+        .unwrap();
+    db.execute("SET time_zone = '-05:00'").unwrap();
+    db.execute("INSERT INTO t VALUES (1, '2024-03-14 19:00')")
+        .unwrap();
+    assert_eq!(
+        db.query("SELECT ts FROM t").unwrap()[0][0],
+        Value::Timestamp(Timestamp::from_micros(MARCH_15))
+    );
+
+    db.execute("UPDATE t SET ts = '2024-03-14 20:00' WHERE id = 1")
+        .unwrap();
+    assert_eq!(
+        db.query("SELECT ts FROM t").unwrap()[0][0],
+        Value::Timestamp(Timestamp::from_micros(MARCH_15 + HOUR))
+    );
+}
@@ -0,0 +1,98 @@
+//! Reorder buffer in front of the timestamp index
+
+use motedb::{DBConfig, Database, TimestampReorderConfig};
+use tempfile::TempDir;
+
+const BASE: i64 = 1_700_000_000_000_000;
+
+/// Insert readings at `BASE + offset_ms` for each offset, in the given order
+fn insert_at(db: &Database, offsets_ms: impl IntoIterator<Item = i64>) {
+    for ms in offsets_ms {
+        db.execute(&format!(
+            "INSERT INTO readings VALUES ({}, {}.0)",
+            BASE + ms * 1_000,
+            ms
+        ))
+        .unwrap();
+    }
+}
+
+/// 0..n ms, each block of 10 reversed (arrives up to 9ms late)
+fn jittered(n: i64) -> Vec<i64> {
+    (0..n)
+        .collect::<Vec<_>>()
+        .chunks(10)
+        .flat_map(|c| c.iter().rev().copied())
+        .collect()
+}
+
+fn count_between(db: &Database, from_ms: i64, to_ms: i64) -> usize {
+    db.query_timestamp_range(BASE + from_ms * 1_000, BASE + to_ms * 1_000)
+        .unwrap()
+        .len()
+}
+
+#[test]
+fn test_out_of_order_ingestion() {
+    let dir = TempDir::new().unwrap();
+    let config = DBConfig {
+        timestamp_reorder: Some(TimestampReorderConfig {
+            lateness_window_micros: 50_000,
+            max_buffered_entries: 10_000,
+        }),
+        ..Default::default()
+    };
+    let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+    db.execute("CREATE TABLE readings (ts TIMESTAMP, v FLOAT)")
+        .unwrap();
+
+    // The timestamp index is (re)built from compacted segments at full checkpoint
+    insert_at(&db, jittered(1000));
+    db.vacuum().unwrap();
+    db.checkpoint_full().unwrap();
+    assert_eq!(count_between(&db, 0, 999), 1000);
+    assert_eq!(count_between(&db, 100, 199), 100);
+
+    let stats = db.timestamp_reorder_stats().unwrap();
+    assert_eq!(stats.buffered, 0);
+    assert_eq!(stats.released, 1000);
+    assert_eq!(stats.reordered, 900);
+    assert_eq!(stats.late, 0);
+
+    // The next second arrives jittered as well and is merged in order
+    insert_at(&db, jittered(2000).into_iter().skip(1000));
+    db.vacuum().unwrap();
+    db.checkpoint_full().unwrap();
+    assert_eq!(count_between(&db, 0, 1999), 2000);
+    assert_eq!(count_between(&db, 995, 1004), 10);
+    let stats = db.timestamp_reorder_stats().unwrap();
+    assert_eq!(stats.buffered, 0);
+    assert_eq!(stats.reordered, 1800);
+}
+
+#[test]
+fn test_reorder_buffer_disabled() {
+    let dir = TempDir::new().unwrap();
+    let config = DBConfig {
+        timestamp_reorder: None,
+        ..Default::default()
+    };
+    let db = Database::create_with_config(dir.path().join("test.mote"), config).unwrap();
+    db.execute("CREATE TABLE readings (ts TIMESTAMP, v FLOAT)")
+        .unwrap();
+    insert_at(&db, jittered(200));
+    db.vacuum().unwrap();
+    db.checkpoint_full().unwrap();
+
+    assert!(db.timestamp_reorder_stats().is_none());
+    assert_eq!(count_between(&db, 0, 199), 200);
+
+    let bad = DBConfig {
+        timestamp_reorder: Some(TimestampReorderConfig {
+            lateness_window_micros: 0,
+            max_buffered_entries: 0,
+        }),
+        ..Default::default()
+    };
+    assert!(Database::create_with_config(dir.path().join("bad.mote"), bad).is_err());
+}
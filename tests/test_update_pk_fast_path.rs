@@ -0,0 +1,113 @@
+//! UPDATE ... WHERE pk = ? goes through the primary-key point path
+
+use motedb::types::Value;
+use motedb::{Database, MoteDB, QueryResult, Session};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE robots (id INT PRIMARY KEY, state TEXT, ticks INT)")
+        .unwrap();
+    for i in 0..20 {
+        db.execute(&format!("INSERT INTO robots VALUES ({}, 'idle', 0)", i))
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_prepared_update_by_pk_applies_every_assignment() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    setup(&db);
+
+    let sql = "UPDATE robots SET state = ?, ticks = ticks + 1 WHERE id = ?";
+    for _ in 0..3 {
+        let result = db
+            .execute_prepared(sql, vec![Value::text("busy".into()), Value::Integer(7)])
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(result.affected_rows(), 1);
+    }
+    db.execute_prepared(
+        "UPDATE robots SET state = 'done', ticks = ? WHERE id = ?",
+        vec![Value::Integer(42), Value::Integer(8)],
+    )
+    .unwrap();
+
+    assert_eq!(
+        rows(&db, "SELECT state, ticks FROM robots WHERE id = 7"),
+        vec![vec![Value::text("busy".into()), Value::Integer(3)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT state, ticks FROM robots WHERE id = 8"),
+        vec![vec![Value::text("done".into()), Value::Integer(42)]]
+    );
+    assert_eq!(
+        rows(&db, "SELECT COUNT(*) FROM robots WHERE state = 'idle'"),
+        vec![vec![Value::Integer(18)]]
+    );
+}
+
+#[test]
+fn test_session_prepared_update_by_pk() {
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(MoteDB::create(dir.path().join("db")).unwrap());
+    let mut session = Session::new(db.clone());
+    session
+        .execute("CREATE TABLE robots (id INT PRIMARY KEY, state TEXT, ticks INT)")
+        .unwrap();
+    session
+        .execute("INSERT INTO robots VALUES (1, 'idle', 0)")
+        .unwrap();
+    session
+        .prepare(
+            "tick",
+            "UPDATE robots SET state = ?, ticks = ticks + 1 WHERE robots.id = ?",
+        )
+        .unwrap();
+
+    let result = session
+        .execute_prepared("tick", vec![Value::text("busy".into()), Value::Integer(1)])
+        .unwrap();
+    assert_eq!(result.affected_rows(), 1);
+    let result = session
+        .execute_prepared("tick", vec![Value::text("busy".into()), Value::Integer(2)])
+        .unwrap();
+    assert_eq!(result.affected_rows(), 0);
+
+    match session.execute("SELECT state, ticks FROM robots").unwrap() {
+        QueryResult::Select { rows, .. } => assert_eq!(
+            rows,
+            vec![vec![Value::text("busy".into()), Value::Integer(1)]]
+        ),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+#[test]
+fn test_prepared_update_by_pk_rolls_back() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    setup(&db);
+
+    db.execute("BEGIN").unwrap();
+    db.execute_prepared(
+        "UPDATE robots SET state = ? WHERE id = ?",
+        vec![Value::text("busy".into()), Value::Integer(3)],
+    )
+    .unwrap();
+    db.execute("ROLLBACK").unwrap();
+
+    assert_eq!(
+        rows(&db, "SELECT state FROM robots WHERE id = 3"),
+        vec![vec![Value::text("idle".into())]]
+    );
+}
@@ -0,0 +1,153 @@
+//! `upsert_embeddings`: ingesting vectors by a user key, resolving existing
+//! rows through the primary key, a column index or a scan.
+
+use motedb::types::Value;
+use motedb::{Database, StorageError};
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn key(k: &str) -> Value {
+    Value::text_from(k)
+}
+
+fn vector(v: &Value) -> Vec<f32> {
+    match v {
+        Value::Vector(v) => v.to_vec(),
+        other => panic!("expected vector, got {:?}", other),
+    }
+}
+
+fn count(db: &Database, table: &str) -> usize {
+    rows(db.execute(&format!("SELECT * FROM {}", table)).unwrap()).len()
+}
+
+#[test]
+fn test_upsert_embeddings_by_primary_key() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE docs (doc_key TEXT PRIMARY KEY, title TEXT, emb VECTOR(2))")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX docs_emb ON docs(emb)")
+        .unwrap();
+
+    let first = db
+        .upsert_embeddings(
+            "docs",
+            "doc_key",
+            vec![
+                (key("a"), vec![1.0, 0.0]),
+                (key("b"), vec![0.0, 1.0]),
+                (key("c"), vec![5.0, 5.0]),
+            ],
+        )
+        .unwrap();
+    assert_eq!((first.inserted, first.updated), (3, 0));
+    assert_eq!(first.row_ids.len(), 3);
+    db.execute("UPDATE docs SET title = 'kept' WHERE doc_key = 'a'")
+        .unwrap();
+
+    // "a" is replaced (last vector wins), "d" is new
+    let second = db
+        .upsert_embeddings(
+            "docs",
+            "doc_key",
+            vec![
+                (key("a"), vec![9.0, 9.0]),
+                (key("d"), vec![-3.0, 0.0]),
+                (key("a"), vec![20.0, 20.0]),
+            ],
+        )
+        .unwrap();
+    assert_eq!((second.inserted, second.updated), (1, 1));
+    assert_eq!(second.row_ids[0], first.row_ids[0]);
+    assert_eq!(second.row_ids[2], first.row_ids[0]);
+    assert_eq!(count(&db, "docs"), 4);
+
+    let a = rows(
+        db.execute("SELECT title, emb FROM docs WHERE doc_key = 'a'")
+            .unwrap(),
+    );
+    assert_eq!(a[0][0], key("kept"));
+    assert_eq!(vector(&a[0][1]), vec![20.0, 20.0]);
+
+    let nearest = db.vector_search("docs_emb", &[19.0, 19.0], 1).unwrap();
+    assert_eq!(nearest[0].0, first.row_ids[0]);
+    // The old vector of "a" is gone from the index
+    let nearest = db.vector_search("docs_emb", &[1.0, 0.1], 1).unwrap();
+    assert_eq!(nearest[0].0, first.row_ids[1]);
+}
+
+#[test]
+fn test_upsert_embeddings_by_indexed_and_scanned_key() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    for table in ["indexed", "scanned"] {
+        db.execute(&format!(
+            "CREATE TABLE {} (id INT PRIMARY KEY AUTO_INCREMENT, sku INT, emb VECTOR(2))",
+            table
+        ))
+        .unwrap();
+    }
+    db.execute("CREATE INDEX indexed_sku ON indexed (sku)")
+        .unwrap();
+
+    for table in ["indexed", "scanned"] {
+        let items = (0..10).map(|i| (Value::Integer(i), vec![i as f32, 0.0]));
+        let first = db.upsert_embeddings(table, "sku", items.collect()).unwrap();
+        assert_eq!((first.inserted, first.updated), (10, 0));
+        db.flush().unwrap();
+
+        let items = (5..15).map(|i| (Value::Integer(i), vec![0.0, i as f32]));
+        let second = db.upsert_embeddings(table, "sku", items.collect()).unwrap();
+        assert_eq!((second.inserted, second.updated), (5, 5), "{}", table);
+        assert_eq!(second.row_ids[..5], first.row_ids[5..]);
+        assert_eq!(count(&db, table), 15);
+
+        let seven = rows(
+            db.execute(&format!("SELECT emb FROM {} WHERE sku = 7", table))
+                .unwrap(),
+        );
+        assert_eq!(seven.len(), 1);
+        assert_eq!(vector(&seven[0][0]), vec![0.0, 7.0]);
+    }
+}
+
+#[test]
+fn test_upsert_embeddings_rejects_bad_input_atomically() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE docs (doc_key TEXT PRIMARY KEY, emb VECTOR(2))")
+        .unwrap();
+    db.execute("CREATE TABLE plain (doc_key TEXT PRIMARY KEY, body TEXT)")
+        .unwrap();
+
+    let err = db
+        .upsert_embeddings(
+            "docs",
+            "doc_key",
+            vec![(key("a"), vec![1.0, 0.0]), (key("b"), vec![1.0])],
+        )
+        .unwrap_err();
+    assert!(matches!(err, StorageError::InvalidArgument(_)), "{:?}", err);
+    let err = db
+        .upsert_embeddings("docs", "doc_key", vec![(Value::Null, vec![1.0, 0.0])])
+        .unwrap_err();
+    assert!(matches!(err, StorageError::InvalidArgument(_)), "{:?}", err);
+    assert_eq!(count(&db, "docs"), 0);
+
+    assert!(matches!(
+        db.upsert_embeddings("docs", "missing", vec![]),
+        Err(StorageError::ColumnNotFound(_))
+    ));
+    assert!(matches!(
+        db.upsert_embeddings("plain", "doc_key", vec![(key("a"), vec![1.0])]),
+        Err(StorageError::InvalidArgument(_))
+    ));
+}
@@ -0,0 +1,216 @@
+//! UUID column type: storage, ordering, indexing and GEN_UUID()
+
+use motedb::types::{Uuid, Value};
+use motedb::Database;
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn uuid(v: &Value) -> Uuid {
+    match v {
+        Value::Uuid(u) => **u,
+        other => panic!("expected Uuid, got {:?}", other),
+    }
+}
+
+fn ints(db: &Database, sql: &str) -> Vec<i64> {
+    rows(db.execute(sql).unwrap())
+        .into_iter()
+        .map(|r| match r[0] {
+            Value::Integer(i) => i,
+            ref v => panic!("unexpected {:?}", v),
+        })
+        .collect()
+}
+
+const A: &str = "0190c6a2-7b3e-7f00-8a1b-2c3d4e5f6a7b";
+const B: &str = "0190c6a2-7b3f-7000-9a1b-2c3d4e5f6a7b";
+
+#[test]
+fn test_uuid_primary_key_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE robots (id UUID PRIMARY KEY, name TEXT)")
+        .unwrap();
+    db.execute(&format!("INSERT INTO robots VALUES ('{}', 'r1')", A))
+        .unwrap();
+    // Upper-case and brace forms parse to the same id
+    db.execute(&format!(
+        "INSERT INTO robots VALUES ('{{{}}}', 'r2')",
+        B.to_uppercase()
+    ))
+    .unwrap();
+    assert!(db
+        .execute(&format!("INSERT INTO robots VALUES ('{}', 'dup')", A))
+        .is_err());
+    assert!(db
+        .execute("INSERT INTO robots VALUES ('not-a-uuid', 'bad')")
+        .is_err());
+
+    let r = rows(
+        db.execute("SELECT id, name FROM robots ORDER BY id")
+            .unwrap(),
+    );
+    assert_eq!(r.len(), 2);
+    assert_eq!(uuid(&r[0][0]).to_string(), A);
+    assert_eq!(uuid(&r[1][0]).to_string(), B);
+
+    let r = rows(
+        db.execute(&format!(
+            "SELECT name FROM robots WHERE id = '{}'",
+            B.to_uppercase()
+        ))
+        .unwrap(),
+    );
+    assert_eq!(r, vec![vec![Value::Text("r2".into())]]);
+    let r = rows(
+        db.execute(&format!("SELECT name FROM robots WHERE id > '{}'", A))
+            .unwrap(),
+    );
+    assert_eq!(r, vec![vec![Value::Text("r2".into())]]);
+}
+
+#[test]
+fn test_gen_uuid_is_time_ordered() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE events (id UUID PRIMARY KEY, seq INT)")
+        .unwrap();
+    for i in 0..50 {
+        db.execute(&format!("INSERT INTO events VALUES (GEN_UUID(), {})", i))
+            .unwrap();
+    }
+    assert_eq!(
+        ints(&db, "SELECT seq FROM events ORDER BY id"),
+        (0..50).collect::<Vec<_>>()
+    );
+    let r = rows(db.execute("SELECT id FROM events").unwrap());
+    assert!(r.iter().all(|row| uuid(&row[0]).version() == 7));
+
+    let r = rows(
+        db.execute("SELECT GEN_RANDOM_UUID(), UUID_TIMESTAMP(GEN_UUID())")
+            .unwrap(),
+    );
+    assert_eq!(uuid(&r[0][0]).version(), 4);
+    assert!(matches!(r[0][1], Value::Timestamp(_)));
+}
+
+#[test]
+fn test_uuid_secondary_index() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY, ref UUID)")
+        .unwrap();
+    let ids: Vec<Uuid> = (0..20).map(|_| Uuid::new_v7()).collect();
+    for (i, u) in ids.iter().enumerate() {
+        db.execute(&format!("INSERT INTO t VALUES ({}, '{}')", i, u))
+            .unwrap();
+    }
+    db.execute("CREATE INDEX idx_ref ON t (ref)").unwrap();
+    db.execute(&format!("INSERT INTO t VALUES (100, '{}')", ids[3]))
+        .unwrap();
+
+    assert_eq!(
+        ints(
+            &db,
+            &format!("SELECT id FROM t WHERE ref = '{}' ORDER BY id", ids[3])
+        ),
+        vec![3, 100]
+    );
+    assert_eq!(
+        ints(
+            &db,
+            &format!("SELECT id FROM t WHERE ref >= '{}' ORDER BY id", ids[18])
+        ),
+        vec![18, 19]
+    );
+}
+
+#[test]
+fn test_uuid_cast_and_reopen() {
+    let dir = TempDir::new().unwrap();
+    {
+        let db = Database::create(dir.path()).unwrap();
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, u UUID)")
+            .unwrap();
+        db.execute(&format!("INSERT INTO t VALUES (1, '{}')", A))
+            .unwrap();
+        db.execute("INSERT INTO t VALUES (2, NULL)").unwrap();
+        db.flush().unwrap();
+        db.execute(&format!("INSERT INTO t VALUES (3, '{}')", B))
+            .unwrap();
+        db.close().unwrap();
+    }
+    let db = Database::open(dir.path()).unwrap();
+    let r = rows(db.execute("SELECT u FROM t ORDER BY id").unwrap());
+    assert_eq!(uuid(&r[0][0]).to_string(), A);
+    assert_eq!(r[1][0], Value::Null);
+    assert_eq!(uuid(&r[2][0]).to_string(), B);
+
+    let r = rows(
+        db.execute("SELECT CAST(u AS TEXT) FROM t WHERE id = 1")
+            .unwrap(),
+    );
+    assert_eq!(r[0][0], Value::Text(A.into()));
+    let r = rows(
+        db.execute(&format!("SELECT CAST('{}' AS UUID)", A.to_uppercase()))
+            .unwrap(),
+    );
+    assert_eq!(uuid(&r[0][0]).to_string(), A);
+    assert_eq!(
+        ints(&db, &format!("SELECT id FROM t WHERE u = '{}'", B)),
+        vec![3]
+    );
+}
+
+#[test]
+fn test_uuid_joins_non_canonical_text() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE u (id INT PRIMARY KEY, k UUID, s TEXT)")
+        .unwrap();
+    let forms = [
+        A.to_string(),
+        A.to_uppercase(),
+        format!("{{{}}}", A),
+        A.replace('-', ""),
+    ];
+    for (i, s) in forms.iter().enumerate() {
+        db.execute(&format!("INSERT INTO u VALUES ({}, '{}', '{}')", i, A, s))
+            .unwrap();
+    }
+
+    // Every text form equals every key, in WHERE and through hash joins
+    assert_eq!(
+        ints(&db, "SELECT id FROM u WHERE k = s ORDER BY id"),
+        vec![0, 1, 2, 3]
+    );
+    assert_eq!(
+        ints(&db, "SELECT COUNT(*) FROM u a JOIN u b ON a.k = b.s"),
+        vec![16]
+    );
+    assert_eq!(
+        ints(&db, "SELECT COUNT(*) FROM u a JOIN u b ON a.s = b.k"),
+        vec![16]
+    );
+    assert_eq!(
+        ints(
+            &db,
+            "SELECT a.id FROM u a JOIN u b ON a.s = b.k WHERE b.id = 0 ORDER BY a.id"
+        ),
+        vec![0, 1, 2, 3]
+    );
+    assert_eq!(
+        ints(
+            &db,
+            "SELECT id FROM u WHERE s IN (SELECT k FROM u) ORDER BY id"
+        ),
+        vec![0, 1, 2, 3]
+    );
+}
@@ -0,0 +1,176 @@
+//! Vector column reads without a full-row fetch: `Database::get_vector` and
+//! point queries that project only VECTOR / TENSOR columns.
+
+use motedb::types::{ArcVec, Tensor, Value};
+use motedb::{Database, StorageError};
+use tempfile::TempDir;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn emb(i: i64) -> Vec<f32> {
+    vec![i as f32, 0.5, -1.0, i as f32 * 2.0]
+}
+
+fn vector(v: &[f32]) -> Value {
+    Value::Vector(ArcVec::new(v.to_vec()))
+}
+
+/// Vector values never compare equal, so compare their elements
+fn floats(row: &[Value]) -> Vec<f32> {
+    match &row[0] {
+        Value::Vector(v) => v.to_vec(),
+        other => panic!("expected vector, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_vector_buffered_and_flushed() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY AUTO_INCREMENT, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 1..=5 {
+        ids.push(
+            db.insert_row(
+                "docs",
+                vec![
+                    Value::Integer(i),
+                    Value::text(format!("body {}", "x".repeat(i as usize))),
+                    vector(&emb(i)),
+                ],
+            )
+            .unwrap(),
+        );
+    }
+    let nulled = db
+        .insert_row(
+            "docs",
+            vec![
+                Value::Integer(6),
+                Value::text("no embedding".to_string()),
+                Value::Null,
+            ],
+        )
+        .unwrap();
+
+    for flushed in [false, true] {
+        if flushed {
+            db.flush().unwrap();
+        }
+        for (i, &rid) in (1..=5).zip(&ids) {
+            assert_eq!(db.get_vector("docs", "emb", rid).unwrap(), Some(emb(i)));
+        }
+        assert_eq!(db.get_vector("docs", "emb", nulled).unwrap(), None);
+        assert_eq!(db.get_vector("docs", "emb", 999).unwrap(), None);
+    }
+
+    // Newest version wins, deleted rows are gone
+    db.execute("UPDATE docs SET emb = [9, 9, 9, 9] WHERE id = 2")
+        .unwrap();
+    db.execute("DELETE FROM docs WHERE id = 3").unwrap();
+    assert_eq!(
+        db.get_vector("docs", "emb", ids[1]).unwrap(),
+        Some(vec![9.0; 4])
+    );
+    assert_eq!(db.get_vector("docs", "emb", ids[2]).unwrap(), None);
+
+    assert!(matches!(
+        db.get_vector("docs", "missing", ids[0]),
+        Err(StorageError::ColumnNotFound(_))
+    ));
+    assert!(matches!(
+        db.get_vector("docs", "body", ids[0]),
+        Err(StorageError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn test_get_vector_flattens_tensors() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE maps (id INT PRIMARY KEY, fmap TENSOR(2, 3))")
+        .unwrap();
+    let data: Vec<f32> = (0..6).map(|i| i as f32).collect();
+    let rid = db
+        .insert_row(
+            "maps",
+            vec![
+                Value::Integer(1),
+                Value::tensor(Tensor::with_shape(vec![2, 3], data.clone()).unwrap()),
+            ],
+        )
+        .unwrap();
+    db.flush().unwrap();
+    assert_eq!(db.get_vector("maps", "fmap", rid).unwrap(), Some(data));
+}
+
+#[test]
+fn test_point_query_projects_vector_column() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    db.execute("CREATE TABLE auto (id INT PRIMARY KEY AUTO_INCREMENT, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    db.execute("CREATE TABLE keyed (id INT PRIMARY KEY, body TEXT, emb VECTOR(4))")
+        .unwrap();
+    for i in 1..=5 {
+        for table in ["auto", "keyed"] {
+            db.execute(&format!(
+                "INSERT INTO {} VALUES ({}, 'doc {}', [{}, 0.5, -1, {}])",
+                table,
+                i,
+                i,
+                i,
+                i * 2
+            ))
+            .unwrap();
+        }
+    }
+
+    for flushed in [false, true] {
+        if flushed {
+            db.flush().unwrap();
+        }
+        for table in ["auto", "keyed"] {
+            let found = rows(
+                db.execute(&format!("SELECT emb FROM {} WHERE id = 3", table))
+                    .unwrap(),
+            );
+            assert_eq!(found.len(), 1, "{}", table);
+            assert_eq!(floats(&found[0]), emb(3), "{}", table);
+
+            let found = rows(
+                db.execute(&format!("SELECT emb AS e, id FROM {} WHERE id = 4", table))
+                    .unwrap(),
+            );
+            assert_eq!(found.len(), 1, "{}", table);
+            assert_eq!(floats(&found[0]), emb(4), "{}", table);
+            assert_eq!(found[0][1], Value::Integer(4));
+
+            // Filters on other columns still apply
+            assert!(
+                rows(
+                    db.execute(&format!(
+                        "SELECT emb FROM {} WHERE id = 4 AND body = 'doc 3'",
+                        table
+                    ))
+                    .unwrap()
+                )
+                .is_empty(),
+                "{}",
+                table
+            );
+            assert!(rows(
+                db.execute(&format!("SELECT emb FROM {} WHERE id = 42", table))
+                    .unwrap()
+            )
+            .is_empty());
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! VECTOR(n, f16|int8) columns: compact row storage, f32 reads, indexing
+
+#[path = "common/mod.rs"]
+mod common;
+
+use common::table_bytes;
+use motedb::types::{Value, VectorEncoding};
+use motedb::Database;
+use std::path::Path;
+use tempfile::TempDir;
+
+const DIM: usize = 64;
+
+fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
+    use motedb::QueryResult;
+    match result.materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn vector(v: &Value) -> Vec<f32> {
+    match v {
+        Value::Vector(v) => v.to_vec(),
+        other => panic!("expected vector, got {:?}", other),
+    }
+}
+
+/// Deterministic embedding; the first component keeps them all distinct
+fn embedding(i: usize) -> Vec<f32> {
+    let mut v: Vec<f32> = (0..DIM)
+        .map(|j| ((i * 31 + j * 17) % 101) as f32 / 10.1 - 5.0)
+        .collect();
+    v[0] = i as f32 / 100.0;
+    v
+}
+
+fn literal(v: &[f32]) -> String {
+    let parts: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+    format!("[{}]", parts.join(", "))
+}
+
+fn setup(path: &Path) -> Database {
+    let db = Database::create(path).unwrap();
+    for (table, ty) in [
+        ("plain", "VECTOR(64)"),
+        ("half", "VECTOR(64, f16)"),
+        ("quant", "VECTOR(64, INT8)"),
+    ] {
+        db.execute(&format!(
+            "CREATE TABLE {} (id INT PRIMARY KEY, emb {})",
+            table, ty
+        ))
+        .unwrap();
+        for i in 0..300 {
+            db.execute(&format!(
+                "INSERT INTO {} VALUES ({}, {})",
+                table,
+                i,
+                literal(&embedding(i))
+            ))
+            .unwrap();
+        }
+    }
+    db
+}
+
+#[test]
+fn test_encoded_vectors_read_back_as_f32() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir.path().join("test.mote"));
+
+    let read = |table: &str| {
+        vector(
+            &rows(
+                db.execute(&format!("SELECT emb FROM {} WHERE id = 7", table))
+                    .unwrap(),
+            )[0][0],
+        )
+    };
+    let original = embedding(7);
+    assert_eq!(read("plain"), original);
+    for (table, enc) in [
+        ("half", VectorEncoding::F16),
+        ("quant", VectorEncoding::Int8),
+    ] {
+        let before = read(table);
+        assert_eq!(before, enc.round_trip(&original).unwrap());
+        let max_err = original
+            .iter()
+            .zip(&before)
+            .fold(0f32, |m, (a, b)| m.max((a - b).abs()));
+        assert!(max_err > 0.0 && max_err < 0.05, "{}: {}", table, max_err);
+
+        // Same values once the rows live in a flushed segment
+        db.flush().unwrap();
+        assert_eq!(read(table), before);
+    }
+
+    assert!(db
+        .execute("CREATE TABLE bad (id INT PRIMARY KEY, emb VECTOR(8, f8))")
+        .is_err());
+    db.execute("CREATE TABLE small (id INT PRIMARY KEY, emb VECTOR(2, f16))")
+        .unwrap();
+    assert!(db
+        .execute("INSERT INTO small VALUES (1, [1.0, 100000.0])")
+        .is_err());
+    assert!(db.execute("INSERT INTO small VALUES (2, [1.0])").is_err());
+    db.execute("INSERT INTO small VALUES (3, NULL)").unwrap();
+    let r = rows(db.execute("SELECT emb FROM small WHERE id = 3").unwrap());
+    assert_eq!(r[0][0], Value::Null);
+}
+
+#[test]
+fn test_encoded_vectors_shrink_segments() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = setup(&path);
+        db.flush().unwrap();
+        db.close().unwrap();
+    }
+    let full = table_bytes(dir.path(), "plain");
+    let half = table_bytes(dir.path(), "half");
+    let quant = table_bytes(dir.path(), "quant");
+    assert!(half < full, "f16 {} vs f32 {}", half, full);
+    assert!(quant < half, "int8 {} vs f16 {}", quant, half);
+
+    let db = Database::open(&path).unwrap();
+    let r = rows(
+        db.execute("SELECT id, emb FROM quant ORDER BY id LIMIT 2")
+            .unwrap(),
+    );
+    assert_eq!(
+        vector(&r[1][1]),
+        VectorEncoding::Int8.round_trip(&embedding(1)).unwrap()
+    );
+}
+
+#[test]
+fn test_encoded_vector_index_search() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    db.execute("CREATE TABLE half (id INT PRIMARY KEY, emb VECTOR(64, f16))")
+        .unwrap();
+    db.execute("CREATE VECTOR INDEX idx_half ON half(emb)")
+        .unwrap();
+    for i in 0..300 {
+        db.execute(&format!(
+            "INSERT INTO half VALUES ({}, {})",
+            i,
+            literal(&embedding(i))
+        ))
+        .unwrap();
+    }
+    db.wait_for_indexes_ready();
+
+    let nearest = |target: usize| {
+        rows(
+            db.execute(&format!(
+                "SELECT id FROM half ORDER BY emb <-> {} LIMIT 1",
+                literal(&embedding(target))
+            ))
+            .unwrap(),
+        )[0][0]
+            .clone()
+    };
+    assert_eq!(nearest(42), Value::Integer(42));
+    db.flush().unwrap();
+    assert_eq!(nearest(7), Value::Integer(7));
+}
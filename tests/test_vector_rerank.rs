@@ -0,0 +1,186 @@
+//! Exact re-ranking of vector search candidates with full-precision vectors,
+//! enabled per index (`WITH (rerank = true)`) or per query / session
+
+use motedb::types::Value;
+use motedb::{Database, MoteDB, QueryResult, Session, VectorSearchOptions};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const DIM: usize = 8;
+const ROWS: usize = 200;
+
+/// Deterministic pseudo-random vectors (LCG), so SQ8 codes don't tie
+fn vectors() -> Vec<Vec<f32>> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..ROWS)
+        .map(|_| {
+            (0..DIM)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((state >> 33) as f32 / (1u64 << 31) as f32) * 2.0 - 1.0
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn literal(v: &[f32]) -> String {
+    let parts: Vec<String> = v.iter().map(|x| format!("{:?}", x)).collect();
+    format!("[{}]", parts.join(", "))
+}
+
+fn setup(with: &str) -> (Database, TempDir, Vec<Vec<f32>>) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute(&format!(
+        "CREATE TABLE items (id INT PRIMARY KEY, emb VECTOR({}))",
+        DIM
+    ))
+    .unwrap();
+    let data = vectors();
+    for (i, v) in data.iter().enumerate() {
+        db.execute(&format!("INSERT INTO items VALUES ({}, {})", i, literal(v)))
+            .unwrap();
+    }
+    db.execute(&format!(
+        "CREATE VECTOR INDEX items_emb ON items(emb) {}",
+        with
+    ))
+    .unwrap();
+    // Searches go through the compressed graph, not the memtable
+    db.flush().unwrap();
+    (db, dir, data)
+}
+
+fn squared_l2(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let na: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let nb: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    1.0 - dot / (na * nb)
+}
+
+/// Hits carry the exact distances of the brute-force top-k, sorted ascending
+fn assert_exact(
+    hits: &[(u64, f32)],
+    data: &[Vec<f32>],
+    query: &[f32],
+    distance: fn(&[f32], &[f32]) -> f32,
+) {
+    assert!(!hits.is_empty());
+    let mut exact: Vec<f32> = data.iter().map(|v| distance(v, query)).collect();
+    exact.sort_by(|a, b| a.total_cmp(b));
+    for ((row_id, dist), want) in hits.iter().zip(&exact) {
+        assert!(
+            (dist - want).abs() < 1e-5,
+            "row {} distance {} != exact {}",
+            row_id,
+            dist,
+            want
+        );
+    }
+}
+
+#[test]
+fn test_rerank_returns_exact_distances() {
+    let (db, _dir, data) = setup("WITH (metric = 'l2')");
+    let query = data[17].clone();
+    let rerank = VectorSearchOptions {
+        rerank: Some(true),
+        ..Default::default()
+    };
+    let hits = db
+        .vector_search_with_options("items_emb", &query, 10, rerank)
+        .unwrap();
+    assert_eq!(hits.len(), 10);
+    assert_exact(&hits, &data, &query, squared_l2);
+    assert!(hits[0].1.abs() < 1e-6);
+}
+
+#[test]
+fn test_rerank_cosine_index_option() {
+    let (db, _dir, data) = setup("WITH (metric = 'cosine', rerank = true)");
+    let query = data[42].clone();
+    // The index setting applies without per-query options
+    let hits = db.vector_search("items_emb", &query, 5).unwrap();
+    assert_eq!(hits.len(), 5);
+    assert_exact(&hits, &data, &query, cosine_distance);
+
+    // ... and a query can still turn it off
+    let off = VectorSearchOptions {
+        rerank: Some(false),
+        ..Default::default()
+    };
+    let hits = db
+        .vector_search_with_options("items_emb", &query, 5, off)
+        .unwrap();
+    assert_eq!(hits.len(), 5);
+}
+
+#[test]
+fn test_session_vector_rerank_setting() {
+    let (db, dir, data) = setup("");
+    drop(db);
+    let db = Arc::new(MoteDB::open(dir.path().join("db")).unwrap());
+    let mut s = Session::new(db);
+
+    let show = |s: &mut Session| match s.execute("SHOW vector_rerank").unwrap() {
+        QueryResult::Select { rows, .. } => match &rows[0][0] {
+            Value::Text(v) => v.to_string(),
+            other => panic!("expected text, got {:?}", other),
+        },
+        other => panic!("expected rows, got {:?}", other),
+    };
+    assert_eq!(show(&mut s), "none");
+    s.execute("SET vector_rerank = on").unwrap();
+    assert_eq!(show(&mut s), "true");
+    assert_eq!(s.settings().vector_rerank, Some(true));
+
+    let query = data[3].clone();
+    let sql = format!(
+        "SELECT id FROM items ORDER BY emb <-> {} LIMIT 5",
+        literal(&query)
+    );
+    let ids: Vec<i64> = match s.execute(&sql).unwrap() {
+        QueryResult::Select { rows, .. } => rows
+            .iter()
+            .map(|r| match r[0] {
+                Value::Integer(id) => id,
+                ref other => panic!("unexpected {:?}", other),
+            })
+            .collect(),
+        other => panic!("expected rows, got {:?}", other),
+    };
+    let mut exact: Vec<(i64, f32)> = data
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i as i64, squared_l2(v, &query)))
+        .collect();
+    exact.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let want: Vec<i64> = exact.iter().take(5).map(|(id, _)| *id).collect();
+    assert_eq!(ids, want);
+
+    s.execute("SET vector_rerank = off").unwrap();
+    assert_eq!(show(&mut s), "false");
+    s.execute("SET vector_rerank = DEFAULT").unwrap();
+    assert_eq!(show(&mut s), "none");
+    assert!(s.execute("SET vector_rerank = 'sometimes'").is_err());
+}
+
+#[test]
+fn test_rerank_option_rejects_invalid_values() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE items (id INT PRIMARY KEY, emb VECTOR(2))")
+        .unwrap();
+    assert!(db
+        .execute("CREATE VECTOR INDEX items_emb ON items(emb) WITH (rerank = 'maybe')")
+        .is_err());
+    db.execute("CREATE VECTOR INDEX items_emb ON items(emb) WITH (rerank = off)")
+        .unwrap();
+}
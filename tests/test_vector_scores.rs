@@ -0,0 +1,172 @@
+//! Distances and text scores selectable in SQL (`emb <-> [...] AS dist`,
+//! `MATCH(...) AGAINST(...) AS score`) for client-side thresholding
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn select(db: &Database, sql: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { columns, rows } => (columns, rows),
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn ids_and_floats(rows: &[Vec<Value>]) -> Vec<(i64, f64)> {
+    rows.iter()
+        .map(|r| match (&r[0], &r[r.len() - 1]) {
+            (Value::Integer(id), Value::Float(d)) => (*id, *d),
+            other => panic!("unexpected row {:?}", other),
+        })
+        .collect()
+}
+
+fn setup(metric: &str) -> (Database, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE items (id INT PRIMARY KEY, name TEXT, emb VECTOR(2))")
+        .unwrap();
+    for i in 0..50 {
+        db.execute(&format!(
+            "INSERT INTO items VALUES ({}, 'n{}', [{}.0, 1.0])",
+            i, i, i
+        ))
+        .unwrap();
+    }
+    db.execute(&format!(
+        "CREATE VECTOR INDEX items_emb ON items(emb) WITH (metric = '{}')",
+        metric
+    ))
+    .unwrap();
+    db.flush().unwrap();
+    // Unflushed rows are found and scored too
+    for i in 50..55 {
+        db.execute(&format!(
+            "INSERT INTO items VALUES ({}, 'n{}', [{}.0, 1.0])",
+            i, i, i
+        ))
+        .unwrap();
+    }
+    (db, dir)
+}
+
+#[test]
+fn test_select_l2_distance_by_alias() {
+    let (db, _dir) = setup("l2");
+    let (columns, rows) = select(
+        &db,
+        "SELECT *, emb <-> [3.0, 0.0] AS dist FROM items ORDER BY dist LIMIT 3",
+    );
+    assert_eq!(columns, vec!["id", "name", "emb", "dist"]);
+    let scored = ids_and_floats(&rows);
+    assert_eq!(scored[0].0, 3);
+    assert!((scored[0].1 - 1.0).abs() < 1e-5);
+    for (_, d) in &scored[1..] {
+        assert!((d - 2f64.sqrt()).abs() < 1e-5);
+    }
+
+    let (_, rows) = select(
+        &db,
+        "SELECT id, emb <-> [52.0, 0.0] AS dist FROM items ORDER BY dist LIMIT 1",
+    );
+    assert_eq!(ids_and_floats(&rows)[0].0, 52);
+
+    // Threshold on the distance
+    let (_, rows) = select(
+        &db,
+        "SELECT id, emb <-> [3.0, 0.0] AS dist FROM items \
+         WHERE emb <-> [3.0, 0.0] < 1.2 ORDER BY dist LIMIT 10",
+    );
+    assert_eq!(
+        ids_and_floats(&rows)
+            .iter()
+            .map(|s| s.0)
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+}
+
+#[test]
+fn test_select_cosine_distance() {
+    let (db, _dir) = setup("cosine");
+    let (_, rows) = select(
+        &db,
+        "SELECT id, emb <=> [3.0, 0.5] AS dist FROM items ORDER BY dist LIMIT 3",
+    );
+    let scored = ids_and_floats(&rows);
+    // [6, 1] points the same way as [3, 0.5]
+    assert_eq!(scored[0].0, 6);
+    assert!(scored[0].1.abs() < 1e-5);
+    assert!(scored.windows(2).all(|w| w[0].1 <= w[1].1));
+
+    // `<->` over a cosine index still orders by L2 distance
+    let (_, rows) = select(
+        &db,
+        "SELECT id, emb <-> [3.0, 0.0] AS dist FROM items ORDER BY dist LIMIT 1",
+    );
+    let scored = ids_and_floats(&rows);
+    assert_eq!(scored[0].0, 3);
+    assert!((scored[0].1 - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_select_and_threshold_text_score() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, body TEXT)")
+        .unwrap();
+    db.execute("CREATE TEXT INDEX docs_body ON docs(body)")
+        .unwrap();
+    for (i, body) in ["rust rust rust", "rust python", "python only", "rust"]
+        .iter()
+        .enumerate()
+    {
+        db.execute(&format!("INSERT INTO docs VALUES ({}, '{}')", i, body))
+            .unwrap();
+    }
+
+    let (columns, rows) = select(
+        &db,
+        "SELECT *, MATCH(body) AGAINST('rust') AS score FROM docs \
+         WHERE MATCH(body) AGAINST('rust') ORDER BY score DESC",
+    );
+    assert_eq!(columns, vec!["id", "body", "score"]);
+    let scored = ids_and_floats(&rows);
+    assert_eq!(scored.len(), 3);
+    assert_eq!(scored[0].0, 0);
+    assert!(scored.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    let threshold = (scored[0].1 + scored[1].1) / 2.0;
+    let (_, rows) = select(
+        &db,
+        &format!(
+            "SELECT id, MATCH(body) AGAINST('rust') AS score FROM docs \
+             WHERE MATCH(body) AGAINST('rust') > {}",
+            threshold
+        ),
+    );
+    assert_eq!(
+        ids_and_floats(&rows)
+            .iter()
+            .map(|s| s.0)
+            .collect::<Vec<_>>(),
+        vec![0]
+    );
+}
+
+#[test]
+fn test_order_by_expression_alias() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE t (id INT PRIMARY KEY)").unwrap();
+    for i in 0..20 {
+        db.execute(&format!("INSERT INTO t VALUES ({})", i))
+            .unwrap();
+    }
+    let (_, rows) = select(&db, "SELECT id, id * -1 AS neg FROM t ORDER BY neg LIMIT 3");
+    let ids: Vec<_> = rows.iter().map(|r| r[0].clone()).collect();
+    assert_eq!(
+        ids,
+        vec![Value::Integer(19), Value::Integer(18), Value::Integer(17)]
+    );
+}
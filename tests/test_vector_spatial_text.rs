@@ -2,8 +2,8 @@
 //! Covers: vector_search, text_search_ranked, create_vector_index,
 //! create_text_index, create_ioctree_index, ioctree_knn_search
 
-use motedb::types::Tensor;
-use motedb::{types::Value, Database};
+use motedb::types::{ArcVec, Tensor};
+use motedb::{types::Value, DBConfig, Database};
 use tempfile::TempDir;
 
 fn rows(result: motedb::StreamingQueryResult) -> Vec<Vec<Value>> {
@@ -62,6 +62,36 @@ fn test_vector_index_create_and_search() {
     }
 }
 
+#[test]
+fn test_vector_index_build_over_cache_capacity() {
+    // More existing rows than the edge profile's vector LRU holds: the
+    // medoid must still resolve once the build batch evicts it
+    let dir = TempDir::new().unwrap();
+    let db = Database::create_with_config(dir.path().join("db"), DBConfig::for_edge()).unwrap();
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, emb VECTOR(4))")
+        .unwrap();
+    let rows: Vec<Vec<Value>> = (0..2000)
+        .map(|i| {
+            vec![
+                Value::Integer(i),
+                Value::Vector(ArcVec::new(vec![i as f32, 1.0, 2.0, 3.0])),
+            ]
+        })
+        .collect();
+    for chunk in rows.chunks(500) {
+        db.execute_prepared_batch("INSERT INTO docs VALUES (?, ?)", chunk.to_vec())
+            .unwrap();
+    }
+
+    db.execute("CREATE VECTOR INDEX docs_emb ON docs(emb)")
+        .unwrap();
+    db.wait_for_indexes_ready();
+    let neighbors = db
+        .vector_search("docs_emb", &[10.0, 1.0, 2.0, 3.0], 5)
+        .unwrap();
+    assert_eq!(neighbors.len(), 5);
+}
+
 #[test]
 fn test_vector_index_stats() {
     let dir = TempDir::new().unwrap();
@@ -187,6 +217,70 @@ fn test_ioctree_knn_search() {
     }
 }
 
+#[test]
+fn test_ioctree_range_beyond_initial_bounds() {
+    use motedb::types::{Geometry, Point, Point3D};
+
+    // Points well outside the octree's initial ±500 world, 2D and 3D, with
+    // the index built both before and after the rows arrive
+    let points: Vec<(f64, f64)> = (0..400)
+        .map(|i| ((i * 37 % 400) as f64 * 2.5, (i * 91 % 400) as f64 * 2.5))
+        .collect();
+    for (two_d, index_first) in [(true, true), (true, false), (false, true), (false, false)] {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        db.execute("CREATE TABLE places (id INT PRIMARY KEY, pos GEOMETRY)")
+            .unwrap();
+        let create_index = || {
+            db.execute("CREATE SPATIAL INDEX places_pos ON places(pos)")
+                .unwrap();
+        };
+        if index_first {
+            create_index();
+        }
+        for (i, (x, y)) in points.iter().enumerate() {
+            let geometry = if two_d {
+                Geometry::Point(Point::new(*x, *y))
+            } else {
+                Geometry::Point3D(Point3D::new(*x, *y, 0.0))
+            };
+            db.insert_row(
+                "places",
+                vec![Value::Integer(i as i64), Value::spatial(geometry)],
+            )
+            .unwrap();
+        }
+        if !index_first {
+            create_index();
+        }
+        db.wait_for_indexes_ready();
+
+        for (min_x, min_y) in [(0.0, 0.0), (480.0, 480.0), (700.0, 100.0), (900.0, 900.0)] {
+            let (max_x, max_y) = (min_x + 100.0, min_y + 100.0);
+            let expected = points
+                .iter()
+                .filter(|(x, y)| *x >= min_x && *x <= max_x && *y >= min_y && *y <= max_y)
+                .count();
+            let found = rows(
+                db.execute(&format!(
+                    "SELECT id FROM places WHERE ST_WITHIN(pos, {:?}, {:?}, {:?}, {:?})",
+                    min_x, min_y, max_x, max_y
+                ))
+                .unwrap(),
+            );
+            assert_eq!(
+                found.len(),
+                expected,
+                "2d={} index_first={} box at ({}, {})",
+                two_d,
+                index_first,
+                min_x,
+                min_y
+            );
+        }
+    }
+}
+
 // === Vector via SQL ORDER BY distance ===
 
 #[test]
@@ -222,6 +316,37 @@ fn test_vector_order_by_distance() {
     }
 }
 
+#[test]
+fn test_delete_with_populated_vector_index() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+
+    db.execute("CREATE TABLE docs (id INT PRIMARY KEY, embedding VECTOR(4))")
+        .unwrap();
+    for i in 0..20 {
+        let row = vec![
+            Value::Integer(i),
+            Value::tensor(Tensor::new(vec![i as f32, 1.0, 0.0, 0.0])),
+        ];
+        db.insert_row("docs", row).unwrap();
+    }
+    db.execute("CREATE VECTOR INDEX docs_embedding ON docs(embedding)")
+        .unwrap();
+    db.wait_for_indexes_ready();
+
+    // Removing a graph node used to deadlock on the node count lock
+    db.execute("DELETE FROM docs WHERE id = 7").unwrap();
+    db.execute("DELETE FROM docs WHERE id < 3").unwrap();
+    db.wait_for_indexes_ready();
+
+    let result = rows(db.execute("SELECT COUNT(*) FROM docs").unwrap());
+    assert_eq!(result[0][0], Value::Integer(16));
+    let neighbors = db
+        .vector_search("docs_embedding", &[7.0, 1.0, 0.0, 0.0], 5)
+        .unwrap();
+    assert!(!neighbors.is_empty());
+}
+
 // === Full-text SQL MATCH AGAINST ===
 
 #[test]
@@ -284,3 +409,81 @@ fn test_create_vector_index_nonexistent_table() {
         "Vector index on nonexistent table should error"
     );
 }
+
+// === NULL geometries and vectors in columnar reads ===
+
+#[test]
+fn test_nullable_spatial_and_vector_columns() {
+    use motedb::types::{ArcVec, Geometry, Point};
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+
+    db.execute("CREATE TABLE poses (id INT PRIMARY KEY, pos GEOMETRY, emb VECTOR(2))")
+        .unwrap();
+    // Odd ids are NULL; a NULL must not shift the values of later rows
+    let row_ids = db
+        .batch_insert(
+            "poses",
+            (1..=4)
+                .map(|i| {
+                    if i % 2 == 0 {
+                        vec![
+                            Value::Integer(i),
+                            Value::spatial(Geometry::Point(Point::new(i as f64, 0.0))),
+                            Value::Vector(ArcVec::new(vec![i as f32, 1.0])),
+                        ]
+                    } else {
+                        vec![Value::Integer(i), Value::Null, Value::Null]
+                    }
+                })
+                .collect(),
+        )
+        .unwrap();
+    let expected = |i: i64| {
+        if i % 2 == 0 {
+            vec![
+                Value::Integer(i),
+                Value::spatial(Geometry::Point(Point::new(i as f64, 0.0))),
+                Value::Vector(ArcVec::new(vec![i as f32, 1.0])),
+            ]
+        } else {
+            vec![Value::Integer(i), Value::Null, Value::Null]
+        }
+    };
+
+    for round in 0..2 {
+        // Value's PartialEq doesn't compare geometries; compare the Debug form
+        assert_eq!(
+            format!("{:?}", db.get_row("poses", row_ids[1]).unwrap()),
+            format!("{:?}", Some(expected(2))),
+            "point read, round {}",
+            round
+        );
+        assert_eq!(
+            format!("{:?}", rows(db.execute("SELECT * FROM poses").unwrap())),
+            format!("{:?}", (1..=4).map(expected).collect::<Vec<_>>())
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                rows(db.execute("SELECT * FROM poses WHERE id > 1").unwrap())
+            ),
+            format!("{:?}", (2..=4).map(expected).collect::<Vec<_>>())
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                rows(
+                    db.execute("SELECT * FROM poses ORDER BY id DESC LIMIT 3")
+                        .unwrap()
+                )
+            ),
+            format!(
+                "{:?}",
+                [4, 3, 2].into_iter().map(expected).collect::<Vec<_>>()
+            )
+        );
+        // Same reads once the rows live in a segment instead of the write buffer
+        db.flush().unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Atomic multi-table write batches (db.write_batch)
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(db: &Database) {
+    db.execute("CREATE TABLE accounts (id INT PRIMARY KEY, balance INT)")
+        .unwrap();
+    db.execute("CREATE TABLE events (id INT PRIMARY KEY AUTO_INCREMENT, note TEXT)")
+        .unwrap();
+    for i in 1..=3 {
+        db.execute(&format!("INSERT INTO accounts VALUES ({}, 100)", i))
+            .unwrap();
+    }
+    db.execute("CREATE INDEX idx_balance ON accounts (balance)")
+        .unwrap();
+}
+
+#[test]
+fn test_write_batch_commits_across_tables() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("test.mote");
+    {
+        let db = Database::create(&path).unwrap();
+        setup(&db);
+
+        let mut batch = db.write_batch();
+        batch
+            .update("accounts", 1, vec![Value::Integer(1), Value::Integer(70)])
+            .update("accounts", 2, vec![Value::Integer(2), Value::Integer(130)])
+            .delete("accounts", 3)
+            .insert("accounts", vec![Value::Integer(4), Value::Integer(5)])
+            .insert("events", vec![Value::Null, Value::Text("transfer".into())])
+            .insert("events", vec![Value::Null, Value::Text("close 3".into())]);
+        assert_eq!(batch.len(), 6);
+        let row_ids = batch.commit().unwrap();
+        assert_eq!(row_ids.len(), 3);
+        assert_eq!(row_ids[0], 4);
+        assert_ne!(row_ids[1], row_ids[2]);
+
+        assert_eq!(
+            rows(&db, "SELECT id, balance FROM accounts ORDER BY id"),
+            vec![
+                vec![Value::Integer(1), Value::Integer(70)],
+                vec![Value::Integer(2), Value::Integer(130)],
+                vec![Value::Integer(4), Value::Integer(5)],
+            ]
+        );
+        assert_eq!(rows(&db, "SELECT note FROM events").len(), 2);
+
+        // Indexes follow the net change of each row
+        assert_eq!(
+            rows(&db, "SELECT id FROM accounts WHERE balance = 130"),
+            vec![vec![Value::Integer(2)]]
+        );
+        assert!(rows(&db, "SELECT id FROM accounts WHERE balance = 100").is_empty());
+        assert_eq!(db.row_count("accounts").unwrap(), 3);
+
+        db.close().unwrap();
+    }
+
+    let db = Database::open(&path).unwrap();
+    assert_eq!(
+        rows(&db, "SELECT id, balance FROM accounts ORDER BY id"),
+        vec![
+            vec![Value::Integer(1), Value::Integer(70)],
+            vec![Value::Integer(2), Value::Integer(130)],
+            vec![Value::Integer(4), Value::Integer(5)],
+        ]
+    );
+    assert_eq!(rows(&db, "SELECT note FROM events").len(), 2);
+}
+
+#[test]
+fn test_write_batch_rejects_whole_batch() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path().join("test.mote")).unwrap();
+    setup(&db);
+    let snapshot = rows(&db, "SELECT id, balance FROM accounts ORDER BY id");
+
+    // Duplicate primary key (against storage, then within the batch)
+    let mut batch = db.write_batch();
+    batch
+        .update("accounts", 1, vec![Value::Integer(1), Value::Integer(0)])
+        .insert("accounts", vec![Value::Integer(2), Value::Integer(1)]);
+    assert!(batch.commit().is_err());
+
+    let mut batch = db.write_batch();
+    batch
+        .insert("accounts", vec![Value::Integer(9), Value::Integer(1)])
+        .insert("accounts", vec![Value::Integer(9), Value::Integer(2)]);
+    assert!(batch.commit().is_err());
+
+    // Missing target row, including one deleted earlier in the batch
+    let mut batch = db.write_batch();
+    batch
+        .delete("accounts", 1)
+        .update("accounts", 1, vec![Value::Integer(1), Value::Integer(0)]);
+    assert!(batch.commit().is_err());
+
+    // Primary key changes must be expressed as delete + insert
+    let mut batch = db.write_batch();
+    batch.update("accounts", 1, vec![Value::Integer(8), Value::Integer(0)]);
+    assert!(batch.commit().is_err());
+
+    // AUTO_INCREMENT overflow, after an explicit id that would have moved
+    // the counter
+    let mut batch = db.write_batch();
+    batch
+        .insert(
+            "events",
+            vec![Value::Integer(i64::MAX - 1000), Value::Text("last".into())],
+        )
+        .insert("events", vec![Value::Null, Value::Text("overflow".into())]);
+    assert!(batch.commit().is_err());
+
+    assert_eq!(
+        rows(&db, "SELECT id, balance FROM accounts ORDER BY id"),
+        snapshot
+    );
+    assert!(rows(&db, "SELECT note FROM events").is_empty());
+
+    // Delete then re-insert of the same key is allowed
+    let mut batch = db.write_batch();
+    batch
+        .delete("accounts", 1)
+        .insert("accounts", vec![Value::Integer(1), Value::Integer(55)]);
+    batch.commit().unwrap();
+    assert_eq!(
+        rows(&db, "SELECT balance FROM accounts WHERE id = 1"),
+        vec![vec![Value::Integer(55)]]
+    );
+    assert_eq!(db.row_count("accounts").unwrap(), 3);
+    assert!(db.write_batch().commit().unwrap().is_empty());
+
+    // The rejected batches took no AUTO_INCREMENT ids
+    let mut batch = db.write_batch();
+    batch.insert("events", vec![Value::Null, Value::Text("first".into())]);
+    assert_eq!(batch.commit().unwrap(), vec![1]);
+}
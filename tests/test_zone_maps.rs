@@ -0,0 +1,63 @@
+//! Zone maps: per-block min/max ranges let filtered scans on non-indexed
+//! columns skip blocks that can't match
+
+use motedb::types::Value;
+use motedb::{Database, QueryResult};
+use tempfile::TempDir;
+
+fn query(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        _ => panic!("Expected Select result"),
+    }
+}
+
+fn setup(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path().join("db")).unwrap();
+    db.execute("CREATE TABLE readings (id INT PRIMARY KEY, seq INT, temp FLOAT, site TEXT)")
+        .unwrap();
+    let ids: Vec<i64> = (0..20_000).collect();
+    for chunk in ids.chunks(1000) {
+        let values: Vec<String> = chunk
+            .iter()
+            .map(|id| format!("({}, {}, {}.5, 's{}')", id, id, id / 10, id % 5))
+            .collect();
+        db.execute(&format!(
+            "INSERT INTO readings VALUES {}",
+            values.join(", ")
+        ))
+        .unwrap();
+    }
+    db.flush().unwrap();
+    db
+}
+
+#[test]
+fn test_range_predicates_skip_blocks() {
+    let dir = TempDir::new().unwrap();
+    let db = setup(&dir);
+    assert_eq!(db.optimizer_stats().zone_skipped_rows, 0);
+
+    assert_eq!(
+        query(&db, "SELECT COUNT(*) FROM readings WHERE seq >= 19990"),
+        vec![vec![Value::Integer(10)]]
+    );
+    let after_count = db.optimizer_stats().zone_skipped_rows;
+    assert!(after_count >= 16_384, "skipped {after_count}");
+
+    assert_eq!(
+        query(&db, "SELECT SUM(seq) FROM readings WHERE temp < 2.0"),
+        vec![vec![Value::Integer((0..20).sum())]]
+    );
+    assert!(db.optimizer_stats().zone_skipped_rows > after_count);
+
+    // Predicates no block can satisfy, and columns without ranges
+    assert_eq!(
+        query(&db, "SELECT COUNT(*) FROM readings WHERE seq > 50000"),
+        vec![vec![Value::Integer(0)]]
+    );
+    assert_eq!(
+        query(&db, "SELECT COUNT(*) FROM readings WHERE site = 's3'"),
+        vec![vec![Value::Integer(4000)]]
+    );
+}
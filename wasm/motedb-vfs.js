@@ -0,0 +1,467 @@
+// Browser glue for `motedb::storage::HostVfsBackend`: keeps a MoteDB
+// database in the Origin Private File System (OPFS).
+//
+// The wasm module calls its `motedb_vfs` imports synchronously, but most of
+// the OPFS API (opening files, listing and removing entries) is async. The
+// imports therefore run in the worker that hosts the database and hand every
+// call to a second, dedicated I/O worker over a SharedArrayBuffer, blocking
+// with `Atomics.wait` until the I/O worker has answered:
+//
+//   // main page (needs cross-origin isolation for SharedArrayBuffer)
+//   const channel = createVfsChannel();
+//   ioWorker.postMessage(channel);   // io worker: serveVfs(channel, await navigator.storage.getDirectory())
+//   dbWorker.postMessage(channel);   // db worker: see below
+//
+//   // db worker
+//   const imports = { wasi_snapshot_preview1: wasi.wasiImport,
+//                     ...vfsImports(channel, () => instance.exports.memory) };
+//
+// and open the database with `DBConfig { storage_backend:
+// Some(Arc::new(HostVfsBackend::new())), .. }`. Paths are resolved relative
+// to the directory handle given to `serveVfs`.
+//
+// OPFS allows one sync access handle per file, so the I/O worker shares one
+// handle between every descriptor open on the same path. Directory renames
+// (REINDEX swaps) are done as copy + delete, which OPFS cannot make atomic.
+
+const IDLE = 0;
+const REQUEST = 1;
+const RESPONSE = 2;
+const STOP = 3;
+
+// Int32 slots of the control block
+const STATE = 0;
+const OP = 1;
+const ARG = 2;
+const LEN1 = 3;
+const LEN2 = 4;
+const LOCK = 5;
+// Float64 slots, starting at byte 32
+const NUM = 0;
+const RESULT = 1;
+const DATA_OFFSET = 48;
+
+const OP_OPEN = 1;
+const OP_CLOSE = 2;
+const OP_READ = 3;
+const OP_WRITE = 4;
+const OP_SIZE = 5;
+const OP_TRUNCATE = 6;
+const OP_FLUSH = 7;
+const OP_STAT = 8;
+const OP_MKDIR = 9;
+const OP_RENAME = 10;
+const OP_REMOVE_FILE = 11;
+const OP_REMOVE_DIR = 12;
+const OP_LIST = 13;
+
+// Error codes understood by host_vfs.rs
+const E_NOT_FOUND = -1;
+const E_EXISTS = -2;
+const E_DENIED = -3;
+const E_INVALID = -5;
+const E_FULL = -6;
+const E_OTHER = -7;
+
+// `vfs_open` flags (host_vfs.rs FLAG_*)
+const FLAG_CREATE = 8;
+const FLAG_TRUNCATE = 16;
+
+/** Shared buffer both workers talk through; `capacity` bounds one transfer. */
+export function createVfsChannel(capacity = 1 << 20) {
+  return new SharedArrayBuffer(DATA_OFFSET + capacity);
+}
+
+/** Ask a running `serveVfs` loop to return. */
+export function stopVfs(channel) {
+  const ctl = new Int32Array(channel, 0, 8);
+  Atomics.store(ctl, STATE, STOP);
+  Atomics.notify(ctl, STATE);
+}
+
+// ==================== Database side ====================
+
+/**
+ * The `motedb_vfs` import module. `getMemory` returns the instance's
+ * `WebAssembly.Memory` (called on every access since memory can grow).
+ */
+export function vfsImports(channel, getMemory) {
+  const ctl = new Int32Array(channel, 0, 8);
+  const num = new Float64Array(channel, 32, 2);
+  const data = new Uint8Array(channel, DATA_OFFSET);
+  const bytes = (ptr, len) => new Uint8Array(getMemory().buffer, ptr, len);
+
+  // One request at a time, even with several wasm threads. `data` belongs
+  // to the caller until the lock is released, so `onResult` copies the
+  // answer out inside the critical section. Reads send no payload and pass
+  // the wanted length as `readLen` instead.
+  function call(op, arg, n, payloads = [], onResult = null, readLen = 0) {
+    while (Atomics.compareExchange(ctl, LOCK, 0, 1) !== 0) {
+      Atomics.wait(ctl, LOCK, 1);
+    }
+    try {
+      const lens = [readLen, 0];
+      let at = 0;
+      payloads.forEach((payload, i) => {
+        data.set(payload, at);
+        lens[i] = payload.length;
+        at += payload.length;
+      });
+      ctl[OP] = op;
+      ctl[ARG] = arg;
+      ctl[LEN1] = lens[0];
+      ctl[LEN2] = lens[1];
+      num[NUM] = n;
+      Atomics.store(ctl, STATE, REQUEST);
+      Atomics.notify(ctl, STATE);
+      while (Atomics.load(ctl, STATE) === REQUEST) {
+        Atomics.wait(ctl, STATE, REQUEST);
+      }
+      const result = num[RESULT];
+      if (onResult) onResult(result, data);
+      return result;
+    } finally {
+      Atomics.store(ctl, STATE, IDLE);
+      Atomics.store(ctl, LOCK, 0);
+      Atomics.notify(ctl, LOCK, 1);
+    }
+  }
+
+  // Copy paths out of wasm memory: `data.set` must not alias it
+  const path = (ptr, len) => bytes(ptr, len).slice();
+  const pathCall = (op) => (ptr, len) => call(op, 0, 0, [path(ptr, len)]);
+
+  return {
+    motedb_vfs: {
+      vfs_open: (ptr, len, flags) => call(OP_OPEN, flags, 0, [path(ptr, len)]),
+      vfs_close: (fd) => {
+        call(OP_CLOSE, fd, 0);
+      },
+      vfs_read_at: (fd, ptr, len, offset) => {
+        let done = 0;
+        let at = Number(offset);
+        while (done < len) {
+          const chunk = Math.min(len - done, data.length);
+          const n = call(OP_READ, fd, at, [], (result, src) => {
+            if (result > 0) bytes(ptr + done, result).set(src.subarray(0, result));
+          }, chunk);
+          if (n < 0) return BigInt(n);
+          done += n;
+          at += n;
+          if (n < chunk) break;
+        }
+        return BigInt(done);
+      },
+      vfs_write_at: (fd, ptr, len, offset) => {
+        let done = 0;
+        let at = Number(offset);
+        while (done < len) {
+          const chunk = Math.min(len - done, data.length);
+          const n = call(OP_WRITE, fd, at, [bytes(ptr + done, chunk).slice()]);
+          if (n < 0) return BigInt(n);
+          done += n;
+          at += n;
+        }
+        return BigInt(done);
+      },
+      vfs_size: (fd) => BigInt(call(OP_SIZE, fd, 0)),
+      vfs_truncate: (fd, size) => call(OP_TRUNCATE, fd, Number(size)),
+      vfs_flush: (fd) => call(OP_FLUSH, fd, 0),
+      vfs_stat: pathCall(OP_STAT),
+      vfs_mkdir_all: pathCall(OP_MKDIR),
+      vfs_rename: (from, fromLen, to, toLen) =>
+        call(OP_RENAME, 0, 0, [path(from, fromLen), path(to, toLen)]),
+      vfs_remove_file: pathCall(OP_REMOVE_FILE),
+      vfs_remove_dir_all: pathCall(OP_REMOVE_DIR),
+      vfs_list_dir: (ptr, len, out, cap) =>
+        BigInt(
+          call(OP_LIST, 0, 0, [path(ptr, len)], (total, src) => {
+            if (total >= 0 && total <= cap) bytes(out, total).set(src.subarray(0, total));
+          }),
+        ),
+    },
+  };
+}
+
+// ==================== I/O worker side ====================
+
+const decoder = new TextDecoder();
+const encoder = new TextEncoder();
+
+class VfsError extends Error {
+  constructor(code) {
+    super(`vfs error ${code}`);
+    this.code = code;
+  }
+}
+
+function errorCode(e) {
+  if (e instanceof VfsError) return e.code;
+  switch (e && e.name) {
+    case "NotFoundError":
+      return E_NOT_FOUND;
+    case "TypeMismatchError":
+    case "TypeError":
+      return E_INVALID;
+    case "NoModificationAllowedError":
+    case "InvalidModificationError":
+    case "NotAllowedError":
+      return E_DENIED;
+    case "QuotaExceededError":
+      return E_FULL;
+    default:
+      return E_OTHER;
+  }
+}
+
+/** `a/b/c` → ["a", "b", "c"]; `..` is refused */
+function components(path) {
+  const parts = path.split("/").filter((p) => p !== "" && p !== ".");
+  if (parts.includes("..")) throw new VfsError(E_INVALID);
+  return parts;
+}
+
+const under = (path, dir) => path === dir || path.startsWith(dir + "/");
+
+/**
+ * Serve `vfsImports` requests against `root` (a `FileSystemDirectoryHandle`,
+ * e.g. `navigator.storage.getDirectory()`) until `stopVfs` is called. Runs
+ * in a dedicated worker: sync access handles only exist there.
+ */
+export async function serveVfs(channel, root) {
+  const ctl = new Int32Array(channel, 0, 8);
+  const num = new Float64Array(channel, 32, 2);
+  const data = new Uint8Array(channel, DATA_OFFSET);
+
+  const handles = new Map(); // normalized path → FileSystemSyncAccessHandle
+  const fds = new Map(); // fd → normalized path, or null once the file is gone
+  let nextFd = 1;
+
+  async function dir(parts, create) {
+    let d = root;
+    for (const name of parts) d = await d.getDirectoryHandle(name, { create });
+    return d;
+  }
+
+  async function fileHandle(parts, create) {
+    if (parts.length === 0) throw new VfsError(E_INVALID);
+    const parent = await dir(parts.slice(0, -1), false);
+    return parent.getFileHandle(parts[parts.length - 1], { create });
+  }
+
+  async function syncHandle(path) {
+    let h = handles.get(path);
+    if (!h) {
+      const fh = await fileHandle(components(path), false);
+      h = await fh.createSyncAccessHandle();
+      handles.set(path, h);
+    }
+    return h;
+  }
+
+  function fdHandle(fd) {
+    const path = fds.get(fd);
+    if (path === undefined) throw new VfsError(E_INVALID);
+    if (path === null) throw new VfsError(E_NOT_FOUND);
+    return syncHandle(path);
+  }
+
+  /** Close the shared handles of `path` and everything below it */
+  function closeUnder(path) {
+    for (const [p, h] of handles) {
+      if (under(p, path)) {
+        h.close();
+        handles.delete(p);
+      }
+    }
+  }
+
+  async function stat(parts) {
+    if (parts.length === 0) return 2;
+    const parent = await dir(parts.slice(0, -1), false);
+    const name = parts[parts.length - 1];
+    try {
+      await parent.getDirectoryHandle(name);
+      return 2;
+    } catch (e) {
+      if (e.name !== "TypeMismatchError") throw e;
+    }
+    await parent.getFileHandle(name);
+    return 1;
+  }
+
+  async function copyFile(fromParts, toParts) {
+    const src = await (await fileHandle(fromParts, false)).createSyncAccessHandle();
+    try {
+      const dst = await (await fileHandle(toParts, true)).createSyncAccessHandle();
+      try {
+        const buf = new Uint8Array(src.getSize());
+        src.read(buf, { at: 0 });
+        dst.truncate(0);
+        dst.write(buf, { at: 0 });
+        dst.flush();
+      } finally {
+        dst.close();
+      }
+    } finally {
+      src.close();
+    }
+  }
+
+  async function copyTree(fromParts, toParts) {
+    const src = await dir(fromParts, false);
+    await dir(toParts, true);
+    for await (const [name, entry] of src.entries()) {
+      if (entry.kind === "directory") {
+        await copyTree([...fromParts, name], [...toParts, name]);
+      } else {
+        await copyFile([...fromParts, name], [...toParts, name]);
+      }
+    }
+  }
+
+  async function remove(parts, recursive) {
+    if (parts.length === 0) throw new VfsError(E_INVALID);
+    const parent = await dir(parts.slice(0, -1), false);
+    await parent.removeEntry(parts[parts.length - 1], { recursive });
+  }
+
+  async function rename(from, to) {
+    const fromParts = components(from);
+    const toParts = components(to);
+    const fromPath = fromParts.join("/");
+    const toPath = toParts.join("/");
+    closeUnder(fromPath);
+    closeUnder(toPath);
+    const kind = await stat(fromParts);
+    if ((await stat(toParts).catch(() => 0)) !== 0) {
+      await remove(toParts, true);
+    }
+    // Descriptors open on the replaced target see it vanish
+    for (const [fd, p] of fds) {
+      if (p !== null && under(p, toPath)) fds.set(fd, null);
+    }
+    const fh = kind === 1 ? await fileHandle(fromParts, false) : null;
+    if (fh && typeof fh.move === "function") {
+      await fh.move(await dir(toParts.slice(0, -1), false), toParts[toParts.length - 1]);
+    } else {
+      if (kind === 1) await copyFile(fromParts, toParts);
+      else await copyTree(fromParts, toParts);
+      await remove(fromParts, true);
+    }
+    // Descriptors follow the file to its new name
+    for (const [fd, p] of fds) {
+      if (p !== null && under(p, fromPath)) fds.set(fd, toPath + p.slice(fromPath.length));
+    }
+  }
+
+  async function handle(op) {
+    const len1 = ctl[LEN1];
+    const pathArg = () => decoder.decode(data.slice(0, len1));
+    switch (op) {
+      case OP_OPEN: {
+        const flags = ctl[ARG];
+        const parts = components(pathArg());
+        const path = parts.join("/");
+        await fileHandle(parts, (flags & FLAG_CREATE) !== 0);
+        const h = await syncHandle(path);
+        if (flags & FLAG_TRUNCATE) h.truncate(0);
+        const fd = nextFd++;
+        fds.set(fd, path);
+        return fd;
+      }
+      case OP_CLOSE: {
+        const fd = ctl[ARG];
+        const path = fds.get(fd);
+        fds.delete(fd);
+        if (path && ![...fds.values()].includes(path)) closeUnder(path);
+        return 0;
+      }
+      case OP_READ: {
+        const h = await fdHandle(ctl[ARG]);
+        return h.read(data.subarray(0, len1), { at: num[NUM] });
+      }
+      case OP_WRITE: {
+        const h = await fdHandle(ctl[ARG]);
+        return h.write(data.subarray(0, len1), { at: num[NUM] });
+      }
+      case OP_SIZE:
+        return (await fdHandle(ctl[ARG])).getSize();
+      case OP_TRUNCATE:
+        (await fdHandle(ctl[ARG])).truncate(num[NUM]);
+        return 0;
+      case OP_FLUSH:
+        (await fdHandle(ctl[ARG])).flush();
+        return 0;
+      case OP_STAT:
+        try {
+          return await stat(components(pathArg()));
+        } catch (e) {
+          if (errorCode(e) === E_NOT_FOUND || errorCode(e) === E_INVALID) return 0;
+          throw e;
+        }
+      case OP_MKDIR:
+        await dir(components(pathArg()), true);
+        return 0;
+      case OP_RENAME: {
+        const from = decoder.decode(data.slice(0, len1));
+        const to = decoder.decode(data.slice(len1, len1 + ctl[LEN2]));
+        await rename(from, to);
+        return 0;
+      }
+      case OP_REMOVE_FILE:
+      case OP_REMOVE_DIR: {
+        const parts = components(pathArg());
+        const path = parts.join("/");
+        const kind = await stat(parts);
+        if (kind === 0) throw new VfsError(E_NOT_FOUND);
+        if ((kind === 2) !== (op === OP_REMOVE_DIR)) throw new VfsError(E_INVALID);
+        closeUnder(path);
+        for (const [fd, p] of fds) {
+          if (p !== null && under(p, path)) fds.set(fd, null);
+        }
+        await remove(parts, op === OP_REMOVE_DIR);
+        return 0;
+      }
+      case OP_LIST: {
+        const names = [];
+        for await (const name of (await dir(components(pathArg()), false)).keys()) {
+          names.push(name);
+        }
+        const listing = encoder.encode(names.join("\n"));
+        if (listing.length > data.length) throw new VfsError(E_OTHER);
+        data.set(listing);
+        return listing.length;
+      }
+      default:
+        throw new VfsError(E_INVALID);
+    }
+  }
+
+  for (;;) {
+    const state = Atomics.load(ctl, STATE);
+    if (state === STOP) break;
+    if (state !== REQUEST) {
+      if (Atomics.waitAsync) {
+        const wait = Atomics.waitAsync(ctl, STATE, state);
+        if (wait.async) await wait.value;
+      } else {
+        Atomics.wait(ctl, STATE, state);
+      }
+      continue;
+    }
+    let result;
+    try {
+      result = await handle(ctl[OP]);
+    } catch (e) {
+      result = errorCode(e);
+    }
+    num[RESULT] = result;
+    Atomics.store(ctl, STATE, RESPONSE);
+    Atomics.notify(ctl, STATE);
+  }
+  for (const h of handles.values()) h.close();
+  handles.clear();
+  // Ready for the next serveVfs on this channel
+  Atomics.store(ctl, STATE, IDLE);
+}
@@ -0,0 +1,67 @@
+//! Creates a database through `HostVfsBackend` on the first run and checks
+//! it on the second; `wasm/test/run-smoke.mjs` runs it twice against one
+//! storage root.
+
+use motedb::storage::{HostVfsBackend, StorageBackend};
+use motedb::types::Value;
+use motedb::{DBConfig, Database, QueryResult};
+use std::path::Path;
+use std::sync::Arc;
+
+fn query(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+    match db.execute(sql).unwrap().materialize().unwrap() {
+        QueryResult::Select { rows, .. } => rows,
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+fn main() {
+    let backend = HostVfsBackend::new();
+    let first = !backend.exists(Path::new("/smoke/db.mote/lsm"));
+    let config = DBConfig {
+        storage_backend: Some(Arc::new(backend)),
+        ..Default::default()
+    };
+    let db = if first {
+        Database::create_with_config("/smoke/db", config).unwrap()
+    } else {
+        Database::open_with_config("/smoke/db", config).unwrap()
+    };
+
+    if first {
+        db.execute("CREATE TABLE t (id INT PRIMARY KEY, name TEXT, v VECTOR(2))")
+            .unwrap();
+        db.execute("CREATE INDEX t_name ON t (name)").unwrap();
+        for chunk in 0..30 {
+            let values: Vec<String> = (0..1000)
+                .map(|i| {
+                    let id = chunk * 1000 + i;
+                    format!("({}, 'n{}', [{}.0, 1.0])", id, id % 100, id % 7)
+                })
+                .collect();
+            db.execute(&format!("INSERT INTO t VALUES {}", values.join(", ")))
+                .unwrap();
+        }
+        db.execute("UPDATE t SET name = 'changed' WHERE id = 5")
+            .unwrap();
+        db.execute("DELETE FROM t WHERE id >= 29000").unwrap();
+        db.execute("REINDEX t_name").unwrap();
+        db.checkpoint().unwrap();
+    }
+
+    assert_eq!(
+        query(&db, "SELECT COUNT(*) FROM t"),
+        vec![vec![Value::Integer(29_000)]]
+    );
+    assert_eq!(
+        query(&db, "SELECT COUNT(*) FROM t WHERE name = 'n42'"),
+        vec![vec![Value::Integer(290)]]
+    );
+    assert_eq!(
+        query(&db, "SELECT id FROM t WHERE name = 'changed'"),
+        vec![vec![Value::Integer(5)]]
+    );
+    let nearest = query(&db, "SELECT id FROM t ORDER BY v <-> [3.0, 1.0] LIMIT 1");
+    assert_eq!(nearest.len(), 1);
+    println!("{} run ok", if first { "create" } else { "reopen" });
+}
@@ -0,0 +1,165 @@
+// In-memory stand-in for the parts of the OPFS API that motedb-vfs.js uses,
+// so the glue can be exercised under Node. Mirrors the browser's rules that
+// matter here: one sync access handle per file at a time, and no removing
+// or moving a file while a handle is open on it.
+
+const fail = (name) => new DOMException(name, name);
+
+class FakeFile {
+  data = new Uint8Array(0);
+  size = 0;
+  locked = false;
+}
+
+class FakeSyncAccessHandle {
+  #file;
+  #closed = false;
+
+  constructor(file) {
+    this.#file = file;
+    file.locked = true;
+  }
+
+  #live() {
+    if (this.#closed) throw fail("InvalidStateError");
+    return this.#file;
+  }
+
+  read(buf, { at = 0 } = {}) {
+    const file = this.#live();
+    const n = Math.max(0, Math.min(buf.length, file.size - at));
+    buf.set(file.data.subarray(at, at + n));
+    return n;
+  }
+
+  write(buf, { at = 0 } = {}) {
+    const file = this.#live();
+    this.#reserve(at + buf.length);
+    file.data.set(buf, at);
+    file.size = Math.max(file.size, at + buf.length);
+    return buf.length;
+  }
+
+  getSize() {
+    return this.#live().size;
+  }
+
+  truncate(size) {
+    const file = this.#live();
+    this.#reserve(size);
+    file.data.fill(0, Math.min(size, file.size), file.data.length);
+    file.size = size;
+  }
+
+  flush() {
+    this.#live();
+  }
+
+  close() {
+    if (!this.#closed) {
+      this.#closed = true;
+      this.#file.locked = false;
+    }
+  }
+
+  #reserve(len) {
+    const file = this.#file;
+    if (len > file.data.length) {
+      const grown = new Uint8Array(Math.max(len, file.data.length * 2));
+      grown.set(file.data.subarray(0, file.size));
+      file.data = grown;
+    }
+  }
+}
+
+class FakeFileHandle {
+  kind = "file";
+
+  constructor(parent, name, file) {
+    this.parent = parent;
+    this.name = name;
+    this.file = file;
+  }
+
+  async createSyncAccessHandle() {
+    if (this.file.locked) throw fail("NoModificationAllowedError");
+    return new FakeSyncAccessHandle(this.file);
+  }
+
+  async move(dir, name) {
+    if (this.file.locked) throw fail("NoModificationAllowedError");
+    if (this.parent.children.get(this.name) !== this.file) throw fail("NotFoundError");
+    const existing = dir.children.get(name);
+    if (existing instanceof FakeDirectoryHandle) throw fail("TypeMismatchError");
+    if (existing && existing.locked) throw fail("NoModificationAllowedError");
+    this.parent.children.delete(this.name);
+    dir.children.set(name, this.file);
+    this.parent = dir;
+    this.name = name;
+  }
+}
+
+export class FakeDirectoryHandle {
+  kind = "directory";
+  children = new Map(); // name → FakeFile | FakeDirectoryHandle
+
+  async getDirectoryHandle(name, { create = false } = {}) {
+    const child = this.children.get(name);
+    if (child instanceof FakeDirectoryHandle) return child;
+    if (child) throw fail("TypeMismatchError");
+    if (!create) throw fail("NotFoundError");
+    const dir = new FakeDirectoryHandle();
+    this.children.set(name, dir);
+    return dir;
+  }
+
+  async getFileHandle(name, { create = false } = {}) {
+    let child = this.children.get(name);
+    if (child instanceof FakeDirectoryHandle) throw fail("TypeMismatchError");
+    if (!child) {
+      if (!create) throw fail("NotFoundError");
+      child = new FakeFile();
+      this.children.set(name, child);
+    }
+    return new FakeFileHandle(this, name, child);
+  }
+
+  async removeEntry(name, { recursive = false } = {}) {
+    const child = this.children.get(name);
+    if (!child) throw fail("NotFoundError");
+    if (child instanceof FakeDirectoryHandle) {
+      if (child.children.size > 0 && !recursive) throw fail("InvalidModificationError");
+      if (child.hasLockedFile()) throw fail("NoModificationAllowedError");
+    } else if (child.locked) {
+      throw fail("NoModificationAllowedError");
+    }
+    this.children.delete(name);
+  }
+
+  async *keys() {
+    for (const name of [...this.children.keys()]) yield name;
+  }
+
+  async *entries() {
+    for (const [name, child] of [...this.children]) {
+      yield [name, child instanceof FakeDirectoryHandle ? child : new FakeFileHandle(this, name, child)];
+    }
+  }
+
+  hasLockedFile() {
+    for (const child of this.children.values()) {
+      if (child instanceof FakeDirectoryHandle ? child.hasLockedFile() : child.locked) return true;
+    }
+    return false;
+  }
+
+  /** `path → size` of every file below this directory */
+  tree(prefix = "") {
+    const out = {};
+    for (const [name, child] of this.children) {
+      if (child instanceof FakeDirectoryHandle) Object.assign(out, child.tree(`${prefix}${name}/`));
+      else out[prefix + name] = child.size;
+    }
+    return out;
+  }
+}
@@ -0,0 +1,38 @@
+// node wasm/test/run-smoke.mjs <motedb-wasm-smoke.wasm>
+//
+// Runs the smoke binary twice against one in-memory OPFS: the first run
+// creates and fills the database, the second reopens it.
+
+import { Worker } from "node:worker_threads";
+import { createVfsChannel, serveVfs, stopVfs } from "../motedb-vfs.js";
+import { FakeDirectoryHandle } from "./fake-opfs.mjs";
+
+const wasm = process.argv[2];
+const channel = createVfsChannel();
+const root = new FakeDirectoryHandle();
+
+for (const run of ["create", "reopen"]) {
+  const served = serveVfs(channel, root);
+  const worker = new Worker(new URL("./smoke-worker.mjs", import.meta.url), {
+    workerData: { channel, wasm },
+  });
+  const code = await new Promise((resolve, reject) => {
+    worker.on("error", reject);
+    worker.on("exit", resolve);
+  });
+  stopVfs(channel);
+  await served;
+  if (code !== 0) {
+    console.error(`${run}: smoke binary exited with ${code}`);
+    process.exit(1);
+  }
+}
+
+const files = Object.keys(root.tree());
+for (const expected of ["smoke/db.mote/catalog.bin", "smoke/db.mote/lsn_counter"]) {
+  if (!files.includes(expected)) {
+    console.error(`missing ${expected} in OPFS: ${files.join(", ")}`);
+    process.exit(1);
+  }
+}
+console.log(`ok: ${files.length} files in OPFS`);
@@ -0,0 +1,16 @@
+// Runs the smoke binary with WASI and the motedb_vfs imports. No directory
+// is preopened, so any file access that bypasses HostVfsBackend fails.
+
+import { readFile } from "node:fs/promises";
+import { WASI } from "node:wasi";
+import { workerData } from "node:worker_threads";
+import { vfsImports } from "../motedb-vfs.js";
+
+const wasi = new WASI({ version: "preview1", args: ["smoke"], env: {}, returnOnExit: true });
+let instance;
+const imports = {
+  ...wasi.getImportObject(),
+  ...vfsImports(workerData.channel, () => instance.exports.memory),
+};
+({ instance } = await WebAssembly.instantiate(await readFile(workerData.wasm), imports));
+process.exitCode = wasi.start(instance);